@@ -1,7 +1,11 @@
 use fyrox::event_loop::EventLoop;
-use fyroxed_base::Editor;
+use fyroxed_base::{cli, Editor};
 
 fn main() {
+    if let Some(exit_code) = cli::run_from_args(|_| ()) {
+        std::process::exit(exit_code);
+    }
+
     let event_loop = EventLoop::new();
     let editor = Editor::new(&event_loop, None);
     editor.run(event_loop)
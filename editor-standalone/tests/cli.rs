@@ -0,0 +1,185 @@
+//! Tests of the headless scene processing mode of the editor.
+
+use fyrox::{
+    core::{algebra::Matrix4, futures::executor::block_on, parking_lot::Mutex, visitor::Visitor},
+    engine::{resource_manager::ResourceManager, SerializationContext},
+    scene::{
+        base::BaseBuilder,
+        collider::ColliderBuilder,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder,
+        },
+        pivot::PivotBuilder,
+        rigidbody::RigidBodyBuilder,
+        Scene, SceneLoader,
+    },
+};
+use serde_json::Value;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fyroxed_cli_{}", name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn save(mut scene: Scene, path: &Path) {
+    let mut visitor = Visitor::new();
+    scene.save("Scene", &mut visitor).unwrap();
+    visitor.save_binary(path).unwrap();
+}
+
+fn valid_scene(path: &Path) {
+    let mut scene = Scene::new();
+    let collider = ColliderBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+    RigidBodyBuilder::new(BaseBuilder::new().with_children(&[collider])).build(&mut scene.graph);
+    let child = PivotBuilder::new(BaseBuilder::new().with_name("Note")).build(&mut scene.graph);
+    PivotBuilder::new(
+        BaseBuilder::new()
+            .with_name("Helpers")
+            .with_tag("EditorOnly".to_owned())
+            .with_children(&[child]),
+    )
+    .build(&mut scene.graph);
+    PivotBuilder::new(BaseBuilder::new().with_name("Level")).build(&mut scene.graph);
+    save(scene, path);
+}
+
+fn scene_with_warning(path: &Path) {
+    let mut scene = Scene::new();
+    ColliderBuilder::new(BaseBuilder::new().with_name("Orphan")).build(&mut scene.graph);
+    save(scene, path);
+}
+
+fn run(dir: &Path, args: &[&str]) -> (i32, Value) {
+    let output = Command::new(env!("CARGO_BIN_EXE_fyroxed"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    let report = serde_json::from_slice(&output.stdout).unwrap();
+    (output.status.code().unwrap(), report)
+}
+
+fn scene_with_floor(path: &Path) {
+    let mut scene = Scene::new();
+    let floor = MeshBuilder::new(BaseBuilder::new().with_name("Floor"))
+        .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+            SurfaceData::make_quad(&Matrix4::identity()),
+        )))
+        .build()])
+        .build(&mut scene.graph);
+    PivotBuilder::new(
+        BaseBuilder::new()
+            .with_name("Level")
+            .with_children(&[floor]),
+    )
+    .build(&mut scene.graph);
+    save(scene, path);
+}
+
+fn load(path: &Path) -> Scene {
+    let context = Arc::new(SerializationContext::new());
+    block_on(async {
+        SceneLoader::from_file(path, context.clone())
+            .await
+            .unwrap()
+            .finish(ResourceManager::new(context))
+            .await
+    })
+}
+
+fn node_count(path: &Path) -> usize {
+    load(path).graph.node_count() as usize
+}
+
+#[test]
+fn test_validate() {
+    let dir = temp_dir("validate");
+    valid_scene(&dir.join("valid.rgs"));
+    scene_with_warning(&dir.join("warning.rgs"));
+
+    let (code, report) = run(&dir, &["validate", "valid.rgs"]);
+    assert_eq!(code, 0);
+    assert_eq!(report["command"], "validate");
+    assert_eq!(report["issues"].as_array().unwrap().len(), 0);
+
+    let (code, report) = run(&dir, &["validate", "warning.rgs"]);
+    assert_eq!(code, 0);
+    assert_eq!(report["issues"][0]["severity"], "warning");
+    assert_eq!(report["issues"][0]["node"], "Orphan");
+
+    let (code, report) = run(&dir, &["validate", "warning.rgs", "--fail-on-warning"]);
+    assert_eq!(code, 1);
+    assert_eq!(report["exit_code"], 1);
+}
+
+#[test]
+fn test_missing_scene() {
+    let dir = temp_dir("missing");
+
+    let (code, report) = run(&dir, &["validate", "missing.rgs"]);
+    assert_eq!(code, 2);
+    assert_eq!(report["issues"][0]["severity"], "error");
+}
+
+#[test]
+fn test_resave() {
+    let dir = temp_dir("resave");
+    valid_scene(&dir.join("scene.rgs"));
+
+    let (code, report) = run(&dir, &["resave", "scene.rgs", "-o", "resaved.rgs"]);
+    assert_eq!(code, 0);
+    assert_eq!(report["output"], "resaved.rgs");
+    assert_eq!(
+        node_count(&dir.join("scene.rgs")),
+        node_count(&dir.join("resaved.rgs"))
+    );
+}
+
+#[test]
+fn test_strip_editor_only() {
+    let dir = temp_dir("strip");
+    valid_scene(&dir.join("scene.rgs"));
+
+    let (code, report) = run(&dir, &["strip-editor-only", "scene.rgs", "-o", "game.rgs"]);
+    assert_eq!(code, 0);
+    assert_eq!(report["removed_nodes"], 2);
+    assert_eq!(
+        node_count(&dir.join("scene.rgs")) - 2,
+        node_count(&dir.join("game.rgs"))
+    );
+}
+
+#[test]
+fn test_bake_navmesh() {
+    let dir = temp_dir("navmesh");
+    scene_with_floor(&dir.join("scene.rgs"));
+
+    let (code, report) = run(
+        &dir,
+        &[
+            "bake-navmesh",
+            "scene.rgs",
+            "-s",
+            "Level/Floor",
+            "-o",
+            "baked.rgs",
+        ],
+    );
+    assert_eq!(code, 0);
+    assert_eq!(report["baked_navmeshes"], 1);
+    let baked = load(&dir.join("baked.rgs"));
+    assert_eq!(baked.navmeshes.iter().count(), 1);
+    assert_eq!(baked.navmeshes.iter().next().unwrap().triangles().len(), 2);
+
+    let (code, report) = run(&dir, &["bake-navmesh", "scene.rgs", "-s", "Level/Wall"]);
+    assert_eq!(code, 2);
+    assert_eq!(report["issues"][0]["severity"], "error");
+}
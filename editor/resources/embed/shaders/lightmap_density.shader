@@ -0,0 +1,149 @@
+(
+    name: "LightmapDensityShader",
+
+    properties: [
+        (
+            name: "lightmapTexture",
+            kind: Sampler(default: None, fallback: Black),
+        ),
+        (
+            name: "targetDensity",
+            kind: Float(8.0),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 2) in vec3 vertexNormal;
+                layout(location = 3) in vec4 vertexTangent;
+                layout(location = 4) in vec4 boneWeights;
+                layout(location = 5) in vec4 boneIndices;
+                layout(location = 6) in vec2 vertexSecondTexCoord;
+
+                uniform mat4 fyrox_worldMatrix;
+                uniform mat4 fyrox_worldViewProjection;
+                #ifndef FYROX_BONE_MATRICES_TEXTURE
+                uniform mat4 fyrox_boneMatrices[60];
+                #endif
+                uniform bool fyrox_useSkeletalAnimation;
+
+                out vec3 position;
+                out vec3 normal;
+                out vec3 tangent;
+                out vec2 texCoord;
+                out vec2 secondTexCoord;
+
+                void main()
+                {
+                    vec4 localPosition = vec4(0);
+                    vec3 localNormal = vec3(0);
+                    vec3 localTangent = vec3(0);
+
+                    if (fyrox_useSkeletalAnimation)
+                    {
+                        vec4 vertex = vec4(vertexPosition, 1.0);
+
+                        mat4 m0 = S_FetchBoneMatrix(int(boneIndices.x));
+                        mat4 m1 = S_FetchBoneMatrix(int(boneIndices.y));
+                        mat4 m2 = S_FetchBoneMatrix(int(boneIndices.z));
+                        mat4 m3 = S_FetchBoneMatrix(int(boneIndices.w));
+
+                        localPosition += m0 * vertex * boneWeights.x;
+                        localPosition += m1 * vertex * boneWeights.y;
+                        localPosition += m2 * vertex * boneWeights.z;
+                        localPosition += m3 * vertex * boneWeights.w;
+
+                        localNormal += mat3(m0) * vertexNormal * boneWeights.x;
+                        localNormal += mat3(m1) * vertexNormal * boneWeights.y;
+                        localNormal += mat3(m2) * vertexNormal * boneWeights.z;
+                        localNormal += mat3(m3) * vertexNormal * boneWeights.w;
+
+                        localTangent += mat3(m0) * vertexTangent.xyz * boneWeights.x;
+                        localTangent += mat3(m1) * vertexTangent.xyz * boneWeights.y;
+                        localTangent += mat3(m2) * vertexTangent.xyz * boneWeights.z;
+                        localTangent += mat3(m3) * vertexTangent.xyz * boneWeights.w;
+                    }
+                    else
+                    {
+                        localPosition = vec4(vertexPosition, 1.0);
+                        localNormal = vertexNormal;
+                        localTangent = vertexTangent.xyz;
+                    }
+
+                    mat3 nm = mat3(fyrox_worldMatrix);
+                    position = vec3(fyrox_worldMatrix * localPosition);
+                    normal = normalize(nm * localNormal);
+                    tangent = normalize(nm * localTangent);
+                    texCoord = vertexTexCoord;
+                    secondTexCoord = vertexSecondTexCoord;
+
+                    gl_Position = fyrox_worldViewProjection * localPosition;
+                }
+               "#,
+
+           fragment_shader:
+               r#"
+                uniform sampler2D lightmapTexture;
+                uniform float targetDensity;
+
+                in vec3 position;
+                in vec3 normal;
+                in vec3 tangent;
+                in vec2 texCoord;
+                in vec2 secondTexCoord;
+
+                out vec4 FragColor;
+
+                void main()
+                {
+                    ivec2 size = textureSize(lightmapTexture, 0);
+
+                    // Fallback texture has a single pixel, so the mesh does not have a lightmap.
+                    if (size.x <= 1 && size.y <= 1)
+                    {
+                        FragColor = vec4(0.35, 0.35, 0.35, 1.0);
+                        return;
+                    }
+
+                    // Amount of lightmap texels per world unit: blue is below the target density,
+                    // green matches it, red is above it.
+                    vec2 texels = secondTexCoord * vec2(size);
+                    float density = length(fwidth(texels)) / max(length(fwidth(position)), 0.00001);
+                    float ratio = clamp(log2(density / targetDensity) * 0.5 + 0.5, 0.0, 1.0);
+                    vec3 color = ratio < 0.5
+                        ? mix(vec3(0.0, 0.2, 1.0), vec3(0.0, 1.0, 0.0), ratio * 2.0)
+                        : mix(vec3(0.0, 1.0, 0.0), vec3(1.0, 0.1, 0.0), ratio * 2.0 - 1.0);
+
+                    // Grid of texels.
+                    vec2 cell = floor(texels);
+                    float checker = mod(cell.x + cell.y, 2.0);
+                    FragColor = vec4(color * mix(0.7, 1.0, checker), 1.0);
+                }
+               "#,
+        ),
+    ],
+)
@@ -0,0 +1,72 @@
+(
+    name: "ReflectionProbePreviewShader",
+
+    properties: [
+        (
+            name: "environmentTexture",
+            kind: Sampler(value: None, fallback: Black),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 2) in vec3 vertexNormal;
+
+                uniform mat4 fyrox_worldMatrix;
+                uniform mat4 fyrox_worldViewProjection;
+
+                out vec3 position;
+                out vec3 normal;
+
+                void main()
+                {
+                    position = (fyrox_worldMatrix * vec4(vertexPosition, 1.0)).xyz;
+                    normal = normalize(mat3(fyrox_worldMatrix) * vertexNormal);
+                    gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                }
+               "#,
+
+           fragment_shader:
+               r#"
+                uniform samplerCube environmentTexture;
+                uniform vec3 fyrox_cameraPosition;
+
+                in vec3 position;
+                in vec3 normal;
+
+                out vec4 FragColor;
+
+                void main()
+                {
+                    // Mirror-like sphere shows everything the probe has captured.
+                    vec3 viewVector = normalize(position - fyrox_cameraPosition);
+                    vec3 reflection = reflect(viewVector, normalize(normal));
+                    FragColor = vec4(texture(environmentTexture, reflection).rgb, 1.0);
+                }
+               "#,
+        ),
+    ],
+)
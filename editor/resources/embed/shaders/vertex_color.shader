@@ -0,0 +1,90 @@
+(
+    name: "VertexColorShader",
+
+    properties: [
+        (
+            name: "channelMask",
+            kind: Vector4([1.0, 0.0, 0.0, 0.0]),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 4) in vec4 boneWeights;
+                layout(location = 5) in vec4 boneIndices;
+                layout(location = 11) in vec4 vertexColor;
+
+                uniform mat4 fyrox_worldViewProjection;
+                #ifndef FYROX_BONE_MATRICES_TEXTURE
+                uniform mat4 fyrox_boneMatrices[60];
+                #endif
+                uniform bool fyrox_useSkeletalAnimation;
+
+                out vec4 color;
+
+                void main()
+                {
+                    vec4 localPosition = vec4(0);
+
+                    if (fyrox_useSkeletalAnimation)
+                    {
+                        vec4 vertex = vec4(vertexPosition, 1.0);
+
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.x)) * vertex * boneWeights.x;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.y)) * vertex * boneWeights.y;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.z)) * vertex * boneWeights.z;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.w)) * vertex * boneWeights.w;
+                    }
+                    else
+                    {
+                        localPosition = vec4(vertexPosition, 1.0);
+                    }
+
+                    color = vertexColor;
+
+                    gl_Position = fyrox_worldViewProjection * localPosition;
+                }
+               "#,
+
+           fragment_shader:
+               r#"
+                uniform vec4 channelMask;
+
+                in vec4 color;
+
+                out vec4 FragColor;
+
+                void main()
+                {
+                    // A single channel is shown in grayscale, meshes without vertex colors read
+                    // (0, 0, 0, 1).
+                    float value = dot(color, channelMask);
+                    FragColor = vec4(value, value, value, 1.0);
+                }
+               "#,
+        ),
+    ],
+)
@@ -0,0 +1,366 @@
+//! Copying and pasting of states between machines.
+//!
+//! Selected states are copied together with their pose nodes and the transitions between them
+//! into a separate machine definition (a fragment), transitions that lead to or from states that
+//! weren't copied are dropped. The fragment is stored as a binary blob, so it does not share any
+//! data with the machine it was copied from and could be pasted into any other machine. Handles
+//! of the fragment are local to it, new handles are allocated when it is pasted.
+
+use fyrox::{
+    animation::machine::{
+        node::PoseNodeDefinition, parameter::ParameterDefinition, state::StateDefinition,
+        transition::TransitionDefinition, MachineDefinition, PoseWeight,
+    },
+    core::{
+        pool::Handle,
+        visitor::{Visit, Visitor},
+    },
+    fxhash::FxHashMap,
+    utils::log::Log,
+};
+
+fn remap<T>(handle: Handle<T>, map: &FxHashMap<Handle<T>, Handle<T>>) -> Handle<T> {
+    map.get(&handle).cloned().unwrap_or_default()
+}
+
+/// Replaces every handle of a pose node using the given maps, handles that are not in the maps
+/// are replaced with `Handle::NONE`.
+fn remap_pose_node(
+    node: &mut PoseNodeDefinition,
+    state_map: &FxHashMap<Handle<StateDefinition>, Handle<StateDefinition>>,
+    node_map: &FxHashMap<Handle<PoseNodeDefinition>, Handle<PoseNodeDefinition>>,
+) {
+    node.parent_state = remap(node.parent_state, state_map);
+
+    match node {
+        PoseNodeDefinition::PlayAnimation(_) => {
+            // No children nodes.
+        }
+        PoseNodeDefinition::BlendAnimations(blend) => {
+            for pose_source in blend.pose_sources.iter_mut() {
+                pose_source.pose_source = remap(pose_source.pose_source, node_map);
+            }
+        }
+        PoseNodeDefinition::BlendAnimationsByIndex(blend) => {
+            for input in blend.inputs.iter_mut() {
+                input.pose_source = remap(input.pose_source, node_map);
+            }
+        }
+    }
+}
+
+/// Returns names of every parameter that is used by the transitions and the pose nodes of the
+/// given definition, names are unique and are in the order of their first use.
+pub fn referenced_parameters(definition: &MachineDefinition) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut add = |name: &str| {
+        if !name.is_empty() && !names.iter().any(|n| n == name) {
+            names.push(name.to_owned());
+        }
+    };
+
+    for transition in definition.transitions.iter() {
+        add(&transition.rule);
+    }
+
+    for node in definition.nodes.iter() {
+        match node {
+            PoseNodeDefinition::PlayAnimation(_) => (),
+            PoseNodeDefinition::BlendAnimations(blend) => {
+                for pose_source in blend.pose_sources.iter() {
+                    if let PoseWeight::Parameter(ref name) = pose_source.weight {
+                        add(name);
+                    }
+                }
+            }
+            PoseNodeDefinition::BlendAnimationsByIndex(blend) => add(&blend.index_parameter),
+        }
+    }
+
+    names
+}
+
+/// Copies given states of the definition into a new definition. Pose nodes of the states and
+/// transitions between them are copied too, parameters are copied only if they're used by the
+/// copied entities.
+pub fn copy_states(
+    definition: &MachineDefinition,
+    states: &[Handle<StateDefinition>],
+) -> MachineDefinition {
+    let mut fragment = MachineDefinition::default();
+
+    let mut state_map = FxHashMap::default();
+    for &state in states {
+        if let Some(state_ref) = definition.states.try_borrow(state) {
+            if !state_map.contains_key(&state) {
+                state_map.insert(state, fragment.states.spawn(state_ref.clone()));
+            }
+        }
+    }
+
+    let mut node_map = FxHashMap::default();
+    for (handle, node) in definition.nodes.pair_iter() {
+        if state_map.contains_key(&node.parent_state) {
+            node_map.insert(handle, fragment.nodes.spawn(node.clone()));
+        }
+    }
+
+    for state in fragment.states.iter_mut() {
+        state.root = remap(state.root, &node_map);
+    }
+
+    for node in fragment.nodes.iter_mut() {
+        remap_pose_node(node, &state_map, &node_map);
+    }
+
+    for transition in definition.transitions.iter() {
+        if let (Some(&source), Some(&dest)) = (
+            state_map.get(&transition.source),
+            state_map.get(&transition.dest),
+        ) {
+            let _ = fragment.transitions.spawn(TransitionDefinition {
+                source,
+                dest,
+                ..transition.clone()
+            });
+        }
+    }
+
+    let used_parameters = referenced_parameters(&fragment);
+    fragment.parameters.container = definition
+        .parameters
+        .container
+        .iter()
+        .filter(|p| used_parameters.contains(&p.name))
+        .cloned()
+        .collect();
+
+    fragment
+}
+
+/// Returns parameters of the fragment that are used by it, but don't exist in the target
+/// definition.
+pub fn missing_parameters(
+    fragment: &MachineDefinition,
+    target: &MachineDefinition,
+) -> Vec<ParameterDefinition> {
+    fragment
+        .parameters
+        .container
+        .iter()
+        .filter(|p| target.parameters.container.iter().all(|t| t.name != p.name))
+        .cloned()
+        .collect()
+}
+
+/// Handles of the entities that were added to a definition by [`paste_fragment`].
+#[derive(Default, Debug, Clone)]
+pub struct PastedEntities {
+    pub states: Vec<Handle<StateDefinition>>,
+    pub nodes: Vec<Handle<PoseNodeDefinition>>,
+    pub transitions: Vec<Handle<TransitionDefinition>>,
+}
+
+/// Adds every state, pose node and transition of the fragment to the target definition and fixes
+/// handles inside them. Parameters are not added, see [`missing_parameters`].
+pub fn paste_fragment(
+    target: &mut MachineDefinition,
+    fragment: &MachineDefinition,
+) -> PastedEntities {
+    let mut pasted = PastedEntities::default();
+
+    let mut state_map = FxHashMap::default();
+    for (handle, state) in fragment.states.pair_iter() {
+        let new_handle = target.states.spawn(state.clone());
+        state_map.insert(handle, new_handle);
+        pasted.states.push(new_handle);
+    }
+
+    let mut node_map = FxHashMap::default();
+    for (handle, node) in fragment.nodes.pair_iter() {
+        let new_handle = target.nodes.spawn(node.clone());
+        node_map.insert(handle, new_handle);
+        pasted.nodes.push(new_handle);
+    }
+
+    for &state in pasted.states.iter() {
+        let state_ref = &mut target.states[state];
+        state_ref.root = remap(state_ref.root, &node_map);
+    }
+
+    for &node in pasted.nodes.iter() {
+        remap_pose_node(&mut target.nodes[node], &state_map, &node_map);
+    }
+
+    for transition in fragment.transitions.iter() {
+        pasted
+            .transitions
+            .push(target.transitions.spawn(TransitionDefinition {
+                source: remap(transition.source, &state_map),
+                dest: remap(transition.dest, &state_map),
+                ..transition.clone()
+            }));
+    }
+
+    pasted
+}
+
+/// Clipboard of the ABSM editor, it outlives the machine that is being edited, so states could
+/// be copied from one machine and pasted to other.
+#[derive(Default)]
+pub struct AbsmClipboard {
+    data: Option<Vec<u8>>,
+}
+
+impl AbsmClipboard {
+    pub fn copy(&mut self, definition: &MachineDefinition, states: &[Handle<StateDefinition>]) {
+        let mut fragment = copy_states(definition, states);
+
+        let mut visitor = Visitor::new();
+        self.data = fragment
+            .visit("Fragment", &mut visitor)
+            .and_then(|_| visitor.save_binary_to_vec())
+            .map_err(|e| Log::err(format!("Unable to copy states. Reason: {:?}", e)))
+            .ok();
+    }
+
+    pub fn paste(&self) -> Option<MachineDefinition> {
+        let data = self.data.clone()?;
+
+        let mut fragment = MachineDefinition::default();
+        Visitor::load_from_memory(data)
+            .and_then(|mut visitor| fragment.visit("Fragment", &mut visitor))
+            .map_err(|e| Log::err(format!("Unable to paste states. Reason: {:?}", e)))
+            .ok()?;
+
+        Some(fragment)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::absm::clipboard::{missing_parameters, paste_fragment, AbsmClipboard};
+    use fyrox::{
+        animation::machine::{
+            node::{
+                blend::{BlendAnimationsDefinition, BlendPoseDefinition},
+                play::PlayAnimationDefinition,
+                BasePoseNodeDefinition, PoseNodeDefinition,
+            },
+            parameter::ParameterDefinition,
+            state::StateDefinition,
+            transition::TransitionDefinition,
+            MachineDefinition, Parameter, PoseWeight,
+        },
+        core::pool::Handle,
+    };
+
+    fn add_state(definition: &mut MachineDefinition, name: &str) -> Handle<StateDefinition> {
+        let state = definition.states.spawn(StateDefinition {
+            name: name.to_owned(),
+            ..Default::default()
+        });
+        let base = BasePoseNodeDefinition {
+            parent_state: state,
+            ..Default::default()
+        };
+        let play =
+            definition
+                .nodes
+                .spawn(PoseNodeDefinition::PlayAnimation(PlayAnimationDefinition {
+                    base: base.clone(),
+                    animation: format!("{}.fbx", name),
+                }));
+        let blend = definition.nodes.spawn(PoseNodeDefinition::BlendAnimations(
+            BlendAnimationsDefinition {
+                base,
+                pose_sources: vec![BlendPoseDefinition {
+                    weight: PoseWeight::Parameter("Weight".to_owned()),
+                    pose_source: play,
+                }],
+            },
+        ));
+        definition.states[state].root = blend;
+        state
+    }
+
+    fn add_transition(
+        definition: &mut MachineDefinition,
+        source: Handle<StateDefinition>,
+        dest: Handle<StateDefinition>,
+        rule: &str,
+    ) {
+        let _ = definition.transitions.spawn(TransitionDefinition {
+            source,
+            dest,
+            rule: rule.to_owned(),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_copy_paste_two_state_cycle() {
+        let mut source = MachineDefinition::default();
+        let idle = add_state(&mut source, "Idle");
+        let walk = add_state(&mut source, "Walk");
+        let jump = add_state(&mut source, "Jump");
+        add_transition(&mut source, idle, walk, "IdleToWalk");
+        add_transition(&mut source, walk, idle, "WalkToIdle");
+        // Must be dropped, because Jump is not copied.
+        add_transition(&mut source, walk, jump, "Jump");
+        for name in ["IdleToWalk", "WalkToIdle", "Jump", "Weight"] {
+            source.parameters.container.push(ParameterDefinition {
+                name: name.to_owned(),
+                value: Parameter::Rule(false),
+            });
+        }
+
+        let mut clipboard = AbsmClipboard::default();
+        clipboard.copy(&source, &[idle, walk]);
+        let fragment = clipboard.paste().unwrap();
+
+        let mut target = source.clone();
+        target.parameters.container.clear();
+        let pasted = paste_fragment(&mut target, &fragment);
+
+        assert_eq!(pasted.states.len(), 2);
+        assert_eq!(pasted.nodes.len(), 4);
+        assert_eq!(pasted.transitions.len(), 2);
+        assert_eq!(target.states.alive_count(), 5);
+
+        let is_old_state = |h: Handle<StateDefinition>| [idle, walk, jump].contains(&h);
+
+        // The pasted transitions connect the two new states with each other.
+        let new_idle = pasted.states[0];
+        let new_walk = pasted.states[1];
+        assert_eq!(target.states[new_idle].name, "Idle");
+        assert_eq!(target.states[new_walk].name, "Walk");
+        let connections = pasted
+            .transitions
+            .iter()
+            .map(|t| (target.transitions[*t].source, target.transitions[*t].dest))
+            .collect::<Vec<_>>();
+        assert!(connections.contains(&(new_idle, new_walk)));
+        assert!(connections.contains(&(new_walk, new_idle)));
+
+        // Nothing references the old handles.
+        for &state in pasted.states.iter() {
+            assert!(!is_old_state(state));
+            assert!(pasted.nodes.contains(&target.states[state].root));
+        }
+        for &node in pasted.nodes.iter() {
+            let node_ref = &target.nodes[node];
+            assert!(pasted.states.contains(&node_ref.parent_state));
+            for child in node_ref.children() {
+                assert!(pasted.nodes.contains(&child));
+            }
+        }
+
+        // Only the parameters that are used by the copied states are offered to be created.
+        let missing = missing_parameters(&fragment, &target)
+            .into_iter()
+            .map(|p| p.name)
+            .collect::<Vec<_>>();
+        assert_eq!(missing, vec!["IdleToWalk", "WalkToIdle", "Weight"]);
+    }
+}
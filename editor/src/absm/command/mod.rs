@@ -1,8 +1,17 @@
-use crate::{absm::SelectedEntity, define_command_stack};
+use crate::{
+    absm::{
+        clipboard::{paste_fragment, PastedEntities},
+        SelectedEntity,
+    },
+    define_command_group, define_command_stack,
+};
 use fyrox::{
     animation::machine::{
-        node::PoseNodeDefinition, parameter::ParameterDefinition, state::StateDefinition,
-        transition::TransitionDefinition, MachineDefinition, MachineInstantiationError, Parameter,
+        node::PoseNodeDefinition,
+        parameter::ParameterDefinition,
+        state::StateDefinition,
+        transition::{TransitionCondition, TransitionDefinition},
+        MachineDefinition, MachineInstantiationError, Parameter,
     },
     asset::ResourceDataRef,
     core::{
@@ -23,6 +32,7 @@ pub struct AbsmEditorContext<'a> {
 }
 
 pub mod blend;
+pub mod state;
 
 define_command_stack!(AbsmCommandTrait, AbsmCommandStack, AbsmEditorContext);
 
@@ -53,53 +63,12 @@ impl AbsmCommand {
     }
 }
 
-#[derive(Debug)]
-pub struct CommandGroup {
-    commands: Vec<AbsmCommand>,
-}
-
-impl From<Vec<AbsmCommand>> for CommandGroup {
-    fn from(commands: Vec<AbsmCommand>) -> Self {
-        Self { commands }
-    }
-}
-
-impl CommandGroup {
-    #[allow(dead_code)]
-    pub fn push(&mut self, command: AbsmCommand) {
-        self.commands.push(command)
-    }
-}
-
-impl AbsmCommandTrait for CommandGroup {
-    fn name(&mut self, context: &AbsmEditorContext) -> String {
-        let mut name = String::from("Command group: ");
-        for cmd in self.commands.iter_mut() {
-            name.push_str(&cmd.name(context));
-            name.push_str(", ");
-        }
-        name
-    }
-
-    fn execute(&mut self, context: &mut AbsmEditorContext) {
-        for cmd in self.commands.iter_mut() {
-            cmd.execute(context);
-        }
-    }
-
-    fn revert(&mut self, context: &mut AbsmEditorContext) {
-        // revert must be done in reverse order.
-        for cmd in self.commands.iter_mut().rev() {
-            cmd.revert(context);
-        }
-    }
-
-    fn finalize(&mut self, context: &mut AbsmEditorContext) {
-        for mut cmd in self.commands.drain(..) {
-            cmd.finalize(context);
-        }
-    }
-}
+define_command_group!(
+    AbsmCommandTrait,
+    CommandGroup,
+    AbsmCommand,
+    AbsmEditorContext
+);
 
 macro_rules! define_spawn_command {
     ($name:ident, $ent_type:ty, $container:ident) => {
@@ -519,6 +488,143 @@ define_free_command!(DeleteStateCommand, StateDefinition, states);
 define_free_command!(DeletePoseNodeCommand, PoseNodeDefinition, nodes);
 define_free_command!(DeleteTransitionCommand, TransitionDefinition, transitions);
 
+/// Adds a copied fragment of a machine to the machine, see [`crate::absm::clipboard`]. Pasted
+/// states become selected.
+#[derive(Debug)]
+pub enum PasteCommand {
+    Unknown,
+    NonExecuted {
+        fragment: MachineDefinition,
+    },
+    Executed {
+        pasted: PastedEntities,
+        prev_entry_state: Handle<StateDefinition>,
+        prev_selection: Vec<SelectedEntity>,
+    },
+    Reverted {
+        states: Vec<(Ticket<StateDefinition>, StateDefinition)>,
+        nodes: Vec<(Ticket<PoseNodeDefinition>, PoseNodeDefinition)>,
+        transitions: Vec<(Ticket<TransitionDefinition>, TransitionDefinition)>,
+    },
+}
+
+impl PasteCommand {
+    pub fn new(fragment: MachineDefinition) -> Self {
+        Self::NonExecuted { fragment }
+    }
+}
+
+impl AbsmCommandTrait for PasteCommand {
+    fn name(&mut self, _context: &AbsmEditorContext) -> String {
+        "Paste".to_string()
+    }
+
+    fn execute(&mut self, context: &mut AbsmEditorContext) {
+        let definition = &mut context.resource.absm_definition;
+
+        let pasted = match std::mem::replace(self, PasteCommand::Unknown) {
+            PasteCommand::NonExecuted { fragment } => paste_fragment(definition, &fragment),
+            PasteCommand::Reverted {
+                states,
+                nodes,
+                transitions,
+            } => PastedEntities {
+                states: states
+                    .into_iter()
+                    .map(|(ticket, state)| definition.states.put_back(ticket, state))
+                    .collect(),
+                nodes: nodes
+                    .into_iter()
+                    .map(|(ticket, node)| definition.nodes.put_back(ticket, node))
+                    .collect(),
+                transitions: transitions
+                    .into_iter()
+                    .map(|(ticket, transition)| definition.transitions.put_back(ticket, transition))
+                    .collect(),
+            },
+            _ => unreachable!(),
+        };
+
+        let prev_entry_state = definition.entry_state;
+
+        // Set entry state if it wasn't set yet.
+        if definition.entry_state.is_none() {
+            if let Some(first) = pasted.states.first() {
+                definition.entry_state = *first;
+            }
+        }
+
+        let prev_selection = std::mem::replace(
+            context.selection,
+            pasted
+                .states
+                .iter()
+                .map(|s| SelectedEntity::State(*s))
+                .collect(),
+        );
+
+        *self = PasteCommand::Executed {
+            pasted,
+            prev_entry_state,
+            prev_selection,
+        };
+    }
+
+    fn revert(&mut self, context: &mut AbsmEditorContext) {
+        match std::mem::replace(self, PasteCommand::Unknown) {
+            PasteCommand::Executed {
+                pasted,
+                prev_entry_state,
+                prev_selection,
+            } => {
+                *context.selection = prev_selection;
+
+                let definition = &mut context.resource.absm_definition;
+                definition.entry_state = prev_entry_state;
+
+                *self = PasteCommand::Reverted {
+                    transitions: pasted
+                        .transitions
+                        .into_iter()
+                        .map(|t| definition.transitions.take_reserve(t))
+                        .collect(),
+                    nodes: pasted
+                        .nodes
+                        .into_iter()
+                        .map(|n| definition.nodes.take_reserve(n))
+                        .collect(),
+                    states: pasted
+                        .states
+                        .into_iter()
+                        .map(|s| definition.states.take_reserve(s))
+                        .collect(),
+                };
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn finalize(&mut self, context: &mut AbsmEditorContext) {
+        if let PasteCommand::Reverted {
+            states,
+            nodes,
+            transitions,
+        } = std::mem::replace(self, PasteCommand::Unknown)
+        {
+            let definition = &mut context.resource.absm_definition;
+            for (ticket, _) in transitions {
+                definition.transitions.forget_ticket(ticket);
+            }
+            for (ticket, _) in nodes {
+                definition.nodes.forget_ticket(ticket);
+            }
+            for (ticket, _) in states {
+                definition.states.forget_ticket(ticket);
+            }
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! define_push_element_to_collection_command {
     ($name:ident<$model_handle:ty, $value_type:ty>($self:ident, $context:ident) $get_collection:block) => {
@@ -765,3 +871,7 @@ define_absm_swap_command!(SetTransitionRuleCommand<Handle<TransitionDefinition>,
 define_absm_swap_command!(SetTransitionInvertRuleCommand<Handle<TransitionDefinition>, bool>[](self, context) {
     &mut context.resource.absm_definition.transitions[self.handle].invert_rule
 });
+
+define_absm_swap_command!(SetTransitionConditionCommand<Handle<TransitionDefinition>, TransitionCondition>[](self, context) {
+    &mut context.resource.absm_definition.transitions[self.handle].condition
+});
@@ -0,0 +1,33 @@
+use crate::{
+    absm::command::{AbsmCommandTrait, AbsmEditorContext},
+    define_absm_swap_command, define_push_element_to_collection_command,
+    define_remove_collection_element_command,
+};
+use fyrox::{
+    animation::machine::state::{StateActionDefinition, StateDefinition},
+    core::pool::Handle,
+};
+
+define_push_element_to_collection_command!(AddStateEnterActionCommand<Handle<StateDefinition>, StateActionDefinition>(self, context) {
+    &mut context.resource.absm_definition.states[self.handle].on_enter
+});
+
+define_remove_collection_element_command!(RemoveStateEnterActionCommand<Handle<StateDefinition>, StateActionDefinition>(self, context) {
+    &mut context.resource.absm_definition.states[self.handle].on_enter
+});
+
+define_absm_swap_command!(SetStateEnterActionNameCommand<Handle<StateDefinition>, String>[index: usize](self, context) {
+    &mut context.resource.absm_definition.states[self.handle].on_enter[self.index].name
+});
+
+define_push_element_to_collection_command!(AddStateExitActionCommand<Handle<StateDefinition>, StateActionDefinition>(self, context) {
+    &mut context.resource.absm_definition.states[self.handle].on_exit
+});
+
+define_remove_collection_element_command!(RemoveStateExitActionCommand<Handle<StateDefinition>, StateActionDefinition>(self, context) {
+    &mut context.resource.absm_definition.states[self.handle].on_exit
+});
+
+define_absm_swap_command!(SetStateExitActionNameCommand<Handle<StateDefinition>, String>[index: usize](self, context) {
+    &mut context.resource.absm_definition.states[self.handle].on_exit[self.index].name
+});
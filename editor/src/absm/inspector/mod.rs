@@ -7,9 +7,15 @@ use crate::{
                 SetBlendAnimationsByIndexParameterCommand, SetBlendAnimationsPoseWeightCommand,
                 SetPoseWeightConstantCommand, SetPoseWeightParameterCommand,
             },
+            state::{
+                AddStateEnterActionCommand, AddStateExitActionCommand,
+                RemoveStateEnterActionCommand, RemoveStateExitActionCommand,
+                SetStateEnterActionNameCommand, SetStateExitActionNameCommand,
+            },
             AbsmCommand, CommandGroup, MovePoseNodeCommand, MoveStateNodeCommand,
-            SetPlayAnimationResourceCommand, SetStateNameCommand, SetTransitionInvertRuleCommand,
-            SetTransitionNameCommand, SetTransitionRuleCommand, SetTransitionTimeCommand,
+            SetPlayAnimationResourceCommand, SetStateNameCommand, SetTransitionConditionCommand,
+            SetTransitionInvertRuleCommand, SetTransitionNameCommand, SetTransitionRuleCommand,
+            SetTransitionTimeCommand,
         },
         message::MessageSender,
         AbsmDataModel, SelectedEntity,
@@ -27,8 +33,8 @@ use fyrox::{
             play::PlayAnimationDefinition,
             BasePoseNodeDefinition, PoseNodeDefinition,
         },
-        state::StateDefinition,
-        transition::TransitionDefinition,
+        state::{StateActionDefinition, StateDefinition},
+        transition::{ComparisonOperator, TransitionCondition, TransitionDefinition},
         MachineDefinition, PoseWeight,
     },
     core::{inspect::Inspect, pool::Handle},
@@ -82,6 +88,12 @@ impl Inspector {
         property_editors
             .insert(VecCollectionPropertyEditorDefinition::<BlendPoseDefinition>::new());
         property_editors.insert(EnumPropertyEditorDefinition::<PoseWeight>::new());
+        property_editors.insert(EnumPropertyEditorDefinition::<TransitionCondition>::new());
+        property_editors.insert(EnumPropertyEditorDefinition::<ComparisonOperator>::new());
+        property_editors
+            .insert(InspectablePropertyEditorDefinition::<StateActionDefinition>::new());
+        property_editors
+            .insert(VecCollectionPropertyEditorDefinition::<StateActionDefinition>::new());
 
         Self {
             window,
@@ -169,7 +181,12 @@ impl Inspector {
                     .iter()
                     .filter_map(|entry| match entry {
                         SelectedEntity::Transition(transition) => {
-                            handle_transition_property_changed(args, *transition)
+                            handle_transition_property_changed(
+                                args,
+                                *transition,
+                                &data_model.resource.data_ref().absm_definition.transitions
+                                    [*transition],
+                            )
                         }
                         SelectedEntity::State(state) => handle_state_property_changed(
                             args,
@@ -213,6 +230,7 @@ impl Inspector {
 fn handle_transition_property_changed(
     args: &PropertyChanged,
     handle: Handle<TransitionDefinition>,
+    transition_definition: &TransitionDefinition,
 ) -> Option<AbsmCommand> {
     match args.value {
         FieldKind::Object(ref value) => match args.name.as_ref() {
@@ -236,12 +254,63 @@ fn handle_transition_property_changed(
                     value: value.cast_clone()?,
                 }))
             }
+            TransitionDefinition::CONDITION => {
+                Some(AbsmCommand::new(SetTransitionConditionCommand {
+                    handle,
+                    value: value.cast_clone()?,
+                }))
+            }
+            _ => None,
+        },
+        FieldKind::Inspectable(ref inner) => match args.name.as_ref() {
+            TransitionDefinition::CONDITION => {
+                let value = handle_transition_condition_property_changed(
+                    inner,
+                    transition_definition.condition,
+                )?;
+                Some(AbsmCommand::new(SetTransitionConditionCommand {
+                    handle,
+                    value,
+                }))
+            }
             _ => None,
         },
         _ => None,
     }
 }
 
+// Fields of the condition are edited by replacing the entire condition, so a single command is
+// used for both variant and field changes.
+fn handle_transition_condition_property_changed(
+    args: &PropertyChanged,
+    mut condition: TransitionCondition,
+) -> Option<TransitionCondition> {
+    if let FieldKind::Object(ref value) = args.value {
+        match (&mut condition, args.name.as_ref()) {
+            (
+                TransitionCondition::StateTime { operator, .. },
+                TransitionCondition::STATE_TIME_OPERATOR,
+            )
+            | (
+                TransitionCondition::StatePosition { operator, .. },
+                TransitionCondition::STATE_POSITION_OPERATOR,
+            ) => *operator = value.cast_clone()?,
+            (
+                TransitionCondition::StateTime { value: v, .. },
+                TransitionCondition::STATE_TIME_VALUE,
+            )
+            | (
+                TransitionCondition::StatePosition { value: v, .. },
+                TransitionCondition::STATE_POSITION_VALUE,
+            ) => *v = value.cast_clone()?,
+            _ => return None,
+        }
+        Some(condition)
+    } else {
+        None
+    }
+}
+
 fn handle_state_property_changed(
     args: &PropertyChanged,
     handle: Handle<StateDefinition>,
@@ -260,6 +329,59 @@ fn handle_state_property_changed(
             })),
             _ => None,
         },
+        FieldKind::Collection(ref collection_changed) => match args.name.as_ref() {
+            StateDefinition::ON_ENTER => match **collection_changed {
+                CollectionChanged::Add => Some(AbsmCommand::new(AddStateEnterActionCommand {
+                    handle,
+                    value: Some(Default::default()),
+                })),
+                CollectionChanged::Remove(i) => Some(AbsmCommand::new(
+                    RemoveStateEnterActionCommand::new(handle, i),
+                )),
+                CollectionChanged::ItemChanged {
+                    index,
+                    ref property,
+                } => match property.value {
+                    FieldKind::Object(ref value) => match property.name.as_ref() {
+                        StateActionDefinition::NAME => {
+                            Some(AbsmCommand::new(SetStateEnterActionNameCommand {
+                                handle,
+                                index,
+                                value: value.cast_clone()?,
+                            }))
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                },
+            },
+            StateDefinition::ON_EXIT => match **collection_changed {
+                CollectionChanged::Add => Some(AbsmCommand::new(AddStateExitActionCommand {
+                    handle,
+                    value: Some(Default::default()),
+                })),
+                CollectionChanged::Remove(i) => Some(AbsmCommand::new(
+                    RemoveStateExitActionCommand::new(handle, i),
+                )),
+                CollectionChanged::ItemChanged {
+                    index,
+                    ref property,
+                } => match property.value {
+                    FieldKind::Object(ref value) => match property.name.as_ref() {
+                        StateActionDefinition::NAME => {
+                            Some(AbsmCommand::new(SetStateExitActionNameCommand {
+                                handle,
+                                index,
+                                value: value.cast_clone()?,
+                            }))
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                },
+            },
+            _ => None,
+        },
         _ => None,
     }
 }
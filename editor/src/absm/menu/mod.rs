@@ -41,6 +41,8 @@ pub struct EditMenu {
     pub menu: Handle<UiNode>,
     undo: Handle<UiNode>,
     redo: Handle<UiNode>,
+    copy: Handle<UiNode>,
+    paste: Handle<UiNode>,
     clear_command_stack: Handle<UiNode>,
 }
 
@@ -48,6 +50,8 @@ impl EditMenu {
     pub fn new(ctx: &mut BuildContext) -> Self {
         let undo;
         let redo;
+        let copy;
+        let paste;
         let clear_command_stack;
         let menu = MenuItemBuilder::new(WidgetBuilder::new())
             .with_content(MenuItemContent::text_no_arrow("Edit"))
@@ -64,6 +68,18 @@ impl EditMenu {
                         .build(ctx);
                     redo
                 },
+                {
+                    copy = MenuItemBuilder::new(WidgetBuilder::new())
+                        .with_content(MenuItemContent::text("Copy"))
+                        .build(ctx);
+                    copy
+                },
+                {
+                    paste = MenuItemBuilder::new(WidgetBuilder::new())
+                        .with_content(MenuItemContent::text("Paste"))
+                        .build(ctx);
+                    paste
+                },
                 {
                     clear_command_stack = MenuItemBuilder::new(WidgetBuilder::new())
                         .with_content(MenuItemContent::text("Clear Command Stack"))
@@ -77,6 +93,8 @@ impl EditMenu {
             menu,
             undo,
             redo,
+            copy,
+            paste,
             clear_command_stack,
         }
     }
@@ -87,6 +105,10 @@ impl EditMenu {
                 sender.undo();
             } else if message.destination() == self.redo {
                 sender.redo();
+            } else if message.destination() == self.copy {
+                sender.copy_selection();
+            } else if message.destination() == self.paste {
+                sender.paste();
             } else if message.destination() == self.clear_command_stack {
                 sender.clear_command_stack();
             }
@@ -6,6 +6,8 @@ pub enum AbsmMessage {
     Undo,
     Redo,
     ClearCommandStack,
+    CopySelection,
+    Paste,
     CreateNewAbsm,
     LoadAbsm,
     SaveCurrentAbsm,
@@ -46,6 +48,14 @@ impl MessageSender {
         self.send(AbsmMessage::ClearCommandStack)
     }
 
+    pub fn copy_selection(&self) {
+        self.send(AbsmMessage::CopySelection)
+    }
+
+    pub fn paste(&self) {
+        self.send(AbsmMessage::Paste)
+    }
+
     pub fn create_new_absm(&self) {
         self.send(AbsmMessage::CreateNewAbsm)
     }
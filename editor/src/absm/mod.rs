@@ -1,8 +1,10 @@
 use crate::{
     absm::{
+        clipboard::{missing_parameters, AbsmClipboard},
         command::{
             blend::{AddInputCommand, AddPoseSourceCommand},
-            AbsmCommand, AbsmCommandStack, AbsmEditorContext,
+            AbsmCommand, AbsmCommandStack, AbsmEditorContext, AddParameterCommand, CommandGroup,
+            PasteCommand,
         },
         inspector::Inspector,
         menu::Menu,
@@ -22,6 +24,7 @@ use fyrox::{
             blend::{BlendPoseDefinition, IndexedBlendInputDefinition},
             PoseNodeDefinition,
         },
+        parameter::ParameterDefinition,
         state::StateDefinition,
         transition::TransitionDefinition,
         Event, MachineDefinition,
@@ -39,6 +42,7 @@ use fyrox::{
         file_browser::{FileBrowserMode, FileSelectorMessage},
         grid::{Column, GridBuilder, Row},
         message::{MessageDirection, UiMessage},
+        messagebox::{MessageBoxBuilder, MessageBoxButtons, MessageBoxMessage, MessageBoxResult},
         widget::WidgetBuilder,
         window::{WindowBuilder, WindowMessage, WindowTitle},
         UiNode, UserInterface,
@@ -51,21 +55,22 @@ use std::{
     sync::mpsc::{channel, Receiver, Sender},
 };
 
-mod canvas;
+pub mod canvas;
+mod clipboard;
 mod command;
-mod connection;
+pub mod connection;
 mod inspector;
 mod menu;
 mod message;
-mod node;
+pub mod node;
 mod parameter;
 mod preview;
 mod segment;
 mod selectable;
-mod socket;
+pub mod socket;
 mod state_graph;
 mod state_viewer;
-mod transition;
+pub mod transition;
 
 const NORMAL_BACKGROUND: Color = Color::opaque(60, 60, 60);
 const SELECTED_BACKGROUND: Color = Color::opaque(80, 80, 80);
@@ -147,6 +152,11 @@ pub struct AbsmEditor {
     state_viewer: StateViewer,
     menu: Menu,
     parameter_panel: ParameterPanel,
+    clipboard: AbsmClipboard,
+    paste_message_box: Handle<UiNode>,
+    // Copied fragment together with the parameters it uses, but the edited machine does not
+    // have. It waits for the user to decide whether the parameters should be created.
+    pending_paste: Option<(MachineDefinition, Vec<ParameterDefinition>)>,
 }
 
 impl AbsmEditor {
@@ -247,6 +257,16 @@ impl AbsmEditor {
             },
         );
 
+        let paste_message_box = MessageBoxBuilder::new(
+            WindowBuilder::new(WidgetBuilder::new().with_width(350.0).with_height(140.0))
+                .can_close(false)
+                .can_minimize(false)
+                .open(false)
+                .with_title(WindowTitle::text("Missing Parameters")),
+        )
+        .with_buttons(MessageBoxButtons::YesNoCancel)
+        .build(ctx);
+
         Self {
             window,
             message_sender: MessageSender::new(tx),
@@ -261,6 +281,9 @@ impl AbsmEditor {
             previewer,
             state_viewer,
             parameter_panel,
+            clipboard: Default::default(),
+            paste_message_box,
+            pending_paste: None,
         }
     }
 
@@ -312,6 +335,75 @@ impl AbsmEditor {
         }
     }
 
+    fn copy_selection(&mut self) {
+        if let Some(data_model) = self.data_model.as_ref() {
+            let states = data_model
+                .selection
+                .iter()
+                .filter_map(|entry| {
+                    if let SelectedEntity::State(state) = entry {
+                        Some(*state)
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            if !states.is_empty() {
+                self.clipboard
+                    .copy(&data_model.resource.data_ref().absm_definition, &states);
+            }
+        }
+    }
+
+    fn paste(&mut self, ui: &UserInterface) -> bool {
+        let fragment = match (self.data_model.as_ref(), self.clipboard.paste()) {
+            (Some(data_model), Some(fragment)) => {
+                let missing =
+                    missing_parameters(&fragment, &data_model.resource.data_ref().absm_definition);
+                if !missing.is_empty() {
+                    ui.send_message(MessageBoxMessage::open(
+                        self.paste_message_box,
+                        MessageDirection::ToWidget,
+                        None,
+                        Some(format!(
+                            "Pasted states use parameters that this machine does not have: {}. \
+                            Do you want to create them?",
+                            missing
+                                .iter()
+                                .map(|p| p.name.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )),
+                    ));
+                    self.pending_paste = Some((fragment, missing));
+                    return false;
+                }
+                fragment
+            }
+            _ => return false,
+        };
+
+        self.do_command(AbsmCommand::new(PasteCommand::new(fragment)))
+    }
+
+    fn finish_paste(&mut self, create_parameters: bool) -> bool {
+        if let Some((fragment, missing)) = self.pending_paste.take() {
+            let mut group = Vec::new();
+            if create_parameters {
+                group.extend(
+                    missing
+                        .into_iter()
+                        .map(|p| AbsmCommand::new(AddParameterCommand::new((), p))),
+                );
+            }
+            group.push(AbsmCommand::new(PasteCommand::new(fragment)));
+            self.do_command(AbsmCommand::new(CommandGroup::from(group)))
+        } else {
+            false
+        }
+    }
+
     fn set_data_model(&mut self, engine: &mut Engine, data_model: Option<AbsmDataModel>) {
         self.clear_command_stack();
 
@@ -414,6 +506,10 @@ impl AbsmEditor {
                 AbsmMessage::ClearCommandStack => {
                     need_sync |= self.clear_command_stack();
                 }
+                AbsmMessage::CopySelection => self.copy_selection(),
+                AbsmMessage::Paste => {
+                    need_sync |= self.paste(&engine.user_interface);
+                }
                 AbsmMessage::CreateNewAbsm => self.create_new_absm(engine),
                 AbsmMessage::LoadAbsm => {
                     self.open_load_dialog(&engine.user_interface);
@@ -444,13 +540,8 @@ impl AbsmEditor {
         self.handle_machine_events(engine);
     }
 
-    pub fn handle_machine_events(&self, engine: &mut Engine) {
-        let scene = &mut engine.scenes[self.previewer.scene()];
-
-        if let Some(machine) = scene
-            .animation_machines
-            .try_get_mut(self.previewer.current_absm())
-        {
+    pub fn handle_machine_events(&mut self, engine: &mut Engine) {
+        if let Some(machine) = self.previewer.machine_mut() {
             while let Some(event) = machine.pop_event() {
                 match event {
                     Event::ActiveStateChanged(state) => {
@@ -501,6 +592,20 @@ impl AbsmEditor {
             } else if message.destination() == self.load_dialog {
                 self.load_absm(path, engine);
             }
+        } else if let Some(MessageBoxMessage::Close(result)) = message.data() {
+            if message.destination() == self.paste_message_box {
+                let need_sync = match result {
+                    MessageBoxResult::Yes => self.finish_paste(true),
+                    MessageBoxResult::No => self.finish_paste(false),
+                    _ => {
+                        self.pending_paste = None;
+                        false
+                    }
+                };
+                if need_sync {
+                    self.sync_to_model(engine);
+                }
+            }
         } else if let Some(WindowMessage::Close) = message.data() {
             if message.destination() == self.window {
                 // Clear on close.
@@ -2,31 +2,63 @@ use crate::{
     absm::message::MessageSender,
     preview::PreviewPanel,
     utils::{create_file_selector, open_file_selector},
+    FIXED_TIMESTEP,
 };
 use fyrox::{
-    animation::machine::Machine,
+    animation::{machine::Machine, AnimationContainer},
     core::{futures::executor::block_on, pool::Handle},
     engine::Engine,
     gui::{
         button::{ButtonBuilder, ButtonMessage},
         file_browser::{FileBrowserMode, FileSelectorMessage},
-        message::UiMessage,
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        numeric::{NumericUpDownBuilder, NumericUpDownMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        text::{TextBuilder, TextMessage},
         widget::WidgetBuilder,
         window::{WindowBuilder, WindowTitle},
-        Thickness, UiNode,
+        Orientation, Thickness, UiNode, UserInterface, VerticalAlignment,
     },
     resource::absm::AbsmResource,
-    scene::Scene,
 };
 use std::path::Path;
 
+/// Isolated instance of a machine that is used for preview. It is not stored in the preview scene,
+/// because the scene would update it with its own time step. Instead, the previewer steps the
+/// machine manually which allows to pause it, change its speed or seek to an arbitrary time.
+struct PreviewInstance {
+    // State of the machine and its animations right after instantiation. Machines cannot be
+    // played backwards, so seeking back is done by re-simulation from this state.
+    initial: (Machine, AnimationContainer),
+    machine: Machine,
+    animations: AnimationContainer,
+}
+
 pub struct Previewer {
     pub window: Handle<UiNode>,
     panel: PreviewPanel,
     load_preview_model: Handle<UiNode>,
     load_dialog: Handle<UiNode>,
-    current_absm: Handle<Machine>,
-    current_resource: Option<AbsmResource>,
+    play_pause: Handle<UiNode>,
+    play_pause_text: Handle<UiNode>,
+    step_frame: Handle<UiNode>,
+    speed_selector: Handle<UiNode>,
+    timeline: Handle<UiNode>,
+    time_text: Handle<UiNode>,
+    instance: Option<PreviewInstance>,
+    playing: bool,
+    speed: f32,
+    time: f32,
+    duration: f32,
+}
+
+fn play_pause_text(playing: bool) -> &'static str {
+    if playing {
+        "Pause"
+    } else {
+        "Play"
+    }
 }
 
 impl Previewer {
@@ -34,11 +66,58 @@ impl Previewer {
         let panel = PreviewPanel::new(engine, 300, 300);
 
         let ctx = &mut engine.user_interface.build_ctx();
+
+        let timeline;
+        let time_text;
+        let content = GridBuilder::new(
+            WidgetBuilder::new().with_child(panel.root).with_child(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .on_row(1)
+                        .with_margin(Thickness::uniform(2.0))
+                        .with_child({
+                            timeline = ScrollBarBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_height(20.0)
+                                    .on_column(0)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .with_min(0.0)
+                            .with_max(1.0)
+                            .with_value(0.0)
+                            .with_step(FIXED_TIMESTEP)
+                            .build(ctx);
+                            timeline
+                        })
+                        .with_child({
+                            time_text = TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_width(90.0)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx);
+                            time_text
+                        }),
+                )
+                .add_row(Row::auto())
+                .add_column(Column::stretch())
+                .add_column(Column::auto())
+                .build(ctx),
+            ),
+        )
+        .add_row(Row::stretch())
+        .add_row(Row::auto())
+        .add_column(Column::stretch())
+        .build(ctx);
+
         let window = WindowBuilder::new(WidgetBuilder::new())
             .can_close(false)
             .can_minimize(false)
             .with_title(WindowTitle::text("Previewer"))
-            .with_content(panel.root)
+            .with_content(content)
             .build(ctx);
 
         let load_preview_model =
@@ -46,7 +125,33 @@ impl Previewer {
                 .with_text("Load")
                 .build(ctx);
 
-        ctx.link(load_preview_model, panel.tools_panel);
+        let play_pause_text = TextBuilder::new(WidgetBuilder::new())
+            .with_text(play_pause_text(true))
+            .with_vertical_text_alignment(VerticalAlignment::Center)
+            .build(ctx);
+        let play_pause =
+            ButtonBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(1.0)))
+                .with_content(play_pause_text)
+                .build(ctx);
+
+        let step_frame =
+            ButtonBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(1.0)))
+                .with_text("Step")
+                .build(ctx);
+
+        let speed_selector = NumericUpDownBuilder::<f32>::new(
+            WidgetBuilder::new()
+                .with_width(60.0)
+                .with_margin(Thickness::uniform(1.0)),
+        )
+        .with_min_value(0.0)
+        .with_step(0.1)
+        .with_value(1.0)
+        .build(ctx);
+
+        for widget in [load_preview_model, play_pause, step_frame, speed_selector] {
+            ctx.link(widget, panel.tools_panel);
+        }
 
         // TODO: Support more formats here.
         let load_dialog = create_file_selector(ctx, "fbx", FileBrowserMode::Open);
@@ -56,8 +161,17 @@ impl Previewer {
             panel,
             load_preview_model,
             load_dialog,
-            current_absm: Default::default(),
-            current_resource: None,
+            play_pause,
+            play_pause_text,
+            step_frame,
+            speed_selector,
+            timeline,
+            time_text,
+            instance: None,
+            playing: true,
+            speed: 1.0,
+            time: 0.0,
+            duration: 1.0,
         }
     }
 
@@ -72,65 +186,168 @@ impl Previewer {
         if let Some(ButtonMessage::Click) = message.data() {
             if message.destination() == self.load_preview_model {
                 open_file_selector(self.load_dialog, &engine.user_interface);
+            } else if message.destination() == self.play_pause {
+                self.set_playing(!self.playing, &engine.user_interface);
+            } else if message.destination() == self.step_frame {
+                self.set_playing(false, &engine.user_interface);
+                self.step(engine, FIXED_TIMESTEP);
             }
         } else if let Some(FileSelectorMessage::Commit(path)) = message.data() {
             if message.destination() == self.load_dialog {
                 sender.set_preview_model(path.clone());
             }
+        } else if let Some(NumericUpDownMessage::<f32>::Value(value)) = message.data() {
+            if message.destination() == self.speed_selector
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.speed = *value;
+            }
+        } else if let Some(ScrollBarMessage::Value(value)) = message.data() {
+            // Handled messages are responses to our own synchronization requests.
+            if message.destination() == self.timeline
+                && message.direction() == MessageDirection::FromWidget
+                && !message.handled()
+            {
+                self.set_playing(false, &engine.user_interface);
+                self.seek(engine, *value);
+            }
         }
     }
 
     pub fn update(&mut self, engine: &mut Engine) {
-        self.panel.update(engine)
+        self.panel.update(engine);
+
+        if self.playing {
+            if self.time + FIXED_TIMESTEP * self.speed > self.duration {
+                // Start over, otherwise the timeline won't be able to show the current time.
+                self.seek(engine, 0.0);
+            } else {
+                self.step(engine, FIXED_TIMESTEP * self.speed);
+            }
+        }
     }
 
     pub fn clear(&mut self, engine: &mut Engine) {
-        self.remove_current_absm(engine);
+        self.instance = None;
+        self.time = 0.0;
 
         self.panel.clear(engine);
+
+        self.sync_timeline(&engine.user_interface);
     }
 
-    fn remove_current_absm(&mut self, engine: &mut Engine) {
-        let scene = &mut engine.scenes[self.panel.scene()];
+    fn set_playing(&mut self, playing: bool, ui: &UserInterface) {
+        self.playing = playing;
 
-        if scene
-            .animation_machines
-            .try_get(self.current_absm)
-            .is_some()
-        {
-            scene
-                .animation_machines
-                .remove_with_animations(self.current_absm, &mut scene.animations);
+        ui.send_message(TextMessage::text(
+            self.play_pause_text,
+            MessageDirection::ToWidget,
+            play_pause_text(playing).to_owned(),
+        ));
+    }
+
+    /// Advances the machine by the given amount of time.
+    fn step(&mut self, engine: &mut Engine, dt: f32) {
+        if let Some(instance) = self.instance.as_mut() {
+            instance.animations.update_animations(dt);
+            instance.machine.evaluate_pose(&instance.animations, dt);
+
+            self.time += dt;
+
+            self.apply_pose(engine);
         }
     }
 
-    pub fn set_absm(&mut self, engine: &mut Engine, resource: &AbsmResource) {
-        if self
-            .current_resource
-            .as_ref()
-            .map_or(false, |current_resource| current_resource == resource)
-        {
-            /*
-            // Just sync instance to resource.
-            if let Some(machine_instance) = scene.animation_machines.try_get_mut(self.current_absm)
-            {
-                machine_instance.resolve();
-            }*/
-            block_on(engine.scenes[self.panel.scene()].resolve(engine.resource_manager.clone()));
-        } else {
-            self.current_resource = Some(resource.clone());
-
-            // Remove previous machine first (if any).
-            self.remove_current_absm(engine);
-
-            // Instantiate new immediately.
-            self.current_absm = block_on(resource.instantiate(
-                self.panel.model(),
-                &mut engine.scenes[self.panel.scene()],
-                engine.resource_manager.clone(),
-            ))
-            .unwrap();
+    /// Sets the machine to the state it has at the given time. Seeking forward simulates the
+    /// machine from its current state, seeking backward re-simulates it from the very beginning.
+    fn seek(&mut self, engine: &mut Engine, time: f32) {
+        if let Some(instance) = self.instance.as_mut() {
+            let time = time.max(0.0);
+
+            let (machine, animations) = if time >= self.time {
+                instance
+                    .machine
+                    .simulate(&instance.animations, time - self.time, FIXED_TIMESTEP)
+            } else {
+                instance
+                    .initial
+                    .0
+                    .simulate(&instance.initial.1, time, FIXED_TIMESTEP)
+            };
+
+            instance.machine = machine;
+            instance.animations = animations;
+
+            self.time = time;
+
+            self.apply_pose(engine);
+        }
+    }
+
+    fn apply_pose(&mut self, engine: &mut Engine) {
+        if let Some(instance) = self.instance.as_ref() {
+            instance
+                .machine
+                .pose()
+                .apply(&mut engine.scenes[self.panel.scene()].graph);
         }
+
+        self.sync_timeline(&engine.user_interface);
+    }
+
+    fn sync_timeline(&self, ui: &UserInterface) {
+        let message = ScrollBarMessage::value(self.timeline, MessageDirection::ToWidget, self.time);
+        message.set_handled(true);
+        ui.send_message(message);
+
+        ui.send_message(TextMessage::text(
+            self.time_text,
+            MessageDirection::ToWidget,
+            format!("{:.2} / {:.2} s", self.time, self.duration),
+        ));
+    }
+
+    pub fn set_absm(&mut self, engine: &mut Engine, resource: &AbsmResource) {
+        // Keep the position on the timeline, so the changes of the definition could be observed
+        // at the same moment of time.
+        let normalized_time = self.time / self.duration;
+
+        let scene = &mut engine.scenes[self.panel.scene()];
+
+        // Instantiate new machine and take it from the scene together with its animations.
+        let instance = block_on(resource.instantiate(
+            self.panel.model(),
+            scene,
+            engine.resource_manager.clone(),
+            None,
+        ))
+        .unwrap();
+        let animations = scene.animations.clone();
+        let (machine, machine_animations) = scene
+            .animation_machines
+            .remove_with_animations(instance, &mut scene.animations);
+
+        self.duration = machine_animations
+            .iter()
+            .map(|animation| animation.length())
+            .fold(1.0, f32::max);
+
+        engine
+            .user_interface
+            .send_message(ScrollBarMessage::max_value(
+                self.timeline,
+                MessageDirection::ToWidget,
+                self.duration,
+            ));
+
+        self.instance = Some(PreviewInstance {
+            initial: (machine.clone(), animations.clone()),
+            machine,
+            animations,
+        });
+
+        self.time = 0.0;
+        self.seek(engine, normalized_time * self.duration);
     }
 
     pub fn set_preview_model(&mut self, engine: &mut Engine, path: &Path, resource: &AbsmResource) {
@@ -140,11 +357,7 @@ impl Previewer {
         }
     }
 
-    pub fn current_absm(&self) -> Handle<Machine> {
-        self.current_absm
-    }
-
-    pub fn scene(&self) -> Handle<Scene> {
-        self.panel.scene()
+    pub fn machine_mut(&mut self) -> Option<&mut Machine> {
+        self.instance.as_mut().map(|instance| &mut instance.machine)
     }
 }
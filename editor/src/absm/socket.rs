@@ -1,6 +1,9 @@
 use fyrox::{
-    animation::machine::node::PoseNodeDefinition,
-    core::{algebra::Vector2, color::Color, pool::Handle},
+    core::{
+        algebra::Vector2,
+        color::Color,
+        pool::{ErasedHandle, Handle},
+    },
     gui::{
         brush::Brush,
         define_constructor, define_widget_deref,
@@ -39,7 +42,7 @@ pub enum SocketDirection {
 pub struct Socket {
     widget: Widget,
     click_position: Option<Vector2<f32>>,
-    pub parent_node: Handle<PoseNodeDefinition>,
+    pub parent_node: ErasedHandle,
     pub direction: SocketDirection,
     #[allow(dead_code)] // TODO
     editor: Handle<UiNode>,
@@ -117,7 +120,7 @@ impl Control for Socket {
 
 pub struct SocketBuilder {
     widget_builder: WidgetBuilder,
-    parent_node: Handle<PoseNodeDefinition>,
+    parent_node: ErasedHandle,
     direction: SocketDirection,
     editor: Handle<UiNode>,
     index: usize,
@@ -134,8 +137,8 @@ impl SocketBuilder {
         }
     }
 
-    pub fn with_parent_node(mut self, parent_node: Handle<PoseNodeDefinition>) -> Self {
-        self.parent_node = parent_node;
+    pub fn with_parent_node<T>(mut self, parent_node: Handle<T>) -> Self {
+        self.parent_node = parent_node.into();
         self
     }
 
@@ -144,7 +147,6 @@ impl SocketBuilder {
         self
     }
 
-    #[allow(dead_code)] // TODO
     pub fn with_editor(mut self, editor: Handle<UiNode>) -> Self {
         self.editor = editor;
         self
@@ -27,6 +27,7 @@ use fyrox::{
 
 pub struct CanvasContextMenu {
     create_state: Handle<UiNode>,
+    paste: Handle<UiNode>,
     pub menu: Handle<UiNode>,
     pub canvas: Handle<UiNode>,
     pub node_context_menu: Handle<UiNode>,
@@ -35,18 +36,27 @@ pub struct CanvasContextMenu {
 impl CanvasContextMenu {
     pub fn new(ctx: &mut BuildContext) -> Self {
         let create_state;
+        let paste;
         let menu = PopupBuilder::new(WidgetBuilder::new().with_visibility(false))
             .with_content(
-                StackPanelBuilder::new(WidgetBuilder::new().with_child({
-                    create_state = create_menu_item("Create State", vec![], ctx);
-                    create_state
-                }))
+                StackPanelBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            create_state = create_menu_item("Create State", vec![], ctx);
+                            create_state
+                        })
+                        .with_child({
+                            paste = create_menu_item("Paste", vec![], ctx);
+                            paste
+                        }),
+                )
                 .build(ctx),
             )
             .build(ctx);
 
         Self {
             create_state,
+            paste,
             menu,
             canvas: Default::default(),
             node_context_menu: Default::default(),
@@ -67,7 +77,11 @@ impl CanvasContextMenu {
                     position: ui.node(self.canvas).screen_to_local(screen_position),
                     name: "New State".to_string(),
                     root: Default::default(),
+                    on_enter: Default::default(),
+                    on_exit: Default::default(),
                 }));
+            } else if message.destination() == self.paste {
+                sender.paste();
             }
         }
     }
@@ -75,6 +89,7 @@ impl CanvasContextMenu {
 
 pub struct NodeContextMenu {
     create_transition: Handle<UiNode>,
+    copy: Handle<UiNode>,
     remove: Handle<UiNode>,
     set_as_entry_state: Handle<UiNode>,
     pub menu: Handle<UiNode>,
@@ -85,6 +100,7 @@ pub struct NodeContextMenu {
 impl NodeContextMenu {
     pub fn new(ctx: &mut BuildContext) -> Self {
         let create_transition;
+        let copy;
         let remove;
         let set_as_entry_state;
         let menu = PopupBuilder::new(WidgetBuilder::new().with_visibility(false))
@@ -95,6 +111,10 @@ impl NodeContextMenu {
                             create_transition = create_menu_item("Create Transition", vec![], ctx);
                             create_transition
                         })
+                        .with_child({
+                            copy = create_menu_item("Copy", vec![], ctx);
+                            copy
+                        })
                         .with_child({
                             remove = create_menu_item("Remove", vec![], ctx);
                             remove
@@ -111,6 +131,7 @@ impl NodeContextMenu {
 
         Self {
             create_transition,
+            copy,
             menu,
             remove,
             canvas: Default::default(),
@@ -137,6 +158,8 @@ impl NodeContextMenu {
                         dest_pos: ui.node(self.canvas).screen_to_local(ui.cursor_position()),
                     },
                 ))
+            } else if message.destination() == self.copy {
+                sender.copy_selection();
             } else if message.destination == self.remove {
                 let states_to_remove = data_model
                     .selection
@@ -155,6 +155,7 @@ impl StateGraphViewer {
                                 dest,
                                 rule: "".to_string(),
                                 invert_rule: false,
+                                condition: Default::default(),
                             }));
                         }
                     }
@@ -82,6 +82,7 @@ fn fetch_socket_pose_node_model_handle(
         .query_component::<Socket>()
         .unwrap()
         .parent_node
+        .into()
 }
 
 impl StateViewer {
@@ -72,19 +72,40 @@ pub fn draw_transition(
     source_pos: Vector2<f32>,
     dest_pos: Vector2<f32>,
 ) {
-    drawing_context.push_line(source_pos, dest_pos, 4.0);
+    draw_transition_arrow(
+        drawing_context,
+        clip_bounds,
+        brush,
+        source_pos,
+        dest_pos,
+        4.0,
+        18.0,
+    );
+}
 
-    let axis = (dest_pos - source_pos).normalize();
-    let center = (dest_pos + source_pos).scale(0.5);
-    let perp = Vector2::new(axis.y, -axis.x).normalize();
+/// Draws a line from source to destination with an arrow in the middle of it. The arrow is
+/// omitted if both points are the same.
+pub fn draw_transition_arrow(
+    drawing_context: &mut DrawingContext,
+    clip_bounds: Rect<f32>,
+    brush: Brush,
+    source_pos: Vector2<f32>,
+    dest_pos: Vector2<f32>,
+    thickness: f32,
+    arrow_size: f32,
+) {
+    drawing_context.push_line(source_pos, dest_pos, thickness);
 
-    let size = 18.0;
+    if let Some(axis) = (dest_pos - source_pos).try_normalize(f32::EPSILON) {
+        let center = (dest_pos + source_pos).scale(0.5);
+        let perp = Vector2::new(axis.y, -axis.x);
 
-    drawing_context.push_triangle_filled([
-        center + axis.scale(size),
-        center + perp.scale(size * 0.5),
-        center - perp.scale(size * 0.5),
-    ]);
+        drawing_context.push_triangle_filled([
+            center + axis.scale(arrow_size),
+            center + perp.scale(arrow_size * 0.5),
+            center - perp.scale(arrow_size * 0.5),
+        ]);
+    }
 
     drawing_context.commit(clip_bounds, brush, CommandTexture::None, None);
 }
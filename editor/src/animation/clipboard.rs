@@ -0,0 +1,267 @@
+//! In-process clipboard of the animation panel. It holds key frames (or whole tracks) copied from
+//! an animation, the key frames could be pasted into the same or any other animation of the scene.
+//!
+//! Tracks in the clipboard are identified by names of their nodes, not by handles, so the keys
+//! could be pasted into an animation of another instance of the same model. Pasted key frames are
+//! embedded into the tracks (see [`Track::set_embedded_key_frames`]), otherwise they would be
+//! overwritten by key frames from the animation resource when the scene is loaded.
+
+use fyrox::{
+    animation::{Animation, KeyFrame, PoseEvaluationFlags, Track},
+    core::pool::Handle,
+    scene::{graph::Graph, node::Node},
+};
+use std::cmp::Ordering;
+
+// Pasted keys replace existing keys, that are closer than this to the pasted time range.
+const KEY_TIME_EPSILON: f32 = 0.001;
+
+/// Key frames of a single track in the clipboard.
+#[derive(Clone, Debug)]
+pub struct ClipboardTrack {
+    /// Name of the animated node, it is used to find a track to paste the keys to.
+    pub node_name: String,
+    /// Key frames with times relative to the beginning of the copied fragment.
+    pub key_frames: Vec<KeyFrame>,
+    // Parameters of the track, they're used only when whole tracks are pasted.
+    pub enabled: bool,
+    pub weight: f32,
+    pub flags: PoseEvaluationFlags,
+}
+
+impl ClipboardTrack {
+    fn new(track: &Track, graph: &Graph, key_frames: Vec<KeyFrame>) -> Self {
+        Self {
+            node_name: graph
+                .try_get(track.get_node())
+                .map_or_else(String::new, |node| node.name_owned()),
+            key_frames,
+            enabled: track.is_enabled(),
+            weight: track.weight(),
+            flags: track.flags(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AnimationClipboard {
+    pub tracks: Vec<ClipboardTrack>,
+    /// `true` if whole tracks were copied, pasted whole tracks replace all key frames of target
+    /// tracks, and tracks of nodes that aren't animated yet are created.
+    pub whole_tracks: bool,
+}
+
+impl AnimationClipboard {
+    /// Copies key frames of the given tracks in `[from; to]` time range. Times of the copied keys
+    /// are relative to the earliest of them, so relative timing of the keys of different tracks is
+    /// preserved. Tracks without keys in the range are not copied.
+    pub fn copy_keys(
+        animation: &Animation,
+        tracks: &[usize],
+        from: f32,
+        to: f32,
+        graph: &Graph,
+    ) -> Self {
+        let in_range = |key: &&KeyFrame| key.time >= from && key.time <= to;
+
+        let selected_tracks = || {
+            tracks
+                .iter()
+                .filter_map(|&index| animation.get_tracks().get(index))
+        };
+
+        let start = selected_tracks()
+            .flat_map(|track| track.get_key_frames().iter().filter(in_range))
+            .map(|key| key.time)
+            .fold(f32::MAX, f32::min);
+
+        Self {
+            tracks: selected_tracks()
+                .filter_map(|track| {
+                    let key_frames = track
+                        .get_key_frames()
+                        .iter()
+                        .filter(in_range)
+                        .map(|key| KeyFrame {
+                            time: key.time - start,
+                            ..*key
+                        })
+                        .collect::<Vec<_>>();
+
+                    if key_frames.is_empty() {
+                        None
+                    } else {
+                        Some(ClipboardTrack::new(track, graph, key_frames))
+                    }
+                })
+                .collect(),
+            whole_tracks: false,
+        }
+    }
+
+    /// Copies the given tracks with all their key frames, times of the keys are not changed.
+    pub fn copy_tracks(animation: &Animation, tracks: &[usize], graph: &Graph) -> Self {
+        Self {
+            tracks: tracks
+                .iter()
+                .filter_map(|&index| animation.get_tracks().get(index))
+                .map(|track| ClipboardTrack::new(track, graph, track.get_key_frames().to_vec()))
+                .collect(),
+            whole_tracks: true,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    /// Returns time of the last key frame in the clipboard.
+    pub fn duration(&self) -> f32 {
+        self.tracks
+            .iter()
+            .flat_map(|track| track.key_frames.iter())
+            .map(|key| key.time)
+            .fold(0.0, f32::max)
+    }
+}
+
+/// Defines what to do with pasted keys that are beyond the end of the target animation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LengthMode {
+    /// The animation is extended to include all pasted keys.
+    Extend,
+    /// Pasted keys after the end of the animation are discarded.
+    Clamp,
+}
+
+impl LengthMode {
+    pub const ALL: [LengthMode; 2] = [LengthMode::Extend, LengthMode::Clamp];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LengthMode::Extend => "Extend Animation",
+            LengthMode::Clamp => "Clamp To Animation",
+        }
+    }
+}
+
+/// A track of the target animation, that receives keys of a track from the clipboard.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PasteTarget {
+    Skip,
+    /// Existing track of the animation with the given index.
+    Track(usize),
+    /// A new track for the given node, it is used only for whole tracks.
+    NewTrack(Handle<Node>),
+}
+
+#[derive(Clone, Debug)]
+pub struct PasteOptions {
+    /// Time at which the beginning of the copied fragment will be placed.
+    pub time: f32,
+    /// Scale of times of pasted keys, values greater than one make the fragment slower.
+    pub time_scale: f32,
+    pub length_mode: LengthMode,
+    /// A target of each track of the clipboard.
+    pub targets: Vec<PasteTarget>,
+}
+
+/// Finds a target for each track of the clipboard - a track of the animation, which node has the
+/// same name as the copied one. If there is no such track, the whole track is pasted to a new track
+/// for a node with the same name, keys of unmatched tracks are skipped.
+pub fn match_targets(
+    clipboard: &AnimationClipboard,
+    animation: &Animation,
+    graph: &Graph,
+) -> Vec<PasteTarget> {
+    clipboard
+        .tracks
+        .iter()
+        .map(|clipboard_track| {
+            let existing = animation.get_tracks().iter().position(|track| {
+                graph
+                    .try_get(track.get_node())
+                    .map_or(false, |node| node.name() == clipboard_track.node_name)
+            });
+
+            match existing {
+                Some(index) => PasteTarget::Track(index),
+                None if clipboard.whole_tracks => {
+                    let node = graph.find_by_name_from_root(&clipboard_track.node_name);
+                    if node.is_some() {
+                        PasteTarget::NewTrack(node)
+                    } else {
+                        PasteTarget::Skip
+                    }
+                }
+                None => PasteTarget::Skip,
+            }
+        })
+        .collect()
+}
+
+/// Returns new tracks and length of the animation with the clipboard pasted into it. The animation
+/// itself is not modified, so the result could be applied by an undoable command.
+pub fn paste(
+    clipboard: &AnimationClipboard,
+    animation: &Animation,
+    options: &PasteOptions,
+) -> (Vec<Track>, f32) {
+    let mut tracks = animation.get_tracks().to_vec();
+
+    let start = options.time.max(0.0);
+    let end = start + clipboard.duration() * options.time_scale;
+    let length = match options.length_mode {
+        LengthMode::Extend => animation.length().max(end),
+        LengthMode::Clamp => animation.length(),
+    };
+
+    for (clipboard_track, target) in clipboard.tracks.iter().zip(options.targets.iter()) {
+        let track = match *target {
+            PasteTarget::Skip => continue,
+            PasteTarget::Track(index) => match tracks.get_mut(index) {
+                Some(track) => track,
+                None => continue,
+            },
+            PasteTarget::NewTrack(node) => {
+                let mut track = Track::new();
+                track.set_node(node);
+                tracks.push(track);
+                tracks.last_mut().unwrap()
+            }
+        };
+
+        let pasted_keys = clipboard_track
+            .key_frames
+            .iter()
+            .map(|key| KeyFrame {
+                time: start + key.time * options.time_scale,
+                ..*key
+            })
+            .filter(|key| key.time <= length + KEY_TIME_EPSILON);
+
+        let mut key_frames = if clipboard.whole_tracks {
+            track.enable(clipboard_track.enabled);
+            track.set_weight(clipboard_track.weight);
+            track.set_flags(clipboard_track.flags);
+            pasted_keys.collect::<Vec<_>>()
+        } else {
+            // Pasted keys replace existing keys in the pasted time range.
+            track
+                .get_key_frames()
+                .iter()
+                .filter(|key| {
+                    key.time < start - KEY_TIME_EPSILON || key.time > end + KEY_TIME_EPSILON
+                })
+                .cloned()
+                .chain(pasted_keys)
+                .collect::<Vec<_>>()
+        };
+        key_frames.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap_or(Ordering::Equal));
+
+        track.set_key_frames(&key_frames);
+        track.set_embedded_key_frames(true);
+    }
+
+    (tracks, length)
+}
@@ -0,0 +1,710 @@
+//! Animation panel - shows animations of the scene and allows to enable or disable their tracks
+//! and change weights of the tracks, which is a simple way to mask animations without ABSM layers.
+//!
+//! Key frames of selected tracks (or whole tracks) could be copied and pasted into the same or
+//! another animation, see [`clipboard`] for more info.
+//!
+//! While the panel is open, it also shows onion skin of the selected animation - skeletons of the
+//! animated nodes at neighbouring key frames, see [`OnionSkinSettings`].
+
+use crate::{
+    animation::{clipboard::AnimationClipboard, paste::PasteWindow},
+    gui::make_dropdown_list_option,
+    scene::{
+        commands::animation::{SetAnimationTrackEnabledCommand, SetAnimationTrackWeightCommand},
+        EditorScene,
+    },
+    send_sync_message,
+    settings::onion_skin::OnionSkinSettings,
+    utils::window_content,
+    GameEngine, Message, Mode, MSG_SYNC_FLAG,
+};
+use fyrox::{
+    animation::{Animation, AnimationContainer, LocalPose},
+    core::{algebra::Matrix4, color::Color, math::Matrix4Ext, pool::Handle, scope_profile},
+    fxhash::FxHashMap,
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        numeric::{NumericUpDownBuilder, NumericUpDownMessage},
+        scroll_viewer::ScrollViewerBuilder,
+        stack_panel::StackPanelBuilder,
+        text::TextBuilder,
+        utils::make_simple_tooltip,
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Orientation, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    scene::{
+        debug::{Line, SceneDrawingContext},
+        graph::Graph,
+        node::Node,
+    },
+};
+use std::{cmp::Ordering, sync::mpsc::Sender};
+
+mod clipboard;
+mod paste;
+
+// Colors of ghosts before and after the current time position, their alpha is defined by the
+// onion skin settings.
+const PREVIOUS_GHOST_COLOR: Color = Color::opaque(255, 90, 90);
+const NEXT_GHOST_COLOR: Color = Color::opaque(90, 255, 90);
+
+// Key frames of different tracks that are closer than this are considered as the same key.
+const KEY_TIME_EPSILON: f32 = 0.001;
+
+struct TrackView {
+    selected: Handle<UiNode>,
+    enabled: Handle<UiNode>,
+    weight: Handle<UiNode>,
+}
+
+fn animation_name(index: usize, animation: &Animation) -> String {
+    match animation.get_resource() {
+        Some(resource) => format!("{}: {}", index, resource.state().path().display()),
+        None => format!("{}: Animation", index),
+    }
+}
+
+fn make_track_view(
+    ctx: &mut BuildContext,
+    name: &str,
+    enabled: bool,
+    weight: f32,
+) -> (Handle<UiNode>, TrackView) {
+    let selected_check_box;
+    let enabled_check_box;
+    let weight_field;
+    let row = GridBuilder::new(
+        WidgetBuilder::new()
+            .with_child({
+                selected_check_box = CheckBoxBuilder::new(
+                    WidgetBuilder::new()
+                        .on_column(0)
+                        .with_margin(Thickness::uniform(1.0))
+                        .with_tooltip(make_simple_tooltip(ctx, "Select the track for copying")),
+                )
+                .checked(Some(false))
+                .build(ctx);
+                selected_check_box
+            })
+            .with_child({
+                enabled_check_box = CheckBoxBuilder::new(
+                    WidgetBuilder::new()
+                        .on_column(1)
+                        .with_margin(Thickness::uniform(1.0))
+                        .with_tooltip(make_simple_tooltip(ctx, "Enable the track")),
+                )
+                .checked(Some(enabled))
+                .build(ctx);
+                enabled_check_box
+            })
+            .with_child(
+                TextBuilder::new(
+                    WidgetBuilder::new()
+                        .on_column(2)
+                        .with_margin(Thickness::left(2.0)),
+                )
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .with_text(name)
+                .build(ctx),
+            )
+            .with_child({
+                weight_field = NumericUpDownBuilder::new(
+                    WidgetBuilder::new()
+                        .on_column(3)
+                        .with_margin(Thickness::uniform(1.0)),
+                )
+                .with_min_value(0.0)
+                .with_step(0.1)
+                .with_precision(2)
+                .with_value(weight)
+                .build(ctx);
+                weight_field
+            }),
+    )
+    .add_row(Row::strict(22.0))
+    .add_column(Column::strict(22.0))
+    .add_column(Column::strict(22.0))
+    .add_column(Column::stretch())
+    .add_column(Column::strict(70.0))
+    .build(ctx);
+
+    (
+        row,
+        TrackView {
+            selected: selected_check_box,
+            enabled: enabled_check_box,
+            weight: weight_field,
+        },
+    )
+}
+
+fn make_button(ctx: &mut BuildContext, text: &str, enabled: bool) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .with_enabled(enabled)
+            .with_margin(Thickness::uniform(1.0))
+            .with_width(80.0),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+fn make_time_field(ctx: &mut BuildContext, tooltip: &str) -> Handle<UiNode> {
+    NumericUpDownBuilder::new(
+        WidgetBuilder::new()
+            .with_margin(Thickness::uniform(1.0))
+            .with_width(70.0)
+            .with_tooltip(make_simple_tooltip(ctx, tooltip)),
+    )
+    .with_min_value(0.0)
+    .with_step(0.1)
+    .with_precision(3)
+    .build(ctx)
+}
+
+fn track_nodes(animations: &AnimationContainer, animation: Handle<Animation>) -> Vec<Handle<Node>> {
+    animations
+        .try_get(animation)
+        .map_or_else(Vec::new, |animation| {
+            animation
+                .get_tracks()
+                .iter()
+                .map(|track| track.get_node())
+                .collect()
+        })
+}
+
+/// Returns times of key frames of the animation, that are the closest to its time position - up to
+/// `previous` keys before it and up to `next` keys after it. Both lists start from the closest key.
+fn ghost_times(animation: &Animation, previous: usize, next: usize) -> (Vec<f32>, Vec<f32>) {
+    let mut times = animation
+        .get_tracks()
+        .iter()
+        .filter(|track| track.is_enabled())
+        .flat_map(|track| track.get_key_frames().iter().map(|key| key.time))
+        .collect::<Vec<_>>();
+    times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    times.dedup_by(|a, b| (*a - *b).abs() < KEY_TIME_EPSILON);
+
+    let time = animation.get_time_position();
+    let before = times
+        .iter()
+        .rev()
+        .filter(|&&t| t < time - KEY_TIME_EPSILON)
+        .take(previous)
+        .cloned()
+        .collect();
+    let after = times
+        .iter()
+        .filter(|&&t| t > time + KEY_TIME_EPSILON)
+        .take(next)
+        .cloned()
+        .collect();
+    (before, after)
+}
+
+// Calculates global transform of the node as if the ghost poses were applied to the graph.
+fn ghost_global_transform(
+    node: Handle<Node>,
+    graph: &Graph,
+    poses: &FxHashMap<Handle<Node>, LocalPose>,
+    transforms: &mut FxHashMap<Handle<Node>, Matrix4<f32>>,
+) -> Matrix4<f32> {
+    if let Some(transform) = transforms.get(&node) {
+        return *transform;
+    }
+
+    let node_ref = &graph[node];
+    let local_transform = match poses.get(&node) {
+        Some(pose) => {
+            let mut local_transform = node_ref.local_transform().clone();
+            local_transform
+                .set_position(pose.position())
+                .set_rotation(pose.rotation())
+                .set_scale(pose.scale());
+            local_transform.matrix()
+        }
+        None => node_ref.local_transform().matrix(),
+    };
+
+    let global_transform = if node_ref.parent().is_some() {
+        ghost_global_transform(node_ref.parent(), graph, poses, transforms) * local_transform
+    } else {
+        local_transform
+    };
+    transforms.insert(node, global_transform);
+    global_transform
+}
+
+// Draws lines between animated nodes and their animated parents in poses at the given time.
+fn draw_ghost_skeleton(
+    animation: &Animation,
+    time: f32,
+    color: Color,
+    graph: &Graph,
+    poses: &mut FxHashMap<Handle<Node>, LocalPose>,
+    transforms: &mut FxHashMap<Handle<Node>, Matrix4<f32>>,
+    ctx: &mut SceneDrawingContext,
+) {
+    poses.clear();
+    transforms.clear();
+
+    for track in animation.get_tracks() {
+        if track.is_enabled() && graph.is_valid_handle(track.get_node()) {
+            if let Some(pose) = track.get_local_pose(time) {
+                poses.insert(track.get_node(), pose);
+            }
+        }
+    }
+
+    for &node in poses.keys() {
+        let parent = graph[node].parent();
+        if poses.contains_key(&parent) {
+            let begin = ghost_global_transform(parent, graph, poses, transforms).position();
+            let end = ghost_global_transform(node, graph, poses, transforms).position();
+            ctx.add_line(Line { begin, end, color });
+        }
+    }
+}
+
+pub struct AnimationPanel {
+    pub window: Handle<UiNode>,
+    animation_selector: Handle<UiNode>,
+    tracks_panel: Handle<UiNode>,
+    copy_from: Handle<UiNode>,
+    copy_to: Handle<UiNode>,
+    copy_keys: Handle<UiNode>,
+    copy_tracks: Handle<UiNode>,
+    paste: Handle<UiNode>,
+    paste_window: PasteWindow,
+    sender: Sender<Message>,
+    animations: Vec<Handle<Animation>>,
+    selected: Handle<Animation>,
+    // Nodes of the tracks of the selected animation, the track views are re-created only if
+    // they're changed.
+    nodes: Vec<Handle<Node>>,
+    track_views: Vec<TrackView>,
+    // Tracks of the selected animation, that will be copied.
+    selected_tracks: Vec<usize>,
+    // Time range of the key frames, that will be copied.
+    key_range: (f32, f32),
+    clipboard: AnimationClipboard,
+    // Buffers of onion skin, they're re-used every frame to avoid allocations.
+    ghost_poses: FxHashMap<Handle<Node>, LocalPose>,
+    ghost_transforms: FxHashMap<Handle<Node>, Matrix4<f32>>,
+}
+
+impl AnimationPanel {
+    pub fn new(ctx: &mut BuildContext, sender: Sender<Message>) -> Self {
+        let animation_selector;
+        let tracks_panel;
+        let copy_from;
+        let copy_to;
+        let copy_keys;
+        let copy_tracks;
+        let paste;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(400.0))
+            .with_title(WindowTitle::text("Animation Panel"))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            animation_selector = DropdownListBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .build(ctx);
+                            animation_selector
+                        })
+                        .with_child(
+                            ScrollViewerBuilder::new(WidgetBuilder::new().on_row(1))
+                                .with_content({
+                                    tracks_panel =
+                                        StackPanelBuilder::new(WidgetBuilder::new()).build(ctx);
+                                    tracks_panel
+                                })
+                                .build(ctx),
+                        )
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .with_child(
+                                        TextBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                                        .with_text("Keys From")
+                                        .build(ctx),
+                                    )
+                                    .with_child({
+                                        copy_from = make_time_field(
+                                            ctx,
+                                            "Time of the first key frame to copy",
+                                        );
+                                        copy_from
+                                    })
+                                    .with_child(
+                                        TextBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                                        .with_text("To")
+                                        .build(ctx),
+                                    )
+                                    .with_child({
+                                        copy_to = make_time_field(
+                                            ctx,
+                                            "Time of the last key frame to copy",
+                                        );
+                                        copy_to
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        )
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(3)
+                                    .with_child({
+                                        copy_keys = make_button(ctx, "Copy Keys", true);
+                                        copy_keys
+                                    })
+                                    .with_child({
+                                        copy_tracks = make_button(ctx, "Copy Tracks", true);
+                                        copy_tracks
+                                    })
+                                    .with_child({
+                                        paste = make_button(ctx, "Paste...", false);
+                                        paste
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_column(Column::stretch())
+                .add_row(Row::strict(24.0))
+                .add_row(Row::stretch())
+                .add_row(Row::strict(24.0))
+                .add_row(Row::strict(24.0))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            animation_selector,
+            tracks_panel,
+            copy_from,
+            copy_to,
+            copy_keys,
+            copy_tracks,
+            paste,
+            paste_window: PasteWindow::new(ctx),
+            sender,
+            animations: Default::default(),
+            selected: Default::default(),
+            nodes: Default::default(),
+            track_views: Default::default(),
+            selected_tracks: Default::default(),
+            key_range: (0.0, 0.0),
+            clipboard: Default::default(),
+            ghost_poses: Default::default(),
+            ghost_transforms: Default::default(),
+        }
+    }
+
+    /// Draws onion skin of the selected animation. It does nothing if the panel is closed, and it
+    /// must be called after debug lines of the scene were cleared.
+    pub fn draw_onion_skin(
+        &mut self,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        settings: &OnionSkinSettings,
+    ) {
+        scope_profile!();
+
+        if !settings.enabled || !engine.user_interface.node(self.window).visibility() {
+            return;
+        }
+
+        let scene = &mut engine.scenes[editor_scene.scene];
+        let animation = match scene.animations.try_get(self.selected) {
+            Some(animation) => animation,
+            None => return,
+        };
+
+        let (before, after) = ghost_times(animation, settings.previous_keys, settings.next_keys);
+        for (times, color) in [(before, PREVIOUS_GHOST_COLOR), (after, NEXT_GHOST_COLOR)] {
+            let mut opacity = settings.opacity;
+            for time in times {
+                draw_ghost_skeleton(
+                    animation,
+                    time,
+                    color.with_new_alpha((opacity.clamp(0.0, 1.0) * 255.0) as u8),
+                    &scene.graph,
+                    &mut self.ghost_poses,
+                    &mut self.ghost_transforms,
+                    &mut scene.drawing_context,
+                );
+                opacity *= settings.falloff;
+            }
+        }
+    }
+
+    pub fn sync_to_model(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        scope_profile!();
+
+        let scene = &engine.scenes[editor_scene.scene];
+        let ui = &mut engine.user_interface;
+
+        let animations = scene
+            .animations
+            .pair_iter()
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+
+        if animations != self.animations {
+            let ctx = &mut ui.build_ctx();
+            let items = scene
+                .animations
+                .iter()
+                .enumerate()
+                .map(|(index, animation)| {
+                    make_dropdown_list_option(ctx, &animation_name(index, animation))
+                })
+                .collect::<Vec<_>>();
+
+            send_sync_message(
+                ui,
+                DropdownListMessage::items(
+                    self.animation_selector,
+                    MessageDirection::ToWidget,
+                    items,
+                ),
+            );
+
+            self.animations = animations;
+        }
+
+        if !self.animations.contains(&self.selected) {
+            self.selected = self.animations.first().cloned().unwrap_or_default();
+        }
+
+        send_sync_message(
+            ui,
+            DropdownListMessage::selection(
+                self.animation_selector,
+                MessageDirection::ToWidget,
+                self.animations.iter().position(|a| *a == self.selected),
+            ),
+        );
+
+        self.sync_tracks(&scene.animations, &scene.graph, ui);
+    }
+
+    fn sync_tracks(
+        &mut self,
+        animations: &AnimationContainer,
+        graph: &Graph,
+        ui: &mut UserInterface,
+    ) {
+        let nodes = track_nodes(animations, self.selected);
+
+        if nodes != self.nodes {
+            for &child in ui.node(self.tracks_panel).children() {
+                ui.send_message(WidgetMessage::remove(child, MessageDirection::ToWidget));
+            }
+
+            self.track_views.clear();
+            self.selected_tracks.clear();
+
+            if let Some(animation) = animations.try_get(self.selected) {
+                self.key_range = (0.0, animation.length());
+                for (field, value) in [(self.copy_from, 0.0), (self.copy_to, animation.length())] {
+                    send_sync_message(
+                        ui,
+                        NumericUpDownMessage::value(field, MessageDirection::ToWidget, value),
+                    );
+                }
+
+                let ctx = &mut ui.build_ctx();
+                for track in animation.get_tracks() {
+                    let name = graph
+                        .try_get(track.get_node())
+                        .map_or("<Invalid Node>", |node| node.name());
+                    let (row, view) =
+                        make_track_view(ctx, name, track.is_enabled(), track.weight());
+                    ctx.link(row, self.tracks_panel);
+                    self.track_views.push(view);
+                }
+            }
+
+            self.nodes = nodes;
+        } else if let Some(animation) = animations.try_get(self.selected) {
+            for (track, view) in animation.get_tracks().iter().zip(self.track_views.iter()) {
+                send_sync_message(
+                    ui,
+                    CheckBoxMessage::checked(
+                        view.enabled,
+                        MessageDirection::ToWidget,
+                        Some(track.is_enabled()),
+                    ),
+                );
+                send_sync_message(
+                    ui,
+                    NumericUpDownMessage::value(
+                        view.weight,
+                        MessageDirection::ToWidget,
+                        track.weight(),
+                    ),
+                );
+            }
+        }
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+    ) {
+        scope_profile!();
+
+        self.paste_window
+            .handle_ui_message(message, editor_scene, engine, &self.sender);
+
+        if message.direction() != MessageDirection::FromWidget || message.has_flags(MSG_SYNC_FLAG) {
+            return;
+        }
+
+        if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            let scene = &engine.scenes[editor_scene.scene];
+            if let Some(animation) = scene.animations.try_get(self.selected) {
+                if message.destination() == self.copy_keys {
+                    let (from, to) = self.key_range;
+                    let clipboard = AnimationClipboard::copy_keys(
+                        animation,
+                        &self.selected_tracks,
+                        from,
+                        to,
+                        &scene.graph,
+                    );
+                    self.set_clipboard(clipboard, &engine.user_interface);
+                } else if message.destination() == self.copy_tracks {
+                    let clipboard = AnimationClipboard::copy_tracks(
+                        animation,
+                        &self.selected_tracks,
+                        &scene.graph,
+                    );
+                    self.set_clipboard(clipboard, &engine.user_interface);
+                } else if message.destination() == self.paste && !self.clipboard.is_empty() {
+                    self.paste_window.open(
+                        &self.clipboard,
+                        self.selected,
+                        animation,
+                        &scene.graph,
+                        &mut engine.user_interface,
+                    );
+                }
+            }
+        } else if let Some(&DropdownListMessage::SelectionChanged(Some(index))) =
+            message.data::<DropdownListMessage>()
+        {
+            if message.destination() == self.animation_selector {
+                if let Some(animation) = self.animations.get(index) {
+                    self.selected = *animation;
+                    let scene = &engine.scenes[editor_scene.scene];
+                    self.sync_tracks(&scene.animations, &scene.graph, &mut engine.user_interface);
+                }
+            }
+        } else if let Some(&CheckBoxMessage::Check(Some(value))) = message.data::<CheckBoxMessage>()
+        {
+            if let Some(index) = self
+                .track_views
+                .iter()
+                .position(|view| view.selected == message.destination())
+            {
+                self.selected_tracks.retain(|&track| track != index);
+                if value {
+                    self.selected_tracks.push(index);
+                    self.selected_tracks.sort_unstable();
+                }
+            } else if let Some(index) = self
+                .track_views
+                .iter()
+                .position(|view| view.enabled == message.destination())
+            {
+                self.sender
+                    .send(Message::do_scene_command(
+                        SetAnimationTrackEnabledCommand::new(self.selected, index, value),
+                    ))
+                    .unwrap();
+            }
+        } else if let Some(&NumericUpDownMessage::Value(value)) =
+            message.data::<NumericUpDownMessage<f32>>()
+        {
+            if message.destination() == self.copy_from {
+                self.key_range.0 = value;
+            } else if message.destination() == self.copy_to {
+                self.key_range.1 = value;
+            } else if let Some(index) = self
+                .track_views
+                .iter()
+                .position(|view| view.weight == message.destination())
+            {
+                self.sender
+                    .send(Message::do_scene_command(
+                        SetAnimationTrackWeightCommand::new(self.selected, index, value),
+                    ))
+                    .unwrap();
+            }
+        }
+    }
+
+    // The clipboard is kept when the scene is changed, so keys could be pasted into an animation of
+    // another scene.
+    fn set_clipboard(&mut self, clipboard: AnimationClipboard, ui: &UserInterface) {
+        ui.send_message(WidgetMessage::enabled(
+            self.paste,
+            MessageDirection::ToWidget,
+            !clipboard.is_empty(),
+        ));
+        self.clipboard = clipboard;
+    }
+
+    pub fn clear(&mut self, ui: &UserInterface) {
+        self.animations.clear();
+        self.selected = Handle::NONE;
+        self.nodes.clear();
+        self.track_views.clear();
+        self.selected_tracks.clear();
+        send_sync_message(
+            ui,
+            DropdownListMessage::items(self.animation_selector, MessageDirection::ToWidget, vec![]),
+        );
+        for &child in ui.node(self.tracks_panel).children() {
+            ui.send_message(WidgetMessage::remove(child, MessageDirection::ToWidget));
+        }
+    }
+
+    pub fn on_mode_changed(&mut self, ui: &UserInterface, mode: &Mode) {
+        ui.send_message(WidgetMessage::enabled(
+            window_content(self.window, ui),
+            MessageDirection::ToWidget,
+            mode.is_edit(),
+        ));
+    }
+}
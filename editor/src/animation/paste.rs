@@ -0,0 +1,412 @@
+//! A dialog with options of pasting of key frames from the clipboard of the animation panel.
+
+use crate::{
+    animation::clipboard::{self, AnimationClipboard, LengthMode, PasteOptions, PasteTarget},
+    gui::make_dropdown_list_option,
+    scene::{commands::animation::PasteAnimationTracksCommand, EditorScene},
+    send_sync_message, GameEngine, Message, MSG_SYNC_FLAG,
+};
+use fyrox::{
+    animation::Animation,
+    core::{pool::Handle, scope_profile},
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        numeric::{NumericUpDownBuilder, NumericUpDownMessage},
+        scroll_viewer::ScrollViewerBuilder,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+    scene::graph::Graph,
+};
+use std::sync::mpsc::Sender;
+
+fn make_label(ctx: &mut BuildContext, row: usize, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(0)
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+fn make_numeric_field(ctx: &mut BuildContext, row: usize, min: f32) -> Handle<UiNode> {
+    NumericUpDownBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(1)
+            .with_margin(Thickness::uniform(1.0)),
+    )
+    .with_min_value(min)
+    .with_step(0.1)
+    .with_precision(3)
+    .build(ctx)
+}
+
+fn target_name(target: PasteTarget, animation: &Animation, graph: &Graph) -> String {
+    let node_name = |node| {
+        graph
+            .try_get(node)
+            .map_or_else(|| "<Invalid Node>".to_owned(), |node| node.name_owned())
+    };
+
+    match target {
+        PasteTarget::Skip => "<Skip>".to_owned(),
+        PasteTarget::Track(index) => animation.get_tracks().get(index).map_or_else(
+            || "<Invalid Track>".to_owned(),
+            |track| node_name(track.get_node()),
+        ),
+        PasteTarget::NewTrack(node) => format!("<New Track> {}", node_name(node)),
+    }
+}
+
+struct TargetView {
+    selector: Handle<UiNode>,
+    // Possible targets in the order of items of the selector.
+    targets: Vec<PasteTarget>,
+}
+
+pub struct PasteWindow {
+    pub window: Handle<UiNode>,
+    time: Handle<UiNode>,
+    time_scale: Handle<UiNode>,
+    length_mode: Handle<UiNode>,
+    info: Handle<UiNode>,
+    targets_panel: Handle<UiNode>,
+    paste: Handle<UiNode>,
+    cancel: Handle<UiNode>,
+    target_views: Vec<TargetView>,
+    animation: Handle<Animation>,
+    clipboard: AnimationClipboard,
+    options: PasteOptions,
+}
+
+impl PasteWindow {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let time;
+        let time_scale;
+        let length_mode;
+        let info;
+        let targets_panel;
+        let paste;
+        let cancel;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(350.0).with_height(300.0))
+            .with_title(WindowTitle::text("Paste Animation Keys"))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            GridBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_child(make_label(ctx, 0, "Paste At"))
+                                    .with_child({
+                                        time = make_numeric_field(ctx, 0, 0.0);
+                                        time
+                                    })
+                                    .with_child(make_label(ctx, 1, "Time Scale"))
+                                    .with_child({
+                                        time_scale = make_numeric_field(ctx, 1, 0.01);
+                                        time_scale
+                                    })
+                                    .with_child(make_label(ctx, 2, "Animation Length"))
+                                    .with_child({
+                                        length_mode = DropdownListBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_row(2)
+                                                .on_column(1)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_items(
+                                            LengthMode::ALL
+                                                .iter()
+                                                .map(|mode| {
+                                                    make_dropdown_list_option(ctx, mode.name())
+                                                })
+                                                .collect(),
+                                        )
+                                        .with_selected(0)
+                                        .build(ctx);
+                                        length_mode
+                                    }),
+                            )
+                            .add_column(Column::strict(120.0))
+                            .add_column(Column::stretch())
+                            .add_row(Row::strict(25.0))
+                            .add_row(Row::strict(25.0))
+                            .add_row(Row::strict(25.0))
+                            .build(ctx),
+                        )
+                        .with_child({
+                            info = TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx);
+                            info
+                        })
+                        .with_child(
+                            ScrollViewerBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_content({
+                                targets_panel =
+                                    StackPanelBuilder::new(WidgetBuilder::new()).build(ctx);
+                                targets_panel
+                            })
+                            .build(ctx),
+                        )
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(3)
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .with_child({
+                                        paste = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(1.0))
+                                                .with_width(80.0),
+                                        )
+                                        .with_text("Paste")
+                                        .build(ctx);
+                                        paste
+                                    })
+                                    .with_child({
+                                        cancel = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(1.0))
+                                                .with_width(80.0),
+                                        )
+                                        .with_text("Cancel")
+                                        .build(ctx);
+                                        cancel
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_column(Column::stretch())
+                .add_row(Row::strict(75.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::stretch())
+                .add_row(Row::strict(25.0))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            time,
+            time_scale,
+            length_mode,
+            info,
+            targets_panel,
+            paste,
+            cancel,
+            target_views: Default::default(),
+            animation: Default::default(),
+            clipboard: Default::default(),
+            options: PasteOptions {
+                time: 0.0,
+                time_scale: 1.0,
+                length_mode: LengthMode::Extend,
+                targets: Default::default(),
+            },
+        }
+    }
+
+    /// Opens the window for pasting of the clipboard into the given animation. Keys are pasted at
+    /// the time position of the animation by default, whole tracks keep their timing.
+    pub fn open(
+        &mut self,
+        clipboard: &AnimationClipboard,
+        animation_handle: Handle<Animation>,
+        animation: &Animation,
+        graph: &Graph,
+        ui: &mut UserInterface,
+    ) {
+        self.animation = animation_handle;
+        self.clipboard = clipboard.clone();
+        self.options = PasteOptions {
+            time: if clipboard.whole_tracks {
+                0.0
+            } else {
+                animation.get_time_position()
+            },
+            time_scale: 1.0,
+            length_mode: self.options.length_mode,
+            targets: clipboard::match_targets(clipboard, animation, graph),
+        };
+
+        send_sync_message(
+            ui,
+            NumericUpDownMessage::value(self.time, MessageDirection::ToWidget, self.options.time),
+        );
+        send_sync_message(
+            ui,
+            NumericUpDownMessage::value(
+                self.time_scale,
+                MessageDirection::ToWidget,
+                self.options.time_scale,
+            ),
+        );
+
+        for &child in ui.node(self.targets_panel).children() {
+            ui.send_message(WidgetMessage::remove(child, MessageDirection::ToWidget));
+        }
+        self.target_views.clear();
+
+        let ctx = &mut ui.build_ctx();
+        for (clipboard_track, &default_target) in
+            clipboard.tracks.iter().zip(self.options.targets.iter())
+        {
+            // Unmatched tracks could be retargeted to any track of the animation.
+            let mut targets = vec![PasteTarget::Skip];
+            targets.extend((0..animation.get_tracks().len()).map(PasteTarget::Track));
+            if let PasteTarget::NewTrack(_) = default_target {
+                targets.push(default_target);
+            }
+
+            let selector = DropdownListBuilder::new(
+                WidgetBuilder::new()
+                    .on_column(1)
+                    .with_margin(Thickness::uniform(1.0)),
+            )
+            .with_items(
+                targets
+                    .iter()
+                    .map(|&target| {
+                        make_dropdown_list_option(ctx, &target_name(target, animation, graph))
+                    })
+                    .collect(),
+            )
+            .with_selected(
+                targets
+                    .iter()
+                    .position(|&target| target == default_target)
+                    .unwrap_or_default(),
+            )
+            .build(ctx);
+
+            let row = GridBuilder::new(
+                WidgetBuilder::new()
+                    .with_child(
+                        TextBuilder::new(WidgetBuilder::new().on_column(0))
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .with_text(&clipboard_track.node_name)
+                            .build(ctx),
+                    )
+                    .with_child(selector),
+            )
+            .add_row(Row::strict(24.0))
+            .add_column(Column::strict(120.0))
+            .add_column(Column::stretch())
+            .build(ctx);
+            ctx.link(row, self.targets_panel);
+
+            self.target_views.push(TargetView { selector, targets });
+        }
+
+        self.sync_info(animation, ui);
+
+        ui.send_message(WindowMessage::open_modal(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    fn sync_info(&self, animation: &Animation, ui: &UserInterface) {
+        let end = self.options.time + self.clipboard.duration() * self.options.time_scale;
+        ui.send_message(TextMessage::text(
+            self.info,
+            MessageDirection::ToWidget,
+            format!(
+                "Pasted keys end at {:.3} s, the animation is {:.3} s long.",
+                end,
+                animation.length()
+            ),
+        ));
+    }
+
+    fn close(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
+        scope_profile!();
+
+        if message.direction() != MessageDirection::FromWidget || message.has_flags(MSG_SYNC_FLAG) {
+            return;
+        }
+
+        let animation = match engine.scenes[editor_scene.scene]
+            .animations
+            .try_get(self.animation)
+        {
+            Some(animation) => animation,
+            None => return,
+        };
+
+        if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.paste {
+                let (tracks, length) = clipboard::paste(&self.clipboard, animation, &self.options);
+                sender
+                    .send(Message::do_scene_command(PasteAnimationTracksCommand::new(
+                        self.animation,
+                        tracks,
+                        length,
+                    )))
+                    .unwrap();
+                self.close(&engine.user_interface);
+            } else if message.destination() == self.cancel {
+                self.close(&engine.user_interface);
+            }
+        } else if let Some(&NumericUpDownMessage::Value(value)) =
+            message.data::<NumericUpDownMessage<f32>>()
+        {
+            if message.destination() == self.time {
+                self.options.time = value;
+                self.sync_info(animation, &engine.user_interface);
+            } else if message.destination() == self.time_scale {
+                self.options.time_scale = value;
+                self.sync_info(animation, &engine.user_interface);
+            }
+        } else if let Some(&DropdownListMessage::SelectionChanged(Some(index))) =
+            message.data::<DropdownListMessage>()
+        {
+            if message.destination() == self.length_mode {
+                self.options.length_mode = LengthMode::ALL[index];
+            } else if let Some(position) = self
+                .target_views
+                .iter()
+                .position(|view| view.selector == message.destination())
+            {
+                self.options.targets[position] = self.target_views[position].targets[index];
+            }
+        }
+    }
+}
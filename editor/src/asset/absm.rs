@@ -0,0 +1,392 @@
+//! Read-only preview of animation blending state machine (ABSM) resources. It shows a miniature of
+//! the state graph, parameters and animations used by the machine, so there is no need to open
+//! the ABSM editor to find out what is inside of a resource.
+
+use crate::absm::transition::draw_transition_arrow;
+use fyrox::{
+    animation::machine::{MachineDefinition, Parameter},
+    asset::ResourceState,
+    core::{algebra::Vector2, color::Color, pool::Handle},
+    engine::resource_manager::ResourceManager,
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        canvas::CanvasBuilder,
+        define_widget_deref,
+        draw::DrawingContext,
+        formatted_text::WrapMode,
+        message::{MessageDirection, UiMessage},
+        scroll_viewer::ScrollViewerBuilder,
+        stack_panel::StackPanelBuilder,
+        text::TextBuilder,
+        widget::{Widget, WidgetBuilder, WidgetMessage},
+        BuildContext, Control, HorizontalAlignment, Thickness, UiNode, UserInterface,
+        VerticalAlignment, BRUSH_DARK,
+    },
+    resource::absm::AbsmResource,
+};
+use std::{
+    any::{Any, TypeId},
+    ops::{Deref, DerefMut},
+    path::Path,
+};
+
+const MINIATURE_WIDTH: f32 = 240.0;
+const MINIATURE_HEIGHT: f32 = 160.0;
+const MINIATURE_MARGIN: f32 = 4.0;
+const STATE_WIDTH: f32 = 70.0;
+const STATE_HEIGHT: f32 = 20.0;
+
+const ERROR_BRUSH: Brush = Brush::Solid(Color::opaque(255, 60, 60));
+const STATE_BRUSH: Brush = Brush::Solid(Color::opaque(80, 80, 80));
+const ENTRY_STATE_BRUSH: Brush = Brush::Solid(Color::opaque(40, 120, 40));
+const TRANSITION_BRUSH: Brush = Brush::Solid(Color::opaque(160, 160, 160));
+
+/// Draws transitions of the miniature, it is placed behind states.
+#[derive(Clone, Debug)]
+struct TransitionArrows {
+    widget: Widget,
+    arrows: Vec<(Vector2<f32>, Vector2<f32>)>,
+}
+
+define_widget_deref!(TransitionArrows);
+
+impl Control for TransitionArrows {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn draw(&self, drawing_context: &mut DrawingContext) {
+        for (source, dest) in self.arrows.iter() {
+            draw_transition_arrow(
+                drawing_context,
+                self.clip_bounds(),
+                self.foreground(),
+                *source,
+                *dest,
+                1.5,
+                8.0,
+            );
+        }
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+    }
+}
+
+/// Returns a list of human-readable problems of the definition, that will most likely cause
+/// errors on instantiation.
+fn validate(definition: &MachineDefinition) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    if definition.states.alive_count() > 0
+        && !definition.states.is_valid_handle(definition.entry_state)
+    {
+        errors.push("Entry state is not set.".to_owned());
+    }
+
+    for state in definition.states.iter() {
+        if !definition.nodes.is_valid_handle(state.root) {
+            errors.push(format!("State {} has no root node.", state.name));
+        }
+    }
+
+    for transition in definition.transitions.iter() {
+        if !definition.states.is_valid_handle(transition.source)
+            || !definition.states.is_valid_handle(transition.dest)
+        {
+            errors.push(format!(
+                "Transition {} refers to a missing state.",
+                transition.name
+            ));
+        }
+
+        if !transition.rule.is_empty()
+            && !definition
+                .parameters
+                .container
+                .iter()
+                .any(|p| p.name == transition.rule)
+        {
+            errors.push(format!(
+                "Transition {} uses unknown rule parameter {}.",
+                transition.name, transition.rule
+            ));
+        }
+    }
+
+    errors
+}
+
+fn make_header(ctx: &mut BuildContext, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(WidgetBuilder::new().with_margin(Thickness {
+        left: 1.0,
+        top: 6.0,
+        right: 1.0,
+        bottom: 2.0,
+    }))
+    .with_text(text)
+    .build(ctx)
+}
+
+fn make_line(ctx: &mut BuildContext, text: &str, brush: Option<Brush>) -> Handle<UiNode> {
+    let mut widget_builder = WidgetBuilder::new().with_margin(Thickness::left(6.0));
+    if let Some(brush) = brush {
+        widget_builder = widget_builder.with_foreground(brush);
+    }
+    TextBuilder::new(widget_builder)
+        .with_wrap(WrapMode::Word)
+        .with_text(text)
+        .build(ctx)
+}
+
+fn make_miniature(ctx: &mut BuildContext, definition: &MachineDefinition) -> Handle<UiNode> {
+    let (min, max) = definition.states.iter().fold(
+        (Vector2::repeat(f32::MAX), Vector2::repeat(-f32::MAX)),
+        |(min, max), state| (min.inf(&state.position), max.sup(&state.position)),
+    );
+    let extent = max - min;
+
+    // Fit the graph into the miniature, but never scale it up.
+    let state_size = Vector2::new(STATE_WIDTH, STATE_HEIGHT);
+    let available = Vector2::new(MINIATURE_WIDTH, MINIATURE_HEIGHT)
+        - state_size
+        - Vector2::repeat(2.0 * MINIATURE_MARGIN);
+    let mut scale = 1.0f32;
+    for (extent, available) in extent.iter().zip(available.iter()) {
+        if *extent > 0.0 {
+            scale = scale.min(available / extent);
+        }
+    }
+
+    let state_position =
+        |position: Vector2<f32>| Vector2::repeat(MINIATURE_MARGIN) + (position - min).scale(scale);
+
+    let arrows = definition
+        .transitions
+        .iter()
+        .filter_map(|transition| {
+            let source = definition.states.try_borrow(transition.source)?;
+            let dest = definition.states.try_borrow(transition.dest)?;
+            let half_size = state_size.scale(0.5);
+            Some((
+                state_position(source.position) + half_size,
+                state_position(dest.position) + half_size,
+            ))
+        })
+        .collect();
+
+    let mut children = vec![ctx.add_node(UiNode::new(TransitionArrows {
+        widget: WidgetBuilder::new()
+            .with_foreground(TRANSITION_BRUSH)
+            .with_clip_to_bounds(false)
+            .build(),
+        arrows,
+    }))];
+
+    for (handle, state) in definition.states.pair_iter() {
+        children.push(
+            BorderBuilder::new(
+                WidgetBuilder::new()
+                    .with_desired_position(state_position(state.position))
+                    .with_width(STATE_WIDTH)
+                    .with_height(STATE_HEIGHT)
+                    .with_background(if handle == definition.entry_state {
+                        ENTRY_STATE_BRUSH
+                    } else {
+                        STATE_BRUSH
+                    })
+                    .with_child(
+                        TextBuilder::new(WidgetBuilder::new())
+                            .with_text(&state.name)
+                            .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx),
+                    ),
+            )
+            .build(ctx),
+        );
+    }
+
+    BorderBuilder::new(
+        WidgetBuilder::new()
+            .with_background(BRUSH_DARK)
+            .with_margin(Thickness::uniform(1.0))
+            .with_child(
+                CanvasBuilder::new(
+                    WidgetBuilder::new()
+                        .with_width(MINIATURE_WIDTH)
+                        .with_height(MINIATURE_HEIGHT)
+                        .with_clip_to_bounds(true)
+                        .with_children(children),
+                )
+                .build(ctx),
+            ),
+    )
+    .build(ctx)
+}
+
+fn parameter_description(parameter: &Parameter) -> String {
+    match parameter {
+        Parameter::Weight(weight) => format!("Weight = {}", weight),
+        Parameter::Rule(rule) => format!("Rule = {}", rule),
+        Parameter::Index(index) => format!("Index = {}", index),
+    }
+}
+
+fn make_content(ctx: &mut BuildContext, resource: &AbsmResource) -> Vec<Handle<UiNode>> {
+    let state = resource.state();
+    match &*state {
+        ResourceState::Pending { .. } => vec![make_line(ctx, "Loading...", None)],
+        ResourceState::LoadError { path, error } => vec![make_line(
+            ctx,
+            &format!(
+                "Unable to load {}. Reason: {}",
+                path.display(),
+                error
+                    .as_ref()
+                    .map_or_else(|| "Unknown".to_owned(), |e| e.to_string())
+            ),
+            Some(ERROR_BRUSH),
+        )],
+        ResourceState::Ok(data) => {
+            let definition = &data.absm_definition;
+
+            let mut content = Vec::new();
+
+            for error in validate(definition) {
+                content.push(make_line(ctx, &error, Some(ERROR_BRUSH)));
+            }
+
+            content.push(make_header(
+                ctx,
+                &format!(
+                    "States ({}), Transitions ({})",
+                    definition.states.alive_count(),
+                    definition.transitions.alive_count()
+                ),
+            ));
+            content.push(make_miniature(ctx, definition));
+
+            content.push(make_header(ctx, "Parameters"));
+            for parameter in definition.parameters.container.iter() {
+                content.push(make_line(
+                    ctx,
+                    &format!(
+                        "{}: {}",
+                        parameter.name,
+                        parameter_description(&parameter.value)
+                    ),
+                    None,
+                ));
+            }
+
+            content.push(make_header(ctx, "Animations"));
+            let mut animations = definition.collect_animation_paths();
+            animations.sort();
+            animations.dedup();
+            for animation in animations {
+                // Missing animations will fail instantiation of the machine.
+                let exists = Path::new(&animation).exists();
+                content.push(make_line(
+                    ctx,
+                    &animation,
+                    if exists { None } else { Some(ERROR_BRUSH) },
+                ));
+            }
+
+            content
+        }
+    }
+}
+
+pub struct AbsmPreview {
+    pub root: Handle<UiNode>,
+    content: Handle<UiNode>,
+    resource: Option<AbsmResource>,
+    /// Whether the content shows a loaded (or failed) resource, or it waits for the resource to load.
+    ready: bool,
+}
+
+impl AbsmPreview {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let content;
+        let root = ScrollViewerBuilder::new(WidgetBuilder::new().with_visibility(false))
+            .with_content({
+                content = StackPanelBuilder::new(WidgetBuilder::new()).build(ctx);
+                content
+            })
+            .build(ctx);
+
+        Self {
+            root,
+            content,
+            resource: None,
+            ready: false,
+        }
+    }
+
+    /// Starts showing the resource at the given path, the resource is loaded in background and the
+    /// preview is filled when the resource is loaded.
+    pub fn show(
+        &mut self,
+        path: &Path,
+        resource_manager: &ResourceManager,
+        ui: &mut UserInterface,
+    ) {
+        self.resource = Some(resource_manager.request_absm(path));
+        self.ready = false;
+        self.rebuild(ui);
+
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    pub fn hide(&mut self, ui: &UserInterface) {
+        self.resource = None;
+        self.ready = false;
+
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            false,
+        ));
+    }
+
+    pub fn update(&mut self, ui: &mut UserInterface) {
+        let loaded = self.resource.as_ref().map_or(false, |resource| {
+            !matches!(*resource.state(), ResourceState::Pending { .. })
+        });
+
+        if loaded && !self.ready {
+            self.rebuild(ui);
+        }
+    }
+
+    fn rebuild(&mut self, ui: &mut UserInterface) {
+        for &child in ui.node(self.content).children() {
+            ui.send_message(WidgetMessage::remove(child, MessageDirection::ToWidget));
+        }
+
+        if let Some(resource) = self.resource.as_ref() {
+            self.ready = !matches!(*resource.state(), ResourceState::Pending { .. });
+
+            let content = make_content(&mut ui.build_ctx(), resource);
+            for child in content {
+                ui.send_message(WidgetMessage::link(
+                    child,
+                    MessageDirection::ToWidget,
+                    self.content,
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! Dialogs that are used by the asset browser to organize assets.
+
+use crate::asset::relocation::RelocationPlan;
+use fyrox::{
+    core::{color::Color, pool::Handle},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        button::{ButtonBuilder, ButtonMessage},
+        formatted_text::WrapMode,
+        grid::{Column, GridBuilder, Row},
+        list_view::{ListViewBuilder, ListViewMessage},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        text_box::{TextBoxBuilder, TextBoxMessage, TextCommitMode},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+    utils::log::Log,
+};
+use std::path::{Path, PathBuf};
+
+fn make_buttons(
+    ctx: &mut BuildContext,
+    row: usize,
+    ok_text: &str,
+) -> (Handle<UiNode>, Handle<UiNode>, Handle<UiNode>) {
+    let ok;
+    let cancel;
+    let panel = StackPanelBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_horizontal_alignment(HorizontalAlignment::Right)
+            .with_child({
+                ok = ButtonBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(1.0))
+                        .with_width(80.0),
+                )
+                .with_text(ok_text)
+                .build(ctx);
+                ok
+            })
+            .with_child({
+                cancel = ButtonBuilder::new(
+                    WidgetBuilder::new()
+                        .with_margin(Thickness::uniform(1.0))
+                        .with_width(80.0),
+                )
+                .with_text("Cancel")
+                .build(ctx);
+                cancel
+            }),
+    )
+    .with_orientation(Orientation::Horizontal)
+    .build(ctx);
+    (panel, ok, cancel)
+}
+
+/// Asks a name of a new folder and creates it.
+pub struct NewFolderWindow {
+    pub window: Handle<UiNode>,
+    name_box: Handle<UiNode>,
+    create: Handle<UiNode>,
+    cancel: Handle<UiNode>,
+    name: String,
+    parent: PathBuf,
+}
+
+impl NewFolderWindow {
+    const DEFAULT_NAME: &'static str = "New Folder";
+
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let name_box;
+        let (buttons, create, cancel) = make_buttons(ctx, 1, "Create");
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(85.0))
+            .with_title(WindowTitle::text("New Folder"))
+            .can_minimize(false)
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            name_box = TextBoxBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_text_commit_mode(TextCommitMode::Immediate)
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .with_text(Self::DEFAULT_NAME)
+                            .build(ctx);
+                            name_box
+                        })
+                        .with_child(buttons),
+                )
+                .add_column(Column::stretch())
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            name_box,
+            create,
+            cancel,
+            name: Self::DEFAULT_NAME.to_owned(),
+            parent: Default::default(),
+        }
+    }
+
+    /// Opens the window to create a folder in the given `parent` folder.
+    pub fn open(&mut self, ui: &UserInterface, parent: PathBuf) {
+        self.parent = parent;
+        self.name = Self::DEFAULT_NAME.to_owned();
+
+        ui.send_message(TextBoxMessage::text(
+            self.name_box,
+            MessageDirection::ToWidget,
+            self.name.clone(),
+        ));
+        ui.send_message(WindowMessage::open_modal(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    fn close(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    /// Returns a path of the new folder, if it was created.
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        ui: &UserInterface,
+    ) -> Option<PathBuf> {
+        if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.create {
+                self.close(ui);
+
+                let name = self.name.trim();
+                if name.is_empty() || Path::new(name).components().count() != 1 {
+                    Log::err(format!("{} is not a valid folder name!", name));
+                    return None;
+                }
+
+                let path = self.parent.join(name);
+                return match std::fs::create_dir(&path) {
+                    Ok(_) => Some(path),
+                    Err(e) => {
+                        Log::err(format!(
+                            "Unable to create folder {}. Reason: {}",
+                            path.display(),
+                            e
+                        ));
+                        None
+                    }
+                };
+            } else if message.destination() == self.cancel {
+                self.close(ui);
+            }
+        } else if let Some(TextBoxMessage::Text(text)) = message.data::<TextBoxMessage>() {
+            if message.destination() == self.name_box
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.name = text.clone();
+            }
+        }
+        None
+    }
+}
+
+/// Shows what will be moved and which files will be changed, the move is done only if the user
+/// confirms it.
+pub struct MoveConfirmationWindow {
+    pub window: Handle<UiNode>,
+    summary: Handle<UiNode>,
+    list: Handle<UiNode>,
+    confirm: Handle<UiNode>,
+    cancel: Handle<UiNode>,
+    plan: Option<RelocationPlan>,
+}
+
+impl MoveConfirmationWindow {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let summary;
+        let list;
+        let (buttons, confirm, cancel) = make_buttons(ctx, 2, "Move");
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(450.0).with_height(350.0))
+            .with_title(WindowTitle::text("Move Assets"))
+            .can_minimize(false)
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            summary = TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_margin(Thickness::uniform(2.0)),
+                            )
+                            .with_wrap(WrapMode::Word)
+                            .build(ctx);
+                            summary
+                        })
+                        .with_child({
+                            list = ListViewBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .build(ctx);
+                            list
+                        })
+                        .with_child(buttons),
+                )
+                .add_column(Column::stretch())
+                .add_row(Row::auto())
+                .add_row(Row::stretch())
+                .add_row(Row::strict(25.0))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            summary,
+            list,
+            confirm,
+            cancel,
+            plan: None,
+        }
+    }
+
+    pub fn open(&mut self, ui: &mut UserInterface, plan: RelocationPlan) {
+        ui.send_message(TextMessage::text(
+            self.summary,
+            MessageDirection::ToWidget,
+            format!(
+                "{} asset(s) will be moved to {}, {} file(s) will be updated to keep \
+                references to the moved assets:",
+                plan.moves.len() + plan.sidecars.len(),
+                plan.moves
+                    .first()
+                    .and_then(|m| m.to.parent())
+                    .map_or_else(Default::default, |p| p.display().to_string()),
+                plan.affected.len()
+            ),
+        ));
+
+        let lines = plan
+            .moves
+            .iter()
+            .chain(plan.sidecars.iter())
+            .map(|m| format!("{} -> {}", m.from.display(), m.to.display()))
+            .chain(plan.affected.iter().map(|affected| {
+                format!(
+                    "{} ({} reference(s))",
+                    affected.path.display(),
+                    affected.references
+                )
+            }))
+            .collect::<Vec<_>>();
+
+        let ctx = &mut ui.build_ctx();
+        let items = lines
+            .into_iter()
+            .enumerate()
+            .map(|(index, line)| {
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_background(Brush::Solid(if index % 2 == 0 {
+                            Color::opaque(70, 70, 70)
+                        } else {
+                            Color::opaque(40, 40, 40)
+                        }))
+                        .with_child(
+                            TextBuilder::new(
+                                WidgetBuilder::new().with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_text(line)
+                            .build(ctx),
+                        ),
+                )
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        ui.send_message(ListViewMessage::items(
+            self.list,
+            MessageDirection::ToWidget,
+            items,
+        ));
+        ui.send_message(WindowMessage::open_modal(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+
+        self.plan = Some(plan);
+    }
+
+    fn close(&mut self, ui: &UserInterface) {
+        self.plan = None;
+
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    /// Returns a plan that was confirmed by the user.
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        ui: &UserInterface,
+    ) -> Option<RelocationPlan> {
+        if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.confirm {
+                let plan = self.plan.take();
+                self.close(ui);
+                return plan;
+            } else if message.destination() == self.cancel {
+                self.close(ui);
+            }
+        }
+        None
+    }
+}
@@ -1,14 +1,177 @@
 use fyrox::{
-    core::inspect::Inspect, engine::resource_manager::ResourceManager,
+    core::{futures::executor::block_on, inspect::Inspect},
+    engine::resource_manager::{
+        options::{
+            folder_import_options_path, import_options_path, resolve_import_options,
+            try_get_folder_import_options, ImportOptions, ImportOptionsSource,
+        },
+        ResourceManager,
+    },
     gui::inspector::PropertyChanged,
+    utils::log::Log,
 };
+use std::path::{Path, PathBuf};
 
 pub mod model;
+pub mod sound;
 pub mod texture;
 
+/// A place where edited import options are stored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportOptionsLocation {
+    /// Own options of a resource.
+    Resource(PathBuf),
+    /// Default options of every resource in a folder.
+    Folder(PathBuf),
+}
+
+impl ImportOptionsLocation {
+    pub fn options_path<T: ImportOptions>(&self) -> PathBuf {
+        match self {
+            Self::Resource(path) => import_options_path(path),
+            Self::Folder(folder) => folder_import_options_path::<T>(folder),
+        }
+    }
+
+    /// Loads options stored at the location. If there are no options, then inherited options are
+    /// loaded - the options that will be used if the location has no options of its own.
+    pub fn load<T: ImportOptions>(&self) -> (T, ImportOptionsSource) {
+        match self {
+            Self::Resource(path) => block_on(resolve_import_options(path, T::default())),
+            Self::Folder(folder) => {
+                for folder in folder.ancestors() {
+                    if let Some(options) = block_on(try_get_folder_import_options(folder)) {
+                        return (options, ImportOptionsSource::Folder(folder.to_owned()));
+                    }
+                }
+                (T::default(), ImportOptionsSource::Default)
+            }
+        }
+    }
+
+    /// Returns `true` if options from the source are stored at the location, `false` - if they're
+    /// inherited.
+    pub fn is_own(&self, source: &ImportOptionsSource) -> bool {
+        match (self, source) {
+            (Self::Resource(_), ImportOptionsSource::Resource) => true,
+            (Self::Folder(folder), ImportOptionsSource::Folder(source)) => folder == source,
+            _ => false,
+        }
+    }
+
+    pub fn save<T: ImportOptions>(&self, options: &T) {
+        let path = self.options_path::<T>();
+        if !options.save(&path) {
+            Log::err(format!(
+                "Unable to save import options to {}",
+                path.display()
+            ));
+        }
+    }
+
+    /// Removes options stored at the location, so inherited options will be used instead.
+    pub fn clear<T: ImportOptions>(&self) {
+        let path = self.options_path::<T>();
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                Log::err(format!(
+                    "Unable to remove import options {}. Reason: {:?}",
+                    path.display(),
+                    e
+                ));
+            }
+        }
+    }
+
+    /// Returns a human-readable description of the source of options for the location.
+    pub fn describe(&self, source: &ImportOptionsSource) -> String {
+        let inherited = match source {
+            _ if self.is_own(source) => return "Own import options".to_owned(),
+            ImportOptionsSource::Folder(folder) => {
+                format!("Inherited from {}", display_folder(folder))
+            }
+            _ => "Inherited from global defaults".to_owned(),
+        };
+        match self {
+            Self::Resource(_) => inherited,
+            Self::Folder(folder) => format!(
+                "Defaults of {} - {}",
+                display_folder(folder),
+                inherited.to_lowercase()
+            ),
+        }
+    }
+
+    /// Returns a path of a resource, that must be reloaded when options are changed.
+    fn resource_path(&self) -> Option<&Path> {
+        match self {
+            Self::Resource(path) => Some(path),
+            Self::Folder(_) => None,
+        }
+    }
+}
+
+fn display_folder(folder: &Path) -> String {
+    if folder.as_os_str().is_empty() {
+        "the project root".to_owned()
+    } else {
+        folder.display().to_string()
+    }
+}
+
 pub trait ImportOptionsHandler {
-    fn apply(&self, resource_manager: ResourceManager);
+    fn apply(&mut self, resource_manager: ResourceManager);
     fn revert(&mut self);
+    /// Removes options stored at the location of the handler, so inherited options are used.
+    fn reset_to_inherited(&mut self, resource_manager: ResourceManager);
     fn value(&self) -> &dyn Inspect;
+    fn describe_source(&self) -> String;
+    fn has_own_options(&self) -> bool;
     fn handle_property_changed(&mut self, property_changed: &PropertyChanged);
 }
+
+/// Import options of a single location together with the source they were loaded from.
+pub struct EditedImportOptions<T> {
+    pub location: ImportOptionsLocation,
+    pub options: T,
+    pub source: ImportOptionsSource,
+}
+
+impl<T: ImportOptions> EditedImportOptions<T> {
+    pub fn new(location: ImportOptionsLocation) -> Self {
+        let (options, source) = location.load();
+        Self {
+            location,
+            options,
+            source,
+        }
+    }
+
+    pub fn reload(&mut self) {
+        let (options, source) = self.location.load();
+        self.options = options;
+        self.source = source;
+    }
+
+    /// Saves the options and returns a path of a resource that must be reloaded, if any.
+    pub fn save(&mut self) -> Option<&Path> {
+        self.location.save(&self.options);
+        self.reload();
+        self.location.resource_path()
+    }
+
+    /// Removes own options and returns a path of a resource that must be reloaded, if any.
+    pub fn clear(&mut self) -> Option<&Path> {
+        self.location.clear::<T>();
+        self.reload();
+        self.location.resource_path()
+    }
+
+    pub fn describe_source(&self) -> String {
+        self.location.describe(&self.source)
+    }
+
+    pub fn has_own_options(&self) -> bool {
+        self.location.is_own(&self.source)
+    }
+}
@@ -1,49 +1,66 @@
-use crate::asset::inspector::handlers::ImportOptionsHandler;
+use crate::asset::inspector::handlers::{
+    EditedImportOptions, ImportOptionsHandler, ImportOptionsLocation,
+};
 use fyrox::{
-    core::{append_extension, futures::executor::block_on, inspect::Inspect},
-    engine::resource_manager::{
-        options::{try_get_import_settings, ImportOptions},
-        ResourceManager,
-    },
+    core::inspect::Inspect,
+    engine::resource_manager::ResourceManager,
     gui::inspector::{FieldKind, PropertyChanged},
     resource::model::ModelImportOptions,
 };
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
 pub struct ModelImportOptionsHandler {
-    resource_path: PathBuf,
-    options: ModelImportOptions,
+    options: EditedImportOptions<ModelImportOptions>,
 }
 
 impl ModelImportOptionsHandler {
-    pub fn new(resource_path: &Path) -> Self {
+    pub fn new(location: ImportOptionsLocation) -> Self {
         Self {
-            resource_path: resource_path.to_owned(),
-            options: block_on(try_get_import_settings(resource_path)).unwrap_or_default(),
+            options: EditedImportOptions::new(location),
         }
     }
 }
 
-impl ImportOptionsHandler for ModelImportOptionsHandler {
-    fn apply(&self, _resource_manager: ResourceManager) {
-        // TODO: Reload model.
+// Only loaded models are reloaded, the engine propagates changes of reloaded models to scenes.
+fn reload_model(resource_manager: ResourceManager, path: Option<&Path>) {
+    if let Some(path) = path {
+        let mut state = resource_manager.state();
+        let models = &mut state.containers_mut().models;
+        if let Some(model) = models.find(path).cloned() {
+            models.reload_resource(model);
+        }
+    }
+}
 
-        self.options
-            .save(&append_extension(&self.resource_path, "options"));
+impl ImportOptionsHandler for ModelImportOptionsHandler {
+    fn apply(&mut self, resource_manager: ResourceManager) {
+        reload_model(resource_manager, self.options.save());
     }
 
     fn revert(&mut self) {
-        self.options = block_on(try_get_import_settings(&self.resource_path)).unwrap_or_default();
+        self.options.reload();
+    }
+
+    fn reset_to_inherited(&mut self, resource_manager: ResourceManager) {
+        reload_model(resource_manager, self.options.clear());
     }
 
     fn value(&self) -> &dyn Inspect {
-        &self.options
+        &self.options.options
+    }
+
+    fn describe_source(&self) -> String {
+        self.options.describe_source()
+    }
+
+    fn has_own_options(&self) -> bool {
+        self.options.has_own_options()
     }
 
     fn handle_property_changed(&mut self, property_changed: &PropertyChanged) {
         if let FieldKind::Object(ref args) = property_changed.value {
             if let ModelImportOptions::MATERIAL_SEARCH_OPTIONS = property_changed.name.as_ref() {
-                self.options.material_search_options = args.cast_clone().unwrap()
+                self.options.options.material_search_options = args.cast_clone().unwrap()
             }
         }
     }
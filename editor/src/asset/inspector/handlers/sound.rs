@@ -0,0 +1,66 @@
+use crate::asset::inspector::handlers::{
+    EditedImportOptions, ImportOptionsHandler, ImportOptionsLocation,
+};
+use fyrox::{
+    core::inspect::Inspect,
+    engine::resource_manager::{loader::sound::SoundBufferImportOptions, ResourceManager},
+    gui::inspector::{FieldKind, PropertyChanged},
+};
+use std::path::Path;
+
+pub struct SoundBufferImportOptionsHandler {
+    options: EditedImportOptions<SoundBufferImportOptions>,
+}
+
+impl SoundBufferImportOptionsHandler {
+    pub fn new(location: ImportOptionsLocation) -> Self {
+        Self {
+            options: EditedImportOptions::new(location),
+        }
+    }
+}
+
+fn reload_sound_buffer(resource_manager: ResourceManager, path: Option<&Path>) {
+    if let Some(path) = path {
+        let buffer = resource_manager.request_sound_buffer(path);
+        resource_manager
+            .state()
+            .containers_mut()
+            .sound_buffers
+            .reload_resource(buffer);
+    }
+}
+
+impl ImportOptionsHandler for SoundBufferImportOptionsHandler {
+    fn apply(&mut self, resource_manager: ResourceManager) {
+        reload_sound_buffer(resource_manager, self.options.save());
+    }
+
+    fn revert(&mut self) {
+        self.options.reload();
+    }
+
+    fn reset_to_inherited(&mut self, resource_manager: ResourceManager) {
+        reload_sound_buffer(resource_manager, self.options.clear());
+    }
+
+    fn value(&self) -> &dyn Inspect {
+        &self.options.options
+    }
+
+    fn describe_source(&self) -> String {
+        self.options.describe_source()
+    }
+
+    fn has_own_options(&self) -> bool {
+        self.options.has_own_options()
+    }
+
+    fn handle_property_changed(&mut self, property_changed: &PropertyChanged) {
+        if let FieldKind::Object(ref args) = property_changed.value {
+            if let SoundBufferImportOptions::STREAM = property_changed.name.as_ref() {
+                self.options.options.stream = args.cast_clone().unwrap()
+            }
+        }
+    }
+}
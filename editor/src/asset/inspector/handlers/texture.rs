@@ -1,71 +1,89 @@
-use crate::asset::inspector::handlers::ImportOptionsHandler;
+use crate::asset::inspector::handlers::{
+    EditedImportOptions, ImportOptionsHandler, ImportOptionsLocation,
+};
 use fyrox::{
-    core::{append_extension, futures::executor::block_on, inspect::Inspect},
-    engine::resource_manager::{
-        options::{try_get_import_settings, ImportOptions},
-        ResourceManager,
-    },
+    core::inspect::Inspect,
+    engine::resource_manager::ResourceManager,
     gui::inspector::{FieldKind, PropertyChanged},
     resource::texture::TextureImportOptions,
 };
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
 pub struct TextureImportOptionsHandler {
-    resource_path: PathBuf,
-    options: TextureImportOptions,
+    options: EditedImportOptions<TextureImportOptions>,
 }
 
 impl TextureImportOptionsHandler {
-    pub fn new(resource_path: &Path) -> Self {
+    pub fn new(location: ImportOptionsLocation) -> Self {
         Self {
-            resource_path: resource_path.to_owned(),
-            options: block_on(try_get_import_settings(resource_path)).unwrap_or_default(),
+            options: EditedImportOptions::new(location),
         }
     }
 }
 
-impl ImportOptionsHandler for TextureImportOptionsHandler {
-    fn apply(&self, resource_manager: ResourceManager) {
-        self.options
-            .save(&append_extension(&self.resource_path, "options"));
-
-        let texture = resource_manager.request_texture(&self.resource_path);
+fn reload_texture(resource_manager: ResourceManager, path: Option<&Path>) {
+    if let Some(path) = path {
+        let texture = resource_manager.request_texture(path);
         resource_manager
             .state()
             .containers_mut()
             .textures
             .reload_resource(texture);
     }
+}
+
+impl ImportOptionsHandler for TextureImportOptionsHandler {
+    fn apply(&mut self, resource_manager: ResourceManager) {
+        reload_texture(resource_manager, self.options.save());
+    }
 
     fn revert(&mut self) {
-        self.options = block_on(try_get_import_settings(&self.resource_path)).unwrap_or_default();
+        self.options.reload();
+    }
+
+    fn reset_to_inherited(&mut self, resource_manager: ResourceManager) {
+        reload_texture(resource_manager, self.options.clear());
     }
 
     fn value(&self) -> &dyn Inspect {
-        &self.options
+        &self.options.options
+    }
+
+    fn describe_source(&self) -> String {
+        self.options.describe_source()
+    }
+
+    fn has_own_options(&self) -> bool {
+        self.options.has_own_options()
     }
 
     fn handle_property_changed(&mut self, property_changed: &PropertyChanged) {
         if let FieldKind::Object(ref args) = property_changed.value {
+            let options = &mut self.options.options;
             match property_changed.name.as_ref() {
-                TextureImportOptions::MINIFICATION_FILTER => self
-                    .options
-                    .set_minification_filter(args.cast_clone().unwrap()),
-                TextureImportOptions::MAGNIFICATION_FILTER => self
-                    .options
-                    .set_magnification_filter(args.cast_clone().unwrap()),
+                TextureImportOptions::MINIFICATION_FILTER => {
+                    options.set_minification_filter(args.cast_clone().unwrap())
+                }
+                TextureImportOptions::MAGNIFICATION_FILTER => {
+                    options.set_magnification_filter(args.cast_clone().unwrap())
+                }
                 TextureImportOptions::S_WRAP_MODE => {
-                    self.options.set_s_wrap_mode(args.cast_clone().unwrap())
+                    options.set_s_wrap_mode(args.cast_clone().unwrap())
                 }
                 TextureImportOptions::T_WRAP_MODE => {
-                    self.options.set_t_wrap_mode(args.cast_clone().unwrap())
+                    options.set_t_wrap_mode(args.cast_clone().unwrap())
                 }
                 TextureImportOptions::ANISOTROPY => {
-                    self.options.set_anisotropy(args.cast_clone().unwrap())
+                    options.set_anisotropy(args.cast_clone().unwrap())
                 }
                 TextureImportOptions::COMPRESSION => {
-                    self.options.set_compression(args.cast_clone().unwrap())
+                    options.set_compression(args.cast_clone().unwrap())
+                }
+                TextureImportOptions::FORMAT => options.set_format(args.cast_clone().unwrap()),
+                TextureImportOptions::NORMAL_MAP => {
+                    options.set_normal_map(args.cast_clone().unwrap())
                 }
+                TextureImportOptions::SRGB => options.set_srgb(args.cast_clone().unwrap()),
                 _ => (),
             }
         }
@@ -12,8 +12,10 @@ use fyrox::{
         message::{MessageDirection, UiMessage},
         scroll_viewer::ScrollViewerBuilder,
         stack_panel::StackPanelBuilder,
-        widget::WidgetBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
         BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
     },
 };
 use std::rc::Rc;
@@ -24,22 +26,37 @@ pub mod handlers;
 pub struct AssetInspector {
     pub container: Handle<UiNode>,
     inspector: Handle<UiNode>,
+    source: Handle<UiNode>,
     apply: Handle<UiNode>,
     revert: Handle<UiNode>,
+    reset: Handle<UiNode>,
     handler: Option<Box<dyn ImportOptionsHandler>>,
 }
 
 impl AssetInspector {
     pub fn new(ctx: &mut BuildContext, row: usize, column: usize) -> Self {
         let inspector;
+        let source;
         let apply;
         let revert;
+        let reset;
         let container = GridBuilder::new(
             WidgetBuilder::new()
                 .on_row(row)
                 .on_column(column)
+                .with_child({
+                    source = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .on_row(0)
+                            .on_column(0)
+                            .with_margin(Thickness::uniform(1.0)),
+                    )
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx);
+                    source
+                })
                 .with_child(
-                    ScrollViewerBuilder::new(WidgetBuilder::new().on_row(0).on_column(0))
+                    ScrollViewerBuilder::new(WidgetBuilder::new().on_row(1).on_column(0))
                         .with_content({
                             inspector = InspectorBuilder::new(WidgetBuilder::new()).build(ctx);
                             inspector
@@ -49,7 +66,7 @@ impl AssetInspector {
                 .with_child(
                     StackPanelBuilder::new(
                         WidgetBuilder::new()
-                            .on_row(1)
+                            .on_row(2)
                             .on_column(0)
                             .with_horizontal_alignment(HorizontalAlignment::Right)
                             .with_child({
@@ -71,12 +88,24 @@ impl AssetInspector {
                                 .with_text("Revert")
                                 .build(ctx);
                                 revert
+                            })
+                            .with_child({
+                                reset = ButtonBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_enabled(false)
+                                        .with_width(100.0)
+                                        .with_margin(Thickness::uniform(1.0)),
+                                )
+                                .with_text("Use Inherited")
+                                .build(ctx);
+                                reset
                             }),
                     )
                     .with_orientation(Orientation::Horizontal)
                     .build(ctx),
                 ),
         )
+        .add_row(Row::strict(22.0))
         .add_row(Row::stretch())
         .add_row(Row::strict(25.0))
         .add_column(Column::stretch())
@@ -85,8 +114,10 @@ impl AssetInspector {
         Self {
             container,
             inspector,
+            source,
             apply,
             revert,
+            reset,
             handler: None,
         }
     }
@@ -114,30 +145,67 @@ impl AssetInspector {
         ));
 
         self.handler = Some(Box::new(handler));
+        self.sync_source(ui);
     }
 
-    pub fn handle_ui_message(&mut self, message: &UiMessage, engine: &mut Engine) {
+    fn sync_source(&self, ui: &UserInterface) {
+        if let Some(handler) = self.handler.as_ref() {
+            ui.send_message(TextMessage::text(
+                self.source,
+                MessageDirection::ToWidget,
+                handler.describe_source(),
+            ));
+            ui.send_message(WidgetMessage::enabled(
+                self.reset,
+                MessageDirection::ToWidget,
+                handler.has_own_options(),
+            ));
+        }
+    }
+
+    fn sync_inspector(&self, ui: &mut UserInterface) {
+        if let Some(handler) = self.handler.as_ref() {
+            let context = ui
+                .node(self.inspector)
+                .cast::<Inspector>()
+                .expect("Must be inspector")
+                .context()
+                .clone();
+            context.sync(handler.value(), ui, 0).unwrap();
+        }
+    }
+
+    /// Returns `true` if stored import options were changed.
+    pub fn handle_ui_message(&mut self, message: &UiMessage, engine: &mut Engine) -> bool {
+        let mut changed = false;
+        let mut reloaded = false;
+
         if let Some(handler) = self.handler.as_mut() {
             if let Some(ButtonMessage::Click) = message.data() {
                 if message.destination() == self.revert {
                     handler.revert();
-                    let context = engine
-                        .user_interface
-                        .node(self.inspector)
-                        .cast::<Inspector>()
-                        .expect("Must be inspector")
-                        .context()
-                        .clone();
-                    context
-                        .sync(handler.value(), &mut engine.user_interface, 0)
-                        .unwrap();
+                    reloaded = true;
                 } else if message.destination() == self.apply {
                     handler.apply(engine.resource_manager.clone());
+                    changed = true;
+                } else if message.destination() == self.reset {
+                    handler.reset_to_inherited(engine.resource_manager.clone());
+                    reloaded = true;
+                    changed = true;
                 }
             } else if let Some(InspectorMessage::PropertyChanged(property_changed)) = message.data()
             {
                 handler.handle_property_changed(property_changed)
             }
         }
+
+        if reloaded {
+            self.sync_inspector(&mut engine.user_interface);
+        }
+        if changed {
+            self.sync_source(&engine.user_interface);
+        }
+
+        changed
     }
 }
@@ -2,15 +2,23 @@ use crate::gui::AssetItemMessage;
 use crate::load_image;
 use fyrox::core::color::Color;
 use fyrox::core::pool::Handle;
+use fyrox::engine::resource_manager::loader::sound::SoundBufferImportOptions;
+use fyrox::engine::resource_manager::options::{folder_import_options_path, import_options_path};
 use fyrox::engine::resource_manager::ResourceManager;
+use fyrox::gui::border::BorderBuilder;
 use fyrox::gui::brush::Brush;
 use fyrox::gui::draw::{CommandTexture, Draw, DrawingContext};
 use fyrox::gui::grid::{Column, GridBuilder, Row};
 use fyrox::gui::image::ImageBuilder;
 use fyrox::gui::message::{MessageDirection, UiMessage};
 use fyrox::gui::text::TextBuilder;
+use fyrox::gui::utils::make_simple_tooltip;
 use fyrox::gui::widget::{Widget, WidgetBuilder, WidgetMessage};
-use fyrox::gui::{BuildContext, Control, HorizontalAlignment, Thickness, UiNode, UserInterface};
+use fyrox::gui::{
+    BuildContext, Control, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+};
+use fyrox::resource::model::ModelImportOptions;
+use fyrox::resource::texture::TextureImportOptions;
 use fyrox::utils::into_gui_texture;
 use std::any::{Any, TypeId};
 use std::ops::{Deref, DerefMut};
@@ -23,6 +31,8 @@ pub struct AssetItem {
     pub path: PathBuf,
     pub kind: AssetKind,
     preview: Handle<UiNode>,
+    // Shown when the asset has its own import options (or a folder has default import options).
+    import_options_marker: Handle<UiNode>,
     selected: bool,
 }
 
@@ -33,6 +43,57 @@ pub enum AssetKind {
     Texture,
     Sound,
     Shader,
+    Absm,
+    Folder,
+}
+
+impl AssetKind {
+    /// Returns kind of an asset at the given path, the kind is defined by the extension of a file.
+    pub fn from_path(path: &Path) -> Self {
+        if path.is_dir() {
+            return AssetKind::Folder;
+        }
+
+        match path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .as_deref()
+        {
+            Some("jpg" | "tga" | "png" | "bmp") => AssetKind::Texture,
+            Some("fbx" | "rgs") => AssetKind::Model,
+            Some("ogg" | "wav") => AssetKind::Sound,
+            Some("shader") => AssetKind::Shader,
+            Some("absm") => AssetKind::Absm,
+            _ => AssetKind::Unknown,
+        }
+    }
+
+    /// Returns `true` if assets of the kind have import options.
+    pub fn has_import_options(self) -> bool {
+        matches!(
+            self,
+            AssetKind::Model | AssetKind::Texture | AssetKind::Sound
+        )
+    }
+}
+
+/// Returns `true` if an asset has its own import options, that override default options of its
+/// folders. For folders, returns `true` if the folder has default import options of any kind.
+pub fn has_own_import_options(path: &Path, kind: AssetKind) -> bool {
+    match kind {
+        AssetKind::Folder => {
+            folder_import_options_path::<TextureImportOptions>(path).exists()
+                || folder_import_options_path::<ModelImportOptions>(path).exists()
+                || folder_import_options_path::<SoundBufferImportOptions>(path).exists()
+        }
+        _ => kind.has_import_options() && import_options_path(path).exists(),
+    }
+}
+
+impl AssetItem {
+    pub fn is_selected(&self) -> bool {
+        self.selected
+    }
 }
 
 impl Deref for AssetItem {
@@ -82,10 +143,21 @@ impl Control for AssetItem {
         if let Some(WidgetMessage::MouseDown { .. }) = message.data::<WidgetMessage>() {
             if !message.handled() {
                 message.set_handled(true);
+                // Ctrl+Click toggles selection of the item, so multiple items could be selected.
+                let select = !ui.keyboard_modifiers().control || !self.selected;
                 ui.send_message(AssetItemMessage::select(
                     self.handle(),
                     MessageDirection::ToWidget,
-                    true,
+                    select,
+                ));
+            }
+        } else if let Some(AssetItemMessage::SyncImportOptions) = message.data::<AssetItemMessage>()
+        {
+            if message.destination() == self.handle() {
+                ui.send_message(WidgetMessage::visibility(
+                    self.import_options_marker,
+                    MessageDirection::ToWidget,
+                    has_own_import_options(&self.path, self.kind),
                 ));
             }
         } else if let Some(AssetItemMessage::Select(select)) = message.data::<AssetItemMessage>() {
@@ -138,28 +210,16 @@ impl AssetItemBuilder {
         resource_manager: ResourceManager,
     ) -> Handle<UiNode> {
         let path = self.path.unwrap_or_default();
-        let mut kind = AssetKind::Unknown;
-        let texture =
-            path.extension()
-                .and_then(|ext| match ext.to_string_lossy().to_lowercase().as_ref() {
-                    "jpg" | "tga" | "png" | "bmp" => {
-                        kind = AssetKind::Texture;
-                        Some(into_gui_texture(resource_manager.request_texture(&path)))
-                    }
-                    "fbx" | "rgs" => {
-                        kind = AssetKind::Model;
-                        load_image(include_bytes!("../../resources/embed/model.png"))
-                    }
-                    "ogg" | "wav" => {
-                        kind = AssetKind::Sound;
-                        load_image(include_bytes!("../../resources/embed/sound.png"))
-                    }
-                    "shader" => {
-                        kind = AssetKind::Shader;
-                        load_image(include_bytes!("../../resources/embed/shader.png"))
-                    }
-                    _ => None,
-                });
+        let kind = AssetKind::from_path(&path);
+        let texture = match kind {
+            AssetKind::Folder => load_image(include_bytes!("../../resources/embed/folder.png")),
+            AssetKind::Texture => Some(into_gui_texture(resource_manager.request_texture(&path))),
+            AssetKind::Model => load_image(include_bytes!("../../resources/embed/model.png")),
+            AssetKind::Sound => load_image(include_bytes!("../../resources/embed/sound.png")),
+            AssetKind::Shader => load_image(include_bytes!("../../resources/embed/shader.png")),
+            AssetKind::Absm => load_image(include_bytes!("../../resources/embed/joint.png")),
+            AssetKind::Unknown => None,
+        };
 
         let preview = ImageBuilder::new(
             WidgetBuilder::new()
@@ -170,17 +230,40 @@ impl AssetItemBuilder {
         .with_opt_texture(texture)
         .build(ctx);
 
+        let import_options_marker = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(has_own_import_options(&path, kind))
+                .with_width(10.0)
+                .with_height(10.0)
+                .with_margin(Thickness::uniform(3.0))
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_background(Brush::Solid(Color::opaque(230, 160, 50)))
+                .with_tooltip(make_simple_tooltip(
+                    ctx,
+                    if kind == AssetKind::Folder {
+                        "The folder has default import options"
+                    } else {
+                        "The asset has its own import options"
+                    },
+                )),
+        )
+        .build(ctx);
+
         let item = AssetItem {
             widget: self
                 .widget_builder
                 .with_margin(Thickness::uniform(1.0))
                 .with_allow_drag(true)
+                // Assets could be dropped on folders to move them.
+                .with_allow_drop(kind == AssetKind::Folder)
                 .with_foreground(Brush::Solid(Color::opaque(50, 50, 50)))
                 .with_child(
                     GridBuilder::new(
                         WidgetBuilder::new()
                             .with_width(64.0)
                             .with_child(preview)
+                            .with_child(import_options_marker)
                             .with_child(
                                 TextBuilder::new(
                                     WidgetBuilder::new()
@@ -201,6 +284,7 @@ impl AssetItemBuilder {
             path,
             kind,
             preview,
+            import_options_marker,
             selected: false,
         };
         ctx.add_node(UiNode::new(item))
@@ -0,0 +1,100 @@
+use crate::{asset::item::AssetKind, menu::create_menu_item};
+use fyrox::{
+    core::pool::Handle,
+    gui::{
+        menu::MenuItemMessage,
+        message::UiMessage,
+        popup::{Placement, PopupBuilder, PopupMessage},
+        stack_panel::StackPanelBuilder,
+        widget::WidgetBuilder,
+        BuildContext, UiNode,
+    },
+};
+
+pub enum FolderAction {
+    /// Edit default import options of assets of the given kind in the folder.
+    EditImportDefaults(AssetKind),
+    /// Re-import every asset in the folder with current import options.
+    Reimport,
+}
+
+pub struct FolderContextMenu {
+    pub menu: Handle<UiNode>,
+    texture_defaults: Handle<UiNode>,
+    model_defaults: Handle<UiNode>,
+    sound_defaults: Handle<UiNode>,
+    reimport: Handle<UiNode>,
+    placement_target: Handle<UiNode>,
+}
+
+impl FolderContextMenu {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let texture_defaults;
+        let model_defaults;
+        let sound_defaults;
+        let reimport;
+        let menu = PopupBuilder::new(WidgetBuilder::new().with_visibility(false))
+            .with_content(
+                StackPanelBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            texture_defaults =
+                                create_menu_item("Texture Import Defaults...", vec![], ctx);
+                            texture_defaults
+                        })
+                        .with_child({
+                            model_defaults =
+                                create_menu_item("Model Import Defaults...", vec![], ctx);
+                            model_defaults
+                        })
+                        .with_child({
+                            sound_defaults =
+                                create_menu_item("Sound Import Defaults...", vec![], ctx);
+                            sound_defaults
+                        })
+                        .with_child({
+                            reimport =
+                                create_menu_item("Re-import With Current Options", vec![], ctx);
+                            reimport
+                        }),
+                )
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            menu,
+            texture_defaults,
+            model_defaults,
+            sound_defaults,
+            reimport,
+            placement_target: Default::default(),
+        }
+    }
+
+    /// Returns a requested action and a widget on which the menu was opened.
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+    ) -> Option<(FolderAction, Handle<UiNode>)> {
+        if let Some(MenuItemMessage::Click) = message.data() {
+            let action = if message.destination() == self.texture_defaults {
+                FolderAction::EditImportDefaults(AssetKind::Texture)
+            } else if message.destination() == self.model_defaults {
+                FolderAction::EditImportDefaults(AssetKind::Model)
+            } else if message.destination() == self.sound_defaults {
+                FolderAction::EditImportDefaults(AssetKind::Sound)
+            } else if message.destination() == self.reimport {
+                FolderAction::Reimport
+            } else {
+                return None;
+            };
+            return Some((action, self.placement_target));
+        } else if let Some(PopupMessage::Placement(Placement::Cursor(target))) = message.data() {
+            if message.destination() == self.menu {
+                self.placement_target = *target;
+            }
+        }
+        None
+    }
+}
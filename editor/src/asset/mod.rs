@@ -1,33 +1,48 @@
 use crate::utils::window_content;
 use crate::{
     asset::{
+        absm::AbsmPreview,
+        dialogs::{MoveConfirmationWindow, NewFolderWindow},
         inspector::{
-            handlers::{model::ModelImportOptionsHandler, texture::TextureImportOptionsHandler},
+            handlers::{
+                model::ModelImportOptionsHandler, sound::SoundBufferImportOptionsHandler,
+                texture::TextureImportOptionsHandler, ImportOptionsLocation,
+            },
             AssetInspector,
         },
         item::AssetItemBuilder,
+        menu::{FolderAction, FolderContextMenu},
+        reimport::ReimportWindow,
+        relocation::RelocationPlan,
     },
     gui::AssetItemMessage,
     preview::PreviewPanel,
+    settings::preview::PreviewSettings,
     AssetItem, AssetKind, GameEngine, Message, Mode,
 };
 use fyrox::{
     core::{
         color::Color, futures::executor::block_on, make_relative_path, pool::Handle, scope_profile,
     },
+    engine::resource_manager::ResourceManager,
     gui::{
         border::BorderBuilder,
         brush::Brush,
+        button::{ButtonBuilder, ButtonMessage},
         file_browser::{FileBrowserBuilder, FileBrowserMessage, Filter},
         grid::{Column, GridBuilder, Row},
-        message::{MessageDirection, UiMessage},
+        message::{MessageDirection, MouseButton, UiMessage},
+        messagebox::{MessageBoxBuilder, MessageBoxButtons, MessageBoxMessage},
         scroll_viewer::{ScrollViewerBuilder, ScrollViewerMessage},
         text::{TextBuilder, TextMessage},
+        tree::Tree,
         widget::{WidgetBuilder, WidgetMessage},
         window::{WindowBuilder, WindowTitle},
         wrap_panel::WrapPanelBuilder,
-        HorizontalAlignment, Orientation, UiNode, UserInterface, VerticalAlignment, BRUSH_DARK,
+        HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface, VerticalAlignment,
+        BRUSH_DARK,
     },
+    utils::log::Log,
 };
 use std::{
     ffi::OsStr,
@@ -35,8 +50,13 @@ use std::{
     sync::mpsc::Sender,
 };
 
+mod absm;
+mod dialogs;
 mod inspector;
 pub mod item;
+mod menu;
+mod reimport;
+pub mod relocation;
 
 pub struct AssetBrowser {
     pub window: Handle<UiNode>,
@@ -45,22 +65,32 @@ pub struct AssetBrowser {
     scroll_panel: Handle<UiNode>,
     selected_properties: Handle<UiNode>,
     preview: PreviewPanel,
+    absm_preview: AbsmPreview,
     items: Vec<Handle<UiNode>>,
     item_to_select: Option<PathBuf>,
     inspector: AssetInspector,
+    current_path: PathBuf,
+    new_folder: Handle<UiNode>,
+    new_folder_window: NewFolderWindow,
+    move_window: MoveConfirmationWindow,
+    error_message_box: Handle<UiNode>,
+    folder_context_menu: FolderContextMenu,
+    reimport_window: ReimportWindow,
 }
 
 impl AssetBrowser {
     pub fn new(engine: &mut GameEngine) -> Self {
-        let preview = PreviewPanel::new(engine, 250, 250);
+        let preview = PreviewPanel::new(engine, 250, 250).with_playback(engine);
         let ctx = &mut engine.user_interface.build_ctx();
 
         let inspector = AssetInspector::new(ctx, 1, 0);
+        let absm_preview = AbsmPreview::new(ctx);
 
         let content_panel;
         let folder_browser;
         let selected_properties;
         let scroll_panel;
+        let new_folder;
 
         let window = WindowBuilder::new(WidgetBuilder::new())
             .can_minimize(false)
@@ -87,12 +117,37 @@ impl AssetBrowser {
                             GridBuilder::new(
                                 WidgetBuilder::new()
                                     .on_column(1)
-                                    .with_child({
-                                        selected_properties =
-                                            TextBuilder::new(WidgetBuilder::new().on_row(0))
-                                                .build(ctx);
-                                        selected_properties
-                                    })
+                                    .with_child(
+                                        GridBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_row(0)
+                                                .with_child({
+                                                    selected_properties = TextBuilder::new(
+                                                        WidgetBuilder::new().on_column(0),
+                                                    )
+                                                    .with_vertical_text_alignment(
+                                                        VerticalAlignment::Center,
+                                                    )
+                                                    .build(ctx);
+                                                    selected_properties
+                                                })
+                                                .with_child({
+                                                    new_folder = ButtonBuilder::new(
+                                                        WidgetBuilder::new()
+                                                            .on_column(1)
+                                                            .with_width(80.0)
+                                                            .with_margin(Thickness::uniform(1.0)),
+                                                    )
+                                                    .with_text("New Folder")
+                                                    .build(ctx);
+                                                    new_folder
+                                                }),
+                                        )
+                                        .add_row(Row::stretch())
+                                        .add_column(Column::stretch())
+                                        .add_column(Column::auto())
+                                        .build(ctx),
+                                    )
                                     .with_child({
                                         scroll_panel = ScrollViewerBuilder::new(
                                             WidgetBuilder::new().on_row(1),
@@ -115,7 +170,7 @@ impl AssetBrowser {
                                         scroll_panel
                                     }),
                             )
-                            .add_row(Row::strict(20.0))
+                            .add_row(Row::strict(24.0))
                             .add_row(Row::stretch())
                             .add_column(Column::stretch())
                             .build(ctx),
@@ -129,6 +184,7 @@ impl AssetBrowser {
                                         GridBuilder::new(
                                             WidgetBuilder::new()
                                                 .with_child(preview.root)
+                                                .with_child(absm_preview.root)
                                                 .with_child(inspector.container),
                                         )
                                         .add_column(Column::stretch())
@@ -148,16 +204,38 @@ impl AssetBrowser {
             )
             .build(ctx);
 
+        let new_folder_window = NewFolderWindow::new(ctx);
+        let move_window = MoveConfirmationWindow::new(ctx);
+        let folder_context_menu = FolderContextMenu::new(ctx);
+        let reimport_window = ReimportWindow::new(ctx);
+
+        let error_message_box = MessageBoxBuilder::new(
+            WindowBuilder::new(WidgetBuilder::new().with_width(350.0).with_height(150.0))
+                .can_minimize(false)
+                .open(false)
+                .with_title(WindowTitle::text("Error")),
+        )
+        .with_buttons(MessageBoxButtons::Ok)
+        .build(ctx);
+
         Self {
             window,
             content_panel,
             folder_browser,
             preview,
+            absm_preview,
             scroll_panel,
             selected_properties,
             items: Default::default(),
             item_to_select: None,
             inspector,
+            current_path: Default::default(),
+            new_folder,
+            new_folder_window,
+            move_window,
+            error_message_box,
+            folder_context_menu,
+            reimport_window,
         }
     }
 
@@ -183,19 +261,43 @@ impl AssetBrowser {
     ) {
         scope_profile!();
 
-        self.inspector.handle_ui_message(message, engine);
+        if self.inspector.handle_ui_message(message, engine) {
+            self.sync_import_options_markers(&engine.user_interface);
+        }
         self.preview.handle_message(message, engine);
 
         let ui = &mut engine.user_interface;
 
+        self.reimport_window.handle_ui_message(message, ui);
+
+        if let Some((action, target)) = self.folder_context_menu.handle_ui_message(message) {
+            let item = ui.find_by_criteria_up(target, |node| node.cast::<AssetItem>().is_some());
+            if let Some(folder) = ui.try_get_node(item).and_then(|_| self.folder_at(item, ui)) {
+                self.on_folder_action(action, folder, engine, sender);
+            }
+            return;
+        }
+
+        if let Some(path) = self.new_folder_window.handle_ui_message(message, ui) {
+            self.on_folder_created(path, ui, engine.resource_manager.clone());
+            return;
+        }
+
+        if let Some(plan) = self.move_window.handle_ui_message(message, ui) {
+            self.execute_plan(plan, engine, &sender);
+            return;
+        }
+
         if let Some(AssetItemMessage::Select(true)) = message.data::<AssetItemMessage>() {
-            // Deselect other items.
-            for &item in self.items.iter().filter(|i| **i != message.destination()) {
-                ui.send_message(AssetItemMessage::select(
-                    item,
-                    MessageDirection::ToWidget,
-                    false,
-                ))
+            // Deselect other items, unless the user is selecting multiple items.
+            if !ui.keyboard_modifiers().control {
+                for &item in self.items.iter().filter(|i| **i != message.destination()) {
+                    ui.send_message(AssetItemMessage::select(
+                        item,
+                        MessageDirection::ToWidget,
+                        false,
+                    ))
+                }
             }
 
             let item = ui
@@ -208,6 +310,17 @@ impl AssetBrowser {
                 format!("Path: {:?}", item.path),
             ));
 
+            // ABSM resources have their own preview instead of the 3D one.
+            let is_absm = item.kind == AssetKind::Absm;
+            ui.send_message(WidgetMessage::visibility(
+                self.preview.root,
+                MessageDirection::ToWidget,
+                !is_absm,
+            ));
+            if !is_absm {
+                self.absm_preview.hide(ui);
+            }
+
             match item.kind {
                 AssetKind::Unknown => {}
                 AssetKind::Model => {
@@ -215,87 +328,327 @@ impl AssetBrowser {
                     block_on(self.preview.load_model(&path, engine));
 
                     self.inspector.inspect_resource_import_options(
-                        ModelImportOptionsHandler::new(&path),
+                        ModelImportOptionsHandler::new(ImportOptionsLocation::Resource(path)),
                         &mut engine.user_interface,
                         sender,
                     )
                 }
                 AssetKind::Texture => self.inspector.inspect_resource_import_options(
-                    TextureImportOptionsHandler::new(&item.path),
+                    TextureImportOptionsHandler::new(ImportOptionsLocation::Resource(
+                        item.path.clone(),
+                    )),
+                    &mut engine.user_interface,
+                    sender,
+                ),
+                AssetKind::Sound => self.inspector.inspect_resource_import_options(
+                    SoundBufferImportOptionsHandler::new(ImportOptionsLocation::Resource(
+                        item.path.clone(),
+                    )),
                     &mut engine.user_interface,
                     sender,
                 ),
-                AssetKind::Sound => {}
-                AssetKind::Shader => {}
+                AssetKind::Absm => {
+                    let path = item.path.clone();
+                    self.absm_preview.show(
+                        &path,
+                        &engine.resource_manager,
+                        &mut engine.user_interface,
+                    );
+                }
+                AssetKind::Shader | AssetKind::Folder => {}
             }
         } else if let Some(FileBrowserMessage::Path(path)) = message.data::<FileBrowserMessage>() {
             if message.destination() == self.folder_browser
                 && message.direction() == MessageDirection::FromWidget
             {
-                let item_to_select = self.item_to_select.take();
-                let mut handle_to_select = Handle::NONE;
-
-                // Clean content panel first.
-                for child in self.items.drain(..) {
-                    ui.send_message(WidgetMessage::remove(child, MessageDirection::ToWidget));
+                self.current_path = path.clone();
+                self.fill_content(ui, engine.resource_manager.clone());
+            }
+        } else if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.new_folder {
+                self.new_folder_window.open(ui, self.current_path.clone());
+            }
+        } else if let Some(WidgetMessage::DoubleClick {
+            button: MouseButton::Left,
+        }) = message.data::<WidgetMessage>()
+        {
+            let item = ui.find_by_criteria_up(message.destination(), |node| {
+                node.cast::<AssetItem>().is_some()
+            });
+            if let Some(item) = ui.try_get_node(item).and_then(|n| n.cast::<AssetItem>()) {
+                // Double click on a folder opens it.
+                if item.kind == AssetKind::Folder {
+                    ui.send_message(FileBrowserMessage::path(
+                        self.folder_browser,
+                        MessageDirection::ToWidget,
+                        item.path.clone(),
+                    ));
                 }
-
-                // Get all supported assets from folder and generate previews for them.
-                if let Ok(dir_iter) = std::fs::read_dir(path) {
-                    for entry in dir_iter.flatten() {
-                        fn check_ext(ext: &OsStr) -> bool {
-                            let ext = ext.to_string_lossy().to_lowercase();
-                            matches!(
-                                ext.as_str(),
-                                "rgs"
-                                    | "fbx"
-                                    | "jpg"
-                                    | "tga"
-                                    | "png"
-                                    | "bmp"
-                                    | "ogg"
-                                    | "wav"
-                                    | "shader"
-                            )
+            }
+        } else if let Some(&WidgetMessage::Drop(dropped)) = message.data::<WidgetMessage>() {
+            if let Some(destination) = self.folder_at(message.destination(), ui) {
+                let sources = self.dragged_paths(dropped, ui);
+                // Dropping a folder on itself is most likely accidental, so do nothing.
+                if !sources.is_empty() && !sources.contains(&destination) {
+                    match RelocationPlan::new(&sources, &destination) {
+                        Ok(plan) => {
+                            if !plan.is_empty() {
+                                self.move_window.open(ui, plan);
+                            }
                         }
+                        Err(e) => self.show_error(ui, e),
+                    }
+                }
+            }
+        }
+    }
+
+    fn fill_content(&mut self, ui: &mut UserInterface, resource_manager: ResourceManager) {
+        let item_to_select = self.item_to_select.take();
+        let mut handle_to_select = Handle::NONE;
+
+        // Clean content panel first.
+        for child in self.items.drain(..) {
+            ui.send_message(WidgetMessage::remove(child, MessageDirection::ToWidget));
+        }
 
-                        let entry_path = make_relative_path(entry.path());
-                        if !entry_path.is_dir() && entry_path.extension().map_or(false, check_ext) {
-                            let asset_item = AssetItemBuilder::new(WidgetBuilder::new())
-                                .with_path(entry_path.clone())
-                                .build(&mut ui.build_ctx(), engine.resource_manager.clone());
+        // Get all folders and supported assets from folder and generate previews for them.
+        if let Ok(dir_iter) = std::fs::read_dir(&self.current_path) {
+            fn check_ext(ext: &OsStr) -> bool {
+                let ext = ext.to_string_lossy().to_lowercase();
+                matches!(
+                    ext.as_str(),
+                    "rgs"
+                        | "fbx"
+                        | "jpg"
+                        | "tga"
+                        | "png"
+                        | "bmp"
+                        | "ogg"
+                        | "wav"
+                        | "shader"
+                        | "absm"
+                )
+            }
 
-                            self.items.push(asset_item);
+            let mut paths = dir_iter
+                .flatten()
+                .map(|entry| make_relative_path(entry.path()))
+                .filter(|path| path.is_dir() || path.extension().map_or(false, check_ext))
+                .collect::<Vec<_>>();
+            // Folders go first.
+            paths.sort_by_key(|path| (!path.is_dir(), path.clone()));
 
-                            ui.send_message(WidgetMessage::link(
-                                asset_item,
-                                MessageDirection::ToWidget,
-                                self.content_panel,
-                            ));
+            for entry_path in paths {
+                let mut widget_builder = WidgetBuilder::new();
+                if entry_path.is_dir() {
+                    widget_builder =
+                        widget_builder.with_context_menu(self.folder_context_menu.menu);
+                }
 
-                            if let Some(item_to_select) = item_to_select.as_ref() {
-                                if item_to_select == &entry_path {
-                                    handle_to_select = asset_item;
-                                }
-                            }
-                        }
+                let asset_item = AssetItemBuilder::new(widget_builder)
+                    .with_path(entry_path.clone())
+                    .build(&mut ui.build_ctx(), resource_manager.clone());
+
+                self.items.push(asset_item);
+
+                ui.send_message(WidgetMessage::link(
+                    asset_item,
+                    MessageDirection::ToWidget,
+                    self.content_panel,
+                ));
+
+                if let Some(item_to_select) = item_to_select.as_ref() {
+                    if item_to_select == &entry_path {
+                        handle_to_select = asset_item;
                     }
                 }
+            }
+        }
 
-                if handle_to_select.is_some() {
-                    ui.send_message(AssetItemMessage::select(
-                        handle_to_select,
-                        MessageDirection::ToWidget,
-                        true,
-                    ));
+        if handle_to_select.is_some() {
+            ui.send_message(AssetItemMessage::select(
+                handle_to_select,
+                MessageDirection::ToWidget,
+                true,
+            ));
 
-                    ui.send_message(ScrollViewerMessage::bring_into_view(
-                        self.scroll_panel,
-                        MessageDirection::ToWidget,
-                        handle_to_select,
-                    ));
+            ui.send_message(ScrollViewerMessage::bring_into_view(
+                self.scroll_panel,
+                MessageDirection::ToWidget,
+                handle_to_select,
+            ));
+        }
+    }
+
+    fn on_folder_action(
+        &mut self,
+        action: FolderAction,
+        folder: PathBuf,
+        engine: &mut GameEngine,
+        sender: Sender<Message>,
+    ) {
+        let ui = &mut engine.user_interface;
+        match action {
+            FolderAction::EditImportDefaults(kind) => {
+                ui.send_message(TextMessage::text(
+                    self.selected_properties,
+                    MessageDirection::ToWidget,
+                    format!("Import defaults of {:?}", folder),
+                ));
+
+                let location = ImportOptionsLocation::Folder(folder);
+                match kind {
+                    AssetKind::Texture => self.inspector.inspect_resource_import_options(
+                        TextureImportOptionsHandler::new(location),
+                        ui,
+                        sender,
+                    ),
+                    AssetKind::Model => self.inspector.inspect_resource_import_options(
+                        ModelImportOptionsHandler::new(location),
+                        ui,
+                        sender,
+                    ),
+                    AssetKind::Sound => self.inspector.inspect_resource_import_options(
+                        SoundBufferImportOptionsHandler::new(location),
+                        ui,
+                        sender,
+                    ),
+                    _ => (),
                 }
             }
+            FolderAction::Reimport => self.reimport_window.open(ui, folder),
+        }
+    }
+
+    /// Updates markers of own import options of every shown asset.
+    fn sync_import_options_markers(&self, ui: &UserInterface) {
+        for &item in self.items.iter() {
+            ui.send_message(AssetItemMessage::sync_import_options(
+                item,
+                MessageDirection::ToWidget,
+            ));
+        }
+    }
+
+    /// Returns a path of a folder, that is represented by the given widget - either a folder item
+    /// in the content panel or an item of the folder tree.
+    fn folder_at(&self, handle: Handle<UiNode>, ui: &UserInterface) -> Option<PathBuf> {
+        let node = ui.node(handle);
+        if let Some(item) = node.cast::<AssetItem>() {
+            if item.kind == AssetKind::Folder {
+                return Some(item.path.clone());
+            }
+        } else if node.cast::<Tree>().is_some() && ui.is_node_child_of(handle, self.folder_browser)
+        {
+            return node
+                .user_data_ref::<PathBuf>()
+                .filter(|path| path.is_dir())
+                .map(make_relative_path);
+        }
+        None
+    }
+
+    /// Returns paths of the assets that are being dragged. If a selected item is dragged, then
+    /// every selected item is dragged with it.
+    fn dragged_paths(&self, dropped: Handle<UiNode>, ui: &UserInterface) -> Vec<PathBuf> {
+        if let Some(dropped_item) = ui.node(dropped).cast::<AssetItem>() {
+            if dropped_item.is_selected() {
+                self.items
+                    .iter()
+                    .filter_map(|&item| ui.node(item).cast::<AssetItem>())
+                    .filter(|item| item.is_selected())
+                    .map(|item| item.path.clone())
+                    .collect()
+            } else {
+                vec![dropped_item.path.clone()]
+            }
+        } else {
+            // Folders could be dragged in the folder tree too.
+            // The root of the tree is the working directory and cannot be moved.
+            self.folder_at(dropped, ui)
+                .filter(|path| {
+                    !path.as_os_str().is_empty() && ui.node(dropped).cast::<Tree>().is_some()
+                })
+                .into_iter()
+                .collect()
+        }
+    }
+
+    fn show_error(&self, ui: &UserInterface, text: String) {
+        Log::err(text.clone());
+        ui.send_message(MessageBoxMessage::open(
+            self.error_message_box,
+            MessageDirection::ToWidget,
+            None,
+            Some(text),
+        ));
+    }
+
+    fn on_folder_created(
+        &mut self,
+        path: PathBuf,
+        ui: &mut UserInterface,
+        resource_manager: ResourceManager,
+    ) {
+        ui.send_message(FileBrowserMessage::add(
+            self.folder_browser,
+            MessageDirection::ToWidget,
+            absolute_path(&path),
+        ));
+
+        self.item_to_select = Some(make_relative_path(path));
+        self.fill_content(ui, resource_manager);
+    }
+
+    fn execute_plan(
+        &mut self,
+        plan: RelocationPlan,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
+        let ui = &mut engine.user_interface;
+
+        if let Err(e) = plan.execute() {
+            self.show_error(ui, e);
+            return;
+        }
+
+        plan.relocate_loaded_resources(&engine.resource_manager);
+
+        for m in plan.moves.iter() {
+            Log::info(format!(
+                "{} was moved to {}",
+                m.from.display(),
+                m.to.display()
+            ));
+
+            // Moved folders must be moved in the folder tree too.
+            ui.send_message(FileBrowserMessage::remove(
+                self.folder_browser,
+                MessageDirection::ToWidget,
+                absolute_path(&m.from),
+            ));
+            ui.send_message(FileBrowserMessage::add(
+                self.folder_browser,
+                MessageDirection::ToWidget,
+                absolute_path(&m.to),
+            ));
+        }
+
+        sender
+            .send(Message::AssetsMoved(plan.moves.clone()))
+            .unwrap();
+
+        // The folder that is currently shown could be moved as well.
+        if let Some(new_path) = plan.remap(&working_dir_relative(&self.current_path)) {
+            ui.send_message(FileBrowserMessage::path(
+                self.folder_browser,
+                MessageDirection::ToWidget,
+                new_path,
+            ));
+        } else {
+            self.fill_content(ui, engine.resource_manager.clone());
         }
     }
 
@@ -309,8 +662,12 @@ impl AssetBrowser {
         self.item_to_select = Some(path);
     }
 
-    pub fn update(&mut self, engine: &mut GameEngine) {
-        self.preview.update(engine)
+    pub fn update(&mut self, engine: &mut GameEngine, preview_settings: &PreviewSettings) {
+        self.preview.set_volume(engine, preview_settings.volume);
+        self.preview.update(engine);
+        self.absm_preview.update(&mut engine.user_interface);
+        self.reimport_window
+            .update(&engine.user_interface, &engine.resource_manager);
     }
 
     pub fn on_mode_changed(&mut self, ui: &UserInterface, mode: &Mode) {
@@ -321,3 +678,17 @@ impl AssetBrowser {
         ));
     }
 }
+
+fn absolute_path(path: &Path) -> PathBuf {
+    std::env::current_dir().unwrap_or_default().join(path)
+}
+
+/// Converts a path from the folder browser (`./data/foo` or `/path/to/project/data/foo`) to the
+/// form used by resources (`data/foo`) without touching the file system, the path may not exist.
+fn working_dir_relative(path: &Path) -> PathBuf {
+    let path = std::env::current_dir()
+        .ok()
+        .and_then(|dir| path.strip_prefix(dir).ok())
+        .unwrap_or(path);
+    path.strip_prefix(".").unwrap_or(path).to_owned()
+}
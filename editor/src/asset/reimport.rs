@@ -0,0 +1,402 @@
+//! Batch re-import of every asset in a folder with current import options.
+//!
+//! Assets are processed in background by the resource manager, at most [`MAX_IN_FLIGHT`] assets
+//! at a time, so the editor stays responsive. An asset that is already loaded is reloaded (the
+//! engine propagates changes of reloaded models to scenes, textures and sound buffers are changed
+//! in place), other assets are loaded with current import options. Cancellation stops dispatching
+//! of new assets, assets that are already processing are finished.
+
+use crate::asset::item::AssetKind;
+use fyrox::{
+    asset::{Resource, ResourceData, ResourceLoadError, ResourceState},
+    core::{make_relative_path, pool::Handle},
+    engine::resource_manager::ResourceManager,
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        progress_bar::{ProgressBarBuilder, ProgressBarMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+    resource::{model::Model, texture::Texture},
+    scene::sound::SoundBufferResource,
+    utils::log::Log,
+};
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+};
+
+/// Maximum amount of assets that are processed at the same time.
+pub const MAX_IN_FLIGHT: usize = 4;
+
+enum ReimportedResource {
+    Texture(Texture),
+    Model(Model),
+    SoundBuffer(SoundBufferResource),
+}
+
+/// Returns `None` while the resource is loading, `Some(true)` if it was loaded successfully.
+fn load_result<T, E>(resource: &Resource<T, E>) -> Option<bool>
+where
+    T: ResourceData,
+    E: ResourceLoadError,
+{
+    match *resource.state() {
+        ResourceState::Pending { .. } => None,
+        ResourceState::LoadError { .. } => Some(false),
+        ResourceState::Ok(_) => Some(true),
+    }
+}
+
+impl ReimportedResource {
+    fn load_result(&self) -> Option<bool> {
+        match self {
+            Self::Texture(texture) => load_result(texture),
+            Self::Model(model) => load_result(model),
+            Self::SoundBuffer(buffer) => load_result(buffer),
+        }
+    }
+}
+
+/// Reloads a resource if it is loaded already, otherwise loads it.
+fn reimport(path: &Path, resource_manager: &ResourceManager) -> Option<ReimportedResource> {
+    let mut state = resource_manager.state();
+    let containers = state.containers_mut();
+
+    macro_rules! reimport {
+        ($container:expr, $variant:ident) => {{
+            let container = &mut $container;
+            match container.find(path).cloned() {
+                Some(resource) => {
+                    container.reload_resource(resource.clone());
+                    Some(ReimportedResource::$variant(resource))
+                }
+                None => Some(ReimportedResource::$variant(container.request(path))),
+            }
+        }};
+    }
+
+    match AssetKind::from_path(path) {
+        AssetKind::Texture => reimport!(containers.textures, Texture),
+        AssetKind::Model => reimport!(containers.models, Model),
+        AssetKind::Sound => reimport!(containers.sound_buffers, SoundBuffer),
+        _ => None,
+    }
+}
+
+fn collect_assets(folder: &Path, assets: &mut Vec<PathBuf>) {
+    if let Ok(dir_iter) = std::fs::read_dir(folder) {
+        let mut paths = dir_iter
+            .flatten()
+            .map(|entry| make_relative_path(entry.path()))
+            .collect::<Vec<_>>();
+        // Keep the order stable, so the progress looks the same on every run.
+        paths.sort();
+
+        for path in paths {
+            if path.is_dir() {
+                collect_assets(&path, assets);
+            } else if AssetKind::from_path(&path).has_import_options() {
+                assets.push(path);
+            }
+        }
+    }
+}
+
+/// State of a batch re-import of every asset in a folder (including sub-folders).
+pub struct BatchReimport {
+    queue: VecDeque<PathBuf>,
+    in_flight: Vec<(PathBuf, ReimportedResource)>,
+    total: usize,
+    processed: usize,
+    failed: usize,
+    cancelled: bool,
+}
+
+impl BatchReimport {
+    pub fn new(folder: &Path) -> Self {
+        let mut assets = Vec::new();
+        collect_assets(folder, &mut assets);
+
+        Self {
+            total: assets.len(),
+            queue: assets.into(),
+            in_flight: Default::default(),
+            processed: 0,
+            failed: 0,
+            cancelled: false,
+        }
+    }
+
+    /// Collects finished assets and dispatches new ones. Returns `true` if the re-import is
+    /// finished.
+    pub fn update(&mut self, resource_manager: &ResourceManager) -> bool {
+        let (processed, failed) = (&mut self.processed, &mut self.failed);
+        self.in_flight
+            .retain(|(path, resource)| match resource.load_result() {
+                Some(loaded) => {
+                    *processed += 1;
+                    if !loaded {
+                        *failed += 1;
+                        Log::err(format!("Unable to re-import {}", path.display()));
+                    }
+                    false
+                }
+                None => true,
+            });
+
+        while !self.cancelled && self.in_flight.len() < MAX_IN_FLIGHT {
+            match self.queue.pop_front() {
+                Some(path) => match reimport(&path, resource_manager) {
+                    Some(resource) => self.in_flight.push((path, resource)),
+                    None => self.processed += 1,
+                },
+                None => break,
+            }
+        }
+
+        self.is_finished()
+    }
+
+    pub fn cancel(&mut self) {
+        self.cancelled = true;
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.in_flight.is_empty() && (self.cancelled || self.queue.is_empty())
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn processed(&self) -> usize {
+        self.processed
+    }
+
+    pub fn failed(&self) -> usize {
+        self.failed
+    }
+
+    pub fn progress(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.processed as f32 / self.total as f32
+        }
+    }
+}
+
+/// Shows progress of a batch re-import and allows to cancel it.
+pub struct ReimportWindow {
+    pub window: Handle<UiNode>,
+    progress_bar: Handle<UiNode>,
+    progress_text: Handle<UiNode>,
+    cancel: Handle<UiNode>,
+    close: Handle<UiNode>,
+    reimport: Option<BatchReimport>,
+    folder: PathBuf,
+}
+
+impl ReimportWindow {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let progress_bar;
+        let progress_text;
+        let cancel;
+        let close;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(400.0).with_height(110.0))
+            .with_title(WindowTitle::text("Re-import Assets"))
+            .can_minimize(false)
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            progress_text = TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx);
+                            progress_text
+                        })
+                        .with_child({
+                            progress_bar = ProgressBarBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .build(ctx);
+                            progress_bar
+                        })
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .with_child({
+                                        cancel = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(1.0))
+                                                .with_width(80.0),
+                                        )
+                                        .with_text("Cancel")
+                                        .build(ctx);
+                                        cancel
+                                    })
+                                    .with_child({
+                                        close = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(1.0))
+                                                .with_width(80.0),
+                                        )
+                                        .with_text("Close")
+                                        .build(ctx);
+                                        close
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_column(Column::stretch())
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            progress_bar,
+            progress_text,
+            cancel,
+            close,
+            reimport: None,
+            folder: Default::default(),
+        }
+    }
+
+    /// Starts re-import of every asset in the folder, does nothing if a re-import is running.
+    pub fn open(&mut self, ui: &UserInterface, folder: PathBuf) {
+        if self.reimport.as_ref().map_or(false, |r| !r.is_finished()) {
+            return;
+        }
+
+        Log::info(format!("Re-importing assets in {}...", folder.display()));
+
+        self.reimport = Some(BatchReimport::new(&folder));
+        self.folder = folder;
+
+        ui.send_message(WindowMessage::open_modal(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+        self.sync_to_model(ui);
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, ui: &UserInterface) {
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.cancel {
+                if let Some(reimport) = self.reimport.as_mut() {
+                    reimport.cancel();
+                }
+                self.sync_to_model(ui);
+            } else if message.destination() == self.close {
+                ui.send_message(WindowMessage::close(
+                    self.window,
+                    MessageDirection::ToWidget,
+                ));
+                self.reimport = None;
+            }
+        }
+    }
+
+    /// Advances the re-import, returns `true` when the re-import was finished on this update.
+    pub fn update(&mut self, ui: &UserInterface, resource_manager: &ResourceManager) -> bool {
+        let finished = match self.reimport.as_mut() {
+            Some(reimport) if !reimport.is_finished() => reimport.update(resource_manager),
+            _ => return false,
+        };
+
+        if finished {
+            if let Some(reimport) = self.reimport.as_ref() {
+                Log::info(format!(
+                    "Re-import of {} is finished: {} of {} assets processed, {} failed.",
+                    self.folder.display(),
+                    reimport.processed(),
+                    reimport.total(),
+                    reimport.failed()
+                ));
+            }
+        }
+
+        self.sync_to_model(ui);
+
+        finished
+    }
+
+    fn sync_to_model(&self, ui: &UserInterface) {
+        let reimport = match self.reimport.as_ref() {
+            Some(reimport) => reimport,
+            None => return,
+        };
+
+        let finished = reimport.is_finished();
+
+        ui.send_message(ProgressBarMessage::progress(
+            self.progress_bar,
+            MessageDirection::ToWidget,
+            reimport.progress(),
+        ));
+        ui.send_message(WidgetMessage::enabled(
+            self.cancel,
+            MessageDirection::ToWidget,
+            !finished && !reimport.is_cancelled(),
+        ));
+        ui.send_message(WidgetMessage::enabled(
+            self.close,
+            MessageDirection::ToWidget,
+            finished,
+        ));
+
+        let status = if finished && reimport.is_cancelled() {
+            "Cancelled"
+        } else if finished {
+            "Done"
+        } else if reimport.is_cancelled() {
+            "Cancelling..."
+        } else {
+            "Re-importing..."
+        };
+        let mut text = format!(
+            "{} {} of {} assets in {}",
+            status,
+            reimport.processed(),
+            reimport.total(),
+            self.folder.display()
+        );
+        if reimport.failed() > 0 {
+            text += &format!(", {} failed (see log)", reimport.failed());
+        }
+
+        ui.send_message(TextMessage::text(
+            self.progress_text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+}
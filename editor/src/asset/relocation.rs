@@ -0,0 +1,341 @@
+//! Moving of assets and folders with fix-ups of references to them.
+//!
+//! A move is done in three steps:
+//!
+//! 1. Planning - [`RelocationPlan::new`] validates requested moves, collects sidecar files of the
+//! assets (import options, scene lock files) and finds every project file in the native format
+//! (scenes, prefabs, animation blending state machines, curves, etc.) that references moved
+//! assets. This information is shown to the user before anything is touched.
+//! 2. Execution - [`RelocationPlan::execute`] rewrites references in affected files and then moves
+//! the files. Every step is recorded in a journal, if something fails every recorded step is
+//! reverted in reverse order, so the project is never left in a half-moved state.
+//! 3. In-memory fix-up - paths of already loaded resources are changed in the resource manager,
+//! so the editor continues to work with moved resources without re-loading them.
+//!
+//! Asset previews are generated in memory, so there is nothing to move for them - they will be
+//! re-created for new paths by the asset browser.
+
+use fyrox::{
+    core::{replace_slashes, visitor::Visitor},
+    engine::resource_manager::ResourceManager,
+    utils::log::Log,
+};
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+/// Extensions of files that must be moved together with an asset, `foo.png` -> `foo.png.options`.
+pub const SIDECAR_EXTENSIONS: [&str; 3] = ["options", "lock", "editor"];
+
+/// Magic of the native file format, see [`Visitor`].
+const NATIVE_FORMAT_MAGIC: &[u8; 4] = b"RG3D";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Move {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// A file that references moved assets.
+pub struct AffectedFile {
+    pub path: PathBuf,
+    /// Amount of references that will be fixed.
+    pub references: usize,
+    new_content: Vec<u8>,
+}
+
+#[derive(Debug)]
+enum JournalEntry {
+    Rewritten { path: PathBuf, original: Vec<u8> },
+    Moved(Move),
+}
+
+/// A list of performed file system operations, that could be reverted.
+#[derive(Default, Debug)]
+struct Journal {
+    entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    fn rewrite(&mut self, path: &Path, content: &[u8]) -> Result<(), String> {
+        let original = std::fs::read(path)
+            .map_err(|e| format!("Unable to read {}. Reason: {}", path.display(), e))?;
+        std::fs::write(path, content)
+            .map_err(|e| format!("Unable to write {}. Reason: {}", path.display(), e))?;
+        self.entries.push(JournalEntry::Rewritten {
+            path: path.to_owned(),
+            original,
+        });
+        Ok(())
+    }
+
+    fn rename(&mut self, m: &Move) -> Result<(), String> {
+        std::fs::rename(&m.from, &m.to).map_err(|e| {
+            format!(
+                "Unable to move {} to {}. Reason: {}",
+                m.from.display(),
+                m.to.display(),
+                e
+            )
+        })?;
+        self.entries.push(JournalEntry::Moved(m.clone()));
+        Ok(())
+    }
+
+    /// Reverts every recorded operation in reverse order. Returns a list of errors, the
+    /// rollback does not stop on errors, it tries to revert as much as possible.
+    fn rollback(self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for entry in self.entries.into_iter().rev() {
+            match entry {
+                JournalEntry::Rewritten { path, original } => {
+                    if let Err(e) = std::fs::write(&path, original) {
+                        errors.push(format!(
+                            "Unable to restore {}. Reason: {}",
+                            path.display(),
+                            e
+                        ));
+                    }
+                }
+                JournalEntry::Moved(m) => {
+                    if let Err(e) = std::fs::rename(&m.to, &m.from) {
+                        errors.push(format!(
+                            "Unable to move {} back to {}. Reason: {}",
+                            m.to.display(),
+                            m.from.display(),
+                            e
+                        ));
+                    }
+                }
+            }
+        }
+        errors
+    }
+}
+
+/// Validated set of moves together with every file that must be changed to perform them.
+pub struct RelocationPlan {
+    /// Assets and folders selected by the user.
+    pub moves: Vec<Move>,
+    /// Sidecar files that are moved together with the assets.
+    pub sidecars: Vec<Move>,
+    /// Files with references to the moved assets.
+    pub affected: Vec<AffectedFile>,
+}
+
+impl RelocationPlan {
+    /// Prepares a move of the `sources` (files or folders) into the `destination` folder. Every
+    /// native file in the working directory is checked for references to the moved assets.
+    /// Sources, that are already in the destination folder, are ignored. Returns an error if the
+    /// move is impossible.
+    pub fn new(sources: &[PathBuf], destination: &Path) -> Result<Self, String> {
+        // Empty path is the working directory.
+        if !destination.as_os_str().is_empty() && !destination.is_dir() {
+            return Err(format!("{} is not a folder!", destination.display()));
+        }
+
+        let mut moves: Vec<Move> = Vec::new();
+        for source in sources {
+            // Children of moved folders are moved together with their parents.
+            if sources
+                .iter()
+                .any(|other| other != source && source.starts_with(other))
+            {
+                continue;
+            }
+
+            if destination.starts_with(source) {
+                return Err(format!("Unable to move {} into itself!", source.display()));
+            }
+
+            if source.parent() == Some(destination) {
+                continue;
+            }
+
+            let file_name = source
+                .file_name()
+                .ok_or_else(|| format!("{} is not a valid path!", source.display()))?;
+            let to = destination.join(file_name);
+            if to.exists() || moves.iter().any(|m| m.to == to) {
+                return Err(format!("{} already exists!", to.display()));
+            }
+
+            moves.push(Move {
+                from: source.clone(),
+                to,
+            });
+        }
+
+        let mut sidecars = Vec::new();
+        for m in moves.iter().filter(|m| m.from.is_file()) {
+            for ext in SIDECAR_EXTENSIONS {
+                let from = append_to_file_name(&m.from, ext);
+                if from.is_file() {
+                    let to = append_to_file_name(&m.to, ext);
+                    if to.exists() {
+                        return Err(format!("{} already exists!", to.display()));
+                    }
+                    sidecars.push(Move { from, to });
+                }
+            }
+        }
+
+        let mut plan = Self {
+            moves,
+            sidecars,
+            affected: Default::default(),
+        };
+
+        if !plan.moves.is_empty() {
+            let mut native_files = Vec::new();
+            collect_native_files(Path::new("."), &mut native_files);
+            for path in native_files {
+                if let Some(affected) = plan.fix_references(&path) {
+                    plan.affected.push(affected);
+                }
+            }
+        }
+
+        Ok(plan)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Returns a new path of an asset, or `None` if the asset is not moved.
+    pub fn remap(&self, path: &Path) -> Option<PathBuf> {
+        remap_path(&self.moves, path)
+    }
+
+    fn fix_references(&self, path: &Path) -> Option<AffectedFile> {
+        let mut visitor = Visitor::load_from_memory(std::fs::read(path).ok()?).ok()?;
+
+        let references = visitor.replace_strings(|string| {
+            let referenced = Path::new(string);
+            // Only existing files could be referenced, this prevents accidental replacement of
+            // names that look like paths (for example a node named as a moved folder).
+            if referenced.is_file() {
+                self.remap(referenced)
+                    .map(|new_path| replace_slashes(new_path).to_string_lossy().into_owned())
+            } else {
+                None
+            }
+        });
+
+        if references == 0 {
+            return None;
+        }
+
+        match visitor.save_binary_to_vec() {
+            Ok(new_content) => Some(AffectedFile {
+                path: path.to_owned(),
+                references,
+                new_content,
+            }),
+            Err(e) => {
+                Log::err(format!(
+                    "Unable to fix references in {}. Reason: {:?}",
+                    path.display(),
+                    e
+                ));
+                None
+            }
+        }
+    }
+
+    /// Performs the move. References are fixed first (in place), then the files are moved. If
+    /// any step fails, every previous step is reverted and an error is returned.
+    pub fn execute(&self) -> Result<(), String> {
+        let mut journal = Journal::default();
+
+        let result = self
+            .affected
+            .iter()
+            .try_for_each(|affected| journal.rewrite(&affected.path, &affected.new_content))
+            .and_then(|_| {
+                self.sidecars
+                    .iter()
+                    .chain(self.moves.iter())
+                    .try_for_each(|m| journal.rename(m))
+            });
+
+        if let Err(error) = result {
+            let rollback_errors = journal.rollback();
+            if rollback_errors.is_empty() {
+                Err(format!("{} All changes were reverted.", error))
+            } else {
+                Err(format!(
+                    "{} Some changes cannot be reverted:\n{}",
+                    error,
+                    rollback_errors.join("\n")
+                ))
+            }
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Changes paths of loaded resources, it must be called after successful [`Self::execute`].
+    pub fn relocate_loaded_resources(&self, resource_manager: &ResourceManager) {
+        let mut state = resource_manager.state();
+        for m in self.moves.iter() {
+            state.relocate_resources(&m.from, &m.to);
+        }
+    }
+}
+
+/// Returns a new path of an asset after the given moves, or `None` if the asset is not moved.
+/// Assets inside moved folders are moved too.
+pub fn remap_path(moves: &[Move], path: &Path) -> Option<PathBuf> {
+    moves.iter().find_map(|m| {
+        path.strip_prefix(&m.from).ok().map(|rest| {
+            if rest.as_os_str().is_empty() {
+                m.to.clone()
+            } else {
+                m.to.join(rest)
+            }
+        })
+    })
+}
+
+fn append_to_file_name(path: &Path, ext: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(ext);
+    path.into()
+}
+
+fn is_native_file(path: &Path) -> bool {
+    let mut magic = [0; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .map_or(false, |_| &magic == NATIVE_FORMAT_MAGIC)
+}
+
+fn collect_native_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    if let Ok(dir_iter) = std::fs::read_dir(dir) {
+        for entry in dir_iter.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            // Hidden folders (.git, etc.) and build artifacts never contain project assets.
+            if name.starts_with('.') || name == "target" {
+                continue;
+            }
+
+            if path.is_dir() {
+                collect_native_files(&path, files);
+            } else if is_native_file(&path) {
+                // Keep paths in the same form as paths of resources - `data/foo.rgs`, not
+                // `./data/foo.rgs`.
+                files.push(
+                    path.strip_prefix(".")
+                        .map_or(path.clone(), |p| p.to_owned()),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,574 @@
+//! Batch renaming of selected scene nodes.
+//!
+//! New names are produced from a pattern, that could contain following placeholders:
+//!
+//! - `{name}` - original name of a node, after find/replace.
+//! - `{n}` - counter, it starts from a configurable value and increases for every node in the
+//! order of the world viewer.
+//! - `{n:3}` - counter padded with spaces to the given width, `{n:03}` - counter padded with
+//! zeros.
+//!
+//! Unknown placeholders are left as is. Case transform is applied to the whole resulting name.
+
+use crate::{
+    gui::make_dropdown_list_option,
+    scene::{
+        commands::{graph::SetNameCommand, CommandGroup, SceneCommand},
+        EditorScene, Selection,
+    },
+    GameEngine, Message,
+};
+use fyrox::{
+    core::{color::Color, pool::Handle, scope_profile},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        button::{ButtonBuilder, ButtonMessage},
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
+        formatted_text::WrapMode,
+        grid::{Column, GridBuilder, Row},
+        list_view::{ListViewBuilder, ListViewMessage},
+        message::{MessageDirection, UiMessage},
+        numeric::{NumericUpDownBuilder, NumericUpDownMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        text_box::{TextBoxBuilder, TextBoxMessage, TextCommitMode},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Orientation, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    scene::{graph::Graph, node::Node},
+};
+use std::{collections::HashMap, sync::mpsc::Sender};
+
+/// Case transform that is applied to the resulting names.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CaseTransform {
+    /// Case is not changed.
+    Keep,
+    /// `all lower case`
+    Lower,
+    /// `ALL UPPER CASE`
+    Upper,
+    /// `First Letter Of Every Word Is Upper Case`
+    Title,
+}
+
+impl CaseTransform {
+    const ALL: [CaseTransform; 4] = [
+        CaseTransform::Keep,
+        CaseTransform::Lower,
+        CaseTransform::Upper,
+        CaseTransform::Title,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            CaseTransform::Keep => "Keep",
+            CaseTransform::Lower => "lower case",
+            CaseTransform::Upper => "UPPER CASE",
+            CaseTransform::Title => "Title Case",
+        }
+    }
+
+    fn apply(self, name: &str) -> String {
+        match self {
+            CaseTransform::Keep => name.to_owned(),
+            CaseTransform::Lower => name.to_lowercase(),
+            CaseTransform::Upper => name.to_uppercase(),
+            CaseTransform::Title => {
+                let mut result = String::with_capacity(name.len());
+                let mut word_start = true;
+                for c in name.chars() {
+                    if word_start {
+                        result.extend(c.to_uppercase());
+                    } else {
+                        result.extend(c.to_lowercase());
+                    }
+                    word_start = !c.is_alphanumeric();
+                }
+                result
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BatchRenameOptions {
+    pub pattern: String,
+    pub counter_start: u32,
+    pub find: String,
+    pub replace: String,
+    pub case: CaseTransform,
+}
+
+impl Default for BatchRenameOptions {
+    fn default() -> Self {
+        Self {
+            pattern: "{name}".to_owned(),
+            counter_start: 1,
+            find: Default::default(),
+            replace: Default::default(),
+            case: CaseTransform::Keep,
+        }
+    }
+}
+
+impl BatchRenameOptions {
+    /// Makes new name of a node with given original name and index in the list of renamed nodes.
+    pub fn make_name(&self, original_name: &str, index: usize) -> String {
+        let name = if self.find.is_empty() {
+            original_name.to_owned()
+        } else {
+            original_name.replace(&self.find, &self.replace)
+        };
+
+        let counter = self.counter_start as usize + index;
+
+        let mut result = String::new();
+        let mut rest = self.pattern.as_str();
+        while let Some(begin) = rest.find('{') {
+            result.push_str(&rest[..begin]);
+            rest = &rest[begin..];
+            match rest.find('}') {
+                Some(end) => {
+                    match expand_placeholder(&rest[1..end], &name, counter) {
+                        Some(value) => result.push_str(&value),
+                        None => result.push_str(&rest[..=end]),
+                    }
+                    rest = &rest[(end + 1)..];
+                }
+                None => break,
+            }
+        }
+        result.push_str(rest);
+
+        self.case.apply(&result)
+    }
+}
+
+fn expand_placeholder(placeholder: &str, name: &str, counter: usize) -> Option<String> {
+    if placeholder == "name" {
+        return Some(name.to_owned());
+    }
+
+    let format = placeholder.strip_prefix('n')?;
+    if format.is_empty() {
+        return Some(counter.to_string());
+    }
+
+    let width = format.strip_prefix(':')?;
+    let zero_padded = width.starts_with('0');
+    let width = width.parse::<usize>().ok()?;
+    Some(if zero_padded {
+        format!("{:0width$}", counter, width = width)
+    } else {
+        format!("{:width$}", counter, width = width)
+    })
+}
+
+/// Collects selected nodes in the order of the world viewer (depth-first, children in the order
+/// of their parent).
+fn collect_in_graph_order(
+    graph: &Graph,
+    node: Handle<Node>,
+    selected: &[Handle<Node>],
+    result: &mut Vec<Handle<Node>>,
+) {
+    if selected.contains(&node) {
+        result.push(node);
+    }
+    for &child in graph[node].children() {
+        collect_in_graph_order(graph, child, selected, result);
+    }
+}
+
+struct RenameEntry {
+    node: Handle<Node>,
+    original_name: String,
+    new_name: String,
+}
+
+fn make_label(ctx: &mut BuildContext, row: usize, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(0)
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+fn make_text_box(ctx: &mut BuildContext, row: usize, text: &str) -> Handle<UiNode> {
+    TextBoxBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(1)
+            .with_margin(Thickness::uniform(1.0)),
+    )
+    .with_text(text)
+    .with_text_commit_mode(TextCommitMode::Immediate)
+    .with_vertical_text_alignment(VerticalAlignment::Center)
+    .build(ctx)
+}
+
+pub struct BatchRenameWindow {
+    pub window: Handle<UiNode>,
+    pattern: Handle<UiNode>,
+    counter_start: Handle<UiNode>,
+    find: Handle<UiNode>,
+    replace: Handle<UiNode>,
+    case: Handle<UiNode>,
+    preview: Handle<UiNode>,
+    collisions: Handle<UiNode>,
+    rename: Handle<UiNode>,
+    cancel: Handle<UiNode>,
+    options: BatchRenameOptions,
+    entries: Vec<RenameEntry>,
+}
+
+impl BatchRenameWindow {
+    pub fn new(engine: &mut GameEngine) -> Self {
+        let pattern;
+        let counter_start;
+        let find;
+        let replace;
+        let case;
+        let preview;
+        let collisions;
+        let rename;
+        let cancel;
+        let ctx = &mut engine.user_interface.build_ctx();
+        let options = BatchRenameOptions::default();
+
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(400.0).with_height(450.0))
+            .with_title(WindowTitle::text("Batch Rename"))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            GridBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_child(make_label(ctx, 0, "Pattern"))
+                                    .with_child({
+                                        pattern = make_text_box(ctx, 0, &options.pattern);
+                                        pattern
+                                    })
+                                    .with_child(
+                                        TextBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_row(1)
+                                                .on_column(1)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("{name} - original name, {n} - counter, {n:03} - padded counter")
+                                        .with_wrap(WrapMode::Word)
+                                        .build(ctx),
+                                    )
+                                    .with_child(make_label(ctx, 2, "Counter Start"))
+                                    .with_child({
+                                        counter_start = NumericUpDownBuilder::<u32>::new(
+                                            WidgetBuilder::new()
+                                                .on_row(2)
+                                                .on_column(1)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_value(options.counter_start)
+                                        .with_precision(0)
+                                        .build(ctx);
+                                        counter_start
+                                    })
+                                    .with_child(make_label(ctx, 3, "Find"))
+                                    .with_child({
+                                        find = make_text_box(ctx, 3, &options.find);
+                                        find
+                                    })
+                                    .with_child(make_label(ctx, 4, "Replace With"))
+                                    .with_child({
+                                        replace = make_text_box(ctx, 4, &options.replace);
+                                        replace
+                                    })
+                                    .with_child(make_label(ctx, 5, "Case"))
+                                    .with_child({
+                                        case = DropdownListBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_row(5)
+                                                .on_column(1)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_items(
+                                            CaseTransform::ALL
+                                                .iter()
+                                                .map(|case| {
+                                                    make_dropdown_list_option(ctx, case.name())
+                                                })
+                                                .collect(),
+                                        )
+                                        .with_selected(0)
+                                        .build(ctx);
+                                        case
+                                    }),
+                            )
+                            .add_column(Column::strict(100.0))
+                            .add_column(Column::stretch())
+                            .add_row(Row::strict(25.0))
+                            .add_row(Row::strict(36.0))
+                            .add_row(Row::strict(25.0))
+                            .add_row(Row::strict(25.0))
+                            .add_row(Row::strict(25.0))
+                            .add_row(Row::strict(25.0))
+                            .build(ctx),
+                        )
+                        .with_child({
+                            preview = ListViewBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .build(ctx);
+                            preview
+                        })
+                        .with_child(
+                            GridBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .with_child({
+                                        collisions = TextBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_column(0)
+                                                .with_margin(Thickness::uniform(1.0))
+                                                .with_foreground(Brush::Solid(Color::RED))
+                                                .with_vertical_alignment(VerticalAlignment::Center),
+                                        )
+                                        .build(ctx);
+                                        collisions
+                                    })
+                                    .with_child(
+                                        StackPanelBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_column(1)
+                                                .with_child({
+                                                    rename = ButtonBuilder::new(
+                                                        WidgetBuilder::new()
+                                                            .with_margin(Thickness::uniform(1.0))
+                                                            .with_width(80.0),
+                                                    )
+                                                    .with_text("Rename")
+                                                    .build(ctx);
+                                                    rename
+                                                })
+                                                .with_child({
+                                                    cancel = ButtonBuilder::new(
+                                                        WidgetBuilder::new()
+                                                            .with_margin(Thickness::uniform(1.0))
+                                                            .with_width(80.0),
+                                                    )
+                                                    .with_text("Cancel")
+                                                    .build(ctx);
+                                                    cancel
+                                                }),
+                                        )
+                                        .with_orientation(Orientation::Horizontal)
+                                        .build(ctx),
+                                    ),
+                            )
+                            .add_column(Column::stretch())
+                            .add_column(Column::auto())
+                            .add_row(Row::stretch())
+                            .build(ctx),
+                        ),
+                )
+                .add_column(Column::stretch())
+                .add_row(Row::auto())
+                .add_row(Row::stretch())
+                .add_row(Row::strict(25.0))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            pattern,
+            counter_start,
+            find,
+            replace,
+            case,
+            preview,
+            collisions,
+            rename,
+            cancel,
+            options,
+            entries: Default::default(),
+        }
+    }
+
+    /// Opens the window for the nodes that are currently selected.
+    pub fn open(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        self.entries.clear();
+
+        if let Selection::Graph(selection) = &editor_scene.selection {
+            let graph = &engine.scenes[editor_scene.scene].graph;
+
+            let mut nodes = Vec::new();
+            collect_in_graph_order(graph, graph.get_root(), selection.nodes(), &mut nodes);
+
+            self.entries = nodes
+                .into_iter()
+                .map(|node| RenameEntry {
+                    node,
+                    original_name: graph[node].name_owned(),
+                    new_name: Default::default(),
+                })
+                .collect();
+        }
+
+        let ui = &mut engine.user_interface;
+        self.update_preview(ui);
+
+        ui.send_message(WindowMessage::open_modal(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    fn close(&mut self, ui: &UserInterface) {
+        self.entries.clear();
+
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    fn update_preview(&mut self, ui: &mut UserInterface) {
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            entry.new_name = self.options.make_name(&entry.original_name, index);
+        }
+
+        let mut name_counts = HashMap::new();
+        for entry in self.entries.iter() {
+            *name_counts.entry(entry.new_name.as_str()).or_insert(0) += 1;
+        }
+
+        let mut collision_count = 0;
+        let ctx = &mut ui.build_ctx();
+        let items = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let is_collision = name_counts[entry.new_name.as_str()] > 1;
+                if is_collision {
+                    collision_count += 1;
+                }
+
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_background(Brush::Solid(if index % 2 == 0 {
+                            Color::opaque(70, 70, 70)
+                        } else {
+                            Color::opaque(40, 40, 40)
+                        }))
+                        .with_child(
+                            TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_margin(Thickness::uniform(1.0))
+                                    .with_foreground(Brush::Solid(if is_collision {
+                                        Color::RED
+                                    } else {
+                                        Color::opaque(210, 210, 210)
+                                    })),
+                            )
+                            .with_text(format!("{} -> {}", entry.original_name, entry.new_name))
+                            .build(ctx),
+                        ),
+                )
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        ui.send_message(ListViewMessage::items(
+            self.preview,
+            MessageDirection::ToWidget,
+            items,
+        ));
+
+        ui.send_message(TextMessage::text(
+            self.collisions,
+            MessageDirection::ToWidget,
+            if collision_count > 0 {
+                format!("{} nodes have the same names!", collision_count)
+            } else {
+                Default::default()
+            },
+        ));
+    }
+
+    fn make_command(&self) -> Option<SceneCommand> {
+        let commands = self
+            .entries
+            .iter()
+            .filter(|entry| entry.new_name != entry.original_name)
+            .map(|entry| SceneCommand::new(SetNameCommand::new(entry.node, entry.new_name.clone())))
+            .collect::<Vec<_>>();
+
+        if commands.is_empty() {
+            None
+        } else {
+            Some(SceneCommand::new(CommandGroup::from(commands)))
+        }
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
+        scope_profile!();
+
+        let ui = &mut engine.user_interface;
+
+        if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.rename {
+                if let Some(command) = self.make_command() {
+                    sender.send(Message::DoSceneCommand(command)).unwrap();
+                }
+                self.close(ui);
+            } else if message.destination() == self.cancel {
+                self.close(ui);
+            }
+        } else if message.direction() == MessageDirection::FromWidget {
+            if let Some(TextBoxMessage::Text(text)) = message.data::<TextBoxMessage>() {
+                if message.destination() == self.pattern {
+                    self.options.pattern = text.clone();
+                } else if message.destination() == self.find {
+                    self.options.find = text.clone();
+                } else if message.destination() == self.replace {
+                    self.options.replace = text.clone();
+                } else {
+                    return;
+                }
+                self.update_preview(ui);
+            } else if let Some(&NumericUpDownMessage::Value(value)) =
+                message.data::<NumericUpDownMessage<u32>>()
+            {
+                if message.destination() == self.counter_start {
+                    self.options.counter_start = value;
+                    self.update_preview(ui);
+                }
+            } else if let Some(&DropdownListMessage::SelectionChanged(Some(index))) =
+                message.data::<DropdownListMessage>()
+            {
+                if message.destination() == self.case {
+                    self.options.case = CaseTransform::ALL[index];
+                    self.update_preview(ui);
+                }
+            }
+        }
+    }
+}
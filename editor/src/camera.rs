@@ -21,6 +21,7 @@ use fyrox::{
         transform::TransformBuilder,
     },
 };
+use serde::{Deserialize, Serialize};
 use std::{
     collections::hash_map::DefaultHasher,
     hash::{Hash, Hasher},
@@ -28,9 +29,79 @@ use std::{
 
 const DEFAULT_Z_OFFSET: f32 = -3.0;
 
+/// Distance from the origin along the view axis of the camera of top and side views. It should be
+/// large enough to fit most of scenes in front of the camera.
+const ORTHO_VIEW_DISTANCE: f32 = 100.0;
+
+/// View of the editor camera. Every view except perspective one uses orthographic projection and
+/// looks along one of world axes, rotation of such views cannot be changed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum CameraView {
+    Perspective,
+    /// Looks along +Z axis, this is the view for 2D scenes.
+    Front,
+    /// Looks along -Y axis.
+    Top,
+    /// Looks along -X axis.
+    Side,
+}
+
+impl Default for CameraView {
+    fn default() -> Self {
+        Self::Perspective
+    }
+}
+
+impl CameraView {
+    pub const ALL: [CameraView; 4] = [
+        CameraView::Perspective,
+        CameraView::Front,
+        CameraView::Top,
+        CameraView::Side,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CameraView::Perspective => "Perspective (3D)",
+            CameraView::Front => "Front (2D)",
+            CameraView::Top => "Top",
+            CameraView::Side => "Side",
+        }
+    }
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|view| *view == self).unwrap()
+    }
+
+    fn projection(self) -> Projection {
+        match self {
+            CameraView::Perspective => Projection::Perspective(Default::default()),
+            _ => Projection::Orthographic(Default::default()),
+        }
+    }
+
+    // Rotation of the pivot of orthographic views and the distance of the camera from the origin.
+    fn ortho_placement(self) -> (UnitQuaternion<f32>, f32) {
+        match self {
+            CameraView::Perspective | CameraView::Front => {
+                (UnitQuaternion::identity(), -DEFAULT_Z_OFFSET)
+            }
+            CameraView::Top => (
+                UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 90.0f32.to_radians()),
+                ORTHO_VIEW_DISTANCE,
+            ),
+            CameraView::Side => (
+                UnitQuaternion::from_axis_angle(&Vector3::y_axis(), -90.0f32.to_radians()),
+                ORTHO_VIEW_DISTANCE,
+            ),
+        }
+    }
+}
+
 pub struct CameraController {
     pub pivot: Handle<Node>,
     pub camera: Handle<Node>,
+    view: CameraView,
     yaw: f32,
     pitch: f32,
     rotate: bool,
@@ -49,6 +120,14 @@ pub struct CameraController {
     scene_context: PickContext,
 }
 
+/// Position and orientation of the camera, it is used to keep the view when a scene is reloaded.
+#[derive(Clone, Debug)]
+pub struct CameraState {
+    pub position: Vector3<f32>,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
 #[derive(Clone)]
 pub struct CameraPickResult {
     pub position: Vector3<f32>,
@@ -78,14 +157,22 @@ where
 }
 
 impl CameraController {
-    pub fn new(graph: &mut Graph, root: Handle<Node>) -> Self {
+    /// Creates new camera. Only one camera of a scene should have a listener, because the last
+    /// listener of a scene takes priority.
+    pub fn new(graph: &mut Graph, root: Handle<Node>, with_listener: bool) -> Self {
+        let listener = if with_listener {
+            vec![ListenerBuilder::new(BaseBuilder::new()).build(graph)]
+        } else {
+            Vec::new()
+        };
+
         let camera;
         let pivot = PivotBuilder::new(
             BaseBuilder::new()
                 .with_children(&[{
                     camera = CameraBuilder::new(
                         BaseBuilder::new()
-                            .with_children(&[ListenerBuilder::new(BaseBuilder::new()).build(graph)])
+                            .with_children(&listener)
                             .with_name("EditorCamera"),
                     )
                     .with_exposure(Exposure::Manual(std::f32::consts::E))
@@ -108,6 +195,7 @@ impl CameraController {
         Self {
             pivot,
             camera,
+            view: CameraView::Perspective,
             yaw: 0.0,
             pitch: 0.0,
             rotate: false,
@@ -127,10 +215,52 @@ impl CameraController {
         }
     }
 
-    pub fn set_projection(&self, graph: &mut Graph, projection: Projection) {
+    pub fn state(&self, graph: &Graph) -> CameraState {
+        CameraState {
+            position: **graph[self.pivot].local_transform().position(),
+            yaw: self.yaw,
+            pitch: self.pitch,
+        }
+    }
+
+    pub fn set_state(&mut self, graph: &mut Graph, state: &CameraState) {
+        self.yaw = state.yaw;
+        self.pitch = state.pitch;
+        graph[self.pivot]
+            .local_transform_mut()
+            .set_position(state.position);
+    }
+
+    pub fn view(&self) -> CameraView {
+        self.view
+    }
+
+    /// Sets new view of the camera, returns `false` if the camera already has the view. Every change
+    /// of the view resets the projection to defaults.
+    pub fn set_view(&mut self, graph: &mut Graph, view: CameraView) -> bool {
+        if self.view == view {
+            return false;
+        }
+
+        self.view = view;
         graph[self.camera]
             .as_camera_mut()
-            .set_projection(projection)
+            .set_projection(view.projection());
+        true
+    }
+
+    /// Releases every pressed button and key, it is used when the camera stops receiving input
+    /// in the middle of an action.
+    pub fn reset_input(&mut self) {
+        self.rotate = false;
+        self.drag = false;
+        self.move_left = false;
+        self.move_right = false;
+        self.move_forward = false;
+        self.move_backward = false;
+        self.move_up = false;
+        self.move_down = false;
+        self.speed_factor = 1.0;
     }
 
     pub fn on_mouse_move(&mut self, delta: Vector2<f32>) {
@@ -351,17 +481,23 @@ impl CameraController {
 
                 camera
                     .local_transform_mut()
-                    .set_rotation(UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 0.0));
+                    .set_rotation(UnitQuaternion::identity());
+
+                let (rotation, distance) = self.view.ortho_placement();
+                let side = rotation * Vector3::x();
+                let up = rotation * Vector3::y();
+                let look = rotation * Vector3::z();
 
                 let local_transform = graph[self.pivot].local_transform_mut();
 
+                // The camera is kept at fixed distance from the origin along its view axis, it can
+                // move only in the plane of the view.
                 let mut new_position = **local_transform.position();
-                new_position.z = DEFAULT_Z_OFFSET;
-                new_position.x += move_vec.x;
-                new_position.y += move_vec.y;
+                new_position -= look.scale(look.dot(&new_position) + distance);
+                new_position += side.scale(move_vec.x) + up.scale(move_vec.y);
 
                 local_transform
-                    .set_rotation(UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.0))
+                    .set_rotation(rotation)
                     .set_position(new_position);
             }
         }
@@ -422,7 +558,7 @@ impl CameraController {
                     // Do coarse, but fast, intersection test with bounding box first.
                     if let Some(points) = object_space_ray.aabb_intersection_points(&aabb) {
                         if has_hull(node) {
-                            if let Some((closest_distance, position)) =
+                            if let Some((closest_distance, position, _)) =
                                 precise_ray_test(node, &ray, ignore_back_faces)
                             {
                                 context.pick_list.push(CameraPickResult {
@@ -526,11 +662,14 @@ fn has_hull(node: &Node) -> bool {
     node.query_component_ref::<Mesh>().is_some()
 }
 
-fn precise_ray_test(
+/// Finds the closest intersection of the ray with the geometry of a mesh. Returns squared distance
+/// to the intersection, its position and normal of the intersected triangle (facing the origin of
+/// the ray), all in world space.
+pub fn precise_ray_test(
     node: &Node,
     ray: &Ray,
     ignore_back_faces: bool,
-) -> Option<(f32, Vector3<f32>)> {
+) -> Option<(f32, Vector3<f32>, Vector3<f32>)> {
     let mut closest_distance = f32::MAX;
     let mut closest_point = None;
 
@@ -560,12 +699,23 @@ fn precise_ray_test(
 
                     if distance < closest_distance {
                         closest_distance = distance;
-                        closest_point = Some(pt);
+                        closest_point = Some((pt, triangle));
                     }
                 }
             }
         }
     }
 
-    closest_point.map(|pt| (closest_distance, pt))
+    closest_point.map(|(pt, triangle)| {
+        let normal = (triangle[1] - triangle[0])
+            .cross(&(triangle[2] - triangle[0]))
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y);
+        let normal = if normal.dot(&ray.dir) > 0.0 {
+            -normal
+        } else {
+            normal
+        };
+        (closest_distance, pt, normal)
+    })
 }
@@ -0,0 +1,546 @@
+//! Headless scene processing mode of the editor. It allows you to process scenes from command line
+//! without opening a window (and without a GPU), which is useful for build pipelines and CI.
+//!
+//! Supported commands:
+//!
+//! - `resave <scene> [-o <output>]` - loads a scene and saves it back in the current format.
+//! - `validate <scene> [--fail-on-warning]` - loads a scene and checks it for common problems.
+//! - `strip-editor-only <scene> -o <output>` - removes every node with [`EDITOR_ONLY_TAG`] tag
+//! (including its descendants) and saves the scene to the output file.
+//! - `bake-navmesh <scene> -s <mesh>... [-o <output>]` - replaces navmeshes of the scene with the
+//! navmeshes baked from the given mesh nodes and saves the scene.
+//! - `prewarm-texture-cache [<folder>]` - imports every texture in the folder (the current one by
+//! default) that is not in the texture cache yet, so the game won't compress textures on load.
+//! - `export [<scene>...] -o <output> [--platform <platform>] [--pack]` - exports the scenes (the
+//! scenes of the project manifest by default) with every resource they use, see [`crate::export`].
+//!
+//! Every command prints a JSON report to the standard output and exits with one of the exit
+//! codes: [`EXIT_SUCCESS`], [`EXIT_VALIDATION_FAILED`] or [`EXIT_FAILURE`].
+
+use crate::export::{export_game_data, ExportOptions, ExportReport, TargetPlatform};
+use clap::{Parser, Subcommand};
+use fyrox::{
+    asset::ResourceState,
+    core::{
+        futures::{executor::block_on, future::join_all},
+        pool::Handle,
+    },
+    engine::{project::ProjectManifest, resource_manager::ResourceManager, SerializationContext},
+    plugin::{Plugin, PluginRegistrationContext},
+    resource::texture::{prewarm_texture_cache, set_texture_cache_root},
+    scene::{mesh::Mesh, node::Node, Scene, SceneLoader},
+    utils::{
+        log::{Log, LogMessage, MessageKind},
+        navmesh::Navmesh,
+    },
+};
+use serde::Serialize;
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver},
+        Arc,
+    },
+};
+
+/// Nodes with this tag are removed by `strip-editor-only` command. Use it for helpers that must
+/// not get in a final build of a game (level design notes, debug geometry, etc.).
+pub const EDITOR_ONLY_TAG: &str = "EditorOnly";
+
+/// A command has finished successfully.
+pub const EXIT_SUCCESS: i32 = 0;
+/// A scene was loaded, but it has errors (or warnings if `--fail-on-warning` is specified).
+pub const EXIT_VALIDATION_FAILED: i32 = 1;
+/// A command has failed, for example a scene cannot be loaded or saved.
+pub const EXIT_FAILURE: i32 = 2;
+
+#[derive(Parser, Debug)]
+#[clap(about = "A scene editor for Fyrox game engine")]
+pub struct CliArgs {
+    /// A command to run without opening the editor.
+    #[clap(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CliCommand {
+    /// Loads a scene and saves it back in the current format.
+    Resave {
+        /// A path to the scene.
+        scene: PathBuf,
+        /// A path to save the scene to, the source scene is overwritten if not specified.
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Loads a scene and checks it for common problems.
+    Validate {
+        /// A path to the scene.
+        scene: PathBuf,
+        /// Treat warnings as errors.
+        #[clap(long)]
+        fail_on_warning: bool,
+    },
+    /// Removes nodes tagged "EditorOnly" and saves the scene to the output file.
+    StripEditorOnly {
+        /// A path to the scene.
+        scene: PathBuf,
+        /// A path to save the stripped scene to.
+        #[clap(short, long)]
+        output: PathBuf,
+    },
+    /// Replaces navmeshes of the scene with the navmeshes baked from the given mesh nodes.
+    BakeNavmesh {
+        /// A path to the scene.
+        scene: PathBuf,
+        /// Paths of mesh nodes (see `NodePath`) to bake the navmeshes from, one navmesh per mesh.
+        #[clap(short, long, required = true)]
+        source: Vec<String>,
+        /// A path to save the scene to, the source scene is overwritten if not specified.
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Imports every texture in the folder and puts its compressed representation in the cache.
+    PrewarmTextureCache {
+        /// A folder with textures, sub-folders are processed too.
+        #[clap(default_value = ".")]
+        folder: PathBuf,
+    },
+    /// Exports scenes with every resource they use, so they could be shipped with a game.
+    Export {
+        /// Paths to the scenes, scenes of the project manifest are used if not specified.
+        scenes: Vec<PathBuf>,
+        /// A folder to put exported data to.
+        #[clap(short, long)]
+        output: PathBuf,
+        /// A platform to export the data for.
+        #[clap(arg_enum, long, default_value = "desktop")]
+        platform: TargetPlatform,
+        /// Pack every file into a single archive.
+        #[clap(long)]
+        pack: bool,
+    },
+}
+
+impl CliCommand {
+    fn name(&self) -> &'static str {
+        match self {
+            CliCommand::Resave { .. } => "resave",
+            CliCommand::Validate { .. } => "validate",
+            CliCommand::StripEditorOnly { .. } => "strip-editor-only",
+            CliCommand::BakeNavmesh { .. } => "bake-navmesh",
+            CliCommand::PrewarmTextureCache { .. } => "prewarm-texture-cache",
+            CliCommand::Export { .. } => "export",
+        }
+    }
+
+    fn scene(&self) -> &Path {
+        match self {
+            CliCommand::Resave { scene, .. }
+            | CliCommand::Validate { scene, .. }
+            | CliCommand::StripEditorOnly { scene, .. }
+            | CliCommand::BakeNavmesh { scene, .. } => scene,
+            CliCommand::PrewarmTextureCache { folder } => folder,
+            CliCommand::Export { scenes, .. } => scenes
+                .first()
+                .map_or_else(|| Path::new("."), |p| p.as_path()),
+        }
+    }
+}
+
+#[derive(Serialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Issue {
+    pub severity: Severity,
+    /// A path of a node (see [`fyrox::scene::graph::path::NodePath`]) the issue is related to.
+    pub node: Option<String>,
+    pub message: String,
+}
+
+impl Issue {
+    fn new(severity: Severity, node: Option<String>, message: String) -> Self {
+        Self {
+            severity,
+            node,
+            message,
+        }
+    }
+}
+
+/// Result of a command, it is printed as JSON.
+#[derive(Serialize, Clone, Debug)]
+pub struct Report {
+    pub command: &'static str,
+    /// A path to the scene or to the folder for commands that process folders.
+    pub scene: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removed_nodes: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub baked_navmeshes: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub imported_textures: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub export: Option<ExportReport>,
+    pub issues: Vec<Issue>,
+    pub exit_code: i32,
+}
+
+impl Report {
+    fn has(&self, severity: Severity) -> bool {
+        self.issues.iter().any(|i| i.severity == severity)
+    }
+}
+
+/// Runs commands of the headless mode. Register plugins of your game using
+/// [`SceneTool::add_plugin`], otherwise scenes with scripts and custom nodes of the game cannot
+/// be loaded.
+pub struct SceneTool {
+    serialization_context: Arc<SerializationContext>,
+    resource_manager: ResourceManager,
+    log_receiver: Receiver<LogMessage>,
+}
+
+impl Default for SceneTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SceneTool {
+    pub fn new() -> Self {
+        if let Ok(Some(project)) = ProjectManifest::load(".") {
+            set_texture_cache_root(&project.data_root);
+        }
+        let serialization_context = Arc::new(SerializationContext::new());
+        // The listener is removed from the log when the tool is dropped.
+        let (sender, log_receiver) = mpsc::channel();
+        Log::add_listener(sender);
+        Self {
+            resource_manager: ResourceManager::new(serialization_context.clone()),
+            serialization_context,
+            log_receiver,
+        }
+    }
+
+    /// Registers node and script types of a plugin.
+    pub fn add_plugin<P: Plugin>(&mut self, mut plugin: P) {
+        plugin.on_register(PluginRegistrationContext {
+            serialization_context: self.serialization_context.clone(),
+        });
+    }
+
+    /// Runs the command and collects every warning and error from the log into the report.
+    pub fn run(&mut self, command: &CliCommand) -> Report {
+        // Skip messages that were written before the command.
+        for _ in self.log_receiver.try_iter() {}
+
+        let mut report = Report {
+            command: command.name(),
+            scene: command.scene().to_owned(),
+            output: None,
+            removed_nodes: None,
+            baked_navmeshes: None,
+            imported_textures: None,
+            export: None,
+            issues: Default::default(),
+            exit_code: EXIT_SUCCESS,
+        };
+
+        let result = self.run_internal(command, &mut report);
+
+        // Resources report their problems into the log, a scene could reference hundreds of them.
+        for message in self.log_receiver.try_iter() {
+            let severity = match message.kind {
+                MessageKind::Information => continue,
+                MessageKind::Warning => Severity::Warning,
+                MessageKind::Error => Severity::Error,
+            };
+            report.issues.push(Issue::new(
+                severity,
+                None,
+                message.content.trim_end().to_owned(),
+            ));
+        }
+
+        report.exit_code = match result {
+            Err(error) => {
+                report.issues.push(Issue::new(Severity::Error, None, error));
+                EXIT_FAILURE
+            }
+            Ok(fail_on_warning) => {
+                if report.has(Severity::Error) || (fail_on_warning && report.has(Severity::Warning))
+                {
+                    EXIT_VALIDATION_FAILED
+                } else {
+                    EXIT_SUCCESS
+                }
+            }
+        };
+
+        report
+    }
+
+    /// Runs the command, prints JSON report to the standard output and returns exit code.
+    pub fn run_and_print(&mut self, command: &CliCommand) -> i32 {
+        // Keep the standard output clean, so the report could be parsed.
+        Log::set_console_output(false);
+
+        let report = self.run(command);
+
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Unable to serialize the report. Reason: {}", e),
+        }
+
+        report.exit_code
+    }
+
+    // Returns `true` if warnings must be treated as errors.
+    fn run_internal(&mut self, command: &CliCommand, report: &mut Report) -> Result<bool, String> {
+        match command {
+            CliCommand::Resave { scene, output } => {
+                let mut loaded = self.load_scene(scene)?;
+                let output = output.clone().unwrap_or_else(|| scene.clone());
+                save_scene(&mut loaded, &output)?;
+                report.output = Some(output);
+                Ok(false)
+            }
+            CliCommand::Validate {
+                scene,
+                fail_on_warning,
+            } => {
+                let loaded = self.load_scene(scene)?;
+                validate(&loaded, &mut report.issues);
+                Ok(*fail_on_warning)
+            }
+            CliCommand::StripEditorOnly { scene, output } => {
+                let mut loaded = self.load_scene(scene)?;
+                report.removed_nodes = Some(strip_editor_only(&mut loaded));
+                save_scene(&mut loaded, output)?;
+                report.output = Some(output.clone());
+                Ok(false)
+            }
+            CliCommand::BakeNavmesh {
+                scene,
+                source,
+                output,
+            } => {
+                let mut loaded = self.load_scene(scene)?;
+                report.baked_navmeshes = Some(bake_navmeshes(&mut loaded, source)?);
+                let output = output.clone().unwrap_or_else(|| scene.clone());
+                save_scene(&mut loaded, &output)?;
+                report.output = Some(output);
+                Ok(false)
+            }
+            CliCommand::PrewarmTextureCache { folder } => {
+                report.imported_textures =
+                    Some(block_on(prewarm_texture_cache(folder, &Default::default())));
+                Ok(false)
+            }
+            CliCommand::Export {
+                scenes,
+                output,
+                platform,
+                pack,
+            } => {
+                let project = ProjectManifest::load(".").ok().flatten();
+                let scenes = if scenes.is_empty() {
+                    project
+                        .as_ref()
+                        .map(|p| p.all_scenes().map(|s| s.to_owned()).collect())
+                        .unwrap_or_default()
+                } else {
+                    scenes.clone()
+                };
+                let data_root = project.map_or_else(|| PathBuf::from("."), |p| p.data_root);
+                let options = ExportOptions {
+                    scenes,
+                    output: output.clone(),
+                    platform: *platform,
+                    pack: *pack,
+                };
+                let export =
+                    export_game_data(&options, self.serialization_context.clone(), &data_root)?;
+                for path in export.missing.iter() {
+                    report.issues.push(Issue::new(
+                        Severity::Error,
+                        None,
+                        format!("{} resource is missing.", path.display()),
+                    ));
+                }
+                for error in export.errors.iter() {
+                    report
+                        .issues
+                        .push(Issue::new(Severity::Error, None, error.clone()));
+                }
+                report.output = Some(output.clone());
+                report.export = Some(export);
+                Ok(false)
+            }
+        }
+    }
+
+    fn load_scene(&self, path: &Path) -> Result<Scene, String> {
+        block_on(async {
+            let loader = SceneLoader::from_file(path, self.serialization_context.clone())
+                .await
+                .map_err(|e| format!("Unable to load {} scene. Reason: {}", path.display(), e))?;
+            Ok(loader.finish(self.resource_manager.clone()).await)
+        })
+    }
+}
+
+/// Parses command line arguments and runs a command if there is any. Returns exit code of the
+/// command, or `None` if no command was specified and the editor must be started as usual.
+/// `register_plugins` allows you to register plugins of your game (see [`SceneTool::add_plugin`]).
+pub fn run_from_args<F>(register_plugins: F) -> Option<i32>
+where
+    F: FnOnce(&mut SceneTool),
+{
+    let command = CliArgs::parse().command?;
+    let mut tool = SceneTool::new();
+    register_plugins(&mut tool);
+    Some(tool.run_and_print(&command))
+}
+
+fn save_scene(scene: &mut Scene, path: &Path) -> Result<(), String> {
+    scene
+        .save_to_file(path)
+        .map_err(|e| format!("Unable to save {} scene. Reason: {}", path.display(), e))
+}
+
+/// Removes every node tagged with [`EDITOR_ONLY_TAG`] with its descendants. Returns amount of
+/// removed nodes.
+pub fn strip_editor_only(scene: &mut Scene) -> usize {
+    let editor_only = scene
+        .graph
+        .pair_iter()
+        .filter(|(_, node)| node.tag() == EDITOR_ONLY_TAG)
+        .map(|(handle, _)| handle)
+        .collect::<Vec<_>>();
+
+    let count_before = scene.graph.node_count();
+    for handle in editor_only {
+        // Could be already removed together with its tagged ancestor.
+        if scene.graph.is_valid_handle(handle) {
+            scene.graph.remove_node(handle);
+        }
+    }
+    (count_before - scene.graph.node_count()) as usize
+}
+
+/// Replaces navmeshes of the scene with the navmeshes baked from the mesh nodes with the given
+/// paths. Returns amount of baked navmeshes.
+pub fn bake_navmeshes(scene: &mut Scene, sources: &[String]) -> Result<usize, String> {
+    // Navmeshes are baked in world space, so global transforms of loaded nodes must be valid.
+    scene.graph.update_hierarchical_data();
+
+    let navmeshes = sources
+        .iter()
+        .map(|path| {
+            scene
+                .graph
+                .find_by_path(path)
+                .and_then(|handle| scene.graph[handle].cast::<Mesh>())
+                .map(Navmesh::from_mesh)
+                .ok_or_else(|| format!("There is no mesh at {} path.", path))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let count = navmeshes.len();
+    scene.navmeshes.clear();
+    for navmesh in navmeshes {
+        scene.navmeshes.add(navmesh);
+    }
+    Ok(count)
+}
+
+fn load_error<T, E>(state: &ResourceState<T, E>) -> Option<String>
+where
+    T: fyrox::asset::ResourceData,
+    E: fyrox::asset::ResourceLoadError,
+{
+    if let ResourceState::LoadError { path, error } = state {
+        Some(format!(
+            "Unable to load {} resource. Reason: {:?}",
+            path.display(),
+            error
+        ))
+    } else {
+        None
+    }
+}
+
+/// Checks the scene for problems that do not prevent it from loading, but most likely are mistakes.
+pub fn validate(scene: &Scene, issues: &mut Vec<Issue>) {
+    let graph = &scene.graph;
+
+    let mut textures = Vec::new();
+    for (handle, node) in graph.pair_iter() {
+        if let Some(mesh) = node.cast::<Mesh>() {
+            for surface in mesh.surfaces() {
+                for property in surface.material().lock().properties().values() {
+                    if let Some(texture) = property.as_sampler() {
+                        textures.push((handle, texture));
+                    }
+                }
+            }
+        }
+    }
+    // Textures are loaded in background, wait until every texture is either loaded or failed.
+    block_on(join_all(textures.iter().map(|(_, t)| t.clone())));
+
+    let path = |handle: Handle<Node>| Some(graph.node_path(handle));
+
+    for (handle, texture) in textures {
+        if let Some(error) = load_error(&*texture.state()) {
+            issues.push(Issue::new(Severity::Error, path(handle), error));
+        }
+    }
+
+    let is_body = |handle: Handle<Node>| {
+        graph.is_valid_handle(handle) && {
+            let node = &graph[handle];
+            node.is_rigid_body() || node.is_rigid_body2d()
+        }
+    };
+
+    for (handle, node) in graph.pair_iter() {
+        if let Some(resource) = node.resource() {
+            if let Some(error) = load_error(&*resource.state()) {
+                issues.push(Issue::new(Severity::Error, path(handle), error));
+            }
+        }
+
+        let bodies = if let Some(joint) = node.cast::<fyrox::scene::joint::Joint>() {
+            Some((joint.body1(), joint.body2()))
+        } else {
+            node.cast::<fyrox::scene::dim2::joint::Joint>()
+                .map(|joint| (joint.body1(), joint.body2()))
+        };
+        if let Some((body1, body2)) = bodies {
+            if !is_body(body1) || !is_body(body2) {
+                issues.push(Issue::new(
+                    Severity::Warning,
+                    path(handle),
+                    "Joint is not attached to two rigid bodies and will have no effect.".to_owned(),
+                ));
+            }
+        }
+
+        if (node.is_collider() || node.is_collider2d()) && !is_body(node.parent()) {
+            issues.push(Issue::new(
+                Severity::Warning,
+                path(handle),
+                "Collider is not a child of a rigid body and will have no effect.".to_owned(),
+            ));
+        }
+    }
+}
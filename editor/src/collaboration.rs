@@ -0,0 +1,426 @@
+//! Collaboration aids for version-controlled projects. There are two parts: lock files which
+//! warns other people that someone is already editing a scene, and detection of external changes
+//! of the opened scene (for example when a scene was updated from a VCS).
+
+use crate::{settings::collaboration::CollaborationSettings, Message};
+use fyrox::{
+    core::pool::Handle,
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        formatted_text::WrapMode,
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        messagebox::{MessageBoxBuilder, MessageBoxButtons, MessageBoxMessage, MessageBoxResult},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+    },
+    utils::log::Log,
+};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::mpsc::{Receiver, Sender},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Contents of a `<scene>.lock` file.
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug)]
+pub struct SceneLock {
+    pub user: String,
+    pub machine: String,
+    /// Time when the lock was created or refreshed, in seconds since UNIX epoch.
+    pub timestamp: u64,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn user_name() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_owned())
+}
+
+fn machine_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .or_else(|| {
+            std::fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|name| name.trim().to_owned())
+        })
+        .unwrap_or_else(|| "unknown".to_owned())
+}
+
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+impl SceneLock {
+    /// Creates a new lock for current user on this machine.
+    pub fn current() -> Self {
+        Self {
+            user: user_name(),
+            machine: machine_name(),
+            timestamp: now(),
+        }
+    }
+
+    /// Returns a path of the lock file for the given scene (`scene.rgs` -> `scene.rgs.lock`).
+    pub fn path_of(scene_path: &Path) -> PathBuf {
+        let mut path = scene_path.as_os_str().to_owned();
+        path.push(".lock");
+        path.into()
+    }
+
+    pub fn read(scene_path: &Path) -> Option<Self> {
+        let file = File::open(Self::path_of(scene_path)).ok()?;
+        ron::de::from_reader(file).ok()
+    }
+
+    pub fn write(&self, scene_path: &Path) -> Result<(), String> {
+        let path = Self::path_of(scene_path);
+        let file = File::create(&path).map_err(|e| e.to_string())?;
+        ron::ser::to_writer_pretty(file, self, PrettyConfig::default()).map_err(|e| e.to_string())
+    }
+
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.timestamp))
+    }
+
+    pub fn is_from_this_machine(&self) -> bool {
+        self.machine == machine_name()
+    }
+
+    pub fn is_owned_by_current_user(&self) -> bool {
+        self.is_from_this_machine() && self.user == user_name()
+    }
+
+    /// Returns true if the lock can be taken over without asking. It is the case when the lock
+    /// was left by an editor on this machine (most likely it crashed) or when the lock is too old.
+    pub fn is_reclaimable(&self, stale_age: Duration) -> bool {
+        self.is_from_this_machine() || self.age() > stale_age
+    }
+}
+
+fn stale_age(settings: &CollaborationSettings) -> Duration {
+    Duration::from_secs(settings.stale_lock_age_minutes as u64 * 60)
+}
+
+pub struct Collaboration {
+    sender: Sender<Message>,
+    file_changes: Option<Receiver<PathBuf>>,
+    scene_path: Option<PathBuf>,
+    // Modification time of the scene file when the editor saw it last time. Used to filter out
+    // changes made by the editor itself.
+    known_modification_time: Option<SystemTime>,
+    holds_lock: bool,
+    last_lock_refresh: Instant,
+    banner: Handle<UiNode>,
+    banner_text: Handle<UiNode>,
+    reload: Handle<UiNode>,
+    keep_mine: Handle<UiNode>,
+    lock_message_box: Handle<UiNode>,
+    pending_scene: Option<PathBuf>,
+}
+
+impl Collaboration {
+    pub fn new(ctx: &mut BuildContext, sender: Sender<Message>) -> Self {
+        let banner_text;
+        let reload;
+        let keep_mine;
+        let banner = WindowBuilder::new(WidgetBuilder::new().with_width(350.0).with_height(110.0))
+            .can_close(false)
+            .can_minimize(false)
+            .open(false)
+            .with_title(WindowTitle::Text("Scene Changed Externally".to_owned()))
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            banner_text = TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_margin(Thickness::uniform(2.0)),
+                            )
+                            .with_wrap(WrapMode::Word)
+                            .build(ctx);
+                            banner_text
+                        })
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .with_child({
+                                        reload = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(80.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Reload")
+                                        .build(ctx);
+                                        reload
+                                    })
+                                    .with_child({
+                                        keep_mine = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(80.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Keep Mine")
+                                        .build(ctx);
+                                        keep_mine
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_row(Row::stretch())
+                .add_row(Row::strict(25.0))
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .build(ctx);
+
+        let lock_message_box = MessageBoxBuilder::new(
+            WindowBuilder::new(WidgetBuilder::new().with_width(350.0).with_height(120.0))
+                .can_close(false)
+                .can_minimize(false)
+                .open(false)
+                .with_title(WindowTitle::Text("Scene Is Locked".to_owned())),
+        )
+        .with_buttons(MessageBoxButtons::YesNo)
+        .build(ctx);
+
+        Self {
+            sender,
+            file_changes: None,
+            scene_path: None,
+            known_modification_time: None,
+            holds_lock: false,
+            last_lock_refresh: Instant::now(),
+            banner,
+            banner_text,
+            reload,
+            keep_mine,
+            lock_message_box,
+            pending_scene: None,
+        }
+    }
+
+    /// Sets a source of file system changes, usually it is a receiver from resource manager's
+    /// watcher.
+    pub fn set_file_changes(&mut self, file_changes: Receiver<PathBuf>) {
+        self.file_changes = Some(file_changes);
+    }
+
+    /// Checks whether the scene can be opened right now. If the scene is locked by someone else,
+    /// shows a warning dialog and returns false. The scene will be opened if the user decides
+    /// to ignore the lock.
+    pub fn try_open(
+        &mut self,
+        scene_path: &Path,
+        settings: &CollaborationSettings,
+        ui: &UserInterface,
+    ) -> bool {
+        if !settings.use_lock_files {
+            return true;
+        }
+
+        match SceneLock::read(scene_path) {
+            Some(lock) if !lock.is_reclaimable(stale_age(settings)) => {
+                ui.send_message(MessageBoxMessage::open(
+                    self.lock_message_box,
+                    MessageDirection::ToWidget,
+                    None,
+                    Some(format!(
+                        "Scene {} is being edited by {} on {} (lock updated {} min ago). \
+                        Your changes may conflict with theirs. Open anyway?",
+                        scene_path.display(),
+                        lock.user,
+                        lock.machine,
+                        lock.age().as_secs() / 60
+                    )),
+                ));
+                self.pending_scene = Some(scene_path.to_owned());
+                false
+            }
+            Some(lock) => {
+                if !lock.is_owned_by_current_user() {
+                    Log::warn(format!(
+                        "Reclaiming stale lock of scene {} left by {} on {}.",
+                        scene_path.display(),
+                        lock.user,
+                        lock.machine
+                    ));
+                }
+                true
+            }
+            None => true,
+        }
+    }
+
+    /// Must be called when a scene was opened or saved to a file.
+    pub fn on_scene_path_changed(
+        &mut self,
+        scene_path: Option<&Path>,
+        settings: &CollaborationSettings,
+        ui: &UserInterface,
+    ) {
+        let same_path = match (self.scene_path.as_deref(), scene_path) {
+            (Some(old), Some(new)) => is_same_file(old, new),
+            _ => false,
+        };
+
+        if !same_path {
+            self.release_lock();
+            self.close_banner(ui);
+            self.scene_path = scene_path.map(|p| p.to_owned());
+        }
+
+        if let Some(path) = self.scene_path.clone() {
+            self.known_modification_time = modification_time(&path);
+
+            if settings.use_lock_files && !self.holds_lock {
+                match SceneLock::read(&path) {
+                    // The scene was opened despite someone else's lock, keep their lock intact.
+                    Some(lock)
+                        if !lock.is_owned_by_current_user()
+                            && !lock.is_reclaimable(stale_age(settings)) => {}
+                    _ => self.acquire_lock(&path),
+                }
+            }
+        } else {
+            self.known_modification_time = None;
+        }
+    }
+
+    /// Must be called when a scene was closed or the editor is about to exit.
+    pub fn on_scene_closed(&mut self, ui: &UserInterface) {
+        self.release_lock();
+        self.close_banner(ui);
+        self.scene_path = None;
+        self.known_modification_time = None;
+    }
+
+    fn acquire_lock(&mut self, scene_path: &Path) {
+        match SceneLock::current().write(scene_path) {
+            Ok(_) => {
+                self.holds_lock = true;
+                self.last_lock_refresh = Instant::now();
+            }
+            Err(e) => Log::err(format!(
+                "Unable to create lock file for scene {}. Reason: {}",
+                scene_path.display(),
+                e
+            )),
+        }
+    }
+
+    fn release_lock(&mut self) {
+        if !self.holds_lock {
+            return;
+        }
+        self.holds_lock = false;
+
+        if let Some(scene_path) = self.scene_path.as_ref() {
+            // Do not remove a lock that was taken over by someone else.
+            if SceneLock::read(scene_path).map_or(false, |lock| lock.is_owned_by_current_user()) {
+                Log::verify(std::fs::remove_file(SceneLock::path_of(scene_path)));
+            }
+        }
+    }
+
+    fn close_banner(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::close(
+            self.banner,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    pub fn update(&mut self, settings: &CollaborationSettings, ui: &UserInterface) {
+        let scene_path = match self.scene_path.clone() {
+            Some(scene_path) => scene_path,
+            None => return,
+        };
+
+        // Refresh the lock periodically, otherwise it will be considered stale during long
+        // editing sessions.
+        if self.holds_lock && self.last_lock_refresh.elapsed() > stale_age(settings) / 4 {
+            self.acquire_lock(&scene_path);
+        }
+
+        if let Some(file_changes) = self.file_changes.as_ref() {
+            let mut changed = false;
+            while let Ok(path) = file_changes.try_recv() {
+                changed |= is_same_file(&path, &scene_path);
+            }
+
+            if changed && settings.detect_external_changes {
+                let modification_time = modification_time(&scene_path);
+                if modification_time != self.known_modification_time {
+                    self.known_modification_time = modification_time;
+
+                    ui.send_message(TextMessage::text(
+                        self.banner_text,
+                        MessageDirection::ToWidget,
+                        format!(
+                            "Scene {} was changed outside of the editor. Reload it and discard \
+                            your changes or keep your version?",
+                            scene_path.display()
+                        ),
+                    ));
+                    ui.send_message(WindowMessage::open(
+                        self.banner,
+                        MessageDirection::ToWidget,
+                        true,
+                    ));
+                }
+            }
+        }
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, ui: &UserInterface) {
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.reload {
+                if let Some(scene_path) = self.scene_path.clone() {
+                    self.sender
+                        .send(Message::ForceLoadScene(scene_path))
+                        .unwrap();
+                }
+                self.close_banner(ui);
+            } else if message.destination() == self.keep_mine {
+                self.close_banner(ui);
+            }
+        } else if let Some(MessageBoxMessage::Close(result)) = message.data() {
+            if message.destination() == self.lock_message_box {
+                if let Some(scene_path) = self.pending_scene.take() {
+                    if let MessageBoxResult::Yes = result {
+                        self.sender
+                            .send(Message::ForceLoadScene(scene_path))
+                            .unwrap();
+                    }
+                }
+            }
+        }
+    }
+}
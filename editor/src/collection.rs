@@ -0,0 +1,451 @@
+//! Node collections panel - named sets of nodes that are independent from the hierarchy of the
+//! scene graph. See [`NodeCollection`] docs for more info.
+
+use crate::{
+    scene::{
+        commands::{
+            collection::{
+                AddNodeCollectionCommand, RemoveNodeCollectionCommand,
+                SetNodeCollectionLockedCommand, SetNodeCollectionNodesCommand,
+            },
+            graph::SetVisibleCommand,
+            ChangeSelectionCommand, CommandGroup, SceneCommand,
+        },
+        EditorScene, Selection,
+    },
+    send_sync_message,
+    utils::window_content,
+    world::graph::selection::GraphSelection,
+    GameEngine, Message, Mode, MSG_SYNC_FLAG,
+};
+use fyrox::{
+    core::{pool::Handle, scope_profile},
+    gui::{
+        border::BorderBuilder,
+        button::{ButtonBuilder, ButtonMessage},
+        decorator::DecoratorBuilder,
+        grid::{Column, GridBuilder, Row},
+        list_view::{ListViewBuilder, ListViewMessage},
+        message::{MessageDirection, UiMessage},
+        text::TextBuilder,
+        text_box::{TextBox, TextBoxBuilder, TextCommitMode},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    scene::{
+        collection::{NodeCollection, NodeCollectionContainer},
+        graph::Graph,
+        node::Node,
+        Scene,
+    },
+};
+use std::sync::mpsc::Sender;
+
+/// Name of a collection if a user did not specify any.
+const DEFAULT_NAME: &str = "Collection";
+
+/// Returns nodes of the selection that could be members of a collection.
+fn selected_nodes(editor_scene: &EditorScene, graph: &Graph) -> Vec<Handle<Node>> {
+    if let Selection::Graph(selection) = &editor_scene.selection {
+        selection
+            .nodes()
+            .iter()
+            .cloned()
+            .filter(|&node| node != graph.get_root() && graph.is_valid_handle(node))
+            .collect()
+    } else {
+        Default::default()
+    }
+}
+
+/// Creates a command that adds selected nodes to the collection with the given index. Returns
+/// `None` if every selected node is already a member of the collection.
+pub fn make_add_selection_to_collection_command(
+    index: usize,
+    editor_scene: &EditorScene,
+    scene: &Scene,
+) -> Option<SceneCommand> {
+    let collection = scene.node_collections.get(index)?;
+    let mut nodes = collection.nodes.clone();
+    for node in selected_nodes(editor_scene, &scene.graph) {
+        if !nodes.contains(&node) {
+            nodes.push(node);
+        }
+    }
+    if nodes.len() == collection.nodes.len() {
+        None
+    } else {
+        Some(SceneCommand::new(SetNodeCollectionNodesCommand::new(
+            index, nodes,
+        )))
+    }
+}
+
+/// Creates a command that removes selected nodes from the collection with the given index.
+/// Returns `None` if no selected node is a member of the collection.
+pub fn make_remove_selection_from_collection_command(
+    index: usize,
+    editor_scene: &EditorScene,
+    scene: &Scene,
+) -> Option<SceneCommand> {
+    let collection = scene.node_collections.get(index)?;
+    let selected = selected_nodes(editor_scene, &scene.graph);
+    let nodes = collection
+        .nodes
+        .iter()
+        .cloned()
+        .filter(|node| !selected.contains(node))
+        .collect::<Vec<_>>();
+    if nodes.len() == collection.nodes.len() {
+        None
+    } else {
+        Some(SceneCommand::new(SetNodeCollectionNodesCommand::new(
+            index, nodes,
+        )))
+    }
+}
+
+/// Creates a single undoable command that changes visibility of every member of a collection.
+/// Returns `None` if there is nothing to change.
+fn make_set_visibility_command(
+    collection: &NodeCollection,
+    graph: &Graph,
+    visibility: bool,
+) -> Option<SceneCommand> {
+    let commands = collection
+        .valid_nodes(graph)
+        .filter(|&node| graph[node].visibility() != visibility)
+        .map(|node| SceneCommand::new(SetVisibleCommand::new(node, visibility)))
+        .collect::<Vec<_>>();
+    if commands.is_empty() {
+        None
+    } else {
+        Some(SceneCommand::new(CommandGroup::from(commands)))
+    }
+}
+
+fn make_button(ctx: &mut BuildContext, text: &str, row: usize, column: usize) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(column)
+            .with_margin(Thickness::uniform(1.0)),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+fn item_text(collection: &NodeCollection, graph: &Graph) -> String {
+    let count = collection.valid_nodes(graph).count();
+    if collection.locked {
+        format!("{} ({}) [Locked]", collection.name, count)
+    } else {
+        format!("{} ({})", collection.name, count)
+    }
+}
+
+pub struct NodeCollectionsPanel {
+    pub window: Handle<UiNode>,
+    collections: Handle<UiNode>,
+    name: Handle<UiNode>,
+    create: Handle<UiNode>,
+    select: Handle<UiNode>,
+    remove: Handle<UiNode>,
+    show: Handle<UiNode>,
+    hide: Handle<UiNode>,
+    lock: Handle<UiNode>,
+    unlock: Handle<UiNode>,
+    sender: Sender<Message>,
+    selected: Option<usize>,
+    // Texts of the items, the list is re-created only if they're changed.
+    item_texts: Vec<String>,
+}
+
+impl NodeCollectionsPanel {
+    pub fn new(ctx: &mut BuildContext, sender: Sender<Message>) -> Self {
+        let collections;
+        let name;
+        let create;
+        let select;
+        let remove;
+        let show;
+        let hide;
+        let lock;
+        let unlock;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(400.0))
+            .with_title(WindowTitle::text("Node Collections"))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            collections =
+                                ListViewBuilder::new(WidgetBuilder::new().on_row(0)).build(ctx);
+                            collections
+                        })
+                        .with_child(
+                            GridBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_child({
+                                        name = TextBoxBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_column(0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text(DEFAULT_NAME)
+                                        .with_text_commit_mode(TextCommitMode::Immediate)
+                                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                                        .build(ctx);
+                                        name
+                                    })
+                                    .with_child({
+                                        create = make_button(ctx, "Create From Selection", 0, 1);
+                                        create
+                                    }),
+                            )
+                            .add_row(Row::stretch())
+                            .add_column(Column::stretch())
+                            .add_column(Column::strict(140.0))
+                            .build(ctx),
+                        )
+                        .with_child(
+                            GridBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .with_child({
+                                        select = make_button(ctx, "Select", 0, 0);
+                                        select
+                                    })
+                                    .with_child({
+                                        show = make_button(ctx, "Show", 0, 1);
+                                        show
+                                    })
+                                    .with_child({
+                                        lock = make_button(ctx, "Lock", 0, 2);
+                                        lock
+                                    })
+                                    .with_child({
+                                        remove = make_button(ctx, "Remove", 1, 0);
+                                        remove
+                                    })
+                                    .with_child({
+                                        hide = make_button(ctx, "Hide", 1, 1);
+                                        hide
+                                    })
+                                    .with_child({
+                                        unlock = make_button(ctx, "Unlock", 1, 2);
+                                        unlock
+                                    }),
+                            )
+                            .add_row(Row::stretch())
+                            .add_row(Row::stretch())
+                            .add_column(Column::stretch())
+                            .add_column(Column::stretch())
+                            .add_column(Column::stretch())
+                            .build(ctx),
+                        ),
+                )
+                .add_column(Column::stretch())
+                .add_row(Row::stretch())
+                .add_row(Row::strict(24.0))
+                .add_row(Row::strict(48.0))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            collections,
+            name,
+            create,
+            select,
+            remove,
+            show,
+            hide,
+            lock,
+            unlock,
+            sender,
+            selected: None,
+            item_texts: Default::default(),
+        }
+    }
+
+    pub fn sync_to_model(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        scope_profile!();
+
+        let scene = &engine.scenes[editor_scene.scene];
+        let ui = &mut engine.user_interface;
+
+        let item_texts = scene
+            .node_collections
+            .iter()
+            .map(|collection| item_text(collection, &scene.graph))
+            .collect::<Vec<_>>();
+
+        if item_texts != self.item_texts {
+            let ctx = &mut ui.build_ctx();
+            let items = item_texts
+                .iter()
+                .map(|text| {
+                    DecoratorBuilder::new(BorderBuilder::new(
+                        WidgetBuilder::new().with_height(22.0).with_child(
+                            TextBuilder::new(
+                                WidgetBuilder::new().with_margin(Thickness::left(2.0)),
+                            )
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .with_text(text)
+                            .build(ctx),
+                        ),
+                    ))
+                    .build(ctx)
+                })
+                .collect::<Vec<_>>();
+
+            send_sync_message(
+                ui,
+                ListViewMessage::items(self.collections, MessageDirection::ToWidget, items),
+            );
+
+            self.item_texts = item_texts;
+        }
+
+        if self.selected.map_or(false, |i| i >= self.item_texts.len()) {
+            self.selected = None;
+        }
+
+        send_sync_message(
+            ui,
+            ListViewMessage::selection(self.collections, MessageDirection::ToWidget, self.selected),
+        );
+
+        self.sync_buttons(&scene.node_collections, ui);
+    }
+
+    fn sync_buttons(&self, collections: &NodeCollectionContainer, ui: &UserInterface) {
+        let selected = self.selected.and_then(|i| collections.get(i));
+        for (button, enabled) in [
+            (self.select, selected.is_some()),
+            (self.remove, selected.is_some()),
+            (self.show, selected.is_some()),
+            (self.hide, selected.is_some()),
+            (self.lock, selected.map_or(false, |c| !c.locked)),
+            (self.unlock, selected.map_or(false, |c| c.locked)),
+        ] {
+            send_sync_message(
+                ui,
+                WidgetMessage::enabled(button, MessageDirection::ToWidget, enabled),
+            );
+        }
+    }
+
+    fn select_members(&self, index: usize, editor_scene: &EditorScene, scene: &Scene) {
+        if let Some(collection) = scene.node_collections.get(index) {
+            let new_selection = Selection::Graph(GraphSelection::from_list(
+                collection.valid_nodes(&scene.graph).collect(),
+            ));
+            if new_selection != editor_scene.selection {
+                self.sender
+                    .send(Message::do_scene_command(ChangeSelectionCommand::new(
+                        new_selection,
+                        editor_scene.selection.clone(),
+                    )))
+                    .unwrap();
+            }
+        }
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        editor_scene: &EditorScene,
+        engine: &GameEngine,
+    ) {
+        scope_profile!();
+
+        let scene = &engine.scenes[editor_scene.scene];
+
+        if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.create {
+                let name = engine
+                    .user_interface
+                    .node(self.name)
+                    .cast::<TextBox>()
+                    .map(|text_box| text_box.text().trim().to_owned())
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| DEFAULT_NAME.to_owned());
+
+                self.sender
+                    .send(Message::do_scene_command(AddNodeCollectionCommand::new(
+                        NodeCollection::new(name, selected_nodes(editor_scene, &scene.graph)),
+                    )))
+                    .unwrap();
+
+                self.selected = Some(scene.node_collections.len());
+            } else if let Some(index) = self.selected {
+                let collection = match scene.node_collections.get(index) {
+                    Some(collection) => collection,
+                    None => return,
+                };
+
+                let command = if message.destination() == self.select {
+                    self.select_members(index, editor_scene, scene);
+                    None
+                } else if message.destination() == self.remove {
+                    Some(SceneCommand::new(RemoveNodeCollectionCommand::new(index)))
+                } else if message.destination() == self.show {
+                    make_set_visibility_command(collection, &scene.graph, true)
+                } else if message.destination() == self.hide {
+                    make_set_visibility_command(collection, &scene.graph, false)
+                } else if message.destination() == self.lock {
+                    Some(SceneCommand::new(SetNodeCollectionLockedCommand::new(
+                        index, true,
+                    )))
+                } else if message.destination() == self.unlock {
+                    Some(SceneCommand::new(SetNodeCollectionLockedCommand::new(
+                        index, false,
+                    )))
+                } else {
+                    None
+                };
+
+                if let Some(command) = command {
+                    self.sender.send(Message::DoSceneCommand(command)).unwrap();
+                }
+            }
+        } else if let Some(&ListViewMessage::SelectionChanged(selection)) =
+            message.data::<ListViewMessage>()
+        {
+            if message.destination() == self.collections
+                && message.direction() == MessageDirection::FromWidget
+                && !message.has_flags(MSG_SYNC_FLAG)
+            {
+                self.selected = selection;
+                self.sync_buttons(&scene.node_collections, &engine.user_interface);
+
+                // Click on a collection selects all of its members.
+                if let Some(index) = selection {
+                    self.select_members(index, editor_scene, scene);
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self, ui: &UserInterface) {
+        self.selected = None;
+        self.item_texts.clear();
+        send_sync_message(
+            ui,
+            ListViewMessage::items(self.collections, MessageDirection::ToWidget, vec![]),
+        );
+    }
+
+    pub fn on_mode_changed(&mut self, ui: &UserInterface, mode: &Mode) {
+        ui.send_message(WidgetMessage::enabled(
+            window_content(self.window, ui),
+            MessageDirection::ToWidget,
+            mode.is_edit(),
+        ));
+    }
+}
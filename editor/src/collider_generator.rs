@@ -0,0 +1,548 @@
+//! Generates rigid bodies with colliders from visual geometry of meshes.
+//!
+//! Every generated rigid body is linked to the node it was generated for (a mesh or a root of
+//! a sub-graph with meshes) and marked with [`GENERATED_COLLIDER_TAG`], so generation could be
+//! repeated - previously generated bodies will be replaced, not duplicated.
+//!
+//! Scale of nodes is always baked into the geometry of colliders, generated bodies and colliders
+//! do not need any scaling. Trimesh and convex hull shapes are built by the physics engine from
+//! the geometry of meshes in the space of the collider, box and capsule shapes are fitted to the
+//! geometry in the (unscaled) space of the rigid body.
+//!
+//! Hitboxes (see [`fyrox::scene::hitbox`]) are generated here as well, they use the same capsule
+//! fitting, but the geometry is taken from vertices of a skinned mesh affected by a bone.
+
+use crate::{
+    gui::make_dropdown_list_option,
+    scene::{
+        commands::{
+            graph::{AddSubGraphCommand, DeleteSubGraphCommand},
+            CommandGroup, SceneCommand,
+        },
+        EditorScene, Selection,
+    },
+    GameEngine, Message,
+};
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Point3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        scope_profile,
+    },
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::TextBuilder,
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+    scene::{
+        base::{BaseBuilder, Mobility},
+        collider::{ColliderBuilder, ColliderShape, ConvexPolyhedronShape, GeometrySource},
+        graph::Graph,
+        hitbox::{fit_bone_capsule, is_hitbox_body, make_hitbox},
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            Mesh,
+        },
+        node::Node,
+        rigidbody::{RigidBodyBuilder, RigidBodyType},
+        transform::TransformBuilder,
+    },
+};
+use std::sync::mpsc::Sender;
+
+/// A tag of rigid bodies created by the generator.
+pub const GENERATED_COLLIDER_TAG: &str = "GeneratedCollider";
+
+/// Kind of shape of generated colliders.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GeneratedShapeKind {
+    /// Triangle mesh that uses exact geometry of meshes. Merged meshes share single collider.
+    Trimesh,
+    /// Convex hull of each mesh. Merged meshes get a collider per mesh on a single rigid body,
+    /// which works as a simple convex decomposition of complex objects made of simple parts.
+    ConvexHull,
+    /// Box that encloses geometry of meshes.
+    Box,
+    /// Capsule that encloses geometry of meshes, it is oriented along the longest side of the
+    /// bounding box of the geometry.
+    Capsule,
+}
+
+impl GeneratedShapeKind {
+    const ALL: [GeneratedShapeKind; 4] = [
+        GeneratedShapeKind::Trimesh,
+        GeneratedShapeKind::ConvexHull,
+        GeneratedShapeKind::Box,
+        GeneratedShapeKind::Capsule,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            GeneratedShapeKind::Trimesh => "Trimesh",
+            GeneratedShapeKind::ConvexHull => "Convex Hull",
+            GeneratedShapeKind::Box => "Box",
+            GeneratedShapeKind::Capsule => "Capsule",
+        }
+    }
+}
+
+const BODY_TYPES: [(RigidBodyType, &str); 3] = [
+    (RigidBodyType::Static, "Static"),
+    (RigidBodyType::Dynamic, "Dynamic"),
+    (RigidBodyType::KinematicPositionBased, "Kinematic"),
+];
+
+#[derive(Clone, Debug)]
+pub struct ColliderGenerationOptions {
+    pub shape: GeneratedShapeKind,
+    pub body_type: RigidBodyType,
+    /// Whether to put all static meshes of a sub-graph into a single rigid body or not. Non-static
+    /// meshes always get their own rigid bodies.
+    pub merge_static_meshes: bool,
+}
+
+impl Default for ColliderGenerationOptions {
+    fn default() -> Self {
+        Self {
+            shape: GeneratedShapeKind::Trimesh,
+            body_type: RigidBodyType::Static,
+            merge_static_meshes: false,
+        }
+    }
+}
+
+/// Calculates bounding box of vertices of given meshes in the space defined by the inverse
+/// transform.
+fn meshes_bounds(
+    graph: &Graph,
+    meshes: &[Handle<Node>],
+    inv_transform: &Matrix4<f32>,
+) -> Option<AxisAlignedBoundingBox> {
+    let mut bounds = AxisAlignedBoundingBox::default();
+    let mut is_empty = true;
+
+    for &handle in meshes {
+        let mesh = graph[handle].as_mesh();
+        let transform = inv_transform * mesh.global_transform();
+        for surface in mesh.surfaces() {
+            let data = surface.data();
+            let data = data.lock();
+            for vertex in data.vertex_buffer.iter() {
+                if let Ok(position) = vertex.read_3_f32(VertexAttributeUsage::Position) {
+                    bounds.add_point(transform.transform_point(&Point3::from(position)).coords);
+                    is_empty = false;
+                }
+            }
+        }
+    }
+
+    if is_empty {
+        None
+    } else {
+        Some(bounds)
+    }
+}
+
+/// Creates colliders for given meshes, the colliders are not linked to anything.
+pub fn make_colliders(
+    graph: &mut Graph,
+    target: Handle<Node>,
+    meshes: &[Handle<Node>],
+    shape: GeneratedShapeKind,
+) -> Vec<Handle<Node>> {
+    // Primitives must not be too thin, otherwise the physics engine will have troubles with them.
+    const MIN_HALF_EXTENT: f32 = 0.001;
+
+    let name = format!("{}Collider", graph[target].name());
+
+    match shape {
+        GeneratedShapeKind::Trimesh => {
+            vec![ColliderBuilder::new(BaseBuilder::new().with_name(name))
+                .with_shape(ColliderShape::trimesh(
+                    meshes.iter().map(|&m| GeometrySource(m)).collect(),
+                ))
+                .build(graph)]
+        }
+        GeneratedShapeKind::ConvexHull => meshes
+            .iter()
+            .map(|&mesh| {
+                ColliderBuilder::new(BaseBuilder::new().with_name(&name))
+                    .with_shape(ColliderShape::Polyhedron(ConvexPolyhedronShape {
+                        geometry_source: GeometrySource(mesh),
+                    }))
+                    .build(graph)
+            })
+            .collect(),
+        GeneratedShapeKind::Box | GeneratedShapeKind::Capsule => {
+            // Generated rigid body has identity local transform, so its space is the isometric
+            // space of the target node.
+            let inv_body_transform = graph
+                .isometric_global_transform(target)
+                .try_inverse()
+                .unwrap_or_else(Matrix4::identity);
+
+            let bounds = match meshes_bounds(graph, meshes, &inv_body_transform) {
+                Some(bounds) => bounds,
+                None => return Vec::new(),
+            };
+
+            let center = bounds.center();
+            let half_extents = bounds.half_extents().map(|v| v.max(MIN_HALF_EXTENT));
+
+            let collider = if shape == GeneratedShapeKind::Box {
+                ColliderBuilder::new(BaseBuilder::new().with_name(name).with_local_transform(
+                    TransformBuilder::new().with_local_position(center).build(),
+                ))
+                .with_shape(ColliderShape::cuboid(
+                    half_extents.x,
+                    half_extents.y,
+                    half_extents.z,
+                ))
+            } else {
+                ColliderBuilder::new(BaseBuilder::new().with_name(name))
+                    .with_shape(ColliderShape::capsule_from_bounds(&bounds))
+            };
+
+            vec![collider.build(graph)]
+        }
+    }
+}
+
+/// Creates a command that generates rigid bodies with colliders for every mesh in the given
+/// sub-graphs, replacing previously generated ones. Returns `None` if there is nothing to
+/// generate.
+pub fn make_generate_colliders_command(
+    roots: &[Handle<Node>],
+    options: &ColliderGenerationOptions,
+    graph: &mut Graph,
+) -> Option<SceneCommand> {
+    let mut commands = Vec::new();
+
+    // Remove previously generated bodies first.
+    for &root in roots {
+        for handle in graph.traverse_handle_iter(root) {
+            if graph[handle].tag() == GENERATED_COLLIDER_TAG {
+                commands.push(SceneCommand::new(DeleteSubGraphCommand::new(handle)));
+            }
+        }
+    }
+
+    // Each group is a target node and meshes that will share a single rigid body, that will be
+    // linked to the target.
+    let mut groups = Vec::new();
+    for &root in roots {
+        let meshes = graph
+            .traverse_handle_iter(root)
+            .filter(|&handle| graph[handle].is_mesh())
+            .collect::<Vec<_>>();
+
+        if options.merge_static_meshes {
+            let (static_meshes, other_meshes): (Vec<_>, Vec<_>) = meshes
+                .into_iter()
+                .partition(|&handle| graph[handle].mobility() == Mobility::Static);
+            if !static_meshes.is_empty() {
+                groups.push((root, static_meshes));
+            }
+            groups.extend(other_meshes.into_iter().map(|mesh| (mesh, vec![mesh])));
+        } else {
+            groups.extend(meshes.into_iter().map(|mesh| (mesh, vec![mesh])));
+        }
+    }
+
+    for (target, meshes) in groups {
+        let colliders = make_colliders(graph, target, &meshes, options.shape);
+        if colliders.is_empty() {
+            continue;
+        }
+
+        let body = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_name(format!("{}Body", graph[target].name()))
+                .with_tag(GENERATED_COLLIDER_TAG.to_owned())
+                .with_children(&colliders),
+        )
+        .with_body_type(options.body_type)
+        .build(graph);
+
+        // Extract the body from the scene immediately, only commands are allowed to modify the
+        // scene.
+        let sub_graph = graph.take_reserve_sub_graph(body);
+        commands.push(SceneCommand::new(AddSubGraphCommand::new(
+            sub_graph, target,
+        )));
+    }
+
+    if commands.is_empty() {
+        None
+    } else {
+        Some(SceneCommand::new(CommandGroup::from(commands)))
+    }
+}
+
+/// Creates a command that generates hitboxes for bones. A selected node could be either a skinned
+/// mesh (hitboxes are generated for all its bones) or a bone of a skinned mesh. Previously
+/// generated hitboxes of the bones are replaced. Returns `None` if there is nothing to generate.
+pub fn make_generate_hitboxes_command(
+    nodes: &[Handle<Node>],
+    graph: &mut Graph,
+) -> Option<SceneCommand> {
+    let mesh_bones = |mesh: &Mesh| {
+        mesh.surfaces()
+            .iter()
+            .flat_map(|s| s.bones().iter().cloned())
+            .collect::<Vec<_>>()
+    };
+
+    // Pairs of a skinned mesh and its bone.
+    let mut bones = Vec::<(Handle<Node>, Handle<Node>)>::new();
+    for &node in nodes {
+        if let Some(mesh) = graph[node].cast::<Mesh>() {
+            for bone in mesh_bones(mesh) {
+                if !bones.iter().any(|(_, b)| *b == bone) {
+                    bones.push((node, bone));
+                }
+            }
+        } else if !bones.iter().any(|(_, b)| *b == node) {
+            let mesh = graph.pair_iter().find_map(|(handle, other)| {
+                other
+                    .cast::<Mesh>()
+                    .filter(|mesh| mesh_bones(mesh).contains(&node))
+                    .map(|_| handle)
+            });
+            if let Some(mesh) = mesh {
+                bones.push((mesh, node));
+            }
+        }
+    }
+
+    let mut commands = Vec::new();
+    for (mesh, bone) in bones {
+        for &child in graph[bone].children() {
+            if is_hitbox_body(graph, child) {
+                commands.push(SceneCommand::new(DeleteSubGraphCommand::new(child)));
+            }
+        }
+
+        if let Some(shape) = fit_bone_capsule(graph, mesh, bone) {
+            let name = format!("{}Hitbox", graph[bone].name());
+            let hitbox = make_hitbox(graph, &name, shape);
+
+            // Only commands are allowed to modify the scene.
+            let sub_graph = graph.take_reserve_sub_graph(hitbox);
+            commands.push(SceneCommand::new(AddSubGraphCommand::new(sub_graph, bone)));
+        }
+    }
+
+    if commands.is_empty() {
+        None
+    } else {
+        Some(SceneCommand::new(CommandGroup::from(commands)))
+    }
+}
+
+fn make_label(ctx: &mut BuildContext, row: usize, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(0)
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+pub struct ColliderGeneratorWindow {
+    pub window: Handle<UiNode>,
+    shape: Handle<UiNode>,
+    body_type: Handle<UiNode>,
+    merge_static_meshes: Handle<UiNode>,
+    generate: Handle<UiNode>,
+    cancel: Handle<UiNode>,
+    options: ColliderGenerationOptions,
+}
+
+impl ColliderGeneratorWindow {
+    pub fn new(engine: &mut GameEngine) -> Self {
+        let shape;
+        let body_type;
+        let merge_static_meshes;
+        let generate;
+        let cancel;
+        let ctx = &mut engine.user_interface.build_ctx();
+        let options = ColliderGenerationOptions::default();
+
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(150.0))
+            .with_title(WindowTitle::text("Generate Collider"))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(make_label(ctx, 0, "Shape"))
+                        .with_child({
+                            shape = DropdownListBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_items(
+                                GeneratedShapeKind::ALL
+                                    .iter()
+                                    .map(|kind| make_dropdown_list_option(ctx, kind.name()))
+                                    .collect(),
+                            )
+                            .with_selected(0)
+                            .build(ctx);
+                            shape
+                        })
+                        .with_child(make_label(ctx, 1, "Body Type"))
+                        .with_child({
+                            body_type = DropdownListBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_items(
+                                BODY_TYPES
+                                    .iter()
+                                    .map(|(_, name)| make_dropdown_list_option(ctx, name))
+                                    .collect(),
+                            )
+                            .with_selected(0)
+                            .build(ctx);
+                            body_type
+                        })
+                        .with_child(make_label(ctx, 2, "Merge Static Meshes"))
+                        .with_child({
+                            merge_static_meshes = CheckBoxBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .checked(Some(options.merge_static_meshes))
+                            .build(ctx);
+                            merge_static_meshes
+                        })
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(4)
+                                    .on_column(1)
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .with_child({
+                                        generate = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(1.0))
+                                                .with_width(80.0),
+                                        )
+                                        .with_text("Generate")
+                                        .build(ctx);
+                                        generate
+                                    })
+                                    .with_child({
+                                        cancel = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(1.0))
+                                                .with_width(80.0),
+                                        )
+                                        .with_text("Cancel")
+                                        .build(ctx);
+                                        cancel
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_column(Column::strict(130.0))
+                .add_column(Column::stretch())
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::stretch())
+                .add_row(Row::strict(25.0))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            shape,
+            body_type,
+            merge_static_meshes,
+            generate,
+            cancel,
+            options,
+        }
+    }
+
+    pub fn open(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::open_modal(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    fn close(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
+        scope_profile!();
+
+        if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.generate {
+                if let Selection::Graph(selection) = &editor_scene.selection {
+                    let graph = &mut engine.scenes[editor_scene.scene].graph;
+                    let roots = selection.root_nodes(graph);
+                    if let Some(command) =
+                        make_generate_colliders_command(&roots, &self.options, graph)
+                    {
+                        sender.send(Message::DoSceneCommand(command)).unwrap();
+                    }
+                }
+                self.close(&engine.user_interface);
+            } else if message.destination() == self.cancel {
+                self.close(&engine.user_interface);
+            }
+        } else if let Some(&DropdownListMessage::SelectionChanged(Some(index))) =
+            message.data::<DropdownListMessage>()
+        {
+            if message.direction() == MessageDirection::FromWidget {
+                if message.destination() == self.shape {
+                    self.options.shape = GeneratedShapeKind::ALL[index];
+                } else if message.destination() == self.body_type {
+                    self.options.body_type = BODY_TYPES[index].0;
+                }
+            }
+        } else if let Some(&CheckBoxMessage::Check(Some(value))) = message.data::<CheckBoxMessage>()
+        {
+            if message.direction() == MessageDirection::FromWidget
+                && message.destination() == self.merge_static_meshes
+            {
+                self.options.merge_static_meshes = value;
+            }
+        }
+    }
+}
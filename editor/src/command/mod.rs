@@ -3,6 +3,14 @@ use std::fmt::Debug;
 
 pub mod panel;
 
+/// Description of an executed command, captured at the moment of execution.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CommandHistoryEntry {
+    pub name: String,
+    /// Names of sub-commands, non-empty only for grouped commands.
+    pub children: Vec<String>,
+}
+
 #[macro_export]
 macro_rules! define_command_stack {
     ($command_trait:ident, $command_stack:ident, $context:ty) => {
@@ -10,19 +18,33 @@ macro_rules! define_command_stack {
             fn name(&mut self, context: &$context) -> String;
             fn execute(&mut self, context: &mut $context);
             fn revert(&mut self, context: &mut $context);
+            /// Fallible version of `execute`, it is used by the command stack and command groups.
+            /// Commands that could fail must override it and leave the context intact on failure,
+            /// failed commands are never put on the stack.
+            fn try_execute(&mut self, context: &mut $context) -> Result<(), String> {
+                self.execute(context);
+                Ok(())
+            }
             fn finalize(&mut self, _: &mut $context) {}
+            /// Returns names of sub-commands, must be implemented for grouped commands only.
+            fn sub_command_names(&mut self, _: &$context) -> Vec<String> {
+                Default::default()
+            }
         }
 
         pub struct $command_stack {
             commands: Vec<Box<dyn $command_trait>>,
+            history: Vec<$crate::command::CommandHistoryEntry>,
             top: Option<usize>,
             debug: bool,
         }
 
+        #[allow(dead_code)]
         impl $command_stack {
             pub fn new(debug: bool) -> Self {
                 Self {
                     commands: Default::default(),
+                    history: Default::default(),
                     top: None,
                     debug,
                 }
@@ -44,6 +66,7 @@ macro_rules! define_command_stack {
                     // Drop everything after top.
                     let top = self.top.unwrap_or(0);
                     if top < self.commands.len() {
+                        self.history.truncate(top);
                         for mut dropped_command in self.commands.drain(top..) {
                             if self.debug {
                                 println!("Finalizing command {:?}", dropped_command);
@@ -57,9 +80,49 @@ macro_rules! define_command_stack {
                     println!("Executing command {:?}", command);
                 }
 
-                command.execute(&mut context);
+                // Names must be captured before execution, because some commands move their
+                // data into scene on execution.
+                let entry = $crate::command::CommandHistoryEntry {
+                    name: command.name(&context),
+                    children: command.sub_command_names(&context),
+                };
+
+                if let Err(error) = command.try_execute(&mut context) {
+                    fyrox::utils::log::Log::err(format!(
+                        "Unable to execute {} command. Reason: {}",
+                        entry.name, error
+                    ));
+                    // Redo commands are already dropped at this point, so the stack is restored
+                    // without them.
+                    self.top = self.commands.len().checked_sub(1);
+                    return;
+                }
 
                 self.commands.push(command);
+                self.history.push(entry);
+            }
+
+            /// Returns a list of executed commands in order of execution.
+            pub fn history(&self) -> &[$crate::command::CommandHistoryEntry] {
+                &self.history
+            }
+
+            /// Returns amount of commands that are currently applied, it is in `[0; history.len()]`
+            /// range.
+            pub fn position(&self) -> usize {
+                if self.commands.is_empty() {
+                    0
+                } else {
+                    self.top.map_or(0, |top| top + 1)
+                }
+            }
+
+            pub fn can_undo(&self) -> bool {
+                self.position() > 0
+            }
+
+            pub fn can_redo(&self) -> bool {
+                self.position() < self.commands.len()
             }
 
             pub fn undo(&mut self, mut context: $context) {
@@ -80,34 +143,41 @@ macro_rules! define_command_stack {
                 }
             }
 
+            /// Executes the next undone command. If the command fails, the context is left as
+            /// is and the command is dropped from the stack together with every command after it,
+            /// because they were done on top of it.
             pub fn redo(&mut self, mut context: $context) {
-                if !self.commands.is_empty() {
-                    let command = match self.top.as_mut() {
-                        None => {
-                            self.top = Some(0);
-                            self.commands.first_mut()
-                        }
-                        Some(top) => {
-                            let last = self.commands.len() - 1;
-                            if *top < last {
-                                *top += 1;
-                                self.commands.get_mut(*top)
-                            } else {
-                                None
-                            }
-                        }
-                    };
+                let index = self.position();
+                if let Some(command) = self.commands.get_mut(index) {
+                    if self.debug {
+                        println!("Redo command {:?}", command);
+                    }
 
-                    if let Some(command) = command {
-                        if self.debug {
-                            println!("Redo command {:?}", command);
+                    if let Err(error) = command.try_execute(&mut context) {
+                        fyrox::utils::log::Log::err(format!(
+                            "Unable to redo {} command. Reason: {}",
+                            self.history
+                                .get(index)
+                                .map_or("", |entry| entry.name.as_str()),
+                            error
+                        ));
+                        self.history.truncate(index);
+                        for mut dropped_command in self.commands.drain(index..) {
+                            if self.debug {
+                                println!("Finalizing command {:?}", dropped_command);
+                            }
+                            dropped_command.finalize(&mut context);
                         }
-                        command.execute(&mut context)
+                        return;
                     }
+
+                    self.top = Some(index);
                 }
             }
 
             pub fn clear(&mut self, mut context: $context) {
+                self.top = None;
+                self.history.clear();
                 for mut dropped_command in self.commands.drain(..) {
                     if self.debug {
                         println!("Finalizing command {:?}", dropped_command);
@@ -119,4 +189,341 @@ macro_rules! define_command_stack {
     };
 }
 
+/// Defines a command that owns a list of child commands and applies them as a single step of
+/// a command stack. Children are executed in order and reverted in reverse order, groups could be
+/// nested.
+///
+/// If a child fails (returns an error from `try_execute` or panics), every child executed before it
+/// is reverted and the whole group fails, so the group is never put on a command stack partially
+/// executed. A panicking child is unable to clean after itself, so it must not leave the context
+/// in a broken state before the panic. The same applies on redo, a command stack drops a group that
+/// failed to be executed again.
+#[macro_export]
+macro_rules! define_command_group {
+    ($command_trait:ident, $command_group:ident, $command:ty, $context:ty) => {
+        #[derive(Debug, Default)]
+        pub struct $command_group {
+            commands: Vec<$command>,
+            custom_name: Option<String>,
+        }
+
+        impl From<Vec<$command>> for $command_group {
+            fn from(commands: Vec<$command>) -> Self {
+                Self {
+                    commands,
+                    custom_name: None,
+                }
+            }
+        }
+
+        #[allow(dead_code)]
+        impl $command_group {
+            /// Sets a name that is used instead of the list of names of child commands, for example
+            /// "Paste 12 Nodes".
+            pub fn with_custom_name<S: AsRef<str>>(mut self, name: S) -> Self {
+                self.custom_name = Some(name.as_ref().to_owned());
+                self
+            }
+
+            pub fn push(&mut self, command: $command) {
+                self.commands.push(command)
+            }
+
+            pub fn len(&self) -> usize {
+                self.commands.len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.commands.is_empty()
+            }
+        }
+
+        impl $command_trait for $command_group {
+            fn name(&mut self, context: &$context) -> String {
+                match self.custom_name.as_ref() {
+                    Some(name) => name.clone(),
+                    None => format!(
+                        "Command group: {}",
+                        self.commands
+                            .iter_mut()
+                            .map(|cmd| cmd.name(context))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                }
+            }
+
+            fn execute(&mut self, context: &mut $context) {
+                if let Err(error) = self.try_execute(context) {
+                    fyrox::utils::log::Log::err(format!(
+                        "Unable to execute command group. Reason: {}",
+                        error
+                    ));
+                }
+            }
+
+            fn try_execute(&mut self, context: &mut $context) -> Result<(), String> {
+                let mut executed = 0;
+                let mut failure = None;
+                for cmd in self.commands.iter_mut() {
+                    // Names must be captured before execution, because some commands move their
+                    // data into the context on execution.
+                    let name = cmd.name(context);
+                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        cmd.try_execute(context)
+                    }))
+                    .unwrap_or_else(|_| Err("the command has panicked".to_owned()));
+                    match result {
+                        Ok(()) => executed += 1,
+                        Err(error) => {
+                            failure = Some(format!("{} failed: {}", name, error));
+                            break;
+                        }
+                    }
+                }
+
+                match failure {
+                    Some(error) => {
+                        // Roll back everything that was done, revert must be done in reverse order.
+                        for cmd in self.commands[..executed].iter_mut().rev() {
+                            cmd.revert(context);
+                        }
+                        Err(error)
+                    }
+                    None => Ok(()),
+                }
+            }
+
+            fn revert(&mut self, context: &mut $context) {
+                // revert must be done in reverse order.
+                for cmd in self.commands.iter_mut().rev() {
+                    cmd.revert(context);
+                }
+            }
+
+            fn finalize(&mut self, context: &mut $context) {
+                // Nested groups finalize their own children.
+                for mut cmd in self.commands.drain(..) {
+                    cmd.finalize(context);
+                }
+            }
+
+            fn sub_command_names(&mut self, context: &$context) -> Vec<String> {
+                self.commands
+                    .iter_mut()
+                    .map(|cmd| cmd.name(context))
+                    .collect()
+            }
+        }
+    };
+}
+
 define_command_stack!(Command, CommandStack, SceneContext);
+
+#[cfg(test)]
+mod test {
+    use std::fmt::Debug;
+
+    #[derive(Debug)]
+    pub struct TestContext<'a> {
+        log: &'a mut Vec<String>,
+    }
+
+    define_command_stack!(TestCommand, TestCommandStack, TestContext);
+    define_command_group!(
+        TestCommand,
+        TestCommandGroup,
+        Box<dyn TestCommand>,
+        TestContext
+    );
+
+    #[derive(Debug)]
+    enum Behavior {
+        Succeed,
+        Fail,
+        Panic,
+        // Succeeds only the first time, so the command could be done, but not redone.
+        FailOnRedo,
+    }
+
+    #[derive(Debug)]
+    struct RecordCommand {
+        name: &'static str,
+        behavior: Behavior,
+        executed: bool,
+    }
+
+    impl TestCommand for RecordCommand {
+        fn name(&mut self, _: &TestContext) -> String {
+            self.name.to_owned()
+        }
+
+        fn execute(&mut self, context: &mut TestContext) {
+            context.log.push(format!("+{}", self.name));
+        }
+
+        fn revert(&mut self, context: &mut TestContext) {
+            context.log.push(format!("-{}", self.name));
+        }
+
+        fn try_execute(&mut self, context: &mut TestContext) -> Result<(), String> {
+            match self.behavior {
+                Behavior::Succeed => {
+                    self.execute(context);
+                    Ok(())
+                }
+                Behavior::Fail => Err("failure".to_owned()),
+                Behavior::Panic => panic!("{} panicked", self.name),
+                Behavior::FailOnRedo => {
+                    if self.executed {
+                        Err("failure".to_owned())
+                    } else {
+                        self.executed = true;
+                        self.execute(context);
+                        Ok(())
+                    }
+                }
+            }
+        }
+
+        fn finalize(&mut self, context: &mut TestContext) {
+            context.log.push(format!("~{}", self.name));
+        }
+    }
+
+    fn command(name: &'static str) -> Box<dyn TestCommand> {
+        Box::new(RecordCommand {
+            name,
+            behavior: Behavior::Succeed,
+            executed: false,
+        })
+    }
+
+    fn failing(name: &'static str, behavior: Behavior) -> Box<dyn TestCommand> {
+        Box::new(RecordCommand {
+            name,
+            behavior,
+            executed: false,
+        })
+    }
+
+    fn group(commands: Vec<Box<dyn TestCommand>>) -> Box<dyn TestCommand> {
+        Box::new(TestCommandGroup::from(commands))
+    }
+
+    fn context(log: &mut Vec<String>) -> TestContext {
+        TestContext { log }
+    }
+
+    fn take_log(log: &mut Vec<String>) -> Vec<String> {
+        std::mem::take(log)
+    }
+
+    #[test]
+    fn test_nested_group_order() {
+        let mut stack = TestCommandStack::new(false);
+        let mut log = Vec::new();
+
+        stack.do_command(
+            group(vec![
+                command("a"),
+                group(vec![command("b"), command("c")]),
+                command("d"),
+            ]),
+            context(&mut log),
+        );
+        assert_eq!(take_log(&mut log), ["+a", "+b", "+c", "+d"]);
+        assert_eq!(stack.position(), 1);
+
+        stack.undo(context(&mut log));
+        assert_eq!(take_log(&mut log), ["-d", "-c", "-b", "-a"]);
+
+        stack.redo(context(&mut log));
+        assert_eq!(take_log(&mut log), ["+a", "+b", "+c", "+d"]);
+
+        // Finalization must reach children of nested groups.
+        stack.clear(context(&mut log));
+        assert_eq!(take_log(&mut log), ["~a", "~b", "~c", "~d"]);
+    }
+
+    #[test]
+    fn test_group_failure_rollback() {
+        let mut stack = TestCommandStack::new(false);
+        let mut log = Vec::new();
+
+        stack.do_command(command("x"), context(&mut log));
+        take_log(&mut log);
+
+        stack.do_command(
+            group(vec![
+                command("a"),
+                group(vec![command("b"), failing("c", Behavior::Fail)]),
+                command("d"),
+            ]),
+            context(&mut log),
+        );
+        assert_eq!(take_log(&mut log), ["+a", "+b", "-b", "-a"]);
+
+        // Failed group must not be on the stack.
+        assert_eq!(stack.position(), 1);
+        assert_eq!(stack.history().len(), 1);
+        assert!(!stack.can_redo());
+
+        stack.undo(context(&mut log));
+        assert_eq!(take_log(&mut log), ["-x"]);
+        assert!(!stack.can_undo());
+    }
+
+    #[test]
+    fn test_group_panic_rollback() {
+        let mut stack = TestCommandStack::new(false);
+        let mut log = Vec::new();
+
+        stack.do_command(
+            group(vec![command("a"), failing("b", Behavior::Panic)]),
+            context(&mut log),
+        );
+        assert_eq!(take_log(&mut log), ["+a", "-a"]);
+        assert_eq!(stack.position(), 0);
+        assert!(stack.history().is_empty());
+    }
+
+    #[test]
+    fn test_group_redo_failure() {
+        let mut stack = TestCommandStack::new(false);
+        let mut log = Vec::new();
+
+        stack.do_command(command("x"), context(&mut log));
+        stack.do_command(
+            group(vec![command("a"), failing("b", Behavior::FailOnRedo)]),
+            context(&mut log),
+        );
+        stack.do_command(command("y"), context(&mut log));
+        stack.undo(context(&mut log));
+        stack.undo(context(&mut log));
+        assert_eq!(take_log(&mut log), ["+x", "+a", "+b", "+y", "-y", "-b", "-a"]);
+
+        // Failed group is rolled back and dropped along with the commands done after it.
+        stack.redo(context(&mut log));
+        assert_eq!(take_log(&mut log), ["+a", "-a", "~a", "~b", "~y"]);
+        assert_eq!(stack.position(), 1);
+        assert_eq!(stack.history().len(), 1);
+        assert!(!stack.can_redo());
+
+        stack.undo(context(&mut log));
+        assert_eq!(take_log(&mut log), ["-x"]);
+    }
+
+    #[test]
+    fn test_group_name() {
+        let mut log = Vec::new();
+        let context = context(&mut log);
+
+        let mut group = TestCommandGroup::from(vec![command("a"), command("b")]);
+        assert_eq!(group.name(&context), "Command group: a, b");
+        assert_eq!(group.sub_command_names(&context), ["a", "b"]);
+
+        let mut group = group.with_custom_name("Paste 2 Nodes");
+        assert_eq!(group.name(&context), "Paste 2 Nodes");
+    }
+}
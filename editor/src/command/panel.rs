@@ -1,7 +1,7 @@
 use crate::utils::window_content;
 use crate::{
-    command::CommandStack, load_image, scene::commands::SceneContext, send_sync_message, Message,
-    Mode,
+    command::{CommandHistoryEntry, CommandStack},
+    load_image, send_sync_message, Message, Mode,
 };
 use fyrox::gui::widget::WidgetMessage;
 use fyrox::{
@@ -11,17 +11,21 @@ use fyrox::{
         button::{ButtonBuilder, ButtonMessage},
         grid::{Column, GridBuilder, Row},
         image::ImageBuilder,
-        list_view::{ListViewBuilder, ListViewMessage},
         message::{MessageDirection, UiMessage},
+        progress_bar::{ProgressBarBuilder, ProgressBarMessage},
         scroll_viewer::ScrollViewerBuilder,
         stack_panel::StackPanelBuilder,
         text::TextBuilder,
+        tree::{TreeBuilder, TreeRootBuilder, TreeRootMessage},
         widget::WidgetBuilder,
         window::{WindowBuilder, WindowTitle},
         BuildContext, Orientation, Thickness, UiNode, UserInterface,
     },
 };
-use std::sync::mpsc::Sender;
+use std::{rc::Rc, sync::mpsc::Sender};
+
+/// Position in the command stack, which is represented by an item in the history list.
+struct HistoryPosition(usize);
 
 pub struct CommandStackViewer {
     pub window: Handle<UiNode>,
@@ -30,6 +34,40 @@ pub struct CommandStackViewer {
     undo: Handle<UiNode>,
     redo: Handle<UiNode>,
     clear: Handle<UiNode>,
+    progress_bar: Handle<UiNode>,
+}
+
+fn make_history_item(
+    ctx: &mut BuildContext,
+    entry: &CommandHistoryEntry,
+    position: usize,
+    brush: Brush,
+) -> Handle<UiNode> {
+    let children = entry
+        .children
+        .iter()
+        .map(|name| {
+            TreeBuilder::new(
+                WidgetBuilder::new().with_user_data(Rc::new(HistoryPosition(position))),
+            )
+            .with_content(
+                TextBuilder::new(WidgetBuilder::new().with_foreground(brush.clone()))
+                    .with_text(name)
+                    .build(ctx),
+            )
+            .build(ctx)
+        })
+        .collect();
+
+    TreeBuilder::new(WidgetBuilder::new().with_user_data(Rc::new(HistoryPosition(position))))
+        .with_expanded(false)
+        .with_items(children)
+        .with_content(
+            TextBuilder::new(WidgetBuilder::new().with_foreground(brush))
+                .with_text(&entry.name)
+                .build(ctx),
+        )
+        .build(ctx)
 }
 
 impl CommandStackViewer {
@@ -38,8 +76,9 @@ impl CommandStackViewer {
         let undo;
         let redo;
         let clear;
+        let progress_bar;
         let window = WindowBuilder::new(WidgetBuilder::new())
-            .with_title(WindowTitle::Text("Command Stack".to_owned()))
+            .with_title(WindowTitle::Text("History".to_owned()))
             .with_content(
                 GridBuilder::new(
                     WidgetBuilder::new()
@@ -114,15 +153,27 @@ impl CommandStackViewer {
                                     .on_row(1),
                             )
                             .with_content({
-                                list = ListViewBuilder::new(WidgetBuilder::new()).build(ctx);
+                                list = TreeRootBuilder::new(WidgetBuilder::new()).build(ctx);
                                 list
                             })
                             .build(ctx),
-                        ),
+                        )
+                        .with_child({
+                            progress_bar = ProgressBarBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_visibility(false)
+                                    .with_height(16.0)
+                                    .with_margin(Thickness::uniform(1.0))
+                                    .on_row(2),
+                            )
+                            .build(ctx);
+                            progress_bar
+                        }),
                 )
                 .add_column(Column::stretch())
                 .add_row(Row::strict(26.0))
                 .add_row(Row::stretch())
+                .add_row(Row::auto())
                 .build(ctx),
             )
             .build(ctx);
@@ -134,10 +185,11 @@ impl CommandStackViewer {
             undo,
             redo,
             clear,
+            progress_bar,
         }
     }
 
-    pub fn handle_ui_message(&self, message: &UiMessage) {
+    pub fn handle_ui_message(&self, message: &UiMessage, ui: &UserInterface) {
         scope_profile!();
 
         if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
@@ -148,44 +200,100 @@ impl CommandStackViewer {
             } else if message.destination() == self.clear {
                 self.sender.send(Message::ClearSceneCommandStack).unwrap();
             }
+        } else if let Some(TreeRootMessage::Selected(selection)) = message.data::<TreeRootMessage>()
+        {
+            if message.destination() == self.list
+                && message.direction() == MessageDirection::FromWidget
+            {
+                if let Some(HistoryPosition(position)) = selection
+                    .first()
+                    .and_then(|&item| ui.try_get_node(item))
+                    .and_then(|item| item.user_data_ref::<HistoryPosition>())
+                {
+                    self.sender
+                        .send(Message::JumpToSceneCommand(*position))
+                        .unwrap();
+                }
+            }
         }
     }
 
-    pub fn sync_to_model(
-        &mut self,
-        command_stack: &mut CommandStack,
-        ctx: &SceneContext,
-        ui: &mut UserInterface,
-    ) {
+    pub fn sync_to_model(&self, command_stack: &CommandStack, ui: &mut UserInterface) {
         scope_profile!();
 
-        let top = command_stack.top;
-        let items = command_stack
-            .commands
-            .iter_mut()
-            .enumerate()
-            .rev() // First command in list is last on stack.
-            .map(|(i, cmd)| {
-                let brush = if let Some(top) = top {
-                    if (0..=top).contains(&i) {
-                        Brush::Solid(Color::opaque(255, 255, 255))
-                    } else {
-                        Brush::Solid(Color::opaque(100, 100, 100))
-                    }
-                } else {
-                    Brush::Solid(Color::opaque(100, 100, 100))
-                };
-
-                TextBuilder::new(WidgetBuilder::new().with_foreground(brush))
-                    .with_text(cmd.name(ctx))
-                    .build(&mut ui.build_ctx())
-            })
-            .collect();
+        let position = command_stack.position();
+
+        let ctx = &mut ui.build_ctx();
+        // An item at `i` represents `i` applied commands.
+        let mut items = vec![TreeBuilder::new(
+            WidgetBuilder::new().with_user_data(Rc::new(HistoryPosition(0))),
+        )
+        .with_content(
+            TextBuilder::new(WidgetBuilder::new())
+                .with_text("<Initial State>")
+                .build(ctx),
+        )
+        .build(ctx)];
+        for (i, entry) in command_stack.history().iter().enumerate() {
+            let brush = if i < position {
+                Brush::Solid(Color::opaque(255, 255, 255))
+            } else {
+                Brush::Solid(Color::opaque(100, 100, 100))
+            };
+
+            items.push(make_history_item(ctx, entry, i + 1, brush));
+        }
+
+        // First command in list is last on stack.
+        let list_items = items.iter().rev().cloned().collect();
+
+        send_sync_message(
+            ui,
+            TreeRootMessage::items(self.list, MessageDirection::ToWidget, list_items),
+        );
+
+        // Highlight current position.
+        send_sync_message(
+            ui,
+            TreeRootMessage::select(
+                self.list,
+                MessageDirection::ToWidget,
+                vec![items[position.min(items.len() - 1)]],
+            ),
+        );
 
         send_sync_message(
             ui,
-            ListViewMessage::items(self.list, MessageDirection::ToWidget, items),
+            WidgetMessage::enabled(
+                self.undo,
+                MessageDirection::ToWidget,
+                command_stack.can_undo(),
+            ),
         );
+        send_sync_message(
+            ui,
+            WidgetMessage::enabled(
+                self.redo,
+                MessageDirection::ToWidget,
+                command_stack.can_redo(),
+            ),
+        );
+    }
+
+    /// Shows progress of a jump in the history, `None` hides progress bar.
+    pub fn set_jump_progress(&self, ui: &UserInterface, progress: Option<f32>) {
+        ui.send_message(WidgetMessage::visibility(
+            self.progress_bar,
+            MessageDirection::ToWidget,
+            progress.is_some(),
+        ));
+        if let Some(progress) = progress {
+            ui.send_message(ProgressBarMessage::progress(
+                self.progress_bar,
+                MessageDirection::ToWidget,
+                progress,
+            ));
+        }
     }
 
     pub fn on_mode_changed(&mut self, ui: &UserInterface, mode: &Mode) {
@@ -0,0 +1,501 @@
+//! Crash handling of the editor.
+//!
+//! The editor installs a panic hook that remembers the panic message, its location and backtrace.
+//! When a panic unwinds to the event loop, every open scene is saved into a timestamped folder
+//! inside [`RECOVERY_FOLDER`] (the same purified copy that is written by normal saves) and a crash
+//! report is written next to it. On next startup the editor offers to open the recovered scenes
+//! and to show the report.
+
+use crate::{scene::EditorScene, GameEngine, Message};
+use fyrox::{
+    core::{parking_lot::Mutex, pool::Handle},
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        formatted_text::WrapMode,
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        scroll_viewer::ScrollViewerBuilder,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+    },
+    utils::log::Log,
+};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Write,
+    fs::{self, File},
+    panic::AssertUnwindSafe,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Name of the folder (relative to the directory the editor was launched from) with recovered
+/// scenes and crash reports.
+pub const RECOVERY_FOLDER: &str = "recovery";
+
+const CRASH_FOLDER_PREFIX: &str = "crash_";
+const REPORT_FILE: &str = "crash_report.txt";
+const INFO_FILE: &str = "recovery.ron";
+const REVIEWED_MARKER: &str = ".reviewed";
+
+/// Scenes are not saved anymore when recovery takes longer than this, the editor is in a broken
+/// state and must not hang forever on exit.
+const RECOVERY_TIME_LIMIT: Duration = Duration::from_secs(10);
+
+struct PanicRecord {
+    message: String,
+    location: String,
+    backtrace: String,
+}
+
+lazy_static! {
+    static ref LAST_PANIC: Mutex<Option<PanicRecord>> = Mutex::new(None);
+    static ref RECOVERY_ROOT: Mutex<PathBuf> = Mutex::new(PathBuf::from(RECOVERY_FOLDER));
+}
+
+// Set while scenes are saved, panics during recovery must not overwrite the original panic and
+// must not start another recovery.
+static IS_RECOVERING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecoveredScene {
+    /// Path of the scene at the moment of the crash, `None` if the scene was never saved.
+    pub original: Option<PathBuf>,
+    pub recovered: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecoveryInfo {
+    pub working_directory: PathBuf,
+    pub scenes: Vec<RecoveredScene>,
+}
+
+// `PanicInfo` is deprecated in favor of `PanicHookInfo`, which is not available in the minimal
+// supported version of Rust.
+#[allow(deprecated)]
+fn panic_message(info: &std::panic::PanicInfo) -> String {
+    if let Some(message) = info.payload().downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = info.payload().downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic payload".to_owned()
+    }
+}
+
+/// Installs the panic hook of the editor, the previous hook is still called after the panic is
+/// recorded. Must be called before the working directory of the editor is changed, the recovery
+/// folder is placed in the current directory.
+pub fn install_panic_hook() {
+    if let Ok(current_dir) = std::env::current_dir() {
+        *RECOVERY_ROOT.lock() = current_dir.join(RECOVERY_FOLDER);
+    }
+
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if !IS_RECOVERING.load(Ordering::SeqCst) {
+            if let Some(mut last_panic) = LAST_PANIC.try_lock_for(Duration::from_millis(100)) {
+                *last_panic = Some(PanicRecord {
+                    message: panic_message(info),
+                    location: info.location().map_or_else(
+                        || "Unknown".to_owned(),
+                        |l| format!("{}:{}:{}", l.file(), l.line(), l.column()),
+                    ),
+                    backtrace: format!("{:?}", backtrace::Backtrace::new()),
+                });
+            }
+        }
+
+        previous(info);
+    }));
+}
+
+fn recovery_root() -> PathBuf {
+    RECOVERY_ROOT.lock().clone()
+}
+
+fn write_report(
+    path: &Path,
+    timestamp: u64,
+    active_scene: Option<&Path>,
+    info: &RecoveryInfo,
+) -> std::io::Result<()> {
+    let mut report = String::new();
+
+    let _ = writeln!(report, "Fyroxed has crashed!");
+    let _ = writeln!(report, "Editor version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "Time: {} (seconds since Unix epoch)", timestamp);
+    let _ = writeln!(
+        report,
+        "Working directory: {}",
+        info.working_directory.display()
+    );
+    let _ = writeln!(
+        report,
+        "Active scene: {}",
+        active_scene.map_or_else(|| "Unsaved".to_owned(), |p| p.display().to_string())
+    );
+
+    match LAST_PANIC
+        .try_lock_for(Duration::from_millis(100))
+        .as_deref()
+    {
+        Some(Some(panic)) => {
+            let _ = writeln!(report, "\nPanic: {}", panic.message);
+            let _ = writeln!(report, "Location: {}", panic.location);
+            let _ = writeln!(report, "\nBacktrace:\n{}", panic.backtrace);
+        }
+        _ => {
+            let _ = writeln!(report, "\nPanic: Unknown");
+        }
+    }
+
+    let _ = writeln!(report, "\nRecovered scenes:");
+    if info.scenes.is_empty() {
+        let _ = writeln!(report, "None");
+    }
+    for scene in info.scenes.iter() {
+        let _ = writeln!(
+            report,
+            "{} -> {}",
+            scene
+                .original
+                .as_ref()
+                .map_or_else(|| "Unsaved".to_owned(), |p| p.display().to_string()),
+            scene.recovered.display()
+        );
+    }
+
+    let _ = writeln!(report, "\nLast log messages:");
+    for message in Log::history() {
+        let _ = writeln!(report, "{}", message);
+    }
+
+    fs::write(path, report)
+}
+
+/// Saves given scenes into a new timestamped recovery folder and writes the crash report there.
+/// A scene that fails to save (or panics while saving) is skipped. Returns the recovery folder,
+/// or `None` if recovery is already in progress or the folder can't be created.
+pub fn recover(
+    scenes: &[&EditorScene],
+    active_scene: Option<&Path>,
+    engine: &mut GameEngine,
+) -> Option<PathBuf> {
+    if IS_RECOVERING.swap(true, Ordering::SeqCst) {
+        return None;
+    }
+
+    let start = Instant::now();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+
+    let folder = recovery_root().join(format!("{}{}", CRASH_FOLDER_PREFIX, timestamp));
+    if let Err(e) = fs::create_dir_all(&folder) {
+        Log::err(format!(
+            "Unable to create recovery folder {}. Reason: {}",
+            folder.display(),
+            e
+        ));
+        return None;
+    }
+
+    let mut info = RecoveryInfo {
+        working_directory: std::env::current_dir().unwrap_or_default(),
+        scenes: Default::default(),
+    };
+
+    for (i, scene) in scenes.iter().enumerate() {
+        // Saving can't be interrupted, so the limit is checked only between scenes.
+        if start.elapsed() > RECOVERY_TIME_LIMIT {
+            Log::err("Recovery time limit exceeded, the rest of scenes is skipped.".to_owned());
+            break;
+        }
+
+        let name = scene
+            .path
+            .as_ref()
+            .and_then(|p| p.file_stem())
+            .map_or_else(|| "unnamed".to_owned(), |s| s.to_string_lossy().to_string());
+        let path = folder.join(format!("{}_{}.rgs", i, name));
+
+        match std::panic::catch_unwind(AssertUnwindSafe(|| scene.save_copy(&path, engine))) {
+            Ok(Ok(())) => info.scenes.push(RecoveredScene {
+                original: scene.path.clone(),
+                recovered: path,
+            }),
+            Ok(Err(e)) => Log::err(format!(
+                "Unable to save recovery scene {}. Reason: {}",
+                path.display(),
+                e
+            )),
+            Err(_) => Log::err(format!(
+                "Unable to save recovery scene {}. Saving has panicked.",
+                path.display()
+            )),
+        }
+    }
+
+    if let Err(e) = write_report(&folder.join(REPORT_FILE), timestamp, active_scene, &info) {
+        Log::err(format!("Unable to write crash report. Reason: {}", e));
+    }
+
+    match File::create(folder.join(INFO_FILE)) {
+        Ok(file) => {
+            if let Err(e) = ron::ser::to_writer_pretty(file, &info, PrettyConfig::default()) {
+                Log::err(format!("Unable to write recovery info. Reason: {}", e));
+            }
+        }
+        Err(e) => Log::err(format!("Unable to write recovery info. Reason: {}", e)),
+    }
+
+    Log::info(format!(
+        "Recovery data was written to {}.",
+        folder.display()
+    ));
+
+    Some(folder)
+}
+
+/// Searches for the latest recovery folder that was not reviewed by the user yet.
+pub fn find_unreviewed_recovery() -> Option<(PathBuf, RecoveryInfo)> {
+    fs::read_dir(recovery_root())
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let timestamp = path
+                .file_name()?
+                .to_str()?
+                .strip_prefix(CRASH_FOLDER_PREFIX)?
+                .parse::<u64>()
+                .ok()?;
+            if path.join(REVIEWED_MARKER).exists() {
+                return None;
+            }
+            let info: RecoveryInfo =
+                ron::de::from_reader(File::open(path.join(INFO_FILE)).ok()?).ok()?;
+            Some((timestamp, path, info))
+        })
+        .max_by_key(|(timestamp, _, _)| *timestamp)
+        .map(|(_, path, info)| (path, info))
+}
+
+fn mark_reviewed(folder: &Path) {
+    if let Err(e) = fs::write(folder.join(REVIEWED_MARKER), "") {
+        Log::err(format!(
+            "Unable to mark recovery folder {} as reviewed. Reason: {}",
+            folder.display(),
+            e
+        ));
+    }
+}
+
+pub struct CrashRecoveryWindow {
+    pub window: Handle<UiNode>,
+    summary: Handle<UiNode>,
+    report: Handle<UiNode>,
+    report_viewer: Handle<UiNode>,
+    open_scene: Handle<UiNode>,
+    show_report: Handle<UiNode>,
+    dismiss: Handle<UiNode>,
+    recovery: Option<(PathBuf, RecoveryInfo)>,
+}
+
+fn make_button(ctx: &mut BuildContext, text: &str) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .with_margin(Thickness::uniform(1.0))
+            .with_width(140.0),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+impl CrashRecoveryWindow {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let summary;
+        let report;
+        let report_viewer;
+        let open_scene;
+        let show_report;
+        let dismiss;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(500.0).with_height(400.0))
+            .with_title(WindowTitle::text("Crash Recovery"))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            summary = TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_margin(Thickness::uniform(2.0)),
+                            )
+                            .with_wrap(WrapMode::Word)
+                            .build(ctx);
+                            summary
+                        })
+                        .with_child({
+                            report_viewer = ScrollViewerBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_visibility(false)
+                                    .with_margin(Thickness::uniform(2.0)),
+                            )
+                            .with_content({
+                                report = TextBuilder::new(WidgetBuilder::new())
+                                    .with_wrap(WrapMode::Letter)
+                                    .build(ctx);
+                                report
+                            })
+                            .build(ctx);
+                            report_viewer
+                        })
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .with_child({
+                                        open_scene = make_button(ctx, "Open Recovered Scene");
+                                        open_scene
+                                    })
+                                    .with_child({
+                                        show_report = make_button(ctx, "Show Report");
+                                        show_report
+                                    })
+                                    .with_child({
+                                        dismiss = make_button(ctx, "Dismiss");
+                                        dismiss
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_column(Column::stretch())
+                .add_row(Row::auto())
+                .add_row(Row::stretch())
+                .add_row(Row::strict(25.0))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            summary,
+            report,
+            report_viewer,
+            open_scene,
+            show_report,
+            dismiss,
+            recovery: None,
+        }
+    }
+
+    pub fn open(&mut self, ui: &UserInterface, folder: PathBuf, info: RecoveryInfo) {
+        let mut summary = format!(
+            "The editor has crashed last time. Recovery data was saved to {}.\n\nRecovered scenes:",
+            folder.display()
+        );
+        if info.scenes.is_empty() {
+            summary += "\nNone";
+        }
+        for scene in info.scenes.iter() {
+            summary += &format!(
+                "\n{}",
+                scene
+                    .original
+                    .as_ref()
+                    .map_or_else(|| "Unsaved".to_owned(), |p| p.display().to_string())
+            );
+        }
+
+        ui.send_message(TextMessage::text(
+            self.summary,
+            MessageDirection::ToWidget,
+            summary,
+        ));
+        ui.send_message(WidgetMessage::enabled(
+            self.open_scene,
+            MessageDirection::ToWidget,
+            !info.scenes.is_empty(),
+        ));
+        ui.send_message(WidgetMessage::visibility(
+            self.report_viewer,
+            MessageDirection::ToWidget,
+            false,
+        ));
+        ui.send_message(WindowMessage::open_modal(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+
+        self.recovery = Some((folder, info));
+    }
+
+    fn close(&mut self, ui: &UserInterface) {
+        if let Some((folder, _)) = self.recovery.take() {
+            mark_reviewed(&folder);
+        }
+
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        ui: &UserInterface,
+        sender: &Sender<Message>,
+    ) {
+        if let Some(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.open_scene {
+                if let Some((_, info)) = self.recovery.as_ref() {
+                    // The editor can have only one scene opened at a time.
+                    if let Some(scene) = info.scenes.first() {
+                        sender
+                            .send(Message::OpenRecoveredScene {
+                                working_directory: info.working_directory.clone(),
+                                scene: scene.clone(),
+                            })
+                            .unwrap();
+                    }
+                }
+                self.close(ui);
+            } else if message.destination() == self.show_report {
+                if let Some((folder, _)) = self.recovery.as_ref() {
+                    let path = folder.join(REPORT_FILE);
+                    let text = fs::read_to_string(&path).unwrap_or_else(|e| {
+                        format!("Unable to read {}. Reason: {}", path.display(), e)
+                    });
+                    ui.send_message(TextMessage::text(
+                        self.report,
+                        MessageDirection::ToWidget,
+                        text,
+                    ));
+                    ui.send_message(WidgetMessage::visibility(
+                        self.report_viewer,
+                        MessageDirection::ToWidget,
+                        true,
+                    ));
+                }
+            } else if message.destination() == self.dismiss {
+                self.close(ui);
+            }
+        }
+    }
+}
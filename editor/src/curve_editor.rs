@@ -4,10 +4,7 @@ use crate::{
 };
 use fyrox::{
     asset::{Resource, ResourceData, ResourceState},
-    core::{
-        color::Color, curve::Curve, futures::executor::block_on, pool::Handle, visitor::prelude::*,
-        visitor::Visitor,
-    },
+    core::{color::Color, curve::Curve, futures::executor::block_on, pool::Handle},
     engine::Engine,
     gui::{
         border::BorderBuilder,
@@ -25,6 +22,7 @@ use fyrox::{
         BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
     },
     resource::curve::{CurveResource, CurveResourceState},
+    utils::log::Log,
 };
 use std::{fmt::Debug, path::PathBuf};
 
@@ -311,10 +309,12 @@ impl CurveEditorWindow {
 
     fn save(&self) {
         if let Some(curve_resource) = self.curve_resource.as_ref() {
-            if let ResourceState::Ok(ref mut state) = *curve_resource.state() {
-                let mut visitor = Visitor::new();
-                state.curve.visit("Curve", &mut visitor).unwrap();
-                visitor.save_binary(&self.path).unwrap();
+            if let Err(e) = curve_resource.save(&self.path) {
+                Log::err(format!(
+                    "Unable to save {} curve. Reason: {}",
+                    self.path.display(),
+                    e
+                ));
             }
         }
     }
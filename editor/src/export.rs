@@ -0,0 +1,655 @@
+//! Game data export - collects everything that is needed to run a game and puts it in a folder
+//! (or a single archive) that can be shipped together with an executable of the game.
+//!
+//! Export consists of the following steps:
+//!
+//! 1. Scenes of the project are loaded and every resource used by them (directly or via other
+//! resources, for example textures of a model) is collected.
+//! 2. Textures are imported with their import options and compressed representations from the
+//! texture cache are exported as well, so the game won't spend time on compression. Some platforms
+//! do not support compressed formats, textures are exported uncompressed for them.
+//! 3. Collected files are copied to the output folder preserving their paths, or packed into
+//! [`ARCHIVE_NAME`] archive. Mount the archive using [`fyrox::core::io::mount`] at start of the
+//! game and every resource will be loaded from it.
+//! 4. Asset files of the data folder that were not exported are reported as unused, it helps to
+//! keep a project clean.
+
+use crate::{gui::make_dropdown_list_option, GameEngine};
+use fyrox::{
+    asset::{Resource, ResourceData, ResourceLoadError, ResourceState},
+    core::{futures::executor::block_on, io::ArchiveBuilder, pool::Handle, scope_profile},
+    engine::{
+        resource_manager::{
+            options::{
+                folder_import_options_path, resolve_import_options, ImportOptions,
+                ImportOptionsSource,
+            },
+            ResourceManager,
+        },
+        SerializationContext,
+    },
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
+        formatted_text::WrapMode,
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        text_box::{TextBoxBuilder, TextCommitMode},
+        widget::WidgetBuilder,
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+    resource::texture::{TextureCompressionFormat, TextureData, TextureImportOptions},
+    scene::SceneLoader,
+    utils::log::Log,
+    walkdir::WalkDir,
+};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    ops::Deref,
+    path::{Component, Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Name of the archive with game data, see [`ExportOptions::pack`].
+pub const ARCHIVE_NAME: &str = "data.pak";
+
+const ASSET_EXTENSIONS: [&str; 13] = [
+    "rgs", "fbx", "jpg", "jpeg", "tga", "png", "bmp", "dds", "ogg", "wav", "shader", "absm",
+    "curve",
+];
+
+#[derive(clap::ArgEnum, Serialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetPlatform {
+    Desktop,
+    Web,
+    Android,
+}
+
+impl TargetPlatform {
+    const ALL: [TargetPlatform; 3] = [Self::Desktop, Self::Web, Self::Android];
+
+    fn name(self) -> &'static str {
+        match self {
+            TargetPlatform::Desktop => "Desktop",
+            TargetPlatform::Web => "Web",
+            TargetPlatform::Android => "Android",
+        }
+    }
+
+    // Block-compressed formats are not guaranteed to be supported by WebGL and mobile GPUs.
+    fn supports_compressed_textures(self) -> bool {
+        self == TargetPlatform::Desktop
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ExportOptions {
+    /// Scenes of the game, only resources that are used by the scenes will be exported.
+    pub scenes: Vec<PathBuf>,
+    /// A folder to put exported data to.
+    pub output: PathBuf,
+    pub platform: TargetPlatform,
+    /// Pack every file into a single [`ARCHIVE_NAME`] archive instead of copying them.
+    pub pack: bool,
+}
+
+/// Result of the export, every path is relative to the project folder.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct ExportReport {
+    pub exported: Vec<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<PathBuf>,
+    /// Resources that are used by the scenes, but cannot be loaded.
+    pub missing: Vec<PathBuf>,
+    /// Asset files of the data folder that are not used by any exported scene.
+    pub unused: Vec<PathBuf>,
+    /// Non-fatal errors, for example a texture that cannot be imported.
+    pub errors: Vec<String>,
+}
+
+fn project_relative(path: &Path) -> Option<PathBuf> {
+    let working_dir = std::env::current_dir().ok()?.canonicalize().ok()?;
+    let path = path.canonicalize().ok()?;
+    path.strip_prefix(working_dir).ok().map(|p| p.to_owned())
+}
+
+fn is_in_folder(path: &Path, folder: &Path) -> bool {
+    let folder = folder
+        .components()
+        .filter(|c| *c != Component::CurDir)
+        .collect::<PathBuf>();
+    !folder.as_os_str().is_empty() && path.starts_with(folder)
+}
+
+fn collect_resources<'a, T, E>(
+    resources: impl Iterator<Item = &'a Resource<T, E>>,
+    used: &mut BTreeSet<PathBuf>,
+    missing: &mut BTreeSet<PathBuf>,
+) where
+    T: ResourceData,
+    E: ResourceLoadError,
+{
+    for resource in resources {
+        let state = resource.state();
+        let path = state.path().into_owned();
+        if let ResourceState::LoadError { .. } = *state {
+            missing.insert(path);
+        } else if path.is_file() {
+            used.insert(path);
+        }
+        // Built-in resources (shaders, for example) have no files.
+    }
+}
+
+fn collect_dependencies(
+    scenes: &[PathBuf],
+    serialization_context: Arc<SerializationContext>,
+    report: &mut ExportReport,
+) -> Result<BTreeSet<PathBuf>, String> {
+    // Fresh resource manager is used, so only resources of the scenes will be in it.
+    let resource_manager = ResourceManager::new(serialization_context.clone());
+
+    let mut loaded_scenes = Vec::new();
+    for path in scenes {
+        let scene = block_on(async {
+            let loader = SceneLoader::from_file(path, serialization_context.clone())
+                .await
+                .map_err(|e| format!("Unable to load {} scene. Reason: {}", path.display(), e))?;
+            Ok::<_, String>(loader.finish(resource_manager.clone()).await)
+        })?;
+        // Keep the scene alive until every resource is loaded.
+        loaded_scenes.push(scene);
+    }
+
+    // Resources could request other resources while loading, wait until everything is loaded.
+    while resource_manager.state().count_pending_resources() != 0 {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let mut used = BTreeSet::new();
+    let mut missing = BTreeSet::new();
+    let state = resource_manager.state();
+    let containers = state.containers();
+    collect_resources(
+        containers.textures.iter().map(Deref::deref),
+        &mut used,
+        &mut missing,
+    );
+    collect_resources(
+        containers.models.iter().map(Deref::deref),
+        &mut used,
+        &mut missing,
+    );
+    collect_resources(
+        containers.sound_buffers.iter().map(Deref::deref),
+        &mut used,
+        &mut missing,
+    );
+    collect_resources(
+        containers.shaders.iter().map(Deref::deref),
+        &mut used,
+        &mut missing,
+    );
+    collect_resources(
+        containers.curves.iter().map(Deref::deref),
+        &mut used,
+        &mut missing,
+    );
+    collect_resources(
+        containers.absm.iter().map(Deref::deref),
+        &mut used,
+        &mut missing,
+    );
+
+    report.missing = missing.into_iter().collect();
+
+    Ok(used)
+}
+
+fn is_texture(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| {
+        ["jpg", "jpeg", "tga", "png", "bmp", "dds"]
+            .iter()
+            .any(|e| ext.eq_ignore_ascii_case(e))
+    })
+}
+
+/// Collects every file used by the scenes and copies (or packs) them to the output folder. See
+/// module docs for more info. `data_root` is a folder that is checked for unused assets.
+pub fn export_game_data(
+    options: &ExportOptions,
+    serialization_context: Arc<SerializationContext>,
+    data_root: &Path,
+) -> Result<ExportReport, String> {
+    if options.scenes.is_empty() {
+        return Err("There are no scenes to export.".to_owned());
+    }
+
+    let mut report = ExportReport::default();
+
+    let used = collect_dependencies(&options.scenes, serialization_context, &mut report)?;
+
+    // Modified import options are written to a temporary folder, source ones must stay intact.
+    let staging = std::env::temp_dir().join(format!("fyrox_export_{}", std::process::id()));
+
+    // Relative path in the output -> source file.
+    let mut files = BTreeMap::new();
+    for path in options.scenes.iter().chain(used.iter()) {
+        let relative = match project_relative(path) {
+            Some(relative) => relative,
+            None => {
+                report.errors.push(format!(
+                    "{} is outside of the project folder and won't be exported.",
+                    path.display()
+                ));
+                continue;
+            }
+        };
+
+        let options_path = fyrox::core::append_extension(&relative, "options");
+
+        if is_texture(&relative) {
+            let (mut import_options, source) = block_on(resolve_import_options(
+                &relative,
+                TextureImportOptions::default(),
+            ));
+
+            if options.platform.supports_compressed_textures() {
+                match source {
+                    ImportOptionsSource::Resource => {
+                        files.insert(options_path.clone(), options_path);
+                    }
+                    // Inherited options must be resolved the same way in the game.
+                    ImportOptionsSource::Folder(folder) => {
+                        let folder_options_path =
+                            folder_import_options_path::<TextureImportOptions>(&folder);
+                        files.insert(folder_options_path.clone(), folder_options_path);
+                    }
+                    ImportOptionsSource::Default => (),
+                }
+            } else {
+                import_options.set_format(TextureCompressionFormat::Uncompressed);
+                let staged = staging.join(&options_path);
+                let saved = staged
+                    .parent()
+                    .map_or(true, |parent| fs::create_dir_all(parent).is_ok())
+                    && import_options.save(&staged);
+                if saved {
+                    files.insert(options_path, staged);
+                } else {
+                    report.errors.push(format!(
+                        "Unable to write import options of {} texture.",
+                        relative.display()
+                    ));
+                }
+            }
+
+            match block_on(TextureData::cached_data_path(&relative, &import_options)) {
+                Ok(Some(cache_path)) => match project_relative(&cache_path) {
+                    Some(cache_relative) => {
+                        files.insert(cache_relative, cache_path);
+                    }
+                    None => report.errors.push(format!(
+                        "Texture cache {} is outside of the project folder.",
+                        cache_path.display()
+                    )),
+                },
+                Ok(None) => (),
+                Err(e) => report.errors.push(format!(
+                    "Unable to import {} texture. Reason: {:?}",
+                    relative.display(),
+                    e
+                )),
+            }
+        } else if options_path.exists() {
+            files.insert(options_path.clone(), options_path);
+        }
+
+        files.insert(relative, path.clone());
+    }
+
+    if options.pack {
+        let mut builder = ArchiveBuilder::new();
+        for (relative, source) in files.iter() {
+            builder.add_file(relative, source);
+        }
+        let archive = options.output.join(ARCHIVE_NAME);
+        fs::create_dir_all(&options.output)
+            .and_then(|_| builder.save(&archive))
+            .map_err(|e| {
+                format!(
+                    "Unable to write {} archive. Reason: {:?}",
+                    archive.display(),
+                    e
+                )
+            })?;
+        report.archive = Some(archive);
+    } else {
+        for (relative, source) in files.iter() {
+            let destination = options.output.join(relative);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent).map_err(|e| {
+                    format!(
+                        "Unable to create {} folder. Reason: {}",
+                        parent.display(),
+                        e
+                    )
+                })?;
+            }
+            fs::copy(source, &destination).map_err(|e| {
+                format!(
+                    "Unable to copy {} to {}. Reason: {}",
+                    source.display(),
+                    destination.display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    let _ = fs::remove_dir_all(&staging);
+
+    let output = project_relative(&options.output).unwrap_or_else(|| options.output.clone());
+    for entry in WalkDir::new(data_root).into_iter().flatten() {
+        let path = entry.path();
+        let is_asset = path.extension().map_or(false, |ext| {
+            ASSET_EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e))
+        });
+        if !is_asset {
+            continue;
+        }
+        if let Some(relative) = project_relative(path) {
+            if !files.contains_key(&relative)
+                && !is_in_folder(&relative, &output)
+                && !path.starts_with(data_root.join(".derived"))
+            {
+                report.unused.push(relative);
+            }
+        }
+    }
+
+    report.exported = files.into_iter().map(|(relative, _)| relative).collect();
+
+    Ok(report)
+}
+
+pub struct ExportWindow {
+    pub window: Handle<UiNode>,
+    platform: Handle<UiNode>,
+    pack: Handle<UiNode>,
+    output: Handle<UiNode>,
+    export: Handle<UiNode>,
+    status: Handle<UiNode>,
+    options: ExportOptions,
+    receiver: Option<Receiver<Result<ExportReport, String>>>,
+}
+
+fn make_row(
+    ctx: &mut BuildContext,
+    row: usize,
+    name: &str,
+    field: Handle<UiNode>,
+) -> Handle<UiNode> {
+    GridBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .with_child(
+                TextBuilder::new(WidgetBuilder::new().on_column(0))
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .with_text(name)
+                    .build(ctx),
+            )
+            .with_child(field),
+    )
+    .add_row(Row::stretch())
+    .add_column(Column::strict(100.0))
+    .add_column(Column::stretch())
+    .build(ctx)
+}
+
+impl ExportWindow {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let options = ExportOptions {
+            scenes: Default::default(),
+            output: PathBuf::from("export"),
+            platform: TargetPlatform::Desktop,
+            pack: false,
+        };
+
+        let platform = DropdownListBuilder::new(
+            WidgetBuilder::new()
+                .on_column(1)
+                .with_margin(Thickness::uniform(1.0)),
+        )
+        .with_items(
+            TargetPlatform::ALL
+                .iter()
+                .map(|platform| make_dropdown_list_option(ctx, platform.name()))
+                .collect(),
+        )
+        .with_selected(0)
+        .build(ctx);
+        let pack = CheckBoxBuilder::new(
+            WidgetBuilder::new()
+                .on_column(1)
+                .with_margin(Thickness::uniform(1.0))
+                .with_horizontal_alignment(HorizontalAlignment::Left),
+        )
+        .checked(Some(options.pack))
+        .build(ctx);
+        let output = TextBoxBuilder::new(
+            WidgetBuilder::new()
+                .on_column(1)
+                .with_margin(Thickness::uniform(1.0)),
+        )
+        .with_text_commit_mode(TextCommitMode::Immediate)
+        .with_vertical_text_alignment(VerticalAlignment::Center)
+        .with_text(options.output.to_string_lossy())
+        .build(ctx);
+
+        let platform_row = make_row(ctx, 0, "Platform", platform);
+        let pack_row = make_row(ctx, 1, "Pack Into Archive", pack);
+        let output_row = make_row(ctx, 2, "Output Folder", output);
+
+        let export;
+        let status;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(400.0).with_height(200.0))
+            .with_title(WindowTitle::text("Export Game Data"))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(platform_row)
+                        .with_child(pack_row)
+                        .with_child(output_row)
+                        .with_child(
+                            TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(3)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_text(
+                                "Scenes of the project manifest are exported, or the current \
+                                scene if there is no manifest.",
+                            )
+                            .with_wrap(WrapMode::Word)
+                            .build(ctx),
+                        )
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(4)
+                                    .with_child({
+                                        status = TextBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(280.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                                        .build(ctx);
+                                        status
+                                    })
+                                    .with_child({
+                                        export = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_width(100.0)
+                                                .with_margin(Thickness::uniform(1.0)),
+                                        )
+                                        .with_text("Export")
+                                        .build(ctx);
+                                        export
+                                    }),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_column(Column::stretch())
+                .add_row(Row::strict(26.0))
+                .add_row(Row::strict(26.0))
+                .add_row(Row::strict(26.0))
+                .add_row(Row::stretch())
+                .add_row(Row::strict(30.0))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            platform,
+            pack,
+            output,
+            export,
+            status,
+            options,
+            receiver: None,
+        }
+    }
+
+    pub fn open(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    fn set_status(&self, ui: &UserInterface, status: &str) {
+        ui.send_message(TextMessage::text(
+            self.status,
+            MessageDirection::ToWidget,
+            status.to_owned(),
+        ));
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        engine: &mut GameEngine,
+        current_scene: Option<&Path>,
+    ) {
+        scope_profile!();
+
+        if message.direction() != MessageDirection::FromWidget {
+            return;
+        }
+
+        if let Some(&DropdownListMessage::SelectionChanged(Some(index))) =
+            message.data::<DropdownListMessage>()
+        {
+            if message.destination() == self.platform {
+                if let Some(platform) = TargetPlatform::ALL.get(index) {
+                    self.options.platform = *platform;
+                }
+            }
+        } else if let Some(&CheckBoxMessage::Check(Some(value))) = message.data::<CheckBoxMessage>()
+        {
+            if message.destination() == self.pack {
+                self.options.pack = value;
+            }
+        } else if let Some(TextMessage::Text(text)) = message.data::<TextMessage>() {
+            if message.destination() == self.output {
+                self.options.output = PathBuf::from(text);
+            }
+        } else if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.export && self.receiver.is_none() {
+                let (scenes, data_root) = match engine.project.as_ref() {
+                    Some(project) => (
+                        project.all_scenes().map(|p| p.to_owned()).collect(),
+                        project.data_root.clone(),
+                    ),
+                    None => (
+                        current_scene.map(|p| p.to_owned()).into_iter().collect(),
+                        PathBuf::from("."),
+                    ),
+                };
+
+                let mut options = self.options.clone();
+                options.scenes = scenes;
+
+                let serialization_context = engine.serialization_context.clone();
+                let (sender, receiver) = mpsc::channel();
+                // Export loads every scene of the game, do not block the editor.
+                std::thread::spawn(move || {
+                    let _ = sender.send(export_game_data(
+                        &options,
+                        serialization_context,
+                        &data_root,
+                    ));
+                });
+                self.receiver = Some(receiver);
+
+                self.set_status(&engine.user_interface, "Exporting...");
+            }
+        }
+    }
+
+    pub fn update(&mut self, ui: &UserInterface) {
+        let result = match self.receiver.as_ref().map(|r| r.try_recv()) {
+            Some(Ok(result)) => result,
+            Some(Err(mpsc::TryRecvError::Disconnected)) => {
+                Err("Export thread has panicked.".to_owned())
+            }
+            _ => return,
+        };
+        self.receiver = None;
+
+        match result {
+            Ok(report) => {
+                for path in report.missing.iter() {
+                    Log::warn(format!("Export: {} resource is missing.", path.display()));
+                }
+                for path in report.unused.iter() {
+                    Log::info(format!("Export: {} asset is unused.", path.display()));
+                }
+                for error in report.errors.iter() {
+                    Log::err(format!("Export: {}", error));
+                }
+                let status = format!(
+                    "Exported {} files, {} missing, {} unused.",
+                    report.exported.len(),
+                    report.missing.len(),
+                    report.unused.len()
+                );
+                Log::info(status.clone());
+                self.set_status(ui, &status);
+            }
+            Err(error) => {
+                Log::err(format!("Unable to export game data. Reason: {}", error));
+                self.set_status(ui, "Export has failed, see log for details.");
+            }
+        }
+    }
+}
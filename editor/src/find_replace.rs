@@ -0,0 +1,710 @@
+//! Scene-wide find and replace for names and tags of scene nodes.
+//!
+//! Text is matched either as a plain substring or as a regular expression, in the latter case
+//! the replacement could reference capture groups: `$1`, `${1}` or `${name}`. Use `${1}` when a
+//! group reference is followed by a letter, digit or underscore, `$1_` is treated as a group
+//! named `1_`.
+//!
+//! All replacements are applied as a single command, so they can be undone at once. Nodes that
+//! belong to locked collections are not skipped, but they're highlighted in the preview list.
+
+use crate::{
+    gui::make_dropdown_list_option,
+    scene::{
+        commands::{
+            graph::{SetNameCommand, SetTagCommand},
+            CommandGroup, SceneCommand,
+        },
+        EditorScene, Selection,
+    },
+    GameEngine, Message,
+};
+use fyrox::{
+    core::{color::Color, pool::Handle, scope_profile},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        button::{ButtonBuilder, ButtonMessage},
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
+        grid::{Column, GridBuilder, Row},
+        list_view::{ListViewBuilder, ListViewMessage},
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        text_box::{TextBoxBuilder, TextBoxMessage, TextCommitMode},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Orientation, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    scene::{node::Node, Scene},
+};
+use regex::Regex;
+use std::{borrow::Cow, sync::mpsc::Sender};
+
+/// Defines how the search string is interpreted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MatchMode {
+    /// Search string is matched as is.
+    Substring,
+    /// Search string is a regular expression, the replacement could use its capture groups.
+    Regex,
+}
+
+impl MatchMode {
+    const ALL: [MatchMode; 2] = [MatchMode::Substring, MatchMode::Regex];
+
+    fn name(self) -> &'static str {
+        match self {
+            MatchMode::Substring => "Substring",
+            MatchMode::Regex => "Regular Expression",
+        }
+    }
+}
+
+/// Defines which nodes are searched.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SearchScope {
+    /// Every node of the scene.
+    WholeScene,
+    /// Selected nodes and all their descendants.
+    SelectedSubtree,
+}
+
+impl SearchScope {
+    const ALL: [SearchScope; 2] = [SearchScope::WholeScene, SearchScope::SelectedSubtree];
+
+    fn name(self) -> &'static str {
+        match self {
+            SearchScope::WholeScene => "Whole Scene",
+            SearchScope::SelectedSubtree => "Selected Subtree",
+        }
+    }
+}
+
+/// Compiled search string together with its replacement.
+#[derive(Clone, Debug)]
+pub enum Replacer {
+    Substring { find: String, replace: String },
+    Regex { regex: Regex, replace: String },
+}
+
+impl Replacer {
+    /// Prepares the search string for matching. Returns a human-readable description of the
+    /// error if the search string is not a valid regular expression.
+    pub fn new(mode: MatchMode, find: &str, replace: &str) -> Result<Self, String> {
+        match mode {
+            MatchMode::Substring => Ok(Self::Substring {
+                find: find.to_owned(),
+                replace: replace.to_owned(),
+            }),
+            MatchMode::Regex => match Regex::new(find) {
+                Ok(regex) => Ok(Self::Regex {
+                    regex,
+                    replace: replace.to_owned(),
+                }),
+                Err(err) => Err(format!("Invalid regular expression: {}", err)),
+            },
+        }
+    }
+
+    /// Returns new text if the search string was found in the given text and the replacement
+    /// changes it, `None` otherwise. An empty search string matches nothing.
+    pub fn apply(&self, text: &str) -> Option<String> {
+        let result = match self {
+            Self::Substring { find, replace } => {
+                if find.is_empty() || !text.contains(find.as_str()) {
+                    return None;
+                }
+                Cow::Owned(text.replace(find.as_str(), replace))
+            }
+            Self::Regex { regex, replace } => {
+                if regex.as_str().is_empty() {
+                    return None;
+                }
+                regex.replace_all(text, replace.as_str())
+            }
+        };
+
+        match result {
+            Cow::Owned(result) if result != text => Some(result),
+            _ => None,
+        }
+    }
+}
+
+/// Part of a node that is matched.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MatchTarget {
+    Name,
+    Tag,
+}
+
+/// A node that could be matched, its data is captured when the window opens, so the preview
+/// could be rebuilt without access to the scene.
+#[derive(Clone, Debug)]
+struct Candidate {
+    node: Handle<Node>,
+    name: String,
+    tag: String,
+    locked: bool,
+    in_selected_subtree: bool,
+}
+
+#[derive(Clone, Debug)]
+struct MatchEntry {
+    node: Handle<Node>,
+    target: MatchTarget,
+    old: String,
+    new: String,
+    locked: bool,
+}
+
+/// Collects nodes in the order of the world viewer (depth-first, children in the order of their
+/// parent). The root of the scene and the editor's own objects are skipped.
+fn collect_candidates(
+    scene: &Scene,
+    node: Handle<Node>,
+    editor_scene: &EditorScene,
+    in_selected_subtree: bool,
+    result: &mut Vec<Candidate>,
+) {
+    if node == editor_scene.editor_objects_root {
+        return;
+    }
+
+    let in_selected_subtree = in_selected_subtree
+        || matches!(&editor_scene.selection, Selection::Graph(selection) if selection.contains(node));
+
+    let graph = &scene.graph;
+    if node != graph.get_root() {
+        result.push(Candidate {
+            node,
+            name: graph[node].name_owned(),
+            tag: graph[node].tag_owned(),
+            locked: scene.node_collections.is_locked(node),
+            in_selected_subtree,
+        });
+    }
+
+    for &child in graph[node].children() {
+        collect_candidates(scene, child, editor_scene, in_selected_subtree, result);
+    }
+}
+
+fn make_label(ctx: &mut BuildContext, row: usize, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(0)
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+fn make_text_box(ctx: &mut BuildContext, row: usize) -> Handle<UiNode> {
+    TextBoxBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(1)
+            .with_margin(Thickness::uniform(1.0)),
+    )
+    .with_text_commit_mode(TextCommitMode::Immediate)
+    .with_vertical_text_alignment(VerticalAlignment::Center)
+    .build(ctx)
+}
+
+fn make_dropdown_list(ctx: &mut BuildContext, row: usize, items: &[&str]) -> Handle<UiNode> {
+    DropdownListBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(1)
+            .with_margin(Thickness::uniform(1.0)),
+    )
+    .with_items(
+        items
+            .iter()
+            .map(|item| make_dropdown_list_option(ctx, item))
+            .collect(),
+    )
+    .with_selected(0)
+    .build(ctx)
+}
+
+fn make_check_box(ctx: &mut BuildContext, text: &str) -> Handle<UiNode> {
+    CheckBoxBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(1.0)))
+        .with_content(
+            TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::left(2.0)))
+                .with_text(text)
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .build(ctx),
+        )
+        .checked(Some(true))
+        .build(ctx)
+}
+
+pub struct FindReplaceWindow {
+    pub window: Handle<UiNode>,
+    find: Handle<UiNode>,
+    replace: Handle<UiNode>,
+    mode: Handle<UiNode>,
+    scope: Handle<UiNode>,
+    names: Handle<UiNode>,
+    tags: Handle<UiNode>,
+    preview: Handle<UiNode>,
+    status: Handle<UiNode>,
+    replace_all: Handle<UiNode>,
+    cancel: Handle<UiNode>,
+    find_text: String,
+    replace_text: String,
+    match_mode: MatchMode,
+    search_scope: SearchScope,
+    match_names: bool,
+    match_tags: bool,
+    candidates: Vec<Candidate>,
+    entries: Vec<MatchEntry>,
+}
+
+impl FindReplaceWindow {
+    pub fn new(engine: &mut GameEngine) -> Self {
+        let find;
+        let replace;
+        let mode;
+        let scope;
+        let names;
+        let tags;
+        let preview;
+        let status;
+        let replace_all;
+        let cancel;
+        let ctx = &mut engine.user_interface.build_ctx();
+
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(450.0).with_height(500.0))
+            .with_title(WindowTitle::text("Find And Replace"))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            GridBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .with_child(make_label(ctx, 0, "Find"))
+                                    .with_child({
+                                        find = make_text_box(ctx, 0);
+                                        find
+                                    })
+                                    .with_child(make_label(ctx, 1, "Replace With"))
+                                    .with_child({
+                                        replace = make_text_box(ctx, 1);
+                                        replace
+                                    })
+                                    .with_child(make_label(ctx, 2, "Mode"))
+                                    .with_child({
+                                        mode = make_dropdown_list(
+                                            ctx,
+                                            2,
+                                            &MatchMode::ALL.map(|mode| mode.name()),
+                                        );
+                                        mode
+                                    })
+                                    .with_child(make_label(ctx, 3, "Scope"))
+                                    .with_child({
+                                        scope = make_dropdown_list(
+                                            ctx,
+                                            3,
+                                            &SearchScope::ALL.map(|scope| scope.name()),
+                                        );
+                                        scope
+                                    })
+                                    .with_child(make_label(ctx, 4, "Search In"))
+                                    .with_child(
+                                        StackPanelBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_row(4)
+                                                .on_column(1)
+                                                .with_child({
+                                                    names = make_check_box(ctx, "Names");
+                                                    names
+                                                })
+                                                .with_child({
+                                                    tags = make_check_box(ctx, "Tags");
+                                                    tags
+                                                }),
+                                        )
+                                        .with_orientation(Orientation::Horizontal)
+                                        .build(ctx),
+                                    ),
+                            )
+                            .add_column(Column::strict(100.0))
+                            .add_column(Column::stretch())
+                            .add_row(Row::strict(25.0))
+                            .add_row(Row::strict(25.0))
+                            .add_row(Row::strict(25.0))
+                            .add_row(Row::strict(25.0))
+                            .add_row(Row::strict(25.0))
+                            .build(ctx),
+                        )
+                        .with_child({
+                            preview = ListViewBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .build(ctx);
+                            preview
+                        })
+                        .with_child(
+                            GridBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .with_child({
+                                        status = TextBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_column(0)
+                                                .with_margin(Thickness::uniform(1.0))
+                                                .with_vertical_alignment(VerticalAlignment::Center),
+                                        )
+                                        .build(ctx);
+                                        status
+                                    })
+                                    .with_child(
+                                        StackPanelBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_column(1)
+                                                .with_child({
+                                                    replace_all = ButtonBuilder::new(
+                                                        WidgetBuilder::new()
+                                                            .with_margin(Thickness::uniform(1.0))
+                                                            .with_width(80.0),
+                                                    )
+                                                    .with_text("Replace All")
+                                                    .build(ctx);
+                                                    replace_all
+                                                })
+                                                .with_child({
+                                                    cancel = ButtonBuilder::new(
+                                                        WidgetBuilder::new()
+                                                            .with_margin(Thickness::uniform(1.0))
+                                                            .with_width(80.0),
+                                                    )
+                                                    .with_text("Cancel")
+                                                    .build(ctx);
+                                                    cancel
+                                                }),
+                                        )
+                                        .with_orientation(Orientation::Horizontal)
+                                        .build(ctx),
+                                    ),
+                            )
+                            .add_column(Column::stretch())
+                            .add_column(Column::auto())
+                            .add_row(Row::stretch())
+                            .build(ctx),
+                        ),
+                )
+                .add_column(Column::stretch())
+                .add_row(Row::auto())
+                .add_row(Row::stretch())
+                .add_row(Row::strict(25.0))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            find,
+            replace,
+            mode,
+            scope,
+            names,
+            tags,
+            preview,
+            status,
+            replace_all,
+            cancel,
+            find_text: Default::default(),
+            replace_text: Default::default(),
+            match_mode: MatchMode::Substring,
+            search_scope: SearchScope::WholeScene,
+            match_names: true,
+            match_tags: true,
+            candidates: Default::default(),
+            entries: Default::default(),
+        }
+    }
+
+    pub fn open(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        let scene = &engine.scenes[editor_scene.scene];
+
+        self.candidates.clear();
+        collect_candidates(
+            scene,
+            scene.graph.get_root(),
+            editor_scene,
+            false,
+            &mut self.candidates,
+        );
+
+        let ui = &mut engine.user_interface;
+        self.update_preview(ui);
+
+        ui.send_message(WindowMessage::open_modal(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    fn close(&mut self, ui: &UserInterface) {
+        self.candidates.clear();
+        self.entries.clear();
+
+        ui.send_message(WindowMessage::close(
+            self.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    fn find_matches(&self, replacer: &Replacer) -> Vec<MatchEntry> {
+        let mut entries = Vec::new();
+        for candidate in self.candidates.iter() {
+            if self.search_scope == SearchScope::SelectedSubtree && !candidate.in_selected_subtree {
+                continue;
+            }
+
+            for (target, enabled, text) in [
+                (MatchTarget::Name, self.match_names, &candidate.name),
+                (MatchTarget::Tag, self.match_tags, &candidate.tag),
+            ] {
+                if !enabled {
+                    continue;
+                }
+                if let Some(new) = replacer.apply(text) {
+                    entries.push(MatchEntry {
+                        node: candidate.node,
+                        target,
+                        old: text.clone(),
+                        new,
+                        locked: candidate.locked,
+                    });
+                }
+            }
+        }
+        entries
+    }
+
+    fn update_preview(&mut self, ui: &mut UserInterface) {
+        let status = match Replacer::new(self.match_mode, &self.find_text, &self.replace_text) {
+            Ok(replacer) => {
+                self.entries = self.find_matches(&replacer);
+                let locked = self.entries.iter().filter(|e| e.locked).count();
+                Ok(if locked > 0 {
+                    format!(
+                        "{} matches, {} in locked nodes.",
+                        self.entries.len(),
+                        locked
+                    )
+                } else {
+                    format!("{} matches.", self.entries.len())
+                })
+            }
+            Err(err) => {
+                self.entries.clear();
+                Err(err)
+            }
+        };
+
+        let ctx = &mut ui.build_ctx();
+        let items = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_background(Brush::Solid(if index % 2 == 0 {
+                            Color::opaque(70, 70, 70)
+                        } else {
+                            Color::opaque(40, 40, 40)
+                        }))
+                        .with_child(
+                            TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_margin(Thickness::uniform(1.0))
+                                    .with_foreground(Brush::Solid(if entry.locked {
+                                        Color::ORANGE
+                                    } else {
+                                        Color::opaque(210, 210, 210)
+                                    })),
+                            )
+                            .with_text(format!(
+                                "{}{}: {} -> {}",
+                                if entry.locked { "[Locked] " } else { "" },
+                                match entry.target {
+                                    MatchTarget::Name => "Name",
+                                    MatchTarget::Tag => "Tag",
+                                },
+                                entry.old,
+                                entry.new
+                            ))
+                            .build(ctx),
+                        ),
+                )
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        ui.send_message(ListViewMessage::items(
+            self.preview,
+            MessageDirection::ToWidget,
+            items,
+        ));
+
+        let (text, color) = match status {
+            Ok(text) => (text, Color::opaque(210, 210, 210)),
+            Err(err) => (err, Color::RED),
+        };
+        ui.send_message(TextMessage::text(
+            self.status,
+            MessageDirection::ToWidget,
+            text,
+        ));
+        ui.send_message(WidgetMessage::foreground(
+            self.status,
+            MessageDirection::ToWidget,
+            Brush::Solid(color),
+        ));
+    }
+
+    fn make_command(&self) -> Option<SceneCommand> {
+        let commands = self
+            .entries
+            .iter()
+            .map(|entry| match entry.target {
+                MatchTarget::Name => {
+                    SceneCommand::new(SetNameCommand::new(entry.node, entry.new.clone()))
+                }
+                MatchTarget::Tag => {
+                    SceneCommand::new(SetTagCommand::new(entry.node, entry.new.clone()))
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if commands.is_empty() {
+            None
+        } else {
+            Some(SceneCommand::new(CommandGroup::from(commands)))
+        }
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
+        scope_profile!();
+
+        let ui = &mut engine.user_interface;
+
+        if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.replace_all {
+                if let Some(command) = self.make_command() {
+                    sender.send(Message::DoSceneCommand(command)).unwrap();
+                }
+                self.close(ui);
+            } else if message.destination() == self.cancel {
+                self.close(ui);
+            }
+        } else if message.direction() == MessageDirection::FromWidget {
+            if let Some(TextBoxMessage::Text(text)) = message.data::<TextBoxMessage>() {
+                if message.destination() == self.find {
+                    self.find_text = text.clone();
+                } else if message.destination() == self.replace {
+                    self.replace_text = text.clone();
+                } else {
+                    return;
+                }
+                self.update_preview(ui);
+            } else if let Some(&DropdownListMessage::SelectionChanged(Some(index))) =
+                message.data::<DropdownListMessage>()
+            {
+                if message.destination() == self.mode {
+                    self.match_mode = MatchMode::ALL[index];
+                } else if message.destination() == self.scope {
+                    self.search_scope = SearchScope::ALL[index];
+                } else {
+                    return;
+                }
+                self.update_preview(ui);
+            } else if let Some(&CheckBoxMessage::Check(Some(value))) =
+                message.data::<CheckBoxMessage>()
+            {
+                if message.destination() == self.names {
+                    self.match_names = value;
+                } else if message.destination() == self.tags {
+                    self.match_tags = value;
+                } else {
+                    return;
+                }
+                self.update_preview(ui);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::find_replace::{MatchMode, Replacer};
+
+    #[test]
+    fn test_substring_replace() {
+        let replacer = Replacer::new(MatchMode::Substring, "Enemy_", "Hostile_").unwrap();
+        assert_eq!(replacer.apply("Enemy_12"), Some("Hostile_12".to_owned()));
+        assert_eq!(
+            replacer.apply("Enemy_Enemy_"),
+            Some("Hostile_Hostile_".to_owned())
+        );
+        assert_eq!(replacer.apply("Player"), None);
+    }
+
+    #[test]
+    fn test_substring_is_not_a_pattern() {
+        let replacer = Replacer::new(MatchMode::Substring, "(\\d+)", "$1").unwrap();
+        assert_eq!(replacer.apply("Enemy_12"), None);
+        assert_eq!(replacer.apply("A(\\d+)"), Some("A$1".to_owned()));
+    }
+
+    #[test]
+    fn test_regex_capture_groups() {
+        let replacer = Replacer::new(MatchMode::Regex, "Enemy_(\\d+)", "Hostile_$1").unwrap();
+        assert_eq!(replacer.apply("Enemy_42"), Some("Hostile_42".to_owned()));
+        assert_eq!(replacer.apply("Enemy_Boss"), None);
+
+        let replacer =
+            Replacer::new(MatchMode::Regex, "(?P<kind>\\w+)_(\\d+)", "${2}_${kind}").unwrap();
+        assert_eq!(replacer.apply("Enemy_7"), Some("7_Enemy".to_owned()));
+    }
+
+    #[test]
+    fn test_empty_and_unchanged_are_not_matches() {
+        for mode in [MatchMode::Substring, MatchMode::Regex] {
+            let replacer = Replacer::new(mode, "", "X").unwrap();
+            assert_eq!(replacer.apply("Enemy"), None);
+
+            let replacer = Replacer::new(mode, "Enemy", "Enemy").unwrap();
+            assert_eq!(replacer.apply("Enemy"), None);
+        }
+    }
+
+    #[test]
+    fn test_invalid_regex_is_reported() {
+        let err = Replacer::new(MatchMode::Regex, "Enemy_(\\d+", "Hostile_$1").unwrap_err();
+        assert!(err.starts_with("Invalid regular expression"));
+
+        // The same string is fine for substring mode.
+        assert!(Replacer::new(MatchMode::Substring, "Enemy_(\\d+", "").is_ok());
+    }
+}
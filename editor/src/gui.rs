@@ -14,6 +14,8 @@ use fyrox::{
 #[derive(Debug, Clone, PartialEq)]
 pub enum AssetItemMessage {
     Select(bool),
+    /// Updates the marker of own import options of an asset.
+    SyncImportOptions,
 }
 
 pub fn make_dropdown_list_option(ctx: &mut BuildContext, name: &str) -> Handle<UiNode> {
@@ -48,4 +50,5 @@ pub fn make_dropdown_list_option_with_height(
 
 impl AssetItemMessage {
     define_constructor!(AssetItemMessage:Select => fn select(bool), layout: false);
+    define_constructor!(AssetItemMessage:SyncImportOptions => fn sync_import_options(), layout: false);
 }
@@ -0,0 +1,513 @@
+use crate::{
+    scene::{commands::hlod::BakeHlodCommand, EditorScene, Selection},
+    GameEngine, Message,
+};
+use fyrox::{
+    core::{pool::Handle, scope_profile},
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        numeric::{NumericUpDownBuilder, NumericUpDownMessage},
+        progress_bar::{ProgressBarBuilder, ProgressBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    scene::{node::Node, Scene},
+    utils::{
+        hlod::{
+            self, HlodBakeError, HlodBakeResult, HlodInputData, HlodSettings, ProgressIndicator,
+        },
+        lightmap::CancellationToken,
+        log::Log,
+    },
+};
+use std::{
+    collections::HashSet,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+};
+
+// Proxies are baked in a background thread, the editor stays responsive and the baking could be
+// cancelled at any time.
+struct BakeTask {
+    scene: Handle<Scene>,
+    root: Handle<Node>,
+    cancellation_token: CancellationToken,
+    progress_indicator: ProgressIndicator,
+    receiver: Receiver<Result<HlodBakeResult, HlodBakeError>>,
+}
+
+pub struct HlodPanel {
+    pub window: Handle<UiNode>,
+    nud_cell_size: Handle<UiNode>,
+    nud_switch_distance: Handle<UiNode>,
+    nud_hysteresis: Handle<UiNode>,
+    nud_triangle_ratio: Handle<UiNode>,
+    nud_tile_size: Handle<UiNode>,
+    root_text: Handle<UiNode>,
+    bake: Handle<UiNode>,
+    cancel: Handle<UiNode>,
+    progress_bar: Handle<UiNode>,
+    progress_text: Handle<UiNode>,
+    settings: HlodSettings,
+    root: Handle<Node>,
+    bake_task: Option<BakeTask>,
+}
+
+pub(crate) fn make_label(text: &str, row: usize, ctx: &mut BuildContext) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(0)
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+pub(crate) fn make_numeric(
+    row: usize,
+    value: f32,
+    min: f32,
+    max: f32,
+    step: f32,
+    precision: usize,
+    ctx: &mut BuildContext,
+) -> Handle<UiNode> {
+    NumericUpDownBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(1)
+            .with_margin(Thickness::uniform(1.0)),
+    )
+    .with_min_value(min)
+    .with_max_value(max)
+    .with_step(step)
+    .with_precision(precision)
+    .with_value(value)
+    .build(ctx)
+}
+
+pub(crate) fn make_text(row: usize, ctx: &mut BuildContext) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(1)
+            .with_margin(Thickness::uniform(1.0)),
+    )
+    .build(ctx)
+}
+
+// Atlases are saved next to the scene, so they could be shipped with it.
+fn hlod_folder(editor_scene: &EditorScene) -> PathBuf {
+    match editor_scene.path.as_ref() {
+        Some(path) => {
+            let stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            path.with_file_name(format!("{}_hlod", stem))
+        }
+        None => PathBuf::from("hlod"),
+    }
+}
+
+impl HlodPanel {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let settings = HlodSettings::default();
+
+        let nud_cell_size;
+        let nud_switch_distance;
+        let nud_hysteresis;
+        let nud_triangle_ratio;
+        let nud_tile_size;
+        let root_text;
+        let bake;
+        let cancel;
+        let progress_bar;
+        let progress_text;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(300.0))
+            .with_title(WindowTitle::Text("HLOD Baker".to_owned()))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(make_label("Cell Size", 0, ctx))
+                        .with_child({
+                            nud_cell_size =
+                                make_numeric(0, settings.cell_size, 0.0, 10000.0, 1.0, 1, ctx);
+                            nud_cell_size
+                        })
+                        .with_child(make_label("Switch Distance", 1, ctx))
+                        .with_child({
+                            nud_switch_distance = make_numeric(
+                                1,
+                                settings.switch_distance,
+                                0.0,
+                                100000.0,
+                                1.0,
+                                1,
+                                ctx,
+                            );
+                            nud_switch_distance
+                        })
+                        .with_child(make_label("Hysteresis", 2, ctx))
+                        .with_child({
+                            nud_hysteresis =
+                                make_numeric(2, settings.hysteresis, 0.0, 1000.0, 0.5, 1, ctx);
+                            nud_hysteresis
+                        })
+                        .with_child(make_label("Triangle Ratio", 3, ctx))
+                        .with_child({
+                            nud_triangle_ratio =
+                                make_numeric(3, settings.triangle_ratio, 0.0, 1.0, 0.05, 2, ctx);
+                            nud_triangle_ratio
+                        })
+                        .with_child(make_label("Tile Size", 4, ctx))
+                        .with_child({
+                            nud_tile_size = make_numeric(
+                                4,
+                                settings.atlas_tile_size as f32,
+                                4.0,
+                                1024.0,
+                                4.0,
+                                0,
+                                ctx,
+                            );
+                            nud_tile_size
+                        })
+                        .with_child(make_label("Root", 5, ctx))
+                        .with_child({
+                            root_text = make_text(5, ctx);
+                            root_text
+                        })
+                        .with_child({
+                            bake = ButtonBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(6)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_text("Bake")
+                            .build(ctx);
+                            bake
+                        })
+                        .with_child({
+                            cancel = ButtonBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_enabled(false)
+                                    .on_row(7)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_text("Cancel")
+                            .build(ctx);
+                            cancel
+                        })
+                        .with_child({
+                            progress_bar = ProgressBarBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_visibility(false)
+                                    .on_row(8)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .build(ctx);
+                            progress_bar
+                        })
+                        .with_child({
+                            progress_text = make_text(9, ctx);
+                            progress_text
+                        }),
+                )
+                .add_column(Column::strict(100.0))
+                .add_column(Column::stretch())
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(16.0))
+                .add_row(Row::strict(25.0))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            nud_cell_size,
+            nud_switch_distance,
+            nud_hysteresis,
+            nud_triangle_ratio,
+            nud_tile_size,
+            root_text,
+            bake,
+            cancel,
+            progress_bar,
+            progress_text,
+            settings,
+            root: Handle::NONE,
+            bake_task: None,
+        }
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+    ) {
+        scope_profile!();
+
+        if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.bake {
+                self.start_bake(editor_scene, engine);
+            } else if message.destination() == self.cancel {
+                if let Some(bake_task) = self.bake_task.as_ref() {
+                    bake_task.cancellation_token.cancel();
+                }
+            }
+        } else if let Some(&NumericUpDownMessage::Value(value)) =
+            message.data::<NumericUpDownMessage<f32>>()
+        {
+            if message.direction() == MessageDirection::FromWidget {
+                if message.destination() == self.nud_cell_size {
+                    self.settings.cell_size = value;
+                } else if message.destination() == self.nud_switch_distance {
+                    self.settings.switch_distance = value;
+                } else if message.destination() == self.nud_hysteresis {
+                    self.settings.hysteresis = value;
+                } else if message.destination() == self.nud_triangle_ratio {
+                    self.settings.triangle_ratio = value;
+                } else if message.destination() == self.nud_tile_size {
+                    self.settings.atlas_tile_size = value as u32;
+                }
+            }
+        }
+    }
+
+    // Meshes under the first selected node are baked, or the whole scene if nothing is selected.
+    fn selected_root(editor_scene: &EditorScene, scene: &Scene) -> Handle<Node> {
+        if let Selection::Graph(selection) = &editor_scene.selection {
+            if let Some(&first) = selection.nodes().first() {
+                if scene.graph.is_valid_handle(first) {
+                    return first;
+                }
+            }
+        }
+        scene.graph.get_root()
+    }
+
+    fn start_bake(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        if self.bake_task.is_some() {
+            return;
+        }
+
+        let scene = &mut engine.scenes[editor_scene.scene];
+        let root = Self::selected_root(editor_scene, scene);
+
+        // Editor objects must not be baked.
+        let editor_objects = scene
+            .graph
+            .traverse_handle_iter(editor_scene.editor_objects_root)
+            .collect::<HashSet<_>>();
+
+        let cancellation_token = CancellationToken::new();
+        let progress_indicator = ProgressIndicator::new();
+
+        // Snapshot is made on the main thread, everything else is done in background.
+        let input = match HlodInputData::from_scene(
+            scene,
+            root,
+            self.settings.clone(),
+            |handle, _| !editor_objects.contains(&handle),
+            cancellation_token.clone(),
+        ) {
+            Ok(input) => input,
+            Err(e) => {
+                Log::err(format!("Unable to bake HLOD. Reason: {}", e));
+                return;
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let thread_cancellation_token = cancellation_token.clone();
+        let thread_progress_indicator = progress_indicator.clone();
+        std::thread::spawn(move || {
+            let result = hlod::bake(input, thread_cancellation_token, thread_progress_indicator);
+            // The editor might be closed already.
+            let _ = sender.send(result);
+        });
+
+        self.bake_task = Some(BakeTask {
+            scene: editor_scene.scene,
+            root,
+            cancellation_token,
+            progress_indicator,
+            receiver,
+        });
+
+        self.sync_progress(&engine.user_interface);
+    }
+
+    fn sync_progress(&self, ui: &UserInterface) {
+        let in_progress = self.bake_task.is_some();
+
+        ui.send_message(WidgetMessage::visibility(
+            self.progress_bar,
+            MessageDirection::ToWidget,
+            in_progress,
+        ));
+        ui.send_message(WidgetMessage::enabled(
+            self.bake,
+            MessageDirection::ToWidget,
+            !in_progress,
+        ));
+        ui.send_message(WidgetMessage::enabled(
+            self.cancel,
+            MessageDirection::ToWidget,
+            in_progress,
+        ));
+
+        let text = if let Some(bake_task) = self.bake_task.as_ref() {
+            let progress = &bake_task.progress_indicator;
+
+            ui.send_message(ProgressBarMessage::progress(
+                self.progress_bar,
+                MessageDirection::ToWidget,
+                progress.progress_percent() as f32 / 100.0,
+            ));
+
+            if bake_task.cancellation_token.is_cancelled() {
+                "Cancelling...".to_owned()
+            } else {
+                format!("Baking: {}%", progress.progress_percent())
+            }
+        } else {
+            Default::default()
+        };
+
+        ui.send_message(TextMessage::text(
+            self.progress_text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+
+    fn update_bake(
+        &mut self,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
+        let bake_task = match self.bake_task.as_ref() {
+            Some(bake_task) => bake_task,
+            None => return,
+        };
+
+        let result = match bake_task.receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => {
+                self.sync_progress(&engine.user_interface);
+                return;
+            }
+            Err(TryRecvError::Disconnected) => {
+                Log::err("HLOD baking thread has crashed!".to_owned());
+                self.bake_task = None;
+                self.sync_progress(&engine.user_interface);
+                return;
+            }
+        };
+
+        let scene = bake_task.scene;
+        let root = bake_task.root;
+        self.bake_task = None;
+        self.sync_progress(&engine.user_interface);
+
+        match result {
+            Ok(result) => {
+                let graph = &mut engine.scenes[editor_scene.scene].graph;
+                if scene != editor_scene.scene || !graph.is_valid_handle(root) {
+                    Log::warn(
+                        "HLOD was discarded, because the scene was changed during baking."
+                            .to_owned(),
+                    );
+                    return;
+                }
+
+                Log::info(format!(
+                    "HLOD baking is done: {} baked, {} unchanged, {} removed.",
+                    result.proxies.len(),
+                    result.unchanged.len(),
+                    result.stale.len()
+                ));
+
+                if result.proxies.is_empty() && result.stale.is_empty() {
+                    return;
+                }
+
+                let folder = hlod_folder(editor_scene);
+                if let Err(e) = result.save(&folder, engine.resource_manager.clone()) {
+                    Log::err(format!(
+                        "Unable to save HLOD atlases to {}. Reason: {}",
+                        folder.display(),
+                        e
+                    ));
+                    return;
+                }
+
+                // New nodes are moved out of the graph, so the command could add them.
+                let added = result
+                    .proxies
+                    .iter()
+                    .map(|proxy| {
+                        let hlod = proxy.instantiate(graph, root);
+                        graph.take_reserve_sub_graph(hlod)
+                    })
+                    .collect();
+
+                sender
+                    .send(Message::do_scene_command(BakeHlodCommand::new(
+                        root,
+                        added,
+                        result.stale,
+                    )))
+                    .unwrap();
+            }
+            Err(HlodBakeError::Cancelled) => Log::info("HLOD baking was cancelled.".to_owned()),
+            Err(e) => Log::err(format!("Unable to bake HLOD. Reason: {}", e)),
+        }
+    }
+
+    /// Tracks HLOD baking and shows the node that will be used as the root of the bake.
+    pub fn update(
+        &mut self,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
+        let scene = &engine.scenes[editor_scene.scene];
+        let root = Self::selected_root(editor_scene, scene);
+        if root != self.root {
+            self.root = root;
+            let name = if root == scene.graph.get_root() {
+                "Whole Scene".to_owned()
+            } else {
+                scene.graph[root].name_owned()
+            };
+            engine.user_interface.send_message(TextMessage::text(
+                self.root_text,
+                MessageDirection::ToWidget,
+                name,
+            ));
+        }
+
+        self.update_bake(editor_scene, engine, sender);
+    }
+}
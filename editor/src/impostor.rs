@@ -0,0 +1,475 @@
+use crate::{
+    hlod::{make_label, make_numeric, make_text},
+    scene::{commands::impostor::BakeImpostorCommand, EditorScene, Selection},
+    GameEngine, Message,
+};
+use fyrox::{
+    core::{pool::Handle, scope_profile},
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        numeric::NumericUpDownMessage,
+        progress_bar::{ProgressBarBuilder, ProgressBarMessage},
+        text::TextMessage,
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface,
+    },
+    scene::{impostor::Impostor, node::Node, Scene},
+    utils::{
+        hlod::ProgressIndicator,
+        impostor::{
+            self, ImpostorBakeError, ImpostorBakeResult, ImpostorInputData, ImpostorSettings,
+        },
+        lightmap::CancellationToken,
+        log::Log,
+    },
+};
+use std::{
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+};
+
+// Frames are rendered in a background thread, the editor stays responsive and the baking could be
+// cancelled at any time.
+struct BakeTask {
+    scene: Handle<Scene>,
+    root: Handle<Node>,
+    cancellation_token: CancellationToken,
+    progress_indicator: ProgressIndicator,
+    receiver: Receiver<Result<ImpostorBakeResult, ImpostorBakeError>>,
+}
+
+pub struct ImpostorPanel {
+    pub window: Handle<UiNode>,
+    nud_frame_count: Handle<UiNode>,
+    nud_frame_size: Handle<UiNode>,
+    nud_lod_distance: Handle<UiNode>,
+    blend_frames: Handle<UiNode>,
+    root_text: Handle<UiNode>,
+    bake: Handle<UiNode>,
+    cancel: Handle<UiNode>,
+    progress_bar: Handle<UiNode>,
+    progress_text: Handle<UiNode>,
+    settings: ImpostorSettings,
+    root: Handle<Node>,
+    bake_task: Option<BakeTask>,
+}
+
+// Atlases are saved next to the model of a prefab instance, so every instance of the prefab could
+// use them. Atlases of other objects are saved next to the scene.
+fn impostor_folder(editor_scene: &EditorScene, scene: &Scene, root: Handle<Node>) -> PathBuf {
+    let path = scene.graph[root]
+        .resource()
+        .map(|model| model.state().path().to_path_buf())
+        .or_else(|| editor_scene.path.clone());
+
+    match path {
+        Some(path) => {
+            let stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            path.with_file_name(format!("{}_impostor", stem))
+        }
+        None => PathBuf::from("impostor"),
+    }
+}
+
+impl ImpostorPanel {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let settings = ImpostorSettings::default();
+
+        let nud_frame_count;
+        let nud_frame_size;
+        let nud_lod_distance;
+        let blend_frames;
+        let root_text;
+        let bake;
+        let cancel;
+        let progress_bar;
+        let progress_text;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(250.0))
+            .with_title(WindowTitle::Text("Impostor Baker".to_owned()))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(make_label("Angles", 0, ctx))
+                        .with_child({
+                            nud_frame_count = make_numeric(
+                                0,
+                                settings.frame_count as f32,
+                                1.0,
+                                64.0,
+                                1.0,
+                                0,
+                                ctx,
+                            );
+                            nud_frame_count
+                        })
+                        .with_child(make_label("Resolution", 1, ctx))
+                        .with_child({
+                            nud_frame_size = make_numeric(
+                                1,
+                                settings.frame_size as f32,
+                                16.0,
+                                1024.0,
+                                16.0,
+                                0,
+                                ctx,
+                            );
+                            nud_frame_size
+                        })
+                        .with_child(make_label("LOD Distance", 2, ctx))
+                        .with_child({
+                            nud_lod_distance =
+                                make_numeric(2, settings.lod_distance, 0.0, 1.0, 0.01, 2, ctx);
+                            nud_lod_distance
+                        })
+                        .with_child(make_label("Blend Frames", 3, ctx))
+                        .with_child({
+                            blend_frames = CheckBoxBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(3)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .checked(Some(settings.blend_frames))
+                            .build(ctx);
+                            blend_frames
+                        })
+                        .with_child(make_label("Object", 4, ctx))
+                        .with_child({
+                            root_text = make_text(4, ctx);
+                            root_text
+                        })
+                        .with_child({
+                            bake = ButtonBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(5)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_text("Bake")
+                            .build(ctx);
+                            bake
+                        })
+                        .with_child({
+                            cancel = ButtonBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_enabled(false)
+                                    .on_row(6)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_text("Cancel")
+                            .build(ctx);
+                            cancel
+                        })
+                        .with_child({
+                            progress_bar = ProgressBarBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_visibility(false)
+                                    .on_row(7)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .build(ctx);
+                            progress_bar
+                        })
+                        .with_child({
+                            progress_text = make_text(8, ctx);
+                            progress_text
+                        }),
+                )
+                .add_column(Column::strict(100.0))
+                .add_column(Column::stretch())
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(16.0))
+                .add_row(Row::strict(25.0))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            nud_frame_count,
+            nud_frame_size,
+            nud_lod_distance,
+            blend_frames,
+            root_text,
+            bake,
+            cancel,
+            progress_bar,
+            progress_text,
+            settings,
+            root: Handle::NONE,
+            bake_task: None,
+        }
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+    ) {
+        scope_profile!();
+
+        if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.bake {
+                self.start_bake(editor_scene, engine);
+            } else if message.destination() == self.cancel {
+                if let Some(bake_task) = self.bake_task.as_ref() {
+                    bake_task.cancellation_token.cancel();
+                }
+            }
+        } else if let Some(&NumericUpDownMessage::Value(value)) =
+            message.data::<NumericUpDownMessage<f32>>()
+        {
+            if message.direction() == MessageDirection::FromWidget {
+                if message.destination() == self.nud_frame_count {
+                    self.settings.frame_count = value as u32;
+                } else if message.destination() == self.nud_frame_size {
+                    self.settings.frame_size = value as u32;
+                } else if message.destination() == self.nud_lod_distance {
+                    self.settings.lod_distance = value;
+                }
+            }
+        } else if let Some(&CheckBoxMessage::Check(Some(value))) = message.data::<CheckBoxMessage>()
+        {
+            if message.destination() == self.blend_frames
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.settings.blend_frames = value;
+            }
+        }
+    }
+
+    // The first selected node is baked, usually it is a root of a prefab instance.
+    fn selected_root(editor_scene: &EditorScene, scene: &Scene) -> Handle<Node> {
+        if let Selection::Graph(selection) = &editor_scene.selection {
+            if let Some(&first) = selection.nodes().first() {
+                if scene.graph.is_valid_handle(first) && first != scene.graph.get_root() {
+                    return first;
+                }
+            }
+        }
+        Handle::NONE
+    }
+
+    fn start_bake(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        if self.bake_task.is_some() {
+            return;
+        }
+
+        let scene = &mut engine.scenes[editor_scene.scene];
+        let root = Self::selected_root(editor_scene, scene);
+        if root.is_none() {
+            Log::err("Select an object to bake an impostor for.".to_owned());
+            return;
+        }
+
+        let cancellation_token = CancellationToken::new();
+        let progress_indicator = ProgressIndicator::new();
+
+        // Snapshot is made on the main thread, everything else is done in background.
+        let input = match ImpostorInputData::from_graph(
+            &mut scene.graph,
+            root,
+            self.settings.clone(),
+            |_, _| true,
+            cancellation_token.clone(),
+        ) {
+            Ok(input) => input,
+            Err(e) => {
+                Log::err(format!("Unable to bake impostor. Reason: {}", e));
+                return;
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let thread_cancellation_token = cancellation_token.clone();
+        let thread_progress_indicator = progress_indicator.clone();
+        std::thread::spawn(move || {
+            let result =
+                impostor::bake(input, thread_cancellation_token, thread_progress_indicator);
+            // The editor might be closed already.
+            let _ = sender.send(result);
+        });
+
+        self.bake_task = Some(BakeTask {
+            scene: editor_scene.scene,
+            root,
+            cancellation_token,
+            progress_indicator,
+            receiver,
+        });
+
+        self.sync_progress(&engine.user_interface);
+    }
+
+    fn sync_progress(&self, ui: &UserInterface) {
+        let in_progress = self.bake_task.is_some();
+
+        ui.send_message(WidgetMessage::visibility(
+            self.progress_bar,
+            MessageDirection::ToWidget,
+            in_progress,
+        ));
+        ui.send_message(WidgetMessage::enabled(
+            self.bake,
+            MessageDirection::ToWidget,
+            !in_progress,
+        ));
+        ui.send_message(WidgetMessage::enabled(
+            self.cancel,
+            MessageDirection::ToWidget,
+            in_progress,
+        ));
+
+        let text = if let Some(bake_task) = self.bake_task.as_ref() {
+            let progress = &bake_task.progress_indicator;
+
+            ui.send_message(ProgressBarMessage::progress(
+                self.progress_bar,
+                MessageDirection::ToWidget,
+                progress.progress_percent() as f32 / 100.0,
+            ));
+
+            if bake_task.cancellation_token.is_cancelled() {
+                "Cancelling...".to_owned()
+            } else {
+                format!("Baking: {}%", progress.progress_percent())
+            }
+        } else {
+            Default::default()
+        };
+
+        ui.send_message(TextMessage::text(
+            self.progress_text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+
+    fn update_bake(
+        &mut self,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
+        let bake_task = match self.bake_task.as_ref() {
+            Some(bake_task) => bake_task,
+            None => return,
+        };
+
+        let result = match bake_task.receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => {
+                self.sync_progress(&engine.user_interface);
+                return;
+            }
+            Err(TryRecvError::Disconnected) => {
+                Log::err("Impostor baking thread has crashed!".to_owned());
+                self.bake_task = None;
+                self.sync_progress(&engine.user_interface);
+                return;
+            }
+        };
+
+        let scene = bake_task.scene;
+        let root = bake_task.root;
+        self.bake_task = None;
+        self.sync_progress(&engine.user_interface);
+
+        match result {
+            Ok(result) => {
+                let scene_ref = &mut engine.scenes[editor_scene.scene];
+                if scene != editor_scene.scene || !scene_ref.graph.is_valid_handle(root) {
+                    Log::warn(
+                        "Impostor was discarded, because the scene was changed during baking."
+                            .to_owned(),
+                    );
+                    return;
+                }
+
+                let folder = impostor_folder(editor_scene, scene_ref, root);
+                if let Err(e) = result.save(&folder, engine.resource_manager.clone()) {
+                    Log::err(format!(
+                        "Unable to save impostor atlases to {}. Reason: {}",
+                        folder.display(),
+                        e
+                    ));
+                    return;
+                }
+
+                let graph = &mut scene_ref.graph;
+
+                // Previously baked impostors are replaced with the new one.
+                let stale = graph[root]
+                    .children()
+                    .iter()
+                    .cloned()
+                    .filter(|&child| graph[child].cast::<Impostor>().is_some())
+                    .collect();
+
+                // The new impostor is moved out of the graph, so the command could add it.
+                let impostor = result.instantiate(graph, root);
+                let lod_group = result.make_lod_group(graph, root, impostor);
+                let added = graph.take_reserve_sub_graph(impostor);
+
+                Log::info(format!(
+                    "Impostor baking is done: {} frames are saved to {}.",
+                    result.frame_count,
+                    folder.display()
+                ));
+
+                sender
+                    .send(Message::do_scene_command(BakeImpostorCommand::new(
+                        root, added, stale, lod_group,
+                    )))
+                    .unwrap();
+            }
+            Err(ImpostorBakeError::Cancelled) => {
+                Log::info("Impostor baking was cancelled.".to_owned())
+            }
+            Err(e) => Log::err(format!("Unable to bake impostor. Reason: {}", e)),
+        }
+    }
+
+    /// Tracks impostor baking and shows the node that will be baked.
+    pub fn update(
+        &mut self,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
+        let scene = &engine.scenes[editor_scene.scene];
+        let root = Self::selected_root(editor_scene, scene);
+        if root != self.root {
+            self.root = root;
+            let name = if root.is_none() {
+                "Nothing Selected".to_owned()
+            } else {
+                scene.graph[root].name_owned()
+            };
+            engine.user_interface.send_message(TextMessage::text(
+                self.root_text,
+                MessageDirection::ToWidget,
+                name,
+            ));
+        }
+
+        self.update_bake(editor_scene, engine, sender);
+    }
+}
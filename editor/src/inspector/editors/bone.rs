@@ -0,0 +1,212 @@
+use crate::{gui::make_dropdown_list_option, inspector::EditorEnvironment};
+use fyrox::{
+    core::pool::Handle,
+    gui::{
+        define_constructor,
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
+        inspector::{
+            editors::{
+                PropertyEditorBuildContext, PropertyEditorDefinition, PropertyEditorInstance,
+                PropertyEditorMessageContext, PropertyEditorTranslationContext,
+            },
+            FieldKind, InspectorError, PropertyChanged,
+        },
+        message::{MessageDirection, UiMessage},
+        widget::{Widget, WidgetBuilder},
+        BuildContext, Control, UiNode, UserInterface,
+    },
+    scene::{mesh::socket::Socket, node::Node},
+};
+use std::{
+    any::{Any, TypeId},
+    ops::{Deref, DerefMut},
+};
+
+/// Returns `true` if the property is a bone of a mesh socket. Such properties are edited by
+/// [`BonePropertyEditorDefinition`] instead of generic handle editor, so the user can pick a bone
+/// of the mesh by its name.
+pub fn is_socket_bone(owner_type_id: TypeId, name: &str) -> bool {
+    owner_type_id == TypeId::of::<Socket>() && name == Socket::BONE
+}
+
+#[derive(Debug, Clone)]
+pub struct BoneEditor {
+    widget: Widget,
+    list: Handle<UiNode>,
+    bones: Vec<Handle<Node>>,
+    value: Handle<Node>,
+}
+
+impl Deref for BoneEditor {
+    type Target = Widget;
+
+    fn deref(&self) -> &Self::Target {
+        &self.widget
+    }
+}
+
+impl DerefMut for BoneEditor {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.widget
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum BoneEditorMessage {
+    Value(Handle<Node>),
+}
+
+impl BoneEditorMessage {
+    define_constructor!(BoneEditorMessage:Value => fn value(Handle<Node>), layout: false);
+}
+
+impl Control for BoneEditor {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(DropdownListMessage::SelectionChanged(Some(i))) = message.data() {
+            if message.destination() == self.list
+                && message.direction() == MessageDirection::FromWidget
+            {
+                if let Some(bone) = self.bones.get(*i) {
+                    ui.send_message(BoneEditorMessage::value(
+                        self.handle(),
+                        MessageDirection::ToWidget,
+                        *bone,
+                    ));
+                }
+            }
+        } else if let Some(BoneEditorMessage::Value(bone)) = message.data::<BoneEditorMessage>() {
+            if message.destination() == self.handle()
+                && message.direction() == MessageDirection::ToWidget
+                && *bone != self.value
+            {
+                self.value = *bone;
+
+                ui.send_message(DropdownListMessage::selection(
+                    self.list,
+                    MessageDirection::ToWidget,
+                    self.bones.iter().position(|b| b == bone),
+                ));
+
+                ui.send_message(message.reverse());
+            }
+        }
+    }
+}
+
+pub struct BoneEditorBuilder {
+    widget_builder: WidgetBuilder,
+    value: Handle<Node>,
+    bones: Vec<(Handle<Node>, String)>,
+}
+
+impl BoneEditorBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            value: Default::default(),
+            bones: Default::default(),
+        }
+    }
+
+    pub fn with_value(mut self, value: Handle<Node>) -> Self {
+        self.value = value;
+        self
+    }
+
+    /// Sets bones that will be shown in the drop-down list.
+    pub fn with_bones(mut self, bones: Vec<(Handle<Node>, String)>) -> Self {
+        self.bones = bones;
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let items = self
+            .bones
+            .iter()
+            .map(|(_, name)| make_dropdown_list_option(ctx, name))
+            .collect::<Vec<_>>();
+
+        let list = DropdownListBuilder::new(WidgetBuilder::new().with_height(26.0))
+            .with_opt_selected(self.bones.iter().position(|(b, _)| *b == self.value))
+            .with_items(items)
+            .build(ctx);
+
+        let editor = BoneEditor {
+            widget: self.widget_builder.with_child(list).build(),
+            list,
+            bones: self.bones.into_iter().map(|(b, _)| b).collect(),
+            value: self.value,
+        };
+
+        ctx.add_node(UiNode::new(editor))
+    }
+}
+
+/// Property editor for [`Socket::bone`] - a drop-down list with the bones of the selected mesh.
+/// It is not registered in the definition container directly, because the bone is a plain
+/// `Handle<Node>`, instead the handle editor delegates socket bones to it.
+#[derive(Debug)]
+pub struct BonePropertyEditorDefinition;
+
+impl PropertyEditorDefinition for BonePropertyEditorDefinition {
+    fn value_type_id(&self) -> TypeId {
+        TypeId::of::<Handle<Node>>()
+    }
+
+    fn create_instance(
+        &self,
+        ctx: PropertyEditorBuildContext,
+    ) -> Result<PropertyEditorInstance, InspectorError> {
+        let value = ctx.property_info.cast_value::<Handle<Node>>()?;
+
+        let bones = ctx
+            .environment
+            .as_ref()
+            .and_then(|e| e.as_any().downcast_ref::<EditorEnvironment>())
+            .map(|e| e.mesh_bones.to_vec())
+            .unwrap_or_default();
+
+        Ok(PropertyEditorInstance::Simple {
+            editor: BoneEditorBuilder::new(WidgetBuilder::new())
+                .with_value(*value)
+                .with_bones(bones)
+                .build(ctx.build_context),
+        })
+    }
+
+    fn create_message(
+        &self,
+        ctx: PropertyEditorMessageContext,
+    ) -> Result<Option<UiMessage>, InspectorError> {
+        let value = ctx.property_info.cast_value::<Handle<Node>>()?;
+
+        Ok(Some(BoneEditorMessage::value(
+            ctx.instance,
+            MessageDirection::ToWidget,
+            *value,
+        )))
+    }
+
+    fn translate_message(&self, ctx: PropertyEditorTranslationContext) -> Option<PropertyChanged> {
+        if ctx.message.direction() == MessageDirection::FromWidget {
+            if let Some(BoneEditorMessage::Value(value)) = ctx.message.data::<BoneEditorMessage>() {
+                return Some(PropertyChanged {
+                    owner_type_id: ctx.owner_type_id,
+                    name: ctx.name.to_string(),
+                    value: FieldKind::object(*value),
+                });
+            }
+        }
+        None
+    }
+}
@@ -1,5 +1,11 @@
 use crate::{
-    world::graph::item::SceneItem, Message, UiMessage, UiNode, UserInterface, VerticalAlignment,
+    gui::make_dropdown_list_option,
+    inspector::{
+        editors::bone::{is_socket_bone, BonePropertyEditorDefinition},
+        EditorEnvironment,
+    },
+    world::graph::item::SceneItem,
+    Message, UiMessage, UiNode, UserInterface, VerticalAlignment,
 };
 use fyrox::gui::button::ButtonMessage;
 use fyrox::gui::inspector::editors::PropertyEditorTranslationContext;
@@ -8,6 +14,7 @@ use fyrox::{
     gui::{
         button::ButtonBuilder,
         define_constructor,
+        dropdown_list::{DropdownListBuilder, DropdownListMessage},
         grid::{Column, GridBuilder, Row},
         inspector::{
             editors::{
@@ -22,6 +29,7 @@ use fyrox::{
         widget::{Widget, WidgetBuilder, WidgetMessage},
         BuildContext, Control,
     },
+    scene::node::Node,
 };
 use std::{
     any::{Any, TypeId},
@@ -59,6 +67,8 @@ pub struct HandlePropertyEditor<T> {
     text: Handle<UiNode>,
     locate: Handle<UiNode>,
     select: Handle<UiNode>,
+    picker: Handle<UiNode>,
+    candidates: Vec<Handle<T>>,
     value: Handle<T>,
     sender: Sender<Message>,
 }
@@ -72,6 +82,8 @@ impl<T> Clone for HandlePropertyEditor<T> {
             sender: self.sender.clone(),
             locate: self.locate,
             select: self.select,
+            picker: self.picker,
+            candidates: self.candidates.clone(),
         }
     }
 }
@@ -117,8 +129,26 @@ impl<T: 'static> Control for HandlePropertyEditor<T> {
                     format!("{}", *handle),
                 ));
 
+                ui.send_message(DropdownListMessage::selection(
+                    self.picker,
+                    MessageDirection::ToWidget,
+                    self.candidates.iter().position(|c| c == handle),
+                ));
+
                 ui.send_message(message.reverse());
             }
+        } else if let Some(DropdownListMessage::SelectionChanged(Some(i))) = message.data() {
+            if message.destination() == self.picker
+                && message.direction() == MessageDirection::FromWidget
+            {
+                if let Some(candidate) = self.candidates.get(*i) {
+                    ui.send_message(HandlePropertyEditorMessage::value(
+                        self.handle(),
+                        MessageDirection::ToWidget,
+                        *candidate,
+                    ));
+                }
+            }
         } else if let Some(WidgetMessage::Drop(dropped)) = message.data() {
             if message.destination() == self.handle() {
                 if let Some(item) = ui.node(*dropped).cast::<SceneItem<T>>() {
@@ -152,6 +182,7 @@ impl<T: 'static> Control for HandlePropertyEditor<T> {
 struct HandlePropertyEditorBuilder<T> {
     widget_builder: WidgetBuilder,
     value: Handle<T>,
+    candidates: Vec<(Handle<T>, String)>,
     sender: Sender<Message>,
 }
 
@@ -161,6 +192,7 @@ impl<T: 'static> HandlePropertyEditorBuilder<T> {
             widget_builder,
             sender,
             value: Default::default(),
+            candidates: Default::default(),
         }
     }
 
@@ -169,10 +201,18 @@ impl<T: 'static> HandlePropertyEditorBuilder<T> {
         self
     }
 
+    /// Sets objects that will be shown in the drop-down list, the list is hidden if there is no
+    /// candidates.
+    pub fn with_candidates(mut self, candidates: Vec<(Handle<T>, String)>) -> Self {
+        self.candidates = candidates;
+        self
+    }
+
     pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
         let text;
         let locate;
         let select;
+        let picker;
         let grid = GridBuilder::new(
             WidgetBuilder::new()
                 .with_child({
@@ -209,9 +249,28 @@ impl<T: 'static> HandlePropertyEditorBuilder<T> {
                     .with_text("*")
                     .build(ctx);
                     select
+                })
+                .with_child({
+                    let items = self
+                        .candidates
+                        .iter()
+                        .map(|(_, name)| make_dropdown_list_option(ctx, name))
+                        .collect::<Vec<_>>();
+                    picker = DropdownListBuilder::new(
+                        WidgetBuilder::new()
+                            .with_visibility(!items.is_empty())
+                            .with_tooltip(make_simple_tooltip(ctx, "Pick Object"))
+                            .on_row(1)
+                            .with_height(26.0),
+                    )
+                    .with_opt_selected(self.candidates.iter().position(|(c, _)| *c == self.value))
+                    .with_items(items)
+                    .build(ctx);
+                    picker
                 }),
         )
         .add_row(Row::stretch())
+        .add_row(Row::auto())
         .add_column(Column::stretch())
         .add_column(Column::auto())
         .add_column(Column::auto())
@@ -232,6 +291,8 @@ impl<T: 'static> HandlePropertyEditorBuilder<T> {
             sender: self.sender,
             locate,
             select,
+            picker,
+            candidates: self.candidates.into_iter().map(|(c, _)| c).collect(),
         };
 
         ctx.add_node(UiNode::new(editor))
@@ -267,14 +328,41 @@ impl<T: 'static> PropertyEditorDefinition for HandlePropertyEditorDefinition<T>
         &self,
         ctx: PropertyEditorBuildContext,
     ) -> Result<PropertyEditorInstance, InspectorError> {
+        if is_socket_bone(ctx.property_info.owner_type_id, ctx.property_info.name) {
+            return BonePropertyEditorDefinition.create_instance(ctx);
+        }
+
         let value = ctx.property_info.cast_value::<Handle<T>>()?;
 
+        // Only scene nodes could be picked from the list, other objects must be dragged from the
+        // world viewer.
+        let candidates = if TypeId::of::<T>() == TypeId::of::<Node>() {
+            ctx.environment
+                .as_ref()
+                .and_then(|e| e.as_any().downcast_ref::<EditorEnvironment>())
+                .map(|e| {
+                    e.pickable_nodes
+                        .iter()
+                        .map(|handle| {
+                            (
+                                Handle::new(handle.index(), handle.generation()),
+                                e.node_paths.get(handle).cloned().unwrap_or_default(),
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else {
+            Default::default()
+        };
+
         Ok(PropertyEditorInstance::Simple {
             editor: HandlePropertyEditorBuilder::new(
                 WidgetBuilder::new(),
                 self.sender.lock().unwrap().clone(),
             )
             .with_value(*value)
+            .with_candidates(candidates)
             .build(ctx.build_context),
         })
     }
@@ -283,6 +371,10 @@ impl<T: 'static> PropertyEditorDefinition for HandlePropertyEditorDefinition<T>
         &self,
         ctx: PropertyEditorMessageContext,
     ) -> Result<Option<UiMessage>, InspectorError> {
+        if is_socket_bone(ctx.property_info.owner_type_id, ctx.property_info.name) {
+            return BonePropertyEditorDefinition.create_message(ctx);
+        }
+
         let value = ctx.property_info.cast_value::<Handle<T>>()?;
 
         Ok(Some(HandlePropertyEditorMessage::value(
@@ -293,6 +385,10 @@ impl<T: 'static> PropertyEditorDefinition for HandlePropertyEditorDefinition<T>
     }
 
     fn translate_message(&self, ctx: PropertyEditorTranslationContext) -> Option<PropertyChanged> {
+        if is_socket_bone(ctx.owner_type_id, ctx.name) {
+            return BonePropertyEditorDefinition.translate_message(ctx);
+        }
+
         if ctx.message.direction() == MessageDirection::FromWidget {
             if let Some(HandlePropertyEditorMessage::Value(value)) =
                 ctx.message.data::<HandlePropertyEditorMessage<T>>()
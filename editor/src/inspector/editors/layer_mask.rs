@@ -0,0 +1,242 @@
+use crate::inspector::EditorEnvironment;
+use fyrox::{
+    core::pool::Handle,
+    gui::{
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        define_constructor,
+        grid::{Column, GridBuilder, Row},
+        inspector::{
+            editors::{
+                PropertyEditorBuildContext, PropertyEditorDefinition, PropertyEditorInstance,
+                PropertyEditorMessageContext, PropertyEditorTranslationContext,
+            },
+            FieldKind, InspectorError, PropertyChanged,
+        },
+        message::{MessageDirection, UiMessage},
+        text::TextBuilder,
+        widget::{Widget, WidgetBuilder},
+        BuildContext, Control, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    scene::base::LayerMask,
+};
+use std::{
+    any::{Any, TypeId},
+    ops::{Deref, DerefMut},
+};
+
+/// Returns a name of a render layer to show in the editor. Layers without a name are shown as
+/// `Layer N`.
+pub fn layer_name(layer_names: &[String], layer: usize) -> String {
+    match layer_names.get(layer) {
+        Some(name) if !name.is_empty() => name.clone(),
+        _ => format!("Layer {}", layer),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LayerMaskEditor {
+    widget: Widget,
+    check_boxes: Vec<Handle<UiNode>>,
+    mask: LayerMask,
+}
+
+impl Deref for LayerMaskEditor {
+    type Target = Widget;
+
+    fn deref(&self) -> &Self::Target {
+        &self.widget
+    }
+}
+
+impl DerefMut for LayerMaskEditor {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.widget
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum LayerMaskEditorMessage {
+    Value(LayerMask),
+}
+
+impl LayerMaskEditorMessage {
+    define_constructor!(LayerMaskEditorMessage:Value => fn value(LayerMask), layout: false);
+}
+
+impl Control for LayerMaskEditor {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(CheckBoxMessage::Check(Some(value))) = message.data::<CheckBoxMessage>() {
+            if message.direction() == MessageDirection::FromWidget {
+                if let Some(layer) = self
+                    .check_boxes
+                    .iter()
+                    .position(|c| *c == message.destination())
+                {
+                    let mut mask = self.mask;
+                    mask.set_layer(layer, *value);
+                    if mask != self.mask {
+                        ui.send_message(LayerMaskEditorMessage::value(
+                            self.handle(),
+                            MessageDirection::ToWidget,
+                            mask,
+                        ));
+                    }
+                }
+            }
+        } else if let Some(LayerMaskEditorMessage::Value(mask)) =
+            message.data::<LayerMaskEditorMessage>()
+        {
+            if message.destination() == self.handle()
+                && message.direction() == MessageDirection::ToWidget
+                && *mask != self.mask
+            {
+                self.mask = *mask;
+
+                for (layer, check_box) in self.check_boxes.iter().enumerate() {
+                    ui.send_message(CheckBoxMessage::checked(
+                        *check_box,
+                        MessageDirection::ToWidget,
+                        Some(mask.contains_layer(layer)),
+                    ));
+                }
+
+                ui.send_message(message.reverse());
+            }
+        }
+    }
+}
+
+pub struct LayerMaskEditorBuilder {
+    widget_builder: WidgetBuilder,
+    mask: LayerMask,
+    layer_names: Vec<String>,
+}
+
+impl LayerMaskEditorBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            mask: LayerMask::ALL,
+            layer_names: Default::default(),
+        }
+    }
+
+    pub fn with_mask(mut self, mask: LayerMask) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    pub fn with_layer_names(mut self, layer_names: Vec<String>) -> Self {
+        self.layer_names = layer_names;
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        const COLUMNS: usize = 2;
+
+        let check_boxes = (0..LayerMask::LAYER_COUNT)
+            .map(|layer| {
+                CheckBoxBuilder::new(
+                    WidgetBuilder::new()
+                        .on_row(layer / COLUMNS)
+                        .on_column(layer % COLUMNS)
+                        .with_margin(Thickness::uniform(1.0)),
+                )
+                .with_content(
+                    TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::left(2.0)))
+                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                        .with_text(layer_name(&self.layer_names, layer))
+                        .build(ctx),
+                )
+                .checked(Some(self.mask.contains_layer(layer)))
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let grid =
+            GridBuilder::new(WidgetBuilder::new().with_children(check_boxes.iter().cloned()))
+                .add_columns((0..COLUMNS).map(|_| Column::stretch()).collect())
+                .add_rows(
+                    (0..LayerMask::LAYER_COUNT / COLUMNS)
+                        .map(|_| Row::strict(20.0))
+                        .collect(),
+                )
+                .build(ctx);
+
+        let editor = LayerMaskEditor {
+            widget: self.widget_builder.with_child(grid).build(),
+            check_boxes,
+            mask: self.mask,
+        };
+
+        ctx.add_node(UiNode::new(editor))
+    }
+}
+
+#[derive(Debug)]
+pub struct LayerMaskPropertyEditorDefinition;
+
+impl PropertyEditorDefinition for LayerMaskPropertyEditorDefinition {
+    fn value_type_id(&self) -> TypeId {
+        TypeId::of::<LayerMask>()
+    }
+
+    fn create_instance(
+        &self,
+        ctx: PropertyEditorBuildContext,
+    ) -> Result<PropertyEditorInstance, InspectorError> {
+        let value = ctx.property_info.cast_value::<LayerMask>()?;
+
+        let layer_names = ctx
+            .environment
+            .as_ref()
+            .and_then(|e| e.as_any().downcast_ref::<EditorEnvironment>())
+            .map(|e| e.layer_names.clone())
+            .unwrap_or_default();
+
+        Ok(PropertyEditorInstance::Simple {
+            editor: LayerMaskEditorBuilder::new(WidgetBuilder::new())
+                .with_mask(*value)
+                .with_layer_names(layer_names)
+                .build(ctx.build_context),
+        })
+    }
+
+    fn create_message(
+        &self,
+        ctx: PropertyEditorMessageContext,
+    ) -> Result<Option<UiMessage>, InspectorError> {
+        let value = ctx.property_info.cast_value::<LayerMask>()?;
+
+        Ok(Some(LayerMaskEditorMessage::value(
+            ctx.instance,
+            MessageDirection::ToWidget,
+            *value,
+        )))
+    }
+
+    fn translate_message(&self, ctx: PropertyEditorTranslationContext) -> Option<PropertyChanged> {
+        if ctx.message.direction() == MessageDirection::FromWidget {
+            if let Some(LayerMaskEditorMessage::Value(value)) =
+                ctx.message.data::<LayerMaskEditorMessage>()
+            {
+                return Some(PropertyChanged {
+                    owner_type_id: ctx.owner_type_id,
+                    name: ctx.name.to_string(),
+                    value: FieldKind::object(*value),
+                });
+            }
+        }
+        None
+    }
+}
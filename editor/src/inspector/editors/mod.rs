@@ -1,8 +1,12 @@
-use crate::inspector::editors::script::ScriptPropertyEditorDefinition;
+use crate::inspector::editors::script::{
+    ScriptComponentPropertyEditorDefinition, ScriptPropertyEditorDefinition,
+};
 use crate::{
     inspector::editors::{
         handle::HandlePropertyEditorDefinition,
+        layer_mask::LayerMaskPropertyEditorDefinition,
         material::MaterialPropertyEditorDefinition,
+        node_path::NodePathPropertyEditorDefinition,
         resource::{
             ModelResourcePropertyEditorDefinition, SoundBufferResourcePropertyEditorDefinition,
         },
@@ -20,8 +24,8 @@ use fyrox::{
     resource::{
         model::MaterialSearchOptions,
         texture::{
-            CompressionOptions, TextureMagnificationFilter, TextureMinificationFilter,
-            TextureWrapMode,
+            CompressionOptions, TextureCompressionFormat, TextureMagnificationFilter,
+            TextureMinificationFilter, TextureWrapMode,
         },
     },
     scene::{
@@ -34,29 +38,43 @@ use fyrox::{
         },
         collider::{ColliderShape, GeometrySource, InteractionGroups},
         dim2,
-        graph::physics::CoefficientCombineRule,
+        fog_volume::FogVolumeShape,
+        graph::physics::{
+            CoefficientCombineRule, IntegrationParameters, PhysicsSettings, PhysicsStepMode,
+        },
         joint::*,
         light::{
             directional::{CsmOptions, FrustumSplitOptions},
-            BaseLight,
+            spot::SpotLightProjection,
+            BaseLight, ScatterPreset,
         },
-        mesh::{surface::Surface, RenderPath},
+        mesh::{socket::Socket, surface::Surface, RenderPath},
+        navmesh_obstacle::NavmeshObstacleShape,
         node::Node,
-        particle_system::emitter::{base::BaseEmitter, Emitter},
+        particle_system::{
+            emitter::{base::BaseEmitter, Emitter},
+            SimulationSpace,
+        },
         rigidbody::RigidBodyType,
         sound::{
             self,
+            context::OcclusionSettings,
             effect::{BaseEffect, EffectInput},
-            Biquad, DistanceModel, Status,
+            AirAbsorption, Biquad, DistanceModel, Status,
         },
         terrain::Layer,
         transform::Transform,
+        trigger_volume::TriggerVolumeShape,
+        wind::Wind,
     },
 };
 use std::sync::mpsc::Sender;
 
+pub mod bone;
 pub mod handle;
+pub mod layer_mask;
 pub mod material;
+pub mod node_path;
 pub mod resource;
 pub mod script;
 pub mod texture;
@@ -86,10 +104,13 @@ pub fn make_property_editors_container(
     let container = PropertyEditorDefinitionContainer::new();
 
     container.insert(TexturePropertyEditorDefinition);
+    container.insert(LayerMaskPropertyEditorDefinition);
+    container.insert(NodePathPropertyEditorDefinition);
     container.insert(MaterialPropertyEditorDefinition {
         sender: Mutex::new(sender.clone()),
     });
     container.insert(VecCollectionPropertyEditorDefinition::<Surface>::new());
+    container.insert(VecCollectionPropertyEditorDefinition::<Socket>::new());
     container.insert(VecCollectionPropertyEditorDefinition::<Layer>::new());
     container.insert(VecCollectionPropertyEditorDefinition::<Emitter>::new());
     container.insert(VecCollectionPropertyEditorDefinition::<LevelOfDetail>::new());
@@ -102,9 +123,12 @@ pub fn make_property_editors_container(
     container.insert(make_status_enum_editor_definition());
     container.insert(EnumPropertyEditorDefinition::<f32>::new_optional());
     container.insert(EnumPropertyEditorDefinition::<LodGroup>::new_optional());
+    container.insert(EnumPropertyEditorDefinition::<dim2::rectangle::NineSlice>::new_optional());
     container.insert(ModelResourcePropertyEditorDefinition);
     container.insert(SoundBufferResourcePropertyEditorDefinition);
     container.insert(InspectablePropertyEditorDefinition::<InteractionGroups>::new());
+    container.insert(InspectablePropertyEditorDefinition::<OcclusionSettings>::new());
+    container.insert(InspectablePropertyEditorDefinition::<AirAbsorption>::new());
     container.insert(InspectablePropertyEditorDefinition::<ColliderShape>::new());
     container.insert(InspectablePropertyEditorDefinition::<GeometrySource>::new());
     container.insert(InspectablePropertyEditorDefinition::<JointParams>::new());
@@ -116,6 +140,12 @@ pub fn make_property_editors_container(
     container.insert(InspectablePropertyEditorDefinition::<OrthographicProjection>::new());
     container.insert(InspectablePropertyEditorDefinition::<Transform>::new());
     container.insert(InspectablePropertyEditorDefinition::<CsmOptions>::new());
+    container.insert(InspectablePropertyEditorDefinition::<Wind>::new());
+    container.insert(InspectablePropertyEditorDefinition::<PhysicsSettings>::new());
+    container.insert(InspectablePropertyEditorDefinition::<
+        dim2::physics::PhysicsSettings,
+    >::new());
+    container.insert(InspectablePropertyEditorDefinition::<IntegrationParameters>::new());
     container.insert(ArrayPropertyEditorDefinition::<f32, 3>::new());
     container.insert(ArrayPropertyEditorDefinition::<f32, 2>::new());
     container.insert(EnumPropertyEditorDefinition::<ColorGradingLut>::new_optional());
@@ -124,7 +154,9 @@ pub fn make_property_editors_container(
     container.insert(HandlePropertyEditorDefinition::<Node>::new(sender));
     container.insert(EnumPropertyEditorDefinition::<dim2::collider::ColliderShape>::new());
     container.insert(EnumPropertyEditorDefinition::<CoefficientCombineRule>::new());
+    container.insert(EnumPropertyEditorDefinition::<PhysicsStepMode>::new());
     container.insert(EnumPropertyEditorDefinition::<CompressionOptions>::new());
+    container.insert(EnumPropertyEditorDefinition::<TextureCompressionFormat>::new());
     container.insert(EnumPropertyEditorDefinition::<TextureWrapMode>::new());
     container.insert(EnumPropertyEditorDefinition::<TextureMagnificationFilter>::new());
     container.insert(EnumPropertyEditorDefinition::<TextureMinificationFilter>::new());
@@ -135,11 +167,18 @@ pub fn make_property_editors_container(
     container.insert(EnumPropertyEditorDefinition::<RigidBodyType>::new());
     container.insert(EnumPropertyEditorDefinition::<Exposure>::new());
     container.insert(EnumPropertyEditorDefinition::<RenderPath>::new());
+    container.insert(EnumPropertyEditorDefinition::<SpotLightProjection>::new());
+    container.insert(EnumPropertyEditorDefinition::<ScatterPreset>::new());
+    container.insert(EnumPropertyEditorDefinition::<FogVolumeShape>::new());
+    container.insert(EnumPropertyEditorDefinition::<TriggerVolumeShape>::new());
+    container.insert(EnumPropertyEditorDefinition::<NavmeshObstacleShape>::new());
+    container.insert(EnumPropertyEditorDefinition::<SimulationSpace>::new());
     container.insert(EnumPropertyEditorDefinition::<FrustumSplitOptions>::new());
     container.insert(EnumPropertyEditorDefinition::<MaterialSearchOptions>::new());
     container.insert(EnumPropertyEditorDefinition::<DistanceModel>::new());
     container.insert(EnumPropertyEditorDefinition::<sound::Renderer>::new());
     container.insert(ScriptPropertyEditorDefinition {});
+    container.insert(ScriptComponentPropertyEditorDefinition {});
 
     container
 }
@@ -0,0 +1,222 @@
+use crate::{inspector::EditorEnvironment, world::graph::item::SceneItem};
+use fyrox::{
+    core::pool::Handle,
+    gui::{
+        define_constructor,
+        inspector::{
+            editors::{
+                PropertyEditorBuildContext, PropertyEditorDefinition, PropertyEditorInstance,
+                PropertyEditorMessageContext, PropertyEditorTranslationContext,
+            },
+            FieldKind, InspectorError, PropertyChanged,
+        },
+        message::{MessageDirection, UiMessage},
+        text_box::{TextBoxBuilder, TextBoxMessage},
+        utils::make_simple_tooltip,
+        widget::{Widget, WidgetBuilder, WidgetMessage},
+        BuildContext, Control, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    scene::{graph::path::NodePath, node::Node},
+    utils::log::Log,
+};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+};
+
+#[derive(Debug, Clone)]
+pub struct NodePathEditor {
+    widget: Widget,
+    text_box: Handle<UiNode>,
+    value: NodePath,
+    node_paths: Rc<HashMap<Handle<Node>, String>>,
+}
+
+impl Deref for NodePathEditor {
+    type Target = Widget;
+
+    fn deref(&self) -> &Self::Target {
+        &self.widget
+    }
+}
+
+impl DerefMut for NodePathEditor {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.widget
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum NodePathEditorMessage {
+    Value(NodePath),
+}
+
+impl NodePathEditorMessage {
+    define_constructor!(NodePathEditorMessage:Value => fn value(NodePath), layout: false);
+}
+
+impl Control for NodePathEditor {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(NodePathEditorMessage::Value(value)) = message.data::<NodePathEditorMessage>() {
+            if message.destination() == self.handle()
+                && message.direction() == MessageDirection::ToWidget
+                && &self.value != value
+            {
+                self.value = value.clone();
+
+                ui.send_message(TextBoxMessage::text(
+                    self.text_box,
+                    MessageDirection::ToWidget,
+                    value.to_string(),
+                ));
+
+                ui.send_message(message.reverse());
+            }
+        } else if let Some(TextBoxMessage::Text(text)) = message.data::<TextBoxMessage>() {
+            if message.destination() == self.text_box
+                && message.direction() == MessageDirection::FromWidget
+            {
+                ui.send_message(NodePathEditorMessage::value(
+                    self.handle(),
+                    MessageDirection::ToWidget,
+                    NodePath::new(text.clone()),
+                ));
+            }
+        } else if let Some(WidgetMessage::Drop(dropped)) = message.data() {
+            if let Some(item) = ui.node(*dropped).cast::<SceneItem<Node>>() {
+                if let Some(path) = self.node_paths.get(&item.entity_handle) {
+                    ui.send_message(NodePathEditorMessage::value(
+                        self.handle(),
+                        MessageDirection::ToWidget,
+                        NodePath::new(path.clone()),
+                    ));
+                } else {
+                    Log::warn(format!(
+                        "Unable to assign a path to {} node. Re-select the object and try again.",
+                        item.name()
+                    ));
+                }
+            }
+        }
+    }
+}
+
+struct NodePathEditorBuilder {
+    widget_builder: WidgetBuilder,
+    value: NodePath,
+}
+
+impl NodePathEditorBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            value: Default::default(),
+        }
+    }
+
+    pub fn with_value(mut self, value: NodePath) -> Self {
+        self.value = value;
+        self
+    }
+
+    pub fn build(
+        self,
+        ctx: &mut BuildContext,
+        node_paths: Rc<HashMap<Handle<Node>, String>>,
+    ) -> Handle<UiNode> {
+        let text_box = TextBoxBuilder::new(
+            WidgetBuilder::new()
+                .with_allow_drop(true)
+                .with_margin(Thickness::uniform(1.0)),
+        )
+        .with_text(self.value.as_str())
+        .with_vertical_text_alignment(VerticalAlignment::Center)
+        .build(ctx);
+
+        let editor = NodePathEditor {
+            widget: self
+                .widget_builder
+                .with_tooltip(make_simple_tooltip(
+                    ctx,
+                    "Use <Alt+Mouse Drag> in World Viewer to assign the value here.",
+                ))
+                .with_allow_drop(true)
+                .with_child(text_box)
+                .build(),
+            text_box,
+            value: self.value,
+            node_paths,
+        };
+
+        ctx.add_node(UiNode::new(editor))
+    }
+}
+
+#[derive(Debug)]
+pub struct NodePathPropertyEditorDefinition;
+
+impl PropertyEditorDefinition for NodePathPropertyEditorDefinition {
+    fn value_type_id(&self) -> TypeId {
+        TypeId::of::<NodePath>()
+    }
+
+    fn create_instance(
+        &self,
+        ctx: PropertyEditorBuildContext,
+    ) -> Result<PropertyEditorInstance, InspectorError> {
+        let value = ctx.property_info.cast_value::<NodePath>()?;
+
+        let node_paths = ctx
+            .environment
+            .as_ref()
+            .and_then(|e| e.as_any().downcast_ref::<EditorEnvironment>())
+            .map(|e| e.node_paths.clone())
+            .unwrap_or_default();
+
+        Ok(PropertyEditorInstance::Simple {
+            editor: NodePathEditorBuilder::new(WidgetBuilder::new())
+                .with_value(value.clone())
+                .build(ctx.build_context, node_paths),
+        })
+    }
+
+    fn create_message(
+        &self,
+        ctx: PropertyEditorMessageContext,
+    ) -> Result<Option<UiMessage>, InspectorError> {
+        let value = ctx.property_info.cast_value::<NodePath>()?;
+
+        Ok(Some(NodePathEditorMessage::value(
+            ctx.instance,
+            MessageDirection::ToWidget,
+            value.clone(),
+        )))
+    }
+
+    fn translate_message(&self, ctx: PropertyEditorTranslationContext) -> Option<PropertyChanged> {
+        if ctx.message.direction() == MessageDirection::FromWidget {
+            if let Some(NodePathEditorMessage::Value(value)) =
+                ctx.message.data::<NodePathEditorMessage>()
+            {
+                return Some(PropertyChanged {
+                    owner_type_id: ctx.owner_type_id,
+                    name: ctx.name.to_string(),
+                    value: FieldKind::object(value.clone()),
+                });
+            }
+        }
+        None
+    }
+}
@@ -2,9 +2,13 @@ use crate::{
     gui::make_dropdown_list_option, inspector::EditorEnvironment, send_sync_message,
     DropdownListBuilder, MSG_SYNC_FLAG,
 };
-use fyrox::engine::SerializationContext;
 use fyrox::{
-    core::{pool::Handle, uuid::Uuid},
+    core::{
+        inspect::{Inspect, PropertyValue},
+        pool::Handle,
+        uuid::Uuid,
+    },
+    engine::SerializationContext,
     gui::{
         define_constructor,
         dropdown_list::{DropdownList, DropdownListMessage},
@@ -21,7 +25,7 @@ use fyrox::{
         widget::{Widget, WidgetBuilder},
         BuildContext, Control, UiNode, UserInterface,
     },
-    script::Script,
+    script::{component::ScriptComponent, Script},
 };
 use std::{
     any::{Any, TypeId},
@@ -148,13 +152,13 @@ impl ScriptPropertyEditorBuilder {
         environment: Option<Rc<dyn InspectorEnvironment>>,
         sync_flag: u64,
         layer_index: usize,
-        script: &Option<Script>,
+        object: Option<&dyn Inspect>,
         definition_container: Rc<PropertyEditorDefinitionContainer>,
         ctx: &mut BuildContext,
     ) -> Handle<UiNode> {
-        let context = script.as_ref().map(|script| {
+        let context = object.map(|object| {
             InspectorContext::from_object(
-                script,
+                object,
                 ctx,
                 definition_container,
                 environment,
@@ -180,47 +184,85 @@ impl ScriptPropertyEditorBuilder {
     }
 }
 
+/// A set of types that could be chosen in the editor.
+struct Variants {
+    /// Name of the empty variant.
+    empty: &'static str,
+    /// Type UUIDs and names of registered types.
+    types: fn(&SerializationContext) -> Vec<(Uuid, String)>,
+}
+
+fn script_types(serialization_context: &SerializationContext) -> Vec<(Uuid, String)> {
+    serialization_context
+        .script_constructors
+        .map()
+        .iter()
+        .map(|(type_uuid, constructor)| (*type_uuid, constructor.name.clone()))
+        .collect()
+}
+
+fn component_types(serialization_context: &SerializationContext) -> Vec<(Uuid, String)> {
+    serialization_context
+        .component_constructors
+        .map()
+        .iter()
+        .map(|(type_uuid, constructor)| (*type_uuid, constructor.name.clone()))
+        .collect()
+}
+
+const SCRIPT_VARIANTS: Variants = Variants {
+    empty: "<No Script>",
+    types: script_types,
+};
+
+const COMPONENT_VARIANTS: Variants = Variants {
+    empty: "<None>",
+    types: component_types,
+};
+
 fn create_items(
+    variants: &Variants,
     serialization_context: Arc<SerializationContext>,
     ctx: &mut BuildContext,
 ) -> Vec<Handle<UiNode>> {
     let mut items = vec![{
-        let empty = make_dropdown_list_option(ctx, "<No Script>");
+        let empty = make_dropdown_list_option(ctx, variants.empty);
         ctx[empty].user_data = Some(Rc::new(Uuid::default()));
         empty
     }];
 
-    items.extend(serialization_context.script_constructors.map().iter().map(
-        |(type_uuid, constructor)| {
-            let item = make_dropdown_list_option(ctx, &constructor.name);
-            ctx[item].user_data = Some(Rc::new(*type_uuid));
-            item
-        },
-    ));
+    items.extend(
+        (variants.types)(&serialization_context)
+            .into_iter()
+            .map(|(type_uuid, name)| {
+                let item = make_dropdown_list_option(ctx, &name);
+                ctx[item].user_data = Some(Rc::new(type_uuid));
+                item
+            }),
+    );
 
     items
 }
 
-fn selected_script(
+fn selected_variant(
+    variants: &Variants,
     serialization_context: Arc<SerializationContext>,
-    value: &Option<Script>,
+    type_uuid: Option<Uuid>,
 ) -> Option<usize> {
-    value
-        .as_ref()
-        .and_then(|s| {
-            serialization_context
-                .script_constructors
-                .map()
+    type_uuid
+        .and_then(|type_uuid| {
+            (variants.types)(&serialization_context)
                 .iter()
-                .position(|(type_uuid, _)| *type_uuid == s.id())
+                .position(|(uuid, _)| *uuid == type_uuid)
         })
         .map(|n| {
-            // Because the list has `<No Script>` element
+            // Because the list has an empty element
             n + 1
         })
 }
 
-fn fetch_script_definitions(
+fn fetch_variant_definitions(
+    variants: &Variants,
     instance: Handle<UiNode>,
     ui: &mut UserInterface,
 ) -> Option<Vec<Handle<UiNode>>> {
@@ -239,7 +281,190 @@ fn fetch_script_definitions(
 
     let editor_environment = get_editor_environment(&environment);
 
-    editor_environment.map(|e| create_items(e.serialization_context.clone(), &mut ui.build_ctx()))
+    editor_environment.map(|e| {
+        create_items(
+            variants,
+            e.serialization_context.clone(),
+            &mut ui.build_ctx(),
+        )
+    })
+}
+
+fn create_variant_editor(
+    variants: &Variants,
+    ctx: PropertyEditorBuildContext,
+    type_uuid: Option<Uuid>,
+    object: Option<&dyn Inspect>,
+) -> Result<PropertyEditorInstance, InspectorError> {
+    let environment =
+        get_editor_environment(&ctx.environment).expect("Must have editor environment!");
+
+    let items = create_items(
+        variants,
+        environment.serialization_context.clone(),
+        ctx.build_context,
+    );
+
+    let variant_selector = DropdownListBuilder::new(WidgetBuilder::new())
+        .with_selected(
+            selected_variant(
+                variants,
+                environment.serialization_context.clone(),
+                type_uuid,
+            )
+            .unwrap_or(0),
+        )
+        .with_items(items)
+        .build(ctx.build_context);
+
+    let editor;
+    let container = make_expander_container(
+        ctx.layer_index,
+        ctx.property_info.display_name,
+        variant_selector,
+        {
+            editor = ScriptPropertyEditorBuilder::new(WidgetBuilder::new()).build(
+                variant_selector,
+                type_uuid,
+                ctx.environment.clone(),
+                ctx.sync_flag,
+                ctx.layer_index,
+                object,
+                ctx.definition_container.clone(),
+                ctx.build_context,
+            );
+            editor
+        },
+        ctx.build_context,
+    );
+
+    Ok(PropertyEditorInstance::Custom { container, editor })
+}
+
+// Syncs the type selector and rebuilds the nested inspector if the type was changed, otherwise
+// only values of the nested inspector are synced.
+fn sync_variant_editor(
+    variants: &Variants,
+    ctx: PropertyEditorMessageContext,
+    type_uuid: Option<Uuid>,
+    object: &dyn Inspect,
+) -> Result<Option<UiMessage>, InspectorError> {
+    let new_definitions_items = fetch_variant_definitions(variants, ctx.instance, ctx.ui);
+
+    let instance_ref = ctx
+        .ui
+        .node(ctx.instance)
+        .cast::<ScriptPropertyEditor>()
+        .expect("Must be ScriptPropertyEditor!");
+
+    let environment = ctx
+        .ui
+        .node(instance_ref.inspector)
+        .cast::<Inspector>()
+        .expect("Must be Inspector!")
+        .context()
+        .environment
+        .clone();
+
+    let editor_environment = get_editor_environment(&environment);
+
+    let variant_selector_ref = ctx
+        .ui
+        .node(instance_ref.variant_selector)
+        .cast::<DropdownList>()
+        .expect("Must be a DropDownList");
+
+    // Type list might change over time if some plugins were reloaded.
+    if Some(variant_selector_ref.items().len())
+        != editor_environment.map(|e| (variants.types)(&e.serialization_context).len())
+    {
+        if let Some(items) = new_definitions_items {
+            send_sync_message(
+                ctx.ui,
+                DropdownListMessage::items(
+                    instance_ref.variant_selector,
+                    MessageDirection::ToWidget,
+                    items,
+                ),
+            );
+            send_sync_message(
+                ctx.ui,
+                ScriptPropertyEditorMessage::value(
+                    ctx.instance,
+                    MessageDirection::ToWidget,
+                    type_uuid,
+                ),
+            );
+        }
+    }
+
+    if instance_ref.selected_script_uuid != type_uuid {
+        send_sync_message(
+            ctx.ui,
+            ScriptPropertyEditorMessage::value(ctx.instance, MessageDirection::ToWidget, type_uuid),
+        );
+
+        let inspector = instance_ref.inspector;
+
+        let context = InspectorContext::from_object(
+            object,
+            &mut ctx.ui.build_ctx(),
+            ctx.definition_container.clone(),
+            environment,
+            ctx.sync_flag,
+            ctx.layer_index + 1,
+        );
+
+        let mut msg = InspectorMessage::context(inspector, MessageDirection::ToWidget, context);
+        msg.flags = MSG_SYNC_FLAG;
+        Ok(Some(msg))
+    } else {
+        let layer_index = ctx.layer_index;
+        let inspector_ctx = ctx
+            .ui
+            .node(instance_ref.inspector)
+            .cast::<Inspector>()
+            .expect("Must be Inspector!")
+            .context()
+            .clone();
+
+        if let Err(e) = inspector_ctx.sync(object, ctx.ui, layer_index + 1) {
+            Err(InspectorError::Group(e))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+// Makes a new value for a changed type using the given constructor, or forwards a change of a
+// property of the nested inspector.
+fn translate_variant_message<T: PropertyValue>(
+    ctx: PropertyEditorTranslationContext,
+    create: fn(&SerializationContext, Option<Uuid>) -> T,
+) -> Option<PropertyChanged> {
+    if ctx.message.direction() == MessageDirection::FromWidget {
+        if let Some(message) = ctx.message.data::<ScriptPropertyEditorMessage>() {
+            match message {
+                ScriptPropertyEditorMessage::Value(value) => {
+                    if let Some(env) = get_editor_environment(&ctx.environment) {
+                        return Some(PropertyChanged {
+                            owner_type_id: ctx.owner_type_id,
+                            name: ctx.name.to_string(),
+                            value: FieldKind::object(create(&env.serialization_context, *value)),
+                        });
+                    }
+                }
+                ScriptPropertyEditorMessage::PropertyChanged(property_changed) => {
+                    return Some(PropertyChanged {
+                        name: ctx.name.to_string(),
+                        owner_type_id: ctx.owner_type_id,
+                        value: FieldKind::Inspectable(Box::new(property_changed.clone())),
+                    })
+                }
+            }
+        }
+    }
+    None
 }
 
 #[derive(Debug)]
@@ -255,41 +480,12 @@ impl PropertyEditorDefinition for ScriptPropertyEditorDefinition {
         ctx: PropertyEditorBuildContext,
     ) -> Result<PropertyEditorInstance, InspectorError> {
         let value = ctx.property_info.cast_value::<Option<Script>>()?;
-
-        let environment =
-            get_editor_environment(&ctx.environment).expect("Must have editor environment!");
-
-        let items = create_items(environment.serialization_context.clone(), ctx.build_context);
-
-        let variant_selector = DropdownListBuilder::new(WidgetBuilder::new())
-            .with_selected(
-                selected_script(environment.serialization_context.clone(), value).unwrap_or(0),
-            )
-            .with_items(items)
-            .build(ctx.build_context);
-
-        let editor;
-        let container = make_expander_container(
-            ctx.layer_index,
-            ctx.property_info.display_name,
-            variant_selector,
-            {
-                editor = ScriptPropertyEditorBuilder::new(WidgetBuilder::new()).build(
-                    variant_selector,
-                    value.as_ref().map(|s| s.id()),
-                    ctx.environment.clone(),
-                    ctx.sync_flag,
-                    ctx.layer_index,
-                    value,
-                    ctx.definition_container.clone(),
-                    ctx.build_context,
-                );
-                editor
-            },
-            ctx.build_context,
-        );
-
-        Ok(PropertyEditorInstance::Custom { container, editor })
+        create_variant_editor(
+            &SCRIPT_VARIANTS,
+            ctx,
+            value.as_ref().map(|s| s.id()),
+            value.as_ref().map(|s| s as &dyn Inspect),
+        )
     }
 
     fn create_message(
@@ -297,133 +493,58 @@ impl PropertyEditorDefinition for ScriptPropertyEditorDefinition {
         ctx: PropertyEditorMessageContext,
     ) -> Result<Option<UiMessage>, InspectorError> {
         let value = ctx.property_info.cast_value::<Option<Script>>()?;
+        sync_variant_editor(&SCRIPT_VARIANTS, ctx, value.as_ref().map(|s| s.id()), value)
+    }
 
-        let new_script_definitions_items = fetch_script_definitions(ctx.instance, ctx.ui);
-
-        let instance_ref = ctx
-            .ui
-            .node(ctx.instance)
-            .cast::<ScriptPropertyEditor>()
-            .expect("Must be EnumPropertyEditor!");
-
-        let environment = ctx
-            .ui
-            .node(instance_ref.inspector)
-            .cast::<Inspector>()
-            .expect("Must be Inspector!")
-            .context()
-            .environment
-            .clone();
-
-        let editor_environment = get_editor_environment(&environment);
-
-        let variant_selector_ref = ctx
-            .ui
-            .node(instance_ref.variant_selector)
-            .cast::<DropdownList>()
-            .expect("Must be a DropDownList");
-
-        // Script list might change over time if some plugins were reloaded.
-        if Some(variant_selector_ref.items().len())
-            != editor_environment.map(|e| {
-                e.serialization_context
-                    .script_constructors
-                    .map()
-                    .values()
-                    .count()
-            })
-        {
-            if let Some(items) = new_script_definitions_items {
-                send_sync_message(
-                    ctx.ui,
-                    DropdownListMessage::items(
-                        instance_ref.variant_selector,
-                        MessageDirection::ToWidget,
-                        items,
-                    ),
-                );
-                send_sync_message(
-                    ctx.ui,
-                    ScriptPropertyEditorMessage::value(
-                        ctx.instance,
-                        MessageDirection::ToWidget,
-                        value.as_ref().map(|s| s.id()),
-                    ),
-                );
-            }
-        }
+    fn translate_message(&self, ctx: PropertyEditorTranslationContext) -> Option<PropertyChanged> {
+        translate_variant_message(ctx, |serialization_context, type_uuid| {
+            type_uuid.and_then(|uuid| serialization_context.script_constructors.try_create(&uuid))
+        })
+    }
+}
 
-        if instance_ref.selected_script_uuid != value.as_ref().map(|s| s.id()) {
-            send_sync_message(
-                ctx.ui,
-                ScriptPropertyEditorMessage::value(
-                    ctx.instance,
-                    MessageDirection::ToWidget,
-                    value.as_ref().map(|s| s.id()),
-                ),
-            );
+/// Editor of polymorphic script fields (see [`ScriptComponent`]). The type of a component could be
+/// chosen from the list of registered component types, then fields of the component are shown
+/// right below.
+#[derive(Debug)]
+pub struct ScriptComponentPropertyEditorDefinition {}
 
-            let inspector = instance_ref.inspector;
+impl PropertyEditorDefinition for ScriptComponentPropertyEditorDefinition {
+    fn value_type_id(&self) -> TypeId {
+        TypeId::of::<ScriptComponent>()
+    }
 
-            let context = InspectorContext::from_object(
-                value,
-                &mut ctx.ui.build_ctx(),
-                ctx.definition_container.clone(),
-                environment,
-                ctx.sync_flag,
-                ctx.layer_index + 1,
-            );
+    fn create_instance(
+        &self,
+        ctx: PropertyEditorBuildContext,
+    ) -> Result<PropertyEditorInstance, InspectorError> {
+        let value = ctx.property_info.cast_value::<ScriptComponent>()?;
+        create_variant_editor(
+            &COMPONENT_VARIANTS,
+            ctx,
+            value.id(),
+            value.get().map(|_| value as &dyn Inspect),
+        )
+    }
 
-            let mut msg = InspectorMessage::context(inspector, MessageDirection::ToWidget, context);
-            msg.flags = MSG_SYNC_FLAG;
-            Ok(Some(msg))
-        } else {
-            let layer_index = ctx.layer_index;
-            let inspector_ctx = ctx
-                .ui
-                .node(instance_ref.inspector)
-                .cast::<Inspector>()
-                .expect("Must be Inspector!")
-                .context()
-                .clone();
-
-            if let Err(e) = inspector_ctx.sync(value, ctx.ui, layer_index + 1) {
-                Err(InspectorError::Group(e))
-            } else {
-                Ok(None)
-            }
-        }
+    fn create_message(
+        &self,
+        ctx: PropertyEditorMessageContext,
+    ) -> Result<Option<UiMessage>, InspectorError> {
+        let value = ctx.property_info.cast_value::<ScriptComponent>()?;
+        sync_variant_editor(&COMPONENT_VARIANTS, ctx, value.id(), value)
     }
 
     fn translate_message(&self, ctx: PropertyEditorTranslationContext) -> Option<PropertyChanged> {
-        if ctx.message.direction() == MessageDirection::FromWidget {
-            if let Some(message) = ctx.message.data::<ScriptPropertyEditorMessage>() {
-                match message {
-                    ScriptPropertyEditorMessage::Value(value) => {
-                        if let Some(env) = get_editor_environment(&ctx.environment) {
-                            let script = value.and_then(|uuid| {
-                                env.serialization_context
-                                    .script_constructors
-                                    .try_create(&uuid)
-                            });
-
-                            return Some(PropertyChanged {
-                                owner_type_id: ctx.owner_type_id,
-                                name: ctx.name.to_string(),
-                                value: FieldKind::object(script),
-                            });
-                        }
-                    }
-                    ScriptPropertyEditorMessage::PropertyChanged(property_changed) => {
-                        return Some(PropertyChanged {
-                            name: ctx.name.to_string(),
-                            owner_type_id: ctx.owner_type_id,
-                            value: FieldKind::Inspectable(Box::new(property_changed.clone())),
-                        })
-                    }
-                }
-            }
-        }
-        None
+        // Switching the type always makes a fresh component with default values.
+        translate_variant_message(ctx, |serialization_context, type_uuid| {
+            type_uuid
+                .and_then(|uuid| {
+                    serialization_context
+                        .component_constructors
+                        .try_create(&uuid)
+                })
+                .unwrap_or_default()
+        })
     }
 }
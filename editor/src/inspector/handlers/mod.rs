@@ -1,3 +1,6 @@
 pub mod effect;
 pub mod node;
+pub mod physics;
+pub mod scene_settings;
 pub mod sound_context;
+pub mod wind;
@@ -28,11 +28,14 @@ pub fn handle_base_property_changed(
                 Base::TAG => SetTagCommand,
                 Base::FRUSTUM_CULLING => SetFrustumCullingCommand,
                 Base::VISIBILITY => SetVisibleCommand,
+                Base::ENABLED => SetEnabledCommand,
                 Base::MOBILITY => SetMobilityCommand,
                 //Base::LIFETIME => SetLifetimeCommand,
                 Base::DEPTH_OFFSET => SetDepthOffsetCommand,
                 Base::LOD_GROUP => SetLodGroupCommand,
                 Base::CAST_SHADOWS => SetCastShadowsCommand,
+                Base::RENDER_MASK => SetRenderMaskCommand,
+                Base::PERSISTENT => SetPersistentCommand,
                 Base::SCRIPT => SetScriptCommand
             )
         }
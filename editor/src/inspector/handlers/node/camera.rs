@@ -103,7 +103,8 @@ pub fn handle_camera_property_changed(
                     Camera::SKY_BOX => SetSkyBoxCommand,
                     Camera::ENVIRONMENT => SetEnvironmentMap,
                     Camera::COLOR_GRADING_LUT => SetColorGradingLutCommand,
-                    Camera::COLOR_GRADING_ENABLED => SetColorGradingEnabledCommand
+                    Camera::COLOR_GRADING_ENABLED => SetColorGradingEnabledCommand,
+                    Camera::CULLING_MASK => SetCameraCullingMaskCommand
                 )
             }
             FieldKind::Inspectable(ref inner) => match args.name.as_ref() {
@@ -0,0 +1,37 @@
+use crate::{
+    handle_properties, inspector::handlers::node::base::handle_base_property_changed,
+    scene::commands::fog_volume::*, SceneCommand,
+};
+use fyrox::{
+    core::pool::Handle,
+    gui::inspector::{FieldKind, PropertyChanged},
+    scene::{fog_volume::FogVolume, node::Node},
+};
+
+pub fn handle_fog_volume_property_changed(
+    args: &PropertyChanged,
+    handle: Handle<Node>,
+    node: &mut Node,
+) -> Option<SceneCommand> {
+    if node.is_fog_volume() {
+        match args.value {
+            FieldKind::Object(ref value) => {
+                handle_properties!(args.name.as_ref(), handle, value,
+                    FogVolume::SHAPE => SetFogVolumeShapeCommand,
+                    FogVolume::EXTENTS => SetFogVolumeExtentsCommand,
+                    FogVolume::RADIUS => SetFogVolumeRadiusCommand,
+                    FogVolume::DENSITY => SetFogVolumeDensityCommand,
+                    FogVolume::COLOR => SetFogVolumeColorCommand,
+                    FogVolume::SCATTER_LIGHTS => SetFogVolumeScatterLightsCommand
+                )
+            }
+            FieldKind::Inspectable(ref inner) => match args.name.as_ref() {
+                FogVolume::BASE => handle_base_property_changed(inner, handle, node),
+                _ => None,
+            },
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
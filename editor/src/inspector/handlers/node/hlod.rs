@@ -0,0 +1,33 @@
+use crate::{
+    handle_properties, inspector::handlers::node::base::handle_base_property_changed,
+    scene::commands::hlod::*, SceneCommand,
+};
+use fyrox::{
+    core::pool::Handle,
+    gui::inspector::{FieldKind, PropertyChanged},
+    scene::{hlod::Hlod, node::Node},
+};
+
+pub fn handle_hlod_property_changed(
+    args: &PropertyChanged,
+    handle: Handle<Node>,
+    node: &mut Node,
+) -> Option<SceneCommand> {
+    if node.is_hlod() {
+        match args.value {
+            FieldKind::Object(ref value) => {
+                handle_properties!(args.name.as_ref(), handle, value,
+                    Hlod::SWITCH_DISTANCE => SetHlodSwitchDistanceCommand,
+                    Hlod::HYSTERESIS => SetHlodHysteresisCommand
+                )
+            }
+            FieldKind::Inspectable(ref inner) => match args.name.as_ref() {
+                Hlod::BASE => handle_base_property_changed(inner, handle, node),
+                _ => None,
+            },
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
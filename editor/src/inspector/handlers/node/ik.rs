@@ -0,0 +1,37 @@
+use crate::{
+    handle_properties, inspector::handlers::node::base::handle_base_property_changed,
+    scene::commands::ik::*, SceneCommand,
+};
+use fyrox::{
+    core::pool::Handle,
+    gui::inspector::{FieldKind, PropertyChanged},
+    scene::{ik::TwoBoneIk, node::Node},
+};
+
+pub fn handle_two_bone_ik_property_changed(
+    args: &PropertyChanged,
+    handle: Handle<Node>,
+    node: &mut Node,
+) -> Option<SceneCommand> {
+    if node.is_two_bone_ik() {
+        match args.value {
+            FieldKind::Object(ref value) => {
+                handle_properties!(args.name.as_ref(), handle, value,
+                    TwoBoneIk::ROOT => SetTwoBoneIkRootCommand,
+                    TwoBoneIk::MIDDLE => SetTwoBoneIkMiddleCommand,
+                    TwoBoneIk::TIP => SetTwoBoneIkTipCommand,
+                    TwoBoneIk::TARGET => SetTwoBoneIkTargetCommand,
+                    TwoBoneIk::POLE => SetTwoBoneIkPoleCommand,
+                    TwoBoneIk::WEIGHT => SetTwoBoneIkWeightCommand
+                )
+            }
+            FieldKind::Inspectable(ref inner) => match args.name.as_ref() {
+                TwoBoneIk::BASE => handle_base_property_changed(inner, handle, node),
+                _ => None,
+            },
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
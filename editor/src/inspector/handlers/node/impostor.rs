@@ -0,0 +1,37 @@
+use crate::{
+    handle_properties, inspector::handlers::node::base::handle_base_property_changed,
+    scene::commands::impostor::*, SceneCommand,
+};
+use fyrox::{
+    core::pool::Handle,
+    gui::inspector::{FieldKind, PropertyChanged},
+    scene::{impostor::Impostor, node::Node},
+};
+
+pub fn handle_impostor_property_changed(
+    args: &PropertyChanged,
+    handle: Handle<Node>,
+    node: &mut Node,
+) -> Option<SceneCommand> {
+    if node.is_impostor() {
+        match args.value {
+            FieldKind::Object(ref value) => {
+                handle_properties!(args.name.as_ref(), handle, value,
+                    Impostor::ATLAS => SetImpostorAtlasCommand,
+                    Impostor::NORMAL_ATLAS => SetImpostorNormalAtlasCommand,
+                    Impostor::FRAME_COUNT => SetImpostorFrameCountCommand,
+                    Impostor::SIZE => SetImpostorSizeCommand,
+                    Impostor::CENTER => SetImpostorCenterCommand,
+                    Impostor::BLEND_FRAMES => SetImpostorBlendFramesCommand
+                )
+            }
+            FieldKind::Inspectable(ref inner) => match args.name.as_ref() {
+                Impostor::BASE => handle_base_property_changed(inner, handle, node),
+                _ => None,
+            },
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
@@ -26,9 +26,14 @@ pub fn handle_base_light_property_changed(
             handle_properties!(args.name.as_ref(), handle, value,
                 BaseLight::COLOR => SetLightColorCommand,
                 BaseLight::CAST_SHADOWS => SetLightCastShadowsCommand,
+                BaseLight::SCATTER_PRESET => SetLightScatterPresetCommand,
                 BaseLight::SCATTER => SetLightScatterCommand,
+                BaseLight::SCATTER_DENSITY => SetLightScatterDensityCommand,
+                BaseLight::SCATTER_ANISOTROPY => SetLightScatterAnisotropyCommand,
                 BaseLight::SCATTER_ENABLED => SetLightScatterEnabledCommand,
-                BaseLight::INTENSITY => SetLightIntensityCommand
+                BaseLight::INTENSITY => SetLightIntensityCommand,
+                BaseLight::SHADOWS_MAX_DISTANCE => SetLightShadowsMaxDistanceCommand,
+                BaseLight::STATIC_SHADOWS => SetLightStaticShadowsCommand
             )
         }
         FieldKind::Inspectable(ref inner) => match args.name.as_ref() {
@@ -52,7 +57,11 @@ pub fn handle_spot_light_property_changed(
                     SpotLight::FALLOFF_ANGLE_DELTA => SetSpotLightFalloffAngleDeltaCommand,
                     SpotLight::SHADOW_BIAS => SetSpotLightShadowBiasCommand,
                     SpotLight::DISTANCE => SetSpotLightDistanceCommand,
-                    SpotLight::COOKIE_TEXTURE => SetSpotLightCookieTextureCommand
+                    SpotLight::COOKIE_TEXTURE => SetSpotLightCookieTextureCommand,
+                    SpotLight::PROJECTION => SetSpotLightProjectionCommand,
+                    SpotLight::ORTHO_WIDTH => SetSpotLightOrthoWidthCommand,
+                    SpotLight::ORTHO_HEIGHT => SetSpotLightOrthoHeightCommand,
+                    SpotLight::EDGE_FALLOFF => SetSpotLightEdgeFalloffCommand
                 )
             }
             FieldKind::Inspectable(ref inner) => match args.name.as_ref() {
@@ -5,7 +5,10 @@ use crate::{
 use fyrox::{
     core::pool::Handle,
     gui::inspector::{CollectionChanged, FieldKind, PropertyChanged},
-    scene::{mesh::Mesh, node::Node},
+    scene::{
+        mesh::{socket::Socket, Mesh},
+        node::Node,
+    },
 };
 
 pub fn handle_mesh_property_changed(
@@ -18,22 +21,47 @@ pub fn handle_mesh_property_changed(
             FieldKind::Object(ref value) => {
                 handle_properties!(args.name.as_ref(), handle, value,
                     Mesh::RENDER_PATH => SetMeshRenderPathCommand,
-                    Mesh::DECAL_LAYER_INDEX => SetMeshDecalLayerIndexCommand
+                    Mesh::DECAL_LAYER_INDEX => SetMeshDecalLayerIndexCommand,
+                    Mesh::BOUNDS_PADDING => SetMeshBoundsPaddingCommand
                 )
             }
-            FieldKind::Collection(ref args) => match **args {
-                CollectionChanged::Add => {
-                    // TODO
-                    None
-                }
-                CollectionChanged::Remove(_) => {
-                    // TODO
-                    None
-                }
-                CollectionChanged::ItemChanged { .. } => {
-                    // TODO
-                    None
-                }
+            FieldKind::Collection(ref collection_changed) => match args.name.as_ref() {
+                Mesh::SOCKETS => match **collection_changed {
+                    CollectionChanged::Add => Some(SceneCommand::new(AddMeshSocketCommand {
+                        handle,
+                        value: Default::default(),
+                    })),
+                    CollectionChanged::Remove(i) => {
+                        Some(SceneCommand::new(RemoveMeshSocketCommand {
+                            handle,
+                            index: i,
+                            value: None,
+                        }))
+                    }
+                    CollectionChanged::ItemChanged {
+                        index,
+                        ref property,
+                    } => {
+                        let mut socket = node.as_mesh().sockets().get(index)?.clone();
+                        if let FieldKind::Object(ref value) = property.value {
+                            match property.name.as_ref() {
+                                Socket::NAME => socket.name = value.cast_clone()?,
+                                Socket::BONE => socket.bone = value.cast_clone()?,
+                                Socket::POSITION => socket.position = value.cast_clone()?,
+                                Socket::ROTATION => socket.rotation = value.cast_clone()?,
+                                _ => return None,
+                            }
+                            Some(SceneCommand::new(SetMeshSocketCommand {
+                                handle,
+                                index,
+                                socket,
+                            }))
+                        } else {
+                            None
+                        }
+                    }
+                },
+                _ => None,
             },
             FieldKind::Inspectable(ref inner) => match args.name.as_ref() {
                 Mesh::BASE => handle_base_property_changed(inner, handle, node),
@@ -5,13 +5,18 @@ use crate::{
     inspector::handlers::node::{
         base::handle_base_property_changed, camera::handle_camera_property_changed,
         collider::handle_collider_property_changed, collider2d::handle_collider2d_property_changed,
-        decal::handle_decal_property_changed, joint::handle_joint_property_changed,
-        joint2d::handle_joint2d_property_changed, light::*,
+        decal::handle_decal_property_changed, fog_volume::handle_fog_volume_property_changed,
+        hlod::handle_hlod_property_changed, ik::handle_two_bone_ik_property_changed,
+        impostor::handle_impostor_property_changed,
+        joint::handle_joint_property_changed, joint2d::handle_joint2d_property_changed, light::*,
         listener::handle_listener_property_changed, mesh::handle_mesh_property_changed,
+        navmesh_obstacle::handle_navmesh_obstacle_property_changed,
         particle_system::ParticleSystemHandler, rectangle::handle_rectangle_property_changed,
+        reflection_probe::handle_reflection_probe_property_changed,
         rigid_body::handle_rigid_body_property_changed,
         rigid_body2d::handle_rigid_body2d_property_changed, sound::handle_sound_property_changed,
         sprite::handle_sprite_property_changed, terrain::handle_terrain_property_changed,
+        trigger_volume::handle_trigger_volume_property_changed,
     },
     SceneCommand,
 };
@@ -25,16 +30,23 @@ use fyrox::{
         collider::Collider,
         decal::Decal,
         dim2,
+        fog_volume::FogVolume,
+        hlod::Hlod,
+        ik::TwoBoneIk,
+        impostor::Impostor,
         joint::Joint,
         light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
         mesh::Mesh,
+        navmesh_obstacle::NavmeshObstacle,
         node::Node,
         particle_system::ParticleSystem,
+        reflection_probe::ReflectionProbe,
         rigidbody::RigidBody,
         sound::listener::Listener,
         sound::Sound,
         sprite::Sprite,
         terrain::Terrain,
+        trigger_volume::TriggerVolume,
     },
 };
 
@@ -43,20 +55,27 @@ pub mod camera;
 pub mod collider;
 pub mod collider2d;
 pub mod decal;
+pub mod fog_volume;
+pub mod hlod;
+pub mod ik;
+pub mod impostor;
 pub mod joint;
 pub mod joint2d;
 pub mod light;
 pub mod listener;
 pub mod mesh;
+pub mod navmesh_obstacle;
 pub mod particle_system;
 pub mod pivot;
 pub mod rectangle;
+pub mod reflection_probe;
 pub mod rigid_body;
 pub mod rigid_body2d;
 pub mod sound;
 pub mod sprite;
 pub mod terrain;
 pub mod transform;
+pub mod trigger_volume;
 
 pub struct SceneNodePropertyChangedHandler {
     pub particle_system_handler: ParticleSystemHandler,
@@ -88,6 +107,20 @@ impl SceneNodePropertyChangedHandler {
             self.particle_system_handler.handle(args, handle, node, ui)
         } else if args.owner_type_id == TypeId::of::<Decal>() {
             handle_decal_property_changed(args, handle, node)
+        } else if args.owner_type_id == TypeId::of::<ReflectionProbe>() {
+            handle_reflection_probe_property_changed(args, handle, node)
+        } else if args.owner_type_id == TypeId::of::<FogVolume>() {
+            handle_fog_volume_property_changed(args, handle, node)
+        } else if args.owner_type_id == TypeId::of::<TriggerVolume>() {
+            handle_trigger_volume_property_changed(args, handle, node)
+        } else if args.owner_type_id == TypeId::of::<NavmeshObstacle>() {
+            handle_navmesh_obstacle_property_changed(args, handle, node)
+        } else if args.owner_type_id == TypeId::of::<Hlod>() {
+            handle_hlod_property_changed(args, handle, node)
+        } else if args.owner_type_id == TypeId::of::<Impostor>() {
+            handle_impostor_property_changed(args, handle, node)
+        } else if args.owner_type_id == TypeId::of::<TwoBoneIk>() {
+            handle_two_bone_ik_property_changed(args, handle, node)
         } else if args.owner_type_id == TypeId::of::<Terrain>() {
             handle_terrain_property_changed(args, handle, node)
         } else if args.owner_type_id == TypeId::of::<Mesh>() {
@@ -107,7 +140,7 @@ impl SceneNodePropertyChangedHandler {
         } else if args.owner_type_id == TypeId::of::<dim2::rectangle::Rectangle>() {
             handle_rectangle_property_changed(args, handle, node)
         } else if args.owner_type_id == TypeId::of::<Sound>() {
-            handle_sound_property_changed(args, handle)
+            handle_sound_property_changed(args, handle, node.as_sound_mut())
         } else if args.owner_type_id == TypeId::of::<Listener>() {
             handle_listener_property_changed(args, handle, node)
         } else {
@@ -0,0 +1,35 @@
+use crate::{
+    handle_properties, inspector::handlers::node::base::handle_base_property_changed,
+    scene::commands::navmesh_obstacle::*, SceneCommand,
+};
+use fyrox::{
+    core::pool::Handle,
+    gui::inspector::{FieldKind, PropertyChanged},
+    scene::{navmesh_obstacle::NavmeshObstacle, node::Node},
+};
+
+pub fn handle_navmesh_obstacle_property_changed(
+    args: &PropertyChanged,
+    handle: Handle<Node>,
+    node: &mut Node,
+) -> Option<SceneCommand> {
+    if node.is_navmesh_obstacle() {
+        match args.value {
+            FieldKind::Object(ref value) => {
+                handle_properties!(args.name.as_ref(), handle, value,
+                    NavmeshObstacle::SHAPE => SetNavmeshObstacleShapeCommand,
+                    NavmeshObstacle::EXTENTS => SetNavmeshObstacleExtentsCommand,
+                    NavmeshObstacle::RADIUS => SetNavmeshObstacleRadiusCommand,
+                    NavmeshObstacle::HEIGHT => SetNavmeshObstacleHeightCommand
+                )
+            }
+            FieldKind::Inspectable(ref inner) => match args.name.as_ref() {
+                NavmeshObstacle::BASE => handle_base_property_changed(inner, handle, node),
+                _ => None,
+            },
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
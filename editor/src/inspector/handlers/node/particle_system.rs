@@ -133,7 +133,9 @@ impl ParticleSystemHandler {
                         ParticleSystem::TEXTURE => SetParticleSystemTextureCommand,
                         ParticleSystem::ACCELERATION => SetAccelerationCommand,
                         ParticleSystem::ENABLED => SetParticleSystemEnabledCommand,
-                        ParticleSystem::SOFT_BOUNDARY_SHARPNESS_FACTOR => SetSoftBoundarySharpnessFactorCommand
+                        ParticleSystem::SOFT_BOUNDARY_SHARPNESS_FACTOR => SetSoftBoundarySharpnessFactorCommand,
+                        ParticleSystem::SIMULATION_SPACE => SetParticleSystemSimulationSpaceCommand,
+                        ParticleSystem::WIND_INFLUENCE => SetParticleSystemWindInfluenceCommand
                     )
                 }
                 FieldKind::Collection(ref collection_changed) => match args.name.as_ref() {
@@ -5,7 +5,10 @@ use crate::{
 use fyrox::{
     core::pool::Handle,
     gui::inspector::{FieldKind, PropertyChanged},
-    scene::{dim2::rectangle::Rectangle, node::Node},
+    scene::{
+        dim2::rectangle::{NineSlice, Rectangle},
+        node::Node,
+    },
 };
 
 pub fn handle_rectangle_property_changed(
@@ -22,10 +25,35 @@ pub fn handle_rectangle_property_changed(
                 Rectangle::COLOR => {
                     make_command!(SetRectangleColorCommand, handle, value)
                 }
+                Rectangle::UV_RECT => {
+                    make_command!(SetRectangleUvRectCommand, handle, value)
+                }
+                Rectangle::NINE_SLICE => {
+                    make_command!(SetRectangleNineSliceCommand, handle, value)
+                }
                 _ => None,
             },
             FieldKind::Inspectable(ref inner) => match args.name.as_ref() {
                 Rectangle::BASE => handle_base_property_changed(inner, handle, node),
+                Rectangle::NINE_SLICE => {
+                    if let FieldKind::Object(ref value) = inner.value {
+                        let mut nine_slice = node.as_rectangle().nine_slice()?;
+                        let margin = *value.cast_value::<f32>()?;
+                        match inner.name.as_ref() {
+                            NineSlice::LEFT => nine_slice.left = margin,
+                            NineSlice::TOP => nine_slice.top = margin,
+                            NineSlice::RIGHT => nine_slice.right = margin,
+                            NineSlice::BOTTOM => nine_slice.bottom = margin,
+                            _ => return None,
+                        }
+                        Some(SceneCommand::new(SetRectangleNineSliceCommand::new(
+                            handle,
+                            Some(nine_slice),
+                        )))
+                    } else {
+                        None
+                    }
+                }
                 _ => None,
             },
             _ => None,
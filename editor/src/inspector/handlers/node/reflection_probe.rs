@@ -0,0 +1,35 @@
+use crate::{
+    handle_properties, inspector::handlers::node::base::handle_base_property_changed,
+    scene::commands::reflection_probe::*, SceneCommand,
+};
+use fyrox::{
+    core::pool::Handle,
+    gui::inspector::{FieldKind, PropertyChanged},
+    scene::{node::Node, reflection_probe::ReflectionProbe},
+};
+
+pub fn handle_reflection_probe_property_changed(
+    args: &PropertyChanged,
+    handle: Handle<Node>,
+    node: &mut Node,
+) -> Option<SceneCommand> {
+    if node.is_reflection_probe() {
+        match args.value {
+            FieldKind::Object(ref value) => {
+                handle_properties!(args.name.as_ref(), handle, value,
+                    ReflectionProbe::EXTENTS => SetReflectionProbeExtentsCommand,
+                    ReflectionProbe::BLEND_DISTANCE => SetReflectionProbeBlendDistanceCommand,
+                    ReflectionProbe::RESOLUTION => SetReflectionProbeResolutionCommand,
+                    ReflectionProbe::ENVIRONMENT => SetReflectionProbeEnvironmentCommand
+                )
+            }
+            FieldKind::Inspectable(ref inner) => match args.name.as_ref() {
+                ReflectionProbe::BASE => handle_base_property_changed(inner, handle, node),
+                _ => None,
+            },
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
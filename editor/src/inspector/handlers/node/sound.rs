@@ -2,12 +2,13 @@ use crate::{make_command, scene::commands::sound::*, SceneCommand};
 use fyrox::{
     core::pool::Handle,
     gui::inspector::{FieldKind, PropertyChanged},
-    scene::{node::Node, sound::Sound},
+    scene::{collider::InteractionGroups, node::Node, sound::Sound},
 };
 
 pub fn handle_sound_property_changed(
     args: &PropertyChanged,
     handle: Handle<Node>,
+    sound: &Sound,
 ) -> Option<SceneCommand> {
     match args.value {
         FieldKind::Object(ref value) => match args.name.as_ref() {
@@ -44,6 +45,44 @@ pub fn handle_sound_property_changed(
             Sound::SPATIAL_BLEND => {
                 make_command!(SetSpatialBlendCommand, handle, value)
             }
+            Sound::SPREAD => {
+                make_command!(SetSoundSpreadCommand, handle, value)
+            }
+            Sound::MIN_DISTANCE => {
+                make_command!(SetSoundMinDistanceCommand, handle, value)
+            }
+            Sound::AIR_ABSORPTION => {
+                make_command!(SetSoundAirAbsorptionCommand, handle, value)
+            }
+            Sound::OCCLUSION_ENABLED => {
+                make_command!(SetSoundOcclusionEnabledCommand, handle, value)
+            }
+            Sound::TIME_SCALE_AFFECTS_PITCH => {
+                make_command!(SetSoundTimeScaleAffectsPitchCommand, handle, value)
+            }
+            _ => None,
+        },
+        FieldKind::Inspectable(ref inner_property) => match args.name.as_ref() {
+            Sound::OCCLUDER_GROUPS => match inner_property.value {
+                FieldKind::Object(ref value) => match inner_property.name.as_ref() {
+                    InteractionGroups::MEMBERSHIPS => {
+                        let mut new_value = sound.occluder_groups();
+                        new_value.memberships = value.cast_clone()?;
+                        Some(SceneCommand::new(SetSoundOccluderGroupsCommand::new(
+                            handle, new_value,
+                        )))
+                    }
+                    InteractionGroups::FILTER => {
+                        let mut new_value = sound.occluder_groups();
+                        new_value.filter = value.cast_clone()?;
+                        Some(SceneCommand::new(SetSoundOccluderGroupsCommand::new(
+                            handle, new_value,
+                        )))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            },
             _ => None,
         },
         _ => None,
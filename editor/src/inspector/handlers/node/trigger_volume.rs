@@ -0,0 +1,64 @@
+use crate::{
+    handle_properties, inspector::handlers::node::base::handle_base_property_changed,
+    scene::commands::trigger_volume::*, SceneCommand,
+};
+use fyrox::{
+    core::pool::Handle,
+    gui::inspector::{FieldKind, PropertyChanged},
+    scene::{
+        collider::{GeometrySource, InteractionGroups},
+        node::Node,
+        trigger_volume::TriggerVolume,
+    },
+};
+
+pub fn handle_trigger_volume_property_changed(
+    args: &PropertyChanged,
+    handle: Handle<Node>,
+    node: &mut Node,
+) -> Option<SceneCommand> {
+    if node.is_trigger_volume() {
+        match args.value {
+            FieldKind::Object(ref value) => {
+                handle_properties!(args.name.as_ref(), handle, value,
+                    TriggerVolume::SHAPE => SetTriggerVolumeShapeCommand,
+                    TriggerVolume::EXTENTS => SetTriggerVolumeExtentsCommand,
+                    TriggerVolume::RADIUS => SetTriggerVolumeRadiusCommand,
+                    TriggerVolume::NOTIFY_ENTERING => SetTriggerVolumeNotifyEnteringCommand
+                )
+            }
+            FieldKind::Inspectable(ref inner) => match args.name.as_ref() {
+                TriggerVolume::GEOMETRY_SOURCE => match inner.value {
+                    FieldKind::Object(ref value) if inner.name == GeometrySource::F_0 => Some(
+                        SceneCommand::new(SetTriggerVolumeGeometrySourceCommand::new(
+                            handle,
+                            GeometrySource(value.cast_clone()?),
+                        )),
+                    ),
+                    _ => None,
+                },
+                TriggerVolume::COLLISION_GROUPS => match inner.value {
+                    FieldKind::Object(ref value) => {
+                        let mut groups = node.as_trigger_volume().collision_groups();
+                        match inner.name.as_ref() {
+                            InteractionGroups::MEMBERSHIPS => {
+                                groups.memberships = value.cast_clone()?
+                            }
+                            InteractionGroups::FILTER => groups.filter = value.cast_clone()?,
+                            _ => return None,
+                        }
+                        Some(SceneCommand::new(
+                            SetTriggerVolumeCollisionGroupsCommand::new(handle, groups),
+                        ))
+                    }
+                    _ => None,
+                },
+                TriggerVolume::BASE => handle_base_property_changed(inner, handle, node),
+                _ => None,
+            },
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{
+    scene::commands::physics::{SetPhysics2DSettingsCommand, SetPhysicsSettingsCommand},
+    SceneCommand,
+};
+use fyrox::{
+    gui::inspector::{FieldKind, PropertyChanged},
+    scene::{
+        dim2,
+        graph::physics::{IntegrationParameters, PhysicsSettings},
+    },
+};
+
+fn set_integration_parameter(
+    args: &PropertyChanged,
+    parameters: &mut IntegrationParameters,
+) -> Option<()> {
+    match args.value {
+        FieldKind::Object(ref value) => {
+            match args.name.as_ref() {
+                IntegrationParameters::DT => parameters.dt = value.cast_clone()?,
+                IntegrationParameters::MIN_CCD_DT => parameters.min_ccd_dt = value.cast_clone()?,
+                IntegrationParameters::ERP => parameters.erp = value.cast_clone()?,
+                IntegrationParameters::DAMPING_RATIO => {
+                    parameters.damping_ratio = value.cast_clone()?
+                }
+                IntegrationParameters::JOINT_ERP => parameters.joint_erp = value.cast_clone()?,
+                IntegrationParameters::JOINT_DAMPING_RATIO => {
+                    parameters.joint_damping_ratio = value.cast_clone()?
+                }
+                IntegrationParameters::ALLOWED_LINEAR_ERROR => {
+                    parameters.allowed_linear_error = value.cast_clone()?
+                }
+                IntegrationParameters::MAX_PENETRATION_CORRECTION => {
+                    parameters.max_penetration_correction = value.cast_clone()?
+                }
+                IntegrationParameters::PREDICTION_DISTANCE => {
+                    parameters.prediction_distance = value.cast_clone()?
+                }
+                IntegrationParameters::MAX_VELOCITY_ITERATIONS => {
+                    parameters.max_velocity_iterations = value.cast_clone()?
+                }
+                IntegrationParameters::MAX_VELOCITY_FRICTION_ITERATIONS => {
+                    parameters.max_velocity_friction_iterations = value.cast_clone()?
+                }
+                IntegrationParameters::MAX_STABILIZATION_ITERATIONS => {
+                    parameters.max_stabilization_iterations = value.cast_clone()?
+                }
+                IntegrationParameters::INTERLEAVE_RESTITUTION_AND_FRICTION_RESOLUTION => {
+                    parameters.interleave_restitution_and_friction_resolution =
+                        value.cast_clone()?
+                }
+                IntegrationParameters::MIN_ISLAND_SIZE => {
+                    parameters.min_island_size = value.cast_clone()?
+                }
+                IntegrationParameters::MAX_CCD_SUBSTEPS => {
+                    parameters.max_ccd_substeps = value.cast_clone()?
+                }
+                _ => return None,
+            }
+            Some(())
+        }
+        _ => None,
+    }
+}
+
+pub fn handle_physics_settings_property_changed(
+    args: &PropertyChanged,
+    settings: &PhysicsSettings,
+) -> Option<SceneCommand> {
+    let mut settings = *settings;
+    match args.value {
+        FieldKind::Object(ref value) => match args.name.as_ref() {
+            PhysicsSettings::GRAVITY => settings.gravity = value.cast_clone()?,
+            PhysicsSettings::STEP_MODE => settings.step_mode = value.cast_clone()?,
+            PhysicsSettings::CCD_ENABLED_BY_DEFAULT => {
+                settings.ccd_enabled_by_default = value.cast_clone()?
+            }
+            PhysicsSettings::LINEAR_SLEEP_THRESHOLD => {
+                settings.linear_sleep_threshold = value.cast_clone()?
+            }
+            PhysicsSettings::ANGULAR_SLEEP_THRESHOLD => {
+                settings.angular_sleep_threshold = value.cast_clone()?
+            }
+            PhysicsSettings::INTERPOLATION => settings.interpolation = value.cast_clone()?,
+            _ => return None,
+        },
+        FieldKind::Inspectable(ref inner_property) => match args.name.as_ref() {
+            PhysicsSettings::INTEGRATION_PARAMETERS => {
+                set_integration_parameter(inner_property, &mut settings.integration_parameters)?
+            }
+            _ => return None,
+        },
+        _ => return None,
+    }
+    Some(SceneCommand::new(SetPhysicsSettingsCommand::new(settings)))
+}
+
+pub fn handle_physics2d_settings_property_changed(
+    args: &PropertyChanged,
+    settings: &dim2::physics::PhysicsSettings,
+) -> Option<SceneCommand> {
+    let mut settings = *settings;
+    match args.value {
+        FieldKind::Object(ref value) => match args.name.as_ref() {
+            dim2::physics::PhysicsSettings::GRAVITY => settings.gravity = value.cast_clone()?,
+            dim2::physics::PhysicsSettings::STEP_MODE => settings.step_mode = value.cast_clone()?,
+            dim2::physics::PhysicsSettings::CCD_ENABLED_BY_DEFAULT => {
+                settings.ccd_enabled_by_default = value.cast_clone()?
+            }
+            dim2::physics::PhysicsSettings::LINEAR_SLEEP_THRESHOLD => {
+                settings.linear_sleep_threshold = value.cast_clone()?
+            }
+            dim2::physics::PhysicsSettings::ANGULAR_SLEEP_THRESHOLD => {
+                settings.angular_sleep_threshold = value.cast_clone()?
+            }
+            _ => return None,
+        },
+        FieldKind::Inspectable(ref inner_property) => match args.name.as_ref() {
+            dim2::physics::PhysicsSettings::INTEGRATION_PARAMETERS => {
+                set_integration_parameter(inner_property, &mut settings.integration_parameters)?
+            }
+            _ => return None,
+        },
+        _ => return None,
+    }
+    Some(SceneCommand::new(SetPhysics2DSettingsCommand::new(
+        settings,
+    )))
+}
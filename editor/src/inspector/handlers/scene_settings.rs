@@ -0,0 +1,87 @@
+use crate::{
+    inspector::handlers::{
+        physics::{
+            handle_physics2d_settings_property_changed, handle_physics_settings_property_changed,
+        },
+        wind::handle_wind_property_changed,
+    },
+    SceneCommand,
+};
+use fyrox::{
+    core::inspect::{Inspect, PropertyInfo},
+    gui::inspector::{FieldKind, PropertyChanged},
+    scene::{
+        dim2,
+        graph::{physics::PhysicsSettings, Graph},
+        wind::Wind,
+    },
+};
+
+/// Scene-wide settings, that are shown in the inspector when scene settings are selected. It is
+/// a copy of the settings of a graph, changes are applied by commands.
+#[derive(Inspect)]
+pub struct SceneSettings {
+    pub wind: Wind,
+    pub physics: PhysicsSettings,
+    #[inspect(display_name = "Physics 2D")]
+    pub physics2d: dim2::physics::PhysicsSettings,
+}
+
+impl SceneSettings {
+    pub fn from_graph(graph: &Graph) -> Self {
+        Self {
+            wind: graph.wind.clone(),
+            physics: graph.physics.settings,
+            physics2d: graph.physics2d.settings,
+        }
+    }
+}
+
+pub fn handle_scene_settings_property_changed(
+    args: &PropertyChanged,
+    graph: &Graph,
+) -> Option<SceneCommand> {
+    match args.value {
+        FieldKind::Inspectable(ref inner_property) => match args.name.as_ref() {
+            SceneSettings::WIND => handle_wind_property_changed(inner_property, &graph.wind),
+            SceneSettings::PHYSICS => {
+                handle_physics_settings_property_changed(inner_property, &graph.physics.settings)
+            }
+            SceneSettings::PHYSICS_2_D => handle_physics2d_settings_property_changed(
+                inner_property,
+                &graph.physics2d.settings,
+            ),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fyrox::{core::algebra::Vector2, gui::inspector::PropertyChanged};
+    use std::any::TypeId;
+
+    #[test]
+    fn test_physics2d_settings_are_editable() {
+        let graph = Graph::new();
+        let gravity = Vector2::<f32>::new(0.0, -20.0);
+
+        let args = PropertyChanged {
+            name: SceneSettings::PHYSICS_2_D.to_owned(),
+            owner_type_id: TypeId::of::<SceneSettings>(),
+            value: FieldKind::Inspectable(Box::new(PropertyChanged {
+                name: dim2::physics::PhysicsSettings::GRAVITY.to_owned(),
+                owner_type_id: TypeId::of::<dim2::physics::PhysicsSettings>(),
+                value: FieldKind::object(gravity),
+            })),
+        };
+
+        let command = handle_scene_settings_property_changed(&args, &graph)
+            .expect("Physics 2D settings must be editable!");
+        let description = format!("{:?}", command);
+        assert!(description.contains("SetPhysics2DSettingsCommand"));
+        assert!(description.contains("-20.0"));
+    }
+}
@@ -1,10 +1,16 @@
 use crate::{scene::commands::sound_context::*, SceneCommand};
 use fyrox::{
     gui::inspector::{FieldKind, PropertyChanged},
-    scene::sound::context::SoundContext,
+    scene::sound::{
+        context::{OcclusionSettings, SoundContext},
+        AirAbsorption,
+    },
 };
 
-pub fn handle_sound_context_property_changed(args: &PropertyChanged) -> Option<SceneCommand> {
+pub fn handle_sound_context_property_changed(
+    args: &PropertyChanged,
+    context: &SoundContext,
+) -> Option<SceneCommand> {
     match args.value {
         FieldKind::Object(ref value) => match args.name.as_ref() {
             SoundContext::PAUSED => Some(SceneCommand::new(SetPausedCommand::new(
@@ -21,6 +27,47 @@ pub fn handle_sound_context_property_changed(args: &PropertyChanged) -> Option<S
             ))),
             _ => None,
         },
+        FieldKind::Inspectable(ref inner_property) => match args.name.as_ref() {
+            SoundContext::OCCLUSION => match inner_property.value {
+                FieldKind::Object(ref value) => {
+                    let mut settings = context.occlusion_settings();
+                    match inner_property.name.as_ref() {
+                        OcclusionSettings::RAY_COUNT => settings.ray_count = value.cast_clone()?,
+                        OcclusionSettings::SPREAD => settings.spread = value.cast_clone()?,
+                        OcclusionSettings::LOW_PASS => settings.low_pass = value.cast_clone()?,
+                        OcclusionSettings::ATTENUATION => {
+                            settings.attenuation = value.cast_clone()?
+                        }
+                        OcclusionSettings::SPEED => settings.speed = value.cast_clone()?,
+                        OcclusionSettings::SOURCES_PER_FRAME => {
+                            settings.sources_per_frame = value.cast_clone()?
+                        }
+                        _ => return None,
+                    }
+                    Some(SceneCommand::new(SetOcclusionSettingsCommand::new(
+                        settings,
+                    )))
+                }
+                _ => None,
+            },
+            SoundContext::AIR_ABSORPTION => match inner_property.value {
+                FieldKind::Object(ref value) => {
+                    let mut settings = context.air_absorption();
+                    match inner_property.name.as_ref() {
+                        AirAbsorption::ENABLED => settings.enabled = value.cast_clone()?,
+                        AirAbsorption::MAX_CUTOFF => settings.max_cutoff = value.cast_clone()?,
+                        AirAbsorption::MIN_CUTOFF => settings.min_cutoff = value.cast_clone()?,
+                        AirAbsorption::HALF_DISTANCE => {
+                            settings.half_distance = value.cast_clone()?
+                        }
+                        _ => return None,
+                    }
+                    Some(SceneCommand::new(SetAirAbsorptionCommand::new(settings)))
+                }
+                _ => None,
+            },
+            _ => None,
+        },
         _ => None,
     }
 }
@@ -0,0 +1,24 @@
+use crate::{scene::commands::wind::SetWindCommand, SceneCommand};
+use fyrox::{
+    gui::inspector::{FieldKind, PropertyChanged},
+    scene::wind::Wind,
+};
+
+pub fn handle_wind_property_changed(args: &PropertyChanged, wind: &Wind) -> Option<SceneCommand> {
+    match args.value {
+        FieldKind::Object(ref value) => {
+            let mut wind = wind.clone();
+            match args.name.as_ref() {
+                Wind::DIRECTION => wind.set_direction(value.cast_clone()?),
+                Wind::STRENGTH => wind.set_strength(value.cast_clone()?),
+                Wind::GUSTINESS => wind.set_gustiness(value.cast_clone()?),
+                Wind::GUST_FREQUENCY => wind.set_gust_frequency(value.cast_clone()?),
+                Wind::GUST_SIZE => wind.set_gust_size(value.cast_clone()?),
+                Wind::TURBULENCE_FREQUENCY => wind.set_turbulence_frequency(value.cast_clone()?),
+                _ => return None,
+            }
+            Some(SceneCommand::new(SetWindCommand::new(wind)))
+        }
+        _ => None,
+    }
+}
@@ -5,10 +5,12 @@ use crate::{
         handlers::{
             effect::handle_reverb_effect_property_changed,
             node::{particle_system::ParticleSystemHandler, SceneNodePropertyChangedHandler},
+            scene_settings::{handle_scene_settings_property_changed, SceneSettings},
             sound_context::handle_sound_context_property_changed,
         },
     },
     scene::{EditorScene, Selection},
+    settings::render_layers::RenderLayersSettings,
     Brush, CommandGroup, GameEngine, Message, Mode, WidgetMessage, WrapMode, MSG_SYNC_FLAG,
 };
 use fyrox::engine::SerializationContext;
@@ -28,10 +30,12 @@ use fyrox::{
         window::{WindowBuilder, WindowTitle},
         BuildContext, Thickness, UiNode, UserInterface,
     },
+    scene::{mesh::Mesh, node::Node},
     utils::log::{Log, MessageKind},
 };
 use std::{
     any::Any,
+    collections::HashMap,
     rc::Rc,
     sync::{mpsc::Sender, Arc},
 };
@@ -42,6 +46,18 @@ pub mod handlers;
 pub struct EditorEnvironment {
     pub resource_manager: ResourceManager,
     pub serialization_context: Arc<SerializationContext>,
+    /// Display names of render layers, see [`crate::settings::render_layers::RenderLayersSettings`].
+    pub layer_names: Vec<String>,
+    /// Paths of every node of the scene, they are used to assign node paths by dragging nodes
+    /// from the world viewer.
+    pub node_paths: Rc<HashMap<Handle<Node>, String>>,
+    /// Nodes that are offered in drop-down lists of node handle editors - descendants of the
+    /// parent of the selected node. It allows you to pick bones of a character without dragging
+    /// them from the world viewer.
+    pub pickable_nodes: Rc<Vec<Handle<Node>>>,
+    /// Bones of the selected mesh with their names, they are offered in drop-down lists of socket
+    /// bone editors.
+    pub mesh_bones: Rc<Vec<(Handle<Node>, String)>>,
 }
 
 impl InspectorEnvironment for EditorEnvironment {
@@ -183,12 +199,17 @@ impl Inspector {
 
         if self.needs_sync {
             if editor_scene.selection.is_single_selection() {
+                let scene_settings;
                 let obj: Option<&dyn Inspect> = match &editor_scene.selection {
                     Selection::Graph(selection) => scene
                         .graph
                         .try_get(selection.nodes()[0])
                         .map(|n| n as &dyn Inspect),
                     Selection::SoundContext => Some(&scene.graph.sound_context as &dyn Inspect),
+                    Selection::SceneSettings => {
+                        scene_settings = SceneSettings::from_graph(&scene.graph);
+                        Some(&scene_settings as &dyn Inspect)
+                    }
                     Selection::Effect(selection) => scene
                         .graph
                         .sound_context
@@ -210,19 +231,13 @@ impl Inspector {
         &mut self,
         obj: &dyn Inspect,
         ui: &mut UserInterface,
-        resource_manager: ResourceManager,
-        serialization_context: Arc<SerializationContext>,
+        environment: EditorEnvironment,
     ) {
-        let environment = Rc::new(EditorEnvironment {
-            resource_manager,
-            serialization_context,
-        });
-
         let context = InspectorContext::from_object(
             obj,
             &mut ui.build_ctx(),
             self.property_editors.clone(),
-            Some(environment),
+            Some(Rc::new(environment)),
             MSG_SYNC_FLAG,
             0,
         );
@@ -241,6 +256,7 @@ impl Inspector {
         message: &Message,
         editor_scene: &EditorScene,
         engine: &mut GameEngine,
+        render_layers: &RenderLayersSettings,
     ) {
         if let Message::SelectionChanged = message {
             let scene = &engine.scenes[editor_scene.scene];
@@ -254,12 +270,17 @@ impl Inspector {
                 ));
 
             if !editor_scene.selection.is_empty() {
+                let scene_settings;
                 let obj: Option<&dyn Inspect> = match &editor_scene.selection {
                     Selection::Graph(selection) => scene
                         .graph
                         .try_get(selection.nodes()[0])
                         .map(|n| n as &dyn Inspect),
                     Selection::SoundContext => Some(&scene.graph.sound_context as &dyn Inspect),
+                    Selection::SceneSettings => {
+                        scene_settings = SceneSettings::from_graph(&scene.graph);
+                        Some(&scene_settings as &dyn Inspect)
+                    }
                     Selection::Effect(selection) => scene
                         .graph
                         .sound_context
@@ -268,13 +289,58 @@ impl Inspector {
                     _ => None,
                 };
 
+                let pickable_nodes = match &editor_scene.selection {
+                    Selection::Graph(selection) => {
+                        let selected = selection.nodes()[0];
+                        scene
+                            .graph
+                            .try_get(selected)
+                            .filter(|node| node.parent().is_some())
+                            .map(|node| {
+                                scene
+                                    .graph
+                                    .traverse_handle_iter(node.parent())
+                                    .filter(|handle| *handle != selected)
+                                    .collect()
+                            })
+                            .unwrap_or_default()
+                    }
+                    _ => Default::default(),
+                };
+
+                let mut mesh_bones = Vec::new();
+                if let Selection::Graph(selection) = &editor_scene.selection {
+                    if let Some(mesh) = scene
+                        .graph
+                        .try_get(selection.nodes()[0])
+                        .and_then(|n| n.cast::<Mesh>())
+                    {
+                        for &bone in mesh.surfaces().iter().flat_map(|s| s.bones()) {
+                            if !mesh_bones.iter().any(|(b, _)| *b == bone) {
+                                if let Some(bone_ref) = scene.graph.try_get(bone) {
+                                    mesh_bones.push((bone, bone_ref.name_owned()));
+                                }
+                            }
+                        }
+                    }
+                }
+
                 if let Some(obj) = obj {
-                    self.change_context(
-                        obj,
-                        &mut engine.user_interface,
-                        engine.resource_manager.clone(),
-                        engine.serialization_context.clone(),
-                    )
+                    let environment = EditorEnvironment {
+                        resource_manager: engine.resource_manager.clone(),
+                        serialization_context: engine.serialization_context.clone(),
+                        layer_names: render_layers.layer_names(),
+                        node_paths: Rc::new(
+                            scene
+                                .graph
+                                .pair_iter()
+                                .map(|(handle, _)| (handle, scene.graph.node_path(handle)))
+                                .collect(),
+                        ),
+                        pickable_nodes: Rc::new(pickable_nodes),
+                        mesh_bones: Rc::new(mesh_bones),
+                    };
+                    self.change_context(obj, &mut engine.user_interface, environment)
                 }
             } else {
                 self.clear(&engine.user_interface);
@@ -343,9 +409,16 @@ impl Inspector {
                             }
                         })
                         .collect::<Vec<_>>(),
-                    Selection::SoundContext => handle_sound_context_property_changed(args)
-                        .map(|c| vec![c])
-                        .unwrap_or_default(),
+                    Selection::SoundContext => {
+                        handle_sound_context_property_changed(args, &scene.graph.sound_context)
+                            .map(|c| vec![c])
+                            .unwrap_or_default()
+                    }
+                    Selection::SceneSettings => {
+                        handle_scene_settings_property_changed(args, &scene.graph)
+                            .map(|c| vec![c])
+                            .unwrap_or_default()
+                    }
                     Selection::Effect(selection) => selection
                         .effects
                         .iter()
@@ -0,0 +1,823 @@
+//! Interaction mode that allows to edit shapes of colliders directly in the scene viewer.
+//!
+//! Selected collider shape is drawn with a set of handles, each handle can be dragged along
+//! a single axis and changes exactly one parameter of the shape. Cubic handles change extents
+//! (half extents of a box, half height of a cylinder or a cone, end points of a capsule),
+//! spherical handles change radii. Every drag produces exactly one undoable command.
+//!
+//! Box and sphere trigger volumes are edited the same way, their shapes are treated as cuboid
+//! and ball collider shapes respectively. Box and cylinder navmesh obstacles are treated as cuboid
+//! and cylinder collider shapes.
+
+use crate::{
+    camera::PickingOptions,
+    interaction::{calculate_gizmo_distance_scaling, InteractionMode},
+    make_color_material,
+    scene::{
+        commands::{
+            collider::{
+                SetBallRadiusCommand, SetCapsuleBeginCommand, SetCapsuleEndCommand,
+                SetCapsuleRadiusCommand, SetConeHalfHeightCommand, SetConeRadiusCommand,
+                SetCuboidHalfExtentsCommand, SetCylinderHalfHeightCommand,
+                SetCylinderRadiusCommand,
+            },
+            navmesh_obstacle::{
+                SetNavmeshObstacleExtentsCommand, SetNavmeshObstacleHeightCommand,
+                SetNavmeshObstacleRadiusCommand,
+            },
+            trigger_volume::{SetTriggerVolumeExtentsCommand, SetTriggerVolumeRadiusCommand},
+            ChangeSelectionCommand, SceneCommand,
+        },
+        EditorScene, Selection,
+    },
+    set_mesh_diffuse_color,
+    settings::Settings,
+    world::graph::selection::GraphSelection,
+    GameEngine, Message,
+};
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Point3, Vector2, Vector3},
+        color::Color,
+        math::{aabb::AxisAlignedBoundingBox, ray::Ray, Matrix4Ext},
+        parking_lot::Mutex,
+        pool::Handle,
+    },
+    scene::{
+        base::BaseBuilder,
+        collider::ColliderShape,
+        debug::SceneDrawingContext,
+        graph::Graph,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder, RenderPath,
+        },
+        navmesh_obstacle::NavmeshObstacleShape,
+        node::Node,
+        trigger_volume::TriggerVolumeShape,
+    },
+    utils::log::Log,
+};
+use std::sync::{mpsc::Sender, Arc};
+
+/// Minimal size of any shape parameter that could be set by dragging a handle.
+const MIN_SIZE: f32 = 0.001;
+
+const SHAPE_COLOR: Color = Color::opaque(0, 255, 128);
+const EXTENT_HANDLE_COLOR: Color = Color::opaque(255, 150, 0);
+const RADIUS_HANDLE_COLOR: Color = Color::opaque(0, 170, 255);
+const ACTIVE_HANDLE_COLOR: Color = Color::opaque(255, 255, 0);
+
+/// Maximum amount of extent handles (box has one handle per face).
+const MAX_EXTENT_HANDLES: usize = 6;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum ShapeParameter {
+    BallRadius,
+    CuboidHalfExtent { axis: usize },
+    CylinderHalfHeight,
+    CylinderRadius,
+    ConeHalfHeight,
+    ConeRadius,
+    CapsuleBegin,
+    CapsuleEnd,
+    CapsuleRadius,
+}
+
+impl ShapeParameter {
+    fn is_radius(self) -> bool {
+        matches!(
+            self,
+            ShapeParameter::BallRadius
+                | ShapeParameter::CylinderRadius
+                | ShapeParameter::ConeRadius
+                | ShapeParameter::CapsuleRadius
+        )
+    }
+}
+
+/// Describes a handle in local space of a collider. The handle is located at
+/// `origin + direction * value` and can be moved only along the line defined by
+/// `origin` and `direction`.
+#[derive(Clone, Debug)]
+struct HandleAxis {
+    parameter: ShapeParameter,
+    origin: Vector3<f32>,
+    direction: Vector3<f32>,
+    value: f32,
+}
+
+impl HandleAxis {
+    fn new(
+        parameter: ShapeParameter,
+        origin: Vector3<f32>,
+        direction: Vector3<f32>,
+        value: f32,
+    ) -> Self {
+        Self {
+            parameter,
+            origin,
+            direction,
+            value,
+        }
+    }
+
+    fn position(&self) -> Vector3<f32> {
+        self.origin + self.direction * self.value
+    }
+}
+
+fn any_orthogonal(v: &Vector3<f32>) -> Vector3<f32> {
+    let candidate = if v.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::z()
+    };
+    v.cross(&candidate)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or_else(Vector3::x)
+}
+
+fn shape_handle_axes(shape: &ColliderShape) -> Vec<HandleAxis> {
+    let center = Vector3::default();
+    match shape {
+        ColliderShape::Ball(ball) => vec![HandleAxis::new(
+            ShapeParameter::BallRadius,
+            center,
+            Vector3::x(),
+            ball.radius,
+        )],
+        ColliderShape::Cuboid(cuboid) => {
+            let mut axes = Vec::with_capacity(MAX_EXTENT_HANDLES);
+            for axis in 0..3 {
+                for sign in [1.0, -1.0] {
+                    let mut direction = Vector3::default();
+                    direction[axis] = sign;
+                    axes.push(HandleAxis::new(
+                        ShapeParameter::CuboidHalfExtent { axis },
+                        center,
+                        direction,
+                        cuboid.half_extents[axis],
+                    ));
+                }
+            }
+            axes
+        }
+        ColliderShape::Cylinder(cylinder) => vec![
+            HandleAxis::new(
+                ShapeParameter::CylinderHalfHeight,
+                center,
+                Vector3::y(),
+                cylinder.half_height,
+            ),
+            HandleAxis::new(
+                ShapeParameter::CylinderHalfHeight,
+                center,
+                -Vector3::y(),
+                cylinder.half_height,
+            ),
+            HandleAxis::new(
+                ShapeParameter::CylinderRadius,
+                center,
+                Vector3::x(),
+                cylinder.radius,
+            ),
+        ],
+        ColliderShape::Cone(cone) => vec![
+            HandleAxis::new(
+                ShapeParameter::ConeHalfHeight,
+                center,
+                Vector3::y(),
+                cone.half_height,
+            ),
+            HandleAxis::new(
+                ShapeParameter::ConeHalfHeight,
+                center,
+                -Vector3::y(),
+                cone.half_height,
+            ),
+            HandleAxis::new(
+                ShapeParameter::ConeRadius,
+                center,
+                Vector3::x(),
+                cone.radius,
+            ),
+        ],
+        ColliderShape::Capsule(capsule) => {
+            let axis = capsule.end - capsule.begin;
+            let length = axis.norm();
+            let direction = axis.try_normalize(f32::EPSILON).unwrap_or_else(Vector3::y);
+            vec![
+                // End points are moved along the capsule axis, the opposite point stays in place.
+                HandleAxis::new(
+                    ShapeParameter::CapsuleBegin,
+                    capsule.end,
+                    -direction,
+                    length,
+                ),
+                HandleAxis::new(ShapeParameter::CapsuleEnd, capsule.begin, direction, length),
+                HandleAxis::new(
+                    ShapeParameter::CapsuleRadius,
+                    (capsule.begin + capsule.end).scale(0.5),
+                    any_orthogonal(&direction),
+                    capsule.radius,
+                ),
+            ]
+        }
+        _ => Default::default(),
+    }
+}
+
+fn apply_parameter(shape: &mut ColliderShape, axis: &HandleAxis, value: f32) {
+    let value = value.max(MIN_SIZE);
+    match (axis.parameter, shape) {
+        (ShapeParameter::BallRadius, ColliderShape::Ball(ball)) => ball.radius = value,
+        (ShapeParameter::CuboidHalfExtent { axis }, ColliderShape::Cuboid(cuboid)) => {
+            cuboid.half_extents[axis] = value
+        }
+        (ShapeParameter::CylinderHalfHeight, ColliderShape::Cylinder(cylinder)) => {
+            cylinder.half_height = value
+        }
+        (ShapeParameter::CylinderRadius, ColliderShape::Cylinder(cylinder)) => {
+            cylinder.radius = value
+        }
+        (ShapeParameter::ConeHalfHeight, ColliderShape::Cone(cone)) => cone.half_height = value,
+        (ShapeParameter::ConeRadius, ColliderShape::Cone(cone)) => cone.radius = value,
+        (ShapeParameter::CapsuleBegin, ColliderShape::Capsule(capsule)) => {
+            capsule.begin = axis.origin + axis.direction * value
+        }
+        (ShapeParameter::CapsuleEnd, ColliderShape::Capsule(capsule)) => {
+            capsule.end = axis.origin + axis.direction * value
+        }
+        (ShapeParameter::CapsuleRadius, ColliderShape::Capsule(capsule)) => capsule.radius = value,
+        _ => (),
+    }
+}
+
+/// Returns shape of a collider, a trigger volume or a navmesh obstacle, convex trigger volumes
+/// have no editable shape.
+fn node_shape(node: &Node) -> Option<ColliderShape> {
+    if node.is_collider() {
+        Some(node.as_collider().shape_value())
+    } else if node.is_trigger_volume() {
+        let volume = node.as_trigger_volume();
+        match volume.shape() {
+            TriggerVolumeShape::Box => {
+                let extents = volume.extents();
+                Some(ColliderShape::cuboid(extents.x, extents.y, extents.z))
+            }
+            TriggerVolumeShape::Sphere => Some(ColliderShape::ball(volume.radius())),
+            TriggerVolumeShape::Convex => None,
+        }
+    } else if node.is_navmesh_obstacle() {
+        let obstacle = node.as_navmesh_obstacle();
+        match obstacle.shape() {
+            NavmeshObstacleShape::Box => {
+                let extents = obstacle.extents();
+                Some(ColliderShape::cuboid(extents.x, extents.y, extents.z))
+            }
+            NavmeshObstacleShape::Cylinder => Some(ColliderShape::cylinder(
+                obstacle.height(),
+                obstacle.radius(),
+            )),
+        }
+    } else {
+        None
+    }
+}
+
+fn set_node_shape(node: &mut Node, shape: ColliderShape) {
+    if node.is_collider() {
+        node.as_collider_mut().set_shape(shape);
+    } else if node.is_trigger_volume() {
+        let volume = node.as_trigger_volume_mut();
+        match shape {
+            ColliderShape::Cuboid(cuboid) => volume.set_extents(cuboid.half_extents),
+            ColliderShape::Ball(ball) => volume.set_radius(ball.radius),
+            _ => (),
+        }
+    } else if node.is_navmesh_obstacle() {
+        let obstacle = node.as_navmesh_obstacle_mut();
+        match shape {
+            ColliderShape::Cuboid(cuboid) => obstacle.set_extents(cuboid.half_extents),
+            ColliderShape::Cylinder(cylinder) => {
+                obstacle.set_height(cylinder.half_height);
+                obstacle.set_radius(cylinder.radius);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Creates a command that sets the given parameter of the collider (trigger volume or navmesh
+/// obstacle) to the value from the `shape`.
+fn make_command(
+    graph: &Graph,
+    collider: Handle<Node>,
+    parameter: ShapeParameter,
+    shape: &ColliderShape,
+) -> Option<SceneCommand> {
+    if graph[collider].is_trigger_volume() {
+        return match (parameter, shape) {
+            (ShapeParameter::BallRadius, ColliderShape::Ball(ball)) => Some(SceneCommand::new(
+                SetTriggerVolumeRadiusCommand::new(collider, ball.radius),
+            )),
+            (ShapeParameter::CuboidHalfExtent { .. }, ColliderShape::Cuboid(cuboid)) => {
+                Some(SceneCommand::new(SetTriggerVolumeExtentsCommand::new(
+                    collider,
+                    cuboid.half_extents,
+                )))
+            }
+            _ => None,
+        };
+    }
+
+    if graph[collider].is_navmesh_obstacle() {
+        return match (parameter, shape) {
+            (ShapeParameter::CuboidHalfExtent { .. }, ColliderShape::Cuboid(cuboid)) => {
+                Some(SceneCommand::new(SetNavmeshObstacleExtentsCommand::new(
+                    collider,
+                    cuboid.half_extents,
+                )))
+            }
+            (ShapeParameter::CylinderHalfHeight, ColliderShape::Cylinder(cylinder)) => {
+                Some(SceneCommand::new(SetNavmeshObstacleHeightCommand::new(
+                    collider,
+                    cylinder.half_height,
+                )))
+            }
+            (ShapeParameter::CylinderRadius, ColliderShape::Cylinder(cylinder)) => {
+                Some(SceneCommand::new(SetNavmeshObstacleRadiusCommand::new(
+                    collider,
+                    cylinder.radius,
+                )))
+            }
+            _ => None,
+        };
+    }
+
+    let command = match (parameter, shape) {
+        (ShapeParameter::BallRadius, ColliderShape::Ball(ball)) => {
+            SceneCommand::new(SetBallRadiusCommand::new(collider, ball.radius))
+        }
+        (ShapeParameter::CuboidHalfExtent { .. }, ColliderShape::Cuboid(cuboid)) => {
+            SceneCommand::new(SetCuboidHalfExtentsCommand::new(
+                collider,
+                cuboid.half_extents,
+            ))
+        }
+        (ShapeParameter::CylinderHalfHeight, ColliderShape::Cylinder(cylinder)) => {
+            SceneCommand::new(SetCylinderHalfHeightCommand::new(
+                collider,
+                cylinder.half_height,
+            ))
+        }
+        (ShapeParameter::CylinderRadius, ColliderShape::Cylinder(cylinder)) => {
+            SceneCommand::new(SetCylinderRadiusCommand::new(collider, cylinder.radius))
+        }
+        (ShapeParameter::ConeHalfHeight, ColliderShape::Cone(cone)) => {
+            SceneCommand::new(SetConeHalfHeightCommand::new(collider, cone.half_height))
+        }
+        (ShapeParameter::ConeRadius, ColliderShape::Cone(cone)) => {
+            SceneCommand::new(SetConeRadiusCommand::new(collider, cone.radius))
+        }
+        (ShapeParameter::CapsuleBegin, ColliderShape::Capsule(capsule)) => {
+            SceneCommand::new(SetCapsuleBeginCommand::new(collider, capsule.begin))
+        }
+        (ShapeParameter::CapsuleEnd, ColliderShape::Capsule(capsule)) => {
+            SceneCommand::new(SetCapsuleEndCommand::new(collider, capsule.end))
+        }
+        (ShapeParameter::CapsuleRadius, ColliderShape::Capsule(capsule)) => {
+            SceneCommand::new(SetCapsuleRadiusCommand::new(collider, capsule.radius))
+        }
+        _ => return None,
+    };
+    Some(command)
+}
+
+fn draw_shape(ctx: &mut SceneDrawingContext, shape: &ColliderShape, transform: Matrix4<f32>) {
+    match shape {
+        ColliderShape::Ball(ball) => {
+            ctx.draw_sphere(transform.position(), 16, 16, ball.radius, SHAPE_COLOR)
+        }
+        ColliderShape::Cuboid(cuboid) => ctx.draw_oob(
+            &AxisAlignedBoundingBox::from_min_max(-cuboid.half_extents, cuboid.half_extents),
+            transform,
+            SHAPE_COLOR,
+        ),
+        ColliderShape::Cylinder(cylinder) => ctx.draw_cylinder(
+            16,
+            cylinder.radius,
+            cylinder.half_height * 2.0,
+            true,
+            transform,
+            SHAPE_COLOR,
+        ),
+        ColliderShape::Cone(cone) => ctx.draw_cone(
+            16,
+            cone.radius,
+            cone.half_height * 2.0,
+            transform,
+            SHAPE_COLOR,
+        ),
+        ColliderShape::Capsule(capsule) => ctx.draw_segment_capsule(
+            capsule.begin,
+            capsule.end,
+            capsule.radius,
+            10,
+            10,
+            transform,
+            SHAPE_COLOR,
+        ),
+        _ => (),
+    }
+}
+
+/// Returns a parameter of a point on the line defined by `origin` and unit `direction` which
+/// is the closest to the given ray. `None` is returned if the ray is parallel to the line.
+fn closest_line_parameter(origin: Vector3<f32>, direction: Vector3<f32>, ray: &Ray) -> Option<f32> {
+    let b = direction.dot(&ray.dir);
+    let c = ray.dir.dot(&ray.dir);
+    let w = origin - ray.origin;
+    let d = direction.dot(&w);
+    let e = ray.dir.dot(&w);
+    let denominator = c - b * b;
+    if denominator.abs() <= 1.0e-6 * c {
+        None
+    } else {
+        Some((b * e - c * d) / denominator)
+    }
+}
+
+/// Collider shapes ignore scaling, so the handles are placed in the isometric (rotation and
+/// translation only) frame of a collider. Non-uniform scale in the hierarchy, however, makes
+/// the scene look differently from what the physics "sees" and such cases are not supported.
+fn has_non_uniform_scale(graph: &Graph, node: Handle<Node>) -> bool {
+    let scale = graph.global_scale_matrix(node);
+    let (x, y, z) = (scale[0].abs(), scale[5].abs(), scale[10].abs());
+    let tolerance = 1.0e-3 * x.max(y).max(z);
+    (x - y).abs() > tolerance || (x - z).abs() > tolerance
+}
+
+fn make_handle(graph: &mut Graph, root: Handle<Node>, radius: bool) -> Handle<Node> {
+    let (name, surface, color) = if radius {
+        (
+            "ColliderRadiusHandle",
+            SurfaceData::make_sphere(8, 8, 0.06, &Matrix4::identity()),
+            RADIUS_HANDLE_COLOR,
+        )
+    } else {
+        (
+            "ColliderExtentHandle",
+            SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                0.1, 0.1, 0.1,
+            ))),
+            EXTENT_HANDLE_COLOR,
+        )
+    };
+
+    let handle = MeshBuilder::new(
+        BaseBuilder::new()
+            .with_cast_shadows(false)
+            .with_name(name)
+            .with_visibility(false),
+    )
+    .with_render_path(RenderPath::Forward)
+    .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(surface)))
+        .with_material(make_color_material(color))
+        .build()])
+    .build(graph);
+
+    graph.link_nodes(handle, root);
+
+    handle
+}
+
+struct DragContext {
+    collider: Handle<Node>,
+    axis: HandleAxis,
+    initial_shape: ColliderShape,
+    world_origin: Vector3<f32>,
+    world_direction: Vector3<f32>,
+    /// Difference between the value of a parameter and the parameter of the grabbed point,
+    /// it prevents a handle from "jumping" under the cursor.
+    grab_offset: f32,
+}
+
+pub struct ColliderShapeInteractionMode {
+    extent_handles: Vec<Handle<Node>>,
+    radius_handle: Handle<Node>,
+    /// Handle nodes bound to shape axes at the last update.
+    bound_handles: Vec<(Handle<Node>, HandleAxis)>,
+    drag: Option<DragContext>,
+    /// A collider that was reported as unsupported, it is used to prevent log spam.
+    warned_collider: Handle<Node>,
+    message_sender: Sender<Message>,
+}
+
+impl ColliderShapeInteractionMode {
+    pub fn new(
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        message_sender: Sender<Message>,
+    ) -> Self {
+        let graph = &mut engine.scenes[editor_scene.scene].graph;
+        let root = editor_scene.editor_objects_root;
+
+        Self {
+            extent_handles: (0..MAX_EXTENT_HANDLES)
+                .map(|_| make_handle(graph, root, false))
+                .collect(),
+            radius_handle: make_handle(graph, root, true),
+            bound_handles: Default::default(),
+            drag: None,
+            warned_collider: Default::default(),
+            message_sender,
+        }
+    }
+
+    fn selected_collider(editor_scene: &EditorScene, graph: &Graph) -> Handle<Node> {
+        if let Selection::Graph(selection) = &editor_scene.selection {
+            if selection.len() == 1 {
+                let first = selection.nodes()[0];
+                if graph
+                    .try_get(first)
+                    .map_or(false, |node| node_shape(node).is_some())
+                {
+                    return first;
+                }
+            }
+        }
+        Handle::NONE
+    }
+
+    fn set_visible(&self, graph: &mut Graph, visible: bool) {
+        for &handle in self.extent_handles.iter() {
+            graph[handle].set_visibility(visible);
+        }
+        graph[self.radius_handle].set_visibility(visible);
+    }
+
+    fn sync_handles(
+        &mut self,
+        graph: &mut Graph,
+        camera: Handle<Node>,
+        collider: Handle<Node>,
+        axes: Vec<HandleAxis>,
+    ) {
+        self.bound_handles.clear();
+
+        if !axes.is_empty() {
+            let transform = graph.isometric_global_transform(collider);
+            let rotation = graph.isometric_global_rotation(collider);
+
+            let mut extent_handles = self.extent_handles.iter();
+            let mut radius_handle = Some(self.radius_handle);
+            for axis in axes {
+                let handle = if axis.parameter.is_radius() {
+                    radius_handle.take()
+                } else {
+                    extent_handles.next().copied()
+                };
+
+                if let Some(handle) = handle {
+                    let position = transform
+                        .transform_point(&Point3::from(axis.position()))
+                        .coords;
+                    let scale = calculate_gizmo_distance_scaling(graph, camera, handle);
+                    graph[handle]
+                        .set_visibility(true)
+                        .local_transform_mut()
+                        .set_position(position)
+                        .set_rotation(rotation)
+                        .set_scale(scale);
+                    self.bound_handles.push((handle, axis));
+                }
+            }
+        }
+
+        let active_parameter = self.drag.as_ref().map(|d| d.axis.parameter);
+        for &handle in self
+            .extent_handles
+            .iter()
+            .chain([self.radius_handle].iter())
+        {
+            let bound = self.bound_handles.iter().find(|(h, _)| *h == handle);
+            if let Some((_, axis)) = bound {
+                let color = if Some(axis.parameter) == active_parameter {
+                    ACTIVE_HANDLE_COLOR
+                } else if axis.parameter.is_radius() {
+                    RADIUS_HANDLE_COLOR
+                } else {
+                    EXTENT_HANDLE_COLOR
+                };
+                set_mesh_diffuse_color(graph[handle].as_mesh_mut(), color);
+            } else {
+                graph[handle].set_visibility(false);
+            }
+        }
+    }
+}
+
+impl InteractionMode for ColliderShapeInteractionMode {
+    fn on_left_mouse_button_down(
+        &mut self,
+        editor_scene: &mut EditorScene,
+        engine: &mut GameEngine,
+        mouse_pos: Vector2<f32>,
+        frame_size: Vector2<f32>,
+        settings: &Settings,
+    ) {
+        let graph = &mut engine.scenes[editor_scene.scene].graph;
+
+        let collider = Self::selected_collider(editor_scene, graph);
+        if collider.is_none() || self.bound_handles.is_empty() {
+            return;
+        }
+
+        let bound_handles = &self.bound_handles;
+        let picked = editor_scene.camera_controller.pick(PickingOptions {
+            cursor_pos: mouse_pos,
+            graph,
+            editor_objects_root: editor_scene.editor_objects_root,
+            screen_size: frame_size,
+            editor_only: true,
+            filter: |handle, _| bound_handles.iter().any(|(h, _)| *h == handle),
+            ignore_back_faces: settings.selection.ignore_back_faces,
+        });
+
+        if let Some(result) = picked {
+            if let Some((_, axis)) = self.bound_handles.iter().find(|(h, _)| *h == result.node) {
+                let transform = graph.isometric_global_transform(collider);
+                let world_origin = transform.transform_point(&Point3::from(axis.origin)).coords;
+                let world_direction = transform.transform_vector(&axis.direction);
+
+                let ray = graph[editor_scene.camera_controller.camera]
+                    .as_camera()
+                    .make_ray(mouse_pos, frame_size);
+
+                if let (Some(grabbed), Some(initial_shape)) = (
+                    closest_line_parameter(world_origin, world_direction, &ray),
+                    node_shape(&graph[collider]),
+                ) {
+                    self.drag = Some(DragContext {
+                        collider,
+                        axis: axis.clone(),
+                        initial_shape,
+                        world_origin,
+                        world_direction,
+                        grab_offset: axis.value - grabbed,
+                    });
+                }
+            }
+        }
+    }
+
+    fn on_left_mouse_button_up(
+        &mut self,
+        editor_scene: &mut EditorScene,
+        engine: &mut GameEngine,
+        mouse_pos: Vector2<f32>,
+        frame_size: Vector2<f32>,
+        settings: &Settings,
+    ) {
+        let scene = &mut engine.scenes[editor_scene.scene];
+        let graph = &mut scene.graph;
+
+        if let Some(drag) = self.drag.take() {
+            if !graph.is_valid_handle(drag.collider) {
+                return;
+            }
+
+            let new_shape = node_shape(&graph[drag.collider]);
+            if let Some(new_shape) = new_shape.filter(|shape| *shape != drag.initial_shape) {
+                // Restore initial shape and commit the change as a command, this way the whole
+                // drag becomes a single undoable action.
+                set_node_shape(&mut graph[drag.collider], drag.initial_shape);
+
+                if let Some(command) =
+                    make_command(graph, drag.collider, drag.axis.parameter, &new_shape)
+                {
+                    self.message_sender
+                        .send(Message::DoSceneCommand(command))
+                        .unwrap();
+                }
+            }
+        } else {
+            let new_selection = editor_scene
+                .camera_controller
+                .pick(PickingOptions {
+                    cursor_pos: mouse_pos,
+                    graph,
+                    editor_objects_root: editor_scene.editor_objects_root,
+                    screen_size: frame_size,
+                    editor_only: false,
+                    // Members of locked collections cannot be picked.
+                    filter: |handle, _| !scene.node_collections.is_locked(handle),
+                    ignore_back_faces: settings.selection.ignore_back_faces,
+                })
+                .map(|result| {
+                    if let (Selection::Graph(selection), true) = (
+                        &editor_scene.selection,
+                        engine.user_interface.keyboard_modifiers().control,
+                    ) {
+                        let mut selection = selection.clone();
+                        selection.insert_or_exclude(result.node);
+                        Selection::Graph(selection)
+                    } else {
+                        Selection::Graph(GraphSelection::single_or_empty(result.node))
+                    }
+                })
+                .unwrap_or_else(|| Selection::Graph(GraphSelection::default()));
+
+            if new_selection != editor_scene.selection {
+                self.message_sender
+                    .send(Message::do_scene_command(ChangeSelectionCommand::new(
+                        new_selection,
+                        editor_scene.selection.clone(),
+                    )))
+                    .unwrap();
+            }
+        }
+    }
+
+    fn on_mouse_move(
+        &mut self,
+        _mouse_offset: Vector2<f32>,
+        mouse_position: Vector2<f32>,
+        camera: Handle<Node>,
+        editor_scene: &mut EditorScene,
+        engine: &mut GameEngine,
+        frame_size: Vector2<f32>,
+        _settings: &Settings,
+    ) {
+        if let Some(drag) = self.drag.as_ref() {
+            let graph = &mut engine.scenes[editor_scene.scene].graph;
+            if !graph.is_valid_handle(drag.collider) {
+                return;
+            }
+
+            let ray = graph[camera]
+                .as_camera()
+                .make_ray(mouse_position, frame_size);
+
+            if let (Some(parameter), Some(mut shape)) = (
+                closest_line_parameter(drag.world_origin, drag.world_direction, &ray),
+                node_shape(&graph[drag.collider]),
+            ) {
+                apply_parameter(&mut shape, &drag.axis, parameter + drag.grab_offset);
+                set_node_shape(&mut graph[drag.collider], shape);
+            }
+        }
+    }
+
+    fn update(
+        &mut self,
+        editor_scene: &mut EditorScene,
+        camera: Handle<Node>,
+        engine: &mut GameEngine,
+        _settings: &Settings,
+    ) {
+        let scene = &mut engine.scenes[editor_scene.scene];
+
+        let collider = Self::selected_collider(editor_scene, &scene.graph);
+
+        let mut axes = Vec::new();
+        if collider.is_some() {
+            if has_non_uniform_scale(&scene.graph, collider) {
+                if self.warned_collider != collider {
+                    Log::warn(format!(
+                        "Unable to edit shape of collider {}: non-uniform scale in its hierarchy \
+                        is not supported, collider shapes ignore scaling. Remove the scale to \
+                        edit the shape in the viewport or use the Inspector instead.",
+                        scene.graph[collider].name()
+                    ));
+                    self.warned_collider = collider;
+                }
+            } else if let Some(shape) = node_shape(&scene.graph[collider]) {
+                draw_shape(
+                    &mut scene.drawing_context,
+                    &shape,
+                    scene.graph.isometric_global_transform(collider),
+                );
+                axes = shape_handle_axes(&shape);
+            }
+        }
+
+        self.sync_handles(&mut scene.graph, camera, collider, axes);
+    }
+
+    fn deactivate(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        let graph = &mut engine.scenes[editor_scene.scene].graph;
+
+        // Cancel unfinished drag.
+        if let Some(drag) = self.drag.take() {
+            if graph.is_valid_handle(drag.collider) {
+                set_node_shape(&mut graph[drag.collider], drag.initial_shape);
+            }
+        }
+
+        self.set_visible(graph, false);
+        self.bound_handles.clear();
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    interaction::plane::PlaneKind,
+    interaction::{plane::PlaneKind, transform_space::TransformSpace},
     make_color_material,
     scene::{EditorScene, Selection},
     set_mesh_diffuse_color, GameEngine,
@@ -301,10 +301,16 @@ impl MoveGizmo {
         Vector3::default()
     }
 
-    pub fn sync_transform(&self, scene: &mut Scene, selection: &Selection, scale: Vector3<f32>) {
+    pub fn sync_transform(
+        &self,
+        scene: &mut Scene,
+        selection: &Selection,
+        scale: Vector3<f32>,
+        space: TransformSpace,
+    ) {
         let graph = &mut scene.graph;
         if let Selection::Graph(selection) = selection {
-            if let Some((rotation, position)) = selection.global_rotation_position(graph) {
+            if let Some((rotation, position)) = space.gizmo_rotation_position(selection, graph) {
                 graph[self.origin]
                     .set_visibility(true)
                     .local_transform_mut()
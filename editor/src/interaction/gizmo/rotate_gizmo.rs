@@ -1,6 +1,6 @@
 use crate::{
-    make_color_material, scene::EditorScene, set_mesh_diffuse_color,
-    world::graph::selection::GraphSelection, GameEngine,
+    interaction::transform_space::TransformSpace, make_color_material, scene::EditorScene,
+    set_mesh_diffuse_color, world::graph::selection::GraphSelection, GameEngine,
 };
 use fyrox::{
     core::{
@@ -221,8 +221,9 @@ impl RotationGizmo {
         graph: &mut Graph,
         selection: &GraphSelection,
         scale: Vector3<f32>,
+        space: TransformSpace,
     ) {
-        if let Some((rotation, position)) = selection.global_rotation_position(graph) {
+        if let Some((rotation, position)) = space.gizmo_rotation_position(selection, graph) {
             graph[self.origin]
                 .set_visibility(true)
                 .local_transform_mut()
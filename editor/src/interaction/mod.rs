@@ -10,14 +10,19 @@ use fyrox::{
 };
 use std::any::Any;
 
+pub mod collider_shape;
 pub mod gizmo;
 pub mod move_mode;
 pub mod navmesh;
 pub mod plane;
 pub mod rotate_mode;
 pub mod scale_mode;
+pub mod scatter;
 pub mod select_mode;
+pub mod snapping;
 pub mod terrain;
+pub mod transform_space;
+pub mod vertex_paint;
 
 pub trait BaseInteractionMode {
     fn as_any(&self) -> &dyn Any;
@@ -70,6 +75,7 @@ pub trait InteractionMode: BaseInteractionMode {
         _editor_scene: &mut EditorScene,
         _camera: Handle<Node>,
         _engine: &mut GameEngine,
+        _settings: &Settings,
     ) {
     }
 
@@ -142,4 +148,7 @@ pub enum InteractionModeKind {
     Rotate = 3,
     Navmesh = 4,
     Terrain = 5,
+    Scatter = 6,
+    ColliderShape = 7,
+    VertexPaint = 8,
 }
@@ -2,11 +2,18 @@ use crate::camera::PickingOptions;
 use crate::{
     camera::CameraController,
     interaction::{
-        calculate_gizmo_distance_scaling, gizmo::move_gizmo::MoveGizmo, plane::PlaneKind,
+        calculate_gizmo_distance_scaling,
+        gizmo::move_gizmo::MoveGizmo,
+        plane::PlaneKind,
+        snapping::{draw_anchor, GeometrySnapping, SnapPoint, SnapQuery},
+        transform_space::{offset_local_position, parent_inv_global_transform},
         InteractionMode,
     },
     scene::{
-        commands::{graph::MoveNodeCommand, ChangeSelectionCommand, CommandGroup, SceneCommand},
+        commands::{
+            graph::{MoveNodeCommand, MoveNodesCommand},
+            ChangeSelectionCommand, SceneCommand,
+        },
         EditorScene, Selection,
     },
     settings::Settings,
@@ -26,7 +33,6 @@ use std::sync::mpsc::Sender;
 
 struct Entry {
     node: Handle<Node>,
-    initial_offset_gizmo_space: Vector3<f32>,
     initial_local_position: Vector3<f32>,
     initial_parent_inv_global_transform: Matrix4<f32>,
     new_local_position: Vector3<f32>,
@@ -36,8 +42,13 @@ struct MoveContext {
     plane: Plane,
     objects: Vec<Entry>,
     plane_kind: PlaneKind,
+    // Picked point at drag start in gizmo space, drag offsets are measured from it.
+    initial_plane_point: Vector3<f32>,
     gizmo_inv_transform: Matrix4<f32>,
-    gizmo_local_transform: Matrix4<f32>,
+    gizmo_transform: Matrix4<f32>,
+    // World position of the point that is moved exactly to a snap target, it is the pivot of the
+    // first moved node unless there is a snap anchor.
+    snap_origin: Vector3<f32>,
 }
 
 impl MoveContext {
@@ -51,16 +62,14 @@ impl MoveContext {
         mut fill: F,
     ) -> Self
     where
-        F: FnMut(Vector3<f32>, Matrix4<f32>, Vector3<f32>) -> Vec<Entry>,
+        F: FnMut() -> Vec<Entry>,
     {
         let graph = &scene.graph;
 
         let gizmo_origin = &graph[move_gizmo.origin];
 
-        let gizmo_inv_transform = gizmo_origin
-            .global_transform()
-            .try_inverse()
-            .unwrap_or_default();
+        let gizmo_transform = gizmo_origin.global_transform();
+        let gizmo_inv_transform = gizmo_transform.try_inverse().unwrap_or_default();
 
         let look_direction =
             gizmo_inv_transform.transform_vector(&graph[camera_controller.camera].look_vector());
@@ -73,16 +82,21 @@ impl MoveContext {
                 .unwrap_or_default(),
         );
 
+        let objects = fill();
+
+        let snap_origin = objects
+            .first()
+            .map(|entry| graph[entry.node].global_position())
+            .unwrap_or_default();
+
         Self {
             plane,
-            objects: fill(
-                plane_point,
-                gizmo_inv_transform,
-                gizmo_origin.global_position(),
-            ),
-            gizmo_local_transform: gizmo_origin.local_transform().matrix(),
-            gizmo_inv_transform,
+            objects,
             plane_kind,
+            initial_plane_point: plane_point,
+            gizmo_inv_transform,
+            gizmo_transform,
+            snap_origin,
         }
     }
 
@@ -102,31 +116,20 @@ impl MoveContext {
             plane_kind,
             mouse_pos,
             frame_size,
-            |plane_point, gizmo_inv_transform, gizmo_origin| {
+            || {
                 let graph = &scene.graph;
                 selection
                     .root_nodes(graph)
                     .iter()
-                    .map(|&node_handle| {
-                        let node = &graph[node_handle];
+                    .map(|&node| {
+                        let local_position = **graph[node].local_transform().position();
                         Entry {
-                            node: node_handle,
-                            initial_offset_gizmo_space: gizmo_inv_transform
-                                .transform_point(&Point3::from(node.global_position()))
-                                .coords
-                                - plane_point
-                                - gizmo_inv_transform
-                                    .transform_vector(&(node.global_position() - gizmo_origin)),
-                            new_local_position: **node.local_transform().position(),
-                            initial_local_position: **node.local_transform().position(),
-                            initial_parent_inv_global_transform: if node.parent().is_some() {
-                                graph[node.parent()]
-                                    .global_transform()
-                                    .try_inverse()
-                                    .unwrap_or_default()
-                            } else {
-                                Matrix4::identity()
-                            },
+                            node,
+                            initial_local_position: local_position,
+                            initial_parent_inv_global_transform: parent_inv_global_transform(
+                                graph, node,
+                            ),
+                            new_local_position: local_position,
                         }
                     })
                     .collect()
@@ -141,27 +144,38 @@ impl MoveContext {
         settings: &Settings,
         mouse_position: Vector2<f32>,
         frame_size: Vector2<f32>,
+        snap_target: Option<Vector3<f32>>,
     ) {
-        if let Some(picked_position_gizmo_space) = camera_controller
-            .pick_on_plane(
-                self.plane,
-                graph,
-                mouse_position,
-                frame_size,
-                self.gizmo_inv_transform,
-            )
-            .map(|p| self.plane_kind.project_point(p))
-        {
+        let world_offset = match snap_target {
+            // Snapping ignores the axes of the gizmo, the snap origin is moved right to the target.
+            Some(snap_target) => Some(snap_target - self.snap_origin),
+            None => camera_controller
+                .pick_on_plane(
+                    self.plane,
+                    graph,
+                    mouse_position,
+                    frame_size,
+                    self.gizmo_inv_transform,
+                )
+                .map(|p| {
+                    // Axes of the gizmo are the axes of the transform space, so the offset is
+                    // measured along them and then converted to world coordinates.
+                    self.gizmo_transform.transform_vector(
+                        &(self.plane_kind.project_point(p) - self.initial_plane_point),
+                    )
+                }),
+        };
+
+        if let Some(world_offset) = world_offset {
             for entry in self.objects.iter_mut() {
-                let mut new_local_position = entry.initial_local_position
-                    + entry.initial_parent_inv_global_transform.transform_vector(
-                        &self.gizmo_local_transform.transform_vector(
-                            &(picked_position_gizmo_space + entry.initial_offset_gizmo_space),
-                        ),
-                    );
+                let mut new_local_position = offset_local_position(
+                    entry.initial_local_position,
+                    &entry.initial_parent_inv_global_transform,
+                    world_offset,
+                );
 
                 // Snap to grid if needed.
-                if settings.move_mode_settings.grid_snapping {
+                if snap_target.is_none() && settings.move_mode_settings.grid_snapping {
                     new_local_position = Vector3::new(
                         round_to_step(
                             new_local_position.x,
@@ -184,10 +198,80 @@ impl MoveContext {
     }
 }
 
+// A point of a selected node that is snapped instead of its pivot.
+#[derive(Copy, Clone)]
+struct SnapAnchor {
+    node: Handle<Node>,
+    local_position: Vector3<f32>,
+}
+
+impl SnapAnchor {
+    fn pick(
+        snapping: &mut GeometrySnapping,
+        graph: &Graph,
+        camera_controller: &CameraController,
+        selection: &GraphSelection,
+        mouse_pos: Vector2<f32>,
+        frame_size: Vector2<f32>,
+        radius: f32,
+    ) -> Option<Self> {
+        let roots = selection.root_nodes(graph);
+        let point = snapping.find(SnapQuery {
+            graph,
+            camera: graph[camera_controller.camera].as_camera(),
+            cursor: mouse_pos,
+            frame_size,
+            radius,
+            roots: &roots,
+            excluded: &[],
+        })?;
+
+        // The anchor is attached to the moved node, that contains the picked mesh.
+        let mut node = point.node;
+        while !roots.contains(&node) {
+            node = graph[node].parent();
+            if node.is_none() {
+                return None;
+            }
+        }
+
+        Some(Self {
+            node,
+            local_position: graph[node]
+                .global_transform()
+                .try_inverse()
+                .unwrap_or_default()
+                .transform_point(&Point3::from(point.position))
+                .coords,
+        })
+    }
+
+    fn global_position(&self, graph: &Graph) -> Vector3<f32> {
+        graph[self.node]
+            .global_transform()
+            .transform_point(&Point3::from(self.local_position))
+            .coords
+    }
+}
+
+/// Moves selected nodes with the move gizmo.
+///
+/// # Snapping
+///
+/// When `Shift` is held while dragging, the nodes are snapped to vertices, edges and surfaces of
+/// other meshes near the cursor (see [`GeometrySnapping`]). The pivot of the first node is moved to
+/// the found point, or the snap anchor if there is one. The anchor is picked by clicking a selected
+/// object with `Shift` held before the drag.
 pub struct MoveInteractionMode {
     move_context: Option<MoveContext>,
     move_gizmo: MoveGizmo,
     message_sender: Sender<Message>,
+    snapping: GeometrySnapping,
+    snap_point: Option<SnapPoint>,
+    snap_anchor: Option<SnapAnchor>,
+    // Set when the last click picked the snap anchor, such click does not change the selection.
+    anchor_picked: bool,
+    frame_size: Vector2<f32>,
 }
 
 impl MoveInteractionMode {
@@ -200,6 +284,11 @@ impl MoveInteractionMode {
             move_context: None,
             move_gizmo: MoveGizmo::new(editor_scene, engine),
             message_sender,
+            snapping: Default::default(),
+            snap_point: None,
+            snap_anchor: None,
+            anchor_picked: false,
+            frame_size: Default::default(),
         }
     }
 }
@@ -213,9 +302,13 @@ impl InteractionMode for MoveInteractionMode {
         frame_size: Vector2<f32>,
         settings: &Settings,
     ) {
+        let shift = engine.user_interface.keyboard_modifiers().shift;
         let scene = &mut engine.scenes[editor_scene.scene];
         let graph = &mut scene.graph;
 
+        self.frame_size = frame_size;
+        self.anchor_picked = false;
+
         let camera = editor_scene.camera_controller.camera;
         let camera_pivot = editor_scene.camera_controller.pivot;
         if let Some(result) = editor_scene.camera_controller.pick(PickingOptions {
@@ -231,7 +324,7 @@ impl InteractionMode for MoveInteractionMode {
         }) {
             if let Some(plane_kind) = self.move_gizmo.handle_pick(result.node, graph) {
                 if let Selection::Graph(selection) = &editor_scene.selection {
-                    self.move_context = Some(MoveContext::from_graph_selection(
+                    let mut move_context = MoveContext::from_graph_selection(
                         selection,
                         scene,
                         &self.move_gizmo,
@@ -239,10 +332,29 @@ impl InteractionMode for MoveInteractionMode {
                         plane_kind,
                         mouse_pos,
                         frame_size,
-                    ));
+                    );
+                    if let Some(anchor) = self.snap_anchor {
+                        move_context.snap_origin = anchor.global_position(&scene.graph);
+                    }
+                    self.move_context = Some(move_context);
                 }
             }
         }
+
+        if self.move_context.is_none() && shift {
+            if let Selection::Graph(selection) = &editor_scene.selection {
+                self.snap_anchor = SnapAnchor::pick(
+                    &mut self.snapping,
+                    &scene.graph,
+                    &editor_scene.camera_controller,
+                    selection,
+                    mouse_pos,
+                    frame_size,
+                    settings.move_mode_settings.snapping_radius,
+                );
+                self.anchor_picked = true;
+            }
+        }
     }
 
     fn on_left_mouse_button_up(
@@ -255,6 +367,9 @@ impl InteractionMode for MoveInteractionMode {
     ) {
         let scene = &mut engine.scenes[editor_scene.scene];
 
+        self.snap_point = None;
+        self.snapping.clear_unused(&scene.graph);
+
         if let Some(move_context) = self.move_context.take() {
             let mut changed = false;
 
@@ -268,26 +383,32 @@ impl InteractionMode for MoveInteractionMode {
             }
 
             if changed {
-                let commands = CommandGroup::from(
-                    move_context
-                        .objects
-                        .iter()
-                        .map(|initial_state| {
-                            SceneCommand::new(MoveNodeCommand::new(
-                                initial_state.node,
-                                initial_state.initial_local_position,
-                                **scene.graph[initial_state.node].local_transform().position(),
-                            ))
-                        })
-                        .collect::<Vec<_>>(),
-                );
+                let entries = move_context
+                    .objects
+                    .iter()
+                    .map(|initial_state| {
+                        (
+                            initial_state.node,
+                            initial_state.initial_local_position,
+                            **scene.graph[initial_state.node].local_transform().position(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                // A single command for the whole selection, so a drag is undone in one step.
+                let command = match entries.as_slice() {
+                    [(node, old_position, new_position)] => {
+                        SceneCommand::new(MoveNodeCommand::new(*node, *old_position, *new_position))
+                    }
+                    _ => SceneCommand::new(MoveNodesCommand::new(entries)),
+                };
 
                 // Commit changes.
                 self.message_sender
-                    .send(Message::DoSceneCommand(SceneCommand::new(commands)))
+                    .send(Message::DoSceneCommand(command))
                     .unwrap();
             }
-        } else {
+        } else if !std::mem::take(&mut self.anchor_picked) {
             let new_selection = editor_scene
                 .camera_controller
                 .pick(PickingOptions {
@@ -296,7 +417,8 @@ impl InteractionMode for MoveInteractionMode {
                     editor_objects_root: editor_scene.editor_objects_root,
                     screen_size: frame_size,
                     editor_only: false,
-                    filter: |_, _| true,
+                    // Members of locked collections cannot be picked.
+                    filter: |handle, _| !scene.node_collections.is_locked(handle),
                     ignore_back_faces: settings.selection.ignore_back_faces,
                 })
                 .map(|result| {
@@ -334,9 +456,30 @@ impl InteractionMode for MoveInteractionMode {
         frame_size: Vector2<f32>,
         settings: &Settings,
     ) {
+        self.frame_size = frame_size;
+
         if let Some(move_context) = self.move_context.as_mut() {
+            let shift = engine.user_interface.keyboard_modifiers().shift;
             let scene = &mut engine.scenes[editor_scene.scene];
-            let graph = &mut scene.graph;
+            let graph = &scene.graph;
+
+            self.snap_point = if shift {
+                // Moved nodes cannot be snapped to themselves.
+                let mut excluded = vec![editor_scene.editor_objects_root];
+                excluded.extend(move_context.objects.iter().map(|entry| entry.node));
+
+                self.snapping.find(SnapQuery {
+                    graph,
+                    camera: graph[editor_scene.camera_controller.camera].as_camera(),
+                    cursor: mouse_position,
+                    frame_size,
+                    radius: settings.move_mode_settings.snapping_radius,
+                    roots: &[graph.get_root()],
+                    excluded: &excluded,
+                })
+            } else {
+                None
+            };
 
             move_context.update(
                 graph,
@@ -344,6 +487,7 @@ impl InteractionMode for MoveInteractionMode {
                 settings,
                 mouse_position,
                 frame_size,
+                self.snap_point.map(|point| point.position),
             );
 
             for entry in move_context.objects.iter() {
@@ -359,16 +503,44 @@ impl InteractionMode for MoveInteractionMode {
         editor_scene: &mut EditorScene,
         camera: Handle<Node>,
         engine: &mut GameEngine,
+        settings: &Settings,
     ) {
         let scene = &mut engine.scenes[editor_scene.scene];
-        let graph = &mut scene.graph;
         if !editor_scene.selection.is_empty() {
-            let scale = calculate_gizmo_distance_scaling(graph, camera, self.move_gizmo.origin);
-            self.move_gizmo.set_visible(graph, true);
-            self.move_gizmo
-                .sync_transform(scene, &editor_scene.selection, scale);
+            let scale =
+                calculate_gizmo_distance_scaling(&scene.graph, camera, self.move_gizmo.origin);
+            self.move_gizmo.set_visible(&mut scene.graph, true);
+            self.move_gizmo.sync_transform(
+                scene,
+                &editor_scene.selection,
+                scale,
+                settings.transform_space,
+            );
         } else {
-            self.move_gizmo.set_visible(graph, false);
+            self.move_gizmo.set_visible(&mut scene.graph, false);
+        }
+
+        let graph = &scene.graph;
+        // The anchor is valid only while its node is selected.
+        if let Some(anchor) = self.snap_anchor {
+            if !graph.is_valid_handle(anchor.node)
+                || !matches!(&editor_scene.selection, Selection::Graph(s) if s.contains(anchor.node))
+            {
+                self.snap_anchor = None;
+            }
+        }
+
+        let camera = graph[camera].as_camera();
+        if let Some(anchor) = self.snap_anchor {
+            draw_anchor(
+                &mut scene.drawing_context,
+                camera,
+                self.frame_size,
+                anchor.global_position(graph),
+            );
+        }
+        if let Some(snap_point) = self.snap_point.as_ref() {
+            snap_point.draw(&mut scene.drawing_context, camera, self.frame_size);
         }
     }
 
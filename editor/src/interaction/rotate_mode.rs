@@ -1,10 +1,16 @@
 use crate::camera::PickingOptions;
 use crate::{
     interaction::{
-        calculate_gizmo_distance_scaling, gizmo::rotate_gizmo::RotationGizmo, InteractionMode,
+        calculate_gizmo_distance_scaling,
+        gizmo::rotate_gizmo::RotationGizmo,
+        transform_space::{parent_global_rotation, rotate_local_rotation},
+        InteractionMode,
     },
     scene::{
-        commands::{graph::RotateNodeCommand, ChangeSelectionCommand, CommandGroup, SceneCommand},
+        commands::{
+            graph::{RotateNodeCommand, RotateNodesCommand},
+            ChangeSelectionCommand, SceneCommand,
+        },
         EditorScene, Selection,
     },
     settings::Settings,
@@ -21,8 +27,18 @@ use fyrox::{
 };
 use std::sync::mpsc::Sender;
 
+struct Entry {
+    node: Handle<Node>,
+    initial_local_rotation: UnitQuaternion<f32>,
+    parent_global_rotation: UnitQuaternion<f32>,
+}
+
 pub struct RotateInteractionMode {
-    initial_rotations: Vec<UnitQuaternion<f32>>,
+    entries: Vec<Entry>,
+    // Rotation of the axes of the transform space at drag start.
+    axes_rotation: UnitQuaternion<f32>,
+    // Rotation accumulated since drag start, in world coordinates.
+    rotation_delta: UnitQuaternion<f32>,
     rotation_gizmo: RotationGizmo,
     interacting: bool,
     message_sender: Sender<Message>,
@@ -35,7 +51,9 @@ impl RotateInteractionMode {
         message_sender: Sender<Message>,
     ) -> Self {
         Self {
-            initial_rotations: Default::default(),
+            entries: Default::default(),
+            axes_rotation: Default::default(),
+            rotation_delta: Default::default(),
             rotation_gizmo: RotationGizmo::new(editor_scene, engine),
             interacting: false,
             message_sender,
@@ -75,7 +93,21 @@ impl InteractionMode for RotateInteractionMode {
                 let graph = &mut engine.scenes[editor_scene.scene].graph;
                 if let Selection::Graph(selection) = &editor_scene.selection {
                     self.interacting = true;
-                    self.initial_rotations = selection.local_rotations(graph);
+                    self.entries = selection
+                        .nodes()
+                        .iter()
+                        .map(|&node| Entry {
+                            node,
+                            initial_local_rotation: **graph[node].local_transform().rotation(),
+                            parent_global_rotation: parent_global_rotation(graph, node),
+                        })
+                        .collect();
+                    self.axes_rotation = settings
+                        .transform_space
+                        .gizmo_rotation_position(selection, graph)
+                        .map(|(rotation, _)| rotation)
+                        .unwrap_or_default();
+                    self.rotation_delta = UnitQuaternion::identity();
                 }
             }
         }
@@ -89,34 +121,36 @@ impl InteractionMode for RotateInteractionMode {
         frame_size: Vector2<f32>,
         settings: &Settings,
     ) {
-        let graph = &mut engine.scenes[editor_scene.scene].graph;
+        let scene = &mut engine.scenes[editor_scene.scene];
+        let graph = &mut scene.graph;
 
         if self.interacting {
-            if let Selection::Graph(selection) = &editor_scene.selection {
-                if !selection.is_empty() {
-                    self.interacting = false;
-                    let current_rotation = selection.local_rotations(graph);
-                    if current_rotation != self.initial_rotations {
-                        let commands = CommandGroup::from(
-                            selection
-                                .nodes()
-                                .iter()
-                                .zip(self.initial_rotations.iter().zip(current_rotation.iter()))
-                                .map(|(&node, (&old_rotation, &new_rotation))| {
-                                    SceneCommand::new(RotateNodeCommand::new(
-                                        node,
-                                        old_rotation,
-                                        new_rotation,
-                                    ))
-                                })
-                                .collect::<Vec<SceneCommand>>(),
-                        );
-                        // Commit changes.
-                        self.message_sender
-                            .send(Message::do_scene_command(commands))
-                            .unwrap();
-                    }
-                }
+            self.interacting = false;
+            let entries = std::mem::take(&mut self.entries);
+            let changed = entries.iter().any(|entry| {
+                **graph[entry.node].local_transform().rotation() != entry.initial_local_rotation
+            });
+            if changed {
+                let entries = entries
+                    .iter()
+                    .map(|entry| {
+                        (
+                            entry.node,
+                            entry.initial_local_rotation,
+                            **graph[entry.node].local_transform().rotation(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                let command = match entries.as_slice() {
+                    [(node, old_rotation, new_rotation)] => SceneCommand::new(
+                        RotateNodeCommand::new(*node, *old_rotation, *new_rotation),
+                    ),
+                    _ => SceneCommand::new(RotateNodesCommand::new(entries)),
+                };
+                // Commit changes.
+                self.message_sender
+                    .send(Message::DoSceneCommand(command))
+                    .unwrap();
             }
         } else {
             let new_selection = editor_scene
@@ -127,7 +161,8 @@ impl InteractionMode for RotateInteractionMode {
                     editor_objects_root: editor_scene.editor_objects_root,
                     screen_size: frame_size,
                     editor_only: false,
-                    filter: |_, _| true,
+                    // Members of locked collections cannot be picked.
+                    filter: |handle, _| !scene.node_collections.is_locked(handle),
                     ignore_back_faces: settings.selection.ignore_back_faces,
                 })
                 .map(|result| {
@@ -165,38 +200,42 @@ impl InteractionMode for RotateInteractionMode {
         frame_size: Vector2<f32>,
         settings: &Settings,
     ) {
-        if let Selection::Graph(selection) = &editor_scene.selection {
-            if self.interacting {
-                let rotation_delta = self.rotation_gizmo.calculate_rotation_delta(
-                    editor_scene,
-                    camera,
-                    mouse_offset,
-                    mouse_position,
-                    engine,
-                    frame_size,
+        if self.interacting {
+            // The delta is calculated around an axis of the gizmo, axes of the gizmo are the axes
+            // of the transform space.
+            let rotation_delta = self.rotation_gizmo.calculate_rotation_delta(
+                editor_scene,
+                camera,
+                mouse_offset,
+                mouse_position,
+                engine,
+                frame_size,
+            );
+            self.rotation_delta = self.axes_rotation
+                * rotation_delta
+                * self.axes_rotation.inverse()
+                * self.rotation_delta;
+
+            for entry in self.entries.iter() {
+                let final_rotation = rotate_local_rotation(
+                    entry.initial_local_rotation,
+                    entry.parent_global_rotation,
+                    self.rotation_delta,
                 );
-                for &node in selection.nodes().iter() {
-                    let transform =
-                        engine.scenes[editor_scene.scene].graph[node].local_transform_mut();
-                    let rotation = **transform.rotation();
-                    let final_rotation = rotation * rotation_delta;
-                    let (mut roll, mut pitch, mut yaw) = final_rotation.euler_angles();
-                    if settings.rotate_mode_settings.angle_snapping {
-                        pitch = round_to_step(
-                            pitch,
-                            settings.rotate_mode_settings.x_snap_step.to_radians(),
-                        );
-                        yaw = round_to_step(
-                            yaw,
-                            settings.rotate_mode_settings.y_snap_step.to_radians(),
-                        );
-                        roll = round_to_step(
-                            roll,
-                            settings.rotate_mode_settings.z_snap_step.to_radians(),
-                        );
-                    }
-                    transform.set_rotation(UnitQuaternion::from_euler_angles(roll, pitch, yaw));
+                let (mut roll, mut pitch, mut yaw) = final_rotation.euler_angles();
+                if settings.rotate_mode_settings.angle_snapping {
+                    pitch = round_to_step(
+                        pitch,
+                        settings.rotate_mode_settings.x_snap_step.to_radians(),
+                    );
+                    yaw =
+                        round_to_step(yaw, settings.rotate_mode_settings.y_snap_step.to_radians());
+                    roll =
+                        round_to_step(roll, settings.rotate_mode_settings.z_snap_step.to_radians());
                 }
+                engine.scenes[editor_scene.scene].graph[entry.node]
+                    .local_transform_mut()
+                    .set_rotation(UnitQuaternion::from_euler_angles(roll, pitch, yaw));
             }
         }
     }
@@ -206,13 +245,19 @@ impl InteractionMode for RotateInteractionMode {
         editor_scene: &mut EditorScene,
         camera: Handle<Node>,
         engine: &mut GameEngine,
+        settings: &Settings,
     ) {
         if let Selection::Graph(selection) = &editor_scene.selection {
             let graph = &mut engine.scenes[editor_scene.scene].graph;
             if !editor_scene.selection.is_empty() {
                 let scale =
                     calculate_gizmo_distance_scaling(graph, camera, self.rotation_gizmo.origin);
-                self.rotation_gizmo.sync_transform(graph, selection, scale);
+                self.rotation_gizmo.sync_transform(
+                    graph,
+                    selection,
+                    scale,
+                    settings.transform_space,
+                );
                 self.rotation_gizmo.set_visible(graph, true);
             } else {
                 self.rotation_gizmo.set_visible(graph, false);
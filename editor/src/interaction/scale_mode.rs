@@ -3,10 +3,16 @@ use crate::scene::commands::SceneCommand;
 use crate::world::graph::selection::GraphSelection;
 use crate::{
     interaction::{
-        calculate_gizmo_distance_scaling, gizmo::scale_gizmo::ScaleGizmo, InteractionMode,
+        calculate_gizmo_distance_scaling,
+        gizmo::scale_gizmo::ScaleGizmo,
+        transform_space::{parent_global_rotation, scale_local_scale},
+        InteractionMode,
     },
     scene::{
-        commands::{graph::ScaleNodeCommand, ChangeSelectionCommand, CommandGroup},
+        commands::{
+            graph::{ScaleNodeCommand, ScaleNodesCommand},
+            ChangeSelectionCommand,
+        },
         EditorScene, Selection,
     },
     settings::Settings,
@@ -14,15 +20,25 @@ use crate::{
 };
 use fyrox::{
     core::{
-        algebra::{Vector2, Vector3},
+        algebra::{UnitQuaternion, Vector2, Vector3},
         pool::Handle,
     },
     scene::node::Node,
 };
 use std::sync::mpsc::Sender;
 
+struct Entry {
+    node: Handle<Node>,
+    initial_local_scale: Vector3<f32>,
+    global_rotation: UnitQuaternion<f32>,
+}
+
 pub struct ScaleInteractionMode {
-    initial_scales: Vec<Vector3<f32>>,
+    entries: Vec<Entry>,
+    // Rotation of the axes of the transform space at drag start.
+    axes_rotation: UnitQuaternion<f32>,
+    // Relative scale accumulated since drag start along the axes of the transform space.
+    scale_delta: Vector3<f32>,
     scale_gizmo: ScaleGizmo,
     interacting: bool,
     message_sender: Sender<Message>,
@@ -35,7 +51,9 @@ impl ScaleInteractionMode {
         message_sender: Sender<Message>,
     ) -> Self {
         Self {
-            initial_scales: Default::default(),
+            entries: Default::default(),
+            axes_rotation: Default::default(),
+            scale_delta: Default::default(),
             scale_gizmo: ScaleGizmo::new(editor_scene, engine),
             interacting: false,
             message_sender,
@@ -73,7 +91,25 @@ impl InteractionMode for ScaleInteractionMode {
                 {
                     let graph = &mut engine.scenes[editor_scene.scene].graph;
                     self.interacting = true;
-                    self.initial_scales = selection.local_scales(graph);
+                    self.entries = selection
+                        .nodes()
+                        .iter()
+                        .map(|&node| {
+                            let local_transform = graph[node].local_transform();
+                            Entry {
+                                node,
+                                initial_local_scale: **local_transform.scale(),
+                                global_rotation: parent_global_rotation(graph, node)
+                                    * **local_transform.rotation(),
+                            }
+                        })
+                        .collect();
+                    self.axes_rotation = settings
+                        .transform_space
+                        .gizmo_rotation_position(selection, graph)
+                        .map(|(rotation, _)| rotation)
+                        .unwrap_or_default();
+                    self.scale_delta = Vector3::default();
                 }
             }
         }
@@ -87,32 +123,36 @@ impl InteractionMode for ScaleInteractionMode {
         frame_size: Vector2<f32>,
         settings: &Settings,
     ) {
-        let graph = &mut engine.scenes[editor_scene.scene].graph;
+        let scene = &mut engine.scenes[editor_scene.scene];
+        let graph = &mut scene.graph;
 
         if self.interacting {
-            if let Selection::Graph(selection) = &editor_scene.selection {
-                if !selection.is_empty() {
-                    self.interacting = false;
-                    let current_scales = selection.local_scales(graph);
-                    if current_scales != self.initial_scales {
-                        // Commit changes.
-                        let commands = CommandGroup::from(
-                            selection
-                                .nodes()
-                                .iter()
-                                .zip(self.initial_scales.iter().zip(current_scales.iter()))
-                                .map(|(&node, (&old_scale, &new_scale))| {
-                                    SceneCommand::new(ScaleNodeCommand::new(
-                                        node, old_scale, new_scale,
-                                    ))
-                                })
-                                .collect::<Vec<_>>(),
-                        );
-                        self.message_sender
-                            .send(Message::do_scene_command(commands))
-                            .unwrap();
+            self.interacting = false;
+            let entries = std::mem::take(&mut self.entries);
+            let changed = entries.iter().any(|entry| {
+                **graph[entry.node].local_transform().scale() != entry.initial_local_scale
+            });
+            if changed {
+                let entries = entries
+                    .iter()
+                    .map(|entry| {
+                        (
+                            entry.node,
+                            entry.initial_local_scale,
+                            **graph[entry.node].local_transform().scale(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                let command = match entries.as_slice() {
+                    [(node, old_scale, new_scale)] => {
+                        SceneCommand::new(ScaleNodeCommand::new(*node, *old_scale, *new_scale))
                     }
-                }
+                    _ => SceneCommand::new(ScaleNodesCommand::new(entries)),
+                };
+                // Commit changes.
+                self.message_sender
+                    .send(Message::DoSceneCommand(command))
+                    .unwrap();
             }
         } else {
             let new_selection = editor_scene
@@ -123,7 +163,8 @@ impl InteractionMode for ScaleInteractionMode {
                     editor_objects_root: editor_scene.editor_objects_root,
                     screen_size: frame_size,
                     editor_only: false,
-                    filter: |_, _| true,
+                    // Members of locked collections cannot be picked.
+                    filter: |handle, _| !scene.node_collections.is_locked(handle),
                     ignore_back_faces: settings.selection.ignore_back_faces,
                 })
                 .map(|result| {
@@ -161,25 +202,28 @@ impl InteractionMode for ScaleInteractionMode {
         frame_size: Vector2<f32>,
         _settings: &Settings,
     ) {
-        if let Selection::Graph(selection) = &editor_scene.selection {
-            if self.interacting {
-                let scale_delta = self.scale_gizmo.calculate_scale_delta(
-                    editor_scene,
-                    camera,
-                    mouse_offset,
-                    mouse_position,
-                    engine,
-                    frame_size,
+        if self.interacting {
+            // The delta is measured along the axes of the gizmo, which are the axes of the
+            // transform space.
+            self.scale_delta += self.scale_gizmo.calculate_scale_delta(
+                editor_scene,
+                camera,
+                mouse_offset,
+                mouse_position,
+                engine,
+                frame_size,
+            );
+
+            for entry in self.entries.iter() {
+                let scale = scale_local_scale(
+                    entry.initial_local_scale,
+                    entry.global_rotation,
+                    self.axes_rotation,
+                    self.scale_delta,
                 );
-                for &node in selection.nodes().iter() {
-                    let transform =
-                        engine.scenes[editor_scene.scene].graph[node].local_transform_mut();
-                    let initial_scale = transform.scale();
-                    let sx = (initial_scale.x * (1.0 + scale_delta.x)).max(std::f32::EPSILON);
-                    let sy = (initial_scale.y * (1.0 + scale_delta.y)).max(std::f32::EPSILON);
-                    let sz = (initial_scale.z * (1.0 + scale_delta.z)).max(std::f32::EPSILON);
-                    transform.set_scale(Vector3::new(sx, sy, sz));
-                }
+                engine.scenes[editor_scene.scene].graph[entry.node]
+                    .local_transform_mut()
+                    .set_scale(scale);
             }
         }
     }
@@ -189,13 +233,15 @@ impl InteractionMode for ScaleInteractionMode {
         editor_scene: &mut EditorScene,
         camera: Handle<Node>,
         engine: &mut GameEngine,
+        settings: &Settings,
     ) {
         if let Selection::Graph(selection) = &editor_scene.selection {
             let graph = &mut engine.scenes[editor_scene.scene].graph;
             if !editor_scene.selection.is_empty() {
                 let scale =
                     calculate_gizmo_distance_scaling(graph, camera, self.scale_gizmo.origin);
-                self.scale_gizmo.sync_transform(graph, selection, scale);
+                self.scale_gizmo
+                    .sync_transform(graph, selection, scale, settings.transform_space);
                 self.scale_gizmo.set_visible(graph, true);
             } else {
                 self.scale_gizmo.set_visible(graph, false);
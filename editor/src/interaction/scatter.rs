@@ -0,0 +1,914 @@
+//! Scatter brush - paints instances of prefabs on surfaces of a scene, for example to populate a
+//! level with grass tufts and rocks.
+//!
+//! Instances are placed on a world-space grid in the XZ plane, each cell of the grid could hold
+//! only one instance, so the density of instances stays the same no matter how many strokes were
+//! made over the same area. Placed instances are tagged with [`SCATTER_TAG`], which allows the
+//! eraser to remove only instances that were created by the brush. Each stroke (or erasure) is a
+//! single command.
+
+use crate::{
+    asset::item::{AssetItem, AssetKind},
+    camera::precise_ray_test,
+    interaction::{terrain::BrushGizmo, InteractionMode},
+    scene::{commands::scatter::ScatterStrokeCommand, EditorScene, Selection},
+    send_sync_message,
+    settings::Settings,
+    GameEngine, Message, MSG_SYNC_FLAG,
+};
+use fyrox::{
+    core::{
+        algebra::{Point3, UnitQuaternion, Vector2, Vector3},
+        arrayvec::ArrayVec,
+        futures::executor::block_on,
+        inspect::{Inspect, PropertyInfo},
+        make_relative_path,
+        math::{ray::Ray, vector_to_quat},
+        pool::Handle,
+        rand::{seq::SliceRandom, thread_rng, Rng},
+        scope_profile,
+    },
+    fxhash::FxHashSet,
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        grid::{Column, GridBuilder, Row},
+        inspector::{
+            editors::PropertyEditorDefinitionContainer, FieldKind, Inspector, InspectorBuilder,
+            InspectorContext, InspectorMessage, PropertyChanged,
+        },
+        list_view::{ListViewBuilder, ListViewMessage},
+        message::{MessageDirection, UiMessage},
+        numeric::{NumericUpDownBuilder, NumericUpDownMessage},
+        scroll_viewer::ScrollViewerBuilder,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    resource::model::Model,
+    scene::{
+        camera::Camera,
+        graph::Graph,
+        node::Node,
+        terrain::{Terrain, TerrainRayCastResult},
+    },
+    utils::log::Log,
+};
+use std::{collections::VecDeque, rc::Rc, sync::mpsc::Sender};
+
+/// Tag of instances that were placed by the scatter brush.
+pub const SCATTER_TAG: &str = "Scatter";
+
+/// Settings of the scatter brush.
+#[derive(Clone, Debug, Inspect)]
+pub struct ScatterBrush {
+    /// Radius of the brush in meters.
+    #[inspect(min_value = 0.1, max_value = 50.0, step = 0.1)]
+    pub radius: f32,
+    /// Amount of instances per square meter.
+    #[inspect(min_value = 0.01, max_value = 50.0, step = 0.1)]
+    pub density: f32,
+    /// Random offset of instances from centers of grid cells, 0.0 - instances are placed on a
+    /// regular grid, 1.0 - instances are placed anywhere in their cells.
+    #[inspect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub jitter: f32,
+    /// Minimum random rotation around vertical axis in degrees.
+    #[inspect(min_value = -360.0, max_value = 360.0, step = 1.0)]
+    pub min_yaw: f32,
+    /// Maximum random rotation around vertical axis in degrees.
+    #[inspect(min_value = -360.0, max_value = 360.0, step = 1.0)]
+    pub max_yaw: f32,
+    /// Minimum random uniform scale of instances.
+    #[inspect(min_value = 0.01, step = 0.05)]
+    pub min_scale: f32,
+    /// Maximum random uniform scale of instances.
+    #[inspect(min_value = 0.01, step = 0.05)]
+    pub max_scale: f32,
+    /// Rotate instances so their up axis matches the normal of a surface.
+    pub align_to_normal: bool,
+    /// Remove scattered instances instead of placing new ones. Holding Shift inverts the mode.
+    pub erase: bool,
+    /// Maximum amount of instances created per frame, placement of large amount of instances is
+    /// spread across multiple frames to keep the editor responsive.
+    #[inspect(min_value = 1.0, step = 1.0)]
+    pub instances_per_frame: usize,
+}
+
+impl Default for ScatterBrush {
+    fn default() -> Self {
+        Self {
+            radius: 2.0,
+            density: 1.0,
+            jitter: 0.75,
+            min_yaw: 0.0,
+            max_yaw: 360.0,
+            min_scale: 0.8,
+            max_scale: 1.2,
+            align_to_normal: false,
+            erase: false,
+            instances_per_frame: 200,
+        }
+    }
+}
+
+impl ScatterBrush {
+    fn handle_property_changed(&mut self, property_changed: &PropertyChanged) -> bool {
+        if let FieldKind::Object(ref args) = property_changed.value {
+            return match property_changed.name.as_ref() {
+                Self::RADIUS => args.try_override(&mut self.radius),
+                Self::DENSITY => args.try_override(&mut self.density),
+                Self::JITTER => args.try_override(&mut self.jitter),
+                Self::MIN_YAW => args.try_override(&mut self.min_yaw),
+                Self::MAX_YAW => args.try_override(&mut self.max_yaw),
+                Self::MIN_SCALE => args.try_override(&mut self.min_scale),
+                Self::MAX_SCALE => args.try_override(&mut self.max_scale),
+                Self::ALIGN_TO_NORMAL => args.try_override(&mut self.align_to_normal),
+                Self::ERASE => args.try_override(&mut self.erase),
+                Self::INSTANCES_PER_FRAME => args.try_override(&mut self.instances_per_frame),
+                _ => false,
+            };
+        }
+        false
+    }
+
+    /// Size of a cell of the placement grid, each cell holds at most one instance.
+    fn cell_size(&self) -> f32 {
+        1.0 / self.density.max(f32::EPSILON).sqrt()
+    }
+}
+
+/// A prefab that is placed by the brush.
+struct ScatterSource {
+    model: Model,
+    /// Relative probability of the prefab to be picked.
+    weight: f32,
+}
+
+/// Picks random source using weights of the sources.
+fn pick_source<'a, R: Rng>(sources: &'a [ScatterSource], rng: &mut R) -> Option<&'a ScatterSource> {
+    sources
+        .choose_weighted(rng, |source| source.weight.max(0.0))
+        .ok()
+}
+
+fn random_in_range<R: Rng>(rng: &mut R, a: f32, b: f32) -> f32 {
+    rng.gen_range(a.min(b)..=a.max(b))
+}
+
+fn cell_of(position: Vector3<f32>, cell_size: f32) -> (i32, i32) {
+    (
+        (position.x / cell_size).floor() as i32,
+        (position.z / cell_size).floor() as i32,
+    )
+}
+
+/// Returns meshes and terrains that could be painted on - everything except editor objects and
+/// scattered instances.
+fn collect_surfaces(graph: &Graph, editor_objects_root: Handle<Node>) -> Vec<Handle<Node>> {
+    let mut surfaces = Vec::new();
+    let mut stack = vec![graph.get_root()];
+    while let Some(handle) = stack.pop() {
+        let node = &graph[handle];
+        if handle == editor_objects_root || node.tag() == SCATTER_TAG {
+            continue;
+        }
+        stack.extend_from_slice(node.children());
+        if node.global_visibility() && (node.is_mesh() || node.is_terrain()) {
+            surfaces.push(handle);
+        }
+    }
+    surfaces
+}
+
+/// Casts the ray against the given surfaces and returns world-space position and normal of the
+/// closest intersection.
+fn cast_ray(
+    graph: &Graph,
+    surfaces: &[Handle<Node>],
+    ray: &Ray,
+) -> Option<(Vector3<f32>, Vector3<f32>)> {
+    // Squared distance, position, normal.
+    let mut closest: Option<(f32, Vector3<f32>, Vector3<f32>)> = None;
+
+    for node in surfaces.iter().filter_map(|&handle| graph.try_get(handle)) {
+        let hit = if let Some(terrain) = node.cast::<Terrain>() {
+            let mut intersections = ArrayVec::<TerrainRayCastResult, 128>::new();
+            terrain.raycast(*ray, &mut intersections, true);
+            // Results of terrain ray cast are in local coordinates of the terrain.
+            intersections.first().map(|intersection| {
+                let transform = terrain.global_transform();
+                let position = transform
+                    .transform_point(&Point3::from(intersection.position))
+                    .coords;
+                let normal = transform
+                    .transform_vector(&intersection.normal)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(Vector3::y);
+                ((position - ray.origin).norm_squared(), position, normal)
+            })
+        } else if ray
+            .aabb_intersection_points(&node.world_bounding_box())
+            .is_some()
+        {
+            precise_ray_test(node, ray, false)
+        } else {
+            None
+        };
+
+        if let Some(hit) = hit {
+            if closest.map_or(true, |closest| hit.0 < closest.0) {
+                closest = Some(hit);
+            }
+        }
+    }
+
+    closest.map(|(_, position, normal)| (position, normal))
+}
+
+/// A point where an instance should be placed, the actual position is found by a ray cast, which
+/// is deferred until the instance is created.
+struct PendingSample {
+    cell: (i32, i32),
+    ray: Ray,
+}
+
+struct Stroke {
+    erase: bool,
+    // Mouse button was released, the stroke will be finished once every pending sample is
+    // processed.
+    released: bool,
+    container: Handle<Node>,
+    surfaces: Vec<Handle<Node>>,
+    cell_size: f32,
+    occupied: FxHashSet<(i32, i32)>,
+    pending: VecDeque<PendingSample>,
+    placed: Vec<Handle<Node>>,
+    // Erased instances are hidden until the end of the stroke.
+    erased: Vec<Handle<Node>>,
+}
+
+impl Stroke {
+    fn new(
+        erase: bool,
+        container: Handle<Node>,
+        brush: &ScatterBrush,
+        graph: &Graph,
+        editor_objects_root: Handle<Node>,
+    ) -> Self {
+        let cell_size = brush.cell_size();
+
+        // Cells occupied by previously placed instances, this prevents infinite stacking of
+        // instances by repeated strokes.
+        let occupied = graph
+            .linear_iter()
+            .filter(|node| node.tag() == SCATTER_TAG)
+            .map(|node| cell_of(node.global_position(), cell_size))
+            .collect();
+
+        Self {
+            erase,
+            released: false,
+            container,
+            surfaces: collect_surfaces(graph, editor_objects_root),
+            cell_size,
+            occupied,
+            pending: Default::default(),
+            placed: Default::default(),
+            erased: Default::default(),
+        }
+    }
+
+    /// Applies the brush at the given position.
+    fn dab(&mut self, center: Vector3<f32>, brush: &ScatterBrush, graph: &mut Graph) {
+        let radius = brush.radius;
+
+        if self.erase {
+            let erased = graph
+                .pair_iter()
+                .filter(|(_, node)| {
+                    node.tag() == SCATTER_TAG
+                        && node.visibility()
+                        && node.global_position().metric_distance(&center) <= radius
+                })
+                .map(|(handle, _)| handle)
+                .collect::<Vec<_>>();
+            for handle in erased {
+                graph[handle].set_visibility(false);
+                self.erased.push(handle);
+            }
+        } else {
+            let mut rng = thread_rng();
+            let s = self.cell_size;
+            let (min_x, min_z) = cell_of(center - Vector3::new(radius, 0.0, radius), s);
+            let (max_x, max_z) = cell_of(center + Vector3::new(radius, 0.0, radius), s);
+            for z in min_z..=max_z {
+                for x in min_x..=max_x {
+                    let cell = (x, z);
+                    let cell_center = Vector2::new((x as f32 + 0.5) * s, (z as f32 + 0.5) * s);
+                    if (cell_center - center.xz()).norm() > radius || !self.occupied.insert(cell) {
+                        continue;
+                    }
+
+                    let offset = Vector2::new(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5))
+                        .scale(brush.jitter * s);
+                    let position = cell_center + offset;
+
+                    // Cast rays down from above of the brush, it covers slopes up to ~60 degrees.
+                    self.pending.push_back(PendingSample {
+                        cell,
+                        ray: Ray::new(
+                            Vector3::new(position.x, center.y + 2.0 * radius, position.y),
+                            Vector3::new(0.0, -4.0 * radius, 0.0),
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Creates instances for pending samples, no more than `instances_per_frame` at once.
+    fn place_pending(
+        &mut self,
+        brush: &ScatterBrush,
+        sources: &[ScatterSource],
+        engine: &mut GameEngine,
+        editor_scene: &EditorScene,
+    ) {
+        scope_profile!();
+
+        let mut rng = thread_rng();
+        let scene = &mut engine.scenes[editor_scene.scene];
+        let container = if scene.graph.is_valid_handle(self.container) {
+            self.container
+        } else {
+            scene.graph.get_root()
+        };
+
+        for _ in 0..brush.instances_per_frame {
+            let sample = match self.pending.pop_front() {
+                Some(sample) => sample,
+                None => break,
+            };
+
+            let source = match pick_source(sources, &mut rng) {
+                Some(source) => source,
+                None => break,
+            };
+
+            let (position, normal) = match cast_ray(&scene.graph, &self.surfaces, &sample.ray) {
+                Some(hit) => hit,
+                None => {
+                    // Nothing to place on, let next strokes try again.
+                    self.occupied.remove(&sample.cell);
+                    continue;
+                }
+            };
+
+            let yaw = UnitQuaternion::from_axis_angle(
+                &Vector3::y_axis(),
+                random_in_range(&mut rng, brush.min_yaw, brush.max_yaw).to_radians(),
+            );
+            let rotation = if brush.align_to_normal {
+                UnitQuaternion::rotation_between(&Vector3::y(), &normal).unwrap_or_else(|| {
+                    // Normal is opposite to the up vector.
+                    vector_to_quat(normal)
+                }) * yaw
+            } else {
+                yaw
+            };
+            let scale = random_in_range(&mut rng, brush.min_scale, brush.max_scale);
+
+            let instance = source.model.instantiate_geometry(scene);
+            let node = &mut scene.graph[instance];
+            node.set_tag(SCATTER_TAG.to_owned());
+            node.local_transform_mut()
+                .set_position(position)
+                .set_rotation(rotation)
+                .set_scale(Vector3::new(scale, scale, scale));
+            scene.graph.link_nodes(instance, container);
+
+            self.placed.push(instance);
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.released && self.pending.is_empty()
+    }
+
+    /// Undoes every change of the stroke and makes a command that could redo them.
+    fn into_command(self, graph: &mut Graph) -> Option<ScatterStrokeCommand> {
+        if self.placed.is_empty() && self.erased.is_empty() {
+            return None;
+        }
+
+        // Changes are re-applied by the command, this way the scene is modified by commands only.
+        let added = self
+            .placed
+            .iter()
+            .map(|&instance| graph.take_reserve_sub_graph(instance))
+            .collect();
+        for &instance in self.erased.iter() {
+            graph[instance].set_visibility(true);
+        }
+
+        Some(ScatterStrokeCommand::new(
+            if graph.is_valid_handle(self.container) {
+                self.container
+            } else {
+                graph.get_root()
+            },
+            added,
+            self.erased,
+        ))
+    }
+}
+
+pub struct ScatterInteractionMode {
+    message_sender: Sender<Message>,
+    brush: ScatterBrush,
+    sources: Vec<ScatterSource>,
+    container: Handle<Node>,
+    brush_gizmo: BrushGizmo,
+    panel: ScatterPanel,
+    // World-space position of the brush on a surface under the cursor.
+    brush_position: Option<Vector3<f32>>,
+    stroke: Option<Stroke>,
+}
+
+impl ScatterInteractionMode {
+    pub fn new(
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        message_sender: Sender<Message>,
+    ) -> Self {
+        let brush = ScatterBrush::default();
+        let panel = ScatterPanel::new(&mut engine.user_interface.build_ctx(), &brush);
+
+        Self {
+            message_sender,
+            brush,
+            sources: Default::default(),
+            container: Handle::NONE,
+            brush_gizmo: BrushGizmo::new(editor_scene, engine),
+            panel,
+            brush_position: None,
+            stroke: None,
+        }
+    }
+
+    fn finish_stroke(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        if let Some(stroke) = self.stroke.take() {
+            if let Some(command) = stroke.into_command(&mut engine.scenes[editor_scene.scene].graph)
+            {
+                self.message_sender
+                    .send(Message::do_scene_command(command))
+                    .unwrap();
+            }
+        }
+    }
+
+    fn container_name(&self, editor_scene: &EditorScene, engine: &GameEngine) -> String {
+        engine.scenes[editor_scene.scene]
+            .graph
+            .try_get(self.container)
+            .map_or_else(|| "Scene Root".to_owned(), |node| node.name_owned())
+    }
+}
+
+impl InteractionMode for ScatterInteractionMode {
+    fn on_left_mouse_button_down(
+        &mut self,
+        editor_scene: &mut EditorScene,
+        engine: &mut GameEngine,
+        _mouse_pos: Vector2<f32>,
+        _frame_size: Vector2<f32>,
+        _settings: &Settings,
+    ) {
+        // Previous stroke could still be placing its instances.
+        if self.stroke.is_some() {
+            return;
+        }
+
+        let erase = self.brush.erase ^ engine.user_interface.keyboard_modifiers().shift;
+
+        if !erase && self.sources.iter().all(|source| source.weight <= 0.0) {
+            Log::warn(
+                "Scatter brush has nothing to place, drop some models on its source list first!"
+                    .to_owned(),
+            );
+            return;
+        }
+
+        let graph = &mut engine.scenes[editor_scene.scene].graph;
+        let mut stroke = Stroke::new(
+            erase,
+            self.container,
+            &self.brush,
+            graph,
+            editor_scene.editor_objects_root,
+        );
+        if let Some(position) = self.brush_position {
+            stroke.dab(position, &self.brush, graph);
+        }
+        self.stroke = Some(stroke);
+    }
+
+    fn on_left_mouse_button_up(
+        &mut self,
+        _editor_scene: &mut EditorScene,
+        _engine: &mut GameEngine,
+        _mouse_pos: Vector2<f32>,
+        _frame_size: Vector2<f32>,
+        _settings: &Settings,
+    ) {
+        if let Some(stroke) = self.stroke.as_mut() {
+            stroke.released = true;
+        }
+    }
+
+    fn on_mouse_move(
+        &mut self,
+        _mouse_offset: Vector2<f32>,
+        mouse_position: Vector2<f32>,
+        camera: Handle<Node>,
+        editor_scene: &mut EditorScene,
+        engine: &mut GameEngine,
+        frame_size: Vector2<f32>,
+        _settings: &Settings,
+    ) {
+        let graph = &mut engine.scenes[editor_scene.scene].graph;
+
+        let ray = match graph[camera].cast::<Camera>() {
+            Some(camera) => camera.make_ray(mouse_position, frame_size),
+            None => return,
+        };
+
+        let hit = match self.stroke {
+            Some(ref stroke) => cast_ray(graph, &stroke.surfaces, &ray),
+            None => cast_ray(
+                graph,
+                &collect_surfaces(graph, editor_scene.editor_objects_root),
+                &ray,
+            ),
+        };
+
+        self.brush_position = hit.map(|(position, _)| position);
+
+        if let Some((position, normal)) = hit {
+            let diameter = 2.0 * self.brush.radius;
+            graph[self.brush_gizmo.brush]
+                .local_transform_mut()
+                .set_position(position)
+                .set_scale(Vector3::new(diameter, diameter, 1.0))
+                .set_rotation(vector_to_quat(normal));
+
+            if let Some(stroke) = self.stroke.as_mut() {
+                if !stroke.released {
+                    stroke.dab(position, &self.brush, graph);
+                }
+            }
+        }
+    }
+
+    fn update(
+        &mut self,
+        editor_scene: &mut EditorScene,
+        _camera: Handle<Node>,
+        engine: &mut GameEngine,
+        _settings: &Settings,
+    ) {
+        if let Some(stroke) = self.stroke.as_mut() {
+            stroke.place_pending(&self.brush, &self.sources, engine, editor_scene);
+
+            if stroke.is_finished() {
+                self.finish_stroke(editor_scene, engine);
+            }
+        }
+    }
+
+    fn activate(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        self.brush_gizmo
+            .set_visible(&mut engine.scenes[editor_scene.scene].graph, true);
+
+        let container_name = self.container_name(editor_scene, engine);
+        self.panel.sync_to_model(
+            &mut engine.user_interface,
+            &self.brush,
+            &self.sources,
+            container_name,
+        );
+
+        engine.user_interface.send_message(WindowMessage::open(
+            self.panel.window,
+            MessageDirection::ToWidget,
+            false,
+        ));
+    }
+
+    fn deactivate(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        // Instances that were not placed yet are discarded.
+        self.finish_stroke(editor_scene, engine);
+
+        self.brush_gizmo
+            .set_visible(&mut engine.scenes[editor_scene.scene].graph, false);
+
+        engine.user_interface.send_message(WindowMessage::close(
+            self.panel.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        editor_scene: &mut EditorScene,
+        engine: &mut GameEngine,
+    ) {
+        let panel = &self.panel;
+
+        if let Some(InspectorMessage::PropertyChanged(property_changed)) =
+            message.data::<InspectorMessage>()
+        {
+            if message.destination() == panel.inspector
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.brush.handle_property_changed(property_changed);
+            }
+        } else if let Some(&WidgetMessage::Drop(dropped)) = message.data::<WidgetMessage>() {
+            if message.destination() == panel.sources {
+                let path = engine
+                    .user_interface
+                    .node(dropped)
+                    .cast::<AssetItem>()
+                    .filter(|item| item.kind == AssetKind::Model)
+                    .map(|item| make_relative_path(&item.path));
+                if let Some(path) = path {
+                    match block_on(engine.resource_manager.request_model(&path)) {
+                        Ok(model) => {
+                            self.sources.push(ScatterSource { model, weight: 1.0 });
+                            self.panel
+                                .sync_sources(&mut engine.user_interface, &self.sources);
+                        }
+                        Err(e) => Log::err(format!(
+                            "Unable to add scatter source {}. Reason: {:?}",
+                            path.display(),
+                            e
+                        )),
+                    }
+                }
+            }
+        } else if let Some(&NumericUpDownMessage::Value(value)) =
+            message.data::<NumericUpDownMessage<f32>>()
+        {
+            if message.direction() == MessageDirection::FromWidget {
+                if let Some(index) = panel
+                    .items
+                    .iter()
+                    .position(|item| item.weight == message.destination())
+                {
+                    self.sources[index].weight = value;
+                }
+            }
+        } else if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == panel.use_selection {
+                if let Selection::Graph(selection) = &editor_scene.selection {
+                    if selection.is_single_selection() {
+                        self.container = selection.nodes()[0];
+                    }
+                }
+                let container_name = self.container_name(editor_scene, engine);
+                self.panel
+                    .sync_container(&engine.user_interface, container_name);
+            } else if let Some(index) = panel
+                .items
+                .iter()
+                .position(|item| item.remove == message.destination())
+            {
+                self.sources.remove(index);
+                self.panel
+                    .sync_sources(&mut engine.user_interface, &self.sources);
+            }
+        }
+    }
+
+    fn on_drop(&mut self, engine: &mut GameEngine) {
+        engine.user_interface.send_message(WidgetMessage::remove(
+            self.panel.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+}
+
+struct SourceItem {
+    weight: Handle<UiNode>,
+    remove: Handle<UiNode>,
+}
+
+struct ScatterPanel {
+    window: Handle<UiNode>,
+    inspector: Handle<UiNode>,
+    sources: Handle<UiNode>,
+    items: Vec<SourceItem>,
+    container: Handle<UiNode>,
+    use_selection: Handle<UiNode>,
+}
+
+impl ScatterPanel {
+    fn new(ctx: &mut BuildContext, brush: &ScatterBrush) -> Self {
+        let context = InspectorContext::from_object(
+            brush,
+            ctx,
+            Rc::new(PropertyEditorDefinitionContainer::new()),
+            None,
+            MSG_SYNC_FLAG,
+            0,
+        );
+
+        let inspector;
+        let sources;
+        let container;
+        let use_selection;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(500.0))
+            .can_close(false)
+            .with_content(
+                ScrollViewerBuilder::new(WidgetBuilder::new())
+                    .with_content(
+                        StackPanelBuilder::new(
+                            WidgetBuilder::new()
+                                .with_child({
+                                    inspector = InspectorBuilder::new(WidgetBuilder::new())
+                                        .with_context(context)
+                                        .build(ctx);
+                                    inspector
+                                })
+                                .with_child(
+                                    TextBuilder::new(
+                                        WidgetBuilder::new().with_margin(Thickness::uniform(2.0)),
+                                    )
+                                    .with_text("Sources (drop models here)")
+                                    .build(ctx),
+                                )
+                                .with_child({
+                                    sources = ListViewBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_min_size(Vector2::new(0.0, 60.0))
+                                            .with_allow_drop(true),
+                                    )
+                                    .build(ctx);
+                                    sources
+                                })
+                                .with_child(
+                                    GridBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_child({
+                                                container = TextBuilder::new(
+                                                    WidgetBuilder::new()
+                                                        .on_column(0)
+                                                        .with_margin(Thickness::uniform(2.0)),
+                                                )
+                                                .with_vertical_text_alignment(
+                                                    VerticalAlignment::Center,
+                                                )
+                                                .build(ctx);
+                                                container
+                                            })
+                                            .with_child({
+                                                use_selection = ButtonBuilder::new(
+                                                    WidgetBuilder::new()
+                                                        .on_column(1)
+                                                        .with_margin(Thickness::uniform(1.0)),
+                                                )
+                                                .with_text("Use Selected")
+                                                .build(ctx);
+                                                use_selection
+                                            }),
+                                    )
+                                    .add_row(Row::strict(24.0))
+                                    .add_column(Column::stretch())
+                                    .add_column(Column::strict(100.0))
+                                    .build(ctx),
+                                ),
+                        )
+                        .build(ctx),
+                    )
+                    .build(ctx),
+            )
+            .open(false)
+            .with_title(WindowTitle::text("Scatter Brush"))
+            .build(ctx);
+
+        Self {
+            window,
+            inspector,
+            sources,
+            items: Default::default(),
+            container,
+            use_selection,
+        }
+    }
+
+    fn sync_to_model(
+        &mut self,
+        ui: &mut UserInterface,
+        brush: &ScatterBrush,
+        sources: &[ScatterSource],
+        container_name: String,
+    ) {
+        let ctx = ui
+            .node(self.inspector)
+            .cast::<Inspector>()
+            .expect("Must be Inspector!")
+            .context()
+            .clone();
+
+        if let Err(e) = ctx.sync(brush, ui, 0) {
+            Log::err(format!(
+                "Failed to sync ScatterPanel's inspector. Reason: {:?}",
+                e
+            ))
+        }
+
+        self.sync_sources(ui, sources);
+        self.sync_container(ui, container_name);
+    }
+
+    fn sync_sources(&mut self, ui: &mut UserInterface, sources: &[ScatterSource]) {
+        let ctx = &mut ui.build_ctx();
+
+        self.items.clear();
+        let mut views = Vec::new();
+        for source in sources {
+            let weight;
+            let remove;
+            views.push(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(
+                            TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_column(0)
+                                    .with_margin(Thickness::left(2.0)),
+                            )
+                            .with_text(
+                                source
+                                    .model
+                                    .state()
+                                    .path()
+                                    .file_name()
+                                    .map(|name| name.to_string_lossy().to_string())
+                                    .unwrap_or_default(),
+                            )
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx),
+                        )
+                        .with_child({
+                            weight = NumericUpDownBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_min_value(0.0f32)
+                            .with_step(0.1)
+                            .with_precision(2)
+                            .with_value(source.weight)
+                            .build(ctx);
+                            weight
+                        })
+                        .with_child({
+                            remove = ButtonBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_column(2)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_text("X")
+                            .build(ctx);
+                            remove
+                        }),
+                )
+                .add_row(Row::strict(22.0))
+                .add_column(Column::stretch())
+                .add_column(Column::strict(60.0))
+                .add_column(Column::strict(22.0))
+                .build(ctx),
+            );
+            self.items.push(SourceItem { weight, remove });
+        }
+
+        send_sync_message(
+            ui,
+            ListViewMessage::items(self.sources, MessageDirection::ToWidget, views),
+        );
+    }
+
+    fn sync_container(&self, ui: &UserInterface, container_name: String) {
+        send_sync_message(
+            ui,
+            TextMessage::text(
+                self.container,
+                MessageDirection::ToWidget,
+                format!("Container: {}", container_name),
+            ),
+        );
+    }
+}
@@ -39,12 +39,19 @@ impl SelectInteractionMode {
 impl InteractionMode for SelectInteractionMode {
     fn on_left_mouse_button_down(
         &mut self,
-        _editor_scene: &mut EditorScene,
+        editor_scene: &mut EditorScene,
         engine: &mut GameEngine,
         mouse_pos: Vector2<f32>,
-        _frame_size: Vector2<f32>,
+        frame_size: Vector2<f32>,
         _settings: &Settings,
     ) {
+        // Mouse position is relative to the active viewport pane, but the selection frame is
+        // placed relative to the whole preview.
+        let mouse_pos = mouse_pos
+            + editor_scene
+                .viewports
+                .active_pane_bounds(frame_size)
+                .position;
         self.click_pos = mouse_pos;
         let ui = &mut engine.user_interface;
         ui.send_message(WidgetMessage::visibility(
@@ -97,6 +104,16 @@ impl InteractionMode for SelectInteractionMode {
                 self.stack.extend_from_slice(node.children());
                 continue;
             }
+            // Members of locked collections cannot be selected, but their descendants can.
+            if scene.node_collections.is_locked(handle) {
+                self.stack.extend_from_slice(node.children());
+                continue;
+            }
+            // Nodes hidden by isolation mode cannot be selected, but isolated descendants can.
+            if !scene.graph.passes_visibility_filter(handle) {
+                self.stack.extend_from_slice(node.children());
+                continue;
+            }
 
             for screen_corner in node
                 .local_bounding_box()
@@ -137,11 +154,16 @@ impl InteractionMode for SelectInteractionMode {
         _mouse_offset: Vector2<f32>,
         mouse_position: Vector2<f32>,
         _camera: Handle<Node>,
-        _editor_scene: &mut EditorScene,
+        editor_scene: &mut EditorScene,
         engine: &mut GameEngine,
-        _frame_size: Vector2<f32>,
+        frame_size: Vector2<f32>,
         _settings: &Settings,
     ) {
+        let mouse_position = mouse_position
+            + editor_scene
+                .viewports
+                .active_pane_bounds(frame_size)
+                .position;
         let ui = &mut engine.user_interface;
         let width = mouse_position.x - self.click_pos.x;
         let height = mouse_position.y - self.click_pos.y;
@@ -180,6 +202,7 @@ impl InteractionMode for SelectInteractionMode {
         _editor_scene: &mut EditorScene,
         _camera: Handle<Node>,
         _engine: &mut GameEngine,
+        _settings: &Settings,
     ) {
     }
 
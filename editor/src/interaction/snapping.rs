@@ -0,0 +1,499 @@
+//! Geometric snapping of moved objects to vertices, edges and surfaces of meshes. See
+//! [`GeometrySnapping`] docs for more info.
+
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3},
+        color::Color,
+        math::{aabb::AxisAlignedBoundingBox, ray::Ray, TriangleDefinition},
+        pool::Handle,
+    },
+    fxhash::FxHashMap,
+    scene::{
+        camera::Camera,
+        debug::{Line, SceneDrawingContext},
+        graph::Graph,
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            surface::SurfaceData,
+            Mesh,
+        },
+        node::{Node, NodeTrait},
+    },
+};
+use std::{cmp::Ordering, hash::Hasher, sync::Arc};
+
+/// Max amount of triangles in a leaf of a bounding volume hierarchy.
+const LEAF_SIZE: usize = 8;
+
+/// Kind of a geometric feature of a mesh.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SnapFeature {
+    Vertex,
+    Edge,
+    Surface,
+}
+
+/// A point of mesh geometry found near the cursor. Everything is in world coordinates.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SnapPoint {
+    pub feature: SnapFeature,
+    pub position: Vector3<f32>,
+    /// Ends of the edge for [`SnapFeature::Edge`], for other features both ends are equal to the
+    /// position.
+    pub edge: [Vector3<f32>; 2],
+    /// Normal of the triangle the point belongs to.
+    pub normal: Vector3<f32>,
+    pub node: Handle<Node>,
+    /// Distance between the cursor and the projection of the point, in pixels.
+    pixel_distance: f32,
+    /// Distance between the camera and the point.
+    depth: f32,
+}
+
+impl SnapPoint {
+    // Vertices are preferred over edges, edges over surfaces. Closer to the cursor is better
+    // among features of the same kind.
+    fn is_better_than(&self, other: &SnapPoint) -> bool {
+        match self.feature.cmp(&other.feature) {
+            Ordering::Less => true,
+            Ordering::Greater => false,
+            Ordering::Equal => match self.feature {
+                SnapFeature::Surface => self.depth < other.depth,
+                _ => {
+                    if (self.pixel_distance - other.pixel_distance).abs() < 1.0 {
+                        self.depth < other.depth
+                    } else {
+                        self.pixel_distance < other.pixel_distance
+                    }
+                }
+            },
+        }
+    }
+
+    /// Draws a small indicator of the point, its size is the same on the screen regardless of
+    /// the distance to the point.
+    pub fn draw(&self, ctx: &mut SceneDrawingContext, camera: &Camera, frame_size: Vector2<f32>) {
+        let size = PixelSize::new(camera, frame_size, 6.0).at(self.depth);
+        let color = Color::opaque(255, 200, 0);
+
+        match self.feature {
+            SnapFeature::Vertex => {
+                ctx.draw_aabb(
+                    &AxisAlignedBoundingBox::from_min_max(
+                        self.position - Vector3::repeat(size),
+                        self.position + Vector3::repeat(size),
+                    ),
+                    color,
+                );
+            }
+            SnapFeature::Edge => {
+                ctx.add_line(Line {
+                    begin: self.edge[0],
+                    end: self.edge[1],
+                    color,
+                });
+                ctx.draw_sphere(self.position, 6, 6, size, color);
+            }
+            SnapFeature::Surface => {
+                let rotation = UnitQuaternion::rotation_between(&Vector3::z(), &self.normal)
+                    .unwrap_or_else(|| {
+                        UnitQuaternion::from_axis_angle(&Vector3::x_axis(), std::f32::consts::PI)
+                    });
+                ctx.draw_circle(
+                    Default::default(),
+                    size,
+                    16,
+                    Matrix4::new_translation(&self.position) * rotation.to_homogeneous(),
+                    color,
+                );
+                ctx.add_line(Line {
+                    begin: self.position,
+                    end: self.position + self.normal.scale(size * 2.0),
+                    color,
+                });
+            }
+        }
+    }
+}
+
+/// Draws a marker of a snap anchor - a point of a moved object that is snapped instead of its
+/// pivot.
+pub fn draw_anchor(
+    ctx: &mut SceneDrawingContext,
+    camera: &Camera,
+    frame_size: Vector2<f32>,
+    position: Vector3<f32>,
+) {
+    let depth = camera.global_position().metric_distance(&position);
+    let size = PixelSize::new(camera, frame_size, 5.0).at(depth);
+    ctx.draw_sphere(position, 8, 8, size, Color::opaque(0, 200, 255));
+}
+
+/// Converts a distance in pixels to a distance in world units at the given depth.
+#[derive(Copy, Clone, Debug)]
+struct PixelSize {
+    base: f32,
+    slope: f32,
+}
+
+impl PixelSize {
+    fn new(camera: &Camera, frame_size: Vector2<f32>, pixels: f32) -> Self {
+        let center = frame_size.scale(0.5);
+        let a = camera.make_ray(center, frame_size);
+        let b = camera.make_ray(center + Vector2::new(pixels, 0.0), frame_size);
+        Self {
+            base: a.origin.metric_distance(&b.origin),
+            slope: a
+                .dir
+                .try_normalize(f32::EPSILON)
+                .zip(b.dir.try_normalize(f32::EPSILON))
+                .map_or(0.0, |(a, b)| a.metric_distance(&b)),
+        }
+    }
+
+    fn at(&self, depth: f32) -> f32 {
+        self.base + self.slope * depth
+    }
+}
+
+enum BvhNodeKind {
+    Leaf { first: usize, count: usize },
+    Branch { left: usize, right: usize },
+}
+
+struct BvhNode {
+    bounds: AxisAlignedBoundingBox,
+    kind: BvhNodeKind,
+}
+
+/// Triangles of a mesh in local coordinates of the mesh with a bounding volume hierarchy over
+/// them.
+struct MeshGeometry {
+    hash: u64,
+    triangles: Vec<[Vector3<f32>; 3]>,
+    nodes: Vec<BvhNode>,
+}
+
+fn read_position(data: &SurfaceData, i: u32) -> Option<Vector3<f32>> {
+    data.vertex_buffer
+        .get(i as usize)
+        .and_then(|v| v.read_3_f32(VertexAttributeUsage::Position).ok())
+}
+
+fn read_triangle(data: &SurfaceData, triangle: &TriangleDefinition) -> Option<[Vector3<f32>; 3]> {
+    Some([
+        read_position(data, triangle[0])?,
+        read_position(data, triangle[1])?,
+        read_position(data, triangle[2])?,
+    ])
+}
+
+// Geometry is rebuilt when a surface is replaced or when contents of a surface is changed.
+fn geometry_hash(mesh: &Mesh) -> u64 {
+    let mut hasher = fyrox::fxhash::FxHasher64::default();
+    for surface in mesh.surfaces() {
+        let data = surface.data();
+        hasher.write_usize(Arc::as_ptr(&data) as *const u8 as usize);
+        hasher.write_u64(data.lock().content_hash());
+    }
+    hasher.finish()
+}
+
+fn triangle_bounds(triangle: &[Vector3<f32>; 3]) -> AxisAlignedBoundingBox {
+    AxisAlignedBoundingBox::from_points(triangle)
+}
+
+impl MeshGeometry {
+    fn new(mesh: &Mesh, hash: u64) -> Self {
+        let mut triangles = Vec::new();
+        for surface in mesh.surfaces() {
+            let data = surface.data();
+            let data = data.lock();
+            triangles.extend(
+                data.geometry_buffer
+                    .iter()
+                    .filter_map(|triangle| read_triangle(&data, triangle)),
+            );
+        }
+
+        let mut geometry = Self {
+            hash,
+            triangles,
+            nodes: Default::default(),
+        };
+        if !geometry.triangles.is_empty() {
+            geometry.build(0, geometry.triangles.len());
+        }
+        geometry
+    }
+
+    // Builds a subtree over the given range of triangles and returns index of its root. Triangles
+    // are split in half by the longest axis of bounds of their centers.
+    fn build(&mut self, first: usize, count: usize) -> usize {
+        let triangles = &mut self.triangles[first..first + count];
+
+        let mut bounds = AxisAlignedBoundingBox::default();
+        let mut centers = AxisAlignedBoundingBox::default();
+        for triangle in triangles.iter() {
+            bounds.add_box(triangle_bounds(triangle));
+            centers.add_point((triangle[0] + triangle[1] + triangle[2]).scale(1.0 / 3.0));
+        }
+
+        let index = self.nodes.len();
+        self.nodes.push(BvhNode {
+            bounds,
+            kind: BvhNodeKind::Leaf { first, count },
+        });
+
+        if count > LEAF_SIZE {
+            let extents = centers.max - centers.min;
+            let axis = extents.imax();
+            let center = |t: &[Vector3<f32>; 3]| t[0][axis] + t[1][axis] + t[2][axis];
+            let half = count / 2;
+            triangles.select_nth_unstable_by(half, |a, b| {
+                center(a).partial_cmp(&center(b)).unwrap_or(Ordering::Equal)
+            });
+
+            let left = self.build(first, half);
+            let right = self.build(first + half, count - half);
+            self.nodes[index].kind = BvhNodeKind::Branch { left, right };
+        }
+
+        index
+    }
+
+    // Collects indices of the triangles, which bounds are within the given distance of the ray.
+    fn query(&self, ray: &Ray, distance: f32, stack: &mut Vec<usize>, result: &mut Vec<usize>) {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        stack.clear();
+        stack.push(0);
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let mut bounds = node.bounds;
+            bounds.inflate(Vector3::repeat(distance * 2.0));
+            if ray.aabb_intersection(&bounds).is_none() {
+                continue;
+            }
+
+            match node.kind {
+                BvhNodeKind::Leaf { first, count } => result.extend(first..first + count),
+                BvhNodeKind::Branch { left, right } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+            }
+        }
+    }
+}
+
+// Returns the point of the segment that is closest to the line of the ray.
+fn closest_point_on_segment(a: Vector3<f32>, b: Vector3<f32>, ray: &Ray) -> Vector3<f32> {
+    let u = b - a;
+    let v = ray.dir;
+    let w = a - ray.origin;
+    let uu = u.dot(&u);
+    let uv = u.dot(&v);
+    let vv = v.dot(&v);
+    let denominator = uu * vv - uv * uv;
+    let s = if denominator > f32::EPSILON {
+        ((uv * v.dot(&w) - vv * u.dot(&w)) / denominator).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    a + u.scale(s)
+}
+
+// Returns the smallest scale of the basis of the transform, it is used to convert distances from
+// world to local space conservatively.
+fn min_scale(transform: &Matrix4<f32>) -> f32 {
+    (0..3)
+        .map(|i| transform.column(i).xyz().norm())
+        .fold(f32::MAX, f32::min)
+}
+
+/// Parameters of a search of snap points.
+pub struct SnapQuery<'a> {
+    pub graph: &'a Graph,
+    pub camera: &'a Camera,
+    /// Position of the cursor relative to the viewport of the camera.
+    pub cursor: Vector2<f32>,
+    pub frame_size: Vector2<f32>,
+    /// Max distance between the cursor and a snap point on the screen, in pixels.
+    pub radius: f32,
+    /// Subtrees, which meshes are searched.
+    pub roots: &'a [Handle<Node>],
+    /// Subtrees, which are excluded from the search.
+    pub excluded: &'a [Handle<Node>],
+}
+
+/// Geometric snapping searches vertices, edges and surfaces of visible meshes near the cursor.
+/// Vertices have the highest priority, then edges and then surfaces, features that are hidden
+/// behind a surface are ignored.
+///
+/// # Performance
+///
+/// Only meshes which bounds are close to the cursor ray are searched. Triangles of each searched
+/// mesh are cached in a bounding volume hierarchy, so the search inside a mesh touches only a few
+/// triangles. A cache of a mesh is built on first search and rebuilt when the geometry of the mesh
+/// changes. Skinned meshes are searched in their bind pose.
+#[derive(Default)]
+pub struct GeometrySnapping {
+    cache: FxHashMap<Handle<Node>, MeshGeometry>,
+    node_stack: Vec<Handle<Node>>,
+    bvh_stack: Vec<usize>,
+    triangles: Vec<usize>,
+}
+
+impl GeometrySnapping {
+    /// Removes caches of deleted meshes.
+    pub fn clear_unused(&mut self, graph: &Graph) {
+        self.cache.retain(|handle, _| {
+            graph
+                .try_get(*handle)
+                .map_or(false, |node| node.cast::<Mesh>().is_some())
+        });
+    }
+
+    /// Returns the best snap point near the cursor, if any.
+    pub fn find(&mut self, query: SnapQuery) -> Option<SnapPoint> {
+        let SnapQuery {
+            graph,
+            camera,
+            cursor,
+            frame_size,
+            radius,
+            roots,
+            excluded,
+        } = query;
+
+        let ray = camera.make_ray(cursor, frame_size);
+        let direction = ray.dir.try_normalize(f32::EPSILON)?;
+        let pixel_size = PixelSize::new(camera, frame_size, radius);
+        let viewport = camera.viewport_pixels(frame_size);
+        // Snap points are projected to the whole frame, so the cursor is converted too.
+        let cursor = Vector2::new(
+            viewport.x() as f32 + cursor.x,
+            frame_size.y - (viewport.y() + viewport.h()) as f32 + cursor.y,
+        );
+
+        let mut best: Option<SnapPoint> = None;
+        let mut closest_surface = f32::MAX;
+        let mut candidates = Vec::new();
+
+        self.node_stack.clear();
+        self.node_stack.extend_from_slice(roots);
+        while let Some(handle) = self.node_stack.pop() {
+            if excluded.contains(&handle) {
+                continue;
+            }
+
+            let node = &graph[handle];
+            self.node_stack.extend_from_slice(node.children());
+
+            let mesh = match node.cast::<Mesh>() {
+                Some(mesh) if node.global_visibility() => mesh,
+                _ => continue,
+            };
+
+            // Coarse test with world bounds, the tolerance is taken at the far side of the bounds.
+            let bounds = mesh.world_bounding_box();
+            let far = bounds
+                .corners()
+                .iter()
+                .map(|corner| corner.metric_distance(&ray.origin))
+                .fold(0.0, f32::max);
+            let world_distance = pixel_size.at(far);
+            let mut inflated = bounds;
+            inflated.inflate(Vector3::repeat(world_distance * 2.0));
+            if ray.aabb_intersection(&inflated).is_none() {
+                continue;
+            }
+
+            let hash = geometry_hash(mesh);
+            let geometry = self
+                .cache
+                .entry(handle)
+                .or_insert_with(|| MeshGeometry::new(mesh, hash));
+            if geometry.hash != hash {
+                *geometry = MeshGeometry::new(mesh, hash);
+            }
+
+            let transform = mesh.global_transform();
+            let local_ray = ray.transform(transform.try_inverse().unwrap_or_default());
+            let local_distance = world_distance / min_scale(&transform).max(f32::EPSILON);
+
+            self.triangles.clear();
+            geometry.query(
+                &local_ray,
+                local_distance,
+                &mut self.bvh_stack,
+                &mut self.triangles,
+            );
+
+            for &index in self.triangles.iter() {
+                let [a, b, c] = geometry.triangles[index];
+                let triangle =
+                    [a, b, c].map(|v| transform.transform_point(&Point3::from(v)).coords);
+                let normal = (triangle[1] - triangle[0])
+                    .cross(&(triangle[2] - triangle[0]))
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(Vector3::y);
+
+                let mut add = |feature, position: Vector3<f32>, edge| {
+                    let depth = (position - ray.origin).dot(&direction);
+                    if let Some(projected) = camera.project(position, frame_size) {
+                        let pixel_distance = projected.metric_distance(&cursor);
+                        if feature == SnapFeature::Surface || pixel_distance <= radius {
+                            candidates.push(SnapPoint {
+                                feature,
+                                position,
+                                edge,
+                                normal,
+                                node: handle,
+                                pixel_distance,
+                                depth,
+                            });
+                        }
+                    }
+                };
+
+                if let Some((_, point)) = ray.triangle_intersection(&triangle) {
+                    add(SnapFeature::Surface, point, [point, point]);
+                }
+                for i in 0..3 {
+                    let (begin, end) = (triangle[i], triangle[(i + 1) % 3]);
+                    add(SnapFeature::Vertex, begin, [begin, begin]);
+                    add(
+                        SnapFeature::Edge,
+                        closest_point_on_segment(begin, end, &ray),
+                        [begin, end],
+                    );
+                }
+            }
+        }
+
+        for candidate in candidates.iter() {
+            if candidate.feature == SnapFeature::Surface {
+                closest_surface = closest_surface.min(candidate.depth);
+            }
+        }
+
+        // Features behind the closest surface are hidden.
+        let tolerance = closest_surface * 0.01;
+        for candidate in candidates {
+            if candidate.depth > closest_surface + tolerance {
+                continue;
+            }
+
+            if best.map_or(true, |best| candidate.is_better_than(&best)) {
+                best = Some(candidate);
+            }
+        }
+
+        best
+    }
+}
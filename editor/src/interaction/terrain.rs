@@ -85,7 +85,7 @@ impl TerrainInteractionMode {
 }
 
 pub struct BrushGizmo {
-    brush: Handle<Node>,
+    pub brush: Handle<Node>,
 }
 
 impl BrushGizmo {
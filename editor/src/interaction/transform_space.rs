@@ -0,0 +1,254 @@
+//! Coordinate spaces of transform interaction modes. See [`TransformSpace`] docs for more info.
+
+use crate::world::graph::selection::GraphSelection;
+use fyrox::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        pool::Handle,
+    },
+    scene::{graph::Graph, node::Node},
+};
+use serde::{Deserialize, Serialize};
+
+/// Coordinate space in which move, rotate and scale interaction modes work. Axes of the gizmos are
+/// aligned with the axes of the space and drag deltas are measured along them, then the deltas are
+/// converted to local coordinates of the nodes using transforms captured at drag start.
+///
+/// Commands of the interaction modes always store old and new local values of the nodes, so undo
+/// restores exactly the state the nodes had at drag start, regardless of the space.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TransformSpace {
+    /// Axes of the selected node.
+    Local,
+    /// Axes of the parent of the selected node.
+    Parent,
+    /// Axes of the world.
+    World,
+}
+
+impl Default for TransformSpace {
+    fn default() -> Self {
+        Self::Local
+    }
+}
+
+impl TransformSpace {
+    pub const ALL: [TransformSpace; 3] = [
+        TransformSpace::Local,
+        TransformSpace::Parent,
+        TransformSpace::World,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TransformSpace::Local => "Local",
+            TransformSpace::Parent => "Parent",
+            TransformSpace::World => "World",
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns rotation of the axes of the space in world coordinates for a node with the given
+    /// local rotation and global rotation of its parent.
+    pub fn axes_rotation(
+        self,
+        parent_global_rotation: UnitQuaternion<f32>,
+        local_rotation: UnitQuaternion<f32>,
+    ) -> UnitQuaternion<f32> {
+        match self {
+            TransformSpace::Local => parent_global_rotation * local_rotation,
+            TransformSpace::Parent => parent_global_rotation,
+            TransformSpace::World => UnitQuaternion::identity(),
+        }
+    }
+
+    /// Returns rotation and position of a gizmo for the selection. Axes of the first selected node
+    /// are used when multiple nodes are selected.
+    pub fn gizmo_rotation_position(
+        self,
+        selection: &GraphSelection,
+        graph: &Graph,
+    ) -> Option<(UnitQuaternion<f32>, Vector3<f32>)> {
+        let (_, position) = selection.global_rotation_position(graph)?;
+        let first = *selection.nodes().first()?;
+        let rotation = self.axes_rotation(
+            parent_global_rotation(graph, first),
+            **graph[first].local_transform().rotation(),
+        );
+        Some((rotation, position))
+    }
+}
+
+/// Returns global rotation of the parent of the node, identity for nodes without a parent.
+pub fn parent_global_rotation(graph: &Graph, node: Handle<Node>) -> UnitQuaternion<f32> {
+    let parent = graph[node].parent();
+    if parent.is_some() {
+        graph.global_rotation(parent)
+    } else {
+        UnitQuaternion::identity()
+    }
+}
+
+/// Returns inverse global transform of the parent of the node, identity for nodes without a parent.
+pub fn parent_inv_global_transform(graph: &Graph, node: Handle<Node>) -> Matrix4<f32> {
+    let parent = graph[node].parent();
+    if parent.is_some() {
+        graph[parent]
+            .global_transform()
+            .try_inverse()
+            .unwrap_or_default()
+    } else {
+        Matrix4::identity()
+    }
+}
+
+/// Returns local position that moves a node by the given offset in world coordinates. The offset
+/// is transformed by the inverse global transform of the parent, so the node moves exactly along
+/// the offset even if the parent is rotated or scaled.
+pub fn offset_local_position(
+    initial_local_position: Vector3<f32>,
+    parent_inv_global_transform: &Matrix4<f32>,
+    world_offset: Vector3<f32>,
+) -> Vector3<f32> {
+    initial_local_position + parent_inv_global_transform.transform_vector(&world_offset)
+}
+
+/// Returns local rotation that rotates a node by the given rotation in world coordinates. The
+/// rotation is composed with the global orientation of the node, the result is converted back to
+/// local coordinates of the node.
+pub fn rotate_local_rotation(
+    initial_local_rotation: UnitQuaternion<f32>,
+    parent_global_rotation: UnitQuaternion<f32>,
+    world_rotation: UnitQuaternion<f32>,
+) -> UnitQuaternion<f32> {
+    let global_rotation = parent_global_rotation * initial_local_rotation;
+    parent_global_rotation.inverse() * world_rotation * global_rotation
+}
+
+/// Returns local scale of a node scaled by the given relative delta measured along the axes of a
+/// space. Scale can only be applied along local axes of a node (other axes would require shear), so
+/// the delta is projected on the local axes.
+pub fn scale_local_scale(
+    initial_local_scale: Vector3<f32>,
+    global_rotation: UnitQuaternion<f32>,
+    axes_rotation: UnitQuaternion<f32>,
+    delta: Vector3<f32>,
+) -> Vector3<f32> {
+    let projection = (global_rotation.inverse() * axes_rotation)
+        .to_rotation_matrix()
+        .into_inner()
+        .abs();
+    let local_delta = projection * delta;
+    Vector3::new(
+        (initial_local_scale.x * (1.0 + local_delta.x)).max(f32::EPSILON),
+        (initial_local_scale.y * (1.0 + local_delta.y)).max(f32::EPSILON),
+        (initial_local_scale.z * (1.0 + local_delta.z)).max(f32::EPSILON),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::interaction::transform_space::{
+        offset_local_position, rotate_local_rotation, scale_local_scale, TransformSpace,
+    };
+    use fyrox::core::algebra::{Matrix4, Point3, UnitQuaternion, Vector3};
+    use std::f32::consts::FRAC_PI_2;
+
+    const EPSILON: f32 = 1.0e-5;
+
+    fn parent_rotation() -> UnitQuaternion<f32> {
+        UnitQuaternion::from_axis_angle(&Vector3::y_axis(), FRAC_PI_2)
+    }
+
+    // Parent is rotated 90 degrees around Y axis and scaled 2x.
+    fn parent_global_transform() -> Matrix4<f32> {
+        Matrix4::new_translation(&Vector3::new(3.0, 1.0, -2.0))
+            * parent_rotation().to_homogeneous()
+            * Matrix4::new_scaling(2.0)
+    }
+
+    fn global_position(local_position: Vector3<f32>) -> Vector3<f32> {
+        parent_global_transform()
+            .transform_point(&Point3::from(local_position))
+            .coords
+    }
+
+    fn assert_vec_eq(a: Vector3<f32>, b: Vector3<f32>) {
+        assert!((a - b).norm() < EPSILON, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_move_along_world_axis() {
+        let initial = Vector3::new(1.0, 0.5, 0.0);
+        let inv_parent = parent_global_transform().try_inverse().unwrap();
+
+        let axes = TransformSpace::World.axes_rotation(parent_rotation(), Default::default());
+        let world_offset = axes * Vector3::new(1.5, 0.0, 0.0);
+        let new = offset_local_position(initial, &inv_parent, world_offset);
+
+        assert_vec_eq(
+            global_position(new) - global_position(initial),
+            Vector3::new(1.5, 0.0, 0.0),
+        );
+        // World X is local Z of the parent and the parent is scaled 2x.
+        assert_vec_eq(new - initial, Vector3::new(0.0, 0.0, 0.75));
+    }
+
+    #[test]
+    fn test_move_along_parent_axis() {
+        let initial = Vector3::new(1.0, 0.5, 0.0);
+        let inv_parent = parent_global_transform().try_inverse().unwrap();
+
+        let axes = TransformSpace::Parent.axes_rotation(parent_rotation(), Default::default());
+        let world_offset = axes * Vector3::new(1.0, 0.0, 0.0);
+        let new = offset_local_position(initial, &inv_parent, world_offset);
+
+        // Parent X points to world -Z.
+        assert_vec_eq(
+            global_position(new) - global_position(initial),
+            Vector3::new(0.0, 0.0, -1.0),
+        );
+        assert_vec_eq(new - initial, Vector3::new(0.5, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_rotate_around_world_axis() {
+        let local = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 0.3);
+        let world_rotation = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), FRAC_PI_2);
+
+        let new = rotate_local_rotation(local, parent_rotation(), world_rotation);
+
+        let old_global = parent_rotation() * local;
+        let new_global = parent_rotation() * new;
+        assert!(new_global.angle_to(&(world_rotation * old_global)) < EPSILON);
+    }
+
+    #[test]
+    fn test_rotate_around_local_axis() {
+        let local = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.3);
+        let delta = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 0.5);
+
+        let axes = TransformSpace::Local.axes_rotation(parent_rotation(), local);
+        let world_rotation = axes * delta * axes.inverse();
+        let new = rotate_local_rotation(local, parent_rotation(), world_rotation);
+
+        assert!(new.angle_to(&(local * delta)) < EPSILON);
+    }
+
+    #[test]
+    fn test_scale_along_world_axis() {
+        let axes = TransformSpace::World.axes_rotation(parent_rotation(), Default::default());
+        let new = scale_local_scale(
+            Vector3::new(1.0, 1.0, 1.0),
+            parent_rotation(),
+            axes,
+            Vector3::new(1.0, 0.0, 0.0),
+        );
+
+        // World X is local Z of the node.
+        assert_vec_eq(new, Vector3::new(1.0, 1.0, 2.0));
+    }
+}
@@ -0,0 +1,683 @@
+//! Vertex paint brush - paints a single channel of vertex colors of a selected mesh, for example
+//! to make masks that blend puddles or moss on static meshes.
+//!
+//! The brush affects every vertex of the mesh within a world-space radius around a point on the
+//! surface under the cursor. Surface data of a mesh is often shared (with a model resource or
+//! other meshes), such data is never modified - the mesh gets its own procedural copy of the data
+//! on the first paint, the copy is saved with the scene. Each stroke (or fill) is a single command,
+//! which stores only changed colors of affected vertices.
+//!
+//! Positions of vertices of skinned meshes are taken in bind pose.
+
+use crate::{
+    camera::precise_ray_test,
+    interaction::{terrain::BrushGizmo, InteractionMode},
+    scene::{
+        commands::{
+            mesh::{PaintVertexColorsCommand, SetSurfaceDataCommand, VertexColorChange},
+            CommandGroup, SceneCommand,
+        },
+        view_mode::ViewMode,
+        EditorScene, Selection,
+    },
+    settings::Settings,
+    GameEngine, Message, MSG_SYNC_FLAG,
+};
+use fyrox::{
+    core::{
+        algebra::{Point3, Vector2, Vector3, Vector4},
+        inspect::{Inspect, PropertyInfo},
+        math::vector_to_quat,
+        parking_lot::Mutex,
+        pool::Handle,
+    },
+    fxhash::FxHashMap,
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        inspector::{
+            editors::{
+                enumeration::EnumPropertyEditorDefinition, PropertyEditorDefinitionContainer,
+            },
+            FieldKind, Inspector, InspectorBuilder, InspectorContext, InspectorMessage,
+            PropertyChanged,
+        },
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface,
+    },
+    scene::{
+        camera::Camera,
+        graph::Graph,
+        mesh::{
+            buffer::{
+                VertexAttributeDataType, VertexAttributeDescriptor, VertexAttributeUsage,
+                VertexReadTrait, VertexWriteTrait, COLOR_SHADER_LOCATION,
+            },
+            surface::SurfaceData,
+            Mesh,
+        },
+        node::Node,
+    },
+    utils::log::Log,
+};
+use std::{rc::Rc, sync::mpsc::Sender, sync::Arc};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Color of vertices that had no vertex colors before painting, it is the value that shaders read
+/// when a mesh has no vertex colors.
+const DEFAULT_VERTEX_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+/// A channel of vertex colors.
+#[derive(
+    Copy, Clone, PartialEq, Eq, Hash, Debug, Inspect, AsRefStr, EnumString, EnumVariantNames,
+)]
+pub enum VertexPaintChannel {
+    Red,
+    Green,
+    Blue,
+    Alpha,
+}
+
+impl VertexPaintChannel {
+    /// Index of the channel in a color vector.
+    pub fn index(self) -> usize {
+        match self {
+            VertexPaintChannel::Red => 0,
+            VertexPaintChannel::Green => 1,
+            VertexPaintChannel::Blue => 2,
+            VertexPaintChannel::Alpha => 3,
+        }
+    }
+}
+
+/// Settings of the vertex paint brush.
+#[derive(Clone, Debug, Inspect)]
+pub struct VertexPaintBrush {
+    /// A channel of vertex colors that is painted.
+    pub channel: VertexPaintChannel,
+    /// Radius of the brush in meters.
+    #[inspect(min_value = 0.01, max_value = 50.0, step = 0.05)]
+    pub radius: f32,
+    /// How fast a channel approaches the value, 1.0 - the value is set at once.
+    #[inspect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub strength: f32,
+    /// Part of the radius where the influence of the brush fades out, 0.0 - hard edge, 1.0 - the
+    /// influence fades out from the center.
+    #[inspect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub falloff: f32,
+    /// A value that is painted into the channel. Holding Shift paints zero.
+    #[inspect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub value: f32,
+}
+
+impl Default for VertexPaintBrush {
+    fn default() -> Self {
+        Self {
+            channel: VertexPaintChannel::Red,
+            radius: 0.5,
+            strength: 0.25,
+            falloff: 0.5,
+            value: 1.0,
+        }
+    }
+}
+
+impl VertexPaintBrush {
+    fn handle_property_changed(&mut self, property_changed: &PropertyChanged) -> bool {
+        if let FieldKind::Object(ref args) = property_changed.value {
+            return match property_changed.name.as_ref() {
+                Self::CHANNEL => args.try_override(&mut self.channel),
+                Self::RADIUS => args.try_override(&mut self.radius),
+                Self::STRENGTH => args.try_override(&mut self.strength),
+                Self::FALLOFF => args.try_override(&mut self.falloff),
+                Self::VALUE => args.try_override(&mut self.value),
+                _ => false,
+            };
+        }
+        false
+    }
+
+    /// Returns influence of the brush on a vertex at the given distance from the center of the
+    /// brush, in `[0; 1]` range.
+    fn influence(&self, distance: f32) -> f32 {
+        if distance > self.radius || self.radius <= 0.0 {
+            return 0.0;
+        }
+
+        let falloff = self.falloff.clamp(0.0, 1.0);
+        let t = distance / self.radius;
+        if t <= 1.0 - falloff {
+            1.0
+        } else {
+            (1.0 - t) / falloff
+        }
+    }
+}
+
+/// Moves the value towards the target by the given amount, `1.0` - the target is reached.
+fn blend(value: f32, target: f32, amount: f32) -> f32 {
+    value + (target - value) * amount.clamp(0.0, 1.0)
+}
+
+/// Returns `true` if surface data must be copied before painting: the data is shared with a model
+/// resource or other meshes (`data` itself is one of the references), or it has no vertex colors
+/// and the color attribute could not be added in place, because the renderer does not track
+/// changes of vertex layout.
+fn needs_copy(data: &Arc<Mutex<SurfaceData>>) -> bool {
+    let shared = Arc::strong_count(data) > 2;
+    let data = data.lock();
+    shared
+        || !data.is_procedural()
+        || !data
+            .vertex_buffer
+            .has_attribute(VertexAttributeUsage::Color)
+}
+
+/// Makes a procedural copy of the data with vertex colors.
+fn make_paintable_copy(data: &SurfaceData) -> SurfaceData {
+    let mut copy = data.procedural_copy();
+    if !copy
+        .vertex_buffer
+        .has_attribute(VertexAttributeUsage::Color)
+    {
+        if let Err(e) = copy.vertex_buffer.modify().add_attribute(
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Color,
+                data_type: VertexAttributeDataType::F32,
+                size: 4,
+                divisor: 0,
+                shader_location: COLOR_SHADER_LOCATION,
+            },
+            DEFAULT_VERTEX_COLOR,
+        ) {
+            Log::err(format!("Unable to add vertex colors. Reason: {:?}", e));
+        }
+    }
+    copy
+}
+
+struct Stroke {
+    mesh: Handle<Node>,
+    name: &'static str,
+    // Surfaces that got their own copies of data on this stroke: index, copy, previous data.
+    copies: Vec<(usize, Arc<Mutex<SurfaceData>>, Arc<Mutex<SurfaceData>>)>,
+    surfaces_modified: bool,
+    // Colors of affected vertices before the stroke, for every surface.
+    original_colors: Vec<FxHashMap<u32, Vector4<f32>>>,
+}
+
+impl Stroke {
+    fn begin(handle: Handle<Node>, graph: &mut Graph, name: &'static str) -> Option<Self> {
+        let mesh = graph.try_get_mut(handle)?.cast_mut::<Mesh>()?;
+        let surfaces_modified = mesh.is_surfaces_modified();
+
+        let mut copies = Vec::new();
+        for index in 0..mesh.surfaces().len() {
+            let data = mesh.surfaces()[index].data();
+            if needs_copy(&data) {
+                let copy = Arc::new(Mutex::new(make_paintable_copy(&data.lock())));
+                mesh.set_surface_data(index, copy.clone());
+                copies.push((index, copy, data));
+            }
+        }
+
+        Some(Self {
+            mesh: handle,
+            name,
+            copies,
+            surfaces_modified,
+            original_colors: vec![Default::default(); mesh.surfaces().len()],
+        })
+    }
+
+    /// Moves the channel of every vertex towards the target, `amount` gives an amount of the
+    /// blend for a vertex by its world-space position.
+    fn paint<F>(&mut self, graph: &mut Graph, channel: VertexPaintChannel, target: f32, amount: F)
+    where
+        F: Fn(Vector3<f32>) -> f32,
+    {
+        let mesh = match graph
+            .try_get_mut(self.mesh)
+            .and_then(|node| node.cast_mut::<Mesh>())
+        {
+            Some(mesh) => mesh,
+            None => return,
+        };
+
+        let transform = mesh.global_transform();
+        let channel = channel.index();
+
+        for (surface, original_colors) in
+            mesh.surfaces().iter().zip(self.original_colors.iter_mut())
+        {
+            let data = surface.data();
+            let mut data = data.lock();
+            let mut vertex_buffer = data.vertex_buffer.modify();
+            for (index, mut vertex) in vertex_buffer.iter_mut().enumerate() {
+                let position = match vertex.read_3_f32(VertexAttributeUsage::Position) {
+                    Ok(position) => transform.transform_point(&Point3::from(position)).coords,
+                    Err(_) => continue,
+                };
+
+                let weight = amount(position);
+                if weight <= 0.0 {
+                    continue;
+                }
+
+                if let Ok(mut color) = vertex.read_4_f32(VertexAttributeUsage::Color) {
+                    original_colors.entry(index as u32).or_insert(color);
+                    color[channel] = blend(color[channel], target, weight);
+                    let _ = vertex.write_4_f32(VertexAttributeUsage::Color, color);
+                }
+            }
+        }
+    }
+
+    /// Undoes every change of the stroke and makes a command that could redo them.
+    fn into_command(self, graph: &mut Graph) -> Option<CommandGroup> {
+        let mesh = graph
+            .try_get_mut(self.mesh)
+            .and_then(|node| node.cast_mut::<Mesh>())?;
+
+        // Changes are re-applied by the commands, this way the scene is modified by commands only.
+        let mut changes = Vec::new();
+        for (surface_index, (surface, original_colors)) in mesh
+            .surfaces()
+            .iter()
+            .zip(self.original_colors.iter())
+            .enumerate()
+        {
+            let data = surface.data();
+            let mut data = data.lock();
+            let mut vertex_buffer = data.vertex_buffer.modify();
+            for (&vertex, &old_color) in original_colors.iter() {
+                if let Some(mut view) = vertex_buffer.get_mut(vertex as usize) {
+                    if let Ok(new_color) = view.read_4_f32(VertexAttributeUsage::Color) {
+                        if new_color != old_color {
+                            changes.push(VertexColorChange {
+                                surface: surface_index,
+                                vertex,
+                                old_color,
+                                new_color,
+                            });
+                        }
+                        let _ = view.write_4_f32(VertexAttributeUsage::Color, old_color);
+                    }
+                }
+            }
+        }
+
+        for (index, _, previous_data) in self.copies.iter().rev() {
+            mesh.set_surface_data(*index, previous_data.clone());
+        }
+        if !self.surfaces_modified {
+            mesh.reset_surfaces_modified_flag();
+        }
+
+        if changes.is_empty() {
+            return None;
+        }
+
+        if !self.copies.is_empty() {
+            Log::warn(format!(
+                "Surface data of {} mesh is shared with its model resource or other meshes, \
+                the mesh got its own copy of the data to paint on. The copy is saved with the scene.",
+                mesh.name()
+            ));
+        }
+
+        let mut commands = self
+            .copies
+            .into_iter()
+            .map(|(index, copy, _)| {
+                SceneCommand::new(SetSurfaceDataCommand::new(self.mesh, index, copy))
+            })
+            .collect::<Vec<_>>();
+        commands.push(SceneCommand::new(PaintVertexColorsCommand::new(
+            self.mesh, changes, self.name,
+        )));
+
+        Some(CommandGroup::from(commands).with_custom_name(self.name))
+    }
+}
+
+/// Returns a mesh that could be painted - the only selected node, if it is a mesh.
+fn selected_mesh(editor_scene: &EditorScene, graph: &Graph) -> Option<Handle<Node>> {
+    if let Selection::Graph(selection) = &editor_scene.selection {
+        if selection.is_single_selection() {
+            let handle = selection.nodes()[0];
+            if graph
+                .try_get(handle)
+                .map_or(false, |node| node.cast::<Mesh>().is_some())
+            {
+                return Some(handle);
+            }
+        }
+    }
+    None
+}
+
+pub struct VertexPaintInteractionMode {
+    message_sender: Sender<Message>,
+    brush: VertexPaintBrush,
+    brush_gizmo: BrushGizmo,
+    panel: VertexPaintPanel,
+    // World-space position of the brush on the selected mesh under the cursor.
+    brush_position: Option<Vector3<f32>>,
+    stroke: Option<Stroke>,
+}
+
+impl VertexPaintInteractionMode {
+    pub fn new(
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        message_sender: Sender<Message>,
+    ) -> Self {
+        let brush = VertexPaintBrush::default();
+        let panel = VertexPaintPanel::new(&mut engine.user_interface.build_ctx(), &brush);
+
+        Self {
+            message_sender,
+            brush,
+            brush_gizmo: BrushGizmo::new(editor_scene, engine),
+            panel,
+            brush_position: None,
+            stroke: None,
+        }
+    }
+
+    fn finish_stroke(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        if let Some(stroke) = self.stroke.take() {
+            if let Some(command) = stroke.into_command(&mut engine.scenes[editor_scene.scene].graph)
+            {
+                self.message_sender
+                    .send(Message::do_scene_command(command))
+                    .unwrap();
+            }
+        }
+    }
+
+    fn dab(&mut self, graph: &mut Graph, center: Vector3<f32>, erase: bool) {
+        if let Some(stroke) = self.stroke.as_mut() {
+            let brush = &self.brush;
+            let target = if erase { 0.0 } else { brush.value };
+            stroke.paint(graph, brush.channel, target, |position| {
+                brush.influence(position.metric_distance(&center)) * brush.strength
+            });
+        }
+    }
+
+    fn fill(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        let graph = &mut engine.scenes[editor_scene.scene].graph;
+        match selected_mesh(editor_scene, graph) {
+            Some(mesh) => {
+                self.finish_stroke(editor_scene, engine);
+
+                let graph = &mut engine.scenes[editor_scene.scene].graph;
+                self.stroke = Stroke::begin(mesh, graph, "Fill Vertex Colors");
+                if let Some(stroke) = self.stroke.as_mut() {
+                    stroke.paint(graph, self.brush.channel, self.brush.value, |_| 1.0);
+                }
+
+                self.finish_stroke(editor_scene, engine);
+            }
+            None => Log::warn("Select a mesh to fill its vertex colors!".to_owned()),
+        }
+    }
+}
+
+impl InteractionMode for VertexPaintInteractionMode {
+    fn on_left_mouse_button_down(
+        &mut self,
+        editor_scene: &mut EditorScene,
+        engine: &mut GameEngine,
+        _mouse_pos: Vector2<f32>,
+        _frame_size: Vector2<f32>,
+        _settings: &Settings,
+    ) {
+        let graph = &mut engine.scenes[editor_scene.scene].graph;
+        if let Some(mesh) = selected_mesh(editor_scene, graph) {
+            self.stroke = Stroke::begin(mesh, graph, "Paint Vertex Colors");
+            if let Some(position) = self.brush_position {
+                let erase = engine.user_interface.keyboard_modifiers().shift;
+                self.dab(
+                    &mut engine.scenes[editor_scene.scene].graph,
+                    position,
+                    erase,
+                );
+            }
+        }
+    }
+
+    fn on_left_mouse_button_up(
+        &mut self,
+        editor_scene: &mut EditorScene,
+        engine: &mut GameEngine,
+        _mouse_pos: Vector2<f32>,
+        _frame_size: Vector2<f32>,
+        _settings: &Settings,
+    ) {
+        self.finish_stroke(editor_scene, engine);
+    }
+
+    fn on_mouse_move(
+        &mut self,
+        _mouse_offset: Vector2<f32>,
+        mouse_position: Vector2<f32>,
+        camera: Handle<Node>,
+        editor_scene: &mut EditorScene,
+        engine: &mut GameEngine,
+        frame_size: Vector2<f32>,
+        _settings: &Settings,
+    ) {
+        let erase = engine.user_interface.keyboard_modifiers().shift;
+        let graph = &mut engine.scenes[editor_scene.scene].graph;
+
+        let ray = match graph[camera].cast::<Camera>() {
+            Some(camera) => camera.make_ray(mouse_position, frame_size),
+            None => return,
+        };
+
+        let hit = selected_mesh(editor_scene, graph)
+            .and_then(|mesh| precise_ray_test(&graph[mesh], &ray, false));
+
+        self.brush_position = hit.map(|(_, position, _)| position);
+
+        if let Some((_, position, normal)) = hit {
+            let diameter = 2.0 * self.brush.radius;
+            graph[self.brush_gizmo.brush]
+                .local_transform_mut()
+                .set_position(position)
+                .set_scale(Vector3::new(diameter, diameter, 1.0))
+                .set_rotation(vector_to_quat(normal));
+
+            self.dab(graph, position, erase);
+        }
+    }
+
+    fn activate(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        self.brush_gizmo
+            .set_visible(&mut engine.scenes[editor_scene.scene].graph, true);
+
+        self.panel
+            .sync_to_model(&mut engine.user_interface, &self.brush);
+
+        engine.user_interface.send_message(WindowMessage::open(
+            self.panel.window,
+            MessageDirection::ToWidget,
+            false,
+        ));
+    }
+
+    fn deactivate(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        self.finish_stroke(editor_scene, engine);
+
+        self.brush_gizmo
+            .set_visible(&mut engine.scenes[editor_scene.scene].graph, false);
+
+        engine.user_interface.send_message(WindowMessage::close(
+            self.panel.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+
+    fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        editor_scene: &mut EditorScene,
+        engine: &mut GameEngine,
+    ) {
+        if let Some(InspectorMessage::PropertyChanged(property_changed)) =
+            message.data::<InspectorMessage>()
+        {
+            if message.destination() == self.panel.inspector
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.brush.handle_property_changed(property_changed);
+            }
+        } else if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.panel.fill {
+                self.fill(editor_scene, engine);
+            } else if message.destination() == self.panel.show_channel {
+                self.message_sender
+                    .send(Message::SetViewMode(ViewMode::VertexColor(
+                        self.brush.channel,
+                    )))
+                    .unwrap();
+            }
+        }
+    }
+
+    fn on_drop(&mut self, engine: &mut GameEngine) {
+        engine.user_interface.send_message(WidgetMessage::remove(
+            self.panel.window,
+            MessageDirection::ToWidget,
+        ));
+    }
+}
+
+struct VertexPaintPanel {
+    window: Handle<UiNode>,
+    inspector: Handle<UiNode>,
+    fill: Handle<UiNode>,
+    show_channel: Handle<UiNode>,
+}
+
+impl VertexPaintPanel {
+    fn new(ctx: &mut BuildContext, brush: &VertexPaintBrush) -> Self {
+        let property_editors = PropertyEditorDefinitionContainer::new();
+        property_editors.insert(EnumPropertyEditorDefinition::<VertexPaintChannel>::new());
+
+        let context = InspectorContext::from_object(
+            brush,
+            ctx,
+            Rc::new(property_editors),
+            None,
+            MSG_SYNC_FLAG,
+            0,
+        );
+
+        let inspector;
+        let fill;
+        let show_channel;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(250.0).with_height(230.0))
+            .can_close(false)
+            .with_content(
+                StackPanelBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child({
+                            inspector = InspectorBuilder::new(WidgetBuilder::new())
+                                .with_context(context)
+                                .build(ctx);
+                            inspector
+                        })
+                        .with_child({
+                            fill = ButtonBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_height(24.0)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_text("Fill Channel")
+                            .build(ctx);
+                            fill
+                        })
+                        .with_child({
+                            show_channel = ButtonBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_height(24.0)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_text("Show Channel")
+                            .build(ctx);
+                            show_channel
+                        }),
+                )
+                .build(ctx),
+            )
+            .open(false)
+            .with_title(WindowTitle::text("Vertex Paint"))
+            .build(ctx);
+
+        Self {
+            window,
+            inspector,
+            fill,
+            show_channel,
+        }
+    }
+
+    fn sync_to_model(&self, ui: &mut UserInterface, brush: &VertexPaintBrush) {
+        let ctx = ui
+            .node(self.inspector)
+            .cast::<Inspector>()
+            .expect("Must be Inspector!")
+            .context()
+            .clone();
+
+        if let Err(e) = ctx.sync(brush, ui, 0) {
+            Log::err(format!(
+                "Failed to sync VertexPaintPanel's inspector. Reason: {:?}",
+                e
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_brush_influence() {
+        let brush = VertexPaintBrush {
+            radius: 2.0,
+            falloff: 0.5,
+            ..Default::default()
+        };
+
+        // Full influence in the inner half, linear fade out in the outer half.
+        assert_eq!(brush.influence(0.0), 1.0);
+        assert_eq!(brush.influence(1.0), 1.0);
+        assert_eq!(brush.influence(1.5), 0.5);
+        assert_eq!(brush.influence(2.0), 0.0);
+        assert_eq!(brush.influence(2.5), 0.0);
+
+        let hard = VertexPaintBrush {
+            radius: 2.0,
+            falloff: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(hard.influence(2.0), 1.0);
+        assert_eq!(hard.influence(2.01), 0.0);
+    }
+
+    #[test]
+    fn test_blend() {
+        assert_eq!(blend(0.0, 1.0, 0.25), 0.25);
+        assert_eq!(blend(0.5, 0.0, 1.0), 0.0);
+        // Amount is clamped, the value never overshoots the target.
+        assert_eq!(blend(0.5, 1.0, 2.0), 1.0);
+    }
+}
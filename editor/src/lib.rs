@@ -11,13 +11,24 @@
 extern crate lazy_static;
 
 mod absm;
+mod animation;
 mod asset;
 mod audio;
+mod batch_rename;
 mod camera;
+pub mod cli;
+mod collaboration;
+mod collection;
+mod collider_generator;
 mod command;
 mod configurator;
+mod crash;
 mod curve_editor;
+pub mod export;
+mod find_replace;
 mod gui;
+mod hlod;
+mod impostor;
 mod inspector;
 mod interaction;
 mod light;
@@ -25,28 +36,50 @@ mod log;
 mod material;
 mod menu;
 mod overlay;
+mod placement;
 mod preview;
 mod scene;
 mod scene_viewer;
 mod settings;
+mod shader_graph;
 mod utils;
 mod world;
 
 use crate::{
     absm::AbsmEditor,
-    asset::{item::AssetItem, item::AssetKind, AssetBrowser},
+    animation::AnimationPanel,
+    asset::{
+        item::AssetItem,
+        item::AssetKind,
+        relocation::{remap_path, Move},
+        AssetBrowser,
+    },
     audio::AudioPanel,
+    batch_rename::BatchRenameWindow,
+    camera::CameraView,
+    collaboration::Collaboration,
+    collection::NodeCollectionsPanel,
+    collider_generator::{make_generate_hitboxes_command, ColliderGeneratorWindow},
     command::{panel::CommandStackViewer, Command, CommandStack},
     configurator::Configurator,
+    crash::{CrashRecoveryWindow, RecoveredScene},
     curve_editor::CurveEditorWindow,
+    export::ExportWindow,
+    find_replace::FindReplaceWindow,
+    hlod::HlodPanel,
+    impostor::ImpostorPanel,
     inspector::Inspector,
     interaction::{
+        collider_shape::ColliderShapeInteractionMode,
         move_mode::MoveInteractionMode,
         navmesh::{EditNavmeshMode, NavmeshPanel},
         rotate_mode::RotateInteractionMode,
         scale_mode::ScaleInteractionMode,
+        scatter::ScatterInteractionMode,
         select_mode::SelectInteractionMode,
         terrain::TerrainInteractionMode,
+        transform_space::TransformSpace,
+        vertex_paint::VertexPaintInteractionMode,
         InteractionMode, InteractionModeKind,
     },
     light::LightPanel,
@@ -54,16 +87,25 @@ use crate::{
     material::MaterialEditor,
     menu::{Menu, MenuContext, Panels},
     overlay::OverlayRenderPass,
+    placement::PlacementWindow,
     scene::{
         commands::{
             graph::AddModelCommand, make_delete_selection_command, mesh::SetMeshTextureCommand,
             particle_system::SetParticleSystemTextureCommand, sprite::SetSpriteTextureCommand,
             ChangeSelectionCommand, CommandGroup, PasteCommand, SceneCommand, SceneContext,
         },
-        is_scene_needs_to_be_saved, EditorScene, Selection,
+        is_scene_needs_to_be_saved,
+        revert::ViewState,
+        view_mode::ViewMode,
+        EditorScene, Selection,
     },
     scene_viewer::SceneViewer,
-    settings::Settings,
+    settings::{
+        graphics::{MAX_RENDER_SCALE, MIN_RENDER_SCALE},
+        viewports::ViewportLayout,
+        Settings,
+    },
+    shader_graph::ShaderGraphEditor,
     utils::{normalize_os_event, path_fixer::PathFixer},
     world::{graph::selection::GraphSelection, WorldViewer},
 };
@@ -78,7 +120,10 @@ use fyrox::{
         sstorage::ImmutableString,
     },
     dpi::LogicalSize,
-    engine::{resource_manager::ResourceManager, Engine, EngineInitParams, SerializationContext},
+    engine::{
+        project::ProjectManifest, resource_manager::ResourceManager, Engine, EngineInitParams,
+        SerializationContext,
+    },
     event::{Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     gui::{
@@ -98,13 +143,8 @@ use fyrox::{
     },
     material::{shader::Shader, Material, PropertyValue},
     plugin::Plugin,
-    resource::texture::{CompressionOptions, Texture, TextureKind},
-    scene::{
-        camera::{Camera, Projection},
-        mesh::Mesh,
-        node::Node,
-        Scene, SceneLoader,
-    },
+    resource::texture::{set_texture_cache_root, CompressionOptions, Texture},
+    scene::{camera::Camera, mesh::Mesh, node::Node, Scene, SceneLoader},
     utils::{
         into_gui_texture,
         log::{Log, MessageKind},
@@ -114,6 +154,7 @@ use fyrox::{
 };
 use std::{
     any::TypeId,
+    panic::AssertUnwindSafe,
     path::{Path, PathBuf},
     sync::{
         mpsc::{self, channel, Receiver, Sender},
@@ -189,9 +230,20 @@ pub enum Message {
     UndoSceneCommand,
     RedoSceneCommand,
     ClearSceneCommandStack,
+    /// Performs a sequence of undo or redo operations until given amount of commands is applied.
+    JumpToSceneCommand(usize),
     SelectionChanged,
     SaveScene(PathBuf),
     LoadScene(PathBuf),
+    /// Loads a scene without checking its lock file.
+    ForceLoadScene(PathBuf),
+    /// Opens a scene saved by the crash handler, see [`crate::crash`].
+    OpenRecoveredScene {
+        working_directory: PathBuf,
+        scene: RecoveredScene,
+    },
+    /// Reloads the current scene from its file, discarding unsaved changes.
+    RevertScene,
     CloseScene,
     SetInteractionMode(InteractionModeKind),
     Configure {
@@ -203,7 +255,16 @@ pub enum Message {
     },
     OpenSettings,
     OpenMaterialEditor(Arc<Mutex<Material>>),
+    OpenColliderGenerator,
+    OpenBatchRename,
+    OpenFindReplace,
+    OpenPlacementWindow,
+    DropSelectionToFloor,
+    /// Generates hitboxes for selected skinned meshes or bones.
+    GenerateHitboxes,
     ShowInAssetBrowser(PathBuf),
+    /// Assets were moved by the asset browser, references to them are already fixed on disk.
+    AssetsMoved(Vec<Move>),
     SetWorldViewerFilter(String),
     LocateObject {
         type_id: TypeId,
@@ -213,7 +274,25 @@ pub enum Message {
         type_id: TypeId,
         handle: ErasedHandle,
     },
-    SetEditorCameraProjection(Projection),
+    /// Sets view of the camera of the active viewport pane.
+    SetEditorCameraView(CameraView),
+    /// Sets debug view mode of the active viewport pane, see
+    /// [`crate::scene::view_mode::ViewMode`].
+    SetViewMode(ViewMode),
+    /// Switches between single and quad view of the scene preview, see
+    /// [`crate::scene::viewports::Viewports`].
+    ToggleViewportLayout,
+    /// Sets scale of the resolution of the scene preview, see
+    /// [`crate::settings::graphics::GraphicsSettings::render_scale`].
+    SetRenderScale(f32),
+    /// Sets coordinate space of move, rotate and scale interaction modes.
+    SetTransformSpace(TransformSpace),
+    /// Hides everything except selected nodes, see [`crate::scene::EditorScene::isolate_selection`].
+    IsolateSelection,
+    /// Shows selected nodes in addition to already isolated ones.
+    AddSelectionToIsolation,
+    /// Shows every node hidden by isolation mode.
+    ExitIsolation,
     SwitchToPlayMode,
     SwitchToEditMode,
     SwitchMode,
@@ -413,11 +492,21 @@ impl SaveSceneConfirmationDialog {
     }
 }
 
+/// State of a jump in the command history, long jumps are spread across multiple frames.
+struct CommandJump {
+    target: usize,
+    distance: usize,
+}
+
+/// Max amount of commands that will be undone or redone per frame when jumping in history.
+const MAX_JUMP_STEPS_PER_FRAME: usize = 16;
+
 pub struct Editor {
     game_loop_data: GameLoopData,
     engine: Engine,
     scene: Option<EditorScene>,
     command_stack: CommandStack,
+    command_jump: Option<CommandJump>,
     message_sender: Sender<Message>,
     message_receiver: Receiver<Message>,
     interaction_modes: Vec<Box<dyn InteractionMode>>,
@@ -429,7 +518,15 @@ pub struct Editor {
     exit_message_box: Handle<UiNode>,
     save_file_selector: Handle<UiNode>,
     save_scene_dialog: SaveSceneConfirmationDialog,
+    collaboration: Collaboration,
     light_panel: LightPanel,
+    collider_generator: ColliderGeneratorWindow,
+    crash_recovery: CrashRecoveryWindow,
+    batch_rename: BatchRenameWindow,
+    find_replace: FindReplaceWindow,
+    placement: PlacementWindow,
+    hlod_panel: HlodPanel,
+    impostor_panel: ImpostorPanel,
     menu: Menu,
     exit: bool,
     configurator: Configurator,
@@ -443,13 +540,26 @@ pub struct Editor {
     pub inspector: Inspector,
     curve_editor: CurveEditorWindow,
     audio_panel: AudioPanel,
+    node_collections: NodeCollectionsPanel,
+    animation_panel: AnimationPanel,
+    export_window: ExportWindow,
     #[allow(dead_code)] // TODO
     absm_editor: AbsmEditor,
+    shader_graph_editor: ShaderGraphEditor,
     mode: Mode,
 }
 
 impl Editor {
     pub fn new(event_loop: &EventLoop<()>, startup_data: Option<StartupData>) -> Self {
+        crash::install_panic_hook();
+        #[cfg(feature = "pool_diagnostics")]
+        fyrox::core::pool::diagnostics::set_invalid_access_hook(Some(|diagnostic, location| {
+            Log::err(format!(
+                "Invalid handle access at {}: {}",
+                location, diagnostic
+            ))
+        }));
+
         let (log_message_sender, log_message_receiver) = channel();
 
         Log::add_listener(log_message_sender);
@@ -525,18 +635,29 @@ impl Editor {
             }
         }
 
-        let scene_viewer = SceneViewer::new(&mut engine, message_sender.clone());
+        let scene_viewer = SceneViewer::new(&mut engine, message_sender.clone(), &settings);
         let asset_browser = AssetBrowser::new(&mut engine);
         let menu = Menu::new(&mut engine, message_sender.clone());
+        let serialization_context = engine.serialization_context.clone();
         let light_panel = LightPanel::new(&mut engine);
+        let collider_generator = ColliderGeneratorWindow::new(&mut engine);
+        let batch_rename = BatchRenameWindow::new(&mut engine);
+        let find_replace = FindReplaceWindow::new(&mut engine);
         let audio_panel = AudioPanel::new(&mut engine);
 
         let ctx = &mut engine.user_interface.build_ctx();
         let navmesh_panel = NavmeshPanel::new(ctx, message_sender.clone());
-        let world_outliner = WorldViewer::new(ctx, message_sender.clone());
+        let placement = PlacementWindow::new(ctx);
+        let crash_recovery = CrashRecoveryWindow::new(ctx);
+        let hlod_panel = HlodPanel::new(ctx);
+        let impostor_panel = ImpostorPanel::new(ctx);
+        let world_outliner = WorldViewer::new(ctx, message_sender.clone(), serialization_context);
         let command_stack_viewer = CommandStackViewer::new(ctx, message_sender.clone());
         let log = LogPanel::new(ctx, log_message_receiver);
         let inspector = Inspector::new(ctx, message_sender.clone());
+        let node_collections = NodeCollectionsPanel::new(ctx, message_sender.clone());
+        let animation_panel = AnimationPanel::new(ctx, message_sender.clone());
+        let export_window = ExportWindow::new(ctx);
 
         let root_grid = GridBuilder::new(
             WidgetBuilder::new()
@@ -676,16 +797,21 @@ impl Editor {
 
         let save_scene_dialog = SaveSceneConfirmationDialog::new(ctx);
 
+        let collaboration = Collaboration::new(ctx, message_sender.clone());
+
         let absm_editor = AbsmEditor::new(&mut engine, message_sender.clone());
 
         let material_editor = MaterialEditor::new(&mut engine);
 
+        let shader_graph_editor = ShaderGraphEditor::new(&mut engine, message_sender.clone());
+
         let mut editor = Self {
             engine,
             navmesh_panel,
             scene_viewer,
             scene: None,
             command_stack: CommandStack::new(false),
+            command_jump: None,
             message_sender,
             message_receiver,
             interaction_modes: Default::default(),
@@ -700,6 +826,13 @@ impl Editor {
             configurator,
             log,
             light_panel,
+            collider_generator,
+            crash_recovery,
+            batch_rename,
+            find_replace,
+            placement,
+            hlod_panel,
+            impostor_panel,
             command_stack_viewer,
             validation_message_box,
             settings,
@@ -708,13 +841,18 @@ impl Editor {
             inspector,
             curve_editor,
             audio_panel,
+            node_collections,
+            animation_panel,
+            export_window,
             save_scene_dialog,
+            collaboration,
             mode: Mode::Edit,
             game_loop_data: GameLoopData {
                 clock: Instant::now(),
                 elapsed_time: 0.0,
             },
             absm_editor,
+            shader_graph_editor,
         };
 
         editor.set_interaction_mode(Some(InteractionModeKind::Move));
@@ -749,6 +887,12 @@ impl Editor {
                 ));
         }
 
+        if let Some((folder, info)) = crash::find_unreviewed_recovery() {
+            editor
+                .crash_recovery
+                .open(&editor.engine.user_interface, folder, info);
+        }
+
         editor
     }
 
@@ -770,7 +914,19 @@ impl Editor {
         self.scene_viewer
             .set_render_target(&self.engine.user_interface, scene.render_target.clone());
 
-        let editor_scene = EditorScene::from_native_scene(scene, &mut self.engine, path.clone());
+        let mut editor_scene =
+            EditorScene::from_native_scene(scene, &mut self.engine, path.clone());
+
+        if let Some(layout) = path
+            .as_ref()
+            .and_then(|path| self.settings.viewport_layouts.get(path))
+        {
+            editor_scene.viewports.apply_settings(
+                layout,
+                &mut editor_scene.camera_controller,
+                &mut self.engine.scenes[editor_scene.scene].graph,
+            );
+        }
 
         self.interaction_modes = vec![
             Box::new(SelectInteractionMode::new(
@@ -803,11 +959,33 @@ impl Editor {
                 &mut self.engine,
                 self.message_sender.clone(),
             )),
+            Box::new(ScatterInteractionMode::new(
+                &editor_scene,
+                &mut self.engine,
+                self.message_sender.clone(),
+            )),
+            Box::new(ColliderShapeInteractionMode::new(
+                &editor_scene,
+                &mut self.engine,
+                self.message_sender.clone(),
+            )),
+            Box::new(VertexPaintInteractionMode::new(
+                &editor_scene,
+                &mut self.engine,
+                self.message_sender.clone(),
+            )),
         ];
 
         self.command_stack = CommandStack::new(false);
+        self.command_jump = None;
         self.scene = Some(editor_scene);
 
+        self.collaboration.on_scene_path_changed(
+            path.as_deref(),
+            &self.settings.collaboration,
+            &self.engine.user_interface,
+        );
+
         self.set_interaction_mode(Some(InteractionModeKind::Move));
 
         self.scene_viewer.set_title(
@@ -819,8 +997,18 @@ impl Editor {
                     .to_string())
             ),
         );
-        self.scene_viewer
-            .reset_camera_projection(&self.engine.user_interface);
+        if let Some(editor_scene) = self.scene.as_ref() {
+            self.scene_viewer.sync_camera_view(
+                &self.engine.user_interface,
+                editor_scene.camera_controller.view(),
+            );
+            self.scene_viewer.sync_view_mode(
+                &self.engine.user_interface,
+                editor_scene
+                    .viewports
+                    .view_mode(editor_scene.viewports.active()),
+            );
+        }
         self.engine.renderer.flush();
     }
 
@@ -929,14 +1117,33 @@ impl Editor {
                 KeyCode::Q if modifiers.control => {
                     sender.send(Message::CloseScene).unwrap();
                 }
+                KeyCode::I => {
+                    if let Some(editor_scene) = self.scene.as_ref() {
+                        sender
+                            .send(if modifiers.shift {
+                                Message::AddSelectionToIsolation
+                            } else if editor_scene.is_isolated() {
+                                Message::ExitIsolation
+                            } else {
+                                Message::IsolateSelection
+                            })
+                            .unwrap();
+                    }
+                }
+                KeyCode::Escape if self.placement.is_simulating() => {
+                    self.placement.cancel(&engine.user_interface);
+                }
                 KeyCode::Delete => {
                     if let Some(editor_scene) = self.scene.as_mut() {
                         if !editor_scene.selection.is_empty() {
                             if let Selection::Graph(_) = editor_scene.selection {
+                                // Shift+Delete deletes nodes of prefab instances without
+                                // suppressing them.
                                 sender
                                     .send(Message::DoSceneCommand(make_delete_selection_command(
                                         editor_scene,
                                         engine,
+                                        modifiers.shift,
                                     )))
                                     .unwrap();
                             }
@@ -959,12 +1166,25 @@ impl Editor {
         let engine = &mut self.engine;
 
         self.absm_editor.handle_ui_message(message, engine);
+        self.shader_graph_editor.handle_ui_message(message, engine);
+        self.export_window.handle_ui_message(
+            message,
+            engine,
+            self.scene.as_ref().and_then(|s| s.path.as_deref()),
+        );
         self.save_scene_dialog.handle_ui_message(
             message,
             &self.message_sender,
             self.scene.as_ref(),
         );
         self.configurator.handle_ui_message(message, engine);
+        self.crash_recovery.handle_ui_message(
+            message,
+            &engine.user_interface,
+            &self.message_sender,
+        );
+        self.collaboration
+            .handle_ui_message(message, &engine.user_interface);
         self.menu.handle_ui_message(
             message,
             MenuContext {
@@ -975,11 +1195,17 @@ impl Editor {
                     world_outliner_window: self.world_viewer.window,
                     asset_window: self.asset_browser.window,
                     light_panel: self.light_panel.window,
+                    hlod_panel: self.hlod_panel.window,
+                    impostor_panel: self.impostor_panel.window,
+                    node_collections: self.node_collections.window,
+                    animation_panel: self.animation_panel.window,
                     log_panel: self.log.window,
                     configurator_window: self.configurator.window,
                     path_fixer: self.path_fixer.window,
                     curve_editor: &self.curve_editor,
                     absm_editor: &self.absm_editor,
+                    shader_graph_editor: &self.shader_graph_editor,
+                    export_window: &self.export_window,
                 },
                 settings: &mut self.settings,
             },
@@ -988,7 +1214,8 @@ impl Editor {
         self.log.handle_ui_message(message, engine);
         self.asset_browser
             .handle_ui_message(message, engine, self.message_sender.clone());
-        self.command_stack_viewer.handle_ui_message(message);
+        self.command_stack_viewer
+            .handle_ui_message(message, &engine.user_interface);
         self.curve_editor.handle_ui_message(message, engine);
         self.path_fixer.handle_ui_message(
             message,
@@ -1040,6 +1267,34 @@ impl Editor {
                 .handle_ui_message(message, editor_scene, engine);
 
             self.light_panel
+                .handle_ui_message(message, editor_scene, engine, &self.message_sender);
+
+            self.node_collections
+                .handle_ui_message(message, editor_scene, engine);
+
+            self.animation_panel
+                .handle_ui_message(message, editor_scene, engine);
+
+            self.collider_generator.handle_ui_message(
+                message,
+                editor_scene,
+                engine,
+                &self.message_sender,
+            );
+
+            self.batch_rename
+                .handle_ui_message(message, engine, &self.message_sender);
+
+            self.find_replace
+                .handle_ui_message(message, engine, &self.message_sender);
+
+            self.placement
+                .handle_ui_message(message, editor_scene, engine, &self.message_sender);
+
+            self.hlod_panel
+                .handle_ui_message(message, editor_scene, engine);
+
+            self.impostor_panel
                 .handle_ui_message(message, editor_scene, engine);
 
             self.material_editor
@@ -1097,7 +1352,7 @@ impl Editor {
     fn set_play_mode(&mut self) {
         let engine = &mut self.engine;
         if let Some(editor_scene) = self.scene.as_ref() {
-            let mut purified_scene = editor_scene.make_purified_scene(engine);
+            let (mut purified_scene, _) = editor_scene.make_purified_scene(engine);
 
             // Hack. Turn on cameras.
             for node in purified_scene.graph.linear_iter_mut() {
@@ -1186,6 +1441,8 @@ impl Editor {
         self.command_stack_viewer.on_mode_changed(ui, &self.mode);
         self.inspector.on_mode_changed(ui, &self.mode);
         self.audio_panel.on_mode_changed(ui, &self.mode);
+        self.node_collections.on_mode_changed(ui, &self.mode);
+        self.animation_panel.on_mode_changed(ui, &self.mode);
         self.navmesh_panel.on_mode_changed(ui, &self.mode);
         self.menu.on_mode_changed(ui, &self.mode);
     }
@@ -1205,20 +1462,15 @@ impl Editor {
             self.material_editor
                 .sync_to_model(&mut engine.user_interface);
             self.audio_panel.sync_to_model(editor_scene, engine);
-            self.command_stack_viewer.sync_to_model(
-                &mut self.command_stack,
-                &SceneContext {
-                    scene: &mut engine.scenes[editor_scene.scene],
-                    message_sender: self.message_sender.clone(),
-                    editor_scene,
-                    resource_manager: engine.resource_manager.clone(),
-                    serialization_context: engine.serialization_context.clone(),
-                },
-                &mut engine.user_interface,
-            )
+            self.node_collections.sync_to_model(editor_scene, engine);
+            self.animation_panel.sync_to_model(editor_scene, engine);
+            self.command_stack_viewer
+                .sync_to_model(&self.command_stack, &mut engine.user_interface)
         } else {
             self.inspector.clear(&engine.user_interface);
             self.world_viewer.clear(&engine.user_interface);
+            self.node_collections.clear(&engine.user_interface);
+            self.animation_panel.clear(&engine.user_interface);
         }
     }
 
@@ -1230,24 +1482,47 @@ impl Editor {
 
     fn handle_resize(&mut self) {
         let engine = &mut self.engine;
+        let render_scale = self
+            .settings
+            .graphics
+            .render_scale
+            .clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE);
+
+        self.scene_viewer
+            .sync_render_scale(&engine.user_interface, render_scale);
+
         if let Some(editor_scene) = self.scene.as_ref() {
             let scene = match self.mode {
                 Mode::Edit => &mut engine.scenes[editor_scene.scene],
                 Mode::Play { scene, .. } => &mut engine.scenes[scene],
             };
 
-            // Create new render target if preview frame has changed its size.
-            if let TextureKind::Rectangle { width, height } =
-                scene.render_target.clone().unwrap().data_ref().kind()
-            {
-                let frame_size = self.scene_viewer.frame_bounds(&engine.user_interface).size;
-                if width != frame_size.x as u32 || height != frame_size.y as u32 {
-                    scene.render_target = Some(Texture::new_render_target(
-                        frame_size.x as u32,
-                        frame_size.y as u32,
-                    ));
-                    self.scene_viewer
-                        .set_render_target(&engine.user_interface, scene.render_target.clone());
+            // Create new render target if preview frame has changed its size or the render scale
+            // was changed. The image widget stretches the frame to its bounds, so picking and
+            // gizmos work in the screen space of the preview and do not depend on the scale.
+            let frame_size = self.scene_viewer.frame_bounds(&engine.user_interface).size;
+            if scene.set_render_target_size(
+                (frame_size.x * render_scale).round() as u32,
+                (frame_size.y * render_scale).round() as u32,
+            ) {
+                self.scene_viewer
+                    .set_render_target(&engine.user_interface, scene.render_target.clone());
+            }
+        }
+    }
+
+    /// Remembers the layout of viewport panes of the current scene, unsaved scenes have no layout.
+    fn save_viewport_layout(&mut self) {
+        if let Some(editor_scene) = self.scene.as_ref() {
+            if let Some(path) = editor_scene.path.as_ref() {
+                self.settings.viewport_layouts.insert(
+                    path.clone(),
+                    editor_scene
+                        .viewports
+                        .settings(&editor_scene.camera_controller),
+                );
+                if let Err(e) = self.settings.save() {
+                    Log::err(format!("Unable to save settings! Reason: {:?}!", e));
                 }
             }
         }
@@ -1268,6 +1543,7 @@ impl Editor {
             );
 
             editor_scene.has_unsaved_changes = true;
+            editor_scene.viewports.invalidate();
 
             true
         } else {
@@ -1287,6 +1563,7 @@ impl Editor {
             });
 
             editor_scene.has_unsaved_changes = true;
+            editor_scene.viewports.invalidate();
 
             true
         } else {
@@ -1306,6 +1583,7 @@ impl Editor {
             });
 
             editor_scene.has_unsaved_changes = true;
+            editor_scene.viewports.invalidate();
 
             true
         } else {
@@ -1313,6 +1591,60 @@ impl Editor {
         }
     }
 
+    fn begin_command_jump(&mut self, target: usize) {
+        let position = self.command_stack.position();
+        let target = target.min(self.command_stack.history().len());
+        if target != position {
+            self.command_jump = Some(CommandJump {
+                target,
+                distance: (target as isize - position as isize).unsigned_abs(),
+            });
+        }
+    }
+
+    /// Performs a portion of pending jump in the command history. Returns `true` if any command
+    /// was undone or redone.
+    fn update_command_jump(&mut self) -> bool {
+        let jump = match self.command_jump.as_ref() {
+            Some(jump) => jump,
+            None => return false,
+        };
+
+        let target = jump.target;
+        let distance = jump.distance;
+
+        let mut changed = false;
+        for _ in 0..MAX_JUMP_STEPS_PER_FRAME {
+            let position = self.command_stack.position();
+            let done = if position > target {
+                !self.undo_scene_command()
+            } else if position < target {
+                !self.redo_scene_command()
+            } else {
+                true
+            };
+            if done {
+                break;
+            }
+            changed = true;
+        }
+
+        let remaining = (target as isize - self.command_stack.position() as isize).unsigned_abs();
+        if remaining == 0 || !changed {
+            self.command_jump = None;
+            self.command_stack_viewer
+                .set_jump_progress(&self.engine.user_interface, None);
+        } else {
+            // Show progress only for jumps that can't be done in one frame.
+            self.command_stack_viewer.set_jump_progress(
+                &self.engine.user_interface,
+                Some(1.0 - remaining as f32 / distance as f32),
+            );
+        }
+
+        changed
+    }
+
     fn clear_scene_command_stack(&mut self) -> bool {
         let engine = &mut self.engine;
         if let Some(editor_scene) = self.scene.as_mut() {
@@ -1329,6 +1661,28 @@ impl Editor {
         }
     }
 
+    fn on_assets_moved(&mut self, moves: &[Move]) {
+        if let Some(editor_scene) = self.scene.as_mut() {
+            if let Some(path) = editor_scene.path.as_mut() {
+                if let Some(new_path) = remap_path(moves, path) {
+                    *path = new_path;
+                    self.scene_viewer.set_title(
+                        &self.engine.user_interface,
+                        format!("Scene Preview - {}", path.display()),
+                    );
+                }
+            }
+
+            // The scene file could be moved or rewritten to fix references, in both cases it is
+            // not an external change.
+            self.collaboration.on_scene_path_changed(
+                editor_scene.path.as_deref(),
+                &self.settings.collaboration,
+                &self.engine.user_interface,
+            );
+        }
+    }
+
     fn save_current_scene(&mut self, path: PathBuf) {
         let engine = &mut self.engine;
         if let Some(editor_scene) = self.scene.as_mut() {
@@ -1341,6 +1695,12 @@ impl Editor {
                     Log::info(message);
 
                     editor_scene.has_unsaved_changes = false;
+
+                    self.collaboration.on_scene_path_changed(
+                        Some(&path),
+                        &self.settings.collaboration,
+                        &engine.user_interface,
+                    );
                 }
                 Err(message) => {
                     Log::err(message.clone());
@@ -1355,22 +1715,136 @@ impl Editor {
         }
     }
 
-    fn load_scene(&mut self, scene_path: PathBuf) {
+    fn load_scene(&mut self, scene_path: PathBuf, force: bool) {
+        if !force
+            && !self.collaboration.try_open(
+                &scene_path,
+                &self.settings.collaboration,
+                &self.engine.user_interface,
+            )
+        {
+            return;
+        }
+
         let engine = &mut self.engine;
         let result = {
-            block_on(SceneLoader::from_file(
+            block_on(SceneLoader::from_file_lenient(
                 &scene_path,
                 engine.serialization_context.clone(),
             ))
         };
         match result {
             Ok(loader) => {
+                // Damaged scene is loaded as much as possible, show what was wrong with it.
+                let report = if loader.errors().is_empty() {
+                    None
+                } else {
+                    let mut report = format!(
+                        "Scene {} is damaged, the following errors were encountered while \
+                        loading it (default values were used instead):\n",
+                        scene_path.display()
+                    );
+                    for error in loader.errors() {
+                        Log::err(error.to_string());
+                        report += &format!("\n{}", error);
+                    }
+                    Some(report)
+                };
+
                 let scene = block_on(loader.finish(engine.resource_manager.clone()));
 
                 self.set_scene(scene, Some(scene_path));
+
+                if let Some(report) = report {
+                    self.engine
+                        .user_interface
+                        .send_message(MessageBoxMessage::open(
+                            self.validation_message_box,
+                            MessageDirection::ToWidget,
+                            None,
+                            Some(report),
+                        ));
+                }
+            }
+            Err(e) => {
+                let message = format!(
+                    "Unable to load scene {}. Reason: {}",
+                    scene_path.display(),
+                    e
+                );
+                Log::err(message.clone());
+                self.engine
+                    .user_interface
+                    .send_message(MessageBoxMessage::open(
+                        self.validation_message_box,
+                        MessageDirection::ToWidget,
+                        None,
+                        Some(message),
+                    ));
+            }
+        }
+    }
+
+    /// Reloads the current scene from its file, keeping the camera, the selection and the state
+    /// of the world viewer. Nothing is changed if the file cannot be loaded.
+    fn revert_current_scene(&mut self) -> bool {
+        let (path, view_state) = match self.scene.as_ref() {
+            Some(editor_scene) => match editor_scene.path.clone() {
+                Some(path) => (
+                    path,
+                    ViewState::capture(
+                        editor_scene,
+                        &self.engine.scenes[editor_scene.scene].graph,
+                        &self
+                            .world_viewer
+                            .expanded_nodes(&self.engine.user_interface),
+                    ),
+                ),
+                None => return false,
+            },
+            None => return false,
+        };
+
+        let result = block_on(SceneLoader::from_file(
+            &path,
+            self.engine.serialization_context.clone(),
+        ));
+        match result {
+            Ok(loader) => {
+                let scene = block_on(loader.finish(self.engine.resource_manager.clone()));
+
+                self.command_jump = None;
+                self.clear_scene_command_stack();
+                self.set_scene(scene, Some(path.clone()));
+
+                if let Some(editor_scene) = self.scene.as_mut() {
+                    let graph = &mut self.engine.scenes[editor_scene.scene].graph;
+                    let expanded = view_state.restore(editor_scene, graph);
+                    self.world_viewer.expand_on_sync(expanded);
+                    self.world_viewer.sync_selection = true;
+                }
+
+                Log::info(format!("Scene {} was reverted.", path.display()));
+
+                true
             }
             Err(e) => {
-                Log::err(e.to_string());
+                let message = format!(
+                    "Unable to revert scene {}, it was left untouched. Reason: {}",
+                    path.display(),
+                    e
+                );
+                Log::err(message.clone());
+                self.engine
+                    .user_interface
+                    .send_message(MessageBoxMessage::open(
+                        self.validation_message_box,
+                        MessageDirection::ToWidget,
+                        None,
+                        Some(message),
+                    ));
+
+                false
             }
         }
     }
@@ -1396,6 +1870,8 @@ impl Editor {
         if let Some(editor_scene) = self.scene.take() {
             engine.scenes.remove(editor_scene.scene);
 
+            self.collaboration.on_scene_closed(&engine.user_interface);
+
             // Preview frame has scene frame texture assigned, it must be cleared explicitly,
             // otherwise it will show last rendered frame in preview which is not what we want.
             self.scene_viewer
@@ -1418,6 +1894,40 @@ impl Editor {
         self.set_scene(scene, None);
     }
 
+    fn open_recovered_scene(&mut self, working_directory: PathBuf, scene: RecoveredScene) {
+        if self.scene.is_none() {
+            // The editor could be not configured yet, the configurator is not needed anymore.
+            self.engine
+                .user_interface
+                .send_message(WindowMessage::close(
+                    self.configurator.window,
+                    MessageDirection::ToWidget,
+                ));
+            self.configure(working_directory);
+        } else if std::env::current_dir().ok().as_ref() != Some(&working_directory) {
+            Log::warn(format!(
+                "Recovered scene was made in {}, some of its resources could be missing.",
+                working_directory.display()
+            ));
+        }
+
+        self.load_scene(scene.recovered.clone(), true);
+
+        // The recovered scene must be saved over the original one, not over the recovery copy.
+        if let Some(editor_scene) = self.scene.as_mut() {
+            if editor_scene.path.as_ref() == Some(&scene.recovered) {
+                editor_scene.path = scene.original.clone();
+                editor_scene.has_unsaved_changes = true;
+
+                self.collaboration.on_scene_path_changed(
+                    scene.original.as_deref(),
+                    &self.settings.collaboration,
+                    &self.engine.user_interface,
+                );
+            }
+        }
+    }
+
     fn configure(&mut self, working_directory: PathBuf) {
         let engine = &mut self.engine;
 
@@ -1433,7 +1943,10 @@ impl Editor {
 
         match FileSystemWatcher::new(&working_directory, Duration::from_secs(1)) {
             Ok(watcher) => {
-                engine.resource_manager.state().set_watcher(Some(watcher));
+                let mut resource_manager = engine.resource_manager.state();
+                resource_manager.set_watcher(Some(watcher));
+                self.collaboration
+                    .set_file_changes(resource_manager.subscribe_to_file_changes());
             }
             Err(e) => {
                 Log::err(format!("Unable to create resource watcher. Reason {:?}", e));
@@ -1444,8 +1957,31 @@ impl Editor {
 
         engine.renderer.flush();
 
-        self.asset_browser
-            .set_working_directory(engine, &working_directory);
+        engine.project = match ProjectManifest::load_and_validate(&working_directory) {
+            Ok(project) => project,
+            Err(e) => {
+                let message = format!("Project manifest cannot be used. Reason: {}", e);
+                Log::err(message.clone());
+                engine.user_interface.send_message(MessageBoxMessage::open(
+                    self.validation_message_box,
+                    MessageDirection::ToWidget,
+                    None,
+                    Some(message),
+                ));
+                None
+            }
+        };
+
+        // Show only the data of the project in the asset browser if the project has a manifest.
+        let data_root = engine
+            .project
+            .as_ref()
+            .map(|project| working_directory.join(&project.data_root))
+            .filter(|data_root| data_root.is_dir())
+            .unwrap_or_else(|| working_directory.clone());
+
+        set_texture_cache_root(&data_root);
+        self.asset_browser.set_working_directory(engine, &data_root);
 
         Log::info(format!(
             "New working directory was successfully set: {:?}",
@@ -1497,8 +2033,21 @@ impl Editor {
         scope_profile!();
 
         self.absm_editor.update(&mut self.engine);
+        self.shader_graph_editor.update(&mut self.engine);
+        self.export_window.update(&self.engine.user_interface);
         self.log.update(&mut self.engine);
 
+        if let Some(editor_scene) = self.scene.as_ref() {
+            self.light_panel
+                .update(editor_scene, &mut self.engine, &self.message_sender);
+            self.placement
+                .update(editor_scene, &mut self.engine, &self.message_sender);
+            self.hlod_panel
+                .update(editor_scene, &mut self.engine, &self.message_sender);
+            self.impostor_panel
+                .update(editor_scene, &mut self.engine, &self.message_sender);
+        }
+
         if let Mode::Play { scene, .. } = self.mode {
             self.engine.update_plugins(dt, true);
 
@@ -1515,12 +2064,18 @@ impl Editor {
                 .handle_message(&message, &self.message_sender);
 
             if let Some(editor_scene) = self.scene.as_ref() {
-                self.inspector
-                    .handle_message(&message, editor_scene, &mut self.engine);
+                self.inspector.handle_message(
+                    &message,
+                    editor_scene,
+                    &mut self.engine,
+                    &self.settings.render_layers,
+                );
             }
 
             match message {
                 Message::DoSceneCommand(command) => {
+                    // New command truncates redo tail, so pending jump is no longer valid.
+                    self.command_jump = None;
                     needs_sync |= self.do_scene_command(command);
                 }
                 Message::UndoSceneCommand => {
@@ -1530,16 +2085,34 @@ impl Editor {
                     needs_sync |= self.redo_scene_command();
                 }
                 Message::ClearSceneCommandStack => {
+                    self.command_jump = None;
                     needs_sync |= self.clear_scene_command_stack();
                 }
+                Message::JumpToSceneCommand(position) => {
+                    self.begin_command_jump(position);
+                }
                 Message::SelectionChanged => {
                     self.world_viewer.sync_selection = true;
                 }
                 Message::SaveScene(path) => self.save_current_scene(path),
                 Message::LoadScene(scene_path) => {
-                    self.load_scene(scene_path);
+                    self.load_scene(scene_path, false);
+                    needs_sync = true;
+                }
+                Message::ForceLoadScene(scene_path) => {
+                    self.load_scene(scene_path, true);
+                    needs_sync = true;
+                }
+                Message::OpenRecoveredScene {
+                    working_directory,
+                    scene,
+                } => {
+                    self.open_recovered_scene(working_directory, scene);
                     needs_sync = true;
                 }
+                Message::RevertScene => {
+                    needs_sync |= self.revert_current_scene();
+                }
                 Message::SetInteractionMode(mode_kind) => {
                     self.set_interaction_mode(Some(mode_kind))
                 }
@@ -1563,10 +2136,50 @@ impl Editor {
                     );
                 }
                 Message::OpenMaterialEditor(material) => self.open_material_editor(material),
+                Message::OpenColliderGenerator => {
+                    self.collider_generator.open(&self.engine.user_interface);
+                }
+                Message::OpenBatchRename => {
+                    if let Some(editor_scene) = self.scene.as_ref() {
+                        self.batch_rename.open(editor_scene, &mut self.engine);
+                    }
+                }
+                Message::OpenFindReplace => {
+                    if let Some(editor_scene) = self.scene.as_ref() {
+                        self.find_replace.open(editor_scene, &mut self.engine);
+                    }
+                }
+                Message::OpenPlacementWindow => {
+                    self.placement.open(&self.engine.user_interface);
+                }
+                Message::DropSelectionToFloor => {
+                    if let Some(editor_scene) = self.scene.as_ref() {
+                        self.placement.drop_to_floor(
+                            editor_scene,
+                            &self.engine,
+                            &self.message_sender,
+                        );
+                    }
+                }
+                Message::GenerateHitboxes => {
+                    if let Some(editor_scene) = self.scene.as_ref() {
+                        if let Selection::Graph(selection) = &editor_scene.selection {
+                            let graph = &mut self.engine.scenes[editor_scene.scene].graph;
+                            if let Some(command) =
+                                make_generate_hitboxes_command(selection.nodes(), graph)
+                            {
+                                self.message_sender
+                                    .send(Message::DoSceneCommand(command))
+                                    .unwrap();
+                            }
+                        }
+                    }
+                }
                 Message::ShowInAssetBrowser(path) => {
                     self.asset_browser
                         .locate_path(&self.engine.user_interface, path);
                 }
+                Message::AssetsMoved(moves) => self.on_assets_moved(&moves),
                 Message::SetWorldViewerFilter(filter) => {
                     self.world_viewer
                         .set_filter(filter, &self.engine.user_interface);
@@ -1578,12 +2191,58 @@ impl Editor {
                 Message::SelectObject { type_id, handle } => {
                     self.select_object(type_id, handle);
                 }
-                Message::SetEditorCameraProjection(projection) => {
-                    if let Some(editor_scene) = self.scene.as_ref() {
-                        editor_scene.camera_controller.set_projection(
-                            &mut self.engine.scenes[editor_scene.scene].graph,
-                            projection,
-                        );
+                Message::SetEditorCameraView(view) => {
+                    if let Some(editor_scene) = self.scene.as_mut() {
+                        if editor_scene
+                            .camera_controller
+                            .set_view(&mut self.engine.scenes[editor_scene.scene].graph, view)
+                        {
+                            self.save_viewport_layout();
+                        }
+                    }
+                }
+                Message::SetViewMode(mode) => {
+                    if let Some(editor_scene) = self.scene.as_mut() {
+                        let active = editor_scene.viewports.active();
+                        editor_scene.viewports.set_view_mode(active, mode);
+                    }
+                }
+                Message::ToggleViewportLayout => {
+                    if let Some(editor_scene) = self.scene.as_mut() {
+                        let viewports = &mut editor_scene.viewports;
+                        viewports.set_layout(match viewports.layout() {
+                            ViewportLayout::Single => ViewportLayout::Quad,
+                            ViewportLayout::Quad => ViewportLayout::Single,
+                        });
+                        self.save_viewport_layout();
+                    }
+                }
+                Message::SetRenderScale(render_scale) => {
+                    self.settings.graphics.render_scale =
+                        render_scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE);
+                    if let Err(e) = self.settings.save() {
+                        Log::err(format!("Unable to save settings! Reason: {:?}!", e));
+                    }
+                }
+                Message::SetTransformSpace(space) => {
+                    self.settings.transform_space = space;
+                    if let Err(e) = self.settings.save() {
+                        Log::err(format!("Unable to save settings! Reason: {:?}!", e));
+                    }
+                }
+                Message::IsolateSelection => {
+                    if let Some(editor_scene) = self.scene.as_mut() {
+                        editor_scene.isolate_selection(&mut self.engine);
+                    }
+                }
+                Message::AddSelectionToIsolation => {
+                    if let Some(editor_scene) = self.scene.as_mut() {
+                        editor_scene.add_selection_to_isolation(&mut self.engine);
+                    }
+                }
+                Message::ExitIsolation => {
+                    if let Some(editor_scene) = self.scene.as_mut() {
+                        editor_scene.exit_isolation(&mut self.engine);
                     }
                 }
                 Message::SwitchMode => match self.mode {
@@ -1607,111 +2266,182 @@ impl Editor {
             }
         }
 
+        needs_sync |= self.update_command_jump();
+
+        self.collaboration
+            .update(&self.settings.collaboration, &self.engine.user_interface);
+
         if needs_sync {
             self.sync_to_model();
         }
 
         self.handle_resize();
 
+        // Panes are not used in play mode, the game renders its own cameras.
+        self.scene_viewer.sync_viewports(
+            &self.engine.user_interface,
+            self.scene.as_ref().filter(|_| self.mode.is_edit()),
+        );
+        self.scene_viewer.sync_isolation(
+            &self.engine.user_interface,
+            self.scene.as_ref().filter(|_| self.mode.is_edit()),
+        );
+
         if let Some(editor_scene) = self.scene.as_mut() {
             if self.mode.is_edit() {
                 editor_scene.draw_debug(&mut self.engine, &self.settings.debugging);
+                self.animation_panel.draw_onion_skin(
+                    editor_scene,
+                    &mut self.engine,
+                    &self.settings.onion_skin,
+                );
             }
 
-            let scene = &mut self.engine.scenes[editor_scene.scene];
-
-            let camera = scene.graph[editor_scene.camera_controller.camera].as_camera_mut();
+            let highlighted_nodes = match editor_scene.selection {
+                Selection::Graph(ref selection)
+                    if self.mode.is_edit() && self.settings.debugging.selection_outline =>
+                {
+                    selection.nodes().to_vec()
+                }
+                _ => Vec::new(),
+            };
+            self.engine
+                .renderer
+                .set_highlighted_nodes(editor_scene.scene, highlighted_nodes);
+            editor_scene.viewports.sync_view_modes(
+                &mut self.engine.renderer,
+                editor_scene.scene,
+                &editor_scene.camera_controller,
+            );
 
-            camera
-                .projection_mut()
-                .set_z_near(self.settings.graphics.z_near);
-            camera
-                .projection_mut()
-                .set_z_far(self.settings.graphics.z_far);
+            let graph = &mut self.engine.scenes[editor_scene.scene].graph;
 
-            let graph = &mut scene.graph;
+            for (_, controller) in editor_scene
+                .viewports
+                .controllers_mut(&mut editor_scene.camera_controller)
+            {
+                let camera = graph[controller.camera].as_camera_mut();
+
+                camera
+                    .projection_mut()
+                    .set_z_near(self.settings.graphics.z_near);
+                camera
+                    .projection_mut()
+                    .set_z_far(self.settings.graphics.z_far);
+            }
 
-            editor_scene.camera_controller.update(graph, dt);
+            let frame_size = self
+                .scene_viewer
+                .frame_bounds(&self.engine.user_interface)
+                .size;
+            editor_scene.viewports.update(
+                &mut editor_scene.camera_controller,
+                graph,
+                frame_size,
+                dt,
+            );
 
             if let Some(mode) = self.current_interaction_mode {
                 self.interaction_modes[mode as usize].update(
                     editor_scene,
                     editor_scene.camera_controller.camera,
                     &mut self.engine,
+                    &self.settings,
                 );
             }
 
-            self.asset_browser.update(&mut self.engine);
+            self.asset_browser
+                .update(&mut self.engine, &self.settings.preview);
             self.material_editor.update(&mut self.engine);
         }
     }
 
     pub fn add_game_plugin<P: Plugin>(&mut self, plugin: P) {
         self.engine.add_plugin(plugin, true, false);
+
+        // The plugin could register its own node types, show them in the creation menus.
+        self.menu
+            .on_node_constructors_changed(&mut self.engine.user_interface);
+        self.world_viewer
+            .on_node_constructors_changed(&mut self.engine.user_interface);
     }
 
     pub fn run(mut self, event_loop: EventLoop<()>) -> ! {
         event_loop.run(move |mut event, _, control_flow| {
-            match event {
-                Event::MainEventsCleared => {
-                    update(&mut self);
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                match event {
+                    Event::MainEventsCleared => {
+                        update(&mut self);
 
-                    if self.exit {
-                        *control_flow = ControlFlow::Exit;
-                    }
-                }
-                Event::RedrawRequested(_) => {
-                    self.engine.render().unwrap();
-                }
-                Event::WindowEvent { ref event, .. } => {
-                    match event {
-                        WindowEvent::CloseRequested => {
-                            self.message_sender
-                                .send(Message::Exit { force: false })
-                                .unwrap();
+                        if self.exit {
+                            self.collaboration
+                                .on_scene_closed(&self.engine.user_interface);
+
+                            *control_flow = ControlFlow::Exit;
                         }
-                        WindowEvent::Resized(size) => {
-                            if let Err(e) = self.engine.set_frame_size((*size).into()) {
-                                fyrox::utils::log::Log::writeln(
-                                    MessageKind::Error,
-                                    format!("Failed to set renderer size! Reason: {:?}", e),
-                                );
+                    }
+                    Event::RedrawRequested(_) => {
+                        self.engine.render().unwrap();
+                    }
+                    Event::WindowEvent { ref event, .. } => {
+                        match event {
+                            WindowEvent::CloseRequested => {
+                                self.message_sender
+                                    .send(Message::Exit { force: false })
+                                    .unwrap();
                             }
-                            self.engine
-                                .user_interface
-                                .send_message(WidgetMessage::width(
-                                    self.root_grid,
-                                    MessageDirection::ToWidget,
-                                    size.width as f32,
-                                ));
-                            self.engine
-                                .user_interface
-                                .send_message(WidgetMessage::height(
-                                    self.root_grid,
-                                    MessageDirection::ToWidget,
-                                    size.height as f32,
-                                ));
+                            WindowEvent::Resized(size) => {
+                                if let Err(e) = self.engine.set_frame_size((*size).into()) {
+                                    fyrox::utils::log::Log::writeln(
+                                        MessageKind::Error,
+                                        format!("Failed to set renderer size! Reason: {:?}", e),
+                                    );
+                                }
+                                self.engine
+                                    .user_interface
+                                    .send_message(WidgetMessage::width(
+                                        self.root_grid,
+                                        MessageDirection::ToWidget,
+                                        size.width as f32,
+                                    ));
+                                self.engine
+                                    .user_interface
+                                    .send_message(WidgetMessage::height(
+                                        self.root_grid,
+                                        MessageDirection::ToWidget,
+                                        size.height as f32,
+                                    ));
+                            }
+                            _ => (),
                         }
-                        _ => (),
-                    }
 
-                    if let Some(os_event) = translate_event(event) {
-                        self.engine.user_interface.process_os_event(&os_event);
+                        if let Some(os_event) = translate_event(event) {
+                            self.engine.user_interface.process_os_event(&os_event);
+                        }
                     }
+                    _ => *control_flow = ControlFlow::Poll,
                 }
-                _ => *control_flow = ControlFlow::Poll,
-            }
 
-            if let Mode::Play { scene, .. } = self.mode {
-                let screen_bounds = self.scene_viewer.frame_bounds(&self.engine.user_interface);
+                if let Mode::Play { scene, .. } = self.mode {
+                    let screen_bounds = self.scene_viewer.frame_bounds(&self.engine.user_interface);
 
-                normalize_os_event(&mut event, screen_bounds.position, screen_bounds.size);
+                    normalize_os_event(&mut event, screen_bounds.position, screen_bounds.size);
 
-                self.engine
-                    .handle_os_event_by_plugins(&event, FIXED_TIMESTEP, true);
+                    self.engine
+                        .handle_os_event_by_plugins(&event, FIXED_TIMESTEP, true);
 
-                self.engine
-                    .handle_os_event_by_scripts(&event, scene, FIXED_TIMESTEP);
+                    self.engine
+                        .handle_os_event_by_scripts(&event, scene, FIXED_TIMESTEP);
+                }
+            }));
+
+            if result.is_err() {
+                // The panic is already recorded by the panic hook, try to save user's work before
+                // exit.
+                let scenes = self.scene.iter().collect::<Vec<_>>();
+                let active_scene = self.scene.as_ref().and_then(|s| s.path.as_deref());
+                crash::recover(&scenes, active_scene, &mut self.engine);
+                std::process::exit(1);
             }
         });
     }
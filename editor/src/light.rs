@@ -1,35 +1,159 @@
-use crate::{scene::EditorScene, GameEngine};
+use crate::{
+    scene::{
+        commands::{
+            graph::SetMobilityCommand, lightmap::SetLightmapCommand,
+            reflection_probe::SetReflectionProbeEnvironmentCommand,
+        },
+        EditorScene, Selection,
+    },
+    GameEngine, Message,
+};
 use fyrox::{
     core::{pool::Handle, scope_profile},
     gui::{
         button::{ButtonBuilder, ButtonMessage},
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
         grid::{Column, GridBuilder, Row},
+        list_view::{ListViewBuilder, ListViewMessage},
         message::{MessageDirection, UiMessage},
         numeric::{NumericUpDownBuilder, NumericUpDownMessage},
-        text::TextBuilder,
-        widget::WidgetBuilder,
+        progress_bar::{ProgressBarBuilder, ProgressBarMessage},
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
         window::{WindowBuilder, WindowTitle},
-        Thickness, UiNode, VerticalAlignment,
+        BuildContext, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    scene::{base::Mobility, node::Node, Scene},
+    utils::{
+        lightmap::{
+            CancellationToken, Lightmap, LightmapGenerationError, LightmapInputData,
+            ProgressIndicator, ProgressStage,
+        },
+        log::Log,
     },
-    utils::lightmap::Lightmap,
 };
+use std::{
+    collections::{HashSet, VecDeque},
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+};
+
+// Lightmap is generated in a background thread, the editor stays responsive and the generation
+// could be cancelled at any time.
+struct BakeTask {
+    scene: Handle<Scene>,
+    cancellation_token: CancellationToken,
+    progress_indicator: ProgressIndicator,
+    receiver: Receiver<Result<Lightmap, LightmapGenerationError>>,
+}
+
+#[derive(PartialEq)]
+struct LightEntry {
+    handle: Handle<Node>,
+    name: String,
+    baked: bool,
+}
 
 pub struct LightPanel {
     pub window: Handle<UiNode>,
     nud_texels_per_unit: Handle<UiNode>,
     nud_spacing: Handle<UiNode>,
+    lights_list: Handle<UiNode>,
+    stats_text: Handle<UiNode>,
     generate: Handle<UiNode>,
+    cancel: Handle<UiNode>,
+    clear: Handle<UiNode>,
+    bake_progress_bar: Handle<UiNode>,
+    bake_progress_text: Handle<UiNode>,
+    capture_all_probes: Handle<UiNode>,
+    capture_selected_probes: Handle<UiNode>,
+    capture_progress_bar: Handle<UiNode>,
+    capture_progress_text: Handle<UiNode>,
     texels_per_unit: u32,
     spacing: f32,
+    lights: Vec<LightEntry>,
+    // Check box per light, in the same order as `lights`.
+    light_check_boxes: Vec<Handle<UiNode>>,
+    static_mesh_count: usize,
+    bake_task: Option<BakeTask>,
+    // Reflection probes are captured one per frame, so the editor stays responsive and
+    // the progress could be shown.
+    capture_queue: VecDeque<Handle<Node>>,
+    capture_total: usize,
+}
+
+fn make_button(text: &str, row: usize, ctx: &mut BuildContext) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(1)
+            .with_margin(Thickness::uniform(1.0)),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+fn make_progress_bar(row: usize, ctx: &mut BuildContext) -> Handle<UiNode> {
+    ProgressBarBuilder::new(
+        WidgetBuilder::new()
+            .with_visibility(false)
+            .on_row(row)
+            .on_column(1)
+            .with_margin(Thickness::uniform(1.0)),
+    )
+    .build(ctx)
+}
+
+fn make_text(row: usize, ctx: &mut BuildContext) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(1)
+            .with_margin(Thickness::uniform(1.0)),
+    )
+    .build(ctx)
+}
+
+fn stage_name(stage: ProgressStage) -> &'static str {
+    match stage {
+        ProgressStage::LightsCaching => "Caching Lights",
+        ProgressStage::UvGeneration => "Generating UVs",
+        ProgressStage::GeometryCaching => "Caching Geometry",
+        ProgressStage::CalculatingLight => "Calculating Light",
+    }
+}
+
+// Lightmap textures are saved next to the scene, so they could be shipped with it.
+fn lightmap_folder(editor_scene: &EditorScene) -> PathBuf {
+    match editor_scene.path.as_ref() {
+        Some(path) => {
+            let stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            path.with_file_name(format!("{}_lightmaps", stem))
+        }
+        None => PathBuf::from("lightmaps"),
+    }
 }
 
 impl LightPanel {
     pub fn new(engine: &mut GameEngine) -> Self {
         let generate;
+        let cancel;
+        let clear;
+        let bake_progress_bar;
+        let bake_progress_text;
+        let lights_list;
+        let stats_text;
+        let capture_all_probes;
+        let capture_selected_probes;
+        let capture_progress_bar;
+        let capture_progress_text;
         let nud_texels_per_unit;
         let nud_spacing;
         let ctx = &mut engine.user_interface.build_ctx();
-        let window = WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(400.0))
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(300.0).with_height(550.0))
             .with_title(WindowTitle::Text("Light Settings".to_owned()))
             .open(false)
             .with_content(
@@ -85,24 +209,93 @@ impl LightPanel {
                             .build(ctx);
                             nud_spacing
                         })
+                        .with_child(
+                            TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .on_column(0)
+                                    .with_margin(Thickness::uniform(1.0))
+                                    .with_vertical_alignment(VerticalAlignment::Top),
+                            )
+                            .with_text("Baked Lights")
+                            .build(ctx),
+                        )
                         .with_child({
-                            generate = ButtonBuilder::new(
+                            lights_list = ListViewBuilder::new(
                                 WidgetBuilder::new()
                                     .on_row(2)
                                     .on_column(1)
                                     .with_margin(Thickness::uniform(1.0)),
                             )
-                            .with_text("Generate Lightmap")
                             .build(ctx);
+                            lights_list
+                        })
+                        .with_child({
+                            stats_text = make_text(3, ctx);
+                            stats_text
+                        })
+                        .with_child({
+                            generate = make_button("Generate Lightmap", 4, ctx);
                             generate
+                        })
+                        .with_child({
+                            cancel = ButtonBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_enabled(false)
+                                    .on_row(5)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_text("Cancel Generation")
+                            .build(ctx);
+                            cancel
+                        })
+                        .with_child({
+                            clear = make_button("Clear Lightmap", 6, ctx);
+                            clear
+                        })
+                        .with_child({
+                            bake_progress_bar = make_progress_bar(7, ctx);
+                            bake_progress_bar
+                        })
+                        .with_child({
+                            bake_progress_text = make_text(8, ctx);
+                            bake_progress_text
+                        })
+                        .with_child({
+                            capture_all_probes =
+                                make_button("Capture All Reflection Probes", 9, ctx);
+                            capture_all_probes
+                        })
+                        .with_child({
+                            capture_selected_probes =
+                                make_button("Re-capture Selected Probes", 10, ctx);
+                            capture_selected_probes
+                        })
+                        .with_child({
+                            capture_progress_bar = make_progress_bar(11, ctx);
+                            capture_progress_bar
+                        })
+                        .with_child({
+                            capture_progress_text = make_text(12, ctx);
+                            capture_progress_text
                         }),
                 )
                 .add_column(Column::strict(100.0))
                 .add_column(Column::stretch())
                 .add_row(Row::strict(25.0))
                 .add_row(Row::strict(25.0))
-                .add_row(Row::strict(25.0))
                 .add_row(Row::stretch())
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(16.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(16.0))
+                .add_row(Row::strict(25.0))
                 .build(ctx),
             )
             .build(ctx);
@@ -110,10 +303,26 @@ impl LightPanel {
         Self {
             window,
             generate,
+            cancel,
+            clear,
+            bake_progress_bar,
+            bake_progress_text,
+            lights_list,
+            stats_text,
+            capture_all_probes,
+            capture_selected_probes,
+            capture_progress_bar,
+            capture_progress_text,
             nud_texels_per_unit,
             texels_per_unit: 128,
             nud_spacing,
             spacing: 0.02,
+            lights: Default::default(),
+            light_check_boxes: Default::default(),
+            static_mesh_count: 0,
+            bake_task: None,
+            capture_queue: Default::default(),
+            capture_total: 0,
         }
     }
 
@@ -122,24 +331,42 @@ impl LightPanel {
         message: &UiMessage,
         editor_scene: &EditorScene,
         engine: &mut GameEngine,
+        sender: &Sender<Message>,
     ) {
         scope_profile!();
 
         if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
             if message.destination() == self.generate {
-                let scene = &mut engine.scenes[editor_scene.scene];
-
-                let lightmap = Lightmap::new(
-                    scene,
-                    self.texels_per_unit,
-                    Default::default(),
-                    Default::default(),
-                )
-                .unwrap();
-                lightmap
-                    .save("./", engine.resource_manager.clone())
-                    .unwrap();
-                scene.set_lightmap(lightmap).unwrap();
+                self.start_bake(editor_scene, engine);
+            } else if message.destination() == self.cancel {
+                if let Some(bake_task) = self.bake_task.as_ref() {
+                    bake_task.cancellation_token.cancel();
+                }
+            } else if message.destination() == self.clear {
+                if engine.scenes[editor_scene.scene].lightmap().is_some() {
+                    sender
+                        .send(Message::do_scene_command(SetLightmapCommand::new(None)))
+                        .unwrap();
+                }
+            } else if message.destination() == self.capture_all_probes {
+                let graph = &engine.scenes[editor_scene.scene].graph;
+                let probes = graph
+                    .pair_iter()
+                    .filter(|(_, node)| node.is_reflection_probe())
+                    .map(|(handle, _)| handle)
+                    .collect();
+                self.enqueue_probes(probes, &engine.user_interface);
+            } else if message.destination() == self.capture_selected_probes {
+                if let Selection::Graph(selection) = &editor_scene.selection {
+                    let graph = &engine.scenes[editor_scene.scene].graph;
+                    let probes = selection
+                        .nodes()
+                        .iter()
+                        .cloned()
+                        .filter(|&handle| graph[handle].is_reflection_probe())
+                        .collect();
+                    self.enqueue_probes(probes, &engine.user_interface);
+                }
             }
         } else if let Some(&NumericUpDownMessage::Value(value)) =
             message.data::<NumericUpDownMessage<f32>>()
@@ -151,6 +378,372 @@ impl LightPanel {
                     self.spacing = value;
                 }
             }
+        } else if let Some(&CheckBoxMessage::Check(Some(baked))) = message.data::<CheckBoxMessage>()
+        {
+            if message.direction() == MessageDirection::FromWidget {
+                if let Some(light) = self
+                    .light_check_boxes
+                    .iter()
+                    .position(|&check_box| check_box == message.destination())
+                    .map(|i| &self.lights[i])
+                {
+                    if light.baked != baked {
+                        let mobility = if baked {
+                            Mobility::Static
+                        } else {
+                            Mobility::Dynamic
+                        };
+                        sender
+                            .send(Message::do_scene_command(SetMobilityCommand::new(
+                                light.handle,
+                                mobility,
+                            )))
+                            .unwrap();
+                    }
+                }
+            }
+        }
+    }
+
+    fn start_bake(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        if self.bake_task.is_some() {
+            return;
+        }
+
+        let scene = &mut engine.scenes[editor_scene.scene];
+
+        // Editor objects must not affect the lightmap.
+        let editor_objects = scene
+            .graph
+            .traverse_handle_iter(editor_scene.editor_objects_root)
+            .collect::<HashSet<_>>();
+
+        let cancellation_token = CancellationToken::new();
+        let progress_indicator = ProgressIndicator::new();
+
+        // Snapshot is made on the main thread, everything else is done in background.
+        let input = match LightmapInputData::from_scene(
+            scene,
+            |handle, _| !editor_objects.contains(&handle),
+            cancellation_token.clone(),
+            progress_indicator.clone(),
+        ) {
+            Ok(input) => input,
+            Err(e) => {
+                Log::err(format!("Unable to generate lightmap. Reason: {}", e));
+                return;
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let texels_per_unit = self.texels_per_unit;
+        let spacing = self.spacing;
+        let thread_cancellation_token = cancellation_token.clone();
+        let thread_progress_indicator = progress_indicator.clone();
+        std::thread::spawn(move || {
+            let result = Lightmap::from_input(
+                input,
+                texels_per_unit,
+                spacing,
+                thread_cancellation_token,
+                thread_progress_indicator,
+            );
+            // The editor might be closed already.
+            let _ = sender.send(result);
+        });
+
+        self.bake_task = Some(BakeTask {
+            scene: editor_scene.scene,
+            cancellation_token,
+            progress_indicator,
+            receiver,
+        });
+
+        self.sync_bake_progress(&engine.user_interface);
+    }
+
+    fn sync_bake_progress(&self, ui: &UserInterface) {
+        let in_progress = self.bake_task.is_some();
+
+        ui.send_message(WidgetMessage::visibility(
+            self.bake_progress_bar,
+            MessageDirection::ToWidget,
+            in_progress,
+        ));
+        ui.send_message(WidgetMessage::enabled(
+            self.generate,
+            MessageDirection::ToWidget,
+            !in_progress,
+        ));
+        ui.send_message(WidgetMessage::enabled(
+            self.cancel,
+            MessageDirection::ToWidget,
+            in_progress,
+        ));
+
+        let text = if let Some(bake_task) = self.bake_task.as_ref() {
+            let progress = &bake_task.progress_indicator;
+
+            ui.send_message(ProgressBarMessage::progress(
+                self.bake_progress_bar,
+                MessageDirection::ToWidget,
+                progress.progress_percent() as f32 / 100.0,
+            ));
+
+            if bake_task.cancellation_token.is_cancelled() {
+                "Cancelling...".to_owned()
+            } else {
+                format!(
+                    "{}: {}%",
+                    stage_name(progress.stage()),
+                    progress.progress_percent()
+                )
+            }
+        } else {
+            Default::default()
+        };
+
+        ui.send_message(TextMessage::text(
+            self.bake_progress_text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+
+    fn update_bake(
+        &mut self,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
+        let bake_task = match self.bake_task.as_ref() {
+            Some(bake_task) => bake_task,
+            None => return,
+        };
+
+        let result = match bake_task.receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => {
+                self.sync_bake_progress(&engine.user_interface);
+                return;
+            }
+            Err(TryRecvError::Disconnected) => {
+                Log::err("Lightmap generation thread has crashed!".to_owned());
+                self.bake_task = None;
+                self.sync_bake_progress(&engine.user_interface);
+                return;
+            }
+        };
+
+        let scene = bake_task.scene;
+        self.bake_task = None;
+        self.sync_bake_progress(&engine.user_interface);
+
+        match result {
+            Ok(lightmap) => {
+                if scene != editor_scene.scene {
+                    Log::warn(
+                        "Lightmap was discarded, because the scene was closed during generation."
+                            .to_owned(),
+                    );
+                    return;
+                }
+
+                let folder = lightmap_folder(editor_scene);
+                match lightmap.save(&folder, engine.resource_manager.clone()) {
+                    Ok(_) => sender
+                        .send(Message::do_scene_command(SetLightmapCommand::new(Some(
+                            lightmap,
+                        ))))
+                        .unwrap(),
+                    Err(e) => Log::err(format!(
+                        "Unable to save lightmap textures to {}. Reason: {}",
+                        folder.display(),
+                        e
+                    )),
+                }
+            }
+            Err(LightmapGenerationError::Cancelled) => {
+                Log::info("Lightmap generation was cancelled.".to_owned())
+            }
+            Err(e) => Log::err(format!("Unable to generate lightmap. Reason: {}", e)),
+        }
+    }
+
+    // Keeps the list of lights in sync with the scene.
+    fn sync_lights(&mut self, editor_scene: &EditorScene, engine: &mut GameEngine) {
+        let graph = &engine.scenes[editor_scene.scene].graph;
+        let ui = &mut engine.user_interface;
+
+        let mut lights = Vec::new();
+        let mut static_mesh_count = 0;
+        for (handle, node) in graph.pair_iter() {
+            if node.is_point_light() || node.is_spot_light() || node.is_directional_light() {
+                lights.push(LightEntry {
+                    handle,
+                    name: node.name_owned(),
+                    baked: node.mobility() == Mobility::Static,
+                });
+            } else if node.is_mesh() && node.mobility() != Mobility::Dynamic {
+                static_mesh_count += 1;
+            }
+        }
+
+        if static_mesh_count != self.static_mesh_count || lights.len() != self.lights.len() {
+            self.static_mesh_count = static_mesh_count;
+            ui.send_message(TextMessage::text(
+                self.stats_text,
+                MessageDirection::ToWidget,
+                format!(
+                    "{} static mesh(es), {} light(s)",
+                    static_mesh_count,
+                    lights.len()
+                ),
+            ));
+        }
+
+        if lights == self.lights {
+            return;
+        }
+
+        let same_lights = lights.len() == self.lights.len()
+            && lights
+                .iter()
+                .zip(self.lights.iter())
+                .all(|(a, b)| a.handle == b.handle && a.name == b.name);
+
+        if same_lights {
+            for ((light, old_light), &check_box) in lights
+                .iter()
+                .zip(self.lights.iter())
+                .zip(self.light_check_boxes.iter())
+            {
+                if light.baked != old_light.baked {
+                    ui.send_message(CheckBoxMessage::checked(
+                        check_box,
+                        MessageDirection::ToWidget,
+                        Some(light.baked),
+                    ));
+                }
+            }
+        } else {
+            let ctx = &mut ui.build_ctx();
+            self.light_check_boxes = lights
+                .iter()
+                .map(|light| {
+                    CheckBoxBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(1.0)))
+                        .checked(Some(light.baked))
+                        .with_content(
+                            TextBuilder::new(
+                                WidgetBuilder::new().with_margin(Thickness::left(2.0)),
+                            )
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .with_text(&light.name)
+                            .build(ctx),
+                        )
+                        .build(ctx)
+                })
+                .collect();
+            ui.send_message(ListViewMessage::items(
+                self.lights_list,
+                MessageDirection::ToWidget,
+                self.light_check_boxes.clone(),
+            ));
+        }
+
+        self.lights = lights;
+    }
+
+    fn enqueue_probes(&mut self, probes: Vec<Handle<Node>>, ui: &UserInterface) {
+        for probe in probes {
+            if !self.capture_queue.contains(&probe) {
+                self.capture_queue.push_back(probe);
+                self.capture_total += 1;
+            }
+        }
+
+        self.sync_capture_progress(ui);
+    }
+
+    fn sync_capture_progress(&self, ui: &UserInterface) {
+        let in_progress = !self.capture_queue.is_empty();
+
+        ui.send_message(WidgetMessage::visibility(
+            self.capture_progress_bar,
+            MessageDirection::ToWidget,
+            in_progress,
+        ));
+
+        let text = if in_progress {
+            let captured = self.capture_total - self.capture_queue.len();
+
+            ui.send_message(ProgressBarMessage::progress(
+                self.capture_progress_bar,
+                MessageDirection::ToWidget,
+                captured as f32 / self.capture_total as f32,
+            ));
+
+            format!(
+                "Capturing reflection probes: {}/{}",
+                captured, self.capture_total
+            )
+        } else {
+            Default::default()
+        };
+
+        ui.send_message(TextMessage::text(
+            self.capture_progress_text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+
+    /// Tracks lightmap generation and captures next reflection probe in the queue (if any).
+    pub fn update(
+        &mut self,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
+        self.sync_lights(editor_scene, engine);
+        self.update_bake(editor_scene, engine, sender);
+
+        if let Some(probe) = self.capture_queue.pop_front() {
+            let scene = &mut engine.scenes[editor_scene.scene];
+
+            if scene
+                .graph
+                .try_get(probe)
+                .map_or(false, |node| node.is_reflection_probe())
+            {
+                // Editor objects must not be visible in reflections.
+                let editor_objects_root = editor_scene.editor_objects_root;
+                scene.graph[editor_objects_root].set_visibility(false);
+                scene.graph.update_hierarchical_data();
+
+                let result = engine.renderer.capture_reflection_probe(scene, probe);
+
+                scene.graph[editor_objects_root].set_visibility(true);
+                scene.graph.update_hierarchical_data();
+
+                match result {
+                    Ok(environment) => sender
+                        .send(Message::do_scene_command(
+                            SetReflectionProbeEnvironmentCommand::new(probe, Some(environment)),
+                        ))
+                        .unwrap(),
+                    Err(e) => Log::err(format!(
+                        "Failed to capture reflection probe {}. Reason: {:?}",
+                        probe, e
+                    )),
+                }
+            }
+
+            if self.capture_queue.is_empty() {
+                self.capture_total = 0;
+            }
+
+            self.sync_capture_progress(&engine.user_interface);
         }
     }
 }
@@ -1,42 +1,23 @@
 use crate::{
-    create_terrain_layer_material,
-    menu::{
-        create_menu_item, create_root_menu_item, dim2::Dim2Menu, physics::PhysicsMenu,
-        physics2d::Physics2dMenu,
-    },
-    scene::commands::graph::AddNodeCommand,
-    Message, Mode,
+    load_image, menu::create_root_menu_item, scene::commands::graph::AddNodeCommand, Message, Mode,
 };
-use fyrox::gui::message::MessageDirection;
-use fyrox::gui::widget::WidgetMessage;
-use fyrox::gui::UserInterface;
-use fyrox::scene::pivot::PivotBuilder;
 use fyrox::{
-    core::{algebra::Matrix4, parking_lot::Mutex, pool::Handle},
-    gui::{menu::MenuItemMessage, message::UiMessage, BuildContext, UiNode},
-    scene::{
-        base::BaseBuilder,
-        camera::CameraBuilder,
-        decal::DecalBuilder,
-        light::{
-            directional::DirectionalLightBuilder, point::PointLightBuilder, spot::SpotLightBuilder,
-            BaseLightBuilder,
-        },
-        mesh::{
-            surface::{Surface, SurfaceData},
-            MeshBuilder,
-        },
-        node::Node,
-        particle_system::{
-            emitter::{base::BaseEmitterBuilder, sphere::SphereEmitterBuilder},
-            ParticleSystemBuilder,
-        },
-        sound::{listener::ListenerBuilder, SoundBuilder},
-        sprite::SpriteBuilder,
-        terrain::{LayerDefinition, TerrainBuilder},
+    core::{algebra::Vector2, pool::Handle, uuid::Uuid},
+    engine::SerializationContext,
+    gui::{
+        image::ImageBuilder,
+        menu::{MenuItemBuilder, MenuItemContent, MenuItemMessage},
+        message::{MessageDirection, UiMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, UiNode, UserInterface,
     },
+    scene::node::Node,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::{mpsc::Sender, Arc},
 };
-use std::sync::{mpsc::Sender, Arc};
 
 pub struct CreateEntityRootMenu {
     pub menu: Handle<UiNode>,
@@ -44,8 +25,8 @@ pub struct CreateEntityRootMenu {
 }
 
 impl CreateEntityRootMenu {
-    pub fn new(ctx: &mut BuildContext) -> Self {
-        let (sub_menus, root_items) = CreateEntityMenu::new(ctx);
+    pub fn new(serialization_context: Arc<SerializationContext>, ctx: &mut BuildContext) -> Self {
+        let (sub_menus, root_items) = CreateEntityMenu::new(serialization_context, ctx);
 
         let menu = create_root_menu_item("Create", root_items, ctx);
 
@@ -68,165 +49,148 @@ impl CreateEntityRootMenu {
             mode.is_edit(),
         ));
     }
+
+    /// Re-creates menu items, must be called when the set of node constructors has changed.
+    pub fn rebuild(&mut self, ui: &mut UserInterface) {
+        self.sub_menus.rebuild(self.menu, ui);
+    }
 }
 
+struct MenuEntry {
+    type_uuid: Uuid,
+    variant: Option<usize>,
+    name: String,
+}
+
+/// Creation menu that is built from node constructors registered in the serialization context,
+/// so every node type registered by the engine or a plugin is available to a user.
 pub struct CreateEntityMenu {
-    create_pivot: Handle<UiNode>,
-    create_cube: Handle<UiNode>,
-    create_cone: Handle<UiNode>,
-    create_sphere: Handle<UiNode>,
-    create_cylinder: Handle<UiNode>,
-    create_quad: Handle<UiNode>,
-    create_decal: Handle<UiNode>,
-    create_point_light: Handle<UiNode>,
-    create_spot_light: Handle<UiNode>,
-    create_directional_light: Handle<UiNode>,
-    create_terrain: Handle<UiNode>,
-    create_camera: Handle<UiNode>,
-    create_sprite: Handle<UiNode>,
-    create_particle_system: Handle<UiNode>,
-    create_listener: Handle<UiNode>,
-    create_sound_source: Handle<UiNode>,
-    physics_menu: PhysicsMenu,
-    physics2d_menu: Physics2dMenu,
-    dim2_menu: Dim2Menu,
+    serialization_context: Arc<SerializationContext>,
+    entries: HashMap<Handle<UiNode>, MenuEntry>,
+}
+
+struct ItemDesc {
+    name: String,
+    icon: Option<PathBuf>,
+    type_uuid: Uuid,
+    variant: Option<usize>,
 }
 
 impl CreateEntityMenu {
-    pub fn new(ctx: &mut BuildContext) -> (Self, Vec<Handle<UiNode>>) {
-        let create_cube;
-        let create_cone;
-        let create_sphere;
-        let create_cylinder;
-        let create_quad;
-        let create_point_light;
-        let create_spot_light;
-        let create_directional_light;
-        let create_camera;
-        let create_sprite;
-        let create_decal;
-        let create_particle_system;
-        let create_terrain;
-        let create_pivot;
-        let create_sound_source;
-        let create_listener;
-        let physics_menu = PhysicsMenu::new(ctx);
-        let physics2d_menu = Physics2dMenu::new(ctx);
-        let dim2_menu = Dim2Menu::new(ctx);
+    pub fn new(
+        serialization_context: Arc<SerializationContext>,
+        ctx: &mut BuildContext,
+    ) -> (Self, Vec<Handle<UiNode>>) {
+        let mut menu = Self {
+            serialization_context,
+            entries: Default::default(),
+        };
 
-        let items = vec![
-            {
-                create_pivot = create_menu_item("Pivot", vec![], ctx);
-                create_pivot
-            },
-            create_menu_item(
-                "Mesh",
-                vec![
-                    {
-                        create_cube = create_menu_item("Cube", vec![], ctx);
-                        create_cube
-                    },
-                    {
-                        create_sphere = create_menu_item("Sphere", vec![], ctx);
-                        create_sphere
-                    },
-                    {
-                        create_cylinder = create_menu_item("Cylinder", vec![], ctx);
-                        create_cylinder
-                    },
-                    {
-                        create_cone = create_menu_item("Cone", vec![], ctx);
-                        create_cone
-                    },
-                    {
-                        create_quad = create_menu_item("Quad", vec![], ctx);
-                        create_quad
-                    },
-                ],
-                ctx,
-            ),
-            create_menu_item(
-                "Sound",
-                vec![
-                    {
-                        create_sound_source = create_menu_item("Source", vec![], ctx);
-                        create_sound_source
-                    },
-                    {
-                        create_listener = create_menu_item("Listener", vec![], ctx);
-                        create_listener
-                    },
-                ],
-                ctx,
-            ),
-            create_menu_item(
-                "Light",
-                vec![
-                    {
-                        create_directional_light =
-                            create_menu_item("Directional Light", vec![], ctx);
-                        create_directional_light
-                    },
-                    {
-                        create_spot_light = create_menu_item("Spot Light", vec![], ctx);
-                        create_spot_light
-                    },
-                    {
-                        create_point_light = create_menu_item("Point Light", vec![], ctx);
-                        create_point_light
+        let items = menu.build_items(ctx);
+
+        (menu, items)
+    }
+
+    fn build_items(&mut self, ctx: &mut BuildContext) -> Vec<Handle<UiNode>> {
+        self.entries.clear();
+
+        // Group everything by categories first, BTreeMap keeps them sorted by name.
+        let mut categories = BTreeMap::<String, Vec<ItemDesc>>::new();
+        for (type_uuid, constructor) in self.serialization_context.node_constructors.map().iter() {
+            let category = categories.entry(constructor.category.clone()).or_default();
+
+            if constructor.variants.is_empty() {
+                category.push(ItemDesc {
+                    name: constructor.name.clone(),
+                    icon: constructor.icon.clone(),
+                    type_uuid: *type_uuid,
+                    variant: None,
+                });
+            } else {
+                for (index, variant) in constructor.variants.iter().enumerate() {
+                    category.push(ItemDesc {
+                        name: variant.name.clone(),
+                        icon: constructor.icon.clone(),
+                        type_uuid: *type_uuid,
+                        variant: Some(index),
+                    });
+                }
+            }
+        }
+
+        let root_items = categories.remove("").unwrap_or_default();
+
+        let mut items = categories
+            .into_iter()
+            .map(|(category, descs)| {
+                let sub_items = self.build_category(descs, ctx);
+                MenuItemBuilder::new(WidgetBuilder::new().with_min_size(Vector2::new(120.0, 22.0)))
+                    .with_content(MenuItemContent::text(&category))
+                    .with_items(sub_items)
+                    .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        items.extend(self.build_category(root_items, ctx));
+
+        items
+    }
+
+    fn build_category(
+        &mut self,
+        mut descs: Vec<ItemDesc>,
+        ctx: &mut BuildContext,
+    ) -> Vec<Handle<UiNode>> {
+        descs.sort_by(|a, b| a.name.cmp(&b.name));
+
+        descs
+            .into_iter()
+            .map(|desc| {
+                let icon = desc
+                    .icon
+                    .and_then(|path| std::fs::read(path).ok())
+                    .and_then(|data| load_image(&data))
+                    .map(|texture| {
+                        ImageBuilder::new(WidgetBuilder::new().with_width(16.0).with_height(16.0))
+                            .with_texture(texture)
+                            .build(ctx)
+                    })
+                    .unwrap_or_default();
+
+                let item = MenuItemBuilder::new(
+                    WidgetBuilder::new().with_min_size(Vector2::new(120.0, 22.0)),
+                )
+                .with_content(MenuItemContent::Text {
+                    text: &desc.name,
+                    shortcut: "",
+                    icon,
+                    arrow: true,
+                })
+                .build(ctx);
+
+                self.entries.insert(
+                    item,
+                    MenuEntry {
+                        type_uuid: desc.type_uuid,
+                        variant: desc.variant,
+                        name: desc.name,
                     },
-                ],
-                ctx,
-            ),
-            physics_menu.menu,
-            physics2d_menu.menu,
-            dim2_menu.menu,
-            {
-                create_camera = create_menu_item("Camera", vec![], ctx);
-                create_camera
-            },
-            {
-                create_sprite = create_menu_item("Sprite", vec![], ctx);
-                create_sprite
-            },
-            {
-                create_particle_system = create_menu_item("Particle System", vec![], ctx);
-                create_particle_system
-            },
-            {
-                create_terrain = create_menu_item("Terrain", vec![], ctx);
-                create_terrain
-            },
-            {
-                create_decal = create_menu_item("Decal", vec![], ctx);
-                create_decal
-            },
-        ];
+                );
+
+                item
+            })
+            .collect()
+    }
+
+    /// Re-creates items of the given menu item using current set of node constructors.
+    pub fn rebuild(&mut self, menu_item: Handle<UiNode>, ui: &mut UserInterface) {
+        let items = self.build_items(&mut ui.build_ctx());
 
-        (
-            Self {
-                create_cube,
-                create_cone,
-                create_sphere,
-                create_cylinder,
-                create_quad,
-                create_point_light,
-                create_spot_light,
-                create_directional_light,
-                create_camera,
-                create_sprite,
-                create_particle_system,
-                create_pivot,
-                create_terrain,
-                create_sound_source,
-                create_listener,
-                create_decal,
-                physics_menu,
-                physics2d_menu,
-                dim2_menu,
-            },
+        ui.send_message(MenuItemMessage::items(
+            menu_item,
+            MessageDirection::ToWidget,
             items,
-        )
+        ));
     }
 
     pub fn handle_ui_message(
@@ -235,123 +199,27 @@ impl CreateEntityMenu {
         sender: &Sender<Message>,
         parent: Handle<Node>,
     ) {
-        self.physics_menu.handle_ui_message(message, sender, parent);
-        self.physics2d_menu
-            .handle_ui_message(message, sender, parent);
-        self.dim2_menu.handle_ui_message(message, sender, parent);
-
         if let Some(MenuItemMessage::Click) = message.data::<MenuItemMessage>() {
-            let node = if message.destination() == self.create_cube {
-                Some(
-                    MeshBuilder::new(BaseBuilder::new().with_name("Cube"))
-                        .with_surfaces(vec![Surface::new(Arc::new(Mutex::new(
-                            SurfaceData::make_cube(Matrix4::identity()),
-                        )))])
-                        .build_node(),
-                )
-            } else if message.destination() == self.create_spot_light {
-                Some(
-                    SpotLightBuilder::new(BaseLightBuilder::new(
-                        BaseBuilder::new().with_name("SpotLight"),
-                    ))
-                    .with_distance(10.0)
-                    .with_hotspot_cone_angle(45.0f32.to_radians())
-                    .with_falloff_angle_delta(2.0f32.to_radians())
-                    .build_node(),
-                )
-            } else if message.destination() == self.create_pivot {
-                Some(PivotBuilder::new(BaseBuilder::new().with_name("Pivot")).build_node())
-            } else if message.destination() == self.create_point_light {
-                Some(
-                    PointLightBuilder::new(BaseLightBuilder::new(
-                        BaseBuilder::new().with_name("PointLight"),
-                    ))
-                    .with_radius(10.0)
-                    .build_node(),
-                )
-            } else if message.destination() == self.create_directional_light {
-                Some(
-                    DirectionalLightBuilder::new(BaseLightBuilder::new(
-                        BaseBuilder::new().with_name("DirectionalLight"),
-                    ))
-                    .build_node(),
-                )
-            } else if message.destination() == self.create_cone {
-                Some(
-                    MeshBuilder::new(BaseBuilder::new().with_name("Cone"))
-                        .with_surfaces(vec![Surface::new(Arc::new(Mutex::new(
-                            SurfaceData::make_cone(16, 0.5, 1.0, &Matrix4::identity()),
-                        )))])
-                        .build_node(),
-                )
-            } else if message.destination() == self.create_cylinder {
-                Some(
-                    MeshBuilder::new(BaseBuilder::new().with_name("Cylinder"))
-                        .with_surfaces(vec![Surface::new(Arc::new(Mutex::new(
-                            SurfaceData::make_cylinder(16, 0.5, 1.0, true, &Matrix4::identity()),
-                        )))])
-                        .build_node(),
-                )
-            } else if message.destination() == self.create_sphere {
-                Some(
-                    MeshBuilder::new(BaseBuilder::new().with_name("Sphere"))
-                        .with_surfaces(vec![Surface::new(Arc::new(Mutex::new(
-                            SurfaceData::make_sphere(16, 16, 0.5, &Matrix4::identity()),
-                        )))])
-                        .build_node(),
-                )
-            } else if message.destination() == self.create_quad {
-                Some(
-                    MeshBuilder::new(BaseBuilder::new().with_name("Quad"))
-                        .with_surfaces(vec![Surface::new(Arc::new(Mutex::new(
-                            SurfaceData::make_quad(&Matrix4::identity()),
-                        )))])
-                        .build_node(),
-                )
-            } else if message.destination() == self.create_camera {
-                Some(
-                    CameraBuilder::new(BaseBuilder::new().with_name("Camera"))
-                        .enabled(false)
-                        .build_node(),
-                )
-            } else if message.destination() == self.create_sprite {
-                Some(SpriteBuilder::new(BaseBuilder::new().with_name("Sprite")).build_node())
-            } else if message.destination() == self.create_sound_source {
-                Some(SoundBuilder::new(BaseBuilder::new().with_name("Sound")).build_node())
-            } else if message.destination() == self.create_particle_system {
-                Some(
-                    ParticleSystemBuilder::new(BaseBuilder::new().with_name("ParticleSystem"))
-                        .with_emitters(vec![SphereEmitterBuilder::new(
-                            BaseEmitterBuilder::new()
-                                .with_max_particles(100)
-                                .resurrect_particles(true),
-                        )
-                        .with_radius(1.0)
-                        .build()])
-                        .build_node(),
-                )
-            } else if message.destination() == self.create_terrain {
-                Some(
-                    TerrainBuilder::new(BaseBuilder::new().with_name("Terrain"))
-                        .with_layers(vec![LayerDefinition {
-                            material: create_terrain_layer_material(),
-                            mask_property_name: "maskTexture".to_owned(),
-                        }])
-                        .with_height_map_resolution(4.0)
-                        .build_node(),
-                )
-            } else if message.destination() == self.create_decal {
-                Some(DecalBuilder::new(BaseBuilder::new().with_name("Decal")).build_node())
-            } else if message.destination() == self.create_listener {
-                Some(ListenerBuilder::new(BaseBuilder::new().with_name("Listener")).build_node())
-            } else {
-                None
-            };
+            if let Some(entry) = self.entries.get(&message.destination()) {
+                let node = self
+                    .serialization_context
+                    .node_constructors
+                    .map()
+                    .get_mut(&entry.type_uuid)
+                    .and_then(|constructor| match entry.variant {
+                        Some(variant) => constructor.create_variant(variant),
+                        None => Some(constructor.create()),
+                    });
+
+                if let Some(mut node) = node {
+                    if node.name().is_empty() {
+                        node.set_name(&entry.name);
+                    }
 
-            if let Some(node) = node {
-                sender
-                    .send(Message::do_scene_command(AddNodeCommand::new(node, parent)))
-                    .unwrap();
+                    sender
+                        .send(Message::do_scene_command(AddNodeCommand::new(node, parent)))
+                        .unwrap();
+                }
             }
         }
     }
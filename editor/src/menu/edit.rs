@@ -1,6 +1,9 @@
 use crate::{
-    menu::{create_menu_item_shortcut, create_root_menu_item},
-    scene::{commands::PasteCommand, EditorScene, Selection},
+    menu::{create_menu_item, create_menu_item_shortcut, create_root_menu_item},
+    scene::{
+        commands::{ChangeSelectionCommand, PasteCommand},
+        EditorScene, Selection,
+    },
     GameEngine, Message, Mode,
 };
 use fyrox::gui::message::MessageDirection;
@@ -18,6 +21,8 @@ pub struct EditMenu {
     redo: Handle<UiNode>,
     copy: Handle<UiNode>,
     paste: Handle<UiNode>,
+    find_replace: Handle<UiNode>,
+    scene_settings: Handle<UiNode>,
 }
 
 impl EditMenu {
@@ -26,6 +31,8 @@ impl EditMenu {
         let undo;
         let copy;
         let paste;
+        let find_replace;
+        let scene_settings;
         let menu = create_root_menu_item(
             "Edit",
             vec![
@@ -45,6 +52,14 @@ impl EditMenu {
                     paste = create_menu_item_shortcut("Paste", "Ctrl+V", vec![], ctx);
                     paste
                 },
+                {
+                    find_replace = create_menu_item("Find And Replace...", vec![], ctx);
+                    find_replace
+                },
+                {
+                    scene_settings = create_menu_item("Scene Settings", vec![], ctx);
+                    scene_settings
+                },
             ],
             ctx,
         );
@@ -55,6 +70,8 @@ impl EditMenu {
             redo,
             copy,
             paste,
+            find_replace,
+            scene_settings,
         }
     }
 
@@ -84,6 +101,15 @@ impl EditMenu {
                 sender.send(Message::UndoSceneCommand).unwrap();
             } else if message.destination() == self.redo {
                 sender.send(Message::RedoSceneCommand).unwrap();
+            } else if message.destination() == self.find_replace {
+                sender.send(Message::OpenFindReplace).unwrap();
+            } else if message.destination() == self.scene_settings {
+                sender
+                    .send(Message::do_scene_command(ChangeSelectionCommand::new(
+                        Selection::SceneSettings,
+                        editor_scene.selection.clone(),
+                    )))
+                    .unwrap();
             }
         }
     }
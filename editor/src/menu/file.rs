@@ -26,6 +26,7 @@ pub struct FileMenu {
     new_scene: Handle<UiNode>,
     pub save: Handle<UiNode>,
     pub save_as: Handle<UiNode>,
+    revert: Handle<UiNode>,
     load: Handle<UiNode>,
     pub close_scene: Handle<UiNode>,
     exit: Handle<UiNode>,
@@ -42,6 +43,7 @@ impl FileMenu {
         let new_scene;
         let save;
         let save_as;
+        let revert;
         let close_scene;
         let load;
         let open_settings;
@@ -75,6 +77,10 @@ impl FileMenu {
                         create_menu_item_shortcut("Save Scene As...", "Ctrl+Shift+S", vec![], ctx);
                     save_as
                 },
+                {
+                    revert = create_menu_item("Revert To Saved", vec![], ctx);
+                    revert
+                },
                 {
                     load = create_menu_item_shortcut("Load Scene...", "Ctrl+L", vec![], ctx);
                     load
@@ -116,6 +122,7 @@ impl FileMenu {
             new_scene,
             save,
             save_as,
+            revert,
             close_scene,
             load,
             exit,
@@ -206,6 +213,10 @@ impl FileMenu {
                         MessageDirection::ToWidget,
                         std::env::current_dir().unwrap(),
                     ));
+            } else if message.destination() == self.revert {
+                if editor_scene.as_ref().map_or(false, |s| s.path.is_some()) {
+                    sender.send(Message::RevertScene).unwrap();
+                }
             } else if message.destination() == self.load {
                 if is_scene_needs_to_be_saved(editor_scene.as_deref()) {
                     sender
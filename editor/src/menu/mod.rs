@@ -1,4 +1,5 @@
 use crate::{
+    export::ExportWindow,
     menu::{
         create::CreateEntityRootMenu, edit::EditMenu, file::FileMenu, utils::UtilsMenu,
         view::ViewMenu,
@@ -6,6 +7,7 @@ use crate::{
     scene::EditorScene,
     send_sync_message,
     settings::Settings,
+    shader_graph::ShaderGraphEditor,
     AbsmEditor, CurveEditorWindow, GameEngine, Message, Mode,
 };
 use fyrox::{
@@ -20,11 +22,8 @@ use fyrox::{
 use std::sync::mpsc::Sender;
 
 pub mod create;
-pub mod dim2;
 pub mod edit;
 pub mod file;
-pub mod physics;
-pub mod physics2d;
 pub mod utils;
 pub mod view;
 
@@ -40,6 +39,10 @@ pub struct Menu {
 
 pub struct Panels<'b> {
     pub light_panel: Handle<UiNode>,
+    pub hlod_panel: Handle<UiNode>,
+    pub impostor_panel: Handle<UiNode>,
+    pub node_collections: Handle<UiNode>,
+    pub animation_panel: Handle<UiNode>,
     pub log_panel: Handle<UiNode>,
     pub inspector_window: Handle<UiNode>,
     pub world_outliner_window: Handle<UiNode>,
@@ -48,6 +51,8 @@ pub struct Panels<'b> {
     pub path_fixer: Handle<UiNode>,
     pub curve_editor: &'b CurveEditorWindow,
     pub absm_editor: &'b AbsmEditor,
+    pub shader_graph_editor: &'b ShaderGraphEditor,
+    pub export_window: &'b ExportWindow,
 }
 
 pub struct MenuContext<'a, 'b> {
@@ -95,7 +100,8 @@ impl Menu {
     pub fn new(engine: &mut GameEngine, message_sender: Sender<Message>) -> Self {
         let file_menu = FileMenu::new(engine);
         let ctx = &mut engine.user_interface.build_ctx();
-        let create_entity_menu = CreateEntityRootMenu::new(ctx);
+        let create_entity_menu =
+            CreateEntityRootMenu::new(engine.serialization_context.clone(), ctx);
         let edit_menu = EditMenu::new(ctx);
         let view_menu = ViewMenu::new(ctx);
         let utils_menu = UtilsMenu::new(ctx);
@@ -185,4 +191,8 @@ impl Menu {
         self.edit_menu.on_mode_changed(ui, mode);
         self.file_menu.on_mode_changed(ui, mode);
     }
+
+    pub fn on_node_constructors_changed(&mut self, ui: &mut UserInterface) {
+        self.create_entity_menu.rebuild(ui);
+    }
 }
@@ -1,12 +1,15 @@
 use crate::menu::{create_menu_item, create_root_menu_item, Panels};
 use fyrox::{
     asset::core::pool::Handle,
+    core::futures::executor::block_on,
     gui::{
         menu::MenuItemMessage,
         message::{MessageDirection, UiMessage},
         window::WindowMessage,
         BuildContext, UiNode, UserInterface,
     },
+    resource::texture::prewarm_texture_cache,
+    utils::log::Log,
 };
 
 pub struct UtilsMenu {
@@ -14,6 +17,11 @@ pub struct UtilsMenu {
     open_path_fixer: Handle<UiNode>,
     open_curve_editor: Handle<UiNode>,
     absm_editor: Handle<UiNode>,
+    shader_graph_editor: Handle<UiNode>,
+    prewarm_texture_cache: Handle<UiNode>,
+    hlod_baker: Handle<UiNode>,
+    impostor_baker: Handle<UiNode>,
+    export_game_data: Handle<UiNode>,
 }
 
 impl UtilsMenu {
@@ -21,6 +29,11 @@ impl UtilsMenu {
         let open_path_fixer;
         let open_curve_editor;
         let absm_editor;
+        let shader_graph_editor;
+        let prewarm_texture_cache;
+        let hlod_baker;
+        let impostor_baker;
+        let export_game_data;
         let menu = create_root_menu_item(
             "Utils",
             vec![
@@ -36,6 +49,26 @@ impl UtilsMenu {
                     absm_editor = create_menu_item("Animation Editor", vec![], ctx);
                     absm_editor
                 },
+                {
+                    shader_graph_editor = create_menu_item("Shader Graph Editor", vec![], ctx);
+                    shader_graph_editor
+                },
+                {
+                    prewarm_texture_cache = create_menu_item("Pre-warm Texture Cache", vec![], ctx);
+                    prewarm_texture_cache
+                },
+                {
+                    hlod_baker = create_menu_item("HLOD Baker", vec![], ctx);
+                    hlod_baker
+                },
+                {
+                    impostor_baker = create_menu_item("Impostor Baker", vec![], ctx);
+                    impostor_baker
+                },
+                {
+                    export_game_data = create_menu_item("Export Game Data...", vec![], ctx);
+                    export_game_data
+                },
             ],
             ctx,
         );
@@ -45,6 +78,11 @@ impl UtilsMenu {
             open_path_fixer,
             open_curve_editor,
             absm_editor,
+            shader_graph_editor,
+            prewarm_texture_cache,
+            hlod_baker,
+            impostor_baker,
+            export_game_data,
         }
     }
 
@@ -60,6 +98,32 @@ impl UtilsMenu {
                 panels.curve_editor.open(ui);
             } else if message.destination() == self.absm_editor {
                 panels.absm_editor.open(ui);
+            } else if message.destination() == self.shader_graph_editor {
+                panels.shader_graph_editor.open(ui);
+            } else if message.destination() == self.hlod_baker {
+                ui.send_message(WindowMessage::open(
+                    panels.hlod_panel,
+                    MessageDirection::ToWidget,
+                    true,
+                ));
+            } else if message.destination() == self.impostor_baker {
+                ui.send_message(WindowMessage::open(
+                    panels.impostor_panel,
+                    MessageDirection::ToWidget,
+                    true,
+                ));
+            } else if message.destination() == self.export_game_data {
+                panels.export_window.open(ui);
+            } else if message.destination() == self.prewarm_texture_cache {
+                // Compression of all textures of a project could take a while, do not block the editor.
+                std::thread::spawn(|| {
+                    Log::info("Pre-warming texture cache...".to_owned());
+                    let count = block_on(prewarm_texture_cache(".", &Default::default()));
+                    Log::info(format!(
+                        "Texture cache is pre-warmed, {} textures were imported.",
+                        count
+                    ));
+                });
             }
         }
     }
@@ -15,6 +15,8 @@ pub struct ViewMenu {
     world_viewer: Handle<UiNode>,
     asset_browser: Handle<UiNode>,
     light_panel: Handle<UiNode>,
+    node_collections: Handle<UiNode>,
+    animation_panel: Handle<UiNode>,
     log_panel: Handle<UiNode>,
 }
 
@@ -33,6 +35,8 @@ impl ViewMenu {
         let asset_browser;
         let world_viewer;
         let light_panel;
+        let node_collections;
+        let animation_panel;
         let log_panel;
         let menu = create_root_menu_item(
             "View",
@@ -53,6 +57,14 @@ impl ViewMenu {
                     light_panel = create_menu_item("Light Panel", vec![], ctx);
                     light_panel
                 },
+                {
+                    node_collections = create_menu_item("Node Collections", vec![], ctx);
+                    node_collections
+                },
+                {
+                    animation_panel = create_menu_item("Animation Panel", vec![], ctx);
+                    animation_panel
+                },
                 {
                     log_panel = create_menu_item("Log Panel", vec![], ctx);
                     log_panel
@@ -67,6 +79,8 @@ impl ViewMenu {
             world_viewer,
             asset_browser,
             light_panel,
+            node_collections,
+            animation_panel,
             log_panel,
         }
     }
@@ -77,6 +91,10 @@ impl ViewMenu {
                 switch_window_state(panels.asset_window, ui, false);
             } else if message.destination() == self.light_panel {
                 switch_window_state(panels.light_panel, ui, true);
+            } else if message.destination() == self.node_collections {
+                switch_window_state(panels.node_collections, ui, true);
+            } else if message.destination() == self.animation_panel {
+                switch_window_state(panels.animation_panel, ui, true);
             } else if message.destination() == self.world_viewer {
                 switch_window_state(panels.world_outliner_window, ui, false);
             } else if message.destination() == self.sidebar {
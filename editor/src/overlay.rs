@@ -130,6 +130,8 @@ impl SceneRenderPass for OverlayRenderPass {
                         dfactor: BlendFactor::OneMinusSrcAlpha,
                     }),
                     stencil_op: Default::default(),
+                    alpha_to_coverage: false,
+                    depth_func: Default::default(),
                 },
                 |mut program_binding| {
                     program_binding
@@ -0,0 +1,851 @@
+//! Tools that place selected objects naturally on the geometry of a scene.
+//!
+//! - Simulation of selection - selected objects fall and settle down in an isolated copy of the
+//! scene graph, the rest of the scene stays static. Selected rigid bodies are simulated with their
+//! own colliders, other selected objects get temporary rigid bodies with convex hulls of their
+//! meshes. Meshes around the selection, that have no rigid bodies, get temporary static triangle
+//! mesh colliders. The copy is stepped over multiple frames, so the simulation could be cancelled
+//! at any time. When objects settle down, their new transforms are written back as a single
+//! command.
+//! - Drop to floor - each selected object is moved down until the bottom of its bounding box
+//! touches meshes or colliders of the scene, optionally aligning the object to the surface.
+//!
+//! Both tools do not touch the scene until they finish, so the physics state of the scene remains
+//! intact.
+
+use crate::{
+    camera::precise_ray_test,
+    collider_generator::{make_colliders, GeneratedShapeKind},
+    scene::{
+        commands::{
+            graph::{MoveNodeCommand, RotateNodeCommand},
+            CommandGroup, SceneCommand,
+        },
+        EditorScene, Selection,
+    },
+    GameEngine, Message,
+};
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3},
+        math::{aabb::AxisAlignedBoundingBox, ray::Ray, Matrix4Ext},
+        pool::Handle,
+        scope_profile,
+    },
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        check_box::{CheckBoxBuilder, CheckBoxMessage},
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, UiMessage},
+        numeric::{NumericUpDownBuilder, NumericUpDownMessage},
+        progress_bar::{ProgressBarBuilder, ProgressBarMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
+    },
+    scene::{
+        base::BaseBuilder,
+        graph::{physics::RayCastOptions, Graph},
+        node::Node,
+        rigidbody::{RigidBody, RigidBodyBuilder, RigidBodyType},
+        transform::TransformBuilder,
+        Scene,
+    },
+    utils::log::Log,
+};
+use std::sync::mpsc::Sender;
+
+/// Maximum distance an object could be dropped to.
+const MAX_DROP_DISTANCE: f32 = 1000.0;
+
+/// Rays are cast from a point slightly above the bottom of an object, so an object that slightly
+/// intersects a surface is placed on it.
+const DROP_RAY_OFFSET: f32 = 0.01;
+
+/// Amount of physics steps per frame, the simulation runs faster than real time.
+const STEPS_PER_FRAME: usize = 4;
+
+/// Every object has zero velocity at the beginning, so the simulation cannot be considered as
+/// settled earlier than this time (in seconds).
+const MIN_SIMULATION_TIME: f32 = 0.25;
+
+/// Meshes that are farther than this distance from the selection do not take part in the
+/// simulation.
+const SIMULATION_AREA_MARGIN: f32 = 10.0;
+
+#[derive(Clone, Debug)]
+pub struct PlacementOptions {
+    /// Maximum time (in seconds) of the simulation.
+    pub duration: f32,
+    /// The simulation stops when linear and angular velocities of every object fall below the
+    /// threshold.
+    pub velocity_threshold: f32,
+    /// Whether to align dropped objects to the normal of a surface they're dropped on or not.
+    pub align_to_normal: bool,
+}
+
+impl Default for PlacementOptions {
+    fn default() -> Self {
+        Self {
+            duration: 5.0,
+            velocity_threshold: 0.05,
+            align_to_normal: false,
+        }
+    }
+}
+
+fn global_rotation(graph: &Graph, node: Handle<Node>) -> UnitQuaternion<f32> {
+    UnitQuaternion::from_matrix(&graph.isometric_global_transform(node).basis())
+}
+
+fn is_in_sub_graphs(graph: &Graph, mut handle: Handle<Node>, roots: &[Handle<Node>]) -> bool {
+    while handle.is_some() {
+        if roots.contains(&handle) {
+            return true;
+        }
+        handle = graph
+            .try_get(handle)
+            .map_or(Handle::NONE, |node| node.parent());
+    }
+    false
+}
+
+/// Calculates world-space bounding box of every mesh in the sub-graph. Returns `None` if there
+/// are no meshes.
+fn sub_graph_bounds(graph: &Graph, root: Handle<Node>) -> Option<AxisAlignedBoundingBox> {
+    let mut bounds: Option<AxisAlignedBoundingBox> = None;
+    for handle in graph.traverse_handle_iter(root) {
+        let node = &graph[handle];
+        if node.is_mesh() {
+            let node_bounds = node.world_bounding_box();
+            match bounds.as_mut() {
+                Some(bounds) => bounds.add_box(node_bounds),
+                None => bounds = Some(node_bounds),
+            }
+        }
+    }
+    bounds
+}
+
+/// Creates commands that move (and optionally rotate) a node, so it will have given global
+/// position and rotation.
+fn make_placement_commands(
+    graph: &Graph,
+    node: Handle<Node>,
+    position: Vector3<f32>,
+    rotation: Option<UnitQuaternion<f32>>,
+    commands: &mut Vec<SceneCommand>,
+) {
+    let parent = graph[node].parent();
+    let (inv_parent_transform, parent_rotation) = if parent.is_some() {
+        (
+            graph[parent]
+                .global_transform()
+                .try_inverse()
+                .unwrap_or_else(Matrix4::identity),
+            global_rotation(graph, parent),
+        )
+    } else {
+        (Matrix4::identity(), UnitQuaternion::identity())
+    };
+
+    let transform = graph[node].local_transform();
+
+    let old_position = **transform.position();
+    let new_position = inv_parent_transform
+        .transform_point(&Point3::from(position))
+        .coords;
+    commands.push(SceneCommand::new(MoveNodeCommand::new(
+        node,
+        old_position,
+        new_position,
+    )));
+
+    if let Some(rotation) = rotation {
+        // Global rotation of a node is `parent * pre * local * post`.
+        let old_rotation = **transform.rotation();
+        let new_rotation = transform.pre_rotation().inverse()
+            * parent_rotation.inverse()
+            * rotation
+            * transform.post_rotation().inverse();
+        commands.push(SceneCommand::new(RotateNodeCommand::new(
+            node,
+            old_rotation,
+            new_rotation,
+        )));
+    }
+}
+
+/// Casts the ray against visible meshes and colliders of the graph, ignoring the excluded
+/// sub-graphs. Returns position and normal of the closest intersection.
+fn cast_ray(
+    graph: &Graph,
+    ray: &Ray,
+    excluded: &[Handle<Node>],
+) -> Option<(Vector3<f32>, Vector3<f32>)> {
+    // Squared distance, position, normal.
+    let mut closest: Option<(f32, Vector3<f32>, Vector3<f32>)> = None;
+
+    for (handle, node) in graph.pair_iter() {
+        if node.is_mesh()
+            && node.global_visibility()
+            && ray
+                .aabb_intersection_points(&node.world_bounding_box())
+                .is_some()
+            && !is_in_sub_graphs(graph, handle, excluded)
+        {
+            if let Some(hit) = precise_ray_test(node, ray, false) {
+                if closest.map_or(true, |closest| hit.0 < closest.0) {
+                    closest = Some(hit);
+                }
+            }
+        }
+    }
+
+    let mut intersections = Vec::new();
+    graph.physics.cast_ray(
+        RayCastOptions {
+            ray_origin: Point3::from(ray.origin),
+            ray_direction: ray.dir,
+            max_len: ray.dir.norm(),
+            groups: Default::default(),
+            sort_results: true,
+        },
+        &mut intersections,
+    );
+    if let Some(intersection) = intersections
+        .iter()
+        .find(|intersection| !is_in_sub_graphs(graph, intersection.collider, excluded))
+    {
+        let position = intersection.position.coords;
+        let sqr_distance = (position - ray.origin).norm_squared();
+        if closest.map_or(true, |closest| sqr_distance < closest.0) {
+            closest = Some((sqr_distance, position, intersection.normal));
+        }
+    }
+
+    closest.map(|(_, position, normal)| (position, normal))
+}
+
+/// Creates a command that drops every given sub-graph down to the closest surface below it.
+/// Returns `None` if there is nothing to drop.
+pub fn make_drop_to_floor_command(
+    graph: &Graph,
+    roots: &[Handle<Node>],
+    editor_objects_root: Handle<Node>,
+    align_to_normal: bool,
+) -> Option<SceneCommand> {
+    let mut excluded = roots.to_vec();
+    excluded.push(editor_objects_root);
+
+    let mut commands = Vec::new();
+    for &root in roots {
+        let node = &graph[root];
+        let position = node.global_position();
+        let bottom = sub_graph_bounds(graph, root).map_or(position, |bounds| {
+            let center = bounds.center();
+            Vector3::new(center.x, bounds.min.y, center.z)
+        });
+
+        let ray = Ray::new(
+            bottom + Vector3::new(0.0, DROP_RAY_OFFSET, 0.0),
+            Vector3::new(0.0, -MAX_DROP_DISTANCE, 0.0),
+        );
+
+        if let Some((hit, normal)) = cast_ray(graph, &ray, &excluded) {
+            if align_to_normal {
+                let rotation = global_rotation(graph, root);
+                let alignment =
+                    UnitQuaternion::rotation_between(&(rotation * Vector3::y()), &normal)
+                        .unwrap_or_else(UnitQuaternion::identity);
+                // Rotate around the bottom point, so the object will touch the surface by it.
+                make_placement_commands(
+                    graph,
+                    root,
+                    hit + alignment * (position - bottom),
+                    Some(alignment * rotation),
+                    &mut commands,
+                );
+            } else {
+                make_placement_commands(
+                    graph,
+                    root,
+                    position + Vector3::new(0.0, hit.y - bottom.y, 0.0),
+                    None,
+                    &mut commands,
+                );
+            }
+        } else {
+            Log::warn(format!(
+                "There is nothing below {} to drop it to.",
+                node.name()
+            ));
+        }
+    }
+
+    if commands.is_empty() {
+        None
+    } else {
+        Some(SceneCommand::new(CommandGroup::from(commands)))
+    }
+}
+
+struct SimulationTarget {
+    /// A selected node in the scene.
+    node: Handle<Node>,
+    /// A copy of the node in the simulated graph.
+    copy: Handle<Node>,
+    /// A rigid body in the simulated graph that moves the copy.
+    body: Handle<Node>,
+}
+
+/// Isolated simulation of selected objects, see module docs for more info.
+pub struct SelectionSimulation {
+    scene: Handle<Scene>,
+    graph: Graph,
+    targets: Vec<SimulationTarget>,
+    elapsed: f32,
+    duration: f32,
+    velocity_threshold: f32,
+}
+
+impl SelectionSimulation {
+    /// Prepares a simulation of the given sub-graphs. Returns `None` if there is nothing to
+    /// simulate.
+    pub fn new(
+        editor_scene: &EditorScene,
+        graph: &Graph,
+        roots: &[Handle<Node>],
+        options: &PlacementOptions,
+    ) -> Option<Self> {
+        let editor_objects_root = editor_scene.editor_objects_root;
+        let (mut copy, old_new_mapping) =
+            graph.clone(&mut |handle, _| handle != editor_objects_root);
+        copy.physics.enabled = true;
+        copy.physics2d.enabled = false;
+        copy.update_hierarchical_data();
+
+        // The rest of the scene does not move.
+        for node in copy.linear_iter_mut() {
+            if let Some(body) = node.cast_mut::<RigidBody>() {
+                body.set_body_type(RigidBodyType::Static);
+            }
+        }
+
+        let mut targets = Vec::new();
+        for &root in roots {
+            let root_copy = match old_new_mapping.get(&root) {
+                Some(&root_copy) => root_copy,
+                None => continue,
+            };
+
+            if let Some(body) = copy[root_copy].cast_mut::<RigidBody>() {
+                body.set_body_type(RigidBodyType::Dynamic);
+                body.wake_up();
+                targets.push(SimulationTarget {
+                    node: root,
+                    copy: root_copy,
+                    body: root_copy,
+                });
+                continue;
+            }
+
+            // A temporary body cannot move other bodies, they must be selected directly.
+            if copy
+                .traverse_handle_iter(root_copy)
+                .any(|handle| copy[handle].cast::<RigidBody>().is_some())
+            {
+                Log::warn(format!(
+                    "{} contains rigid bodies, select the bodies to simulate them.",
+                    graph[root].name()
+                ));
+                continue;
+            }
+
+            let meshes = copy
+                .traverse_handle_iter(root_copy)
+                .filter(|&handle| copy[handle].is_mesh())
+                .collect::<Vec<_>>();
+            let colliders = make_colliders(
+                &mut copy,
+                root_copy,
+                &meshes,
+                GeneratedShapeKind::ConvexHull,
+            );
+            if colliders.is_empty() {
+                Log::warn(format!(
+                    "{} has no meshes, it cannot be simulated.",
+                    graph[root].name()
+                ));
+                continue;
+            }
+
+            let body = RigidBodyBuilder::new(
+                BaseBuilder::new()
+                    .with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(copy[root_copy].global_position())
+                            .with_local_rotation(global_rotation(&copy, root_copy))
+                            .build(),
+                    )
+                    .with_children(&colliders),
+            )
+            .with_body_type(RigidBodyType::Dynamic)
+            .build(&mut copy);
+
+            // The body defines position and rotation of the sub-graph now.
+            let scale = copy.global_scale_matrix(root_copy);
+            copy.link_nodes(root_copy, body);
+            *copy[root_copy].local_transform_mut() = TransformBuilder::new()
+                .with_local_scale(Vector3::new(scale[0], scale[5], scale[10]))
+                .build();
+
+            targets.push(SimulationTarget {
+                node: root,
+                copy: root_copy,
+                body,
+            });
+        }
+
+        if targets.is_empty() {
+            return None;
+        }
+
+        // Static meshes without colliders around the selection (usually a ground) must take part
+        // in the simulation too.
+        let mut area: Option<AxisAlignedBoundingBox> = None;
+        for &root in roots {
+            if let Some(bounds) = sub_graph_bounds(graph, root) {
+                match area.as_mut() {
+                    Some(area) => area.add_box(bounds),
+                    None => area = Some(bounds),
+                }
+            }
+        }
+        if let Some(mut area) = area {
+            area.inflate(Vector3::repeat(2.0 * SIMULATION_AREA_MARGIN));
+            area.min.y = f32::MIN;
+
+            let target_copies = targets.iter().map(|target| target.copy).collect::<Vec<_>>();
+
+            let environment_meshes = copy
+                .pair_iter()
+                .filter(|(handle, node)| {
+                    node.is_mesh()
+                        && node.global_visibility()
+                        && node.world_bounding_box().intersect_aabb(&area)
+                        && !is_in_sub_graphs(&copy, *handle, &target_copies)
+                        && !has_rigid_body(&copy, *handle)
+                })
+                .map(|(handle, _)| handle)
+                .collect::<Vec<_>>();
+
+            for mesh in environment_meshes {
+                let colliders =
+                    make_colliders(&mut copy, mesh, &[mesh], GeneratedShapeKind::Trimesh);
+                RigidBodyBuilder::new(BaseBuilder::new().with_children(&colliders))
+                    .with_body_type(RigidBodyType::Static)
+                    .build(&mut copy);
+            }
+        }
+
+        Some(Self {
+            scene: editor_scene.scene,
+            graph: copy,
+            targets,
+            elapsed: 0.0,
+            duration: options.duration,
+            velocity_threshold: options.velocity_threshold,
+        })
+    }
+
+    /// Performs a few simulation steps. Returns `true` if the simulation is finished.
+    fn step(&mut self) -> bool {
+        let dt = self.graph.physics.settings.integration_parameters.dt;
+        for _ in 0..STEPS_PER_FRAME {
+            self.graph.update(Vector2::new(1.0, 1.0), dt);
+            self.elapsed += dt;
+
+            if self.elapsed >= self.duration
+                || (self.elapsed >= MIN_SIMULATION_TIME && self.is_settled())
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn is_settled(&self) -> bool {
+        self.targets.iter().all(|target| {
+            self.graph
+                .try_get(target.body)
+                .and_then(|node| node.cast::<RigidBody>())
+                .map_or(true, |body| {
+                    body.is_sleeping()
+                        || (body.lin_vel().norm() < self.velocity_threshold
+                            && body.ang_vel().norm() < self.velocity_threshold)
+                })
+        })
+    }
+
+    /// Creates a command that moves simulated objects of the scene to their final positions.
+    fn make_command(&mut self, graph: &Graph) -> Option<SceneCommand> {
+        self.graph.update_hierarchical_data();
+
+        let mut commands = Vec::new();
+        for target in self.targets.iter() {
+            // The node could be deleted while the simulation was running.
+            if graph.is_valid_handle(target.node) {
+                make_placement_commands(
+                    graph,
+                    target.node,
+                    self.graph[target.copy].global_position(),
+                    Some(global_rotation(&self.graph, target.copy)),
+                    &mut commands,
+                );
+            }
+        }
+
+        if commands.is_empty() {
+            None
+        } else {
+            Some(SceneCommand::new(CommandGroup::from(commands)))
+        }
+    }
+}
+
+fn has_rigid_body(graph: &Graph, mesh: Handle<Node>) -> bool {
+    // Generated colliders are linked to meshes, visual meshes of physical objects are linked to
+    // rigid bodies.
+    graph[mesh]
+        .children()
+        .iter()
+        .any(|&child| graph[child].cast::<RigidBody>().is_some())
+        || {
+            let mut parent = graph[mesh].parent();
+            let mut found = false;
+            while parent.is_some() {
+                if graph[parent].cast::<RigidBody>().is_some() {
+                    found = true;
+                    break;
+                }
+                parent = graph[parent].parent();
+            }
+            found
+        }
+}
+
+fn make_label(ctx: &mut BuildContext, row: usize, text: &str) -> Handle<UiNode> {
+    TextBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(0)
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+fn make_button(ctx: &mut BuildContext, text: &str) -> Handle<UiNode> {
+    ButtonBuilder::new(
+        WidgetBuilder::new()
+            .with_margin(Thickness::uniform(1.0))
+            .with_width(90.0),
+    )
+    .with_text(text)
+    .build(ctx)
+}
+
+pub struct PlacementWindow {
+    pub window: Handle<UiNode>,
+    duration: Handle<UiNode>,
+    velocity_threshold: Handle<UiNode>,
+    align_to_normal: Handle<UiNode>,
+    progress_bar: Handle<UiNode>,
+    progress_text: Handle<UiNode>,
+    simulate: Handle<UiNode>,
+    drop_to_floor: Handle<UiNode>,
+    close: Handle<UiNode>,
+    options: PlacementOptions,
+    simulation: Option<SelectionSimulation>,
+}
+
+impl PlacementWindow {
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let duration;
+        let velocity_threshold;
+        let align_to_normal;
+        let progress_bar;
+        let progress_text;
+        let simulate = make_button(ctx, "Simulate");
+        let drop_to_floor = make_button(ctx, "Drop To Floor");
+        let close = make_button(ctx, "Close");
+        let options = PlacementOptions::default();
+
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(320.0).with_height(185.0))
+            .with_title(WindowTitle::text("Place Selection"))
+            .open(false)
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(make_label(ctx, 0, "Duration (s)"))
+                        .with_child({
+                            duration = NumericUpDownBuilder::<f32>::new(
+                                WidgetBuilder::new()
+                                    .on_row(0)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_min_value(0.1)
+                            .with_step(0.5)
+                            .with_value(options.duration)
+                            .build(ctx);
+                            duration
+                        })
+                        .with_child(make_label(ctx, 1, "Velocity Threshold"))
+                        .with_child({
+                            velocity_threshold = NumericUpDownBuilder::<f32>::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .with_min_value(0.0)
+                            .with_step(0.01)
+                            .with_value(options.velocity_threshold)
+                            .build(ctx);
+                            velocity_threshold
+                        })
+                        .with_child(make_label(ctx, 2, "Align To Normal"))
+                        .with_child({
+                            align_to_normal = CheckBoxBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .checked(Some(options.align_to_normal))
+                            .build(ctx);
+                            align_to_normal
+                        })
+                        .with_child({
+                            progress_text = TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(3)
+                                    .on_column(0)
+                                    .with_vertical_alignment(VerticalAlignment::Center),
+                            )
+                            .build(ctx);
+                            progress_text
+                        })
+                        .with_child({
+                            progress_bar = ProgressBarBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_visibility(false)
+                                    .on_row(3)
+                                    .on_column(1)
+                                    .with_margin(Thickness::uniform(1.0)),
+                            )
+                            .build(ctx);
+                            progress_bar
+                        })
+                        .with_child(
+                            StackPanelBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(5)
+                                    .on_column(1)
+                                    .with_horizontal_alignment(HorizontalAlignment::Right)
+                                    .with_child(simulate)
+                                    .with_child(drop_to_floor)
+                                    .with_child(close),
+                            )
+                            .with_orientation(Orientation::Horizontal)
+                            .build(ctx),
+                        ),
+                )
+                .add_column(Column::strict(130.0))
+                .add_column(Column::stretch())
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::strict(25.0))
+                .add_row(Row::stretch())
+                .add_row(Row::strict(25.0))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            window,
+            duration,
+            velocity_threshold,
+            align_to_normal,
+            progress_bar,
+            progress_text,
+            simulate,
+            drop_to_floor,
+            close,
+            options,
+            simulation: None,
+        }
+    }
+
+    pub fn open(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    pub fn is_simulating(&self) -> bool {
+        self.simulation.is_some()
+    }
+
+    /// Stops the simulation without any changes in the scene.
+    pub fn cancel(&mut self, ui: &UserInterface) {
+        if self.simulation.take().is_some() {
+            Log::info("Simulation of selection was cancelled.".to_owned());
+            self.sync_progress(ui);
+        }
+    }
+
+    fn sync_progress(&self, ui: &UserInterface) {
+        ui.send_message(WidgetMessage::visibility(
+            self.progress_bar,
+            MessageDirection::ToWidget,
+            self.simulation.is_some(),
+        ));
+
+        let text = if let Some(simulation) = self.simulation.as_ref() {
+            ui.send_message(ProgressBarMessage::progress(
+                self.progress_bar,
+                MessageDirection::ToWidget,
+                simulation.elapsed / simulation.duration,
+            ));
+
+            format!("{:.1} s (Esc - cancel)", simulation.elapsed)
+        } else {
+            Default::default()
+        };
+
+        ui.send_message(TextMessage::text(
+            self.progress_text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+
+    fn start_simulation(&mut self, editor_scene: &EditorScene, engine: &GameEngine) {
+        if let Selection::Graph(selection) = &editor_scene.selection {
+            let graph = &engine.scenes[editor_scene.scene].graph;
+            let roots = selection.root_nodes(graph);
+            self.simulation = SelectionSimulation::new(editor_scene, graph, &roots, &self.options);
+            self.sync_progress(&engine.user_interface);
+        }
+    }
+
+    /// Drops every selected object to the closest surface below it.
+    pub fn drop_to_floor(
+        &self,
+        editor_scene: &EditorScene,
+        engine: &GameEngine,
+        sender: &Sender<Message>,
+    ) {
+        if let Selection::Graph(selection) = &editor_scene.selection {
+            let graph = &engine.scenes[editor_scene.scene].graph;
+            let roots = selection.root_nodes(graph);
+            if let Some(command) = make_drop_to_floor_command(
+                graph,
+                &roots,
+                editor_scene.editor_objects_root,
+                self.options.align_to_normal,
+            ) {
+                sender.send(Message::DoSceneCommand(command)).unwrap();
+            }
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
+        scope_profile!();
+
+        if let Some(simulation) = self.simulation.as_mut() {
+            // The scene was changed while the simulation was running.
+            if simulation.scene != editor_scene.scene {
+                self.cancel(&engine.user_interface);
+                return;
+            }
+
+            if simulation.step() {
+                if let Some(command) =
+                    simulation.make_command(&engine.scenes[editor_scene.scene].graph)
+                {
+                    sender.send(Message::DoSceneCommand(command)).unwrap();
+                }
+
+                Log::info(format!(
+                    "Simulation of selection is finished in {:.2} s.",
+                    simulation.elapsed
+                ));
+
+                self.simulation = None;
+            }
+
+            self.sync_progress(&engine.user_interface);
+        }
+    }
+
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        editor_scene: &EditorScene,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
+        scope_profile!();
+
+        if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.simulate {
+                if !self.is_simulating() {
+                    self.start_simulation(editor_scene, engine);
+                }
+            } else if message.destination() == self.drop_to_floor {
+                self.drop_to_floor(editor_scene, engine, sender);
+            } else if message.destination() == self.close {
+                engine.user_interface.send_message(WindowMessage::close(
+                    self.window,
+                    MessageDirection::ToWidget,
+                ));
+            }
+        } else if let Some(WindowMessage::Close) = message.data::<WindowMessage>() {
+            if message.destination() == self.window {
+                self.cancel(&engine.user_interface);
+            }
+        } else if let Some(&NumericUpDownMessage::Value(value)) =
+            message.data::<NumericUpDownMessage<f32>>()
+        {
+            if message.direction() == MessageDirection::FromWidget {
+                if message.destination() == self.duration {
+                    self.options.duration = value;
+                } else if message.destination() == self.velocity_threshold {
+                    self.options.velocity_threshold = value;
+                }
+            }
+        } else if let Some(&CheckBoxMessage::Check(Some(value))) = message.data::<CheckBoxMessage>()
+        {
+            if message.direction() == MessageDirection::FromWidget
+                && message.destination() == self.align_to_normal
+            {
+                self.options.align_to_normal = value;
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{utils::built_in_skybox, GameEngine};
+use crate::{utils::built_in_skybox, GameEngine, FIXED_TIMESTEP};
 use fyrox::{
     core::{
         algebra::{UnitQuaternion, Vector2, Vector3},
@@ -13,10 +13,11 @@ use fyrox::{
         image::{Image, ImageBuilder, ImageMessage},
         message::{CursorIcon, MessageDirection, MouseButton, UiMessage},
         stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
         widget::{WidgetBuilder, WidgetMessage},
-        Orientation, Thickness, UiNode,
+        BuildContext, Orientation, Thickness, UiNode, UserInterface, VerticalAlignment,
     },
-    resource::texture::{Texture, TextureKind},
+    resource::{model::Model, texture::Texture},
     scene::{
         base::BaseBuilder,
         camera::{CameraBuilder, Projection},
@@ -39,6 +40,28 @@ enum Mode {
     Rotate,
 }
 
+/// Playback of a previewed model - its animations, sounds and particle systems are driven by a
+/// single clock, that could be paused or restarted. The model lives in the isolated preview scene,
+/// so the playback never affects edited scenes. Stopping or restarting the playback instantiates
+/// the model again, this fully resets transforms, particle pools and playback positions of sounds.
+struct Playback {
+    play_pause: Handle<UiNode>,
+    play_pause_text: Handle<UiNode>,
+    restart: Handle<UiNode>,
+    stop: Handle<UiNode>,
+    time_text: Handle<UiNode>,
+    playing: bool,
+    time: f32,
+}
+
+fn play_pause_text(playing: bool) -> &'static str {
+    if playing {
+        "Pause"
+    } else {
+        "Play"
+    }
+}
+
 pub struct PreviewPanel {
     scene: Handle<Scene>,
     pub root: Handle<UiNode>,
@@ -54,6 +77,8 @@ pub struct PreviewPanel {
     mode: Mode,
     position: Vector3<f32>,
     model: Handle<Node>,
+    resource: Option<Model>,
+    playback: Option<Playback>,
     pub tools_panel: Handle<UiNode>,
 }
 
@@ -219,10 +244,58 @@ impl PreviewPanel {
             hinge,
             position: Default::default(),
             model: Default::default(),
+            resource: None,
+            playback: None,
             tools_panel,
         }
     }
 
+    /// Adds playback controls to the panel. Animations and sounds of loaded models are played
+    /// only if the playback is enabled.
+    pub fn with_playback(mut self, engine: &mut GameEngine) -> Self {
+        let ctx = &mut engine.user_interface.build_ctx();
+
+        let make_button = |ctx: &mut BuildContext, text: &str| {
+            ButtonBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(1.0)))
+                .with_text(text)
+                .build(ctx)
+        };
+
+        let play_pause_text = TextBuilder::new(WidgetBuilder::new())
+            .with_text(play_pause_text(true))
+            .with_vertical_text_alignment(VerticalAlignment::Center)
+            .build(ctx);
+        let play_pause =
+            ButtonBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(1.0)))
+                .with_content(play_pause_text)
+                .build(ctx);
+        let restart = make_button(ctx, "Restart");
+        let stop = make_button(ctx, "Stop");
+        let time_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_width(60.0)
+                .with_margin(Thickness::uniform(1.0)),
+        )
+        .with_vertical_text_alignment(VerticalAlignment::Center)
+        .build(ctx);
+
+        for widget in [play_pause, restart, stop, time_text] {
+            ctx.link(widget, self.tools_panel);
+        }
+
+        self.playback = Some(Playback {
+            play_pause,
+            play_pause_text,
+            restart,
+            stop,
+            time_text,
+            playing: true,
+            time: 0.0,
+        });
+
+        self
+    }
+
     pub fn fit_to_model(&mut self, scene: &mut Scene) {
         let mut bounding_box = AxisAlignedBoundingBox::default();
         for node in scene.graph.linear_iter() {
@@ -249,6 +322,18 @@ impl PreviewPanel {
         if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
             if message.destination() == self.fit {
                 self.fit_to_model(scene);
+            } else if let Some(playback) = self.playback.as_ref() {
+                if message.destination() == playback.play_pause {
+                    let playing = !playback.playing;
+                    self.set_playing(engine, playing);
+                } else if message.destination() == playback.restart {
+                    self.reset(engine);
+                    self.set_playing(engine, true);
+                } else if message.destination() == playback.stop {
+                    self.reset(engine);
+                    self.set_playing(engine, false);
+                }
+                return;
             }
         }
 
@@ -326,40 +411,103 @@ impl PreviewPanel {
             scene.remove_node(self.model);
             self.model = Handle::NONE;
         }
+        self.resource = None;
+        if let Some(playback) = self.playback.as_mut() {
+            playback.time = 0.0;
+        }
+    }
+
+    fn instantiate(&mut self, engine: &mut GameEngine) {
+        if let Some(resource) = self.resource.as_ref() {
+            let scene = &mut engine.scenes[self.scene];
+            self.model = if self.playback.is_some() {
+                resource.instantiate(scene).root
+            } else {
+                resource.instantiate_geometry(scene)
+            };
+        }
     }
 
     pub async fn load_model(&mut self, model: &Path, engine: &mut GameEngine) -> bool {
         self.clear(engine);
         if let Ok(model) = engine.resource_manager.request_model(model).await {
-            let scene = &mut engine.scenes[self.scene];
-            self.model = model.instantiate_geometry(scene);
-            self.fit_to_model(scene);
+            self.resource = Some(model);
+            self.instantiate(engine);
+            self.fit_to_model(&mut engine.scenes[self.scene]);
+            if self.playback.is_some() {
+                self.set_playing(engine, true);
+            }
             true
         } else {
             false
         }
     }
 
+    /// Instantiates the model again, so the playback starts from the very beginning.
+    fn reset(&mut self, engine: &mut GameEngine) {
+        if self.resource.is_some() && self.model.is_some() {
+            engine.scenes[self.scene].remove_node(self.model);
+            self.instantiate(engine);
+        }
+        if let Some(playback) = self.playback.as_mut() {
+            playback.time = 0.0;
+        }
+        self.sync_playback(&engine.user_interface);
+    }
+
+    fn set_playing(&mut self, engine: &mut GameEngine, playing: bool) {
+        if let Some(playback) = self.playback.as_mut() {
+            playback.playing = playing;
+
+            let scene = &mut engine.scenes[self.scene];
+            scene.set_time_scale(if playing { 1.0 } else { 0.0 });
+            scene.graph.sound_context.pause(!playing);
+
+            engine.user_interface.send_message(TextMessage::text(
+                playback.play_pause_text,
+                MessageDirection::ToWidget,
+                play_pause_text(playing).to_owned(),
+            ));
+        }
+        self.sync_playback(&engine.user_interface);
+    }
+
+    fn sync_playback(&self, ui: &UserInterface) {
+        if let Some(playback) = self.playback.as_ref() {
+            ui.send_message(TextMessage::text(
+                playback.time_text,
+                MessageDirection::ToWidget,
+                format!("{:.2} s", playback.time),
+            ));
+        }
+    }
+
+    /// Sets volume of sounds of previewed models.
+    pub fn set_volume(&self, engine: &mut GameEngine, volume: f32) {
+        engine.scenes[self.scene]
+            .graph
+            .sound_context
+            .set_master_gain(volume);
+    }
+
     pub fn update(&mut self, engine: &mut GameEngine) {
+        if let Some(playback) = self.playback.as_mut() {
+            if playback.playing && self.model.is_some() {
+                playback.time += FIXED_TIMESTEP;
+                self.sync_playback(&engine.user_interface);
+            }
+        }
+
         let scene = &mut engine.scenes[self.scene];
 
         // Create new render target if preview frame has changed its size.
-        let (rt_width, rt_height) = if let TextureKind::Rectangle { width, height } =
-            scene.render_target.clone().unwrap().data_ref().kind()
-        {
-            (width, height)
-        } else {
-            unreachable!();
-        };
         if let Some(frame) = engine.user_interface.node(self.frame).cast::<Image>() {
             let frame_size = frame.actual_size();
-            if rt_width != frame_size.x as u32 || rt_height != frame_size.y as u32 {
-                let rt = Texture::new_render_target(frame_size.x as u32, frame_size.y as u32);
-                scene.render_target = Some(rt.clone());
+            if scene.set_render_target_size(frame_size.x as u32, frame_size.y as u32) {
                 engine.user_interface.send_message(ImageMessage::texture(
                     self.frame,
                     MessageDirection::ToWidget,
-                    Some(into_gui_texture(rt)),
+                    scene.render_target.clone().map(into_gui_texture),
                 ));
             }
         }
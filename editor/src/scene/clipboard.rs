@@ -34,7 +34,9 @@ fn deep_clone_nodes(
     let mut old_new_mapping = HashMap::new();
 
     for &root_node in root_nodes.iter() {
-        let (_, old_to_new) = source_graph.copy_node(root_node, dest_graph, &mut |_, _| true);
+        let (root, old_to_new) = source_graph.copy_node(root_node, dest_graph, &mut |_, _| true);
+        // Pasted nodes are new nodes, not the copied ones.
+        dest_graph.regenerate_instance_ids(root);
         // Merge mappings.
         for (old, new) in old_to_new {
             old_new_mapping.insert(old, new);
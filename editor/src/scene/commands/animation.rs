@@ -0,0 +1,101 @@
+use crate::{command::Command, scene::commands::SceneContext};
+use fyrox::{
+    animation::{Animation, Track},
+    core::pool::Handle,
+};
+
+macro_rules! define_animation_track_swap_command {
+    ($($type:ident($value_type:ty): $get:ident, $set:ident, $name:expr;)*) => {
+        $(
+            #[derive(Debug)]
+            pub struct $type {
+                animation: Handle<Animation>,
+                track: usize,
+                value: $value_type,
+            }
+
+            impl $type {
+                pub fn new(animation: Handle<Animation>, track: usize, value: $value_type) -> Self {
+                    Self {
+                        animation,
+                        track,
+                        value,
+                    }
+                }
+
+                fn swap(&mut self, context: &mut SceneContext) {
+                    if let Some(track) = context
+                        .scene
+                        .animations
+                        .try_get_mut(self.animation)
+                        .and_then(|animation| animation.get_tracks_mut().get_mut(self.track))
+                    {
+                        let old = Track::$get(track);
+                        Track::$set(track, self.value);
+                        self.value = old;
+                    }
+                }
+            }
+
+            impl Command for $type {
+                fn name(&mut self, _context: &SceneContext) -> String {
+                    $name.to_owned()
+                }
+
+                fn execute(&mut self, context: &mut SceneContext) {
+                    self.swap(context);
+                }
+
+                fn revert(&mut self, context: &mut SceneContext) {
+                    self.swap(context);
+                }
+            }
+        )*
+    };
+}
+
+define_animation_track_swap_command! {
+    SetAnimationTrackEnabledCommand(bool): is_enabled, enable, "Set Animation Track Enabled";
+    SetAnimationTrackWeightCommand(f32): weight, set_weight, "Set Animation Track Weight";
+}
+
+/// Replaces tracks and length of an animation, it is used to paste key frames and tracks.
+#[derive(Debug)]
+pub struct PasteAnimationTracksCommand {
+    animation: Handle<Animation>,
+    tracks: Vec<Track>,
+    length: f32,
+}
+
+impl PasteAnimationTracksCommand {
+    pub fn new(animation: Handle<Animation>, tracks: Vec<Track>, length: f32) -> Self {
+        Self {
+            animation,
+            tracks,
+            length,
+        }
+    }
+
+    fn swap(&mut self, context: &mut SceneContext) {
+        if let Some(animation) = context.scene.animations.try_get_mut(self.animation) {
+            let old_length = animation.length();
+            self.tracks = animation.set_tracks(std::mem::take(&mut self.tracks));
+            animation.set_length(self.length);
+            self.length = old_length;
+        }
+    }
+}
+
+impl Command for PasteAnimationTracksCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Paste Animation Keys".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+}
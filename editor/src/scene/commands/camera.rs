@@ -7,6 +7,7 @@ use fyrox::{
     core::{math::Rect, pool::Handle},
     resource::texture::Texture,
     scene::{
+        base::LayerMask,
         camera::{Camera, ColorGradingLut, Exposure, SkyBox},
         graph::Graph,
         node::Node,
@@ -25,6 +26,7 @@ define_swap_command! {
     SetExposureCommand(Exposure): exposure, set_exposure, "Set Camera Exposure";
     SetColorGradingLutCommand(Option<ColorGradingLut>): color_grading_lut, set_color_grading_map, "Set Color Grading Lut";
     SetColorGradingEnabledCommand(bool): color_grading_enabled, set_color_grading_enabled, "Set Color Grading Enabled";
+    SetCameraCullingMaskCommand(LayerMask): culling_mask, set_culling_mask, "Set Camera Culling Mask";
 }
 
 define_node_command! {
@@ -112,9 +114,9 @@ impl Command for SetCameraPreviewCommand {
         self.old_value = camera.is_enabled();
         camera.set_enabled(self.value);
 
-        let editor_camera_handle = context.editor_scene.camera_controller.camera;
-        let editor_camera = context.scene.graph[editor_camera_handle].as_camera_mut();
-        editor_camera.set_enabled(!self.value);
+        // Cameras of viewport panes are enabled by the editor every frame, suspend them instead.
+        let editor_scene = &mut *context.editor_scene;
+        editor_scene.viewports.set_suspended(self.value);
 
         // disable other cameras and save their handles to be able to revert
         if self.value {
@@ -122,7 +124,12 @@ impl Command for SetCameraPreviewCommand {
                 .scene
                 .graph
                 .pair_iter_mut()
-                .filter(|(handle, _)| handle != &self.handle && handle != &editor_camera_handle)
+                .filter(|(handle, _)| {
+                    handle != &self.handle
+                        && !editor_scene
+                            .viewports
+                            .is_pane_camera(*handle, &editor_scene.camera_controller)
+                })
                 .filter_map(|(handle, node)| {
                     if let Some(cam) = node.cast_mut::<Camera>() {
                         if cam.is_enabled() {
@@ -148,9 +155,10 @@ impl Command for SetCameraPreviewCommand {
         let camera = context.scene.graph[self.handle].as_camera_mut();
         camera.set_enabled(self.old_value);
 
-        let editor_camera_handle = context.editor_scene.camera_controller.camera;
-        let editor_camera = context.scene.graph[editor_camera_handle].as_camera_mut();
         let editor_camera_enabled = !self.old_value && self.prev_active.is_empty();
-        editor_camera.set_enabled(editor_camera_enabled);
+        context
+            .editor_scene
+            .viewports
+            .set_suspended(!editor_camera_enabled);
     }
 }
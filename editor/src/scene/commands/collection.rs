@@ -0,0 +1,112 @@
+use crate::{Command, SceneContext};
+use fyrox::{
+    core::pool::Handle,
+    scene::{collection::NodeCollection, node::Node},
+};
+
+#[derive(Debug)]
+pub struct AddNodeCollectionCommand {
+    collection: Option<NodeCollection>,
+    index: usize,
+}
+
+impl AddNodeCollectionCommand {
+    pub fn new(collection: NodeCollection) -> Self {
+        Self {
+            collection: Some(collection),
+            index: 0,
+        }
+    }
+}
+
+impl Command for AddNodeCollectionCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Add Node Collection".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.index = context
+            .scene
+            .node_collections
+            .add(self.collection.take().unwrap());
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.collection = Some(context.scene.node_collections.remove(self.index));
+    }
+}
+
+#[derive(Debug)]
+pub struct RemoveNodeCollectionCommand {
+    collection: Option<NodeCollection>,
+    index: usize,
+}
+
+impl RemoveNodeCollectionCommand {
+    pub fn new(index: usize) -> Self {
+        Self {
+            collection: None,
+            index,
+        }
+    }
+}
+
+impl Command for RemoveNodeCollectionCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Remove Node Collection".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.collection = Some(context.scene.node_collections.remove(self.index));
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        context
+            .scene
+            .node_collections
+            .insert(self.index, self.collection.take().unwrap());
+    }
+}
+
+macro_rules! define_collection_swap_command {
+    ($($type:ident($value_type:ty): $field:ident, $name:expr;)*) => {
+        $(
+            #[derive(Debug)]
+            pub struct $type {
+                index: usize,
+                value: $value_type,
+            }
+
+            impl $type {
+                pub fn new(index: usize, value: $value_type) -> Self {
+                    Self { index, value }
+                }
+
+                fn swap(&mut self, context: &mut SceneContext) {
+                    if let Some(collection) = context.scene.node_collections.get_mut(self.index) {
+                        std::mem::swap(&mut collection.$field, &mut self.value);
+                    }
+                }
+            }
+
+            impl Command for $type {
+                fn name(&mut self, _context: &SceneContext) -> String {
+                    $name.to_owned()
+                }
+
+                fn execute(&mut self, context: &mut SceneContext) {
+                    self.swap(context);
+                }
+
+                fn revert(&mut self, context: &mut SceneContext) {
+                    self.swap(context);
+                }
+            }
+        )*
+    };
+}
+
+define_collection_swap_command! {
+    SetNodeCollectionNodesCommand(Vec<Handle<Node>>): nodes, "Set Node Collection Members";
+    SetNodeCollectionLockedCommand(bool): locked, "Set Node Collection Locked";
+}
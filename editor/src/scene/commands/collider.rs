@@ -139,3 +139,118 @@ impl Command for SetTrimeshColliderGeometrySourceValueCommand {
         self.swap(context)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fyrox::scene::base::BaseBuilder;
+
+    // Executes, reverts and executes again a command that changes a single shape parameter and
+    // checks that nothing else was touched.
+    macro_rules! check_round_trip {
+        ($command:ident, $shape:expr, $variant:ident, $field:ident, $value:expr) => {{
+            let mut graph = Graph::new();
+            let collider = ColliderBuilder::new(BaseBuilder::new())
+                .with_shape($shape)
+                .build(&mut graph);
+
+            let initial = graph[collider].as_collider().shape_value();
+            let mut expected = initial.clone();
+            if let ColliderShape::$variant(ref mut shape) = expected {
+                shape.$field = $value;
+            } else {
+                unreachable!()
+            }
+            assert_ne!(initial, expected);
+
+            let mut command = $command::new(collider, $value);
+
+            command.swap(&mut graph);
+            assert_eq!(graph[collider].as_collider().shape_value(), expected);
+
+            command.swap(&mut graph);
+            assert_eq!(graph[collider].as_collider().shape_value(), initial);
+
+            command.swap(&mut graph);
+            assert_eq!(graph[collider].as_collider().shape_value(), expected);
+        }};
+    }
+
+    #[test]
+    fn test_ball_commands_round_trip() {
+        check_round_trip!(
+            SetBallRadiusCommand,
+            ColliderShape::ball(0.5),
+            Ball,
+            radius,
+            2.0
+        );
+    }
+
+    #[test]
+    fn test_cuboid_commands_round_trip() {
+        check_round_trip!(
+            SetCuboidHalfExtentsCommand,
+            ColliderShape::cuboid(0.5, 0.5, 0.5),
+            Cuboid,
+            half_extents,
+            Vector3::new(1.0, 0.5, 2.0)
+        );
+    }
+
+    #[test]
+    fn test_cylinder_commands_round_trip() {
+        check_round_trip!(
+            SetCylinderHalfHeightCommand,
+            ColliderShape::cylinder(0.5, 0.5),
+            Cylinder,
+            half_height,
+            1.5
+        );
+        check_round_trip!(
+            SetCylinderRadiusCommand,
+            ColliderShape::cylinder(0.5, 0.5),
+            Cylinder,
+            radius,
+            0.25
+        );
+    }
+
+    #[test]
+    fn test_cone_commands_round_trip() {
+        check_round_trip!(
+            SetConeHalfHeightCommand,
+            ColliderShape::cone(0.5, 0.5),
+            Cone,
+            half_height,
+            1.5
+        );
+        check_round_trip!(
+            SetConeRadiusCommand,
+            ColliderShape::cone(0.5, 0.5),
+            Cone,
+            radius,
+            0.25
+        );
+    }
+
+    #[test]
+    fn test_capsule_commands_round_trip() {
+        let capsule = || ColliderShape::capsule(Vector3::default(), Vector3::y(), 0.5);
+        check_round_trip!(
+            SetCapsuleBeginCommand,
+            capsule(),
+            Capsule,
+            begin,
+            Vector3::new(0.0, -1.0, 0.0)
+        );
+        check_round_trip!(
+            SetCapsuleEndCommand,
+            capsule(),
+            Capsule,
+            end,
+            Vector3::new(0.0, 3.0, 0.0)
+        );
+        check_round_trip!(SetCapsuleRadiusCommand, capsule(), Capsule, radius, 0.1);
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{scene::editor_data::NodeEditorData, Command, SceneContext};
+use fyrox::{core::pool::Handle, scene::node::Node};
+
+#[derive(Debug)]
+pub struct SetNodeEditorDataCommand {
+    handle: Handle<Node>,
+    data: NodeEditorData,
+}
+
+impl SetNodeEditorDataCommand {
+    pub fn new(handle: Handle<Node>, data: NodeEditorData) -> Self {
+        Self { handle, data }
+    }
+
+    fn swap(&mut self, context: &mut SceneContext) {
+        let data = std::mem::take(&mut self.data);
+        self.data = context.editor_scene.editor_data.set(self.handle, data);
+    }
+}
+
+impl Command for SetNodeEditorDataCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Set Node Editor Data".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+}
@@ -0,0 +1,18 @@
+use crate::{
+    define_swap_command,
+    scene::commands::{Command, SceneContext},
+};
+use fyrox::{
+    core::{algebra::Vector3, color::Color},
+    scene::{fog_volume::FogVolumeShape, node::Node},
+};
+
+define_swap_command! {
+    Node::as_fog_volume_mut,
+    SetFogVolumeShapeCommand(FogVolumeShape): shape, set_shape, "Set Fog Volume Shape";
+    SetFogVolumeExtentsCommand(Vector3<f32>): extents, set_extents, "Set Fog Volume Extents";
+    SetFogVolumeRadiusCommand(f32): radius, set_radius, "Set Fog Volume Radius";
+    SetFogVolumeDensityCommand(f32): density, set_density, "Set Fog Volume Density";
+    SetFogVolumeColorCommand(Color): color, set_color, "Set Fog Volume Color";
+    SetFogVolumeScatterLightsCommand(bool): is_scatter_lights, set_scatter_lights, "Set Fog Volume Scatter Lights";
+}
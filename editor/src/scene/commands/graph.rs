@@ -1,22 +1,28 @@
 use crate::{
-    command::Command, define_node_command, define_swap_command, define_vec_add_remove_commands,
-    scene::commands::SceneContext,
+    command::Command,
+    define_node_command, define_swap_command, define_vec_add_remove_commands,
+    scene::{commands::SceneContext, GraphSelection, Selection},
+    Message,
 };
 use fyrox::{
     animation::Animation,
     core::{
         algebra::{UnitQuaternion, Vector3},
         pool::{Handle, Ticket},
-        visitor::Visitor,
+        visitor::{VisitError, Visitor},
     },
+    engine::SerializationContext,
     scene::{
-        base::{deserialize_script, visit_opt_script, Mobility, Property, PropertyValue},
+        base::{
+            deserialize_script, visit_opt_script, LayerMask, Mobility, Property, PropertyValue,
+        },
         graph::{Graph, SubGraph},
         node::Node,
     },
     script::Script,
+    utils::log::Log,
 };
-use std::io::Cursor;
+use std::{io::Cursor, sync::Arc};
 
 #[derive(Debug)]
 pub struct MoveNodeCommand {
@@ -155,6 +161,117 @@ impl Command for RotateNodeCommand {
     }
 }
 
+/// Moves multiple nodes at once, so the whole edit is undone in one step. Nodes that were deleted
+/// since the command was created are skipped.
+#[derive(Debug)]
+pub struct MoveNodesCommand {
+    nodes: Vec<(Handle<Node>, Vector3<f32>, Vector3<f32>)>,
+}
+
+impl MoveNodesCommand {
+    /// Creates a new command from a list of `(node, old_position, new_position)` entries.
+    pub fn new(nodes: Vec<(Handle<Node>, Vector3<f32>, Vector3<f32>)>) -> Self {
+        Self { nodes }
+    }
+
+    fn swap(&mut self, graph: &mut Graph) {
+        for (handle, old_position, new_position) in self.nodes.iter_mut() {
+            if let Some(node) = graph.try_get_mut(*handle) {
+                node.local_transform_mut().set_position(*new_position);
+            }
+            std::mem::swap(old_position, new_position);
+        }
+    }
+}
+
+impl Command for MoveNodesCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        format!("Move {} Nodes", self.nodes.len())
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(&mut context.scene.graph);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(&mut context.scene.graph);
+    }
+}
+
+/// Scales multiple nodes at once, so the whole edit is undone in one step. Nodes that were deleted
+/// since the command was created are skipped.
+#[derive(Debug)]
+pub struct ScaleNodesCommand {
+    nodes: Vec<(Handle<Node>, Vector3<f32>, Vector3<f32>)>,
+}
+
+impl ScaleNodesCommand {
+    /// Creates a new command from a list of `(node, old_scale, new_scale)` entries.
+    pub fn new(nodes: Vec<(Handle<Node>, Vector3<f32>, Vector3<f32>)>) -> Self {
+        Self { nodes }
+    }
+
+    fn swap(&mut self, graph: &mut Graph) {
+        for (handle, old_scale, new_scale) in self.nodes.iter_mut() {
+            if let Some(node) = graph.try_get_mut(*handle) {
+                node.local_transform_mut().set_scale(*new_scale);
+            }
+            std::mem::swap(old_scale, new_scale);
+        }
+    }
+}
+
+impl Command for ScaleNodesCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        format!("Scale {} Nodes", self.nodes.len())
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(&mut context.scene.graph);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(&mut context.scene.graph);
+    }
+}
+
+/// Rotates multiple nodes at once, so the whole edit is undone in one step. Nodes that were deleted
+/// since the command was created are skipped.
+#[derive(Debug)]
+pub struct RotateNodesCommand {
+    nodes: Vec<(Handle<Node>, UnitQuaternion<f32>, UnitQuaternion<f32>)>,
+}
+
+impl RotateNodesCommand {
+    /// Creates a new command from a list of `(node, old_rotation, new_rotation)` entries.
+    pub fn new(nodes: Vec<(Handle<Node>, UnitQuaternion<f32>, UnitQuaternion<f32>)>) -> Self {
+        Self { nodes }
+    }
+
+    fn swap(&mut self, graph: &mut Graph) {
+        for (handle, old_rotation, new_rotation) in self.nodes.iter_mut() {
+            if let Some(node) = graph.try_get_mut(*handle) {
+                node.local_transform_mut().set_rotation(*new_rotation);
+            }
+            std::mem::swap(old_rotation, new_rotation);
+        }
+    }
+}
+
+impl Command for RotateNodesCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        format!("Rotate {} Nodes", self.nodes.len())
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(&mut context.scene.graph);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(&mut context.scene.graph);
+    }
+}
+
 #[derive(Debug)]
 pub struct LinkNodesCommand {
     child: Handle<Node>,
@@ -333,6 +450,309 @@ impl Command for DeleteSubGraphCommand {
     }
 }
 
+/// Makes a deep copy of a sub-graph and links the copy with the parent of the original, the copy
+/// becomes selected. Scripts are copied through serialization, so the copies have the same state
+/// as the originals would have after saving and loading the scene. Animations, that animate nodes
+/// of the sub-graph only, are copied as well and animate the copy.
+#[derive(Debug)]
+pub struct DuplicateNodeCommand {
+    original: Handle<Node>,
+    copy: Handle<Node>,
+    parent: Handle<Node>,
+    animations: Vec<Handle<Animation>>,
+    sub_graph: Option<SubGraph>,
+    animations_container: Vec<(Ticket<Animation>, Animation)>,
+    old_selection: Selection,
+}
+
+impl DuplicateNodeCommand {
+    pub fn new(original: Handle<Node>) -> Self {
+        Self {
+            original,
+            copy: Default::default(),
+            parent: Default::default(),
+            animations: Default::default(),
+            sub_graph: None,
+            animations_container: Default::default(),
+            old_selection: Selection::None,
+        }
+    }
+
+    fn duplicate(&mut self, context: &mut SceneContext) {
+        let graph = &mut context.scene.graph;
+
+        self.parent = graph[self.original].parent();
+
+        let (copy, old_new_mapping) = graph.copy_node_inplace(self.original, &mut |_, _| true);
+        graph.link_nodes(copy, self.parent);
+        self.copy = copy;
+
+        for (&original, &copy) in old_new_mapping.iter() {
+            if graph[original].script.is_none() {
+                continue;
+            }
+
+            match copy_script(&mut graph[original].script, &context.serialization_context) {
+                Ok(mut script) => {
+                    if let Some(script) = script.as_mut() {
+                        script.remap_handles(&old_new_mapping);
+                        script.restore_resources(context.resource_manager.clone());
+                    }
+                    graph[copy].script = script;
+                }
+                Err(e) => Log::err(format!(
+                    "Unable to copy script of {} node, its clone is used instead. Reason: {:?}",
+                    graph[original].name(),
+                    e
+                )),
+            }
+        }
+
+        let animations = &mut context.scene.animations;
+        let copies = animations
+            .iter()
+            .filter(|animation| {
+                let tracks = animation.get_tracks();
+                !tracks.is_empty()
+                    && tracks
+                        .iter()
+                        .all(|track| old_new_mapping.contains_key(&track.get_node()))
+            })
+            .map(|animation| {
+                let mut copy = animation.clone();
+                for track in copy.get_tracks_mut() {
+                    track.set_node(old_new_mapping[&track.get_node()]);
+                }
+                copy
+            })
+            .collect::<Vec<_>>();
+        self.animations = copies
+            .into_iter()
+            .map(|animation| animations.add(animation))
+            .collect();
+    }
+}
+
+/// Makes a copy of the script by serializing and deserializing it.
+fn copy_script(
+    script: &mut Option<Script>,
+    serialization_context: &Arc<SerializationContext>,
+) -> Result<Option<Script>, VisitError> {
+    let mut visitor = Visitor::new();
+    visitor.environment = Some(serialization_context.clone());
+    visit_opt_script("Script", script, &mut visitor)?;
+    let data = visitor.save_binary_to_vec()?;
+
+    let mut visitor = Visitor::load_from_memory(data)?;
+    visitor.environment = Some(serialization_context.clone());
+    let mut copy = None;
+    visit_opt_script("Script", &mut copy, &mut visitor)?;
+    Ok(copy)
+}
+
+impl Command for DuplicateNodeCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Duplicate Node".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        match self.sub_graph.take() {
+            None => self.duplicate(context),
+            Some(sub_graph) => {
+                let graph = &mut context.scene.graph;
+                self.copy = graph.put_sub_graph_back(sub_graph);
+                graph.link_nodes(self.copy, self.parent);
+                for (ticket, animation) in self.animations_container.drain(..) {
+                    context.scene.animations.put_back(ticket, animation);
+                }
+            }
+        }
+
+        self.old_selection = std::mem::replace(
+            &mut context.editor_scene.selection,
+            Selection::Graph(GraphSelection::single_or_empty(self.copy)),
+        );
+        context
+            .message_sender
+            .send(Message::SelectionChanged)
+            .unwrap();
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.sub_graph = Some(context.scene.graph.take_reserve_sub_graph(self.copy));
+        self.animations_container = self
+            .animations
+            .iter()
+            .map(|&animation| context.scene.animations.take_reserve(animation))
+            .collect();
+
+        context.editor_scene.selection =
+            std::mem::replace(&mut self.old_selection, Selection::None);
+        context
+            .message_sender
+            .send(Message::SelectionChanged)
+            .unwrap();
+    }
+
+    fn finalize(&mut self, context: &mut SceneContext) {
+        if let Some(sub_graph) = self.sub_graph.take() {
+            context.scene.graph.forget_sub_graph(sub_graph)
+        }
+        for (ticket, _) in self.animations_container.drain(..) {
+            context.scene.animations.forget_ticket(ticket);
+        }
+    }
+}
+
+/// Adds previously extracted sub-graph (see [`Graph::take_reserve_sub_graph`]) back to the
+/// graph and links its root with the given parent.
+#[derive(Debug)]
+pub struct AddSubGraphCommand {
+    sub_graph_root: Handle<Node>,
+    sub_graph: Option<SubGraph>,
+    parent: Handle<Node>,
+}
+
+impl AddSubGraphCommand {
+    pub fn new(sub_graph: SubGraph, parent: Handle<Node>) -> Self {
+        Self {
+            sub_graph_root: Default::default(),
+            sub_graph: Some(sub_graph),
+            parent,
+        }
+    }
+}
+
+impl Command for AddSubGraphCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Add Sub Graph".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.sub_graph_root = context
+            .scene
+            .graph
+            .put_sub_graph_back(self.sub_graph.take().unwrap());
+        context
+            .scene
+            .graph
+            .link_nodes(self.sub_graph_root, self.parent);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.sub_graph = Some(
+            context
+                .scene
+                .graph
+                .take_reserve_sub_graph(self.sub_graph_root),
+        );
+    }
+
+    fn finalize(&mut self, context: &mut SceneContext) {
+        if let Some(sub_graph) = self.sub_graph.take() {
+            context.scene.graph.forget_sub_graph(sub_graph)
+        }
+    }
+}
+
+/// Records that a node of a prefab was deleted from its instance, see
+/// [`Graph::suppress_resource_node`]. The node itself must be deleted by a separate command.
+#[derive(Debug)]
+pub struct SuppressResourceNodeCommand {
+    node: Handle<Node>,
+    instance_root: Handle<Node>,
+    original: Handle<Node>,
+}
+
+impl SuppressResourceNodeCommand {
+    pub fn new(node: Handle<Node>) -> Self {
+        Self {
+            node,
+            instance_root: Handle::NONE,
+            original: Handle::NONE,
+        }
+    }
+}
+
+impl Command for SuppressResourceNodeCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Suppress Prefab Node".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        let graph = &mut context.scene.graph;
+        self.original = graph[self.node].original_handle_in_resource();
+        self.instance_root = graph.suppress_resource_node(self.node);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        if self.instance_root.is_some() {
+            context
+                .scene
+                .graph
+                .unsuppress_resource_node(self.instance_root, self.original);
+        }
+    }
+}
+
+/// Restores a suppressed node of a prefab in its instance, see
+/// [`Graph::restore_suppressed_node`].
+#[derive(Debug)]
+pub struct RestoreSuppressedNodeCommand {
+    instance_root: Handle<Node>,
+    original: Handle<Node>,
+    restored: Handle<Node>,
+    parent: Handle<Node>,
+    sub_graph: Option<SubGraph>,
+}
+
+impl RestoreSuppressedNodeCommand {
+    pub fn new(instance_root: Handle<Node>, original: Handle<Node>) -> Self {
+        Self {
+            instance_root,
+            original,
+            restored: Handle::NONE,
+            parent: Handle::NONE,
+            sub_graph: None,
+        }
+    }
+}
+
+impl Command for RestoreSuppressedNodeCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Restore Prefab Node".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        let graph = &mut context.scene.graph;
+        if let Some(sub_graph) = self.sub_graph.take() {
+            // Put the same nodes back on redo, so handles in other commands remain valid.
+            graph.put_sub_graph_back(sub_graph);
+            graph.link_nodes(self.restored, self.parent);
+            graph.unsuppress_resource_node(self.instance_root, self.original);
+        } else {
+            self.restored = graph.restore_suppressed_node(self.instance_root, self.original);
+            if self.restored.is_some() {
+                self.parent = graph[self.restored].parent();
+            }
+        }
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        if self.restored.is_some() {
+            let graph = &mut context.scene.graph;
+            graph.suppress_resource_node(self.restored);
+            self.sub_graph = Some(graph.take_reserve_sub_graph(self.restored));
+        }
+    }
+
+    fn finalize(&mut self, context: &mut SceneContext) {
+        if let Some(sub_graph) = self.sub_graph.take() {
+            context.scene.graph.forget_sub_graph(sub_graph)
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct AddNodeCommand {
     ticket: Option<Ticket<Node>>,
@@ -468,10 +888,13 @@ define_swap_command! {
     SetTagCommand(String): tag_owned, set_tag, "Set Tag";
     SetFrustumCullingCommand(bool): frustum_culling, set_frustum_culling, "Set Frustum Culling";
     SetVisibleCommand(bool): visibility, set_visibility, "Set Visible";
+    SetEnabledCommand(bool): is_enabled, set_enabled, "Set Enabled";
     //SetLifetimeCommand(Option<f32>): lifetime, set_lifetime, "Set Lifetime";
     SetMobilityCommand(Mobility): mobility, set_mobility, "Set Mobility";
     SetDepthOffsetCommand(f32): depth_offset_factor, set_depth_offset_factor, "Set Depth Offset";
     SetCastShadowsCommand(bool): cast_shadows, set_cast_shadows, "Set Cast Shadows";
+    SetRenderMaskCommand(LayerMask): render_mask, set_render_mask, "Set Render Mask";
+    SetPersistentCommand(bool): is_persistent, set_persistent, "Set Persistent";
 }
 
 define_node_command! {
@@ -608,3 +1031,160 @@ impl Command for ScriptDataBlobCommand {
         self.swap(context);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use fyrox::{
+        core::algebra::Vector2,
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            joint::{BallJoint, JointBuilder, JointParams},
+            pivot::PivotBuilder,
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+        },
+    };
+
+    fn update(graph: &mut Graph, frames: usize) {
+        for _ in 0..frames {
+            graph.update(Vector2::new(800.0, 600.0), 1.0 / 60.0);
+        }
+    }
+
+    fn make_body(
+        graph: &mut Graph,
+        position: Vector3<f32>,
+        body_type: RigidBodyType,
+    ) -> Handle<Node> {
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::ball(0.1))
+            .build(graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                )
+                .with_children(&[collider]),
+        )
+        .with_body_type(body_type)
+        .build(graph)
+    }
+
+    #[test]
+    fn test_move_nodes_undo_skips_deleted_nodes() {
+        let mut graph = Graph::new();
+
+        let positions = (0..3)
+            .map(|i| Vector3::new(i as f32, 0.0, 0.0))
+            .collect::<Vec<_>>();
+        let nodes = positions
+            .iter()
+            .map(|position| {
+                PivotBuilder::new(
+                    BaseBuilder::new().with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(*position)
+                            .build(),
+                    ),
+                )
+                .build(&mut graph)
+            })
+            .collect::<Vec<_>>();
+        let offset = Vector3::new(0.0, 1.0, 0.0);
+        let local_position =
+            |graph: &Graph, node: Handle<Node>| **graph[node].local_transform().position();
+
+        let mut command = MoveNodesCommand::new(
+            nodes
+                .iter()
+                .zip(positions.iter())
+                .map(|(node, position)| (*node, *position, position + offset))
+                .collect(),
+        );
+
+        // Execute.
+        command.swap(&mut graph);
+        for (node, initial) in nodes.iter().zip(positions.iter()) {
+            assert_eq!(local_position(&graph, *node), initial + offset);
+        }
+
+        // Undo after one of the nodes was deleted, the rest must be restored.
+        graph.remove_node(nodes[1]);
+        command.swap(&mut graph);
+        assert_eq!(local_position(&graph, nodes[0]), positions[0]);
+        assert_eq!(local_position(&graph, nodes[2]), positions[2]);
+
+        // Redo.
+        command.swap(&mut graph);
+        assert_eq!(local_position(&graph, nodes[0]), positions[0] + offset);
+        assert_eq!(local_position(&graph, nodes[2]), positions[2] + offset);
+    }
+
+    #[test]
+    fn test_joint_survives_reparenting_undo() {
+        let mut graph = Graph::new();
+
+        // A pendulum: a dynamic body hangs on a static one.
+        let anchor = make_body(&mut graph, Vector3::default(), RigidBodyType::Static);
+        let weight = make_body(
+            &mut graph,
+            Vector3::new(1.0, 0.0, 0.0),
+            RigidBodyType::Dynamic,
+        );
+        let joint = JointBuilder::new(BaseBuilder::new())
+            .with_params(JointParams::BallJoint(BallJoint {
+                local_anchor1: Vector3::default(),
+                local_anchor2: Vector3::new(-1.0, 0.0, 0.0),
+                limits_angles: [-std::f32::consts::PI, std::f32::consts::PI],
+            }))
+            .with_body1(anchor)
+            .with_body2(weight)
+            .build(&mut graph);
+        let disabled = PivotBuilder::new(BaseBuilder::new().with_enabled(false)).build(&mut graph);
+
+        update(&mut graph, 1);
+        assert!(graph[joint].as_joint().is_active());
+        assert_eq!(
+            graph.connected_joints(anchor).collect::<Vec<_>>(),
+            vec![joint]
+        );
+        assert_eq!(
+            graph.connected_joints(weight).collect::<Vec<_>>(),
+            vec![joint]
+        );
+        assert_eq!(graph.connected_joints(disabled).count(), 0);
+
+        // Moving the body to a disabled node removes it from simulation, the joint cannot work.
+        let mut command = LinkNodesCommand::new(weight, disabled);
+        command.link(&mut graph);
+        update(&mut graph, 1);
+        assert_eq!(graph[weight].parent(), disabled);
+        assert!(!graph[joint].as_joint().is_active());
+        assert_eq!(
+            graph.connected_joints(weight).collect::<Vec<_>>(),
+            vec![joint]
+        );
+
+        // Undo must bring the joint back.
+        command.link(&mut graph);
+        update(&mut graph, 1);
+        assert_eq!(graph[weight].parent(), graph.get_root());
+        assert!(graph[joint].as_joint().is_active());
+
+        // The weight must swing around the anchor instead of falling down.
+        update(&mut graph, 60);
+        let anchor_position = graph[anchor].global_position();
+        let weight_position = graph[weight].global_position();
+        assert!(weight_position.y < -0.1);
+        assert!(((weight_position - anchor_position).norm() - 1.0).abs() < 0.05);
+
+        // Redo breaks it again.
+        command.link(&mut graph);
+        update(&mut graph, 1);
+        assert!(!graph[joint].as_joint().is_active());
+    }
+}
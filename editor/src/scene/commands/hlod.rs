@@ -0,0 +1,82 @@
+use crate::{command::Command, define_swap_command, scene::commands::SceneContext};
+use fyrox::{
+    core::pool::Handle,
+    scene::{graph::SubGraph, node::Node},
+};
+
+define_swap_command! {
+    Node::as_hlod_mut,
+    SetHlodSwitchDistanceCommand(f32): switch_distance, set_switch_distance, "Set HLOD Switch Distance";
+    SetHlodHysteresisCommand(f32): hysteresis, set_hysteresis, "Set HLOD Hysteresis";
+}
+
+/// Applies result of HLOD baking - adds new HLOD nodes under the root and removes stale ones.
+#[derive(Debug)]
+pub struct BakeHlodCommand {
+    root: Handle<Node>,
+    // Sub-graphs of new HLOD nodes, they're empty while the command is applied.
+    added: Vec<SubGraph>,
+    added_roots: Vec<Handle<Node>>,
+    // Stale HLOD nodes with their parents.
+    stale: Vec<(Handle<Node>, Handle<Node>)>,
+    // Sub-graphs of stale HLOD nodes, they're filled while the command is applied.
+    stale_sub_graphs: Vec<SubGraph>,
+}
+
+impl BakeHlodCommand {
+    pub fn new(root: Handle<Node>, added: Vec<SubGraph>, stale: Vec<Handle<Node>>) -> Self {
+        Self {
+            root,
+            added,
+            added_roots: Default::default(),
+            stale: stale.into_iter().map(|hlod| (hlod, Handle::NONE)).collect(),
+            stale_sub_graphs: Default::default(),
+        }
+    }
+}
+
+impl Command for BakeHlodCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Bake HLOD".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        let graph = &mut context.scene.graph;
+
+        for sub_graph in self.added.drain(..) {
+            let root = graph.put_sub_graph_back(sub_graph);
+            graph.link_nodes(root, self.root);
+            self.added_roots.push(root);
+        }
+
+        for (hlod, parent) in self.stale.iter_mut() {
+            *parent = graph[*hlod].parent();
+            self.stale_sub_graphs
+                .push(graph.take_reserve_sub_graph(*hlod));
+        }
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        let graph = &mut context.scene.graph;
+
+        for (sub_graph, &(_, parent)) in self
+            .stale_sub_graphs
+            .drain(..)
+            .rev()
+            .zip(self.stale.iter().rev())
+        {
+            let hlod = graph.put_sub_graph_back(sub_graph);
+            graph.link_nodes(hlod, parent);
+        }
+
+        for root in self.added_roots.drain(..).rev() {
+            self.added.push(graph.take_reserve_sub_graph(root));
+        }
+    }
+
+    fn finalize(&mut self, context: &mut SceneContext) {
+        for sub_graph in self.added.drain(..).chain(self.stale_sub_graphs.drain(..)) {
+            context.scene.graph.forget_sub_graph(sub_graph);
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use crate::{
+    define_swap_command,
+    scene::commands::{Command, SceneContext},
+};
+use fyrox::{core::pool::Handle, scene::node::Node};
+
+define_swap_command! {
+    Node::as_two_bone_ik_mut,
+    SetTwoBoneIkRootCommand(Handle<Node>): root, set_root, "Set Two-Bone IK Root";
+    SetTwoBoneIkMiddleCommand(Handle<Node>): middle, set_middle, "Set Two-Bone IK Middle";
+    SetTwoBoneIkTipCommand(Handle<Node>): tip, set_tip, "Set Two-Bone IK Tip";
+    SetTwoBoneIkTargetCommand(Handle<Node>): target, set_target, "Set Two-Bone IK Target";
+    SetTwoBoneIkPoleCommand(Handle<Node>): pole, set_pole, "Set Two-Bone IK Pole";
+    SetTwoBoneIkWeightCommand(f32): weight, set_weight, "Set Two-Bone IK Weight";
+}
@@ -0,0 +1,103 @@
+use crate::{command::Command, define_swap_command, scene::commands::SceneContext};
+use fyrox::{
+    core::{
+        algebra::{Vector2, Vector3},
+        pool::Handle,
+    },
+    resource::texture::Texture,
+    scene::{base::LodGroup, graph::SubGraph, node::Node},
+};
+
+define_swap_command! {
+    Node::as_impostor_mut,
+    SetImpostorAtlasCommand(Option<Texture>): atlas_value, set_atlas, "Set Impostor Atlas";
+    SetImpostorNormalAtlasCommand(Option<Texture>): normal_atlas_value, set_normal_atlas, "Set Impostor Normal Atlas";
+    SetImpostorFrameCountCommand(u32): frame_count, set_frame_count, "Set Impostor Frame Count";
+    SetImpostorSizeCommand(Vector2<f32>): size, set_size, "Set Impostor Size";
+    SetImpostorCenterCommand(Vector3<f32>): center, set_center, "Set Impostor Center";
+    SetImpostorBlendFramesCommand(bool): is_blend_frames, set_blend_frames, "Set Impostor Blend Frames";
+}
+
+/// Applies result of impostor baking - adds new impostor under the root, removes previously baked
+/// impostors and sets new LOD group of the root.
+#[derive(Debug)]
+pub struct BakeImpostorCommand {
+    root: Handle<Node>,
+    // Sub-graph of the new impostor, it is empty while the command is applied.
+    added: Option<SubGraph>,
+    added_root: Handle<Node>,
+    stale: Vec<Handle<Node>>,
+    // Sub-graphs of previously baked impostors, they're filled while the command is applied.
+    stale_sub_graphs: Vec<SubGraph>,
+    lod_group: Option<LodGroup>,
+}
+
+impl BakeImpostorCommand {
+    pub fn new(
+        root: Handle<Node>,
+        added: SubGraph,
+        stale: Vec<Handle<Node>>,
+        lod_group: LodGroup,
+    ) -> Self {
+        Self {
+            root,
+            added: Some(added),
+            added_root: Default::default(),
+            stale,
+            stale_sub_graphs: Default::default(),
+            lod_group: Some(lod_group),
+        }
+    }
+
+    fn swap_lod_group(&mut self, context: &mut SceneContext) {
+        let root = &mut context.scene.graph[self.root];
+        let old = root.set_lod_group(self.lod_group.take());
+        self.lod_group = old;
+    }
+}
+
+impl Command for BakeImpostorCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Bake Impostor".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        let graph = &mut context.scene.graph;
+
+        if let Some(sub_graph) = self.added.take() {
+            self.added_root = graph.put_sub_graph_back(sub_graph);
+            graph.link_nodes(self.added_root, self.root);
+        }
+
+        for &stale in self.stale.iter() {
+            self.stale_sub_graphs
+                .push(graph.take_reserve_sub_graph(stale));
+        }
+
+        self.swap_lod_group(context);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap_lod_group(context);
+
+        let graph = &mut context.scene.graph;
+
+        for sub_graph in self.stale_sub_graphs.drain(..).rev() {
+            let stale = graph.put_sub_graph_back(sub_graph);
+            graph.link_nodes(stale, self.root);
+        }
+
+        self.added = Some(graph.take_reserve_sub_graph(self.added_root));
+    }
+
+    fn finalize(&mut self, context: &mut SceneContext) {
+        for sub_graph in self
+            .added
+            .take()
+            .into_iter()
+            .chain(self.stale_sub_graphs.drain(..))
+        {
+            context.scene.graph.forget_sub_graph(sub_graph);
+        }
+    }
+}
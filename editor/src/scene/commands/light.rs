@@ -3,10 +3,15 @@ use crate::{
     scene::commands::{Command, SceneContext},
 };
 use fyrox::{
-    core::{algebra::Vector3, color::Color},
+    core::{algebra::Vector3, color::Color, pool::Handle},
     resource::texture::Texture,
     scene::{
-        light::{point::PointLight, spot::SpotLight, BaseLight},
+        graph::Graph,
+        light::{
+            point::PointLight,
+            spot::{SpotLight, SpotLightProjection},
+            BaseLight, ScatterPreset,
+        },
         node::Node,
     },
 };
@@ -18,12 +23,73 @@ fn node_base_light_mut(node: &mut Node) -> &mut BaseLight {
 define_swap_command! {
     node_base_light_mut,
     SetLightScatterCommand(Vector3<f32>): scatter, set_scatter, "Set Light Scatter";
+    SetLightScatterDensityCommand(f32): scatter_density, set_scatter_density, "Set Light Scatter Density";
+    SetLightScatterAnisotropyCommand(f32): scatter_anisotropy, set_scatter_anisotropy, "Set Light Scatter Anisotropy";
     SetLightScatterEnabledCommand(bool): is_scatter_enabled, enable_scatter, "Set Light Scatter Enabled";
     SetLightIntensityCommand(f32): intensity, set_intensity, "Set Light Intensity";
     SetLightCastShadowsCommand(bool): is_cast_shadows, set_cast_shadows, "Set Light Cast Shadows";
+    SetLightShadowsMaxDistanceCommand(f32): shadows_max_distance, set_shadows_max_distance, "Set Light Shadows Max Distance";
+    SetLightStaticShadowsCommand(bool): is_static_shadows, set_static_shadows, "Set Light Static Shadows";
     SetLightColorCommand(Color): color, set_color, "Set Light Color";
 }
 
+/// Applies a scatter preset to a light. A preset changes multiple scattering parameters at once,
+/// so the command remembers all of them to be able to restore parameters that were tweaked after
+/// the previous preset was applied.
+#[derive(Debug)]
+pub struct SetLightScatterPresetCommand {
+    handle: Handle<Node>,
+    preset: ScatterPreset,
+    // Scatter factor, density and anisotropy, `None` until the command is executed for the first
+    // time - the values are taken from the preset then.
+    parameters: Option<(Vector3<f32>, f32, f32)>,
+}
+
+impl SetLightScatterPresetCommand {
+    pub fn new(handle: Handle<Node>, preset: ScatterPreset) -> Self {
+        Self {
+            handle,
+            preset,
+            parameters: None,
+        }
+    }
+
+    fn swap(&mut self, graph: &mut Graph) {
+        let light = node_base_light_mut(&mut graph[self.handle]);
+
+        let old_preset = light.scatter_preset();
+        let old_parameters = (
+            light.scatter(),
+            light.scatter_density(),
+            light.scatter_anisotropy(),
+        );
+
+        light.set_scatter_preset(self.preset);
+        if let Some((factor, density, anisotropy)) = self.parameters {
+            light.set_scatter(factor);
+            light.set_scatter_density(density);
+            light.set_scatter_anisotropy(anisotropy);
+        }
+
+        self.preset = old_preset;
+        self.parameters = Some(old_parameters);
+    }
+}
+
+impl Command for SetLightScatterPresetCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Set Light Scatter Preset".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(&mut context.scene.graph);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(&mut context.scene.graph);
+    }
+}
+
 fn node_as_spot_mut(node: &mut Node) -> &mut SpotLight {
     node.as_spot_light_mut()
 }
@@ -35,6 +101,10 @@ define_swap_command! {
     SetSpotLightShadowBiasCommand(f32): shadow_bias, set_shadow_bias, "Set Spot Light Shadow Bias";
     SetSpotLightDistanceCommand(f32): distance, set_distance, "Set Spot Light Distance";
     SetSpotLightCookieTextureCommand(Option<Texture>): cookie_texture, set_cookie_texture, "Set Spot Light Cookie Texture";
+    SetSpotLightProjectionCommand(SpotLightProjection): projection, set_projection, "Set Spot Light Projection";
+    SetSpotLightOrthoWidthCommand(f32): ortho_width, set_ortho_width, "Set Spot Light Ortho Width";
+    SetSpotLightOrthoHeightCommand(f32): ortho_height, set_ortho_height, "Set Spot Light Ortho Height";
+    SetSpotLightEdgeFalloffCommand(f32): edge_falloff, set_edge_falloff, "Set Spot Light Edge Falloff";
 }
 
 fn node_as_point_light_mut(node: &mut Node) -> &mut PointLight {
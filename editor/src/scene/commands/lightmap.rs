@@ -0,0 +1,51 @@
+use crate::scene::commands::{Command, SceneContext};
+use fyrox::utils::{lightmap::Lightmap, log::Log};
+
+/// Sets new lightmap to the scene or clears it if `None` is given.
+#[derive(Debug)]
+pub struct SetLightmapCommand {
+    lightmap: Option<Lightmap>,
+    name: String,
+}
+
+impl SetLightmapCommand {
+    pub fn new(lightmap: Option<Lightmap>) -> Self {
+        Self {
+            name: if lightmap.is_some() {
+                "Set Lightmap"
+            } else {
+                "Clear Lightmap"
+            }
+            .to_owned(),
+            lightmap,
+        }
+    }
+
+    fn swap(&mut self, context: &mut SceneContext) {
+        self.lightmap = match self.lightmap.take() {
+            Some(lightmap) => match context.scene.set_lightmap(lightmap) {
+                Ok(old) => old,
+                Err(e) => {
+                    Log::err(format!("Unable to set lightmap. Reason: {}", e));
+                    // Scene is left untouched, make sure that revert won't change it too.
+                    context.scene.lightmap().cloned()
+                }
+            },
+            None => context.scene.clear_lightmap(),
+        };
+    }
+}
+
+impl Command for SetLightmapCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        self.name.clone()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+}
@@ -1,15 +1,24 @@
-use crate::{command::Command, define_swap_command, scene::commands::SceneContext};
+use crate::{
+    command::Command, define_swap_command, define_vec_add_remove_commands,
+    scene::commands::SceneContext,
+};
 use fyrox::core::sstorage::ImmutableString;
 use fyrox::material::shader::SamplerFallback;
 use fyrox::material::PropertyValue;
 use fyrox::{
-    core::pool::Handle,
+    core::{algebra::Vector4, parking_lot::Mutex, pool::Handle},
     resource::texture::Texture,
     scene::{
-        mesh::{Mesh, RenderPath},
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexWriteTrait},
+            socket::Socket,
+            surface::SurfaceData,
+            Mesh, RenderPath,
+        },
         node::Node,
     },
 };
+use std::sync::Arc;
 
 #[derive(Debug)]
 enum TextureSet {
@@ -115,4 +124,164 @@ define_swap_command! {
     Node::as_mesh_mut,
     SetMeshRenderPathCommand(RenderPath): render_path, set_render_path, "Set Mesh Render Path";
     SetMeshDecalLayerIndexCommand(u8): decal_layer_index, set_decal_layer_index, "Set Mesh Decal Layer Index";
+    SetMeshBoundsPaddingCommand(f32): bounds_padding, set_bounds_padding, "Set Mesh Bounds Padding";
+}
+
+define_vec_add_remove_commands!(
+    struct AddMeshSocketCommand, RemoveMeshSocketCommand<Node, Socket>
+    (self, context) { context.scene.graph[self.handle].as_mesh_mut().sockets_mut() }
+);
+
+#[derive(Debug)]
+pub struct SetMeshSocketCommand {
+    pub handle: Handle<Node>,
+    pub index: usize,
+    pub socket: Socket,
+}
+
+impl SetMeshSocketCommand {
+    fn swap(&mut self, context: &mut SceneContext) {
+        std::mem::swap(
+            &mut context.scene.graph[self.handle].as_mesh_mut().sockets_mut()[self.index],
+            &mut self.socket,
+        );
+    }
+}
+
+impl Command for SetMeshSocketCommand {
+    fn name(&mut self, _: &SceneContext) -> String {
+        "Set Mesh Socket".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(context)
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(context)
+    }
+}
+
+/// Replaces data of a surface of a mesh. It is used to give a mesh its own copy of surface data
+/// before the data is modified, so shared data (for example, data of a model resource) stays
+/// intact. The data is saved with the scene, see [`Mesh::set_surface_data`].
+#[derive(Debug)]
+pub struct SetSurfaceDataCommand {
+    handle: Handle<Node>,
+    surface_index: usize,
+    data: Arc<Mutex<SurfaceData>>,
+    surfaces_modified: bool,
+}
+
+impl SetSurfaceDataCommand {
+    pub fn new(handle: Handle<Node>, surface_index: usize, data: Arc<Mutex<SurfaceData>>) -> Self {
+        Self {
+            handle,
+            surface_index,
+            data,
+            surfaces_modified: true,
+        }
+    }
+
+    fn swap(&mut self, context: &mut SceneContext) {
+        if let Some(mesh) = context
+            .scene
+            .graph
+            .try_get_mut(self.handle)
+            .and_then(|node| node.cast_mut::<Mesh>())
+        {
+            let surfaces_modified = mesh.is_surfaces_modified();
+            if let Some(old_data) = mesh.set_surface_data(self.surface_index, self.data.clone()) {
+                // Surfaces that were inherited from a model resource must be inherited again.
+                if !self.surfaces_modified {
+                    mesh.reset_surfaces_modified_flag();
+                }
+                self.data = old_data;
+                self.surfaces_modified = surfaces_modified;
+            }
+        }
+    }
+}
+
+impl Command for SetSurfaceDataCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Make Surface Data Unique".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+}
+
+/// A change of color of a single vertex of a surface.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VertexColorChange {
+    pub surface: usize,
+    pub vertex: u32,
+    pub old_color: Vector4<f32>,
+    pub new_color: Vector4<f32>,
+}
+
+/// Changes vertex colors of surfaces of a mesh, only the colors of affected vertices are stored.
+#[derive(Debug)]
+pub struct PaintVertexColorsCommand {
+    handle: Handle<Node>,
+    changes: Vec<VertexColorChange>,
+    name: &'static str,
+}
+
+impl PaintVertexColorsCommand {
+    pub fn new(handle: Handle<Node>, changes: Vec<VertexColorChange>, name: &'static str) -> Self {
+        Self {
+            handle,
+            changes,
+            name,
+        }
+    }
+
+    fn swap(&mut self, context: &mut SceneContext) {
+        let mesh = match context
+            .scene
+            .graph
+            .try_get_mut(self.handle)
+            .and_then(|node| node.cast_mut::<Mesh>())
+        {
+            Some(mesh) => mesh,
+            None => return,
+        };
+
+        for (surface_index, surface) in mesh.surfaces().iter().enumerate() {
+            let data = surface.data();
+            let mut data = data.lock();
+            let mut vertex_buffer = data.vertex_buffer.modify();
+            for change in self
+                .changes
+                .iter_mut()
+                .filter(|change| change.surface == surface_index)
+            {
+                if let Some(mut vertex) = vertex_buffer.get_mut(change.vertex as usize) {
+                    let _ = vertex.write_4_f32(VertexAttributeUsage::Color, change.new_color);
+                }
+                std::mem::swap(&mut change.old_color, &mut change.new_color);
+            }
+        }
+    }
+}
+
+impl Command for PaintVertexColorsCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        self.name.to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
 }
@@ -1,8 +1,12 @@
 use crate::{
     command::Command,
+    define_command_group,
     scene::{
-        clipboard::DeepCloneResult, commands::graph::DeleteSubGraphCommand, EditorScene,
-        GraphSelection, Selection,
+        clipboard::DeepCloneResult,
+        commands::graph::{
+            DeleteSubGraphCommand, RestoreSuppressedNodeCommand, SuppressResourceNodeCommand,
+        },
+        EditorScene, GraphSelection, Selection,
     },
     GameEngine, Message,
 };
@@ -17,27 +21,41 @@ use std::{
     sync::mpsc::Sender,
 };
 
+pub mod animation;
 pub mod camera;
+pub mod collection;
 pub mod collider;
 pub mod collider2d;
 pub mod decal;
+pub mod editor_data;
 pub mod effect;
+pub mod fog_volume;
 pub mod graph;
+pub mod hlod;
+pub mod ik;
+pub mod impostor;
 pub mod joint;
 pub mod joint2d;
 pub mod light;
+pub mod lightmap;
 pub mod lod;
 pub mod material;
 pub mod mesh;
 pub mod navmesh;
+pub mod navmesh_obstacle;
 pub mod particle_system;
+pub mod physics;
 pub mod rectangle;
+pub mod reflection_probe;
 pub mod rigidbody;
 pub mod rigidbody2d;
+pub mod scatter;
 pub mod sound;
 pub mod sound_context;
 pub mod sprite;
 pub mod terrain;
+pub mod trigger_volume;
+pub mod wind;
 
 #[macro_export]
 macro_rules! get_set_swap {
@@ -87,59 +105,19 @@ impl SceneCommand {
     }
 }
 
-#[derive(Debug)]
-pub struct CommandGroup {
-    commands: Vec<SceneCommand>,
-}
-
-impl From<Vec<SceneCommand>> for CommandGroup {
-    fn from(commands: Vec<SceneCommand>) -> Self {
-        Self { commands }
-    }
-}
-
-impl CommandGroup {
-    pub fn push(&mut self, command: SceneCommand) {
-        self.commands.push(command)
-    }
-}
-
-impl Command for CommandGroup {
-    fn name(&mut self, context: &SceneContext) -> String {
-        let mut name = String::from("Command group: ");
-        for cmd in self.commands.iter_mut() {
-            name.push_str(&cmd.name(context));
-            name.push_str(", ");
-        }
-        name
-    }
-
-    fn execute(&mut self, context: &mut SceneContext) {
-        for cmd in self.commands.iter_mut() {
-            cmd.execute(context);
-        }
-    }
-
-    fn revert(&mut self, context: &mut SceneContext) {
-        // revert must be done in reverse order.
-        for cmd in self.commands.iter_mut().rev() {
-            cmd.revert(context);
-        }
-    }
-
-    fn finalize(&mut self, context: &mut SceneContext) {
-        for mut cmd in self.commands.drain(..) {
-            cmd.finalize(context);
-        }
-    }
-}
+define_command_group!(Command, CommandGroup, SceneCommand, SceneContext);
 
 /// Creates scene command (command group) which removes current selection in editor's scene.
 /// This is **not** trivial because each node has multiple connections inside engine and
 /// in editor's data model, so we have to thoroughly build command using simple commands.
+///
+/// Deleted nodes of prefab instances become suppressed, so they won't be restored from the
+/// prefab on next load. Hard deletion does not leave such override, so the prefab brings
+/// the nodes back on next load.
 pub fn make_delete_selection_command(
     editor_scene: &EditorScene,
     engine: &GameEngine,
+    hard: bool,
 ) -> SceneCommand {
     let graph = &engine.scenes[editor_scene.scene].graph;
 
@@ -172,10 +150,96 @@ pub fn make_delete_selection_command(
     let root_nodes = selection.root_nodes(graph);
 
     for root_node in root_nodes {
+        let node = &graph[root_node];
+        if !hard && node.resource().is_some() && !node.is_resource_instance_root() {
+            command_group.push(SceneCommand::new(SuppressResourceNodeCommand::new(
+                root_node,
+            )));
+        }
         command_group.push(SceneCommand::new(DeleteSubGraphCommand::new(root_node)));
     }
 
-    SceneCommand::new(command_group)
+    SceneCommand::new(
+        command_group.with_custom_name(format!("Delete {} Node(s)", selection.nodes.len())),
+    )
+}
+
+/// Creates a command that restores deleted (suppressed) nodes of prefab instances, that contain
+/// selected nodes. Returns `None` if there is nothing to restore.
+pub fn make_restore_suppressed_nodes_command(
+    editor_scene: &EditorScene,
+    engine: &GameEngine,
+) -> Option<SceneCommand> {
+    let graph = &engine.scenes[editor_scene.scene].graph;
+
+    let selection = match editor_scene.selection {
+        Selection::Graph(ref selection) => selection,
+        _ => return None,
+    };
+
+    let mut instance_roots = Vec::new();
+    for &node in selection.nodes() {
+        let instance_root = graph.find_instance_root(node);
+        if instance_root.is_some() && !instance_roots.contains(&instance_root) {
+            instance_roots.push(instance_root);
+        }
+    }
+
+    let mut commands = Vec::new();
+    for instance_root in instance_roots {
+        for &original in graph[instance_root].suppressed_resource_nodes() {
+            commands.push(SceneCommand::new(RestoreSuppressedNodeCommand::new(
+                instance_root,
+                original,
+            )));
+        }
+    }
+
+    if commands.is_empty() {
+        None
+    } else {
+        Some(SceneCommand::new(
+            CommandGroup::from(commands).with_custom_name("Restore Deleted Prefab Nodes"),
+        ))
+    }
+}
+
+/// Creates a command that deletes selected nodes, that were added to prefab instances. Returns
+/// `None` if there is no such nodes in the selection.
+pub fn make_remove_added_nodes_command(
+    editor_scene: &EditorScene,
+    engine: &GameEngine,
+) -> Option<SceneCommand> {
+    let graph = &engine.scenes[editor_scene.scene].graph;
+
+    let selection = match editor_scene.selection {
+        Selection::Graph(ref selection) => selection,
+        _ => return None,
+    };
+
+    let added_nodes = selection
+        .root_nodes(graph)
+        .into_iter()
+        .filter(|&node| {
+            let instance_root = graph.find_instance_root(graph[node].parent());
+            instance_root.is_some() && graph.find_added_nodes(instance_root).contains(&node)
+        })
+        .collect::<Vec<_>>();
+
+    if added_nodes.is_empty() {
+        return None;
+    }
+
+    let mut command_group = CommandGroup::from(vec![SceneCommand::new(
+        ChangeSelectionCommand::new(Default::default(), Selection::Graph(selection.clone())),
+    )]);
+    for node in added_nodes {
+        command_group.push(SceneCommand::new(DeleteSubGraphCommand::new(node)));
+    }
+
+    Some(SceneCommand::new(
+        command_group.with_custom_name("Remove Added Nodes"),
+    ))
 }
 
 #[derive(Debug)]
@@ -193,6 +257,7 @@ impl ChangeSelectionCommand {
                 Selection::Graph(_) => "Change Selection: Graph",
                 Selection::Navmesh(_) => "Change Selection: Navmesh",
                 Selection::SoundContext => "Change Selection: Sound Context",
+                Selection::SceneSettings => "Change Selection: Scene Settings",
                 Selection::Effect(_) => "Change Selection: Effect",
             }
             .to_owned(),
@@ -0,0 +1,16 @@
+use crate::{
+    define_swap_command,
+    scene::commands::{Command, SceneContext},
+};
+use fyrox::{
+    core::algebra::Vector3,
+    scene::{navmesh_obstacle::NavmeshObstacleShape, node::Node},
+};
+
+define_swap_command! {
+    Node::as_navmesh_obstacle_mut,
+    SetNavmeshObstacleShapeCommand(NavmeshObstacleShape): shape, set_shape, "Set Navmesh Obstacle Shape";
+    SetNavmeshObstacleExtentsCommand(Vector3<f32>): extents, set_extents, "Set Navmesh Obstacle Extents";
+    SetNavmeshObstacleRadiusCommand(f32): radius, set_radius, "Set Navmesh Obstacle Radius";
+    SetNavmeshObstacleHeightCommand(f32): height, set_height, "Set Navmesh Obstacle Height";
+}
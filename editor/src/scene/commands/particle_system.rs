@@ -4,7 +4,7 @@ use fyrox::{
     resource::texture::Texture,
     scene::{
         node::Node,
-        particle_system::{emitter::Emitter, ParticleLimit, ParticleSystem},
+        particle_system::{emitter::Emitter, ParticleLimit, ParticleSystem, SimulationSpace},
     },
 };
 use std::ops::Range;
@@ -179,6 +179,8 @@ define_swap_command! {
     SetAccelerationCommand(Vector3<f32>): acceleration, set_acceleration, "Set Particle System Acceleration";
     SetParticleSystemEnabledCommand(bool): is_enabled, set_enabled, "Set Particle System Enabled";
     SetSoftBoundarySharpnessFactorCommand(f32): soft_boundary_sharpness_factor, set_soft_boundary_sharpness_factor, "Set Soft Boundary Sharpness Factor";
+    SetParticleSystemSimulationSpaceCommand(SimulationSpace): simulation_space, set_simulation_space, "Set Particle System Simulation Space";
+    SetParticleSystemWindInfluenceCommand(f32): wind_influence, set_wind_influence, "Set Particle System Wind Influence";
 }
 
 define_emitter_variant_command! {
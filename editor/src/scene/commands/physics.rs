@@ -0,0 +1,60 @@
+use crate::{Command, SceneContext};
+use fyrox::scene::{dim2, graph::physics::PhysicsSettings};
+
+#[derive(Debug)]
+pub struct SetPhysicsSettingsCommand {
+    value: PhysicsSettings,
+}
+
+impl SetPhysicsSettingsCommand {
+    pub fn new(value: PhysicsSettings) -> Self {
+        Self { value }
+    }
+
+    fn swap(&mut self, context: &mut SceneContext) {
+        std::mem::swap(&mut context.scene.graph.physics.settings, &mut self.value);
+    }
+}
+
+impl Command for SetPhysicsSettingsCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Set Physics Settings".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+}
+
+#[derive(Debug)]
+pub struct SetPhysics2DSettingsCommand {
+    value: dim2::physics::PhysicsSettings,
+}
+
+impl SetPhysics2DSettingsCommand {
+    pub fn new(value: dim2::physics::PhysicsSettings) -> Self {
+        Self { value }
+    }
+
+    fn swap(&mut self, context: &mut SceneContext) {
+        std::mem::swap(&mut context.scene.graph.physics2d.settings, &mut self.value);
+    }
+}
+
+impl Command for SetPhysics2DSettingsCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Set 2D Physics Settings".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+}
@@ -2,10 +2,16 @@ use crate::{
     define_swap_command,
     scene::commands::{Command, SceneContext},
 };
-use fyrox::{core::color::Color, resource::texture::Texture, scene::node::Node};
+use fyrox::{
+    core::{color::Color, math::Rect},
+    resource::texture::Texture,
+    scene::{dim2::rectangle::NineSlice, node::Node},
+};
 
 define_swap_command! {
     Node::as_rectangle_mut,
     SetRectangleColorCommand(Color): color, set_color, "Set Rectangle Color";
     SetRectangleTextureCommand(Option<Texture>): texture_value, set_texture, "Set Rectangle Texture";
+    SetRectangleUvRectCommand(Rect<f32>): uv_rect, set_uv_rect, "Set Rectangle UV Rect";
+    SetRectangleNineSliceCommand(Option<NineSlice>): nine_slice, set_nine_slice, "Set Rectangle Nine-Slice";
 }
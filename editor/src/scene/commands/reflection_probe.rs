@@ -0,0 +1,13 @@
+use crate::{
+    define_swap_command,
+    scene::commands::{Command, SceneContext},
+};
+use fyrox::{core::algebra::Vector3, resource::texture::Texture, scene::node::Node};
+
+define_swap_command! {
+    Node::as_reflection_probe_mut,
+    SetReflectionProbeExtentsCommand(Vector3<f32>): extents, set_extents, "Set Reflection Probe Extents";
+    SetReflectionProbeBlendDistanceCommand(f32): blend_distance, set_blend_distance, "Set Reflection Probe Blend Distance";
+    SetReflectionProbeResolutionCommand(u32): resolution, set_resolution, "Set Reflection Probe Resolution";
+    SetReflectionProbeEnvironmentCommand(Option<Texture>): environment_value, set_environment, "Set Reflection Probe Environment";
+}
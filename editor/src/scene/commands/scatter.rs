@@ -0,0 +1,87 @@
+use crate::{command::Command, scene::commands::SceneContext};
+use fyrox::{
+    core::pool::Handle,
+    scene::{graph::SubGraph, node::Node},
+};
+
+/// A single stroke of the scatter brush - adds instances that were placed by the stroke and
+/// removes instances that were erased by it.
+#[derive(Debug)]
+pub struct ScatterStrokeCommand {
+    container: Handle<Node>,
+    // Sub-graphs of added instances, they're empty while the command is applied.
+    added: Vec<SubGraph>,
+    added_roots: Vec<Handle<Node>>,
+    // Erased instances with their parents.
+    erased: Vec<(Handle<Node>, Handle<Node>)>,
+    // Sub-graphs of erased instances, they're filled while the command is applied.
+    erased_sub_graphs: Vec<SubGraph>,
+}
+
+impl ScatterStrokeCommand {
+    pub fn new(container: Handle<Node>, added: Vec<SubGraph>, erased: Vec<Handle<Node>>) -> Self {
+        Self {
+            container,
+            added,
+            added_roots: Default::default(),
+            erased: erased
+                .into_iter()
+                .map(|root| (root, Handle::NONE))
+                .collect(),
+            erased_sub_graphs: Default::default(),
+        }
+    }
+}
+
+impl Command for ScatterStrokeCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        if self.erased.is_empty() {
+            format!(
+                "Scatter {} Instances",
+                self.added.len() + self.added_roots.len()
+            )
+        } else {
+            format!("Erase {} Instances", self.erased.len())
+        }
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        let graph = &mut context.scene.graph;
+
+        for sub_graph in self.added.drain(..) {
+            let root = graph.put_sub_graph_back(sub_graph);
+            graph.link_nodes(root, self.container);
+            self.added_roots.push(root);
+        }
+
+        for (root, parent) in self.erased.iter_mut() {
+            *parent = graph[*root].parent();
+            self.erased_sub_graphs
+                .push(graph.take_reserve_sub_graph(*root));
+        }
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        let graph = &mut context.scene.graph;
+
+        for (sub_graph, &(_, parent)) in self
+            .erased_sub_graphs
+            .drain(..)
+            .rev()
+            .zip(self.erased.iter().rev())
+        {
+            let root = graph.put_sub_graph_back(sub_graph);
+            graph.link_nodes(root, parent);
+        }
+
+        for root in self.added_roots.drain(..).rev() {
+            self.added.push(graph.take_reserve_sub_graph(root));
+        }
+    }
+
+    fn finalize(&mut self, context: &mut SceneContext) {
+        for sub_graph in self.added.drain(..).chain(self.erased_sub_graphs.drain(..)) {
+            context.scene.graph.forget_sub_graph(sub_graph);
+        }
+    }
+}
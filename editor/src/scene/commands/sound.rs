@@ -1,6 +1,6 @@
 use crate::{command::Command, define_swap_command, scene::commands::SceneContext};
 use fyrox::scene::sound::Status;
-use fyrox::scene::{node::Node, sound::SoundBufferResource};
+use fyrox::scene::{collider::InteractionGroups, node::Node, sound::SoundBufferResource};
 
 define_swap_command! {
     Node::as_sound_mut,
@@ -15,4 +15,10 @@ define_swap_command! {
     SetRolloffFactorCommand(f32): rolloff_factor, set_rolloff_factor, "Set Spatial Sound Source Rolloff Factor";
     SetMaxDistanceCommand(f32): max_distance, set_max_distance, "Set Max Distance";
     SetSpatialBlendCommand(f32): spatial_blend, set_spatial_blend, "Set Spatial Blend";
+    SetSoundSpreadCommand(f32): spread, set_spread, "Set Sound Spread";
+    SetSoundMinDistanceCommand(f32): min_distance, set_min_distance, "Set Sound Min Distance";
+    SetSoundAirAbsorptionCommand(f32): air_absorption, set_air_absorption, "Set Sound Air Absorption";
+    SetSoundOcclusionEnabledCommand(bool): is_occlusion_enabled, set_occlusion_enabled, "Set Sound Occlusion Enabled";
+    SetSoundOccluderGroupsCommand(InteractionGroups): occluder_groups, set_occluder_groups, "Set Sound Occluder Groups";
+    SetSoundTimeScaleAffectsPitchCommand(bool): is_time_scale_affects_pitch, set_time_scale_affects_pitch, "Set Sound Time Scale Affects Pitch";
 }
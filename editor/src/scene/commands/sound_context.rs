@@ -1,5 +1,8 @@
 use crate::{Command, SceneContext};
-use fyrox::scene::sound::{context::SoundContext, DistanceModel, Renderer};
+use fyrox::scene::sound::{
+    context::{OcclusionSettings, SoundContext},
+    AirAbsorption, DistanceModel, Renderer,
+};
 
 macro_rules! define_sound_context_command {
     ($($name:ident($human_readable_name:expr, $value_type:ty, $get:ident, $set:ident); )*) => {
@@ -43,4 +46,6 @@ define_sound_context_command! {
     SetMasterGainCommand("Set Master Gain", f32, master_gain, set_master_gain);
     SetDistanceModelCommand("Set Distance Model", DistanceModel, distance_model, set_distance_model);
     SetRendererCommand("Set Renderer", Renderer, renderer, set_renderer);
+    SetOcclusionSettingsCommand("Set Occlusion Settings", OcclusionSettings, occlusion_settings, set_occlusion_settings);
+    SetAirAbsorptionCommand("Set Air Absorption", AirAbsorption, air_absorption, set_air_absorption);
 }
@@ -0,0 +1,22 @@
+use crate::{
+    define_swap_command,
+    scene::commands::{Command, SceneContext},
+};
+use fyrox::{
+    core::algebra::Vector3,
+    scene::{
+        collider::{GeometrySource, InteractionGroups},
+        node::Node,
+        trigger_volume::TriggerVolumeShape,
+    },
+};
+
+define_swap_command! {
+    Node::as_trigger_volume_mut,
+    SetTriggerVolumeShapeCommand(TriggerVolumeShape): shape, set_shape, "Set Trigger Volume Shape";
+    SetTriggerVolumeExtentsCommand(Vector3<f32>): extents, set_extents, "Set Trigger Volume Extents";
+    SetTriggerVolumeRadiusCommand(f32): radius, set_radius, "Set Trigger Volume Radius";
+    SetTriggerVolumeGeometrySourceCommand(GeometrySource): geometry_source, set_geometry_source, "Set Trigger Volume Geometry Source";
+    SetTriggerVolumeCollisionGroupsCommand(InteractionGroups): collision_groups, set_collision_groups, "Set Trigger Volume Collision Groups";
+    SetTriggerVolumeNotifyEnteringCommand(bool): is_notify_entering, set_notify_entering, "Set Trigger Volume Notify Entering";
+}
@@ -0,0 +1,41 @@
+use crate::{Command, SceneContext};
+use fyrox::scene::wind::Wind;
+
+#[derive(Debug)]
+pub struct SetWindCommand {
+    value: Wind,
+}
+
+impl SetWindCommand {
+    pub fn new(value: Wind) -> Self {
+        Self { value }
+    }
+
+    fn swap(&mut self, context: &mut SceneContext) {
+        let wind = &mut context.scene.graph.wind;
+        let old = wind.clone();
+        // Parameters are copied one by one to keep time of the wind, otherwise vegetation would
+        // jump on every change.
+        wind.set_direction(self.value.direction());
+        wind.set_strength(self.value.strength());
+        wind.set_gustiness(self.value.gustiness());
+        wind.set_gust_frequency(self.value.gust_frequency());
+        wind.set_gust_size(self.value.gust_size());
+        wind.set_turbulence_frequency(self.value.turbulence_frequency());
+        self.value = old;
+    }
+}
+
+impl Command for SetWindCommand {
+    fn name(&mut self, _context: &SceneContext) -> String {
+        "Set Wind".to_owned()
+    }
+
+    fn execute(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+
+    fn revert(&mut self, context: &mut SceneContext) {
+        self.swap(context);
+    }
+}
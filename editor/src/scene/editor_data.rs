@@ -0,0 +1,284 @@
+//! Editor-only data associated with scene nodes (colors, icons, etc.). The data is not a part of
+//! the scene, it is stored in a sidecar file next to the scene (`<scene>.rgs.editor`) so games
+//! never load it.
+
+use crate::{
+    load_image,
+    scene::{
+        commands::{editor_data::SetNodeEditorDataCommand, CommandGroup, SceneCommand},
+        EditorScene, Selection,
+    },
+};
+use fyrox::{
+    core::{
+        append_extension,
+        color::Color,
+        futures::executor::block_on,
+        parking_lot::Mutex,
+        pool::Handle,
+        uuid::Uuid,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    fxhash::FxHashMap,
+    gui::draw::SharedTexture,
+    scene::{
+        camera::Camera,
+        collider::Collider,
+        dim2,
+        graph::Graph,
+        joint::Joint,
+        light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
+        mesh::Mesh,
+        node::{Node, TypeUuidProvider},
+        rigidbody::RigidBody,
+        sound::Sound,
+        terrain::Terrain,
+    },
+};
+use std::path::{Path, PathBuf};
+
+/// An icon of a node in the world viewer.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Visit)]
+#[repr(u32)]
+pub enum NodeIcon {
+    Cube = 0,
+    Light = 1,
+    Camera = 2,
+    Sound = 3,
+    Mesh = 4,
+    RigidBody = 5,
+    Collider = 6,
+    Joint = 7,
+    Terrain = 8,
+    Folder = 9,
+}
+
+impl Default for NodeIcon {
+    fn default() -> Self {
+        Self::Cube
+    }
+}
+
+impl NodeIcon {
+    pub const ALL: [NodeIcon; 10] = [
+        NodeIcon::Cube,
+        NodeIcon::Light,
+        NodeIcon::Camera,
+        NodeIcon::Sound,
+        NodeIcon::Mesh,
+        NodeIcon::RigidBody,
+        NodeIcon::Collider,
+        NodeIcon::Joint,
+        NodeIcon::Terrain,
+        NodeIcon::Folder,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NodeIcon::Cube => "Cube",
+            NodeIcon::Light => "Light",
+            NodeIcon::Camera => "Camera",
+            NodeIcon::Sound => "Sound",
+            NodeIcon::Mesh => "Mesh",
+            NodeIcon::RigidBody => "Rigid Body",
+            NodeIcon::Collider => "Collider",
+            NodeIcon::Joint => "Joint",
+            NodeIcon::Terrain => "Terrain",
+            NodeIcon::Folder => "Folder",
+        }
+    }
+
+    /// Returns a texture of the icon. Textures are loaded once and then shared, so the result
+    /// could be compared with textures of existing widgets to check whether the icon is changed.
+    pub fn texture(self) -> Option<SharedTexture> {
+        ICON_TEXTURES
+            .lock()
+            .entry(self)
+            .or_insert_with(|| {
+                load_image(match self {
+                    NodeIcon::Cube => include_bytes!("../../resources/embed/cube.png"),
+                    NodeIcon::Light => include_bytes!("../../resources/embed/light.png"),
+                    NodeIcon::Camera => include_bytes!("../../resources/embed/camera.png"),
+                    NodeIcon::Sound => include_bytes!("../../resources/embed/sound_source.png"),
+                    NodeIcon::Mesh => include_bytes!("../../resources/embed/model.png"),
+                    NodeIcon::RigidBody => include_bytes!("../../resources/embed/rigid_body.png"),
+                    NodeIcon::Collider => include_bytes!("../../resources/embed/collider.png"),
+                    NodeIcon::Joint => include_bytes!("../../resources/embed/joint.png"),
+                    NodeIcon::Terrain => include_bytes!("../../resources/embed/terrain.png"),
+                    NodeIcon::Folder => include_bytes!("../../resources/embed/folder.png"),
+                })
+            })
+            .clone()
+    }
+
+    /// Returns an icon for the node based on its type. Types without a specific icon are shown
+    /// as a cube.
+    pub fn auto(node: &Node) -> NodeIcon {
+        TYPE_ICONS.get(&node.id()).cloned().unwrap_or_default()
+    }
+}
+
+lazy_static! {
+    static ref ICON_TEXTURES: Mutex<FxHashMap<NodeIcon, Option<SharedTexture>>> =
+        Default::default();
+    static ref TYPE_ICONS: FxHashMap<Uuid, NodeIcon> = [
+        (PointLight::type_uuid(), NodeIcon::Light),
+        (SpotLight::type_uuid(), NodeIcon::Light),
+        (DirectionalLight::type_uuid(), NodeIcon::Light),
+        (Camera::type_uuid(), NodeIcon::Camera),
+        (Sound::type_uuid(), NodeIcon::Sound),
+        (Mesh::type_uuid(), NodeIcon::Mesh),
+        (RigidBody::type_uuid(), NodeIcon::RigidBody),
+        (dim2::rigidbody::RigidBody::type_uuid(), NodeIcon::RigidBody),
+        (Collider::type_uuid(), NodeIcon::Collider),
+        (dim2::collider::Collider::type_uuid(), NodeIcon::Collider),
+        (Joint::type_uuid(), NodeIcon::Joint),
+        (dim2::joint::Joint::type_uuid(), NodeIcon::Joint),
+        (Terrain::type_uuid(), NodeIcon::Terrain),
+    ]
+    .into_iter()
+    .collect();
+}
+
+/// Editor-only data of a single node. New kinds of per-node data should be added here as
+/// `#[visit(optional)]` fields, so sidecar files saved by older versions could still be loaded.
+#[derive(Clone, Default, PartialEq, Debug, Visit)]
+pub struct NodeEditorData {
+    /// A color of the node name in the world viewer and of the selection box in the scene
+    /// preview.
+    pub color: Option<Color>,
+    /// Overrides the icon, that is chosen by the type of the node.
+    pub icon: Option<NodeIcon>,
+}
+
+impl NodeEditorData {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Editor-only data of all nodes of a scene.
+#[derive(Default, Debug, Visit)]
+pub struct EditorData {
+    nodes: FxHashMap<Handle<Node>, NodeEditorData>,
+}
+
+fn sidecar_path(scene_path: &Path) -> PathBuf {
+    append_extension(scene_path, "editor")
+}
+
+impl EditorData {
+    pub fn get(&self, node: Handle<Node>) -> Option<&NodeEditorData> {
+        self.nodes.get(&node)
+    }
+
+    /// Sets the data of a node and returns previous data. Empty data is not stored.
+    pub fn set(&mut self, node: Handle<Node>, data: NodeEditorData) -> NodeEditorData {
+        let old_data = if data.is_empty() {
+            self.nodes.remove(&node)
+        } else {
+            self.nodes.insert(node, data)
+        };
+        old_data.unwrap_or_default()
+    }
+
+    /// Removes data of the nodes that do not exist in the graph, returns the amount of removed
+    /// entries.
+    pub fn prune(&mut self, graph: &Graph) -> usize {
+        let count = self.nodes.len();
+        self.nodes.retain(|node, _| graph.is_valid_handle(*node));
+        count - self.nodes.len()
+    }
+
+    /// Loads the data from the sidecar file of the scene. Missing file means that there is no
+    /// data.
+    pub fn load(scene_path: &Path) -> Result<Self, String> {
+        let path = sidecar_path(scene_path);
+        if !path.exists() {
+            return Ok(Default::default());
+        }
+        let mut visitor = block_on(Visitor::load_binary(&path)).map_err(|e| e.to_string())?;
+        let mut data = Self::default();
+        data.visit("EditorData", &mut visitor)
+            .map_err(|e| e.to_string())?;
+        Ok(data)
+    }
+
+    /// Saves the data to the sidecar file of the scene. Saved scene is a copy, that may have
+    /// different handles of the nodes, so `old_new_map` is used to remap the handles. The file
+    /// is removed if there is nothing to save.
+    pub fn save(
+        &self,
+        scene_path: &Path,
+        old_new_map: &FxHashMap<Handle<Node>, Handle<Node>>,
+    ) -> Result<(), String> {
+        let path = sidecar_path(scene_path);
+
+        let mut remapped = Self {
+            nodes: self
+                .nodes
+                .iter()
+                .filter_map(|(node, data)| {
+                    old_new_map
+                        .get(node)
+                        .map(|new_node| (*new_node, data.clone()))
+                })
+                .collect(),
+        };
+
+        if remapped.nodes.is_empty() {
+            if path.exists() {
+                std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+            }
+            return Ok(());
+        }
+
+        let mut visitor = Visitor::new();
+        remapped
+            .visit("EditorData", &mut visitor)
+            .map_err(|e| e.to_string())?;
+        visitor.save_binary(&path).map_err(|e| e.to_string())
+    }
+}
+
+/// Creates a command that modifies editor data of every selected node using the given function.
+/// Returns `None` if nothing is changed.
+pub fn make_modify_selection_editor_data_command<F>(
+    editor_scene: &EditorScene,
+    mut func: F,
+) -> Option<SceneCommand>
+where
+    F: FnMut(&mut NodeEditorData),
+{
+    let selection = match editor_scene.selection {
+        Selection::Graph(ref selection) => selection,
+        _ => return None,
+    };
+
+    let commands = selection
+        .nodes()
+        .iter()
+        .filter_map(|&node| {
+            let old_data = editor_scene
+                .editor_data
+                .get(node)
+                .cloned()
+                .unwrap_or_default();
+            let mut new_data = old_data.clone();
+            func(&mut new_data);
+            if new_data == old_data {
+                None
+            } else {
+                Some(SceneCommand::new(SetNodeEditorDataCommand::new(
+                    node, new_data,
+                )))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if commands.is_empty() {
+        None
+    } else {
+        Some(SceneCommand::new(CommandGroup::from(commands)))
+    }
+}
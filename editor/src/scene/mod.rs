@@ -5,37 +5,56 @@ use crate::{
         data_model::{Navmesh, NavmeshTriangle, NavmeshVertex},
         selection::NavmeshSelection,
     },
-    scene::clipboard::Clipboard,
+    scene::{
+        clipboard::Clipboard, editor_data::EditorData,
+        reflection_probe_preview::ReflectionProbePreviews,
+        trigger_volume_preview::TriggerVolumePreviews, viewports::Viewports,
+    },
     settings::debugging::DebuggingSettings,
     world::graph::selection::GraphSelection,
     GameEngine,
 };
 use fyrox::{
     core::{
-        algebra::Point3,
+        algebra::{Point3, UnitQuaternion, Vector3},
         color::Color,
         math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
         pool::{Handle, Pool},
         visitor::Visitor,
     },
     engine::Engine,
+    fxhash::{FxHashMap, FxHashSet},
     scene::{
         base::BaseBuilder,
         debug::{Line, SceneDrawingContext},
+        fog_volume::{FogVolume, FogVolumeShape},
         graph::Graph,
+        light::spot::{SpotLight, SpotLightProjection},
         mesh::{
             buffer::{VertexAttributeUsage, VertexReadTrait},
             Mesh,
         },
+        navmesh_obstacle::NavmeshObstacle,
         node::Node,
-        particle_system::ParticleSystem,
         pivot::PivotBuilder,
+        reflection_probe::ReflectionProbe,
         Scene,
     },
+    utils::log::Log,
+};
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    path::{Path, PathBuf},
 };
-use std::{collections::HashMap, fmt::Write, path::PathBuf};
 
 pub mod clipboard;
+pub mod editor_data;
+pub mod reflection_probe_preview;
+pub mod revert;
+pub mod trigger_volume_preview;
+pub mod view_mode;
+pub mod viewports;
 
 #[macro_use]
 pub mod commands;
@@ -47,9 +66,17 @@ pub struct EditorScene {
     // Handle to a root for all editor nodes.
     pub editor_objects_root: Handle<Node>,
     pub selection: Selection,
+    pub reflection_probe_previews: ReflectionProbePreviews,
+    pub trigger_volume_previews: TriggerVolumePreviews,
     pub clipboard: Clipboard,
+    /// Camera controller of the active viewport pane.
     pub camera_controller: CameraController,
+    pub viewports: Viewports,
     pub navmeshes: Pool<Navmesh>,
+    pub editor_data: EditorData,
+    // Roots of sub-graphs that are shown in isolation mode, everything else is hidden. Empty
+    // when isolation mode is off. See `EditorScene::isolate_selection`.
+    isolated_nodes: FxHashSet<Handle<Node>>,
 }
 
 pub fn is_scene_needs_to_be_saved(editor_scene: Option<&EditorScene>) -> bool {
@@ -61,12 +88,38 @@ pub fn is_scene_needs_to_be_saved(editor_scene: Option<&EditorScene>) -> bool {
 impl EditorScene {
     pub fn from_native_scene(mut scene: Scene, engine: &mut Engine, path: Option<PathBuf>) -> Self {
         let root = PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
-        let camera_controller = CameraController::new(&mut scene.graph, root);
+        let camera_controller = CameraController::new(&mut scene.graph, root, true);
+        let viewports = Viewports::new(&mut scene.graph, root);
 
         // Prevent physics simulation in while editing scene.
         scene.graph.physics.enabled = false;
         scene.graph.physics2d.enabled = false;
 
+        // Members of collections could be deleted outside of the editor (for example when a scene
+        // was modified by a game), so remove stale handles to keep member counts correct.
+        let stale_members = scene.node_collections.prune(&scene.graph);
+        if stale_members > 0 {
+            Log::warn(format!(
+                "{} members of node collections were removed, because their nodes do not exist.",
+                stale_members
+            ));
+        }
+
+        let mut editor_data = path
+            .as_deref()
+            .map(|path| {
+                EditorData::load(path).unwrap_or_else(|e| {
+                    Log::warn(format!(
+                        "Unable to load editor data of scene {}. Reason: {}",
+                        path.display(),
+                        e
+                    ));
+                    Default::default()
+                })
+            })
+            .unwrap_or_default();
+        editor_data.prune(&scene.graph);
+
         let mut navmeshes = Pool::new();
 
         for navmesh in scene.navmeshes.iter() {
@@ -94,28 +147,75 @@ impl EditorScene {
             path,
             editor_objects_root: root,
             camera_controller,
+            viewports,
             navmeshes,
+            editor_data,
             scene: engine.scenes.add(scene),
             selection: Default::default(),
+            reflection_probe_previews: Default::default(),
+            trigger_volume_previews: Default::default(),
             clipboard: Default::default(),
             has_unsaved_changes: false,
+            isolated_nodes: Default::default(),
+        }
+    }
+
+    /// Hides every node except selected nodes and their descendants. Visibility flags of nodes
+    /// are not changed, the nodes are hidden by the visibility filter of the graph, which is not
+    /// saved and does not affect the command stack.
+    pub fn isolate_selection(&mut self, engine: &mut GameEngine) {
+        self.isolated_nodes.clear();
+        self.add_selection_to_isolation(engine);
+    }
+
+    /// Shows selected nodes (and their descendants) in addition to the already isolated ones.
+    pub fn add_selection_to_isolation(&mut self, engine: &mut GameEngine) {
+        if let Selection::Graph(selection) = &self.selection {
+            self.isolated_nodes
+                .extend(selection.nodes().iter().cloned());
         }
+        self.apply_isolation(engine);
+    }
+
+    /// Shows every node again, the view is the same as it was before isolation.
+    pub fn exit_isolation(&mut self, engine: &mut GameEngine) {
+        self.isolated_nodes.clear();
+        self.apply_isolation(engine);
+    }
+
+    pub fn is_isolated(&self) -> bool {
+        !self.isolated_nodes.is_empty()
+    }
+
+    pub fn isolated_nodes(&self) -> &FxHashSet<Handle<Node>> {
+        &self.isolated_nodes
+    }
+
+    fn apply_isolation(&self, engine: &mut GameEngine) {
+        let filter = if self.isolated_nodes.is_empty() {
+            None
+        } else {
+            // Editor objects (camera, gizmos, etc.) must stay visible.
+            let mut filter = self.isolated_nodes.clone();
+            filter.insert(self.editor_objects_root);
+            Some(filter)
+        };
+        engine.scenes[self.scene]
+            .graph
+            .set_visibility_filter(filter);
     }
 
-    pub fn make_purified_scene(&self, engine: &mut GameEngine) -> Scene {
+    /// Makes a copy of the scene without editor objects. Handles of nodes of the copy may differ,
+    /// the returned map maps handles of the edited scene to handles of the copy.
+    pub fn make_purified_scene(
+        &self,
+        engine: &mut GameEngine,
+    ) -> (Scene, FxHashMap<Handle<Node>, Handle<Node>>) {
         let scene = &mut engine.scenes[self.scene];
 
         let editor_root = self.editor_objects_root;
-        let (mut pure_scene, _) = scene.clone(&mut |node, _| node != editor_root);
-
-        // Reset state of nodes. For some nodes (such as particles systems) we use scene as preview
-        // so before saving scene, we have to reset state of such nodes.
-        for node in pure_scene.graph.linear_iter_mut() {
-            if let Some(particle_system) = node.cast_mut::<ParticleSystem>() {
-                // Particle system must not save generated vertices.
-                particle_system.clear_particles();
-            }
-        }
+        let (mut pure_scene, old_new_map) =
+            scene.clone_for_save_with_map(&mut |node, _| node != editor_root);
 
         pure_scene.navmeshes.clear();
 
@@ -150,7 +250,7 @@ impl EditorScene {
                 .add(fyrox::utils::navmesh::Navmesh::new(&triangles, &vertices));
         }
 
-        pure_scene
+        (pure_scene, old_new_map)
     }
 
     pub fn save(&mut self, path: PathBuf, engine: &mut GameEngine) -> Result<String, String> {
@@ -161,14 +261,9 @@ impl EditorScene {
         if valid {
             self.path = Some(path.clone());
 
-            let mut pure_scene = self.make_purified_scene(engine);
-
-            let mut visitor = Visitor::new();
-            pure_scene.save("Scene", &mut visitor).unwrap();
-            if let Err(e) = visitor.save_binary(&path) {
-                Err(format!("Failed to save scene! Reason: {}", e))
-            } else {
-                Ok(format!("Scene {} was successfully saved!", path.display()))
+            match self.save_copy(&path, engine) {
+                Ok(()) => Ok(format!("Scene {} was successfully saved!", path.display())),
+                Err(e) => Err(format!("Failed to save scene! Reason: {}", e)),
             }
         } else {
             writeln!(&mut reason, "\nPlease fix errors and try again.").unwrap();
@@ -177,19 +272,126 @@ impl EditorScene {
         }
     }
 
+    /// Writes purified copy of the scene to the given path, without changing the path of the
+    /// scene. It is used by normal saves as well as by crash recovery.
+    pub fn save_copy(&self, path: &Path, engine: &mut GameEngine) -> Result<(), String> {
+        let (mut pure_scene, old_new_map) = self.make_purified_scene(engine);
+
+        let mut visitor = Visitor::new();
+        pure_scene
+            .save("Scene", &mut visitor)
+            .map_err(|e| e.to_string())?;
+        visitor.save_binary(path).map_err(|e| e.to_string())?;
+
+        self.editor_data.save(path, &old_new_map)
+    }
+
     pub fn draw_debug(&mut self, engine: &mut Engine, settings: &DebuggingSettings) {
         let scene = &mut engine.scenes[self.scene];
 
         scene.drawing_context.clear_lines();
 
+        self.reflection_probe_previews
+            .sync(&mut scene.graph, self.editor_objects_root);
+        self.trigger_volume_previews.sync(
+            &mut scene.graph,
+            self.editor_objects_root,
+            &mut scene.drawing_context,
+        );
+
         if let Selection::Graph(selection) = &self.selection {
-            for &node in selection.nodes() {
-                let node = &scene.graph[node];
+            for &node_handle in selection.nodes() {
+                let node = &scene.graph[node_handle];
+                let color = self
+                    .editor_data
+                    .get(node_handle)
+                    .and_then(|data| data.color)
+                    .filter(|_| settings.tint_selection_with_node_color)
+                    .unwrap_or(Color::GREEN);
                 scene.drawing_context.draw_oob(
                     &node.local_bounding_box(),
                     node.global_transform(),
-                    Color::GREEN,
+                    color,
                 );
+
+                // Box of a reflection probe is axis-aligned in world space.
+                if let Some(probe) = node.cast::<ReflectionProbe>() {
+                    scene
+                        .drawing_context
+                        .draw_aabb(&probe.world_box(), Color::opaque(0, 200, 255));
+                }
+
+                // Show the space filled with fog, a sphere is shown by three circles.
+                if let Some(fog_volume) = node.cast::<FogVolume>() {
+                    let color = Color::opaque(200, 200, 255);
+                    match fog_volume.shape() {
+                        FogVolumeShape::Box => scene.drawing_context.draw_oob(
+                            &node.local_bounding_box(),
+                            node.global_transform(),
+                            color,
+                        ),
+                        FogVolumeShape::Sphere => {
+                            for rotation in [
+                                UnitQuaternion::identity(),
+                                UnitQuaternion::from_axis_angle(
+                                    &Vector3::x_axis(),
+                                    std::f32::consts::FRAC_PI_2,
+                                ),
+                                UnitQuaternion::from_axis_angle(
+                                    &Vector3::y_axis(),
+                                    std::f32::consts::FRAC_PI_2,
+                                ),
+                            ] {
+                                scene.drawing_context.draw_circle(
+                                    Vector3::default(),
+                                    fog_volume.radius(),
+                                    32,
+                                    node.global_transform() * rotation.to_homogeneous(),
+                                    color,
+                                );
+                            }
+                        }
+                    }
+                }
+
+                // Show the footprint that is carved out of navmeshes, it is a prism.
+                if let Some(footprint) = node
+                    .cast::<NavmeshObstacle>()
+                    .and_then(|obstacle| obstacle.footprint())
+                {
+                    let color = Color::opaque(255, 120, 0);
+                    let points = footprint.points();
+                    for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+                        for y in [footprint.min_y(), footprint.max_y()] {
+                            scene.drawing_context.add_line(Line {
+                                begin: Vector3::new(a.x, y, a.y),
+                                end: Vector3::new(b.x, y, b.y),
+                                color,
+                            });
+                        }
+                        scene.drawing_context.add_line(Line {
+                            begin: Vector3::new(a.x, footprint.min_y(), a.y),
+                            end: Vector3::new(a.x, footprint.max_y(), a.y),
+                            color,
+                        });
+                    }
+                }
+
+                // Projector lights lit a box in front of them, show it.
+                if let Some(spot) = node.cast::<SpotLight>() {
+                    if spot.projection() == SpotLightProjection::Orthographic {
+                        let half_width = spot.ortho_width() * 0.5;
+                        let half_height = spot.ortho_height() * 0.5;
+                        scene.drawing_context.draw_oob(
+                            &AxisAlignedBoundingBox::from_min_max(
+                                Vector3::new(-half_width, -spot.distance(), -half_height),
+                                Vector3::new(half_width, 0.0, half_height),
+                            ),
+                            scene.graph.isometric_global_transform(node_handle),
+                            Color::opaque(255, 255, 0),
+                        );
+                    }
+                }
             }
         }
 
@@ -297,6 +499,7 @@ impl EditorScene {
 pub enum Selection {
     None,
     SoundContext,
+    SceneSettings,
     Graph(GraphSelection),
     Navmesh(NavmeshSelection),
     Effect(EffectSelection),
@@ -314,7 +517,7 @@ impl Selection {
             Selection::None => true,
             Selection::Graph(graph) => graph.is_empty(),
             Selection::Navmesh(navmesh) => navmesh.is_empty(),
-            Selection::SoundContext => false,
+            Selection::SoundContext | Selection::SceneSettings => false,
             Selection::Effect(effect) => effect.is_empty(),
         }
     }
@@ -324,7 +527,7 @@ impl Selection {
             Selection::None => 0,
             Selection::Graph(graph) => graph.len(),
             Selection::Navmesh(navmesh) => navmesh.len(),
-            Selection::SoundContext => 1,
+            Selection::SoundContext | Selection::SceneSettings => 1,
             Selection::Effect(effect) => effect.len(),
         }
     }
@@ -0,0 +1,107 @@
+use fyrox::{
+    core::{algebra::Matrix4, parking_lot::Mutex, pool::Handle, sstorage::ImmutableString},
+    material::{shader::SamplerFallback, shader::Shader, Material, PropertyValue},
+    resource::texture::Texture,
+    scene::{
+        base::BaseBuilder,
+        graph::Graph,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder, RenderPath,
+        },
+        node::Node,
+        reflection_probe::ReflectionProbe,
+    },
+};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+lazy_static! {
+    static ref REFLECTION_PROBE_PREVIEW_SHADER: Shader = {
+        Shader::from_str(
+            include_str!("../../resources/embed/shaders/reflection_probe_preview.shader",),
+            PathBuf::default(),
+        )
+        .unwrap()
+    };
+}
+
+struct Preview {
+    sphere: Handle<Node>,
+    environment: Texture,
+}
+
+/// Shows a mirror sphere at the position of every reflection probe that has a captured
+/// environment map, so it is possible to check what a probe reflects.
+#[derive(Default)]
+pub struct ReflectionProbePreviews {
+    previews: HashMap<Handle<Node>, Preview>,
+}
+
+fn make_preview_material(environment: Texture) -> Arc<Mutex<Material>> {
+    let mut material = Material::from_shader(REFLECTION_PROBE_PREVIEW_SHADER.clone(), None);
+    material
+        .set_property(
+            &ImmutableString::new("environmentTexture"),
+            PropertyValue::Sampler {
+                value: Some(environment),
+                fallback: SamplerFallback::Black,
+            },
+        )
+        .unwrap();
+    Arc::new(Mutex::new(material))
+}
+
+impl ReflectionProbePreviews {
+    pub fn sync(&mut self, graph: &mut Graph, editor_objects_root: Handle<Node>) {
+        let probes = graph
+            .pair_iter()
+            .filter_map(|(handle, node)| {
+                node.cast::<ReflectionProbe>().and_then(|probe| {
+                    probe
+                        .environment_value()
+                        .map(|environment| (handle, probe.global_position(), environment))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Remove previews of deleted probes and probes without environment maps, as well as
+        // previews with outdated maps - they will be re-created below.
+        self.previews.retain(|probe, preview| {
+            let actual = probes.iter().any(|(handle, _, environment)| {
+                handle == probe && environment == &preview.environment
+            });
+            if !actual {
+                graph.remove_node(preview.sphere);
+            }
+            actual
+        });
+
+        for (handle, position, environment) in probes {
+            let preview = self.previews.entry(handle).or_insert_with(|| {
+                let sphere = MeshBuilder::new(
+                    BaseBuilder::new()
+                        .with_cast_shadows(false)
+                        .with_name("ReflectionProbePreview"),
+                )
+                .with_render_path(RenderPath::Forward)
+                .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+                    SurfaceData::make_sphere(16, 16, 0.25, &Matrix4::identity()),
+                )))
+                .with_material(make_preview_material(environment.clone()))
+                .build()])
+                .build(graph);
+
+                graph.link_nodes(sphere, editor_objects_root);
+
+                Preview {
+                    sphere,
+                    environment,
+                }
+            });
+
+            graph[preview.sphere]
+                .local_transform_mut()
+                .set_position(position);
+        }
+    }
+}
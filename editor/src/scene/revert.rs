@@ -0,0 +1,113 @@
+//! "Revert to saved" - reloading of the current scene from its file, that keeps the view of the
+//! scene: the camera, the selection and expanded items of the world viewer.
+//!
+//! Nodes do not have persistent ids, so nodes of the old graph are matched with nodes of the
+//! reloaded graph by their paths (see [`NodePath`]). Nodes that cannot be found in the reloaded
+//! graph are simply dropped.
+
+use crate::{
+    camera::CameraState,
+    scene::{EditorScene, Selection},
+    world::graph::selection::GraphSelection,
+};
+use fyrox::{
+    core::pool::Handle,
+    scene::{graph::Graph, node::Node},
+};
+
+/// A path from the root of a graph to a node. Every element of the path is a name of a node and
+/// its index among siblings with the same name, so the path stays valid when unrelated nodes are
+/// added, removed or renamed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodePath(Vec<(String, usize)>);
+
+impl NodePath {
+    pub fn new(graph: &Graph, node: Handle<Node>) -> Option<Self> {
+        let mut elements = Vec::new();
+        let mut handle = node;
+        while handle != graph.get_root() {
+            let node_ref = graph.try_get(handle)?;
+            let parent = node_ref.parent();
+            let index = graph
+                .try_get(parent)?
+                .children()
+                .iter()
+                .filter(|&&sibling| graph[sibling].name() == node_ref.name())
+                .position(|&sibling| sibling == handle)?;
+            elements.push((node_ref.name_owned(), index));
+            handle = parent;
+        }
+        elements.reverse();
+        Some(Self(elements))
+    }
+
+    /// Finds a node with the path in the given graph.
+    pub fn resolve(&self, graph: &Graph) -> Option<Handle<Node>> {
+        let mut handle = graph.get_root();
+        for (name, index) in self.0.iter() {
+            handle = graph[handle]
+                .children()
+                .iter()
+                .filter(|&&child| graph[child].name() == name)
+                .nth(*index)
+                .cloned()?;
+        }
+        Some(handle)
+    }
+}
+
+fn make_paths(graph: &Graph, nodes: &[Handle<Node>]) -> Vec<NodePath> {
+    nodes
+        .iter()
+        .filter_map(|&node| NodePath::new(graph, node))
+        .collect()
+}
+
+fn resolve_paths(graph: &Graph, paths: &[NodePath]) -> Vec<Handle<Node>> {
+    paths
+        .iter()
+        .filter_map(|path| path.resolve(graph))
+        .collect()
+}
+
+/// State of the editor that is kept when the scene is reloaded.
+pub struct ViewState {
+    camera: CameraState,
+    selection: Vec<NodePath>,
+    expanded: Vec<NodePath>,
+}
+
+impl ViewState {
+    /// Remembers the view of the scene, `expanded` is a list of nodes whose items are expanded in
+    /// the world viewer.
+    pub fn capture(editor_scene: &EditorScene, graph: &Graph, expanded: &[Handle<Node>]) -> Self {
+        let selection = if let Selection::Graph(selection) = &editor_scene.selection {
+            make_paths(graph, selection.nodes())
+        } else {
+            Default::default()
+        };
+
+        Self {
+            camera: editor_scene.camera_controller.state(graph),
+            selection,
+            expanded: make_paths(graph, expanded),
+        }
+    }
+
+    /// Applies the view to the reloaded scene. Returns a list of nodes whose items must be
+    /// expanded in the world viewer.
+    pub fn restore(&self, editor_scene: &mut EditorScene, graph: &mut Graph) -> Vec<Handle<Node>> {
+        editor_scene
+            .camera_controller
+            .set_state(graph, &self.camera);
+
+        let selection = resolve_paths(graph, &self.selection);
+        editor_scene.selection = if selection.is_empty() {
+            Selection::None
+        } else {
+            Selection::Graph(GraphSelection::from_list(selection))
+        };
+
+        resolve_paths(graph, &self.expanded)
+    }
+}
@@ -0,0 +1,145 @@
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Point3, Vector3},
+        color::Color,
+        parking_lot::Mutex,
+        pool::Handle,
+        sstorage::ImmutableString,
+    },
+    material::{shader::Shader, Material, PropertyValue},
+    scene::{
+        base::BaseBuilder,
+        debug::{Line, SceneDrawingContext},
+        graph::Graph,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder, RenderPath,
+        },
+        node::Node,
+        trigger_volume::{TriggerVolume, TriggerVolumeShape},
+    },
+};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+lazy_static! {
+    static ref TRIGGER_VOLUME_SHADER: Shader = {
+        Shader::from_str(
+            include_str!("../../resources/embed/shaders/trigger_volume.shader",),
+            PathBuf::default(),
+        )
+        .unwrap()
+    };
+}
+
+const TRIGGER_VOLUME_COLOR: Color = Color::from_rgba(255, 120, 200, 60);
+const TRIGGER_VOLUME_EDGE_COLOR: Color = Color::opaque(255, 120, 200);
+
+#[derive(Copy, Clone, PartialEq)]
+enum PreviewShape {
+    Box(Vector3<f32>),
+    Sphere(f32),
+}
+
+struct Preview {
+    mesh: Handle<Node>,
+    shape: PreviewShape,
+}
+
+/// Shows every trigger volume of a scene as a semi-transparent shape, trigger volumes are
+/// invisible otherwise. Convex volumes are shown by edges of their hulls.
+#[derive(Default)]
+pub struct TriggerVolumePreviews {
+    previews: HashMap<Handle<Node>, Preview>,
+}
+
+fn make_material() -> Arc<Mutex<Material>> {
+    let mut material = Material::from_shader(TRIGGER_VOLUME_SHADER.clone(), None);
+    material
+        .set_property(
+            &ImmutableString::new("diffuseColor"),
+            PropertyValue::Color(TRIGGER_VOLUME_COLOR),
+        )
+        .unwrap();
+    Arc::new(Mutex::new(material))
+}
+
+fn make_surface(shape: PreviewShape) -> SurfaceData {
+    match shape {
+        PreviewShape::Box(extents) => {
+            SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&extents.scale(2.0)))
+        }
+        PreviewShape::Sphere(radius) => {
+            SurfaceData::make_sphere(16, 16, radius, &Matrix4::identity())
+        }
+    }
+}
+
+impl TriggerVolumePreviews {
+    pub fn sync(
+        &mut self,
+        graph: &mut Graph,
+        editor_objects_root: Handle<Node>,
+        ctx: &mut SceneDrawingContext,
+    ) {
+        let mut volumes = Vec::new();
+        for (handle, node) in graph.pair_iter() {
+            if let Some(volume) = node.cast::<TriggerVolume>() {
+                let shape = match volume.shape() {
+                    TriggerVolumeShape::Box => PreviewShape::Box(volume.extents()),
+                    TriggerVolumeShape::Sphere => PreviewShape::Sphere(volume.radius()),
+                    TriggerVolumeShape::Convex => {
+                        let transform = graph.isometric_global_transform(handle);
+                        for (begin, end) in volume.convex_hull_edges() {
+                            ctx.add_line(Line {
+                                begin: transform.transform_point(&Point3::from(begin)).coords,
+                                end: transform.transform_point(&Point3::from(end)).coords,
+                                color: TRIGGER_VOLUME_EDGE_COLOR,
+                            });
+                        }
+                        continue;
+                    }
+                };
+                volumes.push((handle, shape));
+            }
+        }
+
+        // Remove previews of deleted volumes and volumes with changed shapes, the latter will be
+        // re-created below.
+        self.previews.retain(|volume, preview| {
+            let actual = volumes
+                .iter()
+                .any(|(handle, shape)| handle == volume && *shape == preview.shape);
+            if !actual {
+                graph.remove_node(preview.mesh);
+            }
+            actual
+        });
+
+        for (handle, shape) in volumes {
+            let preview = self.previews.entry(handle).or_insert_with(|| {
+                let mesh = MeshBuilder::new(
+                    BaseBuilder::new()
+                        .with_cast_shadows(false)
+                        .with_name("TriggerVolumePreview"),
+                )
+                .with_render_path(RenderPath::Forward)
+                .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+                    make_surface(shape),
+                )))
+                .with_material(make_material())
+                .build()])
+                .build(graph);
+
+                graph.link_nodes(mesh, editor_objects_root);
+
+                Preview { mesh, shape }
+            });
+
+            let (rotation, position) = graph.isometric_global_rotation_position(handle);
+            graph[preview.mesh]
+                .local_transform_mut()
+                .set_position(position)
+                .set_rotation(rotation);
+        }
+    }
+}
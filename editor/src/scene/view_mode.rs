@@ -0,0 +1,171 @@
+//! Debug view modes of the viewport panes. A view mode replaces materials of every mesh rendered
+//! by the camera of a pane with a special material, scene materials are never touched, so the
+//! mode is not saved with the scene and switching back to [`ViewMode::Lit`] restores the usual
+//! rendering.
+
+use crate::interaction::vertex_paint::VertexPaintChannel;
+use fyrox::{
+    core::{algebra::Vector4, color::Color, sstorage::ImmutableString},
+    material::{shader::Shader, Material, PropertyValue},
+    renderer::material_override::MaterialOverride,
+};
+use std::path::PathBuf;
+
+lazy_static! {
+    static ref UV_CHECKER_SHADER: Shader = {
+        Shader::from_str(
+            include_str!("../../resources/embed/shaders/uv_checker.shader",),
+            PathBuf::default(),
+        )
+        .unwrap()
+    };
+    static ref LIGHTMAP_DENSITY_SHADER: Shader = {
+        Shader::from_str(
+            include_str!("../../resources/embed/shaders/lightmap_density.shader",),
+            PathBuf::default(),
+        )
+        .unwrap()
+    };
+    static ref NORMALS_SHADER: Shader = {
+        Shader::from_str(
+            include_str!("../../resources/embed/shaders/normals.shader",),
+            PathBuf::default(),
+        )
+        .unwrap()
+    };
+    static ref OVERDRAW_SHADER: Shader = {
+        Shader::from_str(
+            include_str!("../../resources/embed/shaders/overdraw.shader",),
+            PathBuf::default(),
+        )
+        .unwrap()
+    };
+    static ref VERTEX_COLOR_SHADER: Shader = {
+        Shader::from_str(
+            include_str!("../../resources/embed/shaders/vertex_color.shader",),
+            PathBuf::default(),
+        )
+        .unwrap()
+    };
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum ViewMode {
+    /// Usual rendering with lighting and post effects.
+    Lit,
+    /// Checker pattern in the first texture coordinates, shows stretching and seams of UVs.
+    UvChecker,
+    /// Lightmap texels per world unit, meshes without lightmaps are gray.
+    LightmapDensity,
+    /// World space normals.
+    Normals,
+    /// World space tangents.
+    Tangents,
+    /// Amount of layers drawn for every pixel, depth test is disabled.
+    Overdraw,
+    /// A single channel of vertex colors in grayscale.
+    VertexColor(VertexPaintChannel),
+}
+
+impl Default for ViewMode {
+    fn default() -> Self {
+        Self::Lit
+    }
+}
+
+impl ViewMode {
+    pub const ALL: [ViewMode; 10] = [
+        ViewMode::Lit,
+        ViewMode::UvChecker,
+        ViewMode::LightmapDensity,
+        ViewMode::Normals,
+        ViewMode::Tangents,
+        ViewMode::Overdraw,
+        ViewMode::VertexColor(VertexPaintChannel::Red),
+        ViewMode::VertexColor(VertexPaintChannel::Green),
+        ViewMode::VertexColor(VertexPaintChannel::Blue),
+        ViewMode::VertexColor(VertexPaintChannel::Alpha),
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ViewMode::Lit => "Lit",
+            ViewMode::UvChecker => "UV Checker",
+            ViewMode::LightmapDensity => "Lightmap Density",
+            ViewMode::Normals => "Normals",
+            ViewMode::Tangents => "Tangents",
+            ViewMode::Overdraw => "Overdraw",
+            ViewMode::VertexColor(VertexPaintChannel::Red) => "Vertex Color (R)",
+            ViewMode::VertexColor(VertexPaintChannel::Green) => "Vertex Color (G)",
+            ViewMode::VertexColor(VertexPaintChannel::Blue) => "Vertex Color (B)",
+            ViewMode::VertexColor(VertexPaintChannel::Alpha) => "Vertex Color (A)",
+        }
+    }
+
+    pub fn index(self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|m| *m == self)
+            .unwrap_or_default()
+    }
+
+    /// Creates a material override of the mode, `None` for [`ViewMode::Lit`].
+    pub fn make_override(self) -> Option<MaterialOverride> {
+        match self {
+            ViewMode::Lit => None,
+            ViewMode::UvChecker => Some(MaterialOverride::new(Material::from_shader(
+                UV_CHECKER_SHADER.clone(),
+                None,
+            ))),
+            ViewMode::LightmapDensity => Some(
+                MaterialOverride::new(Material::from_shader(LIGHTMAP_DENSITY_SHADER.clone(), None))
+                    .with_inherited_properties(vec![ImmutableString::new("lightmapTexture")]),
+            ),
+            ViewMode::Normals | ViewMode::Tangents => {
+                let mut material = Material::from_shader(NORMALS_SHADER.clone(), None);
+                material
+                    .set_property(
+                        &ImmutableString::new("showTangents"),
+                        PropertyValue::Bool(self == ViewMode::Tangents),
+                    )
+                    .unwrap();
+                Some(MaterialOverride::new(material))
+            }
+            ViewMode::Overdraw => Some(
+                MaterialOverride::new(Material::from_shader(OVERDRAW_SHADER.clone(), None))
+                    .with_clear_color(Color::BLACK),
+            ),
+            ViewMode::VertexColor(channel) => {
+                let mut mask = Vector4::zeros();
+                mask[channel.index()] = 1.0;
+
+                let mut material = Material::from_shader(VERTEX_COLOR_SHADER.clone(), None);
+                material
+                    .set_property(
+                        &ImmutableString::new("channelMask"),
+                        PropertyValue::Vector4(mask),
+                    )
+                    .unwrap();
+                Some(MaterialOverride::new(material))
+            }
+        }
+    }
+}
+
+/// Material overrides of every view mode, materials are created once and shared across panes.
+#[derive(Default)]
+pub struct ViewModeMaterials {
+    overrides: Vec<(ViewMode, MaterialOverride)>,
+}
+
+impl ViewModeMaterials {
+    pub fn get(&mut self, mode: ViewMode) -> Option<MaterialOverride> {
+        if let Some((_, material_override)) = self.overrides.iter().find(|(m, _)| *m == mode) {
+            return Some(material_override.clone());
+        }
+
+        let material_override = mode.make_override()?;
+        self.overrides.push((mode, material_override.clone()));
+        Some(material_override)
+    }
+}
@@ -0,0 +1,352 @@
+//! Viewport panes of the scene preview. See [`Viewports`] docs for more info.
+
+use crate::{
+    camera::{CameraController, CameraView},
+    scene::view_mode::{ViewMode, ViewModeMaterials},
+    settings::viewports::{ViewportLayout, ViewportLayoutSettings, MAX_VIEWPORTS},
+};
+use fyrox::{
+    core::{algebra::Vector2, math::Rect, pool::Handle},
+    renderer::Renderer,
+    scene::{camera::Projection, graph::Graph, node::Node, Scene},
+};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// Amount of updates a pane is rendered for after a change. Global transforms and matrices are
+/// calculated at the beginning of the next update, so the first frame after a change still shows
+/// the old state, the rest covers frames with multiple updates.
+const REDRAW_UPDATES: u8 = 3;
+
+/// Viewport panes of the scene preview.
+///
+/// Every pane has its own camera controller, cameras of the panes render the same scene into their
+/// own regions of the render target of the scene. This way render data of the scene (batches,
+/// shadow maps, etc.) is prepared only once per frame and shared across the panes.
+///
+/// The controller of the active pane is stored in [`super::EditorScene::camera_controller`], so
+/// interaction modes always work with the active pane. Controllers of other panes are stored here
+/// and swapped on activation.
+///
+/// # Rendering
+///
+/// Cameras of hidden panes are disabled. Perspective panes are rendered every frame, orthographic
+/// panes are rendered only when the scene or their camera changes, otherwise they keep the image
+/// of the last rendered frame.
+///
+/// Every pane has its own [`ViewMode`], it is a part of the editor state only and is never saved.
+pub struct Viewports {
+    layout: ViewportLayout,
+    active: usize,
+    controllers: [Option<CameraController>; MAX_VIEWPORTS],
+    suspended: bool,
+    scene_hash: u64,
+    camera_hashes: [u64; MAX_VIEWPORTS],
+    redraw: [u8; MAX_VIEWPORTS],
+    stack: Vec<Handle<Node>>,
+    view_modes: [ViewMode; MAX_VIEWPORTS],
+    view_mode_materials: ViewModeMaterials,
+}
+
+fn hash_floats<'a, I: IntoIterator<Item = &'a f32>>(hasher: &mut DefaultHasher, values: I) {
+    for value in values {
+        value.to_bits().hash(hasher);
+    }
+}
+
+fn camera_hash(
+    graph: &Graph,
+    controller: &CameraController,
+    viewport: &Rect<f32>,
+    frame_size: Vector2<f32>,
+) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let pivot = graph[controller.pivot].local_transform();
+    hash_floats(&mut hasher, pivot.position().iter());
+    hash_floats(&mut hasher, pivot.rotation().coords.iter());
+    let camera = graph[controller.camera].as_camera();
+    hash_floats(
+        &mut hasher,
+        camera.local_transform().rotation().coords.iter(),
+    );
+    hash_floats(&mut hasher, camera.projection().matrix(frame_size).iter());
+    hash_floats(
+        &mut hasher,
+        viewport.position.iter().chain(viewport.size.iter()),
+    );
+    hash_floats(&mut hasher, frame_size.iter());
+    hasher.finish()
+}
+
+impl Viewports {
+    /// Creates cameras of every pane except the first one, the first pane uses the camera
+    /// controller of the scene.
+    pub fn new(graph: &mut Graph, root: Handle<Node>) -> Self {
+        let defaults = ViewportLayoutSettings::default();
+        let mut controllers: [Option<CameraController>; MAX_VIEWPORTS] = Default::default();
+        for (index, controller) in controllers.iter_mut().enumerate().skip(1) {
+            let mut new_controller = CameraController::new(graph, root, false);
+            new_controller.set_view(graph, defaults.views[index]);
+            *controller = Some(new_controller);
+        }
+
+        Self {
+            layout: defaults.layout,
+            active: 0,
+            controllers,
+            suspended: false,
+            scene_hash: 0,
+            camera_hashes: Default::default(),
+            redraw: Default::default(),
+            stack: Default::default(),
+            view_modes: Default::default(),
+            view_mode_materials: Default::default(),
+        }
+    }
+
+    pub fn layout(&self) -> ViewportLayout {
+        self.layout
+    }
+
+    pub fn set_layout(&mut self, layout: ViewportLayout) {
+        self.layout = layout;
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Makes the pane active, the controller of the pane is swapped with the controller of the
+    /// active pane. Returns `true` if the active pane has changed.
+    pub fn set_active(&mut self, index: usize, active_controller: &mut CameraController) -> bool {
+        if index == self.active {
+            return false;
+        }
+
+        if let Some(mut controller) = self.controllers.get_mut(index).and_then(|c| c.take()) {
+            active_controller.reset_input();
+            std::mem::swap(&mut controller, active_controller);
+            self.controllers[self.active] = Some(controller);
+            self.active = index;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn view_mode(&self, index: usize) -> ViewMode {
+        self.view_modes[index]
+    }
+
+    pub fn set_view_mode(&mut self, index: usize, mode: ViewMode) {
+        if self.view_modes[index] != mode {
+            self.view_modes[index] = mode;
+            self.invalidate();
+        }
+    }
+
+    /// Passes material overrides of the view modes of the panes to the renderer, it must be called
+    /// every frame, because controllers (and cameras) are swapped between panes on activation.
+    pub fn sync_view_modes(
+        &mut self,
+        renderer: &mut Renderer,
+        scene: Handle<Scene>,
+        active_controller: &CameraController,
+    ) {
+        for index in 0..MAX_VIEWPORTS {
+            let camera = self.controllers[index]
+                .as_ref()
+                .unwrap_or(active_controller)
+                .camera;
+            let material_override = self.view_mode_materials.get(self.view_modes[index]);
+            renderer.set_material_override(scene, camera, material_override);
+        }
+    }
+
+    /// Disables cameras of every pane, it is used to preview scene cameras.
+    pub fn set_suspended(&mut self, suspended: bool) {
+        self.suspended = suspended;
+    }
+
+    pub fn view(&self, index: usize, active_controller: &CameraController) -> CameraView {
+        match self.controllers[index].as_ref() {
+            Some(controller) => controller.view(),
+            None => active_controller.view(),
+        }
+    }
+
+    pub fn controllers_mut<'a>(
+        &'a mut self,
+        active_controller: &'a mut CameraController,
+    ) -> impl Iterator<Item = (usize, &'a mut CameraController)> + 'a {
+        let active = self.active;
+        self.controllers
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, controller)| controller.as_mut().map(|c| (index, c)))
+            .chain(std::iter::once((active, active_controller)))
+    }
+
+    pub fn is_pane_camera(
+        &self,
+        handle: Handle<Node>,
+        active_controller: &CameraController,
+    ) -> bool {
+        active_controller.camera == handle
+            || self
+                .controllers
+                .iter()
+                .flatten()
+                .any(|c| c.camera == handle)
+    }
+
+    pub fn settings(&self, active_controller: &CameraController) -> ViewportLayoutSettings {
+        let mut views = ViewportLayoutSettings::default().views;
+        for (index, view) in views.iter_mut().enumerate() {
+            *view = self.view(index, active_controller);
+        }
+
+        ViewportLayoutSettings {
+            layout: self.layout,
+            active: self.active,
+            views,
+        }
+    }
+
+    pub fn apply_settings(
+        &mut self,
+        settings: &ViewportLayoutSettings,
+        active_controller: &mut CameraController,
+        graph: &mut Graph,
+    ) {
+        self.set_active(settings.active, active_controller);
+        self.layout = settings.layout;
+        for (index, controller) in self.controllers_mut(active_controller) {
+            controller.set_view(graph, settings.views[index]);
+        }
+    }
+
+    // Normalized bounds of the pane, Y axis goes down as in the UI.
+    fn pane_rect(&self, index: usize) -> Option<Rect<f32>> {
+        match self.layout {
+            ViewportLayout::Single => (index == self.active).then(|| Rect::new(0.0, 0.0, 1.0, 1.0)),
+            ViewportLayout::Quad => (index < MAX_VIEWPORTS)
+                .then(|| Rect::new((index % 2) as f32 * 0.5, (index / 2) as f32 * 0.5, 0.5, 0.5)),
+        }
+    }
+
+    /// Returns bounds of the pane relative to the preview frame of the given size, `None` if the
+    /// pane is hidden.
+    pub fn pane_bounds(&self, index: usize, frame_size: Vector2<f32>) -> Option<Rect<f32>> {
+        self.pane_rect(index).map(|rect| {
+            Rect::new(
+                rect.x() * frame_size.x,
+                rect.y() * frame_size.y,
+                rect.w() * frame_size.x,
+                rect.h() * frame_size.y,
+            )
+        })
+    }
+
+    /// Returns bounds of the active pane relative to the preview frame of the given size.
+    pub fn active_pane_bounds(&self, frame_size: Vector2<f32>) -> Rect<f32> {
+        self.pane_bounds(self.active, frame_size)
+            .unwrap_or_else(|| Rect::new(0.0, 0.0, frame_size.x, frame_size.y))
+    }
+
+    /// Returns a visible pane at the given position relative to the preview frame.
+    pub fn pane_at(&self, position: Vector2<f32>, frame_size: Vector2<f32>) -> Option<usize> {
+        (0..MAX_VIEWPORTS).find(|&index| {
+            self.pane_bounds(index, frame_size)
+                .map_or(false, |bounds| bounds.contains(position))
+        })
+    }
+
+    /// Forces every pane to be rendered, it must be called when the scene was changed in a way that
+    /// does not move anything (materials, lights, etc.).
+    pub fn invalidate(&mut self) {
+        self.redraw = [REDRAW_UPDATES; MAX_VIEWPORTS];
+    }
+
+    // Fingerprint of the scene, it changes when anything moves, appears or disappears. Cameras
+    // of panes are excluded, so moving one camera does not cause other panes to be rendered.
+    fn scene_hash(&mut self, graph: &Graph, active_controller: &CameraController) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        let mut stack = std::mem::take(&mut self.stack);
+        stack.clear();
+        stack.push(graph.get_root());
+        while let Some(handle) = stack.pop() {
+            if active_controller.pivot == handle
+                || self.controllers.iter().flatten().any(|c| c.pivot == handle)
+            {
+                continue;
+            }
+
+            let node = &graph[handle];
+            handle.hash(&mut hasher);
+            node.global_visibility().hash(&mut hasher);
+            hash_floats(&mut hasher, node.global_transform().iter());
+            stack.extend_from_slice(node.children());
+        }
+        self.stack = stack;
+        hasher.finish()
+    }
+
+    /// Updates cameras of every pane and decides which panes must be rendered in this frame.
+    pub fn update(
+        &mut self,
+        active_controller: &mut CameraController,
+        graph: &mut Graph,
+        frame_size: Vector2<f32>,
+        dt: f32,
+    ) {
+        for (_, controller) in self.controllers_mut(active_controller) {
+            controller.update(graph, dt);
+        }
+
+        let scene_hash = self.scene_hash(graph, active_controller);
+        if scene_hash != self.scene_hash {
+            self.scene_hash = scene_hash;
+            self.invalidate();
+        }
+
+        for index in 0..MAX_VIEWPORTS {
+            let pane = self.pane_rect(index);
+            let controller = self.controllers[index]
+                .as_ref()
+                .unwrap_or(&*active_controller);
+
+            let enabled = match pane {
+                Some(pane) if !self.suspended => {
+                    // Viewports of cameras have Y axis going up.
+                    let viewport =
+                        Rect::new(pane.x(), 1.0 - pane.y() - pane.h(), pane.w(), pane.h());
+                    let hash = camera_hash(graph, controller, &viewport, frame_size);
+                    if hash != self.camera_hashes[index] {
+                        self.camera_hashes[index] = hash;
+                        self.redraw[index] = REDRAW_UPDATES;
+                    }
+
+                    let camera = graph[controller.camera].as_camera_mut();
+                    camera.set_viewport(viewport);
+
+                    let redraw = self.redraw[index] > 0
+                        || matches!(camera.projection(), Projection::Perspective(_));
+                    self.redraw[index] = self.redraw[index].saturating_sub(1);
+                    redraw
+                }
+                _ => {
+                    // The pane must be rendered when it is shown again.
+                    self.camera_hashes[index] = 0;
+                    false
+                }
+            };
+
+            graph[controller.camera]
+                .as_camera_mut()
+                .set_enabled(enabled);
+        }
+    }
+}
@@ -1,7 +1,14 @@
 use crate::{
-    camera::PickingOptions, gui::make_dropdown_list_option_with_height, load_image,
-    utils::enable_widget, AddModelCommand, AssetItem, AssetKind, ChangeSelectionCommand,
-    CommandGroup, DropdownListBuilder, EditorScene, GameEngine, GraphSelection, InteractionMode,
+    camera::{CameraView, PickingOptions},
+    gui::make_dropdown_list_option_with_height,
+    interaction::transform_space::TransformSpace,
+    load_image,
+    scene::view_mode::ViewMode,
+    send_sync_message,
+    settings::viewports::{ViewportLayout, MAX_VIEWPORTS},
+    utils::enable_widget,
+    AddModelCommand, AssetItem, AssetKind, ChangeSelectionCommand, CommandGroup,
+    DropdownListBuilder, EditorScene, GameEngine, GraphSelection, InteractionMode,
     InteractionModeKind, Message, Mode, SceneCommand, Selection, SetMeshTextureCommand,
     SetParticleSystemTextureCommand, SetSpriteTextureCommand, Settings,
 };
@@ -19,17 +26,36 @@ use fyrox::{
         image::{ImageBuilder, ImageMessage},
         message::{KeyCode, MessageDirection, MouseButton, UiMessage},
         stack_panel::StackPanelBuilder,
-        text::TextBuilder,
+        text::{TextBuilder, TextMessage},
         widget::{WidgetBuilder, WidgetMessage},
         window::{WindowBuilder, WindowMessage, WindowTitle},
         BuildContext, HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
+        VerticalAlignment,
     },
     resource::texture::{Texture, TextureState},
-    scene::camera::Projection,
     utils::into_gui_texture,
 };
 use std::sync::mpsc::Sender;
 
+/// Presets of the render scale selector of the top ribbon, arbitrary values can be set in the
+/// graphics settings.
+const RENDER_SCALE_PRESETS: [f32; 6] = [0.25, 0.5, 0.75, 1.0, 1.5, 2.0];
+
+/// Maximizes the viewport pane under the mouse or restores quad view.
+const MAXIMIZE_PANE_KEY: KeyCode = KeyCode::F11;
+
+const PANE_COLOR: Color = Color::opaque(70, 70, 70);
+const ACTIVE_PANE_COLOR: Color = Color::opaque(0, 162, 232);
+
+/// State of a frame of a viewport pane, it is used to update frames only on changes.
+#[derive(Copy, Clone, PartialEq)]
+struct PaneFrameState {
+    bounds: Rect<f32>,
+    active: bool,
+    view: CameraView,
+    view_mode: ViewMode,
+}
+
 pub struct SceneViewer {
     frame: Handle<UiNode>,
     window: Handle<UiNode>,
@@ -43,11 +69,27 @@ pub struct SceneViewer {
     scale_mode: Handle<UiNode>,
     navmesh_mode: Handle<UiNode>,
     terrain_mode: Handle<UiNode>,
-    camera_projection: Handle<UiNode>,
+    scatter_mode: Handle<UiNode>,
+    collider_shape_mode: Handle<UiNode>,
+    vertex_paint_mode: Handle<UiNode>,
+    camera_view: Handle<UiNode>,
+    view_mode: Handle<UiNode>,
+    transform_space: Handle<UiNode>,
+    layout: Handle<UiNode>,
+    pane_frames: [Handle<UiNode>; MAX_VIEWPORTS],
+    pane_titles: [Handle<UiNode>; MAX_VIEWPORTS],
+    pane_frame_states: [Option<PaneFrameState>; MAX_VIEWPORTS],
+    render_scale: Handle<UiNode>,
+    render_scale_value: f32,
     switch_mode: Handle<UiNode>,
     sender: Sender<Message>,
     interaction_mode_panel: Handle<UiNode>,
     contextual_actions: Handle<UiNode>,
+    isolation_banner: Handle<UiNode>,
+    isolation_text: Handle<UiNode>,
+    exit_isolation: Handle<UiNode>,
+    // Amount of isolated nodes shown by the banner, it is used to update the banner only on changes.
+    isolation_state: Option<usize>,
 }
 
 fn make_interaction_mode_button(
@@ -87,8 +129,14 @@ fn make_interaction_mode_button(
     .build(ctx)
 }
 
+fn render_scale_preset_index(render_scale: f32) -> Option<usize> {
+    RENDER_SCALE_PRESETS
+        .iter()
+        .position(|preset| (preset - render_scale).abs() < f32::EPSILON)
+}
+
 impl SceneViewer {
-    pub fn new(engine: &mut GameEngine, sender: Sender<Message>) -> Self {
+    pub fn new(engine: &mut GameEngine, sender: Sender<Message>, settings: &Settings) -> Self {
         let ctx = &mut engine.user_interface.build_ctx();
 
         let select_mode_tooltip = "Select Object(s) - Shortcut: [1]\n\nSelection interaction mode \
@@ -97,17 +145,19 @@ impl SceneViewer {
 
         let move_mode_tooltip =
             "Move Object(s) - Shortcut: [2]\n\nMovement interaction mode allows you to move selected \
-        objects. Keep in mind that movement always works in local coordinates!\n\n\
+        objects. Coordinate space of movement is selected in the top ribbon.\n\n\
+        Hold Shift while dragging to snap objects to vertices, edges and surfaces of other meshes. \
+        Shift+Click on a selected object picks a point of it, that is snapped instead of its pivot.\n\n\
         This also allows you to select an object or add an object to current selection using Ctrl+Click";
 
         let rotate_mode_tooltip =
             "Rotate Object(s) - Shortcut: [3]\n\nRotation interaction mode allows you to rotate selected \
-        objects. Keep in mind that rotation always works in local coordinates!\n\n\
+        objects. Coordinate space of rotation is selected in the top ribbon.\n\n\
         This also allows you to select an object or add an object to current selection using Ctrl+Click";
 
         let scale_mode_tooltip =
             "Scale Object(s) - Shortcut: [4]\n\nScaling interaction mode allows you to scale selected \
-        objects. Keep in mind that scaling always works in local coordinates!\n\n\
+        objects. Coordinate space of scaling is selected in the top ribbon.\n\n\
         This also allows you to select an object or add an object to current selection using Ctrl+Click";
 
         let navmesh_mode_tooltip =
@@ -118,6 +168,18 @@ impl SceneViewer {
             "Edit Terrain\n\nTerrain edit mode allows you to modify selected \
         terrain.";
 
+        let scatter_mode_tooltip =
+            "Scatter Brush\n\nScatter brush allows you to paint instances of prefabs on \
+        surfaces of the scene. Hold Shift to erase previously painted instances.";
+
+        let collider_shape_mode_tooltip =
+            "Edit Collider Shape\n\nAllows you to change the shape of selected collider by dragging \
+        its handles. Cubic handles change sizes, spherical handles change radii.";
+
+        let vertex_paint_mode_tooltip =
+            "Vertex Paint\n\nPaints a channel of vertex colors of selected mesh, for example to make \
+        blend masks. Hold Shift to paint zero. Shared surface data is copied and saved with the scene.";
+
         let frame;
         let select_mode;
         let move_mode;
@@ -125,9 +187,92 @@ impl SceneViewer {
         let scale_mode;
         let navmesh_mode;
         let terrain_mode;
+        let scatter_mode;
+        let collider_shape_mode;
+        let vertex_paint_mode;
         let selection_frame;
-        let camera_projection;
+        let camera_view;
+        let view_mode;
+        let transform_space;
+        let layout;
+        let render_scale;
         let switch_mode;
+        let isolation_text;
+        let exit_isolation;
+
+        let mut pane_titles = [Handle::NONE; MAX_VIEWPORTS];
+        let mut pane_frames = [Handle::NONE; MAX_VIEWPORTS];
+        for (frame, title) in pane_frames.iter_mut().zip(pane_titles.iter_mut()) {
+            *title = TextBuilder::new(
+                WidgetBuilder::new()
+                    .with_hit_test_visibility(false)
+                    .with_margin(Thickness::uniform(4.0)),
+            )
+            .build(ctx);
+            *frame = BorderBuilder::new(
+                WidgetBuilder::new()
+                    .with_visibility(false)
+                    .with_hit_test_visibility(false)
+                    .with_background(Brush::Solid(Color::TRANSPARENT))
+                    .with_foreground(Brush::Solid(PANE_COLOR))
+                    .with_child(*title),
+            )
+            .with_stroke_thickness(Thickness::uniform(1.0))
+            .build(ctx);
+        }
+
+        let isolation_banner = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_margin(Thickness::uniform(4.0))
+                .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 160)))
+                .with_foreground(Brush::Solid(ACTIVE_PANE_COLOR))
+                .with_child(
+                    StackPanelBuilder::new(
+                        WidgetBuilder::new()
+                            .with_child({
+                                isolation_text = TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_vertical_alignment(VerticalAlignment::Center)
+                                        .with_margin(Thickness::uniform(4.0)),
+                                )
+                                .build(ctx);
+                                isolation_text
+                            })
+                            .with_child({
+                                exit_isolation = ButtonBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_margin(Thickness::uniform(2.0))
+                                        .with_width(60.0)
+                                        .with_tooltip(
+                                            BorderBuilder::new(
+                                                WidgetBuilder::new().with_child(
+                                                    TextBuilder::new(
+                                                        WidgetBuilder::new()
+                                                            .with_margin(Thickness::uniform(2.0)),
+                                                    )
+                                                    .with_text(
+                                                        "Shows hidden objects again - Shortcut: \
+                                                        [I]\n\nUse [Shift+I] to add selected \
+                                                        objects to isolation.",
+                                                    )
+                                                    .build(ctx),
+                                                ),
+                                            )
+                                            .build(ctx),
+                                        ),
+                                )
+                                .with_text("Exit")
+                                .build(ctx);
+                                exit_isolation
+                            }),
+                    )
+                    .with_orientation(Orientation::Horizontal)
+                    .build(ctx),
+                ),
+        )
+        .with_stroke_thickness(Thickness::uniform(1.0))
+        .build(ctx);
 
         let interaction_mode_panel = StackPanelBuilder::new(
             WidgetBuilder::new()
@@ -181,28 +326,152 @@ impl SceneViewer {
                         terrain_mode_tooltip,
                     );
                     terrain_mode
+                })
+                .with_child({
+                    scatter_mode = make_interaction_mode_button(
+                        ctx,
+                        include_bytes!("../resources/embed/model.png"),
+                        scatter_mode_tooltip,
+                    );
+                    scatter_mode
+                })
+                .with_child({
+                    collider_shape_mode = make_interaction_mode_button(
+                        ctx,
+                        include_bytes!("../resources/embed/collider.png"),
+                        collider_shape_mode_tooltip,
+                    );
+                    collider_shape_mode
+                })
+                .with_child({
+                    vertex_paint_mode = make_interaction_mode_button(
+                        ctx,
+                        include_bytes!("../resources/embed/shader.png"),
+                        vertex_paint_mode_tooltip,
+                    );
+                    vertex_paint_mode
                 }),
         )
         .build(ctx);
 
         let contextual_actions = StackPanelBuilder::new(
             WidgetBuilder::new()
-                .on_column(1)
+                .on_column(2)
                 .with_margin(Thickness::uniform(1.0))
                 .with_horizontal_alignment(HorizontalAlignment::Right)
                 .with_child({
-                    camera_projection = DropdownListBuilder::new(
+                    transform_space = DropdownListBuilder::new(
+                        WidgetBuilder::new()
+                            .with_margin(Thickness::uniform(1.0))
+                            .with_width(80.0)
+                            .with_tooltip(
+                                BorderBuilder::new(
+                                    WidgetBuilder::new().with_child(
+                                        TextBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(2.0)),
+                                        )
+                                        .with_text(
+                                            "Coordinate space of move, rotate and scale \
+                                            interaction modes.",
+                                        )
+                                        .build(ctx),
+                                    ),
+                                )
+                                .build(ctx),
+                            ),
+                    )
+                    .with_items(
+                        TransformSpace::ALL
+                            .iter()
+                            .map(|space| {
+                                make_dropdown_list_option_with_height(ctx, space.name(), 22.0)
+                            })
+                            .collect(),
+                    )
+                    .with_selected(settings.transform_space.index())
+                    .build(ctx);
+                    transform_space
+                })
+                .with_child({
+                    layout = ButtonBuilder::new(
+                        WidgetBuilder::new()
+                            .with_margin(Thickness::uniform(1.0))
+                            .with_width(80.0)
+                            .with_tooltip(
+                                BorderBuilder::new(
+                                    WidgetBuilder::new().with_child(
+                                        TextBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(2.0)),
+                                        )
+                                        .with_text(
+                                            "Switches between single and quad view - Shortcut: \
+                                            [F11]\n\nThe shortcut maximizes the pane under the \
+                                            mouse.",
+                                        )
+                                        .build(ctx),
+                                    ),
+                                )
+                                .build(ctx),
+                            ),
+                    )
+                    .with_text("Quad View")
+                    .build(ctx);
+                    layout
+                })
+                .with_child({
+                    camera_view = DropdownListBuilder::new(
                         WidgetBuilder::new()
                             .with_margin(Thickness::uniform(1.0))
                             .with_width(150.0),
                     )
-                    .with_items(vec![
-                        make_dropdown_list_option_with_height(ctx, "Perspective (3D)", 22.0),
-                        make_dropdown_list_option_with_height(ctx, "Orthographic (2D)", 22.0),
-                    ])
+                    .with_items(
+                        CameraView::ALL
+                            .iter()
+                            .map(|view| {
+                                make_dropdown_list_option_with_height(ctx, view.name(), 22.0)
+                            })
+                            .collect(),
+                    )
                     .with_selected(0)
                     .build(ctx);
-                    camera_projection
+                    camera_view
+                })
+                .with_child({
+                    view_mode = DropdownListBuilder::new(
+                        WidgetBuilder::new()
+                            .with_margin(Thickness::uniform(1.0))
+                            .with_width(130.0)
+                            .with_tooltip(
+                                BorderBuilder::new(
+                                    WidgetBuilder::new().with_child(
+                                        TextBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_margin(Thickness::uniform(2.0)),
+                                        )
+                                        .with_text(
+                                            "Debug view mode of the active pane, it replaces \
+                                            materials of the scene only while rendering and \
+                                            is not saved with the scene.",
+                                        )
+                                        .build(ctx),
+                                    ),
+                                )
+                                .build(ctx),
+                            ),
+                    )
+                    .with_items(
+                        ViewMode::ALL
+                            .iter()
+                            .map(|mode| {
+                                make_dropdown_list_option_with_height(ctx, mode.name(), 22.0)
+                            })
+                            .collect(),
+                    )
+                    .with_selected(0)
+                    .build(ctx);
+                    view_mode
                 }),
         )
         .with_orientation(Orientation::Horizontal)
@@ -221,10 +490,34 @@ impl SceneViewer {
                     .build(ctx);
                     switch_mode
                 })
+                .with_child({
+                    render_scale = DropdownListBuilder::new(
+                        WidgetBuilder::new()
+                            .on_column(1)
+                            .with_margin(Thickness::uniform(2.0))
+                            .with_width(70.0),
+                    )
+                    .with_items(
+                        RENDER_SCALE_PRESETS
+                            .iter()
+                            .map(|scale| {
+                                make_dropdown_list_option_with_height(
+                                    ctx,
+                                    &format!("{}%", scale * 100.0),
+                                    22.0,
+                                )
+                            })
+                            .collect(),
+                    )
+                    .with_selected(render_scale_preset_index(1.0).unwrap_or_default())
+                    .build(ctx);
+                    render_scale
+                })
                 .with_child(contextual_actions),
         )
         .add_column(Column::stretch())
         .add_column(Column::auto())
+        .add_column(Column::auto())
         .add_row(Row::stretch())
         .build(ctx);
 
@@ -253,21 +546,25 @@ impl SceneViewer {
                                     })
                                     .with_child(
                                         CanvasBuilder::new(
-                                            WidgetBuilder::new().on_column(1).with_child({
-                                                selection_frame = BorderBuilder::new(
-                                                    WidgetBuilder::new()
-                                                        .with_visibility(false)
-                                                        .with_background(Brush::Solid(
-                                                            Color::from_rgba(255, 255, 255, 40),
-                                                        ))
-                                                        .with_foreground(Brush::Solid(
-                                                            Color::opaque(0, 255, 0),
-                                                        )),
-                                                )
-                                                .with_stroke_thickness(Thickness::uniform(1.0))
-                                                .build(ctx);
-                                                selection_frame
-                                            }),
+                                            WidgetBuilder::new()
+                                                .on_column(1)
+                                                .with_children(pane_frames.iter().cloned())
+                                                .with_child({
+                                                    selection_frame = BorderBuilder::new(
+                                                        WidgetBuilder::new()
+                                                            .with_visibility(false)
+                                                            .with_background(Brush::Solid(
+                                                                Color::from_rgba(255, 255, 255, 40),
+                                                            ))
+                                                            .with_foreground(Brush::Solid(
+                                                                Color::opaque(0, 255, 0),
+                                                            )),
+                                                    )
+                                                    .with_stroke_thickness(Thickness::uniform(1.0))
+                                                    .build(ctx);
+                                                    selection_frame
+                                                })
+                                                .with_child(isolation_banner),
                                         )
                                         .build(ctx),
                                     )
@@ -299,11 +596,26 @@ impl SceneViewer {
             select_mode,
             navmesh_mode,
             terrain_mode,
-            camera_projection,
+            scatter_mode,
+            collider_shape_mode,
+            vertex_paint_mode,
+            camera_view,
+            view_mode,
+            transform_space,
+            layout,
+            pane_frames,
+            pane_titles,
+            pane_frame_states: Default::default(),
+            render_scale,
+            render_scale_value: 1.0,
             click_mouse_pos: None,
             switch_mode,
             interaction_mode_panel,
             contextual_actions,
+            isolation_banner,
+            isolation_text,
+            exit_isolation,
+            isolation_state: None,
         }
     }
 }
@@ -357,8 +669,28 @@ impl SceneViewer {
                 self.sender
                     .send(Message::SetInteractionMode(InteractionModeKind::Terrain))
                     .unwrap();
+            } else if message.destination() == self.scatter_mode {
+                self.sender
+                    .send(Message::SetInteractionMode(InteractionModeKind::Scatter))
+                    .unwrap();
+            } else if message.destination() == self.collider_shape_mode {
+                self.sender
+                    .send(Message::SetInteractionMode(
+                        InteractionModeKind::ColliderShape,
+                    ))
+                    .unwrap();
+            } else if message.destination() == self.vertex_paint_mode {
+                self.sender
+                    .send(Message::SetInteractionMode(
+                        InteractionModeKind::VertexPaint,
+                    ))
+                    .unwrap();
             } else if message.destination() == self.switch_mode {
                 self.sender.send(Message::SwitchMode).unwrap();
+            } else if message.destination() == self.layout {
+                self.sender.send(Message::ToggleViewportLayout).unwrap();
+            } else if message.destination() == self.exit_isolation {
+                self.sender.send(Message::ExitIsolation).unwrap();
             }
         } else if let Some(WidgetMessage::MouseDown { button, .. }) =
             message.data::<WidgetMessage>()
@@ -369,21 +701,33 @@ impl SceneViewer {
                 self.sender.send(Message::OpenSettings).unwrap();
             }
         } else if let Some(DropdownListMessage::SelectionChanged(Some(index))) = message.data() {
-            if message.destination() == self.camera_projection
+            if message.destination() == self.camera_view
                 && message.direction == MessageDirection::FromWidget
             {
-                if *index == 0 {
+                if let Some(view) = CameraView::ALL.get(*index) {
                     self.sender
-                        .send(Message::SetEditorCameraProjection(Projection::Perspective(
-                            Default::default(),
-                        )))
-                        .unwrap()
-                } else {
+                        .send(Message::SetEditorCameraView(*view))
+                        .unwrap();
+                }
+            } else if message.destination() == self.view_mode
+                && message.direction == MessageDirection::FromWidget
+            {
+                if let Some(mode) = ViewMode::ALL.get(*index) {
+                    self.sender.send(Message::SetViewMode(*mode)).unwrap();
+                }
+            } else if message.destination() == self.transform_space
+                && message.direction == MessageDirection::FromWidget
+            {
+                if let Some(space) = TransformSpace::ALL.get(*index) {
                     self.sender
-                        .send(Message::SetEditorCameraProjection(
-                            Projection::Orthographic(Default::default()),
-                        ))
-                        .unwrap()
+                        .send(Message::SetTransformSpace(*space))
+                        .unwrap();
+                }
+            } else if message.destination() == self.render_scale
+                && message.direction == MessageDirection::FromWidget
+            {
+                if let Some(scale) = RENDER_SCALE_PRESETS.get(*index) {
+                    self.sender.send(Message::SetRenderScale(*scale)).unwrap();
                 }
             }
         }
@@ -459,13 +803,175 @@ impl SceneViewer {
         ));
     }
 
-    pub fn reset_camera_projection(&self, ui: &UserInterface) {
-        // Default camera projection is Perspective.
-        ui.send_message(DropdownListMessage::selection(
-            self.camera_projection,
+    /// Shows the view of the camera of the active viewport pane.
+    pub fn sync_camera_view(&self, ui: &UserInterface, view: CameraView) {
+        send_sync_message(
+            ui,
+            DropdownListMessage::selection(
+                self.camera_view,
+                MessageDirection::ToWidget,
+                Some(view.index()),
+            ),
+        );
+    }
+
+    /// Shows the view mode of the active viewport pane.
+    pub fn sync_view_mode(&self, ui: &UserInterface, mode: ViewMode) {
+        send_sync_message(
+            ui,
+            DropdownListMessage::selection(
+                self.view_mode,
+                MessageDirection::ToWidget,
+                Some(mode.index()),
+            ),
+        );
+    }
+
+    /// Shows frames and titles of viewport panes in quad view, the active pane is highlighted.
+    pub fn sync_viewports(&mut self, ui: &UserInterface, editor_scene: Option<&EditorScene>) {
+        let frame_size = self.frame_bounds(ui).size;
+
+        for index in 0..MAX_VIEWPORTS {
+            let state = editor_scene.and_then(|editor_scene| {
+                let viewports = &editor_scene.viewports;
+                if viewports.layout() == ViewportLayout::Quad {
+                    viewports
+                        .pane_bounds(index, frame_size)
+                        .map(|bounds| PaneFrameState {
+                            bounds,
+                            active: index == viewports.active(),
+                            view: viewports.view(index, &editor_scene.camera_controller),
+                            view_mode: viewports.view_mode(index),
+                        })
+                } else {
+                    None
+                }
+            });
+
+            if self.pane_frame_states[index] == state {
+                continue;
+            }
+            self.pane_frame_states[index] = state;
+
+            let frame = self.pane_frames[index];
+            ui.send_message(WidgetMessage::visibility(
+                frame,
+                MessageDirection::ToWidget,
+                state.is_some(),
+            ));
+            if let Some(state) = state {
+                ui.send_message(WidgetMessage::desired_position(
+                    frame,
+                    MessageDirection::ToWidget,
+                    state.bounds.position,
+                ));
+                ui.send_message(WidgetMessage::width(
+                    frame,
+                    MessageDirection::ToWidget,
+                    state.bounds.w(),
+                ));
+                ui.send_message(WidgetMessage::height(
+                    frame,
+                    MessageDirection::ToWidget,
+                    state.bounds.h(),
+                ));
+                ui.send_message(WidgetMessage::foreground(
+                    frame,
+                    MessageDirection::ToWidget,
+                    Brush::Solid(if state.active {
+                        ACTIVE_PANE_COLOR
+                    } else {
+                        PANE_COLOR
+                    }),
+                ));
+                ui.send_message(TextMessage::text(
+                    self.pane_titles[index],
+                    MessageDirection::ToWidget,
+                    if state.view_mode == ViewMode::Lit {
+                        state.view.name().to_owned()
+                    } else {
+                        format!("{} - {}", state.view.name(), state.view_mode.name())
+                    },
+                ));
+            }
+        }
+    }
+
+    /// Shows a banner with the amount of isolated objects while isolation mode is active.
+    pub fn sync_isolation(&mut self, ui: &UserInterface, editor_scene: Option<&EditorScene>) {
+        let state = editor_scene
+            .filter(|editor_scene| editor_scene.is_isolated())
+            .map(|editor_scene| editor_scene.isolated_nodes().len());
+
+        if self.isolation_state == state {
+            return;
+        }
+        self.isolation_state = state;
+
+        ui.send_message(WidgetMessage::visibility(
+            self.isolation_banner,
             MessageDirection::ToWidget,
-            Some(0),
+            state.is_some(),
         ));
+        if let Some(count) = state {
+            ui.send_message(TextMessage::text(
+                self.isolation_text,
+                MessageDirection::ToWidget,
+                format!("Isolation: {} object(s)", count),
+            ));
+        }
+    }
+
+    /// Activates a viewport pane at the given position relative to the preview frame. The active
+    /// pane does not change while the mouse is captured by the preview (while dragging or rotating
+    /// the camera), so an action started in a pane finishes in the same pane.
+    fn activate_pane_at(
+        &self,
+        position: Vector2<f32>,
+        frame_size: Vector2<f32>,
+        editor_scene: &mut EditorScene,
+        ui: &UserInterface,
+    ) {
+        if ui.captured_node() == self.frame {
+            return;
+        }
+
+        if let Some(index) = editor_scene.viewports.pane_at(position, frame_size) {
+            if editor_scene
+                .viewports
+                .set_active(index, &mut editor_scene.camera_controller)
+            {
+                self.sync_camera_view(ui, editor_scene.camera_controller.view());
+                self.sync_view_mode(ui, editor_scene.viewports.view_mode(index));
+            }
+        }
+    }
+
+    /// Converts a position relative to the preview frame to a position relative to the active pane.
+    fn pane_position(
+        &self,
+        position: Vector2<f32>,
+        frame_size: Vector2<f32>,
+        editor_scene: &EditorScene,
+    ) -> Vector2<f32> {
+        position
+            - editor_scene
+                .viewports
+                .active_pane_bounds(frame_size)
+                .position
+    }
+
+    /// Selects a preset in the render scale selector that matches the given scale, custom scales
+    /// (set in the settings) are shown as no selection.
+    pub fn sync_render_scale(&mut self, ui: &UserInterface, render_scale: f32) {
+        if self.render_scale_value != render_scale {
+            self.render_scale_value = render_scale;
+            ui.send_message(DropdownListMessage::selection(
+                self.render_scale,
+                MessageDirection::ToWidget,
+                render_scale_preset_index(render_scale),
+            ));
+        }
     }
 
     pub fn frame_bounds(&self, ui: &UserInterface) -> Rect<f32> {
@@ -501,6 +1007,12 @@ impl SceneViewer {
         active_interaction_mode: Option<&mut Box<dyn InteractionMode>>,
         engine: &mut Engine,
     ) -> bool {
+        if key == MAXIMIZE_PANE_KEY {
+            // The pane under the mouse is the active one.
+            self.sender.send(Message::ToggleViewportLayout).unwrap();
+            return true;
+        }
+
         if editor_scene.camera_controller.on_key_down(key) {
             return true;
         }
@@ -524,10 +1036,21 @@ impl SceneViewer {
     ) {
         let screen_bounds = self.frame_bounds(&engine.user_interface);
 
+        self.activate_pane_at(
+            pos - screen_bounds.position,
+            screen_bounds.size,
+            editor_scene,
+            &engine.user_interface,
+        );
+
         let last_pos = *self.last_mouse_pos.get_or_insert(pos);
         let mouse_offset = pos - last_pos;
         editor_scene.camera_controller.on_mouse_move(mouse_offset);
-        let rel_pos = pos - screen_bounds.position;
+        let rel_pos = self.pane_position(
+            pos - screen_bounds.position,
+            screen_bounds.size,
+            editor_scene,
+        );
 
         if let Some(interaction_mode) = active_interaction_mode {
             interaction_mode.on_mouse_move(
@@ -560,7 +1083,11 @@ impl SceneViewer {
         if button == MouseButton::Left {
             self.click_mouse_pos = None;
             if let Some(current_im) = active_interaction_mode {
-                let rel_pos = pos - screen_bounds.position;
+                let rel_pos = self.pane_position(
+                    pos - screen_bounds.position,
+                    screen_bounds.size,
+                    editor_scene,
+                );
                 current_im.on_left_mouse_button_up(
                     editor_scene,
                     engine,
@@ -583,13 +1110,24 @@ impl SceneViewer {
         engine: &mut Engine,
         settings: &Settings,
     ) {
-        engine.user_interface.capture_mouse(self.frame());
-
         let screen_bounds = self.frame_bounds(&engine.user_interface);
 
+        self.activate_pane_at(
+            pos - screen_bounds.position,
+            screen_bounds.size,
+            editor_scene,
+            &engine.user_interface,
+        );
+
+        engine.user_interface.capture_mouse(self.frame());
+
         if button == MouseButton::Left {
             if let Some(current_im) = active_interaction_mode {
-                let rel_pos = pos - screen_bounds.position;
+                let rel_pos = self.pane_position(
+                    pos - screen_bounds.position,
+                    screen_bounds.size,
+                    editor_scene,
+                );
 
                 self.click_mouse_pos = Some(rel_pos);
 
@@ -662,13 +1200,25 @@ impl SceneViewer {
                         ];
 
                         self.sender
-                            .send(Message::do_scene_command(CommandGroup::from(group)))
+                            .send(Message::do_scene_command(
+                                CommandGroup::from(group).with_custom_name(format!(
+                                    "Instantiate {}",
+                                    item.path.display()
+                                )),
+                            ))
                             .unwrap();
                     }
                 }
                 AssetKind::Texture => {
                     let cursor_pos = engine.user_interface.cursor_position();
                     let rel_pos = cursor_pos - screen_bounds.position;
+                    self.activate_pane_at(
+                        rel_pos,
+                        frame_size,
+                        editor_scene,
+                        &engine.user_interface,
+                    );
+                    let rel_pos = self.pane_position(rel_pos, frame_size, editor_scene);
                     let graph = &engine.scenes[editor_scene.scene].graph;
                     if let Some(result) = editor_scene.camera_controller.pick(PickingOptions {
                         cursor_pos: rel_pos,
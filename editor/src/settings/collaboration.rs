@@ -0,0 +1,44 @@
+use fyrox::{
+    core::inspect::{Inspect, PropertyInfo},
+    gui::inspector::{FieldKind, PropertyChanged},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug, Inspect)]
+pub struct CollaborationSettings {
+    /// Create `<scene>.lock` files for opened scenes, so other people will be warned when they try
+    /// to open the same scene.
+    pub use_lock_files: bool,
+    /// Locks older than this value (in minutes) are considered stale (left by a crashed editor)
+    /// and can be reclaimed without a warning.
+    #[inspect(min_value = 1.0, step = 1.0)]
+    pub stale_lock_age_minutes: u32,
+    /// Watch the opened scene file and offer to reload it if it was changed outside of the editor.
+    pub detect_external_changes: bool,
+}
+
+impl Default for CollaborationSettings {
+    fn default() -> Self {
+        Self {
+            use_lock_files: true,
+            stale_lock_age_minutes: 60,
+            detect_external_changes: true,
+        }
+    }
+}
+
+impl CollaborationSettings {
+    pub fn handle_property_changed(&mut self, property_changed: &PropertyChanged) -> bool {
+        if let FieldKind::Object(ref args) = property_changed.value {
+            return match property_changed.name.as_ref() {
+                Self::USE_LOCK_FILES => args.try_override(&mut self.use_lock_files),
+                Self::STALE_LOCK_AGE_MINUTES => args.try_override(&mut self.stale_lock_age_minutes),
+                Self::DETECT_EXTERNAL_CHANGES => {
+                    args.try_override(&mut self.detect_external_changes)
+                }
+                _ => false,
+            };
+        }
+        false
+    }
+}
@@ -4,11 +4,25 @@ use fyrox::{
 };
 use serde::{Deserialize, Serialize};
 
+fn default_tint_selection_with_node_color() -> bool {
+    true
+}
+
+fn default_selection_outline() -> bool {
+    true
+}
+
 #[derive(Deserialize, Serialize, PartialEq, Clone, Debug, Inspect)]
 pub struct DebuggingSettings {
     pub show_physics: bool,
     pub show_bounds: bool,
     pub show_tbn: bool,
+    /// Selection boxes of nodes with a color (set in the world viewer) are drawn using the color.
+    #[serde(default = "default_tint_selection_with_node_color")]
+    pub tint_selection_with_node_color: bool,
+    /// Selected nodes are outlined in the scene preview.
+    #[serde(default = "default_selection_outline")]
+    pub selection_outline: bool,
 }
 
 impl Default for DebuggingSettings {
@@ -17,6 +31,8 @@ impl Default for DebuggingSettings {
             show_physics: true,
             show_bounds: true,
             show_tbn: false,
+            tint_selection_with_node_color: default_tint_selection_with_node_color(),
+            selection_outline: default_selection_outline(),
         }
     }
 }
@@ -28,6 +44,10 @@ impl DebuggingSettings {
                 Self::SHOW_PHYSICS => args.try_override(&mut self.show_physics),
                 Self::SHOW_BOUNDS => args.try_override(&mut self.show_bounds),
                 Self::SHOW_TBN => args.try_override(&mut self.show_tbn),
+                Self::TINT_SELECTION_WITH_NODE_COLOR => {
+                    args.try_override(&mut self.tint_selection_with_node_color)
+                }
+                Self::SELECTION_OUTLINE => args.try_override(&mut self.selection_outline),
                 _ => false,
             };
         }
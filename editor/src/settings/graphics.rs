@@ -1,4 +1,4 @@
-use fyrox::renderer::CsmSettings;
+use fyrox::renderer::{CsmSettings, LightClusterSettings, SsaoSettings};
 use fyrox::{
     core::inspect::{Inspect, PropertyInfo},
     gui::inspector::{FieldKind, PropertyChanged},
@@ -6,11 +6,23 @@ use fyrox::{
 };
 use serde::{Deserialize, Serialize};
 
+pub const MIN_RENDER_SCALE: f32 = 0.25;
+pub const MAX_RENDER_SCALE: f32 = 2.0;
+
+fn default_render_scale() -> f32 {
+    1.0
+}
+
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Inspect)]
 pub struct GraphicsSettings {
     pub quality: QualitySettings,
     pub z_near: f32,
     pub z_far: f32,
+    /// Scale of the resolution of the scene preview relative to the size of the preview on the
+    /// screen. Values less than one lower GPU load, values greater than one supersample the scene.
+    #[inspect(min_value = 0.25, max_value = 2.0, step = 0.05)]
+    #[serde(default = "default_render_scale")]
+    pub render_scale: f32,
 }
 
 impl Default for GraphicsSettings {
@@ -19,6 +31,7 @@ impl Default for GraphicsSettings {
             quality: Default::default(),
             z_near: 0.025,
             z_far: 128.0,
+            render_scale: default_render_scale(),
         }
     }
 }
@@ -39,6 +52,49 @@ fn handle_csm_settings_property_changed(
     false
 }
 
+fn handle_ssao_settings_property_changed(
+    settings: &mut SsaoSettings,
+    property_changed: &PropertyChanged,
+) -> bool {
+    if let FieldKind::Object(ref args) = property_changed.value {
+        return match property_changed.name.as_ref() {
+            SsaoSettings::QUALITY => {
+                let mut quality = settings.quality;
+                let changed = args.try_override(&mut quality);
+                if changed {
+                    *settings = SsaoSettings::from_quality(quality);
+                }
+                changed
+            }
+            SsaoSettings::SAMPLE_COUNT => args.try_override(&mut settings.sample_count),
+            SsaoSettings::RESOLUTION_SCALE => args.try_override(&mut settings.resolution_scale),
+            SsaoSettings::BLUR_PASSES => args.try_override(&mut settings.blur_passes),
+            SsaoSettings::TEMPORAL_ACCUMULATION => {
+                args.try_override(&mut settings.temporal_accumulation)
+            }
+            SsaoSettings::BENT_NORMALS => args.try_override(&mut settings.bent_normals),
+            _ => false,
+        };
+    }
+    false
+}
+
+fn handle_light_cluster_settings_property_changed(
+    settings: &mut LightClusterSettings,
+    property_changed: &PropertyChanged,
+) -> bool {
+    if let FieldKind::Object(ref args) = property_changed.value {
+        return match property_changed.name.as_ref() {
+            LightClusterSettings::ENABLED => args.try_override(&mut settings.enabled),
+            LightClusterSettings::TILES_X => args.try_override(&mut settings.tiles_x),
+            LightClusterSettings::TILES_Y => args.try_override(&mut settings.tiles_y),
+            LightClusterSettings::DEPTH_SLICES => args.try_override(&mut settings.depth_slices),
+            _ => false,
+        };
+    }
+    false
+}
+
 fn handle_quality_property_changed(
     settings: &mut QualitySettings,
     property_changed: &PropertyChanged,
@@ -78,20 +134,31 @@ fn handle_quality_property_changed(
                     args.try_override(&mut settings.spot_shadow_map_precision)
                 }
 
+                QualitySettings::SHADOWS_DISTANCE_MULTIPLIER => {
+                    args.try_override(&mut settings.shadows_distance_multiplier)
+                }
+
                 QualitySettings::USE_SSAO => args.try_override(&mut settings.use_ssao),
                 QualitySettings::SSAO_RADIUS => args.try_override(&mut settings.ssao_radius),
 
                 QualitySettings::LIGHT_SCATTER_ENABLED => {
                     args.try_override(&mut settings.light_scatter_enabled)
                 }
+                QualitySettings::FOG_VOLUME_SAMPLES => {
+                    args.try_override(&mut settings.fog_volume_samples)
+                }
 
                 QualitySettings::FXAA => args.try_override(&mut settings.fxaa),
+                QualitySettings::TAA => args.try_override(&mut settings.taa),
 
                 QualitySettings::USE_PARALLAX_MAPPING => {
                     args.try_override(&mut settings.use_parallax_mapping)
                 }
 
                 QualitySettings::USE_BLOOM => args.try_override(&mut settings.use_bloom),
+                QualitySettings::USE_DEPTH_PREPASS => {
+                    args.try_override(&mut settings.use_depth_prepass)
+                }
                 _ => false,
             };
         }
@@ -103,6 +170,18 @@ fn handle_quality_property_changed(
                         &**inner,
                     )
                 }
+                QualitySettings::SSAO_SETTINGS => {
+                    return handle_ssao_settings_property_changed(
+                        &mut settings.ssao_settings,
+                        &**inner,
+                    )
+                }
+                QualitySettings::LIGHT_CLUSTER_SETTINGS => {
+                    return handle_light_cluster_settings_property_changed(
+                        &mut settings.light_cluster_settings,
+                        &**inner,
+                    )
+                }
                 _ => false,
             }
         }
@@ -118,6 +197,12 @@ impl GraphicsSettings {
                 return match property_changed.name.as_ref() {
                     Self::Z_NEAR => args.try_override(&mut self.z_near),
                     Self::Z_FAR => args.try_override(&mut self.z_far),
+                    Self::RENDER_SCALE => {
+                        let changed = args.try_override(&mut self.render_scale);
+                        self.render_scale =
+                            self.render_scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE);
+                        changed
+                    }
                     _ => false,
                 };
             }
@@ -1,9 +1,17 @@
 use crate::{
     inspector::editors::make_property_editors_container,
+    interaction::transform_space::TransformSpace,
     settings::{
-        debugging::DebuggingSettings, graphics::GraphicsSettings,
-        move_mode::MoveInteractionModeSettings, rotate_mode::RotateInteractionModeSettings,
+        collaboration::CollaborationSettings,
+        debugging::DebuggingSettings,
+        graphics::GraphicsSettings,
+        move_mode::MoveInteractionModeSettings,
+        onion_skin::OnionSkinSettings,
+        preview::PreviewSettings,
+        render_layers::{RenderLayer, RenderLayersSettings},
+        rotate_mode::RotateInteractionModeSettings,
         selection::SelectionSettings,
+        viewports::ViewportLayoutSettings,
     },
     GameEngine, Message, MSG_SYNC_FLAG,
 };
@@ -18,6 +26,7 @@ use fyrox::{
         grid::{Column, GridBuilder, Row},
         inspector::{
             editors::{
+                collection::VecCollectionPropertyEditorDefinition,
                 enumeration::EnumPropertyEditorDefinition,
                 inspectable::InspectablePropertyEditorDefinition,
                 PropertyEditorDefinitionContainer,
@@ -31,18 +40,26 @@ use fyrox::{
         window::{WindowBuilder, WindowMessage, WindowTitle},
         HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
     },
-    renderer::{CsmSettings, QualitySettings, ShadowMapPrecision},
+    renderer::{
+        CsmSettings, LightClusterSettings, QualitySettings, ShadowMapPrecision, SsaoQuality,
+        SsaoSettings,
+    },
     utils::log::Log,
 };
 use ron::ser::PrettyConfig;
 use serde::{Deserialize, Serialize};
-use std::{fs::File, path::PathBuf, rc::Rc, sync::mpsc::Sender};
+use std::{collections::HashMap, fs::File, path::PathBuf, rc::Rc, sync::mpsc::Sender};
 
+pub mod collaboration;
 pub mod debugging;
 pub mod graphics;
 pub mod move_mode;
+pub mod onion_skin;
+pub mod preview;
+pub mod render_layers;
 pub mod rotate_mode;
 pub mod selection;
+pub mod viewports;
 
 pub struct SettingsWindow {
     window: Handle<UiNode>,
@@ -58,6 +75,24 @@ pub struct Settings {
     pub debugging: DebuggingSettings,
     pub move_mode_settings: MoveInteractionModeSettings,
     pub rotate_mode_settings: RotateInteractionModeSettings,
+    #[serde(default)]
+    pub collaboration: CollaborationSettings,
+    #[serde(default)]
+    pub render_layers: RenderLayersSettings,
+    #[serde(default)]
+    pub preview: PreviewSettings,
+    #[serde(default)]
+    pub onion_skin: OnionSkinSettings,
+    /// Layouts of viewport panes of scenes, it is not an option and it is not shown in the settings
+    /// window.
+    #[serde(default)]
+    #[inspect(skip)]
+    pub viewport_layouts: HashMap<PathBuf, ViewportLayoutSettings>,
+    /// Coordinate space of move, rotate and scale interaction modes, it is selected in the toolbar
+    /// of the scene preview.
+    #[serde(default)]
+    #[inspect(skip)]
+    pub transform_space: TransformSpace,
 }
 
 #[derive(Debug)]
@@ -106,7 +141,16 @@ impl Settings {
         container.insert(EnumPropertyEditorDefinition::<ShadowMapPrecision>::new());
         container.insert(InspectablePropertyEditorDefinition::<DebuggingSettings>::new());
         container.insert(InspectablePropertyEditorDefinition::<CsmSettings>::new());
+        container.insert(InspectablePropertyEditorDefinition::<SsaoSettings>::new());
+        container.insert(EnumPropertyEditorDefinition::<SsaoQuality>::new());
+        container.insert(InspectablePropertyEditorDefinition::<LightClusterSettings>::new());
+        container.insert(InspectablePropertyEditorDefinition::<CollaborationSettings>::new());
         container.insert(InspectablePropertyEditorDefinition::<QualitySettings>::new());
+        container.insert(InspectablePropertyEditorDefinition::<RenderLayersSettings>::new());
+        container.insert(InspectablePropertyEditorDefinition::<RenderLayer>::new());
+        container.insert(VecCollectionPropertyEditorDefinition::<RenderLayer>::new());
+        container.insert(InspectablePropertyEditorDefinition::<PreviewSettings>::new());
+        container.insert(InspectablePropertyEditorDefinition::<OnionSkinSettings>::new());
         container.insert(InspectablePropertyEditorDefinition::<
             MoveInteractionModeSettings,
         >::new());
@@ -129,6 +173,10 @@ impl Settings {
                 Self::ROTATE_MODE_SETTINGS => {
                     self.rotate_mode_settings.handle_property_changed(&**inner)
                 }
+                Self::COLLABORATION => self.collaboration.handle_property_changed(&**inner),
+                Self::RENDER_LAYERS => self.render_layers.handle_property_changed(&**inner),
+                Self::PREVIEW => self.preview.handle_property_changed(&**inner),
+                Self::ONION_SKIN => self.onion_skin.handle_property_changed(&**inner),
                 _ => false,
             };
         }
@@ -249,7 +297,10 @@ impl SettingsWindow {
                     MessageDirection::ToWidget,
                 ));
             } else if message.destination() == self.default {
-                *settings = Default::default();
+                *settings = Settings {
+                    viewport_layouts: std::mem::take(&mut settings.viewport_layouts),
+                    ..Default::default()
+                };
                 self.sync_to_model(&mut engine.user_interface, settings, sender);
             }
         } else if let Some(InspectorMessage::PropertyChanged(property_changed)) = message.data() {
@@ -261,6 +312,16 @@ impl SettingsWindow {
                     property_changed.path()
                 ))
             }
+
+            // Items of a collection were added or removed, the inspector must be re-created to
+            // reflect the changes.
+            // Changing SSAO quality tier resets every other SSAO setting to the preset of the tier.
+            if settings.render_layers.layers.len() != old_settings.render_layers.layers.len()
+                || settings.graphics.quality.ssao_settings.quality
+                    != old_settings.graphics.quality.ssao_settings.quality
+            {
+                self.sync_to_model(&mut engine.user_interface, settings, sender);
+            }
         }
 
         // Apply only if anything changed.
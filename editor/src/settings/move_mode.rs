@@ -4,12 +4,21 @@ use fyrox::{
 };
 use serde::{Deserialize, Serialize};
 
+fn default_snapping_radius() -> f32 {
+    12.0
+}
+
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Inspect)]
 pub struct MoveInteractionModeSettings {
     pub grid_snapping: bool,
     pub x_snap_step: f32,
     pub y_snap_step: f32,
     pub z_snap_step: f32,
+    /// Max distance in pixels between the cursor and a vertex, an edge or a surface of a mesh that
+    /// moved nodes are snapped to while `Shift` is held.
+    #[inspect(min_value = 1.0, max_value = 64.0, step = 1.0)]
+    #[serde(default = "default_snapping_radius")]
+    pub snapping_radius: f32,
 }
 
 impl Default for MoveInteractionModeSettings {
@@ -19,6 +28,7 @@ impl Default for MoveInteractionModeSettings {
             x_snap_step: 0.05,
             y_snap_step: 0.05,
             z_snap_step: 0.05,
+            snapping_radius: default_snapping_radius(),
         }
     }
 }
@@ -31,6 +41,7 @@ impl MoveInteractionModeSettings {
                 Self::X_SNAP_STEP => args.try_override(&mut self.x_snap_step),
                 Self::Y_SNAP_STEP => args.try_override(&mut self.y_snap_step),
                 Self::Z_SNAP_STEP => args.try_override(&mut self.z_snap_step),
+                Self::SNAPPING_RADIUS => args.try_override(&mut self.snapping_radius),
                 _ => false,
             };
         }
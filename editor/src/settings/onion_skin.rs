@@ -0,0 +1,52 @@
+use fyrox::{
+    core::inspect::{Inspect, PropertyInfo},
+    gui::inspector::{FieldKind, PropertyChanged},
+};
+use serde::{Deserialize, Serialize};
+
+/// Onion skinning shows skeletons of the animation, that is selected in the animation panel, at
+/// neighbouring key frames. It is shown only while the animation panel is open.
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug, Inspect)]
+pub struct OnionSkinSettings {
+    pub enabled: bool,
+    /// Amount of key frames before the current time position of the animation.
+    #[inspect(min_value = 0.0, max_value = 16.0, step = 1.0)]
+    pub previous_keys: usize,
+    /// Amount of key frames after the current time position of the animation.
+    #[inspect(min_value = 0.0, max_value = 16.0, step = 1.0)]
+    pub next_keys: usize,
+    /// Opacity of the closest ghosts.
+    #[inspect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub opacity: f32,
+    /// Opacity of every next ghost is multiplied by this value.
+    #[inspect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub falloff: f32,
+}
+
+impl Default for OnionSkinSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            previous_keys: 2,
+            next_keys: 2,
+            opacity: 0.8,
+            falloff: 0.5,
+        }
+    }
+}
+
+impl OnionSkinSettings {
+    pub fn handle_property_changed(&mut self, property_changed: &PropertyChanged) -> bool {
+        if let FieldKind::Object(ref args) = property_changed.value {
+            return match property_changed.name.as_ref() {
+                Self::ENABLED => args.try_override(&mut self.enabled),
+                Self::PREVIOUS_KEYS => args.try_override(&mut self.previous_keys),
+                Self::NEXT_KEYS => args.try_override(&mut self.next_keys),
+                Self::OPACITY => args.try_override(&mut self.opacity),
+                Self::FALLOFF => args.try_override(&mut self.falloff),
+                _ => false,
+            };
+        }
+        false
+    }
+}
@@ -0,0 +1,30 @@
+use fyrox::{
+    core::inspect::{Inspect, PropertyInfo},
+    gui::inspector::{FieldKind, PropertyChanged},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug, Inspect)]
+pub struct PreviewSettings {
+    /// Volume of sounds of previewed assets.
+    #[inspect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub volume: f32,
+}
+
+impl Default for PreviewSettings {
+    fn default() -> Self {
+        Self { volume: 0.5 }
+    }
+}
+
+impl PreviewSettings {
+    pub fn handle_property_changed(&mut self, property_changed: &PropertyChanged) -> bool {
+        if let FieldKind::Object(ref args) = property_changed.value {
+            return match property_changed.name.as_ref() {
+                Self::VOLUME => args.try_override(&mut self.volume),
+                _ => false,
+            };
+        }
+        false
+    }
+}
@@ -0,0 +1,74 @@
+use crate::inspector::editors::layer_mask::layer_name;
+use fyrox::{
+    core::inspect::{Inspect, PropertyInfo},
+    gui::inspector::{CollectionChanged, FieldKind, PropertyChanged},
+    scene::base::LayerMask,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, PartialEq, Clone, Default, Debug, Inspect)]
+pub struct RenderLayer {
+    pub name: String,
+}
+
+/// Names of render layers of the project. Every name corresponds to a bit of a render mask of
+/// scene nodes and a culling mask of cameras, the first name is the name of the first bit and so
+/// on. The names are used only by the editor, scenes store raw masks.
+#[derive(Deserialize, Serialize, PartialEq, Clone, Default, Debug, Inspect)]
+pub struct RenderLayersSettings {
+    pub layers: Vec<RenderLayer>,
+}
+
+impl RenderLayersSettings {
+    /// Returns display names of all layers, unnamed layers are called `Layer N`.
+    pub fn layer_names(&self) -> Vec<String> {
+        let names = self
+            .layers
+            .iter()
+            .map(|l| l.name.clone())
+            .collect::<Vec<_>>();
+
+        (0..LayerMask::LAYER_COUNT)
+            .map(|layer| layer_name(&names, layer))
+            .collect()
+    }
+
+    pub fn handle_property_changed(&mut self, property_changed: &PropertyChanged) -> bool {
+        if let FieldKind::Collection(ref collection_changed) = property_changed.value {
+            if property_changed.name == Self::LAYERS {
+                return match **collection_changed {
+                    CollectionChanged::Add => {
+                        if self.layers.len() < LayerMask::LAYER_COUNT {
+                            self.layers.push(RenderLayer {
+                                name: format!("Layer {}", self.layers.len()),
+                            });
+                            true
+                        } else {
+                            false
+                        }
+                    }
+                    CollectionChanged::Remove(i) => {
+                        if i < self.layers.len() {
+                            self.layers.remove(i);
+                            true
+                        } else {
+                            false
+                        }
+                    }
+                    CollectionChanged::ItemChanged {
+                        index,
+                        ref property,
+                    } => match (self.layers.get_mut(index), &property.value) {
+                        (Some(layer), FieldKind::Object(args))
+                            if property.name == RenderLayer::NAME =>
+                        {
+                            args.try_override(&mut layer.name)
+                        }
+                        _ => false,
+                    },
+                };
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,42 @@
+use crate::camera::CameraView;
+use serde::{Deserialize, Serialize};
+
+/// Max amount of viewport panes of the scene preview.
+pub const MAX_VIEWPORTS: usize = 4;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ViewportLayout {
+    /// Only the active pane is shown, it occupies the whole preview.
+    Single,
+    /// Every pane is shown in a 2x2 grid.
+    Quad,
+}
+
+impl Default for ViewportLayout {
+    fn default() -> Self {
+        Self::Single
+    }
+}
+
+/// Layout of viewport panes of a scene, it is stored per scene.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ViewportLayoutSettings {
+    pub layout: ViewportLayout,
+    pub active: usize,
+    pub views: [CameraView; MAX_VIEWPORTS],
+}
+
+impl Default for ViewportLayoutSettings {
+    fn default() -> Self {
+        Self {
+            layout: Default::default(),
+            active: 0,
+            views: [
+                CameraView::Perspective,
+                CameraView::Top,
+                CameraView::Front,
+                CameraView::Side,
+            ],
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use crate::{
+    define_command_group, define_command_stack,
+    shader_graph::model::{ShaderGraph, ShaderGraphNode, ShaderNodeSettings},
+};
+use fyrox::core::{
+    algebra::Vector2,
+    pool::{Handle, Ticket},
+};
+use std::{
+    fmt::Debug,
+    ops::{Deref, DerefMut},
+};
+
+#[derive(Debug)]
+pub struct ShaderGraphEditorContext<'a> {
+    pub graph: &'a mut ShaderGraph,
+    pub selection: &'a mut Vec<Handle<ShaderGraphNode>>,
+}
+
+define_command_stack!(
+    ShaderGraphCommandTrait,
+    ShaderGraphCommandStack,
+    ShaderGraphEditorContext
+);
+
+#[derive(Debug)]
+pub struct ShaderGraphCommand(pub Box<dyn ShaderGraphCommandTrait>);
+
+impl Deref for ShaderGraphCommand {
+    type Target = dyn ShaderGraphCommandTrait;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl DerefMut for ShaderGraphCommand {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut *self.0
+    }
+}
+
+impl ShaderGraphCommand {
+    pub fn new<C: ShaderGraphCommandTrait>(cmd: C) -> Self {
+        Self(Box::new(cmd))
+    }
+}
+
+define_command_group!(
+    ShaderGraphCommandTrait,
+    CommandGroup,
+    ShaderGraphCommand,
+    ShaderGraphEditorContext
+);
+
+#[derive(Debug)]
+pub struct ChangeSelectionCommand {
+    pub selection: Vec<Handle<ShaderGraphNode>>,
+}
+
+impl ChangeSelectionCommand {
+    fn swap(&mut self, context: &mut ShaderGraphEditorContext) {
+        std::mem::swap(&mut self.selection, context.selection);
+    }
+}
+
+impl ShaderGraphCommandTrait for ChangeSelectionCommand {
+    fn name(&mut self, _context: &ShaderGraphEditorContext) -> String {
+        "Change Selection".to_string()
+    }
+
+    fn execute(&mut self, context: &mut ShaderGraphEditorContext) {
+        self.swap(context)
+    }
+
+    fn revert(&mut self, context: &mut ShaderGraphEditorContext) {
+        self.swap(context)
+    }
+}
+
+#[derive(Debug)]
+pub enum AddNodeCommand {
+    Unknown,
+    NonExecuted {
+        node: ShaderGraphNode,
+    },
+    Executed {
+        handle: Handle<ShaderGraphNode>,
+    },
+    Reverted {
+        ticket: Ticket<ShaderGraphNode>,
+        node: ShaderGraphNode,
+    },
+}
+
+impl AddNodeCommand {
+    pub fn new(node: ShaderGraphNode) -> Self {
+        Self::NonExecuted { node }
+    }
+}
+
+impl ShaderGraphCommandTrait for AddNodeCommand {
+    fn name(&mut self, _context: &ShaderGraphEditorContext) -> String {
+        "Add Node".to_string()
+    }
+
+    fn execute(&mut self, context: &mut ShaderGraphEditorContext) {
+        match std::mem::replace(self, Self::Unknown) {
+            Self::NonExecuted { node } => {
+                *self = Self::Executed {
+                    handle: context.graph.nodes.spawn(node),
+                };
+            }
+            Self::Reverted { ticket, node } => {
+                *self = Self::Executed {
+                    handle: context.graph.nodes.put_back(ticket, node),
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn revert(&mut self, context: &mut ShaderGraphEditorContext) {
+        match std::mem::replace(self, Self::Unknown) {
+            Self::Executed { handle } => {
+                let (ticket, node) = context.graph.nodes.take_reserve(handle);
+                *self = Self::Reverted { ticket, node }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn finalize(&mut self, context: &mut ShaderGraphEditorContext) {
+        if let Self::Reverted { ticket, .. } = std::mem::replace(self, Self::Unknown) {
+            context.graph.nodes.forget_ticket(ticket)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DeleteNodeCommand {
+    handle: Handle<ShaderGraphNode>,
+    ticket: Option<Ticket<ShaderGraphNode>>,
+    node: Option<ShaderGraphNode>,
+    // Inputs of other nodes that were connected to the deleted node.
+    links: Vec<(Handle<ShaderGraphNode>, usize)>,
+}
+
+impl DeleteNodeCommand {
+    pub fn new(handle: Handle<ShaderGraphNode>) -> Self {
+        Self {
+            handle,
+            ticket: None,
+            node: None,
+            links: Default::default(),
+        }
+    }
+}
+
+impl ShaderGraphCommandTrait for DeleteNodeCommand {
+    fn name(&mut self, _context: &ShaderGraphEditorContext) -> String {
+        "Delete Node".to_string()
+    }
+
+    fn execute(&mut self, context: &mut ShaderGraphEditorContext) {
+        self.links.clear();
+        for (handle, node) in context.graph.nodes.pair_iter_mut() {
+            for (index, input) in node.inputs.iter_mut().enumerate() {
+                if *input == self.handle {
+                    *input = Handle::NONE;
+                    self.links.push((handle, index));
+                }
+            }
+        }
+
+        let (ticket, node) = context.graph.nodes.take_reserve(self.handle);
+        self.ticket = Some(ticket);
+        self.node = Some(node);
+    }
+
+    fn revert(&mut self, context: &mut ShaderGraphEditorContext) {
+        self.handle = context
+            .graph
+            .nodes
+            .put_back(self.ticket.take().unwrap(), self.node.take().unwrap());
+
+        for (node, index) in self.links.drain(..) {
+            context.graph.nodes[node].inputs[index] = self.handle;
+        }
+    }
+
+    fn finalize(&mut self, context: &mut ShaderGraphEditorContext) {
+        if let Some(ticket) = self.ticket.take() {
+            context.graph.nodes.forget_ticket(ticket)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MoveNodeCommand {
+    node: Handle<ShaderGraphNode>,
+    old_position: Vector2<f32>,
+    new_position: Vector2<f32>,
+}
+
+impl MoveNodeCommand {
+    pub fn new(
+        node: Handle<ShaderGraphNode>,
+        old_position: Vector2<f32>,
+        new_position: Vector2<f32>,
+    ) -> Self {
+        Self {
+            node,
+            old_position,
+            new_position,
+        }
+    }
+
+    fn set_position(&self, context: &mut ShaderGraphEditorContext, position: Vector2<f32>) {
+        context.graph.nodes[self.node].position = position;
+    }
+}
+
+impl ShaderGraphCommandTrait for MoveNodeCommand {
+    fn name(&mut self, _context: &ShaderGraphEditorContext) -> String {
+        "Move Node".to_string()
+    }
+
+    fn execute(&mut self, context: &mut ShaderGraphEditorContext) {
+        self.set_position(context, self.new_position);
+    }
+
+    fn revert(&mut self, context: &mut ShaderGraphEditorContext) {
+        self.set_position(context, self.old_position);
+    }
+}
+
+/// Connects an input of a node to the output of other node, or disconnects the input if the
+/// source is `Handle::NONE`. Connections that would form a cycle are rejected.
+#[derive(Debug)]
+pub struct SetInputCommand {
+    pub handle: Handle<ShaderGraphNode>,
+    pub index: usize,
+    pub source: Handle<ShaderGraphNode>,
+}
+
+impl SetInputCommand {
+    fn swap(&mut self, context: &mut ShaderGraphEditorContext) {
+        std::mem::swap(
+            &mut context.graph.nodes[self.handle].inputs[self.index],
+            &mut self.source,
+        );
+    }
+}
+
+impl ShaderGraphCommandTrait for SetInputCommand {
+    fn name(&mut self, _context: &ShaderGraphEditorContext) -> String {
+        if self.source.is_some() {
+            "Connect Nodes".to_string()
+        } else {
+            "Disconnect Nodes".to_string()
+        }
+    }
+
+    fn execute(&mut self, context: &mut ShaderGraphEditorContext) {
+        self.swap(context);
+    }
+
+    fn try_execute(&mut self, context: &mut ShaderGraphEditorContext) -> Result<(), String> {
+        if self.source.is_some() && context.graph.would_create_cycle(self.source, self.handle) {
+            return Err("the connection would create a cycle".to_owned());
+        }
+        self.swap(context);
+        Ok(())
+    }
+
+    fn revert(&mut self, context: &mut ShaderGraphEditorContext) {
+        self.swap(context);
+    }
+}
+
+#[derive(Debug)]
+pub struct SetNodeSettingsCommand {
+    pub handle: Handle<ShaderGraphNode>,
+    pub settings: ShaderNodeSettings,
+}
+
+impl SetNodeSettingsCommand {
+    fn swap(&mut self, context: &mut ShaderGraphEditorContext) {
+        std::mem::swap(
+            &mut context.graph.nodes[self.handle].settings,
+            &mut self.settings,
+        );
+    }
+}
+
+impl ShaderGraphCommandTrait for SetNodeSettingsCommand {
+    fn name(&mut self, _context: &ShaderGraphEditorContext) -> String {
+        "Set Node Settings".to_string()
+    }
+
+    fn execute(&mut self, context: &mut ShaderGraphEditorContext) {
+        self.swap(context);
+    }
+
+    fn revert(&mut self, context: &mut ShaderGraphEditorContext) {
+        self.swap(context);
+    }
+}
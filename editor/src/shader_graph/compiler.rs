@@ -0,0 +1,592 @@
+//! Compiles a shader graph into the engine's shader definition format. Compilation is a simple
+//! traversal of the graph from the output node, every node produces a single local variable in
+//! fragment shaders of every render pass.
+
+use crate::shader_graph::model::{ShaderGraph, ShaderGraphNode, ShaderNodeKind, ValueType};
+use fyrox::{
+    core::pool::Handle,
+    fxhash::{FxHashMap, FxHashSet},
+    material::shader::Shader,
+};
+use std::fmt::{Display, Formatter, Write};
+
+const TEMPLATE: &str = include_str!("template.shader");
+
+/// Names that are used by the template, properties can't have these names.
+const RESERVED_NAMES: [&str; 28] = [
+    "layerIndex",
+    "vegetation",
+    "position",
+    "normal",
+    "texCoord",
+    "tangent",
+    "binormal",
+    "secondTexCoord",
+    "clipPosition",
+    "prevClipPosition",
+    "worldPosition",
+    "tangentSpace",
+    "depth",
+    "main",
+    "outColor",
+    "outNormal",
+    "outAmbient",
+    "outMaterial",
+    "outDecalMask",
+    "outMotionVector",
+    "FragColor",
+    "texture",
+    "float",
+    "int",
+    "uniform",
+    "in",
+    "out",
+    "discard",
+];
+
+const RESERVED_PREFIXES: [&str; 4] = ["fyrox_", "gl_", "S_", "sg_"];
+
+/// An error that occurred during compilation of a graph. The error is bound to a node, unless
+/// it is an error of the graph itself.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompileError {
+    pub node: Handle<ShaderGraphNode>,
+    pub message: String,
+}
+
+impl CompileError {
+    fn new<S: AsRef<str>>(node: Handle<ShaderGraphNode>, message: S) -> Self {
+        Self {
+            node,
+            message: message.as_ref().to_owned(),
+        }
+    }
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.node.is_some() {
+            write!(
+                f,
+                "Node {}:{}: {}",
+                self.node.index(),
+                self.node.generation(),
+                self.message
+            )
+        } else {
+            write!(f, "{}", self.message)
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Expression {
+    code: String,
+    value_type: ValueType,
+}
+
+#[derive(Default)]
+struct Compiler {
+    expressions: FxHashMap<Handle<ShaderGraphNode>, Expression>,
+    body: String,
+    uniforms: String,
+    properties: String,
+    property_names: FxHashSet<String>,
+}
+
+fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+fn float_literal(value: f32) -> String {
+    // Debug formatting always keeps the decimal point, so the literal is a valid GLSL float.
+    format!("{:?}", value)
+}
+
+fn vector_literal(value_type: ValueType, components: &[f32]) -> String {
+    let components = components[..value_type.size()]
+        .iter()
+        .map(|c| float_literal(*c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    match value_type {
+        ValueType::Float => components,
+        _ => format!("{}({})", value_type.glsl_name(), components),
+    }
+}
+
+/// Converts the expression to the given type. Floats are splatted to vectors and vectors are
+/// truncated to smaller ones, any other conversion is an error.
+fn convert(
+    expression: &Expression,
+    target: ValueType,
+    node: Handle<ShaderGraphNode>,
+    input_name: &str,
+) -> Result<String, CompileError> {
+    let size = expression.value_type.size();
+    if expression.value_type == target {
+        Ok(expression.code.clone())
+    } else if expression.value_type == ValueType::Float {
+        Ok(format!("{}({})", target.glsl_name(), expression.code))
+    } else if size > target.size() {
+        Ok(format!("{}.{}", expression.code, &"xyzw"[..target.size()]))
+    } else {
+        Err(CompileError::new(
+            node,
+            format!(
+                "Input {} expects {:?}, but {:?} is connected.",
+                input_name, target, expression.value_type
+            ),
+        ))
+    }
+}
+
+/// Picks a common type of two expressions, floats are implicitly converted to vectors.
+fn unify(
+    a: &Expression,
+    b: &Expression,
+    node: Handle<ShaderGraphNode>,
+) -> Result<ValueType, CompileError> {
+    if a.value_type == b.value_type || b.value_type == ValueType::Float {
+        Ok(a.value_type)
+    } else if a.value_type == ValueType::Float {
+        Ok(b.value_type)
+    } else {
+        Err(CompileError::new(
+            node,
+            format!(
+                "Mismatched types of inputs: {:?} and {:?}.",
+                a.value_type, b.value_type
+            ),
+        ))
+    }
+}
+
+fn swizzle_type(mask: &str, source: ValueType) -> Option<ValueType> {
+    let sets = ["xyzw", "rgba"];
+    let set = sets
+        .iter()
+        .find(|set| mask.chars().all(|c| set.contains(c)))?;
+    if mask
+        .chars()
+        .all(|c| set.find(c).map_or(false, |i| i < source.size()))
+    {
+        ValueType::from_size(mask.len())
+    } else {
+        None
+    }
+}
+
+impl Compiler {
+    fn input(
+        &mut self,
+        graph: &ShaderGraph,
+        handle: Handle<ShaderGraphNode>,
+        index: usize,
+    ) -> Result<Expression, CompileError> {
+        let node = &graph.nodes[handle];
+        let definition = node.kind.inputs()[index];
+        let source = node.inputs[index];
+        if graph.nodes.is_valid_handle(source) {
+            self.visit(graph, source)
+        } else if let Some((value_type, code)) = definition.default {
+            Ok(Expression {
+                code: code.to_owned(),
+                value_type,
+            })
+        } else {
+            Err(CompileError::new(
+                handle,
+                format!("Input {} is not connected.", definition.name),
+            ))
+        }
+    }
+
+    fn typed_input(
+        &mut self,
+        graph: &ShaderGraph,
+        handle: Handle<ShaderGraphNode>,
+        index: usize,
+        value_type: ValueType,
+    ) -> Result<String, CompileError> {
+        let expression = self.input(graph, handle, index)?;
+        let name = graph.nodes[handle].kind.inputs()[index].name;
+        convert(&expression, value_type, handle, name)
+    }
+
+    fn add_property(
+        &mut self,
+        handle: Handle<ShaderGraphNode>,
+        name: &str,
+        glsl_type: &str,
+        kind: String,
+    ) -> Result<(), CompileError> {
+        if !is_valid_identifier(name) {
+            return Err(CompileError::new(
+                handle,
+                format!("{} is not a valid property name.", name),
+            ));
+        }
+        if RESERVED_NAMES.contains(&name) || RESERVED_PREFIXES.iter().any(|p| name.starts_with(p)) {
+            return Err(CompileError::new(
+                handle,
+                format!("Property name {} is reserved.", name),
+            ));
+        }
+        if !self.property_names.insert(name.to_owned()) {
+            return Err(CompileError::new(
+                handle,
+                format!("Property name {} is used more than once.", name),
+            ));
+        }
+
+        writeln!(
+            self.uniforms,
+            "                uniform {} {};",
+            glsl_type, name
+        )
+        .unwrap();
+        writeln!(
+            self.properties,
+            "        (\n            name: \"{}\",\n            kind: {},\n        ),",
+            name, kind
+        )
+        .unwrap();
+
+        Ok(())
+    }
+
+    fn visit(
+        &mut self,
+        graph: &ShaderGraph,
+        handle: Handle<ShaderGraphNode>,
+    ) -> Result<Expression, CompileError> {
+        if let Some(expression) = self.expressions.get(&handle) {
+            return Ok(expression.clone());
+        }
+
+        let node = &graph.nodes[handle];
+        let settings = &node.settings;
+        let value = settings.value;
+
+        let (value_type, code) = match node.kind {
+            ShaderNodeKind::Output => {
+                return Err(CompileError::new(
+                    handle,
+                    "Output node can't be used as an input.",
+                ))
+            }
+            ShaderNodeKind::TexCoord => (ValueType::Vector2, "texCoord".to_owned()),
+            ShaderNodeKind::TextureSample => {
+                let uv = self.typed_input(graph, handle, 0, ValueType::Vector2)?;
+                self.add_property(
+                    handle,
+                    &settings.name,
+                    "sampler2D",
+                    format!("Sampler(default: None, fallback: {:?})", settings.fallback),
+                )?;
+                (
+                    ValueType::Vector4,
+                    format!("texture({}, {})", settings.name, uv),
+                )
+            }
+            ShaderNodeKind::TilingOffset => {
+                let uv = self.typed_input(graph, handle, 0, ValueType::Vector2)?;
+                let tiling = self.typed_input(graph, handle, 1, ValueType::Vector2)?;
+                let offset = self.typed_input(graph, handle, 2, ValueType::Vector2)?;
+                (
+                    ValueType::Vector2,
+                    format!("{} * {} + {}", uv, tiling, offset),
+                )
+            }
+            ShaderNodeKind::Constant => (
+                settings.value_type,
+                vector_literal(settings.value_type, value.as_slice()),
+            ),
+            ShaderNodeKind::Parameter => {
+                let value_type = settings.value_type;
+                let components = value.as_slice()[..value_type.size()]
+                    .iter()
+                    .map(|c| float_literal(*c))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let kind = match value_type {
+                    ValueType::Float => format!("Float({})", components),
+                    _ => format!("{:?}([{}])", value_type, components),
+                };
+                self.add_property(handle, &settings.name, value_type.glsl_name(), kind)?;
+                (value_type, settings.name.clone())
+            }
+            ShaderNodeKind::ColorParameter => {
+                let to_u8 = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+                let kind = format!(
+                    "Color(r: {}, g: {}, b: {}, a: {})",
+                    to_u8(value.x),
+                    to_u8(value.y),
+                    to_u8(value.z),
+                    to_u8(value.w)
+                );
+                self.add_property(handle, &settings.name, "vec4", kind)?;
+                (ValueType::Vector4, settings.name.clone())
+            }
+            ShaderNodeKind::Add
+            | ShaderNodeKind::Subtract
+            | ShaderNodeKind::Multiply
+            | ShaderNodeKind::Divide
+            | ShaderNodeKind::Min
+            | ShaderNodeKind::Max
+            | ShaderNodeKind::Power => {
+                let a = self.input(graph, handle, 0)?;
+                let b = self.input(graph, handle, 1)?;
+                let value_type = unify(&a, &b, handle)?;
+                let a = convert(&a, value_type, handle, "A")?;
+                let b = convert(&b, value_type, handle, "B")?;
+                let code = match node.kind {
+                    ShaderNodeKind::Add => format!("{} + {}", a, b),
+                    ShaderNodeKind::Subtract => format!("{} - {}", a, b),
+                    ShaderNodeKind::Multiply => format!("{} * {}", a, b),
+                    ShaderNodeKind::Divide => format!("{} / {}", a, b),
+                    ShaderNodeKind::Min => format!("min({}, {})", a, b),
+                    ShaderNodeKind::Max => format!("max({}, {})", a, b),
+                    _ => format!("pow({}, {})", a, b),
+                };
+                (value_type, code)
+            }
+            ShaderNodeKind::Lerp => {
+                let a = self.input(graph, handle, 0)?;
+                let b = self.input(graph, handle, 1)?;
+                let t = self.input(graph, handle, 2)?;
+                let value_type = unify(&a, &b, handle)?;
+                let a = convert(&a, value_type, handle, "A")?;
+                let b = convert(&b, value_type, handle, "B")?;
+                // mix() accepts a scalar factor for vectors.
+                let t = if t.value_type == ValueType::Float {
+                    t.code
+                } else {
+                    convert(&t, value_type, handle, "T")?
+                };
+                (value_type, format!("mix({}, {}, {})", a, b, t))
+            }
+            ShaderNodeKind::Clamp => {
+                let x = self.input(graph, handle, 0)?;
+                let value_type = x.value_type;
+                let min = self.typed_input(graph, handle, 1, value_type)?;
+                let max = self.typed_input(graph, handle, 2, value_type)?;
+                (value_type, format!("clamp({}, {}, {})", x.code, min, max))
+            }
+            ShaderNodeKind::NormalUnpack => {
+                let packed = self.input(graph, handle, 0)?;
+                if packed.value_type != ValueType::Vector4 {
+                    return Err(CompileError::new(
+                        handle,
+                        format!(
+                            "Input Value expects a texture sample (Vector4), but {:?} is connected.",
+                            packed.value_type
+                        ),
+                    ));
+                }
+                (
+                    ValueType::Vector3,
+                    format!("S_UnpackNormal({})", packed.code),
+                )
+            }
+            ShaderNodeKind::Swizzle => {
+                let source = self.input(graph, handle, 0)?;
+                let mask = &settings.name;
+                if source.value_type == ValueType::Float {
+                    return Err(CompileError::new(
+                        handle,
+                        "Swizzle can't be applied to a Float.",
+                    ));
+                }
+                let value_type = swizzle_type(mask, source.value_type).ok_or_else(|| {
+                    CompileError::new(
+                        handle,
+                        format!(
+                            "{} is not a valid swizzle mask for {:?}.",
+                            mask, source.value_type
+                        ),
+                    )
+                })?;
+                (value_type, format!("{}.{}", source.code, mask))
+            }
+        };
+
+        let variable = format!("sg_node{}", handle.index());
+        writeln!(
+            self.body,
+            "                    {} {} = {}; // {}",
+            value_type.glsl_name(),
+            variable,
+            code,
+            node.kind.name()
+        )
+        .unwrap();
+
+        let expression = Expression {
+            code: variable,
+            value_type,
+        };
+        self.expressions.insert(handle, expression.clone());
+        Ok(expression)
+    }
+
+    fn compile_output(&mut self, graph: &ShaderGraph) -> Result<(), CompileError> {
+        let output = graph.output;
+        if !graph
+            .nodes
+            .try_borrow(output)
+            .map_or(false, |n| n.kind == ShaderNodeKind::Output)
+        {
+            return Err(CompileError::new(
+                Handle::NONE,
+                "Graph does not have an output node.",
+            ));
+        }
+
+        let names = [
+            "sg_albedo",
+            "sg_normal",
+            "sg_metallic",
+            "sg_roughness",
+            "sg_emission",
+            "sg_opacity",
+        ];
+        for (index, (definition, name)) in ShaderNodeKind::Output
+            .inputs()
+            .iter()
+            .zip(names)
+            .enumerate()
+        {
+            let (value_type, _) = definition.default.unwrap();
+            let code = self.typed_input(graph, output, index, value_type)?;
+            writeln!(
+                self.body,
+                "                    {} {} = {};",
+                value_type.glsl_name(),
+                name,
+                code
+            )
+            .unwrap();
+        }
+
+        Ok(())
+    }
+}
+
+/// Compiles the graph into a source of a shader in the engine's format. The source is validated
+/// the same way as any hand-written shader.
+pub fn compile(graph: &ShaderGraph) -> Result<String, CompileError> {
+    if let Some(node) = graph.find_cycle() {
+        return Err(CompileError::new(node, "The node is a part of a cycle."));
+    }
+
+    let mut compiler = Compiler::default();
+    compiler.compile_output(graph)?;
+
+    let source = TEMPLATE
+        .replace("$NAME", &format!("{:?}", graph.name))
+        .replace("$PROPERTIES", compiler.properties.trim_end())
+        .replace("$UNIFORMS", &compiler.uniforms)
+        .replace("$BODY", compiler.body.trim_end());
+
+    Shader::from_str(&source, "").map_err(|e| {
+        CompileError::new(
+            Handle::NONE,
+            format!("Generated shader is not valid. Reason: {}", e),
+        )
+    })?;
+
+    Ok(source)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::shader_graph::{
+        compiler::compile,
+        model::{ShaderGraph, ShaderGraphNode, ShaderNodeKind, ValueType},
+    };
+    use fyrox::core::algebra::{Vector2, Vector4};
+
+    fn add(
+        graph: &mut ShaderGraph,
+        kind: ShaderNodeKind,
+    ) -> fyrox::core::pool::Handle<ShaderGraphNode> {
+        graph
+            .nodes
+            .spawn(ShaderGraphNode::new(kind, Vector2::default()))
+    }
+
+    #[test]
+    fn test_empty_graph_compiles() {
+        let graph = ShaderGraph::default();
+        let source = compile(&graph).unwrap();
+        assert!(source.contains("vec3 sg_albedo = vec3(1.0);"));
+    }
+
+    #[test]
+    fn test_textured_graph() {
+        let mut graph = ShaderGraph::default();
+
+        let albedo = add(&mut graph, ShaderNodeKind::TextureSample);
+        graph.nodes[albedo].settings.name = "albedoTexture".to_owned();
+
+        let normal = add(&mut graph, ShaderNodeKind::TextureSample);
+        graph.nodes[normal].settings.name = "normalTexture".to_owned();
+        let unpack = add(&mut graph, ShaderNodeKind::NormalUnpack);
+        graph.nodes[unpack].inputs[0] = normal;
+
+        let roughness = add(&mut graph, ShaderNodeKind::Parameter);
+        graph.nodes[roughness].settings.name = "roughness".to_owned();
+        graph.nodes[roughness].settings.value = Vector4::new(0.5, 0.0, 0.0, 0.0);
+
+        let output = graph.output;
+        graph.nodes[output].inputs[0] = albedo;
+        graph.nodes[output].inputs[1] = unpack;
+        graph.nodes[output].inputs[3] = roughness;
+
+        let source = compile(&graph).unwrap();
+        assert!(source.contains("uniform sampler2D albedoTexture;"));
+        assert!(source.contains("kind: Float(0.5)"));
+        assert!(source.contains("vec3 sg_albedo = sg_node1.xyz;"));
+    }
+
+    #[test]
+    fn test_errors_are_mapped_to_nodes() {
+        let mut graph = ShaderGraph::default();
+
+        // Unconnected required input.
+        let add_node = add(&mut graph, ShaderNodeKind::Add);
+        let output = graph.output;
+        graph.nodes[output].inputs[0] = add_node;
+        assert_eq!(compile(&graph).unwrap_err().node, add_node);
+
+        // Type mismatch.
+        let a = add(&mut graph, ShaderNodeKind::Constant);
+        graph.nodes[a].settings.value_type = ValueType::Vector2;
+        let b = add(&mut graph, ShaderNodeKind::Constant);
+        graph.nodes[b].settings.value_type = ValueType::Vector3;
+        graph.nodes[add_node].inputs = vec![a, b];
+        assert_eq!(compile(&graph).unwrap_err().node, add_node);
+
+        // Invalid property name.
+        let param = add(&mut graph, ShaderNodeKind::Parameter);
+        graph.nodes[param].settings.name = "fyrox_worldMatrix".to_owned();
+        graph.nodes[add_node].inputs = vec![param, param];
+        assert_eq!(compile(&graph).unwrap_err().node, param);
+
+        // Cycle.
+        graph.nodes[param].settings.name = "param".to_owned();
+        let other = add(&mut graph, ShaderNodeKind::Multiply);
+        graph.nodes[other].inputs = vec![add_node, param];
+        graph.nodes[add_node].inputs = vec![other, param];
+        assert!(graph.would_create_cycle(add_node, other));
+        assert!(compile(&graph).is_err());
+    }
+}
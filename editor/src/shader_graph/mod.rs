@@ -0,0 +1,935 @@
+//! Node-based shader editor. A graph of nodes is compiled into a shader in the engine's format,
+//! so the result is an ordinary `.shader` asset that could be used by any material.
+
+use crate::{
+    absm::{
+        canvas::{AbsmCanvasBuilder, AbsmCanvasMessage},
+        connection::{Connection, ConnectionBuilder},
+        node::{AbsmNode, AbsmNodeBuilder, AbsmNodeMessage},
+        socket::{Socket, SocketBuilder, SocketDirection},
+    },
+    inspector::editors::make_property_editors_container,
+    menu::{create_menu_item, create_menu_item_shortcut, create_root_menu_item},
+    preview::PreviewPanel,
+    send_sync_message,
+    shader_graph::{
+        command::{
+            AddNodeCommand, ChangeSelectionCommand, CommandGroup, DeleteNodeCommand,
+            MoveNodeCommand, SetInputCommand, SetNodeSettingsCommand, ShaderGraphCommand,
+            ShaderGraphCommandStack, ShaderGraphCommandTrait, ShaderGraphEditorContext,
+        },
+        compiler::{compile, CompileError},
+        model::{ShaderGraph, ShaderGraphNode, ShaderNodeKind, ShaderNodeSettings, ValueType},
+    },
+    utils::{create_file_selector, open_file_selector},
+    GameEngine, Message, MSG_SYNC_FLAG,
+};
+use fyrox::{
+    core::{
+        algebra::Matrix4,
+        futures::executor::block_on,
+        parking_lot::Mutex,
+        pool::Handle,
+        visitor::{Visit, Visitor},
+    },
+    gui::{
+        border::BorderBuilder,
+        file_browser::{FileBrowserMode, FileSelectorMessage},
+        grid::{Column, GridBuilder, Row},
+        inspector::{
+            editors::{
+                enumeration::EnumPropertyEditorDefinition, PropertyEditorDefinitionContainer,
+            },
+            FieldKind, InspectorBuilder, InspectorContext, InspectorMessage,
+        },
+        menu::{MenuBuilder, MenuItemMessage},
+        message::{MessageDirection, UiMessage},
+        popup::{Placement, PopupBuilder, PopupMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        window::{WindowBuilder, WindowMessage, WindowTitle},
+        BuildContext, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    material::{
+        shader::{SamplerFallback, Shader},
+        Material,
+    },
+    scene::{
+        base::BaseBuilder,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder,
+        },
+    },
+    utils::log::Log,
+};
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{mpsc::Sender, Arc},
+};
+
+mod command;
+mod compiler;
+mod model;
+
+struct Menu {
+    new: Handle<UiNode>,
+    open: Handle<UiNode>,
+    save: Handle<UiNode>,
+    save_as: Handle<UiNode>,
+    export: Handle<UiNode>,
+    undo: Handle<UiNode>,
+    redo: Handle<UiNode>,
+}
+
+struct CanvasContextMenu {
+    menu: Handle<UiNode>,
+    items: Vec<(Handle<UiNode>, ShaderNodeKind)>,
+}
+
+impl CanvasContextMenu {
+    fn new(ctx: &mut BuildContext) -> Self {
+        let items = ShaderNodeKind::CREATABLE
+            .iter()
+            .map(|kind| (create_menu_item(kind.name(), vec![], ctx), *kind))
+            .collect::<Vec<_>>();
+
+        let menu = PopupBuilder::new(WidgetBuilder::new().with_visibility(false))
+            .with_content(
+                StackPanelBuilder::new(
+                    WidgetBuilder::new().with_children(items.iter().map(|(item, _)| *item)),
+                )
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self { menu, items }
+    }
+}
+
+struct NodeContextMenu {
+    menu: Handle<UiNode>,
+    remove: Handle<UiNode>,
+}
+
+impl NodeContextMenu {
+    fn new(ctx: &mut BuildContext) -> Self {
+        let remove;
+        let menu = PopupBuilder::new(WidgetBuilder::new().with_visibility(false))
+            .with_content(
+                StackPanelBuilder::new(WidgetBuilder::new().with_child({
+                    remove = create_menu_item("Remove", vec![], ctx);
+                    remove
+                }))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self { menu, remove }
+    }
+}
+
+struct ConnectionContextMenu {
+    menu: Handle<UiNode>,
+    remove: Handle<UiNode>,
+    placement_target: Handle<UiNode>,
+}
+
+impl ConnectionContextMenu {
+    fn new(ctx: &mut BuildContext) -> Self {
+        let remove;
+        let menu = PopupBuilder::new(WidgetBuilder::new().with_visibility(false))
+            .with_content(
+                StackPanelBuilder::new(WidgetBuilder::new().with_child({
+                    remove = create_menu_item("Remove Connection", vec![], ctx);
+                    remove
+                }))
+                .build(ctx),
+            )
+            .build(ctx);
+
+        Self {
+            menu,
+            remove,
+            placement_target: Default::default(),
+        }
+    }
+}
+
+pub struct ShaderGraphEditor {
+    pub window: Handle<UiNode>,
+    canvas: Handle<UiNode>,
+    inspector: Handle<UiNode>,
+    status: Handle<UiNode>,
+    menu: Menu,
+    canvas_context_menu: CanvasContextMenu,
+    node_context_menu: NodeContextMenu,
+    connection_context_menu: ConnectionContextMenu,
+    open_file_selector: Handle<UiNode>,
+    save_file_selector: Handle<UiNode>,
+    export_file_selector: Handle<UiNode>,
+    property_editors: Rc<PropertyEditorDefinitionContainer>,
+    inspected_node: Handle<ShaderGraphNode>,
+    preview: PreviewPanel,
+    graph: ShaderGraph,
+    selection: Vec<Handle<ShaderGraphNode>>,
+    command_stack: ShaderGraphCommandStack,
+    path: PathBuf,
+}
+
+fn create_input_socket(
+    index: usize,
+    name: &str,
+    parent_node: Handle<ShaderGraphNode>,
+    ctx: &mut BuildContext,
+) -> Handle<UiNode> {
+    let label = TextBuilder::new(
+        WidgetBuilder::new()
+            .with_margin(Thickness::left(4.0))
+            .with_vertical_alignment(VerticalAlignment::Center),
+    )
+    .with_text(name)
+    .build(ctx);
+
+    SocketBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+        .with_direction(SocketDirection::Input)
+        .with_parent_node(parent_node)
+        .with_index(index)
+        .with_editor(label)
+        .build(ctx)
+}
+
+fn fetch_model_handle(handle: Handle<UiNode>, ui: &UserInterface) -> Handle<ShaderGraphNode> {
+    ui.node(handle)
+        .query_component::<AbsmNode<ShaderGraphNode>>()
+        .unwrap()
+        .model_handle
+}
+
+impl ShaderGraphEditor {
+    pub fn new(engine: &mut GameEngine, sender: Sender<Message>) -> Self {
+        let mut preview = PreviewPanel::new(engine, 250, 250);
+
+        let graph = &mut engine.scenes[preview.scene()].graph;
+        let sphere = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+                SurfaceData::make_sphere(30, 30, 1.0, &Matrix4::identity()),
+            )))
+            .build()])
+            .build(graph);
+        preview.set_model(sphere, engine);
+
+        let ctx = &mut engine.user_interface.build_ctx();
+
+        let property_editors = make_property_editors_container(sender);
+        property_editors.insert(EnumPropertyEditorDefinition::<ValueType>::new());
+        property_editors.insert(EnumPropertyEditorDefinition::<SamplerFallback>::new());
+
+        let open_file_selector = create_file_selector(ctx, "shadergraph", FileBrowserMode::Open);
+        let save_file_selector = create_file_selector(
+            ctx,
+            "shadergraph",
+            FileBrowserMode::Save {
+                default_file_name: PathBuf::from("unnamed.shadergraph"),
+            },
+        );
+        let export_file_selector = create_file_selector(
+            ctx,
+            "shader",
+            FileBrowserMode::Save {
+                default_file_name: PathBuf::from("unnamed.shader"),
+            },
+        );
+
+        let canvas_context_menu = CanvasContextMenu::new(ctx);
+        let node_context_menu = NodeContextMenu::new(ctx);
+        let connection_context_menu = ConnectionContextMenu::new(ctx);
+
+        let new;
+        let open;
+        let save;
+        let save_as;
+        let export;
+        let undo;
+        let redo;
+        let menu = MenuBuilder::new(WidgetBuilder::new().on_row(0))
+            .with_items(vec![
+                create_root_menu_item(
+                    "File",
+                    vec![
+                        {
+                            new = create_menu_item_shortcut("New", "Ctrl+N", vec![], ctx);
+                            new
+                        },
+                        {
+                            open = create_menu_item_shortcut("Open...", "Ctrl+O", vec![], ctx);
+                            open
+                        },
+                        {
+                            save = create_menu_item_shortcut("Save", "Ctrl+S", vec![], ctx);
+                            save
+                        },
+                        {
+                            save_as = create_menu_item("Save As...", vec![], ctx);
+                            save_as
+                        },
+                        {
+                            export = create_menu_item("Export Shader...", vec![], ctx);
+                            export
+                        },
+                    ],
+                    ctx,
+                ),
+                create_root_menu_item(
+                    "Edit",
+                    vec![
+                        {
+                            undo = create_menu_item_shortcut("Undo", "Ctrl+Z", vec![], ctx);
+                            undo
+                        },
+                        {
+                            redo = create_menu_item_shortcut("Redo", "Ctrl+Y", vec![], ctx);
+                            redo
+                        },
+                    ],
+                    ctx,
+                ),
+            ])
+            .build(ctx);
+
+        let canvas = AbsmCanvasBuilder::new(
+            WidgetBuilder::new().with_context_menu(canvas_context_menu.menu),
+        )
+        .build(ctx);
+
+        let inspector = InspectorBuilder::new(WidgetBuilder::new()).build(ctx);
+        let status;
+        let preview_panel;
+        let window = WindowBuilder::new(WidgetBuilder::new().with_width(1000.0).with_height(600.0))
+            .open(false)
+            .with_title(WindowTitle::text("Shader Graph Editor"))
+            .with_content(
+                GridBuilder::new(
+                    WidgetBuilder::new()
+                        .with_child(menu)
+                        .with_child(
+                            GridBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(1)
+                                    .with_child(
+                                        BorderBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_column(0)
+                                                .with_margin(Thickness::uniform(1.0))
+                                                .with_child(canvas),
+                                        )
+                                        .build(ctx),
+                                    )
+                                    .with_child(
+                                        GridBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_column(1)
+                                                .with_child({
+                                                    preview_panel = BorderBuilder::new(
+                                                        WidgetBuilder::new().on_row(0),
+                                                    )
+                                                    .build(ctx);
+                                                    preview_panel
+                                                })
+                                                .with_child(
+                                                    BorderBuilder::new(
+                                                        WidgetBuilder::new()
+                                                            .on_row(1)
+                                                            .with_child(inspector),
+                                                    )
+                                                    .build(ctx),
+                                                ),
+                                        )
+                                        .add_row(Row::strict(270.0))
+                                        .add_row(Row::stretch())
+                                        .add_column(Column::stretch())
+                                        .build(ctx),
+                                    ),
+                            )
+                            .add_row(Row::stretch())
+                            .add_column(Column::stretch())
+                            .add_column(Column::strict(300.0))
+                            .build(ctx),
+                        )
+                        .with_child({
+                            status = TextBuilder::new(
+                                WidgetBuilder::new()
+                                    .on_row(2)
+                                    .with_margin(Thickness::uniform(2.0)),
+                            )
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx);
+                            status
+                        }),
+                )
+                .add_row(Row::strict(25.0))
+                .add_row(Row::stretch())
+                .add_row(Row::strict(22.0))
+                .add_column(Column::stretch())
+                .build(ctx),
+            )
+            .build(ctx);
+
+        ctx.link(preview.root, preview_panel);
+
+        let mut editor = Self {
+            window,
+            canvas,
+            inspector,
+            status,
+            menu: Menu {
+                new,
+                open,
+                save,
+                save_as,
+                export,
+                undo,
+                redo,
+            },
+            canvas_context_menu,
+            node_context_menu,
+            connection_context_menu,
+            open_file_selector,
+            save_file_selector,
+            export_file_selector,
+            property_editors: Rc::new(property_editors),
+            inspected_node: Default::default(),
+            preview,
+            graph: Default::default(),
+            selection: Default::default(),
+            command_stack: ShaderGraphCommandStack::new(false),
+            path: Default::default(),
+        };
+
+        editor.sync_to_model(engine);
+
+        editor
+    }
+
+    pub fn open(&self, ui: &UserInterface) {
+        ui.send_message(WindowMessage::open(
+            self.window,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    fn do_command<C: ShaderGraphCommandTrait>(&mut self, command: C, engine: &mut GameEngine) {
+        self.command_stack.do_command(
+            Box::new(command),
+            ShaderGraphEditorContext {
+                graph: &mut self.graph,
+                selection: &mut self.selection,
+            },
+        );
+
+        self.sync_to_model(engine);
+    }
+
+    fn set_graph(&mut self, graph: ShaderGraph, path: PathBuf, engine: &mut GameEngine) {
+        self.command_stack.clear(ShaderGraphEditorContext {
+            graph: &mut self.graph,
+            selection: &mut self.selection,
+        });
+
+        self.graph = graph;
+        self.selection.clear();
+        self.path = path;
+
+        self.sync_title(&engine.user_interface);
+        self.sync_to_model(engine);
+    }
+
+    fn sync_title(&self, ui: &UserInterface) {
+        let title = if self.path == PathBuf::default() {
+            "Shader Graph Editor - Unnamed Graph".to_string()
+        } else {
+            format!("Shader Graph Editor - {}", self.path.display())
+        };
+
+        ui.send_message(WindowMessage::title(
+            self.window,
+            MessageDirection::ToWidget,
+            WindowTitle::text(title),
+        ));
+    }
+
+    fn save(&mut self, path: &Path, ui: &UserInterface) {
+        let mut visitor = Visitor::new();
+        Log::verify(self.graph.visit("ShaderGraph", &mut visitor));
+        match visitor.save_binary(path) {
+            Ok(_) => {
+                self.path = path.to_path_buf();
+                self.sync_title(ui);
+            }
+            Err(e) => Log::err(format!(
+                "Unable to save shader graph to {}. Reason: {}",
+                path.display(),
+                e
+            )),
+        }
+    }
+
+    fn load(&mut self, path: &Path, engine: &mut GameEngine) {
+        match block_on(Visitor::load_binary(path)) {
+            Ok(mut visitor) => {
+                let mut graph = ShaderGraph::default();
+                match graph.visit("ShaderGraph", &mut visitor) {
+                    Ok(_) => self.set_graph(graph, path.to_path_buf(), engine),
+                    Err(e) => Log::err(format!(
+                        "Unable to read shader graph from {}. Reason: {}",
+                        path.display(),
+                        e
+                    )),
+                }
+            }
+            Err(e) => Log::err(format!(
+                "Unable to load shader graph from {}. Reason: {}",
+                path.display(),
+                e
+            )),
+        }
+    }
+
+    fn export(&mut self, path: &Path, engine: &mut GameEngine) {
+        match compile(&self.graph) {
+            Ok(source) => {
+                if let Err(e) = std::fs::write(path, source) {
+                    Log::err(format!(
+                        "Unable to write shader to {}. Reason: {}",
+                        path.display(),
+                        e
+                    ));
+                } else {
+                    // Materials that use a previous version of the shader must see the changes
+                    // right away.
+                    block_on(engine.resource_manager.reload_shaders());
+
+                    Log::info(format!("Shader was exported to {}.", path.display()));
+                }
+            }
+            Err(e) => Log::err(format!("Unable to export shader. Reason: {}", e)),
+        }
+    }
+
+    fn sync_to_model(&mut self, engine: &mut GameEngine) {
+        let result = compile(&self.graph);
+
+        self.sync_canvas(&mut engine.user_interface, result.as_ref().err());
+        self.sync_inspector(&mut engine.user_interface);
+        self.sync_preview(engine, &result);
+
+        let status = match result {
+            Ok(_) => "Compiled successfully.".to_owned(),
+            Err(e) => e.to_string(),
+        };
+        engine.user_interface.send_message(TextMessage::text(
+            self.status,
+            MessageDirection::ToWidget,
+            status,
+        ));
+    }
+
+    fn sync_preview(&mut self, engine: &mut GameEngine, result: &Result<String, CompileError>) {
+        if let Ok(source) = result {
+            match Shader::from_str(source, "") {
+                Ok(shader) => {
+                    let material =
+                        Material::from_shader(shader, Some(engine.resource_manager.clone()));
+                    engine.scenes[self.preview.scene()].graph[self.preview.model()]
+                        .as_mesh_mut()
+                        .surfaces_mut()
+                        .first_mut()
+                        .unwrap()
+                        .set_material(Arc::new(Mutex::new(material)));
+                }
+                Err(e) => Log::err(format!("Unable to create preview shader. Reason: {}", e)),
+            }
+        }
+    }
+
+    // Nodes of a shader graph are cheap to build, so the canvas is rebuilt from scratch on
+    // every change of the graph.
+    fn sync_canvas(&mut self, ui: &mut UserInterface, error: Option<&CompileError>) {
+        for &child in ui.node(self.canvas).children() {
+            ui.send_message(WidgetMessage::remove(child, MessageDirection::ToWidget));
+        }
+
+        let mut views = Vec::new();
+        for (handle, node) in self.graph.nodes.pair_iter() {
+            let ctx = &mut ui.build_ctx();
+
+            let input_sockets = node
+                .kind
+                .inputs()
+                .iter()
+                .enumerate()
+                .map(|(index, input)| create_input_socket(index, input.name, handle, ctx))
+                .collect::<Vec<_>>();
+
+            let output_socket = if node.kind.has_output() {
+                SocketBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+                    .with_direction(SocketDirection::Output)
+                    .with_parent_node(handle)
+                    .build(ctx)
+            } else {
+                Handle::NONE
+            };
+
+            let view = AbsmNodeBuilder::new(
+                WidgetBuilder::new()
+                    .with_desired_position(node.position)
+                    .with_context_menu(self.node_context_menu.menu),
+            )
+            .with_name(node.description())
+            .with_title(node.kind.name().to_owned())
+            .with_input_sockets(input_sockets.clone())
+            .with_output_socket(output_socket)
+            .with_model_handle(handle)
+            .build(ctx);
+
+            ctx.link(view, self.canvas);
+
+            if error.map_or(false, |e| e.node == handle) {
+                ui.send_message(AbsmNodeMessage::set_active(
+                    view,
+                    MessageDirection::ToWidget,
+                    true,
+                ));
+            }
+
+            views.push((handle, view, input_sockets, output_socket));
+        }
+
+        // Force update layout to be able to fetch positions of sockets for connections.
+        ui.update(ui.screen_size(), 0.0);
+
+        for (handle, view, input_sockets, _) in views.iter() {
+            for (index, source) in self.graph.nodes[*handle].inputs.iter().enumerate() {
+                if let Some((_, source_view, _, source_socket)) =
+                    views.iter().find(|(h, ..)| h == source)
+                {
+                    let connection = ConnectionBuilder::new(
+                        WidgetBuilder::new().with_context_menu(self.connection_context_menu.menu),
+                    )
+                    .with_source_socket(*source_socket)
+                    .with_source_node(*source_view)
+                    .with_dest_socket(input_sockets[index])
+                    .with_dest_node(*view)
+                    .build(self.canvas, &mut ui.build_ctx());
+
+                    send_sync_message(
+                        ui,
+                        WidgetMessage::link(connection, MessageDirection::ToWidget, self.canvas),
+                    );
+                    send_sync_message(
+                        ui,
+                        WidgetMessage::lowermost(connection, MessageDirection::ToWidget),
+                    );
+                }
+            }
+        }
+
+        let selection = views
+            .iter()
+            .filter(|(handle, ..)| self.selection.contains(handle))
+            .map(|(_, view, ..)| *view)
+            .collect::<Vec<_>>();
+        send_sync_message(
+            ui,
+            AbsmCanvasMessage::selection_changed(
+                self.canvas,
+                MessageDirection::ToWidget,
+                selection,
+            ),
+        );
+
+        send_sync_message(
+            ui,
+            AbsmCanvasMessage::force_sync_dependent_objects(
+                self.canvas,
+                MessageDirection::ToWidget,
+            ),
+        );
+    }
+
+    fn sync_inspector(&mut self, ui: &mut UserInterface) {
+        let selected = self
+            .selection
+            .first()
+            .cloned()
+            .filter(|h| self.graph.nodes.is_valid_handle(*h))
+            .unwrap_or_default();
+
+        if selected != self.inspected_node {
+            self.inspected_node = selected;
+
+            let ctx = if let Some(node) = self.graph.nodes.try_borrow(selected) {
+                InspectorContext::from_object(
+                    &node.settings,
+                    &mut ui.build_ctx(),
+                    self.property_editors.clone(),
+                    None,
+                    MSG_SYNC_FLAG,
+                    0,
+                )
+            } else {
+                Default::default()
+            };
+
+            ui.send_message(InspectorMessage::context(
+                self.inspector,
+                MessageDirection::ToWidget,
+                ctx,
+            ));
+        } else if let Some(node) = self.graph.nodes.try_borrow(selected) {
+            let ctx = ui
+                .node(self.inspector)
+                .cast::<fyrox::gui::inspector::Inspector>()
+                .unwrap()
+                .context()
+                .clone();
+
+            if let Err(sync_errors) = ctx.sync(&node.settings, ui, 0) {
+                for error in sync_errors {
+                    Log::err(format!("Failed to sync property. Reason: {:?}", error))
+                }
+            }
+        }
+    }
+
+    fn handle_settings_changed(&mut self, message: &UiMessage, engine: &mut GameEngine) {
+        if let Some(InspectorMessage::PropertyChanged(args)) = message.data::<InspectorMessage>() {
+            let node = match self.graph.nodes.try_borrow(self.inspected_node) {
+                Some(node) => node,
+                None => return,
+            };
+
+            let current = node.settings.clone();
+            let settings = match args.value {
+                FieldKind::Object(ref value) => match args.name.as_ref() {
+                    ShaderNodeSettings::NAME => value
+                        .cast_clone()
+                        .map(|name| ShaderNodeSettings { name, ..current }),
+                    ShaderNodeSettings::VALUE_TYPE => {
+                        value.cast_clone().map(|value_type| ShaderNodeSettings {
+                            value_type,
+                            ..current
+                        })
+                    }
+                    ShaderNodeSettings::VALUE => value
+                        .cast_clone()
+                        .map(|value| ShaderNodeSettings { value, ..current }),
+                    ShaderNodeSettings::FALLBACK => {
+                        value.cast_clone().map(|fallback| ShaderNodeSettings {
+                            fallback,
+                            ..current
+                        })
+                    }
+                    _ => None,
+                },
+                _ => None,
+            };
+
+            if let Some(settings) = settings {
+                self.do_command(
+                    SetNodeSettingsCommand {
+                        handle: self.inspected_node,
+                        settings,
+                    },
+                    engine,
+                );
+            } else {
+                Log::err(format!("Failed to handle a property {}", args.path()))
+            }
+        }
+    }
+
+    fn handle_canvas_message(&mut self, message: &UiMessage, engine: &mut GameEngine) {
+        let ui = &engine.user_interface;
+
+        if let Some(msg) = message.data::<AbsmCanvasMessage>() {
+            match msg {
+                AbsmCanvasMessage::CommitDrag { entries } => {
+                    let commands = entries
+                        .iter()
+                        .map(|e| {
+                            ShaderGraphCommand::new(MoveNodeCommand::new(
+                                fetch_model_handle(e.node, ui),
+                                e.initial_position,
+                                ui.node(e.node).actual_local_position(),
+                            ))
+                        })
+                        .collect::<Vec<_>>();
+
+                    self.do_command(CommandGroup::from(commands), engine);
+                }
+                AbsmCanvasMessage::SelectionChanged(selection) => {
+                    if message.direction() == MessageDirection::FromWidget {
+                        let selection = selection
+                            .iter()
+                            .filter_map(|n| {
+                                ui.node(*n)
+                                    .query_component::<AbsmNode<ShaderGraphNode>>()
+                                    .map(|node| node.model_handle)
+                            })
+                            .collect::<Vec<_>>();
+
+                        if !selection.is_empty() && selection != self.selection {
+                            self.do_command(ChangeSelectionCommand { selection }, engine);
+                        }
+                    }
+                }
+                AbsmCanvasMessage::CommitConnection {
+                    source_socket,
+                    dest_socket,
+                } => {
+                    let source_ref = ui.node(*source_socket).query_component::<Socket>().unwrap();
+                    let dest_ref = ui.node(*dest_socket).query_component::<Socket>().unwrap();
+
+                    let command = SetInputCommand {
+                        handle: dest_ref.parent_node.into(),
+                        index: dest_ref.index,
+                        source: source_ref.parent_node.into(),
+                    };
+
+                    if self
+                        .graph
+                        .would_create_cycle(command.source, command.handle)
+                    {
+                        Log::warn(
+                            "Connection was rejected, because it would create a cycle.".to_owned(),
+                        );
+                    } else {
+                        self.do_command(command, engine);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn handle_context_menus(&mut self, message: &UiMessage, engine: &mut GameEngine) {
+        let ui = &engine.user_interface;
+
+        if let Some(MenuItemMessage::Click) = message.data() {
+            if let Some(kind) = self
+                .canvas_context_menu
+                .items
+                .iter()
+                .find(|(item, _)| *item == message.destination())
+                .map(|(_, kind)| *kind)
+            {
+                let position = ui
+                    .node(self.canvas)
+                    .screen_to_local(ui.node(self.canvas_context_menu.menu).screen_position());
+
+                self.do_command(
+                    AddNodeCommand::new(ShaderGraphNode::new(kind, position)),
+                    engine,
+                );
+            } else if message.destination() == self.node_context_menu.remove {
+                let mut group = vec![ShaderGraphCommand::new(ChangeSelectionCommand {
+                    selection: vec![],
+                })];
+
+                // The output node is a part of every graph and can't be removed.
+                group.extend(
+                    self.selection
+                        .iter()
+                        .filter(|h| **h != self.graph.output)
+                        .map(|h| ShaderGraphCommand::new(DeleteNodeCommand::new(*h))),
+                );
+
+                self.do_command(CommandGroup::from(group), engine);
+            } else if message.destination() == self.connection_context_menu.remove {
+                let connection_ref = ui
+                    .node(self.connection_context_menu.placement_target)
+                    .query_component::<Connection>()
+                    .unwrap();
+
+                let dest_socket = ui
+                    .node(connection_ref.segment.dest)
+                    .query_component::<Socket>()
+                    .unwrap();
+
+                let command = SetInputCommand {
+                    handle: dest_socket.parent_node.into(),
+                    index: dest_socket.index,
+                    source: Handle::NONE,
+                };
+
+                self.do_command(command, engine);
+            }
+        } else if let Some(PopupMessage::Placement(Placement::Cursor(target))) = message.data() {
+            if message.destination() == self.connection_context_menu.menu {
+                self.connection_context_menu.placement_target = *target;
+            }
+        }
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, engine: &mut GameEngine) {
+        self.preview.handle_message(message, engine);
+
+        if message.destination() == self.canvas {
+            self.handle_canvas_message(message, engine);
+        } else if message.destination() == self.inspector
+            && message.direction() == MessageDirection::FromWidget
+        {
+            self.handle_settings_changed(message, engine);
+        }
+
+        self.handle_context_menus(message, engine);
+
+        if let Some(MenuItemMessage::Click) = message.data() {
+            let ui = &engine.user_interface;
+            if message.destination() == self.menu.new {
+                self.set_graph(ShaderGraph::default(), Default::default(), engine);
+            } else if message.destination() == self.menu.open {
+                open_file_selector(self.open_file_selector, ui);
+            } else if message.destination() == self.menu.save {
+                if self.path == PathBuf::default() {
+                    open_file_selector(self.save_file_selector, ui);
+                } else {
+                    let path = self.path.clone();
+                    self.save(&path, ui);
+                }
+            } else if message.destination() == self.menu.save_as {
+                open_file_selector(self.save_file_selector, ui);
+            } else if message.destination() == self.menu.export {
+                open_file_selector(self.export_file_selector, ui);
+            } else if message.destination() == self.menu.undo {
+                self.command_stack.undo(ShaderGraphEditorContext {
+                    graph: &mut self.graph,
+                    selection: &mut self.selection,
+                });
+                self.sync_to_model(engine);
+            } else if message.destination() == self.menu.redo {
+                self.command_stack.redo(ShaderGraphEditorContext {
+                    graph: &mut self.graph,
+                    selection: &mut self.selection,
+                });
+                self.sync_to_model(engine);
+            }
+        } else if let Some(FileSelectorMessage::Commit(path)) = message.data() {
+            if message.destination() == self.open_file_selector {
+                self.load(path, engine);
+            } else if message.destination() == self.save_file_selector {
+                self.save(path, &engine.user_interface);
+            } else if message.destination() == self.export_file_selector {
+                self.export(path, engine);
+            }
+        }
+    }
+
+    pub fn update(&mut self, engine: &mut GameEngine) {
+        self.preview.update(engine);
+    }
+}
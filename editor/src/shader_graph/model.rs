@@ -0,0 +1,361 @@
+use fyrox::{
+    core::{
+        algebra::{Vector2, Vector4},
+        inspect::{Inspect, PropertyInfo},
+        pool::{Handle, Pool},
+        visitor::prelude::*,
+    },
+    fxhash::FxHashSet,
+    material::shader::SamplerFallback,
+};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Type of a value flowing through the graph, it maps directly to a GLSL type.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Hash, Visit, Inspect, AsRefStr, EnumString, EnumVariantNames,
+)]
+pub enum ValueType {
+    Float,
+    Vector2,
+    Vector3,
+    Vector4,
+}
+
+impl Default for ValueType {
+    fn default() -> Self {
+        Self::Float
+    }
+}
+
+impl ValueType {
+    pub fn from_size(size: usize) -> Option<Self> {
+        match size {
+            1 => Some(Self::Float),
+            2 => Some(Self::Vector2),
+            3 => Some(Self::Vector3),
+            4 => Some(Self::Vector4),
+            _ => None,
+        }
+    }
+
+    pub fn size(self) -> usize {
+        match self {
+            Self::Float => 1,
+            Self::Vector2 => 2,
+            Self::Vector3 => 3,
+            Self::Vector4 => 4,
+        }
+    }
+
+    pub fn glsl_name(self) -> &'static str {
+        match self {
+            Self::Float => "float",
+            Self::Vector2 => "vec2",
+            Self::Vector3 => "vec3",
+            Self::Vector4 => "vec4",
+        }
+    }
+}
+
+/// Describes an input socket of a node. Inputs with a default value could be left unconnected,
+/// the rest must be connected for the graph to compile.
+#[derive(Copy, Clone, Debug)]
+pub struct InputDefinition {
+    pub name: &'static str,
+    pub default: Option<(ValueType, &'static str)>,
+}
+
+const fn input(name: &'static str) -> InputDefinition {
+    InputDefinition {
+        name,
+        default: None,
+    }
+}
+
+const fn input_or(
+    name: &'static str,
+    value_type: ValueType,
+    default: &'static str,
+) -> InputDefinition {
+    InputDefinition {
+        name,
+        default: Some((value_type, default)),
+    }
+}
+
+const OUTPUT_INPUTS: [InputDefinition; 6] = [
+    input_or("Albedo", ValueType::Vector3, "vec3(1.0)"),
+    input_or("Normal", ValueType::Vector3, "vec3(0.0, 0.0, 1.0)"),
+    input_or("Metallic", ValueType::Float, "0.0"),
+    input_or("Roughness", ValueType::Float, "1.0"),
+    input_or("Emission", ValueType::Vector3, "vec3(0.0)"),
+    input_or("Opacity", ValueType::Float, "1.0"),
+];
+const TEXTURE_SAMPLE_INPUTS: [InputDefinition; 1] =
+    [input_or("UV", ValueType::Vector2, "texCoord")];
+const TILING_OFFSET_INPUTS: [InputDefinition; 3] = [
+    input_or("UV", ValueType::Vector2, "texCoord"),
+    input_or("Tiling", ValueType::Vector2, "vec2(1.0)"),
+    input_or("Offset", ValueType::Vector2, "vec2(0.0)"),
+];
+const BINARY_INPUTS: [InputDefinition; 2] = [input("A"), input("B")];
+const LERP_INPUTS: [InputDefinition; 3] = [input("A"), input("B"), input("T")];
+const CLAMP_INPUTS: [InputDefinition; 3] = [
+    input("Value"),
+    input_or("Min", ValueType::Float, "0.0"),
+    input_or("Max", ValueType::Float, "1.0"),
+];
+const SINGLE_INPUT: [InputDefinition; 1] = [input("Value")];
+
+/// Kind of a node, it defines the inputs of a node and the code it produces.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Visit)]
+pub enum ShaderNodeKind {
+    /// Final node of the graph, its inputs match the inputs of the standard material.
+    Output,
+    /// First texture coordinates of a mesh.
+    TexCoord,
+    /// Samples a texture that is exposed as a material property.
+    TextureSample,
+    /// Scales and shifts texture coordinates.
+    TilingOffset,
+    /// A constant value that is baked into the shader.
+    Constant,
+    /// A value that is exposed as a material property.
+    Parameter,
+    /// An sRGB color that is exposed as a material property.
+    ColorParameter,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Min,
+    Max,
+    Power,
+    Lerp,
+    Clamp,
+    /// Unpacks a tangent-space normal from a normal map sample.
+    NormalUnpack,
+    /// Picks components of a vector, for example `xy` or `rgb`.
+    Swizzle,
+}
+
+impl Default for ShaderNodeKind {
+    fn default() -> Self {
+        Self::Constant
+    }
+}
+
+impl ShaderNodeKind {
+    /// Every kind of node that could be created by a user, the output node is created with the
+    /// graph and can't be added or removed.
+    pub const CREATABLE: [ShaderNodeKind; 17] = [
+        Self::TexCoord,
+        Self::TextureSample,
+        Self::TilingOffset,
+        Self::Constant,
+        Self::Parameter,
+        Self::ColorParameter,
+        Self::Add,
+        Self::Subtract,
+        Self::Multiply,
+        Self::Divide,
+        Self::Min,
+        Self::Max,
+        Self::Power,
+        Self::Lerp,
+        Self::Clamp,
+        Self::NormalUnpack,
+        Self::Swizzle,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Output => "Material Output",
+            Self::TexCoord => "Texture Coordinates",
+            Self::TextureSample => "Texture Sample",
+            Self::TilingOffset => "Tiling And Offset",
+            Self::Constant => "Constant",
+            Self::Parameter => "Parameter",
+            Self::ColorParameter => "Color Parameter",
+            Self::Add => "Add",
+            Self::Subtract => "Subtract",
+            Self::Multiply => "Multiply",
+            Self::Divide => "Divide",
+            Self::Min => "Min",
+            Self::Max => "Max",
+            Self::Power => "Power",
+            Self::Lerp => "Lerp",
+            Self::Clamp => "Clamp",
+            Self::NormalUnpack => "Normal Unpack",
+            Self::Swizzle => "Swizzle",
+        }
+    }
+
+    pub fn inputs(self) -> &'static [InputDefinition] {
+        match self {
+            Self::Output => &OUTPUT_INPUTS,
+            Self::TexCoord | Self::Constant | Self::Parameter | Self::ColorParameter => &[],
+            Self::TextureSample => &TEXTURE_SAMPLE_INPUTS,
+            Self::TilingOffset => &TILING_OFFSET_INPUTS,
+            Self::Add
+            | Self::Subtract
+            | Self::Multiply
+            | Self::Divide
+            | Self::Min
+            | Self::Max
+            | Self::Power => &BINARY_INPUTS,
+            Self::Lerp => &LERP_INPUTS,
+            Self::Clamp => &CLAMP_INPUTS,
+            Self::NormalUnpack | Self::Swizzle => &SINGLE_INPUT,
+        }
+    }
+
+    pub fn has_output(self) -> bool {
+        self != Self::Output
+    }
+}
+
+/// Editable settings of a node, not every kind of node uses every setting.
+#[derive(Clone, Debug, Default, PartialEq, Visit, Inspect)]
+pub struct ShaderNodeSettings {
+    #[inspect(
+        description = "Name of the material property for parameters and textures, or a swizzle mask."
+    )]
+    pub name: String,
+    #[inspect(description = "Type of a constant or a parameter.")]
+    pub value_type: ValueType,
+    #[inspect(description = "Value of a constant or default value of a parameter.")]
+    pub value: Vector4<f32>,
+    #[inspect(description = "Value that is sampled when a texture is not set.")]
+    pub fallback: SamplerFallback,
+}
+
+impl ShaderNodeSettings {
+    fn for_kind(kind: ShaderNodeKind) -> Self {
+        let mut settings = Self::default();
+        match kind {
+            ShaderNodeKind::TextureSample => settings.name = "texture".to_owned(),
+            ShaderNodeKind::Parameter => settings.name = "parameter".to_owned(),
+            ShaderNodeKind::ColorParameter => {
+                settings.name = "color".to_owned();
+                settings.value = Vector4::new(1.0, 1.0, 1.0, 1.0);
+            }
+            ShaderNodeKind::Swizzle => settings.name = "xyz".to_owned(),
+            _ => (),
+        }
+        settings
+    }
+}
+
+#[derive(Clone, Debug, Default, Visit)]
+pub struct ShaderGraphNode {
+    pub position: Vector2<f32>,
+    pub kind: ShaderNodeKind,
+    pub settings: ShaderNodeSettings,
+    /// Sources of every input of the node, unconnected inputs have `Handle::NONE`.
+    pub inputs: Vec<Handle<ShaderGraphNode>>,
+}
+
+impl ShaderGraphNode {
+    pub fn new(kind: ShaderNodeKind, position: Vector2<f32>) -> Self {
+        Self {
+            position,
+            kind,
+            settings: ShaderNodeSettings::for_kind(kind),
+            inputs: vec![Handle::NONE; kind.inputs().len()],
+        }
+    }
+
+    /// Returns a short description of the node that is shown in the node's view.
+    pub fn description(&self) -> String {
+        match self.kind {
+            ShaderNodeKind::TextureSample
+            | ShaderNodeKind::Parameter
+            | ShaderNodeKind::ColorParameter
+            | ShaderNodeKind::Swizzle => self.settings.name.clone(),
+            ShaderNodeKind::Constant => {
+                let v = self.settings.value;
+                match self.settings.value_type {
+                    ValueType::Float => format!("{}", v.x),
+                    ValueType::Vector2 => format!("({}; {})", v.x, v.y),
+                    ValueType::Vector3 => format!("({}; {}; {})", v.x, v.y, v.z),
+                    ValueType::Vector4 => format!("({}; {}; {}; {})", v.x, v.y, v.z, v.w),
+                }
+            }
+            _ => Default::default(),
+        }
+    }
+}
+
+/// A graph of nodes that is compiled into a shader. It is saved as a separate asset, so it could
+/// be edited and compiled again later.
+#[derive(Clone, Debug, Visit)]
+pub struct ShaderGraph {
+    pub name: String,
+    pub nodes: Pool<ShaderGraphNode>,
+    pub output: Handle<ShaderGraphNode>,
+}
+
+impl Default for ShaderGraph {
+    fn default() -> Self {
+        let mut nodes = Pool::new();
+        let output = nodes.spawn(ShaderGraphNode::new(
+            ShaderNodeKind::Output,
+            Vector2::new(400.0, 100.0),
+        ));
+        Self {
+            name: "ShaderGraph".to_owned(),
+            nodes,
+            output,
+        }
+    }
+}
+
+impl ShaderGraph {
+    /// Checks whether the `node` directly or indirectly uses the output of the `dependency`.
+    pub fn depends_on(
+        &self,
+        node: Handle<ShaderGraphNode>,
+        dependency: Handle<ShaderGraphNode>,
+    ) -> bool {
+        let mut visited = FxHashSet::default();
+        let mut stack = vec![node];
+        while let Some(handle) = stack.pop() {
+            if handle == dependency {
+                return true;
+            }
+            if visited.insert(handle) {
+                if let Some(node) = self.nodes.try_borrow(handle) {
+                    stack.extend(node.inputs.iter().filter(|h| h.is_some()));
+                }
+            }
+        }
+        false
+    }
+
+    /// Checks whether connecting the output of the `source` to an input of the `dest` would form
+    /// a cycle.
+    pub fn would_create_cycle(
+        &self,
+        source: Handle<ShaderGraphNode>,
+        dest: Handle<ShaderGraphNode>,
+    ) -> bool {
+        self.depends_on(source, dest)
+    }
+
+    /// Returns a node that is a part of a cycle, if any. Cycles can't be created in the editor,
+    /// but a graph could be modified outside of it.
+    pub fn find_cycle(&self) -> Option<Handle<ShaderGraphNode>> {
+        self.nodes.pair_iter().find_map(|(handle, node)| {
+            if node
+                .inputs
+                .iter()
+                .any(|input| input.is_some() && self.depends_on(*input, handle))
+            {
+                Some(handle)
+            } else {
+                None
+            }
+        })
+    }
+}
@@ -0,0 +1,522 @@
+// Template of a shader produced by the shader graph editor. Vertex shaders are the same as in the
+// standard shader, fragment shaders are filled with the code generated from a graph.
+(
+    name: $NAME,
+
+    properties: [
+        (
+            name: "layerIndex",
+            kind: UInt(0),
+        ),
+        (
+            name: "vegetation",
+            kind: Bool(false),
+        ),
+$PROPERTIES
+    ],
+
+    passes: [
+        (
+            name: "GBuffer",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+                r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 2) in vec3 vertexNormal;
+                layout(location = 3) in vec4 vertexTangent;
+                layout(location = 4) in vec4 boneWeights;
+                layout(location = 5) in vec4 boneIndices;
+                layout(location = 6) in vec2 vertexSecondTexCoord;
+                layout(location = 11) in vec4 vertexColor;
+
+                // Define uniforms with reserved names. Fyrox will automatically provide
+                // required data to these uniforms.
+                uniform mat4 fyrox_worldMatrix;
+                uniform mat4 fyrox_worldViewProjection;
+                #ifndef FYROX_BONE_MATRICES_TEXTURE
+                uniform mat4 fyrox_boneMatrices[60];
+                #endif
+                uniform bool fyrox_useSkeletalAnimation;
+
+                // Properties.
+                uniform bool vegetation;
+
+                out vec3 position;
+                out vec3 normal;
+                out vec2 texCoord;
+                out vec3 tangent;
+                out vec3 binormal;
+                out vec2 secondTexCoord;
+                out vec4 clipPosition;
+                out vec4 prevClipPosition;
+
+                void main()
+                {
+                    vec4 localPosition = vec4(0);
+                    vec4 prevLocalPosition = vec4(0);
+                    vec3 localNormal = vec3(0);
+                    vec3 localTangent = vec3(0);
+
+                    if (fyrox_useSkeletalAnimation)
+                    {
+                        vec4 vertex = vec4(vertexPosition, 1.0);
+
+                        int i0 = int(boneIndices.x);
+                        int i1 = int(boneIndices.y);
+                        int i2 = int(boneIndices.z);
+                        int i3 = int(boneIndices.w);
+
+                        mat4 m0 = S_FetchBoneMatrix(i0);
+                        mat4 m1 = S_FetchBoneMatrix(i1);
+                        mat4 m2 = S_FetchBoneMatrix(i2);
+                        mat4 m3 = S_FetchBoneMatrix(i3);
+
+                        localPosition += m0 * vertex * boneWeights.x;
+                        localPosition += m1 * vertex * boneWeights.y;
+                        localPosition += m2 * vertex * boneWeights.z;
+                        localPosition += m3 * vertex * boneWeights.w;
+
+                        // Previous pose is needed for motion vectors.
+                        prevLocalPosition += S_FetchPrevBoneMatrix(i0) * vertex * boneWeights.x;
+                        prevLocalPosition += S_FetchPrevBoneMatrix(i1) * vertex * boneWeights.y;
+                        prevLocalPosition += S_FetchPrevBoneMatrix(i2) * vertex * boneWeights.z;
+                        prevLocalPosition += S_FetchPrevBoneMatrix(i3) * vertex * boneWeights.w;
+
+                        localNormal += mat3(m0) * vertexNormal * boneWeights.x;
+                        localNormal += mat3(m1) * vertexNormal * boneWeights.y;
+                        localNormal += mat3(m2) * vertexNormal * boneWeights.z;
+                        localNormal += mat3(m3) * vertexNormal * boneWeights.w;
+
+                        localTangent += mat3(m0) * vertexTangent.xyz * boneWeights.x;
+                        localTangent += mat3(m1) * vertexTangent.xyz * boneWeights.y;
+                        localTangent += mat3(m2) * vertexTangent.xyz * boneWeights.z;
+                        localTangent += mat3(m3) * vertexTangent.xyz * boneWeights.w;
+                    }
+                    else
+                    {
+                        localPosition = vec4(vertexPosition, 1.0);
+                        prevLocalPosition = localPosition;
+                        localNormal = vertexNormal;
+                        localTangent = vertexTangent.xyz;
+                    }
+
+                    if (vegetation)
+                    {
+                        // Previous position is swayed by the same offset, so sway has no motion vectors.
+                        vec4 swayed = S_WindSwayLocal(localPosition, fyrox_worldMatrix, vertexColor.r);
+                        prevLocalPosition += swayed - localPosition;
+                        localPosition = swayed;
+                    }
+
+                    mat3 nm = mat3(fyrox_worldMatrix);
+                    normal = normalize(nm * localNormal);
+                    tangent = normalize(nm * localTangent);
+                    binormal = normalize(vertexTangent.w * cross(tangent, normal));
+                    texCoord = vertexTexCoord;
+                    position = vec3(fyrox_worldMatrix * localPosition);
+                    secondTexCoord = vertexSecondTexCoord;
+
+                    gl_Position = fyrox_worldViewProjection * localPosition;
+                    clipPosition = gl_Position;
+                    prevClipPosition = fyrox_prevWorldViewProjection * prevLocalPosition;
+                }
+                "#,
+            fragment_shader:
+                r#"
+                layout(location = 0) out vec4 outColor;
+                layout(location = 1) out vec4 outNormal;
+                layout(location = 2) out vec4 outAmbient;
+                layout(location = 3) out vec4 outMaterial;
+                layout(location = 4) out uint outDecalMask;
+                layout(location = 5) out vec2 outMotionVector;
+
+                uniform uint layerIndex;
+
+                // Properties.
+$UNIFORMS
+                in vec3 position;
+                in vec3 normal;
+                in vec2 texCoord;
+                in vec3 tangent;
+                in vec3 binormal;
+                in vec2 secondTexCoord;
+                in vec4 clipPosition;
+                in vec4 prevClipPosition;
+
+                void main()
+                {
+$BODY
+                    if (sg_opacity < 0.5) discard;
+
+                    mat3 tangentSpace = mat3(tangent, binormal, normal);
+
+                    outColor = vec4(sg_albedo, 1.0);
+                    outNormal = vec4(normalize(tangentSpace * sg_normal) * 0.5 + 0.5, 1.0);
+                    outMaterial = vec4(sg_metallic, sg_roughness, 1.0, 1.0);
+                    outAmbient = vec4(sg_emission, 1.0);
+                    outDecalMask = layerIndex;
+                    outMotionVector = S_ComputeMotionVector(clipPosition, prevClipPosition);
+                }
+                "#,
+        ),
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: Some(BlendFunc(
+                    sfactor: SrcAlpha,
+                    dfactor: OneMinusSrcAlpha,
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+                r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 5) in vec4 boneWeights;
+                layout(location = 6) in vec4 boneIndices;
+                layout(location = 11) in vec4 vertexColor;
+
+                uniform mat4 fyrox_worldViewProjection;
+                uniform bool fyrox_useSkeletalAnimation;
+                #ifndef FYROX_BONE_MATRICES_TEXTURE
+                uniform mat4 fyrox_boneMatrices[60];
+                #endif
+
+                uniform mat4 fyrox_worldMatrix;
+
+                // Properties.
+                uniform bool vegetation;
+
+                out vec3 position;
+                out vec2 texCoord;
+
+                void main()
+                {
+                    vec4 localPosition = vec4(0);
+                    if (fyrox_useSkeletalAnimation)
+                    {
+                        vec4 vertex = vec4(vertexPosition, 1.0);
+
+                        int i0 = int(boneIndices.x);
+                        int i1 = int(boneIndices.y);
+                        int i2 = int(boneIndices.z);
+                        int i3 = int(boneIndices.w);
+
+                        localPosition += S_FetchBoneMatrix(i0) * vertex * boneWeights.x;
+                        localPosition += S_FetchBoneMatrix(i1) * vertex * boneWeights.y;
+                        localPosition += S_FetchBoneMatrix(i2) * vertex * boneWeights.z;
+                        localPosition += S_FetchBoneMatrix(i3) * vertex * boneWeights.w;
+                    }
+                    else
+                    {
+                        localPosition = vec4(vertexPosition, 1.0);
+                    }
+                    if (vegetation)
+                    {
+                        localPosition = S_WindSwayLocal(localPosition, fyrox_worldMatrix, vertexColor.r);
+                    }
+                    gl_Position = fyrox_worldViewProjection * localPosition;
+                    texCoord = vertexTexCoord;
+                }
+                "#,
+            fragment_shader:
+                r#"
+                // Properties.
+$UNIFORMS
+                out vec4 FragColor;
+
+                in vec2 texCoord;
+
+                void main()
+                {
+$BODY
+                    FragColor = vec4(sg_albedo + sg_emission, sg_opacity);
+                }
+                "#,
+        ),
+        (
+            name: "DirectionalShadow",
+            draw_parameters: DrawParameters (
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: false,
+                    green: false,
+                    blue: false,
+                    alpha: false,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+                r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 4) in vec4 boneWeights;
+                layout(location = 5) in vec4 boneIndices;
+                layout(location = 11) in vec4 vertexColor;
+
+                uniform mat4 fyrox_worldViewProjection;
+                uniform bool fyrox_useSkeletalAnimation;
+                #ifndef FYROX_BONE_MATRICES_TEXTURE
+                uniform mat4 fyrox_boneMatrices[60];
+                #endif
+
+                uniform mat4 fyrox_worldMatrix;
+
+                // Properties.
+                uniform bool vegetation;
+
+                out vec2 texCoord;
+
+                void main()
+                {
+                    vec4 localPosition = vec4(0);
+
+                    if (fyrox_useSkeletalAnimation)
+                    {
+                        vec4 vertex = vec4(vertexPosition, 1.0);
+
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.x)) * vertex * boneWeights.x;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.y)) * vertex * boneWeights.y;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.z)) * vertex * boneWeights.z;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.w)) * vertex * boneWeights.w;
+                    }
+                    else
+                    {
+                        localPosition = vec4(vertexPosition, 1.0);
+                    }
+                    if (vegetation)
+                    {
+                        localPosition = S_WindSwayLocal(localPosition, fyrox_worldMatrix, vertexColor.r);
+                    }
+
+                    gl_Position = fyrox_worldViewProjection * localPosition;
+                    texCoord = vertexTexCoord;
+                }
+                "#,
+            fragment_shader:
+                r#"
+                // Properties.
+$UNIFORMS
+                in vec2 texCoord;
+
+                void main()
+                {
+$BODY
+                    if (sg_opacity < 0.2) discard;
+                }
+                "#,
+        ),
+        (
+            name: "SpotShadow",
+            draw_parameters: DrawParameters (
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: false,
+                    green: false,
+                    blue: false,
+                    alpha: false,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+                r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 4) in vec4 boneWeights;
+                layout(location = 5) in vec4 boneIndices;
+                layout(location = 11) in vec4 vertexColor;
+
+                uniform mat4 fyrox_worldViewProjection;
+                uniform bool fyrox_useSkeletalAnimation;
+                #ifndef FYROX_BONE_MATRICES_TEXTURE
+                uniform mat4 fyrox_boneMatrices[60];
+                #endif
+
+                uniform mat4 fyrox_worldMatrix;
+
+                // Properties.
+                uniform bool vegetation;
+
+                out vec2 texCoord;
+
+                void main()
+                {
+                    vec4 localPosition = vec4(0);
+
+                    if (fyrox_useSkeletalAnimation)
+                    {
+                        vec4 vertex = vec4(vertexPosition, 1.0);
+
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.x)) * vertex * boneWeights.x;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.y)) * vertex * boneWeights.y;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.z)) * vertex * boneWeights.z;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.w)) * vertex * boneWeights.w;
+                    }
+                    else
+                    {
+                        localPosition = vec4(vertexPosition, 1.0);
+                    }
+                    if (vegetation)
+                    {
+                        localPosition = S_WindSwayLocal(localPosition, fyrox_worldMatrix, vertexColor.r);
+                    }
+
+                    gl_Position = fyrox_worldViewProjection * localPosition;
+                    texCoord = vertexTexCoord;
+                }
+                "#,
+            fragment_shader:
+                r#"
+                // Properties.
+$UNIFORMS
+                in vec2 texCoord;
+
+                void main()
+                {
+$BODY
+                    if (sg_opacity < 0.2) discard;
+                }
+                "#,
+        ),
+        (
+            name: "PointShadow",
+            draw_parameters: DrawParameters (
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+                r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 4) in vec4 boneWeights;
+                layout(location = 5) in vec4 boneIndices;
+                layout(location = 11) in vec4 vertexColor;
+
+                uniform mat4 fyrox_worldMatrix;
+                uniform mat4 fyrox_worldViewProjection;
+                uniform bool fyrox_useSkeletalAnimation;
+                #ifndef FYROX_BONE_MATRICES_TEXTURE
+                uniform mat4 fyrox_boneMatrices[60];
+                #endif
+
+                // Properties.
+                uniform bool vegetation;
+
+                out vec2 texCoord;
+                out vec3 worldPosition;
+
+                void main()
+                {
+                    vec4 localPosition = vec4(0);
+
+                    if (fyrox_useSkeletalAnimation)
+                    {
+                        vec4 vertex = vec4(vertexPosition, 1.0);
+
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.x)) * vertex * boneWeights.x;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.y)) * vertex * boneWeights.y;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.z)) * vertex * boneWeights.z;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.w)) * vertex * boneWeights.w;
+                    }
+                    else
+                    {
+                        localPosition = vec4(vertexPosition, 1.0);
+                    }
+                    if (vegetation)
+                    {
+                        localPosition = S_WindSwayLocal(localPosition, fyrox_worldMatrix, vertexColor.r);
+                    }
+
+                    gl_Position = fyrox_worldViewProjection * localPosition;
+                    worldPosition = (fyrox_worldMatrix * localPosition).xyz;
+                    texCoord = vertexTexCoord;
+                }
+                "#,
+            fragment_shader:
+                r#"
+                // Properties.
+$UNIFORMS
+                uniform vec3 fyrox_lightPosition;
+
+                in vec2 texCoord;
+                in vec3 worldPosition;
+
+                layout(location = 0) out float depth;
+
+                void main()
+                {
+$BODY
+                    if (sg_opacity < 0.2) discard;
+                    depth = length(fyrox_lightPosition - worldPosition);
+                }
+                "#,
+        ),
+    ],
+)
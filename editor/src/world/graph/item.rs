@@ -1,15 +1,15 @@
 use fyrox::{
-    core::{algebra::Vector2, pool::Handle},
+    core::{algebra::Vector2, color::Color, pool::Handle},
     gui::{
         brush::Brush,
         define_constructor,
         draw::{DrawingContext, SharedTexture},
         grid::{Column, GridBuilder, Row},
-        image::ImageBuilder,
+        image::{ImageBuilder, ImageMessage},
         message::{MessageDirection, OsEvent, UiMessage},
         text::{TextBuilder, TextMessage},
         tree::{Tree, TreeBuilder},
-        widget::{Widget, WidgetBuilder},
+        widget::{Widget, WidgetBuilder, WidgetMessage},
         BuildContext, Control, NodeHandleMapping, Thickness, UiNode, UserInterface,
         VerticalAlignment,
     },
@@ -24,16 +24,28 @@ use std::{
 #[derive(Debug, Clone, PartialEq)]
 pub enum SceneItemMessage {
     Name(String),
+    Icon(Option<SharedTexture>),
+    TextBrush(Brush),
+    /// A short text after the name, empty string hides the badge.
+    Badge(String),
 }
 
 impl SceneItemMessage {
     define_constructor!(SceneItemMessage:Name => fn name(String), layout: false);
+    define_constructor!(SceneItemMessage:Icon => fn icon(Option<SharedTexture>), layout: false);
+    define_constructor!(SceneItemMessage:TextBrush => fn text_brush(Brush), layout: false);
+    define_constructor!(SceneItemMessage:Badge => fn badge(String), layout: false);
 }
 
 pub struct SceneItem<T> {
     pub tree: Tree,
     text_name: Handle<UiNode>,
     name_value: String,
+    image: Handle<UiNode>,
+    icon: Option<SharedTexture>,
+    text_brush: Brush,
+    text_badge: Handle<UiNode>,
+    badge: String,
     pub entity_handle: Handle<T>,
 }
 
@@ -41,6 +53,18 @@ impl<T> SceneItem<T> {
     pub fn name(&self) -> &str {
         &self.name_value
     }
+
+    pub fn icon(&self) -> Option<&SharedTexture> {
+        self.icon.as_ref()
+    }
+
+    pub fn text_brush(&self) -> &Brush {
+        &self.text_brush
+    }
+
+    pub fn badge(&self) -> &str {
+        &self.badge
+    }
 }
 
 impl<T> Clone for SceneItem<T> {
@@ -49,6 +73,11 @@ impl<T> Clone for SceneItem<T> {
             tree: self.tree.clone(),
             text_name: self.text_name,
             name_value: self.name_value.clone(),
+            image: self.image,
+            icon: self.icon.clone(),
+            text_brush: self.text_brush.clone(),
+            text_badge: self.text_badge,
+            badge: self.badge.clone(),
             entity_handle: self.entity_handle,
         }
     }
@@ -88,6 +117,8 @@ impl<T: 'static> Control for SceneItem<T> {
     fn resolve(&mut self, node_map: &NodeHandleMapping) {
         self.tree.resolve(node_map);
         node_map.resolve(&mut self.text_name);
+        node_map.resolve(&mut self.image);
+        node_map.resolve(&mut self.text_badge);
     }
 
     fn measure_override(&self, ui: &UserInterface, available_size: Vector2<f32>) -> Vector2<f32> {
@@ -109,20 +140,51 @@ impl<T: 'static> Control for SceneItem<T> {
     fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
         self.tree.handle_routed_message(ui, message);
 
-        if let Some(SceneItemMessage::Name(name)) = message.data::<SceneItemMessage>() {
+        if let Some(msg) = message.data::<SceneItemMessage>() {
             if message.destination() == self.handle() {
-                self.name_value = format!(
-                    "{} ({}:{})",
-                    name,
-                    self.entity_handle.index(),
-                    self.entity_handle.generation()
-                );
-
-                ui.send_message(TextMessage::text(
-                    self.text_name,
-                    MessageDirection::ToWidget,
-                    self.name_value.clone(),
-                ));
+                match msg {
+                    SceneItemMessage::Name(name) => {
+                        self.name_value = format!(
+                            "{} ({}:{})",
+                            name,
+                            self.entity_handle.index(),
+                            self.entity_handle.generation()
+                        );
+
+                        ui.send_message(TextMessage::text(
+                            self.text_name,
+                            MessageDirection::ToWidget,
+                            self.name_value.clone(),
+                        ));
+                    }
+                    SceneItemMessage::Icon(icon) => {
+                        self.icon = icon.clone();
+
+                        ui.send_message(ImageMessage::texture(
+                            self.image,
+                            MessageDirection::ToWidget,
+                            icon.clone(),
+                        ));
+                    }
+                    SceneItemMessage::TextBrush(brush) => {
+                        self.text_brush = brush.clone();
+
+                        ui.send_message(WidgetMessage::foreground(
+                            self.text_name,
+                            MessageDirection::ToWidget,
+                            brush.clone(),
+                        ));
+                    }
+                    SceneItemMessage::Badge(badge) => {
+                        self.badge = badge.clone();
+
+                        ui.send_message(TextMessage::text(
+                            self.text_badge,
+                            MessageDirection::ToWidget,
+                            badge.clone(),
+                        ));
+                    }
+                }
             }
         }
     }
@@ -147,6 +209,7 @@ pub struct SceneItemBuilder<T> {
     name: String,
     icon: Option<SharedTexture>,
     text_brush: Option<Brush>,
+    badge: String,
 }
 
 impl<T: 'static> SceneItemBuilder<T> {
@@ -157,6 +220,7 @@ impl<T: 'static> SceneItemBuilder<T> {
             name: Default::default(),
             icon: None,
             text_brush: None,
+            badge: Default::default(),
         }
     }
 
@@ -180,28 +244,36 @@ impl<T: 'static> SceneItemBuilder<T> {
         self
     }
 
+    pub fn with_badge(mut self, badge: String) -> Self {
+        self.badge = badge;
+        self
+    }
+
     pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let text_brush = self
+            .text_brush
+            .unwrap_or(Brush::Solid(fyrox::gui::COLOR_FOREGROUND));
+        let image;
         let text_name;
+        let text_badge;
         let content = GridBuilder::new(
             WidgetBuilder::new()
-                .with_child(
-                    ImageBuilder::new(
+                .with_child({
+                    image = ImageBuilder::new(
                         WidgetBuilder::new()
                             .with_width(16.0)
                             .with_height(16.0)
                             .on_column(0)
                             .with_margin(Thickness::uniform(1.0)),
                     )
-                    .with_opt_texture(self.icon)
-                    .build(ctx),
-                )
+                    .with_opt_texture(self.icon.clone())
+                    .build(ctx);
+                    image
+                })
                 .with_child({
                     text_name = TextBuilder::new(
                         WidgetBuilder::new()
-                            .with_foreground(
-                                self.text_brush
-                                    .unwrap_or(Brush::Solid(fyrox::gui::COLOR_FOREGROUND)),
-                            )
+                            .with_foreground(text_brush.clone())
                             .with_margin(Thickness::uniform(1.0))
                             .on_column(1)
                             .with_vertical_alignment(VerticalAlignment::Center),
@@ -214,10 +286,23 @@ impl<T: 'static> SceneItemBuilder<T> {
                     ))
                     .build(ctx);
                     text_name
+                })
+                .with_child({
+                    text_badge = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_foreground(Brush::Solid(Color::opaque(220, 180, 90)))
+                            .with_margin(Thickness::uniform(1.0))
+                            .on_column(2)
+                            .with_vertical_alignment(VerticalAlignment::Center),
+                    )
+                    .with_text(self.badge.clone())
+                    .build(ctx);
+                    text_badge
                 }),
         )
         .add_row(Row::stretch())
         .add_column(Column::auto())
+        .add_column(Column::auto())
         .add_column(Column::stretch())
         .build(ctx);
 
@@ -228,6 +313,11 @@ impl<T: 'static> SceneItemBuilder<T> {
             entity_handle: self.entity_handle,
             name_value: self.name,
             text_name,
+            image,
+            icon: self.icon,
+            text_brush,
+            text_badge,
+            badge: self.badge,
         };
 
         ctx.add_node(UiNode::new(item))
@@ -1,34 +1,119 @@
-use crate::menu::create::CreateEntityMenu;
 use crate::{
-    scene::{commands::make_delete_selection_command, EditorScene, Selection},
+    collection::{
+        make_add_selection_to_collection_command, make_remove_selection_from_collection_command,
+    },
+    menu::create::CreateEntityMenu,
+    scene::{
+        commands::{
+            graph::DuplicateNodeCommand, make_delete_selection_command,
+            make_remove_added_nodes_command, make_restore_suppressed_nodes_command,
+        },
+        editor_data::{make_modify_selection_editor_data_command, NodeIcon},
+        EditorScene, Selection,
+    },
     GameEngine, Message,
 };
 use fyrox::{
-    core::{algebra::Vector2, pool::Handle, scope_profile},
+    core::{algebra::Vector2, color::Color, pool::Handle, scope_profile},
+    engine::SerializationContext,
     gui::{
         menu::{MenuItemBuilder, MenuItemContent, MenuItemMessage},
-        message::UiMessage,
+        message::{MessageDirection, UiMessage},
         popup::PopupBuilder,
         stack_panel::StackPanelBuilder,
         widget::WidgetBuilder,
-        BuildContext, UiNode,
+        BuildContext, UiNode, UserInterface,
     },
+    scene::collection::NodeCollectionContainer,
+    utils::log::Log,
 };
-use std::sync::mpsc::Sender;
+use std::sync::{mpsc::Sender, Arc};
 
 pub struct ItemContextMenu {
     pub menu: Handle<UiNode>,
     delete_selection: Handle<UiNode>,
     copy_selection: Handle<UiNode>,
+    duplicate: Handle<UiNode>,
+    create_child: Handle<UiNode>,
+    generate_collider: Handle<UiNode>,
+    generate_hitboxes: Handle<UiNode>,
+    batch_rename: Handle<UiNode>,
+    simulate_selection: Handle<UiNode>,
+    drop_to_floor: Handle<UiNode>,
+    add_to_collection: Handle<UiNode>,
+    remove_from_collection: Handle<UiNode>,
+    restore_suppressed_nodes: Handle<UiNode>,
+    remove_added_nodes: Handle<UiNode>,
+    // Sub-items of the node color menu, the last item resets the color.
+    color_items: Vec<Handle<UiNode>>,
+    // Sub-items of the node icon menu, the last item resets the icon to the automatic one.
+    icon_items: Vec<Handle<UiNode>>,
+    // Sub-items of the collection menus, their order matches the order of the collections.
+    add_to_collection_items: Vec<Handle<UiNode>>,
+    remove_from_collection_items: Vec<Handle<UiNode>>,
+    collection_names: Vec<String>,
     create_entity_menu: CreateEntityMenu,
 }
 
+const NODE_COLORS: [(&str, Color); 6] = [
+    ("Red", Color::opaque(230, 80, 80)),
+    ("Orange", Color::opaque(240, 160, 60)),
+    ("Yellow", Color::opaque(230, 220, 80)),
+    ("Green", Color::opaque(100, 210, 100)),
+    ("Blue", Color::opaque(90, 150, 240)),
+    ("Purple", Color::opaque(180, 110, 230)),
+];
+
+fn make_items<'a>(
+    names: impl Iterator<Item = &'a str>,
+    ctx: &mut BuildContext,
+) -> Vec<Handle<UiNode>> {
+    names
+        .map(|name| {
+            MenuItemBuilder::new(WidgetBuilder::new().with_min_size(Vector2::new(120.0, 20.0)))
+                .with_content(MenuItemContent::text(name))
+                .build(ctx)
+        })
+        .collect()
+}
+
+fn make_collection_items(names: &[String], ctx: &mut BuildContext) -> Vec<Handle<UiNode>> {
+    make_items(names.iter().map(|name| name.as_str()), ctx)
+}
+
 impl ItemContextMenu {
-    pub fn new(ctx: &mut BuildContext) -> Self {
+    pub fn new(serialization_context: Arc<SerializationContext>, ctx: &mut BuildContext) -> Self {
         let delete_selection;
         let copy_selection;
+        let duplicate;
+        let create_child;
+        let generate_collider;
+        let generate_hitboxes;
+        let batch_rename;
+        let simulate_selection;
+        let drop_to_floor;
+        let add_to_collection;
+        let remove_from_collection;
+        let restore_suppressed_nodes;
+        let remove_added_nodes;
+
+        let (create_entity_menu, create_entity_menu_root_items) =
+            CreateEntityMenu::new(serialization_context, ctx);
 
-        let (create_entity_menu, create_entity_menu_root_items) = CreateEntityMenu::new(ctx);
+        let color_items = make_items(
+            NODE_COLORS
+                .iter()
+                .map(|(name, _)| *name)
+                .chain(std::iter::once("None")),
+            ctx,
+        );
+        let icon_items = make_items(
+            NodeIcon::ALL
+                .iter()
+                .map(|icon| icon.name())
+                .chain(std::iter::once("Auto")),
+            ctx,
+        );
 
         let menu = PopupBuilder::new(WidgetBuilder::new().with_visibility(false))
             .with_content(
@@ -60,12 +145,109 @@ impl ItemContextMenu {
                             .build(ctx);
                             copy_selection
                         })
-                        .with_child(
-                            MenuItemBuilder::new(
+                        .with_child({
+                            duplicate = MenuItemBuilder::new(
+                                WidgetBuilder::new().with_min_size(Vector2::new(120.0, 20.0)),
+                            )
+                            .with_content(MenuItemContent::text("Duplicate"))
+                            .build(ctx);
+                            duplicate
+                        })
+                        .with_child({
+                            create_child = MenuItemBuilder::new(
                                 WidgetBuilder::new().with_min_size(Vector2::new(120.0, 20.0)),
                             )
                             .with_content(MenuItemContent::text("Create Child"))
                             .with_items(create_entity_menu_root_items)
+                            .build(ctx);
+                            create_child
+                        })
+                        .with_child({
+                            generate_collider = MenuItemBuilder::new(
+                                WidgetBuilder::new().with_min_size(Vector2::new(120.0, 20.0)),
+                            )
+                            .with_content(MenuItemContent::text("Generate Collider..."))
+                            .build(ctx);
+                            generate_collider
+                        })
+                        .with_child({
+                            generate_hitboxes = MenuItemBuilder::new(
+                                WidgetBuilder::new().with_min_size(Vector2::new(120.0, 20.0)),
+                            )
+                            .with_content(MenuItemContent::text("Generate Hitboxes"))
+                            .build(ctx);
+                            generate_hitboxes
+                        })
+                        .with_child({
+                            batch_rename = MenuItemBuilder::new(
+                                WidgetBuilder::new().with_min_size(Vector2::new(120.0, 20.0)),
+                            )
+                            .with_content(MenuItemContent::text("Batch Rename..."))
+                            .build(ctx);
+                            batch_rename
+                        })
+                        .with_child({
+                            simulate_selection = MenuItemBuilder::new(
+                                WidgetBuilder::new().with_min_size(Vector2::new(120.0, 20.0)),
+                            )
+                            .with_content(MenuItemContent::text("Simulate Selection..."))
+                            .build(ctx);
+                            simulate_selection
+                        })
+                        .with_child({
+                            drop_to_floor = MenuItemBuilder::new(
+                                WidgetBuilder::new().with_min_size(Vector2::new(120.0, 20.0)),
+                            )
+                            .with_content(MenuItemContent::text("Drop To Floor"))
+                            .build(ctx);
+                            drop_to_floor
+                        })
+                        .with_child({
+                            add_to_collection = MenuItemBuilder::new(
+                                WidgetBuilder::new().with_min_size(Vector2::new(120.0, 20.0)),
+                            )
+                            .with_content(MenuItemContent::text("Add To Collection"))
+                            .build(ctx);
+                            add_to_collection
+                        })
+                        .with_child({
+                            remove_from_collection = MenuItemBuilder::new(
+                                WidgetBuilder::new().with_min_size(Vector2::new(120.0, 20.0)),
+                            )
+                            .with_content(MenuItemContent::text("Remove From Collection"))
+                            .build(ctx);
+                            remove_from_collection
+                        })
+                        .with_child({
+                            restore_suppressed_nodes = MenuItemBuilder::new(
+                                WidgetBuilder::new().with_min_size(Vector2::new(120.0, 20.0)),
+                            )
+                            .with_content(MenuItemContent::text("Restore Deleted Prefab Nodes"))
+                            .build(ctx);
+                            restore_suppressed_nodes
+                        })
+                        .with_child({
+                            remove_added_nodes = MenuItemBuilder::new(
+                                WidgetBuilder::new().with_min_size(Vector2::new(120.0, 20.0)),
+                            )
+                            .with_content(MenuItemContent::text("Remove Added Nodes"))
+                            .build(ctx);
+                            remove_added_nodes
+                        })
+                        .with_child(
+                            MenuItemBuilder::new(
+                                WidgetBuilder::new().with_min_size(Vector2::new(120.0, 20.0)),
+                            )
+                            .with_content(MenuItemContent::text("Node Color"))
+                            .with_items(color_items.clone())
+                            .build(ctx),
+                        )
+                        .with_child(
+                            MenuItemBuilder::new(
+                                WidgetBuilder::new().with_min_size(Vector2::new(120.0, 20.0)),
+                            )
+                            .with_content(MenuItemContent::text("Node Icon"))
+                            .with_items(icon_items.clone())
                             .build(ctx),
                         ),
                 )
@@ -78,9 +260,60 @@ impl ItemContextMenu {
             menu,
             delete_selection,
             copy_selection,
+            duplicate,
+            create_child,
+            generate_collider,
+            generate_hitboxes,
+            batch_rename,
+            simulate_selection,
+            drop_to_floor,
+            add_to_collection,
+            remove_from_collection,
+            restore_suppressed_nodes,
+            remove_added_nodes,
+            color_items,
+            icon_items,
+            add_to_collection_items: Default::default(),
+            remove_from_collection_items: Default::default(),
+            collection_names: Default::default(),
+        }
+    }
+
+    /// Re-creates sub-items of the collection menus if the set of collections has changed.
+    pub fn sync_collections(
+        &mut self,
+        collections: &NodeCollectionContainer,
+        ui: &mut UserInterface,
+    ) {
+        let names = collections
+            .iter()
+            .map(|c| c.name.clone())
+            .collect::<Vec<_>>();
+
+        if names != self.collection_names {
+            let ctx = &mut ui.build_ctx();
+            self.add_to_collection_items = make_collection_items(&names, ctx);
+            self.remove_from_collection_items = make_collection_items(&names, ctx);
+
+            ui.send_message(MenuItemMessage::items(
+                self.add_to_collection,
+                MessageDirection::ToWidget,
+                self.add_to_collection_items.clone(),
+            ));
+            ui.send_message(MenuItemMessage::items(
+                self.remove_from_collection,
+                MessageDirection::ToWidget,
+                self.remove_from_collection_items.clone(),
+            ));
+
+            self.collection_names = names;
         }
     }
 
+    pub fn on_node_constructors_changed(&mut self, ui: &mut UserInterface) {
+        self.create_entity_menu.rebuild(self.create_child, ui);
+    }
+
     pub fn handle_ui_message(
         &mut self,
         message: &UiMessage,
@@ -103,6 +336,7 @@ impl ItemContextMenu {
                     .send(Message::DoSceneCommand(make_delete_selection_command(
                         editor_scene,
                         engine,
+                        false,
                     )))
                     .unwrap();
             } else if message.destination() == self.copy_selection {
@@ -113,6 +347,82 @@ impl ItemContextMenu {
                         engine,
                     );
                 }
+            } else if message.destination() == self.duplicate {
+                if let Selection::Graph(graph_selection) = &editor_scene.selection {
+                    let graph = &engine.scenes[editor_scene.scene].graph;
+                    match graph_selection.root_nodes(graph).as_slice() {
+                        [root] if *root != graph.get_root() => sender
+                            .send(Message::do_scene_command(DuplicateNodeCommand::new(*root)))
+                            .unwrap(),
+                        _ => Log::warn("Select a single node to duplicate it!".to_owned()),
+                    }
+                }
+            } else if message.destination() == self.generate_collider {
+                sender.send(Message::OpenColliderGenerator).unwrap();
+            } else if message.destination() == self.generate_hitboxes {
+                sender.send(Message::GenerateHitboxes).unwrap();
+            } else if message.destination() == self.batch_rename {
+                sender.send(Message::OpenBatchRename).unwrap();
+            } else if message.destination() == self.simulate_selection {
+                sender.send(Message::OpenPlacementWindow).unwrap();
+            } else if message.destination() == self.drop_to_floor {
+                sender.send(Message::DropSelectionToFloor).unwrap();
+            } else if message.destination() == self.restore_suppressed_nodes {
+                if let Some(command) = make_restore_suppressed_nodes_command(editor_scene, engine) {
+                    sender.send(Message::DoSceneCommand(command)).unwrap();
+                }
+            } else if message.destination() == self.remove_added_nodes {
+                if let Some(command) = make_remove_added_nodes_command(editor_scene, engine) {
+                    sender.send(Message::DoSceneCommand(command)).unwrap();
+                }
+            } else if let Some(index) = self
+                .color_items
+                .iter()
+                .position(|&item| item == message.destination())
+            {
+                let color = NODE_COLORS.get(index).map(|(_, color)| *color);
+                if let Some(command) =
+                    make_modify_selection_editor_data_command(editor_scene, |data| {
+                        data.color = color
+                    })
+                {
+                    sender.send(Message::DoSceneCommand(command)).unwrap();
+                }
+            } else if let Some(index) = self
+                .icon_items
+                .iter()
+                .position(|&item| item == message.destination())
+            {
+                let icon = NodeIcon::ALL.get(index).cloned();
+                if let Some(command) =
+                    make_modify_selection_editor_data_command(editor_scene, |data| data.icon = icon)
+                {
+                    sender.send(Message::DoSceneCommand(command)).unwrap();
+                }
+            } else if let Some(index) = self
+                .add_to_collection_items
+                .iter()
+                .position(|&item| item == message.destination())
+            {
+                if let Some(command) = make_add_selection_to_collection_command(
+                    index,
+                    editor_scene,
+                    &engine.scenes[editor_scene.scene],
+                ) {
+                    sender.send(Message::DoSceneCommand(command)).unwrap();
+                }
+            } else if let Some(index) = self
+                .remove_from_collection_items
+                .iter()
+                .position(|&item| item == message.destination())
+            {
+                if let Some(command) = make_remove_selection_from_collection_command(
+                    index,
+                    editor_scene,
+                    &engine.scenes[editor_scene.scene],
+                ) {
+                    sender.send(Message::DoSceneCommand(command)).unwrap();
+                }
             }
         }
     }
@@ -1,8 +1,8 @@
 use crate::utils::window_content;
 use crate::{
-    load_image,
     scene::{
         commands::{graph::LinkNodesCommand, ChangeSelectionCommand},
+        editor_data::{NodeEditorData, NodeIcon},
         EditorScene, Selection,
     },
     send_sync_message,
@@ -22,12 +22,14 @@ use fyrox::{
         pool::{ErasedHandle, Handle},
         scope_profile,
     },
-    engine::Engine,
+    engine::{Engine, SerializationContext},
+    fxhash::FxHashSet,
     gui::{
         brush::Brush,
         button::{ButtonBuilder, ButtonMessage},
         check_box::{CheckBoxBuilder, CheckBoxMessage},
         decorator::{Decorator, DecoratorMessage},
+        draw::SharedTexture,
         grid::{Column, GridBuilder, Row},
         message::{MessageDirection, UiMessage},
         scroll_viewer::{ScrollViewerBuilder, ScrollViewerMessage},
@@ -44,7 +46,12 @@ use fyrox::{
     },
     scene::{graph::Graph, node::Node, Scene},
 };
-use std::{any::TypeId, cmp::Ordering, collections::HashMap, sync::mpsc::Sender};
+use std::{
+    any::TypeId,
+    cmp::Ordering,
+    collections::HashMap,
+    sync::{mpsc::Sender, Arc},
+};
 
 pub mod graph;
 pub mod search;
@@ -71,27 +78,64 @@ pub struct WorldViewer {
     scroll_view: Handle<UiNode>,
     item_context_menu: ItemContextMenu,
     node_to_view_map: HashMap<Handle<Node>, Handle<UiNode>>,
+    /// Nodes whose items must be expanded once they're created, see [`Self::expand_on_sync`].
+    pending_expansion: Vec<Handle<Node>>,
+}
+
+/// Returns an icon and a text brush of the item of a node. Editor data of the node overrides
+/// the automatic icon and the default color.
+fn node_item_style(
+    node: &Node,
+    editor_data: Option<&NodeEditorData>,
+) -> (Option<SharedTexture>, Brush) {
+    let icon = editor_data
+        .and_then(|data| data.icon)
+        .unwrap_or_else(|| NodeIcon::auto(node))
+        .texture();
+
+    let brush = if let Some(color) = editor_data.and_then(|data| data.color) {
+        Brush::Solid(color)
+    } else if node.resource().is_some() {
+        Brush::Solid(Color::opaque(160, 160, 200))
+    } else {
+        Brush::Solid(fyrox::gui::COLOR_FOREGROUND)
+    };
+
+    (icon, brush)
+}
+
+/// Returns handles of nodes that were added to prefab instances of the graph.
+fn find_added_nodes(graph: &Graph) -> FxHashSet<Handle<Node>> {
+    graph
+        .pair_iter()
+        .filter(|(_, node)| node.is_resource_instance_root())
+        .flat_map(|(handle, _)| graph.find_added_nodes(handle))
+        .collect()
+}
+
+/// Returns a badge of the item of a node, the badge shows structural overrides of prefab
+/// instances: whether the node was added to an instance or how many nodes of the instance
+/// were deleted.
+fn node_badge(node: &Node, handle: Handle<Node>, added_nodes: &FxHashSet<Handle<Node>>) -> String {
+    let mut badge = String::new();
+    if added_nodes.contains(&handle) {
+        badge += "+";
+    }
+    if node.is_resource_instance_root() && !node.suppressed_resource_nodes().is_empty() {
+        badge += &format!("-{}", node.suppressed_resource_nodes().len());
+    }
+    badge
 }
 
 fn make_graph_node_item(
     node: &Node,
     handle: Handle<Node>,
+    editor_data: Option<&NodeEditorData>,
+    badge: String,
     ctx: &mut BuildContext,
     context_menu: Handle<UiNode>,
 ) -> Handle<UiNode> {
-    let icon = if node.is_point_light() || node.is_directional_light() || node.is_spot_light() {
-        load_image(include_bytes!("../../resources/embed/light.png"))
-    } else if node.is_joint() || node.is_joint2d() {
-        load_image(include_bytes!("../../resources/embed/joint.png"))
-    } else if node.is_rigid_body() || node.is_rigid_body2d() {
-        load_image(include_bytes!("../../resources/embed/rigid_body.png"))
-    } else if node.is_collider() || node.is_collider2d() {
-        load_image(include_bytes!("../../resources/embed/collider.png"))
-    } else if node.is_sound() {
-        load_image(include_bytes!("../../resources/embed/sound_source.png"))
-    } else {
-        load_image(include_bytes!("../../resources/embed/cube.png"))
-    };
+    let (icon, brush) = node_item_style(node, editor_data);
 
     SceneItemBuilder::new(TreeBuilder::new(
         WidgetBuilder::new()
@@ -103,14 +147,11 @@ fn make_graph_node_item(
             })
             .with_context_menu(context_menu),
     ))
-    .with_text_brush(if node.resource().is_some() {
-        Brush::Solid(Color::opaque(160, 160, 200))
-    } else {
-        Brush::Solid(fyrox::gui::COLOR_FOREGROUND)
-    })
+    .with_text_brush(brush)
     .with_name(node.name().to_owned())
     .with_entity_handle(handle)
     .with_icon(icon)
+    .with_badge(badge)
     .build(ctx)
 }
 
@@ -165,7 +206,11 @@ fn make_folder(ctx: &mut BuildContext, name: &str) -> Handle<UiNode> {
 }
 
 impl WorldViewer {
-    pub fn new(ctx: &mut BuildContext, sender: Sender<Message>) -> Self {
+    pub fn new(
+        ctx: &mut BuildContext,
+        sender: Sender<Message>,
+        serialization_context: Arc<SerializationContext>,
+    ) -> Self {
         let track_selection_state = true;
         let tree_root;
         let node_path;
@@ -279,7 +324,7 @@ impl WorldViewer {
             )
             .build(ctx);
 
-        let item_context_menu = ItemContextMenu::new(ctx);
+        let item_context_menu = ItemContextMenu::new(serialization_context, ctx);
 
         Self {
             search_bar,
@@ -299,6 +344,7 @@ impl WorldViewer {
             scroll_view,
             item_context_menu,
             node_to_view_map: Default::default(),
+            pending_expansion: Default::default(),
             filter: Default::default(),
         }
     }
@@ -310,10 +356,27 @@ impl WorldViewer {
         let graph = &mut scene.graph;
         let ui = &mut engine.user_interface;
 
+        self.item_context_menu
+            .sync_collections(&scene.node_collections, ui);
+
         let mut selected_items = Vec::new();
 
         selected_items.extend(self.sync_graph(ui, editor_scene, graph));
 
+        for node in self.pending_expansion.drain(..) {
+            if let Some(&view) = self.node_to_view_map.get(&node) {
+                send_sync_message(
+                    ui,
+                    TreeMessage::expand(
+                        view,
+                        MessageDirection::ToWidget,
+                        true,
+                        TreeExpansionStrategy::Direct,
+                    ),
+                );
+            }
+        }
+
         if !selected_items.is_empty() {
             send_sync_message(
                 ui,
@@ -380,6 +443,8 @@ impl WorldViewer {
     ) -> Vec<Handle<UiNode>> {
         let mut selected_items = Vec::new();
 
+        let added_nodes = find_added_nodes(graph);
+
         // Sync tree structure with graph structure.
         self.stack.clear();
         self.stack.push((self.graph_folder, graph.get_root()));
@@ -455,6 +520,8 @@ impl WorldViewer {
                                 let graph_node_item = make_graph_node_item(
                                     &graph[child_handle],
                                     child_handle,
+                                    editor_scene.editor_data.get(child_handle),
+                                    node_badge(&graph[child_handle], child_handle, &added_nodes),
                                     &mut ui.build_ctx(),
                                     self.item_context_menu.menu,
                                 );
@@ -482,6 +549,8 @@ impl WorldViewer {
                     let graph_node_item = make_graph_node_item(
                         node,
                         node_handle,
+                        editor_scene.editor_data.get(node_handle),
+                        node_badge(node, node_handle, &added_nodes),
                         &mut ui.build_ctx(),
                         self.item_context_menu.menu,
                     );
@@ -519,6 +588,29 @@ impl WorldViewer {
                         );
                     }
 
+                    let (icon, brush) =
+                        node_item_style(node, editor_scene.editor_data.get(item.entity_handle));
+                    if item.icon() != icon.as_ref() {
+                        send_sync_message(
+                            ui,
+                            SceneItemMessage::icon(handle, MessageDirection::ToWidget, icon),
+                        );
+                    }
+                    if item.text_brush() != &brush {
+                        send_sync_message(
+                            ui,
+                            SceneItemMessage::text_brush(handle, MessageDirection::ToWidget, brush),
+                        );
+                    }
+
+                    let badge = node_badge(node, item.entity_handle, &added_nodes);
+                    if item.badge() != badge {
+                        send_sync_message(
+                            ui,
+                            SceneItemMessage::badge(handle, MessageDirection::ToWidget, badge),
+                        );
+                    }
+
                     stack.extend_from_slice(item.tree.items());
                 }
             } else if let Some(root) = ui_node.cast::<TreeRoot>() {
@@ -786,6 +878,25 @@ impl WorldViewer {
         }
     }
 
+    /// Returns a list of nodes whose items are expanded.
+    pub fn expanded_nodes(&self, ui: &UserInterface) -> Vec<Handle<Node>> {
+        self.node_to_view_map
+            .iter()
+            .filter(|(_, &view)| {
+                ui.try_get_node(view)
+                    .and_then(|n| n.cast::<SceneItem<Node>>())
+                    .map_or(false, |item| item.tree.expanded())
+            })
+            .map(|(&node, _)| node)
+            .collect()
+    }
+
+    /// Expands items of the given nodes on next sync, it is used to restore the state of the tree
+    /// when a scene is reloaded.
+    pub fn expand_on_sync(&mut self, nodes: Vec<Handle<Node>>) {
+        self.pending_expansion = nodes;
+    }
+
     pub fn clear(&mut self, ui: &UserInterface) {
         self.node_to_view_map.clear();
 
@@ -805,6 +916,10 @@ impl WorldViewer {
             mode.is_edit(),
         ));
     }
+
+    pub fn on_node_constructors_changed(&mut self, ui: &mut UserInterface) {
+        self.item_context_menu.on_node_constructors_changed(ui);
+    }
 }
 
 fn map_selection<T>(
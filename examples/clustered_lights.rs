@@ -0,0 +1,273 @@
+//! Example 19. Clustered lighting.
+//!
+//! Difficulty: Medium.
+//!
+//! This example shows a scene with hundreds of small point lights, that are shaded in a single
+//! clustered lighting pass instead of one draw call per light. Press `C` to toggle clustering and
+//! compare frame time, press `V` to render the scene with both paths and compare the results.
+
+pub mod shared;
+
+use crate::shared::create_camera;
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Vector3},
+        color::Color,
+        parking_lot::Mutex,
+        pool::Handle,
+        rand::Rng,
+    },
+    engine::{framework::prelude::*, resource_manager::ResourceManager, Engine},
+    event::{ElementState, VirtualKeyCode, WindowEvent},
+    event_loop::ControlFlow,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    rand::thread_rng,
+    scene::{
+        base::BaseBuilder,
+        light::{point::PointLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder,
+        },
+        node::Node,
+        reflection_probe::ReflectionProbeBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::sync::Arc;
+
+/// Amount of lights in the scene.
+const LIGHT_COUNT: usize = 300;
+
+/// Maximum allowed difference between results of clustered and per-light paths.
+const TOLERANCE: f32 = 0.01;
+
+struct GameSceneLoader {
+    scene: Scene,
+    probe: Handle<Node>,
+}
+
+impl GameSceneLoader {
+    async fn load_with(resource_manager: ResourceManager) -> Self {
+        let mut scene = Scene::new();
+
+        scene.ambient_lighting_color = Color::opaque(30, 30, 30);
+
+        create_camera(
+            resource_manager.clone(),
+            Vector3::new(0.0, 6.0, -22.0),
+            &mut scene.graph,
+        )
+        .await;
+
+        // Floor.
+        MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+                SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                    40.0, 0.2, 40.0,
+                ))),
+            )))
+            .build()])
+            .build(&mut scene.graph);
+
+        // Pillars to have some geometry that is lit from different sides.
+        for x in -3..=3 {
+            for z in -3..=3 {
+                MeshBuilder::new(
+                    BaseBuilder::new().with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(Vector3::new(x as f32 * 5.0, 1.5, z as f32 * 5.0))
+                            .build(),
+                    ),
+                )
+                .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+                    SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                        0.5, 3.0, 0.5,
+                    ))),
+                )))
+                .build()])
+                .build(&mut scene.graph);
+            }
+        }
+
+        // Lots of small lights without shadows - they're the ones that use clustered path.
+        let mut rng = thread_rng();
+        for _ in 0..LIGHT_COUNT {
+            PointLightBuilder::new(
+                BaseLightBuilder::new(
+                    BaseBuilder::new().with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(Vector3::new(
+                                rng.gen_range(-18.0..18.0),
+                                rng.gen_range(0.3..2.5),
+                                rng.gen_range(-18.0..18.0),
+                            ))
+                            .build(),
+                    ),
+                )
+                .with_color(Color::opaque(
+                    rng.gen_range(64..255),
+                    rng.gen_range(64..255),
+                    rng.gen_range(64..255),
+                ))
+                .cast_shadows(false),
+            )
+            .with_radius(rng.gen_range(1.5..3.0))
+            .build(&mut scene.graph);
+        }
+
+        // The probe is used only to render the scene off-screen to compare lighting paths.
+        let probe = ReflectionProbeBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 4.0, 0.0))
+                    .build(),
+            ),
+        )
+        .with_resolution(128)
+        .build(&mut scene.graph);
+
+        Self { scene, probe }
+    }
+}
+
+struct Game {
+    scene: Handle<Scene>,
+    probe: Handle<Node>,
+    debug_text: Handle<UiNode>,
+    comparison: String,
+}
+
+impl Game {
+    fn set_clustering_enabled(engine: &mut Engine, enabled: bool) {
+        let mut settings = engine.renderer.get_quality_settings();
+        settings.light_cluster_settings.enabled = enabled;
+        engine.renderer.set_quality_settings(&settings).unwrap();
+    }
+
+    fn capture(&self, engine: &mut Engine) -> Vec<f32> {
+        let texture = engine
+            .renderer
+            .capture_reflection_probe(&engine.scenes[self.scene], self.probe)
+            .unwrap();
+        let data = texture.data_ref();
+        let pixels = data
+            .data()
+            .chunks_exact(4)
+            .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        pixels
+    }
+
+    // Renders the scene with both lighting paths and compares the results.
+    fn compare(&mut self, engine: &mut Engine) {
+        let enabled = engine
+            .renderer
+            .get_quality_settings()
+            .light_cluster_settings
+            .enabled;
+
+        Self::set_clustering_enabled(engine, false);
+        let reference = self.capture(engine);
+        Self::set_clustering_enabled(engine, true);
+        let clustered = self.capture(engine);
+        Self::set_clustering_enabled(engine, enabled);
+
+        let mut max_difference = 0.0f32;
+        let mut total_difference = 0.0;
+        for (a, b) in reference.iter().zip(clustered.iter()) {
+            let difference = (a - b).abs();
+            max_difference = max_difference.max(difference);
+            total_difference += difference;
+        }
+        let mean_difference = total_difference / reference.len().max(1) as f32;
+
+        self.comparison = format!(
+            "Comparison: {} - Max Difference: {:.4}, Mean Difference: {:.6}",
+            if max_difference <= TOLERANCE {
+                "Passed"
+            } else {
+                "Failed"
+            },
+            max_difference,
+            mean_difference
+        );
+    }
+}
+
+impl GameState for Game {
+    fn init(engine: &mut Engine) -> Self
+    where
+        Self: Sized,
+    {
+        let scene = fyrox::core::futures::executor::block_on(GameSceneLoader::load_with(
+            engine.resource_manager.clone(),
+        ));
+
+        Self {
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut engine.user_interface.build_ctx()),
+            scene: engine.scenes.add(scene.scene),
+            probe: scene.probe,
+            comparison: "Press V to compare lighting paths".to_owned(),
+        }
+    }
+
+    fn on_tick(&mut self, engine: &mut Engine, _dt: f32, _: &mut ControlFlow) {
+        let statistics = engine.renderer.get_statistics();
+
+        engine.user_interface.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example 19 - Clustered Lighting\nClustering: {} (press C to toggle)\n{}\n\
+                Frame Time: {:.2} ms\n{}",
+                if engine
+                    .renderer
+                    .get_quality_settings()
+                    .light_cluster_settings
+                    .enabled
+                {
+                    "On"
+                } else {
+                    "Off"
+                },
+                self.comparison,
+                statistics.pure_frame_time * 1000.0,
+                statistics
+            ),
+        ));
+    }
+
+    fn on_window_event(&mut self, engine: &mut Engine, event: WindowEvent) {
+        if let WindowEvent::KeyboardInput { input, .. } = event {
+            if input.state == ElementState::Pressed {
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::C) => {
+                        let enabled = engine
+                            .renderer
+                            .get_quality_settings()
+                            .light_cluster_settings
+                            .enabled;
+                        Self::set_clustering_enabled(engine, !enabled);
+                    }
+                    Some(VirtualKeyCode::V) => self.compare(engine),
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+fn main() {
+    Framework::<Game>::new()
+        .unwrap()
+        .title("Example 19 - Clustered Lighting")
+        .run();
+}
@@ -0,0 +1,300 @@
+//! Example 27. Output color management.
+//!
+//! Difficulty: Easy.
+//!
+//! This example shows a test pattern, that is used to check output targets of the renderer. The
+//! user interface shows a grayscale ramp and saturated primary and secondary colors, the scene
+//! shows a row of emissive quads, brightness of every quad is two times higher than brightness of
+//! the previous one. Press `O` to cycle output targets, unsupported targets fall back to sRGB.
+//! `Up`/`Down` change paper white and `PageUp`/`PageDown` change maximum luminance of HDR output.
+//!
+//! What to look for:
+//! - sRGB: every step of the grayscale ramp is distinguishable, the brightest quads are tone mapped
+//!   to the same white as the white of the ramp.
+//! - Rec.709: dark steps of the ramp are darker than with sRGB on an sRGB display, and look the
+//!   same as sRGB ones on a display that is set to BT.1886.
+//! - HDR10: the ramp and the primaries look the same as in SDR, the brightest quads are brighter
+//!   than the white of the ramp and are not clipped until maximum luminance.
+
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        color::Color,
+        parking_lot::Mutex,
+        pool::Handle,
+        sstorage::ImmutableString,
+    },
+    engine::{framework::prelude::*, Engine},
+    event::{ElementState, VirtualKeyCode, WindowEvent},
+    event_loop::ControlFlow,
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        message::MessageDirection,
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        BuildContext, Orientation, Thickness, UiNode,
+    },
+    material::{shader::Shader, Material, PropertyValue},
+    renderer::color_management::{ColorManagementSettings, OutputTarget},
+    scene::{
+        base::BaseBuilder,
+        camera::{CameraBuilder, Exposure},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder,
+        },
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::{path::PathBuf, sync::Arc};
+
+/// Unlit shader, that outputs linear color multiplied by intensity, so it could be brighter than
+/// white before tone mapping.
+const EMISSIVE_SHADER: &str = r#"(
+    name: "EmissiveShader",
+
+    properties: [
+        (
+            name: "intensity",
+            kind: Vector3([1.0, 1.0, 1.0]),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r"
+                layout(location = 0) in vec3 vertexPosition;
+
+                uniform mat4 fyrox_worldViewProjection;
+
+                void main()
+                {
+                    gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                }
+               ",
+
+           fragment_shader:
+               r"
+                uniform vec3 intensity;
+
+                out vec4 FragColor;
+
+                void main()
+                {
+                    FragColor = vec4(intensity, 1.0);
+                }
+               ",
+        ),
+    ],
+)"#;
+
+/// Amount of emissive quads, the first one has intensity of 1/4.
+const QUAD_COUNT: usize = 9;
+
+/// Amount of steps of the grayscale ramp.
+const RAMP_STEPS: usize = 16;
+
+/// Size of a single patch of the pattern in the user interface.
+const PATCH_SIZE: f32 = 48.0;
+
+fn make_patch(ctx: &mut BuildContext, color: Color) -> Handle<UiNode> {
+    BorderBuilder::new(
+        WidgetBuilder::new()
+            .with_width(PATCH_SIZE)
+            .with_height(PATCH_SIZE)
+            .with_background(Brush::Solid(color)),
+    )
+    .with_stroke_thickness(Thickness::uniform(0.0))
+    .build(ctx)
+}
+
+fn make_row(ctx: &mut BuildContext, colors: impl Iterator<Item = Color>) -> Handle<UiNode> {
+    let patches = colors
+        .map(|color| make_patch(ctx, color))
+        .collect::<Vec<_>>();
+    StackPanelBuilder::new(WidgetBuilder::new().with_children(patches))
+        .with_orientation(Orientation::Horizontal)
+        .build(ctx)
+}
+
+fn make_pattern(ctx: &mut BuildContext) -> Handle<UiNode> {
+    let ramp = make_row(
+        ctx,
+        (0..RAMP_STEPS).map(|i| {
+            let value = (i * 255 / (RAMP_STEPS - 1)) as u8;
+            Color::opaque(value, value, value)
+        }),
+    );
+    let primaries = make_row(
+        ctx,
+        [
+            Color::opaque(255, 0, 0),
+            Color::opaque(0, 255, 0),
+            Color::opaque(0, 0, 255),
+            Color::opaque(0, 255, 255),
+            Color::opaque(255, 0, 255),
+            Color::opaque(255, 255, 0),
+            Color::WHITE,
+        ]
+        .into_iter(),
+    );
+
+    StackPanelBuilder::new(
+        WidgetBuilder::new()
+            .with_margin(Thickness::uniform(10.0))
+            .with_desired_position(Vector2::new(0.0, 140.0))
+            .with_children([ramp, primaries]),
+    )
+    .build(ctx)
+}
+
+struct Game {
+    settings: ColorManagementSettings,
+    debug_text: Handle<UiNode>,
+}
+
+impl Game {
+    fn apply_settings(&mut self, engine: &mut Engine) {
+        if let Err(e) = engine.renderer.set_color_management_settings(self.settings) {
+            self.settings.output_target = OutputTarget::Srgb;
+            println!("Unable to change output target. Reason: {:?}", e);
+        }
+    }
+}
+
+impl GameState for Game {
+    fn init(engine: &mut Engine) -> Self
+    where
+        Self: Sized,
+    {
+        let mut scene = Scene::new();
+
+        scene.ambient_lighting_color = Color::BLACK;
+
+        // Fixed exposure of 1.0, so the frame does not adapt to the brightness of the quads.
+        CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, -1.5, -8.0))
+                    .build(),
+            ),
+        )
+        .with_exposure(Exposure::Manual(1.0))
+        .build(&mut scene.graph);
+
+        let shader = Shader::from_str(EMISSIVE_SHADER, PathBuf::default()).unwrap();
+
+        for i in 0..QUAD_COUNT {
+            let mut material = Material::from_shader(shader.clone(), None);
+            let intensity = 2.0f32.powi(i as i32 - 2);
+            material
+                .set_property(
+                    &ImmutableString::new("intensity"),
+                    PropertyValue::Vector3(Vector3::repeat(intensity)),
+                )
+                .unwrap();
+
+            let x = (i as f32 - (QUAD_COUNT - 1) as f32 * 0.5) * 1.1;
+            MeshBuilder::new(BaseBuilder::new())
+                .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+                    SurfaceData::make_quad(&Matrix4::new_translation(&Vector3::new(x, 0.0, 0.0))),
+                )))
+                .with_material(Arc::new(Mutex::new(material)))
+                .build()])
+                .build(&mut scene.graph);
+        }
+
+        engine.scenes.add(scene);
+
+        let ctx = &mut engine.user_interface.build_ctx();
+        make_pattern(ctx);
+
+        Self {
+            settings: engine.renderer.color_management_settings(),
+            debug_text: TextBuilder::new(WidgetBuilder::new()).build(ctx),
+        }
+    }
+
+    fn on_tick(&mut self, engine: &mut Engine, _dt: f32, _: &mut ControlFlow) {
+        let capabilities = engine.renderer.output_capabilities();
+        let supported = capabilities
+            .supported_targets()
+            .iter()
+            .map(|target| target.name())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        engine.user_interface.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example 27 - Output Color Management\n\
+                Output: {} (requested {}, press O to change)\n\
+                Supported: {}, back buffer: {} bits per channel\n\
+                Paper white: {} nits (Up/Down)\n\
+                Max luminance: {} nits (PageUp/PageDown)",
+                engine.renderer.active_output_target(),
+                self.settings.output_target,
+                supported,
+                capabilities.bits_per_channel(),
+                self.settings.paper_white,
+                self.settings.max_luminance,
+            ),
+        ));
+    }
+
+    fn on_window_event(&mut self, engine: &mut Engine, event: WindowEvent) {
+        if let WindowEvent::KeyboardInput { input, .. } = event {
+            if input.state == ElementState::Pressed {
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::O) => {
+                        let index = OutputTarget::ALL
+                            .iter()
+                            .position(|t| *t == self.settings.output_target)
+                            .unwrap();
+                        self.settings.output_target =
+                            OutputTarget::ALL[(index + 1) % OutputTarget::ALL.len()];
+                    }
+                    Some(VirtualKeyCode::Up) => self.settings.paper_white += 20.0,
+                    Some(VirtualKeyCode::Down) => self.settings.paper_white -= 20.0,
+                    Some(VirtualKeyCode::PageUp) => self.settings.max_luminance += 100.0,
+                    Some(VirtualKeyCode::PageDown) => self.settings.max_luminance -= 100.0,
+                    _ => return,
+                }
+                self.settings = self.settings.clamped();
+                self.apply_settings(engine);
+            }
+        }
+    }
+}
+
+fn main() {
+    Framework::<Game>::new()
+        .unwrap()
+        .title("Example 27 - Output Color Management")
+        .run();
+}
@@ -0,0 +1,121 @@
+//! Example - Custom vertex attributes.
+//!
+//! Difficulty: Medium.
+//!
+//! This example shows how to add a named custom attribute to a vertex buffer of a surface and
+//! how to use it in a custom shader. The sphere is colored by per-vertex "wetness" - the lower
+//! a vertex is, the wetter it is.
+
+pub mod shared;
+
+use crate::shared::create_camera;
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Vector3},
+        color::Color,
+        futures::executor::block_on,
+        parking_lot::Mutex,
+    },
+    engine::{framework::prelude::*, resource_manager::ResourceManager, Engine},
+    material::Material,
+    scene::{
+        base::BaseBuilder,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            buffer::{
+                CustomVertexAttributeDescriptor, VertexAttributeDataType, VertexAttributeUsage,
+                VertexReadTrait, VertexWriteTrait,
+            },
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder,
+        },
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::sync::Arc;
+
+struct Game;
+
+fn create_wet_sphere() -> SurfaceData {
+    let mut data = SurfaceData::make_sphere(40, 40, 1.0, &Matrix4::identity());
+
+    let mut vertex_buffer = data.vertex_buffer.modify();
+    let wetness = vertex_buffer
+        .add_custom_attribute(
+            CustomVertexAttributeDescriptor {
+                name: "wetness".to_owned(),
+                data_type: VertexAttributeDataType::F32,
+                size: 1,
+            },
+            0.0f32,
+        )
+        .unwrap();
+
+    for mut vertex in vertex_buffer.iter_mut() {
+        let position = vertex.read_3_f32(VertexAttributeUsage::Position).unwrap();
+        // Fill the lower half of the sphere with "water".
+        vertex
+            .write_1_f32(wetness, (0.25 - position.y).clamp(0.0, 1.0))
+            .unwrap();
+    }
+
+    drop(vertex_buffer);
+
+    data
+}
+
+fn create_wetness_material(resource_manager: ResourceManager) -> Arc<Mutex<Material>> {
+    let shader =
+        block_on(resource_manager.request_shader("examples/data/shaders/wetness.shader")).unwrap();
+
+    Arc::new(Mutex::new(Material::from_shader(
+        shader,
+        Some(resource_manager),
+    )))
+}
+
+impl GameState for Game {
+    fn init(engine: &mut Engine) -> Self
+    where
+        Self: Sized,
+    {
+        let mut scene = Scene::new();
+
+        scene.ambient_lighting_color = Color::opaque(80, 80, 80);
+
+        block_on(create_camera(
+            engine.resource_manager.clone(),
+            Vector3::new(0.0, 0.5, -3.0),
+            &mut scene.graph,
+        ));
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 5.0, 0.0))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+                create_wet_sphere(),
+            )))
+            .with_material(create_wetness_material(engine.resource_manager.clone()))
+            .build()])
+            .build(&mut scene.graph);
+
+        engine.scenes.add(scene);
+
+        Self
+    }
+}
+
+fn main() {
+    Framework::<Game>::new()
+        .unwrap()
+        .title("Example - Custom Vertex Attributes")
+        .run();
+}
@@ -50,6 +50,8 @@
             out vec3 position;
             out vec3 normal;
             out vec2 texCoord;
+            out vec4 clipPosition;
+            out vec4 prevClipPosition;
 
             vec3 Wave(vec3 p)
             {
@@ -63,6 +65,8 @@
                 texCoord = vertexTexCoord;
                 normal = normalize(mat3(fyrox_worldMatrix) * vertexNormal);
                 gl_Position = fyrox_worldViewProjection * vec4(Wave(vertexPosition), 1.0);
+                clipPosition = gl_Position;
+                prevClipPosition = fyrox_prevWorldViewProjection * vec4(Wave(vertexPosition), 1.0);
             }
             "#,
 
@@ -73,12 +77,15 @@
             layout(location = 2) out vec4 outAmbient;
             layout(location = 3) out vec4 outMaterial;
             layout(location = 4) out uint outDecalMask;
+            layout(location = 5) out vec2 outMotionVector;
 
             // Properties.
             uniform sampler2D diffuseTexture;
 
             in vec3 normal;
             in vec2 texCoord;
+            in vec4 clipPosition;
+            in vec4 prevClipPosition;
 
             void main()
             {
@@ -87,6 +94,7 @@
                 outMaterial = vec4(0.0, 1.0, 0.0, 1.0);
                 outAmbient = vec4(0.0, 0.0, 0.0, 1.0);
                 outDecalMask = 0u;
+                outMotionVector = S_ComputeMotionVector(clipPosition, prevClipPosition);
             }
             "#,
         ),
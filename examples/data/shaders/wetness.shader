@@ -0,0 +1,98 @@
+(
+    name: "WetnessShader",
+
+    properties: [
+        (
+            name: "dryColor",
+            kind: Color(r: 200, g: 170, b: 110, a: 255),
+        ),
+        (
+            name: "wetColor",
+            kind: Color(r: 40, g: 60, b: 110, a: 255),
+        ),
+    ],
+
+    // Custom vertex attributes, they must be added to vertex buffers in the same order. The
+    // engine declares them in every vertex shader automatically.
+    vertex_attributes: [
+        (name: "wetness", size: 1),
+    ],
+
+    passes: [
+        (
+            name: "GBuffer",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+            r#"
+            layout(location = 0) in vec3 vertexPosition;
+            layout(location = 2) in vec3 vertexNormal;
+
+            uniform mat4 fyrox_worldMatrix;
+            uniform mat4 fyrox_worldViewProjection;
+
+            out vec3 normal;
+            out float fragmentWetness;
+            out vec4 clipPosition;
+            out vec4 prevClipPosition;
+
+            void main()
+            {
+                // `wetness` is a custom vertex attribute, it is declared by the engine.
+                fragmentWetness = wetness;
+                normal = normalize(mat3(fyrox_worldMatrix) * vertexNormal);
+                gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                clipPosition = gl_Position;
+                prevClipPosition = fyrox_prevWorldViewProjection * vec4(vertexPosition, 1.0);
+            }
+            "#,
+
+            fragment_shader:
+            r#"
+            layout(location = 0) out vec4 outColor;
+            layout(location = 1) out vec4 outNormal;
+            layout(location = 2) out vec4 outAmbient;
+            layout(location = 3) out vec4 outMaterial;
+            layout(location = 4) out uint outDecalMask;
+            layout(location = 5) out vec2 outMotionVector;
+
+            // Properties.
+            uniform vec4 dryColor;
+            uniform vec4 wetColor;
+
+            in vec3 normal;
+            in float fragmentWetness;
+            in vec4 clipPosition;
+            in vec4 prevClipPosition;
+
+            void main()
+            {
+                outColor = mix(dryColor, wetColor, clamp(fragmentWetness, 0.0, 1.0));
+                outNormal = vec4(normal * 0.5 + 0.5, 1.0);
+                // Wet surfaces are smoother.
+                outMaterial = vec4(0.0, 1.0 - 0.8 * fragmentWetness, 0.0, 1.0);
+                outAmbient = vec4(0.0, 0.0, 0.0, 1.0);
+                outDecalMask = 0u;
+                outMotionVector = S_ComputeMotionVector(clipPosition, prevClipPosition);
+            }
+            "#,
+        ),
+    ],
+)
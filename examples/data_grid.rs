@@ -0,0 +1,96 @@
+//! Example - Data grid
+//!
+//! Difficulty: Easy
+//!
+//! This example shows how to use data grid widget to show large tables. Only visible rows are
+//! created by the grid, so it stays fast even with hundreds of thousands of rows.
+
+use fyrox::{
+    core::{pool::Handle, rand::Rng},
+    engine::{framework::prelude::*, Engine},
+    gui::{
+        button::{ButtonBuilder, ButtonMessage},
+        data_grid::{DataGridBuilder, DataGridColumn, DataGridMessage},
+        message::{MessageDirection, UiMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    rand::thread_rng,
+};
+use std::rc::Rc;
+
+struct Entry {
+    name: String,
+    size: u32,
+}
+
+struct Game {
+    grid: Handle<UiNode>,
+}
+
+impl GameState for Game {
+    fn init(engine: &mut Engine) -> Self
+    where
+        Self: Sized,
+    {
+        let mut rng = thread_rng();
+        let entries = Rc::new(
+            (0..100_000)
+                .map(|i| Entry {
+                    name: format!("Item {}", i),
+                    size: rng.gen_range(0..1_000_000),
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        let ctx = &mut engine.user_interface.build_ctx();
+
+        let name_entries = entries.clone();
+        let name_cmp_entries = entries.clone();
+        let size_entries = entries.clone();
+        let size_cmp_entries = entries.clone();
+        let grid = DataGridBuilder::new(WidgetBuilder::new().with_width(500.0).with_height(400.0))
+            .with_row_count(entries.len())
+            .with_multi_selection(true)
+            .with_column(
+                DataGridColumn::text("Name", 200.0, move |row| name_entries[row].name.clone())
+                    .with_comparator(move |a, b| {
+                        name_cmp_entries[a].name.cmp(&name_cmp_entries[b].name)
+                    }),
+            )
+            .with_column(
+                DataGridColumn::text("Size", 150.0, move |row| size_entries[row].size.to_string())
+                    .with_comparator(move |a, b| {
+                        size_cmp_entries[a].size.cmp(&size_cmp_entries[b].size)
+                    }),
+            )
+            // Cells can contain any widgets.
+            .with_column(DataGridColumn::new("Action", 100.0, |ctx, row| {
+                ButtonBuilder::new(WidgetBuilder::new())
+                    .with_text(&format!("Print {}", row))
+                    .build(ctx)
+            }))
+            .build(ctx);
+
+        Self { grid }
+    }
+
+    fn on_ui_message(&mut self, _engine: &mut Engine, message: UiMessage) {
+        if let Some(DataGridMessage::Selection(selection)) = message.data::<DataGridMessage>() {
+            if message.destination() == self.grid
+                && message.direction() == MessageDirection::FromWidget
+            {
+                println!("Selected rows: {:?}", selection);
+            }
+        } else if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            println!("Button {:?} was clicked!", message.destination());
+        }
+    }
+}
+
+fn main() {
+    Framework::<Game>::new()
+        .unwrap()
+        .title("Example - Data Grid")
+        .run();
+}
@@ -0,0 +1,174 @@
+//! Example 28. Frame pacing.
+//!
+//! Difficulty: Easy.
+//!
+//! This example shows how to limit frame rate, change vertical synchronization without restarting
+//! the game and choose what happens when the window loses focus. Press `F` to cycle target frame
+//! rates, `V` to cycle vertical synchronization modes and `B` to cycle background policies, then
+//! switch to another window to see the policy in action.
+//!
+//! The cube rotates with a constant speed regardless of frame rate, because the game is updated
+//! with fixed time steps.
+
+use fyrox::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        parking_lot::Mutex,
+        pool::Handle,
+    },
+    engine::{
+        frame_pacing::{BackgroundPolicy, VsyncMode},
+        framework::prelude::*,
+        Engine,
+    },
+    event::{ElementState, VirtualKeyCode, WindowEvent},
+    event_loop::ControlFlow,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder,
+        },
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::sync::Arc;
+
+const TARGET_FRAME_RATES: [Option<u32>; 4] = [None, Some(30), Some(60), Some(144)];
+
+const BACKGROUND_POLICIES: [BackgroundPolicy; 3] = [
+    BackgroundPolicy::KeepRunning,
+    BackgroundPolicy::Throttle(10),
+    BackgroundPolicy::PauseRendering,
+];
+
+fn next<T: PartialEq + Copy>(values: &[T], current: T) -> T {
+    let index = values.iter().position(|v| *v == current).unwrap_or(0);
+    values[(index + 1) % values.len()]
+}
+
+struct Game {
+    scene: Handle<Scene>,
+    cube: Handle<Node>,
+    angle: f32,
+    debug_text: Handle<UiNode>,
+}
+
+impl GameState for Game {
+    fn init(engine: &mut Engine) -> Self
+    where
+        Self: Sized,
+    {
+        let mut scene = Scene::new();
+
+        CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, -3.0))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        45.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        let cube = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+                SurfaceData::make_cube(Matrix4::identity()),
+            )))
+            .build()])
+            .build(&mut scene.graph);
+
+        Self {
+            scene: engine.scenes.add(scene),
+            cube,
+            angle: 0.0,
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut engine.user_interface.build_ctx()),
+        }
+    }
+
+    fn on_tick(&mut self, engine: &mut Engine, dt: f32, _: &mut ControlFlow) {
+        self.angle += dt;
+
+        engine.scenes[self.scene].graph[self.cube]
+            .local_transform_mut()
+            .set_rotation(UnitQuaternion::from_axis_angle(
+                &Vector3::y_axis(),
+                self.angle,
+            ));
+
+        let pacing = &engine.frame_pacing;
+        let target_fps = match pacing.target_fps() {
+            Some(fps) => fps.to_string(),
+            None => "Unlimited".to_owned(),
+        };
+
+        engine.user_interface.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example 28 - Frame Pacing\n\
+                FPS: {}\n\
+                Target FPS: {} (press F to change)\n\
+                VSync: {} (requested {}, press V to change)\n\
+                Background: {} (press B to change)\n\
+                Focused: {}",
+                engine.renderer.get_statistics().frames_per_second,
+                target_fps,
+                pacing.active_vsync(),
+                pacing.vsync(),
+                pacing.background_policy(),
+                pacing.is_focused(),
+            ),
+        ));
+    }
+
+    fn on_window_event(&mut self, engine: &mut Engine, event: WindowEvent) {
+        if let WindowEvent::KeyboardInput { input, .. } = event {
+            if input.state == ElementState::Pressed {
+                let pacing = &mut engine.frame_pacing;
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::F) => {
+                        pacing.set_target_fps(next(&TARGET_FRAME_RATES, pacing.target_fps()))
+                    }
+                    Some(VirtualKeyCode::V) => {
+                        pacing.set_vsync(next(&VsyncMode::ALL, pacing.vsync()))
+                    }
+                    Some(VirtualKeyCode::B) => pacing.set_background_policy(next(
+                        &BACKGROUND_POLICIES,
+                        pacing.background_policy(),
+                    )),
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+fn main() {
+    Framework::<Game>::new()
+        .unwrap()
+        .title("Example 28 - Frame Pacing")
+        .run();
+}
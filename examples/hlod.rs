@@ -0,0 +1,247 @@
+//! Example 29. Hierarchical level of detail (HLOD).
+//!
+//! Difficulty: Medium.
+//!
+//! This example shows a city made of hundreds of small static buildings. Buildings are grouped
+//! by city blocks and every block is baked into a single simplified proxy, that replaces the block
+//! when it is far from the camera. Use `W` and `S` to move the camera and watch how the amount of
+//! draw calls changes, press `H` to toggle HLOD and compare.
+
+pub mod shared;
+
+use crate::shared::create_camera;
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Vector3},
+        color::Color,
+        parking_lot::Mutex,
+        pool::Handle,
+        rand::Rng,
+        sstorage::ImmutableString,
+    },
+    engine::{framework::prelude::*, resource_manager::ResourceManager, Engine},
+    event::{ElementState, VirtualKeyCode, WindowEvent},
+    event_loop::ControlFlow,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    material::{Material, PropertyValue},
+    rand::thread_rng,
+    scene::{
+        base::{BaseBuilder, Mobility},
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder,
+        },
+        node::Node,
+        pivot::PivotBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+    utils::{
+        hlod::{self, HlodInputData, HlodSettings, ProgressIndicator},
+        lightmap::CancellationToken,
+    },
+};
+use std::sync::Arc;
+
+/// Amount of city blocks along each axis.
+const BLOCK_COUNT: i32 = 8;
+
+/// Amount of buildings along each side of a block.
+const BUILDINGS_PER_BLOCK: i32 = 4;
+
+/// Size of a block with the streets around it.
+const BLOCK_SIZE: f32 = 40.0;
+
+struct GameSceneLoader {
+    scene: Scene,
+    camera: Handle<Node>,
+    hlods: Vec<Handle<Node>>,
+}
+
+impl GameSceneLoader {
+    async fn load_with(resource_manager: ResourceManager) -> Self {
+        let mut scene = Scene::new();
+
+        scene.ambient_lighting_color = Color::opaque(80, 80, 80);
+
+        let camera = create_camera(
+            resource_manager.clone(),
+            Vector3::new(0.0, 25.0, -60.0),
+            &mut scene.graph,
+        )
+        .await;
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new()))
+            .build(&mut scene.graph);
+
+        let city = PivotBuilder::new(BaseBuilder::new().with_name("City")).build(&mut scene.graph);
+
+        let mut rng = thread_rng();
+        let building_spacing = BLOCK_SIZE / (BUILDINGS_PER_BLOCK + 1) as f32;
+        for bx in 0..BLOCK_COUNT {
+            for bz in 0..BLOCK_COUNT {
+                for x in 0..BUILDINGS_PER_BLOCK {
+                    for z in 0..BUILDINGS_PER_BLOCK {
+                        let height = rng.gen_range(4.0..20.0);
+                        let position = Vector3::new(
+                            (bx as f32 - BLOCK_COUNT as f32 * 0.5) * BLOCK_SIZE
+                                + (x + 1) as f32 * building_spacing,
+                            height * 0.5,
+                            bz as f32 * BLOCK_SIZE + (z + 1) as f32 * building_spacing,
+                        );
+
+                        let mut material = Material::standard();
+                        material
+                            .set_property(
+                                &ImmutableString::new("diffuseColor"),
+                                PropertyValue::Color(Color::opaque(
+                                    rng.gen_range(100..230),
+                                    rng.gen_range(100..230),
+                                    rng.gen_range(100..230),
+                                )),
+                            )
+                            .unwrap();
+
+                        // Rounded roofs give the simplifier something to work with.
+                        let building = MeshBuilder::new(
+                            BaseBuilder::new()
+                                .with_mobility(Mobility::Static)
+                                .with_local_transform(
+                                    TransformBuilder::new()
+                                        .with_local_position(position)
+                                        .with_local_scale(Vector3::new(
+                                            building_spacing * 0.35,
+                                            height * 0.5,
+                                            building_spacing * 0.35,
+                                        ))
+                                        .build(),
+                                ),
+                        )
+                        .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+                            SurfaceData::make_sphere(16, 16, 1.0, &Matrix4::identity()),
+                        )))
+                        .with_material(Arc::new(Mutex::new(material)))
+                        .build()])
+                        .build(&mut scene.graph);
+
+                        scene.graph.link_nodes(building, city);
+                    }
+                }
+            }
+        }
+
+        // Bake a proxy per city block. Normally this is done in the editor, and proxies are saved
+        // with the scene.
+        let input = HlodInputData::from_scene(
+            &mut scene,
+            city,
+            HlodSettings {
+                cell_size: BLOCK_SIZE,
+                switch_distance: 120.0,
+                hysteresis: 10.0,
+                triangle_ratio: 0.1,
+                atlas_tile_size: 4,
+            },
+            |_, _| true,
+            CancellationToken::new(),
+        )
+        .unwrap();
+        let hlods = hlod::bake(input, CancellationToken::new(), ProgressIndicator::new())
+            .unwrap()
+            .apply(&mut scene.graph, city);
+
+        Self {
+            scene,
+            camera,
+            hlods,
+        }
+    }
+}
+
+struct Game {
+    scene: Handle<Scene>,
+    camera: Handle<Node>,
+    hlods: Vec<Handle<Node>>,
+    hlod_enabled: bool,
+    debug_text: Handle<UiNode>,
+}
+
+impl Game {
+    fn set_hlod_enabled(&mut self, engine: &mut Engine, enabled: bool) {
+        self.hlod_enabled = enabled;
+        // Invisible HLOD node does not hide original meshes.
+        let graph = &mut engine.scenes[self.scene].graph;
+        for &hlod in self.hlods.iter() {
+            graph[hlod].set_visibility(enabled);
+        }
+    }
+}
+
+impl GameState for Game {
+    fn init(engine: &mut Engine) -> Self
+    where
+        Self: Sized,
+    {
+        let scene = fyrox::core::futures::executor::block_on(GameSceneLoader::load_with(
+            engine.resource_manager.clone(),
+        ));
+
+        Self {
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut engine.user_interface.build_ctx()),
+            scene: engine.scenes.add(scene.scene),
+            camera: scene.camera,
+            hlods: scene.hlods,
+            hlod_enabled: true,
+        }
+    }
+
+    fn on_tick(&mut self, engine: &mut Engine, _dt: f32, _: &mut ControlFlow) {
+        let statistics = engine.renderer.get_statistics();
+
+        engine.user_interface.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example 29 - HLOD\nHLOD: {} (press H to toggle), {} proxies\n\
+                Use W/S to move the camera\n{}",
+                if self.hlod_enabled { "On" } else { "Off" },
+                self.hlods.len(),
+                statistics
+            ),
+        ));
+    }
+
+    fn on_window_event(&mut self, engine: &mut Engine, event: WindowEvent) {
+        if let WindowEvent::KeyboardInput { input, .. } = event {
+            if input.state == ElementState::Pressed {
+                let step = match input.virtual_keycode {
+                    Some(VirtualKeyCode::W) => 5.0,
+                    Some(VirtualKeyCode::S) => -5.0,
+                    Some(VirtualKeyCode::H) => {
+                        let enabled = !self.hlod_enabled;
+                        self.set_hlod_enabled(engine, enabled);
+                        0.0
+                    }
+                    _ => 0.0,
+                };
+                engine.scenes[self.scene].graph[self.camera]
+                    .local_transform_mut()
+                    .offset(Vector3::new(0.0, 0.0, step));
+            }
+        }
+    }
+}
+
+fn main() {
+    Framework::<Game>::new()
+        .unwrap()
+        .title("Example 29 - HLOD")
+        .run();
+}
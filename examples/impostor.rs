@@ -0,0 +1,283 @@
+//! Example 24. Impostors.
+//!
+//! Difficulty: Medium.
+//!
+//! This example shows a forest of ten thousands procedural trees. A single tree is baked into an
+//! impostor - a camera-facing billboard that picks a frame from an atlas with pre-rendered views
+//! of the tree from a number of directions around it. Every tree then gets its own impostor node,
+//! that replaces the tree when it is far from the camera. Impostors of all trees share the same
+//! material, so they are rendered in a single batch. Use `W` and `S` to move the camera, press `I`
+//! to toggle impostors and compare the amount of draw calls and triangles.
+
+pub mod shared;
+
+use crate::shared::create_camera;
+use fyrox::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        color::Color,
+        parking_lot::Mutex,
+        pool::Handle,
+        rand::Rng,
+        sstorage::ImmutableString,
+    },
+    engine::{framework::prelude::*, resource_manager::ResourceManager, Engine},
+    event::{ElementState, VirtualKeyCode, WindowEvent},
+    event_loop::ControlFlow,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    material::{Material, PropertyValue},
+    rand::thread_rng,
+    scene::{
+        base::{BaseBuilder, LodGroup},
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder,
+        },
+        node::Node,
+        pivot::PivotBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+    utils::{
+        hlod::ProgressIndicator,
+        impostor::{self, ImpostorInputData, ImpostorSettings},
+        lightmap::CancellationToken,
+    },
+};
+use std::sync::Arc;
+
+/// Amount of trees along each axis.
+const TREES_PER_SIDE: i32 = 100;
+
+/// Distance between neighbouring trees.
+const TREE_SPACING: f32 = 4.0;
+
+struct Tree {
+    root: Handle<Node>,
+    impostor: Handle<Node>,
+    lod_group: LodGroup,
+}
+
+struct GameSceneLoader {
+    scene: Scene,
+    camera: Handle<Node>,
+    trees: Vec<Tree>,
+}
+
+fn make_material(color: Color) -> Arc<Mutex<Material>> {
+    let mut material = Material::standard();
+    material
+        .set_property(
+            &ImmutableString::new("diffuseColor"),
+            PropertyValue::Color(color),
+        )
+        .unwrap();
+    Arc::new(Mutex::new(material))
+}
+
+impl GameSceneLoader {
+    async fn load_with(resource_manager: ResourceManager) -> Self {
+        let mut scene = Scene::new();
+
+        scene.ambient_lighting_color = Color::opaque(80, 80, 80);
+
+        let camera = create_camera(
+            resource_manager.clone(),
+            Vector3::new(0.0, 6.0, -20.0),
+            &mut scene.graph,
+        )
+        .await;
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new()))
+            .build(&mut scene.graph);
+
+        // Geometry and materials are shared by all trees, only transforms are different.
+        let trunk = Arc::new(Mutex::new(SurfaceData::make_cylinder(
+            12,
+            0.15,
+            1.5,
+            false,
+            &Matrix4::identity(),
+        )));
+        let foliage = Arc::new(Mutex::new(SurfaceData::make_cone(
+            16,
+            1.0,
+            3.0,
+            &Matrix4::new_translation(&Vector3::new(0.0, 1.0, 0.0)),
+        )));
+        let bark = make_material(Color::opaque(110, 80, 50));
+        let leaves = make_material(Color::opaque(40, 120, 50));
+
+        let mut rng = thread_rng();
+        let mut roots = Vec::new();
+        for x in 0..TREES_PER_SIDE {
+            for z in 0..TREES_PER_SIDE {
+                let position = Vector3::new(
+                    (x as f32 - TREES_PER_SIDE as f32 * 0.5) * TREE_SPACING
+                        + rng.gen_range(-1.0..1.0),
+                    0.0,
+                    z as f32 * TREE_SPACING + rng.gen_range(-1.0..1.0),
+                );
+                let scale = rng.gen_range(0.8..1.3);
+
+                let root = PivotBuilder::new(
+                    BaseBuilder::new()
+                        .with_name("Tree")
+                        .with_local_transform(
+                            TransformBuilder::new()
+                                .with_local_position(position)
+                                .with_local_rotation(UnitQuaternion::from_axis_angle(
+                                    &Vector3::y_axis(),
+                                    rng.gen_range(0.0..std::f32::consts::TAU),
+                                ))
+                                .with_local_scale(Vector3::repeat(scale))
+                                .build(),
+                        )
+                        .with_children(&[MeshBuilder::new(BaseBuilder::new())
+                            .with_surfaces(vec![
+                                SurfaceBuilder::new(trunk.clone())
+                                    .with_material(bark.clone())
+                                    .build(),
+                                SurfaceBuilder::new(foliage.clone())
+                                    .with_material(leaves.clone())
+                                    .build(),
+                            ])
+                            .build(&mut scene.graph)]),
+                )
+                .build(&mut scene.graph);
+
+                roots.push(root);
+            }
+        }
+
+        // Bake a single tree, all trees look the same from far away. Normally this is done in
+        // the editor, and atlases are saved with the scene.
+        let input = ImpostorInputData::from_graph(
+            &mut scene.graph,
+            roots[0],
+            ImpostorSettings {
+                frame_count: 8,
+                frame_size: 128,
+                lod_distance: 0.1,
+                blend_frames: true,
+            },
+            |_, _| true,
+            CancellationToken::new(),
+        )
+        .unwrap();
+        let result =
+            impostor::bake(input, CancellationToken::new(), ProgressIndicator::new()).unwrap();
+
+        let trees = roots
+            .into_iter()
+            .map(|root| {
+                let impostor = result.apply(&mut scene.graph, root);
+                Tree {
+                    root,
+                    impostor,
+                    lod_group: scene.graph[root].lod_group().cloned().unwrap(),
+                }
+            })
+            .collect();
+
+        Self {
+            scene,
+            camera,
+            trees,
+        }
+    }
+}
+
+struct Game {
+    scene: Handle<Scene>,
+    camera: Handle<Node>,
+    trees: Vec<Tree>,
+    impostors_enabled: bool,
+    debug_text: Handle<UiNode>,
+}
+
+impl Game {
+    fn set_impostors_enabled(&mut self, engine: &mut Engine, enabled: bool) {
+        self.impostors_enabled = enabled;
+        // Without LOD groups trees are always rendered in full detail.
+        let graph = &mut engine.scenes[self.scene].graph;
+        for tree in self.trees.iter() {
+            graph[tree.impostor].set_visibility(enabled);
+            graph[tree.root].set_lod_group(if enabled {
+                Some(tree.lod_group.clone())
+            } else {
+                None
+            });
+        }
+    }
+}
+
+impl GameState for Game {
+    fn init(engine: &mut Engine) -> Self
+    where
+        Self: Sized,
+    {
+        let scene = fyrox::core::futures::executor::block_on(GameSceneLoader::load_with(
+            engine.resource_manager.clone(),
+        ));
+
+        Self {
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut engine.user_interface.build_ctx()),
+            scene: engine.scenes.add(scene.scene),
+            camera: scene.camera,
+            trees: scene.trees,
+            impostors_enabled: true,
+        }
+    }
+
+    fn on_tick(&mut self, engine: &mut Engine, _dt: f32, _: &mut ControlFlow) {
+        let statistics = engine.renderer.get_statistics();
+
+        engine.user_interface.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example 24 - Impostors\nImpostors: {} (press I to toggle), {} trees\n\
+                Use W/S to move the camera\nDraw calls: {}, triangles: {}",
+                if self.impostors_enabled { "On" } else { "Off" },
+                self.trees.len(),
+                statistics.geometry.draw_calls,
+                statistics.geometry.triangles_rendered
+            ),
+        ));
+    }
+
+    fn on_window_event(&mut self, engine: &mut Engine, event: WindowEvent) {
+        if let WindowEvent::KeyboardInput { input, .. } = event {
+            if input.state == ElementState::Pressed {
+                let step = match input.virtual_keycode {
+                    Some(VirtualKeyCode::W) => 5.0,
+                    Some(VirtualKeyCode::S) => -5.0,
+                    Some(VirtualKeyCode::I) => {
+                        let enabled = !self.impostors_enabled;
+                        self.set_impostors_enabled(engine, enabled);
+                        0.0
+                    }
+                    _ => 0.0,
+                };
+                engine.scenes[self.scene].graph[self.camera]
+                    .local_transform_mut()
+                    .offset(Vector3::new(0.0, 0.0, step));
+            }
+        }
+    }
+}
+
+fn main() {
+    Framework::<Game>::new()
+        .unwrap()
+        .title("Example 24 - Impostors")
+        .run();
+}
@@ -0,0 +1,310 @@
+//! Example 22. Light scattering presets.
+//!
+//! Difficulty: Easy.
+//!
+//! This example shows spot lights with every light scattering preset side by side. Press `A` to
+//! toggle anisotropy of scattering to see how it changes the look of light volumes, press `P` to
+//! render every preset off-screen and save the results side by side in a single image, the image
+//! is used in the documentation of the presets.
+
+pub mod shared;
+
+use crate::shared::create_camera;
+use fyrox::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        color::Color,
+        parking_lot::Mutex,
+        pool::Handle,
+    },
+    engine::{framework::prelude::*, resource_manager::ResourceManager, Engine},
+    event::{ElementState, VirtualKeyCode, WindowEvent},
+    event_loop::ControlFlow,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    scene::{
+        base::BaseBuilder,
+        graph::Graph,
+        light::{spot::SpotLightBuilder, BaseLight, BaseLightBuilder, ScatterPreset},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder,
+        },
+        node::Node,
+        reflection_probe::ReflectionProbeBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::sync::Arc;
+
+/// Presets in the order they are shown, custom preset keeps default parameters of a light.
+const PRESETS: [ScatterPreset; 5] = [
+    ScatterPreset::Custom,
+    ScatterPreset::Clear,
+    ScatterPreset::Haze,
+    ScatterPreset::Fog,
+    ScatterPreset::Dust,
+];
+
+/// Distance between lights, it is large enough for neighbour lights to stay out of view of the
+/// probe.
+const SPACING: f32 = 10.0;
+
+/// Resolution of a single image of a preset.
+const IMAGE_SIZE: u32 = 256;
+
+const IMAGE_PATH: &str = "examples/screenshots/light_scattering.png";
+
+fn make_box(graph: &mut Graph, position: Vector3<f32>, size: Vector3<f32>) -> Handle<Node> {
+    MeshBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(position)
+                .build(),
+        ),
+    )
+    .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+        SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&size)),
+    )))
+    .build()])
+    .build(graph)
+}
+
+fn light_position(index: usize) -> Vector3<f32> {
+    Vector3::new(
+        (index as f32 - (PRESETS.len() - 1) as f32 * 0.5) * SPACING,
+        5.0,
+        0.0,
+    )
+}
+
+struct GameSceneLoader {
+    scene: Scene,
+    lights: Vec<Handle<Node>>,
+    probe: Handle<Node>,
+}
+
+impl GameSceneLoader {
+    async fn load_with(resource_manager: ResourceManager) -> Self {
+        let mut scene = Scene::new();
+
+        scene.ambient_lighting_color = Color::opaque(20, 20, 20);
+
+        create_camera(
+            resource_manager.clone(),
+            Vector3::new(0.0, 4.0, -32.0),
+            &mut scene.graph,
+        )
+        .await;
+
+        let graph = &mut scene.graph;
+
+        // Floor and a back wall to have something to lit.
+        make_box(graph, Vector3::default(), Vector3::new(60.0, 0.2, 20.0));
+        make_box(
+            graph,
+            Vector3::new(0.0, 5.0, 6.0),
+            Vector3::new(60.0, 10.0, 0.2),
+        );
+
+        let lights = PRESETS
+            .iter()
+            .enumerate()
+            .map(|(index, &preset)| {
+                // A pillar in front of each light to show shadows in light volumes.
+                make_box(
+                    graph,
+                    light_position(index) + Vector3::new(0.8, -4.0, 0.0),
+                    Vector3::new(0.5, 2.0, 0.5),
+                );
+
+                SpotLightBuilder::new(
+                    BaseLightBuilder::new(
+                        BaseBuilder::new().with_local_transform(
+                            TransformBuilder::new()
+                                .with_local_position(light_position(index))
+                                .with_local_rotation(UnitQuaternion::from_axis_angle(
+                                    &Vector3::x_axis(),
+                                    20.0f32.to_radians(),
+                                ))
+                                .build(),
+                        ),
+                    )
+                    .with_color(Color::opaque(255, 240, 220))
+                    .with_scatter_preset(preset),
+                )
+                .with_distance(12.0)
+                .with_hotspot_cone_angle(40.0f32.to_radians())
+                .build(graph)
+            })
+            .collect();
+
+        // The probe is used only to render presets off-screen.
+        let probe = ReflectionProbeBuilder::new(BaseBuilder::new())
+            .with_resolution(IMAGE_SIZE)
+            .build(graph);
+
+        Self {
+            scene,
+            lights,
+            probe,
+        }
+    }
+}
+
+struct Game {
+    scene: Handle<Scene>,
+    lights: Vec<Handle<Node>>,
+    probe: Handle<Node>,
+    anisotropy: bool,
+    debug_text: Handle<UiNode>,
+    status: String,
+}
+
+impl Game {
+    fn set_anisotropy(&mut self, engine: &mut Engine, enabled: bool) {
+        self.anisotropy = enabled;
+        let graph = &mut engine.scenes[self.scene].graph;
+        for (&light, preset) in self.lights.iter().zip(PRESETS) {
+            if let Some(light) = graph[light].query_component_mut::<BaseLight>() {
+                let anisotropy = if enabled {
+                    preset.parameters().map_or(0.0, |p| p.anisotropy)
+                } else {
+                    0.0
+                };
+                light.set_scatter_anisotropy(anisotropy);
+            }
+        }
+    }
+
+    // Renders the +Z face of the probe placed in front of the light and converts it to 8-bit sRGB.
+    fn capture(&self, engine: &mut Engine, index: usize) -> Vec<[u8; 3]> {
+        engine.scenes[self.scene].graph[self.probe]
+            .local_transform_mut()
+            .set_position(light_position(index) + Vector3::new(0.0, -2.0, -7.0));
+        // Global transform of the probe must be updated before rendering.
+        engine.scenes[self.scene].graph.update_hierarchical_data();
+
+        let texture = engine
+            .renderer
+            .capture_reflection_probe(&engine.scenes[self.scene], self.probe)
+            .unwrap();
+        let data = texture.data_ref();
+
+        // Faces of the cube map are stored one after another in +X, -X, +Y, -Y, +Z, -Z order,
+        // every pixel is three floats in linear color space.
+        let face_size = (IMAGE_SIZE * IMAGE_SIZE * 3) as usize;
+        let floats = data
+            .data()
+            .chunks_exact(4)
+            .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .skip(4 * face_size)
+            .take(face_size)
+            .collect::<Vec<_>>();
+
+        floats
+            .chunks_exact(3)
+            .map(|rgb| {
+                let mut pixel = [0; 3];
+                for (out, &value) in pixel.iter_mut().zip(rgb) {
+                    // Simple Reinhard tone mapping, then gamma correction.
+                    let mapped = (value / (1.0 + value)).powf(1.0 / 2.2);
+                    *out = (mapped.clamp(0.0, 1.0) * 255.0) as u8;
+                }
+                pixel
+            })
+            .collect()
+    }
+
+    fn save_image(&mut self, engine: &mut Engine) {
+        let width = IMAGE_SIZE * PRESETS.len() as u32;
+        let mut image = image::RgbImage::new(width, IMAGE_SIZE);
+
+        for index in 0..PRESETS.len() {
+            let pixels = self.capture(engine, index);
+            for (i, pixel) in pixels.into_iter().enumerate() {
+                let x = i as u32 % IMAGE_SIZE;
+                let y = i as u32 / IMAGE_SIZE;
+                // Faces of cube maps are mirrored horizontally.
+                image.put_pixel(
+                    index as u32 * IMAGE_SIZE + IMAGE_SIZE - 1 - x,
+                    y,
+                    image::Rgb(pixel),
+                );
+            }
+        }
+
+        self.status = match image.save(IMAGE_PATH) {
+            Ok(_) => format!("Saved to {}", IMAGE_PATH),
+            Err(e) => format!("Failed to save image: {}", e),
+        };
+    }
+}
+
+impl GameState for Game {
+    fn init(engine: &mut Engine) -> Self
+    where
+        Self: Sized,
+    {
+        let scene = fyrox::core::futures::executor::block_on(GameSceneLoader::load_with(
+            engine.resource_manager.clone(),
+        ));
+
+        Self {
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut engine.user_interface.build_ctx()),
+            scene: engine.scenes.add(scene.scene),
+            lights: scene.lights,
+            probe: scene.probe,
+            anisotropy: true,
+            status: "Press P to save presets side by side".to_owned(),
+        }
+    }
+
+    fn on_tick(&mut self, engine: &mut Engine, _dt: f32, _: &mut ControlFlow) {
+        let presets = PRESETS
+            .iter()
+            .map(|preset| preset.as_ref())
+            .collect::<Vec<&str>>()
+            .join(" | ");
+
+        engine.user_interface.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example 22 - Light Scattering Presets\nPresets (left to right): {}\n\
+                Anisotropy: {} (press A to toggle)\n{}",
+                presets,
+                if self.anisotropy { "On" } else { "Off" },
+                self.status,
+            ),
+        ));
+    }
+
+    fn on_window_event(&mut self, engine: &mut Engine, event: WindowEvent) {
+        if let WindowEvent::KeyboardInput { input, .. } = event {
+            if input.state == ElementState::Pressed {
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::A) => {
+                        let enabled = self.anisotropy;
+                        self.set_anisotropy(engine, !enabled);
+                    }
+                    Some(VirtualKeyCode::P) => self.save_image(engine),
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+fn main() {
+    Framework::<Game>::new()
+        .unwrap()
+        .title("Example 22 - Light Scattering Presets")
+        .run();
+}
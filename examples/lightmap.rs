@@ -30,8 +30,7 @@ use fyrox::{
         window::{WindowBuilder, WindowMessage, WindowTitle},
         BuildContext, HorizontalAlignment, Thickness, UiNode, VerticalAlignment,
     },
-    scene::light::BaseLight,
-    scene::{node::Node, Scene, SceneLoader},
+    scene::{base::Mobility, node::Node, Scene, SceneLoader},
     utils::{
         lightmap::{CancellationToken, Lightmap, ProgressIndicator, ProgressStage},
         log::{Log, MessageKind},
@@ -241,20 +240,21 @@ fn create_scene_async(
                     .instantiate(&mut scene)
                     .root;
 
+                // Only static meshes and lights are baked into a lightmap.
+                let nodes = scene.graph.traverse_handle_iter(root).collect::<Vec<_>>();
+                for node in nodes {
+                    scene.graph[node].set_mobility(Mobility::Static);
+                }
+
                 if let Ok(lightmap) =
                     Lightmap::new(&mut scene, 64, cancellation_token, progress_indicator)
                 {
                     lightmap
                         .save("examples/data/lightmaps/", resource_manager)
                         .unwrap();
+                    // Baked lights won't be applied dynamically.
                     scene.set_lightmap(lightmap).unwrap();
 
-                    for node in scene.graph.linear_iter_mut() {
-                        if node.query_component_ref::<BaseLight>().is_some() {
-                            node.set_visibility(false);
-                        }
-                    }
-
                     let mut visitor = Visitor::new();
                     scene.save("Scene", &mut visitor).unwrap();
                     visitor.save_binary(LIGHTMAP_SCENE_PATH).unwrap();
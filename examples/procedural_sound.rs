@@ -0,0 +1,172 @@
+//! Example 21. Procedural sound.
+//!
+//! Difficulty: Easy.
+//!
+//! This example shows how to synthesize a sound at runtime. A sine wave is generated by a callback
+//! of a procedural sound buffer, the sound engine pulls samples from it in small portions exactly
+//! when they are needed. Frequency of the sine follows the velocity of a moving node, so the faster
+//! the node moves, the higher the tone is.
+
+pub mod shared;
+
+use crate::shared::create_camera;
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Vector3},
+        color::Color,
+        parking_lot::Mutex,
+        pool::Handle,
+    },
+    engine::{framework::prelude::*, resource_manager::ResourceManager, Engine},
+    event_loop::ControlFlow,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    scene::{
+        base::BaseBuilder,
+        light::{point::PointLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder,
+        },
+        node::Node,
+        pivot::PivotBuilder,
+        sound::{ProceduralBuffer, SoundBufferResource, SoundBuilder, Status, SAMPLE_RATE},
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::sync::Arc;
+
+const BASE_FREQUENCY: f32 = 110.0;
+const FREQUENCY_PER_SPEED: f32 = 60.0;
+
+struct GameSceneLoader {
+    scene: Scene,
+    cube: Handle<Node>,
+}
+
+impl GameSceneLoader {
+    async fn load_with(resource_manager: ResourceManager, frequency: Arc<Mutex<f32>>) -> Self {
+        let mut scene = Scene::new();
+
+        scene.ambient_lighting_color = Color::opaque(200, 200, 200);
+
+        // Camera has a listener attached to it.
+        create_camera(
+            resource_manager.clone(),
+            Vector3::new(0.0, 2.0, -8.0),
+            &mut scene.graph,
+        )
+        .await;
+
+        PointLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 8.0, -4.0))
+                    .build(),
+            ),
+        ))
+        .with_radius(20.0)
+        .build(&mut scene.graph);
+
+        // The callback is called from the sound thread, the frequency is shared with the game.
+        let mut phase = 0.0f32;
+        let buffer = ProceduralBuffer::from_callback(SAMPLE_RATE as usize, 1, move |samples| {
+            let step = *frequency.lock() * std::f32::consts::TAU / SAMPLE_RATE as f32;
+            for sample in samples.iter_mut() {
+                *sample = 0.5 * phase.sin();
+                phase = (phase + step) % std::f32::consts::TAU;
+            }
+            samples.len()
+        })
+        .unwrap();
+
+        let sound = SoundBuilder::new(BaseBuilder::new())
+            .with_buffer(Some(SoundBufferResource::new_procedural(buffer)))
+            .with_status(Status::Playing)
+            .with_radius(3.0)
+            .build(&mut scene.graph);
+
+        let mesh = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+                SurfaceData::make_cube(Matrix4::identity()),
+            )))
+            .build()])
+            .build(&mut scene.graph);
+
+        // The sound is attached to the cube, so it moves together with the cube.
+        let cube = PivotBuilder::new(BaseBuilder::new().with_children(&[mesh, sound]))
+            .build(&mut scene.graph);
+
+        Self { scene, cube }
+    }
+}
+
+struct Game {
+    scene: Handle<Scene>,
+    cube: Handle<Node>,
+    frequency: Arc<Mutex<f32>>,
+    time: f32,
+    debug_text: Handle<UiNode>,
+}
+
+impl GameState for Game {
+    fn init(engine: &mut Engine) -> Self
+    where
+        Self: Sized,
+    {
+        let frequency = Arc::new(Mutex::new(BASE_FREQUENCY));
+
+        let scene = fyrox::core::futures::executor::block_on(GameSceneLoader::load_with(
+            engine.resource_manager.clone(),
+            frequency.clone(),
+        ));
+
+        Self {
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut engine.user_interface.build_ctx()),
+            scene: engine.scenes.add(scene.scene),
+            cube: scene.cube,
+            frequency,
+            time: 0.0,
+        }
+    }
+
+    fn on_tick(&mut self, engine: &mut Engine, dt: f32, _: &mut ControlFlow) {
+        let scene = &mut engine.scenes[self.scene];
+
+        // Move the cube back and forth with varying speed.
+        self.time += dt;
+        let old_position = **scene.graph[self.cube].local_transform().position();
+        let new_position = Vector3::new(4.0 * (self.time * 0.7).sin().powi(3), 1.0, 0.0);
+        scene.graph[self.cube]
+            .local_transform_mut()
+            .set_position(new_position);
+
+        // Frequency follows the speed of the cube.
+        let speed = (new_position - old_position).norm() / dt;
+        *self.frequency.lock() = BASE_FREQUENCY + speed * FREQUENCY_PER_SPEED;
+
+        engine.user_interface.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example 21 - Procedural Sound\nSpeed: {:.2}\nFrequency: {:.1} Hz\nFPS: {}",
+                speed,
+                *self.frequency.lock(),
+                engine.renderer.get_statistics().frames_per_second
+            ),
+        ));
+    }
+}
+
+fn main() {
+    Framework::<Game>::new()
+        .unwrap()
+        .title("Example 21 - Procedural Sound")
+        .run();
+}
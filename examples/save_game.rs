@@ -0,0 +1,208 @@
+//! Example 25. Save games.
+//!
+//! Difficulty: Easy.
+//!
+//! This example shows how to make save games using scene state snapshots. The level is saved to a
+//! file as usual, then the game changes the level - the player moves and loses some health, the
+//! door gets opened. Only this persistent state is saved into a small save game, which is then
+//! applied to a freshly loaded level. It does not open a window, run it like so:
+//! `cargo run --example save_game`.
+
+use fyrox::{
+    core::{
+        algebra::Vector3,
+        futures::executor::block_on,
+        inspect::{Inspect, PropertyInfo},
+        pool::Handle,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    engine::{resource_manager::ResourceManager, SerializationContext},
+    scene::{
+        base::BaseBuilder,
+        node::{Node, TypeUuidProvider},
+        pivot::PivotBuilder,
+        transform::TransformBuilder,
+        Scene, SceneLoader,
+    },
+    script::{Script, ScriptTrait},
+};
+use std::{path::Path, sync::Arc};
+
+#[derive(Visit, Inspect, Debug, Clone)]
+struct Player {
+    health: f32,
+    // Comes from the level, it is not a part of save games.
+    max_health: f32,
+}
+
+impl TypeUuidProvider for Player {
+    fn type_uuid() -> Uuid {
+        uuid!("0c3e5b7d-9f21-4a46-8d8b-1e3f5a7c9b02")
+    }
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self {
+            health: 100.0,
+            max_health: 100.0,
+        }
+    }
+}
+
+impl ScriptTrait for Player {
+    fn persistent_fields(&self) -> &'static [&'static str] {
+        &[Self::HEALTH]
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn plugin_uuid(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+#[derive(Visit, Inspect, Debug, Clone, Default)]
+struct Door {
+    is_open: bool,
+}
+
+impl TypeUuidProvider for Door {
+    fn type_uuid() -> Uuid {
+        uuid!("7e9a1c3f-5b2d-4f80-a6c4-3d5f7b9e1a24")
+    }
+}
+
+impl ScriptTrait for Door {
+    fn persistent_fields(&self) -> &'static [&'static str] {
+        &[Self::IS_OPEN]
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn plugin_uuid(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+fn create_level() -> Scene {
+    let mut scene = Scene::new();
+
+    // Position of the player is saved, because the node is persistent.
+    PivotBuilder::new(
+        BaseBuilder::new()
+            .with_name("Player")
+            .with_persistent(true)
+            .with_script(Script::new(Player::default())),
+    )
+    .build(&mut scene.graph);
+
+    PivotBuilder::new(
+        BaseBuilder::new()
+            .with_name("Door")
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, 10.0))
+                    .build(),
+            )
+            .with_script(Script::new(Door::default())),
+    )
+    .build(&mut scene.graph);
+
+    scene
+}
+
+fn load_level(
+    path: &Path,
+    serialization_context: Arc<SerializationContext>,
+    resource_manager: ResourceManager,
+) -> Scene {
+    block_on(async {
+        SceneLoader::from_file(path, serialization_context)
+            .await
+            .unwrap()
+            .finish(resource_manager)
+            .await
+    })
+}
+
+fn find(scene: &Scene, name: &str) -> Handle<Node> {
+    scene.graph.find_by_name_from_root(name)
+}
+
+fn script<'a, T: ScriptTrait>(scene: &'a Scene, name: &str) -> &'a T {
+    scene.graph[find(scene, name)]
+        .script
+        .as_ref()
+        .and_then(|s| s.cast::<T>())
+        .unwrap()
+}
+
+fn script_mut<'a, T: ScriptTrait>(scene: &'a mut Scene, name: &str) -> &'a mut T {
+    let handle = find(scene, name);
+    scene.graph[handle]
+        .script
+        .as_mut()
+        .and_then(|s| s.cast_mut::<T>())
+        .unwrap()
+}
+
+fn main() {
+    let serialization_context = Arc::new(SerializationContext::new());
+    serialization_context
+        .script_constructors
+        .add::<Player, Player, _>("Player");
+    serialization_context
+        .script_constructors
+        .add::<Door, Door, _>("Door");
+    let resource_manager = ResourceManager::new(serialization_context.clone());
+
+    // Normally levels are made in the editor.
+    let level_path = std::env::temp_dir().join("save_game_level.rgs");
+    create_level().save_to_file(&level_path).unwrap();
+
+    // Play the game for a while.
+    let mut scene = load_level(
+        &level_path,
+        serialization_context.clone(),
+        resource_manager.clone(),
+    );
+    let player = find(&scene, "Player");
+    scene.graph[player]
+        .local_transform_mut()
+        .set_position(Vector3::new(1.0, 0.0, 9.0));
+    script_mut::<Player>(&mut scene, "Player").health = 42.0;
+    script_mut::<Door>(&mut scene, "Door").is_open = true;
+
+    let save_game = scene.capture_state().unwrap();
+    println!("Save game size: {} bytes", save_game.len());
+
+    // Load the level again and apply the save game.
+    let mut scene = load_level(&level_path, serialization_context.clone(), resource_manager);
+    let report = scene
+        .restore_state(save_game, serialization_context)
+        .unwrap();
+    println!(
+        "Restored state of {} nodes, {} nodes are missing",
+        report.restored,
+        report.missing.len()
+    );
+
+    let player = find(&scene, "Player");
+    assert_eq!(
+        **scene.graph[player].local_transform().position(),
+        Vector3::new(1.0, 0.0, 9.0)
+    );
+    assert_eq!(script::<Player>(&scene, "Player").health, 42.0);
+    assert_eq!(script::<Player>(&scene, "Player").max_health, 100.0);
+    assert!(script::<Door>(&scene, "Door").is_open);
+
+    println!("The state was restored successfully!");
+
+    std::fs::remove_file(&level_path).unwrap();
+}
@@ -0,0 +1,84 @@
+//! Example - Saving a procedurally generated scene.
+//!
+//! Difficulty: Easy.
+//!
+//! This example shows how a tool (a level generator, for example) could build a scene in code and
+//! save it to a file that could be opened in the editor. It does not open a window, run it like
+//! so: `cargo run --example save_scene -- my_level.rgs`.
+
+use fyrox::{
+    core::algebra::{Matrix4, Vector3},
+    core::parking_lot::Mutex,
+    scene::{
+        base::BaseBuilder,
+        light::{point::PointLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder,
+        },
+        pivot::PivotBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::{path::PathBuf, sync::Arc};
+
+fn generate_level() -> Scene {
+    let mut scene = Scene::new();
+
+    // A row of pillars, every pillar is a separate mesh with its own surface data.
+    let pillars = (0..10)
+        .map(|i| {
+            MeshBuilder::new(
+                BaseBuilder::new()
+                    .with_name(format!("Pillar{}", i))
+                    .with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(Vector3::new(i as f32 * 2.0, 0.0, 0.0))
+                            .build(),
+                    ),
+            )
+            .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+                SurfaceData::make_cylinder(16, 0.25, 3.0, true, &Matrix4::identity()),
+            )))
+            .build()])
+            .build(&mut scene.graph)
+        })
+        .collect::<Vec<_>>();
+
+    PivotBuilder::new(
+        BaseBuilder::new()
+            .with_name("Pillars")
+            .with_children(&pillars),
+    )
+    .build(&mut scene.graph);
+
+    PointLightBuilder::new(BaseLightBuilder::new(
+        BaseBuilder::new().with_name("Light").with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(10.0, 5.0, 0.0))
+                .build(),
+        ),
+    ))
+    .with_radius(20.0)
+    .build(&mut scene.graph);
+
+    scene
+}
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("generated_level.rgs"));
+
+    let scene = generate_level();
+
+    match scene.save_to_file(&path) {
+        Ok(_) => println!("Scene was saved to {}", path.display()),
+        Err(e) => {
+            eprintln!("Unable to save scene to {}. Reason: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -6,28 +6,74 @@ use fyrox::{
     },
     core::{
         algebra::{UnitQuaternion, Vector3},
+        color::Color,
         inspect::{Inspect, PropertyInfo},
         pool::Handle,
         uuid::uuid,
         visitor::prelude::*,
     },
     engine::resource_manager::ResourceManager,
+    gui::inspector::{FieldKind, PropertyChanged},
+    handle_object_property_changed,
     resource::model::Model,
     scene::{
         node::{Node, TypeUuidProvider},
         Scene,
     },
     script::{ScriptContext, ScriptTrait},
+    utils::navmesh::NavmeshAgent,
 };
 
-#[derive(Visit, Inspect, Default, Debug)]
+#[derive(Visit, Inspect, Debug)]
 pub struct Bot {
     collider: Handle<Node>,
+
+    // Bot decides to follow the target by itself, there is no point to edit the flag.
+    #[inspect(
+        read_only,
+        description = "Whether the bot is chasing its target or not."
+    )]
     follow_target: bool,
+
     target: Vector3<f32>,
 
+    #[inspect(
+        display_name = "Sight Radius (m)",
+        min_value = 0.0,
+        max_value = 20.0,
+        step = 0.1,
+        precision = 2,
+        description = "Distance at which the bot notices its target."
+    )]
+    #[visit(optional)] // Backward compatibility
+    sight_radius: f32,
+
+    #[inspect(
+        min_value = 0.1,
+        max_value = 5.0,
+        step = 0.1,
+        precision = 2,
+        description = "Distance at which the bot stops and starts to attack."
+    )]
+    #[visit(optional)] // Backward compatibility
+    attack_distance: f32,
+
+    #[inspect(
+        min_value = 0.0,
+        max_value = 10.0,
+        step = 0.05,
+        precision = 2,
+        description = "Movement speed in meters per second."
+    )]
+    #[visit(optional)] // Backward compatibility
+    speed: f32,
+
     #[inspect(skip)]
     machine: Option<BotAnimationMachine>,
+
+    #[visit(skip)]
+    #[inspect(skip)]
+    path: Vec<Vector3<f32>>,
 }
 
 impl Clone for Bot {
@@ -36,8 +82,27 @@ impl Clone for Bot {
             collider: self.collider,
             follow_target: self.follow_target,
             target: self.target,
+            sight_radius: self.sight_radius,
+            attack_distance: self.attack_distance,
+            speed: self.speed,
             // Animation machine cannot be cloned, instead it will be re-created on demand.
             machine: None,
+            path: Default::default(),
+        }
+    }
+}
+
+impl Default for Bot {
+    fn default() -> Self {
+        Self {
+            collider: Default::default(),
+            follow_target: false,
+            target: Default::default(),
+            sight_radius: 1.5,
+            attack_distance: 0.6,
+            speed: 0.9,
+            machine: None,
+            path: Default::default(),
         }
     }
 }
@@ -49,6 +114,16 @@ impl TypeUuidProvider for Bot {
 }
 
 impl ScriptTrait for Bot {
+    fn on_property_changed(&mut self, args: &PropertyChanged) -> bool {
+        handle_object_property_changed!(self, args,
+            Self::COLLIDER => collider,
+            Self::TARGET => target,
+            Self::SIGHT_RADIUS => sight_radius,
+            Self::ATTACK_DISTANCE => attack_distance,
+            Self::SPEED => speed
+        )
+    }
+
     fn on_init(&mut self, context: ScriptContext) {
         let ScriptContext {
             scene,
@@ -91,7 +166,8 @@ impl ScriptTrait for Bot {
             scene, node, dt, ..
         } = context;
 
-        let attack_distance = 0.6;
+        let attack_distance = self.attack_distance;
+        let sight_radius = self.sight_radius;
 
         // Simple AI - follow target by a straight line.
         let self_position = node.global_position();
@@ -100,10 +176,46 @@ impl ScriptTrait for Bot {
         // Distance to target.
         let distance = direction.norm();
 
-        if distance != 0.0 && distance < 1.5 {
+        if distance != 0.0 && distance < sight_radius {
             self.follow_target = true;
         }
 
+        // Show what the bot "thinks" - its sight radius and a path to the target over the navmesh
+        // (if any). Zero duration means that the shapes will be visible for one frame only.
+        scene.debug_draw.draw_sphere(
+            self_position,
+            sight_radius,
+            if self.follow_target {
+                Color::RED
+            } else {
+                Color::GREEN
+            },
+            0.0,
+        );
+        if let Some(navmesh) = scene.navmeshes.at_mut(0) {
+            let mut agent = NavmeshAgent::new();
+            if agent
+                .calculate_path(navmesh, self_position, self.target)
+                .is_ok()
+            {
+                self.path.clear();
+                self.path.extend_from_slice(agent.path());
+            }
+        }
+        for segment in self.path.windows(2) {
+            scene
+                .debug_draw
+                .draw_arrow(segment[0], segment[1], Color::opaque(255, 255, 0), 0.0);
+        }
+        if self.follow_target {
+            scene.debug_draw.draw_text_3d(
+                self_position + Vector3::new(0.0, 2.0, 0.0),
+                format!("Distance: {:.2}", distance),
+                Color::WHITE,
+                0.0,
+            );
+        }
+
         if self.follow_target && distance != 0.0 {
             let rigid_body = node.as_rigid_body_mut();
 
@@ -118,7 +230,7 @@ impl ScriptTrait for Bot {
             // Move only if we're far enough from the target.
             if distance > attack_distance {
                 // Normalize direction vector and scale it by movement speed.
-                let xz_velocity = direction.scale(1.0 / distance).scale(0.9);
+                let xz_velocity = direction.scale(1.0 / distance).scale(self.speed);
 
                 let new_velocity =
                     Vector3::new(xz_velocity.x, rigid_body.lin_vel().y, xz_velocity.z);
@@ -1,4 +1,4 @@
-use crate::bot::Bot;
+use crate::{bot::Bot, walker::GridWalker};
 use fyrox::{
     core::{
         algebra::{UnitQuaternion, Vector2, Vector3},
@@ -33,6 +33,7 @@ use fyrox::{
 use std::{cell::RefCell, rc::Rc};
 
 mod bot;
+mod walker;
 
 pub struct GamePlugin {
     scene: Handle<Scene>,
@@ -117,6 +118,7 @@ impl Plugin for GamePlugin {
         scripts.add::<GamePlugin, Player, &str>("Player");
         scripts.add::<GamePlugin, Jumper, &str>("Jumper");
         scripts.add::<GamePlugin, Bot, &str>("Bot");
+        scripts.add::<GamePlugin, GridWalker, &str>("Grid Walker");
     }
 
     fn on_standalone_init(&mut self, context: PluginContext) {
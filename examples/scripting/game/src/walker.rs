@@ -0,0 +1,234 @@
+//! A script that walks its node over a big grid with obstacles to random destinations. Path
+//! search over such grid is too slow to be done in a single frame, so it is done on a worker
+//! thread using the task pool of the engine and the found path is applied on the main thread,
+//! when it is ready.
+
+use crate::{GamePlugin, Uuid};
+use fyrox::{
+    core::{
+        algebra::Vector3,
+        color::Color,
+        inspect::{Inspect, PropertyInfo},
+        uuid::uuid,
+        visitor::prelude::*,
+    },
+    engine::task::{TaskHandle, TaskResult},
+    gui::inspector::PropertyChanged,
+    handle_object_property_changed,
+    rand::{thread_rng, Rng},
+    scene::node::TypeUuidProvider,
+    script::{ScriptContext, ScriptTrait},
+    utils::astar::{PathFinder, PathVertex},
+};
+
+#[derive(Visit, Inspect, Debug)]
+pub struct GridWalker {
+    #[inspect(
+        min_value = 2.0,
+        max_value = 256.0,
+        description = "Amount of cells of the grid along each axis."
+    )]
+    grid_size: u32,
+
+    #[inspect(
+        min_value = 0.1,
+        max_value = 10.0,
+        step = 0.1,
+        precision = 2,
+        description = "Size of a grid cell in meters."
+    )]
+    cell_size: f32,
+
+    #[inspect(
+        min_value = 0.0,
+        max_value = 10.0,
+        step = 0.05,
+        precision = 2,
+        description = "Movement speed in meters per second."
+    )]
+    speed: f32,
+
+    #[visit(skip)]
+    #[inspect(skip)]
+    task: Option<TaskHandle>,
+
+    #[visit(skip)]
+    #[inspect(skip)]
+    path: Vec<Vector3<f32>>,
+}
+
+impl Clone for GridWalker {
+    fn clone(&self) -> Self {
+        Self {
+            grid_size: self.grid_size,
+            cell_size: self.cell_size,
+            speed: self.speed,
+            // The task belongs to the original node, a copy will search its own path.
+            task: None,
+            path: Default::default(),
+        }
+    }
+}
+
+impl Default for GridWalker {
+    fn default() -> Self {
+        Self {
+            grid_size: 64,
+            cell_size: 0.5,
+            speed: 2.0,
+            task: None,
+            path: Default::default(),
+        }
+    }
+}
+
+impl TypeUuidProvider for GridWalker {
+    fn type_uuid() -> Uuid {
+        uuid!("3b8f4d2a-6c1e-4f7b-9a5d-0e2c8b71f964")
+    }
+}
+
+// Walls with a single passage in each of them.
+fn is_obstacle(x: u32, z: u32) -> bool {
+    x % 8 == 4 && (z + x * 3) % 16 != 0
+}
+
+// Runs on a worker thread, so it must not touch the scene - everything it needs is passed in.
+fn find_path(
+    grid_size: u32,
+    cell_size: f32,
+    from: Vector3<f32>,
+    to: Vector3<f32>,
+) -> Vec<Vector3<f32>> {
+    let index = |x: u32, z: u32| (z * grid_size + x) as usize;
+
+    let mut vertices = Vec::with_capacity((grid_size * grid_size) as usize);
+    for z in 0..grid_size {
+        for x in 0..grid_size {
+            vertices.push(PathVertex::new(Vector3::new(
+                x as f32 * cell_size,
+                from.y,
+                z as f32 * cell_size,
+            )));
+        }
+    }
+
+    let mut path_finder = PathFinder::new();
+    path_finder.set_vertices(vertices);
+
+    for z in 0..grid_size {
+        for x in 0..grid_size {
+            if is_obstacle(x, z) {
+                continue;
+            }
+            if x + 1 < grid_size && !is_obstacle(x + 1, z) {
+                path_finder.link_bidirect(index(x, z), index(x + 1, z));
+            }
+            if z + 1 < grid_size && !is_obstacle(x, z + 1) {
+                path_finder.link_bidirect(index(x, z), index(x, z + 1));
+            }
+        }
+    }
+
+    let mut path = Vec::new();
+    if let (Some(from), Some(to)) = (
+        path_finder.get_closest_vertex_to(from),
+        path_finder.get_closest_vertex_to(to),
+    ) {
+        if path_finder.build(from, to, &mut path).is_err() {
+            path.clear();
+        }
+    }
+
+    // Path finder returns the path from the end to the beginning.
+    path.reverse();
+    path
+}
+
+impl ScriptTrait for GridWalker {
+    fn on_property_changed(&mut self, args: &PropertyChanged) -> bool {
+        handle_object_property_changed!(self, args,
+            Self::GRID_SIZE => grid_size,
+            Self::CELL_SIZE => cell_size,
+            Self::SPEED => speed
+        )
+    }
+
+    fn on_update(&mut self, mut context: ScriptContext) {
+        let position = context.node.global_position();
+
+        if self.path.is_empty() {
+            // Search for a new path only if there is no search in progress already.
+            if self.task.is_none() {
+                let mut rng = thread_rng();
+                let (x, z) = loop {
+                    let x = rng.gen_range(0..self.grid_size);
+                    let z = rng.gen_range(0..self.grid_size);
+                    if !is_obstacle(x, z) {
+                        break (x, z);
+                    }
+                };
+                let destination = Vector3::new(
+                    x as f32 * self.cell_size,
+                    position.y,
+                    z as f32 * self.cell_size,
+                );
+
+                let grid_size = self.grid_size;
+                let cell_size = self.cell_size;
+                self.task =
+                    Some(context.spawn_task(move || {
+                        find_path(grid_size, cell_size, position, destination)
+                    }));
+            }
+        } else {
+            let target = self.path[0];
+            let direction = target - position;
+            let distance = direction.norm();
+            let step = self.speed * context.dt;
+
+            let new_position = if distance <= step {
+                self.path.remove(0);
+                target
+            } else {
+                position + direction.scale(step / distance)
+            };
+
+            // The node is expected to be a child of the root, so its local position is the same
+            // as global.
+            context
+                .node
+                .local_transform_mut()
+                .set_position(new_position);
+        }
+
+        for segment in self.path.windows(2) {
+            context.scene.debug_draw.draw_arrow(
+                segment[0],
+                segment[1],
+                Color::opaque(0, 200, 255),
+                0.0,
+            );
+        }
+    }
+
+    fn on_task_complete(&mut self, task: TaskHandle, result: TaskResult, _context: ScriptContext) {
+        if self.task == Some(task) {
+            self.task = None;
+
+            // An empty path means that the destination is unreachable, a new destination will be
+            // picked on the next update.
+            if let Ok(path) = result.downcast::<Vec<Vector3<f32>>>() {
+                self.path = *path;
+            }
+        }
+    }
+
+    fn plugin_uuid(&self) -> Uuid {
+        GamePlugin::type_uuid()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
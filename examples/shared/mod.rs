@@ -587,7 +587,10 @@ impl Player {
         let is_moving = velocity.norm_squared() > 0.0;
 
         //let body = scene.graph[self.body].as_rigid_body_mut();
-        let body = scene.graph[self.body].cast_mut::<RigidBody>().unwrap();
+        let body = scene
+            .graph
+            .try_get_mut_of_type::<RigidBody>(self.body)
+            .unwrap();
 
         let position = **body.local_transform().position();
 
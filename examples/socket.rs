@@ -0,0 +1,180 @@
+//! Example 14. Attachment sockets.
+//!
+//! Difficulty: Easy.
+//!
+//! This example shows how to use attachment sockets of a skinned mesh to attach a prop to a hand
+//! of an animated character.
+
+pub mod shared;
+
+use crate::shared::create_camera;
+use fyrox::{
+    animation::Animation,
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        color::Color,
+        parking_lot::Mutex,
+        pool::Handle,
+    },
+    engine::{framework::prelude::*, resource_manager::ResourceManager, Engine},
+    event_loop::ControlFlow,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    scene::{
+        base::BaseBuilder,
+        light::{point::PointLightBuilder, BaseLightBuilder},
+        mesh::{
+            socket::Socket,
+            surface::{SurfaceBuilder, SurfaceData},
+            Mesh, MeshBuilder,
+        },
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::sync::Arc;
+
+struct GameSceneLoader {
+    scene: Scene,
+    walk_animation: Handle<Animation>,
+}
+
+impl GameSceneLoader {
+    async fn load_with(resource_manager: ResourceManager) -> Self {
+        let mut scene = Scene::new();
+
+        scene.ambient_lighting_color = Color::opaque(200, 200, 200);
+
+        create_camera(
+            resource_manager.clone(),
+            Vector3::new(0.0, 6.0, -12.0),
+            &mut scene.graph,
+        )
+        .await;
+
+        PointLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 12.0, 0.0))
+                    .build(),
+            ),
+        ))
+        .with_radius(20.0)
+        .build(&mut scene.graph);
+
+        let (model_resource, walk_animation_resource) = fyrox::core::futures::join!(
+            resource_manager.request_model("examples/data/mutant/mutant.FBX"),
+            resource_manager.request_model("examples/data/mutant/walk.fbx")
+        );
+
+        let model_handle = model_resource.unwrap().instantiate_geometry(&mut scene);
+
+        scene.graph[model_handle]
+            .local_transform_mut()
+            .set_scale(Vector3::new(0.05, 0.05, 0.05))
+            .set_rotation(UnitQuaternion::from_axis_angle(
+                &Vector3::y_axis(),
+                180.0f32.to_radians(),
+            ));
+
+        let walk_animation = *walk_animation_resource
+            .unwrap()
+            .retarget_animations(model_handle, &mut scene)
+            .get(0)
+            .unwrap();
+
+        // Find skinned mesh of the character and add a socket to it. Usually sockets are added
+        // in the editor, but here we're doing it manually. The only place where we need to know
+        // the name of the bone is the socket itself, the rest of the code uses socket name.
+        let skinned_mesh = scene.graph.find(model_handle, &mut |n| {
+            n.cast::<Mesh>().map_or(false, |m| {
+                m.surfaces().iter().any(|s| !s.bones().is_empty())
+            })
+        });
+        let hand = scene.graph.find_by_name(model_handle, "Mutant:RightHand");
+        scene.graph[skinned_mesh].as_mesh_mut().add_socket(
+            Socket::new("RightHand", hand)
+                .with_position(Vector3::new(0.0, 10.0, 0.0))
+                .with_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::x_axis(),
+                    90.0f32.to_radians(),
+                )),
+        );
+
+        // Create a simple prop - a "sword" made from a stretched cube.
+        let sword = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+                SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                    3.0, 3.0, 60.0,
+                ))),
+            )))
+            .build()])
+            .build(&mut scene.graph);
+
+        // Attach the prop to the hand. The prop becomes a child of the hand bone, which means
+        // that it will be moved together with the bone, without any lag.
+        scene
+            .graph
+            .attach_to_socket(skinned_mesh, "RightHand", sword)
+            .unwrap();
+
+        Self {
+            scene,
+            walk_animation,
+        }
+    }
+}
+
+struct Game {
+    scene: Handle<Scene>,
+    walk_animation: Handle<Animation>,
+    debug_text: Handle<UiNode>,
+}
+
+impl GameState for Game {
+    fn init(engine: &mut Engine) -> Self
+    where
+        Self: Sized,
+    {
+        let scene = fyrox::core::futures::executor::block_on(GameSceneLoader::load_with(
+            engine.resource_manager.clone(),
+        ));
+
+        Self {
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut engine.user_interface.build_ctx()),
+            scene: engine.scenes.add(scene.scene),
+            walk_animation: scene.walk_animation,
+        }
+    }
+
+    fn on_tick(&mut self, engine: &mut Engine, _dt: f32, _: &mut ControlFlow) {
+        let scene = &mut engine.scenes[self.scene];
+
+        scene
+            .animations
+            .get_mut(self.walk_animation)
+            .get_pose()
+            .apply(&mut scene.graph);
+
+        engine.user_interface.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example 14 - Attachment Sockets\nFPS: {}",
+                engine.renderer.get_statistics().frames_per_second
+            ),
+        ));
+    }
+}
+
+fn main() {
+    Framework::<Game>::new()
+        .unwrap()
+        .title("Example 14 - Attachment Sockets")
+        .run();
+}
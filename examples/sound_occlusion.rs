@@ -0,0 +1,186 @@
+//! Example 15. Sound occlusion.
+//!
+//! Difficulty: Easy.
+//!
+//! This example shows how to make sounds occluded by scene geometry. A looping sound moves around
+//! a wall, and when the wall is between the sound and the listener, the sound becomes muffled
+//! and quieter.
+
+pub mod shared;
+
+use crate::shared::create_camera;
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Vector3},
+        color::Color,
+        parking_lot::Mutex,
+        pool::Handle,
+    },
+    engine::{framework::prelude::*, resource_manager::ResourceManager, Engine},
+    event_loop::ControlFlow,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    scene::{
+        base::BaseBuilder,
+        collider::{ColliderBuilder, ColliderShape},
+        light::{point::PointLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder,
+        },
+        node::Node,
+        rigidbody::{RigidBodyBuilder, RigidBodyType},
+        sound::{context::OcclusionSettings, Sound, SoundBuilder, Status},
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::sync::Arc;
+
+struct GameSceneLoader {
+    scene: Scene,
+    sound: Handle<Node>,
+}
+
+impl GameSceneLoader {
+    async fn load_with(resource_manager: ResourceManager) -> Self {
+        let mut scene = Scene::new();
+
+        scene.ambient_lighting_color = Color::opaque(200, 200, 200);
+
+        // Camera has a listener attached to it.
+        create_camera(
+            resource_manager.clone(),
+            Vector3::new(0.0, 2.0, -8.0),
+            &mut scene.graph,
+        )
+        .await;
+
+        PointLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 8.0, -4.0))
+                    .build(),
+            ),
+        ))
+        .with_radius(20.0)
+        .build(&mut scene.graph);
+
+        // Create a wall. Occlusion works only with colliders, the mesh is needed only to see the wall.
+        let wall_mesh = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+                SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                    6.0, 4.0, 0.2,
+                ))),
+            )))
+            .build()])
+            .build(&mut scene.graph);
+        let wall_collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(3.0, 2.0, 0.1))
+            .build(&mut scene.graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 2.0, 0.0))
+                        .build(),
+                )
+                .with_children(&[wall_mesh, wall_collider]),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(&mut scene.graph);
+
+        // Make occlusion a bit more pronounced than by default.
+        scene
+            .graph
+            .sound_context
+            .set_occlusion_settings(OcclusionSettings {
+                attenuation: 0.7,
+                low_pass: 0.9,
+                ..Default::default()
+            });
+
+        let buffer = resource_manager
+            .request_sound_buffer("examples/data/sounds/FootStep_shoe_stone_step1.wav")
+            .await
+            .unwrap();
+
+        let sound = SoundBuilder::new(BaseBuilder::new())
+            .with_buffer(Some(buffer))
+            .with_looping(true)
+            .with_status(Status::Playing)
+            .with_radius(3.0)
+            // Occlusion is disabled by default, so it must be enabled explicitly.
+            .with_occlusion_enabled(true)
+            .build(&mut scene.graph);
+
+        Self { scene, sound }
+    }
+}
+
+struct Game {
+    scene: Handle<Scene>,
+    sound: Handle<Node>,
+    angle: f32,
+    debug_text: Handle<UiNode>,
+}
+
+impl GameState for Game {
+    fn init(engine: &mut Engine) -> Self
+    where
+        Self: Sized,
+    {
+        let scene = fyrox::core::futures::executor::block_on(GameSceneLoader::load_with(
+            engine.resource_manager.clone(),
+        ));
+
+        Self {
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut engine.user_interface.build_ctx()),
+            scene: engine.scenes.add(scene.scene),
+            sound: scene.sound,
+            angle: 0.0,
+        }
+    }
+
+    fn on_tick(&mut self, engine: &mut Engine, dt: f32, _: &mut ControlFlow) {
+        let scene = &mut engine.scenes[self.scene];
+
+        // Move the sound around the wall.
+        self.angle += dt * 0.5;
+        scene.graph[self.sound]
+            .local_transform_mut()
+            .set_position(Vector3::new(
+                6.0 * self.angle.cos(),
+                1.0,
+                6.0 * self.angle.sin(),
+            ));
+
+        let occlusion = scene
+            .graph
+            .try_get_of_type::<Sound>(self.sound)
+            .unwrap()
+            .occlusion_factor();
+
+        engine.user_interface.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example 15 - Sound Occlusion\nOcclusion: {:.2}\nFPS: {}",
+                occlusion,
+                engine.renderer.get_statistics().frames_per_second
+            ),
+        ));
+    }
+}
+
+fn main() {
+    Framework::<Game>::new()
+        .unwrap()
+        .title("Example 15 - Sound Occlusion")
+        .run();
+}
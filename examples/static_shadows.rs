@@ -0,0 +1,345 @@
+//! Example 20. Static shadows.
+//!
+//! Difficulty: Medium.
+//!
+//! This example shows a room with static walls and pillars that is lit by a point light and a
+//! spot light with static shadows, and a cube that flies around the room. Shadows of static
+//! geometry are rendered once and cached, only the cube is rendered into shadow maps every frame.
+//! Press `S` to toggle static shadows and compare statistics, press `V` to render the scene with
+//! static shadows on and off and compare the results.
+
+pub mod shared;
+
+use crate::shared::create_camera;
+use fyrox::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        color::Color,
+        parking_lot::Mutex,
+        pool::Handle,
+    },
+    engine::{framework::prelude::*, resource_manager::ResourceManager, Engine},
+    event::{ElementState, VirtualKeyCode, WindowEvent},
+    event_loop::ControlFlow,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    scene::{
+        base::{BaseBuilder, Mobility},
+        graph::Graph,
+        light::{point::PointLightBuilder, spot::SpotLightBuilder, BaseLight, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder,
+        },
+        node::Node,
+        reflection_probe::ReflectionProbeBuilder,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::sync::Arc;
+
+/// Maximum allowed difference between results of static and fully dynamic shadows.
+const TOLERANCE: f32 = 0.01;
+
+fn make_box(
+    graph: &mut Graph,
+    position: Vector3<f32>,
+    size: Vector3<f32>,
+    mobility: Mobility,
+) -> Handle<Node> {
+    MeshBuilder::new(
+        BaseBuilder::new()
+            .with_mobility(mobility)
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            ),
+    )
+    .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+        SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&size)),
+    )))
+    .build()])
+    .build(graph)
+}
+
+struct GameSceneLoader {
+    scene: Scene,
+    lights: Vec<Handle<Node>>,
+    cube: Handle<Node>,
+    probe: Handle<Node>,
+}
+
+impl GameSceneLoader {
+    async fn load_with(resource_manager: ResourceManager) -> Self {
+        let mut scene = Scene::new();
+
+        scene.ambient_lighting_color = Color::opaque(30, 30, 30);
+
+        create_camera(
+            resource_manager.clone(),
+            Vector3::new(0.0, 6.0, -14.0),
+            &mut scene.graph,
+        )
+        .await;
+
+        let graph = &mut scene.graph;
+
+        // Floor and walls of the room.
+        make_box(
+            graph,
+            Vector3::default(),
+            Vector3::new(20.0, 0.2, 20.0),
+            Mobility::Static,
+        );
+        make_box(
+            graph,
+            Vector3::new(0.0, 2.0, 10.0),
+            Vector3::new(20.0, 4.0, 0.2),
+            Mobility::Static,
+        );
+        make_box(
+            graph,
+            Vector3::new(-10.0, 2.0, 0.0),
+            Vector3::new(0.2, 4.0, 20.0),
+            Mobility::Static,
+        );
+        make_box(
+            graph,
+            Vector3::new(10.0, 2.0, 0.0),
+            Vector3::new(0.2, 4.0, 20.0),
+            Mobility::Static,
+        );
+
+        // Pillars to have some static shadows.
+        for x in [-5.0, 5.0] {
+            for z in [-5.0, 5.0] {
+                make_box(
+                    graph,
+                    Vector3::new(x, 1.5, z),
+                    Vector3::new(0.7, 3.0, 0.7),
+                    Mobility::Static,
+                );
+            }
+        }
+
+        // The only moving object in the scene.
+        let cube = make_box(
+            graph,
+            Vector3::new(3.0, 1.0, 0.0),
+            Vector3::new(1.0, 1.0, 1.0),
+            Mobility::Dynamic,
+        );
+
+        let point_light = PointLightBuilder::new(
+            BaseLightBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 3.5, 0.0))
+                        .build(),
+                ),
+            )
+            .with_static_shadows(true),
+        )
+        .with_radius(15.0)
+        .build(graph);
+
+        let spot_light = SpotLightBuilder::new(
+            BaseLightBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(-8.0, 3.8, -8.0))
+                        .with_local_rotation(
+                            UnitQuaternion::from_axis_angle(
+                                &Vector3::y_axis(),
+                                45.0f32.to_radians(),
+                            ) * UnitQuaternion::from_axis_angle(
+                                &Vector3::x_axis(),
+                                60.0f32.to_radians(),
+                            ),
+                        )
+                        .build(),
+                ),
+            )
+            .with_color(Color::opaque(255, 200, 150))
+            .with_static_shadows(true),
+        )
+        .with_distance(25.0)
+        .with_hotspot_cone_angle(60.0f32.to_radians())
+        .build(graph);
+
+        // The probe is used only to render the scene off-screen to compare shadows.
+        let probe = ReflectionProbeBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 2.0, -6.0))
+                    .build(),
+            ),
+        )
+        .with_resolution(128)
+        .build(graph);
+
+        Self {
+            scene,
+            lights: vec![point_light, spot_light],
+            cube,
+            probe,
+        }
+    }
+}
+
+struct Game {
+    scene: Handle<Scene>,
+    lights: Vec<Handle<Node>>,
+    cube: Handle<Node>,
+    probe: Handle<Node>,
+    angle: f32,
+    debug_text: Handle<UiNode>,
+    comparison: String,
+}
+
+impl Game {
+    fn set_static_shadows(&self, engine: &mut Engine, enabled: bool) {
+        let graph = &mut engine.scenes[self.scene].graph;
+        for &light in self.lights.iter() {
+            if let Some(light) = graph[light].query_component_mut::<BaseLight>() {
+                light.set_static_shadows(enabled);
+            }
+        }
+    }
+
+    fn is_static_shadows(&self, engine: &Engine) -> bool {
+        let graph = &engine.scenes[self.scene].graph;
+        self.lights.iter().all(|&light| {
+            graph[light]
+                .query_component_ref::<BaseLight>()
+                .map_or(false, |light| light.is_static_shadows())
+        })
+    }
+
+    fn capture(&self, engine: &mut Engine) -> Vec<f32> {
+        let texture = engine
+            .renderer
+            .capture_reflection_probe(&engine.scenes[self.scene], self.probe)
+            .unwrap();
+        let data = texture.data_ref();
+        let pixels = data
+            .data()
+            .chunks_exact(4)
+            .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        pixels
+    }
+
+    // Renders the scene with cached static shadows and with fully dynamic shadows and compares
+    // the results, they must be the same.
+    fn compare(&mut self, engine: &mut Engine) {
+        let enabled = self.is_static_shadows(engine);
+
+        self.set_static_shadows(engine, false);
+        let reference = self.capture(engine);
+        self.set_static_shadows(engine, true);
+        let combined = self.capture(engine);
+        self.set_static_shadows(engine, enabled);
+
+        let mut max_difference = 0.0f32;
+        let mut total_difference = 0.0;
+        for (a, b) in reference.iter().zip(combined.iter()) {
+            let difference = (a - b).abs();
+            max_difference = max_difference.max(difference);
+            total_difference += difference;
+        }
+        let mean_difference = total_difference / reference.len().max(1) as f32;
+
+        self.comparison = format!(
+            "Comparison: {} - Max Difference: {:.4}, Mean Difference: {:.6}",
+            if max_difference <= TOLERANCE {
+                "Passed"
+            } else {
+                "Failed"
+            },
+            max_difference,
+            mean_difference
+        );
+    }
+}
+
+impl GameState for Game {
+    fn init(engine: &mut Engine) -> Self
+    where
+        Self: Sized,
+    {
+        let scene = fyrox::core::futures::executor::block_on(GameSceneLoader::load_with(
+            engine.resource_manager.clone(),
+        ));
+
+        Self {
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut engine.user_interface.build_ctx()),
+            scene: engine.scenes.add(scene.scene),
+            lights: scene.lights,
+            cube: scene.cube,
+            probe: scene.probe,
+            angle: 0.0,
+            comparison: "Press V to compare shadows".to_owned(),
+        }
+    }
+
+    fn on_tick(&mut self, engine: &mut Engine, dt: f32, _: &mut ControlFlow) {
+        self.angle += dt * 0.5;
+
+        engine.scenes[self.scene].graph[self.cube]
+            .local_transform_mut()
+            .set_position(Vector3::new(
+                3.0 * self.angle.cos(),
+                1.0,
+                3.0 * self.angle.sin(),
+            ));
+
+        let statistics = engine.renderer.get_statistics();
+
+        engine.user_interface.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example 20 - Static Shadows\nStatic Shadows: {} (press S to toggle)\n{}\n\
+                Frame Time: {:.2} ms\n{}",
+                if self.is_static_shadows(engine) {
+                    "On"
+                } else {
+                    "Off"
+                },
+                self.comparison,
+                statistics.pure_frame_time * 1000.0,
+                statistics
+            ),
+        ));
+    }
+
+    fn on_window_event(&mut self, engine: &mut Engine, event: WindowEvent) {
+        if let WindowEvent::KeyboardInput { input, .. } = event {
+            if input.state == ElementState::Pressed {
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::S) => {
+                        let enabled = self.is_static_shadows(engine);
+                        self.set_static_shadows(engine, !enabled);
+                    }
+                    Some(VirtualKeyCode::V) => self.compare(engine),
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+fn main() {
+    Framework::<Game>::new()
+        .unwrap()
+        .title("Example 20 - Static Shadows")
+        .run();
+}
@@ -0,0 +1,241 @@
+//! Example - World-space UI.
+//!
+//! Difficulty: Medium.
+//!
+//! This example shows how to render an interactive user interface on a mesh in the world - a monitor
+//! with a button that toggles a light. Look at the monitor and press Enter to "use" it, so you can
+//! type something in its text box, press Escape to leave it.
+
+pub mod shared;
+
+use crate::shared::create_camera;
+use fyrox::{
+    core::{
+        algebra::{Matrix4, Vector3},
+        color::Color,
+        futures::executor::block_on,
+        parking_lot::Mutex,
+        pool::Handle,
+    },
+    engine::{
+        framework::prelude::*,
+        ui_surface::{UiSurface, UiSurfaceBuilder},
+        Engine,
+    },
+    event::{ElementState, VirtualKeyCode, WindowEvent},
+    event_loop::ControlFlow,
+    gui::{
+        brush::Brush,
+        button::{ButtonBuilder, ButtonMessage},
+        grid::{Column, GridBuilder, Row},
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        text_box::TextBoxBuilder,
+        widget::WidgetBuilder,
+        HorizontalAlignment, Thickness, UiNode, VerticalAlignment,
+    },
+    scene::{
+        base::BaseBuilder,
+        light::{point::PointLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder,
+        },
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::sync::Arc;
+
+struct Game {
+    scene: Handle<Scene>,
+    light: Handle<Node>,
+    surface: Handle<UiSurface>,
+    button: Handle<UiNode>,
+    light_state: Handle<UiNode>,
+    debug_text: Handle<UiNode>,
+}
+
+fn light_state_text(enabled: bool) -> String {
+    format!("Light is {}", if enabled { "ON" } else { "OFF" })
+}
+
+impl GameState for Game {
+    fn init(engine: &mut Engine) -> Self
+    where
+        Self: Sized,
+    {
+        let mut scene = Scene::new();
+
+        scene.ambient_lighting_color = Color::opaque(120, 120, 120);
+
+        block_on(create_camera(
+            engine.resource_manager.clone(),
+            Vector3::new(0.0, 1.0, -2.0),
+            &mut scene.graph,
+        ));
+
+        let light = PointLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(1.0, 2.0, -1.0))
+                    .build(),
+            ),
+        ))
+        .with_radius(6.0)
+        .build(&mut scene.graph);
+
+        // Monitor case.
+        MeshBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 1.0, 0.06))
+                    .build(),
+            ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+            SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                1.7, 1.0, 0.1,
+            ))),
+        )))
+        .build()])
+        .build(&mut scene.graph);
+
+        // Screen of the monitor - a quad facing the camera, the UI will be rendered on it.
+        let screen = MeshBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                    .build(),
+            ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+            SurfaceData::make_quad(&Matrix4::new_nonuniform_scaling(&Vector3::new(
+                1.6, 0.9, 1.0,
+            ))),
+        )))
+        .build()])
+        .build(&mut scene.graph);
+
+        let scene = engine.scenes.add(scene);
+
+        // Resolution of the surface should have the same aspect ratio as the screen.
+        let mut surface = UiSurfaceBuilder::new(scene, screen)
+            .with_resolution(640, 360)
+            .with_interaction_distance(4.0)
+            .build();
+
+        let ctx = &mut surface.ui_mut().build_ctx();
+        let light_state;
+        let button;
+        GridBuilder::new(
+            WidgetBuilder::new()
+                .with_background(Brush::Solid(Color::opaque(20, 40, 80)))
+                .with_child({
+                    light_state = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .on_row(0)
+                            .with_margin(Thickness::uniform(10.0)),
+                    )
+                    .with_text(light_state_text(true))
+                    .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx);
+                    light_state
+                })
+                .with_child({
+                    button = ButtonBuilder::new(
+                        WidgetBuilder::new()
+                            .on_row(1)
+                            .with_margin(Thickness::uniform(10.0)),
+                    )
+                    .with_text("Toggle Light")
+                    .build(ctx);
+                    button
+                })
+                .with_child(
+                    TextBoxBuilder::new(
+                        WidgetBuilder::new()
+                            .on_row(2)
+                            .with_margin(Thickness::uniform(10.0)),
+                    )
+                    .with_text("Press Enter to type here")
+                    .build(ctx),
+                ),
+        )
+        .add_row(Row::stretch())
+        .add_row(Row::stretch())
+        .add_row(Row::stretch())
+        .add_column(Column::stretch())
+        .build(ctx);
+
+        let surface = engine.ui_surfaces.add(surface);
+
+        Self {
+            scene,
+            light,
+            surface,
+            button,
+            light_state,
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut engine.user_interface.build_ctx()),
+        }
+    }
+
+    fn on_tick(&mut self, engine: &mut Engine, _dt: f32, _: &mut ControlFlow) {
+        // Messages of world-space UIs must be polled separately.
+        while let Some(message) = engine.ui_surfaces[self.surface].ui_mut().poll_message() {
+            if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+                if message.destination() == self.button {
+                    let light = &mut engine.scenes[self.scene].graph[self.light];
+                    let enabled = !light.visibility();
+                    light.set_visibility(enabled);
+
+                    engine.ui_surfaces[self.surface]
+                        .ui()
+                        .send_message(TextMessage::text(
+                            self.light_state,
+                            MessageDirection::ToWidget,
+                            light_state_text(enabled),
+                        ));
+                }
+            }
+        }
+
+        let surface = &engine.ui_surfaces[self.surface];
+        engine.user_interface.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example - World-space UI\nFPS: {}\nCursor over the screen: {}\nUsing the monitor: {}",
+                engine.renderer.get_statistics().frames_per_second,
+                surface.is_hovered(),
+                surface.has_keyboard_focus()
+            ),
+        ));
+    }
+
+    fn on_window_event(&mut self, engine: &mut Engine, event: WindowEvent) {
+        if let WindowEvent::KeyboardInput { input, .. } = event {
+            if input.state == ElementState::Pressed {
+                let surface = &mut engine.ui_surfaces[self.surface];
+                match input.virtual_keycode {
+                    // The monitor could be "used" only if the player looks at it.
+                    Some(VirtualKeyCode::Return) if surface.is_hovered() => {
+                        surface.set_keyboard_focus(true)
+                    }
+                    Some(VirtualKeyCode::Escape) => surface.set_keyboard_focus(false),
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+fn main() {
+    Framework::<Game>::new()
+        .unwrap()
+        .title("Example - World-space UI")
+        .run();
+}
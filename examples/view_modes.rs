@@ -0,0 +1,378 @@
+//! Example 26. Debug view modes.
+//!
+//! Difficulty: Easy.
+//!
+//! This example shows how to replace materials of every mesh rendered by a camera with a single
+//! material using a material override of the renderer, the editor uses it for its debug view
+//! modes and the shaders of the modes are taken from the editor. Press `M` to cycle view modes,
+//! press `T` to run a smoke test: the scene is rendered with UV checker and normals modes, the
+//! frames are checked and saved as screenshots, then the override is removed and the lit frame
+//! must match the lit frame that was rendered before the overrides.
+
+use fyrox::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        color::Color,
+        parking_lot::Mutex,
+        pool::Handle,
+        sstorage::ImmutableString,
+    },
+    engine::{framework::prelude::*, Engine},
+    event::{ElementState, VirtualKeyCode, WindowEvent},
+    event_loop::ControlFlow,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    material::{shader::Shader, Material, PropertyValue},
+    renderer::material_override::MaterialOverride,
+    scene::{
+        base::BaseBuilder,
+        camera::CameraBuilder,
+        graph::Graph,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData},
+            MeshBuilder,
+        },
+        node::Node,
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::{path::PathBuf, sync::Arc};
+
+const UV_CHECKER_SHADER: &str = include_str!("../editor/resources/embed/shaders/uv_checker.shader");
+const NORMALS_SHADER: &str = include_str!("../editor/resources/embed/shaders/normals.shader");
+
+/// Maximum difference of color channels of the lit frames before and after the test.
+const TOLERANCE: f32 = 1.0 / 255.0;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Mode {
+    Lit,
+    UvChecker,
+    Normals,
+}
+
+impl Mode {
+    const ALL: [Mode; 3] = [Mode::Lit, Mode::UvChecker, Mode::Normals];
+
+    fn name(self) -> &'static str {
+        match self {
+            Mode::Lit => "Lit",
+            Mode::UvChecker => "UV Checker",
+            Mode::Normals => "Normals",
+        }
+    }
+
+    fn make_override(self) -> Option<MaterialOverride> {
+        let source = match self {
+            Mode::Lit => return None,
+            Mode::UvChecker => UV_CHECKER_SHADER,
+            Mode::Normals => NORMALS_SHADER,
+        };
+        let shader = Shader::from_str(source, PathBuf::default()).unwrap();
+        Some(MaterialOverride::new(Material::from_shader(shader, None)))
+    }
+}
+
+/// A frame of the scene, rows go from bottom to top.
+struct Frame {
+    width: usize,
+    height: usize,
+    pixels: Vec<f32>,
+}
+
+impl Frame {
+    fn read(engine: &mut Engine, scene: Handle<Scene>) -> Option<Self> {
+        engine
+            .renderer
+            .read_scene_frame(scene)
+            .map(|(width, height, pixels)| Self {
+                width,
+                height,
+                pixels,
+            })
+    }
+
+    fn pixel(&self, x: usize, y: usize) -> [f32; 3] {
+        let i = (y * self.width + x) * 3;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    fn max_difference(&self, other: &Frame) -> f32 {
+        self.pixels
+            .iter()
+            .zip(other.pixels.iter())
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f32::max)
+    }
+
+    fn save(&self, path: &str) -> Result<(), image::ImageError> {
+        let mut image = image::RgbImage::new(self.width as u32, self.height as u32);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let rgb = self.pixel(x, y).map(|c| (c.clamp(0.0, 1.0) * 255.0) as u8);
+                image.put_pixel(x as u32, (self.height - 1 - y) as u32, image::Rgb(rgb));
+            }
+        }
+        image.save(path)
+    }
+}
+
+fn make_cube(graph: &mut Graph, position: Vector3<f32>, rotation: UnitQuaternion<f32>) {
+    let mut material = Material::standard();
+    material
+        .set_property(
+            &ImmutableString::new("diffuseColor"),
+            PropertyValue::Color(Color::opaque(180, 120, 80)),
+        )
+        .unwrap();
+
+    MeshBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(position)
+                .with_local_rotation(rotation)
+                .build(),
+        ),
+    )
+    .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+        SurfaceData::make_cube(Matrix4::identity()),
+    )))
+    .with_material(Arc::new(Mutex::new(material)))
+    .build()])
+    .build(graph);
+}
+
+/// Steps of the smoke test, every step is rendered and checked immediately.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum TestStep {
+    Reference,
+    UvChecker,
+    Normals,
+    Restored,
+}
+
+impl TestStep {
+    const ALL: [TestStep; 4] = [
+        TestStep::Reference,
+        TestStep::UvChecker,
+        TestStep::Normals,
+        TestStep::Restored,
+    ];
+
+    fn mode(self) -> Mode {
+        match self {
+            TestStep::Reference | TestStep::Restored => Mode::Lit,
+            TestStep::UvChecker => Mode::UvChecker,
+            TestStep::Normals => Mode::Normals,
+        }
+    }
+}
+
+struct Game {
+    scene: Handle<Scene>,
+    camera: Handle<Node>,
+    mode: Mode,
+    debug_text: Handle<UiNode>,
+    status: String,
+}
+
+impl Game {
+    fn set_mode(&mut self, engine: &mut Engine, mode: Mode) {
+        self.mode = mode;
+        engine
+            .renderer
+            .set_material_override(self.scene, self.camera, mode.make_override());
+    }
+
+    fn check(step: TestStep, frame: &Frame, reference: Option<&Frame>, errors: &mut Vec<String>) {
+        let (cx, cy) = (frame.width / 2, frame.height / 2);
+
+        match step {
+            TestStep::Reference => (),
+            TestStep::UvChecker => {
+                // Both light and dark cells of the checker must be visible on the front face.
+                let (mut light, mut dark) = (0, 0);
+                for y in cy - frame.height / 8..cy + frame.height / 8 {
+                    for x in cx - frame.width / 8..cx + frame.width / 8 {
+                        let [r, g, b] = frame.pixel(x, y);
+                        let luminance = 0.299 * r + 0.587 * g + 0.114 * b;
+                        if luminance > 0.45 {
+                            light += 1;
+                        } else if luminance < 0.25 {
+                            dark += 1;
+                        }
+                    }
+                }
+                if light == 0 || dark == 0 {
+                    errors.push(format!(
+                        "UV checker: no checker pattern ({} light, {} dark pixels)",
+                        light, dark
+                    ));
+                }
+            }
+            TestStep::Normals => {
+                // The front face looks at the camera, its normal is -Z, which is (0.5, 0.5, 0.0)
+                // in colors.
+                let [r, g, b] = frame.pixel(cx, cy);
+                if (r - 0.5).abs() > 0.02 || (g - 0.5).abs() > 0.02 || b > 0.02 {
+                    errors.push(format!(
+                        "Normals: unexpected color of the front face ({:.3}, {:.3}, {:.3})",
+                        r, g, b
+                    ));
+                }
+            }
+            TestStep::Restored => {
+                if let Some(reference) = reference {
+                    let difference = reference.max_difference(frame);
+                    if difference > TOLERANCE {
+                        errors.push(format!(
+                            "Lit frame was not restored, max difference is {:.4}",
+                            difference
+                        ));
+                    }
+                }
+            }
+        }
+
+        let screenshot = match step {
+            TestStep::UvChecker => Some("examples/screenshots/view_modes_uv_checker.png"),
+            TestStep::Normals => Some("examples/screenshots/view_modes_normals.png"),
+            _ => None,
+        };
+        if let Some(Err(e)) = screenshot.map(|path| frame.save(path)) {
+            errors.push(format!("Unable to save screenshot: {}", e));
+        }
+    }
+
+    // Renders every step of the test directly, without waiting for the next frame.
+    fn run_test(&mut self, engine: &mut Engine) {
+        let mode = self.mode;
+        let mut reference = None;
+        let mut errors = Vec::new();
+
+        for step in TestStep::ALL {
+            self.set_mode(engine, step.mode());
+            if let Err(e) = engine.render() {
+                errors.push(format!("Unable to render a frame: {:?}", e));
+                break;
+            }
+
+            match Frame::read(engine, self.scene) {
+                Some(frame) => {
+                    Self::check(step, &frame, reference.as_ref(), &mut errors);
+                    if step == TestStep::Reference {
+                        reference = Some(frame);
+                    }
+                }
+                None => errors.push("Scene was not rendered".to_owned()),
+            }
+        }
+
+        self.set_mode(engine, mode);
+
+        self.status = if errors.is_empty() {
+            "Smoke test: Passed".to_owned()
+        } else {
+            format!("Smoke test: Failed\n{}", errors.join("\n"))
+        };
+    }
+}
+
+impl GameState for Game {
+    fn init(engine: &mut Engine) -> Self
+    where
+        Self: Sized,
+    {
+        let mut scene = Scene::new();
+
+        scene.ambient_lighting_color = Color::opaque(60, 60, 60);
+
+        let camera = CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, -2.5))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        45.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        // The front face of the first cube covers the center of the frame.
+        make_cube(
+            &mut scene.graph,
+            Vector3::default(),
+            UnitQuaternion::identity(),
+        );
+        make_cube(
+            &mut scene.graph,
+            Vector3::new(1.6, 0.4, 1.0),
+            UnitQuaternion::from_euler_angles(0.4, 0.7, 0.0),
+        );
+        make_cube(
+            &mut scene.graph,
+            Vector3::new(-1.6, -0.4, 1.0),
+            UnitQuaternion::from_euler_angles(-0.3, 0.5, 0.2),
+        );
+
+        Self {
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut engine.user_interface.build_ctx()),
+            scene: engine.scenes.add(scene),
+            camera,
+            mode: Mode::Lit,
+            status: "Press T to run the smoke test".to_owned(),
+        }
+    }
+
+    fn on_tick(&mut self, engine: &mut Engine, _dt: f32, _: &mut ControlFlow) {
+        engine.user_interface.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example 26 - Debug View Modes\nMode: {} (press M to change)\n{}",
+                self.mode.name(),
+                self.status,
+            ),
+        ));
+    }
+
+    fn on_window_event(&mut self, engine: &mut Engine, event: WindowEvent) {
+        if let WindowEvent::KeyboardInput { input, .. } = event {
+            if input.state == ElementState::Pressed {
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::M) => {
+                        let index = Mode::ALL.iter().position(|m| *m == self.mode).unwrap();
+                        let mode = Mode::ALL[(index + 1) % Mode::ALL.len()];
+                        self.set_mode(engine, mode);
+                    }
+                    Some(VirtualKeyCode::T) => self.run_test(engine),
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+fn main() {
+    Framework::<Game>::new()
+        .unwrap()
+        .title("Example 26 - Debug View Modes")
+        .run();
+}
@@ -0,0 +1,339 @@
+//! Example 23. Wind.
+//!
+//! Difficulty: Easy.
+//!
+//! This example shows a field of grass that sways in the wind and a few columns of smoke that
+//! drift with the same wind. Grass is a mesh with vertex colors, red channel of the color defines
+//! flexibility of a vertex - roots of blades are rigid and tips are flexible. Its material has
+//! `vegetation` property enabled, so the standard shader sways the vertices. Smoke particles are
+//! dragged by the wind according to wind influence of their particle system. Both use the same
+//! velocity field, so gusts could be seen passing through the grass and the smoke at the same time.
+//!
+//! Use arrow keys to change direction and strength of the wind, press `G` to toggle gusts.
+
+pub mod shared;
+
+use crate::shared::create_camera;
+use fyrox::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3, Vector4},
+        color::Color,
+        color_gradient::{ColorGradient, GradientPoint},
+        math::TriangleDefinition,
+        parking_lot::Mutex,
+        pool::Handle,
+        sstorage::ImmutableString,
+    },
+    engine::{framework::prelude::*, resource_manager::ResourceManager, Engine},
+    event::{ElementState, VirtualKeyCode, WindowEvent},
+    event_loop::ControlFlow,
+    gui::{
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::WidgetBuilder,
+        UiNode,
+    },
+    material::{shader::SamplerFallback, Material, PropertyValue},
+    rand::Rng,
+    scene::{
+        base::BaseBuilder,
+        graph::Graph,
+        light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+        mesh::{
+            buffer::{
+                TriangleBuffer, VertexAttributeDataType, VertexAttributeDescriptor,
+                VertexAttributeUsage, VertexBuffer, VertexWriteTrait, COLOR_SHADER_LOCATION,
+            },
+            surface::{SurfaceBuilder, SurfaceData},
+            vertex::StaticVertex,
+            MeshBuilder,
+        },
+        particle_system::{
+            emitter::{base::BaseEmitterBuilder, sphere::SphereEmitterBuilder},
+            ParticleSystemBuilder,
+        },
+        transform::TransformBuilder,
+        Scene,
+    },
+};
+use std::sync::Arc;
+
+/// Size of the grass field.
+const FIELD_SIZE: f32 = 20.0;
+
+const BLADE_COUNT: usize = 6000;
+
+const BLADE_WIDTH: f32 = 0.08;
+
+const BLADE_HEIGHT: f32 = 0.6;
+
+/// Creates a mesh with a lot of randomly placed grass blades. Every blade is a quad, which is
+/// visible from both sides, weights of its bottom vertices are zero and top vertices are one.
+fn create_grass(graph: &mut Graph, resource_manager: &ResourceManager) {
+    let mut rng = fyrox::rand::thread_rng();
+
+    let mut vertices = Vec::with_capacity(BLADE_COUNT * 4);
+    let mut weights = Vec::with_capacity(BLADE_COUNT * 4);
+    let mut triangles = Vec::with_capacity(BLADE_COUNT * 4);
+    for _ in 0..BLADE_COUNT {
+        let center = Vector3::new(
+            rng.gen_range(-0.5..0.5) * FIELD_SIZE,
+            0.0,
+            rng.gen_range(-0.5..0.5) * FIELD_SIZE,
+        );
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let side = Vector3::new(angle.cos(), 0.0, angle.sin()).scale(BLADE_WIDTH * 0.5);
+        let height = BLADE_HEIGHT * rng.gen_range(0.6..1.4);
+        let normal = Vector3::new(-angle.sin(), 0.0, angle.cos());
+
+        let first = vertices.len() as u32;
+        for (offset, tex_coord, weight) in [
+            (-side, Vector2::new(0.0, 1.0), 0.0),
+            (side, Vector2::new(1.0, 1.0), 0.0),
+            (
+                side + Vector3::new(0.0, height, 0.0),
+                Vector2::new(1.0, 0.0),
+                1.0,
+            ),
+            (
+                -side + Vector3::new(0.0, height, 0.0),
+                Vector2::new(0.0, 0.0),
+                1.0,
+            ),
+        ] {
+            vertices.push(StaticVertex {
+                position: center + offset,
+                tex_coord,
+                normal,
+                tangent: Vector4::default(),
+            });
+            weights.push(weight);
+        }
+
+        // Both sides of a blade must be visible.
+        triangles.push(TriangleDefinition([first, first + 1, first + 2]));
+        triangles.push(TriangleDefinition([first, first + 2, first + 3]));
+        triangles.push(TriangleDefinition([first, first + 2, first + 1]));
+        triangles.push(TriangleDefinition([first, first + 3, first + 2]));
+    }
+
+    let mut data = SurfaceData::new(
+        VertexBuffer::new(vertices.len(), StaticVertex::layout(), vertices).unwrap(),
+        TriangleBuffer::new(triangles),
+        true,
+    );
+
+    // The standard shader takes flexibility of a vertex from red channel of vertex color.
+    let mut vertex_buffer = data.vertex_buffer.modify();
+    vertex_buffer
+        .add_attribute(
+            VertexAttributeDescriptor {
+                usage: VertexAttributeUsage::Color,
+                data_type: VertexAttributeDataType::F32,
+                size: 4,
+                divisor: 0,
+                shader_location: COLOR_SHADER_LOCATION,
+            },
+            [0.0f32; 4],
+        )
+        .unwrap();
+    for (mut vertex, weight) in vertex_buffer.iter_mut().zip(weights) {
+        vertex
+            .write_4_f32(
+                VertexAttributeUsage::Color,
+                Vector4::new(weight, 0.0, 0.0, 1.0),
+            )
+            .unwrap();
+    }
+    drop(vertex_buffer);
+
+    let mut material = Material::standard();
+    material
+        .set_property(
+            &ImmutableString::new("diffuseTexture"),
+            PropertyValue::Sampler {
+                value: Some(
+                    resource_manager.request_texture("examples/data/Grass_DiffuseColor.jpg"),
+                ),
+                fallback: SamplerFallback::White,
+            },
+        )
+        .unwrap();
+    material
+        .set_property(
+            &ImmutableString::new("vegetation"),
+            PropertyValue::Bool(true),
+        )
+        .unwrap();
+
+    MeshBuilder::new(BaseBuilder::new())
+        .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(data)))
+            .with_material(Arc::new(Mutex::new(material)))
+            .build()])
+        .build(graph);
+}
+
+fn create_smoke(graph: &mut Graph, position: Vector3<f32>) {
+    let mut gradient = ColorGradient::new();
+    gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(120, 120, 120, 0)));
+    gradient.add_point(GradientPoint::new(
+        0.10,
+        Color::from_rgba(120, 120, 120, 180),
+    ));
+    gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(200, 200, 200, 0)));
+
+    ParticleSystemBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(position)
+                .build(),
+        ),
+    )
+    .with_emitters(vec![SphereEmitterBuilder::new(
+        BaseEmitterBuilder::new()
+            .with_spawn_rate(40)
+            .with_max_particles(400)
+            .with_lifetime_range(4.0..6.0)
+            .with_size_range(0.2..0.3)
+            .with_size_modifier_range(0.1..0.2)
+            .with_y_velocity_range(0.01..0.02),
+    )
+    .with_radius(0.2)
+    .build()])
+    // Hot smoke goes up.
+    .with_acceleration(Vector3::new(0.0, 0.3, 0.0))
+    .with_color_over_lifetime_gradient(gradient)
+    // Light particles, they follow the wind closely.
+    .with_wind_influence(1.0)
+    .build(graph);
+}
+
+struct GameSceneLoader {
+    scene: Scene,
+}
+
+impl GameSceneLoader {
+    async fn load_with(resource_manager: ResourceManager) -> Self {
+        let mut scene = Scene::new();
+
+        scene.ambient_lighting_color = Color::opaque(120, 120, 120);
+
+        create_camera(
+            resource_manager.clone(),
+            Vector3::new(0.0, 3.0, -14.0),
+            &mut scene.graph,
+        )
+        .await;
+
+        DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        50.0f32.to_radians(),
+                    ))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+
+        // Ground.
+        MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+                SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                    FIELD_SIZE, 0.1, FIELD_SIZE,
+                ))),
+            )))
+            .build()])
+            .build(&mut scene.graph);
+
+        create_grass(&mut scene.graph, &resource_manager);
+
+        for position in [
+            Vector3::new(-6.0, 0.0, -4.0),
+            Vector3::new(-2.0, 0.0, 2.0),
+            Vector3::new(4.0, 0.0, -1.0),
+        ] {
+            create_smoke(&mut scene.graph, position);
+        }
+
+        let wind = &mut scene.graph.wind;
+        wind.set_strength(4.0);
+        wind.set_gustiness(0.6);
+
+        Self { scene }
+    }
+}
+
+struct Game {
+    scene: Handle<Scene>,
+    debug_text: Handle<UiNode>,
+    wind_angle: f32,
+}
+
+impl GameState for Game {
+    fn init(engine: &mut Engine) -> Self
+    where
+        Self: Sized,
+    {
+        let scene = fyrox::core::futures::executor::block_on(GameSceneLoader::load_with(
+            engine.resource_manager.clone(),
+        ));
+
+        Self {
+            debug_text: TextBuilder::new(WidgetBuilder::new())
+                .build(&mut engine.user_interface.build_ctx()),
+            scene: engine.scenes.add(scene.scene),
+            wind_angle: 0.0,
+        }
+    }
+
+    fn on_tick(&mut self, engine: &mut Engine, _dt: f32, _: &mut ControlFlow) {
+        let wind = &engine.scenes[self.scene].graph.wind;
+
+        engine.user_interface.send_message(TextMessage::text(
+            self.debug_text,
+            MessageDirection::ToWidget,
+            format!(
+                "Example 23 - Wind\nUse [Left][Right] to change direction and [Up][Down] \
+                to change strength of the wind, press [G] to toggle gusts.\n\
+                Direction: {:.0} degrees\nStrength: {:.1} m/s\nGustiness: {:.1}",
+                self.wind_angle.to_degrees(),
+                wind.strength(),
+                wind.gustiness(),
+            ),
+        ));
+    }
+
+    fn on_window_event(&mut self, engine: &mut Engine, event: WindowEvent) {
+        if let WindowEvent::KeyboardInput { input, .. } = event {
+            if input.state == ElementState::Pressed {
+                // Scripts could change the wind in the same way at any time.
+                let wind = &mut engine.scenes[self.scene].graph.wind;
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::Left) => self.wind_angle -= 15.0f32.to_radians(),
+                    Some(VirtualKeyCode::Right) => self.wind_angle += 15.0f32.to_radians(),
+                    Some(VirtualKeyCode::Up) => wind.set_strength(wind.strength() + 1.0),
+                    Some(VirtualKeyCode::Down) => wind.set_strength(wind.strength() - 1.0),
+                    Some(VirtualKeyCode::G) => {
+                        let gustiness = if wind.gustiness() > 0.0 { 0.0 } else { 0.6 };
+                        wind.set_gustiness(gustiness)
+                    }
+                    _ => (),
+                }
+                wind.set_direction(Vector3::new(
+                    self.wind_angle.cos(),
+                    0.0,
+                    self.wind_angle.sin(),
+                ));
+            }
+        }
+    }
+}
+
+fn main() {
+    Framework::<Game>::new()
+        .unwrap()
+        .title("Example 23 - Wind")
+        .run();
+}
@@ -141,6 +141,12 @@ impl<'a> PropertyInfo<'a> {
 /// - `#[inspect(expand)]` - extends the list of properties in case of composition, in other words it
 /// "flattens" and exposes the properties of an inner object. Useful when you have a structure that
 /// has some fields that are complex objects that implements `Inspect` too.
+/// - `#[inspect(read_only)]` - the property is shown, but cannot be edited.
+/// - `#[inspect(min_value = 0.0, max_value = 1.0)]` - the range of a numeric property. Editors
+/// clamp new values to the range, scripts also clamp loaded values (see `Script::clamp_to_property_ranges`).
+/// - `#[inspect(step = 0.1)]` - increment/decrement step of a numeric property.
+/// - `#[inspect(precision = 3)]` - maximum amount of decimal places of a numeric property.
+/// - `#[inspect(description = "Some text")]` - description of the property, editors show it as a tooltip.
 pub trait Inspect {
     /// Returns information about "public" properties.
     fn properties(&self) -> Vec<PropertyInfo<'_>>;
@@ -1,5 +1,44 @@
-use std::io::Error;
-use std::path::Path;
+//! File input for resources. Files are read from the file system (or fetched over network on
+//! WebAssembly), unless they're provided by one of mounted [`ResourceIo`] backends.
+//!
+//! # Backends
+//!
+//! There are few built-in backends:
+//!
+//! - [`FsIo`] - a folder of the file system.
+//! - [`ArchiveIo`] - an archive made by [`ArchiveBuilder`], it allows you to pack every resource
+//!   of a game into a single file.
+//! - [`ZipIo`] - a zip archive.
+//! - [`EmbeddedIo`] - files embedded into the executable using `include_bytes!`.
+//! - `FetchIo` - files fetched from a server, available on WebAssembly only.
+//!
+//! # Mounting
+//!
+//! Backends are mounted using [`mount`] or [`mount_at`]. Every backend is mounted at a virtual
+//! prefix, files of the backend are accessible by paths that start with the prefix, and a priority,
+//! backends with higher priority are searched first. This way a patch archive could override files
+//! of the base archive:
+//!
+//! ```no_run
+//! # use fyrox_core::io::{self, ZipIo};
+//! # use std::sync::Arc;
+//! io::mount_at("data", 0, Arc::new(ZipIo::open("base.zip").unwrap()));
+//! io::mount_at("data", 1, Arc::new(ZipIo::open("patch.zip").unwrap()));
+//! ```
+//!
+//! Files of any mounted backend take precedence over the files in the file system.
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use fxhash::{FxHashMap, FxHashSet};
+use parking_lot::{Mutex, RwLock};
+use std::{
+    future::Future,
+    io::{Cursor, Error, ErrorKind, Read, Seek, SeekFrom, Write},
+    path::{Component, Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+};
+use zip::{result::ZipError, ZipArchive};
 
 #[derive(Debug)]
 pub enum FileLoadError {
@@ -13,6 +52,15 @@ impl From<std::io::Error> for FileLoadError {
     }
 }
 
+impl From<ZipError> for FileLoadError {
+    fn from(e: ZipError) -> Self {
+        match e {
+            ZipError::Io(e) => Self::Io(e),
+            _ => Self::Custom(e.to_string()),
+        }
+    }
+}
+
 #[cfg(target_arch = "wasm32")]
 impl From<wasm_bindgen::JsValue> for FileLoadError {
     fn from(value: wasm_bindgen::JsValue) -> Self {
@@ -24,11 +72,137 @@ impl From<wasm_bindgen::JsValue> for FileLoadError {
     }
 }
 
+/// A future returned by [`ResourceIo`] methods. It is not `Send` on WebAssembly, because browser
+/// APIs are single-threaded.
+#[cfg(not(target_arch = "wasm32"))]
+pub type ResourceIoFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A future returned by [`ResourceIo`] methods. It is not `Send` on WebAssembly, because browser
+/// APIs are single-threaded.
+#[cfg(target_arch = "wasm32")]
+pub type ResourceIoFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// A source of files, that could be mounted (see [`mount_at`]) to provide files for [`load_file`]
+/// and [`exists`] instead of the file system. Paths passed to the methods are relative to the
+/// prefix the source is mounted at.
+pub trait ResourceIo: Send + Sync {
+    /// Returns content of a file at the given path or `None` if the source has no such file, in
+    /// this case the file is searched in other sources.
+    fn load_file<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Option<Result<Vec<u8>, FileLoadError>>>;
+
+    /// Returns `true` if the source has a file at the given path.
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool>;
+
+    /// Returns paths of files and folders in the given folder. Returns `None` if the source
+    /// cannot list its files (for example, a server).
+    fn read_dir(&self, _path: &Path) -> Option<Vec<PathBuf>> {
+        None
+    }
+
+    /// Returns `true` if changes of the files of the source are tracked by the file system
+    /// watcher of the resource manager. Only files in the file system could be tracked.
+    fn is_watchable(&self) -> bool {
+        false
+    }
+}
+
+struct MountPoint {
+    prefix: String,
+    priority: i32,
+    io: Arc<dyn ResourceIo>,
+}
+
+lazy_static! {
+    // Sorted by priority, higher priority goes first.
+    static ref MOUNTED: RwLock<Vec<MountPoint>> = Default::default();
+}
+
+/// Mounts the given source of files at the root, with zero priority. See [`mount_at`] for more
+/// info.
+pub fn mount(io: Arc<dyn ResourceIo>) {
+    mount_at("", 0, io);
+}
+
+/// Mounts the given source of files at the given prefix, for example if a source is mounted at
+/// `data/dlc`, its file `foo.png` is accessible as `data/dlc/foo.png`. Sources with higher priority
+/// are searched first, if priorities are equal, the source mounted later is searched first. Files
+/// of any source take precedence over the files in the file system.
+pub fn mount_at<P: AsRef<Path>>(prefix: P, priority: i32, io: Arc<dyn ResourceIo>) {
+    let mut mounted = MOUNTED.write();
+    let index = mounted
+        .iter()
+        .position(|mount_point| mount_point.priority <= priority)
+        .unwrap_or(mounted.len());
+    mounted.insert(
+        index,
+        MountPoint {
+            prefix: archive_key(prefix.as_ref()),
+            priority,
+            io,
+        },
+    );
+}
+
+/// Unmounts previously mounted source of files. Returns `true` if the source was mounted.
+pub fn unmount(io: &Arc<dyn ResourceIo>) -> bool {
+    let mut mounted = MOUNTED.write();
+    let count = mounted.len();
+    mounted.retain(|mount_point| !Arc::ptr_eq(&mount_point.io, io));
+    count != mounted.len()
+}
+
+/// Unmounts every mounted source of files.
+pub fn unmount_all() {
+    MOUNTED.write().clear();
+}
+
+// Returns sources, that could contain the file, with paths relative to the sources. The lock is
+// not held while the sources are accessed, because they're accessed asynchronously.
+fn candidates(path: &Path) -> Vec<(Arc<dyn ResourceIo>, PathBuf)> {
+    let key = archive_key(path);
+    MOUNTED
+        .read()
+        .iter()
+        .filter_map(|mount_point| {
+            strip_key_prefix(&key, &mount_point.prefix)
+                .map(|relative| (mount_point.io.clone(), PathBuf::from(relative)))
+        })
+        .collect()
+}
+
+fn strip_key_prefix<'a>(key: &'a str, prefix: &str) -> Option<&'a str> {
+    if prefix.is_empty() {
+        Some(key)
+    } else if key == prefix {
+        Some("")
+    } else {
+        key.strip_prefix(prefix)
+            .and_then(|rest| rest.strip_prefix('/'))
+    }
+}
+
+/// Tries to load a file from mounted sources only. Returns `None` if there is no such file in
+/// any mounted source.
+pub async fn load_mounted_file<P: AsRef<Path>>(path: P) -> Option<Result<Vec<u8>, FileLoadError>> {
+    for (io, relative) in candidates(path.as_ref()) {
+        if let Some(result) = io.load_file(&relative).await {
+            return Some(result);
+        }
+    }
+    None
+}
+
 pub async fn load_file<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, FileLoadError> {
+    if let Some(result) = load_mounted_file(path.as_ref()).await {
+        return result;
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     {
         use std::fs::File;
-        use std::io::Read;
 
         let mut file = File::open(path)?;
         let mut buffer = Vec::new();
@@ -38,26 +212,23 @@ pub async fn load_file<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, FileLoadError
 
     #[cfg(target_arch = "wasm32")]
     {
-        use js_sys::Uint8Array;
-        use wasm_bindgen::JsCast;
-        use wasm_bindgen_futures::JsFuture;
-
-        match web_sys::window() {
-            Some(window) => {
-                let resp_value =
-                    JsFuture::from(window.fetch_with_str(path.as_ref().to_str().unwrap())).await?;
-
-                let resp: web_sys::Response = resp_value.dyn_into().unwrap();
-                let data = JsFuture::from(resp.array_buffer().unwrap()).await?;
-                let bytes = Uint8Array::new(&data).to_vec();
-                Ok(bytes)
-            }
-            None => Err(FileLoadError::Custom("Window not found!".to_owned())),
+        match fetch(path.as_ref().to_str().unwrap()).await? {
+            Some(bytes) => Ok(bytes),
+            None => Err(FileLoadError::Custom(format!(
+                "File {} not found!",
+                path.as_ref().display()
+            ))),
         }
     }
 }
 
 pub async fn exists<P: AsRef<Path>>(path: P) -> bool {
+    for (io, relative) in candidates(path.as_ref()) {
+        if io.exists(&relative).await {
+            return true;
+        }
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     {
         path.as_ref().exists()
@@ -65,22 +236,784 @@ pub async fn exists<P: AsRef<Path>>(path: P) -> bool {
 
     #[cfg(target_arch = "wasm32")]
     {
-        use wasm_bindgen::JsCast;
-        use wasm_bindgen_futures::JsFuture;
-
-        match web_sys::window() {
-            Some(window) => {
-                if let Ok(resp_value) =
-                    JsFuture::from(window.fetch_with_str(path.as_ref().to_str().unwrap())).await
-                {
-                    let resp: web_sys::Response = resp_value.dyn_into().unwrap();
-
-                    resp.status() == 200
-                } else {
-                    false
+        matches!(fetch(path.as_ref().to_str().unwrap()).await, Ok(Some(_)))
+    }
+}
+
+/// Returns `true` if changes of the file could be tracked by the file system watcher, it is so
+/// when the file is not provided by a mounted source or provided by a watchable source (see
+/// [`ResourceIo::is_watchable`]).
+pub async fn is_watchable<P: AsRef<Path>>(path: P) -> bool {
+    for (io, relative) in candidates(path.as_ref()) {
+        if io.exists(&relative).await {
+            return io.is_watchable();
+        }
+    }
+    cfg!(not(target_arch = "wasm32"))
+}
+
+/// Returns paths of files and folders in the given folder, merged from every mounted source that
+/// supports listing and the file system.
+pub fn read_dir<P: AsRef<Path>>(path: P) -> Vec<PathBuf> {
+    let path = path.as_ref();
+    let key = archive_key(path);
+    let mut unique = FxHashSet::default();
+    let mut paths = Vec::new();
+
+    let mounted = MOUNTED
+        .read()
+        .iter()
+        .map(|mount_point| (mount_point.io.clone(), mount_point.prefix.clone()))
+        .collect::<Vec<_>>();
+    for (io, prefix) in mounted {
+        if let Some(relative) = strip_key_prefix(&key, &prefix) {
+            for entry in io.read_dir(Path::new(relative)).unwrap_or_default() {
+                let full = Path::new(&prefix).join(entry);
+                if unique.insert(full.clone()) {
+                    paths.push(full);
+                }
+            }
+        } else if let Some(relative) = strip_key_prefix(&prefix, &key) {
+            // The source is mounted inside of the folder, show its mount point as a folder.
+            if let Some(first) = relative.split('/').next() {
+                let full = path.join(first);
+                if unique.insert(full.clone()) {
+                    paths.push(full);
                 }
             }
-            None => false,
         }
     }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Ok(entries) = std::fs::read_dir(path) {
+        for entry in entries.flatten() {
+            if unique.insert(entry.path()) {
+                paths.push(entry.path());
+            }
+        }
+    }
+
+    paths
+}
+
+// Lists direct children of the folder using keys of files of a source.
+fn list_keys<'a, I: Iterator<Item = &'a str>>(keys: I, folder: &Path) -> Vec<PathBuf> {
+    let folder = archive_key(folder);
+    let mut children = FxHashSet::default();
+    for key in keys {
+        if let Some(relative) = strip_key_prefix(key, &folder) {
+            if let Some(first) = relative.split('/').next() {
+                children.insert(Path::new(&folder).join(first));
+            }
+        }
+    }
+    children.into_iter().collect()
+}
+
+/// A source of files (see [`ResourceIo`]) that reads files from a folder of the file system.
+/// Changes of the files could be tracked by the file system watcher.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, Default)]
+pub struct FsIo {
+    root: PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FsIo {
+    /// Creates new source, that reads files from the given folder.
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_owned(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ResourceIo for FsIo {
+    fn load_file<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Option<Result<Vec<u8>, FileLoadError>>> {
+        Box::pin(async move {
+            match std::fs::read(self.root.join(path)) {
+                Ok(data) => Some(Ok(data)),
+                Err(e) if e.kind() == ErrorKind::NotFound => None,
+                Err(e) => Some(Err(FileLoadError::Io(e))),
+            }
+        })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move { self.root.join(path).is_file() })
+    }
+
+    fn read_dir(&self, path: &Path) -> Option<Vec<PathBuf>> {
+        let entries = std::fs::read_dir(self.root.join(path)).ok()?;
+        Some(
+            entries
+                .flatten()
+                .map(|entry| path.join(entry.file_name()))
+                .collect(),
+        )
+    }
+
+    fn is_watchable(&self) -> bool {
+        true
+    }
+}
+
+/// A source of files (see [`ResourceIo`]) that provides files embedded into the executable, it
+/// allows you to ship a game as a single executable file.
+///
+/// ```no_run
+/// # use fyrox_core::io::{self, EmbeddedIo};
+/// # use std::sync::Arc;
+/// io::mount(Arc::new(
+///     EmbeddedIo::new().with_file("data/logo.png", include_bytes!("../../README.md")),
+/// ));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct EmbeddedIo {
+    files: FxHashMap<String, &'static [u8]>,
+}
+
+impl EmbeddedIo {
+    /// Creates new empty source.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file to the source.
+    pub fn add_file<P: AsRef<Path>>(&mut self, path: P, data: &'static [u8]) {
+        self.files.insert(archive_key(path.as_ref()), data);
+    }
+
+    /// Adds a file to the source.
+    pub fn with_file<P: AsRef<Path>>(mut self, path: P, data: &'static [u8]) -> Self {
+        self.add_file(path, data);
+        self
+    }
+}
+
+impl ResourceIo for EmbeddedIo {
+    fn load_file<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Option<Result<Vec<u8>, FileLoadError>>> {
+        Box::pin(async move {
+            self.files
+                .get(&archive_key(path))
+                .map(|data| Ok(data.to_vec()))
+        })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move { self.files.contains_key(&archive_key(path)) })
+    }
+
+    fn read_dir(&self, path: &Path) -> Option<Vec<PathBuf>> {
+        Some(list_keys(self.files.keys().map(|key| key.as_str()), path))
+    }
+}
+
+trait ZipSource: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ZipSource for T {}
+
+/// A source of files (see [`ResourceIo`]) that reads files from a zip archive. Stored and
+/// deflated files are supported.
+pub struct ZipIo {
+    archive: Mutex<ZipArchive<Box<dyn ZipSource>>>,
+    // Path in the archive + index of the file.
+    entries: FxHashMap<String, usize>,
+}
+
+impl ZipIo {
+    /// Opens a zip archive and reads its index.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, FileLoadError> {
+        Self::new(Box::new(std::io::BufReader::new(std::fs::File::open(
+            path,
+        )?)))
+    }
+
+    /// Creates a zip archive from its content, it is useful for archives embedded into the
+    /// executable or fetched from a server.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, FileLoadError> {
+        Self::new(Box::new(Cursor::new(data)))
+    }
+
+    fn new(source: Box<dyn ZipSource>) -> Result<Self, FileLoadError> {
+        let mut archive = ZipArchive::new(source)?;
+        let mut entries = FxHashMap::default();
+        for index in 0..archive.len() {
+            let file = archive.by_index_raw(index)?;
+            if file.is_file() {
+                entries.insert(archive_key(Path::new(file.name())), index);
+            }
+        }
+        Ok(Self {
+            archive: Mutex::new(archive),
+            entries,
+        })
+    }
+
+    /// Returns an iterator over paths of every file in the archive.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(|path| path.as_str())
+    }
+
+    fn read(&self, index: usize) -> Result<Vec<u8>, FileLoadError> {
+        let mut archive = self.archive.lock();
+        let mut file = archive.by_index(index)?;
+        let mut data = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut data)?;
+        Ok(data)
+    }
+}
+
+impl ResourceIo for ZipIo {
+    fn load_file<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Option<Result<Vec<u8>, FileLoadError>>> {
+        Box::pin(async move {
+            self.entries
+                .get(&archive_key(path))
+                .map(|index| self.read(*index))
+        })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move { self.entries.contains_key(&archive_key(path)) })
+    }
+
+    fn read_dir(&self, path: &Path) -> Option<Vec<PathBuf>> {
+        Some(list_keys(self.paths(), path))
+    }
+}
+
+// Fetches a file from the server the game was loaded from, `None` means that there's no such file.
+#[cfg(target_arch = "wasm32")]
+async fn fetch(url: &str) -> Result<Option<Vec<u8>>, FileLoadError> {
+    use js_sys::Uint8Array;
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+
+    match web_sys::window() {
+        Some(window) => {
+            let resp_value = JsFuture::from(window.fetch_with_str(url)).await?;
+            let resp: web_sys::Response = resp_value.dyn_into().unwrap();
+            if !resp.ok() {
+                return Ok(None);
+            }
+            let data = JsFuture::from(resp.array_buffer().unwrap()).await?;
+            Ok(Some(Uint8Array::new(&data).to_vec()))
+        }
+        None => Err(FileLoadError::Custom("Window not found!".to_owned())),
+    }
+}
+
+/// A source of files (see [`ResourceIo`]) that fetches files from a server, for example a CDN.
+/// Path of a file is appended to the base url of the source.
+#[cfg(target_arch = "wasm32")]
+#[derive(Clone, Debug)]
+pub struct FetchIo {
+    base_url: String,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl FetchIo {
+    /// Creates new source, that fetches files from the given url.
+    pub fn new<S: AsRef<str>>(base_url: S) -> Self {
+        Self {
+            base_url: base_url.as_ref().trim_end_matches('/').to_owned(),
+        }
+    }
+
+    fn url(&self, path: &Path) -> String {
+        format!("{}/{}", self.base_url, archive_key(path))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl ResourceIo for FetchIo {
+    fn load_file<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Option<Result<Vec<u8>, FileLoadError>>> {
+        Box::pin(async move { fetch(&self.url(path)).await.transpose() })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move { matches!(fetch(&self.url(path)).await, Ok(Some(_))) })
+    }
+}
+
+/// Magic of archive files, see [`ArchiveIo`].
+const ARCHIVE_MAGIC: &[u8; 4] = b"FYAR";
+
+/// Increment this if the layout of archive files has changed.
+const ARCHIVE_VERSION: u32 = 1;
+
+/// Converts a path to a key in the index of an archive: relative paths with forward slashes and
+/// without `.` components, so `./data\foo.png` and `data/foo.png` refer to the same file.
+fn archive_key(path: &Path) -> String {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+        .replace('\\', "/")
+}
+
+#[derive(Copy, Clone, Debug)]
+struct ArchiveEntry {
+    offset: u64,
+    size: u64,
+}
+
+#[derive(Debug)]
+enum ArchiveSource {
+    #[cfg(not(target_arch = "wasm32"))]
+    File(PathBuf),
+    Memory(Vec<u8>),
+}
+
+/// A source of files (see [`ResourceIo`]) that reads files from a single archive file made by
+/// [`ArchiveBuilder`]. Paths of files in the archive are relative to the working directory of a
+/// game, so a game could use the same paths for resources regardless of whether they're packed
+/// or not. Mount the archive using [`mount`] before loading any resources:
+///
+/// ```no_run
+/// # use fyrox_core::io::{self, ArchiveIo};
+/// # use std::sync::Arc;
+/// io::mount(Arc::new(ArchiveIo::open("data.pak").unwrap()));
+/// ```
+///
+/// The archive starts with an index (paths, offsets and sizes of files), only the index is
+/// read when the archive is opened, files are read on demand.
+#[derive(Debug)]
+pub struct ArchiveIo {
+    source: ArchiveSource,
+    entries: FxHashMap<String, ArchiveEntry>,
+}
+
+fn read_index<R: Read>(reader: &mut R) -> Result<FxHashMap<String, ArchiveEntry>, FileLoadError> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != ARCHIVE_MAGIC {
+        return Err(FileLoadError::Custom("Not an archive file!".to_owned()));
+    }
+
+    let version = reader.read_u32::<LittleEndian>()?;
+    if version != ARCHIVE_VERSION {
+        return Err(FileLoadError::Custom(format!(
+            "Unsupported archive version {}, expected {}!",
+            version, ARCHIVE_VERSION
+        )));
+    }
+
+    let count = reader.read_u32::<LittleEndian>()?;
+    let mut entries = FxHashMap::default();
+    for _ in 0..count {
+        let length = reader.read_u32::<LittleEndian>()?;
+        let mut path = vec![0; length as usize];
+        reader.read_exact(&mut path)?;
+        let path = String::from_utf8(path)
+            .map_err(|_| FileLoadError::Custom("Malformed path in archive index!".to_owned()))?;
+        let offset = reader.read_u64::<LittleEndian>()?;
+        let size = reader.read_u64::<LittleEndian>()?;
+        entries.insert(path, ArchiveEntry { offset, size });
+    }
+    Ok(entries)
+}
+
+impl ArchiveIo {
+    /// Opens an archive file and reads its index.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, FileLoadError> {
+        let mut reader = std::io::BufReader::new(std::fs::File::open(path.as_ref())?);
+        Ok(Self {
+            entries: read_index(&mut reader)?,
+            source: ArchiveSource::File(path.as_ref().to_owned()),
+        })
+    }
+
+    /// Creates an archive from its content, it is useful for platforms without file system
+    /// (for example the archive could be fetched by [`load_file`] on WebAssembly).
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, FileLoadError> {
+        Ok(Self {
+            entries: read_index(&mut Cursor::new(&data))?,
+            source: ArchiveSource::Memory(data),
+        })
+    }
+
+    /// Returns an iterator over paths of every file in the archive.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(|path| path.as_str())
+    }
+
+    fn read(&self, entry: ArchiveEntry) -> Result<Vec<u8>, FileLoadError> {
+        match &self.source {
+            #[cfg(not(target_arch = "wasm32"))]
+            ArchiveSource::File(path) => {
+                let mut file = std::fs::File::open(path)?;
+                file.seek(SeekFrom::Start(entry.offset))?;
+                let mut data = vec![0; entry.size as usize];
+                file.read_exact(&mut data)?;
+                Ok(data)
+            }
+            ArchiveSource::Memory(data) => {
+                let range = entry.offset as usize..(entry.offset + entry.size) as usize;
+                data.get(range).map(|data| data.to_vec()).ok_or_else(|| {
+                    FileLoadError::Io(Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "File is out of bounds of the archive!",
+                    ))
+                })
+            }
+        }
+    }
+}
+
+impl ResourceIo for ArchiveIo {
+    fn load_file<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Option<Result<Vec<u8>, FileLoadError>>> {
+        Box::pin(async move {
+            self.entries
+                .get(&archive_key(path))
+                .map(|entry| self.read(*entry))
+        })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move { self.entries.contains_key(&archive_key(path)) })
+    }
+
+    fn read_dir(&self, path: &Path) -> Option<Vec<PathBuf>> {
+        Some(list_keys(self.paths(), path))
+    }
+}
+
+/// Makes archive files for [`ArchiveIo`].
+#[derive(Default, Debug)]
+pub struct ArchiveBuilder {
+    // Path in the archive + path of the source file.
+    files: Vec<(String, PathBuf)>,
+}
+
+impl ArchiveBuilder {
+    /// Creates new empty archive builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file to the archive. `archive_path` is the path that will be used to load the file
+    /// from the archive, `source` is a path of the file in the file system.
+    pub fn add_file<P: AsRef<Path>, S: AsRef<Path>>(&mut self, archive_path: P, source: S) {
+        self.files.push((
+            archive_key(archive_path.as_ref()),
+            source.as_ref().to_owned(),
+        ));
+    }
+
+    /// Writes the archive to the given writer, content of the files is copied from the file
+    /// system.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let sizes = self
+            .files
+            .iter()
+            .map(|(_, source)| std::fs::metadata(source).map(|metadata| metadata.len()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let index_size = self
+            .files
+            .iter()
+            .map(|(path, _)| 4 + path.len() as u64 + 8 + 8)
+            .sum::<u64>();
+
+        writer.write_all(ARCHIVE_MAGIC)?;
+        writer.write_u32::<LittleEndian>(ARCHIVE_VERSION)?;
+        writer.write_u32::<LittleEndian>(self.files.len() as u32)?;
+
+        let mut offset = 4 + 4 + 4 + index_size;
+        for ((path, _), size) in self.files.iter().zip(sizes.iter()) {
+            writer.write_u32::<LittleEndian>(path.len() as u32)?;
+            writer.write_all(path.as_bytes())?;
+            writer.write_u64::<LittleEndian>(offset)?;
+            writer.write_u64::<LittleEndian>(*size)?;
+            offset += size;
+        }
+
+        for ((_, source), size) in self.files.iter().zip(sizes) {
+            let copied = std::io::copy(&mut std::fs::File::open(source)?, writer)?;
+            if copied != size {
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    format!("File {} was changed while archiving!", source.display()),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the archive to a file at the given path.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::io::{self, ArchiveBuilder, ArchiveIo, EmbeddedIo, FsIo, ResourceIo, ZipIo};
+    use futures::executor::block_on;
+    use std::{
+        io::{Cursor, Write},
+        path::{Path, PathBuf},
+        sync::Arc,
+    };
+    use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+    fn make_archive(name: &str) -> Vec<u8> {
+        let folder = std::env::temp_dir().join(name);
+        std::fs::create_dir_all(&folder).unwrap();
+        let foo = folder.join("foo.txt");
+        let bar = folder.join("bar.bin");
+        std::fs::write(&foo, b"Foo").unwrap();
+        std::fs::write(&bar, [1, 2, 3, 4, 5]).unwrap();
+
+        let mut builder = ArchiveBuilder::new();
+        builder.add_file("data/foo.txt", &foo);
+        builder.add_file("./data\\sub/bar.bin", &bar);
+        builder.add_file("data/copy.txt", &foo);
+        let mut archive = Vec::new();
+        builder.write(&mut archive).unwrap();
+
+        std::fs::remove_dir_all(&folder).unwrap();
+        archive
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let archive = ArchiveIo::from_bytes(make_archive("fyrox_archive_round_trip")).unwrap();
+
+        let mut paths = archive.paths().collect::<Vec<_>>();
+        paths.sort_unstable();
+        assert_eq!(paths, ["data/copy.txt", "data/foo.txt", "data/sub/bar.bin"]);
+
+        assert_eq!(
+            block_on(archive.load_file(Path::new("data/foo.txt")))
+                .unwrap()
+                .unwrap(),
+            b"Foo"
+        );
+        assert_eq!(
+            block_on(archive.load_file(Path::new("./data/foo.txt")))
+                .unwrap()
+                .unwrap(),
+            b"Foo"
+        );
+        assert_eq!(
+            block_on(archive.load_file(Path::new("data/sub/bar.bin")))
+                .unwrap()
+                .unwrap(),
+            [1, 2, 3, 4, 5]
+        );
+        assert!(block_on(archive.exists(Path::new("data/foo.txt"))));
+        assert!(!block_on(archive.exists(Path::new("data/baz.txt"))));
+        assert!(block_on(archive.load_file(Path::new("data/baz.txt"))).is_none());
+    }
+
+    #[test]
+    fn test_archive_from_file() {
+        let path = std::env::temp_dir().join("fyrox_archive_test_from_file.pak");
+        std::fs::write(&path, make_archive("fyrox_archive_from_file")).unwrap();
+
+        let archive = ArchiveIo::open(&path).unwrap();
+        assert_eq!(
+            block_on(archive.load_file(Path::new("data/foo.txt")))
+                .unwrap()
+                .unwrap(),
+            b"Foo"
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_mounted_archive() {
+        let archive: Arc<dyn ResourceIo> =
+            Arc::new(ArchiveIo::from_bytes(make_archive("fyrox_archive_mounted")).unwrap());
+
+        // There is no such file in the file system.
+        assert!(!block_on(io::exists("data/foo.txt")));
+
+        io::mount(archive.clone());
+        assert!(block_on(io::exists("data/foo.txt")));
+        assert_eq!(block_on(io::load_file("data/foo.txt")).unwrap(), b"Foo");
+        assert!(io::unmount(&archive));
+        assert!(!block_on(io::exists("data/foo.txt")));
+        assert!(!io::unmount(&archive));
+    }
+
+    #[test]
+    fn test_invalid_archive() {
+        assert!(ArchiveIo::from_bytes(b"RG3D".to_vec()).is_err());
+        assert!(ArchiveIo::from_bytes(Vec::new()).is_err());
+    }
+
+    fn make_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .add_directory("dir/", FileOptions::default())
+            .unwrap();
+        for (index, (path, data)) in files.iter().enumerate() {
+            // Mix compression methods, both must be readable.
+            let method = if index % 2 == 0 {
+                CompressionMethod::Stored
+            } else {
+                CompressionMethod::Deflated
+            };
+            writer
+                .start_file(*path, FileOptions::default().compression_method(method))
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn sorted(mut paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_zip() {
+        let zip = ZipIo::from_bytes(make_zip(&[
+            ("foo.txt", b"Foo"),
+            ("dir/bar.txt", b"Bar"),
+            ("dir/sub/baz.txt", b"Baz"),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            block_on(zip.load_file(Path::new("./dir/bar.txt")))
+                .unwrap()
+                .unwrap(),
+            b"Bar"
+        );
+        assert_eq!(
+            block_on(zip.load_file(Path::new("dir/sub/baz.txt")))
+                .unwrap()
+                .unwrap(),
+            b"Baz"
+        );
+        // Folders are not files.
+        assert!(!block_on(zip.exists(Path::new("dir"))));
+        assert!(block_on(zip.load_file(Path::new("bar.txt"))).is_none());
+        assert_eq!(
+            sorted(zip.read_dir(Path::new("dir")).unwrap()),
+            [PathBuf::from("dir/bar.txt"), PathBuf::from("dir/sub")]
+        );
+    }
+
+    #[test]
+    fn test_mount_prefix_and_priority() {
+        let base: Arc<dyn ResourceIo> = Arc::new(
+            ZipIo::from_bytes(make_zip(&[("a.txt", b"Base A"), ("b.txt", b"Base B")])).unwrap(),
+        );
+        let patch: Arc<dyn ResourceIo> =
+            Arc::new(ZipIo::from_bytes(make_zip(&[("b.txt", b"Patch B")])).unwrap());
+
+        // The patch is mounted first, but it still overrides the base, because of higher priority.
+        io::mount_at("fyrox_test_priority/data", 1, patch.clone());
+        io::mount_at("fyrox_test_priority/data", 0, base.clone());
+
+        assert_eq!(
+            block_on(io::load_file("fyrox_test_priority/data/a.txt")).unwrap(),
+            b"Base A"
+        );
+        assert_eq!(
+            block_on(io::load_file("fyrox_test_priority/data/b.txt")).unwrap(),
+            b"Patch B"
+        );
+        // Files are accessible only at the prefix.
+        assert!(!block_on(io::exists("a.txt")));
+        assert!(!block_on(io::exists("fyrox_test_priority/a.txt")));
+        assert_eq!(
+            sorted(io::read_dir("fyrox_test_priority/data")),
+            [
+                PathBuf::from("fyrox_test_priority/data/a.txt"),
+                PathBuf::from("fyrox_test_priority/data/b.txt")
+            ]
+        );
+        assert_eq!(
+            io::read_dir("fyrox_test_priority"),
+            [PathBuf::from("fyrox_test_priority/data")]
+        );
+        // Archives are not tracked by the watcher.
+        assert!(!block_on(io::is_watchable(
+            "fyrox_test_priority/data/b.txt"
+        )));
+
+        assert!(io::unmount(&patch));
+        assert_eq!(
+            block_on(io::load_file("fyrox_test_priority/data/b.txt")).unwrap(),
+            b"Base B"
+        );
+        assert!(io::unmount(&base));
+    }
+
+    #[test]
+    fn test_embedded() {
+        static FOO: &[u8] = b"Foo";
+
+        let embedded = EmbeddedIo::new()
+            .with_file("data/foo.txt", FOO)
+            .with_file("data/sub/bar.txt", b"Bar");
+
+        assert_eq!(
+            block_on(embedded.load_file(Path::new("data/foo.txt")))
+                .unwrap()
+                .unwrap(),
+            FOO
+        );
+        assert!(block_on(embedded.exists(Path::new("data/sub/bar.txt"))));
+        assert!(!block_on(embedded.exists(Path::new("data/bar.txt"))));
+        assert_eq!(
+            sorted(embedded.read_dir(Path::new("data")).unwrap()),
+            [PathBuf::from("data/foo.txt"), PathBuf::from("data/sub")]
+        );
+    }
+
+    #[test]
+    fn test_fs() {
+        let folder = std::env::temp_dir().join("fyrox_fs_io");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(folder.join("foo.txt"), b"Foo").unwrap();
+
+        let fs = FsIo::new(&folder);
+        assert_eq!(
+            block_on(fs.load_file(Path::new("foo.txt")))
+                .unwrap()
+                .unwrap(),
+            b"Foo"
+        );
+        assert!(block_on(fs.load_file(Path::new("bar.txt"))).is_none());
+        assert_eq!(
+            fs.read_dir(Path::new("")).unwrap(),
+            [PathBuf::from("foo.txt")]
+        );
+        assert!(fs.is_watchable());
+
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
 }
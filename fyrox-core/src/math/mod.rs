@@ -469,6 +469,35 @@ pub fn lerpf(a: f32, b: f32, t: f32) -> f32 {
     a + (b - a) * t
 }
 
+/// Returns frame-rate independent interpolation factor for exponential smoothing. Unlike naive
+/// `lerp(current, target, sharpness * dt)`, which depends on frame rate (and overshoots when
+/// `sharpness * dt > 1`), the result of the smoothing with this factor is the same for a single
+/// step of `dt` and for two steps of `dt / 2`. Higher sharpness means faster convergence. Zero `dt`
+/// (for example when the time is paused) gives zero factor.
+#[inline]
+pub fn damp_factor(sharpness: f32, dt: f32) -> f32 {
+    1.0 - (-sharpness.max(0.0) * dt.max(0.0)).exp()
+}
+
+/// Frame-rate independent exponential smoothing of `current` towards `target`. See
+/// [`damp_factor`] for more info.
+#[inline]
+pub fn dampf(current: f32, target: f32, sharpness: f32, dt: f32) -> f32 {
+    lerpf(current, target, damp_factor(sharpness, dt))
+}
+
+/// Frame-rate independent exponential smoothing of `current` towards `target`. See
+/// [`damp_factor`] for more info.
+#[inline]
+pub fn damp_vec3(
+    current: Vector3<f32>,
+    target: Vector3<f32>,
+    sharpness: f32,
+    dt: f32,
+) -> Vector3<f32> {
+    current.lerp(&target, damp_factor(sharpness, dt))
+}
+
 // https://en.wikipedia.org/wiki/Cubic_Hermite_spline
 #[inline]
 pub fn cubicf(p0: f32, p1: f32, t: f32, m0: f32, m1: f32) -> f32 {
@@ -1119,6 +1148,18 @@ mod test {
         .is_some());
     }
 
+    #[test]
+    fn damping_is_frame_rate_independent() {
+        let one_step = super::dampf(0.0, 10.0, 5.0, 0.1);
+        let half_step = super::dampf(0.0, 10.0, 5.0, 0.05);
+        let two_steps = super::dampf(half_step, 10.0, 5.0, 0.05);
+        assert!((one_step - two_steps).abs() < 1.0e-5);
+
+        // No time has passed - nothing must change.
+        assert_eq!(super::dampf(1.0, 10.0, 5.0, 0.0), 1.0);
+        assert_eq!(super::damp_factor(5.0, 0.0), 0.0);
+    }
+
     #[test]
     fn smooth_angle() {
         let mut angle = SmoothAngle {
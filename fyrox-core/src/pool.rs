@@ -19,6 +19,9 @@
 //! load portions of data into its cache piece by piece, it will be free from any
 //! indirections that might cause cache invalidation. This is the so called cache
 //! friendliness.
+//!
+//! With `pool_diagnostics` feature pools also remember where their objects were spawned and freed,
+//! see [`diagnostics`] module docs for more info.
 
 #![allow(clippy::unneeded_field_pattern)]
 
@@ -36,6 +39,9 @@ use std::{
     ops::{Index, IndexMut},
 };
 
+#[cfg(feature = "pool_diagnostics")]
+pub mod diagnostics;
+
 const INVALID_GENERATION: u32 = 0;
 
 pub trait PayloadContainer: Sized {
@@ -100,6 +106,8 @@ where
 {
     records: Vec<PoolRecord<T, P>>,
     free_stack: Vec<u32>,
+    #[cfg(feature = "pool_diagnostics")]
+    diagnostics: diagnostics::PoolDiagnostics,
 }
 
 impl<T: PartialEq> PartialEq for Pool<T> {
@@ -332,6 +340,10 @@ where
         let mut region = visitor.enter_region(name)?;
         self.records.visit("Records", &mut region)?;
         self.free_stack.visit("FreeStack", &mut region)?;
+        #[cfg(feature = "pool_diagnostics")]
+        if region.is_reading() {
+            self.diagnostics = Default::default();
+        }
         Ok(())
     }
 }
@@ -409,6 +421,8 @@ impl<T: Clone> Clone for Pool<T> {
         Self {
             records: self.records.clone(),
             free_stack: self.free_stack.clone(),
+            #[cfg(feature = "pool_diagnostics")]
+            diagnostics: self.diagnostics.clone(),
         }
     }
 }
@@ -422,6 +436,8 @@ where
         Pool {
             records: Vec::new(),
             free_stack: Vec::new(),
+            #[cfg(feature = "pool_diagnostics")]
+            diagnostics: Default::default(),
         }
     }
 
@@ -431,6 +447,8 @@ where
         Pool {
             records: Vec::with_capacity(capacity),
             free_stack: Vec::new(),
+            #[cfg(feature = "pool_diagnostics")]
+            diagnostics: Default::default(),
         }
     }
 
@@ -450,6 +468,7 @@ where
 
     #[inline]
     #[must_use]
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     pub fn spawn(&mut self, payload: T) -> Handle<T> {
         self.spawn_with(|_| payload)
     }
@@ -469,6 +488,7 @@ where
     ///
     /// [`take_reserve`]: Pool::take_reserve
     #[inline]
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     pub fn spawn_at(&mut self, index: u32, payload: T) -> Result<Handle<T>, T> {
         self.spawn_at_internal(index, INVALID_GENERATION, payload)
     }
@@ -487,10 +507,12 @@ where
     /// Panics if the index is occupied or reserved (e.g. by [`take_reserve`]).
     ///
     /// [`take_reserve`]: Pool::take_reserve
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     pub fn spawn_at_handle(&mut self, handle: Handle<T>, payload: T) -> Result<Handle<T>, T> {
         self.spawn_at_internal(handle.index, handle.generation, payload)
     }
 
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     fn spawn_at_internal(
         &mut self,
         index: u32,
@@ -519,6 +541,10 @@ where
                     record.generation = generation;
                     record.payload = P::new(payload);
 
+                    #[cfg(feature = "pool_diagnostics")]
+                    self.diagnostics
+                        .on_spawn(index, generation, std::panic::Location::caller());
+
                     Ok(Handle::new(index, generation))
                 }
             },
@@ -543,6 +569,10 @@ where
                     payload: P::new(payload),
                 });
 
+                #[cfg(feature = "pool_diagnostics")]
+                self.diagnostics
+                    .on_spawn(index, generation, std::panic::Location::caller());
+
                 Ok(Handle::new(index, generation))
             }
         }
@@ -552,6 +582,7 @@ where
     #[must_use]
     /// Construct a value with the handle it would be given.
     /// Note: Handle is _not_ valid until function has finished executing.
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     pub fn spawn_with<F: FnOnce(Handle<T>) -> T>(&mut self, callback: F) -> Handle<T> {
         if let Some(free_index) = self.free_stack.pop() {
             let record = self
@@ -576,6 +607,11 @@ where
 
             record.generation = generation;
             record.payload.replace(payload);
+
+            #[cfg(feature = "pool_diagnostics")]
+            self.diagnostics
+                .on_spawn(free_index, generation, std::panic::Location::caller());
+
             handle
         } else {
             // No free records, create new one
@@ -596,6 +632,10 @@ where
 
             self.records.push(record);
 
+            #[cfg(feature = "pool_diagnostics")]
+            self.diagnostics
+                .on_spawn(handle.index, generation, std::panic::Location::caller());
+
             handle
         }
     }
@@ -631,6 +671,11 @@ where
 
             record.generation = generation;
             record.payload.replace(payload);
+
+            #[cfg(feature = "pool_diagnostics")]
+            self.diagnostics
+                .on_spawn(free_index, generation, std::panic::Location::caller());
+
             handle
         } else {
             // No free records, create new one
@@ -651,6 +696,10 @@ where
 
             self.records.push(record);
 
+            #[cfg(feature = "pool_diagnostics")]
+            self.diagnostics
+                .on_spawn(handle.index, generation, std::panic::Location::caller());
+
             handle
         }
     }
@@ -664,7 +713,11 @@ where
     /// at handle's index is different than the object was there before).
     #[inline]
     #[must_use]
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     pub fn borrow(&self, handle: Handle<T>) -> &T {
+        #[cfg(feature = "pool_diagnostics")]
+        self.check_handle(handle, "borrow");
+
         if let Some(record) = self.records_get(handle.index) {
             if record.generation == handle.generation {
                 if let Some(payload) = record.payload.as_ref() {
@@ -706,7 +759,11 @@ where
     /// ```
     #[inline]
     #[must_use]
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     pub fn borrow_mut(&mut self, handle: Handle<T>) -> &mut T {
+        #[cfg(feature = "pool_diagnostics")]
+        self.check_handle(handle, "borrow");
+
         let record_count = self.records.len();
         if let Some(record) = self.records_get_mut(handle.index) {
             if record.generation == handle.generation {
@@ -906,12 +963,19 @@ where
     ///
     /// Panics if the given handle is invalid.
     #[inline]
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     pub fn free(&mut self, handle: Handle<T>) -> T {
+        #[cfg(feature = "pool_diagnostics")]
+        self.check_handle(handle, "free");
+
         let index = usize::try_from(handle.index).expect("index overflowed usize");
         if let Some(record) = self.records.get_mut(index) {
             if record.generation == handle.generation {
                 // Remember this index as free
                 self.free_stack.push(handle.index);
+                #[cfg(feature = "pool_diagnostics")]
+                self.diagnostics
+                    .on_free(handle.index, std::panic::Location::caller());
                 // Return current payload.
                 if let Some(payload) = record.payload.take() {
                     payload
@@ -933,12 +997,16 @@ where
     /// is invalid. After object is moved out if the pool, all handles to the object will become
     /// invalid.
     #[inline]
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     pub fn try_free(&mut self, handle: Handle<T>) -> Option<T> {
         let index = usize::try_from(handle.index).expect("index overflowed usize");
         self.records.get_mut(index).and_then(|record| {
             if record.generation == handle.generation {
                 if let Some(payload) = record.payload.take() {
                     self.free_stack.push(handle.index);
+                    #[cfg(feature = "pool_diagnostics")]
+                    self.diagnostics
+                        .on_free(handle.index, std::panic::Location::caller());
                     Some(payload)
                 } else {
                     None
@@ -970,7 +1038,11 @@ where
     ///
     /// [`put_back`]: Pool::put_back
     #[inline]
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     pub fn take_reserve(&mut self, handle: Handle<T>) -> (Ticket<T>, T) {
+        #[cfg(feature = "pool_diagnostics")]
+        self.check_handle(handle, "take");
+
         if let Some(record) = self.records_get_mut(handle.index) {
             if record.generation == handle.generation {
                 if let Some(payload) = record.payload.take() {
@@ -1036,8 +1108,12 @@ where
     /// Forgets that value at ticket was reserved and makes it usable again.
     /// Useful when you don't need to put value back by ticket, but just make
     /// pool record usable again.
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     pub fn forget_ticket(&mut self, ticket: Ticket<T>) {
         self.free_stack.push(ticket.index);
+        #[cfg(feature = "pool_diagnostics")]
+        self.diagnostics
+            .on_free(ticket.index, std::panic::Location::caller());
     }
 
     /// Returns total capacity of pool. Capacity has nothing about real amount of objects in pool!
@@ -1055,9 +1131,12 @@ where
     /// to each other. This method will make all produced handles invalid and any further
     /// calls for [`borrow`](Self::borrow) or [`borrow_mut`](Self::borrow_mut) will raise panic.
     #[inline]
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     pub fn clear(&mut self) {
         self.records.clear();
         self.free_stack.clear();
+        #[cfg(feature = "pool_diagnostics")]
+        self.diagnostics.on_clear(std::panic::Location::caller());
     }
 
     #[inline]
@@ -1126,13 +1205,23 @@ where
     }
 
     #[inline]
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     pub fn replace(&mut self, handle: Handle<T>, payload: T) -> Option<T> {
         let index_usize = usize::try_from(handle.index).expect("index overflowed usize");
         if let Some(record) = self.records.get_mut(index_usize) {
             if record.generation == handle.generation {
                 self.free_stack.retain(|i| *i != handle.index);
 
-                record.payload.replace(payload)
+                let old = record.payload.replace(payload);
+                #[cfg(feature = "pool_diagnostics")]
+                if old.is_none() {
+                    self.diagnostics.on_spawn(
+                        handle.index,
+                        handle.generation,
+                        std::panic::Location::caller(),
+                    );
+                }
+                old
             } else {
                 panic!("Attempt to replace object in pool using dangling handle! Handle is {:?}, but pool record has {} generation", handle, record.generation);
             }
@@ -1160,6 +1249,59 @@ where
         }
     }
 
+    /// Sets a label of an object at the given handle, the label is shown in diagnostics of invalid
+    /// handles and in leak reports. Does nothing if the handle is invalid.
+    #[cfg(feature = "pool_diagnostics")]
+    pub fn set_debug_label<S: Into<String>>(&mut self, handle: Handle<T>, label: S) {
+        if self.is_valid_handle(handle) {
+            self.diagnostics.set_label(handle.index, label.into());
+        }
+    }
+
+    /// Returns diagnostic information about an object at the given handle, `None` if the handle is
+    /// invalid.
+    #[cfg(feature = "pool_diagnostics")]
+    pub fn debug_info(&self, handle: Handle<T>) -> Option<&diagnostics::ObjectInfo> {
+        if self.is_valid_handle(handle) {
+            self.diagnostics
+                .object(handle.index)
+                .filter(|o| o.generation == handle.generation)
+        } else {
+            None
+        }
+    }
+
+    /// Explains why the given handle is invalid (or tells nothing useful if it is valid). This is
+    /// the same diagnostic that is shown when the pool panics because of an invalid handle.
+    #[cfg(feature = "pool_diagnostics")]
+    pub fn diagnose(&self, handle: Handle<T>) -> diagnostics::HandleDiagnostic {
+        self.diagnostics
+            .diagnose(handle.index, handle.generation, std::any::type_name::<T>())
+    }
+
+    /// Returns a list of objects that are still alive in the pool, grouped by their labels.
+    #[cfg(feature = "pool_diagnostics")]
+    pub fn leak_report(&self) -> diagnostics::LeakReport {
+        diagnostics::LeakReport::new(
+            std::any::type_name::<T>(),
+            self.pair_iter()
+                .filter_map(|(handle, _)| self.debug_info(handle)),
+        )
+    }
+
+    #[cfg(feature = "pool_diagnostics")]
+    #[track_caller]
+    fn check_handle(&self, handle: Handle<T>, action: &str) {
+        if !self.is_valid_handle(handle) {
+            let diagnostic = self.diagnose(handle);
+            diagnostics::notify_invalid_access(&diagnostic, std::panic::Location::caller());
+            panic!(
+                "Attempt to {} object using invalid handle {:?}! {}",
+                action, handle, diagnostic
+            );
+        }
+    }
+
     /// Creates new pool iterator that iterates over filled records in pool.
     ///
     /// # Example
@@ -1235,6 +1377,7 @@ where
 
     /// Retains pool records selected by `pred`. Useful when you need to remove all pool records
     /// by some criteria.
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     pub fn retain<F>(&mut self, mut pred: F)
     where
         F: FnMut(&T) -> bool,
@@ -1253,6 +1396,9 @@ where
             if !retain {
                 self.free_stack.push(i as u32);
                 record.payload.take(); // and Drop
+                #[cfg(feature = "pool_diagnostics")]
+                self.diagnostics
+                    .on_free(i as u32, std::panic::Location::caller());
             }
         }
     }
@@ -1307,6 +1453,7 @@ where
 {
     type Output = T;
 
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     fn index(&self, index: Handle<T>) -> &Self::Output {
         self.borrow(index)
     }
@@ -1317,6 +1464,7 @@ where
     T: 'static,
     P: PayloadContainer<Element = T> + 'static,
 {
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     fn index_mut(&mut self, index: Handle<T>) -> &mut Self::Output {
         self.borrow_mut(index)
     }
@@ -1581,4 +1729,72 @@ mod test {
         assert_eq!(pool.try_free(handle), None);
         assert_eq!(pool.free_stack.len(), 1);
     }
+
+    #[cfg(feature = "pool_diagnostics")]
+    #[test]
+    fn pool_test_stale_handle_diagnostic() {
+        let mut pool = Pool::<u32>::new();
+
+        let spawn_line = line!() + 1;
+        let foo = pool.spawn(1);
+        pool.set_debug_label(foo, "Foo");
+        let free_line = line!() + 1;
+        pool.free(foo);
+        let bar = pool.spawn(2);
+        pool.set_debug_label(bar, "Bar");
+        assert_eq!(bar.index, foo.index);
+
+        let diagnostic = pool.diagnose(foo);
+        assert_eq!(
+            diagnostic.object.as_ref().unwrap().label.as_deref(),
+            Some("Foo")
+        );
+        assert_eq!(
+            diagnostic.current.as_ref().unwrap().label.as_deref(),
+            Some("Bar")
+        );
+
+        let text = diagnostic.to_string();
+        assert!(text.contains("[Idx: 0; Gen: 1] of pool of `u32` is invalid"));
+        assert!(text.contains(&format!(
+            "It pointed to \"Foo\" spawned at {}:{}:",
+            file!(),
+            spawn_line
+        )));
+        assert!(text.contains(&format!("ago at {}:{}:", file!(), free_line)));
+        assert!(text.contains("The record is now occupied by \"Bar\" (generation 2)"));
+    }
+
+    #[cfg(feature = "pool_diagnostics")]
+    #[test]
+    #[should_panic(expected = "It pointed to \"Foo\"")]
+    fn pool_test_stale_handle_borrow_panics_with_diagnostic() {
+        let mut pool = Pool::<u32>::new();
+        let foo = pool.spawn(1);
+        pool.set_debug_label(foo, "Foo");
+        pool.free(foo);
+        let _ = pool.borrow(foo);
+    }
+
+    #[cfg(feature = "pool_diagnostics")]
+    #[test]
+    fn pool_test_leak_report() {
+        let mut pool = Pool::<u32>::new();
+        for i in 0..3 {
+            let handle = pool.spawn(i);
+            pool.set_debug_label(handle, "Foo");
+        }
+        let bar = pool.spawn(3);
+        pool.set_debug_label(bar, "Bar");
+        let baz = pool.spawn(4);
+        pool.free(baz);
+
+        let report = pool.leak_report();
+        assert_eq!(report.total_count(), 4);
+        assert_eq!(report.groups.len(), 2);
+        assert_eq!(report.groups[0].label, "Foo");
+        assert_eq!(report.groups[0].spawned_at.len(), 3);
+        assert_eq!(report.groups[1].label, "Bar");
+        assert!(report.to_string().contains("3 x \"Foo\""));
+    }
 }
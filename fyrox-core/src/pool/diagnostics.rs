@@ -0,0 +1,284 @@
+//! Diagnostics of pool records, that helps to find out why a handle became invalid. It is compiled
+//! in only with `pool_diagnostics` feature, without the feature pools do not store anything extra.
+//!
+//! When the feature is enabled, every pool remembers where each of its objects was spawned and freed
+//! (using caller location of pool methods), and an optional label of each object (for example scene
+//! graph labels nodes with their names and types). When an invalid handle is used to borrow or free
+//! an object, the panic message contains a [`HandleDiagnostic`] that tells what used to live in the
+//! record, when and where it was freed and what occupies the record now.
+//!
+//! Pools can also produce a [`LeakReport`] - a list of objects that are still alive, grouped by
+//! their labels. It is useful to find objects that should have been removed before destruction of
+//! a scene.
+
+use crate::parking_lot::{const_mutex, Mutex};
+use instant::Instant;
+use std::{
+    fmt::{Display, Formatter},
+    panic::Location,
+};
+
+/// A place in source code.
+pub type CallSite = &'static Location<'static>;
+
+/// A function that is called with a diagnostic and a place of access right before a pool panics
+/// because of an invalid handle. See [`set_invalid_access_hook`].
+pub type InvalidAccessHook = fn(&HandleDiagnostic, CallSite);
+
+static INVALID_ACCESS_HOOK: Mutex<Option<InvalidAccessHook>> = const_mutex(None);
+
+/// Sets a function that will be called right before a pool panics because of an invalid handle. It
+/// can be used to write diagnostics to a log of an application. Pass `None` to remove the hook.
+pub fn set_invalid_access_hook(hook: Option<InvalidAccessHook>) {
+    *INVALID_ACCESS_HOOK.lock() = hook;
+}
+
+pub(super) fn notify_invalid_access(diagnostic: &HandleDiagnostic, location: CallSite) {
+    let hook = *INVALID_ACCESS_HOOK.lock();
+    if let Some(hook) = hook {
+        hook(diagnostic, location)
+    }
+}
+
+/// Information about an object that was freed.
+#[derive(Copy, Clone, Debug)]
+pub struct FreeInfo {
+    /// A place where the object was freed.
+    pub location: CallSite,
+    /// Time at which the object was freed.
+    pub time: Instant,
+}
+
+/// Information about an object that lives (or lived) in a pool record.
+#[derive(Clone, Debug)]
+pub struct ObjectInfo {
+    /// Generation of the object, handles to the object have the same generation.
+    pub generation: u32,
+    /// Optional label of the object, see [`Pool::set_debug_label`](super::Pool::set_debug_label).
+    pub label: Option<String>,
+    /// A place where the object was spawned.
+    pub spawned_at: CallSite,
+    /// Information about freeing of the object, `None` if the object is still alive.
+    pub freed: Option<FreeInfo>,
+}
+
+impl ObjectInfo {
+    fn name(&self) -> &str {
+        self.label.as_deref().unwrap_or("<unlabeled>")
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(super) struct RecordDiagnostics {
+    current: Option<ObjectInfo>,
+    previous: Option<ObjectInfo>,
+}
+
+/// Per-record diagnostics of a pool. Records are added lazily, so records of a pool that were
+/// loaded from a file simply have no information.
+#[derive(Clone, Debug, Default)]
+pub(super) struct PoolDiagnostics {
+    records: Vec<RecordDiagnostics>,
+}
+
+impl PoolDiagnostics {
+    fn record_mut(&mut self, index: u32) -> &mut RecordDiagnostics {
+        let index = index as usize;
+        if index >= self.records.len() {
+            self.records.resize_with(index + 1, Default::default);
+        }
+        &mut self.records[index]
+    }
+
+    pub(super) fn on_spawn(&mut self, index: u32, generation: u32, location: CallSite) {
+        let record = self.record_mut(index);
+        if let Some(current) = record.current.take() {
+            // Possible only if the pool was loaded, or the record was made free by `forget_ticket`.
+            record.previous = Some(current);
+        }
+        record.current = Some(ObjectInfo {
+            generation,
+            label: None,
+            spawned_at: location,
+            freed: None,
+        });
+    }
+
+    pub(super) fn on_free(&mut self, index: u32, location: CallSite) {
+        let record = self.record_mut(index);
+        if let Some(mut current) = record.current.take() {
+            current.freed = Some(FreeInfo {
+                location,
+                time: Instant::now(),
+            });
+            record.previous = Some(current);
+        }
+    }
+
+    pub(super) fn on_clear(&mut self, location: CallSite) {
+        for index in 0..self.records.len() {
+            self.on_free(index as u32, location);
+        }
+    }
+
+    pub(super) fn set_label(&mut self, index: u32, label: String) {
+        if let Some(current) = self.record_mut(index).current.as_mut() {
+            current.label = Some(label);
+        }
+    }
+
+    pub(super) fn object(&self, index: u32) -> Option<&ObjectInfo> {
+        self.records
+            .get(index as usize)
+            .and_then(|r| r.current.as_ref())
+    }
+
+    pub(super) fn diagnose(
+        &self,
+        index: u32,
+        generation: u32,
+        type_name: &'static str,
+    ) -> HandleDiagnostic {
+        let record = self.records.get(index as usize);
+        let current = record.and_then(|r| r.current.clone());
+        let object = record.and_then(|r| {
+            r.current
+                .iter()
+                .chain(r.previous.iter())
+                .find(|o| o.generation == generation)
+                .cloned()
+        });
+        HandleDiagnostic {
+            index,
+            generation,
+            type_name,
+            object,
+            current,
+        }
+    }
+}
+
+/// Explanation why a handle is invalid.
+#[derive(Clone, Debug)]
+pub struct HandleDiagnostic {
+    /// Index of the handle.
+    pub index: u32,
+    /// Generation of the handle.
+    pub generation: u32,
+    /// Type of objects of the pool.
+    pub type_name: &'static str,
+    /// The object the handle pointed to, `None` if the pool knows nothing about it. A pool keeps
+    /// information only about a current object of a record and the last freed one.
+    pub object: Option<ObjectInfo>,
+    /// The object that lives in the record at the moment.
+    pub current: Option<ObjectInfo>,
+}
+
+impl Display for HandleDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Handle [Idx: {}; Gen: {}] of pool of `{}` is invalid.",
+            self.index, self.generation, self.type_name
+        )?;
+
+        match self.object.as_ref() {
+            Some(object) => {
+                write!(
+                    f,
+                    " It pointed to \"{}\" spawned at {}",
+                    object.name(),
+                    object.spawned_at
+                )?;
+                match object.freed.as_ref() {
+                    Some(freed) => write!(
+                        f,
+                        ", which was freed {:?} ago at {}.",
+                        freed.time.elapsed(),
+                        freed.location
+                    )?,
+                    None => write!(f, ", which is taken out of the pool at the moment.")?,
+                }
+            }
+            None => write!(f, " No information about the object it pointed to.")?,
+        }
+
+        match self.current.as_ref() {
+            Some(current) if current.generation != self.generation => write!(
+                f,
+                " The record is now occupied by \"{}\" (generation {}) spawned at {}.",
+                current.name(),
+                current.generation,
+                current.spawned_at
+            ),
+            Some(_) => Ok(()),
+            None => write!(f, " The record is vacant now."),
+        }
+    }
+}
+
+/// A group of alive objects with the same label, see [`LeakReport`].
+#[derive(Clone, Debug)]
+pub struct LeakGroup {
+    /// Label of the objects.
+    pub label: String,
+    /// Places where the objects were spawned, one for each object.
+    pub spawned_at: Vec<CallSite>,
+}
+
+/// A list of objects that are still alive in a pool, grouped by their labels.
+#[derive(Clone, Debug, Default)]
+pub struct LeakReport {
+    /// Type of objects of the pool.
+    pub type_name: &'static str,
+    /// Groups of objects, sorted by amount of objects in descending order.
+    pub groups: Vec<LeakGroup>,
+}
+
+impl LeakReport {
+    pub(super) fn new<'a, I>(type_name: &'static str, objects: I) -> Self
+    where
+        I: Iterator<Item = &'a ObjectInfo>,
+    {
+        let mut groups: Vec<LeakGroup> = Vec::new();
+        for object in objects {
+            match groups.iter_mut().find(|g| g.label == object.name()) {
+                Some(group) => group.spawned_at.push(object.spawned_at),
+                None => groups.push(LeakGroup {
+                    label: object.name().to_owned(),
+                    spawned_at: vec![object.spawned_at],
+                }),
+            }
+        }
+        groups.sort_by_key(|g| std::cmp::Reverse(g.spawned_at.len()));
+        Self { type_name, groups }
+    }
+
+    /// Returns total amount of alive objects.
+    pub fn total_count(&self) -> usize {
+        self.groups.iter().map(|g| g.spawned_at.len()).sum()
+    }
+
+    /// Returns `true` if there is no alive objects.
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+}
+
+impl Display for LeakReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} alive object(s) of type `{}`:",
+            self.total_count(),
+            self.type_name
+        )?;
+        for group in self.groups.iter() {
+            writeln!(f, "  {} x \"{}\"", group.spawned_at.len(), group.label)?;
+            for location in group.spawned_at.iter() {
+                writeln!(f, "    spawned at {}", location)?;
+            }
+        }
+        Ok(())
+    }
+}
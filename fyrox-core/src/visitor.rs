@@ -7,6 +7,14 @@
 //! container for data fields. Data field is tuple of name and value, value can be any of simple Rust
 //! types and some of basic structures of the crate. Main criteria of what could be the field and what
 //! not is the ability to be represented as set of bytes without any aliasing issues.
+//!
+//! # Errors
+//!
+//! Errors that are related to the structure of the data (missing fields or regions, mismatching
+//! field types) carry the full path of the region, for example `Scene/Graph/Pool/Records[142]/ItemData`,
+//! where `Records[142]` is the 142nd item of a vector. When the visitor is in lenient mode (see
+//! [`Visitor::set_lenient`]), such errors are recorded instead of aborting reading where it is
+//! possible to continue with default values.
 
 pub use fyrox_core_derive::Visit;
 
@@ -46,6 +54,7 @@ use std::{
 };
 use uuid::Uuid;
 
+#[derive(Clone)]
 pub enum FieldKind {
     Bool(bool),
     U8(u8),
@@ -172,16 +181,23 @@ impl<'a, T: Pod> Visit for PodVecView<'a, T> {
                             std::mem::forget(owned_bytes);
                             Ok(())
                         } else {
-                            Err(VisitError::TypeMismatch)
+                            visitor.type_mismatch(
+                                name,
+                                std::any::type_name::<Vec<T>>(),
+                                "PodArray of another type",
+                            )
                         }
                     }
-                    _ => Err(VisitError::FieldTypeDoesNotMatch),
+                    ref other => {
+                        let found = other.type_name();
+                        visitor.type_mismatch(name, std::any::type_name::<Vec<T>>(), found)
+                    }
                 }
             } else {
-                Err(VisitError::FieldDoesNotExist(name.to_owned()))
+                Err(VisitError::FieldDoesNotExist(visitor.field_path(name)))
             }
         } else if visitor.find_field(name).is_some() {
-            Err(VisitError::FieldAlreadyExists(name.to_owned()))
+            Err(VisitError::FieldAlreadyExists(visitor.field_path(name)))
         } else {
             let node = visitor.current_node();
             node.fields.push(Field::new(
@@ -207,6 +223,33 @@ impl<'a, T: Pod> Visit for PodVecView<'a, T> {
 }
 
 impl FieldKind {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Self::Bool(_) => "bool",
+            Self::U8(_) => "u8",
+            Self::I8(_) => "i8",
+            Self::U16(_) => "u16",
+            Self::I16(_) => "i16",
+            Self::U32(_) => "u32",
+            Self::I32(_) => "i32",
+            Self::U64(_) => "u64",
+            Self::I64(_) => "i64",
+            Self::F32(_) => "f32",
+            Self::F64(_) => "f64",
+            Self::Vector2(_) => "Vector2<f32>",
+            Self::Vector3(_) => "Vector3<f32>",
+            Self::Vector4(_) => "Vector4<f32>",
+            Self::UnitQuaternion(_) => "UnitQuaternion<f32>",
+            Self::Matrix2(_) => "Matrix2<f32>",
+            Self::Matrix3(_) => "Matrix3<f32>",
+            Self::Matrix4(_) => "Matrix4<f32>",
+            Self::Data(_) => "Data",
+            Self::Uuid(_) => "Uuid",
+            Self::UnitComplex(_) => "UnitComplex<f32>",
+            Self::PodArray { .. } => "PodArray",
+        }
+    }
+
     fn as_string(&self) -> String {
         match self {
             Self::Bool(data) => format!("<bool = {}>, ", data),
@@ -280,19 +323,23 @@ macro_rules! impl_field_data {
         impl Visit for $type_name {
             fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
                 if visitor.reading {
-                    if let Some(field) = visitor.find_field(name) {
-                        match field.kind {
-                            $($kind)*(data) => {
-                                *self = data.clone();
-                                Ok(())
-                            },
-                            _ => Err(VisitError::FieldTypeDoesNotMatch)
+                    let data = visitor.find_field(name).map(|field| match field.kind {
+                        $($kind)*(data) => Ok(data.clone()),
+                        ref other => Err(other.type_name()),
+                    });
+                    match data {
+                        Some(Ok(data)) => {
+                            *self = data;
+                            Ok(())
                         }
-                    } else {
-                        Err(VisitError::FieldDoesNotExist(name.to_owned()))
+                        Some(Err(found)) => {
+                            let expected = $($kind)*(self.clone()).type_name();
+                            visitor.type_mismatch(name, expected, found)
+                        }
+                        None => Err(VisitError::FieldDoesNotExist(visitor.field_path(name))),
                     }
                 } else if visitor.find_field(name).is_some() {
-                    Err(VisitError::FieldAlreadyExists(name.to_owned()))
+                    Err(VisitError::FieldAlreadyExists(visitor.field_path(name)))
                 } else {
                     let node = visitor.current_node();
                     node.fields.push(Field::new(name, $($kind)*(self.clone())));
@@ -339,13 +386,16 @@ impl<'a> Visit for Data<'a> {
                         *self.vec = data.clone();
                         Ok(())
                     }
-                    _ => Err(VisitError::FieldTypeDoesNotMatch),
+                    ref other => {
+                        let found = other.type_name();
+                        visitor.type_mismatch(name, "Data", found)
+                    }
                 }
             } else {
-                Err(VisitError::FieldDoesNotExist(name.to_owned()))
+                Err(VisitError::FieldDoesNotExist(visitor.field_path(name)))
             }
         } else if visitor.find_field(name).is_some() {
-            Err(VisitError::FieldAlreadyExists(name.to_owned()))
+            Err(VisitError::FieldAlreadyExists(visitor.field_path(name)))
         } else {
             let node = visitor.current_node();
             node.fields
@@ -355,6 +405,7 @@ impl<'a> Visit for Data<'a> {
     }
 }
 
+#[derive(Clone)]
 pub struct Field {
     name: String,
     kind: FieldKind,
@@ -364,12 +415,32 @@ pub struct Field {
 pub enum VisitError {
     Io(std::io::Error),
     UnknownFieldType(u8),
+    /// A field with the given path does not exist.
     FieldDoesNotExist(String),
+    /// A field with the given path already exists.
     FieldAlreadyExists(String),
+    /// A region with the given path already exists.
     RegionAlreadyExists(String),
     InvalidCurrentNode,
-    FieldTypeDoesNotMatch,
+    /// Actual type of a field does not match the type of the value that is being read.
+    FieldTypeDoesNotMatch {
+        /// Full path of the field.
+        path: String,
+        /// Type of the value that is being read.
+        expected: &'static str,
+        /// Type of the value that is stored in the field.
+        found: &'static str,
+    },
+    /// A region with the given path does not exist.
     RegionDoesNotExist(String),
+    /// An error has occurred inside a region with the given path, the error itself does not have
+    /// information about where it has happened.
+    InRegion {
+        /// Full path of the region.
+        path: String,
+        /// Actual error.
+        error: Box<VisitError>,
+    },
     NoActiveNode,
     NotSupportedFormat,
     InvalidName,
@@ -390,8 +461,17 @@ impl Display for VisitError {
             Self::FieldAlreadyExists(name) => write!(f, "field already exists {}", name),
             Self::RegionAlreadyExists(name) => write!(f, "region already exists {}", name),
             Self::InvalidCurrentNode => write!(f, "invalid current node"),
-            Self::FieldTypeDoesNotMatch => write!(f, "field type does not match"),
+            Self::FieldTypeDoesNotMatch {
+                path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "field type does not match {} - expected {}, found {}",
+                path, expected, found
+            ),
             Self::RegionDoesNotExist(name) => write!(f, "region does not exists {}", name),
+            Self::InRegion { path, error } => write!(f, "{} (in {})", error, path),
             Self::NoActiveNode => write!(f, "no active node"),
             Self::NotSupportedFormat => write!(f, "not supported format"),
             Self::InvalidName => write!(f, "invalid name"),
@@ -405,6 +485,35 @@ impl Display for VisitError {
     }
 }
 
+impl std::error::Error for VisitError {}
+
+impl VisitError {
+    /// Returns `true` if the error carries the path of the place where it has happened.
+    pub fn has_path(&self) -> bool {
+        matches!(
+            self,
+            Self::FieldDoesNotExist(_)
+                | Self::FieldAlreadyExists(_)
+                | Self::RegionAlreadyExists(_)
+                | Self::FieldTypeDoesNotMatch { .. }
+                | Self::RegionDoesNotExist(_)
+                | Self::InRegion { .. }
+        )
+    }
+
+    /// Attaches the given path to the error, if it does not have a path yet.
+    pub fn in_region(self, path: &str) -> Self {
+        if self.has_path() {
+            self
+        } else {
+            Self::InRegion {
+                path: path.to_owned(),
+                error: Box::new(self),
+            }
+        }
+    }
+}
+
 impl<'a, T> From<std::sync::PoisonError<std::sync::MutexGuard<'a, T>>> for VisitError {
     fn from(_: std::sync::PoisonError<std::sync::MutexGuard<'a, T>>) -> Self {
         Self::PoisonedMutex
@@ -742,6 +851,8 @@ pub struct Visitor {
     reading: bool,
     current_node: Handle<Node>,
     root: Handle<Node>,
+    lenient: bool,
+    errors: Vec<VisitError>,
     pub environment: Option<Arc<dyn Any>>,
 }
 
@@ -768,10 +879,111 @@ impl Visitor {
             reading: false,
             current_node: root,
             root,
+            lenient: false,
+            errors: Default::default(),
             environment: None,
         }
     }
 
+    /// Enables or disables lenient mode. In lenient mode errors that are not fatal for reading
+    /// (for example mismatching field types) are recorded (see [`Self::errors`]) instead of
+    /// aborting reading, the values that cannot be read are left with their default values.
+    /// Missing fields and regions are not recorded, because they're often optional (for example
+    /// when reading data saved by an older version).
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    /// Returns `true` if the visitor is in lenient mode, see [`Self::set_lenient`].
+    pub fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    /// Returns a list of errors that were recorded in lenient mode.
+    pub fn errors(&self) -> &[VisitError] {
+        &self.errors
+    }
+
+    /// Takes a list of errors that were recorded in lenient mode, leaving the list empty.
+    pub fn take_errors(&mut self) -> Vec<VisitError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Records the error if the visitor is reading data in lenient mode, so the caller can
+    /// continue with default values. Otherwise the error is returned back. The error gets the
+    /// path of the current region if it does not have a path.
+    pub fn report_error(&mut self, error: VisitError) -> VisitResult {
+        let error = error.in_region(&self.current_path());
+        if self.reading && self.lenient {
+            self.errors.push(error);
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+
+    fn type_mismatch(
+        &mut self,
+        name: &str,
+        expected: &'static str,
+        found: &'static str,
+    ) -> VisitResult {
+        let error = VisitError::FieldTypeDoesNotMatch {
+            path: self.field_path(name),
+            expected,
+            found,
+        };
+        self.report_error(error)
+    }
+
+    fn push_path_segment(path: &mut String, segment: &str) {
+        // Items of collections are shown as indices of their parent region.
+        match segment.strip_prefix("Item") {
+            Some(index)
+                if !path.is_empty()
+                    && !index.is_empty()
+                    && index.bytes().all(|b| b.is_ascii_digit()) =>
+            {
+                path.push('[');
+                path.push_str(index);
+                path.push(']');
+            }
+            _ => {
+                if !path.is_empty() {
+                    path.push('/');
+                }
+                path.push_str(segment);
+            }
+        }
+    }
+
+    fn node_path(&self, mut node_handle: Handle<Node>) -> String {
+        let mut segments = Vec::new();
+        while let Some(node) = self.nodes.try_borrow(node_handle) {
+            if node_handle != self.root {
+                segments.push(node.name.as_str());
+            }
+            node_handle = node.parent;
+        }
+
+        let mut path = String::new();
+        for segment in segments.iter().rev() {
+            Self::push_path_segment(&mut path, segment);
+        }
+        path
+    }
+
+    /// Returns full path of the current region, for example `Scene/Graph/Pool/Records[142]/ItemData`.
+    pub fn current_path(&self) -> String {
+        self.node_path(self.current_node)
+    }
+
+    fn field_path(&self, name: &str) -> String {
+        let mut path = self.current_path();
+        Self::push_path_segment(&mut path, name);
+        path
+    }
+
     fn find_field(&mut self, name: &str) -> Option<&mut Field> {
         let node = self.nodes.borrow_mut(self.current_node);
         for field in node.fields.iter_mut() {
@@ -805,14 +1017,14 @@ impl Visitor {
                 self.current_node = region;
                 Ok(RegionGuard(self))
             } else {
-                Err(VisitError::RegionDoesNotExist(name.to_owned()))
+                Err(VisitError::RegionDoesNotExist(self.field_path(name)))
             }
         } else {
             // Make sure that node does not exists already.
             for child_handle in node.children.iter() {
                 let child = self.nodes.borrow(*child_handle);
                 if child.name == name {
-                    return Err(VisitError::RegionAlreadyExists(name.to_owned()));
+                    return Err(VisitError::RegionAlreadyExists(self.field_path(name)));
                 }
             }
 
@@ -901,26 +1113,43 @@ impl Visitor {
         self.save_binary_to_memory(writer)
     }
 
-    fn load_node_binary(&mut self, file: &mut dyn Read) -> Result<Handle<Node>, VisitError> {
-        let name_len = file.read_u32::<LittleEndian>()? as usize;
-        let mut raw_name = vec![Default::default(); name_len];
-        file.read_exact(raw_name.as_mut_slice())?;
+    fn load_node_binary(
+        &mut self,
+        file: &mut dyn Read,
+        parent_path: &str,
+    ) -> Result<Handle<Node>, VisitError> {
+        let read_name = |file: &mut dyn Read| -> Result<String, VisitError> {
+            let name_len = file.read_u32::<LittleEndian>()? as usize;
+            let mut raw_name = vec![Default::default(); name_len];
+            file.read_exact(raw_name.as_mut_slice())?;
+            Ok(String::from_utf8(raw_name)?)
+        };
 
         let mut node = Node {
-            name: String::from_utf8(raw_name)?,
+            name: read_name(file).map_err(|e| e.in_region(parent_path))?,
             ..Node::default()
         };
 
-        let field_count = file.read_u32::<LittleEndian>()? as usize;
+        // Root node is not a part of paths.
+        let mut path = parent_path.to_owned();
+        if !parent_path.is_empty() || node.name != "__ROOT__" {
+            Self::push_path_segment(&mut path, &node.name);
+        }
+
+        let field_count = file
+            .read_u32::<LittleEndian>()
+            .map_err(|e| VisitError::from(e).in_region(&path))? as usize;
         for _ in 0..field_count {
-            let field = Field::load(file)?;
+            let field = Field::load(file).map_err(|e| e.in_region(&path))?;
             node.fields.push(field);
         }
 
         let mut children = Vec::new();
-        let child_count = file.read_u32::<LittleEndian>()? as usize;
+        let child_count = file
+            .read_u32::<LittleEndian>()
+            .map_err(|e| VisitError::from(e).in_region(&path))? as usize;
         for _ in 0..child_count {
-            children.push(self.load_node_binary(file)?);
+            children.push(self.load_node_binary(file, &path)?);
         }
 
         node.children = children.clone();
@@ -934,6 +1163,163 @@ impl Visitor {
         Ok(handle)
     }
 
+    /// Replaces strings (and paths) stored in the visitor with the values returned by the given
+    /// function, a string is left intact if the function returns `None`. Strings are detected by
+    /// their layout - a region with only `Length` and `Data` fields, so it is possible to patch
+    /// serialized data without knowing its actual structure, for example to fix paths to moved
+    /// resources. Returns the amount of replaced strings.
+    pub fn replace_strings<F>(&mut self, mut func: F) -> usize
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let mut count = 0;
+        for node in self.nodes.iter_mut() {
+            if node.fields.len() != 2 {
+                continue;
+            }
+
+            let (length, data) = match node.fields.as_mut_slice() {
+                [a, b] if a.name == "Length" && b.name == "Data" => (a, b),
+                [a, b] if a.name == "Data" && b.name == "Length" => (b, a),
+                _ => continue,
+            };
+
+            if let (FieldKind::U32(length), FieldKind::Data(bytes)) =
+                (&mut length.kind, &mut data.kind)
+            {
+                if *length as usize != bytes.len() {
+                    continue;
+                }
+
+                let new_string = match std::str::from_utf8(bytes) {
+                    Ok(string) => func(string),
+                    Err(_) => continue,
+                };
+
+                if let Some(new_string) = new_string {
+                    *length = new_string.len() as u32;
+                    *bytes = new_string.into_bytes();
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    fn find_child(&self, parent: Handle<Node>, name: &str) -> Option<Handle<Node>> {
+        self.nodes
+            .borrow(parent)
+            .children
+            .iter()
+            .cloned()
+            .find(|child| self.nodes.borrow(*child).name == name)
+    }
+
+    fn free_subtree(&mut self, node_handle: Handle<Node>) {
+        let node = self.nodes.free(node_handle);
+        for child in node.children {
+            self.free_subtree(child);
+        }
+    }
+
+    // Makes a deep copy of the node of another visitor and attaches it to the given parent.
+    fn copy_subtree(&mut self, source: &Visitor, source_node: Handle<Node>, parent: Handle<Node>) {
+        let source_node = source.nodes.borrow(source_node);
+        let mut node = Node::new(&source_node.name, parent);
+        node.fields = source_node.fields.clone();
+        let node_handle = self.nodes.spawn(node);
+        self.nodes.borrow_mut(parent).children.push(node_handle);
+        for &child in source_node.children.iter() {
+            self.copy_subtree(source, child, node_handle);
+        }
+    }
+
+    /// Removes every field and child region of the given child region of the current region,
+    /// whose name is rejected by the filter. It allows to keep only a part of serialized data,
+    /// for example only some fields of a structure. Returns the amount of removed entries.
+    pub fn filter_region<F>(&mut self, region: &str, mut filter: F) -> Result<usize, VisitError>
+    where
+        F: FnMut(&str) -> bool,
+    {
+        let region_handle = self
+            .find_child(self.current_node, region)
+            .ok_or_else(|| VisitError::RegionDoesNotExist(self.field_path(region)))?;
+
+        let node = self.nodes.borrow_mut(region_handle);
+        let field_count = node.fields.len();
+        node.fields.retain(|field| filter(&field.name));
+        let mut count = field_count - node.fields.len();
+
+        let children = std::mem::take(&mut node.children);
+        let mut retained = Vec::with_capacity(children.len());
+        for child in children {
+            if filter(&self.nodes.borrow(child).name) {
+                retained.push(child);
+            } else {
+                self.free_subtree(child);
+                count += 1;
+            }
+        }
+        self.nodes.borrow_mut(region_handle).children = retained;
+
+        Ok(count)
+    }
+
+    /// Copies fields and child regions accepted by the filter from the region `source_region` of
+    /// the current region of the `source` visitor to the child region `region` of the current
+    /// region of this visitor. Entries with the same names are replaced, the rest of entries are
+    /// left intact. It allows to apply partial data over complete data, which then can be read
+    /// back as usual. Returns the amount of copied entries.
+    pub fn merge_region<F>(
+        &mut self,
+        region: &str,
+        source: &Visitor,
+        source_region: &str,
+        mut filter: F,
+    ) -> Result<usize, VisitError>
+    where
+        F: FnMut(&str) -> bool,
+    {
+        let region_handle = self
+            .find_child(self.current_node, region)
+            .ok_or_else(|| VisitError::RegionDoesNotExist(self.field_path(region)))?;
+        let source_handle = source
+            .find_child(source.current_node, source_region)
+            .ok_or_else(|| VisitError::RegionDoesNotExist(source.field_path(source_region)))?;
+        let source_node = source.nodes.borrow(source_handle);
+
+        let mut count = 0;
+        for source_field in source_node.fields.iter() {
+            if !filter(&source_field.name) {
+                continue;
+            }
+            let node = self.nodes.borrow_mut(region_handle);
+            match node.fields.iter_mut().find(|f| f.name == source_field.name) {
+                Some(field) => *field = source_field.clone(),
+                None => node.fields.push(source_field.clone()),
+            }
+            count += 1;
+        }
+
+        for &source_child in source_node.children.iter() {
+            let name = source.nodes.borrow(source_child).name.as_str();
+            if !filter(name) {
+                continue;
+            }
+            if let Some(existing) = self.find_child(region_handle, name) {
+                self.free_subtree(existing);
+                self.nodes
+                    .borrow_mut(region_handle)
+                    .children
+                    .retain(|c| *c != existing);
+            }
+            self.copy_subtree(source, source_child, region_handle);
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
     pub async fn load_binary<P: AsRef<Path>>(path: P) -> Result<Self, VisitError> {
         Self::load_from_memory(io::load_file(path).await?)
     }
@@ -952,9 +1338,11 @@ impl Visitor {
             reading: true,
             current_node: Handle::NONE,
             root: Handle::NONE,
+            lenient: false,
+            errors: Default::default(),
             environment: None,
         };
-        visitor.root = visitor.load_node_binary(&mut reader)?;
+        visitor.root = visitor.load_node_binary(&mut reader, "")?;
         visitor.current_node = visitor.root;
         Ok(visitor)
     }
@@ -986,9 +1374,21 @@ where
         if region.reading {
             for index in 0..len {
                 let region_name = format!("Item{}", index);
-                let mut region = region.enter_region(region_name.as_str())?;
                 let mut object = T::default();
-                object.visit("ItemData", &mut region)?;
+                // In lenient mode broken items are replaced with partially read or default ones,
+                // this keeps indices of the rest of the items intact.
+                let entered = match region.enter_region(region_name.as_str()) {
+                    Ok(mut item_region) => {
+                        if let Err(e) = object.visit("ItemData", &mut item_region) {
+                            item_region.report_error(e)?;
+                        }
+                        Ok(())
+                    }
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = entered {
+                    region.report_error(e)?;
+                }
                 self.push(object);
             }
         } else {
@@ -1479,8 +1879,13 @@ impl<T: Visit> Visit for Range<T> {
 
 #[cfg(test)]
 mod test {
-    use crate::visitor::{Data, Visit, VisitResult, Visitor};
-    use std::{fs::File, io::Write, path::Path, rc::Rc};
+    use crate::visitor::{Data, Visit, VisitError, VisitResult, Visitor};
+    use std::{
+        fs::File,
+        io::Write,
+        path::{Path, PathBuf},
+        rc::Rc,
+    };
 
     #[derive(Visit, Default)]
     pub struct Model {
@@ -1583,4 +1988,181 @@ mod test {
             objects.visit("Objects", &mut visitor).unwrap();
         }
     }
+
+    #[test]
+    fn visitor_replace_strings_test() {
+        #[derive(Default, Visit)]
+        struct Asset {
+            name: String,
+            path: PathBuf,
+            id: u32,
+        }
+
+        let mut visitor = Visitor::new();
+        let mut assets = vec![
+            Asset {
+                name: "Foo".to_owned(),
+                path: "data/foo.png".into(),
+                id: 1,
+            },
+            Asset {
+                name: "Bar".to_owned(),
+                path: "data/bar.png".into(),
+                id: 2,
+            },
+        ];
+        assets.visit("Assets", &mut visitor).unwrap();
+
+        let mut visitor = Visitor::load_from_memory(visitor.save_binary_to_vec().unwrap()).unwrap();
+        let count = visitor.replace_strings(|string| {
+            string
+                .strip_prefix("data/")
+                .map(|rest| format!("data/textures/{}", rest))
+        });
+        assert_eq!(count, 2);
+
+        let mut visitor = Visitor::load_from_memory(visitor.save_binary_to_vec().unwrap()).unwrap();
+        let mut loaded: Vec<Asset> = Vec::new();
+        loaded.visit("Assets", &mut visitor).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].name, "Foo");
+        assert_eq!(loaded[0].path, PathBuf::from("data/textures/foo.png"));
+        assert_eq!(loaded[0].id, 1);
+        assert_eq!(loaded[1].name, "Bar");
+        assert_eq!(loaded[1].path, PathBuf::from("data/textures/bar.png"));
+        assert_eq!(loaded[1].id, 2);
+    }
+
+    #[derive(Default, Visit)]
+    struct Weapon {
+        name: String,
+        ammo: f32,
+    }
+
+    #[derive(Default, Visit)]
+    struct NewWeapon {
+        name: String,
+        ammo: u32,
+    }
+
+    fn save_weapons() -> Vec<u8> {
+        let mut visitor = Visitor::new();
+        let mut region = visitor.enter_region("Scene").unwrap();
+        let mut weapons = (0..3)
+            .map(|i| Weapon {
+                name: format!("Weapon{}", i),
+                ammo: i as f32,
+            })
+            .collect::<Vec<_>>();
+        weapons.visit("Weapons", &mut region).unwrap();
+        drop(region);
+        visitor.save_binary_to_vec().unwrap()
+    }
+
+    #[test]
+    fn visitor_error_path_test() {
+        let mut visitor = Visitor::load_from_memory(save_weapons()).unwrap();
+        let mut region = visitor.enter_region("Scene").unwrap();
+
+        let mut weapons: Vec<NewWeapon> = Vec::new();
+        match weapons.visit("Weapons", &mut region) {
+            Err(VisitError::FieldTypeDoesNotMatch {
+                path,
+                expected,
+                found,
+            }) => {
+                assert_eq!(path, "Scene/Weapons[0]/ItemData/Ammo");
+                assert_eq!(expected, "u32");
+                assert_eq!(found, "f32");
+            }
+            _ => panic!("type mismatch must be reported"),
+        }
+
+        let mut missing = 0u32;
+        match missing.visit("Missing", &mut region) {
+            Err(VisitError::FieldDoesNotExist(path)) => assert_eq!(path, "Scene/Missing"),
+            _ => panic!("missing field must be reported"),
+        }
+    }
+
+    #[test]
+    fn visitor_lenient_mode_test() {
+        let mut visitor = Visitor::load_from_memory(save_weapons()).unwrap();
+        visitor.set_lenient(true);
+        let mut region = visitor.enter_region("Scene").unwrap();
+
+        let mut weapons: Vec<NewWeapon> = Vec::new();
+        weapons.visit("Weapons", &mut region).unwrap();
+        drop(region);
+
+        // Every item is read, broken fields are left with default values.
+        assert_eq!(weapons.len(), 3);
+        for (i, weapon) in weapons.iter().enumerate() {
+            assert_eq!(weapon.name, format!("Weapon{}", i));
+            assert_eq!(weapon.ammo, 0);
+        }
+
+        let errors = visitor.take_errors();
+        assert_eq!(errors.len(), 3);
+        for (i, error) in errors.iter().enumerate() {
+            assert!(error
+                .to_string()
+                .contains(&format!("Scene/Weapons[{}]/ItemData/Ammo", i)));
+        }
+    }
+
+    #[test]
+    fn visitor_truncated_data_test() {
+        let mut data = save_weapons();
+        data.truncate(data.len() / 2);
+
+        match Visitor::load_from_memory(data) {
+            Err(VisitError::InRegion { path, error }) => {
+                assert!(path.starts_with("Scene/Weapons"), "{}", path);
+                assert!(matches!(*error, VisitError::Io(_)));
+            }
+            _ => panic!("truncated data must be reported"),
+        }
+    }
+
+    #[test]
+    fn visitor_filter_and_merge_region_test() {
+        // Save only the name of a weapon.
+        let mut visitor = Visitor::new();
+        let mut weapon = Weapon {
+            name: "Rifle".to_owned(),
+            ammo: 30.0,
+        };
+        weapon.visit("Weapon", &mut visitor).unwrap();
+        assert_eq!(
+            visitor
+                .filter_region("Weapon", |name| name == "Name")
+                .unwrap(),
+            1
+        );
+        let partial = Visitor::load_from_memory(visitor.save_binary_to_vec().unwrap()).unwrap();
+
+        // Apply it over complete data of another weapon.
+        let mut visitor = Visitor::new();
+        let mut other = Weapon {
+            name: "Pistol".to_owned(),
+            ammo: 12.0,
+        };
+        other.visit("Weapon", &mut visitor).unwrap();
+        assert_eq!(
+            visitor
+                .merge_region("Weapon", &partial, "Weapon", |_| true)
+                .unwrap(),
+            1
+        );
+        assert!(visitor
+            .merge_region("Weapon", &partial, "Unknown", |_| true)
+            .is_err());
+
+        let mut visitor = Visitor::load_from_memory(visitor.save_binary_to_vec().unwrap()).unwrap();
+        let mut loaded = Weapon::default();
+        loaded.visit("Weapon", &mut visitor).unwrap();
+        assert_eq!(loaded.name, "Rifle");
+        assert_eq!(loaded.ammo, 12.0);
+    }
 }
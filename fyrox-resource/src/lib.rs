@@ -9,6 +9,7 @@ use crate::core::{
 use std::fmt::Formatter;
 use std::{
     borrow::Cow,
+    error::Error,
     fmt::Debug,
     future::Future,
     hash::{Hash, Hasher},
@@ -28,6 +29,25 @@ pub trait ResourceData: 'static + Default + Debug + Visit + Send {
 
     /// Sets new path to resource data.
     fn set_path(&mut self, path: PathBuf);
+
+    /// Writes the resource data to the given file, so it could be loaded back later. The path of
+    /// the resource data stays the same, use [`Self::set_path`] to change it if needed. Not every
+    /// resource could be saved, the default implementation returns an error.
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        Err(format!(
+            "Unable to save {} resource to {}, saving is not supported!",
+            self.path().display(),
+            path.display()
+        )
+        .into())
+    }
+
+    /// Returns approximate amount of memory (in bytes) occupied by the heavy part of the data
+    /// (pixels, samples, etc.). It is used only for statistics of resource unloading, the default
+    /// implementation returns zero.
+    fn memory_usage(&self) -> usize {
+        0
+    }
 }
 
 /// A trait for resource load error.
@@ -278,6 +298,19 @@ impl<T: ResourceData, E: ResourceLoadError> Resource<T, E> {
             guard: self.state(),
         }
     }
+
+    /// Writes the resource data to the given file, see [`ResourceData::save`]. Fails if the
+    /// resource is not loaded yet or failed to load.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        match &mut *self.state() {
+            ResourceState::Ok(data) => data.save(path.as_ref()),
+            state => Err(format!(
+                "Unable to save {} resource, because it is not loaded!",
+                state.path().display()
+            )
+            .into()),
+        }
+    }
 }
 
 impl<T: ResourceData, E: ResourceLoadError> Default for Resource<T, E> {
@@ -402,6 +435,17 @@ impl<T: ResourceData, E: ResourceLoadError> ResourceState<T, E> {
         }
     }
 
+    /// Changes the path of the resource source, the resource itself stays intact. Useful when the
+    /// source file was moved or renamed.
+    #[inline]
+    pub fn set_path(&mut self, new_path: PathBuf) {
+        match self {
+            Self::Pending { path, .. } => *path = new_path,
+            Self::LoadError { path, .. } => *path = new_path,
+            Self::Ok(details) => details.set_path(new_path),
+        }
+    }
+
     /// Changes ResourceState::Pending state to ResourceState::Ok(data) with given `data`.
     /// Additionally it wakes all futures.
     #[inline]
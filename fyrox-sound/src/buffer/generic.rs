@@ -163,7 +163,7 @@ impl GenericBuffer {
     #[inline]
     pub fn duration(&self) -> Duration {
         Duration::from_secs_f64(
-            (self.samples.len() / (self.channel_count * self.sample_rate)) as f64,
+            self.samples.len() as f64 / (self.channel_count * self.sample_rate) as f64,
         )
     }
 }
@@ -8,7 +8,9 @@
 //! is just inefficient memory-wise. Sound samples are very heavy: for example a mono sound that lasts
 //! just 1 second will take ~172 Kb of memory (with 44100 Hz sampling rate and float sample representation).
 
-use crate::buffer::{generic::GenericBuffer, streaming::StreamingBuffer};
+use crate::buffer::{
+    generic::GenericBuffer, procedural::ProceduralBuffer, streaming::StreamingBuffer,
+};
 use crate::error::SoundError;
 use fyrox_core::{io::FileLoadError, visitor::prelude::*};
 use fyrox_resource::{define_new_resource, Resource, ResourceData, ResourceState};
@@ -22,6 +24,7 @@ use std::{
 };
 
 pub mod generic;
+pub mod procedural;
 pub mod streaming;
 
 /// Data source enumeration. Provides unified way of selecting data source for sound buffers. It can be either
@@ -103,6 +106,12 @@ impl DataSource {
     where
         P: AsRef<Path>,
     {
+        // Files of mounted sources (archives, etc.) are read into memory entirely.
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(data) = fyrox_core::io::load_mounted_file(path.as_ref()).await {
+            return Ok(DataSource::from_memory(data?));
+        }
+
         Ok(DataSource::File {
             path: path.as_ref().to_path_buf(),
 
@@ -180,6 +189,14 @@ impl SoundBufferResource {
             SoundBufferState::Generic(GenericBuffer::new(data_source)?),
         ))))
     }
+
+    /// Creates new procedural sound buffer, that pulls samples from code. See
+    /// [`procedural`] module docs for more info.
+    pub fn new_procedural(buffer: ProceduralBuffer) -> Self {
+        Self(Resource::new(ResourceState::Ok(
+            SoundBufferState::Procedural(buffer),
+        )))
+    }
 }
 
 /// Sound buffer is a data source for sound sources. See module documentation for more info.
@@ -194,6 +211,11 @@ pub enum SoundBufferState {
     /// takes very large amount of RAM. Allows random access only to currently loaded
     /// block, so in general there is no *true* random access.
     Streaming(StreamingBuffer),
+
+    /// Buffer that pulls small portions of samples from code, exactly when they are needed.
+    /// Used for sounds synthesized at runtime. Its source of samples is not serialized, see
+    /// [`procedural`] module docs.
+    Procedural(ProceduralBuffer),
 }
 
 impl SoundBufferState {
@@ -208,6 +230,17 @@ impl SoundBufferState {
     pub fn raw_generic(data_source: DataSource) -> Result<Self, DataSource> {
         Ok(Self::Generic(GenericBuffer::new(data_source)?))
     }
+
+    /// Returns total duration of the sound. Streaming buffers contain only a small portion of
+    /// data, so their duration is taken from the decoder. Zero duration is returned if a raw
+    /// streaming source does not know its length and for procedural buffers.
+    pub fn duration(&self) -> Duration {
+        match self {
+            SoundBufferState::Generic(generic) => generic.duration(),
+            SoundBufferState::Streaming(streaming) => streaming.duration().unwrap_or_default(),
+            SoundBufferState::Procedural(_) => Duration::default(),
+        }
+    }
 }
 
 impl Default for SoundBufferState {
@@ -220,22 +253,24 @@ impl Deref for SoundBufferState {
     type Target = GenericBuffer;
 
     /// Returns shared reference to generic buffer for any enum variant. It is possible because
-    /// streaming sound buffers are built on top of generic buffers.
+    /// streaming and procedural sound buffers are built on top of generic buffers.
     fn deref(&self) -> &Self::Target {
         match self {
             SoundBufferState::Generic(v) => v,
             SoundBufferState::Streaming(v) => v,
+            SoundBufferState::Procedural(v) => v,
         }
     }
 }
 
 impl DerefMut for SoundBufferState {
     /// Returns mutable reference to generic buffer for any enum variant. It is possible because
-    /// streaming sound buffers are built on top of generic buffers.
+    /// streaming and procedural sound buffers are built on top of generic buffers.
     fn deref_mut(&mut self) -> &mut Self::Target {
         match self {
             SoundBufferState::Generic(v) => v,
             SoundBufferState::Streaming(v) => v,
+            SoundBufferState::Procedural(v) => v,
         }
     }
 }
@@ -248,4 +283,8 @@ impl ResourceData for SoundBufferState {
     fn set_path(&mut self, path: PathBuf) {
         self.external_source_path = path;
     }
+
+    fn memory_usage(&self) -> usize {
+        self.samples.len() * std::mem::size_of::<f32>()
+    }
 }
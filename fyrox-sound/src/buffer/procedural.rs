@@ -0,0 +1,410 @@
+//! Procedural buffer.
+//!
+//! # Overview
+//!
+//! Procedural buffers are used for sounds that are synthesized at runtime (engine sounds, procedural
+//! footsteps, etc.). Samples are pulled from a [`ProceduralSource`] in small portions, exactly when the
+//! mixer needs them, so the sound reacts on changes of the game state with very small latency. A source
+//! could be a callback (see [`ProceduralBuffer::from_callback`]) or a queue of samples, that the game
+//! fills from any thread (see [`ProceduralBuffer::with_queue`]).
+//!
+//! If a source cannot provide enough samples (an underrun), the missing part is filled with silence
+//! and the underrun counter of the buffer is incremented, see [`ProceduralBuffer::underrun_count`].
+//!
+//! # Usage
+//!
+//! ```no_run
+//! use fyrox_sound::buffer::{procedural::ProceduralBuffer, SoundBufferResource};
+//!
+//! fn make_sine_buffer() -> SoundBufferResource {
+//!     let mut phase = 0.0f32;
+//!     let buffer = ProceduralBuffer::from_callback(44100, 1, move |samples: &mut [f32]| {
+//!         for sample in samples.iter_mut() {
+//!             *sample = phase.sin();
+//!             phase += 440.0 * std::f32::consts::TAU / 44100.0;
+//!         }
+//!         samples.len()
+//!     })
+//!     .unwrap();
+//!     SoundBufferResource::new_procedural(buffer)
+//! }
+//! ```
+//!
+//! # Serialization
+//!
+//! Sources of procedural buffers are code, they cannot be saved. Only sample rate and channel count
+//! of a buffer are saved, a loaded buffer is a placeholder that produces silence until a new source
+//! is bound to it using [`ProceduralBuffer::set_source`]. Games must re-bind sources of procedural
+//! buffers after loading a saved game.
+//!
+//! # Notes
+//!
+//! Procedural buffer cannot be shared across multiple sources, because every source would pull its
+//! own portion of samples. On attempt to create a source with a procedural buffer that already in use
+//! you'll get error.
+
+use crate::{buffer::generic::GenericBuffer, error::SoundError};
+use fyrox_core::visitor::{Visit, VisitResult, Visitor};
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Formatter},
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex},
+};
+
+/// A source of samples for procedural buffers.
+pub trait ProceduralSource: Send + Debug {
+    /// Fills the given slice with samples in interleaved format and returns the amount of written
+    /// samples. Length of the slice is always a multiple of the channel count of the buffer. If the
+    /// returned amount is less than the length of the slice, the rest of the slice is filled with
+    /// silence and it is counted as an underrun.
+    fn fill(&mut self, samples: &mut [f32]) -> usize;
+
+    /// Called when a sound source that plays the buffer is stopped. Can be used to reset the state
+    /// of a generator or to drop queued samples.
+    fn stop(&mut self) {}
+}
+
+struct CallbackSource<F>(F);
+
+impl<F> Debug for CallbackSource<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CallbackSource")
+    }
+}
+
+impl<F> ProceduralSource for CallbackSource<F>
+where
+    F: FnMut(&mut [f32]) -> usize + Send,
+{
+    fn fill(&mut self, samples: &mut [f32]) -> usize {
+        (self.0)(samples)
+    }
+}
+
+/// A bounded queue of samples in interleaved format. The queue can be cloned and filled from any
+/// thread, while a procedural buffer pulls samples from it.
+#[derive(Clone, Debug)]
+pub struct SampleQueue {
+    samples: Arc<Mutex<VecDeque<f32>>>,
+    capacity: usize,
+}
+
+impl SampleQueue {
+    /// Creates new queue that can hold the given amount of samples.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Puts samples at the end of the queue and returns the amount of samples that were put. Samples
+    /// that do not fit in the queue are discarded.
+    pub fn push(&self, samples: &[f32]) -> usize {
+        let mut queue = self.samples.lock().unwrap();
+        let count = samples.len().min(self.capacity - queue.len());
+        queue.extend(&samples[..count]);
+        count
+    }
+
+    /// Returns the amount of samples in the queue.
+    pub fn len(&self) -> usize {
+        self.samples.lock().unwrap().len()
+    }
+
+    /// Returns `true` if the queue has no samples.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns max amount of samples the queue can hold.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Removes every sample from the queue.
+    pub fn clear(&self) {
+        self.samples.lock().unwrap().clear();
+    }
+}
+
+impl ProceduralSource for SampleQueue {
+    fn fill(&mut self, samples: &mut [f32]) -> usize {
+        let mut queue = self.samples.lock().unwrap();
+        let count = samples.len().min(queue.len());
+        for (sample, queued) in samples.iter_mut().zip(queue.drain(..count)) {
+            *sample = queued;
+        }
+        count
+    }
+
+    fn stop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Procedural buffer for sounds synthesized at runtime. Does not support random access.
+#[derive(Debug, Default)]
+pub struct ProceduralBuffer {
+    pub(in crate) generic: GenericBuffer,
+    /// Count of sources that share this buffer, only one source can pull samples from the buffer.
+    pub(in crate) use_count: usize,
+    source: Option<Box<dyn ProceduralSource>>,
+    underrun_count: usize,
+}
+
+impl ProceduralBuffer {
+    /// Creates new procedural buffer that pulls samples from the given source. Only mono and stereo
+    /// buffers are supported, `Err` is returned for other channel counts.
+    pub fn new(
+        sample_rate: usize,
+        channel_count: usize,
+        source: Box<dyn ProceduralSource>,
+    ) -> Result<Self, SoundError> {
+        if !(1..=2).contains(&channel_count) || sample_rate == 0 {
+            return Err(SoundError::UnsupportedFormat);
+        }
+
+        Ok(Self {
+            generic: GenericBuffer {
+                samples: Default::default(),
+                channel_count,
+                sample_rate,
+                external_source_path: Default::default(),
+            },
+            use_count: 0,
+            source: Some(source),
+            underrun_count: 0,
+        })
+    }
+
+    /// Creates new procedural buffer that pulls samples from the given callback. See
+    /// [`ProceduralSource::fill`] for the meaning of the arguments and the return value of the callback.
+    pub fn from_callback<F>(
+        sample_rate: usize,
+        channel_count: usize,
+        callback: F,
+    ) -> Result<Self, SoundError>
+    where
+        F: FnMut(&mut [f32]) -> usize + Send + 'static,
+    {
+        Self::new(
+            sample_rate,
+            channel_count,
+            Box::new(CallbackSource(callback)),
+        )
+    }
+
+    /// Creates new procedural buffer that pulls samples from a queue of the given capacity. Returns
+    /// the buffer and the queue that should be filled by the game.
+    pub fn with_queue(
+        sample_rate: usize,
+        channel_count: usize,
+        capacity: usize,
+    ) -> Result<(Self, SampleQueue), SoundError> {
+        let queue = SampleQueue::new(capacity);
+        Ok((
+            Self::new(sample_rate, channel_count, Box::new(queue.clone()))?,
+            queue,
+        ))
+    }
+
+    /// Binds new source of samples to the buffer and returns previous one. Used to re-bind sources
+    /// of loaded buffers, see module docs.
+    pub fn set_source(
+        &mut self,
+        source: Option<Box<dyn ProceduralSource>>,
+    ) -> Option<Box<dyn ProceduralSource>> {
+        self.generic.samples.clear();
+        std::mem::replace(&mut self.source, source)
+    }
+
+    /// Returns `true` if the buffer has a source of samples. Loaded buffers have no source until it
+    /// is bound by [`Self::set_source`].
+    pub fn has_source(&self) -> bool {
+        self.source.is_some()
+    }
+
+    /// Returns the amount of times the source was unable to provide requested amount of samples.
+    pub fn underrun_count(&self) -> usize {
+        self.underrun_count
+    }
+
+    /// Pulls the given amount of samples per channel from the source. Buffers without a source are
+    /// filled with silence, it is not counted as an underrun.
+    pub(in crate) fn read_next_block(&mut self, frame_count: usize) {
+        let samples = &mut self.generic.samples;
+        samples.clear();
+        samples.resize(frame_count * self.generic.channel_count, 0.0);
+        if let Some(source) = self.source.as_mut() {
+            let count = source.fill(samples);
+            if count < samples.len() {
+                for sample in &mut samples[count..] {
+                    *sample = 0.0;
+                }
+                self.underrun_count += 1;
+            }
+        }
+    }
+
+    /// Discards pulled samples and notifies the source that playback is stopped.
+    pub(in crate) fn stop(&mut self) {
+        self.generic.samples.clear();
+        if let Some(source) = self.source.as_mut() {
+            source.stop();
+        }
+    }
+}
+
+impl Visit for ProceduralBuffer {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        let mut sample_rate = self.generic.sample_rate as u32;
+        sample_rate.visit("SampleRate", &mut region)?;
+        let mut channel_count = self.generic.channel_count as u32;
+        channel_count.visit("ChannelCount", &mut region)?;
+
+        if region.is_reading() {
+            self.generic.sample_rate = sample_rate as usize;
+            self.generic.channel_count = channel_count as usize;
+        }
+
+        Ok(())
+    }
+}
+
+impl Deref for ProceduralBuffer {
+    type Target = GenericBuffer;
+
+    /// Returns shared reference to internal generic buffer. It contains only the last pulled portion
+    /// of samples.
+    fn deref(&self) -> &Self::Target {
+        &self.generic
+    }
+}
+
+impl DerefMut for ProceduralBuffer {
+    /// Returns mutable reference to internal generic buffer.
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.generic
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        buffer::{procedural::ProceduralBuffer, SoundBufferResource, SoundBufferState},
+        context::SAMPLE_RATE,
+        source::{SoundSource, SoundSourceBuilder, Status},
+    };
+    use fyrox_core::visitor::{Visit, Visitor};
+    use std::sync::{Arc, Mutex};
+
+    fn make_source(buffer: SoundBufferResource) -> SoundSource {
+        SoundSourceBuilder::new()
+            .with_buffer(buffer)
+            .with_status(Status::Playing)
+            .build()
+            .unwrap()
+    }
+
+    fn underrun_count(buffer: &SoundBufferResource) -> usize {
+        match *buffer.data_ref() {
+            SoundBufferState::Procedural(ref procedural) => procedural.underrun_count(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_callback_is_pulled_with_requested_chunks() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let callback_requests = requests.clone();
+        let mut counter = 0.0;
+        let buffer = SoundBufferResource::new_procedural(
+            ProceduralBuffer::from_callback(SAMPLE_RATE as usize, 2, move |samples| {
+                callback_requests.lock().unwrap().push(samples.len());
+                for frame in samples.chunks_mut(2) {
+                    frame[0] = counter;
+                    frame[1] = -counter;
+                    counter += 1.0;
+                }
+                samples.len()
+            })
+            .unwrap(),
+        );
+        let mut source = make_source(buffer.clone());
+
+        let mut rendered = Vec::new();
+        for amount in [512, 512, 100, 1000] {
+            source.render(amount);
+            rendered.extend_from_slice(source.frame_samples());
+        }
+
+        assert_eq!(*requests.lock().unwrap(), vec![1024, 1024, 200, 2000]);
+        for (i, &(left, right)) in rendered.iter().enumerate() {
+            assert_eq!((left, right), (i as f32, -(i as f32)));
+        }
+        assert_eq!(underrun_count(&buffer), 0);
+        assert_eq!(source.status(), Status::Playing);
+
+        // Stopped source must not pull samples.
+        source.stop().unwrap();
+        source.render(512);
+        assert_eq!(requests.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_queue_underrun_is_silent() {
+        let (buffer, queue) = ProceduralBuffer::with_queue(SAMPLE_RATE as usize, 1, 4096).unwrap();
+        let buffer = SoundBufferResource::new_procedural(buffer);
+        let mut source = make_source(buffer.clone());
+
+        assert_eq!(queue.push(&[0.5; 300]), 300);
+        source.render(200);
+        assert!(source.frame_samples().iter().all(|&s| s == (0.5, 0.5)));
+        assert_eq!(underrun_count(&buffer), 0);
+
+        source.render(200);
+        assert!(source.frame_samples()[..100]
+            .iter()
+            .all(|&s| s == (0.5, 0.5)));
+        assert!(source.frame_samples()[100..]
+            .iter()
+            .all(|&s| s == (0.0, 0.0)));
+        assert_eq!(underrun_count(&buffer), 1);
+
+        // Queued samples are dropped when the source stops.
+        queue.push(&[0.5; 100]);
+        source.stop().unwrap();
+        assert!(queue.is_empty());
+
+        // The queue is bounded.
+        assert_eq!(queue.push(&[0.0; 5000]), 4096);
+    }
+
+    #[test]
+    fn test_procedural_buffer_is_saved_as_placeholder() {
+        let mut buffer = SoundBufferState::Procedural(
+            ProceduralBuffer::from_callback(22050, 2, |samples| samples.len()).unwrap(),
+        );
+
+        let mut visitor = Visitor::new();
+        buffer.visit("Buffer", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        let mut loaded = SoundBufferState::default();
+        loaded.visit("Buffer", &mut visitor).unwrap();
+
+        match loaded {
+            SoundBufferState::Procedural(ref procedural) => {
+                assert_eq!(procedural.sample_rate(), 22050);
+                assert_eq!(procedural.channel_count(), 2);
+                assert!(!procedural.has_source());
+            }
+            _ => panic!("procedural buffer must be loaded as procedural"),
+        }
+    }
+}
@@ -196,14 +196,19 @@ impl StreamingBuffer {
             .read_next_samples_block_into(&mut self.generic.samples);
     }
 
+    /// Moves the decoder to the beginning of the stream and loads the first block.
     #[inline]
     pub(in crate) fn rewind(&mut self) -> Result<(), SoundError> {
-        self.streaming_source.rewind()
+        let result = self.streaming_source.rewind();
+        self.read_next_block();
+        result
     }
 
+    /// Moves the decoder to the given position and loads the block that starts at it.
     #[inline]
     pub(in crate) fn time_seek(&mut self, location: Duration) {
         self.streaming_source.time_seek(location);
+        self.read_next_block();
     }
 }
 
@@ -223,3 +228,138 @@ impl DerefMut for StreamingBuffer {
         &mut self.generic
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        buffer::{streaming::StreamingBuffer, DataSource, SoundBufferResource},
+        context,
+        source::{SoundSource, SoundSourceBuilder, Status},
+    };
+    use std::{io::Cursor, time::Duration};
+
+    const SAMPLE_RATE: u32 = 44100;
+    // Intentionally not a multiple of the block size.
+    const FRAME_COUNT: usize = StreamingBuffer::STREAM_SAMPLE_COUNT * 5 / 2;
+
+    fn make_wav_fixture() -> Vec<u8> {
+        let mut data = Vec::new();
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: SAMPLE_RATE,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::new(Cursor::new(&mut data), spec).unwrap();
+        for i in 0..FRAME_COUNT {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let left = (t * 440.0 * std::f32::consts::TAU).sin() * 10000.0;
+            writer.write_sample(left as i16).unwrap();
+            writer.write_sample((i % 1000) as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+        data
+    }
+
+    fn make_buffers(data: Vec<u8>) -> (SoundBufferResource, SoundBufferResource) {
+        (
+            SoundBufferResource::new_generic(DataSource::from_memory(data.clone())).unwrap(),
+            SoundBufferResource::new_streaming(DataSource::from_memory(data)).unwrap(),
+        )
+    }
+
+    fn make_source(buffer: SoundBufferResource, looping: bool) -> SoundSource {
+        SoundSourceBuilder::new()
+            .with_buffer(buffer)
+            .with_looping(looping)
+            .with_status(Status::Playing)
+            .build()
+            .unwrap()
+    }
+
+    // Renders samples in small portions, just like the mixer does.
+    fn render(source: &mut SoundSource, frame_count: usize) -> Vec<(f32, f32)> {
+        let mut samples = Vec::new();
+        while samples.len() < frame_count {
+            source.render(1000.min(frame_count - samples.len()));
+            samples.extend_from_slice(source.frame_samples());
+        }
+        samples
+    }
+
+    #[test]
+    fn test_streaming_buffer_duration() {
+        let (generic, streaming) = make_buffers(make_wav_fixture());
+
+        let expected = Duration::from_secs_f64(FRAME_COUNT as f64 / SAMPLE_RATE as f64);
+        assert_eq!(generic.data_ref().duration(), expected);
+        assert_eq!(streaming.data_ref().duration(), expected);
+    }
+
+    #[test]
+    fn test_streaming_buffer_matches_full_decode() {
+        let (generic, streaming) = make_buffers(make_wav_fixture());
+        let mut generic_source = make_source(generic, false);
+        let mut streaming_source = make_source(streaming, false);
+
+        let expected = render(&mut generic_source, FRAME_COUNT + 1000);
+        assert_eq!(render(&mut streaming_source, FRAME_COUNT + 1000), expected);
+        assert_eq!(generic_source.status(), Status::Stopped);
+        assert_eq!(streaming_source.status(), Status::Stopped);
+
+        // Stopped source must be able to play again from the beginning.
+        generic_source.play();
+        streaming_source.play();
+        assert_eq!(
+            render(&mut streaming_source, 5000),
+            render(&mut generic_source, 5000)
+        );
+    }
+
+    #[test]
+    fn test_streaming_buffer_looping_and_seeking() {
+        let (generic, streaming) = make_buffers(make_wav_fixture());
+        let mut generic_source = make_source(generic, true);
+        let mut streaming_source = make_source(streaming, true);
+
+        // Loop a couple of times across the end of the stream.
+        let expected = render(&mut generic_source, FRAME_COUNT * 2 + 1234);
+        assert_eq!(
+            render(&mut streaming_source, FRAME_COUNT * 2 + 1234),
+            expected
+        );
+
+        for time in [1.5, 0.25, 2.0] {
+            generic_source.set_playback_time(Duration::from_secs_f32(time));
+            streaming_source.set_playback_time(Duration::from_secs_f32(time));
+            assert_eq!(
+                generic_source.playback_time(),
+                streaming_source.playback_time()
+            );
+            assert_eq!(
+                render(&mut streaming_source, 60000),
+                render(&mut generic_source, 60000)
+            );
+        }
+    }
+
+    #[test]
+    fn test_streaming_ogg_matches_full_decode() {
+        let data = std::fs::read("examples/data/waterfall.ogg").unwrap();
+        let (generic, streaming) = make_buffers(data);
+
+        let duration = generic.data_ref().duration();
+        assert_eq!(streaming.data_ref().duration(), duration);
+
+        // The file is resampled to the rate of the context, count frames of the output.
+        let frame_count = (duration.as_secs_f64() * context::SAMPLE_RATE as f64).round() as usize;
+        let mut generic_source = make_source(generic, false);
+        let mut streaming_source = make_source(streaming, false);
+        assert_eq!(
+            render(&mut streaming_source, frame_count + 1000),
+            render(&mut generic_source, frame_count + 1000)
+        );
+        assert_eq!(generic_source.status(), Status::Stopped);
+        assert_eq!(streaming_source.status(), Status::Stopped);
+    }
+}
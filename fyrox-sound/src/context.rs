@@ -85,6 +85,59 @@ impl Default for DistanceModel {
     }
 }
 
+/// Air absorption makes distant sounds duller, by filtering out high frequencies. Every sound
+/// source is passed through a low-pass filter with cutoff frequency that falls with distance to the
+/// listener:
+///
+/// `cutoff = max(max_cutoff * 0.5 ^ (distance * multiplier / half_distance), min_cutoff)`
+///
+/// where `multiplier` is a per-source air absorption multiplier (see
+/// [`SoundSource::set_air_absorption`]) and `distance` is measured from the edge of the
+/// full-volume sphere of the source (see [`SoundSource::set_min_distance`]).
+#[derive(Debug, Clone, PartialEq, Visit, Inspect)]
+pub struct AirAbsorption {
+    /// Whether the air absorption is enabled or not. Disabled by default.
+    pub enabled: bool,
+    /// Cutoff frequency (in Hz) of sounds near the listener.
+    #[inspect(min_value = 20.0, max_value = 22050.0, step = 100.0)]
+    pub max_cutoff: f32,
+    /// The lowest cutoff frequency (in Hz), it is reached by very distant sounds.
+    #[inspect(min_value = 20.0, max_value = 22050.0, step = 100.0)]
+    pub min_cutoff: f32,
+    /// Distance at which the cutoff frequency becomes two times lower.
+    #[inspect(min_value = 0.01, step = 1.0)]
+    pub half_distance: f32,
+}
+
+impl Default for AirAbsorption {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_cutoff: 20000.0,
+            min_cutoff: 1000.0,
+            half_distance: 100.0,
+        }
+    }
+}
+
+impl AirAbsorption {
+    /// Calculates cutoff frequency (in Hz) of a source at the given distance with the given
+    /// per-source multiplier. Returns `None` if the air absorption is disabled.
+    pub fn cutoff(&self, distance: f32, multiplier: f32) -> Option<f32> {
+        if !self.enabled {
+            return None;
+        }
+
+        let half_distance = self.half_distance.max(f32::EPSILON);
+        let exponent = distance.max(0.0) * multiplier.max(0.0) / half_distance;
+        Some(
+            (self.max_cutoff * 0.5f32.powf(exponent))
+                .max(self.min_cutoff)
+                .min(self.max_cutoff),
+        )
+    }
+}
+
 /// See module docs.
 #[derive(Clone, Default, Debug, Visit)]
 pub struct SoundContext {
@@ -108,6 +161,7 @@ pub struct State {
     effects: Pool<Effect>,
     distance_model: DistanceModel,
     paused: bool,
+    air_absorption: AirAbsorption,
 }
 
 impl State {
@@ -154,6 +208,16 @@ impl State {
         self.distance_model
     }
 
+    /// Sets new air absorption settings. See [`AirAbsorption`] docs for more info.
+    pub fn set_air_absorption(&mut self, air_absorption: AirAbsorption) {
+        self.air_absorption = air_absorption;
+    }
+
+    /// Returns current air absorption settings.
+    pub fn air_absorption(&self) -> &AirAbsorption {
+        &self.air_absorption
+    }
+
     /// Adds new effect to effects chain. Each sample from
     pub fn add_effect(&mut self, effect: Effect) -> Handle<Effect> {
         self.effects.spawn(effect)
@@ -275,6 +339,7 @@ impl State {
                 .iter_mut()
                 .filter(|s| s.status() == Status::Playing)
             {
+                source.update_air_absorption(&self.listener, &self.air_absorption);
                 source.render(buf.len());
 
                 match self.renderer {
@@ -334,6 +399,7 @@ impl SoundContext {
                 effects: Pool::new(),
                 distance_model: DistanceModel::InverseDistance,
                 paused: false,
+                air_absorption: Default::default(),
             }))),
         }
     }
@@ -389,6 +455,8 @@ impl Visit for State {
         self.renderer.visit("Renderer", &mut region)?;
         self.paused.visit("Paused", &mut region)?;
         self.distance_model.visit("DistanceModel", &mut region)?;
+        // Backward compatibility.
+        let _ = self.air_absorption.visit("AirAbsorption", &mut region);
 
         Ok(())
     }
@@ -13,6 +13,8 @@ pub struct OggDecoder {
     // create new OggStreamReader from it. Its ugly.
     reader: Option<Box<OggStreamReader<DataSource>>>,
     samples: vec::IntoIter<f32>,
+    // Total amount of samples per channel, taken from the last page of the stream.
+    total_samples: Option<u64>,
     pub channel_count: usize,
     pub sample_rate: usize,
 }
@@ -28,17 +30,21 @@ impl Iterator for OggDecoder {
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(sample) = self.samples.next() {
-            Some(sample)
-        } else {
-            if let Some(reader) = self.reader.as_mut() {
-                if let Ok(Some(samples)) =
-                    reader.read_dec_packet_generic::<InterleavedSamples<f32>>()
-                {
-                    self.samples = samples.samples.into_iter();
-                }
+        loop {
+            if let Some(sample) = self.samples.next() {
+                return Some(sample);
+            }
+            // Some packets (for example the first one after seeking) do not produce any samples,
+            // so keep reading until we get some or reach the end of the stream.
+            match self
+                .reader
+                .as_mut()?
+                .read_dec_packet_generic::<InterleavedSamples<f32>>()
+            {
+                Ok(Some(samples)) => self.samples = samples.samples.into_iter(),
+                // End of stream or corrupted data.
+                _ => return None,
             }
-            self.samples.next()
         }
     }
 }
@@ -53,62 +59,120 @@ fn is_vorbis_ogg(source: &mut DataSource) -> bool {
     is_vorbis
 }
 
+// Returns absolute granule position of the last page of the stream, for vorbis streams it is the
+// total amount of samples per channel. Position of the source is restored.
+fn last_granule_position(source: &mut DataSource) -> Option<u64> {
+    // Max size of an ogg page is a bit less than 64 Kb, so the header of the last page is
+    // guaranteed to be within this range from the end.
+    const MAX_PAGE_SIZE: u64 = 65536;
+
+    let pos = source.stream_position().ok()?;
+    let len = source.seek(SeekFrom::End(0)).ok()?;
+    let window = len.min(MAX_PAGE_SIZE);
+    let mut data = vec![0; window as usize];
+    let result = source
+        .seek(SeekFrom::Start(len - window))
+        .and_then(|_| source.read_exact(&mut data))
+        .ok()
+        .and_then(|_| {
+            data.windows(14).rev().find_map(|header| {
+                // Capture pattern followed by the stream structure version (always zero).
+                if &header[0..5] == b"OggS\0" {
+                    let granule_position = u64::from_le_bytes(header[6..14].try_into().unwrap());
+                    // -1 means that no packet finishes on the page.
+                    if granule_position != u64::MAX {
+                        return Some(granule_position);
+                    }
+                }
+                None
+            })
+        });
+    source.seek(SeekFrom::Start(pos)).ok()?;
+    result
+}
+
 impl OggDecoder {
     pub fn new(mut source: DataSource) -> Result<Self, DataSource> {
         if is_vorbis_ogg(&mut source) {
-            let mut reader = OggStreamReader::new(source).unwrap();
-
-            let samples = if let Ok(Some(samples)) =
-                reader.read_dec_packet_generic::<InterleavedSamples<f32>>()
-            {
-                samples.samples.into_iter()
-            } else {
-                Vec::new().into_iter()
-            };
-
-            Ok(Self {
-                samples,
-                channel_count: reader.ident_hdr.audio_channels as usize,
-                sample_rate: reader.ident_hdr.audio_sample_rate as usize,
-                reader: Some(Box::new(reader)),
-            })
+            let total_samples = last_granule_position(&mut source);
+            Ok(Self::from_vorbis_source(source, total_samples))
         } else {
             Err(source)
         }
     }
 
+    fn from_vorbis_source(source: DataSource, total_samples: Option<u64>) -> Self {
+        let mut reader = OggStreamReader::new(source).unwrap();
+
+        let samples = if let Ok(Some(samples)) =
+            reader.read_dec_packet_generic::<InterleavedSamples<f32>>()
+        {
+            samples.samples.into_iter()
+        } else {
+            Vec::new().into_iter()
+        };
+
+        Self {
+            samples,
+            total_samples,
+            channel_count: reader.ident_hdr.audio_channels as usize,
+            sample_rate: reader.ident_hdr.audio_sample_rate as usize,
+            reader: Some(Box::new(reader)),
+        }
+    }
+
     pub fn rewind(&mut self) -> Result<(), SoundError> {
         // We have to create completely new instance of decoder because of bug in seek_absgp_pg
         // For more info see - https://github.com/RustAudio/lewton/issues/73
-        let mut source = self.reader.take().unwrap().into_inner().into_inner();
+        let mut source = self
+            .reader
+            .take()
+            .ok_or(SoundError::UnsupportedFormat)?
+            .into_inner()
+            .into_inner();
         source.seek(SeekFrom::Start(0))?;
-        *self = match Self::new(source) {
-            Ok(ogg_decoder) => ogg_decoder,
+        if is_vorbis_ogg(&mut source) {
+            *self = Self::from_vorbis_source(source, self.total_samples);
+            Ok(())
+        } else {
             // Drop source here, this will invalidate decoder and it can't produce any
             // samples anymore. This is unrecoverable error, but *should* never happen
             // in reality.
-            Err(_) => return Err(SoundError::UnsupportedFormat),
-        };
-        Ok(())
+            self.samples = Vec::new().into_iter();
+            Err(SoundError::UnsupportedFormat)
+        }
     }
 
     pub fn time_seek(&mut self, location: Duration) {
-        // seek_absgp_pg seems to be bugged - it fails at seeking when all packets were read already.
+        // seek_absgp_pg seems to be bugged - it fails at seeking when all packets were read already,
+        // so we have to start from the beginning of the stream.
         // For more info see - https://github.com/RustAudio/lewton/issues/73
-        let sample_index =
-            self.channel_count as f64 * location.as_secs_f64() * self.sample_rate as f64;
-        if self
-            .reader
-            .as_mut()
-            .unwrap()
-            .seek_absgp_pg(sample_index as u64)
-            .is_err()
-        {
-            println!("Failed to seek vorbis/ogg, see https://github.com/RustAudio/lewton/issues/73")
+        if self.rewind().is_err() {
+            return;
+        }
+
+        // Granule position of vorbis streams is the number of samples per channel.
+        let granule_position = (location.as_secs_f64() * self.sample_rate as f64) as u64;
+        if granule_position == 0 {
+            // Rewinding is enough, seeking would discard samples of the first packet.
+            return;
+        }
+        if let Some(reader) = self.reader.as_mut() {
+            // Seeking has page granularity, so the actual position will be slightly before the
+            // requested one.
+            if reader.seek_absgp_pg(granule_position).is_ok() {
+                // Discard samples of the first packet that were decoded on rewind.
+                self.samples = Vec::new().into_iter();
+            } else {
+                println!(
+                    "Failed to seek vorbis/ogg, see https://github.com/RustAudio/lewton/issues/73"
+                )
+            }
         }
     }
 
     pub fn duration(&self) -> Option<Duration> {
-        None
+        self.total_samples
+            .map(|total| Duration::from_secs_f64(total as f64 / self.sample_rate as f64))
     }
 }
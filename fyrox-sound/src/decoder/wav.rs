@@ -81,8 +81,8 @@ impl WavDecoder {
     }
 
     pub fn duration(&self) -> Option<Duration> {
-        Some(Duration::from_secs_f32(
-            self.reader.duration() as f32 / self.reader.spec().sample_rate as f32,
+        Some(Duration::from_secs_f64(
+            self.reader.duration() as f64 / self.reader.spec().sample_rate as f64,
         ))
     }
 
@@ -39,8 +39,8 @@ pub enum SoundError {
     /// normalize a vector with length `|v| == 0.0`.
     MathError(String),
 
-    /// You tried to create a source with streaming buffer that is currently being
-    /// used by some other source. This is wrong because only one source can play
+    /// You tried to create a source with streaming (or procedural) buffer that is currently
+    /// being used by some other source. This is wrong because only one source can play
     /// sound from streaming buffer.
     StreamingBufferAlreadyInUse,
 
@@ -30,8 +30,11 @@
 #![allow(clippy::float_cmp)]
 
 use crate::{
-    buffer::{streaming::StreamingBuffer, SoundBufferResource, SoundBufferState},
-    context::DistanceModel,
+    buffer::{
+        generic::GenericBuffer, streaming::StreamingBuffer, SoundBufferResource, SoundBufferState,
+    },
+    context::{AirAbsorption, DistanceModel, SAMPLE_RATE},
+    dsp::filters::OnePole,
     error::SoundError,
     listener::Listener,
 };
@@ -120,6 +123,15 @@ pub struct SoundSource {
     max_distance: f32,
     #[inspect(min_value = 0.0, step = 0.05)]
     rolloff_factor: f32,
+    #[inspect(min_value = 0.0, max_value = 360.0, step = 1.0)]
+    #[visit(optional)] // Backward compatibility
+    spread: f32,
+    #[inspect(min_value = 0.0, step = 0.05)]
+    #[visit(optional)] // Backward compatibility
+    min_distance: f32,
+    #[inspect(min_value = 0.0, step = 0.05)]
+    #[visit(optional)] // Backward compatibility
+    air_absorption: f32,
     // Some data that needed for iterative overlap-save convolution.
     #[inspect(skip)]
     #[visit(skip)]
@@ -133,6 +145,24 @@ pub struct SoundSource {
     #[inspect(skip)]
     #[visit(skip)]
     pub(in crate) prev_distance_gain: Option<f32>,
+    // Occlusion is driven by external code (usually by the engine, which does ray casting through
+    // the scene geometry), so it is not serialized.
+    #[inspect(skip)]
+    #[visit(skip)]
+    occlusion_gain: f32,
+    #[inspect(skip)]
+    #[visit(skip)]
+    occlusion_low_pass: f32,
+    #[inspect(skip)]
+    #[visit(skip)]
+    occlusion_filters: (OnePole, OnePole),
+    // Air absorption is calculated by the context on every render call.
+    #[inspect(skip)]
+    #[visit(skip)]
+    air_absorption_cutoff: Option<f32>,
+    #[inspect(skip)]
+    #[visit(skip)]
+    air_absorption_filters: (OnePole, OnePole),
 }
 
 impl Default for SoundSource {
@@ -158,10 +188,18 @@ impl Default for SoundSource {
             position: Vector3::new(0.0, 0.0, 0.0),
             max_distance: f32::MAX,
             rolloff_factor: 1.0,
+            spread: 0.0,
+            min_distance: 0.0,
+            air_absorption: 1.0,
             prev_left_samples: Default::default(),
             prev_right_samples: Default::default(),
             prev_sampling_vector: Vector3::new(0.0, 0.0, 1.0),
             prev_distance_gain: None,
+            occlusion_gain: 1.0,
+            occlusion_low_pass: 0.0,
+            occlusion_filters: Default::default(),
+            air_absorption_cutoff: None,
+            air_absorption_filters: Default::default(),
         }
     }
 }
@@ -203,24 +241,31 @@ impl SoundSource {
         self.buf_read_pos = 0.0;
         self.playback_pos = 0.0;
 
-        // If we already have streaming buffer assigned make sure to decrease use count
-        // so it can be reused later on if needed.
+        // If we already have streaming or procedural buffer assigned make sure to decrease use
+        // count so it can be reused later on if needed.
         if let Some(buffer) = self.buffer.clone() {
-            if let SoundBufferState::Streaming(ref mut streaming) = *buffer.data_ref() {
-                streaming.use_count = streaming.use_count.saturating_sub(1);
-            }
+            release_buffer(&mut buffer.data_ref());
         }
 
         if let Some(buffer) = buffer.clone() {
             match *buffer.state() {
                 ResourceState::LoadError { .. } => return Err(SoundError::BufferFailedToLoad),
                 ResourceState::Ok(ref mut locked_buffer) => {
-                    // Check new buffer if streaming - it must not be used by anyone else.
-                    if let SoundBufferState::Streaming(ref mut streaming) = *locked_buffer {
-                        if streaming.use_count != 0 {
+                    // Check new buffer if streaming or procedural - it must not be used by anyone else.
+                    let use_count = match *locked_buffer {
+                        SoundBufferState::Streaming(ref mut streaming) => {
+                            Some(&mut streaming.use_count)
+                        }
+                        SoundBufferState::Procedural(ref mut procedural) => {
+                            Some(&mut procedural.use_count)
+                        }
+                        SoundBufferState::Generic(_) => None,
+                    };
+                    if let Some(use_count) = use_count {
+                        if *use_count != 0 {
                             return Err(SoundError::StreamingBufferAlreadyInUse);
                         }
-                        streaming.use_count += 1;
+                        *use_count += 1;
                     }
 
                     // Make sure to recalculate resampling multiplier, otherwise sound will play incorrectly.
@@ -273,6 +318,36 @@ impl SoundSource {
         self.gain
     }
 
+    /// Sets occlusion parameters of the source. Occlusion is used to simulate sound obstruction by
+    /// some obstacles between a source and the listener. `gain` is an additional volume multiplier
+    /// in 0..1 range and `low_pass` is a strength of low-pass filtering in 0..1 range, where 0 means
+    /// no filtering at all and 1 - maximum filtering (only the lowest frequencies will pass).
+    ///
+    /// # Notes
+    ///
+    /// Usually there is no need to call this method manually, the engine calculates occlusion for
+    /// every sound source that has occlusion enabled.
+    pub fn set_occlusion(&mut self, gain: f32, low_pass: f32) -> &mut Self {
+        self.occlusion_gain = gain.clamp(0.0, 1.0);
+        self.occlusion_low_pass = low_pass.clamp(0.0, 1.0);
+        // Keep some frequencies, otherwise the filter will eat the entire signal.
+        let pole = self.occlusion_low_pass * 0.98;
+        self.occlusion_filters.0.set_pole(pole);
+        self.occlusion_filters.1.set_pole(pole);
+        self
+    }
+
+    /// Returns current occlusion gain. See [`Self::set_occlusion`] for more info.
+    pub fn occlusion_gain(&self) -> f32 {
+        self.occlusion_gain
+    }
+
+    /// Returns current strength of occlusion low-pass filtering. See [`Self::set_occlusion`] for
+    /// more info.
+    pub fn occlusion_low_pass(&self) -> f32 {
+        self.occlusion_low_pass
+    }
+
     /// Sets panning coefficient. Value must be in -1..+1 range. Where -1 - only left channel will be audible,
     /// 0 - both, +1 - only right.
     pub fn set_panning(&mut self, panning: f32) -> &mut Self {
@@ -325,7 +400,8 @@ impl SoundSource {
         self.pitch
     }
 
-    /// Stops sound source. Automatically rewinds streaming buffers.
+    /// Stops sound source. Automatically rewinds streaming buffers and notifies sources of
+    /// procedural buffers.
     pub fn stop(&mut self) -> Result<(), SoundError> {
         self.status = Status::Stopped;
 
@@ -334,8 +410,10 @@ impl SoundSource {
 
         if let Some(buffer) = self.buffer.as_ref() {
             let mut buffer = buffer.data_ref();
-            if let SoundBufferState::Streaming(ref mut streaming) = *buffer {
-                streaming.rewind()?;
+            match *buffer {
+                SoundBufferState::Streaming(ref mut streaming) => streaming.rewind()?,
+                SoundBufferState::Procedural(ref mut procedural) => procedural.stop(),
+                SoundBufferState::Generic(_) => (),
             }
         }
 
@@ -390,6 +468,62 @@ impl SoundSource {
         self.max_distance
     }
 
+    /// Sets spread angle (in degrees, 0..360 range) of the source. Spread defines an angle over which
+    /// the source is spatially spread, it reduces panning of the source: 0 degrees means a point
+    /// source with full panning, 360 degrees - the source surrounds the listener and there is no
+    /// panning at all. The spread also grows automatically when the listener comes close to the
+    /// full-volume sphere of the source (see [`Self::set_min_distance`]), so large sources do not
+    /// snap between channels when the listener passes near them. HRTF renderer uses exact direction
+    /// to the source and ignores the spread.
+    pub fn set_spread(&mut self, spread: f32) -> &mut Self {
+        self.spread = spread.clamp(0.0, 360.0);
+        self
+    }
+
+    /// Returns spread angle of the source in degrees.
+    pub fn spread(&self) -> f32 {
+        self.spread
+    }
+
+    /// Sets radius of a sphere around the source, in which the source is heard at full volume. The
+    /// distance attenuation curve (defined by radius, rolloff factor and max distance) starts at
+    /// the edge of the sphere, so a large source (a waterfall, for example) could be loud around its
+    /// whole volume and still have the same falloff. Default value is 0, which means that the curve
+    /// starts at the position of the source.
+    pub fn set_min_distance(&mut self, min_distance: f32) -> &mut Self {
+        self.min_distance = min_distance.max(0.0);
+        self
+    }
+
+    /// Returns radius of the full-volume sphere.
+    pub fn min_distance(&self) -> f32 {
+        self.min_distance
+    }
+
+    /// Sets air absorption multiplier of the source. The multiplier scales the distance in the air
+    /// absorption formula (see [`AirAbsorption`]): 0 - the source is not affected by the air
+    /// absorption, 1 - default absorption, larger values - the source gets duller faster.
+    pub fn set_air_absorption(&mut self, multiplier: f32) -> &mut Self {
+        self.air_absorption = multiplier.max(0.0);
+        self
+    }
+
+    /// Returns air absorption multiplier of the source.
+    pub fn air_absorption(&self) -> f32 {
+        self.air_absorption
+    }
+
+    /// Returns current cutoff frequency (in Hz) of the air absorption filter of the source. `None`
+    /// means that the air absorption is disabled in the context.
+    pub fn air_absorption_cutoff(&self) -> Option<f32> {
+        self.air_absorption_cutoff
+    }
+
+    // Distance from the listener to the edge of the full-volume sphere.
+    fn distance_to_sphere(&self, listener: &Listener) -> f32 {
+        (self.position.metric_distance(&listener.position()) - self.min_distance).max(0.0)
+    }
+
     // Distance models were taken from OpenAL Specification because it looks like they're
     // standard in industry and there is no need to reinvent it.
     // https://www.openal.org/documentation/openal-1.1-specification.pdf
@@ -399,8 +533,7 @@ impl SoundSource {
         distance_model: DistanceModel,
     ) -> f32 {
         let distance = self
-            .position
-            .metric_distance(&listener.position())
+            .distance_to_sphere(listener)
             .max(self.radius)
             .min(self.max_distance);
         match distance_model {
@@ -415,13 +548,48 @@ impl SoundSource {
         }
     }
 
-    pub(in crate) fn calculate_panning(&self, listener: &Listener) -> f32 {
-        (self.position - listener.position())
+    /// Returns effective spread angle (in degrees) of the source for the given listener. It is the
+    /// largest of the spread of the source and the doubled angular diameter of the full-volume
+    /// sphere, as seen by the listener. This way the spread smoothly reaches 360 degrees when the
+    /// listener touches the sphere.
+    pub fn calculate_spread(&self, listener: &Listener) -> f32 {
+        let distance = self.position.metric_distance(&listener.position());
+        let near_spread = if self.min_distance <= 0.0 {
+            0.0
+        } else if distance <= self.min_distance {
+            360.0
+        } else {
+            4.0 * (self.min_distance / distance).asin().to_degrees()
+        };
+        self.spread.max(near_spread).min(360.0)
+    }
+
+    /// Returns panning coefficient in -1..+1 range for the given listener, it takes spread of the
+    /// source into account (see [`Self::set_spread`]).
+    pub fn calculate_panning(&self, listener: &Listener) -> f32 {
+        let panning = (self.position - listener.position())
             .try_normalize(f32::EPSILON)
             // Fallback to look axis will give zero panning which will result in even
             // gain in each channels (as if there was no panning at all).
             .unwrap_or_else(|| listener.look_axis())
-            .dot(&listener.ear_axis())
+            .dot(&listener.ear_axis());
+        panning * (1.0 - self.calculate_spread(listener) / 360.0)
+    }
+
+    pub(in crate) fn update_air_absorption(
+        &mut self,
+        listener: &Listener,
+        air_absorption: &AirAbsorption,
+    ) {
+        let cutoff = air_absorption.cutoff(self.distance_to_sphere(listener), self.air_absorption);
+        if cutoff != self.air_absorption_cutoff {
+            if let Some(cutoff) = cutoff {
+                let fc = cutoff / SAMPLE_RATE as f32;
+                self.air_absorption_filters.0.set_fc(fc);
+                self.air_absorption_filters.1.set_fc(fc);
+            }
+            self.air_absorption_cutoff = cutoff;
+        }
     }
 
     pub(in crate) fn calculate_sampling_vector(&self, listener: &Listener) -> Vector3<f32> {
@@ -448,26 +616,32 @@ impl SoundSource {
     pub fn set_playback_time(&mut self, time: Duration) {
         if let Some(buffer) = self.buffer.as_ref() {
             let mut buffer = buffer.data_ref();
-            if let SoundBufferState::Streaming(ref mut streaming) = *buffer {
-                // Make sure decoder is at right position.
-                streaming.time_seek(time);
-            }
+            // Do not allow to seek past the end of the sound. Length of some raw streaming
+            // sources is unknown, there is nothing to clamp to.
+            let time = match *buffer {
+                SoundBufferState::Generic(ref generic) => time.min(generic.duration()),
+                SoundBufferState::Streaming(ref streaming) => streaming
+                    .duration()
+                    .map_or(time, |duration| time.min(duration)),
+                SoundBufferState::Procedural(_) => time,
+            };
             // Set absolute position first.
             self.playback_pos = time.as_secs_f64() * buffer.sample_rate as f64;
             // Then adjust buffer read position.
             self.buf_read_pos = match *buffer {
                 SoundBufferState::Streaming(ref mut streaming) => {
-                    // Make sure to load correct data into buffer from decoder.
-                    streaming.read_next_block();
-                    // Streaming sources has different buffer read position because
-                    // buffer contains only small portion of data.
-                    self.playback_pos % (StreamingBuffer::STREAM_SAMPLE_COUNT as f64)
+                    // Make sure decoder is at right position and the buffer contains the data
+                    // starting at this position.
+                    streaming.time_seek(time);
+                    0.0
+                }
+                SoundBufferState::Procedural(ref mut procedural) => {
+                    // Procedural buffers cannot seek, just drop pulled samples.
+                    procedural.samples.clear();
+                    0.0
                 }
                 SoundBufferState::Generic(_) => self.playback_pos,
             };
-            assert!(
-                self.buf_read_pos * (buffer.channel_count() as f64) < buffer.samples().len() as f64
-            );
         }
     }
 
@@ -481,13 +655,50 @@ impl SoundSource {
         if let Some(buffer) = self.buffer.clone() {
             let mut state = buffer.state();
             if let ResourceState::Ok(ref mut buffer) = *state {
-                if self.status == Status::Playing && !buffer.is_empty() {
-                    self.render_playing(buffer, amount);
+                if self.status == Status::Playing {
+                    // Procedural buffers have no samples until they are played.
+                    if let SoundBufferState::Procedural(ref mut procedural) = *buffer {
+                        if procedural.is_empty() {
+                            self.buf_read_pos = 0.0;
+                            procedural.read_next_block(self.procedural_block_size(amount));
+                        }
+                    }
+                    if !buffer.is_empty() {
+                        self.render_playing(buffer, amount);
+                    }
                 }
             }
         }
         // Fill the remaining part of frame_samples.
         self.frame_samples.resize(amount, (0.0, 0.0));
+
+        self.apply_occlusion();
+        self.apply_air_absorption();
+    }
+
+    fn apply_air_absorption(&mut self) {
+        if self.air_absorption_cutoff.is_none() {
+            return;
+        }
+
+        let (left_filter, right_filter) = &mut self.air_absorption_filters;
+        for (left, right) in self.frame_samples.iter_mut() {
+            *left = left_filter.feed(*left);
+            *right = right_filter.feed(*right);
+        }
+    }
+
+    fn apply_occlusion(&mut self) {
+        if self.occlusion_gain == 1.0 && self.occlusion_low_pass == 0.0 {
+            return;
+        }
+
+        let gain = self.occlusion_gain;
+        let (left_filter, right_filter) = &mut self.occlusion_filters;
+        for (left, right) in self.frame_samples.iter_mut() {
+            *left = left_filter.feed(*left) * gain;
+            *right = right_filter.feed(*right) * gain;
+        }
     }
 
     fn render_playing(&mut self, buffer: &mut SoundBufferState, amount: usize) {
@@ -501,29 +712,52 @@ impl SoundSource {
             let channel_count = buffer.channel_count();
             let len = buffer.samples().len();
             let mut end_reached = true;
-            if let SoundBufferState::Streaming(streaming) = buffer {
-                // Means that this is the last available block.
-                if len != channel_count * StreamingBuffer::STREAM_SAMPLE_COUNT {
-                    let _ = streaming.rewind();
-                } else {
+            match buffer {
+                SoundBufferState::Streaming(streaming) => {
+                    self.prev_buffer_sample = get_last_sample(streaming);
+                    // A block that is shorter than usual means that this is the last available block.
+                    // The stream could also end exactly at the block boundary, in this case the next
+                    // block will be empty.
+                    if len == channel_count * StreamingBuffer::STREAM_SAMPLE_COUNT {
+                        streaming.read_next_block();
+                        end_reached = streaming.is_empty();
+                    }
+                    if end_reached {
+                        // Load the first block, so looping sounds continue seamlessly and stopped
+                        // ones are ready to be played again.
+                        let _ = streaming.rewind();
+                    }
+                }
+                SoundBufferState::Procedural(procedural) => {
+                    // Procedural buffers never end, the next block contains just enough samples to
+                    // finish the frame.
+                    self.prev_buffer_sample = get_last_sample(procedural);
+                    procedural.read_next_block(self.procedural_block_size(amount - count));
                     end_reached = false;
                 }
-                self.prev_buffer_sample = get_last_sample(streaming);
-                streaming.read_next_block();
+                SoundBufferState::Generic(_) => (),
             }
             if end_reached {
-                if !self.looping {
+                // Stopped sound must start from the beginning when played again, the same as
+                // after `stop`.
+                self.buf_read_pos = 0.0;
+                self.playback_pos = 0.0;
+                if !self.looping || buffer.is_empty() {
                     self.status = Status::Stopped;
                     return;
                 }
-                self.buf_read_pos = 0.0;
-                self.playback_pos = 0.0;
             } else {
                 self.buf_read_pos -= len as f64 / channel_count as f64;
             }
         }
     }
 
+    // Returns the amount of samples per channel that must be pulled from a procedural buffer to
+    // render the given amount of samples.
+    fn procedural_block_size(&self, amount: usize) -> usize {
+        ((amount as f64 * self.pitch * self.resampling_multiplier).ceil() as usize).max(1)
+    }
+
     // Renders until the end of the block or until amount samples is written and returns
     // the number of written samples.
     fn render_until_block_end(
@@ -643,7 +877,7 @@ impl SoundSource {
     }
 }
 
-fn get_last_sample(buffer: &StreamingBuffer) -> (f32, f32) {
+fn get_last_sample(buffer: &GenericBuffer) -> (f32, f32) {
     let len = buffer.samples.len();
     if len == 0 {
         return (0.0, 0.0);
@@ -655,13 +889,23 @@ fn get_last_sample(buffer: &StreamingBuffer) -> (f32, f32) {
     }
 }
 
+// Decreases use count of streaming and procedural buffers.
+fn release_buffer(buffer: &mut SoundBufferState) {
+    match buffer {
+        SoundBufferState::Streaming(streaming) => {
+            streaming.use_count = streaming.use_count.saturating_sub(1);
+        }
+        SoundBufferState::Procedural(procedural) => {
+            procedural.use_count = procedural.use_count.saturating_sub(1);
+        }
+        SoundBufferState::Generic(_) => (),
+    }
+}
+
 impl Drop for SoundSource {
     fn drop(&mut self) {
         if let Some(buffer) = self.buffer.as_ref() {
-            let mut buffer = buffer.data_ref();
-            if let SoundBufferState::Streaming(ref mut streaming) = *buffer {
-                streaming.use_count = streaming.use_count.saturating_sub(1);
-            }
+            release_buffer(&mut buffer.data_ref());
         }
     }
 }
@@ -702,6 +946,9 @@ pub struct SoundSourceBuilder {
     max_distance: f32,
     rolloff_factor: f32,
     spatial_blend: f32,
+    spread: f32,
+    min_distance: f32,
+    air_absorption: f32,
 }
 
 impl Default for SoundSourceBuilder {
@@ -728,6 +975,9 @@ impl SoundSourceBuilder {
             max_distance: f32::MAX,
             rolloff_factor: 1.0,
             spatial_blend: 1.0,
+            spread: 0.0,
+            min_distance: 0.0,
+            air_absorption: 1.0,
         }
     }
 
@@ -821,6 +1071,24 @@ impl SoundSourceBuilder {
         self
     }
 
+    /// See [`SoundSource::set_spread`]
+    pub fn with_spread(mut self, spread: f32) -> Self {
+        self.spread = spread.clamp(0.0, 360.0);
+        self
+    }
+
+    /// See [`SoundSource::set_min_distance`]
+    pub fn with_min_distance(mut self, min_distance: f32) -> Self {
+        self.min_distance = min_distance.max(0.0);
+        self
+    }
+
+    /// See [`SoundSource::set_air_absorption`]
+    pub fn with_air_absorption(mut self, multiplier: f32) -> Self {
+        self.air_absorption = multiplier.max(0.0);
+        self
+    }
+
     /// Creates new instance of generic sound source. May fail if buffer is invalid.
     pub fn build(self) -> Result<SoundSource, SoundError> {
         let mut source = SoundSource {
@@ -838,9 +1106,24 @@ impl SoundSourceBuilder {
             max_distance: self.max_distance,
             rolloff_factor: self.rolloff_factor,
             spatial_blend: self.spatial_blend,
+            spread: self.spread,
+            min_distance: self.min_distance,
+            air_absorption: self.air_absorption,
             prev_left_samples: Default::default(),
             prev_right_samples: Default::default(),
-            ..Default::default()
+            buf_read_pos: 0.0,
+            playback_pos: 0.0,
+            resampling_multiplier: 1.0,
+            last_left_gain: None,
+            last_right_gain: None,
+            prev_buffer_sample: (0.0, 0.0),
+            prev_sampling_vector: Vector3::new(0.0, 0.0, 1.0),
+            prev_distance_gain: None,
+            occlusion_gain: 1.0,
+            occlusion_low_pass: 0.0,
+            occlusion_filters: Default::default(),
+            air_absorption_cutoff: None,
+            air_absorption_filters: Default::default(),
         };
 
         source.set_buffer(self.buffer)?;
@@ -849,3 +1132,98 @@ impl SoundSourceBuilder {
         Ok(source)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        context::{AirAbsorption, DistanceModel},
+        listener::Listener,
+        source::SoundSourceBuilder,
+    };
+    use fyrox_core::algebra::Vector3;
+
+    fn approx_eq(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.001
+    }
+
+    #[test]
+    fn test_spread_reduces_panning() {
+        let listener = Listener::new();
+
+        for (spread, expected) in [(0.0, 1.0), (180.0, 0.5), (360.0, 0.0)] {
+            let source = SoundSourceBuilder::new()
+                .with_position(Vector3::new(10.0, 0.0, 0.0))
+                .with_spread(spread)
+                .build()
+                .unwrap();
+            assert!(approx_eq(source.calculate_panning(&listener), expected));
+        }
+    }
+
+    #[test]
+    fn test_min_distance_widens_spread_and_keeps_full_volume() {
+        let listener = Listener::new();
+
+        let mut source = SoundSourceBuilder::new()
+            .with_position(Vector3::new(10.0, 0.0, 0.0))
+            .with_min_distance(5.0)
+            .with_radius(1.0)
+            .build()
+            .unwrap();
+
+        // The sphere is seen at 60 degrees, so the spread is 120 degrees.
+        assert!(approx_eq(source.calculate_spread(&listener), 120.0));
+        assert!(approx_eq(source.calculate_panning(&listener), 2.0 / 3.0));
+
+        // Inside the sphere there is no panning and no attenuation.
+        source.set_position(Vector3::new(4.0, 0.0, 0.0));
+        assert!(approx_eq(source.calculate_panning(&listener), 0.0));
+        assert!(approx_eq(
+            source.calculate_distance_gain(&listener, DistanceModel::InverseDistance),
+            1.0
+        ));
+
+        // Attenuation curve starts at the edge of the sphere.
+        source.set_position(Vector3::new(5.5, 0.0, 0.0));
+        assert!(approx_eq(
+            source.calculate_distance_gain(&listener, DistanceModel::InverseDistance),
+            1.0
+        ));
+        source.set_position(Vector3::new(7.0, 0.0, 0.0));
+        assert!(approx_eq(
+            source.calculate_distance_gain(&listener, DistanceModel::InverseDistance),
+            0.5
+        ));
+    }
+
+    #[test]
+    fn test_air_absorption_cutoff_falls_with_distance() {
+        let listener = Listener::new();
+        let air_absorption = AirAbsorption {
+            enabled: true,
+            max_cutoff: 20000.0,
+            min_cutoff: 1000.0,
+            half_distance: 100.0,
+        };
+
+        let mut source = SoundSourceBuilder::new()
+            .with_position(Vector3::new(100.0, 0.0, 0.0))
+            .build()
+            .unwrap();
+
+        for (multiplier, expected) in [(0.0, 20000.0), (1.0, 10000.0), (2.0, 5000.0)] {
+            source.set_air_absorption(multiplier);
+            source.update_air_absorption(&listener, &air_absorption);
+            assert!(approx_eq(source.air_absorption_cutoff().unwrap(), expected));
+        }
+
+        // Very distant sounds are clamped to the lowest cutoff.
+        source.set_air_absorption(1.0);
+        source.set_position(Vector3::new(10000.0, 0.0, 0.0));
+        source.update_air_absorption(&listener, &air_absorption);
+        assert!(approx_eq(source.air_absorption_cutoff().unwrap(), 1000.0));
+
+        source.update_air_absorption(&listener, &AirAbsorption::default());
+        assert_eq!(source.air_absorption_cutoff(), None);
+    }
+}
@@ -0,0 +1,924 @@
+//! Data grid widget allows you to show tabular data with column headers, sorting and selection.
+//!
+//! Rows are virtualized - only visible rows have widgets, so the grid stays responsive with
+//! tens of thousands of rows. The grid does not own the data, instead it operates on row
+//! indices and asks column cell templates to build a widget for a particular row on demand.
+//!
+//! ## Built-in controls
+//!
+//! `Click` on a column header - sorts rows using column's comparator, next click reverses order.
+//! `Click` on a row - selects the row.
+//! `Ctrl+Click` on a row - enables multi-selection (if allowed).
+
+use crate::{
+    border::BorderBuilder,
+    brush::Brush,
+    button::{ButtonBuilder, ButtonMessage},
+    canvas::CanvasBuilder,
+    core::{algebra::Vector2, color::Color, pool::Handle},
+    decorator::{DecoratorBuilder, DecoratorMessage},
+    define_constructor,
+    grid::{Column, GridBuilder, Row},
+    message::{KeyboardModifiers, MessageDirection, UiMessage},
+    scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+    stack_panel::StackPanelBuilder,
+    text::TextBuilder,
+    utils::{make_arrow, ArrowDirection},
+    widget::{Widget, WidgetBuilder, WidgetMessage},
+    BuildContext, Control, HorizontalAlignment, NodeHandleMapping, Orientation, Thickness, UiNode,
+    UserInterface, VerticalAlignment, BRUSH_DARK, BRUSH_LIGHT,
+};
+use std::{
+    any::{Any, TypeId},
+    cell::Cell,
+    cmp::Ordering,
+    fmt::{Debug, Formatter},
+    ops::{Deref, DerefMut, Range},
+    rc::Rc,
+};
+
+/// A function that builds a widget for a cell of a given row.
+pub type CellTemplate = Rc<dyn Fn(&mut BuildContext, usize) -> Handle<UiNode>>;
+
+/// A function that compares two rows (by their indices) for sorting.
+pub type RowComparator = Rc<dyn Fn(usize, usize) -> Ordering>;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl SortOrder {
+    pub fn reverse(self) -> Self {
+        match self {
+            SortOrder::Ascending => SortOrder::Descending,
+            SortOrder::Descending => SortOrder::Ascending,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataGridMessage {
+    /// Sets new amount of rows in the grid. Sorting (if any) will be re-applied.
+    ///
+    /// Direction: **To Widget**.
+    RowCount(usize),
+    /// Sorts rows by a column. Has no effect if the column has no comparator.
+    ///
+    /// Direction: **To/From Widget**.
+    Sort {
+        column: usize,
+        order: SortOrder,
+    },
+    /// A set of selected rows (indices of rows in the source data, not in the sorted view).
+    ///
+    /// Direction: **To/From Widget**.
+    Selection(Vec<usize>),
+    /// Scrolls the grid so the row will be visible.
+    ///
+    /// Direction: **To Widget**.
+    BringRowIntoView(usize),
+    /// Re-creates every visible row, must be used when source data has changed.
+    ///
+    /// Direction: **To Widget**.
+    Invalidate,
+    // Private, do not use. For internal needs only.
+    SyncViewport,
+}
+
+impl DataGridMessage {
+    define_constructor!(DataGridMessage:RowCount => fn row_count(usize), layout: false);
+    define_constructor!(DataGridMessage:Sort => fn sort(column: usize, order: SortOrder), layout: false);
+    define_constructor!(DataGridMessage:Selection => fn selection(Vec<usize>), layout: false);
+    define_constructor!(DataGridMessage:BringRowIntoView => fn bring_row_into_view(usize), layout: false);
+    define_constructor!(DataGridMessage:Invalidate => fn invalidate(), layout: false);
+    define_constructor!(DataGridMessage:SyncViewport => fn sync_viewport(), layout: false);
+}
+
+/// Description of a column of the data grid.
+#[derive(Clone)]
+pub struct DataGridColumn {
+    pub header: String,
+    pub width: f32,
+    pub template: CellTemplate,
+    pub comparator: Option<RowComparator>,
+}
+
+impl Debug for DataGridColumn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DataGridColumn")
+            .field("header", &self.header)
+            .field("width", &self.width)
+            .field("sortable", &self.comparator.is_some())
+            .finish()
+    }
+}
+
+impl DataGridColumn {
+    /// Creates new column that will use the template to build cell widgets.
+    pub fn new<S, F>(header: S, width: f32, template: F) -> Self
+    where
+        S: AsRef<str>,
+        F: Fn(&mut BuildContext, usize) -> Handle<UiNode> + 'static,
+    {
+        Self {
+            header: header.as_ref().to_owned(),
+            width,
+            template: Rc::new(template),
+            comparator: None,
+        }
+    }
+
+    /// Creates new column with simple text cells.
+    pub fn text<S, F>(header: S, width: f32, text: F) -> Self
+    where
+        S: AsRef<str>,
+        F: Fn(usize) -> String + 'static,
+    {
+        Self::new(header, width, move |ctx, row| {
+            TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::left(2.0)))
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .with_text(text(row))
+                .build(ctx)
+        })
+    }
+
+    /// Makes the column sortable.
+    pub fn with_comparator<C>(mut self, comparator: C) -> Self
+    where
+        C: Fn(usize, usize) -> Ordering + 'static,
+    {
+        self.comparator = Some(Rc::new(comparator));
+        self
+    }
+}
+
+/// Returns a range of positions of rows that intersect the viewport. `scroll` is a vertical
+/// offset of the viewport from the top of the first row.
+pub fn visible_row_range(
+    row_count: usize,
+    row_height: f32,
+    scroll: f32,
+    viewport_height: f32,
+) -> Range<usize> {
+    if row_count == 0 || row_height <= 0.0 || viewport_height <= 0.0 {
+        return 0..0;
+    }
+
+    let scroll = scroll.max(0.0);
+    let first = ((scroll / row_height).floor() as usize).min(row_count);
+    let last = (((scroll + viewport_height) / row_height).ceil() as usize).min(row_count);
+
+    first..last
+}
+
+/// Returns max scroll value for given amount of rows in a viewport.
+pub fn max_scroll(row_count: usize, row_height: f32, viewport_height: f32) -> f32 {
+    (row_count as f32 * row_height - viewport_height).max(0.0)
+}
+
+/// Returns a scroll value that makes the row at given position fully visible, while moving the
+/// viewport as little as possible.
+pub fn scroll_to_position(
+    position: usize,
+    row_height: f32,
+    scroll: f32,
+    viewport_height: f32,
+) -> f32 {
+    let top = position as f32 * row_height;
+    let bottom = top + row_height;
+    if top < scroll {
+        top
+    } else if bottom > scroll + viewport_height {
+        (bottom - viewport_height).max(0.0)
+    } else {
+        scroll
+    }
+}
+
+fn apply_click(
+    selection: &[usize],
+    row: usize,
+    modifiers: KeyboardModifiers,
+    multi_selection: bool,
+) -> Vec<usize> {
+    if multi_selection && modifiers.control {
+        let mut selection = selection.to_vec();
+        if let Some(position) = selection.iter().position(|r| *r == row) {
+            selection.remove(position);
+        } else {
+            selection.push(row);
+        }
+        selection
+    } else {
+        vec![row]
+    }
+}
+
+#[derive(Clone, Debug)]
+struct RealizedRow {
+    // Index of the row in the source data.
+    row: usize,
+    // Position of the row in the sorted view.
+    position: usize,
+    handle: Handle<UiNode>,
+    decorator: Handle<UiNode>,
+}
+
+#[derive(Clone)]
+struct Header {
+    button: Handle<UiNode>,
+    ascending: Handle<UiNode>,
+    descending: Handle<UiNode>,
+}
+
+#[derive(Clone)]
+pub struct DataGrid {
+    widget: Widget,
+    columns: Vec<DataGridColumn>,
+    headers: Vec<Header>,
+    row_height: f32,
+    // Maps position in the view to an index of a row in source data.
+    order: Vec<usize>,
+    sort: Option<(usize, SortOrder)>,
+    selection: Vec<usize>,
+    multi_selection: bool,
+    scroll: f32,
+    viewport_height: Cell<f32>,
+    body: Handle<UiNode>,
+    v_scroll_bar: Handle<UiNode>,
+    rows: Vec<RealizedRow>,
+}
+
+crate::define_widget_deref!(DataGrid);
+
+impl DataGrid {
+    pub fn columns(&self) -> &[DataGridColumn] {
+        &self.columns
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn row_height(&self) -> f32 {
+        self.row_height
+    }
+
+    /// Returns indices of rows in the order they're shown.
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    pub fn sorting(&self) -> Option<(usize, SortOrder)> {
+        self.sort
+    }
+
+    pub fn selection(&self) -> &[usize] {
+        &self.selection
+    }
+
+    pub fn is_multi_selection_allowed(&self) -> bool {
+        self.multi_selection
+    }
+
+    /// Returns the range of positions of rows that currently have widgets.
+    pub fn visible_rows(&self) -> Range<usize> {
+        visible_row_range(
+            self.order.len(),
+            self.row_height,
+            self.scroll,
+            self.viewport_height.get(),
+        )
+    }
+
+    /// Returns a handle of a widget of a row, if the row is visible.
+    pub fn row_widget(&self, row: usize) -> Handle<UiNode> {
+        self.rows
+            .iter()
+            .find(|r| r.row == row)
+            .map(|r| r.handle)
+            .unwrap_or_default()
+    }
+
+    fn apply_sort(&mut self) {
+        if let Some((column, order)) = self.sort {
+            if let Some(comparator) = self.columns.get(column).and_then(|c| c.comparator.as_ref()) {
+                self.order.sort_by(|a, b| {
+                    let ordering = comparator(*a, *b);
+                    match order {
+                        SortOrder::Ascending => ordering,
+                        SortOrder::Descending => ordering.reverse(),
+                    }
+                });
+            }
+        }
+    }
+
+    fn sync_headers(&self, ui: &UserInterface) {
+        for (i, header) in self.headers.iter().enumerate() {
+            let order = self
+                .sort
+                .and_then(|(c, o)| if c == i { Some(o) } else { None });
+            ui.send_message(WidgetMessage::visibility(
+                header.ascending,
+                MessageDirection::ToWidget,
+                order == Some(SortOrder::Ascending),
+            ));
+            ui.send_message(WidgetMessage::visibility(
+                header.descending,
+                MessageDirection::ToWidget,
+                order == Some(SortOrder::Descending),
+            ));
+        }
+    }
+
+    fn sync_decorators(&self, ui: &UserInterface) {
+        for row in self.rows.iter() {
+            ui.send_message(DecoratorMessage::select(
+                row.decorator,
+                MessageDirection::ToWidget,
+                self.selection.contains(&row.row),
+            ));
+        }
+    }
+
+    fn sync_scroll_bar(&self, ui: &UserInterface) {
+        ui.send_message(ScrollBarMessage::max_value(
+            self.v_scroll_bar,
+            MessageDirection::ToWidget,
+            max_scroll(
+                self.order.len(),
+                self.row_height,
+                self.viewport_height.get(),
+            ),
+        ));
+    }
+
+    fn clear_rows(&mut self, ui: &UserInterface) {
+        for row in self.rows.drain(..) {
+            ui.send_message(WidgetMessage::remove(
+                row.handle,
+                MessageDirection::ToWidget,
+            ));
+        }
+    }
+
+    fn build_row(&self, ctx: &mut BuildContext, row: usize, position: usize) -> RealizedRow {
+        let cells = self
+            .columns
+            .iter()
+            .map(|column| {
+                let content = (column.template)(ctx, row);
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_width(column.width)
+                        .with_clip_to_bounds(true)
+                        .with_background(Brush::Solid(Color::TRANSPARENT))
+                        .with_foreground(Brush::Solid(Color::TRANSPARENT))
+                        .with_child(content),
+                )
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let decorator = DecoratorBuilder::new(BorderBuilder::new(
+            WidgetBuilder::new().with_child(
+                StackPanelBuilder::new(WidgetBuilder::new().with_children(cells))
+                    .with_orientation(Orientation::Horizontal)
+                    .build(ctx),
+            ),
+        ))
+        .with_selected_brush(crate::BRUSH_BRIGHT_BLUE)
+        .build(ctx);
+
+        let handle = ctx.add_node(UiNode::new(DataGridRow {
+            widget: WidgetBuilder::new()
+                .with_height(self.row_height)
+                .with_width(self.columns.iter().map(|c| c.width).sum())
+                .with_child(decorator)
+                .build(),
+            row,
+        }));
+
+        RealizedRow {
+            row,
+            position,
+            handle,
+            decorator,
+        }
+    }
+
+    /// Creates widgets for rows that became visible and destroys widgets of rows that are
+    /// out of the viewport.
+    fn realize_rows(&mut self, ui: &mut UserInterface) {
+        let range = self.visible_rows();
+
+        let order = &self.order;
+        self.rows.retain(|r| {
+            let keep = range.contains(&r.position) && order[r.position] == r.row;
+            if !keep {
+                ui.send_message(WidgetMessage::remove(r.handle, MessageDirection::ToWidget));
+            }
+            keep
+        });
+
+        for position in range {
+            if !self.rows.iter().any(|r| r.position == position) {
+                let row = self.build_row(&mut ui.build_ctx(), self.order[position], position);
+                ui.send_message(WidgetMessage::link(
+                    row.handle,
+                    MessageDirection::ToWidget,
+                    self.body,
+                ));
+                self.rows.push(row);
+            }
+        }
+
+        for row in self.rows.iter() {
+            ui.send_message(WidgetMessage::desired_position(
+                row.handle,
+                MessageDirection::ToWidget,
+                Vector2::new(0.0, row.position as f32 * self.row_height - self.scroll),
+            ));
+        }
+
+        self.sync_decorators(ui);
+    }
+
+    fn find_row(&self, ui: &UserInterface, mut handle: Handle<UiNode>) -> Option<usize> {
+        while handle.is_some() && handle != self.handle {
+            if let Some(row) = ui.node(handle).cast::<DataGridRow>() {
+                return Some(row.row);
+            }
+            handle = ui.node(handle).parent();
+        }
+        None
+    }
+}
+
+impl Control for DataGrid {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn resolve(&mut self, node_map: &NodeHandleMapping) {
+        node_map.resolve(&mut self.body);
+        node_map.resolve(&mut self.v_scroll_bar);
+        for header in self.headers.iter_mut() {
+            node_map.resolve(&mut header.button);
+            node_map.resolve(&mut header.ascending);
+            node_map.resolve(&mut header.descending);
+        }
+        for row in self.rows.iter_mut() {
+            node_map.resolve(&mut row.handle);
+            node_map.resolve(&mut row.decorator);
+        }
+    }
+
+    fn arrange_override(&self, ui: &UserInterface, final_size: Vector2<f32>) -> Vector2<f32> {
+        let size = self.widget.arrange_override(ui, final_size);
+
+        let viewport_height = ui.node(self.body).actual_size().y;
+        if self.viewport_height.get() != viewport_height {
+            self.viewport_height.set(viewport_height);
+            ui.send_message(DataGridMessage::sync_viewport(
+                self.handle,
+                MessageDirection::ToWidget,
+            ));
+        }
+
+        size
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+
+        if let Some(msg) = message.data::<DataGridMessage>() {
+            if message.destination() == self.handle()
+                && message.direction() == MessageDirection::ToWidget
+            {
+                match msg {
+                    &DataGridMessage::RowCount(count) => {
+                        self.order = (0..count).collect();
+                        self.apply_sort();
+                        self.selection.retain(|r| *r < count);
+                        self.scroll = self.scroll.min(max_scroll(
+                            count,
+                            self.row_height,
+                            self.viewport_height.get(),
+                        ));
+                        self.clear_rows(ui);
+                        self.realize_rows(ui);
+                        self.sync_scroll_bar(ui);
+                    }
+                    &DataGridMessage::Sort { column, order } => {
+                        let sortable = self
+                            .columns
+                            .get(column)
+                            .map_or(false, |c| c.comparator.is_some());
+                        if sortable && self.sort != Some((column, order)) {
+                            self.sort = Some((column, order));
+                            self.order = (0..self.order.len()).collect();
+                            self.apply_sort();
+                            self.realize_rows(ui);
+                            self.sync_headers(ui);
+                            ui.send_message(message.reverse());
+                        }
+                    }
+                    DataGridMessage::Selection(selection) => {
+                        if &self.selection != selection {
+                            self.selection = selection.clone();
+                            self.sync_decorators(ui);
+                            ui.send_message(message.reverse());
+                        }
+                    }
+                    &DataGridMessage::BringRowIntoView(row) => {
+                        if let Some(position) = self.order.iter().position(|r| *r == row) {
+                            let scroll = scroll_to_position(
+                                position,
+                                self.row_height,
+                                self.scroll,
+                                self.viewport_height.get(),
+                            );
+                            ui.send_message(ScrollBarMessage::value(
+                                self.v_scroll_bar,
+                                MessageDirection::ToWidget,
+                                scroll,
+                            ));
+                        }
+                    }
+                    DataGridMessage::Invalidate => {
+                        self.clear_rows(ui);
+                        self.realize_rows(ui);
+                    }
+                    DataGridMessage::SyncViewport => {
+                        self.realize_rows(ui);
+                        self.sync_scroll_bar(ui);
+                    }
+                }
+            }
+        } else if let Some(ScrollBarMessage::Value(value)) = message.data::<ScrollBarMessage>() {
+            if message.destination() == self.v_scroll_bar
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.scroll = *value;
+                self.realize_rows(ui);
+            }
+        } else if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if let Some(column) = self
+                .headers
+                .iter()
+                .position(|h| h.button == message.destination())
+            {
+                let order = match self.sort {
+                    Some((c, order)) if c == column => order.reverse(),
+                    _ => SortOrder::Ascending,
+                };
+                ui.send_message(DataGridMessage::sort(
+                    self.handle,
+                    MessageDirection::ToWidget,
+                    column,
+                    order,
+                ));
+            }
+        } else if let Some(msg) = message.data::<WidgetMessage>() {
+            match msg {
+                WidgetMessage::MouseUp { .. } if !message.handled() => {
+                    if let Some(row) = self.find_row(ui, message.destination()) {
+                        let selection = apply_click(
+                            &self.selection,
+                            row,
+                            ui.keyboard_modifiers(),
+                            self.multi_selection,
+                        );
+                        ui.send_message(DataGridMessage::selection(
+                            self.handle,
+                            MessageDirection::ToWidget,
+                            selection,
+                        ));
+                        message.set_handled(true);
+                    }
+                }
+                &WidgetMessage::MouseWheel { amount, .. } if !message.handled() => {
+                    let scroll = (self.scroll - amount * self.row_height).clamp(
+                        0.0,
+                        max_scroll(
+                            self.order.len(),
+                            self.row_height,
+                            self.viewport_height.get(),
+                        ),
+                    );
+                    ui.send_message(ScrollBarMessage::value(
+                        self.v_scroll_bar,
+                        MessageDirection::ToWidget,
+                        scroll,
+                    ));
+                    message.set_handled(true);
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Container of a row, created by the grid internally.
+#[derive(Clone)]
+pub struct DataGridRow {
+    widget: Widget,
+    row: usize,
+}
+
+crate::define_widget_deref!(DataGridRow);
+
+impl DataGridRow {
+    /// Returns index of the row in source data.
+    pub fn row(&self) -> usize {
+        self.row
+    }
+}
+
+impl Control for DataGridRow {
+    fn query_component(&self, type_id: TypeId) -> Option<&dyn Any> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn handle_routed_message(&mut self, ui: &mut UserInterface, message: &mut UiMessage) {
+        self.widget.handle_routed_message(ui, message);
+    }
+}
+
+pub struct DataGridBuilder {
+    widget_builder: WidgetBuilder,
+    columns: Vec<DataGridColumn>,
+    row_count: usize,
+    row_height: f32,
+    multi_selection: bool,
+}
+
+impl DataGridBuilder {
+    pub fn new(widget_builder: WidgetBuilder) -> Self {
+        Self {
+            widget_builder,
+            columns: Default::default(),
+            row_count: 0,
+            row_height: 22.0,
+            multi_selection: false,
+        }
+    }
+
+    pub fn with_column(mut self, column: DataGridColumn) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    pub fn with_columns(mut self, columns: Vec<DataGridColumn>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    pub fn with_row_count(mut self, row_count: usize) -> Self {
+        self.row_count = row_count;
+        self
+    }
+
+    /// Sets height of every row. Rows must have the same height for virtualization to work.
+    pub fn with_row_height(mut self, row_height: f32) -> Self {
+        self.row_height = row_height;
+        self
+    }
+
+    pub fn with_multi_selection(mut self, multi_selection: bool) -> Self {
+        self.multi_selection = multi_selection;
+        self
+    }
+
+    pub fn build(self, ctx: &mut BuildContext) -> Handle<UiNode> {
+        let headers = self
+            .columns
+            .iter()
+            .map(|column| {
+                let ascending;
+                let descending;
+                let button = ButtonBuilder::new(WidgetBuilder::new().with_width(column.width))
+                    .with_content(
+                        GridBuilder::new(
+                            WidgetBuilder::new()
+                                .with_child(
+                                    TextBuilder::new(
+                                        WidgetBuilder::new().with_margin(Thickness::left(2.0)),
+                                    )
+                                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                                    .with_text(&column.header)
+                                    .build(ctx),
+                                )
+                                .with_child({
+                                    ascending = make_arrow(ctx, ArrowDirection::Top, 8.0);
+                                    ctx[ascending].set_column(1).set_visibility(false);
+                                    ascending
+                                })
+                                .with_child({
+                                    descending = make_arrow(ctx, ArrowDirection::Bottom, 8.0);
+                                    ctx[descending].set_column(1).set_visibility(false);
+                                    descending
+                                }),
+                        )
+                        .add_row(Row::auto())
+                        .add_column(Column::stretch())
+                        .add_column(Column::strict(12.0))
+                        .build(ctx),
+                    )
+                    .build(ctx);
+                Header {
+                    button,
+                    ascending,
+                    descending,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let header_panel = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_children(headers.iter().map(|h| h.button)),
+        )
+        .with_orientation(Orientation::Horizontal)
+        .build(ctx);
+
+        let body = CanvasBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_clip_to_bounds(true)
+                .with_vertical_alignment(VerticalAlignment::Stretch),
+        )
+        .build(ctx);
+
+        let v_scroll_bar =
+            ScrollBarBuilder::new(WidgetBuilder::new().on_row(1).on_column(1).with_width(16.0))
+                .with_orientation(Orientation::Vertical)
+                .with_step(self.row_height)
+                .with_max(0.0)
+                .build(ctx);
+
+        let back = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_background(BRUSH_DARK)
+                .with_foreground(BRUSH_LIGHT)
+                .with_child(
+                    GridBuilder::new(
+                        WidgetBuilder::new()
+                            .with_child(header_panel)
+                            .with_child(body)
+                            .with_child(v_scroll_bar),
+                    )
+                    .add_row(Row::auto())
+                    .add_row(Row::stretch())
+                    .add_column(Column::stretch())
+                    .add_column(Column::auto())
+                    .build(ctx),
+                ),
+        )
+        .with_stroke_thickness(Thickness::uniform(1.0))
+        .build(ctx);
+
+        let data_grid = DataGrid {
+            widget: self.widget_builder.with_child(back).build(),
+            columns: self.columns,
+            headers,
+            row_height: self.row_height,
+            order: (0..self.row_count).collect(),
+            sort: None,
+            selection: Default::default(),
+            multi_selection: self.multi_selection,
+            scroll: 0.0,
+            viewport_height: Cell::new(0.0),
+            body,
+            v_scroll_bar,
+            rows: Default::default(),
+        };
+
+        ctx.add_node(UiNode::new(data_grid))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector2,
+        data_grid::{
+            apply_click, max_scroll, scroll_to_position, visible_row_range, DataGrid,
+            DataGridBuilder, DataGridColumn, DataGridMessage, SortOrder,
+        },
+        message::{KeyboardModifiers, MessageDirection},
+        widget::WidgetBuilder,
+        UserInterface,
+    };
+
+    #[test]
+    fn test_visible_row_range() {
+        assert_eq!(visible_row_range(0, 20.0, 0.0, 100.0), 0..0);
+        assert_eq!(visible_row_range(100, 0.0, 0.0, 100.0), 0..0);
+        assert_eq!(visible_row_range(100, 20.0, 0.0, 0.0), 0..0);
+        assert_eq!(visible_row_range(100, 20.0, 0.0, 100.0), 0..5);
+        // Partially visible rows at both ends must be included.
+        assert_eq!(visible_row_range(100, 20.0, 10.0, 100.0), 0..6);
+        assert_eq!(visible_row_range(100, 20.0, 20.0, 100.0), 1..6);
+        // Scrolled to the end.
+        assert_eq!(visible_row_range(100, 20.0, 1900.0, 100.0), 95..100);
+        // Viewport is larger than content.
+        assert_eq!(visible_row_range(3, 20.0, 0.0, 500.0), 0..3);
+        // Scroll out of bounds must not produce invalid range.
+        assert_eq!(visible_row_range(3, 20.0, 1000.0, 100.0), 3..3);
+        assert_eq!(visible_row_range(3, 20.0, -10.0, 20.0), 0..1);
+    }
+
+    #[test]
+    fn test_visible_row_range_on_resize() {
+        let range = visible_row_range(1000, 25.0, 250.0, 100.0);
+        assert_eq!(range, 10..14);
+        let range = visible_row_range(1000, 25.0, 250.0, 260.0);
+        assert_eq!(range, 10..21);
+        let range = visible_row_range(1000, 25.0, 250.0, 10.0);
+        assert_eq!(range, 10..11);
+    }
+
+    #[test]
+    fn test_max_scroll() {
+        assert_eq!(max_scroll(0, 20.0, 100.0), 0.0);
+        assert_eq!(max_scroll(3, 20.0, 100.0), 0.0);
+        assert_eq!(max_scroll(10, 20.0, 100.0), 100.0);
+    }
+
+    #[test]
+    fn test_scroll_to_position() {
+        // Already visible.
+        assert_eq!(scroll_to_position(2, 20.0, 0.0, 100.0), 0.0);
+        // Above viewport.
+        assert_eq!(scroll_to_position(1, 20.0, 60.0, 100.0), 20.0);
+        // Below viewport.
+        assert_eq!(scroll_to_position(10, 20.0, 0.0, 100.0), 120.0);
+    }
+
+    #[test]
+    fn test_apply_click() {
+        let control = KeyboardModifiers {
+            control: true,
+            ..Default::default()
+        };
+        assert_eq!(apply_click(&[1, 2], 3, Default::default(), true), vec![3]);
+        assert_eq!(apply_click(&[1, 2], 3, control, true), vec![1, 2, 3]);
+        assert_eq!(apply_click(&[1, 2], 2, control, true), vec![1]);
+        assert_eq!(apply_click(&[1, 2], 3, control, false), vec![3]);
+    }
+
+    #[test]
+    fn test_sorting_and_virtualization() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut ui = UserInterface::new(screen_size);
+        let values = (0..1000).rev().collect::<Vec<i32>>();
+        let data = values.clone();
+        let grid = DataGridBuilder::new(WidgetBuilder::new().with_height(200.0))
+            .with_row_height(20.0)
+            .with_row_count(values.len())
+            .with_column(
+                DataGridColumn::text("Value", 100.0, move |row| data[row].to_string())
+                    .with_comparator(move |a, b| values[a].cmp(&values[b])),
+            )
+            .build(&mut ui.build_ctx());
+
+        for _ in 0..3 {
+            ui.update(screen_size, 0.0);
+            while ui.poll_message().is_some() {}
+        }
+
+        let grid_ref = ui.node(grid).cast::<DataGrid>().unwrap();
+        let visible = grid_ref.visible_rows();
+        assert_eq!(visible.start, 0);
+        assert!(visible.end > 0 && visible.end < 20);
+        assert!(grid_ref.row_widget(0).is_some());
+        assert!(grid_ref.row_widget(500).is_none());
+
+        ui.send_message(DataGridMessage::sort(
+            grid,
+            MessageDirection::ToWidget,
+            0,
+            SortOrder::Ascending,
+        ));
+        while ui.poll_message().is_some() {}
+
+        let grid_ref = ui.node(grid).cast::<DataGrid>().unwrap();
+        assert_eq!(grid_ref.order()[0], 999);
+        assert_eq!(grid_ref.order()[999], 0);
+        assert!(grid_ref.row_widget(999).is_some());
+        assert!(grid_ref.row_widget(0).is_none());
+    }
+}
@@ -18,6 +18,7 @@ pub mod canvas;
 pub mod check_box;
 pub mod color;
 pub mod curve;
+pub mod data_grid;
 pub mod decorator;
 pub mod dock;
 pub mod draw;
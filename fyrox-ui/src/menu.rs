@@ -39,12 +39,15 @@ pub enum MenuItemMessage {
     Open,
     Close,
     Click,
+    /// Replaces items of a menu item with the given ones. Previous items will be removed.
+    Items(Vec<Handle<UiNode>>),
 }
 
 impl MenuItemMessage {
     define_constructor!(MenuItemMessage:Open => fn open(), layout: false);
     define_constructor!(MenuItemMessage:Close => fn close(), layout: false);
     define_constructor!(MenuItemMessage:Click => fn click(), layout: false);
+    define_constructor!(MenuItemMessage:Items => fn items(Vec<Handle<UiNode>>), layout: false);
 }
 
 #[derive(Clone)]
@@ -319,6 +322,22 @@ impl Control for MenuItem {
                     ui.send_message(PopupMessage::close(self.popup, MessageDirection::ToWidget));
                 }
                 MenuItemMessage::Click => {}
+                MenuItemMessage::Items(items) => {
+                    if message.destination() == self.handle()
+                        && message.direction() == MessageDirection::ToWidget
+                    {
+                        let content = StackPanelBuilder::new(
+                            WidgetBuilder::new().with_children(items.iter().cloned()),
+                        )
+                        .build(&mut ui.build_ctx());
+                        ui.send_message(PopupMessage::content(
+                            self.popup,
+                            MessageDirection::ToWidget,
+                            content,
+                        ));
+                        self.items = items.clone();
+                    }
+                }
             }
         }
     }
@@ -0,0 +1,319 @@
+//! Lightweight blending of animations without an animation machine. See [`AnimationBlender`] docs
+//! for more info.
+
+use crate::{
+    animation::{Animation, AnimationPose},
+    core::pool::{Handle, Pool},
+};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone)]
+struct WeightFade {
+    animation: Handle<Animation>,
+    target: f32,
+    // Weight units per second.
+    rate: f32,
+}
+
+#[derive(Debug, Clone)]
+struct QueuedAnimation {
+    after: Handle<Animation>,
+    next: Handle<Animation>,
+    fade_duration: f32,
+}
+
+/// Animation blender allows you to make smooth transitions between animations for simple cases
+/// when a full animation blending state machine (see [`crate::animation::machine::Machine`]) is
+/// overkill, for example to crossfade from idle to death animation once.
+///
+/// Every scene has its own blender, which is accessible through the animation container of the
+/// scene: use [`crate::animation::AnimationContainer::crossfade`] and
+/// [`crate::animation::AnimationContainer::play_then`]. The blender changes weights of the
+/// animations (see [`Animation::set_weight`]) over time, and mixes poses of the animations
+/// using the weights. The final pose is applied to the scene graph automatically.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{animation::Animation, core::pool::Handle, scene::Scene};
+///
+/// fn die(scene: &mut Scene, idle: Handle<Animation>, death: Handle<Animation>) {
+///     // Idle animation will fade out and death animation will fade in in 0.3 seconds.
+///     scene.animations.crossfade(idle, death, 0.3);
+/// }
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct AnimationBlender {
+    animations: Vec<Handle<Animation>>,
+    fades: Vec<WeightFade>,
+    queue: VecDeque<QueuedAnimation>,
+    pose: AnimationPose,
+}
+
+impl AnimationBlender {
+    /// Returns `true` if the blender controls at least one animation.
+    pub fn is_active(&self) -> bool {
+        !self.animations.is_empty()
+    }
+
+    /// Returns `true` if the animation is controlled by the blender.
+    pub fn is_blending(&self, animation: Handle<Animation>) -> bool {
+        self.animations.contains(&animation)
+    }
+
+    /// Returns final pose of all animations controlled by the blender.
+    pub fn pose(&self) -> &AnimationPose {
+        &self.pose
+    }
+
+    /// Releases all animations, their weights and states are left as is.
+    pub fn clear(&mut self) {
+        self.animations.clear();
+        self.fades.clear();
+        self.queue.clear();
+        self.pose.reset();
+    }
+
+    fn set_fade(&mut self, animation: Handle<Animation>, target: f32, duration: f32) {
+        self.fades.retain(|f| f.animation != animation);
+        self.fades.push(WeightFade {
+            animation,
+            target,
+            rate: if duration > 0.0 {
+                1.0 / duration
+            } else {
+                f32::INFINITY
+            },
+        });
+    }
+
+    pub(in crate) fn crossfade(
+        &mut self,
+        pool: &mut Pool<Animation>,
+        from: Handle<Animation>,
+        to: Handle<Animation>,
+        duration: f32,
+    ) {
+        if from == to || !pool.is_valid_handle(from) || !pool.is_valid_handle(to) {
+            return;
+        }
+
+        if !self.is_blending(from) {
+            self.animations.push(from);
+        }
+
+        let to_animation = &mut pool[to];
+        if self.is_blending(to) {
+            // The animation is partially active (it is fading in or out), keep its time position
+            // and continue from its current weight.
+            to_animation.set_enabled(true);
+        } else {
+            to_animation.rewind().set_weight(0.0).set_enabled(true);
+            // Pose must be valid right away, otherwise it will be blended with an empty pose.
+            to_animation.update_pose();
+            self.animations.push(to);
+        }
+
+        self.set_fade(from, 0.0, duration);
+        self.set_fade(to, 1.0, duration);
+    }
+
+    pub(in crate) fn play_then(
+        &mut self,
+        pool: &mut Pool<Animation>,
+        first: Handle<Animation>,
+        next: Handle<Animation>,
+        fade_duration: f32,
+    ) {
+        if !pool.is_valid_handle(first) || !pool.is_valid_handle(next) {
+            return;
+        }
+
+        // Allow chaining - `first` will start when its own turn comes.
+        let is_queued = self.queue.iter().any(|q| q.next == first);
+        if !is_queued && !self.is_blending(first) {
+            let first_animation = &mut pool[first];
+            first_animation.rewind().set_weight(1.0).set_enabled(true);
+            first_animation.update_pose();
+            self.animations.push(first);
+        }
+
+        self.queue.push_back(QueuedAnimation {
+            after: first,
+            next,
+            fade_duration,
+        });
+    }
+
+    pub(in crate) fn update(&mut self, pool: &mut Pool<Animation>, dt: f32) {
+        // Forget removed animations.
+        self.animations.retain(|a| pool.is_valid_handle(*a));
+        self.fades.retain(|f| pool.is_valid_handle(f.animation));
+        self.queue
+            .retain(|q| pool.is_valid_handle(q.after) && pool.is_valid_handle(q.next));
+
+        while let Some(queued) = self.queue.front() {
+            let after = &pool[queued.after];
+            let remaining = if after.has_ended() {
+                0.0
+            } else if after.get_speed() == 0.0 {
+                f32::INFINITY
+            } else {
+                (after.length() - after.get_time_position()) / after.get_speed().abs()
+            };
+            if self.is_blending(queued.after) && remaining > queued.fade_duration {
+                break;
+            }
+            let queued = self.queue.pop_front().unwrap();
+            self.crossfade(pool, queued.after, queued.next, queued.fade_duration);
+        }
+
+        let animations = &mut self.animations;
+        self.fades.retain(|fade| {
+            let animation = &mut pool[fade.animation];
+            let step = fade.rate * dt;
+            let weight = animation.weight();
+            let new_weight = if weight < fade.target {
+                (weight + step).min(fade.target)
+            } else {
+                (weight - step).max(fade.target)
+            };
+            animation.set_weight(new_weight);
+
+            let done = new_weight == fade.target;
+            if done && fade.target == 0.0 {
+                // Fully faded out animation does not contribute to the pose anymore.
+                animation.set_enabled(false);
+                animations.retain(|a| *a != fade.animation);
+            }
+            !done
+        });
+
+        self.pose.reset();
+        let total_weight = self
+            .animations
+            .iter()
+            .map(|a| pool[*a].weight())
+            .sum::<f32>();
+        if total_weight > 0.0 {
+            for handle in self.animations.iter() {
+                let animation = &pool[*handle];
+                self.pose
+                    .blend_with(animation.get_pose(), animation.weight() / total_weight);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{Animation, AnimationContainer, KeyFrame, Track},
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            pool::Handle,
+        },
+        scene::node::Node,
+    };
+
+    fn make_animation(node: Handle<Node>, x: f32, length: f32) -> Animation {
+        let mut track = Track::new();
+        track.set_node(node);
+        for time in [0.0, length] {
+            track.add_key_frame(KeyFrame::new(
+                time,
+                Vector3::new(x, 0.0, 0.0),
+                Vector3::new(1.0, 1.0, 1.0),
+                UnitQuaternion::identity(),
+            ));
+        }
+        let mut animation = Animation::default();
+        animation.add_track(track);
+        animation
+    }
+
+    fn position(animations: &AnimationContainer, node: Handle<Node>) -> f32 {
+        animations
+            .blended_pose()
+            .local_pose(node)
+            .unwrap()
+            .position()
+            .x
+    }
+
+    #[test]
+    fn test_crossfade() {
+        let node = Handle::new(1, 1);
+        let mut animations = AnimationContainer::new();
+        let idle = animations.add(make_animation(node, 0.0, 1.0));
+        let death = animations.add(make_animation(node, 10.0, 1.0));
+
+        animations.crossfade(idle, death, 1.0);
+
+        animations.update_animations(0.5);
+        assert_eq!(animations[idle].weight(), 0.5);
+        assert_eq!(animations[death].weight(), 0.5);
+        assert!((position(&animations, node) - 5.0).abs() < 1.0e-5);
+
+        animations.update_animations(0.25);
+        assert!((position(&animations, node) - 7.5).abs() < 1.0e-5);
+
+        animations.update_animations(0.25);
+        assert!(!animations[idle].is_enabled());
+        assert!(!animations.blender().is_blending(idle));
+        assert_eq!(animations[death].weight(), 1.0);
+
+        animations.update_animations(0.1);
+        assert!((position(&animations, node) - 10.0).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_crossfade_partially_active() {
+        let node = Handle::new(1, 1);
+        let mut animations = AnimationContainer::new();
+        let idle = animations.add(make_animation(node, 0.0, 1.0));
+        let walk = animations.add(make_animation(node, 10.0, 1.0));
+
+        animations.crossfade(idle, walk, 1.0);
+        animations.update_animations(0.25);
+
+        // Change mind and go back to idle, it must continue from current weight.
+        animations.crossfade(walk, idle, 1.0);
+        assert_eq!(animations[idle].weight(), 0.75);
+        animations.update_animations(0.125);
+        assert_eq!(animations[idle].weight(), 0.875);
+        assert_eq!(animations[walk].weight(), 0.125);
+        assert!((position(&animations, node) - 1.25).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_play_then() {
+        let node = Handle::new(1, 1);
+        let mut animations = AnimationContainer::new();
+        let a = animations.add(make_animation(node, 0.0, 1.0));
+        let b = animations.add(make_animation(node, 10.0, 1.0));
+        let c = animations.add(make_animation(node, 20.0, 1.0));
+        for animation in [a, b, c] {
+            animations[animation].set_loop(false).set_enabled(false);
+        }
+
+        animations.play_then(a, b, 0.5);
+        animations.play_then(b, c, 0.5);
+
+        assert!(animations[a].is_enabled());
+        assert!(!animations[b].is_enabled());
+
+        // `b` starts to fade in when `a` has 0.5 seconds left.
+        animations.update_animations(0.25);
+        assert!(!animations[b].is_enabled());
+        animations.update_animations(0.25);
+        assert!(animations[b].is_enabled());
+        assert!(!animations[c].is_enabled());
+
+        for _ in 0..4 {
+            animations.update_animations(0.25);
+        }
+        assert!(!animations[a].is_enabled());
+        assert!(animations[c].is_enabled());
+    }
+}
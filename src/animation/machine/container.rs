@@ -70,6 +70,12 @@ impl AnimationMachineContainer {
         self.pool.iter_mut()
     }
 
+    /// Returns an iterator over handles and references of every machine, it allows tools to find
+    /// machines (for example, by their [`Machine::root`]) and to address them later by handle.
+    pub fn pair_iter(&self) -> impl Iterator<Item = (Handle<Machine>, &Machine)> {
+        self.pool.pair_iter()
+    }
+
     pub async fn resolve(
         &mut self,
         resource_manager: ResourceManager,
@@ -2,7 +2,11 @@ use crate::{
     animation::machine::{State, Transition},
     core::pool::Handle,
 };
-use std::collections::VecDeque;
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Formatter},
+    sync::Arc,
+};
 
 /// Specific machine event.
 #[derive(Debug, Clone)]
@@ -20,13 +24,49 @@ pub enum Event {
     ActiveTransitionChanged(Handle<Transition>),
 }
 
+/// Tells when a [`StateAction`] was fired.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StateActionKind {
+    /// The machine has entered the state.
+    Enter,
+    /// The machine has left the state.
+    Exit,
+}
+
+/// An action that was fired by a state of a machine, see
+/// [`crate::animation::machine::state::StateDefinition::on_enter`] and
+/// [`crate::animation::machine::state::StateDefinition::on_exit`]. Actions are delivered to the
+/// script of the root node of the machine (see [`crate::script::ScriptTrait::on_state_action`])
+/// or to a callback registered with [`crate::animation::machine::Machine::set_action_callback`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateAction {
+    /// Whether the state was entered or left.
+    pub kind: StateActionKind,
+    /// A handle of the state that fired the action.
+    pub state: Handle<State>,
+    /// A name of the state that fired the action.
+    pub state_name: String,
+    /// A name of the action.
+    pub name: String,
+}
+
+/// A callback that receives every fired [`StateAction`] instead of the script of the root node.
+#[derive(Clone)]
+pub struct StateActionCallback(pub Arc<dyn Fn(&StateAction) + Send + Sync>);
+
+impl Debug for StateActionCallback {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "StateActionCallback")
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct LimitedEventQueue {
-    queue: VecDeque<Event>,
+pub struct LimitedEventQueue<T = Event> {
+    queue: VecDeque<T>,
     limit: u32,
 }
 
-impl Default for LimitedEventQueue {
+impl<T> Default for LimitedEventQueue<T> {
     fn default() -> Self {
         Self {
             queue: Default::default(),
@@ -35,7 +75,7 @@ impl Default for LimitedEventQueue {
     }
 }
 
-impl LimitedEventQueue {
+impl<T> LimitedEventQueue<T> {
     pub fn new(limit: u32) -> Self {
         Self {
             queue: VecDeque::with_capacity(limit as usize),
@@ -43,13 +83,13 @@ impl LimitedEventQueue {
         }
     }
 
-    pub fn push(&mut self, event: Event) {
+    pub fn push(&mut self, event: T) {
         if self.queue.len() < (self.limit as usize) {
             self.queue.push_back(event);
         }
     }
 
-    pub fn pop(&mut self) -> Option<Event> {
+    pub fn pop(&mut self) -> Option<T> {
         self.queue.pop_front()
     }
 }
@@ -42,6 +42,12 @@
 //! is just Rule parameter which can have boolean value that indicates that transition
 //! should be activated.
 //!
+//! Transitions could also have built-in conditions, that are based on the time since the machine
+//! entered the source state or on the playback position of the source state, see
+//! [`TransitionCondition`] docs for more info. For example, an attack state could be left when its
+//! animation is 80% done without any parameters set from scripts. The same values could be
+//! fetched using [`Machine::state_time`] and [`Machine::state_position`].
+//!
 //! Example:
 //!
 //! ```no_run
@@ -88,19 +94,20 @@
 //! locomotion and other is for combat. This means that locomotion machine will take control over
 //! lower body and combat machine will control upper body.
 
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use std::cmp::Ordering;
 use std::sync::Arc;
 
 use crate::{
     animation::{
         machine::{
-            event::LimitedEventQueue,
+            event::{LimitedEventQueue, StateActionCallback},
             node::{BasePoseNode, PoseNodeDefinition},
             parameter::ParameterContainerDefinition,
             state::StateDefinition,
             transition::TransitionDefinition,
         },
+        retarget::{BindPose, BoneMapping},
         AnimationContainer, AnimationPose,
     },
     core::futures::future::join_all,
@@ -118,16 +125,16 @@ use crate::{
     scene::{graph::Graph, node::Node, Scene},
     utils::log::{Log, MessageKind},
 };
-pub use event::Event;
+pub use event::{Event, StateAction, StateActionKind};
 use fyrox_resource::ResourceState;
 pub use node::{
     blend::{BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput},
     play::PlayAnimation,
     EvaluatePose, PoseNode,
 };
-pub use parameter::{Parameter, ParameterContainer, PoseWeight};
+pub use parameter::{Parameter, ParameterContainer, ParameterError, PoseWeight};
 pub use state::State;
-pub use transition::Transition;
+pub use transition::{ComparisonOperator, Transition, TransitionCondition};
 
 pub mod container;
 pub mod event;
@@ -153,7 +160,13 @@ pub struct Machine {
     #[visit(skip)]
     events: LimitedEventQueue,
     #[visit(skip)]
+    actions: LimitedEventQueue<StateAction>,
+    #[visit(skip)]
+    action_callback: Option<StateActionCallback>,
+    #[visit(skip)]
     debug: bool,
+    #[visit(skip)]
+    changed_parameters: FxHashSet<String>,
 }
 
 #[derive(Default, Debug, Visit, Clone)]
@@ -209,6 +222,7 @@ fn instantiate_node(
     definition_handle: Handle<PoseNodeDefinition>,
     animation_resources: &FxHashMap<String, Model>,
     root: Handle<Node>,
+    retargeting: Option<(&BoneMapping, &BindPose)>,
     graph: &mut Graph,
     animations: &mut AnimationContainer,
 ) -> Result<PoseNode, MachineInstantiationError> {
@@ -217,8 +231,12 @@ fn instantiate_node(
             let resource = animation_resources.get(&play_animation.animation).unwrap();
 
             let animation = if matches!(*resource.state(), ResourceState::Ok(_)) {
-                *resource
-                    .retarget_animations_internal(root, graph, animations)
+                let instances = match retargeting {
+                    Some((mapping, bind_pose)) => resource
+                        .retarget_animations_with_mapping_internal(mapping, bind_pose, animations),
+                    None => resource.retarget_animations_internal(root, graph, animations),
+                };
+                *instances
                     .first()
                     .ok_or(MachineInstantiationError::InvalidAnimation)?
             } else {
@@ -277,7 +295,8 @@ async fn load_animation_resources(
 }
 
 impl MachineDefinition {
-    pub(crate) fn collect_animation_paths(&self) -> Vec<String> {
+    /// Returns paths of animation resources used by every PlayAnimation node of the machine.
+    pub fn collect_animation_paths(&self) -> Vec<String> {
         self.nodes
             .iter()
             .filter_map(|node| {
@@ -315,14 +334,21 @@ impl MachineDefinition {
         root: Handle<Node>,
         scene: &mut Scene,
         resource_manager: ResourceManager,
+        bone_mapping: Option<&BoneMapping>,
     ) -> Result<Handle<Machine>, MachineInstantiationError> {
         let mut machine = Machine::new(root);
 
         let animation_resources = load_animation_resources(self, resource_manager).await;
 
+        // The hierarchy is not animated yet, so its current pose is the bind pose.
+        let bind_pose = bone_mapping.map(|_| BindPose::from_hierarchy(&scene.graph, root));
+        let retargeting = bone_mapping.zip(bind_pose.as_ref());
+
         // Initialize parameters.
         for definition in self.parameters.container.iter() {
-            machine.set_parameter(&definition.name, definition.value);
+            machine
+                .parameters
+                .insert(definition.name.clone(), definition.value);
         }
 
         // Instantiate nodes.
@@ -333,6 +359,7 @@ impl MachineDefinition {
                 definition_handle,
                 &animation_resources,
                 root,
+                retargeting,
                 &mut scene.graph,
                 &mut scene.animations,
             )?);
@@ -397,7 +424,9 @@ impl MachineDefinition {
                     .get(&state_definition.root)
                     .cloned()
                     .unwrap_or_default(),
-            );
+            )
+            .with_on_enter(state_definition.on_enter_names())
+            .with_on_exit(state_definition.on_exit_names());
 
             state.definition = definition_handle;
 
@@ -420,7 +449,8 @@ impl MachineDefinition {
                     .expect("There must be a respective dest state!"),
                 transition_definition.transition_time,
                 transition_definition.rule.as_str(),
-            );
+            )
+            .with_condition(transition_definition.condition);
 
             transition.definition = transition_definition_handle;
 
@@ -485,7 +515,10 @@ impl Machine {
             active_transition: Default::default(),
             parameters: Default::default(),
             events: LimitedEventQueue::new(2048),
+            actions: LimitedEventQueue::new(2048),
+            action_callback: None,
             debug: false,
+            changed_parameters: Default::default(),
         }
     }
 
@@ -498,16 +531,78 @@ impl Machine {
     pub fn set_parameter(&mut self, id: &str, new_value: Parameter) -> &mut Self {
         match self.parameters.get_mut(id) {
             Some(parameter) => {
-                *parameter = new_value;
+                if *parameter != new_value {
+                    *parameter = new_value;
+                    self.changed_parameters.insert(id.to_owned());
+                }
             }
             None => {
                 self.parameters.insert(id.to_owned(), new_value);
+                self.changed_parameters.insert(id.to_owned());
             }
         }
 
         self
     }
 
+    /// Sets a new value of an existing parameter. Unlike [`Self::set_parameter`], it never adds
+    /// new parameters and it does not allow to change the kind of a parameter, so it could be used
+    /// by tools (debug consoles, etc.) that don't know the layout of the machine at compile time.
+    #[inline]
+    pub fn set_parameter_dynamic(
+        &mut self,
+        name: &str,
+        new_value: Parameter,
+    ) -> Result<(), ParameterError> {
+        let parameter = self
+            .parameters
+            .get(name)
+            .ok_or_else(|| ParameterError::NotFound(name.to_owned()))?;
+
+        if !parameter.is_same_kind(&new_value) {
+            return Err(ParameterError::KindMismatch {
+                name: name.to_owned(),
+                expected: parameter.as_ref().to_owned(),
+                actual: new_value.as_ref().to_owned(),
+            });
+        }
+
+        self.set_parameter(name, new_value);
+
+        Ok(())
+    }
+
+    /// Returns a value of a parameter with given name.
+    #[inline]
+    pub fn parameter(&self, name: &str) -> Option<Parameter> {
+        self.parameters.get(name).cloned()
+    }
+
+    /// Returns an iterator over names and values of every parameter of the machine. The order of
+    /// parameters is unspecified.
+    #[inline]
+    pub fn parameters(&self) -> impl Iterator<Item = (&str, Parameter)> {
+        self.parameters
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+    }
+
+    /// Returns `true` if the parameter was added or its value was changed since the last
+    /// [`Self::take_changed_parameters`] call.
+    #[inline]
+    pub fn is_parameter_changed(&self, name: &str) -> bool {
+        self.changed_parameters.contains(name)
+    }
+
+    /// Returns names of every parameter that was added or changed since the last call and resets
+    /// the change flags, it could be used to highlight recently changed parameters in debug tools.
+    /// Initial values of parameters (set when a machine is instantiated or synced with its
+    /// resource) are not treated as changes.
+    #[inline]
+    pub fn take_changed_parameters(&mut self) -> Vec<String> {
+        self.changed_parameters.drain().collect()
+    }
+
     #[inline]
     pub fn set_entry_state(&mut self, entry_state: Handle<State>) {
         self.active_state = entry_state;
@@ -548,6 +643,37 @@ impl Machine {
         self.events.pop()
     }
 
+    /// Pops the oldest fired state action. Actions are collected only if there is no action
+    /// callback (see [`Self::set_action_callback`]). Usually there is no need to call this method
+    /// directly, the engine delivers actions to the script of the root node of the machine.
+    #[inline]
+    pub fn pop_action(&mut self) -> Option<StateAction> {
+        self.actions.pop()
+    }
+
+    /// Sets a callback that will be called for every fired state action (see
+    /// [`state::StateDefinition::on_enter`]) right at the moment it is fired. When the callback is
+    /// set, actions are not collected and thus are not delivered to the script of the root node.
+    #[inline]
+    pub fn set_action_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&StateAction) + Send + Sync + 'static,
+    {
+        self.action_callback = Some(StateActionCallback(Arc::new(callback)));
+    }
+
+    /// Removes the action callback, see [`Self::set_action_callback`].
+    #[inline]
+    pub fn remove_action_callback(&mut self) {
+        self.action_callback = None;
+    }
+
+    /// Returns a handle of the node the machine is animating.
+    #[inline]
+    pub fn root(&self) -> Handle<Node> {
+        self.root
+    }
+
     #[inline]
     pub fn resource(&self) -> Option<AbsmResource> {
         self.resource.clone()
@@ -559,6 +685,10 @@ impl Machine {
             transition.reset();
         }
 
+        for state in self.states.iter_mut() {
+            state.elapsed_time = 0.0;
+        }
+
         self.active_state = self.entry_state;
     }
 
@@ -582,6 +712,35 @@ impl Machine {
         self.active_transition
     }
 
+    /// Returns the state the machine is in: the active state or the destination of the active
+    /// transition.
+    #[inline]
+    pub fn current_state(&self) -> Handle<State> {
+        if self.active_transition.is_some() {
+            self.transitions[self.active_transition].dest()
+        } else {
+            self.active_state
+        }
+    }
+
+    /// Returns time (in seconds) since the machine entered its current state (see
+    /// [`Self::current_state`]).
+    #[inline]
+    pub fn state_time(&self) -> f32 {
+        self.states
+            .try_borrow(self.current_state())
+            .map_or(0.0, |state| state.elapsed_time())
+    }
+
+    /// Returns normalized playback position (0..1) of the current state (see
+    /// [`Self::current_state`] and [`State::position`]).
+    #[inline]
+    pub fn state_position(&self) -> f32 {
+        self.states
+            .try_borrow(self.current_state())
+            .map_or(0.0, |state| state.position())
+    }
+
     #[inline]
     pub fn transitions(&self) -> &Pool<Transition> {
         &self.transitions
@@ -610,6 +769,19 @@ impl Machine {
         if let Some(resource) = self.resource.clone() {
             let definition = &resource.data_ref().absm_definition;
 
+            // Animations of a machine are either retargeted by names or all of them are retargeted
+            // using the same bone mapping, new animations must be retargeted the same way.
+            let retargeting = self.nodes.iter().find_map(|node| match node {
+                PoseNode::PlayAnimation(play_animation) => animations
+                    .try_get(play_animation.animation)
+                    .and_then(|animation| animation.retargeting())
+                    .cloned(),
+                _ => None,
+            });
+            let retargeting = retargeting
+                .as_ref()
+                .map(|retargeting| (&retargeting.mapping, &retargeting.target_bind_pose));
+
             // Step 1. Restore integrity - add missing entities, remove nonexistent from instance.
             match definition
                 .nodes
@@ -645,6 +817,7 @@ impl Machine {
                                 node_definition_handle,
                                 animation_resources,
                                 self.root,
+                                retargeting,
                                 graph,
                                 animations,
                             )
@@ -699,7 +872,9 @@ impl Machine {
                         {
                             let root = find_node_by_definition(&self.nodes, state_definition.root);
 
-                            let mut state = State::new(state_definition.name.as_ref(), root);
+                            let mut state = State::new(state_definition.name.as_ref(), root)
+                                .with_on_enter(state_definition.on_enter_names())
+                                .with_on_exit(state_definition.on_exit_names());
 
                             state.definition = state_definition_handle;
 
@@ -752,7 +927,8 @@ impl Machine {
                                 find_state_by_definition(&self.states, transition_definition.dest),
                                 transition_definition.transition_time,
                                 transition_definition.rule.as_str(),
-                            );
+                            )
+                            .with_condition(transition_definition.condition);
 
                             transition.definition = transition_definition_handle;
 
@@ -787,13 +963,17 @@ impl Machine {
                                 {
                                     if matches!(*definition_animation.state(), ResourceState::Ok(_))
                                     {
-                                        definition_animation
-                                            .retarget_animations_internal(
-                                                self.root, graph, animations,
-                                            )
-                                            .first()
-                                            .cloned()
-                                            .unwrap_or_default()
+                                        let instances = match retargeting {
+                                            Some((mapping, bind_pose)) => definition_animation
+                                                .retarget_animations_with_mapping_internal(
+                                                    mapping, bind_pose, animations,
+                                                ),
+                                            None => definition_animation
+                                                .retarget_animations_internal(
+                                                    self.root, graph, animations,
+                                                ),
+                                        };
+                                        instances.first().cloned().unwrap_or_default()
                                     } else {
                                         Handle::NONE
                                     }
@@ -870,6 +1050,12 @@ impl Machine {
                     definition: state.definition,
                     name: state_definition.name.clone(),
                     root: find_node_by_definition(&self.nodes, state_definition.root),
+                    on_enter: state_definition.on_enter_names(),
+                    on_exit: state_definition.on_exit_names(),
+                    elapsed_time: state.elapsed_time,
+                    position: state.position,
+                    position_source: state.position_source,
+                    position_wrapped: state.position_wrapped,
                 };
             }
 
@@ -885,18 +1071,176 @@ impl Machine {
                     dest: find_state_by_definition(&self.states, transition_definition.dest),
                     rule: transition_definition.rule.clone(),
                     invert_rule: transition_definition.invert_rule,
+                    condition: transition_definition.condition,
                     blend_factor: transition.blend_factor,
                 };
             }
 
             // Step 3. Sync parameters.
             self.parameters.clear();
+            self.changed_parameters.clear();
             for definition in definition.parameters.container.iter() {
-                self.set_parameter(&definition.name, definition.value);
+                self.parameters
+                    .insert(definition.name.clone(), definition.value);
+            }
+        }
+    }
+
+    /// Returns final pose of the machine calculated by the last [`Self::evaluate_pose`] call.
+    #[inline]
+    pub fn pose(&self) -> &AnimationPose {
+        &self.final_pose
+    }
+
+    /// Simulates the machine and the animations it uses for the given amount of time, starting from
+    /// their current state. The simulation is done using fixed time steps (the last step could be
+    /// shorter to hit `time` exactly), which makes it deterministic - the same initial state and
+    /// the same arguments always give the same result. The machine and the animations are left
+    /// untouched, simulated copies are returned instead.
+    ///
+    /// # Use cases
+    ///
+    /// Machines are not reversible, the only way to get a state of a machine at some point of time
+    /// is to simulate it from some known state. This is useful for previewing and scrubbing (see
+    /// the ABSM editor for example).
+    pub fn simulate(
+        &self,
+        animations: &AnimationContainer,
+        time: f32,
+        step: f32,
+    ) -> (Machine, AnimationContainer) {
+        assert!(step > 0.0);
+
+        let mut machine = self.clone();
+        let mut animations = animations.clone();
+
+        // Use integer amount of steps to prevent accumulation of floating point errors.
+        let time = time.max(0.0);
+        let full_steps = (time / step).floor() as usize;
+        let remainder = time - full_steps as f32 * step;
+
+        for dt in std::iter::repeat(step)
+            .take(full_steps)
+            .chain(std::iter::once(remainder).filter(|dt| *dt > 0.0))
+        {
+            animations.update_animations(dt);
+            machine.evaluate_pose(&animations, dt);
+        }
+
+        (machine, animations)
+    }
+
+    fn fire_actions(&mut self, state: Handle<State>, kind: StateActionKind) {
+        let state_ref = &self.states[state];
+        let names = match kind {
+            StateActionKind::Enter => &state_ref.on_enter,
+            StateActionKind::Exit => &state_ref.on_exit,
+        };
+        for name in names {
+            let action = StateAction {
+                kind,
+                state,
+                state_name: state_ref.name.clone(),
+                name: name.clone(),
+            };
+            if let Some(callback) = self.action_callback.as_ref() {
+                (callback.0)(&action);
+            } else {
+                self.actions.push(action);
             }
         }
     }
 
+    fn find_transition(&self) -> Option<Handle<Transition>> {
+        self.transitions
+            .pair_iter()
+            .find_map(|(handle, transition)| {
+                if transition.dest() == self.active_state
+                    || transition.source() != self.active_state
+                {
+                    return None;
+                }
+
+                if !transition
+                    .condition()
+                    .is_satisfied(&self.states[self.active_state])
+                {
+                    return None;
+                }
+
+                if transition.condition() != TransitionCondition::Rule
+                    && transition.rule().is_empty()
+                {
+                    return Some(handle);
+                }
+
+                if let Some(Parameter::Rule(active)) = self.parameters.get(transition.rule()) {
+                    if *active != transition.invert_rule {
+                        return Some(handle);
+                    }
+                }
+
+                None
+            })
+    }
+
+    fn begin_transition(&mut self, handle: Handle<Transition>) {
+        let source = self.transitions[handle].source();
+        let dest = self.transitions[handle].dest();
+
+        self.events.push(Event::StateLeave(source));
+        if self.debug {
+            Log::writeln(
+                MessageKind::Information,
+                format!("Leaving state: {}", self.states[source].name()),
+            );
+        }
+        self.fire_actions(source, StateActionKind::Exit);
+
+        self.events.push(Event::StateEnter(dest));
+        if self.debug {
+            Log::writeln(
+                MessageKind::Information,
+                format!("Entering state: {}", self.states[dest].name()),
+            );
+        }
+        self.fire_actions(dest, StateActionKind::Enter);
+        self.states[dest].elapsed_time = 0.0;
+
+        self.active_state = Handle::NONE;
+
+        self.active_transition = handle;
+        self.events
+            .push(Event::ActiveTransitionChanged(self.active_transition));
+    }
+
+    fn end_transition(&mut self) {
+        let transition = &mut self.transitions[self.active_transition];
+        transition.reset();
+
+        self.active_transition = Handle::NONE;
+        self.events
+            .push(Event::ActiveTransitionChanged(self.active_transition));
+
+        self.active_state = transition.dest();
+        self.events
+            .push(Event::ActiveStateChanged(self.active_state));
+
+        if self.debug {
+            Log::writeln(
+                MessageKind::Information,
+                format!(
+                    "Active state changed: {}",
+                    self.states[self.active_state].name()
+                ),
+            );
+        }
+    }
+
+    /// Evaluates the machine and returns its final pose. Transitions could chain through multiple
+    /// states in a single evaluation (if they are instant), state actions are fired exactly once
+    /// per every state change. Every transition can be taken at most once per evaluation, this
+    /// prevents infinite loops on cycles of instant transitions.
     pub fn evaluate_pose(&mut self, animations: &AnimationContainer, dt: f32) -> &AnimationPose {
         self.final_pose.reset();
 
@@ -906,59 +1250,42 @@ impl Machine {
                 state.update(&self.nodes, &self.parameters, animations, dt);
             }
 
-            if self.active_transition.is_none() {
-                // Find transition.
-                for (handle, transition) in self.transitions.pair_iter_mut() {
-                    if transition.dest() == self.active_state
-                        || transition.source() != self.active_state
-                    {
-                        continue;
-                    }
-                    if let Some(Parameter::Rule(mut active)) =
-                        self.parameters.get(transition.rule()).cloned()
-                    {
-                        if transition.invert_rule {
-                            active = !active;
-                        }
-
-                        if active {
-                            self.events.push(Event::StateLeave(self.active_state));
-                            if self.debug {
-                                Log::writeln(
-                                    MessageKind::Information,
-                                    format!(
-                                        "Leaving state: {}",
-                                        self.states[self.active_state].name()
-                                    ),
-                                );
-                            }
+            let current_state = self.current_state();
+            if let Some(state) = self.states.try_borrow_mut(current_state) {
+                state.elapsed_time += dt;
+            }
 
-                            self.events.push(Event::StateEnter(transition.source()));
-                            if self.debug {
-                                Log::writeln(
-                                    MessageKind::Information,
-                                    format!(
-                                        "Entering state: {}",
-                                        self.states[transition.source()].name()
-                                    ),
-                                );
-                            }
+            let mut transitions_left = self.transitions.alive_count();
+            let mut transition_dt = dt;
+            loop {
+                if self.active_transition.is_none() {
+                    if transitions_left == 0 {
+                        break;
+                    }
+                    transitions_left -= 1;
 
-                            self.active_state = Handle::NONE;
+                    match self.find_transition() {
+                        Some(transition) => self.begin_transition(transition),
+                        None => break,
+                    }
+                }
 
-                            self.active_transition = handle;
-                            self.events
-                                .push(Event::ActiveTransitionChanged(self.active_transition));
+                let transition = &mut self.transitions[self.active_transition];
+                transition.update(transition_dt);
+                // Time is spent by the first transition, chained transitions are taken only if
+                // they are instant.
+                transition_dt = 0.0;
 
-                            break;
-                        }
-                    }
+                if transition.is_done() {
+                    self.end_transition();
+                } else {
+                    break;
                 }
             }
 
             // Double check for active transition because we can have empty machine.
             if self.active_transition.is_some() {
-                let transition = &mut self.transitions[self.active_transition];
+                let transition = &self.transitions[self.active_transition];
 
                 // Blend between source and dest states.
                 if let Some(source_pose) = self.states[transition.source()].pose(&self.nodes) {
@@ -969,30 +1296,6 @@ impl Machine {
                     self.final_pose
                         .blend_with(&dest_pose, transition.blend_factor());
                 }
-
-                transition.update(dt);
-
-                if transition.is_done() {
-                    transition.reset();
-
-                    self.active_transition = Handle::NONE;
-                    self.events
-                        .push(Event::ActiveTransitionChanged(self.active_transition));
-
-                    self.active_state = transition.dest();
-                    self.events
-                        .push(Event::ActiveStateChanged(self.active_state));
-
-                    if self.debug {
-                        Log::writeln(
-                            MessageKind::Information,
-                            format!(
-                                "Active state changed: {}",
-                                self.states[self.active_state].name()
-                            ),
-                        );
-                    }
-                }
             } else {
                 // We must have active state all the time when we do not have any active transition.
                 // Just get pose from active state.
@@ -1005,3 +1308,469 @@ impl Machine {
         &self.final_pose
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{
+            machine::{
+                state::{StateActionDefinition, StateDefinition},
+                transition::TransitionDefinition,
+                ComparisonOperator, Machine, Parameter, ParameterError, PoseNode, State,
+                StateAction, StateActionKind, Transition, TransitionCondition,
+            },
+            Animation, AnimationContainer, KeyFrame, Track,
+        },
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            pool::Handle,
+            visitor::{Visit, Visitor},
+        },
+    };
+    use std::sync::{Arc, Mutex};
+
+    fn make_animation(length: f32) -> Animation {
+        let mut track = Track::new();
+        for time in [0.0, length] {
+            track.add_key_frame(KeyFrame::new(
+                time,
+                Vector3::new(time, 0.0, 0.0),
+                Vector3::new(1.0, 1.0, 1.0),
+                UnitQuaternion::identity(),
+            ));
+        }
+        let mut animation = Animation::default();
+        animation.add_track(track);
+        animation
+    }
+
+    fn make_machine() -> (Machine, AnimationContainer, Handle<State>, Handle<State>) {
+        let mut animations = AnimationContainer::new();
+        let idle = animations.add(make_animation(1.0));
+        let walk = animations.add(make_animation(2.0));
+
+        let mut machine = Machine::new(Handle::NONE);
+        let idle_node = machine.add_node(PoseNode::make_play_animation(idle));
+        let idle_state = machine.add_state(State::new("Idle", idle_node));
+        let walk_node = machine.add_node(PoseNode::make_play_animation(walk));
+        let walk_state = machine.add_state(State::new("Walk", walk_node));
+        machine.add_transition(Transition::new(
+            "IdleToWalk",
+            idle_state,
+            walk_state,
+            0.5,
+            "Walk",
+        ));
+        machine.set_parameter("Walk", Parameter::Rule(true));
+
+        (machine, animations, idle_state, walk_state)
+    }
+
+    #[test]
+    fn test_parameters_dynamic_access() {
+        let mut machine = Machine::new(Handle::NONE);
+        machine
+            .set_parameter("Rule", Parameter::Rule(false))
+            .set_parameter("Weight", Parameter::Weight(0.25))
+            .set_parameter("Index", Parameter::Index(1));
+        machine.take_changed_parameters();
+
+        let mut parameters = machine.parameters().collect::<Vec<_>>();
+        parameters.sort_by(|a, b| a.0.cmp(b.0));
+        assert_eq!(
+            parameters,
+            vec![
+                ("Index", Parameter::Index(1)),
+                ("Rule", Parameter::Rule(false)),
+                ("Weight", Parameter::Weight(0.25)),
+            ]
+        );
+
+        // Mutate every parameter by its name.
+        let new_values = [
+            ("Index", Parameter::Index(3)),
+            ("Rule", Parameter::Rule(true)),
+            ("Weight", Parameter::Weight(0.75)),
+        ];
+        for (name, value) in new_values {
+            assert!(!machine.is_parameter_changed(name));
+            assert_eq!(machine.set_parameter_dynamic(name, value), Ok(()));
+            assert!(machine.is_parameter_changed(name));
+            assert_eq!(machine.parameter(name), Some(value));
+        }
+
+        let mut changed = machine.take_changed_parameters();
+        changed.sort();
+        assert_eq!(changed, vec!["Index", "Rule", "Weight"]);
+        assert!(machine.take_changed_parameters().is_empty());
+
+        // Same value is not a change.
+        assert_eq!(
+            machine.set_parameter_dynamic("Weight", Parameter::Weight(0.75)),
+            Ok(())
+        );
+        assert!(!machine.is_parameter_changed("Weight"));
+
+        // Kind of a parameter cannot be changed and new parameters cannot be added.
+        assert_eq!(
+            machine.set_parameter_dynamic("Rule", Parameter::Weight(1.0)),
+            Err(ParameterError::KindMismatch {
+                name: "Rule".to_owned(),
+                expected: "Rule".to_owned(),
+                actual: "Weight".to_owned(),
+            })
+        );
+        assert_eq!(
+            machine.set_parameter_dynamic("Missing", Parameter::Index(0)),
+            Err(ParameterError::NotFound("Missing".to_owned()))
+        );
+        assert_eq!(machine.parameter("Rule"), Some(Parameter::Rule(true)));
+        assert_eq!(machine.parameter("Missing"), None);
+        assert!(machine.take_changed_parameters().is_empty());
+    }
+
+    #[test]
+    fn test_machine_simulation_is_deterministic() {
+        let (machine, animations, _, _) = make_machine();
+
+        let (a, a_animations) = machine.simulate(&animations, 1.3, 1.0 / 60.0);
+        let (b, b_animations) = machine.simulate(&animations, 1.3, 1.0 / 60.0);
+
+        assert_eq!(a.active_state(), b.active_state());
+        assert_eq!(a.active_transition(), b.active_transition());
+        for ((_, a), (_, b)) in a_animations.pair_iter().zip(b_animations.pair_iter()) {
+            assert_eq!(a.get_time_position(), b.get_time_position());
+        }
+    }
+
+    #[test]
+    fn test_machine_simulation_transitions() {
+        let (machine, animations, idle, walk) = make_machine();
+
+        assert_eq!(machine.active_state(), idle);
+
+        // Transition is in progress.
+        let (halfway, _) = machine.simulate(&animations, 0.25, 0.05);
+        assert!(halfway.active_transition().is_some());
+
+        // Transition is done.
+        let (done, _) = machine.simulate(&animations, 1.0, 0.05);
+        assert!(done.active_transition().is_none());
+        assert_eq!(done.active_state(), walk);
+
+        // Source is untouched.
+        assert_eq!(machine.active_state(), idle);
+        assert!(machine.active_transition().is_none());
+    }
+
+    #[test]
+    fn test_machine_simulation_can_be_continued() {
+        let (machine, animations, _, _) = make_machine();
+
+        let (direct, direct_animations) = machine.simulate(&animations, 1.5, 0.1);
+
+        let (first, first_animations) = machine.simulate(&animations, 1.0, 0.1);
+        let (continued, continued_animations) = first.simulate(&first_animations, 0.5, 0.1);
+
+        assert_eq!(direct.active_state(), continued.active_state());
+        for ((_, a), (_, b)) in direct_animations
+            .pair_iter()
+            .zip(continued_animations.pair_iter())
+        {
+            assert!((a.get_time_position() - b.get_time_position()).abs() < 0.0001);
+        }
+    }
+
+    fn pop_actions(machine: &mut Machine) -> Vec<(StateActionKind, String, String)> {
+        std::iter::from_fn(|| machine.pop_action())
+            .map(|a| (a.kind, a.state_name, a.name))
+            .collect()
+    }
+
+    fn action(kind: StateActionKind, state: &str, name: &str) -> (StateActionKind, String, String) {
+        (kind, state.to_owned(), name.to_owned())
+    }
+
+    fn add_state_with_actions(machine: &mut Machine, name: &str) -> Handle<State> {
+        let node = machine.add_node(PoseNode::make_play_animation(Handle::NONE));
+        machine.add_state(
+            State::new(name, node)
+                .with_on_enter(vec![format!("Enter{}", name)])
+                .with_on_exit(vec![format!("Exit{}", name)]),
+        )
+    }
+
+    #[test]
+    fn test_state_actions_are_fired_once_per_transition() {
+        let animations = AnimationContainer::new();
+        let mut machine = Machine::new(Handle::NONE);
+        let idle = add_state_with_actions(&mut machine, "Idle");
+        let walk = add_state_with_actions(&mut machine, "Walk");
+        machine.add_transition(Transition::new("IdleToWalk", idle, walk, 0.5, "Walk"));
+        machine.set_parameter("Walk", Parameter::Rule(true));
+
+        // Entry state does not fire actions.
+        assert!(pop_actions(&mut machine).is_empty());
+
+        for _ in 0..20 {
+            machine.evaluate_pose(&animations, 0.1);
+        }
+
+        assert_eq!(machine.active_state(), walk);
+        assert_eq!(
+            pop_actions(&mut machine),
+            vec![
+                action(StateActionKind::Exit, "Idle", "ExitIdle"),
+                action(StateActionKind::Enter, "Walk", "EnterWalk"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_state_actions_of_chained_transitions() {
+        let animations = AnimationContainer::new();
+        let mut machine = Machine::new(Handle::NONE);
+        let a = add_state_with_actions(&mut machine, "A");
+        let b = add_state_with_actions(&mut machine, "B");
+        let c = add_state_with_actions(&mut machine, "C");
+        machine.add_transition(Transition::new("AToB", a, b, 0.0, "Go"));
+        machine.add_transition(Transition::new("BToC", b, c, 0.0, "Go"));
+        machine.set_parameter("Go", Parameter::Rule(true));
+
+        machine.evaluate_pose(&animations, 0.1);
+
+        assert_eq!(machine.active_state(), c);
+        assert!(machine.active_transition().is_none());
+        assert_eq!(
+            pop_actions(&mut machine),
+            vec![
+                action(StateActionKind::Exit, "A", "ExitA"),
+                action(StateActionKind::Enter, "B", "EnterB"),
+                action(StateActionKind::Exit, "B", "ExitB"),
+                action(StateActionKind::Enter, "C", "EnterC"),
+            ]
+        );
+
+        // Nothing changes, nothing is fired.
+        machine.evaluate_pose(&animations, 0.1);
+        assert!(pop_actions(&mut machine).is_empty());
+    }
+
+    #[test]
+    fn test_state_actions_of_reentrant_transitions() {
+        let animations = AnimationContainer::new();
+        let mut machine = Machine::new(Handle::NONE);
+        let a = add_state_with_actions(&mut machine, "A");
+        let b = add_state_with_actions(&mut machine, "B");
+        machine.add_transition(Transition::new("AToB", a, b, 0.0, "Go"));
+        machine.add_transition(Transition::new("BToA", b, a, 0.0, "Go"));
+        machine.set_parameter("Go", Parameter::Rule(true));
+
+        let round_trip = vec![
+            action(StateActionKind::Exit, "A", "ExitA"),
+            action(StateActionKind::Enter, "B", "EnterB"),
+            action(StateActionKind::Exit, "B", "ExitB"),
+            action(StateActionKind::Enter, "A", "EnterA"),
+        ];
+
+        // Every transition of the cycle is taken once per evaluation.
+        for _ in 0..3 {
+            machine.evaluate_pose(&animations, 0.1);
+            assert_eq!(machine.active_state(), a);
+            assert_eq!(pop_actions(&mut machine), round_trip);
+        }
+    }
+
+    #[test]
+    fn test_state_actions_of_reentrant_timed_transitions() {
+        let animations = AnimationContainer::new();
+        let mut machine = Machine::new(Handle::NONE);
+        let a = add_state_with_actions(&mut machine, "A");
+        let b = add_state_with_actions(&mut machine, "B");
+        machine.add_transition(Transition::new("AToB", a, b, 0.25, "Go"));
+        let mut back = Transition::new("BToA", b, a, 0.25, "Go");
+        back.set_invert_rule(true);
+        machine.add_transition(back);
+
+        machine.set_parameter("Go", Parameter::Rule(true));
+        for _ in 0..10 {
+            machine.evaluate_pose(&animations, 0.1);
+        }
+        assert_eq!(machine.active_state(), b);
+
+        machine.set_parameter("Go", Parameter::Rule(false));
+        for _ in 0..10 {
+            machine.evaluate_pose(&animations, 0.1);
+        }
+        assert_eq!(machine.active_state(), a);
+
+        assert_eq!(
+            pop_actions(&mut machine),
+            vec![
+                action(StateActionKind::Exit, "A", "ExitA"),
+                action(StateActionKind::Enter, "B", "EnterB"),
+                action(StateActionKind::Exit, "B", "ExitB"),
+                action(StateActionKind::Enter, "A", "EnterA"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_state_action_callback() {
+        let animations = AnimationContainer::new();
+        let mut machine = Machine::new(Handle::NONE);
+        let a = add_state_with_actions(&mut machine, "A");
+        let b = add_state_with_actions(&mut machine, "B");
+        machine.add_transition(Transition::new("AToB", a, b, 0.0, "Go"));
+        machine.set_parameter("Go", Parameter::Rule(true));
+
+        let fired = Arc::new(Mutex::new(Vec::<StateAction>::new()));
+        let fired_clone = fired.clone();
+        machine.set_action_callback(move |action| fired_clone.lock().unwrap().push(action.clone()));
+
+        machine.evaluate_pose(&animations, 0.1);
+
+        // Actions go to the callback only.
+        assert!(pop_actions(&mut machine).is_empty());
+        let fired = fired.lock().unwrap();
+        assert_eq!(fired.len(), 2);
+        assert_eq!(fired[0].state, a);
+        assert_eq!(fired[1].state, b);
+    }
+
+    #[test]
+    fn test_state_definition_actions_serialization() {
+        let mut definition = StateDefinition {
+            name: "Land".to_owned(),
+            on_enter: vec![StateActionDefinition {
+                name: "Dust".to_owned(),
+            }],
+            on_exit: vec![StateActionDefinition {
+                name: "StopDust".to_owned(),
+            }],
+            ..Default::default()
+        };
+
+        let mut visitor = Visitor::new();
+        definition.visit("State", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut loaded = StateDefinition::default();
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        loaded.visit("State", &mut visitor).unwrap();
+
+        assert_eq!(loaded.on_enter, definition.on_enter);
+        assert_eq!(loaded.on_exit, definition.on_exit);
+        assert_eq!(loaded.on_enter_names(), vec!["Dust".to_owned()]);
+    }
+
+    fn make_attack_machine(
+        condition: TransitionCondition,
+    ) -> (Machine, AnimationContainer, Handle<State>, Handle<State>) {
+        let mut animations = AnimationContainer::new();
+        let animation = animations.add(make_animation(1.0));
+
+        let mut machine = Machine::new(Handle::NONE);
+        let attack_node = machine.add_node(PoseNode::make_play_animation(animation));
+        let attack = machine.add_state(State::new("Attack", attack_node));
+        let idle_node = machine.add_node(PoseNode::make_play_animation(Handle::NONE));
+        let idle = machine.add_state(State::new("Idle", idle_node));
+        machine.add_transition(
+            Transition::new("AttackToIdle", attack, idle, 0.0, "").with_condition(condition),
+        );
+
+        (machine, animations, attack, idle)
+    }
+
+    fn step(machine: &mut Machine, animations: &mut AnimationContainer, dt: f32) {
+        animations.update_animations(dt);
+        machine.evaluate_pose(animations, dt);
+    }
+
+    #[test]
+    fn test_state_position_condition_at_loop_boundary() {
+        let (mut machine, mut animations, attack, idle) =
+            make_attack_machine(TransitionCondition::StatePosition {
+                operator: ComparisonOperator::GreaterOrEqual,
+                value: 0.8,
+            });
+
+        step(&mut machine, &mut animations, 0.35);
+        step(&mut machine, &mut animations, 0.35);
+        assert_eq!(machine.active_state(), attack);
+        assert!((machine.state_position() - 0.7).abs() < 0.0001);
+
+        // The animation jumps from 0.7 over the end of the loop to 0.05, the trigger is not missed.
+        step(&mut machine, &mut animations, 0.35);
+        assert_eq!(machine.active_state(), idle);
+        assert!((machine.get_state(attack).position() - 0.05).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_state_position_condition_ignores_rewind() {
+        let (mut machine, mut animations, attack, _) =
+            make_attack_machine(TransitionCondition::StatePosition {
+                operator: ComparisonOperator::GreaterOrEqual,
+                value: 0.8,
+            });
+
+        step(&mut machine, &mut animations, 0.3);
+        step(&mut machine, &mut animations, 0.3);
+        for animation in animations.iter_mut() {
+            animation.rewind();
+        }
+        step(&mut machine, &mut animations, 0.1);
+
+        assert_eq!(machine.active_state(), attack);
+        assert!((machine.state_position() - 0.1).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_state_time_condition() {
+        let (mut machine, mut animations, attack, idle) =
+            make_attack_machine(TransitionCondition::StateTime {
+                operator: ComparisonOperator::Greater,
+                value: 0.5,
+            });
+        // A non-empty rule must be satisfied too.
+        machine.transitions.iter_mut().next().unwrap().rule = "Go".to_owned();
+        machine.set_parameter("Go", Parameter::Rule(false));
+
+        for _ in 0..10 {
+            step(&mut machine, &mut animations, 0.1);
+        }
+        assert_eq!(machine.active_state(), attack);
+        assert!((machine.state_time() - 1.0).abs() < 0.0001);
+
+        machine.set_parameter("Go", Parameter::Rule(true));
+        step(&mut machine, &mut animations, 0.1);
+        assert_eq!(machine.active_state(), idle);
+        // Time is counted from the moment the machine entered the state.
+        assert_eq!(machine.state_time(), 0.0);
+        step(&mut machine, &mut animations, 0.1);
+        assert!((machine.state_time() - 0.1).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_transition_condition_serialization() {
+        let condition = TransitionCondition::StatePosition {
+            operator: ComparisonOperator::LessOrEqual,
+            value: 0.25,
+        };
+        let mut definition = TransitionDefinition {
+            name: "AttackToIdle".to_owned(),
+            condition,
+            ..Default::default()
+        };
+
+        let mut visitor = Visitor::new();
+        definition.visit("Transition", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut loaded = TransitionDefinition::default();
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        loaded.visit("Transition", &mut visitor).unwrap();
+
+        assert_eq!(loaded.condition, condition);
+    }
+}
@@ -57,6 +57,20 @@ impl BlendPose {
             pose_source,
         }
     }
+
+    /// Returns actual weight of the pose, weights of missing parameters are zero.
+    pub fn evaluate_weight(&self, params: &ParameterContainer) -> f32 {
+        match self.weight {
+            PoseWeight::Constant(value) => value,
+            PoseWeight::Parameter(ref param_id) => {
+                if let Some(Parameter::Weight(weight)) = params.get(param_id) {
+                    *weight
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
 }
 
 /// Animation blend node. It takes multiple input poses and mixes them together into
@@ -137,16 +151,7 @@ impl EvaluatePose for BlendAnimations {
     ) -> Ref<AnimationPose> {
         self.output_pose.borrow_mut().reset();
         for blend_pose in self.pose_sources.iter() {
-            let weight = match blend_pose.weight {
-                PoseWeight::Constant(value) => value,
-                PoseWeight::Parameter(ref param_id) => {
-                    if let Some(Parameter::Weight(weight)) = params.get(param_id) {
-                        *weight
-                    } else {
-                        0.0
-                    }
-                }
-            };
+            let weight = blend_pose.evaluate_weight(params);
 
             if let Some(pose_source) = nodes
                 .try_borrow(blend_pose.pose_source)
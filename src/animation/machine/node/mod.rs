@@ -8,7 +8,7 @@ use crate::{
                 play::{PlayAnimation, PlayAnimationDefinition},
             },
             state::StateDefinition,
-            BlendAnimationsByIndex, BlendPose, IndexedBlendInput, ParameterContainer,
+            BlendAnimationsByIndex, BlendPose, IndexedBlendInput, Parameter, ParameterContainer,
         },
         Animation, AnimationContainer, AnimationPose,
     },
@@ -21,6 +21,7 @@ use crate::{
 };
 use std::{
     cell::Ref,
+    cmp::Ordering,
     ops::{Deref, DerefMut},
 };
 
@@ -69,6 +70,41 @@ impl PoseNode {
     ) -> Self {
         Self::BlendAnimationsByIndex(BlendAnimationsByIndex::new(index_parameter, inputs))
     }
+
+    /// Returns the animation of a PlayAnimation node that contributes the most to the pose of the
+    /// node. Blend nodes are followed through the input with the largest weight, indexed blend
+    /// nodes are followed through the input selected by the index parameter.
+    pub fn dominant_animation(
+        &self,
+        nodes: &Pool<PoseNode>,
+        params: &ParameterContainer,
+    ) -> Handle<Animation> {
+        let source = match self {
+            PoseNode::PlayAnimation(play_animation) => return play_animation.animation,
+            PoseNode::BlendAnimations(blend_animations) => blend_animations
+                .pose_sources
+                .iter()
+                .max_by(|a, b| {
+                    a.evaluate_weight(params)
+                        .partial_cmp(&b.evaluate_weight(params))
+                        .unwrap_or(Ordering::Equal)
+                })
+                .map(|blend_pose| blend_pose.pose_source),
+            PoseNode::BlendAnimationsByIndex(blend_animations) => {
+                match params.get(&blend_animations.index_parameter) {
+                    Some(&Parameter::Index(index)) => blend_animations
+                        .inputs
+                        .get(index as usize)
+                        .map(|input| input.pose_source),
+                    _ => None,
+                }
+            }
+        };
+
+        source
+            .and_then(|source| nodes.try_borrow(source))
+            .map_or(Handle::NONE, |node| node.dominant_animation(nodes, params))
+    }
 }
 
 macro_rules! static_dispatch {
@@ -8,7 +8,7 @@ use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 /// Machine parameter.  Machine uses various parameters for specific actions. For example
 /// Rule parameter is used to check where transition from a state to state is possible.
 /// See module docs for example.
-#[derive(Copy, Clone, Debug, Inspect, Visit, EnumVariantNames, EnumString, AsRefStr)]
+#[derive(Copy, Clone, Debug, PartialEq, Inspect, Visit, EnumVariantNames, EnumString, AsRefStr)]
 pub enum Parameter {
     /// Weight parameter is used to control blend weight in BlendAnimation node.
     Weight(f32),
@@ -26,6 +26,33 @@ impl Default for Parameter {
     }
 }
 
+impl Parameter {
+    /// Returns `true` if both parameters are of the same kind, values are ignored.
+    pub fn is_same_kind(&self, other: &Parameter) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+/// An error that may occur when a parameter is set by its name, see
+/// [`crate::animation::machine::Machine::set_parameter_dynamic`].
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ParameterError {
+    /// There is no parameter with given name.
+    #[error("There is no parameter with name {0}.")]
+    NotFound(String),
+
+    /// The parameter exists, but the new value has different kind.
+    #[error("Parameter {name} is of {expected} kind, but a value of {actual} kind was given.")]
+    KindMismatch {
+        /// A name of the parameter.
+        name: String,
+        /// A kind of the existing parameter.
+        expected: String,
+        /// A kind of the new value.
+        actual: String,
+    },
+}
+
 /// Specific animation pose weight.
 #[derive(Debug, Visit, Clone, Inspect, EnumVariantNames, EnumString, AsRefStr)]
 pub enum PoseWeight {
@@ -1,7 +1,7 @@
 use crate::{
     animation::{
         machine::{node::PoseNodeDefinition, EvaluatePose, ParameterContainer, PoseNode},
-        AnimationContainer, AnimationPose,
+        Animation, AnimationContainer, AnimationPose,
     },
     core::{
         algebra::Vector2,
@@ -19,6 +19,31 @@ pub struct State {
     pub definition: Handle<StateDefinition>,
     pub(crate) name: String,
     pub(crate) root: Handle<PoseNode>,
+    /// Names of actions that will be fired when the machine enters the state.
+    #[visit(optional)] // Backward compatibility
+    pub(crate) on_enter: Vec<String>,
+    /// Names of actions that will be fired when the machine leaves the state.
+    #[visit(optional)] // Backward compatibility
+    pub(crate) on_exit: Vec<String>,
+    /// Time (in seconds) since the machine entered the state.
+    #[visit(optional)] // Backward compatibility
+    pub(crate) elapsed_time: f32,
+    /// Normalized playback position of the dominant animation, see [`State::position`].
+    #[visit(skip)]
+    pub(crate) position: f32,
+    /// Animation that defined the position in the last update.
+    #[visit(skip)]
+    pub(crate) position_source: Handle<Animation>,
+    /// `true` if the dominant animation has wrapped around in the last update.
+    #[visit(skip)]
+    pub(crate) position_wrapped: bool,
+}
+
+/// A named action of a state, see [`StateDefinition::on_enter`] and [`StateDefinition::on_exit`].
+#[derive(Default, Debug, Visit, Clone, Inspect, PartialEq)]
+pub struct StateActionDefinition {
+    #[inspect(description = "Name of the action, it is passed to scripts as is.")]
+    pub name: String,
 }
 
 #[derive(Default, Debug, Visit, Clone, Inspect)]
@@ -27,6 +52,24 @@ pub struct StateDefinition {
     pub name: String,
     #[inspect(skip)]
     pub root: Handle<PoseNodeDefinition>,
+    /// Actions that will be fired when the machine enters the state.
+    #[inspect(description = "Actions that will be fired when the machine enters the state.")]
+    #[visit(optional)] // Backward compatibility
+    pub on_enter: Vec<StateActionDefinition>,
+    /// Actions that will be fired when the machine leaves the state.
+    #[inspect(description = "Actions that will be fired when the machine leaves the state.")]
+    #[visit(optional)] // Backward compatibility
+    pub on_exit: Vec<StateActionDefinition>,
+}
+
+impl StateDefinition {
+    pub(crate) fn on_enter_names(&self) -> Vec<String> {
+        self.on_enter.iter().map(|a| a.name.clone()).collect()
+    }
+
+    pub(crate) fn on_exit_names(&self) -> Vec<String> {
+        self.on_exit.iter().map(|a| a.name.clone()).collect()
+    }
 }
 
 impl State {
@@ -36,13 +79,55 @@ impl State {
             definition: Default::default(),
             name: name.to_owned(),
             root,
+            on_enter: Default::default(),
+            on_exit: Default::default(),
+            elapsed_time: 0.0,
+            position: 0.0,
+            position_source: Default::default(),
+            position_wrapped: false,
         }
     }
 
+    /// Sets names of actions that will be fired when the machine enters the state.
+    pub fn with_on_enter(mut self, actions: Vec<String>) -> Self {
+        self.on_enter = actions;
+        self
+    }
+
+    /// Sets names of actions that will be fired when the machine leaves the state.
+    pub fn with_on_exit(mut self, actions: Vec<String>) -> Self {
+        self.on_exit = actions;
+        self
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// Returns names of actions that will be fired when the machine enters the state.
+    pub fn on_enter(&self) -> &[String] {
+        &self.on_enter
+    }
+
+    /// Returns names of actions that will be fired when the machine leaves the state.
+    pub fn on_exit(&self) -> &[String] {
+        &self.on_exit
+    }
+
+    /// Returns time (in seconds) since the machine entered the state. The time is counted from the
+    /// start of the transition to the state.
+    pub fn elapsed_time(&self) -> f32 {
+        self.elapsed_time
+    }
+
+    /// Returns normalized playback position (0..1) of the dominant animation of the state. The
+    /// dominant animation is the animation of a PlayAnimation node that contributes the most to
+    /// the pose of the state (see [`PoseNode::dominant_animation`]). Looping animations report
+    /// the position within the current loop.
+    pub fn position(&self) -> f32 {
+        self.position
+    }
+
     pub fn pose<'a>(&self, nodes: &'a Pool<PoseNode>) -> Option<Ref<'a, AnimationPose>> {
         nodes.try_borrow(self.root).map(|root| root.pose())
     }
@@ -56,6 +141,40 @@ impl State {
     ) {
         if let Some(root) = nodes.try_borrow(self.root) {
             root.eval_pose(nodes, params, animations, dt);
+
+            let animation = root.dominant_animation(nodes, params);
+            self.update_position(animation, animations, dt);
+        }
+    }
+
+    fn update_position(
+        &mut self,
+        handle: Handle<Animation>,
+        animations: &AnimationContainer,
+        dt: f32,
+    ) {
+        let prev_position = self.position;
+        let prev_source = std::mem::replace(&mut self.position_source, handle);
+
+        match animations.try_get(handle) {
+            Some(animation) if animation.length() > 0.0 => {
+                self.position = animation.get_time_position() / animation.length();
+
+                // Wrapping is detected by the expected advance of the position, so rewinding an
+                // animation is not treated as a wrap.
+                let advance = dt * animation.get_speed() / animation.length();
+                self.position_wrapped = animation.is_loop()
+                    && prev_source == handle
+                    && if advance >= 0.0 {
+                        self.position < prev_position && prev_position + advance >= 1.0
+                    } else {
+                        self.position > prev_position && prev_position + advance <= 0.0
+                    };
+            }
+            _ => {
+                self.position = 0.0;
+                self.position_wrapped = false;
+            }
         }
     }
 }
@@ -6,6 +6,95 @@ use crate::{
         visitor::prelude::*,
     },
 };
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Comparison operator of built-in transition conditions, see [`TransitionCondition`].
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Visit, Inspect, EnumVariantNames, EnumString, AsRefStr,
+)]
+#[repr(u32)]
+pub enum ComparisonOperator {
+    /// Actual value is less than the value of the condition.
+    Less,
+    /// Actual value is less than or equal to the value of the condition.
+    LessOrEqual,
+    /// Actual value is greater than the value of the condition.
+    Greater,
+    /// Actual value is greater than or equal to the value of the condition.
+    GreaterOrEqual,
+}
+
+impl Default for ComparisonOperator {
+    fn default() -> Self {
+        Self::GreaterOrEqual
+    }
+}
+
+impl ComparisonOperator {
+    /// Compares actual value with the given one.
+    pub fn compare(self, actual: f32, value: f32) -> bool {
+        match self {
+            ComparisonOperator::Less => actual < value,
+            ComparisonOperator::LessOrEqual => actual <= value,
+            ComparisonOperator::Greater => actual > value,
+            ComparisonOperator::GreaterOrEqual => actual >= value,
+        }
+    }
+}
+
+/// Built-in condition of a transition, it is based on the state of the source state and does not
+/// require any parameters to be set from scripts. For example, `StatePosition` with
+/// `GreaterOrEqual` operator and `0.8` value could be used to leave an attack state when its
+/// animation is 80% done.
+///
+/// Built-in conditions are checked together with the Rule parameter of a transition: the
+/// transition is taken when both are satisfied. If the rule of a transition with built-in
+/// condition is empty, only the built-in condition is checked.
+#[derive(Copy, Clone, Debug, PartialEq, Visit, Inspect, EnumVariantNames, EnumString, AsRefStr)]
+pub enum TransitionCondition {
+    /// Only the Rule parameter of the transition is checked.
+    Rule,
+    /// Compares the time (in seconds) since the machine entered the source state with the value.
+    StateTime {
+        /// Operator that is used to compare the time of the state with the value.
+        operator: ComparisonOperator,
+        /// Time in seconds.
+        #[inspect(min_value = 0.0, step = 0.1)]
+        value: f32,
+    },
+    /// Compares normalized playback position (0..1) of the source state with the value. See
+    /// [`State::position`] for more info.
+    StatePosition {
+        /// Operator that is used to compare the position of the state with the value.
+        operator: ComparisonOperator,
+        /// Normalized position in `0..1` range.
+        #[inspect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+        value: f32,
+    },
+}
+
+impl Default for TransitionCondition {
+    fn default() -> Self {
+        Self::Rule
+    }
+}
+
+impl TransitionCondition {
+    /// Returns `true` if the condition is satisfied for the given source state.
+    pub fn is_satisfied(&self, state: &State) -> bool {
+        match *self {
+            TransitionCondition::Rule => true,
+            TransitionCondition::StateTime { operator, value } => {
+                operator.compare(state.elapsed_time(), value)
+            }
+            TransitionCondition::StatePosition { operator, value } => {
+                // A looping animation passes through every position of the loop when it wraps,
+                // so a threshold that was jumped over in a single update is not missed.
+                state.position_wrapped || operator.compare(state.position(), value)
+            }
+        }
+    }
+}
 
 /// Transition is a connection between two states with a rule that defines possibility
 /// of actual transition with blending.
@@ -24,6 +113,9 @@ pub struct Transition {
     /// have a pair of transitions that depend on a single Rule parameter, but have different
     /// directions (A -> B, B -> A).
     pub(crate) invert_rule: bool,
+    /// Built-in condition which is checked together with the rule.
+    #[visit(optional)] // Backward compatibility
+    pub(crate) condition: TransitionCondition,
     /// 0 - evaluates `src` pose, 1 - `dest`, 0..1 - blends `src` and `dest`
     pub(crate) blend_factor: f32,
 }
@@ -55,6 +147,14 @@ pub struct TransitionDefinition {
       but have different directions (A -> B, B -> A)."
     )]
     pub invert_rule: bool,
+    /// Built-in condition which is checked together with the rule.
+    #[inspect(
+        description = "Built-in condition based on the time or the playback position \
+        of the source state. It is checked together with the rule, an empty rule is ignored \
+        if the condition is set."
+    )]
+    #[visit(optional)] // Backward compatibility
+    pub condition: TransitionCondition,
 }
 
 impl Transition {
@@ -74,10 +174,17 @@ impl Transition {
             dest,
             rule: rule.to_owned(),
             invert_rule: false,
+            condition: Default::default(),
             blend_factor: 0.0,
         }
     }
 
+    /// Sets built-in condition of the transition, see [`TransitionCondition`] for more info.
+    pub fn with_condition(mut self, condition: TransitionCondition) -> Self {
+        self.condition = condition;
+        self
+    }
+
     #[inline]
     pub fn name(&self) -> &str {
         self.name.as_str()
@@ -123,6 +230,16 @@ impl Transition {
         self.invert_rule
     }
 
+    #[inline]
+    pub fn condition(&self) -> TransitionCondition {
+        self.condition
+    }
+
+    #[inline]
+    pub fn set_condition(&mut self, condition: TransitionCondition) {
+        self.condition = condition;
+    }
+
     pub(super) fn reset(&mut self) {
         self.elapsed_time = 0.0;
         self.blend_factor = 0.0;
@@ -133,6 +250,11 @@ impl Transition {
         if self.elapsed_time > self.transition_time {
             self.elapsed_time = self.transition_time;
         }
-        self.blend_factor = self.elapsed_time / self.transition_time;
+        // Instant transitions are done right away.
+        self.blend_factor = if self.transition_time > 0.0 {
+            self.elapsed_time / self.transition_time
+        } else {
+            1.0
+        };
     }
 }
@@ -1,7 +1,10 @@
+pub mod blender;
 pub mod machine;
+pub mod retarget;
 
 use crate::engine::resource_manager::ResourceManager;
 use crate::{
+    animation::{blender::AnimationBlender, retarget::Retargeting},
     asset::ResourceState,
     core::{
         algebra::{UnitQuaternion, Vector3},
@@ -61,16 +64,38 @@ pub struct PoseEvaluationFlags {
     pub ignore_scale: bool,
 }
 
-#[derive(Debug, Visit)]
+#[derive(Debug)]
 pub struct Track {
     // Frames are not serialized, because it makes no sense to store them in save file,
-    // they will be taken from resource on Resolve stage.
-    #[visit(skip)]
+    // they will be taken from resource on Resolve stage. Embedded frames are the exception,
+    // see `Track::set_embedded_key_frames`.
     frames: Vec<KeyFrame>,
     enabled: bool,
     max_time: f32,
     node: Handle<Node>,
     flags: PoseEvaluationFlags,
+    weight: f32,
+    embedded_frames: bool,
+}
+
+impl Visit for Track {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.enabled.visit("Enabled", &mut region)?;
+        self.max_time.visit("MaxTime", &mut region)?;
+        self.node.visit("Node", &mut region)?;
+        self.flags.visit("Flags", &mut region)?;
+        // Backward compatibility.
+        let _ = self.weight.visit("Weight", &mut region);
+        let _ = self.embedded_frames.visit("EmbeddedFrames", &mut region);
+
+        if self.embedded_frames {
+            self.frames.visit("Frames", &mut region)?;
+        }
+
+        Ok(())
+    }
 }
 
 impl Clone for Track {
@@ -81,6 +106,8 @@ impl Clone for Track {
             max_time: self.max_time,
             node: self.node,
             flags: self.flags,
+            weight: self.weight,
+            embedded_frames: self.embedded_frames,
         }
     }
 }
@@ -93,6 +120,8 @@ impl Default for Track {
             max_time: 0.0,
             node: Default::default(),
             flags: Default::default(),
+            weight: 1.0,
+            embedded_frames: false,
         }
     }
 }
@@ -137,6 +166,18 @@ impl Track {
         self.enabled
     }
 
+    /// Sets weight of the track, it is a multiplier for the weight of the animation, applied only to
+    /// the node of the track when the pose of the animation is blended with other poses. A pose
+    /// of a track with zero weight contributes nothing, the same as a pose of a disabled track.
+    pub fn set_weight(&mut self, weight: f32) {
+        self.weight = weight.max(0.0);
+    }
+
+    /// Returns weight of the track. See [`Self::set_weight`] for more info.
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+
     pub fn set_key_frames(&mut self, key_frames: &[KeyFrame]) {
         self.frames = key_frames.to_vec();
         self.max_time = 0.0;
@@ -152,6 +193,21 @@ impl Track {
         &self.frames
     }
 
+    /// Defines whether key frames of the track are stored together with the track or not. By
+    /// default key frames are taken from the animation resource when a scene is loaded, so
+    /// any changes of the key frames (for example, keys pasted in the editor) are lost. Embedded
+    /// key frames are saved with the track and they're never overwritten by key frames from the
+    /// resource.
+    pub fn set_embedded_key_frames(&mut self, embedded: bool) {
+        self.embedded_frames = embedded;
+    }
+
+    /// Returns `true` if key frames of the track are stored together with the track. See
+    /// [`Self::set_embedded_key_frames`] for more info.
+    pub fn has_embedded_key_frames(&self) -> bool {
+        self.embedded_frames
+    }
+
     pub fn get_local_pose(&self, mut time: f32) -> Option<LocalPose> {
         if self.frames.is_empty() {
             return None;
@@ -163,6 +219,7 @@ impl Track {
                 position: k.position,
                 scale: k.scale,
                 rotation: k.rotation,
+                weight: self.weight,
             });
         }
 
@@ -182,6 +239,7 @@ impl Track {
                 position: k.position,
                 scale: k.scale,
                 rotation: k.rotation,
+                weight: self.weight,
             })
         } else {
             let left = &self.frames[right_index - 1];
@@ -205,6 +263,7 @@ impl Track {
                 } else {
                     left.rotation.nlerp(&right.rotation, interpolator)
                 },
+                weight: self.weight,
             })
         }
     }
@@ -276,12 +335,18 @@ pub struct Animation {
     speed: f32,
     looped: bool,
     enabled: bool,
-    pub(in crate) resource: Option<Model>,
+    #[visit(optional)] // Backward compatibility
+    weight: f32,
+    pub(crate) resource: Option<Model>,
     #[visit(skip)]
     pose: AnimationPose,
     signals: Vec<AnimationSignal>,
     #[visit(skip)]
     events: VecDeque<AnimationEvent>,
+    // Parameters of retargeting, if the animation was retargeted from another skeleton. They're
+    // used to restore key frames from the resource.
+    #[visit(optional)] // Backward compatibility
+    retargeting: Option<Retargeting>,
 }
 
 /// Snapshot of scene node local transform state.
@@ -291,6 +356,9 @@ pub struct LocalPose {
     position: Vector3<f32>,
     scale: Vector3<f32>,
     rotation: UnitQuaternion<f32>,
+    // Accumulated weight of the poses blended into this pose, initially it is the weight of the
+    // track.
+    weight: f32,
 }
 
 impl Default for LocalPose {
@@ -300,6 +368,7 @@ impl Default for LocalPose {
             position: Vector3::default(),
             scale: Vector3::new(1.0, 1.0, 1.0),
             rotation: UnitQuaternion::identity(),
+            weight: 1.0,
         }
     }
 }
@@ -307,17 +376,24 @@ impl Default for LocalPose {
 impl LocalPose {
     fn weighted_clone(&self, weight: f32) -> Self {
         Self {
-            node: self.node,
-            position: self.position.scale(weight),
-            rotation: self.rotation.nlerp(&self.rotation, weight),
-            scale: self.scale.scale(weight),
+            weight: self.weight * weight,
+            ..self.clone()
         }
     }
 
+    /// Blends the pose with the other pose. Weight of the other pose is multiplied by the given
+    /// weight, the result is the weighted average of all the poses blended so far, so a node
+    /// that is animated only by some of the blended poses is fully controlled by them.
     pub fn blend_with(&mut self, other: &LocalPose, weight: f32) {
-        self.position += other.position.scale(weight);
-        self.rotation = self.rotation.nlerp(&other.rotation, weight);
-        self.scale += other.scale.scale(weight);
+        let weight = other.weight * weight;
+        let total_weight = self.weight + weight;
+        if total_weight > 0.0 {
+            let t = weight / total_weight;
+            self.position = self.position.lerp(&other.position, t);
+            self.rotation = self.rotation.nlerp(&other.rotation, t);
+            self.scale = self.scale.lerp(&other.scale, t);
+        }
+        self.weight = total_weight;
     }
 
     pub fn position(&self) -> Vector3<f32> {
@@ -331,6 +407,11 @@ impl LocalPose {
     pub fn rotation(&self) -> UnitQuaternion<f32> {
         self.rotation
     }
+
+    /// Returns accumulated weight of the pose, see [`Track::set_weight`].
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
 }
 
 #[derive(Default, Debug, Clone)]
@@ -351,13 +432,18 @@ impl AnimationPose {
             if let Some(current_pose) = self.local_poses.get_mut(handle) {
                 current_pose.blend_with(other_pose, weight);
             } else {
-                // There are no corresponding local pose, do fake blend between identity
-                // pose and other.
+                // There are no corresponding local pose, the node is controlled only by the
+                // other pose.
                 self.add_local_pose(other_pose.weighted_clone(weight));
             }
         }
     }
 
+    /// Returns local pose of the given node, if the pose affects the node.
+    pub fn local_pose(&self, node: Handle<Node>) -> Option<&LocalPose> {
+        self.local_poses.get(&node)
+    }
+
     fn add_local_pose(&mut self, local_pose: LocalPose) {
         self.local_poses.insert(local_pose.node, local_pose);
     }
@@ -405,10 +491,12 @@ impl Clone for Animation {
             time_position: self.time_position,
             looped: self.looped,
             enabled: self.enabled,
+            weight: self.weight,
             resource: self.resource.clone(),
             pose: Default::default(),
             signals: self.signals.clone(),
             events: Default::default(),
+            retargeting: self.retargeting.clone(),
         }
     }
 }
@@ -428,6 +516,20 @@ impl Animation {
         &self.tracks
     }
 
+    /// Replaces all tracks of the animation and returns the old ones. The animation is extended
+    /// to the longest of the new tracks, use [`Self::set_length`] to change its length precisely.
+    pub fn set_tracks(&mut self, tracks: Vec<Track>) -> Vec<Track> {
+        let old_tracks = std::mem::replace(&mut self.tracks, tracks);
+
+        for track in self.tracks.iter() {
+            if track.max_time > self.length {
+                self.length = track.max_time;
+            }
+        }
+
+        old_tracks
+    }
+
     pub fn set_time_position(&mut self, time: f32) -> &mut Self {
         if self.looped {
             self.time_position = wrapf(time, 0.0, self.length);
@@ -445,6 +547,14 @@ impl Animation {
         self.length
     }
 
+    /// Sets new length of the animation. Key frames after the end of the animation are kept, but
+    /// they're never reached during playback. Time position is clamped (or wrapped for looped
+    /// animations) to the new length.
+    pub fn set_length(&mut self, length: f32) -> &mut Self {
+        self.length = length.max(0.0);
+        self.set_time_position(self.time_position)
+    }
+
     fn tick(&mut self, dt: f32) {
         self.update_pose();
 
@@ -503,6 +613,18 @@ impl Animation {
         self.enabled
     }
 
+    /// Sets weight of the animation, it is used by [`AnimationBlender`] to mix poses of multiple
+    /// animations. Weight of the animation is ignored if the animation is not blended.
+    pub fn set_weight(&mut self, weight: f32) -> &mut Self {
+        self.weight = weight.max(0.0);
+        self
+    }
+
+    /// Returns weight of the animation. See [`Self::set_weight`] for more info.
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+
     pub fn set_speed(&mut self, speed: f32) -> &mut Self {
         self.speed = speed;
         self
@@ -516,6 +638,12 @@ impl Animation {
         self.resource.clone()
     }
 
+    /// Returns parameters of retargeting, if the animation was retargeted from another skeleton
+    /// using [`retarget::retarget_animation`].
+    pub fn retargeting(&self) -> Option<&Retargeting> {
+        self.retargeting.as_ref()
+    }
+
     pub fn signals(&self) -> &[AnimationSignal] {
         &self.signals
     }
@@ -573,6 +701,59 @@ impl Animation {
         }
     }
 
+    /// Enables or disables tracks of every node with the given name.
+    pub fn set_named_track_enabled(&mut self, name: &str, enabled: bool, graph: &Graph) {
+        for track in self.tracks.iter_mut() {
+            if graph
+                .try_get(track.node)
+                .map_or(false, |node| node.name() == name)
+            {
+                track.enabled = enabled;
+            }
+        }
+    }
+
+    /// Disables tracks of the given node and all of its descendants. It is a shortcut for
+    /// [`Self::set_tracks_enabled_from`].
+    pub fn disable_tracks_below(&mut self, root_bone: Handle<Node>, graph: &Graph) {
+        self.set_tracks_enabled_from(root_bone, false, graph)
+    }
+
+    /// Sets weight of the track of the given node. See [`Track::set_weight`] for more info.
+    pub fn set_node_track_weight(&mut self, handle: Handle<Node>, weight: f32) {
+        for track in self.tracks.iter_mut() {
+            if track.node == handle {
+                track.set_weight(weight);
+            }
+        }
+    }
+
+    /// Sets weight of tracks of every node with the given name. See [`Track::set_weight`] for
+    /// more info.
+    pub fn set_named_track_weight(&mut self, name: &str, weight: f32, graph: &Graph) {
+        for track in self.tracks.iter_mut() {
+            if graph
+                .try_get(track.node)
+                .map_or(false, |node| node.name() == name)
+            {
+                track.set_weight(weight);
+            }
+        }
+    }
+
+    /// Sets weight of tracks of the given node and all of its descendants. For example, an aim
+    /// animation could affect upper body only partially, when it is blended with a run animation.
+    /// See [`Track::set_weight`] for more info.
+    pub fn set_weight_for_subtree(&mut self, root: Handle<Node>, weight: f32, graph: &Graph) {
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            self.set_node_track_weight(node, weight);
+            if let Some(node) = graph.try_get(node) {
+                stack.extend_from_slice(node.children());
+            }
+        }
+    }
+
     pub fn track_of(&self, handle: Handle<Node>) -> Option<&Track> {
         for track in self.tracks.iter() {
             if track.node == handle {
@@ -591,14 +772,14 @@ impl Animation {
         None
     }
 
-    pub(in crate) fn restore_resources(&mut self, resource_manager: ResourceManager) {
+    pub(crate) fn restore_resources(&mut self, resource_manager: ResourceManager) {
         if let Some(resource) = self.resource.as_mut() {
             let new_resource = resource_manager.request_model(resource.state().path());
             *resource = new_resource;
         }
     }
 
-    pub(in crate) fn resolve(&mut self, graph: &Graph) {
+    pub(crate) fn resolve(&mut self, graph: &Graph) {
         // Copy key frames from resource for each animation. This is needed because we
         // do not store key frames in save file, but just keep reference to resource
         // from which key frames should be taken on load.
@@ -608,7 +789,12 @@ impl Animation {
                 ResourceState::Ok(ref data) => {
                     // TODO: Here we assume that resource contains only *one* animation.
                     if let Some(ref_animation) = data.get_scene().animations.pool.at(0) {
-                        for track in self.get_tracks_mut() {
+                        for track in self.tracks.iter_mut() {
+                            // Embedded key frames are saved with the track.
+                            if track.has_embedded_key_frames() {
+                                continue;
+                            }
+
                             // This may panic if animation has track that refers to a deleted node,
                             // it can happen if you deleted a node but forgot to remove animation
                             // that uses this node.
@@ -624,13 +810,30 @@ impl Animation {
                             // you did animation retargeting from animation resource to your character
                             // instantiated model, which is essentially copies key frames to new
                             // animation targeted to character instance.
+                            //
+                            // Retargeted animations are bound to nodes with different names, the
+                            // key frames must be converted for them too.
+                            let source_name = match self.retargeting.as_ref() {
+                                Some(retargeting) => {
+                                    retargeting.mapping.source_of(track_node.name())
+                                }
+                                None => Some(track_node.name()),
+                            };
                             let mut found = false;
                             for ref_track in ref_animation.get_tracks().iter() {
-                                if track_node.name()
-                                    == data.get_scene().graph[ref_track.get_node()].name()
-                                {
-                                    track.set_key_frames(ref_track.get_key_frames());
-                                    found = true;
+                                let ref_name = data.get_scene().graph[ref_track.get_node()].name();
+                                if source_name == Some(ref_name) {
+                                    let key_frames = match self.retargeting.as_ref() {
+                                        Some(retargeting) => retargeting.retarget_key_frames(
+                                            ref_name,
+                                            ref_track.get_key_frames(),
+                                        ),
+                                        None => Some(ref_track.get_key_frames().to_vec()),
+                                    };
+                                    if let Some(key_frames) = key_frames {
+                                        track.set_key_frames(&key_frames);
+                                        found = true;
+                                    }
                                     break;
                                 }
                             }
@@ -667,7 +870,9 @@ impl Animation {
     fn update_pose(&mut self) {
         self.pose.reset();
         for track in self.tracks.iter() {
-            if track.is_enabled() {
+            // Disabled track contributes nothing, so other animations blended with this one
+            // will fully control the node of the track.
+            if track.is_enabled() && track.weight > 0.0 {
                 if let Some(local_pose) = track.get_local_pose(self.time_position) {
                     self.pose.add_local_pose(local_pose);
                 }
@@ -689,10 +894,12 @@ impl Default for Animation {
             time_position: 0.0,
             enabled: true,
             looped: true,
+            weight: 1.0,
             resource: Default::default(),
             pose: Default::default(),
             signals: Default::default(),
             events: Default::default(),
+            retargeting: None,
         }
     }
 }
@@ -700,6 +907,7 @@ impl Default for Animation {
 #[derive(Debug, Clone)]
 pub struct AnimationContainer {
     pool: Pool<Animation>,
+    blender: AnimationBlender,
 }
 
 impl Default for AnimationContainer {
@@ -709,8 +917,11 @@ impl Default for AnimationContainer {
 }
 
 impl AnimationContainer {
-    pub(in crate) fn new() -> Self {
-        Self { pool: Pool::new() }
+    pub(crate) fn new() -> Self {
+        Self {
+            pool: Pool::new(),
+            blender: Default::default(),
+        }
     }
 
     #[inline]
@@ -765,7 +976,8 @@ impl AnimationContainer {
 
     #[inline]
     pub fn clear(&mut self) {
-        self.pool.clear()
+        self.pool.clear();
+        self.blender.clear();
     }
 
     #[inline]
@@ -814,6 +1026,67 @@ impl AnimationContainer {
         for animation in self.pool.iter_mut().filter(|anim| anim.enabled) {
             animation.tick(dt);
         }
+
+        self.blender.update(&mut self.pool, dt);
+    }
+
+    /// Same as [`Self::update_animations`], but animations that animate disabled scene nodes only
+    /// are paused, so they can be resumed from the same position when the nodes are enabled again.
+    pub(crate) fn update_animations_in_graph(&mut self, graph: &Graph, dt: f32) {
+        for animation in self.pool.iter_mut().filter(|anim| anim.enabled) {
+            let tracks = animation.get_tracks();
+            let paused = !tracks.is_empty()
+                && tracks.iter().all(|track| {
+                    graph
+                        .try_get(track.get_node())
+                        .map_or(false, |node| !node.is_globally_enabled())
+                });
+
+            if !paused {
+                animation.tick(dt);
+            }
+        }
+
+        self.blender.update(&mut self.pool, dt);
+    }
+
+    /// Smoothly changes weight of `from` animation to zero and weight of `to` animation to one
+    /// during the given amount of seconds. `to` animation is rewound and enabled, unless it is
+    /// already blended (for example, it is still fading out), in this case it continues from its
+    /// current weight and time position. `from` animation is disabled when it is fully faded out.
+    /// See [`AnimationBlender`] docs for more info.
+    pub fn crossfade(&mut self, from: Handle<Animation>, to: Handle<Animation>, duration: f32) {
+        self.blender.crossfade(&mut self.pool, from, to, duration)
+    }
+
+    /// Plays `first` animation (if it is not already playing or queued) and crossfades to `next`
+    /// animation when there are `fade_duration` seconds left until the end of `first` animation.
+    /// Calls could be chained: `play_then(a, b, ..)` and then `play_then(b, c, ..)` will play
+    /// `a`, `b` and `c` one after another.
+    pub fn play_then(
+        &mut self,
+        first: Handle<Animation>,
+        next: Handle<Animation>,
+        fade_duration: f32,
+    ) {
+        self.blender
+            .play_then(&mut self.pool, first, next, fade_duration)
+    }
+
+    /// Returns a reference to the animation blender of the container.
+    pub fn blender(&self) -> &AnimationBlender {
+        &self.blender
+    }
+
+    /// Returns a reference to the animation blender of the container.
+    pub fn blender_mut(&mut self) -> &mut AnimationBlender {
+        &mut self.blender
+    }
+
+    /// Returns weighted mix of poses of all animations controlled by the blender. The pose is
+    /// applied to the scene graph automatically.
+    pub fn blended_pose(&self) -> &AnimationPose {
+        self.blender.pose()
     }
 
     /// Removes queued animation events from every animation in the container.
@@ -859,3 +1132,185 @@ impl IndexMut<Handle<Animation>> for AnimationContainer {
         &mut self.pool[index]
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{
+            machine::{node::blend::BlendPose, Machine, PoseNode, State},
+            Animation, AnimationContainer, AnimationPose, KeyFrame, Track,
+        },
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            pool::Handle,
+            visitor::{Visit, Visitor},
+        },
+        scene::node::Node,
+    };
+
+    fn torso() -> Handle<Node> {
+        Handle::new(1, 1)
+    }
+
+    fn leg() -> Handle<Node> {
+        Handle::new(2, 1)
+    }
+
+    fn make_track(node: Handle<Node>, x: f32) -> Track {
+        let mut track = Track::new();
+        track.set_node(node);
+        for time in [0.0, 1.0] {
+            track.add_key_frame(KeyFrame::new(
+                time,
+                Vector3::new(x, 0.0, 0.0),
+                Vector3::new(1.0, 1.0, 1.0),
+                UnitQuaternion::identity(),
+            ));
+        }
+        track
+    }
+
+    // Aim animation moves both bones to 10, run animation moves both bones to 2.
+    fn make_animations() -> (AnimationContainer, Handle<Animation>, Handle<Animation>) {
+        let mut animations = AnimationContainer::new();
+
+        let mut aim = Animation::default();
+        aim.add_track(make_track(torso(), 10.0));
+        aim.add_track(make_track(leg(), 10.0));
+        aim.set_node_track_enabled(leg(), false);
+        let aim = animations.add(aim);
+
+        let mut run = Animation::default();
+        run.add_track(make_track(torso(), 2.0));
+        run.add_track(make_track(leg(), 2.0));
+        let run = animations.add(run);
+
+        animations.update_animations(0.1);
+
+        (animations, aim, run)
+    }
+
+    fn x(pose: &AnimationPose, node: Handle<Node>) -> f32 {
+        pose.local_pose(node).unwrap().position().x
+    }
+
+    #[test]
+    fn test_disabled_track_contributes_nothing() {
+        let (animations, aim, run) = make_animations();
+
+        let mut pose = AnimationPose::default();
+        pose.blend_with(animations[aim].get_pose(), 0.5);
+        pose.blend_with(animations[run].get_pose(), 0.5);
+
+        assert!((x(&pose, torso()) - 6.0).abs() < 1.0e-5);
+        // The leg is fully controlled by the run animation.
+        assert!((x(&pose, leg()) - 2.0).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_disabled_track_in_machine_blending() {
+        let (animations, aim, run) = make_animations();
+
+        let mut machine = Machine::new(Handle::NONE);
+        let aim_node = machine.add_node(PoseNode::make_play_animation(aim));
+        let run_node = machine.add_node(PoseNode::make_play_animation(run));
+        let blend = machine.add_node(PoseNode::make_blend_animations(vec![
+            BlendPose::with_constant_weight(0.5, aim_node),
+            BlendPose::with_constant_weight(0.5, run_node),
+        ]));
+        machine.add_state(State::new("RunAndAim", blend));
+
+        let pose = machine.evaluate_pose(&animations, 0.1);
+        assert!((x(pose, torso()) - 6.0).abs() < 1.0e-5);
+        assert!((x(pose, leg()) - 2.0).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_track_weight() {
+        let (mut animations, aim, run) = make_animations();
+
+        animations[aim].set_node_track_weight(torso(), 3.0);
+        animations.update_animations(0.1);
+
+        let mut pose = AnimationPose::default();
+        pose.blend_with(animations[aim].get_pose(), 0.5);
+        pose.blend_with(animations[run].get_pose(), 0.5);
+
+        // (10 * 1.5 + 2 * 0.5) / 2
+        assert!((x(&pose, torso()) - 8.0).abs() < 1.0e-5);
+
+        // Zero weight is the same as disabled track.
+        animations[aim].set_node_track_weight(torso(), 0.0);
+        animations.update_animations(0.1);
+        assert!(animations[aim].get_pose().local_pose(torso()).is_none());
+    }
+
+    #[test]
+    fn test_track_controls_serialization() {
+        let mut animation = Animation::default();
+        animation.add_track(make_track(torso(), 1.0));
+        animation.add_track(make_track(leg(), 1.0));
+        animation.set_node_track_enabled(leg(), false);
+        animation.set_node_track_weight(torso(), 0.25);
+
+        let mut visitor = Visitor::new();
+        animation.visit("Animation", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut loaded = Animation::default();
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        loaded.visit("Animation", &mut visitor).unwrap();
+
+        assert_eq!(loaded.track_of(torso()).unwrap().weight(), 0.25);
+        assert!(loaded.track_of(torso()).unwrap().is_enabled());
+        assert!(!loaded.track_of(leg()).unwrap().is_enabled());
+    }
+
+    #[test]
+    fn test_embedded_key_frames_serialization() {
+        let mut animation = Animation::default();
+        animation.add_track(make_track(torso(), 3.0));
+        animation.add_track(make_track(leg(), 3.0));
+        animation
+            .track_of_mut(torso())
+            .unwrap()
+            .set_embedded_key_frames(true);
+
+        let mut visitor = Visitor::new();
+        animation.visit("Animation", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut loaded = Animation::default();
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        loaded.visit("Animation", &mut visitor).unwrap();
+
+        let torso_track = loaded.track_of(torso()).unwrap();
+        assert!(torso_track.has_embedded_key_frames());
+        assert_eq!(torso_track.get_key_frames().len(), 2);
+        assert_eq!(torso_track.get_key_frames()[1].position.x, 3.0);
+        // Key frames of other tracks are restored from the resource.
+        assert!(loaded.track_of(leg()).unwrap().get_key_frames().is_empty());
+    }
+
+    #[test]
+    fn test_set_tracks_and_length() {
+        let mut animation = Animation::default();
+        animation.add_track(make_track(torso(), 1.0));
+        animation.set_loop(false).set_time_position(1.0);
+
+        let mut long_track = make_track(leg(), 1.0);
+        long_track.add_key_frame(KeyFrame::new(
+            4.0,
+            Vector3::default(),
+            Vector3::new(1.0, 1.0, 1.0),
+            UnitQuaternion::identity(),
+        ));
+        let old_tracks = animation.set_tracks(vec![long_track]);
+        assert_eq!(old_tracks.len(), 1);
+        assert_eq!(animation.length(), 4.0);
+
+        animation.set_length(0.5);
+        assert_eq!(animation.length(), 0.5);
+        assert_eq!(animation.get_time_position(), 0.5);
+    }
+}
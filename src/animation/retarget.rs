@@ -0,0 +1,657 @@
+//! Skeleton retargeting - transfers animations between skeletons with different bone names,
+//! proportions and orientations of bones. See [`retarget_animation`] docs for more info.
+
+use crate::{
+    animation::{Animation, KeyFrame},
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+        visitor::prelude::*,
+    },
+    scene::{graph::Graph, node::Node},
+};
+use serde::{Deserialize, Serialize};
+
+/// A pair of names of a bone in source and target skeletons.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Visit, Serialize, Deserialize)]
+pub struct BoneMappingEntry {
+    /// Name of a bone in the source skeleton (the skeleton the animation was authored for).
+    pub source: String,
+    /// Name of a bone in the target skeleton.
+    pub target: String,
+}
+
+/// Bone mapping defines which bone of a target skeleton is driven by a bone of a source skeleton.
+/// Bones that are not mapped are left in their bind pose on the target skeleton, and animation
+/// tracks of unmapped source bones are dropped.
+///
+/// Mapping can be generated automatically by bone names (see [`Self::from_hierarchies`]) and then
+/// adjusted manually using [`Self::set`]. Mapping is serializable, so it can be stored alongside
+/// animations of a character and reused.
+///
+/// # Root bone
+///
+/// Translation of bones is not retargeted, bones keep bind positions of the target skeleton so
+/// different proportions do not cause deformations. The only exception is the root bone (usually
+/// hips), its translation is scaled by the ratio of heights of root bones of the skeletons, so
+/// a character with longer legs moves further. The root bone is the topmost mapped bone of the
+/// source skeleton, unless it is set explicitly by [`Self::set_root_bone`].
+#[derive(Clone, Debug, Default, PartialEq, Visit, Serialize, Deserialize)]
+pub struct BoneMapping {
+    entries: Vec<BoneMappingEntry>,
+    root_bone: String,
+}
+
+/// Makes bone name suitable for fuzzy comparison - namespace prefix (`mixamorig:Hips`,
+/// `Armature|Hips`) is removed as well as any separators and case.
+fn normalize_bone_name(name: &str) -> String {
+    name.rsplit(|c| c == ':' || c == '|')
+        .next()
+        .unwrap_or(name)
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// Collects names of descendants of the root in depth-first order, the root itself is excluded.
+fn collect_bone_names(graph: &Graph, root: Handle<Node>) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut stack = graph[root]
+        .children()
+        .iter()
+        .rev()
+        .cloned()
+        .collect::<Vec<_>>();
+    while let Some(handle) = stack.pop() {
+        let node = &graph[handle];
+        names.push(node.name_owned());
+        stack.extend(node.children().iter().rev().cloned());
+    }
+    names
+}
+
+impl BoneMapping {
+    /// Creates new empty mapping.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Generates mapping by names of bones of two hierarchies. Bones with exactly the same names
+    /// are matched first, then the rest of the bones are matched by names without namespace
+    /// prefixes, separators and case, so `mixamorig:LeftUpLeg` matches `left_up_leg`. Root nodes
+    /// of the hierarchies are not included in the mapping.
+    pub fn from_hierarchies(
+        source_graph: &Graph,
+        source_root: Handle<Node>,
+        target_graph: &Graph,
+        target_root: Handle<Node>,
+    ) -> Self {
+        Self::from_names(
+            &collect_bone_names(source_graph, source_root),
+            &collect_bone_names(target_graph, target_root),
+        )
+    }
+
+    /// Generates mapping by names of bones, see [`Self::from_hierarchies`] for more info. Each
+    /// target bone is used only once.
+    pub fn from_names<S: AsRef<str>, T: AsRef<str>>(source: &[S], target: &[T]) -> Self {
+        let mut matches: Vec<Option<usize>> = vec![None; source.len()];
+        let mut used = vec![false; target.len()];
+
+        let passes: [fn(&str) -> String; 2] = [|name| name.to_owned(), normalize_bone_name];
+        for normalize in passes {
+            let normalized_target = target
+                .iter()
+                .map(|name| normalize(name.as_ref()))
+                .collect::<Vec<_>>();
+            for (source_index, source_name) in source.iter().enumerate() {
+                if matches[source_index].is_some() {
+                    continue;
+                }
+                let source_name = normalize(source_name.as_ref());
+                if let Some(target_index) = normalized_target
+                    .iter()
+                    .enumerate()
+                    .position(|(i, name)| !used[i] && *name == source_name)
+                {
+                    used[target_index] = true;
+                    matches[source_index] = Some(target_index);
+                }
+            }
+        }
+
+        Self {
+            entries: source
+                .iter()
+                .zip(matches)
+                .filter_map(|(source_name, target_index)| {
+                    target_index.map(|target_index| BoneMappingEntry {
+                        source: source_name.as_ref().to_owned(),
+                        target: target[target_index].as_ref().to_owned(),
+                    })
+                })
+                .collect(),
+            root_bone: Default::default(),
+        }
+    }
+
+    /// Maps a source bone to a target bone, previous mapping of the source bone is replaced.
+    pub fn set<S: AsRef<str>, T: AsRef<str>>(&mut self, source: S, target: T) {
+        let source = source.as_ref();
+        let target = target.as_ref();
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.source == source) {
+            entry.target = target.to_owned();
+        } else {
+            self.entries.push(BoneMappingEntry {
+                source: source.to_owned(),
+                target: target.to_owned(),
+            });
+        }
+    }
+
+    /// Removes mapping of a source bone. Returns `false` if the bone was not mapped.
+    pub fn remove(&mut self, source: &str) -> bool {
+        let count = self.entries.len();
+        self.entries.retain(|e| e.source != source);
+        count != self.entries.len()
+    }
+
+    /// Returns name of a target bone, that is driven by the given source bone.
+    pub fn target_of(&self, source: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|e| e.source == source)
+            .map(|e| e.target.as_str())
+    }
+
+    /// Returns name of a source bone, that drives the given target bone.
+    pub fn source_of(&self, target: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|e| e.target == target)
+            .map(|e| e.source.as_str())
+    }
+
+    /// Sets name of the source bone, that keeps its translation. Empty name means that the topmost
+    /// mapped bone is used. See type docs for more info.
+    pub fn set_root_bone<S: AsRef<str>>(&mut self, source: S) {
+        self.root_bone = source.as_ref().to_owned();
+    }
+
+    /// Returns name of the source root bone, if it was set explicitly.
+    pub fn root_bone(&self) -> Option<&str> {
+        if self.root_bone.is_empty() {
+            None
+        } else {
+            Some(&self.root_bone)
+        }
+    }
+
+    /// Returns every mapped bone.
+    pub fn entries(&self) -> &[BoneMappingEntry] {
+        &self.entries
+    }
+
+    /// Returns `true` if there are no mapped bones.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Bind (rest) pose of a bone. Rotations are relative to the root of the skeleton.
+#[derive(Clone, Debug, Default, PartialEq, Visit)]
+pub struct BoneBindPose {
+    /// A handle of the bone in the graph, the bind pose was taken from.
+    pub handle: Handle<Node>,
+    /// Name of the bone.
+    pub name: String,
+    /// Local position of the bone.
+    pub position: Vector3<f32>,
+    /// Local rotation of the bone (without pre- and post-rotation).
+    pub rotation: UnitQuaternion<f32>,
+    /// Pre-rotation of the bone.
+    pub pre_rotation: UnitQuaternion<f32>,
+    /// Post-rotation of the bone.
+    pub post_rotation: UnitQuaternion<f32>,
+    /// Rotation of the bone relative to the root of the skeleton.
+    pub global_rotation: UnitQuaternion<f32>,
+    /// Rotation of the parent of the bone relative to the root of the skeleton.
+    pub parent_global_rotation: UnitQuaternion<f32>,
+    /// Position of the bone relative to the root of the skeleton.
+    pub global_position: Vector3<f32>,
+}
+
+impl BoneBindPose {
+    fn full_rotation(&self, rotation: UnitQuaternion<f32>) -> UnitQuaternion<f32> {
+        self.pre_rotation * rotation * self.post_rotation.inverse()
+    }
+}
+
+/// Bind (rest) pose of a skeleton, it is used to convert animations between skeletons with
+/// different orientations of bones. Scaling, rotation and scaling pivots are ignored.
+#[derive(Clone, Debug, Default, PartialEq, Visit)]
+pub struct BindPose {
+    bones: Vec<BoneBindPose>,
+}
+
+impl BindPose {
+    /// Takes current local transforms of every descendant of the root as the bind pose. The
+    /// hierarchy must be in its rest pose (not animated) at the moment of the call.
+    pub fn from_hierarchy(graph: &Graph, root: Handle<Node>) -> Self {
+        let mut bones = Vec::new();
+        let mut stack = graph[root]
+            .children()
+            .iter()
+            .rev()
+            .map(|&child| (child, UnitQuaternion::identity(), Vector3::default()))
+            .collect::<Vec<_>>();
+        while let Some((handle, parent_global_rotation, parent_global_position)) = stack.pop() {
+            let node = &graph[handle];
+            let transform = node.local_transform();
+
+            let mut bone = BoneBindPose {
+                handle,
+                name: node.name_owned(),
+                position: **transform.position(),
+                rotation: **transform.rotation(),
+                pre_rotation: **transform.pre_rotation(),
+                post_rotation: **transform.post_rotation(),
+                global_rotation: Default::default(),
+                parent_global_rotation,
+                global_position: parent_global_position
+                    + parent_global_rotation * **transform.position(),
+            };
+            bone.global_rotation = parent_global_rotation * bone.full_rotation(bone.rotation);
+
+            stack.extend(
+                node.children()
+                    .iter()
+                    .rev()
+                    .map(|&child| (child, bone.global_rotation, bone.global_position)),
+            );
+
+            bones.push(bone);
+        }
+        Self { bones }
+    }
+
+    /// Returns bind pose of a bone with the given name.
+    pub fn bone(&self, name: &str) -> Option<&BoneBindPose> {
+        self.bones.iter().find(|b| b.name == name)
+    }
+
+    /// Returns bind pose of a bone with the given handle.
+    pub fn bone_by_handle(&self, handle: Handle<Node>) -> Option<&BoneBindPose> {
+        self.bones.iter().find(|b| b.handle == handle)
+    }
+
+    /// Returns bind poses of every bone of the skeleton in depth-first order.
+    pub fn bones(&self) -> &[BoneBindPose] {
+        &self.bones
+    }
+}
+
+/// Everything that is needed to convert key frames of a source animation to a target skeleton.
+/// Retargeted animations keep it, so key frames could be restored from the animation resource
+/// when a scene is loaded.
+#[derive(Clone, Debug, Default, PartialEq, Visit)]
+pub struct Retargeting {
+    /// Mapping of bones of the source skeleton to the target skeleton.
+    pub mapping: BoneMapping,
+    /// Bind pose of the skeleton the animation was authored for.
+    pub source_bind_pose: BindPose,
+    /// Bind pose of the skeleton the animation is retargeted to.
+    pub target_bind_pose: BindPose,
+}
+
+impl Retargeting {
+    fn root_bone(&self) -> Option<&BoneBindPose> {
+        match self.mapping.root_bone() {
+            Some(name) => self.source_bind_pose.bone(name),
+            None => self
+                .source_bind_pose
+                .bones
+                .iter()
+                .find(|bone| self.mapping.target_of(&bone.name).is_some()),
+        }
+    }
+
+    fn root_scale(&self, root: &BoneBindPose) -> f32 {
+        let source_height = root.global_position.norm();
+        match self
+            .mapping
+            .target_of(&root.name)
+            .and_then(|name| self.target_bind_pose.bone(name))
+        {
+            Some(target) if source_height > f32::EPSILON => {
+                target.global_position.norm() / source_height
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// Converts key frames of a source bone to key frames of the target bone it is mapped to.
+    /// Returns `None` if the source bone is not mapped or it is missing in any of the bind poses.
+    pub fn retarget_key_frames(
+        &self,
+        source_bone: &str,
+        key_frames: &[KeyFrame],
+    ) -> Option<Vec<KeyFrame>> {
+        let source = self.source_bind_pose.bone(source_bone)?;
+        let target = self
+            .target_bind_pose
+            .bone(self.mapping.target_of(source_bone)?)?;
+
+        // Rotation of the source bone relative to its bind pose is transferred to the target
+        // bone in the space of the skeleton root, so differently oriented bones rotate the same
+        // way. The formula is derived from equality of the deltas:
+        //
+        // target_parent * target_local * target_bind⁻¹ = source_parent * source_local * source_bind⁻¹
+        //
+        // where the parents are in their bind poses, because their deltas are already equal.
+        let parent_delta = target.parent_global_rotation.inverse() * source.parent_global_rotation;
+        let bind_delta = source.global_rotation.inverse() * target.global_rotation;
+
+        let root_scale = self
+            .root_bone()
+            .filter(|root| root.name == source.name)
+            .map(|root| self.root_scale(root));
+
+        Some(
+            key_frames
+                .iter()
+                .map(|key_frame| {
+                    let full_rotation =
+                        parent_delta * source.full_rotation(key_frame.rotation) * bind_delta;
+                    KeyFrame {
+                        position: match root_scale {
+                            Some(scale) => {
+                                target.position
+                                    + parent_delta
+                                        * (key_frame.position - source.position).scale(scale)
+                            }
+                            None => target.position,
+                        },
+                        scale: key_frame.scale,
+                        rotation: target.pre_rotation.inverse()
+                            * full_rotation
+                            * target.post_rotation,
+                        time: key_frame.time,
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Creates a copy of an animation of a source skeleton for a target skeleton using the bone
+/// mapping and bind poses of both skeletons. Source bind pose must contain handles of the nodes
+/// animated by the animation, target bind pose provides handles of the nodes of the new animation.
+///
+/// Rotations are converted through the difference of bind poses, so bones with different
+/// orientations (for example, a skeleton exported from another tool) rotate in the same way.
+/// Translation is kept only for the root bone (see [`BoneMapping`] docs), the rest of the bones
+/// keep bind positions of the target skeleton, which prevents sliding and deformations when
+/// proportions of skeletons differ.
+///
+/// Tracks of source bones that are not mapped are dropped. The new animation remembers
+/// retargeting parameters, so it is correctly restored from its resource on load.
+pub fn retarget_animation(
+    animation: &Animation,
+    mapping: &BoneMapping,
+    source_bind_pose: &BindPose,
+    target_bind_pose: &BindPose,
+) -> Animation {
+    let retargeting = Retargeting {
+        mapping: mapping.clone(),
+        source_bind_pose: source_bind_pose.clone(),
+        target_bind_pose: target_bind_pose.clone(),
+    };
+
+    let mut result = animation.clone();
+    result.tracks = animation
+        .tracks
+        .iter()
+        .filter_map(|track| {
+            let source = source_bind_pose.bone_by_handle(track.get_node())?;
+            let target = target_bind_pose.bone(mapping.target_of(&source.name)?)?;
+            // The track is cloned, so every other property (including ones that will be added
+            // later) is kept as is.
+            let mut new_track = track.clone();
+            new_track.set_node(target.handle);
+            new_track.set_key_frames(
+                &retargeting.retarget_key_frames(&source.name, track.get_key_frames())?,
+            );
+            Some(new_track)
+        })
+        .collect();
+    result.retargeting = Some(retargeting);
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{
+            retarget::{retarget_animation, BindPose, BoneMapping},
+            Animation, KeyFrame, Track,
+        },
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            pool::Handle,
+        },
+        scene::{
+            base::BaseBuilder, graph::Graph, node::Node, pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+    use std::f32::consts::FRAC_PI_2;
+
+    // (name, parent index, position relative to the root, rotation relative to the root)
+    type BoneDesc = (
+        &'static str,
+        Option<usize>,
+        Vector3<f32>,
+        UnitQuaternion<f32>,
+    );
+
+    fn make_skeleton(graph: &mut Graph, bones: &[BoneDesc]) -> Vec<Handle<Node>> {
+        let mut handles: Vec<Handle<Node>> = Vec::new();
+        for (name, parent, position, rotation) in bones.iter().cloned() {
+            let (parent_position, parent_rotation) = match parent {
+                Some(parent) => (bones[parent].2, bones[parent].3),
+                None => (Vector3::default(), UnitQuaternion::identity()),
+            };
+            let handle = PivotBuilder::new(
+                BaseBuilder::new().with_name(name).with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(
+                            parent_rotation.inverse() * (position - parent_position),
+                        )
+                        .with_local_rotation(parent_rotation.inverse() * rotation)
+                        .build(),
+                ),
+            )
+            .build(graph);
+            if let Some(parent) = parent {
+                graph.link_nodes(handle, handles[parent]);
+            }
+            handles.push(handle);
+        }
+        handles
+    }
+
+    fn apply(graph: &mut Graph, animation: &Animation, time: f32) {
+        for track in animation.get_tracks() {
+            let pose = track.get_local_pose(time).unwrap();
+            graph[track.get_node()]
+                .local_transform_mut()
+                .set_position(pose.position())
+                .set_rotation(pose.rotation());
+        }
+        graph.update_hierarchical_data();
+    }
+
+    fn rotation_track(node: Handle<Node>, position: Vector3<f32>, angle: f32) -> Track {
+        let mut track = Track::new();
+        track.set_node(node);
+        track.set_key_frames(&[
+            KeyFrame::new(
+                0.0,
+                position,
+                Vector3::new(1.0, 1.0, 1.0),
+                UnitQuaternion::identity(),
+            ),
+            KeyFrame::new(
+                1.0,
+                position,
+                Vector3::new(1.0, 1.0, 1.0),
+                UnitQuaternion::from_axis_angle(&Vector3::z_axis(), angle),
+            ),
+        ]);
+        track
+    }
+
+    #[test]
+    fn test_retargeting_to_skeleton_with_different_proportions() {
+        let identity = UnitQuaternion::identity();
+
+        // Source skeleton has identity bind rotations.
+        let mut source_graph = Graph::new();
+        let source_root = source_graph.get_root();
+        let source = make_skeleton(
+            &mut source_graph,
+            &[
+                ("Hips", None, Vector3::new(0.0, 1.0, 0.0), identity),
+                ("Spine", Some(0), Vector3::new(0.0, 1.5, 0.0), identity),
+                ("UpperArm", Some(1), Vector3::new(0.2, 1.5, 0.0), identity),
+                ("Forearm", Some(2), Vector3::new(0.5, 1.5, 0.0), identity),
+                ("Hand", Some(3), Vector3::new(0.75, 1.5, 0.0), identity),
+            ],
+        );
+
+        // Target skeleton is 1.2 times bigger, has prefixed names, different bind rotations and
+        // an extra twist bone that is not animated.
+        let scale = 1.2;
+        let rotated = |angle: f32| UnitQuaternion::from_axis_angle(&Vector3::y_axis(), angle);
+        let mut target_graph = Graph::new();
+        let target_root = target_graph.get_root();
+        let target = make_skeleton(
+            &mut target_graph,
+            &[
+                (
+                    "mixamorig:Hips",
+                    None,
+                    Vector3::new(0.0, 1.0, 0.0).scale(scale),
+                    rotated(FRAC_PI_2),
+                ),
+                (
+                    "mixamorig:Spine",
+                    Some(0),
+                    Vector3::new(0.0, 1.5, 0.0).scale(scale),
+                    identity,
+                ),
+                (
+                    "mixamorig:Upper_Arm",
+                    Some(1),
+                    Vector3::new(0.2, 1.5, 0.0).scale(scale),
+                    rotated(-FRAC_PI_2),
+                ),
+                (
+                    "mixamorig:Forearm",
+                    Some(2),
+                    Vector3::new(0.5, 1.5, 0.0).scale(scale),
+                    rotated(0.3),
+                ),
+                (
+                    "mixamorig:ForearmTwist",
+                    Some(3),
+                    Vector3::new(0.6, 1.5, 0.0).scale(scale),
+                    identity,
+                ),
+                (
+                    "mixamorig:Hand",
+                    Some(4),
+                    Vector3::new(0.75, 1.5, 0.0).scale(scale),
+                    rotated(1.0),
+                ),
+            ],
+        );
+
+        let mapping =
+            BoneMapping::from_hierarchies(&source_graph, source_root, &target_graph, target_root);
+        assert_eq!(mapping.entries().len(), 5);
+        assert_eq!(mapping.target_of("UpperArm"), Some("mixamorig:Upper_Arm"));
+        assert_eq!(mapping.source_of("mixamorig:ForearmTwist"), None);
+
+        let source_bind_pose = BindPose::from_hierarchy(&source_graph, source_root);
+        let target_bind_pose = BindPose::from_hierarchy(&target_graph, target_root);
+
+        // Hips move forward, arm bends.
+        let mut animation = Animation::default();
+        let mut hips = Track::new();
+        hips.set_node(source[0]);
+        hips.set_key_frames(&[
+            KeyFrame::new(
+                0.0,
+                Vector3::new(0.0, 1.0, 0.0),
+                Vector3::new(1.0, 1.0, 1.0),
+                identity,
+            ),
+            KeyFrame::new(
+                1.0,
+                Vector3::new(0.5, 1.0, 0.0),
+                Vector3::new(1.0, 1.0, 1.0),
+                UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.2),
+            ),
+        ]);
+        animation.add_track(hips);
+        animation.add_track(rotation_track(source[2], Vector3::new(0.2, 0.0, 0.0), 0.8));
+        animation.add_track(rotation_track(source[3], Vector3::new(0.3, 0.0, 0.0), -0.6));
+
+        let retargeted =
+            retarget_animation(&animation, &mapping, &source_bind_pose, &target_bind_pose);
+        assert_eq!(retargeted.get_tracks().len(), 3);
+        assert!(retargeted.retargeting.is_some());
+
+        for &time in [0.0, 0.5, 1.0].iter() {
+            apply(&mut source_graph, &animation, time);
+            apply(&mut target_graph, &retargeted, time);
+
+            // End effectors must be at the same place relative to the size of the skeletons.
+            for (source_bone, target_bone) in [(source[4], target[5]), (source[0], target[0])] {
+                let expected = source_graph[source_bone].global_position().scale(scale);
+                let actual = target_graph[target_bone].global_position();
+                assert!(
+                    (expected - actual).norm() < 0.001,
+                    "time {}: expected {:?}, got {:?}",
+                    time,
+                    expected,
+                    actual
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_bone_mapping_overrides() {
+        let mut mapping = BoneMapping::from_names(
+            &["Hips", "LeftArm", "Head"],
+            &["hips", "left_arm", "Armature|HEAD", "Jaw"],
+        );
+        assert_eq!(mapping.target_of("Hips"), Some("hips"));
+        assert_eq!(mapping.target_of("LeftArm"), Some("left_arm"));
+        assert_eq!(mapping.target_of("Head"), Some("Armature|HEAD"));
+
+        mapping.set("Head", "Jaw");
+        assert_eq!(mapping.target_of("Head"), Some("Jaw"));
+        assert!(mapping.remove("LeftArm"));
+        assert!(!mapping.remove("LeftArm"));
+        assert_eq!(mapping.entries().len(), 2);
+
+        assert_eq!(mapping.root_bone(), None);
+        mapping.set_root_bone("Hips");
+        assert_eq!(mapping.root_bone(), Some("Hips"));
+    }
+}
@@ -1,6 +1,9 @@
 //! All possible errors that can happen in the engine.
 
-use crate::{renderer::framework::error::FrameworkError, scene::sound::SoundError};
+use crate::{
+    engine::project::ProjectManifestError, renderer::framework::error::FrameworkError,
+    scene::sound::SoundError,
+};
 
 /// See module docs.
 #[derive(Debug, thiserror::Error)]
@@ -11,6 +14,9 @@ pub enum EngineError {
     /// Rendering system error.
     #[error(transparent)]
     Renderer(FrameworkError),
+    /// Project manifest is invalid or does not match the engine.
+    #[error(transparent)]
+    Project(ProjectManifestError),
     /// Internal error.
     #[error("Custom error: {0}")]
     Custom(String),
@@ -22,6 +28,12 @@ impl From<SoundError> for EngineError {
     }
 }
 
+impl From<ProjectManifestError> for EngineError {
+    fn from(project: ProjectManifestError) -> Self {
+        Self::Project(project)
+    }
+}
+
 impl From<FrameworkError> for EngineError {
     fn from(renderer: FrameworkError) -> Self {
         Self::Renderer(renderer)
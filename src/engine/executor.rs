@@ -3,11 +3,14 @@
 use crate::core::pool::Handle;
 use crate::scene::Scene;
 use crate::{
-    core::instant::Instant,
-    engine::{resource_manager::ResourceManager, Engine, EngineInitParams, SerializationContext},
+    engine::{
+        error::EngineError, frame_pacing::FixedStepTimer, project::ProjectManifest,
+        resource_manager::ResourceManager, Engine, EngineInitParams, SerializationContext,
+    },
     event::{Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     plugin::Plugin,
+    resource::texture::set_texture_cache_root,
     utils::{
         log::{Log, MessageKind},
         translate_event,
@@ -46,24 +49,47 @@ impl Default for Executor {
 }
 
 impl Executor {
+    /// Creates new executor. Panics if the executor cannot be created, see [`Self::try_new`].
     pub fn new() -> Self {
+        match Self::try_new() {
+            Ok(executor) => executor,
+            Err(e) => {
+                Log::err(format!("Unable to create game executor. Reason: {}", e));
+                panic!("Unable to create game executor. Reason: {}", e)
+            }
+        }
+    }
+
+    /// Creates new executor. The project manifest (if any) is loaded from the working directory
+    /// and validated before the engine is created, so a game never runs with an engine that was
+    /// built with wrong features.
+    pub fn try_new() -> Result<Self, EngineError> {
+        let project = ProjectManifest::load_and_validate(".")?;
+
         let event_loop = EventLoop::new();
 
-        let window_builder = WindowBuilder::new()
-            .with_title("Fyrox Game Executor")
-            .with_resizable(true);
+        let title = project
+            .as_ref()
+            .filter(|project| !project.name.is_empty())
+            .map_or("Fyrox Game Executor", |project| project.name.as_str());
+
+        let window_builder = WindowBuilder::new().with_title(title).with_resizable(true);
 
         let serialization_context = Arc::new(SerializationContext::new());
-        let engine = Engine::new(EngineInitParams {
+        let mut engine = Engine::new(EngineInitParams {
             window_builder,
             resource_manager: ResourceManager::new(serialization_context.clone()),
             serialization_context,
             events_loop: &event_loop,
             vsync: true,
-        })
-        .unwrap();
+        })?;
+
+        if let Some(project) = project.as_ref() {
+            set_texture_cache_root(&project.data_root);
+        }
+        engine.project = project;
 
-        Self { event_loop, engine }
+        Ok(Self { event_loop, engine })
     }
 
     pub fn add_plugin<P>(&mut self, plugin: P)
@@ -77,9 +103,8 @@ impl Executor {
         let mut engine = self.engine;
         let event_loop = self.event_loop;
 
-        let clock = Instant::now();
         let fixed_timestep = 1.0 / 60.0;
-        let mut elapsed_time = 0.0;
+        let mut timer = FixedStepTimer::new(fixed_timestep);
         let mut initialized_scenes = HashSet::<Handle<Scene>>::default();
 
         event_loop.run(move |event, _, control_flow| {
@@ -102,11 +127,7 @@ impl Executor {
 
             match event {
                 Event::MainEventsCleared => {
-                    let mut dt = clock.elapsed().as_secs_f32() - elapsed_time;
-                    while dt >= fixed_timestep {
-                        dt -= fixed_timestep;
-                        elapsed_time += fixed_timestep;
-
+                    for _ in 0..timer.tick() {
                         engine.update_plugins(fixed_timestep, false);
 
                         for &scene_handle in scenes.iter() {
@@ -118,12 +139,19 @@ impl Executor {
 
                     while let Some(_ui_event) = engine.user_interface.poll_message() {}
 
-                    engine.get_window().request_redraw();
+                    if !engine.frame_pacing.is_rendering_paused() {
+                        engine.get_window().request_redraw();
+                    }
                 }
                 Event::RedrawRequested(_) => {
                     engine.render().unwrap();
                 }
+                Event::RedrawEventsCleared => {
+                    engine.frame_pacing.wait_for_next_frame();
+                }
                 Event::WindowEvent { event, .. } => {
+                    engine.frame_pacing.handle_window_event(&event);
+
                     match event {
                         WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                         WindowEvent::Resized(size) => {
@@ -139,6 +167,11 @@ impl Executor {
 
                     if let Some(os_event) = translate_event(&event) {
                         engine.user_interface.process_os_event(&os_event);
+                        engine.ui_surfaces.process_os_event(
+                            &os_event,
+                            &engine.scenes,
+                            engine.renderer.get_frame_bounds(),
+                        );
                     }
                 }
                 _ => *control_flow = ControlFlow::Poll,
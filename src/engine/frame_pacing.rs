@@ -0,0 +1,672 @@
+//! Frame pacing - frame rate limiting, vertical synchronization and behaviour of the game when its
+//! window loses focus.
+//!
+//! Frame pacing is controlled by [`FramePacing`], every engine has one (see
+//! [`super::Engine::frame_pacing`]), it is also available to plugins and scripts through their
+//! contexts. The game loops of [`super::executor::Executor`] and [`super::framework::Framework`]
+//! use it automatically, custom game loops must do the following:
+//!
+//! - Pass window events to [`FramePacing::handle_window_event`], so it knows whether the window is
+//!   focused or not.
+//! - Do not render frames while [`FramePacing::is_rendering_paused`] returns `true`.
+//! - Call [`FramePacing::wait_for_next_frame`] at the end of every iteration of the loop (for
+//!   example, on `Event::RedrawEventsCleared`).
+//! - Use [`FixedStepTimer`] to update the game with fixed time steps, it limits amount of steps
+//!   after stalls, so there is no giant time delta when the game is un-throttled.
+//!
+//! Settings of frame pacing could be saved by a game as it wants - [`FramePacingSettings`] is
+//! serializable.
+//!
+//! ## Frame rate limiting
+//!
+//! The limit is implemented by a sleep/spin hybrid: the main thread sleeps in short intervals while
+//! it is safe to sleep and spins for the rest of the time, so the limit is precise even on
+//! platforms with coarse sleep granularity. Spinning consumes some CPU time, the amount of time to
+//! spin is estimated from actual durations of previous sleeps.
+
+use crate::{core::instant::Instant, event::WindowEvent, utils::log::Log};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter},
+    time::Duration,
+};
+
+/// Frame rate of the game loop while rendering is paused by
+/// [`BackgroundPolicy::PauseRendering`], updates still need to run at some rate.
+pub const PAUSED_FRAME_RATE: u32 = 60;
+
+/// Maximum amount of time, that [`FixedStepTimer`] converts to simulation steps at once by default.
+pub const DEFAULT_MAX_LAG: f32 = 1.0;
+
+/// Requested duration of a single sleep of the frame rate limiter.
+const SLEEP_QUANTUM: Duration = Duration::from_millis(1);
+
+/// Initial estimate of the actual duration of a sleep for [`SLEEP_QUANTUM`].
+const INITIAL_SLEEP_ESTIMATE: Duration = Duration::from_millis(2);
+
+/// Vertical synchronization mode.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VsyncMode {
+    /// Frames are presented immediately, tearing is possible.
+    Off,
+    /// Frames are presented in sync with the refresh rate of the monitor.
+    On,
+    /// Frames are presented in sync with the refresh rate of the monitor, unless a frame was late -
+    /// then it is presented immediately. Requires a driver support, falls back to [`Self::On`]
+    /// otherwise.
+    Adaptive,
+}
+
+impl VsyncMode {
+    /// Every mode, could be used to build a list of modes in a UI.
+    pub const ALL: [VsyncMode; 3] = [VsyncMode::Off, VsyncMode::On, VsyncMode::Adaptive];
+
+    /// Returns a human-readable name of the mode.
+    pub fn name(self) -> &'static str {
+        match self {
+            VsyncMode::Off => "Off",
+            VsyncMode::On => "On",
+            VsyncMode::Adaptive => "Adaptive",
+        }
+    }
+
+    /// Returns swap interval of the mode, negative intervals mean adaptive synchronization.
+    pub fn swap_interval(self) -> i32 {
+        match self {
+            VsyncMode::Off => 0,
+            VsyncMode::On => 1,
+            VsyncMode::Adaptive => -1,
+        }
+    }
+}
+
+impl Display for VsyncMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Defines what happens when the window of the game loses focus.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BackgroundPolicy {
+    /// Nothing changes.
+    KeepRunning,
+    /// Frame rate is limited to the given amount of frames per second (or to the target frame
+    /// rate, if it is lower).
+    Throttle(u32),
+    /// Frames are not rendered, updates continue at [`PAUSED_FRAME_RATE`].
+    PauseRendering,
+}
+
+impl Default for BackgroundPolicy {
+    fn default() -> Self {
+        Self::KeepRunning
+    }
+}
+
+impl Display for BackgroundPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackgroundPolicy::KeepRunning => write!(f, "Keep Running"),
+            BackgroundPolicy::Throttle(fps) => write!(f, "Throttle to {} FPS", fps),
+            BackgroundPolicy::PauseRendering => write!(f, "Pause Rendering"),
+        }
+    }
+}
+
+/// Settings of frame pacing, see [module docs](self) for more info.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FramePacingSettings {
+    /// Maximum amount of frames per second, `None` - unlimited.
+    pub target_fps: Option<u32>,
+    /// Vertical synchronization mode.
+    pub vsync: VsyncMode,
+    /// Defines what happens when the window of the game loses focus.
+    pub background_policy: BackgroundPolicy,
+}
+
+impl Default for FramePacingSettings {
+    fn default() -> Self {
+        Self {
+            target_fps: None,
+            vsync: VsyncMode::On,
+            background_policy: Default::default(),
+        }
+    }
+}
+
+/// Returns duration of a frame for the given frame rate, zero frame rate is treated as one frame
+/// per second.
+pub fn frame_interval(fps: u32) -> Duration {
+    Duration::from_secs_f64(1.0 / fps.max(1) as f64)
+}
+
+/// Returns the moment when the next frame should start. The cadence of previous frames is kept
+/// unless the loop is late by more than a frame (a stall) or the deadline is farther than a single
+/// interval (the interval was shortened), then the cadence starts from scratch.
+pub fn next_frame_deadline(previous: Option<Instant>, now: Instant, interval: Duration) -> Instant {
+    match previous {
+        Some(previous) => {
+            let deadline = previous + interval;
+            if deadline + interval < now {
+                now
+            } else if deadline > now + interval {
+                now + interval
+            } else {
+                deadline
+            }
+        }
+        None => now,
+    }
+}
+
+/// Updates the estimate of the actual duration of a sleep. Longer sleeps raise the estimate
+/// immediately, shorter ones lower it slowly, so occasional long sleeps are taken into account for
+/// a while.
+pub fn update_sleep_estimate(estimate: Duration, observed: Duration) -> Duration {
+    if observed > estimate {
+        observed
+    } else {
+        estimate - (estimate - observed) / 32
+    }
+}
+
+/// Frame pacing state of the engine, see [module docs](self) for more info.
+#[derive(Debug)]
+pub struct FramePacing {
+    settings: FramePacingSettings,
+    active_vsync: VsyncMode,
+    vsync_changed: bool,
+    focused: bool,
+    deadline: Option<Instant>,
+    sleep_estimate: Duration,
+}
+
+impl FramePacing {
+    /// Creates frame pacing with the given settings, vertical synchronization of the swap chain
+    /// must be set to the given active mode.
+    pub fn new(settings: FramePacingSettings, active_vsync: VsyncMode) -> Self {
+        Self {
+            vsync_changed: settings.vsync != active_vsync,
+            settings,
+            active_vsync,
+            focused: true,
+            deadline: None,
+            sleep_estimate: INITIAL_SLEEP_ESTIMATE,
+        }
+    }
+
+    /// Returns current settings.
+    pub fn settings(&self) -> FramePacingSettings {
+        self.settings
+    }
+
+    /// Sets new settings, could be used to apply previously saved settings.
+    pub fn set_settings(&mut self, settings: FramePacingSettings) {
+        self.set_target_fps(settings.target_fps);
+        self.set_vsync(settings.vsync);
+        self.set_background_policy(settings.background_policy);
+    }
+
+    /// Sets maximum amount of frames per second, `None` - unlimited.
+    pub fn set_target_fps(&mut self, target_fps: Option<u32>) {
+        self.settings.target_fps = target_fps;
+    }
+
+    /// Returns maximum amount of frames per second, `None` - unlimited.
+    pub fn target_fps(&self) -> Option<u32> {
+        self.settings.target_fps
+    }
+
+    /// Requests a vertical synchronization mode. The mode is applied by the engine before the next
+    /// frame, use [`Self::active_vsync`] to check which mode was actually applied.
+    pub fn set_vsync(&mut self, vsync: VsyncMode) {
+        self.settings.vsync = vsync;
+        self.vsync_changed = true;
+    }
+
+    /// Returns requested vertical synchronization mode.
+    pub fn vsync(&self) -> VsyncMode {
+        self.settings.vsync
+    }
+
+    /// Returns vertical synchronization mode of the swap chain. It could differ from the requested
+    /// mode, if the requested mode is not supported.
+    pub fn active_vsync(&self) -> VsyncMode {
+        self.active_vsync
+    }
+
+    /// Sets what happens when the window of the game loses focus.
+    pub fn set_background_policy(&mut self, policy: BackgroundPolicy) {
+        self.settings.background_policy = policy;
+    }
+
+    /// Returns what happens when the window of the game loses focus.
+    pub fn background_policy(&self) -> BackgroundPolicy {
+        self.settings.background_policy
+    }
+
+    /// Tracks focus of the window.
+    pub fn handle_window_event(&mut self, event: &WindowEvent) {
+        if let WindowEvent::Focused(focused) = event {
+            self.set_focused(*focused);
+        }
+    }
+
+    /// Sets whether the window of the game is focused or not, usually it is done by
+    /// [`Self::handle_window_event`].
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    /// Returns `true` if the window of the game is focused.
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Returns current limit of frames per second, it takes focus of the window and background
+    /// policy into account. `None` - unlimited.
+    pub fn frame_rate_limit(&self) -> Option<u32> {
+        let background_limit = if self.focused {
+            None
+        } else {
+            match self.settings.background_policy {
+                BackgroundPolicy::KeepRunning => None,
+                BackgroundPolicy::Throttle(fps) => Some(fps),
+                BackgroundPolicy::PauseRendering => Some(PAUSED_FRAME_RATE),
+            }
+        };
+
+        match (self.settings.target_fps, background_limit) {
+            (Some(target), Some(background)) => Some(target.min(background)),
+            (target, background) => target.or(background),
+        }
+    }
+
+    /// Returns `true` if frames must not be rendered.
+    pub fn is_rendering_paused(&self) -> bool {
+        !self.focused && self.settings.background_policy == BackgroundPolicy::PauseRendering
+    }
+
+    /// Blocks the current thread until the next frame should start according to the current frame
+    /// rate limit. Must be called once per iteration of the game loop. Does not block on WebAssembly,
+    /// browsers pace frames by themselves.
+    pub fn wait_for_next_frame(&mut self) {
+        let interval = match self.frame_rate_limit() {
+            Some(fps) => frame_interval(fps),
+            None => {
+                self.deadline = None;
+                return;
+            }
+        };
+
+        let deadline = next_frame_deadline(self.deadline, Instant::now(), interval);
+        self.deadline = Some(deadline);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.sleep_until(deadline);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn sleep_until(&mut self, deadline: Instant) {
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+
+            if deadline - now > self.sleep_estimate {
+                std::thread::sleep(SLEEP_QUANTUM);
+                self.sleep_estimate = update_sleep_estimate(self.sleep_estimate, now.elapsed());
+            } else {
+                std::hint::spin_loop();
+            }
+        }
+    }
+
+    /// Returns requested vertical synchronization mode, if it was changed since last call.
+    pub(crate) fn take_vsync_request(&mut self) -> Option<VsyncMode> {
+        if std::mem::take(&mut self.vsync_changed) {
+            Some(self.settings.vsync)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn set_active_vsync(&mut self, vsync: VsyncMode) {
+        if vsync != self.settings.vsync {
+            Log::warn(format!(
+                "Vertical synchronization mode {} is not supported, {} is used instead.",
+                self.settings.vsync, vsync
+            ));
+        }
+        self.active_vsync = vsync;
+    }
+}
+
+/// Splits real time into fixed time steps of simulation. Amount of time that is converted to steps
+/// at once is limited, so a stall of the game loop (or a long frame of a throttled game) does not
+/// cause a burst of simulation steps - the excess time is dropped.
+#[derive(Clone, Debug)]
+pub struct FixedStepTimer {
+    timestep: f32,
+    max_lag: f32,
+    lag: f32,
+    last_tick: Option<Instant>,
+}
+
+impl FixedStepTimer {
+    /// Creates a new timer with the given time step (in seconds).
+    pub fn new(timestep: f32) -> Self {
+        Self {
+            timestep,
+            max_lag: DEFAULT_MAX_LAG,
+            lag: 0.0,
+            last_tick: None,
+        }
+    }
+
+    /// Sets maximum amount of time (in seconds), that is converted to steps at once.
+    #[must_use]
+    pub fn with_max_lag(mut self, max_lag: f32) -> Self {
+        self.max_lag = max_lag;
+        self
+    }
+
+    /// Returns time step (in seconds).
+    pub fn timestep(&self) -> f32 {
+        self.timestep
+    }
+
+    /// Returns amount of steps that should be performed now, time of the first tick is the start
+    /// of the timer.
+    pub fn tick(&mut self) -> u32 {
+        let now = Instant::now();
+        let elapsed = self
+            .last_tick
+            .map_or(0.0, |last_tick| (now - last_tick).as_secs_f32());
+        self.last_tick = Some(now);
+        self.advance(elapsed)
+    }
+
+    /// Adds the given amount of time (in seconds) and returns amount of steps that should be
+    /// performed.
+    pub fn advance(&mut self, elapsed: f32) -> u32 {
+        self.lag = (self.lag + elapsed.max(0.0)).min(self.max_lag.max(self.timestep));
+        let steps = (self.lag / self.timestep) as u32;
+        self.lag -= steps as f32 * self.timestep;
+        steps
+    }
+
+    /// Drops accumulated time, the next tick starts the timer again.
+    pub fn reset(&mut self) {
+        self.lag = 0.0;
+        self.last_tick = None;
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) mod swap_interval {
+    //! Changes swap interval of the current OpenGL context. Contexts are created with a fixed swap
+    //! interval, so the interval is changed by platform extensions, their functions are loaded
+    //! from the context.
+
+    use std::{
+        ffi::{c_void, CStr},
+        os::raw::{c_char, c_int, c_uint, c_ulong},
+    };
+
+    const CGL_CP_SWAP_INTERVAL: c_int = 222;
+
+    unsafe fn load<F: Copy>(
+        get_proc_address: &dyn Fn(&str) -> *const c_void,
+        name: &str,
+    ) -> Option<F> {
+        let address = get_proc_address(name);
+        if address.is_null() {
+            None
+        } else {
+            Some(std::mem::transmute_copy(&address))
+        }
+    }
+
+    fn has_extension(extensions: *const c_char, name: &str) -> bool {
+        !extensions.is_null()
+            && unsafe { CStr::from_ptr(extensions) }
+                .to_string_lossy()
+                .split_whitespace()
+                .any(|extension| extension == name)
+    }
+
+    /// Sets swap interval of the current context, negative intervals mean adaptive
+    /// synchronization. Returns `false` if the interval is not supported.
+    pub fn set_swap_interval(
+        get_proc_address: &dyn Fn(&str) -> *const c_void,
+        interval: i32,
+    ) -> bool {
+        unsafe {
+            // Windows.
+            if let Some(swap_interval) =
+                load::<extern "system" fn(c_int) -> c_int>(get_proc_address, "wglSwapIntervalEXT")
+            {
+                if interval < 0 {
+                    let extensions = load::<extern "system" fn() -> *const c_char>(
+                        get_proc_address,
+                        "wglGetExtensionsStringEXT",
+                    );
+                    if !extensions.map_or(false, |extensions| {
+                        has_extension(extensions(), "WGL_EXT_swap_control_tear")
+                    }) {
+                        return false;
+                    }
+                }
+                return swap_interval(interval) != 0;
+            }
+
+            // X11. Unsupported intervals are X errors, that are fatal by default, so they're
+            // checked beforehand.
+            if let (Some(swap_interval), Some(display), Some(drawable), Some(extensions)) = (
+                load::<extern "C" fn(*mut c_void, c_ulong, c_int)>(
+                    get_proc_address,
+                    "glXSwapIntervalEXT",
+                ),
+                load::<extern "C" fn() -> *mut c_void>(get_proc_address, "glXGetCurrentDisplay"),
+                load::<extern "C" fn() -> c_ulong>(get_proc_address, "glXGetCurrentDrawable"),
+                load::<extern "C" fn(*mut c_void, c_int) -> *const c_char>(
+                    get_proc_address,
+                    "glXQueryExtensionsString",
+                ),
+            ) {
+                let display = display();
+                let drawable = drawable();
+                if display.is_null() || drawable == 0 {
+                    return false;
+                }
+                let extensions = extensions(display, 0);
+                if !has_extension(extensions, "GLX_EXT_swap_control")
+                    || (interval < 0 && !has_extension(extensions, "GLX_EXT_swap_control_tear"))
+                {
+                    return false;
+                }
+                swap_interval(display, drawable, interval);
+                return true;
+            }
+            if let Some(swap_interval) =
+                load::<extern "C" fn(c_uint) -> c_int>(get_proc_address, "glXSwapIntervalMESA")
+            {
+                return interval >= 0 && swap_interval(interval as c_uint) == 0;
+            }
+
+            // Wayland, Android and other EGL platforms, EGL has no adaptive synchronization.
+            if let (Some(swap_interval), Some(display)) = (
+                load::<extern "system" fn(*mut c_void, c_int) -> c_uint>(
+                    get_proc_address,
+                    "eglSwapInterval",
+                ),
+                load::<extern "system" fn() -> *mut c_void>(
+                    get_proc_address,
+                    "eglGetCurrentDisplay",
+                ),
+            ) {
+                return interval >= 0 && swap_interval(display(), interval) != 0;
+            }
+
+            // macOS, CGL has no adaptive synchronization as well.
+            if let (Some(set_parameter), Some(context)) = (
+                load::<extern "C" fn(*mut c_void, c_int, *const c_int) -> c_int>(
+                    get_proc_address,
+                    "CGLSetParameter",
+                ),
+                load::<extern "C" fn() -> *mut c_void>(get_proc_address, "CGLGetCurrentContext"),
+            ) {
+                let value = interval as c_int;
+                return interval >= 0
+                    && set_parameter(context(), CGL_CP_SWAP_INTERVAL, &value) == 0;
+            }
+
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::instant::Instant,
+        engine::frame_pacing::{
+            frame_interval, next_frame_deadline, update_sleep_estimate, BackgroundPolicy,
+            FixedStepTimer, FramePacing, FramePacingSettings, VsyncMode, PAUSED_FRAME_RATE,
+        },
+    };
+    use std::time::Duration;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_frame_interval() {
+        assert_eq!(frame_interval(50), ms(20));
+        assert_eq!(frame_interval(1), ms(1000));
+        assert_eq!(frame_interval(0), ms(1000));
+    }
+
+    #[test]
+    fn test_next_frame_deadline() {
+        let start = Instant::now();
+        let interval = ms(20);
+
+        // The first frame starts immediately.
+        assert_eq!(next_frame_deadline(None, start, interval), start);
+
+        // The cadence is kept, a frame that was a bit late does not shift next frames.
+        assert_eq!(
+            next_frame_deadline(Some(start), start + ms(5), interval),
+            start + ms(20)
+        );
+        assert_eq!(
+            next_frame_deadline(Some(start), start + ms(30), interval),
+            start + ms(20)
+        );
+
+        // After a stall there is no burst of frames to catch up.
+        assert_eq!(
+            next_frame_deadline(Some(start), start + ms(500), interval),
+            start + ms(500)
+        );
+
+        // Un-throttling: the previous deadline was set for 1 FPS, the next frame must not wait
+        // for a whole second.
+        assert_eq!(
+            next_frame_deadline(Some(start + ms(1000)), start + ms(10), interval),
+            start + ms(30)
+        );
+    }
+
+    #[test]
+    fn test_sleep_estimate() {
+        // Long sleeps raise the estimate immediately.
+        assert_eq!(update_sleep_estimate(ms(2), ms(15)), ms(15));
+
+        // Short sleeps lower it slowly, but it converges.
+        let mut estimate = ms(15);
+        estimate = update_sleep_estimate(estimate, ms(1));
+        assert!(estimate < ms(15) && estimate > ms(14));
+        for _ in 0..1000 {
+            estimate = update_sleep_estimate(estimate, ms(1));
+        }
+        assert!(estimate < Duration::from_micros(1100));
+    }
+
+    #[test]
+    fn test_frame_rate_limit() {
+        let mut pacing = FramePacing::new(
+            FramePacingSettings {
+                target_fps: Some(144),
+                vsync: VsyncMode::Off,
+                background_policy: BackgroundPolicy::Throttle(10),
+            },
+            VsyncMode::Off,
+        );
+        assert_eq!(pacing.frame_rate_limit(), Some(144));
+        assert!(!pacing.is_rendering_paused());
+
+        pacing.set_focused(false);
+        assert_eq!(pacing.frame_rate_limit(), Some(10));
+
+        // Target frame rate lower than the background one wins.
+        pacing.set_target_fps(Some(5));
+        assert_eq!(pacing.frame_rate_limit(), Some(5));
+
+        pacing.set_target_fps(None);
+        pacing.set_background_policy(BackgroundPolicy::PauseRendering);
+        assert_eq!(pacing.frame_rate_limit(), Some(PAUSED_FRAME_RATE));
+        assert!(pacing.is_rendering_paused());
+
+        pacing.set_background_policy(BackgroundPolicy::KeepRunning);
+        assert_eq!(pacing.frame_rate_limit(), None);
+
+        // Focus restores the target frame rate.
+        pacing.set_background_policy(BackgroundPolicy::Throttle(10));
+        pacing.set_focused(true);
+        assert_eq!(pacing.frame_rate_limit(), None);
+        assert!(!pacing.is_rendering_paused());
+    }
+
+    #[test]
+    fn test_vsync_request() {
+        let mut pacing = FramePacing::new(FramePacingSettings::default(), VsyncMode::On);
+        assert_eq!(pacing.take_vsync_request(), None);
+
+        pacing.set_vsync(VsyncMode::Adaptive);
+        assert_eq!(pacing.take_vsync_request(), Some(VsyncMode::Adaptive));
+        assert_eq!(pacing.take_vsync_request(), None);
+
+        pacing.set_active_vsync(VsyncMode::On);
+        assert_eq!(pacing.vsync(), VsyncMode::Adaptive);
+        assert_eq!(pacing.active_vsync(), VsyncMode::On);
+    }
+
+    #[test]
+    fn test_fixed_step_timer() {
+        let mut timer = FixedStepTimer::new(0.25).with_max_lag(1.0);
+
+        assert_eq!(timer.advance(0.125), 0);
+        assert_eq!(timer.advance(0.25), 1);
+        // The remainder is kept.
+        assert_eq!(timer.advance(0.125), 1);
+
+        // A throttled frame is simulated entirely.
+        assert_eq!(timer.advance(0.75), 3);
+
+        // A stall does not cause a burst of steps.
+        assert_eq!(timer.advance(10.0), 4);
+        assert_eq!(timer.advance(0.0), 0);
+
+        // Time does not go backwards.
+        assert_eq!(timer.advance(-1.0), 0);
+
+        timer.reset();
+        assert_eq!(timer.advance(0.125), 0);
+    }
+}
@@ -8,8 +8,9 @@ use crate::engine::{EngineInitParams, SerializationContext};
 use crate::gui::message::UiMessage;
 use crate::utils::log::{Log, MessageKind};
 use crate::{
-    core::instant::Instant,
-    engine::{error::EngineError, resource_manager::ResourceManager, Engine},
+    engine::{
+        error::EngineError, frame_pacing::FixedStepTimer, resource_manager::ResourceManager, Engine,
+    },
     event::{DeviceEvent, DeviceId, Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     utils::translate_event,
@@ -93,33 +94,34 @@ impl<State: GameState> Framework<State> {
         let mut engine = self.engine;
         engine.get_window().set_title(&self.title);
         let mut state = self.state;
-        let clock = Instant::now();
-        let fixed_timestep = 1.0 / 60.0;
-        let mut elapsed_time = 0.0;
+        let mut timer = FixedStepTimer::new(1.0 / 60.0);
 
         self.event_loop
             .run(move |event, _, control_flow| match event {
                 Event::MainEventsCleared => {
-                    let mut dt = clock.elapsed().as_secs_f32() - elapsed_time;
-                    while dt >= fixed_timestep {
-                        dt -= fixed_timestep;
-                        elapsed_time += fixed_timestep;
+                    for _ in 0..timer.tick() {
+                        state.on_tick(&mut engine, timer.timestep(), control_flow);
 
-                        state.on_tick(&mut engine, fixed_timestep, control_flow);
-
-                        engine.update(fixed_timestep);
+                        engine.update(timer.timestep());
                     }
 
                     while let Some(ui_msg) = engine.user_interface.poll_message() {
                         state.on_ui_message(&mut engine, ui_msg);
                     }
 
-                    engine.get_window().request_redraw();
+                    if !engine.frame_pacing.is_rendering_paused() {
+                        engine.get_window().request_redraw();
+                    }
                 }
                 Event::RedrawRequested(_) => {
                     engine.render().unwrap();
                 }
+                Event::RedrawEventsCleared => {
+                    engine.frame_pacing.wait_for_next_frame();
+                }
                 Event::WindowEvent { event, .. } => {
+                    engine.frame_pacing.handle_window_event(&event);
+
                     match event {
                         WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                         WindowEvent::Resized(size) => {
@@ -135,6 +137,11 @@ impl<State: GameState> Framework<State> {
 
                     if let Some(os_event) = translate_event(&event) {
                         engine.user_interface.process_os_event(&os_event);
+                        engine.ui_surfaces.process_os_event(
+                            &os_event,
+                            &engine.scenes,
+                            engine.renderer.get_frame_bounds(),
+                        );
                     }
 
                     state.on_window_event(&mut engine, event);
@@ -5,26 +5,40 @@
 
 pub mod error;
 pub mod executor;
+pub mod frame_pacing;
 pub mod framework;
+pub mod project;
 pub mod resource_manager;
+pub mod task;
+pub mod ui_surface;
 
 use crate::{
     asset::ResourceState,
-    core::{algebra::Vector2, instant, pool::Handle},
+    core::{algebra::Vector2, instant, pool::Handle, uuid::Uuid},
     engine::{
         error::EngineError,
+        frame_pacing::{FramePacing, FramePacingSettings, VsyncMode},
+        project::ProjectManifest,
         resource_manager::{container::event::ResourceEvent, ResourceManager},
+        task::TaskContainer,
+        ui_surface::UiSurfaceContainer,
     },
     event::Event,
     event_loop::EventLoop,
     gui::UserInterface,
     plugin::{Plugin, PluginContext, PluginRegistrationContext},
-    renderer::{framework::error::FrameworkError, Renderer},
+    renderer::{color_management::OutputCapabilities, framework::error::FrameworkError, Renderer},
     resource::{model::Model, texture::TextureKind},
     scene::{
-        node::constructor::NodeConstructorContainer, sound::SoundEngine, Scene, SceneContainer,
+        node::{constructor::NodeConstructorContainer, Node},
+        sound::SoundEngine,
+        trigger_volume::{TriggerEvent, TriggerVolume},
+        Scene, SceneContainer,
+    },
+    script::{
+        constructor::{ScriptComponentConstructorContainer, ScriptConstructorContainer},
+        Script, ScriptContext,
     },
-    script::{constructor::ScriptConstructorContainer, Script, ScriptContext},
     utils::log::Log,
     window::{Window, WindowBuilder},
 };
@@ -45,6 +59,9 @@ pub struct SerializationContext {
     pub node_constructors: NodeConstructorContainer,
     /// A script constructor container.
     pub script_constructors: ScriptConstructorContainer,
+    /// A script component constructor container, see [`crate::script::component::ScriptComponent`]
+    /// docs for more info.
+    pub component_constructors: ScriptComponentConstructorContainer,
 }
 
 impl Default for SerializationContext {
@@ -59,6 +76,7 @@ impl SerializationContext {
         Self {
             node_constructors: NodeConstructorContainer::new(),
             script_constructors: ScriptConstructorContainer::new(),
+            component_constructors: ScriptComponentConstructorContainer::new(),
         }
     }
 }
@@ -74,6 +92,9 @@ pub struct Engine {
     pub renderer: Renderer,
     /// User interface allows you to build interface of any kind.
     pub user_interface: UserInterface,
+    /// World-space user interfaces, that are rendered onto meshes in scenes. See
+    /// [`ui_surface::UiSurface`] docs for more info.
+    pub ui_surfaces: UiSurfaceContainer,
     /// Current resource manager. Resource manager can be cloned (it does clone only ref) to be able to
     /// use resource manager from any thread, this is useful to load resources from multiple
     /// threads to decrease loading times of your game by utilizing all available power of
@@ -85,6 +106,9 @@ pub struct Engine {
     /// for such statistics, probably it is best to make separate structure to hold all
     /// such data.
     pub ui_time: Duration,
+    /// Frame rate limit, vertical synchronization mode and behaviour of the game when its window
+    /// loses focus. See [`frame_pacing`] module docs for more info.
+    pub frame_pacing: FramePacing,
 
     model_events_receiver: Receiver<ResourceEvent<Model>>,
 
@@ -96,9 +120,20 @@ pub struct Engine {
     // A set of plugins used by the engine.
     plugins: Vec<Box<dyn Plugin>>,
 
+    // Whether the simulation of scenes is paused or not, see `set_paused` for more info.
+    paused: bool,
+
+    // Tasks spawned by plugins, every task is owned by a plugin with respective id.
+    plugin_tasks: TaskContainer<Uuid>,
+
     /// A special container that is able to create nodes by their type UUID. Use a copy of this
     /// value whenever you need it as a parameter in other parts of the engine.
     pub serialization_context: Arc<SerializationContext>,
+
+    /// Manifest of the project that is run by the engine, `None` if there is no manifest. It is
+    /// loaded and validated by the game executor and the editor, see [`ProjectManifest`] docs for
+    /// more info.
+    pub project: Option<ProjectManifest>,
 }
 
 struct ResourceGraphVertex {
@@ -188,7 +223,8 @@ pub struct EngineInitParams<'a> {
     /// Whether to use vertical synchronization or not. V-sync will force your game to render
     /// frames with the synchronization rate of your monitor (which is ~60 FPS). Keep in mind
     /// vertical synchronization could not be available on your OS and engine might fail to
-    /// initialize if v-sync is on.
+    /// initialize if v-sync is on. The mode could be changed later by
+    /// [`FramePacing::set_vsync`].
     pub vsync: bool,
 }
 
@@ -233,7 +269,7 @@ impl Engine {
         } = params;
 
         #[cfg(not(target_arch = "wasm32"))]
-        let (context, client_size) = {
+        let (context, client_size, output_capabilities) = {
             let context_wrapper: glutin::WindowedContext<glutin::NotCurrent> =
                 glutin::ContextBuilder::new()
                     .with_vsync(vsync)
@@ -249,9 +285,17 @@ impl Engine {
                 Err((_, e)) => return Err(EngineError::from(e)),
             };
             let inner_size = ctx.window().inner_size();
+            let pixel_format = ctx.get_pixel_format();
             (
                 ctx,
                 Vector2::new(inner_size.width as f32, inner_size.height as f32),
+                OutputCapabilities {
+                    color_bits: pixel_format.color_bits,
+                    alpha_bits: pixel_format.alpha_bits,
+                    // OpenGL has no way to tell the compositor that the back buffer is
+                    // PQ-encoded.
+                    hdr10: false,
+                },
             )
         };
 
@@ -287,6 +331,10 @@ impl Engine {
             )
         };
 
+        // WebGL back buffer is always 8-bit sRGB.
+        #[cfg(target_arch = "wasm32")]
+        let output_capabilities = OutputCapabilities::default();
+
         #[cfg(not(target_arch = "wasm32"))]
         let glow_context =
             { unsafe { glow::Context::from_loader_function(|s| context.get_proc_address(s)) } };
@@ -296,6 +344,7 @@ impl Engine {
         let renderer = Renderer::new(
             glow_context,
             (client_size.x as u32, client_size.y as u32),
+            output_capabilities,
             &resource_manager,
         )?;
 
@@ -314,13 +363,27 @@ impl Engine {
             scenes: SceneContainer::new(sound_engine.clone()),
             sound_engine,
             user_interface: UserInterface::new(client_size),
+            ui_surfaces: Default::default(),
             ui_time: Default::default(),
+            frame_pacing: {
+                let vsync = if vsync { VsyncMode::On } else { VsyncMode::Off };
+                FramePacing::new(
+                    FramePacingSettings {
+                        vsync,
+                        ..Default::default()
+                    },
+                    vsync,
+                )
+            },
             #[cfg(not(target_arch = "wasm32"))]
             context,
             #[cfg(target_arch = "wasm32")]
             window,
             plugins: Default::default(),
             serialization_context: node_constructors,
+            paused: false,
+            project: None,
+            plugin_tasks: Default::default(),
         })
     }
 
@@ -358,6 +421,26 @@ impl Engine {
         self.post_update(dt);
     }
 
+    /// Pauses or resumes simulation of every scene. When paused, scenes are updated with zero delta
+    /// time - nothing is simulated (animations, physics, particles, scripts, etc. are frozen), but
+    /// hierarchical data of scene graphs is still updated, so you can still modify scenes. The user
+    /// interface, resource manager and the renderer keep working as usual, so pause menus will work
+    /// fine.
+    ///
+    /// Time that has passed while the engine was paused is not accumulated, so there is no "catch
+    /// up" (multiple simulation steps in a single frame) when the engine is resumed.
+    ///
+    /// If you need to pause only a particular scene, use [`Scene::set_time_scale`] with zero time
+    /// scale instead.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Returns `true` if the simulation of scenes is paused. See [`Self::set_paused`] for more info.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
     /// Performs pre update for the engine.
     ///
     /// Normally, this is called from `Engine::update()`.
@@ -367,9 +450,15 @@ impl Engine {
         let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
 
         self.resource_manager.state().update(dt);
+        let unloaded_textures = self.resource_manager.state().take_unloaded_textures();
+        for key in unloaded_textures {
+            self.renderer.unload_texture_by_key(key);
+        }
         self.renderer.update_caches(dt);
         self.handle_model_events();
 
+        let scene_dt = if self.paused { 0.0 } else { dt };
+
         for scene in self.scenes.iter_mut().filter(|s| s.enabled) {
             let frame_size = scene.render_target.as_ref().map_or(window_size, |rt| {
                 if let TextureKind::Rectangle { width, height } = rt.data_ref().kind() {
@@ -379,7 +468,7 @@ impl Engine {
                 }
             });
 
-            scene.update(frame_size, dt);
+            scene.update(frame_size, scene_dt);
         }
     }
 
@@ -393,6 +482,7 @@ impl Engine {
 
         let time = instant::Instant::now();
         self.user_interface.update(window_size, dt);
+        self.ui_surfaces.update(&mut self.scenes, dt);
         self.ui_time = instant::Instant::now() - time;
     }
 
@@ -411,11 +501,41 @@ impl Engine {
             renderer: &mut self.renderer,
             dt,
             serialization_context: self.serialization_context.clone(),
+            project: self.project.as_ref(),
+            tasks: &mut self.plugin_tasks,
+            frame_pacing: &mut self.frame_pacing,
         };
 
         for plugin in self.plugins.iter_mut() {
             plugin.update(&mut context);
         }
+
+        let plugins = &self.plugins;
+        let completed = self
+            .plugin_tasks
+            .update(&self.resource_manager.task_pool(), |id| {
+                plugins.iter().any(|p| p.id() == *id)
+            });
+
+        for task in completed {
+            if let Some(plugin) = self.plugins.iter_mut().find(|p| p.id() == task.owner) {
+                plugin.on_task_complete(
+                    task.handle,
+                    task.result,
+                    PluginContext {
+                        is_in_editor,
+                        scenes: &mut self.scenes,
+                        resource_manager: &self.resource_manager,
+                        renderer: &mut self.renderer,
+                        dt,
+                        serialization_context: self.serialization_context.clone(),
+                        project: self.project.as_ref(),
+                        tasks: &mut self.plugin_tasks,
+                        frame_pacing: &mut self.frame_pacing,
+                    },
+                );
+            }
+        }
     }
 
     /// Calls [`Plugin::on_unload`] of every plugin.
@@ -433,6 +553,9 @@ impl Engine {
             renderer: &mut self.renderer,
             dt,
             serialization_context: self.serialization_context.clone(),
+            project: self.project.as_ref(),
+            tasks: &mut self.plugin_tasks,
+            frame_pacing: &mut self.frame_pacing,
         };
 
         for plugin in self.plugins.iter_mut() {
@@ -452,6 +575,9 @@ impl Engine {
                     renderer: &mut self.renderer,
                     dt,
                     serialization_context: self.serialization_context.clone(),
+                    project: self.project.as_ref(),
+                    tasks: &mut self.plugin_tasks,
+                    frame_pacing: &mut self.frame_pacing,
                 },
             );
         }
@@ -474,6 +600,9 @@ impl Engine {
                     renderer: &mut self.renderer,
                     dt,
                     serialization_context: self.serialization_context.clone(),
+                    project: self.project.as_ref(),
+                    tasks: &mut self.plugin_tasks,
+                    frame_pacing: &mut self.frame_pacing,
                 },
             );
         }
@@ -489,6 +618,9 @@ impl Engine {
                 renderer: &mut self.renderer,
                 dt,
                 serialization_context: self.serialization_context.clone(),
+                project: self.project.as_ref(),
+                tasks: &mut self.plugin_tasks,
+                frame_pacing: &mut self.frame_pacing,
             });
         }
     }
@@ -497,60 +629,77 @@ impl Engine {
     where
         T: FnMut(&mut Script, ScriptContext),
     {
-        let scene = &mut self.scenes[scene];
-
         // Iterate over the nodes without borrowing, we'll move data around to solve borrowing issues.
-        for node_index in 0..scene.graph.capacity() {
-            let handle = scene.graph.handle_from_index(node_index);
-
-            // We're interested only in nodes with scripts.
-            if scene
-                .graph
-                .try_get(handle)
-                .map_or(true, |node| node.script.is_none())
-            {
-                continue;
-            }
+        for node_index in 0..self.scenes[scene].graph.capacity() {
+            let handle = self.scenes[scene].graph.handle_from_index(node_index);
 
-            // If a node has script assigned, then temporarily move it out of the pool with taking
-            // the ownership to satisfy borrow checker. Moving a node out of the pool is fast, because
-            // it is just a copy of 16 bytes which can be performed in a single instruction on modern
-            // CPUs.
-            let (ticket, mut node) = scene.graph.take_reserve_internal(handle);
-
-            // Take the script off the node to get mutable borrow to it without mutably borrowing
-            // the node itself. This operation is fast as well.
-            let mut script = node.script.take().unwrap();
-
-            // Find respective plugin.
-            if let Some(plugin) = self
-                .plugins
-                .iter_mut()
-                .find(|p| p.id() == script.plugin_uuid())
-            {
-                // Form the context with all available data.
-                let context = ScriptContext {
-                    dt,
-                    plugin: &mut **plugin,
-                    node: &mut node,
-                    handle,
-                    scene,
-                    resource_manager: &self.resource_manager,
-                };
+            self.process_node_script(scene, handle, dt, &mut func);
+        }
+    }
 
-                func(&mut script, context);
-            }
+    fn process_node_script<T>(
+        &mut self,
+        scene: Handle<Scene>,
+        handle: Handle<Node>,
+        dt: f32,
+        func: &mut T,
+    ) where
+        T: FnMut(&mut Script, ScriptContext),
+    {
+        let scene = &mut self.scenes[scene];
+        let unscaled_dt = dt;
+        let dt = dt * scene.time_scale();
+
+        // We're interested only in nodes with scripts.
+        if scene
+            .graph
+            .try_get(handle)
+            .map_or(true, |node| node.script.is_none())
+        {
+            return;
+        }
 
-            // Put the script back to the node.
-            node.script = Some(script);
+        // If a node has script assigned, then temporarily move it out of the pool with taking
+        // the ownership to satisfy borrow checker. Moving a node out of the pool is fast, because
+        // it is just a copy of 16 bytes which can be performed in a single instruction on modern
+        // CPUs.
+        let (ticket, mut node) = scene.graph.take_reserve_internal(handle);
+
+        // Take the script off the node to get mutable borrow to it without mutably borrowing
+        // the node itself. This operation is fast as well.
+        let mut script = node.script.take().unwrap();
+
+        // Find respective plugin.
+        if let Some(plugin) = self
+            .plugins
+            .iter_mut()
+            .find(|p| p.id() == script.plugin_uuid())
+        {
+            // Form the context with all available data.
+            let context = ScriptContext {
+                dt,
+                unscaled_dt,
+                plugin: &mut **plugin,
+                node: &mut node,
+                handle,
+                scene,
+                resource_manager: &self.resource_manager,
+                frame_pacing: &mut self.frame_pacing,
+            };
 
-            // Put the node back in the graph.
-            scene.graph.put_back_internal(ticket, node);
+            func(&mut script, context);
         }
+
+        // Put the script back to the node.
+        node.script = Some(script);
+
+        // Put the node back in the graph.
+        scene.graph.put_back_internal(ticket, node);
     }
 
     /// Updates scripts of specified scene. It must be called manually! Usually the editor
-    /// calls this for you when it is in the play mode.
+    /// calls this for you when it is in the play mode. Does nothing if the engine is paused (see
+    /// [`Self::set_paused`]).
     ///
     /// # Important notes
     ///
@@ -558,10 +707,140 @@ impl Engine {
     /// engine as a framework, then you should not call this method because you'll most likely
     /// do something wrong.
     pub fn update_scene_scripts(&mut self, scene: Handle<Scene>, dt: f32) {
-        self.process_scripts(scene, dt, |script, context| script.on_update(context));
+        if self.paused {
+            return;
+        }
+
+        // Notify scripts about enabled state changes of their nodes first, `None` means that the
+        // script sees its node for the first time and there is nothing to notify about.
+        self.process_scripts(scene, dt, |script, context| {
+            let enabled = context.node.is_globally_enabled();
+            match context.node.script_enabled.replace(enabled) {
+                Some(false) if enabled => script.on_enable(context),
+                Some(true) if !enabled => script.on_disable(context),
+                _ => (),
+            }
+        });
+
+        self.start_scripts(scene, dt);
+
+        self.process_scripts(scene, dt, |script, context| {
+            if context.node.is_globally_enabled() {
+                script.on_update(context)
+            }
+        });
+
+        self.deliver_state_actions(scene, dt);
+
+        self.deliver_trigger_events(scene, dt);
+
+        self.deliver_task_results(scene, dt);
+    }
+
+    /// Calls [`crate::script::ScriptTrait::on_start`] for scripts of the scene, whose resources are
+    /// loaded. See [`crate::script::start::ScriptStartQueue`] docs for more info.
+    fn start_scripts(&mut self, scene: Handle<Scene>, dt: f32) {
+        let resource_manager = &self.resource_manager;
+        let scene_ref = &mut self.scenes[scene];
+        let ready = scene_ref.script_starts.update(&mut scene_ref.graph, || {
+            resource_manager.state().pending_resource_keys()
+        });
+
+        for handle in ready {
+            self.process_node_script(scene, handle, dt, &mut |script, context| {
+                script.on_start(context)
+            });
+        }
+    }
+
+    /// Delivers results of finished tasks to the scripts of the nodes that spawned them and spawns
+    /// queued tasks. Results of tasks, whose nodes were deleted or lost their scripts, are dropped.
+    fn deliver_task_results(&mut self, scene: Handle<Scene>, dt: f32) {
+        let task_pool = self.resource_manager.task_pool();
+        let scene_ref = &mut self.scenes[scene];
+        let graph = &scene_ref.graph;
+        let completed = scene_ref.tasks.update(&task_pool, |owner| {
+            graph
+                .try_get(*owner)
+                .map_or(false, |node| node.script.is_some())
+        });
+
+        for task in completed {
+            let mut result = Some(task.result);
+            self.process_node_script(scene, task.owner, dt, &mut |script, context| {
+                if let Some(result) = result.take() {
+                    script.on_task_complete(task.handle, result, context)
+                }
+            });
+        }
+    }
+
+    /// Delivers state actions fired by animation blending state machines of the scene to the
+    /// scripts of their root nodes. Actions of machines without a scripted root node are dropped.
+    fn deliver_state_actions(&mut self, scene: Handle<Scene>, dt: f32) {
+        let mut actions = Vec::new();
+        for machine in self.scenes[scene].animation_machines.iter_mut() {
+            while let Some(action) = machine.pop_action() {
+                actions.push((machine.root(), action));
+            }
+        }
+
+        for (root, action) in actions {
+            self.process_node_script(scene, root, dt, &mut |script, context| {
+                script.on_state_action(&action, context)
+            });
+        }
     }
 
-    /// Passes specified OS event to every script of the specified scene.
+    /// Delivers events of trigger volumes of the scene to the scripts of the volumes and, if a
+    /// volume is set to notify entering bodies, to the scripts of the bodies. Disabled volumes are
+    /// not updated, so their events are outdated and ignored.
+    fn deliver_trigger_events(&mut self, scene: Handle<Scene>, dt: f32) {
+        let mut events = Vec::new();
+        for (handle, node) in self.scenes[scene].graph.pair_iter() {
+            if !node.is_globally_enabled() {
+                continue;
+            }
+
+            if let Some(trigger) = node.cast::<TriggerVolume>() {
+                let notify = trigger.is_notify_entering();
+                events.extend(
+                    trigger
+                        .events()
+                        .iter()
+                        .map(|event| (handle, notify, *event)),
+                );
+            }
+        }
+
+        for (trigger, notify, event) in events {
+            match event {
+                TriggerEvent::Enter(other) => {
+                    self.process_node_script(scene, trigger, dt, &mut |script, context| {
+                        script.on_trigger_enter(other, context)
+                    });
+                    if notify {
+                        self.process_node_script(scene, other, dt, &mut |script, context| {
+                            script.on_trigger_enter(trigger, context)
+                        });
+                    }
+                }
+                TriggerEvent::Exit(other) => {
+                    self.process_node_script(scene, trigger, dt, &mut |script, context| {
+                        script.on_trigger_exit(other, context)
+                    });
+                    if notify {
+                        self.process_node_script(scene, other, dt, &mut |script, context| {
+                            script.on_trigger_exit(trigger, context)
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Passes specified OS event to every script of the specified scene. Scripts of disabled nodes
+    /// are ignored.
     ///
     /// # Important notes
     ///
@@ -570,7 +849,9 @@ impl Engine {
     /// do something wrong.
     pub fn handle_os_event_by_scripts(&mut self, event: &Event<()>, scene: Handle<Scene>, dt: f32) {
         self.process_scripts(scene, dt, |script, context| {
-            script.on_os_event(event, context)
+            if context.node.is_globally_enabled() {
+                script.on_os_event(event, context)
+            }
         })
     }
 
@@ -617,8 +898,62 @@ impl Engine {
     /// see anything.
     #[inline]
     pub fn render(&mut self) -> Result<(), FrameworkError> {
+        self.apply_vsync();
+
         self.user_interface.draw();
 
+        // Scenes are rendered with interpolated physics, but game code must see actual transforms,
+        // so they're restored right after rendering.
+        for scene in self.scenes.iter_mut() {
+            scene.graph.begin_physics_interpolation();
+        }
+
+        let result = self.render_scenes();
+
+        for scene in self.scenes.iter_mut() {
+            scene.graph.end_physics_interpolation();
+        }
+
+        result
+    }
+
+    /// Applies vertical synchronization mode requested by [`FramePacing::set_vsync`]. Adaptive
+    /// synchronization falls back to the regular one, if it is not supported.
+    fn apply_vsync(&mut self) {
+        let requested = match self.frame_pacing.take_vsync_request() {
+            Some(requested) => requested,
+            None => return,
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let active = {
+            let context = &self.context;
+            let get_proc_address = |name: &str| context.get_proc_address(name);
+            let candidates = match requested {
+                VsyncMode::Adaptive => vec![VsyncMode::Adaptive, VsyncMode::On],
+                mode => vec![mode],
+            };
+            candidates
+                .into_iter()
+                .find(|mode| {
+                    frame_pacing::swap_interval::set_swap_interval(
+                        &get_proc_address,
+                        mode.swap_interval(),
+                    )
+                })
+                .unwrap_or_else(|| self.frame_pacing.active_vsync())
+        };
+
+        // Browsers always present frames in sync with the display.
+        #[cfg(target_arch = "wasm32")]
+        let active = VsyncMode::On;
+
+        self.frame_pacing.set_active_vsync(active);
+    }
+
+    fn render_scenes(&mut self) -> Result<(), FrameworkError> {
+        self.ui_surfaces.render(&mut self.renderer, &self.scenes)?;
+
         #[cfg(not(target_arch = "wasm32"))]
         {
             self.renderer.render_and_swap_buffers(
@@ -662,6 +997,9 @@ impl Engine {
                 renderer: &mut self.renderer,
                 dt: 0.0,
                 serialization_context: self.serialization_context.clone(),
+                project: self.project.as_ref(),
+                tasks: &mut self.plugin_tasks,
+                frame_pacing: &mut self.frame_pacing,
             });
         }
 
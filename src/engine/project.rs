@@ -0,0 +1,355 @@
+//! Project manifest is a `fyrox-project.toml` file in the root folder of a project. It describes
+//! the project for the engine and the editor: which engine features the project needs, where its
+//! data is stored and which scene is the main one. See [`ProjectManifest`] docs for more info.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Name of the manifest file, it must be placed in the root folder of a project.
+pub const MANIFEST_FILE_NAME: &str = "fyrox-project.toml";
+
+/// Every optional feature of the engine, that could be listed in a project manifest.
+///
+/// - `2d` - 2D scenes, physics and rendering.
+/// - `3d` - 3D scenes, physics and rendering.
+/// - `sound` - sound engine and sound sources in scenes.
+/// - `debug_draw` - scene debug drawing, it is compiled in by `debug_draw` cargo feature (enabled
+/// by default).
+/// - `profiler` - built-in profiler, it is compiled in by `enable_profiler` cargo feature.
+/// - `hot_reload` - automatic reloading of changed resources, it is not available on WebAssembly.
+pub const KNOWN_FEATURES: [&str; 6] = ["2d", "3d", "sound", "debug_draw", "profiler", "hot_reload"];
+
+/// Returns a list of features that were compiled into the engine.
+pub fn enabled_features() -> Vec<&'static str> {
+    KNOWN_FEATURES
+        .iter()
+        .copied()
+        .filter(|feature| match *feature {
+            "debug_draw" => cfg!(feature = "debug_draw"),
+            "profiler" => cfg!(feature = "enable_profiler"),
+            "hot_reload" => !cfg!(target_arch = "wasm32"),
+            _ => true,
+        })
+        .collect()
+}
+
+/// An error that may occur when a project manifest is loaded or validated.
+#[derive(Debug)]
+pub enum ProjectManifestError {
+    /// Unable to read the manifest file.
+    Io {
+        /// A path of the manifest.
+        path: PathBuf,
+        /// Actual error.
+        error: std::io::Error,
+    },
+    /// The manifest file has invalid syntax or unknown fields.
+    Parse {
+        /// A path of the manifest.
+        path: PathBuf,
+        /// Actual error.
+        error: toml::de::Error,
+    },
+    /// The manifest does not match the features of the engine.
+    Features {
+        /// Features that are required by the project, but were compiled out of the engine.
+        missing: Vec<String>,
+        /// Features that are excluded by the project, but were compiled into the engine.
+        excluded: Vec<String>,
+        /// Features that are unknown to the engine, most likely a typo.
+        unknown: Vec<String>,
+    },
+}
+
+impl Display for ProjectManifestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectManifestError::Io { path, error } => write!(
+                f,
+                "Unable to read project manifest {}. Reason: {}",
+                path.display(),
+                error
+            ),
+            ProjectManifestError::Parse { path, error } => write!(
+                f,
+                "Project manifest {} is malformed. Reason: {}",
+                path.display(),
+                error
+            ),
+            ProjectManifestError::Features {
+                missing,
+                excluded,
+                unknown,
+            } => {
+                writeln!(f, "The engine does not match the project manifest:")?;
+                if !missing.is_empty() {
+                    writeln!(f, "- required features are missing: {}", missing.join(", "))?;
+                }
+                if !excluded.is_empty() {
+                    writeln!(
+                        f,
+                        "- excluded features are present: {}",
+                        excluded.join(", ")
+                    )?;
+                }
+                if !unknown.is_empty() {
+                    writeln!(f, "- unknown features: {}", unknown.join(", "))?;
+                }
+                write!(
+                    f,
+                    "Rebuild the engine with the respective cargo features or change the manifest. \
+                    Known features are: {}.",
+                    KNOWN_FEATURES.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProjectManifestError {}
+
+/// Project manifest describes a project for the engine and the editor. It is read at startup by
+/// the game executor and the editor, the engine validates that every feature required by the
+/// project was compiled in (and every excluded feature was compiled out), so a project never runs
+/// with a wrong engine build. The manifest is available to plugins via
+/// [`crate::plugin::PluginContext::project`].
+///
+/// # Example
+///
+/// ```toml
+/// name = "My Game"
+/// data_root = "data"
+/// main_scene = "data/scenes/main.rgs"
+/// scenes = ["data/scenes/level1.rgs", "data/scenes/level2.rgs"]
+/// required_features = ["3d", "sound"]
+/// # Debug features must never be shipped.
+/// excluded_features = ["debug_draw", "profiler"]
+/// ```
+///
+/// Every field is optional. Paths are relative to the root folder of the project.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectManifest {
+    /// Name of the project.
+    pub name: String,
+    /// A folder with the assets of the project. The editor shows its content in the asset browser.
+    pub data_root: PathBuf,
+    /// A scene that should be loaded first. The engine does not load the scene by itself, it is up
+    /// to plugins to decide what to do with it.
+    pub main_scene: Option<PathBuf>,
+    /// Other scenes of the game, that are loaded by plugins. Together with the main scene they're
+    /// the starting points of the game data export - only resources that are used by the scenes
+    /// get into a build of the game.
+    pub scenes: Vec<PathBuf>,
+    /// Features that must be compiled into the engine, see [`KNOWN_FEATURES`].
+    pub required_features: Vec<String>,
+    /// Features that must not be compiled into the engine, for example debug features for
+    /// shipping builds. See [`KNOWN_FEATURES`].
+    pub excluded_features: Vec<String>,
+}
+
+impl Default for ProjectManifest {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            data_root: PathBuf::from("."),
+            main_scene: None,
+            scenes: Default::default(),
+            required_features: Default::default(),
+            excluded_features: Default::default(),
+        }
+    }
+}
+
+impl FromStr for ProjectManifest {
+    type Err = toml::de::Error;
+
+    /// Parses a manifest from a string in TOML format.
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        toml::from_str(source)
+    }
+}
+
+impl ProjectManifest {
+    /// Loads the manifest from the given project folder. Returns `Ok(None)` if there is no
+    /// manifest in the folder.
+    pub fn load<P: AsRef<Path>>(project_dir: P) -> Result<Option<Self>, ProjectManifestError> {
+        let path = project_dir.as_ref().join(MANIFEST_FILE_NAME);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let source = std::fs::read_to_string(&path).map_err(|error| ProjectManifestError::Io {
+            path: path.clone(),
+            error,
+        })?;
+
+        source
+            .parse::<Self>()
+            .map(Some)
+            .map_err(|error| ProjectManifestError::Parse { path, error })
+    }
+
+    /// Checks whether the features of the project match the given set of enabled features.
+    /// Returns a single error that lists every mismatch.
+    pub fn validate_features(&self, enabled: &[&str]) -> Result<(), ProjectManifestError> {
+        let unknown = self
+            .required_features
+            .iter()
+            .chain(self.excluded_features.iter())
+            .filter(|feature| !KNOWN_FEATURES.contains(&feature.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let missing = self
+            .required_features
+            .iter()
+            .filter(|feature| {
+                KNOWN_FEATURES.contains(&feature.as_str()) && !enabled.contains(&feature.as_str())
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let excluded = self
+            .excluded_features
+            .iter()
+            .filter(|feature| enabled.contains(&feature.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() && excluded.is_empty() && unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(ProjectManifestError::Features {
+                missing,
+                excluded,
+                unknown,
+            })
+        }
+    }
+
+    /// Checks whether the features of the project match the features of the engine, see
+    /// [`enabled_features`].
+    pub fn validate(&self) -> Result<(), ProjectManifestError> {
+        self.validate_features(&enabled_features())
+    }
+
+    /// Returns the main scene followed by other scenes of the project.
+    pub fn all_scenes(&self) -> impl Iterator<Item = &Path> {
+        self.main_scene
+            .iter()
+            .chain(self.scenes.iter())
+            .map(|path| path.as_path())
+    }
+
+    /// Loads the manifest from the given project folder and validates it, see [`Self::load`] and
+    /// [`Self::validate`].
+    pub fn load_and_validate<P: AsRef<Path>>(
+        project_dir: P,
+    ) -> Result<Option<Self>, ProjectManifestError> {
+        let manifest = Self::load(project_dir)?;
+        if let Some(manifest) = manifest.as_ref() {
+            manifest.validate()?;
+        }
+        Ok(manifest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::engine::project::{ProjectManifest, ProjectManifestError};
+    use std::{path::PathBuf, str::FromStr};
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = ProjectManifest::from_str(
+            r#"
+            name = "My Game"
+            data_root = "data"
+            main_scene = "data/scenes/main.rgs"
+            scenes = ["data/scenes/level1.rgs"]
+            required_features = ["3d", "sound"]
+            excluded_features = ["profiler"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest,
+            ProjectManifest {
+                name: "My Game".to_owned(),
+                data_root: PathBuf::from("data"),
+                main_scene: Some(PathBuf::from("data/scenes/main.rgs")),
+                scenes: vec![PathBuf::from("data/scenes/level1.rgs")],
+                required_features: vec!["3d".to_owned(), "sound".to_owned()],
+                excluded_features: vec!["profiler".to_owned()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_manifest() {
+        assert_eq!(
+            ProjectManifest::from_str("").unwrap(),
+            ProjectManifest::default()
+        );
+    }
+
+    #[test]
+    fn test_parse_malformed_manifest() {
+        assert!(ProjectManifest::from_str("required_features = \"3d\"").is_err());
+        assert!(ProjectManifest::from_str("unknown_field = 1").is_err());
+    }
+
+    #[test]
+    fn test_validate_features() {
+        let manifest = ProjectManifest {
+            required_features: vec!["3d".to_owned(), "sound".to_owned()],
+            excluded_features: vec!["profiler".to_owned()],
+            ..Default::default()
+        };
+
+        assert!(manifest.validate_features(&["3d", "sound"]).is_ok());
+
+        match manifest.validate_features(&["2d", "profiler"]) {
+            Err(ProjectManifestError::Features {
+                missing,
+                excluded,
+                unknown,
+            }) => {
+                assert_eq!(missing, vec!["3d".to_owned(), "sound".to_owned()]);
+                assert_eq!(excluded, vec!["profiler".to_owned()]);
+                assert!(unknown.is_empty());
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_validate_unknown_features() {
+        let manifest = ProjectManifest {
+            required_features: vec!["3d".to_owned(), "sund".to_owned()],
+            ..Default::default()
+        };
+
+        let error = manifest.validate_features(&["3d", "sound"]).unwrap_err();
+        match &error {
+            ProjectManifestError::Features {
+                missing,
+                excluded,
+                unknown,
+            } => {
+                // Unknown features are reported only once.
+                assert!(missing.is_empty());
+                assert!(excluded.is_empty());
+                assert_eq!(unknown, &vec!["sund".to_owned()]);
+            }
+            _ => unreachable!(),
+        }
+        assert!(error.to_string().contains("unknown features: sund"));
+    }
+}
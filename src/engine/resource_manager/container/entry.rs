@@ -1,6 +1,6 @@
 //! Resource manager timed entry. It holds strong reference for a resource and a simple timer
 //! variable. When someone uses a resource, the timer variable is reset to default resource
-//! lifetime. Timer gradually decreases its value and once it reaches zero, the entry is deleted.
+//! lifetime. Timer gradually decreases its value and once it reaches zero, the entry is unloaded.
 //! The inner resource might still be in use (have a strong reference to it), the resource data
 //! will be deleted once no one uses the resource.
 
@@ -3,32 +3,68 @@
 
 use crate::{
     asset::{Resource, ResourceData, ResourceLoadError, ResourceState},
-    core::variable::TemplateVariable,
-    core::VecExtensions,
-    engine::resource_manager::{
-        container::{
-            entry::{TimedEntry, DEFAULT_RESOURCE_LIFETIME},
-            event::{ResourceEvent, ResourceEventBroadcaster},
+    core::{instant::Instant, variable::TemplateVariable},
+    engine::{
+        resource_manager::{
+            container::{
+                entry::{TimedEntry, DEFAULT_RESOURCE_LIFETIME},
+                event::{ResourceEvent, ResourceEventBroadcaster},
+            },
+            loader::ResourceLoader,
+            options::ImportOptions,
         },
-        loader::ResourceLoader,
-        options::ImportOptions,
         task::TaskPool,
     },
     utils::log::Log,
 };
-use std::{future::Future, ops::Deref, path::Path, sync::Arc};
+use fxhash::FxHashSet;
+use std::{
+    future::Future,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 pub mod entry;
 pub mod event;
 
+/// Statistics of a sweep of unused resources.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SweepStatistics {
+    /// Amount of unloaded resources.
+    pub unloaded_count: usize,
+    /// Approximate amount of freed memory in bytes, see [`ResourceData::memory_usage`].
+    pub freed_bytes: usize,
+}
+
 pub(crate) trait Container {
     fn try_reload_resource_from_path(&mut self, path: &Path) -> bool;
+
+    fn relocate_resources(&mut self, from: &Path, to: &Path) -> usize;
+
+    fn sweep(
+        &mut self,
+        cursor: &mut usize,
+        deadline: Option<Instant>,
+        force: bool,
+        statistics: &mut SweepStatistics,
+        unloaded_keys: &mut Vec<usize>,
+    ) -> bool;
 }
 
 /// Generic container for any resource in the engine. Main purpose of the container is to
-/// track resources life time and remove unused timed-out resources. It also provides useful
+/// track resources life time and unload unused timed-out resources. It also provides useful
 /// methods to search resources, count loaded or pending, wait until all resources are loading,
 /// etc.
+///
+/// # Unloading
+///
+/// A resource that is used only by the container is unloaded when its time to live runs out,
+/// unloading is done by sweeps of the resource manager (see
+/// [`crate::engine::resource_manager::ResourceManagerState::unload_unused`]). The container
+/// remembers paths of unloaded resources and a resource is transparently loaded again when it
+/// is requested. Resources that must stay in memory even if they're not used (for example, UI
+/// atlases) could be protected from unloading by [`Self::pin`].
 pub struct ResourceContainer<T, O>
 where
     T: Clone + 'static,
@@ -38,6 +74,8 @@ where
     default_import_options: O,
     task_pool: Arc<TaskPool>,
     loader: Box<dyn ResourceLoader<T, O>>,
+    pinned: FxHashSet<usize>,
+    unloaded: FxHashSet<PathBuf>,
 
     /// Event broadcaster can be used to "subscribe" for events happening inside the container.    
     pub event_broadcaster: ResourceEventBroadcaster<T>,
@@ -56,6 +94,8 @@ where
             default_import_options: Default::default(),
             task_pool,
             loader,
+            pinned: Default::default(),
+            unloaded: Default::default(),
             event_broadcaster: ResourceEventBroadcaster::new(),
         }
     }
@@ -93,38 +133,133 @@ where
         None
     }
 
-    /// Tracks life time of resource and removes unused resources after some time of idling.
+    /// Changes paths of every resource that was loaded from `from` path. The path could be either a
+    /// file or a folder, in the latter case every resource inside the folder (including
+    /// sub-folders) will be relocated to the respective path inside `to` folder. Returns the amount
+    /// of relocated resources.
+    ///
+    /// The method does not touch the file system, it only fixes paths of resources in memory after
+    /// their files were moved.
+    pub fn relocate<P: AsRef<Path>, Q: AsRef<Path>>(&mut self, from: P, to: Q) -> usize {
+        let mut count = 0;
+        for resource in self.resources.iter() {
+            let mut state = resource.state();
+            let new_path = state.path().strip_prefix(from.as_ref()).ok().map(|rest| {
+                if rest.as_os_str().is_empty() {
+                    to.as_ref().to_owned()
+                } else {
+                    to.as_ref().join(rest)
+                }
+            });
+            if let Some(new_path) = new_path {
+                state.set_path(new_path);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Tracks life time of resources. Resources are not unloaded here, timed-out resources are
+    /// unloaded by sweeps, see [`Self::sweep`].
     pub fn update(&mut self, dt: f32) {
-        self.resources.retain_mut_ext(|resource| {
+        for resource in self.resources.iter_mut() {
             // One usage means that the resource has single owner, and that owner
             // is this container. Such resources have limited life time, if the time
-            // runs out before it gets shared again, the resource will be deleted.
+            // runs out before it gets shared again, the resource will be unloaded.
             if resource.use_count() <= 1 {
                 resource.time_to_live -= dt;
-                if resource.time_to_live <= 0.0 {
-                    let path = resource.state().path().to_path_buf();
-
-                    Log::info(format!(
-                        "Resource {} destroyed because it is not used anymore!",
-                        path.display()
-                    ));
-
-                    self.event_broadcaster
-                        .broadcast(ResourceEvent::Removed(path));
-
-                    false
-                } else {
-                    // Keep resource alive for short period of time.
-                    true
-                }
             } else {
                 // Make sure to reset timer if a resource is used by more than one owner.
                 resource.time_to_live = DEFAULT_RESOURCE_LIFETIME;
+            }
+        }
+    }
+
+    /// Protects the resource from unloading, even if it is not used anywhere. Returns `false` if
+    /// the resource does not belong to the container.
+    pub fn pin(&mut self, resource: &T) -> bool {
+        let key = resource.key();
+        if self.resources.iter().any(|r| r.value.key() == key) {
+            self.pinned.insert(key);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Allows the resource to be unloaded again. Returns `false` if the resource was not pinned.
+    pub fn unpin(&mut self, resource: &T) -> bool {
+        self.pinned.remove(&resource.key())
+    }
+
+    /// Returns `true` if the resource is protected from unloading, see [`Self::pin`].
+    pub fn is_pinned(&self, resource: &T) -> bool {
+        self.pinned.contains(&resource.key())
+    }
+
+    /// Returns `true` if a resource at the given path was unloaded and was not requested since
+    /// then.
+    pub fn is_unloaded<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.unloaded.contains(path.as_ref())
+    }
+
+    /// Returns an iterator over paths of unloaded resources that were not requested again.
+    pub fn unloaded(&self) -> impl Iterator<Item = &Path> {
+        self.unloaded.iter().map(|p| p.as_path())
+    }
+
+    fn is_unloadable(&self, entry: &TimedEntry<T>, force: bool) -> bool {
+        entry.value.use_count() <= 1
+            && (force || entry.time_to_live <= 0.0)
+            && !self.pinned.contains(&entry.value.key())
+    }
 
-                // Keep resource alive while it has more than one owner.
-                true
+    /// Unloads unused resources starting from the `cursor` position, until the `deadline` is
+    /// reached. Only timed-out resources are unloaded, unless `force` is set - in this case every
+    /// unused resource is unloaded. Pinned resources are never unloaded. Keys of unloaded
+    /// resources are added to `unloaded_keys`, so caches of the renderer could be invalidated.
+    /// Returns `true` if every resource was checked, `false` - if the sweep must be continued
+    /// from the `cursor` later.
+    pub fn sweep(
+        &mut self,
+        cursor: &mut usize,
+        deadline: Option<Instant>,
+        force: bool,
+        statistics: &mut SweepStatistics,
+        unloaded_keys: &mut Vec<usize>,
+    ) -> bool {
+        while *cursor < self.resources.len() {
+            if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+                return false;
             }
-        });
+
+            if !self.is_unloadable(&self.resources[*cursor], force) {
+                *cursor += 1;
+                continue;
+            }
+
+            let entry = self.resources.remove(*cursor);
+            let path = entry.value.state().path().to_path_buf();
+
+            statistics.unloaded_count += 1;
+            if let ResourceState::Ok(data) = &*entry.value.state() {
+                statistics.freed_bytes += data.memory_usage();
+            }
+            unloaded_keys.push(entry.value.key());
+
+            Log::info(format!(
+                "Resource {} was unloaded because it is not used anymore!",
+                path.display()
+            ));
+
+            self.event_broadcaster
+                .broadcast(ResourceEvent::Removed(path.clone()));
+
+            // Only the path is kept, the data is dropped together with the entry.
+            self.unloaded.insert(path);
+        }
+
+        true
     }
 
     /// Returns total amount of resources in the container.
@@ -142,10 +277,13 @@ where
         self.resources.iter().map(|entry| &entry.value)
     }
 
-    /// Immediately destroys all resources in the container that are not used anywhere else.
+    /// Immediately destroys all resources in the container that are not used anywhere else,
+    /// except pinned ones.
     pub fn destroy_unused(&mut self) {
-        self.resources
-            .retain(|resource| resource.value.use_count() > 1);
+        let pinned = &self.pinned;
+        self.resources.retain(|resource| {
+            resource.value.use_count() > 1 || pinned.contains(&resource.value.key())
+        });
     }
 
     /// Returns total amount of resources that still loading.
@@ -159,6 +297,14 @@ where
         })
     }
 
+    /// Returns keys (see [`Resource::key`]) of resources in pending state.
+    pub fn pending_resource_keys(&self) -> impl Iterator<Item = usize> + '_ {
+        self.resources
+            .iter()
+            .filter(|resource| matches!(*resource.state(), ResourceState::Pending { .. }))
+            .map(|resource| resource.key())
+    }
+
     /// Returns total amount of completely loaded resources.
     pub fn count_loaded_resources(&self) -> usize {
         self.resources.iter().fold(0, |counter, resource| {
@@ -212,6 +358,13 @@ where
         match self.find(path.as_ref()) {
             Some(existing) => existing.clone(),
             None => {
+                if self.unloaded.remove(path.as_ref()) {
+                    Log::info(format!(
+                        "Resource {} was unloaded before, loading it again...",
+                        path.as_ref().display()
+                    ));
+                }
+
                 let resource = T::from(Resource::new(ResourceState::new_pending(
                     path.as_ref().to_owned(),
                 )));
@@ -310,4 +463,234 @@ where
             false
         }
     }
+
+    fn relocate_resources(&mut self, from: &Path, to: &Path) -> usize {
+        self.relocate(from, to)
+    }
+
+    fn sweep(
+        &mut self,
+        cursor: &mut usize,
+        deadline: Option<Instant>,
+        force: bool,
+        statistics: &mut SweepStatistics,
+        unloaded_keys: &mut Vec<usize>,
+    ) -> bool {
+        ResourceContainer::sweep(self, cursor, deadline, force, statistics, unloaded_keys)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{futures::executor::block_on, instant::Instant},
+        engine::{
+            resource_manager::{
+                container::{
+                    entry::DEFAULT_RESOURCE_LIFETIME, event::ResourceEventBroadcaster,
+                    ResourceContainer, SweepStatistics,
+                },
+                loader::{BoxedLoaderFuture, ResourceLoader},
+            },
+            task::TaskPool,
+        },
+        resource::texture::{
+            Texture, TextureData, TextureImportOptions, TextureKind, TexturePixelKind,
+        },
+    };
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    const TEXTURE_SIZE: usize = 4 * 4 * 4;
+
+    struct TestLoader {
+        loads: Arc<AtomicUsize>,
+    }
+
+    impl ResourceLoader<Texture, TextureImportOptions> for TestLoader {
+        fn load(
+            &self,
+            texture: Texture,
+            _default_import_options: TextureImportOptions,
+            event_broadcaster: ResourceEventBroadcaster<Texture>,
+            reload: bool,
+        ) -> BoxedLoaderFuture {
+            let loads = self.loads.clone();
+            Box::pin(async move {
+                loads.fetch_add(1, Ordering::SeqCst);
+                let path = texture.state().path().to_path_buf();
+                let mut data = TextureData::from_bytes(
+                    TextureKind::Rectangle {
+                        width: 4,
+                        height: 4,
+                    },
+                    TexturePixelKind::RGBA8,
+                    vec![0; TEXTURE_SIZE],
+                    false,
+                )
+                .unwrap();
+                data.set_path(path);
+                texture.state().commit_ok(data);
+                event_broadcaster.broadcast_loaded_or_reloaded(texture, reload);
+            })
+        }
+    }
+
+    fn make_container() -> (
+        ResourceContainer<Texture, TextureImportOptions>,
+        Arc<AtomicUsize>,
+    ) {
+        let loads = Arc::new(AtomicUsize::new(0));
+        let container = ResourceContainer::new(
+            Arc::new(TaskPool::new()),
+            Box::new(TestLoader {
+                loads: loads.clone(),
+            }),
+        );
+        (container, loads)
+    }
+
+    // A loading task could hold its copy of a resource for a short time after the resource was
+    // loaded.
+    fn wait_until_unused(container: &ResourceContainer<Texture, TextureImportOptions>) {
+        let start = Instant::now();
+        while container.iter().any(|r| r.use_count() > 1)
+            && start.elapsed() < Duration::from_secs(10)
+        {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn wait_until_unused_except(
+        container: &ResourceContainer<Texture, TextureImportOptions>,
+        used: &Texture,
+    ) {
+        let start = Instant::now();
+        while container.iter().any(|r| r != used && r.use_count() > 1)
+            && start.elapsed() < Duration::from_secs(10)
+        {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn sweep(
+        container: &mut ResourceContainer<Texture, TextureImportOptions>,
+        force: bool,
+    ) -> (SweepStatistics, Vec<usize>) {
+        let mut statistics = SweepStatistics::default();
+        let mut keys = Vec::new();
+        assert!(container.sweep(&mut 0, None, force, &mut statistics, &mut keys));
+        (statistics, keys)
+    }
+
+    #[test]
+    fn test_unused_resource_is_unloaded_and_reloaded() {
+        let (mut container, loads) = make_container();
+
+        let texture = container.request("level1/wall.png");
+        assert!(block_on(texture.clone()).is_ok());
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+        // Resources that are in use are never unloaded.
+        container.update(DEFAULT_RESOURCE_LIFETIME + 1.0);
+        assert_eq!(sweep(&mut container, true).0, SweepStatistics::default());
+
+        // The scene that used the texture was dropped.
+        let key = texture.key();
+        drop(texture);
+        wait_until_unused(&container);
+
+        // Time to live has not run out yet.
+        assert_eq!(sweep(&mut container, false).0.unloaded_count, 0);
+
+        container.update(DEFAULT_RESOURCE_LIFETIME + 1.0);
+        let (statistics, keys) = sweep(&mut container, false);
+        assert_eq!(
+            statistics,
+            SweepStatistics {
+                unloaded_count: 1,
+                freed_bytes: TEXTURE_SIZE
+            }
+        );
+        assert_eq!(keys, vec![key]);
+        assert!(container.is_empty());
+        assert!(container.is_unloaded("level1/wall.png"));
+
+        // The texture is needed again.
+        let texture = container.request("level1/wall.png");
+        assert!(!container.is_unloaded("level1/wall.png"));
+        assert!(block_on(texture.clone()).is_ok());
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+        assert_eq!(texture.data_ref().data().len(), TEXTURE_SIZE);
+    }
+
+    #[test]
+    fn test_pinned_resource_is_not_unloaded() {
+        let (mut container, _) = make_container();
+
+        let atlas = container.request("ui/atlas.png");
+        assert!(block_on(atlas.clone()).is_ok());
+        assert!(container.pin(&atlas));
+        assert!(container.is_pinned(&atlas));
+
+        let other = container.request("ui/other.png");
+        assert!(block_on(other.clone()).is_ok());
+        drop(other);
+        wait_until_unused_except(&container, &atlas);
+
+        let (statistics, _) = sweep(&mut container, true);
+        assert_eq!(statistics.unloaded_count, 1);
+        assert_eq!(container.len(), 1);
+
+        // Pinned resources are kept even if they're not used anywhere.
+        let atlas_key = atlas.key();
+        drop(atlas);
+        wait_until_unused(&container);
+        assert_eq!(sweep(&mut container, true).0.unloaded_count, 0);
+        assert_eq!(container.len(), 1);
+
+        let atlas = container.request("ui/atlas.png");
+        assert_eq!(atlas.key(), atlas_key);
+        assert!(container.unpin(&atlas));
+        drop(atlas);
+        wait_until_unused(&container);
+        assert_eq!(sweep(&mut container, true).0.unloaded_count, 1);
+        assert!(container.is_unloaded("ui/atlas.png"));
+    }
+
+    #[test]
+    fn test_sweep_is_time_sliced() {
+        let (mut container, _) = make_container();
+
+        for path in ["a.png", "b.png", "c.png"] {
+            assert!(block_on(container.request(path)).is_ok());
+        }
+        wait_until_unused(&container);
+        container.update(DEFAULT_RESOURCE_LIFETIME + 1.0);
+
+        // No time left in the frame, the sweep must be continued later.
+        let mut cursor = 0;
+        let mut statistics = SweepStatistics::default();
+        let mut keys = Vec::new();
+        assert!(!container.sweep(
+            &mut cursor,
+            Some(Instant::now()),
+            false,
+            &mut statistics,
+            &mut keys
+        ));
+        assert_eq!(statistics.unloaded_count, 0);
+        assert_eq!(container.len(), 3);
+
+        assert!(container.sweep(&mut cursor, None, false, &mut statistics, &mut keys));
+        assert_eq!(statistics.unloaded_count, 3);
+        assert_eq!(statistics.freed_bytes, 3 * TEXTURE_SIZE);
+        assert_eq!(keys.len(), 3);
+        assert!(container.is_empty());
+    }
 }
@@ -5,7 +5,7 @@ use crate::{
     engine::resource_manager::{
         container::event::ResourceEventBroadcaster,
         loader::{BoxedLoaderFuture, ResourceLoader},
-        options::try_get_import_settings,
+        options::resolve_import_options,
         ResourceManager,
     },
     resource::model::{Model, ModelData, ModelImportOptions},
@@ -36,9 +36,7 @@ impl ResourceLoader<Model, ModelImportOptions> for ModelLoader {
         Box::pin(async move {
             let path = model.state().path().to_path_buf();
 
-            let import_options = try_get_import_settings(&path)
-                .await
-                .unwrap_or(default_import_options);
+            let (import_options, _) = resolve_import_options(&path, default_import_options).await;
 
             match ModelData::load(&path, node_constructors, resource_manager, import_options).await
             {
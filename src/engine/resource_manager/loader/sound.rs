@@ -5,7 +5,7 @@ use crate::{
     engine::resource_manager::{
         container::event::ResourceEventBroadcaster,
         loader::{BoxedLoaderFuture, ResourceLoader},
-        options::{try_get_import_settings, ImportOptions},
+        options::{resolve_import_options, ImportOptions},
     },
     utils::log::Log,
 };
@@ -17,11 +17,16 @@ use serde::{Deserialize, Serialize};
 /// Defines sound buffer resource import options.
 #[derive(Clone, Deserialize, Serialize, Default, Inspect)]
 pub struct SoundBufferImportOptions {
-    /// Whether the buffer is streaming or not.
+    /// Whether the buffer is streaming or not. Streaming buffers keep the file opened and decode
+    /// it in small blocks while playing, instead of decoding the whole file into memory at once.
+    /// It is the best choice for long sounds, like music or ambience. Ignored on WebAssembly,
+    /// sounds are always fully decoded there.
     pub stream: bool,
 }
 
-impl ImportOptions for SoundBufferImportOptions {}
+impl ImportOptions for SoundBufferImportOptions {
+    const FOLDER_OPTIONS_FILE_NAME: &'static str = "folder.sound.options";
+}
 
 /// Default implementation for sound buffer loading.
 pub struct SoundBufferLoader;
@@ -37,19 +42,24 @@ impl ResourceLoader<SoundBufferResource, SoundBufferImportOptions> for SoundBuff
         Box::pin(async move {
             let path = resource.state().path().to_path_buf();
 
-            let import_options = try_get_import_settings(&path)
-                .await
-                .unwrap_or(default_import_options);
+            let (import_options, _) = resolve_import_options(&path, default_import_options).await;
 
             match DataSource::from_file(&path).await {
                 Ok(source) => {
-                    let buffer = if import_options.stream {
+                    // There is no way to read a part of a file on WebAssembly, the file is
+                    // loaded into memory anyway, so fall back to the full decoding.
+                    let stream = import_options.stream && !cfg!(target_arch = "wasm32");
+                    let buffer = if stream {
                         SoundBufferState::raw_streaming(source)
                     } else {
                         SoundBufferState::raw_generic(source)
                     };
                     match buffer {
-                        Ok(sound_buffer) => {
+                        Ok(mut sound_buffer) => {
+                            // Files from mounted archives are read into memory, such sources do
+                            // not know their paths.
+                            sound_buffer.set_external_data_path(path.clone());
+
                             resource.state().commit_ok(sound_buffer);
 
                             event_broadcaster.broadcast_loaded_or_reloaded(resource, reload);
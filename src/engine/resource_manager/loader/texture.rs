@@ -5,7 +5,7 @@ use crate::{
     engine::resource_manager::{
         container::event::ResourceEventBroadcaster,
         loader::{BoxedLoaderFuture, ResourceLoader},
-        options::try_get_import_settings,
+        options::resolve_import_options,
     },
     resource::texture::{Texture, TextureData, TextureImportOptions},
     utils::log::Log,
@@ -25,15 +25,10 @@ impl ResourceLoader<Texture, TextureImportOptions> for TextureLoader {
         Box::pin(async move {
             let path = texture.state().path().to_path_buf();
 
-            let import_options = try_get_import_settings(&path)
-                .await
-                .unwrap_or(default_import_options);
-
-            let gen_mip_maps = import_options.minification_filter.is_using_mip_mapping();
+            let (import_options, _) = resolve_import_options(&path, default_import_options).await;
 
             let time = instant::Instant::now();
-            match TextureData::load_from_file(&path, import_options.compression, gen_mip_maps).await
-            {
+            match TextureData::load_from_file(&path, &import_options).await {
                 Ok(mut raw_texture) => {
                     Log::info(format!(
                         "Texture {:?} is loaded in {:?}!",
@@ -1,14 +1,35 @@
 //! Resource manager controls loading and lifetime of resource in the engine.
+//!
+//! # Unloading
+//!
+//! Resources that are not used anywhere (for example, textures of a level that was unloaded) are
+//! unloaded automatically when their time to live runs out. Unused resources are searched by
+//! periodic sweeps, each sweep is split across multiple frames so it never takes more than a
+//! small time budget per frame (see [`ResourceManagerState::set_sweep_time_budget`]). Unloaded
+//! resources are loaded again when they're requested. Use
+//! [`ResourceManagerState::unload_unused`] to unload every unused resource immediately (for
+//! example, after a level change) and [`ResourceContainer::pin`] to protect resources from
+//! unloading.
+//!
+//! # Resource IO
+//!
+//! Resources are read through [`crate::core::io`], so they could be loaded not only from the file
+//! system, but also from zip archives, data embedded in the executable, etc. Use
+//! [`ResourceManager::mount_io`] to mount such sources, paths of requested resources stay the same
+//! regardless of where the data actually comes from. Hot reloading works only for resources that
+//! are loaded from watchable sources (the file system).
 
 use crate::{
     core::{
-        futures::future::join_all,
+        futures::{executor::block_on, future::join_all},
+        instant::Instant,
+        io::{self, ResourceIo},
         make_relative_path,
         parking_lot::{Mutex, MutexGuard},
     },
     engine::{
         resource_manager::{
-            container::{Container, ResourceContainer},
+            container::{Container, ResourceContainer, SweepStatistics},
             loader::{
                 absm::AbsmLoader,
                 curve::CurveLoader,
@@ -18,8 +39,8 @@ use crate::{
                 texture::TextureLoader,
                 ResourceLoader,
             },
-            task::TaskPool,
         },
+        task::TaskPool,
         SerializationContext,
     },
     material::shader::{Shader, ShaderImportOptions},
@@ -31,14 +52,21 @@ use crate::{
     },
     utils::{log::Log, watcher::FileSystemWatcher},
 };
+use fxhash::FxHashSet;
 use fyrox_sound::buffer::SoundBufferResource;
 use notify::DebouncedEvent;
-use std::{path::Path, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+    time::Duration,
+};
 
 pub mod container;
 pub mod loader;
 pub mod options;
-mod task;
 
 /// Storage of resource containers.
 pub struct ContainersStorage {
@@ -109,18 +137,52 @@ impl ContainersStorage {
     {
         self.absm.set_loader(loader);
     }
+
+    // Textures go first, the order is used by sweeps to find out which unloaded resources have
+    // copies in GPU memory.
+    fn as_dyn_array_mut(&mut self) -> [&mut dyn Container; 6] {
+        [
+            &mut self.textures as &mut dyn Container,
+            &mut self.models as &mut dyn Container,
+            &mut self.sound_buffers as &mut dyn Container,
+            &mut self.shaders as &mut dyn Container,
+            &mut self.curves as &mut dyn Container,
+            &mut self.absm as &mut dyn Container,
+        ]
+    }
+}
+
+/// Default interval between sweeps of unused resources in seconds.
+pub const DEFAULT_SWEEP_INTERVAL: f32 = 1.0;
+
+/// Default time that a sweep of unused resources could take in a frame.
+pub const DEFAULT_SWEEP_TIME_BUDGET: Duration = Duration::from_micros(500);
+
+#[derive(Default)]
+struct SweepProgress {
+    container: usize,
+    cursor: usize,
+    statistics: SweepStatistics,
 }
 
 /// See module docs.
 pub struct ResourceManagerState {
     containers_storage: Option<ContainersStorage>,
     watcher: Option<FileSystemWatcher>,
+    file_change_subscribers: Vec<Sender<PathBuf>>,
+    sweep_interval: f32,
+    sweep_time_budget: Duration,
+    sweep_timer: f32,
+    sweep: Option<SweepProgress>,
+    last_sweep_statistics: SweepStatistics,
+    unloaded_textures: Vec<usize>,
 }
 
 /// See module docs.
 #[derive(Clone)]
 pub struct ResourceManager {
     state: Arc<Mutex<ResourceManagerState>>,
+    task_pool: Arc<TaskPool>,
 }
 
 /// An error that may occur during texture registration.
@@ -146,12 +208,13 @@ impl From<TextureError> for TextureRegistrationError {
 impl ResourceManager {
     /// Creates a resource manager with default settings and loaders.
     pub fn new(serialization_context: Arc<SerializationContext>) -> Self {
+        let task_pool = Arc::new(TaskPool::new());
+
         let resource_manager = Self {
             state: Arc::new(Mutex::new(ResourceManagerState::new())),
+            task_pool: task_pool.clone(),
         };
 
-        let task_pool = Arc::new(TaskPool::new());
-
         resource_manager.state().containers_storage = Some(ContainersStorage {
             textures: ResourceContainer::new(task_pool.clone(), Box::new(TextureLoader)),
             models: ResourceContainer::new(
@@ -170,6 +233,25 @@ impl ResourceManager {
         resource_manager
     }
 
+    /// Returns a reference to the task pool that is used to load resources. The pool is shared
+    /// across the engine, see [`TaskPool`] docs for more info.
+    pub fn task_pool(&self) -> Arc<TaskPool> {
+        self.task_pool.clone()
+    }
+
+    /// Mounts a source of resource data at the given path prefix. Sources with higher priority are
+    /// checked first, so they could be used to override data of other sources (for example, a
+    /// patch archive on top of base game data). Mount points are global, they're shared by every
+    /// resource manager. See [`io::mount_at`] for more info.
+    pub fn mount_io<P: AsRef<Path>>(&self, prefix: P, priority: i32, io: Arc<dyn ResourceIo>) {
+        io::mount_at(prefix, priority, io)
+    }
+
+    /// Unmounts every mount point of the given source. Returns `true` if the source was mounted.
+    pub fn unmount_io(&self, io: &Arc<dyn ResourceIo>) -> bool {
+        io::unmount(io)
+    }
+
     /// Returns a guarded reference to internal state of resource manager.
     pub fn state(&self) -> MutexGuard<'_, ResourceManagerState> {
         self.state.lock()
@@ -371,6 +453,13 @@ impl ResourceManagerState {
         Self {
             containers_storage: None,
             watcher: None,
+            file_change_subscribers: Default::default(),
+            sweep_interval: DEFAULT_SWEEP_INTERVAL,
+            sweep_time_budget: DEFAULT_SWEEP_TIME_BUDGET,
+            sweep_timer: DEFAULT_SWEEP_INTERVAL,
+            sweep: None,
+            last_sweep_statistics: Default::default(),
+            unloaded_textures: Default::default(),
         }
     }
 
@@ -382,6 +471,16 @@ impl ResourceManagerState {
         self.watcher = watcher;
     }
 
+    /// Subscribes to file system changes tracked by the watcher (see [`Self::set_watcher`]). The
+    /// returned receiver will get a path (relative to the working directory) of every changed file,
+    /// not only resources. This allows you to track changes of arbitrary files without creating
+    /// another watcher. A subscriber is automatically removed when its receiver is dropped.
+    pub fn subscribe_to_file_changes(&mut self) -> Receiver<PathBuf> {
+        let (sender, receiver) = channel();
+        self.file_change_subscribers.push(sender);
+        receiver
+    }
+
     /// Returns a reference to resource containers storage.
     pub fn containers(&self) -> &ContainersStorage {
         self.containers_storage
@@ -407,6 +506,22 @@ impl ResourceManagerState {
             + containers.absm.count_pending_resources()
     }
 
+    /// Returns keys (see [`crate::asset::Resource::key`]) of every resource in pending state. It
+    /// could be used to wait for a specific set of resources - a resource is loaded (or failed to
+    /// load) when its key is no longer in the set.
+    pub fn pending_resource_keys(&self) -> FxHashSet<usize> {
+        let containers = self.containers();
+        containers
+            .textures
+            .pending_resource_keys()
+            .chain(containers.sound_buffers.pending_resource_keys())
+            .chain(containers.models.pending_resource_keys())
+            .chain(containers.shaders.pending_resource_keys())
+            .chain(containers.curves.pending_resource_keys())
+            .chain(containers.absm.pending_resource_keys())
+            .collect()
+    }
+
     /// Returns total amount of loaded resources.
     pub fn count_loaded_resources(&self) -> usize {
         let containers = self.containers();
@@ -442,6 +557,127 @@ impl ResourceManagerState {
         }
     }
 
+    /// Sets interval between sweeps of unused resources in seconds. Zero interval means that a
+    /// new sweep starts right after the previous one.
+    pub fn set_sweep_interval(&mut self, interval: f32) {
+        self.sweep_interval = interval.max(0.0);
+    }
+
+    /// Returns interval between sweeps of unused resources in seconds.
+    pub fn sweep_interval(&self) -> f32 {
+        self.sweep_interval
+    }
+
+    /// Sets maximum time that a sweep of unused resources could take in a frame. A sweep that
+    /// does not fit into the budget is continued in the next frames. Checking a resource is
+    /// cheap, most of the time is spent on freeing data of unloaded resources.
+    pub fn set_sweep_time_budget(&mut self, budget: Duration) {
+        self.sweep_time_budget = budget;
+    }
+
+    /// Returns maximum time that a sweep of unused resources could take in a frame.
+    pub fn sweep_time_budget(&self) -> Duration {
+        self.sweep_time_budget
+    }
+
+    /// Returns statistics of the last finished sweep (either periodic or explicit).
+    pub fn last_sweep_statistics(&self) -> SweepStatistics {
+        self.last_sweep_statistics
+    }
+
+    /// Immediately unloads every resource that is not used anywhere, regardless of its time to
+    /// live. Pinned resources (see [`ResourceContainer::pin`]) are kept. Unloaded resources will
+    /// be loaded again if they're requested. Returns statistics of the sweep.
+    ///
+    /// Unlike periodic sweeps, this method is not time-sliced, so it is better to call it at
+    /// moments when a frame spike is not noticeable, for example, after a level was unloaded.
+    pub fn unload_unused(&mut self) -> SweepStatistics {
+        let mut progress = SweepProgress::default();
+        self.sweep(&mut progress, None, true);
+        self.finish_sweep(progress.statistics);
+        progress.statistics
+    }
+
+    // Returns `true` if every container was checked.
+    fn sweep(
+        &mut self,
+        progress: &mut SweepProgress,
+        deadline: Option<Instant>,
+        force: bool,
+    ) -> bool {
+        let containers_storage = self
+            .containers_storage
+            .as_mut()
+            .expect("Corrupted resource manager!");
+        let containers = containers_storage.as_dyn_array_mut();
+
+        let mut unloaded_keys = Vec::new();
+        while progress.container < containers.len() {
+            let finished = containers[progress.container].sweep(
+                &mut progress.cursor,
+                deadline,
+                force,
+                &mut progress.statistics,
+                &mut unloaded_keys,
+            );
+
+            if progress.container == 0 {
+                self.unloaded_textures.append(&mut unloaded_keys);
+            } else {
+                unloaded_keys.clear();
+            }
+
+            if !finished {
+                return false;
+            }
+
+            progress.container += 1;
+            progress.cursor = 0;
+        }
+
+        true
+    }
+
+    fn finish_sweep(&mut self, statistics: SweepStatistics) {
+        if statistics.unloaded_count > 0 {
+            Log::info(format!(
+                "{} unused resources were unloaded, {} bytes were freed.",
+                statistics.unloaded_count, statistics.freed_bytes
+            ));
+        }
+
+        self.last_sweep_statistics = statistics;
+    }
+
+    // Periodic sweep, it checks resources until the time budget of the frame runs out and
+    // continues in the next frame.
+    fn update_sweep(&mut self, dt: f32) {
+        let mut progress = match self.sweep.take() {
+            Some(progress) => progress,
+            None => {
+                self.sweep_timer -= dt;
+                if self.sweep_timer > 0.0 {
+                    return;
+                }
+                self.sweep_timer = self.sweep_interval;
+                SweepProgress::default()
+            }
+        };
+
+        let deadline = Instant::now() + self.sweep_time_budget;
+        if self.sweep(&mut progress, Some(deadline), false) {
+            self.finish_sweep(progress.statistics);
+        } else {
+            self.sweep = Some(progress);
+        }
+    }
+
+    /// Returns keys of textures that were unloaded since the last call, GPU copies of such
+    /// textures must be removed from the renderer.
+    pub(crate) fn take_unloaded_textures(&mut self) -> Vec<usize> {
+        std::mem::take(&mut self.unloaded_textures)
+    }
+
     /// Immediately destroys all unused resources.
     pub fn destroy_unused_resources(&mut self) {
         let containers = self.containers_mut();
@@ -453,9 +689,28 @@ impl ResourceManagerState {
         containers.absm.destroy_unused();
     }
 
+    /// Changes paths of every resource in every container that was loaded from `from` path (a file
+    /// or a folder) to respective paths at `to`. It must be called after resource files were moved
+    /// to keep resources that are already loaded in sync with the file system. Returns the amount
+    /// of relocated resources. See [`ResourceContainer::relocate`] for more info.
+    pub fn relocate_resources<P: AsRef<Path>, Q: AsRef<Path>>(&mut self, from: P, to: Q) -> usize {
+        let containers = self.containers_mut();
+        [
+            &mut containers.textures as &mut dyn Container,
+            &mut containers.models as &mut dyn Container,
+            &mut containers.sound_buffers as &mut dyn Container,
+            &mut containers.shaders as &mut dyn Container,
+            &mut containers.curves as &mut dyn Container,
+            &mut containers.absm as &mut dyn Container,
+        ]
+        .into_iter()
+        .map(|container| container.relocate_resources(from.as_ref(), to.as_ref()))
+        .sum()
+    }
+
     /// Update resource containers and do hot-reloading.
     ///
-    /// Resources are removed if they're not used
+    /// Resources are unloaded if they're not used
     /// or reloaded if they have changed in disk.
     ///
     /// Normally, this is called from `Engine::update()`.
@@ -469,10 +724,21 @@ impl ResourceManagerState {
         containers.curves.update(dt);
         containers.absm.update(dt);
 
+        self.update_sweep(dt);
+
         if let Some(watcher) = self.watcher.as_ref() {
             if let Some(DebouncedEvent::Write(path)) = watcher.try_get_event() {
                 let relative_path = make_relative_path(path);
 
+                self.file_change_subscribers
+                    .retain(|subscriber| subscriber.send(relative_path.clone()).is_ok());
+
+                // A file could be shadowed by a source that cannot be watched (an archive, for
+                // example), reloading would load the same data again.
+                if !block_on(io::is_watchable(&relative_path)) {
+                    return;
+                }
+
                 Log::info(format!(
                     "File {} was changed, trying to reload a respective resource...",
                     relative_path.display()
@@ -495,3 +761,71 @@ impl ResourceManagerState {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            futures::executor::block_on,
+            io::{ResourceIo, ZipIo},
+            visitor::Visitor,
+        },
+        engine::{resource_manager::ResourceManager, SerializationContext},
+        resource::texture::TextureKind,
+        scene::{base::BaseBuilder, pivot::PivotBuilder, Scene},
+    };
+    use image::{ImageOutputFormat, Rgba, RgbaImage};
+    use std::{
+        io::{Cursor, Write},
+        sync::Arc,
+    };
+    use zip::{write::FileOptions, ZipWriter};
+
+    fn make_archive() -> Vec<u8> {
+        let mut png = Vec::new();
+        RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255]))
+            .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+            .unwrap();
+
+        let mut scene = Scene::new();
+        PivotBuilder::new(BaseBuilder::new().with_name("ZipPivot")).build(&mut scene.graph);
+        let mut visitor = Visitor::new();
+        scene.save("Scene", &mut visitor).unwrap();
+        let rgs = visitor.save_binary_to_vec().unwrap();
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, data) in [("textures/red.png", png), ("scenes/level.rgs", rgs)] {
+            writer.start_file(path, FileOptions::default()).unwrap();
+            writer.write_all(&data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_load_resources_from_zip() {
+        let archive: Arc<dyn ResourceIo> = Arc::new(ZipIo::from_bytes(make_archive()).unwrap());
+        let resource_manager = ResourceManager::new(Arc::new(SerializationContext::new()));
+        resource_manager.mount_io("fyrox_test_zip", 0, archive.clone());
+
+        let texture =
+            block_on(resource_manager.request_texture("fyrox_test_zip/textures/red.png")).unwrap();
+        assert!(matches!(
+            texture.data_ref().kind(),
+            TextureKind::Rectangle {
+                width: 2,
+                height: 2
+            }
+        ));
+
+        let model =
+            block_on(resource_manager.request_model("fyrox_test_zip/scenes/level.rgs")).unwrap();
+        assert!(model
+            .data_ref()
+            .get_scene()
+            .graph
+            .find_by_name_from_root("ZipPivot")
+            .is_some());
+
+        assert!(resource_manager.unmount_io(&archive));
+    }
+}
@@ -1,4 +1,18 @@
 //! Resource import options common traits.
+//!
+//! # Resolution order
+//!
+//! Import options of a resource are taken from the first source that exists:
+//!
+//! 1. Options of the resource itself - a file next to the resource with additional `.options`
+//!    extension (`data/textures/wall.png.options` for `data/textures/wall.png`).
+//! 2. Default options of the nearest folder - a file with [`ImportOptions::FOLDER_OPTIONS_FILE_NAME`]
+//!    name in the folder of the resource or in any of its parent folders, nearest folder wins.
+//! 3. Default import options of a resource container (see
+//!    [`super::container::ResourceContainer::set_default_import_options`]).
+//!
+//! Malformed files are reported to the log and skipped, so the next source is used. See
+//! [`resolve_import_options`] for more info.
 
 use crate::utils::log::{Log, MessageKind};
 use fyrox_core::{append_extension, io};
@@ -6,10 +20,14 @@ use ron::ser::PrettyConfig;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::fs::File;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// A trait for resource import options. It provides generic functionality shared over all types of import options.
 pub trait ImportOptions: Serialize + DeserializeOwned + Default + Clone {
+    /// Name of a file in a folder, that stores default import options of resources of this kind
+    /// in the folder (and its sub-folders). Must be unique across every kind of import options.
+    const FOLDER_OPTIONS_FILE_NAME: &'static str;
+
     /// Saves import options into a specified file.
     fn save(&self, path: &Path) -> bool {
         if let Ok(file) = File::create(path) {
@@ -21,31 +39,65 @@ pub trait ImportOptions: Serialize + DeserializeOwned + Default + Clone {
     }
 }
 
+/// Returns path of a file, that stores own import options of a resource.
+pub fn import_options_path(resource_path: &Path) -> PathBuf {
+    append_extension(resource_path, "options")
+}
+
+/// Returns path of a file, that stores default import options of the folder.
+pub fn folder_import_options_path<T>(folder: &Path) -> PathBuf
+where
+    T: ImportOptions,
+{
+    folder.join(T::FOLDER_OPTIONS_FILE_NAME)
+}
+
+/// Source of import options of a resource, see [module docs](self) for more info.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportOptionsSource {
+    /// The resource has its own import options.
+    Resource,
+    /// Import options are inherited from the default options of the folder.
+    Folder(PathBuf),
+    /// Neither the resource nor its folders have import options, default import options of a
+    /// resource container are used.
+    Default,
+}
+
+fn parse_options<T>(bytes: &[u8], options_path: &Path) -> Option<T>
+where
+    T: ImportOptions,
+{
+    match ron::de::from_bytes::<T>(bytes) {
+        Ok(options) => Some(options),
+        Err(e) => {
+            Log::writeln(
+                MessageKind::Error,
+                format!(
+                    "Malformed options file {}! Reason: {:?}",
+                    options_path.display(),
+                    e
+                ),
+            );
+
+            None
+        }
+    }
+}
+
 /// Tries to load import settings for a resource. It is not part of ImportOptions trait because
 /// `async fn` is not yet supported for traits.
+///
+/// Only own options of the resource are loaded, use [`resolve_import_options`] to take default
+/// options of folders into account.
 pub async fn try_get_import_settings<T>(resource_path: &Path) -> Option<T>
 where
     T: ImportOptions,
 {
-    let settings_path = append_extension(resource_path, "options");
+    let settings_path = import_options_path(resource_path);
 
     match io::load_file(&settings_path).await {
-        Ok(bytes) => match ron::de::from_bytes::<T>(&bytes) {
-            Ok(options) => Some(options),
-            Err(e) => {
-                Log::writeln(
-                    MessageKind::Error,
-                    format!(
-                        "Malformed options file {} for {} resource! Reason: {:?}",
-                        settings_path.display(),
-                        resource_path.display(),
-                        e
-                    ),
-                );
-
-                None
-            }
-        },
+        Ok(bytes) => parse_options(&bytes, &settings_path),
         Err(e) => {
             Log::writeln(
                 MessageKind::Information,
@@ -61,3 +113,133 @@ where
         }
     }
 }
+
+/// Tries to load default import options of the folder. Parent folders are not checked.
+pub async fn try_get_folder_import_options<T>(folder: &Path) -> Option<T>
+where
+    T: ImportOptions,
+{
+    let options_path = folder_import_options_path::<T>(folder);
+
+    if !io::exists(&options_path).await {
+        return None;
+    }
+
+    match io::load_file(&options_path).await {
+        Ok(bytes) => parse_options(&bytes, &options_path),
+        Err(e) => {
+            Log::writeln(
+                MessageKind::Error,
+                format!(
+                    "Unable to load folder options file {}! Reason: {:?}",
+                    options_path.display(),
+                    e
+                ),
+            );
+
+            None
+        }
+    }
+}
+
+/// Finds import options of a resource and tells where they come from. The order is: own options
+/// of the resource, default options of the nearest folder (starting from the folder of the
+/// resource up to the root), the given default options. See [module docs](self) for more info.
+pub async fn resolve_import_options<T>(
+    resource_path: &Path,
+    default_import_options: T,
+) -> (T, ImportOptionsSource)
+where
+    T: ImportOptions,
+{
+    let options_path = import_options_path(resource_path);
+    if io::exists(&options_path).await {
+        if let Some(options) = try_get_import_settings(resource_path).await {
+            return (options, ImportOptionsSource::Resource);
+        }
+    }
+
+    for folder in resource_path.ancestors().skip(1) {
+        if let Some(options) = try_get_folder_import_options(folder).await {
+            return (options, ImportOptionsSource::Folder(folder.to_owned()));
+        }
+    }
+
+    (default_import_options, ImportOptionsSource::Default)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::futures::executor::block_on,
+        engine::resource_manager::options::{
+            folder_import_options_path, import_options_path, resolve_import_options,
+            ImportOptions, ImportOptionsSource,
+        },
+    };
+    use serde::{Deserialize, Serialize};
+    use std::{fs, path::Path};
+
+    #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct TestOptions {
+        value: u32,
+    }
+
+    impl ImportOptions for TestOptions {
+        const FOLDER_OPTIONS_FILE_NAME: &'static str = "folder.test.options";
+    }
+
+    fn resolve(path: &Path) -> (u32, ImportOptionsSource) {
+        let (options, source) = block_on(resolve_import_options(path, TestOptions { value: 1 }));
+        (options.value, source)
+    }
+
+    #[test]
+    fn test_import_options_resolution_order() {
+        let root = std::env::temp_dir().join("fyrox_import_options_resolution");
+        let _ = fs::remove_dir_all(&root);
+        let nested = root.join("textures").join("walls");
+        fs::create_dir_all(&nested).unwrap();
+        let resource = nested.join("brick.png");
+
+        // Global default.
+        assert_eq!(resolve(&resource), (1, ImportOptionsSource::Default));
+
+        // Default options of a folder are inherited by every sub-folder.
+        TestOptions { value: 2 }.save(&folder_import_options_path::<TestOptions>(&root));
+        assert_eq!(
+            resolve(&resource),
+            (2, ImportOptionsSource::Folder(root.clone()))
+        );
+
+        // The nearest folder wins.
+        let textures = root.join("textures");
+        TestOptions { value: 3 }.save(&folder_import_options_path::<TestOptions>(&textures));
+        assert_eq!(
+            resolve(&resource),
+            (3, ImportOptionsSource::Folder(textures.clone()))
+        );
+
+        // Malformed folder options are skipped.
+        fs::write(
+            folder_import_options_path::<TestOptions>(&nested),
+            "not an options file",
+        )
+        .unwrap();
+        assert_eq!(resolve(&resource), (3, ImportOptionsSource::Folder(textures)));
+
+        // Own options of the resource override everything.
+        TestOptions { value: 4 }.save(&import_options_path(&resource));
+        assert_eq!(resolve(&resource), (4, ImportOptionsSource::Resource));
+
+        // Removing the override restores inherited options.
+        fs::remove_file(import_options_path(&resource)).unwrap();
+        fs::remove_file(folder_import_options_path::<TestOptions>(&nested)).unwrap();
+        assert_eq!(
+            resolve(&resource),
+            (3, ImportOptionsSource::Folder(root.join("textures")))
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,377 @@
+//! Task pool of the engine and per-owner task containers. The pool runs heavy work (resource
+//! loading, path finding, procedural generation, etc.) on worker threads, task containers allow
+//! scripts and plugins to offload their work to the pool and get results back on the main thread.
+//!
+//! On WebAssembly there are no worker threads, tasks are scheduled as micro-tasks of the browser
+//! event loop instead. It means that a task still does not block the frame in which it was spawned,
+//! but it blocks the main thread while running.
+
+#![warn(missing_docs)]
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::core::futures::executor::ThreadPool;
+use crate::utils::log::Log;
+use std::{
+    any::Any,
+    collections::VecDeque,
+    fmt::{Debug, Formatter},
+    future::Future,
+    panic::AssertUnwindSafe,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc,
+    },
+};
+
+/// A pool of worker threads that is shared across the engine. The same pool is used by the
+/// resource manager to load resources and by task containers of scenes and plugins.
+pub struct TaskPool {
+    #[cfg(not(target_arch = "wasm32"))]
+    thread_pool: ThreadPool,
+}
+
+impl Default for TaskPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskPool {
+    /// Creates new task pool with one worker thread per CPU core.
+    pub fn new() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            thread_pool: ThreadPool::new().unwrap(),
+        }
+    }
+
+    /// Spawns a future that will be polled to completion on the pool.
+    #[cfg(target_arch = "wasm32")]
+    pub fn spawn_task<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        crate::core::wasm_bindgen_futures::spawn_local(future);
+    }
+
+    /// Spawns a future that will be polled to completion on the pool.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn_task<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.thread_pool.spawn_ok(future);
+    }
+}
+
+/// Result of a task, it is a boxed value returned by the closure of the task. Use
+/// [`Box::downcast`] to get the actual value.
+pub type TaskResult = Box<dyn Any + Send>;
+
+/// Default amount of tasks that can be in-flight at the same time in a task container.
+pub const DEFAULT_TASK_LIMIT: usize = 16;
+
+/// A handle of a task spawned in a [`TaskContainer`]. Handles are unique within a container.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TaskHandle(u64);
+
+type TaskFunc = Box<dyn FnOnce() -> TaskResult + Send>;
+
+struct QueuedTask<O> {
+    handle: TaskHandle,
+    owner: O,
+    func: TaskFunc,
+}
+
+struct InFlightTask<O> {
+    handle: TaskHandle,
+    owner: O,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// A task that has finished its work, the result must be delivered to its owner.
+pub struct CompletedTask<O> {
+    /// A handle of the task.
+    pub handle: TaskHandle,
+    /// Owner of the task.
+    pub owner: O,
+    /// Result of the task.
+    pub result: TaskResult,
+}
+
+/// A container of tasks spawned by a set of owners (nodes of a scene, plugins, etc.). Every task
+/// is a closure that runs on the [`TaskPool`] and its result is returned to the owner on the main
+/// thread by [`TaskContainer::update`].
+///
+/// The container limits amount of tasks that run at the same time (see [`Self::set_limit`]), the
+/// rest of the tasks wait in a queue and spawned in the order of creation when there is a free
+/// slot.
+///
+/// A task is cancelled when its owner is gone (for example a node was deleted) or explicitly by
+/// [`Self::cancel`]. Queued tasks are dropped without running, tasks that are already running are
+/// not interrupted, but their results are discarded.
+pub struct TaskContainer<O> {
+    limit: usize,
+    next_id: u64,
+    queue: VecDeque<QueuedTask<O>>,
+    in_flight: Vec<InFlightTask<O>>,
+    sender: Sender<(TaskHandle, Option<TaskResult>)>,
+    receiver: Receiver<(TaskHandle, Option<TaskResult>)>,
+}
+
+impl<O> Debug for TaskContainer<O> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskContainer")
+            .field("limit", &self.limit)
+            .field("queued", &self.queue.len())
+            .field("in_flight", &self.in_flight.len())
+            .finish()
+    }
+}
+
+impl<O> Default for TaskContainer<O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O> TaskContainer<O> {
+    /// Creates new empty task container with [`DEFAULT_TASK_LIMIT`].
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            limit: DEFAULT_TASK_LIMIT,
+            next_id: 0,
+            queue: Default::default(),
+            in_flight: Default::default(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Sets maximum amount of tasks that can run at the same time. Zero limit pauses spawning of
+    /// new tasks, they'll wait in the queue until the limit is increased.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    /// Returns maximum amount of tasks that can run at the same time.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Returns amount of tasks that wait for a free slot.
+    pub fn queued_count(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns amount of tasks that are running on the pool right now.
+    pub fn in_flight_count(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Adds new task to the container. The task will be spawned on the next [`Self::update`] call
+    /// if there is a free slot. The closure runs on a worker thread, so it must own everything it
+    /// needs, use cloned or copied data of a scene, not the scene itself.
+    pub fn spawn<F, T>(&mut self, owner: O, func: F) -> TaskHandle
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Any + Send,
+    {
+        self.next_id += 1;
+        let handle = TaskHandle(self.next_id);
+        self.queue.push_back(QueuedTask {
+            handle,
+            owner,
+            func: Box::new(move || Box::new(func()) as TaskResult),
+        });
+        handle
+    }
+
+    /// Cancels a task. Returns `false` if there is no such task (it has finished already or it
+    /// was never spawned in this container).
+    pub fn cancel(&mut self, handle: TaskHandle) -> bool {
+        if let Some(position) = self.queue.iter().position(|t| t.handle == handle) {
+            self.queue.remove(position);
+            true
+        } else if let Some(task) = self.in_flight.iter().find(|t| t.handle == handle) {
+            !task.cancelled.swap(true, Ordering::Relaxed)
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if the task is either queued or running.
+    pub fn is_pending(&self, handle: TaskHandle) -> bool {
+        self.queue.iter().any(|t| t.handle == handle)
+            || self
+                .in_flight
+                .iter()
+                .any(|t| t.handle == handle && !t.cancelled.load(Ordering::Relaxed))
+    }
+
+    /// Collects finished tasks of alive owners, cancels tasks of owners that are gone and spawns
+    /// queued tasks on the pool while there are free slots. Returns finished tasks in the order
+    /// of completion.
+    pub fn update<A>(&mut self, task_pool: &TaskPool, mut is_alive: A) -> Vec<CompletedTask<O>>
+    where
+        A: FnMut(&O) -> bool,
+    {
+        let mut completed = Vec::new();
+        while let Ok((handle, result)) = self.receiver.try_recv() {
+            if let Some(position) = self.in_flight.iter().position(|t| t.handle == handle) {
+                let task = self.in_flight.remove(position);
+                if let Some(result) = result {
+                    if !task.cancelled.load(Ordering::Relaxed) && is_alive(&task.owner) {
+                        completed.push(CompletedTask {
+                            handle,
+                            owner: task.owner,
+                            result,
+                        });
+                    }
+                }
+            }
+        }
+
+        self.queue.retain(|task| is_alive(&task.owner));
+        for task in self.in_flight.iter() {
+            if !is_alive(&task.owner) {
+                task.cancelled.store(true, Ordering::Relaxed);
+            }
+        }
+
+        while self.in_flight.len() < self.limit {
+            let task = match self.queue.pop_front() {
+                Some(task) => task,
+                None => break,
+            };
+
+            let cancelled = Arc::new(AtomicBool::new(false));
+            let sender = self.sender.clone();
+            let handle = task.handle;
+            let func = task.func;
+            let task_cancelled = cancelled.clone();
+            task_pool.spawn_task(async move {
+                // A task could be cancelled while it was waiting for a worker thread.
+                let result = if task_cancelled.load(Ordering::Relaxed) {
+                    None
+                } else {
+                    match std::panic::catch_unwind(AssertUnwindSafe(func)) {
+                        Ok(result) => Some(result),
+                        Err(_) => {
+                            Log::err(format!("Task {:?} has panicked!", handle));
+                            None
+                        }
+                    }
+                };
+                // The container could be destroyed already, the result is simply dropped then.
+                let _ = sender.send((handle, result));
+            });
+
+            self.in_flight.push(InFlightTask {
+                handle,
+                owner: task.owner,
+                cancelled,
+            });
+        }
+
+        completed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::engine::task::{TaskContainer, TaskPool};
+    use std::time::{Duration, Instant};
+
+    fn wait_for<O, A>(
+        container: &mut TaskContainer<O>,
+        pool: &TaskPool,
+        mut is_alive: A,
+    ) -> Vec<(O, u32)>
+    where
+        A: FnMut(&O) -> bool,
+    {
+        let mut results = Vec::new();
+        let start = Instant::now();
+        while (container.in_flight_count() > 0 || container.queued_count() > 0)
+            && start.elapsed() < Duration::from_secs(10)
+        {
+            for task in container.update(pool, &mut is_alive) {
+                results.push((task.owner, *task.result.downcast::<u32>().unwrap()));
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        results
+    }
+
+    #[test]
+    fn test_task_results_are_delivered_to_owners() {
+        let pool = TaskPool::new();
+        let mut container = TaskContainer::new();
+
+        let a = container.spawn(1, || 10u32);
+        let b = container.spawn(2, || 20u32);
+        assert_ne!(a, b);
+        assert!(container.is_pending(a));
+
+        let mut results = wait_for(&mut container, &pool, |_| true);
+        results.sort_unstable();
+        assert_eq!(results, vec![(1, 10), (2, 20)]);
+        assert!(!container.is_pending(a));
+    }
+
+    #[test]
+    fn test_task_limit() {
+        let pool = TaskPool::new();
+        let mut container = TaskContainer::new();
+        container.set_limit(2);
+
+        for i in 0..5u32 {
+            container.spawn(i, move || {
+                std::thread::sleep(Duration::from_millis(5));
+                i
+            });
+        }
+
+        assert!(container.update(&pool, |_| true).is_empty());
+        assert_eq!(container.in_flight_count(), 2);
+        assert_eq!(container.queued_count(), 3);
+
+        let mut results = wait_for(&mut container, &pool, |_| true);
+        results.sort_unstable();
+        assert_eq!(results, (0..5).map(|i| (i, i)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_task_cancellation() {
+        let pool = TaskPool::new();
+        let mut container = TaskContainer::new();
+        container.set_limit(0);
+
+        let cancelled = container.spawn(1, || 1u32);
+        container.spawn(2, || 2u32);
+        container.spawn(3, || 3u32);
+        assert!(container.cancel(cancelled));
+        assert!(!container.cancel(cancelled));
+
+        // Owner 2 is gone, its task must not be delivered.
+        container.set_limit(1);
+        let results = wait_for(&mut container, &pool, |owner| *owner != 2);
+        assert_eq!(results, vec![(3, 3)]);
+    }
+
+    #[test]
+    fn test_panicked_task_frees_slot() {
+        let pool = TaskPool::new();
+        let mut container = TaskContainer::new();
+        container.set_limit(1);
+
+        container.spawn(1, || -> u32 { panic!("Task failure") });
+        container.spawn(2, || 2u32);
+
+        let results = wait_for(&mut container, &pool, |_| true);
+        assert_eq!(results, vec![(2, 2)]);
+    }
+}
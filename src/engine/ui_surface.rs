@@ -0,0 +1,537 @@
+//! World-space user interfaces. See [`UiSurface`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3},
+        math::{self, ray::Ray},
+        pool::{Handle, Pool},
+        sstorage::ImmutableString,
+    },
+    gui::{
+        message::{ButtonState, OsEvent},
+        UserInterface,
+    },
+    material::{shader::SamplerFallback, PropertyValue},
+    renderer::{framework::error::FrameworkError, Renderer},
+    resource::texture::Texture,
+    scene::{
+        camera::Camera,
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            Mesh,
+        },
+        node::Node,
+        Scene, SceneContainer,
+    },
+    utils::log::Log,
+};
+use std::{
+    cmp::Ordering,
+    ops::{Index, IndexMut},
+};
+
+/// A user interface that is rendered onto a mesh in a scene, like an interactive touch-screen of
+/// an in-game computer.
+///
+/// # How it works
+///
+/// Every surface has its own [`UserInterface`] instance, which is rendered into a texture every
+/// frame. The texture is assigned to a material property (`diffuseTexture` by default) of every
+/// surface of the mesh. Mouse input is routed to the UI by casting a ray from the camera through
+/// the mouse cursor; a hit position on the mesh is converted into UI-space cursor position using
+/// texture coordinates of the mesh, so hovering, clicks and drag'n'drop work the same as in a
+/// usual screen-space UI. Keyboard input is routed to the surface only when it has keyboard focus,
+/// see [`UiSurface::set_keyboard_focus`].
+///
+/// Texture coordinates (0, 1) and (1, 0) of the mesh correspond to top-left and bottom-right
+/// corners of the UI respectively.
+///
+/// # Lifetime
+///
+/// Surfaces are owned by the engine (see [`crate::engine::Engine::ui_surfaces`]) and are not
+/// serialized with scenes - widgets cannot be shared across threads and there is no such thing as
+/// UI resource, so the content of a surface must be built from code. A surface references its
+/// scene and mesh by handles and is updated and rendered only when its scene is enabled.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::pool::Handle,
+///     engine::{
+///         ui_surface::{UiSurface, UiSurfaceBuilder},
+///         Engine,
+///     },
+///     gui::{button::ButtonBuilder, widget::WidgetBuilder, UiNode},
+///     scene::{node::Node, Scene},
+/// };
+///
+/// fn create_monitor_screen(
+///     engine: &mut Engine,
+///     scene: Handle<Scene>,
+///     monitor_screen: Handle<Node>,
+/// ) -> (Handle<UiSurface>, Handle<UiNode>) {
+///     let mut surface = UiSurfaceBuilder::new(scene, monitor_screen)
+///         .with_resolution(512, 256)
+///         .with_interaction_distance(3.0)
+///         .build();
+///
+///     let button = ButtonBuilder::new(WidgetBuilder::new())
+///         .with_text("Open the door")
+///         .build(&mut surface.ui_mut().build_ctx());
+///
+///     (engine.ui_surfaces.add(surface), button)
+/// }
+/// ```
+pub struct UiSurface {
+    ui: UserInterface,
+    render_target: Texture,
+    scene: Handle<Scene>,
+    mesh: Handle<Node>,
+    camera: Handle<Node>,
+    texture_property: ImmutableString,
+    interaction_distance: f32,
+    keyboard_focus: bool,
+    hovered: bool,
+    texture_applied: bool,
+}
+
+/// A result of ray casting against a UI surface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UiSurfaceRayCastResult {
+    /// Distance from the origin of the ray to the hit point in world space.
+    pub distance: f32,
+    /// Hit point in world space.
+    pub position: Vector3<f32>,
+    /// Hit point in UI space, it could be used as cursor position.
+    pub ui_position: Vector2<f32>,
+}
+
+impl UiSurface {
+    /// Returns a reference to the user interface of the surface.
+    pub fn ui(&self) -> &UserInterface {
+        &self.ui
+    }
+
+    /// Returns a reference to the user interface of the surface. Use it to build widgets and to
+    /// poll messages from them.
+    pub fn ui_mut(&mut self) -> &mut UserInterface {
+        &mut self.ui
+    }
+
+    /// Returns the texture to which the UI is rendered.
+    pub fn render_target(&self) -> &Texture {
+        &self.render_target
+    }
+
+    /// Returns a handle of the scene to which the surface belongs.
+    pub fn scene(&self) -> Handle<Scene> {
+        self.scene
+    }
+
+    /// Returns a handle of the mesh on which the UI is rendered.
+    pub fn mesh(&self) -> Handle<Node> {
+        self.mesh
+    }
+
+    /// Sets a camera that will be used to route mouse input to the surface. [`Handle::NONE`] means
+    /// that the first enabled camera of the scene will be used.
+    pub fn set_camera(&mut self, camera: Handle<Node>) {
+        self.camera = camera;
+    }
+
+    /// Returns a handle of the camera that is used to route mouse input to the surface.
+    pub fn camera(&self) -> Handle<Node> {
+        self.camera
+    }
+
+    /// Sets max distance (in world units) from the camera to the surface at which the surface
+    /// will receive mouse input.
+    pub fn set_interaction_distance(&mut self, distance: f32) {
+        self.interaction_distance = distance.max(0.0);
+    }
+
+    /// Returns max interaction distance, see [`Self::set_interaction_distance`].
+    pub fn interaction_distance(&self) -> f32 {
+        self.interaction_distance
+    }
+
+    /// Gives or takes keyboard focus from the surface. Keyboard input is routed only to the surfaces
+    /// with keyboard focus, usually the focus is given when the player "uses" an in-game screen and
+    /// taken back when the player leaves it.
+    pub fn set_keyboard_focus(&mut self, focus: bool) {
+        self.keyboard_focus = focus;
+    }
+
+    /// Returns `true` if the surface has keyboard focus.
+    pub fn has_keyboard_focus(&self) -> bool {
+        self.keyboard_focus
+    }
+
+    /// Returns `true` if mouse cursor is over the surface.
+    pub fn is_hovered(&self) -> bool {
+        self.hovered
+    }
+
+    fn find_camera<'a>(&self, scene: &'a Scene) -> Option<&'a Camera> {
+        if self.camera.is_some() {
+            scene
+                .graph
+                .try_get(self.camera)
+                .and_then(|n| n.cast::<Camera>())
+        } else {
+            scene
+                .graph
+                .linear_iter()
+                .filter_map(|n| n.cast::<Camera>())
+                .find(|c| c.is_enabled())
+        }
+    }
+
+    /// Casts a ray (in world space) against the mesh of the surface and returns the closest hit
+    /// point, if any. The interaction distance is not taken into account.
+    pub fn ray_cast(&self, scene: &Scene, ray: &Ray) -> Option<UiSurfaceRayCastResult> {
+        let mesh = scene
+            .graph
+            .try_get(self.mesh)
+            .and_then(|n| n.cast::<Mesh>())?;
+
+        let transform = mesh.global_transform();
+        let local_ray = ray.transform(transform.try_inverse()?);
+        let ui_size = self.ui.screen_size();
+
+        let mut closest: Option<(f32, Vector3<f32>, Vector2<f32>)> = None;
+        for surface in mesh.surfaces() {
+            let data = surface.data();
+            let data = data.lock();
+            'triangle_loop: for triangle in data.geometry_buffer.iter() {
+                let mut positions = [Vector3::default(); 3];
+                let mut tex_coords = [Vector2::default(); 3];
+                for (i, &index) in triangle.0.iter().enumerate() {
+                    let vertex = match data.vertex_buffer.get(index as usize) {
+                        Some(vertex) => vertex,
+                        None => continue 'triangle_loop,
+                    };
+                    positions[i] = vertex
+                        .read_3_f32(VertexAttributeUsage::Position)
+                        .unwrap_or_default();
+                    tex_coords[i] = vertex
+                        .read_2_f32(VertexAttributeUsage::TexCoord0)
+                        .unwrap_or_default();
+                }
+
+                if let Some((toi, point)) = local_ray.triangle_intersection(&positions) {
+                    if closest.map_or(true, |(closest_toi, _, _)| toi < closest_toi) {
+                        let (a, b, c) = math::get_barycentric_coords(
+                            &point,
+                            &positions[0],
+                            &positions[1],
+                            &positions[2],
+                        );
+                        let tex_coord = tex_coords[0].scale(a)
+                            + tex_coords[1].scale(b)
+                            + tex_coords[2].scale(c);
+                        closest = Some((toi, point, tex_coord));
+                    }
+                }
+            }
+        }
+
+        closest.map(|(_, local_position, tex_coord)| {
+            let position = transform.transform_point(&local_position.into()).coords;
+            UiSurfaceRayCastResult {
+                distance: (position - ray.origin).norm(),
+                position,
+                // UI is rendered upside down in the texture, because its Y axis points down.
+                ui_position: Vector2::new(tex_coord.x * ui_size.x, (1.0 - tex_coord.y) * ui_size.y),
+            }
+        })
+    }
+
+    fn pick(
+        &self,
+        scenes: &SceneContainer,
+        cursor_position: Vector2<f32>,
+        screen_size: Vector2<f32>,
+    ) -> Option<UiSurfaceRayCastResult> {
+        let scene = scenes.try_get(self.scene).filter(|s| s.enabled)?;
+        let camera = self.find_camera(scene)?;
+        let ray = camera.make_ray(cursor_position, screen_size);
+        self.ray_cast(scene, &ray)
+            .filter(|result| result.distance <= self.interaction_distance)
+    }
+
+    fn set_hovered(&mut self, cursor_position: Option<Vector2<f32>>) -> bool {
+        let was_hovered = self.hovered;
+        self.hovered = cursor_position.is_some();
+        match cursor_position {
+            Some(position) => self.ui.process_os_event(&OsEvent::CursorMoved { position }),
+            None => {
+                if was_hovered {
+                    // Move the cursor out of the UI, so hovered widgets will receive "mouse leave"
+                    // messages.
+                    self.ui.process_os_event(&OsEvent::CursorMoved {
+                        position: Vector2::repeat(-1.0),
+                    });
+                }
+                false
+            }
+        }
+    }
+
+    fn process_os_event(&mut self, event: &OsEvent) -> bool {
+        match event {
+            OsEvent::MouseInput { state, .. } => {
+                // Releases must always pass through, otherwise the UI will be stuck in dragging
+                // state if the button was released outside of the surface.
+                if self.hovered || *state == ButtonState::Released {
+                    self.ui.process_os_event(event) && self.hovered
+                } else {
+                    false
+                }
+            }
+            OsEvent::MouseWheel(..) => self.hovered && self.ui.process_os_event(event),
+            OsEvent::KeyboardInput { .. } | OsEvent::Character(_) => {
+                self.keyboard_focus && self.ui.process_os_event(event)
+            }
+            OsEvent::KeyboardModifiers(_) => {
+                self.ui.process_os_event(event);
+                false
+            }
+            // Handled by the container.
+            OsEvent::CursorMoved { .. } => false,
+        }
+    }
+
+    fn apply_texture(&mut self, scene: &mut Scene) {
+        match scene
+            .graph
+            .try_get_mut(self.mesh)
+            .and_then(|n| n.cast_mut::<Mesh>())
+        {
+            Some(mesh) => {
+                if self.texture_applied {
+                    return;
+                }
+
+                for surface in mesh.surfaces_mut() {
+                    if let Err(e) = surface.material().lock().set_property(
+                        &self.texture_property,
+                        PropertyValue::Sampler {
+                            value: Some(self.render_target.clone()),
+                            fallback: SamplerFallback::White,
+                        },
+                    ) {
+                        Log::err(format!(
+                            "Failed to apply UI surface texture to material. Reason {:?}",
+                            e
+                        ))
+                    }
+                }
+
+                self.texture_applied = true;
+            }
+            None => {
+                // Apply the texture again if the mesh will appear (for example if a scene was
+                // reloaded).
+                self.texture_applied = false;
+            }
+        }
+    }
+}
+
+/// Allows you to build a [`UiSurface`] in declarative manner.
+pub struct UiSurfaceBuilder {
+    scene: Handle<Scene>,
+    mesh: Handle<Node>,
+    camera: Handle<Node>,
+    width: u32,
+    height: u32,
+    texture_property: ImmutableString,
+    interaction_distance: f32,
+}
+
+impl UiSurfaceBuilder {
+    /// Creates new builder for a surface that will be rendered on the given mesh of the given
+    /// scene.
+    pub fn new(scene: Handle<Scene>, mesh: Handle<Node>) -> Self {
+        Self {
+            scene,
+            mesh,
+            camera: Handle::NONE,
+            width: 512,
+            height: 512,
+            texture_property: ImmutableString::new("diffuseTexture"),
+            interaction_distance: 2.0,
+        }
+    }
+
+    /// Sets size of the UI in pixels, it defines resolution of the texture as well.
+    pub fn with_resolution(mut self, width: u32, height: u32) -> Self {
+        self.width = width.max(1);
+        self.height = height.max(1);
+        self
+    }
+
+    /// Sets a camera that will be used to route mouse input, see [`UiSurface::set_camera`].
+    pub fn with_camera(mut self, camera: Handle<Node>) -> Self {
+        self.camera = camera;
+        self
+    }
+
+    /// Sets the name of a material property to which the texture with the UI will be assigned.
+    /// Default is `diffuseTexture`.
+    pub fn with_texture_property(mut self, name: &str) -> Self {
+        self.texture_property = ImmutableString::new(name);
+        self
+    }
+
+    /// Sets max interaction distance, see [`UiSurface::set_interaction_distance`].
+    pub fn with_interaction_distance(mut self, distance: f32) -> Self {
+        self.interaction_distance = distance.max(0.0);
+        self
+    }
+
+    /// Creates new surface. Use [`UiSurfaceContainer::add`] to make it functional.
+    pub fn build(self) -> UiSurface {
+        UiSurface {
+            ui: UserInterface::new(Vector2::new(self.width as f32, self.height as f32)),
+            render_target: Texture::new_render_target(self.width, self.height),
+            scene: self.scene,
+            mesh: self.mesh,
+            camera: self.camera,
+            texture_property: self.texture_property,
+            interaction_distance: self.interaction_distance,
+            keyboard_focus: false,
+            hovered: false,
+            texture_applied: false,
+        }
+    }
+}
+
+/// A container for world-space user interfaces.
+#[derive(Default)]
+pub struct UiSurfaceContainer {
+    pool: Pool<UiSurface>,
+}
+
+impl UiSurfaceContainer {
+    /// Adds new surface to the container and returns its handle.
+    pub fn add(&mut self, surface: UiSurface) -> Handle<UiSurface> {
+        self.pool.spawn(surface)
+    }
+
+    /// Removes a surface by its handle.
+    pub fn remove(&mut self, handle: Handle<UiSurface>) -> UiSurface {
+        self.pool.free(handle)
+    }
+
+    /// Creates new immutable iterator.
+    pub fn iter(&self) -> impl Iterator<Item = &UiSurface> {
+        self.pool.iter()
+    }
+
+    /// Creates new mutable iterator.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut UiSurface> {
+        self.pool.iter_mut()
+    }
+
+    /// Checks if given handle is valid.
+    pub fn is_valid_handle(&self, handle: Handle<UiSurface>) -> bool {
+        self.pool.is_valid_handle(handle)
+    }
+
+    /// Tries to borrow a surface by its handle.
+    pub fn try_get(&self, handle: Handle<UiSurface>) -> Option<&UiSurface> {
+        self.pool.try_borrow(handle)
+    }
+
+    /// Tries to borrow a surface by its handle.
+    pub fn try_get_mut(&mut self, handle: Handle<UiSurface>) -> Option<&mut UiSurface> {
+        self.pool.try_borrow_mut(handle)
+    }
+
+    /// Destroys all surfaces. All handles will become invalid.
+    pub fn clear(&mut self) {
+        self.pool.clear()
+    }
+
+    /// Routes an OS event to the surfaces. Mouse input goes to the closest surface under the
+    /// cursor (within its interaction distance), keyboard input goes to every surface with keyboard
+    /// focus. `screen_size` is the size of the frame to which scenes are rendered. Returns `true`
+    /// if the event was processed by any surface.
+    pub fn process_os_event(
+        &mut self,
+        event: &OsEvent,
+        scenes: &SceneContainer,
+        screen_size: Vector2<f32>,
+    ) -> bool {
+        if let OsEvent::CursorMoved { position } = *event {
+            let closest = self
+                .pool
+                .pair_iter()
+                .filter_map(|(handle, surface)| {
+                    surface
+                        .pick(scenes, position, screen_size)
+                        .map(|result| (handle, result))
+                })
+                .min_by(|(_, a), (_, b)| {
+                    a.distance
+                        .partial_cmp(&b.distance)
+                        .unwrap_or(Ordering::Equal)
+                });
+
+            let mut processed = false;
+            for (handle, surface) in self.pool.pair_iter_mut() {
+                let cursor_position = closest
+                    .filter(|(closest, _)| *closest == handle)
+                    .map(|(_, result)| result.ui_position);
+                processed |= surface.set_hovered(cursor_position);
+            }
+            processed
+        } else {
+            let mut processed = false;
+            for surface in self.pool.iter_mut() {
+                processed |= surface.process_os_event(event);
+            }
+            processed
+        }
+    }
+
+    pub(crate) fn update(&mut self, scenes: &mut SceneContainer, dt: f32) {
+        for surface in self.pool.iter_mut() {
+            if let Some(scene) = scenes.try_get_mut(surface.scene).filter(|s| s.enabled) {
+                surface.apply_texture(scene);
+                let screen_size = surface.ui.screen_size();
+                surface.ui.update(screen_size, dt);
+            }
+        }
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        renderer: &mut Renderer,
+        scenes: &SceneContainer,
+    ) -> Result<(), FrameworkError> {
+        for surface in self.pool.iter_mut() {
+            if scenes.try_get(surface.scene).map_or(false, |s| s.enabled) {
+                renderer.render_ui_to_texture(surface.render_target.clone(), &mut surface.ui)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Index<Handle<UiSurface>> for UiSurfaceContainer {
+    type Output = UiSurface;
+
+    fn index(&self, index: Handle<UiSurface>) -> &Self::Output {
+        &self.pool[index]
+    }
+}
+
+impl IndexMut<Handle<UiSurface>> for UiSurfaceContainer {
+    fn index_mut(&mut self, index: Handle<UiSurface>) -> &mut Self::Output {
+        &mut self.pool[index]
+    }
+}
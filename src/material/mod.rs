@@ -410,6 +410,11 @@ impl Material {
         Self::from_shader(Shader::standard_terrain(), None)
     }
 
+    /// Creates new instance of standard impostor material.
+    pub fn standard_impostor() -> Self {
+        Self::from_shader(Shader::standard_impostor(), None)
+    }
+
     /// Creates a new material instance with given shader. Each property will have default values
     /// defined in the shader.
     ///
@@ -493,7 +498,7 @@ impl Material {
         }
     }
 
-    pub(in crate) fn resolve(&mut self, resource_manager: ResourceManager) {
+    pub(crate) fn resolve(&mut self, resource_manager: ResourceManager) {
         for value in self.properties.values_mut() {
             if let PropertyValue::Sampler {
                 value: Some(texture),
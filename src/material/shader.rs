@@ -6,6 +6,7 @@ use crate::{
     asset::{define_new_resource, Resource, ResourceData, ResourceState},
     core::{
         algebra::{Matrix2, Matrix3, Matrix4, Vector2, Vector3, Vector4},
+        inspect::{Inspect, PropertyInfo},
         io::{self, FileLoadError},
         sparse::AtomicIndex,
         visitor::prelude::*,
@@ -16,6 +17,7 @@ use crate::{
         cache::{shader::ShaderSet, CacheEntry},
         framework::framebuffer::DrawParameters,
     },
+    scene::mesh::buffer::{VertexBuffer, CUSTOM_ATTRIBUTES_SHADER_LOCATION},
 };
 use ron::Error;
 use serde::{Deserialize, Serialize};
@@ -24,6 +26,7 @@ use std::{
     io::Cursor,
     path::{Path, PathBuf},
 };
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
 /// A source code of the standard shader.
 pub const STANDARD_SHADER_SRC: &str = include_str!("standard/standard.shader");
@@ -31,6 +34,13 @@ pub const STANDARD_SHADER_SRC: &str = include_str!("standard/standard.shader");
 /// A source code of the standard terrain shader.
 pub const STANDARD_TERRAIN_SHADER_SRC: &str = include_str!("standard/terrain.shader");
 
+/// A source code of the standard terrain shader that renders all layers of a terrain in a single
+/// pass using texture arrays.
+pub const STANDARD_TERRAIN_ARRAY_SHADER_SRC: &str = include_str!("standard/terrain_array.shader");
+
+/// A source code of the standard impostor shader.
+pub const STANDARD_IMPOSTOR_SHADER_SRC: &str = include_str!("standard/impostor.shader");
+
 /// Internal state of the shader.
 ///
 /// # Notes
@@ -44,7 +54,7 @@ pub struct ShaderState {
     /// Shader definition contains description of properties and render passes.
     pub definition: ShaderDefinition,
 
-    pub(in crate) cache_index: AtomicIndex<CacheEntry<ShaderSet>>,
+    pub(crate) cache_index: AtomicIndex<CacheEntry<ShaderSet>>,
 }
 
 impl Visit for ShaderState {
@@ -60,6 +70,11 @@ impl Visit for ShaderState {
                 self.definition = ShaderDefinition::from_str(STANDARD_SHADER_SRC).unwrap();
             } else if self.path == Path::new("StandardTerrain") {
                 self.definition = ShaderDefinition::from_str(STANDARD_TERRAIN_SHADER_SRC).unwrap();
+            } else if self.path == Path::new("StandardTerrainArray") {
+                self.definition =
+                    ShaderDefinition::from_str(STANDARD_TERRAIN_ARRAY_SHADER_SRC).unwrap();
+            } else if self.path == Path::new("StandardImpostor") {
+                self.definition = ShaderDefinition::from_str(STANDARD_IMPOSTOR_SHADER_SRC).unwrap();
             }
         }
 
@@ -82,7 +97,18 @@ impl Visit for ShaderState {
 ///
 /// Fallback value is also helpful to catch missing textures, you'll definitely know the texture is
 /// missing by very specific value in the fallback texture.
-#[derive(Deserialize, Debug, PartialEq, Clone, Copy, Visit)]
+#[derive(
+    Deserialize,
+    Debug,
+    PartialEq,
+    Clone,
+    Copy,
+    Visit,
+    Inspect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
 pub enum SamplerFallback {
     /// A 1x1px white texture.
     White,
@@ -218,6 +244,16 @@ pub struct RenderPassDefinition {
     pub fragment_shader: String,
 }
 
+/// Named custom vertex attribute definition. See [`Shader`] docs for more info about custom
+/// vertex attributes.
+#[derive(Default, Deserialize, Debug, PartialEq)]
+pub struct VertexAttributeDefinition {
+    /// A name of the attribute, it must match the name of a custom attribute of a vertex buffer.
+    pub name: String,
+    /// Size of the attribute expressed in components (1 - `float`, 2 - `vec2`, etc.).
+    pub size: u8,
+}
+
 /// A definition of the shader.
 #[derive(Default, Deserialize, Debug, PartialEq)]
 pub struct ShaderDefinition {
@@ -227,9 +263,66 @@ pub struct ShaderDefinition {
     pub passes: Vec<RenderPassDefinition>,
     /// A set of property definitions.
     pub properties: Vec<PropertyDefinition>,
+    /// A set of named custom vertex attributes.
+    #[serde(default)]
+    pub vertex_attributes: Vec<VertexAttributeDefinition>,
 }
 
 impl ShaderDefinition {
+    /// Returns GLSL declarations of custom vertex attributes, they're inserted in every vertex
+    /// shader of the render passes.
+    pub fn vertex_attributes_source(&self) -> String {
+        self.vertex_attributes
+            .iter()
+            .enumerate()
+            .map(|(index, attribute)| {
+                let kind = match attribute.size {
+                    1 => "float".to_owned(),
+                    size => format!("vec{}", size),
+                };
+                format!(
+                    "layout(location = {}) in {} {};\n",
+                    CUSTOM_ATTRIBUTES_SHADER_LOCATION as usize + index,
+                    kind,
+                    attribute.name
+                )
+            })
+            .collect()
+    }
+
+    /// Checks whether the custom vertex attributes of the shader match the custom attributes
+    /// of the given vertex buffer. Attributes are bound by locations, so n-th attribute of the
+    /// shader must be n-th custom attribute of the buffer with the same name and size.
+    pub fn validate_vertex_buffer(&self, buffer: &VertexBuffer) -> Result<(), String> {
+        for (index, definition) in self.vertex_attributes.iter().enumerate() {
+            let location = CUSTOM_ATTRIBUTES_SHADER_LOCATION as usize + index;
+            match buffer.custom_attribute(&definition.name) {
+                Some(attribute) if attribute.shader_location as usize != location => {
+                    return Err(format!(
+                        "Custom vertex attribute {} must be bound to location {}, but it is \
+                        bound to {}. Add custom attributes to the vertex buffer in the same \
+                        order as they're defined in the shader.",
+                        definition.name, location, attribute.shader_location
+                    ));
+                }
+                Some(attribute) if attribute.size != definition.size => {
+                    return Err(format!(
+                        "Custom vertex attribute {} has size {}, but the shader expects {}.",
+                        definition.name, attribute.size, definition.size
+                    ));
+                }
+                Some(_) => (),
+                None => {
+                    return Err(format!(
+                        "Vertex buffer does not have custom vertex attribute {}.",
+                        definition.name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn from_buf(buf: Vec<u8>) -> Result<Self, ShaderError> {
         Ok(ron::de::from_reader(Cursor::new(buf))?)
     }
@@ -240,7 +333,7 @@ impl ShaderDefinition {
 }
 
 impl ShaderState {
-    pub(in crate) async fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ShaderError> {
+    pub(crate) async fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ShaderError> {
         let content = io::load_file(path.as_ref()).await?;
         Ok(Self {
             path: path.as_ref().to_owned(),
@@ -249,7 +342,7 @@ impl ShaderState {
         })
     }
 
-    pub(in crate) fn from_str<P: AsRef<Path>>(str: &str, path: P) -> Result<Self, ShaderError> {
+    pub(crate) fn from_str<P: AsRef<Path>>(str: &str, path: P) -> Result<Self, ShaderError> {
         Ok(Self {
             path: path.as_ref().to_owned(),
             definition: ShaderDefinition::from_str(str)?,
@@ -421,6 +514,13 @@ define_new_resource!(
     /// about each rendered object. These textures then are used for physically-based lighting.
     /// Use this pass when you want the standard lighting to work with your objects.
     ///
+    /// - DepthPrePass - An optional pass that emits only depth values of an object, it is rendered
+    /// before GBuffer pass when depth pre-pass is enabled in quality settings. Objects that have
+    /// this pass are then rendered in GBuffer pass with `Equal` depth test and without depth
+    /// writes, so expensive fragment shaders are executed only once per pixel. The pass must
+    /// produce exactly the same positions as GBuffer pass (use `invariant gl_Position;` in both
+    /// passes) and discard the same fragments, otherwise the object will have holes.
+    ///
     /// - Forward - A pass that draws an object directly in render target. This pass is very
     /// limiting, it does not support lighting, shadows, etc. It should be only used to render
     /// translucent objects.
@@ -439,11 +539,14 @@ define_new_resource!(
     /// |---------------------------|-----------------|--------------------------------------------
     /// | fyrox_worldMatrix          | `Matrix4`       | Local-to-world transformation.
     /// | fyrox_worldViewProjection  | `Matrix4`       | Local-to-clip-space transform.
-    /// | fyrox_boneMatrices         | `[Matrix4; 60]` | Array of bone matrices.
+    /// | fyrox_boneMatrices         | `[Matrix4; 60]` | Array of bone matrices (see below).
     /// | fyrox_useSkeletalAnimation | `Vector3`       | Whether skinned meshes is rendering or not.
     /// | fyrox_cameraPosition       | `Vector3`       | Position of the camera.
     /// | fyrox_usePOM               | `bool`          | Whether to use parallax mapping or not.
     /// | fyrox_lightPosition        | `Vector3`       | Light position.
+    /// | fyrox_wind                 | `Vector4`       | Wind direction (xyz) and strength (w).
+    /// | fyrox_windNoise            | `Vector4`       | Gustiness, gust frequency, gust size, turbulence frequency.
+    /// | fyrox_windTime             | `f32`           | Time of the wind.
     ///
     /// To use any of the variables, just define a uniform with appropriate name:
     ///
@@ -454,6 +557,59 @@ define_new_resource!(
     ///
     /// This list will be extended in future releases.
     ///
+    /// ## Skinning
+    ///
+    /// Use `S_FetchBoneMatrix(index)` function to get a bone matrix in a vertex shader. The engine
+    /// stores bone matrices in a texture if the GPU can fetch textures in vertex shaders, in this
+    /// case there is no limit on the amount of bones (except 256, because bone indices are stored
+    /// in bytes). Otherwise bone matrices are passed via `fyrox_boneMatrices` uniform array, which
+    /// is limited to 60 matrices, and you have to define it like so:
+    ///
+    /// ```glsl
+    /// #ifndef FYROX_BONE_MATRICES_TEXTURE
+    /// uniform mat4 fyrox_boneMatrices[60];
+    /// #endif
+    /// ```
+    ///
+    /// ## Custom vertex attributes
+    ///
+    /// Vertex buffers may have named custom attributes (see
+    /// [`crate::scene::mesh::buffer::VertexBufferRefMut::add_custom_attribute`]), to use them in
+    /// a shader list them in `vertex_attributes` field of the shader definition:
+    ///
+    /// ```ron
+    /// vertex_attributes: [
+    ///     (name: "wetness", size: 1),
+    ///     (name: "secondColor", size: 4),
+    /// ],
+    /// ```
+    ///
+    /// The engine declares the attributes in every vertex shader of the shader automatically, so
+    /// `wetness` could be used as `float` and `secondColor` as `vec4`, you must not declare them
+    /// by yourself. Custom attributes are bound by locations, so they must be added to a vertex
+    /// buffer in the same order as they're listed in the shader. Surfaces are validated against
+    /// the shader before rendering, surfaces with mismatching attributes are not rendered.
+    ///
+    /// ## Motion vectors
+    ///
+    /// Temporal anti-aliasing requires GBuffer pass to write screen-space motion vectors of
+    /// fragments to the sixth render target (`layout(location = 5) out vec2 outMotionVector;`),
+    /// otherwise moving objects will leave trails behind them. Use `S_ComputeMotionVector` with
+    /// current and previous clip-space positions of a vertex, the previous position could be
+    /// obtained using `fyrox_prevWorldViewProjection` matrix (and `S_FetchPrevBoneMatrix(index)`
+    /// for skinned meshes). The matrix and `fyrox_jitter` are defined automatically, so you must
+    /// not define them by yourself. See the standard shader for an example.
+    ///
+    /// ## Wind
+    ///
+    /// Wind uniforms are defined automatically, use `S_WindVelocity(worldPosition)` to get velocity
+    /// of the wind at a point, `S_WindSway(worldPosition, weight)` to get an offset of a vegetation
+    /// vertex, or `S_WindSwayLocal(localPosition, fyrox_worldMatrix, weight)` to displace a vertex
+    /// in local space. The functions match [`crate::scene::wind::Wind`] exactly, so vegetation
+    /// sways in sync with particles. The standard shader sways vertices of materials with enabled
+    /// `vegetation` property, using red channel of vertex color (location
+    /// [`crate::scene::mesh::buffer::COLOR_SHADER_LOCATION`]) as a weight.
+    ///
     /// # Drawing parameters
     ///
     /// Drawing parameters defines which GPU functions to use and at which state. For example, to render
@@ -495,6 +651,10 @@ define_new_resource!(
     ///      - Stencil options.
     ///      - **Possible values:** [StencilOp](crate::renderer::framework::state::StencilOp)
     ///
+    ///   - depth_func:
+    ///      - Depth comparison function, optional, `LessOrEqual` by default.
+    ///      - **Possible values:** [CompareFunc](crate::renderer::framework::state::CompareFunc)
+    ///
     /// # Standard shader
     ///
     /// By default Fyrox uses standard material for rendering, it covers 95% of uses cases and it is very
@@ -530,9 +690,25 @@ impl Shader {
         STANDARD_TERRAIN.clone()
     }
 
+    /// Returns an instance of standard terrain shader that uses texture arrays to render all
+    /// layers of a terrain in a single pass.
+    pub fn standard_terrain_array() -> Self {
+        STANDARD_TERRAIN_ARRAY.clone()
+    }
+
+    /// Returns an instance of standard impostor shader.
+    pub fn standard_impostor() -> Self {
+        STANDARD_IMPOSTOR.clone()
+    }
+
     /// Returns a list of standard shader.
     pub fn standard_shaders() -> Vec<Shader> {
-        vec![Self::standard(), Self::standard_terrain()]
+        vec![
+            Self::standard(),
+            Self::standard_terrain(),
+            Self::standard_terrain_array(),
+            Self::standard_impostor(),
+        ]
     }
 }
 
@@ -540,7 +716,9 @@ impl Shader {
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct ShaderImportOptions {}
 
-impl ImportOptions for ShaderImportOptions {}
+impl ImportOptions for ShaderImportOptions {
+    const FOLDER_OPTIONS_FILE_NAME: &'static str = "folder.shader.options";
+}
 
 lazy_static! {
     static ref STANDARD: Shader = Shader(Resource::new(ResourceState::Ok(
@@ -554,11 +732,31 @@ lazy_static! {
     )));
 }
 
+lazy_static! {
+    static ref STANDARD_TERRAIN_ARRAY: Shader = Shader(Resource::new(ResourceState::Ok(
+        ShaderState::from_str(STANDARD_TERRAIN_ARRAY_SHADER_SRC, "StandardTerrainArray").unwrap(),
+    )));
+}
+
+lazy_static! {
+    static ref STANDARD_IMPOSTOR: Shader = Shader(Resource::new(ResourceState::Ok(
+        ShaderState::from_str(STANDARD_IMPOSTOR_SHADER_SRC, "StandardImpostor").unwrap(),
+    )));
+}
+
 #[cfg(test)]
 mod test {
-    use crate::material::shader::{
-        PropertyDefinition, PropertyKind, RenderPassDefinition, SamplerFallback, Shader,
-        ShaderDefinition,
+    use crate::{
+        core::algebra::Vector3,
+        material::shader::{
+            PropertyDefinition, PropertyKind, RenderPassDefinition, SamplerFallback, Shader,
+            ShaderDefinition, VertexAttributeDefinition,
+        },
+        renderer::framework::state::CompareFunc,
+        scene::mesh::{
+            buffer::{CustomVertexAttributeDescriptor, VertexAttributeDataType, VertexBuffer},
+            vertex::SimpleVertex,
+        },
     };
 
     #[test]
@@ -621,8 +819,180 @@ mod test {
                 vertex_shader: "<CODE>".to_string(),
                 fragment_shader: "<CODE>".to_string(),
             }],
+            vertex_attributes: Default::default(),
         };
 
         assert_eq!(data.definition, reference_definition);
     }
+
+    fn add_attribute<T: Copy>(buffer: &mut VertexBuffer, name: &str, fill_value: T) {
+        buffer
+            .modify()
+            .add_custom_attribute(
+                CustomVertexAttributeDescriptor {
+                    name: name.to_owned(),
+                    data_type: VertexAttributeDataType::F32,
+                    size: (std::mem::size_of::<T>() / std::mem::size_of::<f32>()) as u8,
+                },
+                fill_value,
+            )
+            .unwrap();
+    }
+
+    fn make_buffer() -> VertexBuffer {
+        VertexBuffer::new(
+            1,
+            SimpleVertex::layout(),
+            vec![SimpleVertex {
+                position: Vector3::default(),
+            }],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_custom_vertex_attributes() {
+        let code = r##"
+            (
+                name: "TestShader",
+                properties: [],
+                passes: [],
+                vertex_attributes: [
+                    (name: "wetness", size: 1),
+                    (name: "secondColor", size: 4),
+                ],
+            )
+            "##;
+
+        let shader = Shader::from_str(code, "test").unwrap();
+        let data = shader.data_ref();
+        let definition = &data.definition;
+
+        assert_eq!(
+            definition.vertex_attributes,
+            vec![
+                VertexAttributeDefinition {
+                    name: "wetness".to_owned(),
+                    size: 1
+                },
+                VertexAttributeDefinition {
+                    name: "secondColor".to_owned(),
+                    size: 4
+                }
+            ]
+        );
+        assert_eq!(
+            definition.vertex_attributes_source(),
+            "layout(location = 7) in float wetness;\nlayout(location = 8) in vec4 secondColor;\n"
+        );
+    }
+
+    #[test]
+    fn test_alpha_to_coverage_draw_parameter() {
+        let code = r##"
+            (
+                name: "TestShader",
+                properties: [],
+                passes: [
+                    (
+                        name: "GBuffer",
+                        draw_parameters: DrawParameters(
+                            cull_face: None,
+                            color_write: ColorMask(
+                                red: true,
+                                green: true,
+                                blue: true,
+                                alpha: true,
+                            ),
+                            depth_write: true,
+                            stencil_test: None,
+                            depth_test: true,
+                            blend: None,
+                            stencil_op: StencilOp(
+                                fail: Keep,
+                                zfail: Keep,
+                                zpass: Keep,
+                                write_mask: 0xFFFF_FFFF,
+                            ),
+                            alpha_to_coverage: true,
+                        ),
+                        vertex_shader: "<CODE>",
+                        fragment_shader: "<CODE>",
+                    ),
+                ],
+            )
+            "##;
+
+        let shader = Shader::from_str(code, "test").unwrap();
+        let data = shader.data_ref();
+
+        assert!(data.definition.passes[0].draw_parameters.alpha_to_coverage);
+        assert!(
+            !Shader::standard().data_ref().definition.passes[0]
+                .draw_parameters
+                .alpha_to_coverage
+        );
+    }
+
+    #[test]
+    fn test_standard_shader_depth_prepass() {
+        let shader = Shader::standard();
+        let data = shader.data_ref();
+        let passes = &data.definition.passes;
+
+        let gbuffer = passes.iter().find(|p| p.name == "GBuffer").unwrap();
+        let prepass = passes.iter().find(|p| p.name == "DepthPrePass").unwrap();
+
+        // Depth function is optional and it is the same for both passes, the renderer replaces it
+        // with `Equal` for GBuffer pass when the pre-pass is enabled.
+        assert_eq!(gbuffer.draw_parameters.depth_func, CompareFunc::LessOrEqual);
+        assert_eq!(prepass.draw_parameters.depth_func, CompareFunc::LessOrEqual);
+
+        assert!(prepass.draw_parameters.depth_write);
+        assert!(prepass.draw_parameters.depth_test);
+        assert_eq!(
+            prepass.draw_parameters.cull_face,
+            gbuffer.draw_parameters.cull_face
+        );
+        let color_write = &prepass.draw_parameters.color_write;
+        assert!(!color_write.red && !color_write.green && !color_write.blue && !color_write.alpha);
+    }
+
+    #[test]
+    fn test_validate_vertex_buffer() {
+        let definition = ShaderDefinition {
+            vertex_attributes: vec![
+                VertexAttributeDefinition {
+                    name: "wetness".to_owned(),
+                    size: 1,
+                },
+                VertexAttributeDefinition {
+                    name: "secondColor".to_owned(),
+                    size: 4,
+                },
+            ],
+            ..Default::default()
+        };
+
+        // Missing attributes.
+        let mut buffer = make_buffer();
+        add_attribute(&mut buffer, "wetness", 0.0f32);
+        assert!(definition.validate_vertex_buffer(&buffer).is_err());
+
+        // Matching attributes.
+        add_attribute(&mut buffer, "secondColor", [0.0f32; 4]);
+        assert!(definition.validate_vertex_buffer(&buffer).is_ok());
+
+        // Wrong order.
+        let mut buffer = make_buffer();
+        add_attribute(&mut buffer, "secondColor", [0.0f32; 4]);
+        add_attribute(&mut buffer, "wetness", 0.0f32);
+        assert!(definition.validate_vertex_buffer(&buffer).is_err());
+
+        // Wrong size.
+        let mut buffer = make_buffer();
+        add_attribute(&mut buffer, "wetness", [0.0f32; 2]);
+        add_attribute(&mut buffer, "secondColor", [0.0f32; 4]);
+        assert!(definition.validate_vertex_buffer(&buffer).is_err());
+    }
 }
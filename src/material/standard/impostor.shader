@@ -0,0 +1,179 @@
+(
+    name: "StandardImpostorShader",
+
+    // Each property's name must match respective uniform name. Properties of impostors are set
+    // by the renderer from respective Impostor node, see scene::impostor::Impostor.
+    properties: [
+        (
+            name: "impostorAtlas",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "impostorNormalAtlas",
+            kind: Sampler(default: None, fallback: Normal),
+        ),
+        (
+            name: "impostorSize",
+            kind: Vector2([1.0, 1.0]),
+        ),
+        (
+            name: "impostorCenter",
+            kind: Vector3([0.0, 0.0, 0.0]),
+        ),
+        (
+            name: "frameCount",
+            kind: UInt(1),
+        ),
+        (
+            // Amount of columns and rows of frames in the atlases.
+            name: "atlasGrid",
+            kind: Vector2([1.0, 1.0]),
+        ),
+        (
+            name: "blendFrames",
+            kind: Bool(true),
+        ),
+        (
+            name: "diffuseColor",
+            kind: Color(r: 255, g: 255, b: 255, a: 255),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "GBuffer",
+            draw_parameters: DrawParameters(
+                // The quad is always turned to the camera.
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+                r#"
+                layout(location = 0) in vec3 vertexPosition;
+
+                // Define uniforms with reserved names. Fyrox will automatically provide
+                // required data to these uniforms.
+                uniform mat4 fyrox_worldMatrix;
+                uniform mat4 fyrox_worldViewProjection;
+                uniform vec3 fyrox_cameraPosition;
+
+                uniform vec2 impostorSize;
+                uniform vec3 impostorCenter;
+                uniform uint frameCount;
+                uniform vec2 atlasGrid;
+                uniform bool blendFrames;
+
+                out vec2 firstTexCoord;
+                out vec2 secondTexCoord;
+                out float blendFactor;
+                out vec4 clipPosition;
+                out vec4 prevClipPosition;
+
+                vec2 FrameTexCoord(uint frame, vec2 texCoord)
+                {
+                    float column = float(frame % uint(atlasGrid.x));
+                    float row = float(frame / uint(atlasGrid.x));
+                    return (vec2(column, row) + texCoord) / atlasGrid;
+                }
+
+                void main()
+                {
+                    // Everything is done in local space of the impostor, the quad rotates only
+                    // around vertical axis.
+                    vec3 localCameraPosition = vec3(inverse(fyrox_worldMatrix) * vec4(fyrox_cameraPosition, 1.0));
+                    vec3 toCamera = localCameraPosition - impostorCenter;
+                    toCamera.y = 0.0;
+                    vec3 direction = length(toCamera) > 0.0001 ? normalize(toCamera) : vec3(0.0, 0.0, 1.0);
+                    vec3 right = cross(vec3(0.0, 1.0, 0.0), direction);
+
+                    vec3 localPosition = impostorCenter
+                        + right * vertexPosition.x * impostorSize.x
+                        + vec3(0.0, vertexPosition.y * impostorSize.y, 0.0);
+
+                    // Top of the frames is at the beginning of the atlases.
+                    vec2 texCoord = vec2(vertexPosition.x + 0.5, 0.5 - vertexPosition.y);
+
+                    // Must match Impostor::frames_for_direction.
+                    float angle = atan(direction.x, direction.z);
+                    if (angle < 0.0) {
+                        angle += 2.0 * PI;
+                    }
+                    float frame = angle / (2.0 * PI) * float(frameCount);
+                    uint first;
+                    uint second;
+                    if (blendFrames) {
+                        first = uint(floor(frame)) % frameCount;
+                        second = (first + 1u) % frameCount;
+                        blendFactor = fract(frame);
+                    } else {
+                        first = uint(floor(frame + 0.5)) % frameCount;
+                        second = first;
+                        blendFactor = 0.0;
+                    }
+                    firstTexCoord = FrameTexCoord(first, texCoord);
+                    secondTexCoord = FrameTexCoord(second, texCoord);
+
+                    gl_Position = fyrox_worldViewProjection * vec4(localPosition, 1.0);
+                    clipPosition = gl_Position;
+                    prevClipPosition = fyrox_prevWorldViewProjection * vec4(localPosition, 1.0);
+                }
+                "#,
+            fragment_shader:
+                r#"
+                layout(location = 0) out vec4 outColor;
+                layout(location = 1) out vec4 outNormal;
+                layout(location = 2) out vec4 outAmbient;
+                layout(location = 3) out vec4 outMaterial;
+                layout(location = 4) out uint outDecalMask;
+                layout(location = 5) out vec4 outMotionVector;
+
+                uniform mat4 fyrox_worldMatrix;
+
+                uniform sampler2D impostorAtlas;
+                uniform sampler2D impostorNormalAtlas;
+                uniform vec4 diffuseColor;
+
+                in vec2 firstTexCoord;
+                in vec2 secondTexCoord;
+                in float blendFactor;
+                in vec4 clipPosition;
+                in vec4 prevClipPosition;
+
+                void main()
+                {
+                    vec4 color = mix(texture(impostorAtlas, firstTexCoord), texture(impostorAtlas, secondTexCoord), blendFactor);
+                    if (color.a < 0.5) {
+                        discard;
+                    }
+
+                    // Normals are baked in local space of the impostor.
+                    vec3 n = mix(texture(impostorNormalAtlas, firstTexCoord).xyz, texture(impostorNormalAtlas, secondTexCoord).xyz, blendFactor);
+                    n = normalize(mat3(fyrox_worldMatrix) * (n * 2.0 - 1.0));
+
+                    outColor = diffuseColor * vec4(color.rgb, 1.0);
+                    outNormal = vec4(n * 0.5 + 0.5, 1.0);
+                    // Non-metallic, rough and not occluded - lighting is baked as neutral.
+                    outMaterial = vec4(0.0, 1.0, 1.0, 1.0);
+                    outAmbient = vec4(0.0, 0.0, 0.0, 1.0);
+                    outDecalMask = 0u;
+                    outMotionVector = vec4(S_ComputeMotionVector(clipPosition, prevClipPosition), 0.0, 1.0);
+                }
+                "#,
+        ),
+    ],
+)
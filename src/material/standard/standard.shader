@@ -51,6 +51,23 @@
             name: "diffuseColor",
             kind: Color(r: 255, g: 255, b: 255, a: 255),
         ),
+        // Sway vertices by the wind, red channel of vertex color defines flexibility of a vertex.
+        (
+            name: "vegetation",
+            kind: Bool(false),
+        ),
+        // Masked surfaces (foliage, fences, etc.). Alpha-to-coverage gives smooth edges when the
+        // G-Buffer is multisampled, otherwise it behaves as dithered transparency.
+        (
+            name: "alphaToCoverage",
+            kind: Bool(false),
+        ),
+        // Dithered transparency: alpha is used as a probability of a pixel to be drawn, so
+        // semi-transparent surfaces could be drawn without sorting.
+        (
+            name: "dithered",
+            kind: Bool(false),
+        ),
     ],
 
     passes: [
@@ -84,24 +101,35 @@
                 layout(location = 4) in vec4 boneWeights;
                 layout(location = 5) in vec4 boneIndices;
                 layout(location = 6) in vec2 vertexSecondTexCoord;
+                layout(location = 11) in vec4 vertexColor;
 
                 // Define uniforms with reserved names. Fyrox will automatically provide
                 // required data to these uniforms.
                 uniform mat4 fyrox_worldMatrix;
                 uniform mat4 fyrox_worldViewProjection;
+                #ifndef FYROX_BONE_MATRICES_TEXTURE
                 uniform mat4 fyrox_boneMatrices[60];
+                #endif
                 uniform bool fyrox_useSkeletalAnimation;
 
+                // Properties.
+                uniform bool vegetation;
+
                 out vec3 position;
                 out vec3 normal;
                 out vec2 texCoord;
                 out vec3 tangent;
                 out vec3 binormal;
                 out vec2 secondTexCoord;
+                out vec4 clipPosition;
+                out vec4 prevClipPosition;
+
+                invariant gl_Position;
 
                 void main()
                 {
                     vec4 localPosition = vec4(0);
+                    vec4 prevLocalPosition = vec4(0);
                     vec3 localNormal = vec3(0);
                     vec3 localTangent = vec3(0);
 
@@ -114,16 +142,22 @@
                         int i2 = int(boneIndices.z);
                         int i3 = int(boneIndices.w);
 
-                        mat4 m0 = fyrox_boneMatrices[i0];
-                        mat4 m1 = fyrox_boneMatrices[i1];
-                        mat4 m2 = fyrox_boneMatrices[i2];
-                        mat4 m3 = fyrox_boneMatrices[i3];
+                        mat4 m0 = S_FetchBoneMatrix(i0);
+                        mat4 m1 = S_FetchBoneMatrix(i1);
+                        mat4 m2 = S_FetchBoneMatrix(i2);
+                        mat4 m3 = S_FetchBoneMatrix(i3);
 
                         localPosition += m0 * vertex * boneWeights.x;
                         localPosition += m1 * vertex * boneWeights.y;
                         localPosition += m2 * vertex * boneWeights.z;
                         localPosition += m3 * vertex * boneWeights.w;
 
+                        // Previous pose is needed for motion vectors.
+                        prevLocalPosition += S_FetchPrevBoneMatrix(i0) * vertex * boneWeights.x;
+                        prevLocalPosition += S_FetchPrevBoneMatrix(i1) * vertex * boneWeights.y;
+                        prevLocalPosition += S_FetchPrevBoneMatrix(i2) * vertex * boneWeights.z;
+                        prevLocalPosition += S_FetchPrevBoneMatrix(i3) * vertex * boneWeights.w;
+
                         localNormal += mat3(m0) * vertexNormal * boneWeights.x;
                         localNormal += mat3(m1) * vertexNormal * boneWeights.y;
                         localNormal += mat3(m2) * vertexNormal * boneWeights.z;
@@ -137,10 +171,19 @@
                     else
                     {
                         localPosition = vec4(vertexPosition, 1.0);
+                        prevLocalPosition = localPosition;
                         localNormal = vertexNormal;
                         localTangent = vertexTangent.xyz;
                     }
 
+                    if (vegetation)
+                    {
+                        // Previous position is swayed by the same offset, so sway has no motion vectors.
+                        vec4 swayed = S_WindSwayLocal(localPosition, fyrox_worldMatrix, vertexColor.r);
+                        prevLocalPosition += swayed - localPosition;
+                        localPosition = swayed;
+                    }
+
                     mat3 nm = mat3(fyrox_worldMatrix);
                     normal = normalize(nm * localNormal);
                     tangent = normalize(nm * localTangent);
@@ -150,6 +193,8 @@
                     secondTexCoord = vertexSecondTexCoord;
 
                     gl_Position = fyrox_worldViewProjection * localPosition;
+                    clipPosition = gl_Position;
+                    prevClipPosition = fyrox_prevWorldViewProjection * prevLocalPosition;
                 }
                 "#,
             fragment_shader:
@@ -159,6 +204,7 @@
                 layout(location = 2) out vec4 outAmbient;
                 layout(location = 3) out vec4 outMaterial;
                 layout(location = 4) out uint outDecalMask;
+                layout(location = 5) out vec2 outMotionVector;
 
                 // Properties.
                 uniform sampler2D diffuseTexture;
@@ -173,6 +219,8 @@
                 uniform uint layerIndex;
                 uniform vec3 emissionStrength;
                 uniform vec4 diffuseColor;
+                uniform bool alphaToCoverage;
+                uniform bool dithered;
 
                 // Define uniforms with reserved names. Fyrox will automatically provide
                 // required data to these uniforms.
@@ -185,6 +233,8 @@
                 in vec3 tangent;
                 in vec3 binormal;
                 in vec2 secondTexCoord;
+                in vec4 clipPosition;
+                in vec4 prevClipPosition;
 
                 void main()
                 {
@@ -201,14 +251,19 @@
 
                     outColor = diffuseColor * texture(diffuseTexture, tc);
 
-                    // Alpha test.
-                    if (outColor.a < 0.5) {
+                    if (dithered || alphaToCoverage) {
+                        // Single-sampled coverage, there is no sharp edge to alias.
+                        if (outColor.a <= S_DitherThreshold(gl_FragCoord.xy)) {
+                            discard;
+                        }
+                    } else if (outColor.a < 0.5) {
+                        // Alpha test.
                         discard;
                     }
                     outColor.a = 1.0;
 
-                    vec4 n = normalize(texture(normalTexture, tc) * 2.0 - 1.0);
-                    outNormal = vec4(normalize(tangentSpace * n.xyz) * 0.5 + 0.5, 1.0);
+                    vec3 n = S_UnpackNormal(texture(normalTexture, tc));
+                    outNormal = vec4(normalize(tangentSpace * n) * 0.5 + 0.5, 1.0);
 
                     outMaterial.x = texture(metallicTexture, tc).r;
                     outMaterial.y = texture(roughnessTexture, tc).r;
@@ -219,6 +274,164 @@
                     outAmbient.a = 1.0;
 
                     outDecalMask = layerIndex;
+
+                    outMotionVector = S_ComputeMotionVector(clipPosition, prevClipPosition);
+                }
+                "#,
+        ),
+        (
+            // Optional pass that is rendered before GBuffer pass if depth pre-pass is enabled.
+            // Positions and discarded fragments must match GBuffer pass exactly, because GBuffer
+            // pass is rendered with Equal depth test after it.
+            name: "DepthPrePass",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: false,
+                    green: false,
+                    blue: false,
+                    alpha: false,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+                r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 2) in vec3 vertexNormal;
+                layout(location = 3) in vec4 vertexTangent;
+                layout(location = 4) in vec4 boneWeights;
+                layout(location = 5) in vec4 boneIndices;
+                layout(location = 11) in vec4 vertexColor;
+
+                uniform mat4 fyrox_worldMatrix;
+                uniform mat4 fyrox_worldViewProjection;
+                #ifndef FYROX_BONE_MATRICES_TEXTURE
+                uniform mat4 fyrox_boneMatrices[60];
+                #endif
+                uniform bool fyrox_useSkeletalAnimation;
+
+                // Properties.
+                uniform bool vegetation;
+
+                out vec3 position;
+                out vec3 normal;
+                out vec2 texCoord;
+                out vec3 tangent;
+                out vec3 binormal;
+
+                invariant gl_Position;
+
+                void main()
+                {
+                    vec4 localPosition = vec4(0);
+                    vec3 localNormal = vec3(0);
+                    vec3 localTangent = vec3(0);
+
+                    // Must be the same as in GBuffer pass, otherwise depth values won't match.
+                    if (fyrox_useSkeletalAnimation)
+                    {
+                        vec4 vertex = vec4(vertexPosition, 1.0);
+
+                        int i0 = int(boneIndices.x);
+                        int i1 = int(boneIndices.y);
+                        int i2 = int(boneIndices.z);
+                        int i3 = int(boneIndices.w);
+
+                        mat4 m0 = S_FetchBoneMatrix(i0);
+                        mat4 m1 = S_FetchBoneMatrix(i1);
+                        mat4 m2 = S_FetchBoneMatrix(i2);
+                        mat4 m3 = S_FetchBoneMatrix(i3);
+
+                        localPosition += m0 * vertex * boneWeights.x;
+                        localPosition += m1 * vertex * boneWeights.y;
+                        localPosition += m2 * vertex * boneWeights.z;
+                        localPosition += m3 * vertex * boneWeights.w;
+
+                        localNormal += mat3(m0) * vertexNormal * boneWeights.x;
+                        localNormal += mat3(m1) * vertexNormal * boneWeights.y;
+                        localNormal += mat3(m2) * vertexNormal * boneWeights.z;
+                        localNormal += mat3(m3) * vertexNormal * boneWeights.w;
+
+                        localTangent += mat3(m0) * vertexTangent.xyz * boneWeights.x;
+                        localTangent += mat3(m1) * vertexTangent.xyz * boneWeights.y;
+                        localTangent += mat3(m2) * vertexTangent.xyz * boneWeights.z;
+                        localTangent += mat3(m3) * vertexTangent.xyz * boneWeights.w;
+                    }
+                    else
+                    {
+                        localPosition = vec4(vertexPosition, 1.0);
+                        localNormal = vertexNormal;
+                        localTangent = vertexTangent.xyz;
+                    }
+
+                    if (vegetation)
+                    {
+                        localPosition = S_WindSwayLocal(localPosition, fyrox_worldMatrix, vertexColor.r);
+                    }
+
+                    // Tangent space is needed only for parallax mapping, it shifts texture
+                    // coordinates and so it affects alpha test.
+                    mat3 nm = mat3(fyrox_worldMatrix);
+                    normal = normalize(nm * localNormal);
+                    tangent = normalize(nm * localTangent);
+                    binormal = normalize(vertexTangent.w * cross(tangent, normal));
+                    texCoord = vertexTexCoord;
+                    position = vec3(fyrox_worldMatrix * localPosition);
+
+                    gl_Position = fyrox_worldViewProjection * localPosition;
+                }
+                "#,
+            fragment_shader:
+                r#"
+                // Properties.
+                uniform sampler2D diffuseTexture;
+                uniform sampler2D heightTexture;
+                uniform vec2 texCoordScale;
+                uniform vec4 diffuseColor;
+                uniform bool alphaToCoverage;
+                uniform bool dithered;
+
+                uniform vec3 fyrox_cameraPosition;
+                uniform bool fyrox_usePOM;
+
+                in vec3 position;
+                in vec3 normal;
+                in vec2 texCoord;
+                in vec3 tangent;
+                in vec3 binormal;
+
+                void main()
+                {
+                    // Masked materials must discard the same fragments as in GBuffer pass.
+                    vec2 tc;
+                    if (fyrox_usePOM) {
+                        mat3 tangentSpace = mat3(tangent, binormal, normal);
+                        vec3 toFragment = normalize(position - fyrox_cameraPosition);
+                        vec3 toFragmentTangentSpace = normalize(transpose(tangentSpace) * toFragment);
+                        tc = S_ComputeParallaxTextureCoordinates(heightTexture, toFragmentTangentSpace, texCoord * texCoordScale, normal);
+                    } else {
+                        tc = texCoord * texCoordScale;
+                    }
+
+                    float alpha = diffuseColor.a * texture(diffuseTexture, tc).a;
+
+                    if (dithered || alphaToCoverage) {
+                        if (alpha <= S_DitherThreshold(gl_FragCoord.xy)) {
+                            discard;
+                        }
+                    } else if (alpha < 0.5) {
+                        discard;
+                    }
                 }
                 "#,
         ),
@@ -252,10 +465,18 @@
                 layout(location = 1) in vec2 vertexTexCoord;
                 layout(location = 5) in vec4 boneWeights;
                 layout(location = 6) in vec4 boneIndices;
+                layout(location = 11) in vec4 vertexColor;
 
                 uniform mat4 fyrox_worldViewProjection;
                 uniform bool fyrox_useSkeletalAnimation;
+                #ifndef FYROX_BONE_MATRICES_TEXTURE
                 uniform mat4 fyrox_boneMatrices[60];
+                #endif
+
+                uniform mat4 fyrox_worldMatrix;
+
+                // Properties.
+                uniform bool vegetation;
 
                 out vec3 position;
                 out vec2 texCoord;
@@ -272,15 +493,19 @@
                         int i2 = int(boneIndices.z);
                         int i3 = int(boneIndices.w);
 
-                        localPosition += fyrox_boneMatrices[i0] * vertex * boneWeights.x;
-                        localPosition += fyrox_boneMatrices[i1] * vertex * boneWeights.y;
-                        localPosition += fyrox_boneMatrices[i2] * vertex * boneWeights.z;
-                        localPosition += fyrox_boneMatrices[i3] * vertex * boneWeights.w;
+                        localPosition += S_FetchBoneMatrix(i0) * vertex * boneWeights.x;
+                        localPosition += S_FetchBoneMatrix(i1) * vertex * boneWeights.y;
+                        localPosition += S_FetchBoneMatrix(i2) * vertex * boneWeights.z;
+                        localPosition += S_FetchBoneMatrix(i3) * vertex * boneWeights.w;
                     }
                     else
                     {
                         localPosition = vec4(vertexPosition, 1.0);
                     }
+                    if (vegetation)
+                    {
+                        localPosition = S_WindSwayLocal(localPosition, fyrox_worldMatrix, vertexColor.r);
+                    }
                     gl_Position = fyrox_worldViewProjection * localPosition;
                     texCoord = vertexTexCoord;
                 }
@@ -330,10 +555,18 @@
                 layout(location = 1) in vec2 vertexTexCoord;
                 layout(location = 4) in vec4 boneWeights;
                 layout(location = 5) in vec4 boneIndices;
+                layout(location = 11) in vec4 vertexColor;
 
                 uniform mat4 fyrox_worldViewProjection;
                 uniform bool fyrox_useSkeletalAnimation;
+                #ifndef FYROX_BONE_MATRICES_TEXTURE
                 uniform mat4 fyrox_boneMatrices[60];
+                #endif
+
+                uniform mat4 fyrox_worldMatrix;
+
+                // Properties.
+                uniform bool vegetation;
 
                 out vec2 texCoord;
 
@@ -345,15 +578,19 @@
                     {
                         vec4 vertex = vec4(vertexPosition, 1.0);
 
-                        localPosition += fyrox_boneMatrices[int(boneIndices.x)] * vertex * boneWeights.x;
-                        localPosition += fyrox_boneMatrices[int(boneIndices.y)] * vertex * boneWeights.y;
-                        localPosition += fyrox_boneMatrices[int(boneIndices.z)] * vertex * boneWeights.z;
-                        localPosition += fyrox_boneMatrices[int(boneIndices.w)] * vertex * boneWeights.w;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.x)) * vertex * boneWeights.x;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.y)) * vertex * boneWeights.y;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.z)) * vertex * boneWeights.z;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.w)) * vertex * boneWeights.w;
                     }
                     else
                     {
                         localPosition = vec4(vertexPosition, 1.0);
                     }
+                    if (vegetation)
+                    {
+                        localPosition = S_WindSwayLocal(localPosition, fyrox_worldMatrix, vertexColor.r);
+                    }
 
                     gl_Position = fyrox_worldViewProjection * localPosition;
                     texCoord = vertexTexCoord;
@@ -401,10 +638,18 @@
                 layout(location = 1) in vec2 vertexTexCoord;
                 layout(location = 4) in vec4 boneWeights;
                 layout(location = 5) in vec4 boneIndices;
+                layout(location = 11) in vec4 vertexColor;
 
                 uniform mat4 fyrox_worldViewProjection;
                 uniform bool fyrox_useSkeletalAnimation;
+                #ifndef FYROX_BONE_MATRICES_TEXTURE
                 uniform mat4 fyrox_boneMatrices[60];
+                #endif
+
+                uniform mat4 fyrox_worldMatrix;
+
+                // Properties.
+                uniform bool vegetation;
 
                 out vec2 texCoord;
 
@@ -416,15 +661,19 @@
                     {
                         vec4 vertex = vec4(vertexPosition, 1.0);
 
-                        localPosition += fyrox_boneMatrices[int(boneIndices.x)] * vertex * boneWeights.x;
-                        localPosition += fyrox_boneMatrices[int(boneIndices.y)] * vertex * boneWeights.y;
-                        localPosition += fyrox_boneMatrices[int(boneIndices.z)] * vertex * boneWeights.z;
-                        localPosition += fyrox_boneMatrices[int(boneIndices.w)] * vertex * boneWeights.w;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.x)) * vertex * boneWeights.x;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.y)) * vertex * boneWeights.y;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.z)) * vertex * boneWeights.z;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.w)) * vertex * boneWeights.w;
                     }
                     else
                     {
                         localPosition = vec4(vertexPosition, 1.0);
                     }
+                    if (vegetation)
+                    {
+                        localPosition = S_WindSwayLocal(localPosition, fyrox_worldMatrix, vertexColor.r);
+                    }
 
                     gl_Position = fyrox_worldViewProjection * localPosition;
                     texCoord = vertexTexCoord;
@@ -472,11 +721,17 @@
                 layout(location = 1) in vec2 vertexTexCoord;
                 layout(location = 4) in vec4 boneWeights;
                 layout(location = 5) in vec4 boneIndices;
+                layout(location = 11) in vec4 vertexColor;
 
                 uniform mat4 fyrox_worldMatrix;
                 uniform mat4 fyrox_worldViewProjection;
                 uniform bool fyrox_useSkeletalAnimation;
+                #ifndef FYROX_BONE_MATRICES_TEXTURE
                 uniform mat4 fyrox_boneMatrices[60];
+                #endif
+
+                // Properties.
+                uniform bool vegetation;
 
                 out vec2 texCoord;
                 out vec3 worldPosition;
@@ -489,15 +744,19 @@
                     {
                         vec4 vertex = vec4(vertexPosition, 1.0);
 
-                        localPosition += fyrox_boneMatrices[int(boneIndices.x)] * vertex * boneWeights.x;
-                        localPosition += fyrox_boneMatrices[int(boneIndices.y)] * vertex * boneWeights.y;
-                        localPosition += fyrox_boneMatrices[int(boneIndices.z)] * vertex * boneWeights.z;
-                        localPosition += fyrox_boneMatrices[int(boneIndices.w)] * vertex * boneWeights.w;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.x)) * vertex * boneWeights.x;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.y)) * vertex * boneWeights.y;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.z)) * vertex * boneWeights.z;
+                        localPosition += S_FetchBoneMatrix(int(boneIndices.w)) * vertex * boneWeights.w;
                     }
                     else
                     {
                         localPosition = vec4(vertexPosition, 1.0);
                     }
+                    if (vegetation)
+                    {
+                        localPosition = S_WindSwayLocal(localPosition, fyrox_worldMatrix, vertexColor.r);
+                    }
 
                     gl_Position = fyrox_worldViewProjection * localPosition;
                     worldPosition = (fyrox_worldMatrix * localPosition).xyz;
@@ -103,6 +103,8 @@
                 out vec3 tangent;
                 out vec3 binormal;
                 out vec2 secondTexCoord;
+                out vec4 clipPosition;
+                out vec4 prevClipPosition;
 
                 void main()
                 {
@@ -114,6 +116,8 @@
                     position = vec3(fyrox_worldMatrix * vec4(vertexPosition, 1.0));
                     secondTexCoord = vertexSecondTexCoord;
                     gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                    clipPosition = gl_Position;
+                    prevClipPosition = fyrox_prevWorldViewProjection * vec4(vertexPosition, 1.0);
                 }
                 "#,
             fragment_shader:
@@ -123,6 +127,8 @@
                 layout(location = 2) out vec4 outAmbient;
                 layout(location = 3) out vec4 outMaterial;
                 layout(location = 4) out uint outDecalMask;
+                // Alpha is used only for blending, motion vectors are the same for every layer.
+                layout(location = 5) out vec4 outMotionVector;
 
                 // Properties.
                 uniform sampler2D diffuseTexture;
@@ -150,6 +156,8 @@
                 in vec3 tangent;
                 in vec3 binormal;
                 in vec2 secondTexCoord;
+                in vec4 clipPosition;
+                in vec4 prevClipPosition;
 
                 void main()
                 {
@@ -166,8 +174,8 @@
 
                     outColor = diffuseColor * texture(diffuseTexture, tc);
 
-                    vec4 n = normalize(texture(normalTexture, tc) * 2.0 - 1.0);
-                    outNormal = vec4(normalize(tangentSpace * n.xyz) * 0.5 + 0.5, 1.0);
+                    vec3 n = S_UnpackNormal(texture(normalTexture, tc));
+                    outNormal = vec4(normalize(tangentSpace * n) * 0.5 + 0.5, 1.0);
 
                     outMaterial.x = texture(metallicTexture, tc).r;
                     outMaterial.y = texture(roughnessTexture, tc).r;
@@ -178,6 +186,7 @@
                     outAmbient.a = 1.0;
 
                     outDecalMask = layerIndex;
+                    outMotionVector = vec4(S_ComputeMotionVector(clipPosition, prevClipPosition), 0.0, 1.0);
 
                     float mask = texture(maskTexture, texCoord).r;
 
@@ -0,0 +1,446 @@
+(
+    name: "StandardTerrainArrayShader",
+
+    // Each property's name must match respective uniform name. This shader renders all layers of
+    // a terrain chunk in a single pass, every texture is an array where each layer of the array
+    // corresponds to respective terrain layer.
+    properties: [
+        (
+            name: "diffuseTexture",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "normalTexture",
+            kind: Sampler(default: None, fallback: Normal),
+        ),
+        (
+            name: "metallicTexture",
+            kind: Sampler(default: None, fallback: Black),
+        ),
+        (
+            name: "roughnessTexture",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "emissionTexture",
+            kind: Sampler(default: None, fallback: Black),
+        ),
+        (
+            name: "lightmapTexture",
+            kind: Sampler(default: None, fallback: Black),
+        ),
+        (
+            name: "aoTexture",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "maskTexture",
+            kind: Sampler(default: None, fallback: White),
+        ),
+        (
+            name: "texCoordScale",
+            kind: Vector2Array([]),
+        ),
+        (
+            name: "layerCount",
+            kind: UInt(0),
+        ),
+        (
+            name: "layerIndex",
+            kind: UInt(0),
+        ),
+        (
+            name: "emissionStrength",
+            kind: Vector3Array([]),
+        ),
+        (
+            name: "diffuseColor",
+            kind: Vector4Array([]),
+        ),
+    ],
+
+    passes: [
+        (
+            name: "GBuffer",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: Some(BlendFunc(
+                    sfactor: SrcAlpha,
+                    dfactor: OneMinusSrcAlpha,
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+                r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 2) in vec3 vertexNormal;
+                layout(location = 3) in vec4 vertexTangent;
+                layout(location = 4) in vec4 boneWeights;
+                layout(location = 5) in vec4 boneIndices;
+                layout(location = 6) in vec2 vertexSecondTexCoord;
+
+                // Define uniforms with reserved names. Fyrox will automatically provide
+                // required data to these uniforms.
+                uniform mat4 fyrox_worldMatrix;
+                uniform mat4 fyrox_worldViewProjection;
+
+                out vec3 position;
+                out vec3 normal;
+                out vec2 texCoord;
+                out vec3 tangent;
+                out vec3 binormal;
+                out vec2 secondTexCoord;
+                out vec4 clipPosition;
+                out vec4 prevClipPosition;
+
+                void main()
+                {
+                    mat3 nm = mat3(fyrox_worldMatrix);
+                    normal = normalize(nm * vertexNormal);
+                    tangent = normalize(nm * vertexTangent.xyz);
+                    binormal = normalize(vertexTangent.w * cross(tangent, normal));
+                    texCoord = vertexTexCoord;
+                    position = vec3(fyrox_worldMatrix * vec4(vertexPosition, 1.0));
+                    secondTexCoord = vertexSecondTexCoord;
+                    gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                    clipPosition = gl_Position;
+                    prevClipPosition = fyrox_prevWorldViewProjection * vec4(vertexPosition, 1.0);
+                }
+                "#,
+            fragment_shader:
+                r#"
+                layout(location = 0) out vec4 outColor;
+                layout(location = 1) out vec4 outNormal;
+                layout(location = 2) out vec4 outAmbient;
+                layout(location = 3) out vec4 outMaterial;
+                layout(location = 4) out uint outDecalMask;
+                // Alpha is used only for blending, motion vectors are the same for every layer.
+                layout(location = 5) out vec4 outMotionVector;
+
+                #define MAX_LAYERS 32
+
+                // Properties.
+                uniform sampler2DArray diffuseTexture;
+                uniform sampler2DArray normalTexture;
+                uniform sampler2DArray metallicTexture;
+                uniform sampler2DArray roughnessTexture;
+                uniform sampler2DArray emissionTexture;
+                uniform sampler2DArray aoTexture;
+                uniform sampler2DArray maskTexture;
+                uniform sampler2D lightmapTexture;
+                uniform vec2 texCoordScale[MAX_LAYERS];
+                uniform vec3 emissionStrength[MAX_LAYERS];
+                uniform vec4 diffuseColor[MAX_LAYERS];
+                uniform uint layerCount;
+                uniform uint layerIndex;
+
+                in vec3 position;
+                in vec3 normal;
+                in vec2 texCoord;
+                in vec3 tangent;
+                in vec3 binormal;
+                in vec2 secondTexCoord;
+                in vec4 clipPosition;
+                in vec4 prevClipPosition;
+
+                void main()
+                {
+                    mat3 tangentSpace = mat3(tangent, binormal, normal);
+
+                    vec3 color = vec3(0.0);
+                    vec3 packedNormal = vec3(0.0);
+                    vec3 material = vec3(0.0);
+                    vec3 emission = vec3(0.0);
+                    float transparency = 1.0;
+
+                    // Layers are blended in the same way as separate passes with SrcAlpha blending.
+                    for (uint i = 0u; i < layerCount; ++i) {
+                        float layer = float(i);
+                        vec3 tc = vec3(texCoord * texCoordScale[i], layer);
+                        float mask = texture(maskTexture, vec3(texCoord, layer)).r;
+
+                        vec3 layerColor = diffuseColor[i].rgb * texture(diffuseTexture, tc).rgb;
+                        color = mix(color, layerColor, mask);
+
+                        vec3 n = S_UnpackNormal(texture(normalTexture, tc));
+                        packedNormal = mix(packedNormal, normalize(tangentSpace * n) * 0.5 + 0.5, mask);
+
+                        vec3 layerMaterial = vec3(
+                            texture(metallicTexture, tc).r,
+                            texture(roughnessTexture, tc).r,
+                            texture(aoTexture, tc).r
+                        );
+                        material = mix(material, layerMaterial, mask);
+
+                        emission = mix(emission, emissionStrength[i] * texture(emissionTexture, tc).rgb, mask);
+
+                        transparency *= 1.0 - mask;
+                    }
+
+                    float coverage = 1.0 - transparency;
+                    if (coverage <= 0.0) {
+                        discard;
+                    }
+
+                    outColor = vec4(color / coverage, coverage);
+                    outNormal = vec4(packedNormal / coverage, coverage);
+                    outMaterial = vec4(material / coverage, coverage);
+                    outAmbient = vec4(emission / coverage + texture(lightmapTexture, secondTexCoord).rgb, coverage);
+                    outDecalMask = layerIndex;
+                    outMotionVector = vec4(S_ComputeMotionVector(clipPosition, prevClipPosition), 0.0, 1.0);
+                }
+                "#,
+        ),
+        (
+            name: "Forward",
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: Some(BlendFunc(
+                    sfactor: SrcAlpha,
+                    dfactor: OneMinusSrcAlpha,
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+            vertex_shader:
+               r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+
+                uniform mat4 fyrox_worldViewProjection;
+
+                out vec3 position;
+                out vec2 texCoord;
+
+                void main()
+                {
+                    gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                    texCoord = vertexTexCoord;
+                }
+               "#,
+
+           fragment_shader:
+               r#"
+                #define MAX_LAYERS 32
+
+                uniform sampler2DArray diffuseTexture;
+                uniform sampler2DArray maskTexture;
+                uniform vec2 texCoordScale[MAX_LAYERS];
+                uniform vec4 diffuseColor[MAX_LAYERS];
+                uniform uint layerCount;
+
+                out vec4 FragColor;
+
+                in vec2 texCoord;
+
+                void main()
+                {
+                    vec4 color = vec4(0.0);
+                    float transparency = 1.0;
+                    for (uint i = 0u; i < layerCount; ++i) {
+                        float layer = float(i);
+                        float mask = texture(maskTexture, vec3(texCoord, layer)).r;
+                        vec4 layerColor = diffuseColor[i] * texture(diffuseTexture, vec3(texCoord * texCoordScale[i], layer));
+                        color = mix(color, layerColor, mask);
+                        transparency *= 1.0 - mask;
+                    }
+                    float coverage = 1.0 - transparency;
+                    if (coverage <= 0.0) {
+                        discard;
+                    }
+                    FragColor = vec4(color.rgb / coverage, coverage);
+                }
+               "#,
+        ),
+        (
+            name: "SpotShadow",
+
+            draw_parameters: DrawParameters (
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: false,
+                    green: false,
+                    blue: false,
+                    alpha: false,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+
+            vertex_shader:
+                r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+
+                uniform mat4 fyrox_worldViewProjection;
+
+                out vec2 texCoord;
+
+                void main()
+                {
+                    gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                    texCoord = vertexTexCoord;
+                }
+                "#,
+
+            fragment_shader:
+                r#"
+                uniform sampler2DArray diffuseTexture;
+
+                in vec2 texCoord;
+
+                void main()
+                {
+                    if (texture(diffuseTexture, vec3(texCoord, 0.0)).a < 0.2) discard;
+                }
+                "#,
+        ),
+        (
+            name: "DirectionalShadow",
+
+            draw_parameters: DrawParameters (
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: false,
+                    green: false,
+                    blue: false,
+                    alpha: false,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+
+            vertex_shader:
+                r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+
+                uniform mat4 fyrox_worldViewProjection;
+
+                out vec2 texCoord;
+
+                void main()
+                {
+                    gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                    texCoord = vertexTexCoord;
+                }
+                "#,
+
+            fragment_shader:
+                r#"
+                uniform sampler2DArray diffuseTexture;
+
+                in vec2 texCoord;
+
+                void main()
+                {
+                    if (texture(diffuseTexture, vec3(texCoord, 0.0)).a < 0.2) discard;
+                }
+                "#,
+        ),
+        (
+            name: "PointShadow",
+
+            draw_parameters: DrawParameters (
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: true,
+                stencil_test: None,
+                depth_test: true,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+            ),
+
+            vertex_shader:
+                r#"
+                layout(location = 0) in vec3 vertexPosition;
+                layout(location = 1) in vec2 vertexTexCoord;
+
+                uniform mat4 fyrox_worldMatrix;
+                uniform mat4 fyrox_worldViewProjection;
+
+                out vec2 texCoord;
+                out vec3 worldPosition;
+
+                void main()
+                {
+                    gl_Position = fyrox_worldViewProjection * vec4(vertexPosition, 1.0);
+                    worldPosition = (fyrox_worldMatrix * vec4(vertexPosition, 1.0)).xyz;
+                    texCoord = vertexTexCoord;
+                }
+                "#,
+
+            fragment_shader:
+                r#"
+                uniform sampler2DArray diffuseTexture;
+
+                uniform vec3 fyrox_lightPosition;
+
+                in vec2 texCoord;
+                in vec3 worldPosition;
+
+                layout(location = 0) out float depth;
+
+                void main()
+                {
+                    if (texture(diffuseTexture, vec3(texCoord, 0.0)).a < 0.2) discard;
+                    depth = length(fyrox_lightPosition - worldPosition);
+                }
+                "#,
+        )
+    ],
+)
\ No newline at end of file
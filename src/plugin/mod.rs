@@ -5,12 +5,18 @@
 use crate::{
     core::pool::Handle,
     core::uuid::Uuid,
-    engine::{resource_manager::ResourceManager, SerializationContext},
+    engine::{
+        frame_pacing::FramePacing,
+        project::ProjectManifest,
+        resource_manager::ResourceManager,
+        task::{TaskContainer, TaskHandle, TaskResult},
+        SerializationContext,
+    },
     event::Event,
     renderer::Renderer,
     scene::{Scene, SceneContainer},
 };
-use std::sync::Arc;
+use std::{any::Any, sync::Arc};
 
 /// Contains plugin environment for the registration stage.
 pub struct PluginRegistrationContext {
@@ -49,6 +55,43 @@ pub struct PluginContext<'a> {
     /// A reference to serialization context of the engine. See [`SerializationContext`] for more
     /// info.
     pub serialization_context: Arc<SerializationContext>,
+
+    /// A reference to the manifest of the running project, `None` if the project has no manifest.
+    /// See [`ProjectManifest`] docs for more info.
+    pub project: Option<&'a ProjectManifest>,
+
+    /// A reference to the container of tasks spawned by plugins, every task is owned by a plugin
+    /// with respective id. Use [`PluginContext::spawn_task`] to spawn new tasks.
+    pub tasks: &'a mut TaskContainer<Uuid>,
+
+    /// A reference to frame pacing settings of the engine, it could be used to limit frame rate,
+    /// change vertical synchronization mode, etc. See [`FramePacing`] docs for more info.
+    pub frame_pacing: &'a mut FramePacing,
+}
+
+impl<'a> PluginContext<'a> {
+    /// Spawns a task that runs given closure on a worker thread. The result of the closure will be
+    /// passed to [`Plugin::on_task_complete`] of the plugin with the given id on the main thread.
+    /// The closure must own every piece of data it needs, the scenes cannot be accessed from it.
+    /// See [`TaskContainer`] docs for more info.
+    ///
+    /// ```rust
+    /// # use fyrox::{plugin::{Plugin, PluginContext}, core::uuid::Uuid};
+    /// # struct MyPlugin;
+    /// # impl Plugin for MyPlugin {
+    /// fn update(&mut self, context: &mut PluginContext) {
+    ///     context.spawn_task(self.id(), || (0..1_000_000u64).sum::<u64>());
+    /// }
+    /// #   fn id(&self) -> Uuid { todo!() }
+    /// # }
+    /// ```
+    pub fn spawn_task<F, T>(&mut self, plugin_id: Uuid, func: F) -> TaskHandle
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Any + Send,
+    {
+        self.tasks.spawn(plugin_id, func)
+    }
 }
 
 /// Plugin is a convenient interface that allow you to extend engine's functionality.
@@ -211,6 +254,17 @@ pub trait Plugin: 'static {
     /// Use <https://www.uuidgenerator.net/> to generate one.
     fn id(&self) -> Uuid;
 
+    /// The method is called when a task spawned by the plugin (see [`PluginContext::spawn_task`])
+    /// is finished. It is called on the main thread right after [`Self::update`], so it is safe to
+    /// modify the scenes here. Use [`Box::downcast`] to get the actual value of the result.
+    fn on_task_complete(
+        &mut self,
+        #[allow(unused_variables)] task: TaskHandle,
+        #[allow(unused_variables)] result: TaskResult,
+        #[allow(unused_variables)] context: PluginContext,
+    ) {
+    }
+
     /// The method is called when the main window receives an event from the OS. The main use of
     /// the method is to respond to some external events, for example an event from keyboard or
     /// gamepad. See [`Event`] docs for more info.
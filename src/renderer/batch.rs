@@ -1,16 +1,24 @@
 //! The module responsible for batch generation for rendering optimizations.
 
 use crate::{
+    asset::ResourceState,
     core::{
-        algebra::Matrix4, arrayvec::ArrayVec, parking_lot::Mutex, pool::Handle, scope_profile,
+        algebra::Matrix4, parking_lot::Mutex, pool::Handle, scope_profile,
         sstorage::ImmutableString,
     },
     material::{Material, PropertyValue},
+    renderer::{
+        framework::{error::FrameworkError, gpu_texture::GpuTexture, state::PipelineState},
+        storage::MatrixStorage,
+    },
     scene::{
+        base::{LayerMask, Mobility},
         graph::Graph,
+        impostor::Impostor,
         mesh::{surface::SurfaceData, Mesh, RenderPath},
         node::Node,
         terrain::Terrain,
+        wind::Wind,
     },
     utils::log::{Log, MessageKind},
 };
@@ -18,13 +26,19 @@ use bitflags::bitflags;
 use fxhash::{FxHashMap, FxHasher};
 use fyrox_core::math::aabb::AxisAlignedBoundingBox;
 use std::{
+    cell::RefCell,
     fmt::{Debug, Formatter},
     hash::Hasher,
+    ops::{Deref, Range},
+    rc::Rc,
     sync::Arc,
 };
 
-/// Maximum amount of bone matrices per instance.
-pub const BONE_MATRICES_COUNT: usize = 64;
+/// Maximum amount of bone matrices per instance that can be passed to shaders via the
+/// `fyrox_boneMatrices` uniform array. This limit is used only on GPUs that cannot fetch
+/// textures in vertex shaders, otherwise bone matrices are stored in a texture and there is
+/// no limit.
+pub const BONE_MATRICES_COUNT: usize = 60;
 
 bitflags! {
     /// A set of flags for surface instance. It is just a compact way for storing multiple boolean
@@ -38,6 +52,8 @@ bitflags! {
         const CAST_SHADOWS = 0b0000_0010;
         /// Whether the isntance should use frustum culling or not.
         const FRUSTUM_CULLING = 0b0000_0100;
+        /// Whether the instance belongs to a node with static or stationary mobility or not.
+        const IS_STATIC = 0b0000_1000;
     }
 }
 
@@ -55,6 +71,9 @@ impl SurfaceInstanceFlags {
         if node.frustum_culling() {
             flags.insert(SurfaceInstanceFlags::FRUSTUM_CULLING);
         }
+        if node.mobility() != Mobility::Dynamic {
+            flags.insert(SurfaceInstanceFlags::IS_STATIC);
+        }
 
         flags
     }
@@ -66,14 +85,20 @@ pub struct SurfaceInstance {
     pub owner: Handle<Node>,
     /// A world matrix.
     pub world_transform: Matrix4<f32>,
+    /// A world matrix from the previous frame, it is used to calculate motion vectors. It is equal
+    /// to `world_transform` if the previous transform is unknown.
+    pub prev_world_transform: Matrix4<f32>,
     /// A set of flags for surface instance.
     pub flags: SurfaceInstanceFlags,
     /// World space axis-aligned bounding box.
     pub world_aabb: AxisAlignedBoundingBox,
-    /// A set of bone matrices.
-    pub bone_matrices: ArrayVec<Matrix4<f32>, BONE_MATRICES_COUNT>,
+    /// A range of bone matrices of the instance in [`BatchStorage::bone_matrices`]. Use
+    /// [`BatchStorage::instance_bone_matrices`] to get the matrices.
+    pub bone_matrices: Range<usize>,
     /// A depth-hack value.
     pub depth_offset: f32,
+    /// Render mask of an owner node.
+    pub render_mask: LayerMask,
 }
 
 /// A set of surface instances that share the same vertex/index data and a material.
@@ -113,12 +138,54 @@ pub struct BatchStorage {
     batch_map: FxHashMap<u64, usize>,
     /// Sorted list of batches.
     pub batches: Vec<Batch>,
+    /// Bone matrices of every skinned instance, instances with the same set of bones share
+    /// the matrices.
+    pub bone_matrices: Vec<Matrix4<f32>>,
+    /// Bone matrices from the previous frame in the same layout as [`Self::bone_matrices`]. It
+    /// is empty if previous transforms of nodes were not provided for batch generation.
+    pub prev_bone_matrices: Vec<Matrix4<f32>>,
+    /// Wind of the graph the batches were generated for, it is passed to shaders as built-in
+    /// uniforms.
+    pub wind: Wind,
+    impostor_materials: FxHashMap<u64, Arc<Mutex<Material>>>,
+    bone_matrices_storage: Option<MatrixStorage>,
+    prev_bone_matrices_storage: Option<MatrixStorage>,
+}
+
+/// Checks custom vertex attributes of surface data against the shader of a material. The check
+/// is done only for shaders with custom vertex attributes, so standard surfaces are not affected.
+fn validate_vertex_attributes(
+    data: &Mutex<SurfaceData>,
+    material: &Mutex<Material>,
+) -> Result<(), String> {
+    let material = material.lock();
+    let shader = material.shader().state();
+    if let ResourceState::Ok(shader_state) = shader.deref() {
+        if !shader_state.definition.vertex_attributes.is_empty() {
+            return shader_state
+                .definition
+                .validate_vertex_buffer(&data.lock().vertex_buffer);
+        }
+    }
+    Ok(())
 }
 
 impl BatchStorage {
-    pub(in crate) fn generate_batches(&mut self, graph: &Graph) {
+    /// Generates batches for the given graph. Optional `prev_transforms` are global transforms of
+    /// the nodes from the previous frame, they are used to calculate motion vectors.
+    pub(crate) fn generate_batches(
+        &mut self,
+        graph: &Graph,
+        prev_transforms: Option<&FxHashMap<Handle<Node>, Matrix4<f32>>>,
+    ) {
         scope_profile!();
 
+        let prev_transform = |handle: Handle<Node>| {
+            prev_transforms
+                .and_then(|transforms| transforms.get(&handle).cloned())
+                .unwrap_or_else(|| graph[handle].global_transform())
+        };
+
         for batch in self.batches.iter_mut() {
             batch.instances.clear();
             self.buffers
@@ -127,16 +194,22 @@ impl BatchStorage {
 
         self.batches.clear();
         self.batch_map.clear();
+        self.bone_matrices.clear();
+        self.prev_bone_matrices.clear();
+        self.wind = graph.wind.clone();
+
+        let mut bone_sets = FxHashMap::<&[Handle<Node>], Range<usize>>::default();
+        let mut used_impostor_materials = FxHashMap::default();
 
         for (handle, node) in graph.pair_iter() {
             if let Some(mesh) = node.cast::<Mesh>() {
                 for surface in mesh.surfaces().iter() {
                     let is_skinned = !surface.bones.is_empty();
 
-                    let world = if is_skinned {
-                        Matrix4::identity()
+                    let (world, prev_world) = if is_skinned {
+                        (Matrix4::identity(), Matrix4::identity())
                     } else {
-                        mesh.global_transform()
+                        (mesh.global_transform(), prev_transform(handle))
                     };
 
                     let data = surface.data();
@@ -145,6 +218,18 @@ impl BatchStorage {
                     let batch = if let Some(&batch_index) = self.batch_map.get(&batch_id) {
                         self.batches.get_mut(batch_index).unwrap()
                     } else {
+                        if let Err(reason) = validate_vertex_attributes(&data, surface.material()) {
+                            Log::writeln(
+                                MessageKind::Error,
+                                format!(
+                                    "Surface of {} mesh cannot be rendered. Reason: {}",
+                                    mesh.name(),
+                                    reason
+                                ),
+                            );
+                            continue;
+                        }
+
                         self.batch_map.insert(batch_id, self.batches.len());
                         self.batches.push(Batch {
                             id: batch_id,
@@ -168,93 +253,208 @@ impl BatchStorage {
                     batch.sort_index = surface.material_id();
                     batch.material = surface.material().clone();
 
+                    let bone_matrices = if is_skinned {
+                        let pool = &mut self.bone_matrices;
+                        let prev_pool = &mut self.prev_bone_matrices;
+                        bone_sets
+                            .entry(surface.bones.as_slice())
+                            .or_insert_with(|| {
+                                let start = pool.len();
+                                pool.extend(surface.bones.iter().map(|&bone_handle| {
+                                    let bone_node = &graph[bone_handle];
+                                    bone_node.global_transform()
+                                        * bone_node.inv_bind_pose_transform()
+                                }));
+                                if prev_transforms.is_some() {
+                                    prev_pool.extend(surface.bones.iter().map(|&bone_handle| {
+                                        prev_transform(bone_handle)
+                                            * graph[bone_handle].inv_bind_pose_transform()
+                                    }));
+                                }
+                                start..pool.len()
+                            })
+                            .clone()
+                    } else {
+                        Default::default()
+                    };
+
                     batch.instances.push(SurfaceInstance {
                         world_transform: world,
+                        prev_world_transform: prev_world,
                         flags: SurfaceInstanceFlags::from_node(node),
                         world_aabb: node.world_bounding_box(),
-                        bone_matrices: surface
-                            .bones
-                            .iter()
-                            .map(|&bone_handle| {
-                                let bone_node = &graph[bone_handle];
-                                bone_node.global_transform() * bone_node.inv_bind_pose_transform()
-                            })
-                            .collect(),
+                        bone_matrices,
                         owner: handle,
                         depth_offset: mesh.depth_offset_factor(),
+                        render_mask: node.render_mask(),
                     });
                 }
             } else if let Some(terrain) = node.cast::<Terrain>() {
-                for (layer_index, layer) in terrain.layers().iter().enumerate() {
-                    for (chunk_index, chunk) in terrain.chunks_ref().iter().enumerate() {
+                let single_pass_materials = terrain.single_pass_materials();
+                if !single_pass_materials.is_empty() {
+                    // Every chunk is rendered in one pass with all layers at once.
+                    for (chunk, material) in terrain.chunks_ref().iter().zip(single_pass_materials)
+                    {
                         let data = chunk.data();
-                        let data_key = &*data as *const _ as u64;
-
-                        let mut material = (*layer.material.lock()).clone();
-                        match material.set_property(
-                            &ImmutableString::new(&layer.mask_property_name),
-                            PropertyValue::Sampler {
-                                value: Some(layer.chunk_masks[chunk_index].clone()),
-                                fallback: Default::default(),
-                            },
-                        ) {
-                            Ok(_) => {
-                                let material = Arc::new(Mutex::new(material));
-
-                                let mut hasher = FxHasher::default();
-
-                                hasher.write_u64(&*material as *const _ as u64);
-                                hasher.write_u64(data_key);
-
-                                let key = hasher.finish();
-
-                                let batch = if let Some(&batch_index) = self.batch_map.get(&key) {
-                                    self.batches.get_mut(batch_index).unwrap()
-                                } else {
-                                    self.batch_map.insert(key, self.batches.len());
-                                    self.batches.push(Batch {
-                                        id: key,
-                                        data: data.clone(),
-                                        instances: self
-                                            .buffers
-                                            .remove_entry(&key)
-                                            .map(|(_, buf)| buf)
-                                            .unwrap_or_default(),
-                                        material: material.clone(),
-                                        is_skinned: false,
-                                        render_path: RenderPath::Deferred,
-                                        sort_index: layer_index as u64,
-                                        decal_layer_index: terrain.decal_layer_index(),
+
+                        let mut hasher = FxHasher::default();
+                        hasher.write_u64(&**material as *const _ as u64);
+                        hasher.write_u64(&*data as *const _ as u64);
+                        let key = hasher.finish();
+
+                        let batch = if let Some(&batch_index) = self.batch_map.get(&key) {
+                            self.batches.get_mut(batch_index).unwrap()
+                        } else {
+                            self.batch_map.insert(key, self.batches.len());
+                            self.batches.push(Batch {
+                                id: key,
+                                data: data.clone(),
+                                instances: self
+                                    .buffers
+                                    .remove_entry(&key)
+                                    .map(|(_, buf)| buf)
+                                    .unwrap_or_default(),
+                                material: material.clone(),
+                                is_skinned: false,
+                                render_path: RenderPath::Deferred,
+                                sort_index: 0,
+                                decal_layer_index: terrain.decal_layer_index(),
+                            });
+                            self.batches.last_mut().unwrap()
+                        };
+
+                        batch.instances.push(SurfaceInstance {
+                            world_transform: terrain.global_transform(),
+                            prev_world_transform: prev_transform(handle),
+                            flags: SurfaceInstanceFlags::from_node(node),
+                            world_aabb: terrain.world_bounding_box(),
+                            bone_matrices: Default::default(),
+                            owner: handle,
+                            depth_offset: terrain.depth_offset_factor(),
+                            render_mask: terrain.render_mask(),
+                        });
+                    }
+                } else {
+                    for (layer_index, layer) in terrain.layers().iter().enumerate() {
+                        for (chunk_index, chunk) in terrain.chunks_ref().iter().enumerate() {
+                            let data = chunk.data();
+                            let data_key = &*data as *const _ as u64;
+
+                            let mut material = (*layer.material.lock()).clone();
+                            match material.set_property(
+                                &ImmutableString::new(&layer.mask_property_name),
+                                PropertyValue::Sampler {
+                                    value: Some(layer.chunk_masks[chunk_index].clone()),
+                                    fallback: Default::default(),
+                                },
+                            ) {
+                                Ok(_) => {
+                                    let material = Arc::new(Mutex::new(material));
+
+                                    let mut hasher = FxHasher::default();
+
+                                    hasher.write_u64(&*material as *const _ as u64);
+                                    hasher.write_u64(data_key);
+
+                                    let key = hasher.finish();
+
+                                    let batch = if let Some(&batch_index) = self.batch_map.get(&key)
+                                    {
+                                        self.batches.get_mut(batch_index).unwrap()
+                                    } else {
+                                        self.batch_map.insert(key, self.batches.len());
+                                        self.batches.push(Batch {
+                                            id: key,
+                                            data: data.clone(),
+                                            instances: self
+                                                .buffers
+                                                .remove_entry(&key)
+                                                .map(|(_, buf)| buf)
+                                                .unwrap_or_default(),
+                                            material: material.clone(),
+                                            is_skinned: false,
+                                            render_path: RenderPath::Deferred,
+                                            sort_index: layer_index as u64,
+                                            decal_layer_index: terrain.decal_layer_index(),
+                                        });
+                                        self.batches.last_mut().unwrap()
+                                    };
+
+                                    batch.sort_index = layer_index as u64;
+                                    batch.material = material;
+
+                                    batch.instances.push(SurfaceInstance {
+                                        world_transform: terrain.global_transform(),
+                                        prev_world_transform: prev_transform(handle),
+                                        flags: SurfaceInstanceFlags::from_node(node),
+                                        world_aabb: terrain.world_bounding_box(),
+                                        bone_matrices: Default::default(),
+                                        owner: handle,
+                                        depth_offset: terrain.depth_offset_factor(),
+                                        render_mask: terrain.render_mask(),
                                     });
-                                    self.batches.last_mut().unwrap()
-                                };
-
-                                batch.sort_index = layer_index as u64;
-                                batch.material = material;
-
-                                batch.instances.push(SurfaceInstance {
-                                    world_transform: terrain.global_transform(),
-                                    flags: SurfaceInstanceFlags::from_node(node),
-                                    world_aabb: terrain.world_bounding_box(),
-                                    bone_matrices: Default::default(),
-                                    owner: handle,
-                                    depth_offset: terrain.depth_offset_factor(),
-                                });
-                            }
-                            Err(e) => Log::writeln(
-                                MessageKind::Error,
-                                format!(
-                                    "Failed to prepare batch for terrain chunk.\
-                                 Unable to set mask texture for terrain material. Reason: {:?}",
-                                    e
+                                }
+                                Err(e) => Log::writeln(
+                                    MessageKind::Error,
+                                    format!(
+                                        "Failed to prepare batch for terrain chunk.\
+                                     Unable to set mask texture for terrain material. Reason: {:?}",
+                                        e
+                                    ),
                                 ),
-                            ),
+                            }
                         }
                     }
                 }
+            } else if let Some(impostor) = node.cast::<Impostor>() {
+                // All impostors share the same quad, impostors with the same parameters share a
+                // material, so thousands of impostors of a prefab form a single batch.
+                let key = impostor.batch_key();
+
+                let material = self
+                    .impostor_materials
+                    .remove(&key)
+                    .or_else(|| used_impostor_materials.get(&key).cloned())
+                    .unwrap_or_else(|| Arc::new(Mutex::new(impostor.create_material())));
+                used_impostor_materials.insert(key, material.clone());
+
+                let batch = if let Some(&batch_index) = self.batch_map.get(&key) {
+                    self.batches.get_mut(batch_index).unwrap()
+                } else {
+                    self.batch_map.insert(key, self.batches.len());
+                    self.batches.push(Batch {
+                        id: key,
+                        data: Impostor::quad(),
+                        instances: self
+                            .buffers
+                            .remove_entry(&key)
+                            .map(|(_, buf)| buf)
+                            .unwrap_or_default(),
+                        material,
+                        is_skinned: false,
+                        render_path: RenderPath::Deferred,
+                        sort_index: 0,
+                        decal_layer_index: 0,
+                    });
+                    self.batches.last_mut().unwrap()
+                };
+
+                batch.instances.push(SurfaceInstance {
+                    world_transform: impostor.global_transform(),
+                    prev_world_transform: prev_transform(handle),
+                    flags: SurfaceInstanceFlags::from_node(node),
+                    world_aabb: impostor.world_bounding_box(),
+                    bone_matrices: Default::default(),
+                    owner: handle,
+                    depth_offset: 0.0,
+                    render_mask: impostor.render_mask(),
+                });
             }
         }
 
+        // Materials of impostors that weren't rendered this frame are dropped.
+        self.impostor_materials = used_impostor_materials;
+
         for batch in self.batches.iter_mut() {
             // We have to shrink instance storage if it has a lot of backing memory, to keep memory
             // consumption at reasonable levels.
@@ -265,4 +465,334 @@ impl BatchStorage {
 
         self.batches.sort_unstable_by_key(|b| b.sort_index);
     }
+
+    /// Returns bone matrices of the given instance. The slice is empty for non-skinned
+    /// instances.
+    pub fn instance_bone_matrices(&self, instance: &SurfaceInstance) -> &[Matrix4<f32>] {
+        &self.bone_matrices[instance.bone_matrices.clone()]
+    }
+
+    /// Uploads bone matrices to the GPU, must be called after batch generation. Does nothing
+    /// if the GPU cannot fetch textures in vertex shaders, in this case the matrices will be
+    /// passed via uniforms.
+    pub(crate) fn upload_bone_matrices(
+        &mut self,
+        state: &mut PipelineState,
+    ) -> Result<(), FrameworkError> {
+        if !state.supports_vertex_texture_fetch() || self.bone_matrices.is_empty() {
+            return Ok(());
+        }
+
+        if self.bone_matrices_storage.is_none() {
+            self.bone_matrices_storage = Some(MatrixStorage::new(state)?);
+        }
+
+        if let Some(storage) = self.bone_matrices_storage.as_mut() {
+            storage.upload(state, &self.bone_matrices)?;
+        }
+
+        if !self.prev_bone_matrices.is_empty() {
+            if self.prev_bone_matrices_storage.is_none() {
+                self.prev_bone_matrices_storage = Some(MatrixStorage::new(state)?);
+            }
+
+            if let Some(storage) = self.prev_bone_matrices_storage.as_mut() {
+                storage.upload(state, &self.prev_bone_matrices)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns a texture with bone matrices of all instances, it is `None` if the GPU cannot
+    /// fetch textures in vertex shaders.
+    pub(crate) fn bone_matrices_texture(&self) -> Option<&Rc<RefCell<GpuTexture>>> {
+        self.bone_matrices_storage
+            .as_ref()
+            .map(|storage| storage.texture())
+    }
+
+    /// Returns a texture with bone matrices of all instances from the previous frame. It is
+    /// `None` if the GPU cannot fetch textures in vertex shaders or if previous transforms were
+    /// not provided for batch generation.
+    pub(crate) fn prev_bone_matrices_texture(&self) -> Option<&Rc<RefCell<GpuTexture>>> {
+        if self.prev_bone_matrices.is_empty() {
+            None
+        } else {
+            self.prev_bone_matrices_storage
+                .as_ref()
+                .map(|storage| storage.texture())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3, Vector4},
+            math::TriangleDefinition,
+            parking_lot::Mutex,
+            pool::Handle,
+        },
+        renderer::{
+            batch::{BatchStorage, BONE_MATRICES_COUNT},
+            storage::TEXTURE_WIDTH,
+        },
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            impostor::ImpostorBuilder,
+            mesh::{
+                buffer::{TriangleBuffer, VertexBuffer},
+                surface::{SurfaceBuilder, SurfaceData},
+                vertex::AnimatedVertex,
+                Mesh, MeshBuilder,
+            },
+            node::Node,
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+    use fxhash::FxHashMap;
+    use std::sync::Arc;
+
+    const BONE_COUNT: usize = 100;
+
+    fn make_skeleton(graph: &mut Graph) -> Vec<Handle<Node>> {
+        let mut bones = Vec::new();
+        let mut parent = graph.get_root();
+        for i in 0..BONE_COUNT {
+            let bone = PivotBuilder::new(
+                BaseBuilder::new()
+                    .with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(Vector3::new(0.0, 0.1, 0.0))
+                            .with_local_rotation(UnitQuaternion::from_axis_angle(
+                                &Vector3::z_axis(),
+                                0.01 * i as f32,
+                            ))
+                            .build(),
+                    )
+                    .with_inv_bind_pose_transform(Matrix4::new_translation(&Vector3::new(
+                        0.0,
+                        -0.1 * i as f32,
+                        0.0,
+                    ))),
+            )
+            .build(graph);
+            graph.link_nodes(bone, parent);
+            bones.push(bone);
+            parent = bone;
+        }
+        bones
+    }
+
+    fn make_vertices() -> Vec<AnimatedVertex> {
+        (0..BONE_COUNT)
+            .map(|i| AnimatedVertex {
+                position: Vector3::new(0.5, 0.1 * i as f32, -0.25),
+                bone_weights: [0.4, 0.3, 0.2, 0.1],
+                bone_indices: [
+                    i as u8,
+                    ((i + 37) % BONE_COUNT) as u8,
+                    ((i + 71) % BONE_COUNT) as u8,
+                    (BONE_COUNT - 1) as u8,
+                ],
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn make_mesh(graph: &mut Graph, bones: Vec<Handle<Node>>) -> Handle<Node> {
+        let vertices = make_vertices();
+        let data = SurfaceData::new(
+            VertexBuffer::new(vertices.len(), AnimatedVertex::layout(), vertices).unwrap(),
+            TriangleBuffer::new(vec![TriangleDefinition([0, 1, 2])]),
+            true,
+        );
+        MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(data)))
+                .with_bones(bones)
+                .build()])
+            .build(graph)
+    }
+
+    // Mimics `S_FetchBoneMatrix` from shared.glsl, the texture holds the matrices exactly in the
+    // same order as they are stored in the batch storage.
+    fn fetch_bone_matrix(storage: &[Matrix4<f32>], offset: usize, index: usize) -> Matrix4<f32> {
+        let texels = storage
+            .iter()
+            .flat_map(|m| m.as_slice().chunks(4))
+            .collect::<Vec<_>>();
+        let texel = 4 * (offset + index);
+        let (x, y) = (texel % TEXTURE_WIDTH, texel / TEXTURE_WIDTH);
+        let column = |i: usize| Vector4::from_column_slice(texels[y * TEXTURE_WIDTH + x + i]);
+        Matrix4::from_columns(&[column(0), column(1), column(2), column(3)])
+    }
+
+    #[test]
+    fn test_skinning_with_many_bones() {
+        assert!(BONE_COUNT > BONE_MATRICES_COUNT);
+
+        let mut graph = Graph::new();
+        let bones = make_skeleton(&mut graph);
+        let mut reversed_bones = bones.clone();
+        reversed_bones.reverse();
+        let meshes = [
+            make_mesh(&mut graph, bones.clone()),
+            // Shares bone matrices with the first one.
+            make_mesh(&mut graph, bones.clone()),
+            make_mesh(&mut graph, reversed_bones),
+        ];
+        graph.update_hierarchical_data();
+
+        let mut batch_storage = BatchStorage::default();
+        batch_storage.generate_batches(&graph, None);
+
+        assert_eq!(batch_storage.bone_matrices.len(), 2 * BONE_COUNT);
+
+        let instances = batch_storage
+            .batches
+            .iter()
+            .flat_map(|b| b.instances.iter())
+            .collect::<Vec<_>>();
+        assert_eq!(instances.len(), meshes.len());
+
+        for instance in instances {
+            assert!(meshes.contains(&instance.owner));
+            assert_eq!(
+                batch_storage.instance_bone_matrices(instance).len(),
+                BONE_COUNT
+            );
+
+            let surface = &graph[instance.owner].cast::<Mesh>().unwrap().surfaces()[0];
+            for vertex in make_vertices() {
+                let position = Point3::from(vertex.position);
+
+                let mut expected = Vector3::default();
+                let mut actual = Vector3::default();
+                for (&index, &weight) in vertex.bone_indices.iter().zip(vertex.bone_weights.iter())
+                {
+                    let bone = &graph[surface.bones[index as usize]];
+                    let reference = bone.global_transform() * bone.inv_bind_pose_transform();
+                    expected += reference.transform_point(&position).coords.scale(weight);
+
+                    let fetched = fetch_bone_matrix(
+                        &batch_storage.bone_matrices,
+                        instance.bone_matrices.start,
+                        index as usize,
+                    );
+                    actual += fetched.transform_point(&position).coords.scale(weight);
+                }
+
+                assert!((expected - actual).norm() < 1.0e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_prev_transforms() {
+        let mut graph = Graph::new();
+        let bones = make_skeleton(&mut graph);
+        let skinned = make_mesh(&mut graph, bones.clone());
+        let rigid = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+                SurfaceData::make_cube(Matrix4::identity()),
+            )))
+            .build()])
+            .build(&mut graph);
+        graph.update_hierarchical_data();
+
+        let prev_transforms = graph
+            .pair_iter()
+            .map(|(handle, node)| (handle, node.global_transform()))
+            .collect::<FxHashMap<_, _>>();
+
+        // Move everything.
+        for &node in [bones[0], rigid].iter() {
+            graph[node]
+                .local_transform_mut()
+                .set_position(Vector3::new(1.0, 2.0, 3.0));
+        }
+        graph.update_hierarchical_data();
+
+        let mut batch_storage = BatchStorage::default();
+
+        batch_storage.generate_batches(&graph, None);
+        assert!(batch_storage.prev_bone_matrices.is_empty());
+        for instance in batch_storage
+            .batches
+            .iter()
+            .flat_map(|b| b.instances.iter())
+        {
+            assert_eq!(instance.prev_world_transform, instance.world_transform);
+        }
+
+        batch_storage.generate_batches(&graph, Some(&prev_transforms));
+        assert_eq!(
+            batch_storage.prev_bone_matrices.len(),
+            batch_storage.bone_matrices.len()
+        );
+        for instance in batch_storage
+            .batches
+            .iter()
+            .flat_map(|b| b.instances.iter())
+        {
+            if instance.owner == rigid {
+                assert_eq!(instance.prev_world_transform, prev_transforms[&rigid]);
+                assert_ne!(instance.prev_world_transform, instance.world_transform);
+            } else {
+                assert_eq!(instance.owner, skinned);
+                let range = instance.bone_matrices.clone();
+                for (&bone, prev) in bones
+                    .iter()
+                    .zip(batch_storage.prev_bone_matrices[range].iter())
+                {
+                    assert_eq!(
+                        *prev,
+                        prev_transforms[&bone] * graph[bone].inv_bind_pose_transform()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_impostors_share_batch() {
+        let mut graph = Graph::new();
+        let impostors = (0..10)
+            .map(|i| {
+                ImpostorBuilder::new(
+                    BaseBuilder::new().with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(Vector3::new(i as f32, 0.0, 0.0))
+                            .build(),
+                    ),
+                )
+                .with_size(Vector2::new(2.0, 4.0))
+                .build(&mut graph)
+            })
+            .collect::<Vec<_>>();
+        // Different parameters - different batch.
+        let other = ImpostorBuilder::new(BaseBuilder::new())
+            .with_frame_count(16)
+            .build(&mut graph);
+        graph.update_hierarchical_data();
+
+        let mut batch_storage = BatchStorage::default();
+        for _ in 0..2 {
+            batch_storage.generate_batches(&graph, None);
+
+            assert_eq!(batch_storage.batches.len(), 2);
+            for batch in batch_storage.batches.iter() {
+                if batch.instances.len() == 1 {
+                    assert_eq!(batch.instances[0].owner, other);
+                } else {
+                    assert_eq!(batch.instances.len(), impostors.len());
+                }
+            }
+        }
+        assert_eq!(batch_storage.impostor_materials.len(), 2);
+    }
 }
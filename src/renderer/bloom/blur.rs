@@ -139,6 +139,8 @@ impl GaussianBlur {
                 depth_test: false,
                 blend: None,
                 stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
             },
             |mut program_binding| {
                 program_binding
@@ -167,6 +169,8 @@ impl GaussianBlur {
                 depth_test: false,
                 blend: None,
                 stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
             },
             |mut program_binding| {
                 program_binding
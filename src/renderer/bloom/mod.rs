@@ -125,6 +125,8 @@ impl BloomRenderer {
                 depth_test: false,
                 blend: None,
                 stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
             },
             |mut program_binding| {
                 program_binding
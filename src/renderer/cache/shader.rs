@@ -24,12 +24,13 @@ pub struct ShaderSet {
 impl ShaderSet {
     pub fn new(state: &mut PipelineState, shader: &ShaderState) -> Option<Self> {
         let mut map = FxHashMap::default();
+        let vertex_attributes = shader.definition.vertex_attributes_source();
         for render_pass in shader.definition.passes.iter() {
             let program_name = format!("{}_{}", shader.definition.name, render_pass.name);
             match GpuProgram::from_source(
                 state,
                 &program_name,
-                &render_pass.vertex_shader,
+                &(vertex_attributes.clone() + &render_pass.vertex_shader),
                 &render_pass.fragment_shader,
             ) {
                 Ok(gpu_program) => {
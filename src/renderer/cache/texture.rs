@@ -9,11 +9,23 @@ use crate::{
             state::PipelineState,
         },
     },
-    resource::texture::{Texture, TextureState},
+    resource::texture::{Texture, TextureData, TextureState},
     utils::log::{Log, MessageKind},
 };
 use fxhash::FxHashMap;
-use std::{cell::RefCell, collections::hash_map::Entry, ops::Deref, rc::Rc};
+use std::{borrow::Cow, cell::RefCell, collections::hash_map::Entry, ops::Deref, rc::Rc};
+
+/// Returns pixel kind and data of the texture that can be uploaded to the GPU. Compressed data is
+/// decompressed if the GPU does not support its format.
+fn gpu_data<'a>(state: &PipelineState, texture: &'a TextureData) -> (PixelKind, Cow<'a, [u8]>) {
+    let pixel_kind = PixelKind::from(texture.pixel_kind());
+    if !state.supports_pixel_kind(pixel_kind) {
+        if let Some((pixel_kind, data)) = texture.decompress() {
+            return (PixelKind::from(pixel_kind), Cow::Owned(data));
+        }
+    }
+    (pixel_kind, Cow::Borrowed(texture.data()))
+}
 
 #[derive(Default)]
 pub struct TextureCache {
@@ -32,14 +44,15 @@ impl TextureCache {
         let texture = texture.state();
 
         if let TextureState::Ok(texture) = texture.deref() {
+            let (pixel_kind, data) = gpu_data(state, texture);
             let gpu_texture = GpuTexture::new(
                 state,
                 texture.kind().into(),
-                PixelKind::from(texture.pixel_kind()),
+                pixel_kind,
                 texture.minification_filter().into(),
                 texture.magnification_filter().into(),
                 texture.mip_count() as usize,
-                Some(texture.data()),
+                Some(&data),
             )?;
 
             match self.map.entry(key) {
@@ -87,12 +100,13 @@ impl TextureCache {
                     // Data might change from last frame, so we have to check it and upload new if so.
                     let data_hash = texture.data_hash();
                     if entry.value_hash != data_hash {
+                        let (pixel_kind, data) = gpu_data(state, texture);
                         let mut tex = entry.borrow_mut();
                         if let Err(e) = tex.bind_mut(state, 0).set_data(
                             texture.kind().into(),
-                            texture.pixel_kind().into(),
+                            pixel_kind,
                             texture.mip_count() as usize,
-                            Some(texture.data()),
+                            Some(&data),
                         ) {
                             Log::writeln(
                                 MessageKind::Error,
@@ -144,14 +158,15 @@ impl TextureCache {
                     entry
                 }
                 Entry::Vacant(e) => {
+                    let (pixel_kind, data) = gpu_data(state, texture);
                     let gpu_texture = match GpuTexture::new(
                         state,
                         texture.kind().into(),
-                        PixelKind::from(texture.pixel_kind()),
+                        pixel_kind,
                         texture.minification_filter().into(),
                         texture.magnification_filter().into(),
                         texture.mip_count() as usize,
-                        Some(texture.data()),
+                        Some(&data),
                     ) {
                         Ok(texture) => texture,
                         Err(e) => {
@@ -192,6 +207,12 @@ impl TextureCache {
     }
 
     pub fn unload(&mut self, texture: Texture) {
-        self.map.remove(&texture.key());
+        self.unload_by_key(texture.key());
+    }
+
+    /// Removes GPU texture of a texture resource with the given key, it is used when the resource
+    /// itself does not exist anymore.
+    pub fn unload_by_key(&mut self, key: usize) {
+        self.map.remove(&key);
     }
 }
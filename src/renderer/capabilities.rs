@@ -0,0 +1,241 @@
+//! Capabilities of the renderer on current hardware. Some built-in shaders may fail to compile on
+//! old or buggy drivers, in this case the renderer does not fail, instead it disables the feature
+//! that uses the shader and continues to work in degraded mode. See [`RendererCapabilities`] docs
+//! for more info.
+
+use crate::{
+    renderer::{framework::error::FrameworkError, QualitySettings},
+    utils::log::{Log, MessageKind},
+};
+use std::fmt::{Display, Formatter};
+
+/// Optional feature of the renderer, that can be disabled if it is not supported by hardware.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RendererFeature {
+    /// Deferred lighting. If it is disabled, scenes are rendered unlit - only with colors of
+    /// their materials.
+    Lighting,
+    /// Screen-space ambient occlusion.
+    Ssao,
+    /// Volumetric light scattering.
+    LightScattering,
+    /// Fast approximate anti-aliasing.
+    Fxaa,
+    /// Cascaded shadow maps of directional lights.
+    Csm,
+}
+
+impl RendererFeature {
+    /// Every optional feature of the renderer.
+    pub const ALL: [RendererFeature; 5] = [
+        RendererFeature::Lighting,
+        RendererFeature::Ssao,
+        RendererFeature::LightScattering,
+        RendererFeature::Fxaa,
+        RendererFeature::Csm,
+    ];
+
+    /// Returns human-readable name of the feature.
+    pub fn name(self) -> &'static str {
+        match self {
+            RendererFeature::Lighting => "Lighting",
+            RendererFeature::Ssao => "Screen-Space Ambient Occlusion",
+            RendererFeature::LightScattering => "Light Scattering",
+            RendererFeature::Fxaa => "FXAA",
+            RendererFeature::Csm => "Directional Light Shadows",
+        }
+    }
+}
+
+/// Status of an optional feature of the renderer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FeatureStatus {
+    /// The feature is supported and can be used.
+    Active,
+    /// The feature is not supported, the reason is usually a shader compilation or linking error.
+    Disabled {
+        /// Description of the error that caused the feature to be disabled.
+        reason: String,
+    },
+}
+
+/// A report of optional features of the renderer that are available on current hardware. It is
+/// filled once, when the renderer is created, and can be used to inform a user that some features
+/// are not available. Disabled features are forcibly turned off in quality settings, so a game does
+/// not need to do anything special for the renderer to work.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox::renderer::capabilities::RendererCapabilities;
+/// fn show_warnings(capabilities: &RendererCapabilities) {
+///     for warning in capabilities.warnings() {
+///         // Show the warning to the user.
+///         println!("{}", warning);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RendererCapabilities {
+    disabled: Vec<(RendererFeature, String)>,
+}
+
+impl RendererCapabilities {
+    /// Returns status of the feature.
+    pub fn status(&self, feature: RendererFeature) -> FeatureStatus {
+        match self.disabled.iter().find(|(f, _)| *f == feature) {
+            Some((_, reason)) => FeatureStatus::Disabled {
+                reason: reason.clone(),
+            },
+            None => FeatureStatus::Active,
+        }
+    }
+
+    /// Returns `true` if the feature is supported.
+    pub fn is_active(&self, feature: RendererFeature) -> bool {
+        self.disabled.iter().all(|(f, _)| *f != feature)
+    }
+
+    /// Returns `true` if at least one feature is disabled.
+    pub fn is_degraded(&self) -> bool {
+        !self.disabled.is_empty()
+    }
+
+    /// Returns an iterator over disabled features and reasons why they were disabled.
+    pub fn disabled_features(&self) -> impl Iterator<Item = (RendererFeature, &str)> {
+        self.disabled
+            .iter()
+            .map(|(feature, reason)| (*feature, reason.as_str()))
+    }
+
+    /// Returns a list of short messages, that are suitable to be shown to a user.
+    pub fn warnings(&self) -> Vec<String> {
+        self.disabled
+            .iter()
+            .map(|(feature, _)| {
+                format!(
+                    "{} is not supported by your graphics card and was disabled.",
+                    feature.name()
+                )
+            })
+            .collect()
+    }
+
+    /// Turns off every disabled feature in the given quality settings.
+    pub fn restrict(&self, settings: &mut QualitySettings) {
+        for (feature, _) in self.disabled.iter() {
+            match feature {
+                // Lighting cannot be turned off in settings, the renderer checks it by itself.
+                RendererFeature::Lighting => (),
+                RendererFeature::Ssao => settings.use_ssao = false,
+                RendererFeature::LightScattering => settings.light_scatter_enabled = false,
+                RendererFeature::Fxaa => settings.fxaa = false,
+                RendererFeature::Csm => settings.csm_settings.enabled = false,
+            }
+        }
+    }
+
+    /// Marks the feature as disabled.
+    pub fn disable(&mut self, feature: RendererFeature, reason: String) {
+        Log::writeln(
+            MessageKind::Error,
+            format!(
+                "{} was disabled, because it is not supported. Reason: {}",
+                feature.name(),
+                reason
+            ),
+        );
+
+        match self.disabled.iter_mut().find(|(f, _)| *f == feature) {
+            Some((_, existing)) => *existing = reason,
+            None => self.disabled.push((feature, reason)),
+        }
+    }
+
+    /// Returns the result of initialization of a part of the renderer that implements the feature,
+    /// or disables the feature if the initialization has failed.
+    pub(crate) fn try_init<T>(
+        &mut self,
+        feature: RendererFeature,
+        result: Result<T, FrameworkError>,
+    ) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.disable(feature, error.to_string());
+                None
+            }
+        }
+    }
+}
+
+impl Display for RendererCapabilities {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for feature in RendererFeature::ALL {
+            match self.status(feature) {
+                FeatureStatus::Active => writeln!(f, "{}: Active", feature.name())?,
+                FeatureStatus::Disabled { reason } => {
+                    writeln!(f, "{}: Disabled ({})", feature.name(), reason)?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::{
+        capabilities::{FeatureStatus, RendererCapabilities, RendererFeature},
+        framework::error::FrameworkError,
+        QualitySettings,
+    };
+
+    fn compilation_error(name: &str) -> FrameworkError {
+        FrameworkError::ShaderCompilationFailed {
+            shader_name: name.to_owned(),
+            error_message: "0:1: syntax error".to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_failed_feature_is_disabled() {
+        let mut capabilities = RendererCapabilities::default();
+
+        assert_eq!(
+            capabilities.try_init(RendererFeature::Fxaa, Ok::<_, FrameworkError>(1)),
+            Some(1)
+        );
+        assert_eq!(
+            capabilities
+                .try_init::<u32>(RendererFeature::Ssao, Err(compilation_error("SsaoShader"))),
+            None
+        );
+
+        assert!(capabilities.is_degraded());
+        assert!(capabilities.is_active(RendererFeature::Fxaa));
+        assert!(!capabilities.is_active(RendererFeature::Ssao));
+        assert!(matches!(
+            capabilities.status(RendererFeature::Ssao),
+            FeatureStatus::Disabled { reason } if reason.contains("SsaoShader")
+        ));
+        assert_eq!(capabilities.disabled_features().count(), 1);
+        assert_eq!(capabilities.warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_restrict_quality_settings() {
+        let mut capabilities = RendererCapabilities::default();
+        for feature in RendererFeature::ALL {
+            capabilities.disable(feature, "test".to_owned());
+        }
+
+        let mut settings = QualitySettings::ultra();
+        capabilities.restrict(&mut settings);
+
+        assert!(!settings.use_ssao);
+        assert!(!settings.light_scatter_enabled);
+        assert!(!settings.fxaa);
+        assert!(!settings.csm_settings.enabled);
+    }
+}
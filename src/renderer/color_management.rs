@@ -0,0 +1,403 @@
+//! Output color management. The renderer works in linear space with sRGB (Rec.709) primaries, by
+//! default the final frame is encoded with sRGB transfer function and goes directly into the back
+//! buffer. Other output targets add a composition pass, that converts the frame into the color
+//! space of a display. See [`ColorManagementSettings`] and [`OutputCapabilities`] for more info.
+
+use crate::{
+    core::{math::Rect, scope_profile, sstorage::ImmutableString},
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, DrawParameters, FrameBuffer},
+            geometry_buffer::GeometryBuffer,
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{
+                Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
+                PixelKind, WrapMode,
+            },
+            state::PipelineState,
+        },
+        make_viewport_matrix, RenderPassStatistics,
+    },
+};
+use std::{
+    cell::RefCell,
+    fmt::{Display, Formatter},
+    rc::Rc,
+};
+
+/// Luminance of the brightest value that could be encoded with PQ transfer function, in nits.
+pub const PQ_MAX_LUMINANCE: f32 = 10000.0;
+
+/// A color space of the final frame, it must match the color space that a display expects.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum OutputTarget {
+    /// sRGB primaries and sRGB transfer function. This is what most of the desktop displays
+    /// expect, the frame goes to the back buffer as is.
+    Srgb = 0,
+    /// Rec.709 primaries (the same as sRGB) and BT.1886 transfer function (pure 2.4 gamma), for
+    /// displays and capture devices that are calibrated for video. Dark tones are brighter than
+    /// with [`OutputTarget::Srgb`] on such displays.
+    Rec709 = 1,
+    /// Rec.2020 primaries and PQ (SMPTE ST 2084) transfer function, for HDR displays. Values
+    /// above the white of the SDR frame are kept (up to
+    /// [`ColorManagementSettings::max_luminance`]), instead of being compressed by tone mapping.
+    Hdr10 = 2,
+}
+
+impl Default for OutputTarget {
+    fn default() -> Self {
+        Self::Srgb
+    }
+}
+
+impl OutputTarget {
+    /// Every output target.
+    pub const ALL: [OutputTarget; 3] = [
+        OutputTarget::Srgb,
+        OutputTarget::Rec709,
+        OutputTarget::Hdr10,
+    ];
+
+    /// Returns human-readable name of the target.
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputTarget::Srgb => "sRGB",
+            OutputTarget::Rec709 => "Rec.709",
+            OutputTarget::Hdr10 => "HDR10",
+        }
+    }
+
+    /// Returns `true` if the target keeps values above SDR white.
+    pub fn is_hdr(self) -> bool {
+        self == OutputTarget::Hdr10
+    }
+}
+
+impl Display for OutputTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A set of options of output color management.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ColorManagementSettings {
+    /// Desired output target. If the target is not supported (see [`OutputCapabilities`]), the
+    /// renderer falls back to [`OutputTarget::Srgb`].
+    pub output_target: OutputTarget,
+    /// Luminance of SDR white (UI, tone mapped scene white) on HDR displays, in nits. Used only
+    /// by [`OutputTarget::Hdr10`]. Default is 203 nits, as recommended by ITU-R BT.2408.
+    pub paper_white: f32,
+    /// Luminance of the brightest value of the frame on HDR displays, in nits. It should match
+    /// peak luminance of a display, brighter values are clipped by the display. Used only by
+    /// [`OutputTarget::Hdr10`].
+    pub max_luminance: f32,
+}
+
+impl Default for ColorManagementSettings {
+    fn default() -> Self {
+        Self {
+            output_target: Default::default(),
+            paper_white: 203.0,
+            max_luminance: 1000.0,
+        }
+    }
+}
+
+impl ColorManagementSettings {
+    /// Returns a copy of the settings with luminance values clamped to the valid range: paper white
+    /// is in `[1; PQ_MAX_LUMINANCE]` range and maximum luminance is not less than paper white.
+    pub fn clamped(&self) -> Self {
+        let paper_white = self.paper_white.clamp(1.0, PQ_MAX_LUMINANCE);
+        Self {
+            output_target: self.output_target,
+            paper_white,
+            max_luminance: self.max_luminance.clamp(paper_white, PQ_MAX_LUMINANCE),
+        }
+    }
+
+    /// Returns peak value of the tone mapped frame relative to SDR white (1.0), it is 1.0 for SDR
+    /// targets.
+    pub fn peak_white(&self) -> f32 {
+        if self.output_target.is_hdr() {
+            let settings = self.clamped();
+            settings.max_luminance / settings.paper_white
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Describes the format of the back buffer, that was provided by windowing backend. It is filled
+/// once, when the engine is created.
+///
+/// # HDR10
+///
+/// HDR10 output requires a back buffer with at least 10 bits per color channel and a way to tell
+/// the compositor of OS that the back buffer contains PQ-encoded Rec.2020 colors. Neither the
+/// desktop backend (OpenGL contexts created by glutin) nor WebGL provide the latter, so HDR10 is
+/// reported as not supported on them, and [`OutputTarget::Hdr10`] falls back to sRGB.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct OutputCapabilities {
+    /// Total amount of bits per pixel of color channels (without alpha) of the back buffer.
+    pub color_bits: u8,
+    /// Amount of bits of alpha channel of the back buffer.
+    pub alpha_bits: u8,
+    /// Whether the backend is able to signal PQ-encoded Rec.2020 colors to the compositor.
+    pub hdr10: bool,
+}
+
+impl Default for OutputCapabilities {
+    fn default() -> Self {
+        Self {
+            color_bits: 24,
+            alpha_bits: 8,
+            hdr10: false,
+        }
+    }
+}
+
+impl OutputCapabilities {
+    /// Returns amount of bits per color channel of the back buffer.
+    pub fn bits_per_channel(&self) -> u8 {
+        self.color_bits / 3
+    }
+
+    /// Returns `true` if the target could be used with the back buffer.
+    pub fn supports(&self, target: OutputTarget) -> bool {
+        match target {
+            OutputTarget::Srgb | OutputTarget::Rec709 => true,
+            OutputTarget::Hdr10 => self.hdr10 && self.bits_per_channel() >= 10,
+        }
+    }
+
+    /// Returns every target that could be used with the back buffer.
+    pub fn supported_targets(&self) -> Vec<OutputTarget> {
+        OutputTarget::ALL
+            .iter()
+            .cloned()
+            .filter(|target| self.supports(*target))
+            .collect()
+    }
+}
+
+struct OutputShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    frame_texture: UniformLocation,
+    output_target: UniformLocation,
+    paper_white: UniformLocation,
+    max_luminance: UniformLocation,
+}
+
+impl OutputShader {
+    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/output_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "OutputShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            frame_texture: program
+                .uniform_location(state, &ImmutableString::new("frameTexture"))?,
+            output_target: program
+                .uniform_location(state, &ImmutableString::new("outputTarget"))?,
+            paper_white: program.uniform_location(state, &ImmutableString::new("paperWhite"))?,
+            max_luminance: program
+                .uniform_location(state, &ImmutableString::new("maxLuminance"))?,
+            program,
+        })
+    }
+}
+
+fn make_composition_framebuffer(
+    state: &mut PipelineState,
+    frame_size: (u32, u32),
+) -> Result<FrameBuffer, FrameworkError> {
+    let kind = GpuTextureKind::Rectangle {
+        width: frame_size.0 as usize,
+        height: frame_size.1 as usize,
+    };
+
+    // Composition frame keeps sRGB-encoded colors, but values above 1.0 must survive for HDR
+    // output.
+    let mut color_texture = GpuTexture::new(
+        state,
+        kind,
+        PixelKind::RGBA16F,
+        MinificationFilter::Nearest,
+        MagnificationFilter::Nearest,
+        1,
+        None,
+    )?;
+    color_texture
+        .bind_mut(state, 0)
+        .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+        .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+
+    // The user interface uses stencil buffer for clipping.
+    let depth_stencil = GpuTexture::new(
+        state,
+        kind,
+        PixelKind::D24S8,
+        MinificationFilter::Nearest,
+        MagnificationFilter::Nearest,
+        1,
+        None,
+    )?;
+
+    FrameBuffer::new(
+        state,
+        Some(Attachment {
+            kind: AttachmentKind::DepthStencil,
+            texture: Rc::new(RefCell::new(depth_stencil)),
+        }),
+        vec![Attachment {
+            kind: AttachmentKind::Color,
+            texture: Rc::new(RefCell::new(color_texture)),
+        }],
+    )
+}
+
+/// Composition pass of non-sRGB output targets. Everything that normally goes into the back
+/// buffer is rendered into an intermediate frame instead, the frame is then converted to the
+/// color space of the output target and copied into the back buffer.
+pub(crate) struct OutputRenderer {
+    shader: OutputShader,
+    framebuffer: FrameBuffer,
+    frame_size: (u32, u32),
+}
+
+impl OutputRenderer {
+    pub(crate) fn new(
+        state: &mut PipelineState,
+        frame_size: (u32, u32),
+    ) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: OutputShader::new(state)?,
+            framebuffer: make_composition_framebuffer(state, frame_size)?,
+            frame_size,
+        })
+    }
+
+    pub(crate) fn set_frame_size(
+        &mut self,
+        state: &mut PipelineState,
+        frame_size: (u32, u32),
+    ) -> Result<(), FrameworkError> {
+        if self.frame_size != frame_size {
+            self.framebuffer = make_composition_framebuffer(state, frame_size)?;
+            self.frame_size = frame_size;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn framebuffer(&mut self) -> &mut FrameBuffer {
+        &mut self.framebuffer
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        state: &mut PipelineState,
+        backbuffer: &mut FrameBuffer,
+        viewport: Rect<i32>,
+        quad: &GeometryBuffer,
+        settings: &ColorManagementSettings,
+    ) -> RenderPassStatistics {
+        scope_profile!();
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let shader = &self.shader;
+        let frame_matrix = make_viewport_matrix(viewport);
+        let frame_texture = self.framebuffer.color_attachments()[0].texture.clone();
+        let settings = settings.clamped();
+
+        statistics += backbuffer.draw(
+            quad,
+            state,
+            viewport,
+            &shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: None,
+                stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
+            },
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(&shader.wvp_matrix, &frame_matrix)
+                    .set_texture(&shader.frame_texture, &frame_texture)
+                    .set_i32(&shader.output_target, settings.output_target as i32)
+                    .set_f32(&shader.paper_white, settings.paper_white)
+                    .set_f32(&shader.max_luminance, settings.max_luminance);
+            },
+        );
+
+        statistics
+    }
+}
+
+/// Selects a frame buffer, that receives everything that normally goes to the back buffer.
+pub(crate) fn composition_target<'a>(
+    backbuffer: &'a mut FrameBuffer,
+    output_renderer: &'a mut Option<OutputRenderer>,
+) -> &'a mut FrameBuffer {
+    match output_renderer {
+        Some(output_renderer) => output_renderer.framebuffer(),
+        None => backbuffer,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::color_management::{
+        ColorManagementSettings, OutputCapabilities, OutputTarget,
+    };
+
+    #[test]
+    fn test_hdr10_requires_backend_support() {
+        let sdr = OutputCapabilities::default();
+        assert_eq!(
+            sdr.supported_targets(),
+            vec![OutputTarget::Srgb, OutputTarget::Rec709]
+        );
+
+        // 8-bit back buffer is not enough, even if the backend can signal PQ.
+        let no_deep_color = OutputCapabilities {
+            hdr10: true,
+            ..Default::default()
+        };
+        assert!(!no_deep_color.supports(OutputTarget::Hdr10));
+
+        let hdr = OutputCapabilities {
+            color_bits: 30,
+            alpha_bits: 2,
+            hdr10: true,
+        };
+        assert!(hdr.supports(OutputTarget::Hdr10));
+    }
+
+    #[test]
+    fn test_peak_white() {
+        let mut settings = ColorManagementSettings::default();
+        assert_eq!(settings.peak_white(), 1.0);
+
+        settings.output_target = OutputTarget::Hdr10;
+        settings.paper_white = 200.0;
+        settings.max_luminance = 1000.0;
+        assert_eq!(settings.peak_white(), 5.0);
+
+        // Maximum luminance can't be lower than paper white.
+        settings.max_luminance = 100.0;
+        assert_eq!(settings.peak_white(), 1.0);
+    }
+}
@@ -5,7 +5,17 @@
 
 use crate::core::sstorage::ImmutableString;
 use crate::{
-    core::{algebra::Vector3, math::Rect, scope_profile},
+    core::{
+        algebra::{Vector2, Vector3},
+        math::Rect,
+        scope_profile,
+    },
+    gui::{
+        brush::Brush,
+        draw::DrawingContext,
+        formatted_text::FormattedTextBuilder,
+        ttf::{FontBuilder, SharedFont},
+    },
     renderer::framework::{
         error::FrameworkError,
         framebuffer::{DrawParameters, FrameBuffer},
@@ -14,11 +24,20 @@ use crate::{
             GeometryBufferBuilder, GeometryBufferKind,
         },
         gpu_program::{GpuProgram, UniformLocation},
-        state::PipelineState,
+        gpu_texture::GpuTexture,
+        state::{BlendFactor, BlendFunc, PipelineState},
+    },
+    renderer::{
+        cache::texture::TextureCache,
+        ui_renderer::{UiRenderContext, UiRenderer},
+        RenderPassStatistics,
+    },
+    scene::{
+        camera::Camera,
+        debug::{DebugText, Line},
     },
-    renderer::RenderPassStatistics,
-    scene::{camera::Camera, debug::SceneDrawingContext},
 };
+use std::{cell::RefCell, rc::Rc};
 
 #[repr(C)]
 struct Vertex {
@@ -32,6 +51,19 @@ pub struct DebugRenderer {
     vertices: Vec<Vertex>,
     line_indices: Vec<[u32; 2]>,
     shader: DebugShader,
+    font: SharedFont,
+    text_drawing_context: DrawingContext,
+}
+
+pub(in crate) struct DebugTextRenderContext<'a, 'b> {
+    pub state: &'a mut PipelineState,
+    pub framebuffer: &'b mut FrameBuffer,
+    pub frame_size: Vector2<f32>,
+    pub texts: &'b [DebugText],
+    pub camera: &'b Camera,
+    pub ui_renderer: &'a mut UiRenderer,
+    pub white_dummy: Rc<RefCell<GpuTexture>>,
+    pub texture_cache: &'a mut TextureCache,
 }
 
 pub(in crate) struct DebugShader {
@@ -78,17 +110,22 @@ impl DebugRenderer {
             shader: DebugShader::new(state)?,
             vertices: Default::default(),
             line_indices: Default::default(),
+            font: SharedFont::new(FontBuilder::new().build_builtin().unwrap()),
+            text_drawing_context: Default::default(),
         })
     }
 
-    pub(in crate) fn render(
+    pub(in crate) fn render<'a, I>(
         &mut self,
         state: &mut PipelineState,
         viewport: Rect<i32>,
         framebuffer: &mut FrameBuffer,
-        drawing_context: &SceneDrawingContext,
+        lines: I,
         camera: &Camera,
-    ) -> RenderPassStatistics {
+    ) -> RenderPassStatistics
+    where
+        I: Iterator<Item = &'a Line>,
+    {
         scope_profile!();
 
         let mut statistics = RenderPassStatistics::default();
@@ -97,7 +134,7 @@ impl DebugRenderer {
         self.line_indices.clear();
 
         let mut i = 0;
-        for line in drawing_context.lines.iter() {
+        for line in lines {
             let color = line.color.into();
             self.vertices.push(Vertex {
                 position: line.begin,
@@ -124,8 +161,13 @@ impl DebugRenderer {
                 depth_write: false,
                 stencil_test: None,
                 depth_test: true,
-                blend: None,
+                blend: Some(BlendFunc {
+                    sfactor: BlendFactor::SrcAlpha,
+                    dfactor: BlendFactor::OneMinusSrcAlpha,
+                }),
                 stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
             },
             |mut program_binding| {
                 program_binding
@@ -137,4 +179,65 @@ impl DebugRenderer {
 
         statistics
     }
+
+    /// Projects every text on screen and draws it on top of everything in the frame buffer.
+    pub(in crate) fn render_texts(
+        &mut self,
+        args: DebugTextRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let DebugTextRenderContext {
+            state,
+            framebuffer,
+            frame_size,
+            texts,
+            camera,
+            ui_renderer,
+            white_dummy,
+            texture_cache,
+        } = args;
+
+        if texts.is_empty() {
+            return Ok(Default::default());
+        }
+
+        // Text must not go outside of the viewport of the camera.
+        let viewport = camera.viewport_pixels(frame_size);
+        let clip_bounds = Rect::new(
+            viewport.x() as f32,
+            frame_size.y - (viewport.y() + viewport.h()) as f32,
+            viewport.w() as f32,
+            viewport.h() as f32,
+        );
+
+        self.text_drawing_context.clear();
+        for text in texts {
+            if let Some(position) = camera.project(text.position, frame_size) {
+                let mut formatted_text = FormattedTextBuilder::new(self.font.clone())
+                    .with_text(text.text.clone())
+                    .with_brush(Brush::Solid(text.color))
+                    .with_constraint(Vector2::new(f32::INFINITY, f32::INFINITY))
+                    .build();
+                let size = formatted_text.build();
+                // Center the text at the projected point.
+                self.text_drawing_context.draw_text(
+                    clip_bounds,
+                    position - size.scale(0.5),
+                    &formatted_text,
+                );
+            }
+        }
+
+        ui_renderer.render(UiRenderContext {
+            state,
+            viewport: Rect::new(0, 0, frame_size.x as i32, frame_size.y as i32),
+            frame_buffer: framebuffer,
+            frame_width: frame_size.x,
+            frame_height: frame_size.y,
+            drawing_context: &self.text_drawing_context,
+            white_dummy,
+            texture_cache,
+        })
+    }
 }
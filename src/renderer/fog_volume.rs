@@ -0,0 +1,461 @@
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector3},
+        color::Color,
+        math::{frustum::Frustum, Rect},
+        scope_profile,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{DrawParameters, FrameBuffer},
+            geometry_buffer::{GeometryBuffer, GeometryBufferKind},
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::GpuTexture,
+            state::{BlendFactor, BlendFunc, PipelineState},
+        },
+        QualitySettings, RenderPassStatistics,
+    },
+    scene::{
+        camera::Camera,
+        fog_volume::{FogVolume, FogVolumeShape},
+        graph::Graph,
+        light::{
+            directional::DirectionalLight,
+            point::PointLight,
+            spot::{SpotLight, SpotLightProjection},
+            BaseLight, DEFAULT_SCATTER_B, DEFAULT_SCATTER_G, DEFAULT_SCATTER_R,
+        },
+        mesh::surface::SurfaceData,
+        node::{Node, NodeTrait},
+    },
+};
+use std::{
+    cell::RefCell,
+    fmt::{Display, Formatter},
+    ops::AddAssign,
+    rc::Rc,
+};
+
+/// Max amount of lights that could scatter in a fog volume, must be in sync with the shader.
+pub const MAX_FOG_VOLUME_LIGHTS: usize = 16;
+
+const LIGHT_KIND_POINT: i32 = 0;
+const LIGHT_KIND_SPOT: i32 = 1;
+const LIGHT_KIND_DIRECTIONAL: i32 = 2;
+
+/// Shows how many fog volumes were rendered and how expensive they were.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct FogVolumeStatistics {
+    /// Amount of fog volumes rendered with ray-marching.
+    pub ray_marched_volumes_rendered: usize,
+    /// Amount of fog volumes rendered as analytic fog.
+    pub analytic_volumes_rendered: usize,
+    /// Total amount of lights that scattered in rendered fog volumes.
+    pub scattering_lights: usize,
+    /// Amount of ray-marching steps per pixel.
+    pub samples_per_pixel: usize,
+}
+
+impl AddAssign for FogVolumeStatistics {
+    fn add_assign(&mut self, rhs: Self) {
+        self.ray_marched_volumes_rendered += rhs.ray_marched_volumes_rendered;
+        self.analytic_volumes_rendered += rhs.analytic_volumes_rendered;
+        self.scattering_lights += rhs.scattering_lights;
+        self.samples_per_pixel = self.samples_per_pixel.max(rhs.samples_per_pixel);
+    }
+}
+
+impl Display for FogVolumeStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Fog Volume Statistics:\n\
+            \tRay-marched Volumes: {}\n\
+            \tAnalytic Volumes: {}\n\
+            \tScattering Lights: {}\n\
+            \tSamples Per Pixel: {}\n",
+            self.ray_marched_volumes_rendered,
+            self.analytic_volumes_rendered,
+            self.scattering_lights,
+            self.samples_per_pixel
+        )
+    }
+}
+
+struct FogVolumeShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    depth_sampler: UniformLocation,
+    inv_view_proj_matrix: UniformLocation,
+    inv_world_matrix: UniformLocation,
+    camera_position: UniformLocation,
+    shape: UniformLocation,
+    extents: UniformLocation,
+    radius: UniformLocation,
+    density: UniformLocation,
+    fog_color: UniformLocation,
+    ambient_color: UniformLocation,
+    sample_count: UniformLocation,
+    light_count: UniformLocation,
+    light_kind: UniformLocation,
+    light_position: UniformLocation,
+    light_direction: UniformLocation,
+    light_color: UniformLocation,
+    light_radius: UniformLocation,
+    half_hotspot_cone_angle_cos: UniformLocation,
+    half_cone_angle_cos: UniformLocation,
+    light_anisotropy: UniformLocation,
+}
+
+impl FogVolumeShader {
+    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/fog_volume_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+        let program =
+            GpuProgram::from_source(state, "FogVolumeShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            depth_sampler: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            inv_view_proj_matrix: program
+                .uniform_location(state, &ImmutableString::new("invViewProj"))?,
+            inv_world_matrix: program
+                .uniform_location(state, &ImmutableString::new("invWorldMatrix"))?,
+            camera_position: program
+                .uniform_location(state, &ImmutableString::new("cameraPosition"))?,
+            shape: program.uniform_location(state, &ImmutableString::new("shape"))?,
+            extents: program.uniform_location(state, &ImmutableString::new("extents"))?,
+            radius: program.uniform_location(state, &ImmutableString::new("radius"))?,
+            density: program.uniform_location(state, &ImmutableString::new("density"))?,
+            fog_color: program.uniform_location(state, &ImmutableString::new("fogColor"))?,
+            ambient_color: program
+                .uniform_location(state, &ImmutableString::new("ambientColor"))?,
+            sample_count: program.uniform_location(state, &ImmutableString::new("sampleCount"))?,
+            light_count: program.uniform_location(state, &ImmutableString::new("lightCount"))?,
+            light_kind: program.uniform_location(state, &ImmutableString::new("lightKind"))?,
+            light_position: program
+                .uniform_location(state, &ImmutableString::new("lightPosition"))?,
+            light_direction: program
+                .uniform_location(state, &ImmutableString::new("lightDirection"))?,
+            light_color: program.uniform_location(state, &ImmutableString::new("lightColor"))?,
+            light_radius: program.uniform_location(state, &ImmutableString::new("lightRadius"))?,
+            half_hotspot_cone_angle_cos: program
+                .uniform_location(state, &ImmutableString::new("halfHotspotConeAngleCos"))?,
+            half_cone_angle_cos: program
+                .uniform_location(state, &ImmutableString::new("halfConeAngleCos"))?,
+            light_anisotropy: program
+                .uniform_location(state, &ImmutableString::new("lightAnisotropy"))?,
+            program,
+        })
+    }
+}
+
+/// A light prepared for scattering in a fog volume.
+struct ScatteringLight {
+    kind: i32,
+    position: Vector3<f32>,
+    direction: Vector3<f32>,
+    color: Vector3<f32>,
+    radius: f32,
+    half_hotspot_cone_angle_cos: f32,
+    half_cone_angle_cos: f32,
+    anisotropy: f32,
+}
+
+impl ScatteringLight {
+    fn from_node(light: &Node) -> Option<Self> {
+        let scl = light.local_transform().scale();
+        let radius_scale = scl.x.max(scl.y).max(scl.z);
+        let direction = light
+            .up_vector()
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::z);
+
+        let (kind, base_light, radius, half_hotspot_cone_angle_cos, half_cone_angle_cos) =
+            if let Some(spot) = light.cast::<SpotLight>() {
+                // Light scattering is not supported for projector lights.
+                if spot.projection() == SpotLightProjection::Orthographic {
+                    return None;
+                }
+                (
+                    LIGHT_KIND_SPOT,
+                    spot.base_light_ref(),
+                    spot.distance() * radius_scale,
+                    (spot.hotspot_cone_angle() * 0.5).cos(),
+                    (spot.full_cone_angle() * 0.5).cos(),
+                )
+            } else if let Some(point) = light.cast::<PointLight>() {
+                (
+                    LIGHT_KIND_POINT,
+                    point.base_light_ref(),
+                    point.radius() * radius_scale,
+                    0.0,
+                    0.0,
+                )
+            } else if let Some(directional) = light.cast::<DirectionalLight>() {
+                (
+                    LIGHT_KIND_DIRECTIONAL,
+                    directional.base_light_ref(),
+                    f32::MAX,
+                    0.0,
+                    0.0,
+                )
+            } else {
+                return None;
+            };
+
+        if !base_light.is_scatter_enabled() {
+            return None;
+        }
+
+        Some(Self {
+            kind,
+            position: light.global_position(),
+            direction,
+            color: scattered_color(base_light),
+            radius,
+            half_hotspot_cone_angle_cos,
+            half_cone_angle_cos,
+            anisotropy: base_light.scatter_anisotropy(),
+        })
+    }
+}
+
+/// Returns linear color of a light that scatters in fog. Scatter factor of a light (multiplied by
+/// its density) is relative to the default one, so lights with default settings scatter their full
+/// color.
+fn scattered_color(light: &BaseLight) -> Vector3<f32> {
+    let scatter = light.effective_scatter().component_div(&Vector3::new(
+        DEFAULT_SCATTER_R,
+        DEFAULT_SCATTER_G,
+        DEFAULT_SCATTER_B,
+    ));
+    light
+        .color()
+        .srgb_to_linear_f32()
+        .xyz()
+        .component_mul(&scatter)
+        .scale(light.intensity())
+}
+
+pub(in crate) struct FogVolumeRenderContext<'a> {
+    pub state: &'a mut PipelineState,
+    pub graph: &'a Graph,
+    pub camera: &'a Camera,
+    pub depth: Rc<RefCell<GpuTexture>>,
+    pub frame_buffer: &'a mut FrameBuffer,
+    pub viewport: Rect<i32>,
+    pub ambient_color: Color,
+    pub settings: &'a QualitySettings,
+}
+
+pub struct FogVolumeRenderer {
+    shader: FogVolumeShader,
+    quad: GeometryBuffer,
+}
+
+impl FogVolumeRenderer {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: FogVolumeShader::new(state)?,
+            quad: GeometryBuffer::from_surface_data(
+                &SurfaceData::make_unit_xy_quad(),
+                GeometryBufferKind::StaticDraw,
+                state,
+            ),
+        })
+    }
+
+    /// Renders every visible fog volume on top of lit opaque geometry. Must be called before
+    /// rendering of transparent objects.
+    #[must_use]
+    pub(in crate) fn render(
+        &mut self,
+        args: FogVolumeRenderContext,
+    ) -> (RenderPassStatistics, FogVolumeStatistics) {
+        scope_profile!();
+
+        let mut pass_stats = RenderPassStatistics::default();
+        let mut fog_stats = FogVolumeStatistics::default();
+
+        let FogVolumeRenderContext {
+            state,
+            graph,
+            camera,
+            depth,
+            frame_buffer,
+            viewport,
+            ambient_color,
+            settings,
+        } = args;
+
+        let frustum = Frustum::from(camera.view_projection_matrix()).unwrap_or_default();
+
+        let volumes = graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<FogVolume>())
+            .filter(|volume| {
+                volume.global_visibility()
+                    && volume.density() > 0.0
+                    && camera.can_see_layers(volume.render_mask())
+                    && frustum.is_intersects_aabb(&volume.world_bounding_box())
+            })
+            .collect::<Vec<_>>();
+
+        if volumes.is_empty() {
+            return (pass_stats, fog_stats);
+        }
+
+        // Lights are gathered once per frame, every volume then picks lights that intersect it.
+        let lights = if settings.fog_volume_samples > 0 {
+            graph
+                .linear_iter()
+                .filter(|node| {
+                    node.global_visibility() && camera.can_see_layers(node.render_mask())
+                })
+                .filter_map(ScatteringLight::from_node)
+                .collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+
+        let frame_matrix = Matrix4::new_orthographic(
+            0.0,
+            viewport.w() as f32,
+            viewport.h() as f32,
+            0.0,
+            -1.0,
+            1.0,
+        ) * Matrix4::new_nonuniform_scaling(&Vector3::new(
+            viewport.w() as f32,
+            viewport.h() as f32,
+            0.0,
+        ));
+        let inv_view_projection = camera
+            .view_projection_matrix()
+            .try_inverse()
+            .unwrap_or_default();
+        let camera_position = camera.global_position();
+        let ambient_color = ambient_color.srgb_to_linear_f32().xyz();
+
+        fog_stats.samples_per_pixel = settings.fog_volume_samples as usize;
+
+        for volume in volumes {
+            let inv_world_matrix = match volume.global_transform().try_inverse() {
+                Some(inv_world_matrix) => inv_world_matrix,
+                // Degenerate volume (zero scale), nothing to render.
+                None => continue,
+            };
+
+            let mut volume_lights = Vec::new();
+            if volume.is_scatter_lights() {
+                let world_box = volume.world_bounding_box();
+                volume_lights = lights
+                    .iter()
+                    .filter(|light| {
+                        light.kind == LIGHT_KIND_DIRECTIONAL
+                            || world_box.is_intersects_sphere(light.position, light.radius)
+                    })
+                    .collect::<Vec<_>>();
+                // The closest lights are more important.
+                let center = world_box.center();
+                volume_lights.sort_by(|a, b| {
+                    let a = (a.position - center).norm_squared();
+                    let b = (b.position - center).norm_squared();
+                    a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+                });
+                volume_lights.truncate(MAX_FOG_VOLUME_LIGHTS);
+            }
+
+            let mut light_kind = [0; MAX_FOG_VOLUME_LIGHTS];
+            let mut light_position = [Vector3::default(); MAX_FOG_VOLUME_LIGHTS];
+            let mut light_direction = [Vector3::default(); MAX_FOG_VOLUME_LIGHTS];
+            let mut light_color = [Vector3::default(); MAX_FOG_VOLUME_LIGHTS];
+            let mut light_radius = [0.0; MAX_FOG_VOLUME_LIGHTS];
+            let mut half_hotspot_cone_angle_cos = [0.0; MAX_FOG_VOLUME_LIGHTS];
+            let mut half_cone_angle_cos = [0.0; MAX_FOG_VOLUME_LIGHTS];
+            let mut light_anisotropy = [0.0; MAX_FOG_VOLUME_LIGHTS];
+            for (i, light) in volume_lights.iter().enumerate() {
+                light_kind[i] = light.kind;
+                light_position[i] = light.position;
+                light_direction[i] = light.direction;
+                light_color[i] = light.color;
+                light_radius[i] = light.radius;
+                half_hotspot_cone_angle_cos[i] = light.half_hotspot_cone_angle_cos;
+                half_cone_angle_cos[i] = light.half_cone_angle_cos;
+                light_anisotropy[i] = light.anisotropy;
+            }
+
+            if settings.fog_volume_samples > 0 {
+                fog_stats.ray_marched_volumes_rendered += 1;
+                fog_stats.scattering_lights += volume_lights.len();
+            } else {
+                fog_stats.analytic_volumes_rendered += 1;
+            }
+
+            let shader = &self.shader;
+            pass_stats += frame_buffer.draw(
+                &self.quad,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test: false,
+                    // Fog color is premultiplied by its opacity.
+                    blend: Some(BlendFunc {
+                        sfactor: BlendFactor::One,
+                        dfactor: BlendFactor::OneMinusSrcAlpha,
+                    }),
+                    stencil_op: Default::default(),
+                    alpha_to_coverage: false,
+                    depth_func: Default::default(),
+                },
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&shader.wvp_matrix, &frame_matrix)
+                        .set_texture(&shader.depth_sampler, &depth)
+                        .set_matrix4(&shader.inv_view_proj_matrix, &inv_view_projection)
+                        .set_matrix4(&shader.inv_world_matrix, &inv_world_matrix)
+                        .set_vector3(&shader.camera_position, &camera_position)
+                        .set_i32(
+                            &shader.shape,
+                            match volume.shape() {
+                                FogVolumeShape::Box => 0,
+                                FogVolumeShape::Sphere => 1,
+                            },
+                        )
+                        .set_vector3(&shader.extents, &volume.extents())
+                        .set_f32(&shader.radius, volume.radius())
+                        .set_f32(&shader.density, volume.density())
+                        .set_vector3(
+                            &shader.fog_color,
+                            &volume.color().srgb_to_linear_f32().xyz(),
+                        )
+                        .set_vector3(&shader.ambient_color, &ambient_color)
+                        .set_i32(&shader.sample_count, settings.fog_volume_samples as i32)
+                        .set_i32(&shader.light_count, volume_lights.len() as i32)
+                        .set_i32_slice(&shader.light_kind, &light_kind)
+                        .set_vector3_slice(&shader.light_position, &light_position)
+                        .set_vector3_slice(&shader.light_direction, &light_direction)
+                        .set_vector3_slice(&shader.light_color, &light_color)
+                        .set_f32_slice(&shader.light_radius, &light_radius)
+                        .set_f32_slice(
+                            &shader.half_hotspot_cone_angle_cos,
+                            &half_hotspot_cone_angle_cos,
+                        )
+                        .set_f32_slice(&shader.half_cone_angle_cos, &half_cone_angle_cos)
+                        .set_f32_slice(&shader.light_anisotropy, &light_anisotropy);
+                },
+            );
+        }
+
+        (pass_stats, fog_stats)
+    }
+}
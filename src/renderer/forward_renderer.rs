@@ -8,7 +8,11 @@
 
 use crate::core::sstorage::ImmutableString;
 use crate::{
-    core::{math::Rect, scope_profile},
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        math::Rect,
+        scope_profile,
+    },
     renderer::{
         apply_material,
         batch::BatchStorage,
@@ -20,11 +24,11 @@ use crate::{
 };
 use std::{cell::RefCell, rc::Rc};
 
-pub(in crate) struct ForwardRenderer {
+pub(crate) struct ForwardRenderer {
     render_pass_name: ImmutableString,
 }
 
-pub(in crate) struct ForwardRenderContext<'a, 'b> {
+pub(crate) struct ForwardRenderContext<'a, 'b> {
     pub state: &'a mut PipelineState,
     pub camera: &'b Camera,
     pub geom_cache: &'a mut GeometryCache,
@@ -37,16 +41,18 @@ pub(in crate) struct ForwardRenderContext<'a, 'b> {
     pub white_dummy: Rc<RefCell<GpuTexture>>,
     pub normal_dummy: Rc<RefCell<GpuTexture>>,
     pub black_dummy: Rc<RefCell<GpuTexture>>,
+    /// Sub-pixel offset (in NDC) of the projection matrix, see [`crate::renderer::gbuffer`].
+    pub jitter: Vector2<f32>,
 }
 
 impl ForwardRenderer {
-    pub(in crate) fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             render_pass_name: ImmutableString::new("Forward"),
         }
     }
 
-    pub(in crate) fn render(&self, args: ForwardRenderContext) -> RenderPassStatistics {
+    pub(crate) fn render(&self, args: ForwardRenderContext) -> RenderPassStatistics {
         scope_profile!();
 
         let mut statistics = RenderPassStatistics::default();
@@ -64,9 +70,11 @@ impl ForwardRenderer {
             white_dummy,
             normal_dummy,
             black_dummy,
+            jitter,
         } = args;
 
-        let initial_view_projection = camera.view_projection_matrix();
+        let jitter_matrix = Matrix4::new_translation(&Vector3::new(jitter.x, jitter.y, 0.0));
+        let initial_view_projection = jitter_matrix * camera.view_projection_matrix();
 
         for batch in batch_storage
             .batches
@@ -85,11 +93,13 @@ impl ForwardRenderer {
                         let view_projection = if instance.depth_offset != 0.0 {
                             let mut projection = camera.projection_matrix();
                             projection[14] -= instance.depth_offset;
-                            projection * camera.view_matrix()
+                            jitter_matrix * projection * camera.view_matrix()
                         } else {
                             initial_view_projection
                         };
 
+                        let wvp_matrix = view_projection * instance.world_transform;
+
                         statistics += framebuffer.draw(
                             geometry,
                             state,
@@ -102,12 +112,19 @@ impl ForwardRenderer {
                                     program_binding: &mut program_binding,
                                     texture_cache,
                                     world_matrix: &instance.world_transform,
-                                    wvp_matrix: &(view_projection * instance.world_transform),
-                                    bone_matrices: &instance.bone_matrices,
+                                    wvp_matrix: &wvp_matrix,
+                                    bone_matrices: batch_storage.instance_bone_matrices(instance),
+                                    bone_matrices_offset: instance.bone_matrices.start,
+                                    bone_matrices_storage: batch_storage.bone_matrices_texture(),
                                     use_skeletal_animation: batch.is_skinned,
                                     camera_position: &camera.global_position(),
                                     use_pom: quality_settings.use_parallax_mapping,
                                     light_position: &Default::default(),
+                                    // Forward pass does not write motion vectors.
+                                    prev_wvp_matrix: &wvp_matrix,
+                                    prev_bone_matrices_storage: None,
+                                    jitter: &jitter,
+                                    wind: &batch_storage.wind,
                                     normal_dummy: normal_dummy.clone(),
                                     white_dummy: white_dummy.clone(),
                                     black_dummy: black_dummy.clone(),
@@ -5,7 +5,7 @@ use crate::{
         geometry_buffer::{DrawCallStatistics, GeometryBuffer},
         gpu_program::{GpuProgram, GpuProgramBinding},
         gpu_texture::{CubeMapFace, GpuTexture, GpuTextureKind, PixelElementKind},
-        state::{BlendFunc, ColorMask, PipelineState, StencilFunc, StencilOp},
+        state::{BlendFunc, ColorMask, CompareFunc, PipelineState, StencilFunc, StencilOp},
     },
 };
 use glow::HasContext;
@@ -53,6 +53,16 @@ pub struct DrawParameters {
     pub depth_test: bool,
     pub blend: Option<BlendFunc>,
     pub stencil_op: StencilOp,
+    /// Converts alpha of the first render target into a coverage mask of samples, which gives
+    /// smooth edges of alpha-tested geometry (foliage, fences, etc.) without sorting. Works only
+    /// when rendering into a multisampled render target, ignored otherwise.
+    #[serde(default)]
+    #[visit(optional)] // Backward compatibility
+    pub alpha_to_coverage: bool,
+    /// Comparison function of the depth test, it is used only if `depth_test` is enabled.
+    #[serde(default)]
+    #[visit(optional)] // Backward compatibility
+    pub depth_func: CompareFunc,
 }
 
 impl Default for DrawParameters {
@@ -65,6 +75,8 @@ impl Default for DrawParameters {
             depth_test: true,
             blend: None,
             stencil_op: Default::default(),
+            alpha_to_coverage: false,
+            depth_func: Default::default(),
         }
     }
 }
@@ -107,6 +119,15 @@ unsafe fn set_attachment(state: &mut PipelineState, gl_attachment_kind: u32, tex
                 0,
             );
         }
+        GpuTextureKind::Array { .. } => {
+            state.gl.framebuffer_texture_layer(
+                glow::FRAMEBUFFER,
+                gl_attachment_kind,
+                Some(texture.id()),
+                0,
+                0,
+            );
+        }
     }
 }
 
@@ -208,6 +229,29 @@ impl FrameBuffer {
         self
     }
 
+    /// Reads pixels of the first color attachment in the given rectangle as RGB triples of 32-bit
+    /// floats. It is a blocking operation that stalls the pipeline, it should be used only in
+    /// offline tasks, such as baking.
+    pub fn read_pixels_rgb_f32(&self, state: &mut PipelineState, rect: Rect<i32>) -> Vec<u8> {
+        let mut bytes = vec![0u8; (rect.w().max(0) * rect.h().max(0)) as usize * 3 * 4];
+
+        unsafe {
+            state.set_framebuffer(self.fbo);
+            state.gl.read_buffer(glow::COLOR_ATTACHMENT0);
+            state.gl.read_pixels(
+                rect.x(),
+                rect.y(),
+                rect.w(),
+                rect.h(),
+                glow::RGB,
+                glow::FLOAT,
+                glow::PixelPackData::Slice(&mut bytes),
+            );
+        }
+
+        bytes
+    }
+
     /// None is possible only for back buffer.
     pub fn id(&self) -> Option<glow::Framebuffer> {
         self.fbo
@@ -4,11 +4,13 @@ use crate::{
         algebra::Matrix2,
         algebra::{Matrix3, Matrix4, Vector2, Vector3, Vector4},
         color::Color,
+        parking_lot::Mutex,
     },
+    lazy_static::lazy_static,
     renderer::framework::{error::FrameworkError, gpu_texture::GpuTexture, state::PipelineState},
     utils::log::{Log, MessageKind},
 };
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use glow::HasContext;
 use std::ops::Deref;
 use std::{cell::RefCell, marker::PhantomData, rc::Rc};
@@ -28,10 +30,18 @@ pub enum BuiltInUniform {
     WorldMatrix,
     WorldViewProjectionMatrix,
     BoneMatrices,
+    BoneMatricesStorage,
+    BoneMatricesOffset,
     UseSkeletalAnimation,
     CameraPosition,
     UsePOM,
     LightPosition,
+    PrevWorldViewProjectionMatrix,
+    PrevBoneMatricesStorage,
+    Jitter,
+    Wind,
+    WindNoise,
+    WindTime,
     // Must be last.
     Count,
 }
@@ -43,13 +53,37 @@ pub struct UniformLocation {
     thread_mark: PhantomData<*const u8>,
 }
 
+lazy_static! {
+    static ref INJECTED_FAILURES: Mutex<FxHashSet<String>> = Default::default();
+}
+
+// A shader that never compiles, it replaces sources of programs with injected failures.
+const BROKEN_SHADER_SOURCE: &str = "#error Simulated shader compilation failure.";
+
+/// Makes every further compilation of a GPU program with the given name (for example
+/// `"SsaoShader"`) fail as if the source of the program was invalid. It is intended to test the
+/// degraded mode of the renderer (see [`crate::renderer::capabilities`]). Built-in shaders are
+/// compiled when the renderer is created, so failures must be injected before that.
+pub fn inject_shader_failure(program_name: &str) {
+    INJECTED_FAILURES.lock().insert(program_name.to_owned());
+}
+
+/// Removes every failure injected by [`inject_shader_failure`].
+pub fn clear_injected_shader_failures() {
+    INJECTED_FAILURES.lock().clear();
+}
+
+fn is_failure_injected(program_name: &str) -> bool {
+    INJECTED_FAILURES.lock().contains(program_name)
+}
+
 unsafe fn create_shader(
     state: &mut PipelineState,
     name: String,
     actual_type: u32,
     source: &str,
 ) -> Result<glow::Shader, FrameworkError> {
-    let merged_source = prepare_source_code(source);
+    let merged_source = prepare_source_code(source, state.supports_vertex_texture_fetch());
 
     let shader = state.gl.create_shader(actual_type)?;
     state.gl.shader_source(shader, &merged_source);
@@ -86,8 +120,16 @@ unsafe fn create_shader(
 }
 
 #[allow(clippy::let_and_return)]
-fn prepare_source_code(code: &str) -> String {
-    let mut full_source_code = "#version 330 core\n// include 'shared.glsl'\n".to_owned();
+fn prepare_source_code(code: &str, supports_vertex_texture_fetch: bool) -> String {
+    let mut full_source_code = "#version 330 core\n".to_owned();
+
+    // Bone matrices are fetched from a texture if possible, otherwise shaders fall back to
+    // an array of uniforms (see `S_FetchBoneMatrix` in shared.glsl).
+    if supports_vertex_texture_fetch {
+        full_source_code += "#define FYROX_BONE_MATRICES_TEXTURE\n";
+    }
+
+    full_source_code += "// include 'shared.glsl'\n";
 
     // HACK
     #[cfg(target_arch = "wasm32")]
@@ -422,6 +464,10 @@ fn fetch_built_in_uniform_locations(
         fetch_uniform_location(state, program, "fyrox_worldViewProjection");
     locations[BuiltInUniform::BoneMatrices as usize] =
         fetch_uniform_location(state, program, "fyrox_boneMatrices");
+    locations[BuiltInUniform::BoneMatricesStorage as usize] =
+        fetch_uniform_location(state, program, "fyrox_boneMatricesStorage");
+    locations[BuiltInUniform::BoneMatricesOffset as usize] =
+        fetch_uniform_location(state, program, "fyrox_boneMatricesOffset");
     locations[BuiltInUniform::UseSkeletalAnimation as usize] =
         fetch_uniform_location(state, program, "fyrox_useSkeletalAnimation");
     locations[BuiltInUniform::CameraPosition as usize] =
@@ -430,6 +476,17 @@ fn fetch_built_in_uniform_locations(
         fetch_uniform_location(state, program, "fyrox_usePOM");
     locations[BuiltInUniform::LightPosition as usize] =
         fetch_uniform_location(state, program, "fyrox_lightPosition");
+    locations[BuiltInUniform::PrevWorldViewProjectionMatrix as usize] =
+        fetch_uniform_location(state, program, "fyrox_prevWorldViewProjection");
+    locations[BuiltInUniform::PrevBoneMatricesStorage as usize] =
+        fetch_uniform_location(state, program, "fyrox_prevBoneMatricesStorage");
+    locations[BuiltInUniform::Jitter as usize] =
+        fetch_uniform_location(state, program, "fyrox_jitter");
+    locations[BuiltInUniform::Wind as usize] = fetch_uniform_location(state, program, "fyrox_wind");
+    locations[BuiltInUniform::WindNoise as usize] =
+        fetch_uniform_location(state, program, "fyrox_windNoise");
+    locations[BuiltInUniform::WindTime as usize] =
+        fetch_uniform_location(state, program, "fyrox_windTime");
 
     locations
 }
@@ -441,6 +498,12 @@ impl GpuProgram {
         vertex_source: &str,
         fragment_source: &str,
     ) -> Result<GpuProgram, FrameworkError> {
+        let fragment_source = if is_failure_injected(name) {
+            BROKEN_SHADER_SOURCE
+        } else {
+            fragment_source
+        };
+
         unsafe {
             let vertex_shader = create_shader(
                 state,
@@ -540,3 +603,20 @@ impl Drop for GpuProgram {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::framework::gpu_program::{
+        clear_injected_shader_failures, inject_shader_failure, is_failure_injected,
+    };
+
+    #[test]
+    fn test_shader_failure_injection() {
+        inject_shader_failure("TestFailingShader");
+        assert!(is_failure_injected("TestFailingShader"));
+        assert!(!is_failure_injected("TestShader"));
+
+        clear_injected_shader_failures();
+        assert!(!is_failure_injected("TestFailingShader"));
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
         TextureWrapMode,
     },
 };
-use glow::{HasContext, COMPRESSED_RED_RGTC1, COMPRESSED_RG_RGTC2};
+use glow::{HasContext, PixelUnpackData, COMPRESSED_RED_RGTC1, COMPRESSED_RG_RGTC2};
 use std::marker::PhantomData;
 
 #[derive(Copy, Clone)]
@@ -27,6 +27,11 @@ pub enum GpuTextureKind {
         height: usize,
         depth: usize,
     },
+    Array {
+        width: usize,
+        height: usize,
+        layers: usize,
+    },
 }
 
 impl From<TextureKind> for GpuTextureKind {
@@ -52,6 +57,15 @@ impl From<TextureKind> for GpuTextureKind {
                 height: height as usize,
                 depth: depth as usize,
             },
+            TextureKind::Array {
+                width,
+                height,
+                layers,
+            } => GpuTextureKind::Array {
+                width: width as usize,
+                height: height as usize,
+                layers: layers as usize,
+            },
         }
     }
 }
@@ -63,11 +77,12 @@ impl GpuTextureKind {
             Self::Rectangle { .. } => glow::TEXTURE_2D,
             Self::Cube { .. } => glow::TEXTURE_CUBE_MAP,
             Self::Volume { .. } => glow::TEXTURE_3D,
+            Self::Array { .. } => glow::TEXTURE_2D_ARRAY,
         }
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum PixelKind {
     F32,
     F16,
@@ -94,6 +109,7 @@ pub enum PixelKind {
     RGB32F,
     RGBA32F,
     RGBA16F,
+    RG16F,
     R8RGTC,
     RG8RGTC,
     R11G11B10F,
@@ -143,6 +159,7 @@ impl PixelKind {
             | Self::BGRA8
             | Self::BGR8
             | Self::RG16
+            | Self::RG16F
             | Self::R16
             | Self::D24S8
             | Self::D32F
@@ -172,6 +189,7 @@ impl PixelKind {
             // to add new entry here.
             Self::RGBA16
             | Self::RGBA16F
+            | Self::RG16F
             | Self::RGB16
             | Self::RGBA8
             | Self::SRGBA8
@@ -196,6 +214,55 @@ impl PixelKind {
         }
     }
 
+    /// Returns a triple of OpenGL pixel type, pixel format and internal format of the pixel kind.
+    /// Compressed formats have zero type and format.
+    fn gl_formats(self) -> (u32, u32, u32) {
+        match self {
+            Self::F32 => (glow::FLOAT, glow::RED, glow::R32F),
+            Self::F16 => (glow::FLOAT, glow::RED, glow::R16F),
+            Self::D32F => (glow::FLOAT, glow::DEPTH_COMPONENT, glow::DEPTH_COMPONENT32F),
+            Self::D16 => (
+                glow::UNSIGNED_SHORT,
+                glow::DEPTH_COMPONENT,
+                glow::DEPTH_COMPONENT16,
+            ),
+            Self::D24S8 => (
+                glow::UNSIGNED_INT_24_8,
+                glow::DEPTH_STENCIL,
+                glow::DEPTH24_STENCIL8,
+            ),
+            Self::RGBA8 => (glow::UNSIGNED_BYTE, glow::RGBA, glow::RGBA8),
+            Self::SRGBA8 => (glow::UNSIGNED_BYTE, glow::RGBA, glow::SRGB8_ALPHA8),
+            Self::RGB8 => (glow::UNSIGNED_BYTE, glow::RGB, glow::RGB8),
+            Self::SRGB8 => (glow::UNSIGNED_BYTE, glow::RGB, glow::SRGB8),
+            Self::RG8 => (glow::UNSIGNED_BYTE, glow::RG, glow::RG8),
+            Self::R8 => (glow::UNSIGNED_BYTE, glow::RED, glow::R8),
+            Self::R8UI => (glow::UNSIGNED_BYTE, glow::RED_INTEGER, glow::R8UI),
+            Self::BGRA8 => (glow::UNSIGNED_BYTE, glow::BGRA, glow::RGBA8),
+            Self::BGR8 => (glow::UNSIGNED_BYTE, glow::BGR, glow::RGB8),
+            Self::RG16 => (glow::UNSIGNED_SHORT, glow::RG, glow::RG16),
+            Self::R16 => (glow::UNSIGNED_SHORT, glow::RED, glow::R16),
+            Self::RGB16 => (glow::UNSIGNED_SHORT, glow::RGB, glow::RGB16),
+            Self::RGBA16 => (glow::UNSIGNED_SHORT, glow::RGBA, glow::RGBA16),
+            Self::RGB10A2 => (
+                glow::UNSIGNED_INT_2_10_10_10_REV,
+                glow::RGBA,
+                glow::RGB10_A2,
+            ),
+            Self::DXT1RGB => (0, 0, GL_COMPRESSED_RGB_S3TC_DXT1_EXT),
+            Self::DXT1RGBA => (0, 0, GL_COMPRESSED_RGBA_S3TC_DXT1_EXT),
+            Self::DXT3RGBA => (0, 0, GL_COMPRESSED_RGBA_S3TC_DXT3_EXT),
+            Self::DXT5RGBA => (0, 0, GL_COMPRESSED_RGBA_S3TC_DXT5_EXT),
+            Self::R8RGTC => (0, 0, COMPRESSED_RED_RGTC1),
+            Self::RG8RGTC => (0, 0, COMPRESSED_RG_RGTC2),
+            Self::RGB32F => (glow::FLOAT, glow::RGB, glow::RGB32F),
+            Self::RGBA32F => (glow::FLOAT, glow::RGBA, glow::RGBA32F),
+            Self::RGBA16F => (glow::FLOAT, glow::RGBA, glow::RGBA16F),
+            Self::RG16F => (glow::FLOAT, glow::RG, glow::RG16F),
+            Self::R11G11B10F => (glow::FLOAT, glow::RGB, glow::R11F_G11F_B10F),
+        }
+    }
+
     pub fn element_kind(self) -> PixelElementKind {
         match self {
             Self::F32
@@ -203,6 +270,7 @@ impl PixelKind {
             | Self::RGB32F
             | Self::RGBA32F
             | Self::RGBA16F
+            | Self::RG16F
             | Self::D32F
             | Self::R11G11B10F => PixelElementKind::Float,
             Self::D16
@@ -261,6 +329,7 @@ fn image_3d_size_bytes(pixel_kind: PixelKind, width: usize, height: usize, depth
         | PixelKind::SRGBA8
         | PixelKind::BGRA8
         | PixelKind::RG16
+        | PixelKind::RG16F
         | PixelKind::D24S8
         | PixelKind::D32F
         | PixelKind::F32
@@ -291,6 +360,7 @@ fn image_2d_size_bytes(pixel_kind: PixelKind, width: usize, height: usize) -> us
         | PixelKind::SRGBA8
         | PixelKind::BGRA8
         | PixelKind::RG16
+        | PixelKind::RG16F
         | PixelKind::D24S8
         | PixelKind::D32F
         | PixelKind::F32
@@ -320,6 +390,7 @@ fn image_1d_size_bytes(pixel_kind: PixelKind, length: usize) -> usize {
         | PixelKind::SRGBA8
         | PixelKind::BGRA8
         | PixelKind::RG16
+        | PixelKind::RG16F
         | PixelKind::D24S8
         | PixelKind::D32F
         | PixelKind::F32
@@ -598,6 +669,21 @@ impl<'a> TextureBinding<'a> {
                         break 'mip_loop;
                     }
                 }
+                GpuTextureKind::Array {
+                    width,
+                    height,
+                    layers,
+                } => {
+                    if let (Some(width), Some(height)) = (
+                        width.checked_shr(mip as u32),
+                        height.checked_shr(mip as u32),
+                    ) {
+                        desired_byte_count +=
+                            layers * image_2d_size_bytes(pixel_kind, width, height);
+                    } else {
+                        break 'mip_loop;
+                    }
+                }
             };
         }
 
@@ -620,49 +706,7 @@ impl<'a> TextureBinding<'a> {
             self.state
                 .set_texture(0, target, Some(self.texture.texture));
 
-            let (type_, format, internal_format) = match pixel_kind {
-                PixelKind::F32 => (glow::FLOAT, glow::RED, glow::R32F),
-                PixelKind::F16 => (glow::FLOAT, glow::RED, glow::R16F),
-                PixelKind::D32F => (glow::FLOAT, glow::DEPTH_COMPONENT, glow::DEPTH_COMPONENT32F),
-                PixelKind::D16 => (
-                    glow::UNSIGNED_SHORT,
-                    glow::DEPTH_COMPONENT,
-                    glow::DEPTH_COMPONENT16,
-                ),
-                PixelKind::D24S8 => (
-                    glow::UNSIGNED_INT_24_8,
-                    glow::DEPTH_STENCIL,
-                    glow::DEPTH24_STENCIL8,
-                ),
-                PixelKind::RGBA8 => (glow::UNSIGNED_BYTE, glow::RGBA, glow::RGBA8),
-                PixelKind::SRGBA8 => (glow::UNSIGNED_BYTE, glow::RGBA, glow::SRGB8_ALPHA8),
-                PixelKind::RGB8 => (glow::UNSIGNED_BYTE, glow::RGB, glow::RGB8),
-                PixelKind::SRGB8 => (glow::UNSIGNED_BYTE, glow::RGB, glow::SRGB8),
-                PixelKind::RG8 => (glow::UNSIGNED_BYTE, glow::RG, glow::RG8),
-                PixelKind::R8 => (glow::UNSIGNED_BYTE, glow::RED, glow::R8),
-                PixelKind::R8UI => (glow::UNSIGNED_BYTE, glow::RED_INTEGER, glow::R8UI),
-                PixelKind::BGRA8 => (glow::UNSIGNED_BYTE, glow::BGRA, glow::RGBA8),
-                PixelKind::BGR8 => (glow::UNSIGNED_BYTE, glow::BGR, glow::RGB8),
-                PixelKind::RG16 => (glow::UNSIGNED_SHORT, glow::RG, glow::RG16),
-                PixelKind::R16 => (glow::UNSIGNED_SHORT, glow::RED, glow::R16),
-                PixelKind::RGB16 => (glow::UNSIGNED_SHORT, glow::RGB, glow::RGB16),
-                PixelKind::RGBA16 => (glow::UNSIGNED_SHORT, glow::RGBA, glow::RGBA16),
-                PixelKind::RGB10A2 => (
-                    glow::UNSIGNED_INT_2_10_10_10_REV,
-                    glow::RGBA,
-                    glow::RGB10_A2,
-                ),
-                PixelKind::DXT1RGB => (0, 0, GL_COMPRESSED_RGB_S3TC_DXT1_EXT),
-                PixelKind::DXT1RGBA => (0, 0, GL_COMPRESSED_RGBA_S3TC_DXT1_EXT),
-                PixelKind::DXT3RGBA => (0, 0, GL_COMPRESSED_RGBA_S3TC_DXT3_EXT),
-                PixelKind::DXT5RGBA => (0, 0, GL_COMPRESSED_RGBA_S3TC_DXT5_EXT),
-                PixelKind::R8RGTC => (0, 0, COMPRESSED_RED_RGTC1),
-                PixelKind::RG8RGTC => (0, 0, COMPRESSED_RG_RGTC2),
-                PixelKind::RGB32F => (glow::FLOAT, glow::RGB, glow::RGB32F),
-                PixelKind::RGBA32F => (glow::FLOAT, glow::RGBA, glow::RGBA32F),
-                PixelKind::RGBA16F => (glow::FLOAT, glow::RGBA, glow::RGBA16F),
-                PixelKind::R11G11B10F => (glow::FLOAT, glow::RGB, glow::R11F_G11F_B10F),
-            };
+            let (type_, format, internal_format) = pixel_kind.gl_formats();
 
             let is_compressed = pixel_kind.is_compressed();
 
@@ -838,6 +882,55 @@ impl<'a> TextureBinding<'a> {
                                 );
                             }
 
+                            mip_byte_offset += size as usize;
+                        } else {
+                            // No need to add degenerated mips (0x1, 0x2, 4x0, etc).
+                            break 'mip_loop2;
+                        }
+                    }
+                    GpuTextureKind::Array {
+                        width,
+                        height,
+                        layers,
+                    } => {
+                        if let (Some(width), Some(height)) = (
+                            width.checked_shr(mip as u32),
+                            height.checked_shr(mip as u32),
+                        ) {
+                            // Every mip level contains all layers one after another.
+                            let size =
+                                (layers * image_2d_size_bytes(pixel_kind, width, height)) as i32;
+                            let pixels = data.map(|data| {
+                                &data[mip_byte_offset..(mip_byte_offset + size as usize)]
+                            });
+
+                            if is_compressed {
+                                self.state.gl.compressed_tex_image_3d(
+                                    glow::TEXTURE_2D_ARRAY,
+                                    mip as i32,
+                                    internal_format as i32,
+                                    width as i32,
+                                    height as i32,
+                                    layers as i32,
+                                    0,
+                                    size,
+                                    pixels.ok_or(FrameworkError::EmptyTextureData)?,
+                                );
+                            } else {
+                                self.state.gl.tex_image_3d(
+                                    glow::TEXTURE_2D_ARRAY,
+                                    mip as i32,
+                                    internal_format as i32,
+                                    width as i32,
+                                    height as i32,
+                                    layers as i32,
+                                    0,
+                                    format,
+                                    type_,
+                                    pixels,
+                                );
+                            }
+
                             mip_byte_offset += size as usize;
                         } else {
                             // No need to add degenerated mips (0x1, 0x2, 4x0, etc).
@@ -850,6 +943,70 @@ impl<'a> TextureBinding<'a> {
 
         Ok(self)
     }
+
+    /// Replaces a region of the main level of detail of a rectangle texture. Unlike
+    /// [`Self::set_data`], it does not reallocate texture storage, so it is suitable for
+    /// textures that are updated every frame. The region must lie inside the texture.
+    pub fn set_sub_data(
+        self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        data: &[u8],
+    ) -> Result<Self, FrameworkError> {
+        let pixel_kind = self.texture.pixel_kind;
+
+        match self.texture.kind {
+            GpuTextureKind::Rectangle {
+                width: texture_width,
+                height: texture_height,
+            } if !pixel_kind.is_compressed()
+                && x + width <= texture_width
+                && y + height <= texture_height => {}
+            _ => {
+                return Err(FrameworkError::Custom(
+                    "Sub-data can only be set to a region of a non-compressed rectangle texture!"
+                        .to_owned(),
+                ))
+            }
+        }
+
+        let desired_byte_count = image_2d_size_bytes(pixel_kind, width, height);
+        if data.len() != desired_byte_count {
+            return Err(FrameworkError::InvalidTextureData {
+                expected_data_size: desired_byte_count,
+                actual_data_size: data.len(),
+            });
+        }
+
+        unsafe {
+            self.state
+                .set_texture(0, glow::TEXTURE_2D, Some(self.texture.texture));
+
+            let (type_, format, _) = pixel_kind.gl_formats();
+
+            if let Some(alignment) = pixel_kind.unpack_alignment() {
+                self.state
+                    .gl
+                    .pixel_store_i32(glow::UNPACK_ALIGNMENT, alignment);
+            }
+
+            self.state.gl.tex_sub_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                format,
+                type_,
+                PixelUnpackData::Slice(data),
+            );
+        }
+
+        Ok(self)
+    }
 }
 
 const GL_COMPRESSED_RGB_S3TC_DXT1_EXT: u32 = 0x83F0;
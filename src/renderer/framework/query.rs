@@ -0,0 +1,120 @@
+//! GPU queries, see [`GpuTimer`] docs for more info.
+
+use crate::renderer::framework::state::PipelineState;
+use glow::HasContext;
+use std::marker::PhantomData;
+
+/// Amount of queries in flight. Results of a query become available a few frames later, so
+/// multiple queries are used in round-robin fashion to never wait for the GPU.
+const QUERY_COUNT: usize = 4;
+
+/// Measures how much time the GPU spent on a set of commands between [`GpuTimer::begin`] and
+/// [`GpuTimer::end`]. Results are delayed by a few frames, because the GPU runs asynchronously.
+/// Timers are not supported on WebAssembly, [`GpuTimer::time`] always returns `None` there.
+///
+/// # Important notes
+///
+/// Timers cannot be nested, only one timer could be active at a time.
+pub struct GpuTimer {
+    state: *mut PipelineState,
+    queries: Vec<glow::Query>,
+    pending: [bool; QUERY_COUNT],
+    current: usize,
+    active: bool,
+    time: Option<f32>,
+    // Force compiler to not implement Send and Sync, because OpenGL is not thread-safe.
+    thread_mark: PhantomData<*const u8>,
+}
+
+impl GpuTimer {
+    /// Creates new timer.
+    pub fn new(state: &mut PipelineState) -> Self {
+        let queries = if cfg!(target_arch = "wasm32") {
+            Default::default()
+        } else {
+            (0..QUERY_COUNT)
+                .filter_map(|_| unsafe { state.gl.create_query().ok() })
+                .collect()
+        };
+
+        Self {
+            state,
+            queries,
+            pending: [false; QUERY_COUNT],
+            current: 0,
+            active: false,
+            time: None,
+            thread_mark: PhantomData,
+        }
+    }
+
+    fn is_supported(&self) -> bool {
+        self.queries.len() == QUERY_COUNT
+    }
+
+    fn poll(&mut self, state: &mut PipelineState) {
+        for (query, pending) in self.queries.iter().zip(self.pending.iter_mut()) {
+            if *pending {
+                unsafe {
+                    if state
+                        .gl
+                        .get_query_parameter_u32(*query, glow::QUERY_RESULT_AVAILABLE)
+                        != 0
+                    {
+                        let nanoseconds =
+                            state.gl.get_query_parameter_u32(*query, glow::QUERY_RESULT);
+                        self.time = Some(nanoseconds as f32 / 1_000_000_000.0);
+                        *pending = false;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Starts measuring. It does nothing if there is no free query, the measurement is skipped
+    /// in this case.
+    pub fn begin(&mut self, state: &mut PipelineState) {
+        if !self.is_supported() || self.active {
+            return;
+        }
+
+        self.poll(state);
+
+        if !self.pending[self.current] {
+            unsafe {
+                state
+                    .gl
+                    .begin_query(glow::TIME_ELAPSED, self.queries[self.current]);
+            }
+            self.active = true;
+        }
+    }
+
+    /// Stops measuring, the result will be available via [`Self::time`] a few frames later.
+    pub fn end(&mut self, state: &mut PipelineState) {
+        if self.active {
+            unsafe {
+                state.gl.end_query(glow::TIME_ELAPSED);
+            }
+            self.pending[self.current] = true;
+            self.current = (self.current + 1) % QUERY_COUNT;
+            self.active = false;
+        }
+    }
+
+    /// Returns the latest measured time in seconds, or `None` if there is no result yet or timers
+    /// are not supported.
+    pub fn time(&self) -> Option<f32> {
+        self.time
+    }
+}
+
+impl Drop for GpuTimer {
+    fn drop(&mut self) {
+        unsafe {
+            for query in self.queries.iter() {
+                (*self.state).gl.delete_query(*query);
+            }
+        }
+    }
+}
@@ -158,6 +158,51 @@ float S_InScatter(vec3 start, vec3 dir, vec3 lightPos, float d)
     return l;
 }
 
+// Henyey-Greenstein phase function, where cosTheta is the cosine of the angle between directions
+// of incident and scattered light and g is anisotropy in (-1; 1) range. The function is scaled
+// by 4*PI, so it is equal to one for isotropic scattering (g = 0).
+// https://www.pbr-book.org/3ed-2018/Volume_Scattering/Phase_Functions
+float S_HenyeyGreenstein(float cosTheta, float g)
+{
+    float k = 1.0 + g * g - 2.0 * g * cosTheta;
+    return (1.0 - g * g) / (k * sqrt(k));
+}
+
+// Same as S_InScatter, but takes anisotropy of scattering into account. The integral is
+// evaluated over the angle under which the ray is seen from the light, the phase function is
+// smooth in this parametrization, so a few samples are enough.
+float S_InScatterAnisotropic(vec3 start, vec3 dir, vec3 lightPos, float d, float g)
+{
+    if (abs(g) < 0.001)
+    {
+        return S_InScatter(start, dir, lightPos, d);
+    }
+
+    // light to ray origin
+    vec3 q = start - lightPos;
+
+    // coefficients
+    float b = dot(dir, q);
+    float c = dot(q, q);
+
+    float s = 1.0 / sqrt(c - b*b);
+    float a0 = atan(b*s);
+    float a1 = atan((d + b) * s);
+
+    // Sine of the angle is the cosine of the angle between light-to-point direction and the ray,
+    // light scattered towards the viewer goes against the ray.
+    const int samples = 8;
+    float da = (a1 - a0) / float(samples);
+    float sum = 0.0;
+    for (int i = 0; i < samples; ++i)
+    {
+        float a = a0 + (float(i) + 0.5) * da;
+        sum += S_HenyeyGreenstein(-sin(a), g);
+    }
+
+    return s * sum * da;
+}
+
 // https://en.wikipedia.org/wiki/Rayleigh_scattering
 vec3 S_RayleighScatter(vec3 start, vec3 dir, vec3 lightPos, float d)
 {
@@ -330,6 +375,118 @@ vec4 S_SRGBToLinear(vec4 color) {
     return vec4(rgb, color.a);
 }
 
+// Unpacks a tangent space normal from a normal map. Only red and green channels are used and Z is
+// restored, so it works for both three-channel and two-channel (BC5) normal maps.
+vec3 S_UnpackNormal(vec4 packedNormal) {
+    vec2 xy = packedNormal.xy * 2.0 - 1.0;
+    return vec3(xy, sqrt(max(0.0, 1.0 - dot(xy, xy))));
+}
+
 float S_Luminance(vec3 x) {
     return dot(x, vec3(0.299, 0.587, 0.114));
-}
\ No newline at end of file
+}
+// Fetches a bone matrix of a skinned surface by its index. The engine stores bone matrices of
+// all skinned surfaces in a single texture (4 texels per matrix, one texel per column) and
+// passes the index of the first matrix of a surface in `fyrox_boneMatricesOffset`. If vertex
+// texture fetch is not supported, the matrices are passed in the `fyrox_boneMatrices` uniform
+// array instead, which limits the amount of bones to 60. In this case a shader must define the
+// array by itself:
+//
+// #ifndef FYROX_BONE_MATRICES_TEXTURE
+// uniform mat4 fyrox_boneMatrices[60];
+// #endif
+#ifdef FYROX_BONE_MATRICES_TEXTURE
+uniform highp sampler2D fyrox_boneMatricesStorage;
+uniform highp sampler2D fyrox_prevBoneMatricesStorage;
+uniform int fyrox_boneMatricesOffset;
+
+mat4 S_FetchMatrix(highp sampler2D storage, int index)
+{
+    int width = textureSize(storage, 0).x;
+    int texel = 4 * (fyrox_boneMatricesOffset + index);
+    // Texture width is a multiple of 4, so columns of a matrix are always in the same row.
+    ivec2 coord = ivec2(texel % width, texel / width);
+
+    return mat4(
+        texelFetch(storage, coord, 0),
+        texelFetch(storage, coord + ivec2(1, 0), 0),
+        texelFetch(storage, coord + ivec2(2, 0), 0),
+        texelFetch(storage, coord + ivec2(3, 0), 0)
+    );
+}
+
+mat4 S_FetchBoneMatrix(int index)
+{
+    return S_FetchMatrix(fyrox_boneMatricesStorage, index);
+}
+
+// Fetches a bone matrix from the previous frame, it is used to calculate motion vectors of
+// skinned surfaces. Previous matrices are stored in the same layout as the current ones.
+mat4 S_FetchPrevBoneMatrix(int index)
+{
+    return S_FetchMatrix(fyrox_prevBoneMatricesStorage, index);
+}
+#else
+#define S_FetchBoneMatrix(index) fyrox_boneMatrices[index]
+// There is no room for previous pose in uniforms, so skinned surfaces will have motion vectors
+// of their nodes only.
+#define S_FetchPrevBoneMatrix(index) fyrox_boneMatrices[index]
+#endif
+
+// Previous (unjittered) view-projection matrix multiplied by previous world matrix of a node and
+// the jitter offset (in NDC) of the current frame. Both are used by temporal anti-aliasing, when
+// it is disabled the matrix is equal to `fyrox_worldViewProjection` and the jitter is zero.
+uniform mat4 fyrox_prevWorldViewProjection;
+uniform vec2 fyrox_jitter;
+
+// Computes screen-space motion vector (in texture coordinates) of a fragment using its current
+// (jittered) and previous clip-space positions.
+vec2 S_ComputeMotionVector(vec4 clipPosition, vec4 prevClipPosition)
+{
+    vec2 current = clipPosition.xy / clipPosition.w - fyrox_jitter;
+    vec2 previous = prevClipPosition.xy / prevClipPosition.w;
+    return (current - previous) * 0.5;
+}
+
+// Returns a threshold in [0; 1) range for dithered (screen-door) transparency: a fragment must be
+// discarded if its alpha is less than the threshold. Interleaved gradient noise is used, it has a
+// blue noise-like spectrum, so the pattern is barely visible. The pattern is shifted by the jitter,
+// so when temporal anti-aliasing is enabled it changes every frame and resolves into smooth
+// transparency.
+float S_DitherThreshold(vec2 fragCoord)
+{
+    vec2 p = fragCoord + fyrox_jitter * 4096.0;
+    return fract(52.9829189 * fract(dot(p, vec2(0.06711056, 0.00583715))));
+}
+
+// Wind of the scene: normalized direction and strength (speed in m/s) of the wind, gustiness, gust
+// frequency, gust size and turbulence frequency, and time of the wind. The functions below exactly
+// match `Wind::velocity_at` and `Wind::sway_at`, so vegetation sways in sync with particles.
+uniform vec4 fyrox_wind;
+uniform vec4 fyrox_windNoise;
+uniform float fyrox_windTime;
+
+// Returns velocity of the wind at the given world position.
+vec3 S_WindVelocity(vec3 worldPosition)
+{
+    vec3 direction = fyrox_wind.xyz;
+    float gustPhase = 2.0 * PI * (dot(worldPosition, direction) / fyrox_windNoise.z - fyrox_windTime * fyrox_windNoise.y);
+    float noise = 0.6 * sin(gustPhase) + 0.3 * sin(2.0 * gustPhase + 1.7) + 0.1 * sin(5.0 * gustPhase + 4.1);
+    float turbulence = sin(2.0 * PI * fyrox_windTime * fyrox_windNoise.w + dot(worldPosition, vec3(1.3, 0.7, 1.1)));
+    return direction * fyrox_wind.w * (1.0 + fyrox_windNoise.x * noise + 0.25 * turbulence);
+}
+
+// Returns world-space offset of a vegetation vertex, weight defines flexibility of the vertex
+// (0.0 - rigid, 1.0 - fully flexible).
+vec3 S_WindSway(vec3 worldPosition, float weight)
+{
+    return S_WindVelocity(worldPosition) * weight * 0.05;
+}
+
+// Displaces local position of a vegetation vertex by the wind. The offset is calculated in world
+// space and then transformed back to local space of a mesh.
+vec4 S_WindSwayLocal(vec4 localPosition, mat4 worldMatrix, float weight)
+{
+    vec3 worldPosition = (worldMatrix * localPosition).xyz;
+    return localPosition + vec4(inverse(mat3(worldMatrix)) * S_WindSway(worldPosition, weight), 0.0);
+}
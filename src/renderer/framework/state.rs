@@ -1,6 +1,9 @@
 use crate::{
     core::{color::Color, math::Rect, visitor::prelude::*},
-    renderer::framework::framebuffer::{CullFace, DrawParameters},
+    renderer::framework::{
+        framebuffer::{CullFace, DrawParameters},
+        gpu_texture::PixelKind,
+    },
     utils::log::{Log, MessageKind},
 };
 use glow::{Framebuffer, HasContext};
@@ -130,6 +133,7 @@ pub struct PipelineState {
     stencil_test: bool,
     cull_face: CullFace,
     culling: bool,
+    alpha_to_coverage: bool,
     stencil_mask: u32,
     clear_color: Color,
     clear_stencil: i32,
@@ -151,6 +155,10 @@ pub struct PipelineState {
     vbo: Option<glow::Buffer>,
 
     frame_statistics: PipelineStatistics,
+
+    supports_vertex_texture_fetch: bool,
+    supports_s3tc: bool,
+    supports_rgtc: bool,
 }
 
 #[derive(Copy, Clone)]
@@ -276,9 +284,28 @@ impl Default for StencilOp {
 
 impl PipelineState {
     pub fn new(context: glow::Context) -> Self {
-        unsafe {
+        let supports_vertex_texture_fetch = unsafe {
             context.depth_func(CompareFunc::default() as u32);
-        }
+
+            context.get_parameter_i32(glow::MAX_VERTEX_TEXTURE_IMAGE_UNITS) > 0
+        };
+
+        let (supports_s3tc, supports_rgtc) = {
+            let extensions = context.supported_extensions();
+            let has_any = |names: &[&str]| names.iter().any(|name| extensions.contains(*name));
+            (
+                has_any(&[
+                    "GL_EXT_texture_compression_s3tc",
+                    "WEBGL_compressed_texture_s3tc",
+                ]),
+                // RGTC is a core feature of desktop OpenGL since 3.0.
+                !context.version().is_embedded
+                    || has_any(&[
+                        "GL_EXT_texture_compression_rgtc",
+                        "EXT_texture_compression_rgtc",
+                    ]),
+            )
+        };
 
         Self {
             gl: context,
@@ -290,6 +317,7 @@ impl PipelineState {
             stencil_test: false,
             cull_face: CullFace::Back,
             culling: false,
+            alpha_to_coverage: false,
             stencil_mask: 0xFFFF_FFFF,
             clear_color: Color::from_rgba(0, 0, 0, 0),
             clear_stencil: 0,
@@ -305,6 +333,29 @@ impl PipelineState {
             vao: Default::default(),
             vbo: Default::default(),
             frame_statistics: Default::default(),
+            supports_vertex_texture_fetch,
+            supports_s3tc,
+            supports_rgtc,
+        }
+    }
+
+    /// Returns `true` if textures can be sampled in vertex shaders. Some old or embedded GPUs
+    /// do not have any texture units available to vertex shaders.
+    pub fn supports_vertex_texture_fetch(&self) -> bool {
+        self.supports_vertex_texture_fetch
+    }
+
+    /// Returns `true` if textures with the given pixel kind can be created. Uncompressed pixel kinds
+    /// are always supported, while S3TC (DXT) and RGTC compressed formats are usually not available
+    /// on mobile GPUs and in browsers.
+    pub fn supports_pixel_kind(&self, pixel_kind: PixelKind) -> bool {
+        match pixel_kind {
+            PixelKind::DXT1RGB
+            | PixelKind::DXT1RGBA
+            | PixelKind::DXT3RGBA
+            | PixelKind::DXT5RGBA => self.supports_s3tc,
+            PixelKind::R8RGTC | PixelKind::RG8RGTC => self.supports_rgtc,
+            _ => true,
         }
     }
 
@@ -427,6 +478,20 @@ impl PipelineState {
         }
     }
 
+    pub fn set_alpha_to_coverage(&mut self, alpha_to_coverage: bool) {
+        if self.alpha_to_coverage != alpha_to_coverage {
+            self.alpha_to_coverage = alpha_to_coverage;
+
+            unsafe {
+                if self.alpha_to_coverage {
+                    self.gl.enable(glow::SAMPLE_ALPHA_TO_COVERAGE);
+                } else {
+                    self.gl.disable(glow::SAMPLE_ALPHA_TO_COVERAGE);
+                }
+            }
+        }
+    }
+
     pub fn set_stencil_mask(&mut self, stencil_mask: u32) {
         if self.stencil_mask != stencil_mask {
             self.stencil_mask = stencil_mask;
@@ -654,6 +719,7 @@ impl PipelineState {
         }
         self.set_depth_test(draw_params.depth_test);
         self.set_depth_write(draw_params.depth_write);
+        self.set_depth_func(draw_params.depth_func);
         self.set_color_write(draw_params.color_write);
 
         if let Some(stencil_func) = draw_params.stencil_test {
@@ -671,6 +737,8 @@ impl PipelineState {
         } else {
             self.set_culling(false);
         }
+
+        self.set_alpha_to_coverage(draw_params.alpha_to_coverage);
     }
 
     pub fn pipeline_statistics(&self) -> PipelineStatistics {
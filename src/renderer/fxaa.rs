@@ -96,6 +96,8 @@ impl FxaaRenderer {
                 depth_test: false,
                 blend: None,
                 stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
             },
             |mut program_binding| {
                 program_binding
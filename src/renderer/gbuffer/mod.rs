@@ -5,6 +5,7 @@
 //! RT2: RGBA16F - Ambient light + emission (both in xyz)
 //! RT3: RGBA8 - Metallic (x) + Roughness (y) + Ambient Occlusion (z)
 //! RT4: R8UI - Decal mask (x)
+//! RT5: RG16F - Motion vectors (xy), used by temporal anti-aliasing
 //!
 //! Every alpha channel is used for layer blending for terrains. This is inefficient, but for
 //! now I don't know better solution.
@@ -14,11 +15,12 @@ use crate::renderer::framework::geometry_buffer::{GeometryBuffer, GeometryBuffer
 use crate::scene::decal::Decal;
 use crate::{
     core::{
-        algebra::{Matrix4, Vector2},
+        algebra::{Matrix4, Vector2, Vector3},
         color::Color,
         math::Rect,
         scope_profile,
     },
+    material::PropertyValue,
     renderer::{
         apply_material,
         batch::BatchStorage,
@@ -31,17 +33,85 @@ use crate::{
                 Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
                 PixelKind, WrapMode,
             },
-            state::{BlendFactor, BlendFunc, PipelineState},
+            query::GpuTimer,
+            state::{BlendFactor, BlendFunc, CompareFunc, PipelineState},
         },
         gbuffer::decal::DecalShader,
         GeometryCache, MaterialContext, RenderPassStatistics, TextureCache,
     },
     scene::{camera::Camera, graph::Graph, mesh::surface::SurfaceData, mesh::RenderPath},
 };
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    fmt::{Display, Formatter},
+    ops::AddAssign,
+    rc::Rc,
+};
 
 mod decal;
 
+/// Statistics of the G-Buffer pass.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct GBufferStatistics {
+    /// Draw calls and triangles of the pass, including depth pre-pass and decals.
+    pub pass: RenderPassStatistics,
+    /// Whether depth pre-pass was rendered or not.
+    pub depth_prepass: bool,
+    /// Amount of draw calls of depth pre-pass, they're also included in `pass`.
+    pub depth_prepass_draw_calls: usize,
+    /// Amount of draw calls of the G-Buffer pass that were rendered with `Equal` depth test
+    /// after depth pre-pass, their fragments are shaded at most once per pixel.
+    pub early_z_draw_calls: usize,
+    /// Time (in seconds) that GPU spent on depth pre-pass. It is `None` if the pre-pass is
+    /// disabled, GPU timers are not supported or there is no result yet.
+    pub depth_prepass_gpu_time: Option<f32>,
+    /// Time (in seconds) that GPU spent on the G-Buffer pass (without depth pre-pass and
+    /// decals). It is `None` if GPU timers are not supported or there is no result yet.
+    pub gpu_time: Option<f32>,
+}
+
+fn add_gpu_time(a: Option<f32>, b: Option<f32>) -> Option<f32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, b) => a.or(b),
+    }
+}
+
+impl AddAssign for GBufferStatistics {
+    fn add_assign(&mut self, rhs: Self) {
+        self.pass += rhs.pass;
+        self.depth_prepass |= rhs.depth_prepass;
+        self.depth_prepass_draw_calls += rhs.depth_prepass_draw_calls;
+        self.early_z_draw_calls += rhs.early_z_draw_calls;
+        self.depth_prepass_gpu_time =
+            add_gpu_time(self.depth_prepass_gpu_time, rhs.depth_prepass_gpu_time);
+        self.gpu_time = add_gpu_time(self.gpu_time, rhs.gpu_time);
+    }
+}
+
+impl Display for GBufferStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let write_time = |f: &mut Formatter<'_>, time: Option<f32>| match time {
+            Some(time) => write!(f, "{:.3} ms", time * 1000.0),
+            None => write!(f, "N/A"),
+        };
+
+        write!(
+            f,
+            "G-Buffer Statistics:\n\
+            \tDepth Pre-pass: {}\n\
+            \tDepth Pre-pass Draw Calls: {}\n\
+            \tEarly-Z Draw Calls: {}\n\
+            \tDepth Pre-pass GPU Time: ",
+            self.depth_prepass, self.depth_prepass_draw_calls, self.early_z_draw_calls
+        )?;
+        write_time(f, self.depth_prepass_gpu_time)?;
+        write!(f, "\n\tG-Buffer GPU Time: ")?;
+        write_time(f, self.gpu_time)?;
+        writeln!(f)
+    }
+}
+
 pub struct GBuffer {
     framebuffer: FrameBuffer,
     decal_framebuffer: FrameBuffer,
@@ -50,9 +120,13 @@ pub struct GBuffer {
     cube: GeometryBuffer,
     decal_shader: DecalShader,
     render_pass_name: ImmutableString,
+    depth_prepass_name: ImmutableString,
+    alpha_to_coverage_property: ImmutableString,
+    depth_prepass_timer: GpuTimer,
+    timer: GpuTimer,
 }
 
-pub(in crate) struct GBufferRenderContext<'a, 'b> {
+pub(crate) struct GBufferRenderContext<'a, 'b> {
     pub state: &'a mut PipelineState,
     pub camera: &'b Camera,
     pub geom_cache: &'a mut GeometryCache,
@@ -65,7 +139,16 @@ pub(in crate) struct GBufferRenderContext<'a, 'b> {
     pub normal_dummy: Rc<RefCell<GpuTexture>>,
     pub black_dummy: Rc<RefCell<GpuTexture>>,
     pub use_parallax_mapping: bool,
+    /// Whether to render depth of the objects before filling the G-Buffer, see
+    /// [`crate::renderer::QualitySettings::use_depth_prepass`] docs for more info.
+    pub use_depth_prepass: bool,
     pub graph: &'b Graph,
+    /// Sub-pixel offset (in NDC) of the projection matrix, it is non-zero only if temporal
+    /// anti-aliasing is enabled.
+    pub jitter: Vector2<f32>,
+    /// Unjittered view-projection matrix of the previous frame, it is used to calculate
+    /// motion vectors.
+    pub prev_view_projection: Matrix4<f32>,
 }
 
 impl GBuffer {
@@ -164,6 +247,20 @@ impl GBuffer {
             .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
             .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
 
+        let mut motion_vectors_texture = GpuTexture::new(
+            state,
+            GpuTextureKind::Rectangle { width, height },
+            PixelKind::RG16F,
+            MinificationFilter::Nearest,
+            MagnificationFilter::Nearest,
+            1,
+            None,
+        )?;
+        motion_vectors_texture
+            .bind_mut(state, 0)
+            .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+
         let framebuffer = FrameBuffer::new(
             state,
             Some(Attachment {
@@ -191,6 +288,10 @@ impl GBuffer {
                     kind: AttachmentKind::Color,
                     texture: Rc::new(RefCell::new(decal_mask_texture)),
                 },
+                Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: Rc::new(RefCell::new(motion_vectors_texture)),
+                },
             ],
         )?;
 
@@ -221,6 +322,10 @@ impl GBuffer {
             ),
             decal_framebuffer,
             render_pass_name: ImmutableString::new("GBuffer"),
+            depth_prepass_name: ImmutableString::new("DepthPrePass"),
+            alpha_to_coverage_property: ImmutableString::new("alphaToCoverage"),
+            depth_prepass_timer: GpuTimer::new(state),
+            timer: GpuTimer::new(state),
         })
     }
 
@@ -252,11 +357,15 @@ impl GBuffer {
         self.framebuffer.color_attachments()[4].texture.clone()
     }
 
+    pub fn motion_vectors_texture(&self) -> Rc<RefCell<GpuTexture>> {
+        self.framebuffer.color_attachments()[5].texture.clone()
+    }
+
     #[must_use]
-    pub(in crate) fn fill(&mut self, args: GBufferRenderContext) -> RenderPassStatistics {
+    pub(crate) fn fill(&mut self, args: GBufferRenderContext) -> GBufferStatistics {
         scope_profile!();
 
-        let mut statistics = RenderPassStatistics::default();
+        let mut statistics = GBufferStatistics::default();
 
         let GBufferRenderContext {
             state,
@@ -266,10 +375,13 @@ impl GBuffer {
             texture_cache,
             shader_cache,
             use_parallax_mapping,
+            use_depth_prepass,
             white_dummy,
             normal_dummy,
             black_dummy,
             graph,
+            jitter,
+            prev_view_projection,
             ..
         } = args;
 
@@ -282,60 +394,146 @@ impl GBuffer {
             Some(0),
         );
 
-        let initial_view_projection = camera.view_projection_matrix();
+        // Jitter is applied on top of projection, so the whole image is shifted by a sub-pixel
+        // offset.
+        let jitter_matrix = Matrix4::new_translation(&Vector3::new(jitter.x, jitter.y, 0.0));
+        let initial_view_projection = jitter_matrix * camera.view_projection_matrix();
 
-        for batch in batch_storage
-            .batches
-            .iter()
-            .filter(|b| b.render_path == RenderPath::Deferred)
-        {
-            let material = batch.material.lock();
-            let geometry = geom_cache.get(state, &batch.data);
+        // Depth pre-pass fills depth buffer first, so the G-Buffer pass shades only visible
+        // fragments of the objects that were rendered in the pre-pass.
+        for is_depth_prepass in [true, false] {
+            if is_depth_prepass && !use_depth_prepass {
+                continue;
+            }
 
-            if let Some(render_pass) = shader_cache
-                .get(state, material.shader())
-                .and_then(|shader_set| shader_set.render_passes.get(&self.render_pass_name))
+            // Timers cannot be nested, so each pass has its own timer.
+            if is_depth_prepass {
+                self.depth_prepass_timer.begin(state);
+            } else {
+                self.timer.begin(state);
+            }
+
+            for batch in batch_storage
+                .batches
+                .iter()
+                .filter(|b| b.render_path == RenderPath::Deferred)
             {
-                for instance in batch.instances.iter() {
-                    if camera.visibility_cache.is_visible(instance.owner) {
-                        let apply_uniforms = |mut program_binding: GpuProgramBinding| {
-                            let view_projection = if instance.depth_offset != 0.0 {
-                                let mut projection = camera.projection_matrix();
-                                projection[14] -= instance.depth_offset;
-                                projection * camera.view_matrix()
-                            } else {
-                                initial_view_projection
+                let material = batch.material.lock();
+                let geometry = geom_cache.get(state, &batch.data);
+
+                let shader_set = match shader_cache.get(state, material.shader()) {
+                    Some(shader_set) => shader_set,
+                    None => continue,
+                };
+
+                let pass_name = if is_depth_prepass {
+                    &self.depth_prepass_name
+                } else {
+                    &self.render_pass_name
+                };
+
+                if let Some(render_pass) = shader_set.render_passes.get(pass_name) {
+                    // Depth of the objects that have the pre-pass is already in the depth buffer,
+                    // objects without it (terrains, custom shaders) are rendered as usual.
+                    let early_z = use_depth_prepass
+                        && !is_depth_prepass
+                        && shader_set
+                            .render_passes
+                            .contains_key(&self.depth_prepass_name);
+
+                    let draw_params = if is_depth_prepass {
+                        render_pass.draw_params.clone()
+                    } else {
+                        // Materials could request alpha-to-coverage, it has effect only if the
+                        // G-Buffer is multisampled.
+                        let alpha_to_coverage = render_pass.draw_params.alpha_to_coverage
+                            || matches!(
+                                material.property_ref(&self.alpha_to_coverage_property),
+                                Some(PropertyValue::Bool(true))
+                            );
+
+                        if early_z {
+                            DrawParameters {
+                                alpha_to_coverage,
+                                depth_write: false,
+                                depth_func: CompareFunc::Equal,
+                                ..render_pass.draw_params.clone()
+                            }
+                        } else {
+                            DrawParameters {
+                                alpha_to_coverage,
+                                ..render_pass.draw_params.clone()
+                            }
+                        }
+                    };
+
+                    for instance in batch.instances.iter() {
+                        if camera.visibility_cache.is_visible(instance.owner) {
+                            let apply_uniforms = |mut program_binding: GpuProgramBinding| {
+                                let view_projection = if instance.depth_offset != 0.0 {
+                                    let mut projection = camera.projection_matrix();
+                                    projection[14] -= instance.depth_offset;
+                                    jitter_matrix * projection * camera.view_matrix()
+                                } else {
+                                    initial_view_projection
+                                };
+
+                                apply_material(MaterialContext {
+                                    material: &*material,
+                                    program_binding: &mut program_binding,
+                                    texture_cache,
+                                    world_matrix: &instance.world_transform,
+                                    wvp_matrix: &(view_projection * instance.world_transform),
+                                    bone_matrices: batch_storage.instance_bone_matrices(instance),
+                                    bone_matrices_offset: instance.bone_matrices.start,
+                                    bone_matrices_storage: batch_storage.bone_matrices_texture(),
+                                    use_skeletal_animation: batch.is_skinned,
+                                    camera_position: &camera.global_position(),
+                                    use_pom: use_parallax_mapping,
+                                    light_position: &Default::default(),
+                                    prev_wvp_matrix: &(prev_view_projection
+                                        * instance.prev_world_transform),
+                                    prev_bone_matrices_storage: batch_storage
+                                        .prev_bone_matrices_texture(),
+                                    jitter: &jitter,
+                                    wind: &batch_storage.wind,
+                                    normal_dummy: normal_dummy.clone(),
+                                    white_dummy: white_dummy.clone(),
+                                    black_dummy: black_dummy.clone(),
+                                });
                             };
 
-                            apply_material(MaterialContext {
-                                material: &*material,
-                                program_binding: &mut program_binding,
-                                texture_cache,
-                                world_matrix: &instance.world_transform,
-                                wvp_matrix: &(view_projection * instance.world_transform),
-                                bone_matrices: &instance.bone_matrices,
-                                use_skeletal_animation: batch.is_skinned,
-                                camera_position: &camera.global_position(),
-                                use_pom: use_parallax_mapping,
-                                light_position: &Default::default(),
-                                normal_dummy: normal_dummy.clone(),
-                                white_dummy: white_dummy.clone(),
-                                black_dummy: black_dummy.clone(),
-                            });
-                        };
-
-                        statistics += self.framebuffer.draw(
-                            geometry,
-                            state,
-                            viewport,
-                            &render_pass.program,
-                            &render_pass.draw_params,
-                            apply_uniforms,
-                        );
+                            statistics.pass += self.framebuffer.draw(
+                                geometry,
+                                state,
+                                viewport,
+                                &render_pass.program,
+                                &draw_params,
+                                apply_uniforms,
+                            );
+
+                            if is_depth_prepass {
+                                statistics.depth_prepass_draw_calls += 1;
+                            } else if early_z {
+                                statistics.early_z_draw_calls += 1;
+                            }
+                        }
                     }
                 }
             }
+
+            if is_depth_prepass {
+                self.depth_prepass_timer.end(state);
+            } else {
+                self.timer.end(state);
+            }
+        }
+
+        statistics.depth_prepass = use_depth_prepass;
+        if use_depth_prepass {
+            statistics.depth_prepass_gpu_time = self.depth_prepass_timer.time();
         }
+        statistics.gpu_time = self.timer.time();
 
         let inv_view_proj = initial_view_projection.try_inverse().unwrap_or_default();
         let depth = self.depth();
@@ -346,7 +544,11 @@ impl GBuffer {
         // for rendering. We'll render in the G-Buffer, but depth will be used from final frame, since
         // decals do not modify depth (only diffuse and normal maps).
         let unit_cube = &self.cube;
-        for decal in graph.linear_iter().filter_map(|n| n.cast::<Decal>()) {
+        for decal in graph
+            .linear_iter()
+            .filter_map(|n| n.cast::<Decal>())
+            .filter(|d| camera.can_see_layers(d.render_mask()))
+        {
             let shader = &self.decal_shader;
             let program = &self.decal_shader.program;
 
@@ -362,7 +564,7 @@ impl GBuffer {
 
             let world_view_proj = initial_view_projection * decal.global_transform();
 
-            statistics += self.decal_framebuffer.draw(
+            statistics.pass += self.decal_framebuffer.draw(
                 unit_cube,
                 state,
                 viewport,
@@ -378,6 +580,8 @@ impl GBuffer {
                         dfactor: BlendFactor::OneMinusSrcAlpha,
                     }),
                     stencil_op: Default::default(),
+                    alpha_to_coverage: false,
+                    depth_func: Default::default(),
                 },
                 |mut program_binding| {
                     program_binding
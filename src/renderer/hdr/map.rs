@@ -18,6 +18,8 @@ pub struct MapShader {
     pub max_luminance: UniformLocation,
     pub auto_exposure: UniformLocation,
     pub fixed_exposure: UniformLocation,
+    pub hdr_output: UniformLocation,
+    pub peak_white: UniformLocation,
 }
 
 impl MapShader {
@@ -48,6 +50,8 @@ impl MapShader {
                 .uniform_location(state, &ImmutableString::new("autoExposure"))?,
             fixed_exposure: program
                 .uniform_location(state, &ImmutableString::new("fixedExposure"))?,
+            hdr_output: program.uniform_location(state, &ImmutableString::new("hdrOutput"))?,
+            peak_white: program.uniform_location(state, &ImmutableString::new("peakWhite"))?,
             program,
         })
     }
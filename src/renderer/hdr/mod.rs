@@ -161,6 +161,8 @@ impl HighDynamicRangeRenderer {
                 depth_test: false,
                 blend: None,
                 stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
             },
             |mut program_binding| {
                 program_binding
@@ -195,6 +197,8 @@ impl HighDynamicRangeRenderer {
                     depth_test: false,
                     blend: None,
                     stencil_op: Default::default(),
+                    alpha_to_coverage: false,
+                    depth_func: Default::default(),
                 },
                 |mut program_binding| {
                     program_binding
@@ -234,6 +238,8 @@ impl HighDynamicRangeRenderer {
                 depth_test: false,
                 blend: None,
                 stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
             },
             |mut program_binding| {
                 program_binding
@@ -257,6 +263,7 @@ impl HighDynamicRangeRenderer {
         exposure: Exposure,
         color_grading_lut: Option<&ColorGradingLut>,
         use_color_grading: bool,
+        peak_white: Option<f32>,
         texture_cache: &mut TextureCache,
     ) -> DrawCallStatistics {
         let shader = &self.map_shader;
@@ -280,6 +287,8 @@ impl HighDynamicRangeRenderer {
                 depth_test: false,
                 blend: None,
                 stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
             },
             |mut program_binding| {
                 let program_binding = program_binding
@@ -291,7 +300,9 @@ impl HighDynamicRangeRenderer {
                         &shader.use_color_grading,
                         use_color_grading && color_grading_lut.is_some(),
                     )
-                    .set_texture(&shader.color_map_sampler, &color_grading_lut_tex);
+                    .set_texture(&shader.color_map_sampler, &color_grading_lut_tex)
+                    .set_bool(&shader.hdr_output, peak_white.is_some())
+                    .set_f32(&shader.peak_white, peak_white.unwrap_or(1.0));
 
                 match exposure {
                     Exposure::Auto {
@@ -315,6 +326,8 @@ impl HighDynamicRangeRenderer {
         )
     }
 
+    /// Tone maps the HDR frame into the LDR frame buffer. If `peak_white` is set, the frame is
+    /// mapped into `[0; peak_white]` range instead of `[0; 1]`, it is used for HDR output.
     pub fn render(
         &mut self,
         state: &mut PipelineState,
@@ -327,6 +340,7 @@ impl HighDynamicRangeRenderer {
         exposure: Exposure,
         color_grading_lut: Option<&ColorGradingLut>,
         use_color_grading: bool,
+        peak_white: Option<f32>,
         texture_cache: &mut TextureCache,
     ) -> RenderPassStatistics {
         let mut stats = RenderPassStatistics::default();
@@ -343,6 +357,7 @@ impl HighDynamicRangeRenderer {
             exposure,
             color_grading_lut,
             use_color_grading,
+            peak_white,
             texture_cache,
         );
         stats
@@ -5,13 +5,29 @@ use crate::renderer::framework::{
     state::PipelineState,
 };
 
+/// Max amount of reflection probes that could affect a pixel, must be in sync with the shader.
+pub const MAX_REFLECTION_PROBES: usize = 4;
+
 pub struct AmbientLightShader {
     pub program: GpuProgram,
     pub wvp_matrix: UniformLocation,
     pub diffuse_texture: UniformLocation,
     pub ambient_color: UniformLocation,
     pub ao_sampler: UniformLocation,
+    pub use_bent_normals: UniformLocation,
     pub ambient_texture: UniformLocation,
+    pub depth_texture: UniformLocation,
+    pub normal_texture: UniformLocation,
+    pub material_texture: UniformLocation,
+    pub environment_texture: UniformLocation,
+    pub probe_textures: [UniformLocation; MAX_REFLECTION_PROBES],
+    pub inv_view_proj_matrix: UniformLocation,
+    pub camera_position: UniformLocation,
+    pub probe_count: UniformLocation,
+    pub probe_box_min: UniformLocation,
+    pub probe_box_max: UniformLocation,
+    pub probe_position: UniformLocation,
+    pub probe_blend_distance: UniformLocation,
 }
 
 impl AmbientLightShader {
@@ -28,8 +44,35 @@ impl AmbientLightShader {
             ambient_color: program
                 .uniform_location(state, &ImmutableString::new("ambientColor"))?,
             ao_sampler: program.uniform_location(state, &ImmutableString::new("aoSampler"))?,
+            use_bent_normals: program
+                .uniform_location(state, &ImmutableString::new("useBentNormals"))?,
             ambient_texture: program
                 .uniform_location(state, &ImmutableString::new("ambientTexture"))?,
+            depth_texture: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            normal_texture: program
+                .uniform_location(state, &ImmutableString::new("normalTexture"))?,
+            material_texture: program
+                .uniform_location(state, &ImmutableString::new("materialTexture"))?,
+            environment_texture: program
+                .uniform_location(state, &ImmutableString::new("environmentTexture"))?,
+            probe_textures: [
+                program.uniform_location(state, &ImmutableString::new("probeTexture0"))?,
+                program.uniform_location(state, &ImmutableString::new("probeTexture1"))?,
+                program.uniform_location(state, &ImmutableString::new("probeTexture2"))?,
+                program.uniform_location(state, &ImmutableString::new("probeTexture3"))?,
+            ],
+            inv_view_proj_matrix: program
+                .uniform_location(state, &ImmutableString::new("invViewProj"))?,
+            camera_position: program
+                .uniform_location(state, &ImmutableString::new("cameraPosition"))?,
+            probe_count: program.uniform_location(state, &ImmutableString::new("probeCount"))?,
+            probe_box_min: program.uniform_location(state, &ImmutableString::new("probeBoxMin"))?,
+            probe_box_max: program.uniform_location(state, &ImmutableString::new("probeBoxMax"))?,
+            probe_position: program
+                .uniform_location(state, &ImmutableString::new("probePosition"))?,
+            probe_blend_distance: program
+                .uniform_location(state, &ImmutableString::new("probeBlendDistance"))?,
             program,
         })
     }
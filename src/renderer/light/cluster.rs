@@ -0,0 +1,623 @@
+//! Light clustering, see [`LightClusterSettings`] docs for more info.
+//!
+//! Lights are binned into clusters on CPU side, then the lights and the lists of lights of every
+//! cluster are uploaded into a single float texture, which is read by the clustered lighting
+//! shader.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector3, Vector4},
+        math::aabb::AxisAlignedBoundingBox,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            gpu_program::{GpuProgram, UniformLocation},
+            state::PipelineState,
+        },
+        LightClusterSettings,
+    },
+};
+use std::{
+    fmt::{Display, Formatter},
+    ops::AddAssign,
+};
+
+/// Depth slices are distributed exponentially, so they cannot start at zero distance. Clusters
+/// start at this distance if the near clipping plane of a camera is closer.
+const MIN_Z_NEAR: f32 = 0.01;
+
+/// Amount of texels per light in the storage texture.
+const TEXELS_PER_LIGHT: usize = 3;
+
+/// Statistics of light clustering.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ClusterStatistics {
+    /// Total amount of clusters.
+    pub clusters: usize,
+    /// Total amount of lights in every cluster, a light is counted once per each cluster it
+    /// touches.
+    pub light_references: usize,
+    /// Maximum amount of lights in a single cluster.
+    pub max_lights_per_cluster: usize,
+}
+
+impl ClusterStatistics {
+    /// Returns average amount of lights per cluster.
+    pub fn average_lights_per_cluster(&self) -> f32 {
+        if self.clusters == 0 {
+            0.0
+        } else {
+            self.light_references as f32 / self.clusters as f32
+        }
+    }
+}
+
+impl AddAssign for ClusterStatistics {
+    fn add_assign(&mut self, rhs: Self) {
+        self.clusters += rhs.clusters;
+        self.light_references += rhs.light_references;
+        self.max_lights_per_cluster = self.max_lights_per_cluster.max(rhs.max_lights_per_cluster);
+    }
+}
+
+impl Display for ClusterStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} Clusters, Lights Per Cluster: {:.2} Avg, {} Max",
+            self.clusters,
+            self.average_lights_per_cluster(),
+            self.max_lights_per_cluster
+        )
+    }
+}
+
+/// A light that is shaded in the clustered lighting pass. Point lights are represented as spot
+/// lights with a cone that covers the whole sphere.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ClusteredLight {
+    /// Position of the light in world space.
+    pub position: Vector3<f32>,
+    pub radius: f32,
+    /// Linear color of the light premultiplied by its intensity.
+    pub color: Vector3<f32>,
+    /// Normalized emission direction in world space.
+    pub direction: Vector3<f32>,
+    pub half_cone_angle_cos: f32,
+    pub half_hotspot_cone_angle_cos: f32,
+}
+
+impl ClusteredLight {
+    /// Creates new omnidirectional light.
+    pub fn point(position: Vector3<f32>, radius: f32, color: Vector3<f32>) -> Self {
+        Self {
+            position,
+            radius,
+            color,
+            direction: Vector3::y(),
+            // Cone factor is `smoothstep(half_cone_angle_cos, half_hotspot_cone_angle_cos, cos)`,
+            // it is always 1.0 when both values are less than -1.0.
+            half_cone_angle_cos: -2.0,
+            half_hotspot_cone_angle_cos: -1.5,
+        }
+    }
+
+    /// Creates new spot light with perspective projection.
+    pub fn spot(
+        position: Vector3<f32>,
+        radius: f32,
+        color: Vector3<f32>,
+        direction: Vector3<f32>,
+        half_cone_angle_cos: f32,
+        half_hotspot_cone_angle_cos: f32,
+    ) -> Self {
+        Self {
+            position,
+            radius,
+            color,
+            direction,
+            half_cone_angle_cos,
+            half_hotspot_cone_angle_cos,
+        }
+    }
+}
+
+/// Grid of clusters, that splits the view frustum of a camera into screen-space tiles and depth
+/// slices. Every cluster holds a list of lights, whose spheres of influence intersect it.
+pub struct LightClusters {
+    settings: LightClusterSettings,
+    projection_matrix: Matrix4<f32>,
+    z_near: f32,
+    z_far: f32,
+    // View-space bounds of every cluster, they depend on the projection only.
+    bounds: Vec<AxisAlignedBoundingBox>,
+    cluster_lights: Vec<Vec<u32>>,
+    statistics: ClusterStatistics,
+}
+
+impl LightClusters {
+    pub fn new(settings: &LightClusterSettings) -> Self {
+        Self {
+            settings: settings.clamped(),
+            projection_matrix: Matrix4::zeros(),
+            z_near: 0.0,
+            z_far: 0.0,
+            bounds: Default::default(),
+            cluster_lights: Default::default(),
+            statistics: Default::default(),
+        }
+    }
+
+    /// Returns current settings of the grid.
+    pub fn settings(&self) -> &LightClusterSettings {
+        &self.settings
+    }
+
+    /// Changes dimensions of the grid, bounds of clusters will be re-calculated on next build.
+    pub fn set_settings(&mut self, settings: &LightClusterSettings) {
+        let settings = settings.clamped();
+        if settings != self.settings {
+            self.settings = settings;
+            self.bounds.clear();
+        }
+    }
+
+    /// Returns total amount of clusters.
+    pub fn cluster_count(&self) -> usize {
+        self.settings.tiles_x * self.settings.tiles_y * self.settings.depth_slices
+    }
+
+    /// Returns a scale, that converts logarithm of relative depth into index of depth slice.
+    pub fn depth_slice_scale(&self) -> f32 {
+        self.settings.depth_slices as f32 / (self.z_far / self.z_near).ln()
+    }
+
+    /// Returns distance to the near clipping plane, that was used to build the clusters.
+    pub fn z_near(&self) -> f32 {
+        self.z_near
+    }
+
+    /// Returns statistics of the last build.
+    pub fn statistics(&self) -> ClusterStatistics {
+        self.statistics
+    }
+
+    fn cluster_index(&self, x: usize, y: usize, z: usize) -> usize {
+        x + self.settings.tiles_x * (y + self.settings.tiles_y * z)
+    }
+
+    fn depth_of_slice(&self, slice: usize) -> f32 {
+        self.z_near
+            * (self.z_far / self.z_near).powf(slice as f32 / self.settings.depth_slices as f32)
+    }
+
+    /// Returns index of depth slice for the given (positive) view-space depth.
+    fn slice_of_depth(&self, depth: f32) -> usize {
+        let slice =
+            ((depth.max(self.z_near) / self.z_near).ln() * self.depth_slice_scale()).floor();
+        (slice.max(0.0) as usize).min(self.settings.depth_slices - 1)
+    }
+
+    /// Returns index of a tile for the given coordinate in normalized device coordinates.
+    fn tile_of_ndc(ndc: f32, tiles: usize) -> usize {
+        let tile = ((ndc * 0.5 + 0.5) * tiles as f32).floor();
+        (tile.max(0.0) as usize).min(tiles - 1)
+    }
+
+    fn update_bounds(&mut self, projection_matrix: &Matrix4<f32>, z_near: f32, z_far: f32) {
+        let z_near = z_near.max(MIN_Z_NEAR);
+        let z_far = z_far.max(z_near * 2.0);
+
+        if !self.bounds.is_empty()
+            && self.projection_matrix == *projection_matrix
+            && self.z_near == z_near
+            && self.z_far == z_far
+        {
+            return;
+        }
+
+        self.projection_matrix = *projection_matrix;
+        self.z_near = z_near;
+        self.z_far = z_far;
+
+        let inv_projection = projection_matrix.try_inverse().unwrap_or_default();
+        let unproject = |x: f32, y: f32, z: f32| {
+            let p = inv_projection * Vector4::new(x, y, z, 1.0);
+            p.xyz().scale(1.0 / p.w)
+        };
+
+        let LightClusterSettings {
+            tiles_x,
+            tiles_y,
+            depth_slices,
+            ..
+        } = self.settings;
+
+        self.bounds.clear();
+        self.bounds
+            .resize(self.cluster_count(), AxisAlignedBoundingBox::default());
+
+        for y in 0..tiles_y {
+            for x in 0..tiles_x {
+                let x0 = -1.0 + 2.0 * x as f32 / tiles_x as f32;
+                let x1 = -1.0 + 2.0 * (x + 1) as f32 / tiles_x as f32;
+                let y0 = -1.0 + 2.0 * y as f32 / tiles_y as f32;
+                let y1 = -1.0 + 2.0 * (y + 1) as f32 / tiles_y as f32;
+
+                // Edges of the tile as lines from the near to the far plane in view space.
+                let edges = [(x0, y0), (x1, y0), (x0, y1), (x1, y1)]
+                    .map(|(x, y)| (unproject(x, y, -1.0), unproject(x, y, 1.0)));
+
+                for z in 0..depth_slices {
+                    let mut aabb = AxisAlignedBoundingBox::default();
+                    for depth in [self.depth_of_slice(z), self.depth_of_slice(z + 1)] {
+                        for (begin, end) in edges.iter() {
+                            let t = (-depth - begin.z) / (end.z - begin.z);
+                            aabb.add_point(begin.lerp(end, t));
+                        }
+                    }
+                    let index = self.cluster_index(x, y, z);
+                    self.bounds[index] = aabb;
+                }
+            }
+        }
+    }
+
+    /// Bins the given lights into the clusters of a camera with the given matrices and clipping
+    /// planes.
+    pub fn build(
+        &mut self,
+        view_matrix: &Matrix4<f32>,
+        projection_matrix: &Matrix4<f32>,
+        z_near: f32,
+        z_far: f32,
+        lights: &[ClusteredLight],
+    ) {
+        self.update_bounds(projection_matrix, z_near, z_far);
+
+        let cluster_count = self.cluster_count();
+        self.cluster_lights
+            .resize_with(cluster_count, Default::default);
+        for list in self.cluster_lights.iter_mut() {
+            list.clear();
+        }
+
+        let LightClusterSettings {
+            tiles_x, tiles_y, ..
+        } = self.settings;
+
+        for (light_index, light) in lights.iter().enumerate() {
+            let center = view_matrix.transform_point(&light.position.into()).coords;
+            let radius = light.radius;
+
+            // View space looks along -Z, clamp the bounds of the sphere to the clusters.
+            let z_min = (center.z - radius).max(-self.z_far);
+            let z_max = (center.z + radius).min(-self.z_near);
+            if z_min > z_max {
+                continue;
+            }
+
+            let first_slice = self.slice_of_depth(-z_max);
+            let last_slice = self.slice_of_depth(-z_min);
+
+            // Bounds of the clamped box in front of the camera are projected to the bounds of
+            // its corners.
+            let mut ndc_min = Vector3::repeat(f32::MAX);
+            let mut ndc_max = Vector3::repeat(-f32::MAX);
+            for z in [z_min, z_max] {
+                for y in [center.y - radius, center.y + radius] {
+                    for x in [center.x - radius, center.x + radius] {
+                        let clip = projection_matrix * Vector4::new(x, y, z, 1.0);
+                        let ndc = clip.xyz().scale(1.0 / clip.w);
+                        ndc_min = ndc_min.inf(&ndc);
+                        ndc_max = ndc_max.sup(&ndc);
+                    }
+                }
+            }
+
+            if ndc_max.x < -1.0 || ndc_min.x > 1.0 || ndc_max.y < -1.0 || ndc_min.y > 1.0 {
+                continue;
+            }
+
+            let first_x = Self::tile_of_ndc(ndc_min.x, tiles_x);
+            let last_x = Self::tile_of_ndc(ndc_max.x, tiles_x);
+            let first_y = Self::tile_of_ndc(ndc_min.y, tiles_y);
+            let last_y = Self::tile_of_ndc(ndc_max.y, tiles_y);
+
+            for z in first_slice..=last_slice {
+                for y in first_y..=last_y {
+                    for x in first_x..=last_x {
+                        let index = self.cluster_index(x, y, z);
+                        if self.bounds[index].is_intersects_sphere(center, radius) {
+                            self.cluster_lights[index].push(light_index as u32);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.statistics = ClusterStatistics {
+            clusters: cluster_count,
+            light_references: self.cluster_lights.iter().map(|l| l.len()).sum(),
+            max_lights_per_cluster: self
+                .cluster_lights
+                .iter()
+                .map(|l| l.len())
+                .max()
+                .unwrap_or_default(),
+        };
+    }
+
+    /// Returns indices of lights of a cluster at the given position in the grid.
+    #[cfg(test)]
+    pub fn lights_of_cluster(&self, x: usize, y: usize, z: usize) -> &[u32] {
+        &self.cluster_lights[self.cluster_index(x, y, z)]
+    }
+
+    /// Returns indices of lights of a cluster, that contains the given view-space point.
+    #[cfg(test)]
+    pub fn lights_at(&self, view_position: Vector3<f32>) -> &[u32] {
+        let clip = self.projection_matrix * view_position.push(1.0);
+        let ndc = clip.xyz().scale(1.0 / clip.w);
+        self.lights_of_cluster(
+            Self::tile_of_ndc(ndc.x, self.settings.tiles_x),
+            Self::tile_of_ndc(ndc.y, self.settings.tiles_y),
+            self.slice_of_depth(-view_position.z),
+        )
+    }
+
+    /// Packs the lights and the lists of lights of every cluster in the layout expected by the
+    /// clustered lighting shader. Returns offsets (in texels) of the clusters and the indices.
+    pub fn pack(
+        &self,
+        lights: &[ClusteredLight],
+        texels: &mut Vec<Vector4<f32>>,
+    ) -> (usize, usize) {
+        texels.clear();
+
+        for light in lights {
+            texels.push(light.position.push(light.radius));
+            texels.push(light.color.push(light.half_cone_angle_cos));
+            texels.push(light.direction.push(light.half_hotspot_cone_angle_cos));
+        }
+        debug_assert_eq!(texels.len(), lights.len() * TEXELS_PER_LIGHT);
+
+        let cluster_offset = texels.len();
+        let mut first_light = 0;
+        for list in self.cluster_lights.iter() {
+            // Floats can represent integers up to 2^24 exactly, it is more than enough.
+            texels.push(Vector4::new(
+                first_light as f32,
+                list.len() as f32,
+                0.0,
+                0.0,
+            ));
+            first_light += list.len();
+        }
+
+        let index_offset = texels.len();
+        let mut packed = [0.0; 4];
+        let mut count = 0;
+        for &light_index in self.cluster_lights.iter().flatten() {
+            packed[count % 4] = light_index as f32;
+            count += 1;
+            if count % 4 == 0 {
+                texels.push(Vector4::from(packed));
+            }
+        }
+        if count % 4 != 0 {
+            texels.push(Vector4::from(packed));
+        }
+
+        (cluster_offset, index_offset)
+    }
+}
+
+pub struct ClusteredLightShader {
+    pub program: GpuProgram,
+    pub wvp_matrix: UniformLocation,
+    pub depth_sampler: UniformLocation,
+    pub color_sampler: UniformLocation,
+    pub normal_sampler: UniformLocation,
+    pub material_sampler: UniformLocation,
+    pub light_storage: UniformLocation,
+    pub inv_view_proj_matrix: UniformLocation,
+    pub inv_proj_matrix: UniformLocation,
+    pub camera_position: UniformLocation,
+    pub cluster_offset: UniformLocation,
+    pub index_offset: UniformLocation,
+    pub tiles_x: UniformLocation,
+    pub tiles_y: UniformLocation,
+    pub depth_slices: UniformLocation,
+    pub z_near: UniformLocation,
+    pub depth_slice_scale: UniformLocation,
+}
+
+impl ClusteredLightShader {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("../shaders/deferred_clustered_light_fs.glsl");
+        let vertex_source = include_str!("../shaders/deferred_light_vs.glsl");
+        let program = GpuProgram::from_source(
+            state,
+            "ClusteredLightShader",
+            vertex_source,
+            fragment_source,
+        )?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            depth_sampler: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            color_sampler: program
+                .uniform_location(state, &ImmutableString::new("colorTexture"))?,
+            normal_sampler: program
+                .uniform_location(state, &ImmutableString::new("normalTexture"))?,
+            material_sampler: program
+                .uniform_location(state, &ImmutableString::new("materialTexture"))?,
+            light_storage: program
+                .uniform_location(state, &ImmutableString::new("lightStorage"))?,
+            inv_view_proj_matrix: program
+                .uniform_location(state, &ImmutableString::new("invViewProj"))?,
+            inv_proj_matrix: program.uniform_location(state, &ImmutableString::new("invProj"))?,
+            camera_position: program
+                .uniform_location(state, &ImmutableString::new("cameraPosition"))?,
+            cluster_offset: program
+                .uniform_location(state, &ImmutableString::new("clusterOffset"))?,
+            index_offset: program.uniform_location(state, &ImmutableString::new("indexOffset"))?,
+            tiles_x: program.uniform_location(state, &ImmutableString::new("tilesX"))?,
+            tiles_y: program.uniform_location(state, &ImmutableString::new("tilesY"))?,
+            depth_slices: program.uniform_location(state, &ImmutableString::new("depthSlices"))?,
+            z_near: program.uniform_location(state, &ImmutableString::new("zNear"))?,
+            depth_slice_scale: program
+                .uniform_location(state, &ImmutableString::new("depthSliceScale"))?,
+            program,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Point3, Vector3, Vector4},
+        renderer::{
+            light::cluster::{ClusteredLight, LightClusters},
+            LightClusterSettings,
+        },
+    };
+
+    fn make_lights() -> Vec<ClusteredLight> {
+        // Deterministic pseudo-random distribution of small lights.
+        let mut seed = 12345u32;
+        let mut next = move || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 8) as f32 / (1u32 << 24) as f32
+        };
+
+        (0..300)
+            .map(|_| {
+                ClusteredLight::point(
+                    Vector3::new(
+                        next() * 40.0 - 20.0,
+                        next() * 10.0 - 5.0,
+                        next() * 60.0 - 5.0,
+                    ),
+                    0.5 + next() * 2.0,
+                    Vector3::new(1.0, 1.0, 1.0),
+                )
+            })
+            .collect()
+    }
+
+    fn check_conservative(view: Matrix4<f32>, projection: Matrix4<f32>, z_near: f32, z_far: f32) {
+        let lights = make_lights();
+        let mut clusters = LightClusters::new(&LightClusterSettings::default());
+        clusters.build(&view, &projection, z_near, z_far, &lights);
+
+        let inv_projection = projection.try_inverse().unwrap();
+        let inv_view = view.try_inverse().unwrap();
+
+        // Every point on the screen must "see" every light that affects it.
+        let steps = 32;
+        for iy in 0..steps {
+            for ix in 0..steps {
+                for iz in 0..steps {
+                    let ndc = Vector4::new(
+                        -1.0 + 2.0 * (ix as f32 + 0.5) / steps as f32,
+                        -1.0 + 2.0 * (iy as f32 + 0.5) / steps as f32,
+                        -1.0 + 2.0 * (iz as f32 + 0.5) / steps as f32,
+                        1.0,
+                    );
+                    let view_position = inv_projection * ndc;
+                    let view_position = view_position.xyz().scale(1.0 / view_position.w);
+                    let world_position = inv_view
+                        .transform_point(&Point3::from(view_position))
+                        .coords;
+
+                    let cluster_lights = clusters.lights_at(view_position);
+                    for (index, light) in lights.iter().enumerate() {
+                        if (light.position - world_position).norm() < light.radius {
+                            assert!(
+                                cluster_lights.contains(&(index as u32)),
+                                "light {} is missing in the cluster of {:?}",
+                                index,
+                                view_position
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        // Clustering is useless if every cluster contains every light.
+        let statistics = clusters.statistics();
+        assert!(statistics.average_lights_per_cluster() < lights.len() as f32 * 0.1);
+    }
+
+    #[test]
+    fn test_clusters_are_conservative_perspective() {
+        let view = Matrix4::look_at_rh(
+            &Point3::new(0.0, 2.0, -10.0),
+            &Point3::new(0.0, 0.0, 10.0),
+            &Vector3::y(),
+        );
+        let projection = Matrix4::new_perspective(16.0 / 9.0, 1.2, 0.1, 64.0);
+        check_conservative(view, projection, 0.1, 64.0);
+    }
+
+    #[test]
+    fn test_clusters_are_conservative_orthographic() {
+        let view = Matrix4::look_at_rh(
+            &Point3::new(0.0, 0.0, -10.0),
+            &Point3::new(0.0, 0.0, 10.0),
+            &Vector3::y(),
+        );
+        let projection = Matrix4::new_orthographic(-20.0, 20.0, -10.0, 10.0, 0.0, 64.0);
+        check_conservative(view, projection, 0.0, 64.0);
+    }
+
+    #[test]
+    fn test_pack() {
+        let lights = vec![
+            ClusteredLight::point(
+                Vector3::new(0.0, 0.0, 5.0),
+                1.0,
+                Vector3::new(1.0, 0.0, 0.0),
+            ),
+            ClusteredLight::point(
+                Vector3::new(0.0, 0.0, 5.5),
+                1.0,
+                Vector3::new(0.0, 1.0, 0.0),
+            ),
+        ];
+        let view = Matrix4::look_at_rh(
+            &Point3::new(0.0, 0.0, 0.0),
+            &Point3::new(0.0, 0.0, 1.0),
+            &Vector3::y(),
+        );
+        let projection = Matrix4::new_perspective(1.0, 1.2, 0.1, 64.0);
+
+        let mut clusters = LightClusters::new(&LightClusterSettings::default());
+        clusters.build(&view, &projection, 0.1, 64.0, &lights);
+
+        let mut texels = Vec::new();
+        let (cluster_offset, index_offset) = clusters.pack(&lights, &mut texels);
+        assert_eq!(cluster_offset, 6);
+        assert_eq!(index_offset, cluster_offset + clusters.cluster_count());
+
+        let statistics = clusters.statistics();
+        assert_eq!(
+            texels.len(),
+            index_offset + (statistics.light_references + 3) / 4
+        );
+
+        // Lists of clusters are stored one after another.
+        let mut expected_first = 0.0;
+        for texel in &texels[cluster_offset..index_offset] {
+            assert_eq!(texel.x, expected_first);
+            expected_first += texel.y;
+        }
+        assert_eq!(expected_first as usize, statistics.light_references);
+    }
+}
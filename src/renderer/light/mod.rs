@@ -2,7 +2,9 @@ use crate::renderer::framework::geometry_buffer::{GeometryBuffer, GeometryBuffer
 use crate::renderer::shadow::csm::CsmRenderContext;
 use crate::scene::light::directional::DirectionalLight;
 use crate::scene::light::point::PointLight;
-use crate::scene::light::spot::SpotLight;
+use crate::scene::light::spot::{SpotLight, SpotLightProjection};
+use crate::scene::light::BaseLight;
+use crate::scene::reflection_probe::ReflectionProbe;
 use crate::{
     core::{
         algebra::{Matrix4, Point3, Vector3},
@@ -13,6 +15,7 @@ use crate::{
     renderer::{
         batch::BatchStorage,
         cache::shader::ShaderCache,
+        capabilities::{RendererCapabilities, RendererFeature},
         flat_shader::FlatShader,
         framework::{
             error::FrameworkError,
@@ -25,17 +28,22 @@ use crate::{
         },
         gbuffer::GBuffer,
         light::{
-            ambient::AmbientLightShader, directional::DirectionalLightShader,
-            point::PointLightShader, spot::SpotLightShader,
+            ambient::{AmbientLightShader, MAX_REFLECTION_PROBES},
+            cluster::{ClusterStatistics, ClusteredLight, ClusteredLightShader, LightClusters},
+            directional::DirectionalLightShader,
+            point::PointLightShader,
+            spot::{OrthoSpotLightShader, SpotLightShader},
         },
         light_volume::LightVolumeRenderer,
         shadow::{
             csm::CsmRenderer,
             point::{PointShadowMapRenderContext, PointShadowMapRenderer},
             spot::SpotShadowMapRenderer,
+            static_shadow_casters_key, ShadowCasters,
         },
         skybox_shader::SkyboxShader,
-        ssao::ScreenSpaceAmbientOcclusionRenderer,
+        ssao::{ScreenSpaceAmbientOcclusionRenderer, SsaoHistory},
+        storage::VectorStorage,
         GeometryCache, QualitySettings, RenderPassStatistics, TextureCache,
     },
     scene::{
@@ -45,10 +53,12 @@ use crate::{
             surface::SurfaceData,
             vertex::SimpleVertex,
         },
+        node::Node,
         Scene,
     },
+    utils::log::{Log, MessageKind},
 };
-use fyrox_core::algebra::Vector2;
+use fyrox_core::algebra::{Vector2, Vector4};
 use std::{
     cell::RefCell,
     fmt::{Display, Formatter},
@@ -57,10 +67,59 @@ use std::{
 };
 
 pub mod ambient;
+pub mod cluster;
 pub mod directional;
 pub mod point;
 pub mod spot;
 
+/// Statistics of screen-space ambient occlusion pass.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SsaoStatistics {
+    pub sample_count: usize,
+    /// Width of ambient occlusion map in pixels.
+    pub width: usize,
+    /// Height of ambient occlusion map in pixels.
+    pub height: usize,
+    pub blur_passes: usize,
+    pub temporal_accumulation: bool,
+    /// Time (in seconds) that GPU spent on the pass. It is `None` if GPU timers are not supported
+    /// or there is no result yet.
+    pub gpu_time: Option<f32>,
+}
+
+impl AddAssign for SsaoStatistics {
+    fn add_assign(&mut self, rhs: Self) {
+        // Every camera uses the same settings, so only the time is accumulated.
+        self.sample_count = self.sample_count.max(rhs.sample_count);
+        self.width = self.width.max(rhs.width);
+        self.height = self.height.max(rhs.height);
+        self.blur_passes = self.blur_passes.max(rhs.blur_passes);
+        self.temporal_accumulation |= rhs.temporal_accumulation;
+        self.gpu_time = match (self.gpu_time, rhs.gpu_time) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+    }
+}
+
+impl Display for SsaoStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{}, {} Samples, {} Blur Passes, Temporal: {}, GPU Time: ",
+            self.width,
+            self.height,
+            self.sample_count,
+            self.blur_passes,
+            self.temporal_accumulation
+        )?;
+        match self.gpu_time {
+            Some(time) => write!(f, "{:.3} ms", time * 1000.0),
+            None => write!(f, "N/A"),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Default)]
 pub struct LightingStatistics {
     pub point_lights_rendered: usize,
@@ -69,6 +128,24 @@ pub struct LightingStatistics {
     pub spot_lights_rendered: usize,
     pub spot_shadow_maps_rendered: usize,
     pub directional_lights_rendered: usize,
+    /// Amount of point and spot lights that were shaded in the clustered lighting pass, they're
+    /// not included in `point_lights_rendered` and `spot_lights_rendered`.
+    pub clustered_lights_rendered: usize,
+    /// Statistics of light clustering, it is default if clustering is disabled.
+    pub clusters: ClusterStatistics,
+    /// Amount of shadow casting lights that were rendered without shadows, because they were too
+    /// far from the camera.
+    pub shadows_skipped_by_distance: usize,
+    /// Amount of cached shadow maps of static geometry that were (re)rendered, it is non-zero
+    /// only when a light with static shadows appears for the first time or static geometry in
+    /// its area was changed.
+    pub static_shadow_maps_rendered: usize,
+    /// Amount of shadow maps with only dynamic shadow casters, that were rendered for lights with
+    /// static shadows. They're also included in `point_shadow_maps_rendered` and
+    /// `spot_shadow_maps_rendered`.
+    pub dynamic_shadow_maps_rendered: usize,
+    /// Statistics of screen-space ambient occlusion, it is default if SSAO is disabled.
+    pub ssao: SsaoStatistics,
 }
 
 impl AddAssign for LightingStatistics {
@@ -78,7 +155,13 @@ impl AddAssign for LightingStatistics {
         self.spot_lights_rendered += rhs.spot_lights_rendered;
         self.spot_shadow_maps_rendered += rhs.spot_shadow_maps_rendered;
         self.directional_lights_rendered += rhs.directional_lights_rendered;
+        self.clustered_lights_rendered += rhs.clustered_lights_rendered;
+        self.clusters += rhs.clusters;
         self.csm_rendered += rhs.csm_rendered;
+        self.shadows_skipped_by_distance += rhs.shadows_skipped_by_distance;
+        self.static_shadow_maps_rendered += rhs.static_shadow_maps_rendered;
+        self.dynamic_shadow_maps_rendered += rhs.dynamic_shadow_maps_rendered;
+        self.ssao += rhs.ssao;
     }
 }
 
@@ -90,25 +173,87 @@ impl Display for LightingStatistics {
             \tPoint Lights: {}\n\
             \tSpot Lights: {}\n\
             \tDirectional Lights: {}\n\
+            \tClustered Lights: {}\n\
+            \tClusters: {}\n\
             \tPoint Shadow Maps: {}\n\
             \tSpot Shadow Maps: {}\n\
-            \tSpot Shadow Maps: {}\n",
+            \tSpot Shadow Maps: {}\n\
+            \tShadows Skipped By Distance: {}\n\
+            \tStatic Shadow Maps: {}\n\
+            \tDynamic Shadow Maps: {}\n\
+            \tSSAO: {}\n",
             self.point_lights_rendered,
             self.spot_lights_rendered,
             self.directional_lights_rendered,
+            self.clustered_lights_rendered,
+            self.clusters,
             self.point_shadow_maps_rendered,
             self.spot_shadow_maps_rendered,
-            self.csm_rendered
+            self.csm_rendered,
+            self.shadows_skipped_by_distance,
+            self.static_shadow_maps_rendered,
+            self.dynamic_shadow_maps_rendered,
+            self.ssao
         )
     }
 }
 
+/// Converts a light into its clustered representation. Returns `None` for lights that cannot be
+/// shaded in the clustered pass - directional lights, orthographic spot lights and spot lights
+/// with cookie textures.
+fn make_clustered_light(
+    light: &Node,
+    position: Vector3<f32>,
+    radius: f32,
+    direction: Vector3<f32>,
+) -> Option<ClusteredLight> {
+    let linear_color = |base: &BaseLight| {
+        base.color()
+            .srgb_to_linear_f32()
+            .xyz()
+            .scale(base.intensity())
+    };
+
+    if let Some(point_light) = light.cast::<PointLight>() {
+        Some(ClusteredLight::point(
+            position,
+            radius,
+            linear_color(point_light.base_light_ref()),
+        ))
+    } else if let Some(spot_light) = light.cast::<SpotLight>() {
+        if spot_light.projection() == SpotLightProjection::Perspective
+            && spot_light.cookie_texture_ref().is_none()
+        {
+            Some(ClusteredLight::spot(
+                position,
+                radius,
+                linear_color(spot_light.base_light_ref()),
+                direction,
+                (spot_light.full_cone_angle() * 0.5).cos(),
+                (spot_light.hotspot_cone_angle() * 0.5).cos(),
+            ))
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
 pub struct DeferredLightRenderer {
-    pub ssao_renderer: ScreenSpaceAmbientOcclusionRenderer,
+    // Optional parts are `None` if they're not supported by hardware, see `RendererCapabilities`.
+    pub ssao_renderer: Option<ScreenSpaceAmbientOcclusionRenderer>,
     spot_light_shader: SpotLightShader,
+    ortho_spot_light_shader: OrthoSpotLightShader,
     point_light_shader: PointLightShader,
     directional_light_shader: DirectionalLightShader,
     ambient_light_shader: AmbientLightShader,
+    clustered_light_shader: ClusteredLightShader,
+    light_clusters: LightClusters,
+    light_storage: VectorStorage,
+    // Buffers that are re-used every frame to avoid allocations.
+    clustered_lights: Vec<ClusteredLight>,
+    light_texels: Vec<Vector4<f32>>,
     quad: GeometryBuffer,
     sphere: GeometryBuffer,
     skybox: GeometryBuffer,
@@ -116,11 +261,11 @@ pub struct DeferredLightRenderer {
     skybox_shader: SkyboxShader,
     spot_shadow_map_renderer: SpotShadowMapRenderer,
     point_shadow_map_renderer: PointShadowMapRenderer,
-    csm_renderer: CsmRenderer,
-    light_volume: LightVolumeRenderer,
+    csm_renderer: Option<CsmRenderer>,
+    light_volume: Option<LightVolumeRenderer>,
 }
 
-pub(in crate) struct DeferredRendererContext<'a> {
+pub(crate) struct DeferredRendererContext<'a> {
     pub state: &'a mut PipelineState,
     pub scene: &'a Scene,
     pub camera: &'a Camera,
@@ -135,6 +280,10 @@ pub(in crate) struct DeferredRendererContext<'a> {
     pub normal_dummy: Rc<RefCell<GpuTexture>>,
     pub white_dummy: Rc<RefCell<GpuTexture>>,
     pub black_dummy: Rc<RefCell<GpuTexture>>,
+    pub environment_dummy: Rc<RefCell<GpuTexture>>,
+    /// Accumulated ambient occlusion of the camera, it is used only if temporal accumulation of
+    /// SSAO is enabled.
+    pub ssao_history: Option<&'a mut SsaoHistory>,
 }
 
 impl DeferredLightRenderer {
@@ -142,6 +291,7 @@ impl DeferredLightRenderer {
         state: &mut PipelineState,
         frame_size: (u32, u32),
         settings: &QualitySettings,
+        capabilities: &mut RendererCapabilities,
     ) -> Result<Self, FrameworkError> {
         let vertices = vec![
             // Front
@@ -179,15 +329,25 @@ impl DeferredLightRenderer {
         let quality_defaults = QualitySettings::default();
 
         Ok(Self {
-            ssao_renderer: ScreenSpaceAmbientOcclusionRenderer::new(
-                state,
-                frame_size.0 as usize,
-                frame_size.1 as usize,
-            )?,
+            ssao_renderer: capabilities.try_init(
+                RendererFeature::Ssao,
+                ScreenSpaceAmbientOcclusionRenderer::new(
+                    state,
+                    frame_size.0 as usize,
+                    frame_size.1 as usize,
+                    &settings.ssao_settings,
+                ),
+            ),
             spot_light_shader: SpotLightShader::new(state)?,
+            ortho_spot_light_shader: OrthoSpotLightShader::new(state)?,
             point_light_shader: PointLightShader::new(state)?,
             directional_light_shader: DirectionalLightShader::new(state)?,
             ambient_light_shader: AmbientLightShader::new(state)?,
+            clustered_light_shader: ClusteredLightShader::new(state)?,
+            light_clusters: LightClusters::new(&settings.light_cluster_settings),
+            light_storage: VectorStorage::new(state)?,
+            clustered_lights: Default::default(),
+            light_texels: Default::default(),
             quad: GeometryBuffer::from_surface_data(
                 &SurfaceData::make_unit_xy_quad(),
                 GeometryBufferKind::StaticDraw,
@@ -232,18 +392,32 @@ impl DeferredLightRenderer {
                 settings.point_shadow_map_size,
                 quality_defaults.point_shadow_map_precision,
             )?,
-            light_volume: LightVolumeRenderer::new(state)?,
-            csm_renderer: CsmRenderer::new(
-                state,
-                quality_defaults.csm_settings.size,
-                quality_defaults.csm_settings.precision,
-            )?,
+            light_volume: capabilities.try_init(
+                RendererFeature::LightScattering,
+                LightVolumeRenderer::new(state),
+            ),
+            csm_renderer: capabilities.try_init(
+                RendererFeature::Csm,
+                CsmRenderer::new(
+                    state,
+                    quality_defaults.csm_settings.size,
+                    quality_defaults.csm_settings.precision,
+                ),
+            ),
         })
     }
 
+    /// Frees cached static shadow maps of lights that weren't rendered since the previous call,
+    /// it must be called once per frame after every scene was rendered.
+    pub(crate) fn collect_static_shadow_maps(&mut self) {
+        self.spot_shadow_map_renderer.collect_static_garbage();
+        self.point_shadow_map_renderer.collect_static_garbage();
+    }
+
     pub fn set_quality_settings(
         &mut self,
         state: &mut PipelineState,
+        frame_size: (u32, u32),
         settings: &QualitySettings,
     ) -> Result<(), FrameworkError> {
         if settings.spot_shadow_map_size != self.spot_shadow_map_renderer.base_size()
@@ -264,16 +438,30 @@ impl DeferredLightRenderer {
                 settings.point_shadow_map_precision,
             )?;
         }
-        if settings.csm_settings.precision != self.csm_renderer.precision()
-            || settings.csm_settings.size != self.csm_renderer.size()
-        {
-            self.csm_renderer = CsmRenderer::new(
-                state,
-                settings.csm_settings.size,
-                settings.csm_settings.precision,
-            )?;
+        if let Some(csm_renderer) = self.csm_renderer.as_mut() {
+            if settings.csm_settings.precision != csm_renderer.precision()
+                || settings.csm_settings.size != csm_renderer.size()
+            {
+                *csm_renderer = CsmRenderer::new(
+                    state,
+                    settings.csm_settings.size,
+                    settings.csm_settings.precision,
+                )?;
+            }
         }
-        self.ssao_renderer.set_radius(settings.ssao_radius);
+        if let Some(ssao_renderer) = self.ssao_renderer.as_mut() {
+            if settings.ssao_settings.clamped() != *ssao_renderer.settings() {
+                *ssao_renderer = ScreenSpaceAmbientOcclusionRenderer::new(
+                    state,
+                    frame_size.0 as usize,
+                    frame_size.1 as usize,
+                    &settings.ssao_settings,
+                )?;
+            }
+            ssao_renderer.set_radius(settings.ssao_radius);
+        }
+        self.light_clusters
+            .set_settings(&settings.light_cluster_settings);
         Ok(())
     }
 
@@ -282,16 +470,107 @@ impl DeferredLightRenderer {
         state: &mut PipelineState,
         frame_size: (u32, u32),
     ) -> Result<(), FrameworkError> {
-        self.ssao_renderer = ScreenSpaceAmbientOcclusionRenderer::new(
-            state,
-            frame_size.0 as usize,
-            frame_size.1 as usize,
-        )?;
+        if let Some(ssao_renderer) = self.ssao_renderer.as_mut() {
+            let settings = *ssao_renderer.settings();
+            let radius = ssao_renderer.radius();
+            *ssao_renderer = ScreenSpaceAmbientOcclusionRenderer::new(
+                state,
+                frame_size.0 as usize,
+                frame_size.1 as usize,
+                &settings,
+            )?;
+            ssao_renderer.set_radius(radius);
+        }
         Ok(())
     }
 
+    /// Bins collected lights into clusters and shades all of them in a single full-screen pass.
+    #[allow(clippy::too_many_arguments)]
+    fn render_clustered_lights(
+        &mut self,
+        state: &mut PipelineState,
+        camera: &Camera,
+        gbuffer: &GBuffer,
+        frame_buffer: &mut FrameBuffer,
+        viewport: Rect<i32>,
+        frame_matrix: Matrix4<f32>,
+        inv_view_projection: Matrix4<f32>,
+    ) -> RenderPassStatistics {
+        scope_profile!();
+
+        self.light_clusters.build(
+            &camera.view_matrix(),
+            &camera.projection_matrix(),
+            camera.projection().z_near(),
+            camera.projection().z_far(),
+            &self.clustered_lights,
+        );
+
+        let (cluster_offset, index_offset) = self
+            .light_clusters
+            .pack(&self.clustered_lights, &mut self.light_texels);
+
+        if let Err(e) = self.light_storage.upload(state, &self.light_texels) {
+            Log::writeln(
+                MessageKind::Error,
+                format!("Unable to upload clustered lights. Reason: {:?}", e),
+            );
+            return Default::default();
+        }
+
+        let inv_projection = camera.projection_matrix().try_inverse().unwrap_or_default();
+        let camera_position = camera.global_position();
+        let light_storage = self.light_storage.texture().clone();
+        let grid = *self.light_clusters.settings();
+        let z_near = self.light_clusters.z_near();
+        let depth_slice_scale = self.light_clusters.depth_slice_scale();
+        let shader = &self.clustered_light_shader;
+
+        let mut pass_stats = RenderPassStatistics::default();
+        pass_stats += frame_buffer.draw(
+            &self.quad,
+            state,
+            viewport,
+            &shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: Some(BlendFunc {
+                    sfactor: BlendFactor::One,
+                    dfactor: BlendFactor::One,
+                }),
+                stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
+            },
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(&shader.wvp_matrix, &frame_matrix)
+                    .set_matrix4(&shader.inv_view_proj_matrix, &inv_view_projection)
+                    .set_matrix4(&shader.inv_proj_matrix, &inv_projection)
+                    .set_vector3(&shader.camera_position, &camera_position)
+                    .set_texture(&shader.depth_sampler, &gbuffer.depth())
+                    .set_texture(&shader.color_sampler, &gbuffer.diffuse_texture())
+                    .set_texture(&shader.normal_sampler, &gbuffer.normal_texture())
+                    .set_texture(&shader.material_sampler, &gbuffer.material_texture())
+                    .set_texture(&shader.light_storage, &light_storage)
+                    .set_i32(&shader.cluster_offset, cluster_offset as i32)
+                    .set_i32(&shader.index_offset, index_offset as i32)
+                    .set_i32(&shader.tiles_x, grid.tiles_x as i32)
+                    .set_i32(&shader.tiles_y, grid.tiles_y as i32)
+                    .set_i32(&shader.depth_slices, grid.depth_slices as i32)
+                    .set_f32(&shader.z_near, z_near)
+                    .set_f32(&shader.depth_slice_scale, depth_slice_scale);
+            },
+        );
+        pass_stats
+    }
+
     #[must_use]
-    pub(in crate) fn render(
+    pub(crate) fn render(
         &mut self,
         args: DeferredRendererContext,
     ) -> (RenderPassStatistics, LightingStatistics) {
@@ -315,6 +594,8 @@ impl DeferredLightRenderer {
             batch_storage,
             frame_buffer,
             black_dummy,
+            environment_dummy,
+            ssao_history,
         } = args;
 
         let viewport = Rect::new(0, 0, gbuffer.width, gbuffer.height);
@@ -340,13 +621,18 @@ impl DeferredLightRenderer {
         let camera_global_position = camera.global_position();
 
         // Fill SSAO map.
-        if settings.use_ssao {
-            pass_stats += self.ssao_renderer.render(
+        let ssao_renderer = self.ssao_renderer.as_mut().filter(|_| settings.use_ssao);
+        let use_ssao = ssao_renderer.is_some();
+        if let Some(ssao_renderer) = ssao_renderer {
+            pass_stats += ssao_renderer.render(
                 state,
                 gbuffer,
                 projection_matrix,
                 camera.view_matrix().basis(),
+                view_projection,
+                ssao_history,
             );
+            light_stats.ssao = ssao_renderer.statistics();
         }
 
         // Render skybox (if any).
@@ -374,6 +660,8 @@ impl DeferredLightRenderer {
                             depth_test: false,
                             blend: None,
                             stencil_op: Default::default(),
+                            alpha_to_coverage: false,
+                            depth_func: Default::default(),
                         },
                         0,
                         12,
@@ -387,13 +675,70 @@ impl DeferredLightRenderer {
             }
         }
 
+        // Gather reflection probes that could affect visible pixels, the closest ones to the camera
+        // are more important.
+        let mut probes = scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<ReflectionProbe>())
+            .filter(|probe| {
+                probe.global_visibility()
+                    && camera.can_see_layers(probe.render_mask())
+                    && frustum.is_intersects_aabb(&probe.world_box())
+            })
+            .filter_map(|probe| {
+                probe
+                    .environment()
+                    .and_then(|environment| textures.get(state, environment))
+                    .map(|gpu_texture| (probe, gpu_texture))
+            })
+            .collect::<Vec<_>>();
+        probes.sort_by(|(a, _), (b, _)| {
+            let a = (a.global_position() - camera_global_position).norm_squared();
+            let b = (b.global_position() - camera_global_position).norm_squared();
+            a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        probes.truncate(MAX_REFLECTION_PROBES);
+
+        let mut probe_box_min = [Vector3::default(); MAX_REFLECTION_PROBES];
+        let mut probe_box_max = [Vector3::default(); MAX_REFLECTION_PROBES];
+        let mut probe_position = [Vector3::default(); MAX_REFLECTION_PROBES];
+        let mut probe_blend_distance = [0.0; MAX_REFLECTION_PROBES];
+        let mut probe_textures = [
+            environment_dummy.clone(),
+            environment_dummy.clone(),
+            environment_dummy.clone(),
+            environment_dummy.clone(),
+        ];
+        for (i, (probe, gpu_texture)) in probes.iter().enumerate() {
+            let world_box = probe.world_box();
+            probe_box_min[i] = world_box.min;
+            probe_box_max[i] = world_box.max;
+            probe_position[i] = probe.global_position();
+            probe_blend_distance[i] = probe.blend_distance();
+            probe_textures[i] = gpu_texture.clone();
+        }
+
+        // Pixels outside of every probe reflect the environment of the camera or its skybox.
+        let environment_map = camera
+            .environment_ref()
+            .or_else(|| camera.skybox_ref().and_then(|skybox| skybox.cubemap_ref()))
+            .and_then(|environment| textures.get(state, environment))
+            .unwrap_or_else(|| environment_dummy.clone());
+
         // Ambient light.
         let gbuffer_depth_map = gbuffer.depth();
         let gbuffer_diffuse_map = gbuffer.diffuse_texture();
         let gbuffer_normal_map = gbuffer.normal_texture();
         let gbuffer_material_map = gbuffer.material_texture();
         let gbuffer_ambient_map = gbuffer.ambient_texture();
-        let ao_map = self.ssao_renderer.ao_map();
+        let (ao_map, use_bent_normals) = match self.ssao_renderer.as_ref().filter(|_| use_ssao) {
+            Some(ssao_renderer) => (
+                ssao_renderer.ao_map(),
+                ssao_renderer.settings().bent_normals,
+            ),
+            None => (white_dummy.clone(), false),
+        };
 
         frame_buffer.draw(
             &self.quad,
@@ -411,6 +756,8 @@ impl DeferredLightRenderer {
                     dfactor: BlendFactor::OneMinusSrcAlpha,
                 }),
                 stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
             },
             |mut program_binding| {
                 program_binding
@@ -420,71 +767,187 @@ impl DeferredLightRenderer {
                         &self.ambient_light_shader.diffuse_texture,
                         &gbuffer_diffuse_map,
                     )
-                    .set_texture(
-                        &self.ambient_light_shader.ao_sampler,
-                        if settings.use_ssao {
-                            &ao_map
-                        } else {
-                            &white_dummy
-                        },
+                    .set_texture(&self.ambient_light_shader.ao_sampler, &ao_map)
+                    .set_bool(
+                        &self.ambient_light_shader.use_bent_normals,
+                        use_bent_normals,
                     )
                     .set_texture(
                         &self.ambient_light_shader.ambient_texture,
                         &gbuffer_ambient_map,
+                    )
+                    .set_texture(&self.ambient_light_shader.depth_texture, &gbuffer_depth_map)
+                    .set_texture(
+                        &self.ambient_light_shader.normal_texture,
+                        &gbuffer_normal_map,
+                    )
+                    .set_texture(
+                        &self.ambient_light_shader.material_texture,
+                        &gbuffer_material_map,
+                    )
+                    .set_texture(
+                        &self.ambient_light_shader.environment_texture,
+                        &environment_map,
+                    )
+                    .set_matrix4(
+                        &self.ambient_light_shader.inv_view_proj_matrix,
+                        &inv_view_projection,
+                    )
+                    .set_vector3(
+                        &self.ambient_light_shader.camera_position,
+                        &camera_global_position,
+                    )
+                    .set_i32(&self.ambient_light_shader.probe_count, probes.len() as i32)
+                    .set_vector3_slice(&self.ambient_light_shader.probe_box_min, &probe_box_min)
+                    .set_vector3_slice(&self.ambient_light_shader.probe_box_max, &probe_box_max)
+                    .set_vector3_slice(&self.ambient_light_shader.probe_position, &probe_position)
+                    .set_f32_slice(
+                        &self.ambient_light_shader.probe_blend_distance,
+                        &probe_blend_distance,
                     );
+
+                for (location, texture) in self
+                    .ambient_light_shader
+                    .probe_textures
+                    .iter()
+                    .zip(probe_textures.iter())
+                {
+                    program_binding.set_texture(location, texture);
+                }
             },
         );
 
+        self.clustered_lights.clear();
+
         for (light_handle, light) in scene.graph.pair_iter() {
-            if !light.global_visibility() {
+            if !light.global_visibility() || !camera.can_see_layers(light.render_mask()) {
                 continue;
             }
 
-            let distance_to_camera = (light.global_position() - camera.global_position()).norm();
-
-            let (raw_radius, shadows_distance, shadows_enabled) = if let Some(spot_light) =
-                light.cast::<SpotLight>()
+            // Contribution of baked lights is already in the lightmap.
+            if scene
+                .lightmap()
+                .map_or(false, |lightmap| lightmap.is_baked_light(light_handle))
             {
-                (
-                    spot_light.distance(),
-                    settings.spot_shadows_distance,
-                    spot_light.base_light_ref().is_cast_shadows()
-                        && distance_to_camera <= settings.spot_shadows_distance
-                        && settings.spot_shadows_enabled,
-                )
-            } else if let Some(point_light) = light.cast::<PointLight>() {
-                (
-                    point_light.radius(),
-                    settings.point_shadows_distance,
-                    point_light.base_light_ref().is_cast_shadows()
-                        && distance_to_camera <= settings.point_shadows_distance
-                        && settings.point_shadows_enabled,
-                )
-            } else if let Some(directional) = light.cast::<DirectionalLight>() {
-                (
-                    f32::MAX,
-                    0.0,
-                    directional.base_light_ref().is_cast_shadows() && settings.csm_settings.enabled,
-                )
-            } else {
                 continue;
+            }
+
+            let distance_to_camera = (light.global_position() - camera.global_position()).norm();
+
+            // Shadows of directional lights are never culled by distance, so they have no
+            // shadows distance.
+            let (raw_radius, base_light, shadows_distance, shadows_enabled) =
+                if let Some(spot_light) = light.cast::<SpotLight>() {
+                    (
+                        spot_light.distance(),
+                        spot_light.base_light_ref(),
+                        Some(settings.spot_shadows_distance),
+                        settings.spot_shadows_enabled,
+                    )
+                } else if let Some(point_light) = light.cast::<PointLight>() {
+                    (
+                        point_light.radius(),
+                        point_light.base_light_ref(),
+                        Some(settings.point_shadows_distance),
+                        settings.point_shadows_enabled,
+                    )
+                } else if let Some(directional) = light.cast::<DirectionalLight>() {
+                    (
+                        f32::MAX,
+                        directional.base_light_ref(),
+                        None,
+                        settings.csm_settings.enabled && self.csm_renderer.is_some(),
+                    )
+                } else {
+                    continue;
+                };
+
+            let shadows_enabled = shadows_enabled && base_light.is_cast_shadows();
+
+            let (shadows_distance, shadows_in_range) = match shadows_distance {
+                Some(quality_distance) => {
+                    let max_distance = base_light.shadows_max_distance().min(quality_distance)
+                        * settings.shadows_distance_multiplier;
+                    // Must be evaluated every frame (even for lights outside of the frustum) to
+                    // keep the state of hysteresis up-to-date.
+                    (
+                        max_distance,
+                        base_light.update_shadows_culling(distance_to_camera, max_distance),
+                    )
+                }
+                None => (0.0, true),
             };
 
             let light_position = light.global_position();
             let scl = light.local_transform().scale();
             let light_radius_scale = scl.x.max(scl.y).max(scl.z);
             let light_radius = light_radius_scale * raw_radius;
-            let light_r_inflate = 1.05 * light_radius;
+            // Orthographic spot light lits a box in front of it, so its light volume must enclose
+            // the whole box.
+            let light_volume_radius = match light.cast::<SpotLight>() {
+                Some(spot) if spot.projection() == SpotLightProjection::Orthographic => {
+                    let half_width = spot.ortho_width() * 0.5;
+                    let half_height = spot.ortho_height() * 0.5;
+                    (half_width * half_width
+                        + half_height * half_height
+                        + light_radius * light_radius)
+                        .sqrt()
+                }
+                _ => light_radius,
+            };
+            let light_r_inflate = 1.05 * light_volume_radius;
             let light_radius_vec = Vector3::new(light_r_inflate, light_r_inflate, light_r_inflate);
             let emit_direction = light
                 .up_vector()
                 .try_normalize(f32::EPSILON)
                 .unwrap_or_else(Vector3::z);
 
-            if !frustum.is_intersects_sphere(light_position, light_radius) {
+            if !frustum.is_intersects_sphere(light_position, light_volume_radius) {
                 continue;
             }
 
+            if shadows_enabled && !shadows_in_range {
+                light_stats.shadows_skipped_by_distance += 1;
+            }
+            let shadows_enabled = shadows_enabled && shadows_in_range;
+
+            // Shadows of directional lights depend on the camera, so they cannot be cached.
+            let mut static_shadows = shadows_enabled
+                && base_light.is_static_shadows()
+                && light.cast::<DirectionalLight>().is_none();
+
+            // Volumetric light does not depend on the way the light itself is drawn.
+            if let Some(light_volume) = self
+                .light_volume
+                .as_mut()
+                .filter(|_| settings.light_scatter_enabled)
+            {
+                pass_stats += light_volume.render_volume(
+                    state,
+                    light,
+                    light_handle,
+                    gbuffer,
+                    &self.quad,
+                    camera.view_matrix(),
+                    inv_projection,
+                    view_projection,
+                    viewport,
+                    &scene.graph,
+                    frame_buffer,
+                );
+            }
+
+            // Lights without shadows are shaded in a single pass after every other light.
+            if settings.light_cluster_settings.enabled && !shadows_enabled {
+                if let Some(clustered_light) =
+                    make_clustered_light(light, light_position, light_radius, emit_direction)
+                {
+                    self.clustered_lights.push(clustered_light);
+                    light_stats.clustered_lights_rendered += 1;
+                    continue;
+                }
+            }
+
             let b1 = shadows_distance * 0.2;
             let b2 = shadows_distance * 0.4;
             let cascade_index =
@@ -498,10 +961,11 @@ impl DeferredLightRenderer {
 
             let mut light_view_projection = Matrix4::identity();
 
-            if shadows_enabled {
-                if let Some(spot) = light.cast::<SpotLight>() {
-                    let light_projection_matrix =
-                        Matrix4::new_perspective(1.0, spot.full_cone_angle(), 0.01, light_radius);
+            if let Some(spot) = light.cast::<SpotLight>() {
+                // Orthographic spot light needs the matrix to calculate its light box even if
+                // it does not cast shadows.
+                if shadows_enabled || spot.projection() == SpotLightProjection::Orthographic {
+                    let light_projection_matrix = spot.projection_matrix(light_radius);
 
                     let light_look_at = light_position - emit_direction;
 
@@ -517,6 +981,48 @@ impl DeferredLightRenderer {
                     );
 
                     light_view_projection = light_projection_matrix * light_view_matrix;
+                }
+            }
+
+            if shadows_enabled {
+                if light.cast::<SpotLight>().is_some() {
+                    if static_shadows {
+                        let light_frustum =
+                            Frustum::from(light_view_projection).unwrap_or_default();
+                        let key = static_shadow_casters_key(
+                            batch_storage,
+                            camera.culling_mask(),
+                            light_view_projection.as_slice(),
+                            |aabb| light_frustum.is_intersects_aabb(aabb),
+                        );
+                        match self.spot_shadow_map_renderer.render_static(
+                            state,
+                            light_handle,
+                            key,
+                            &light_view_projection,
+                            batch_storage,
+                            geometry_cache,
+                            shader_cache,
+                            textures,
+                            normal_dummy.clone(),
+                            white_dummy.clone(),
+                            black_dummy.clone(),
+                            camera.culling_mask(),
+                        ) {
+                            Ok(Some(stats)) => {
+                                pass_stats += stats;
+                                light_stats.static_shadow_maps_rendered += 1;
+                            }
+                            Ok(None) => (),
+                            Err(e) => {
+                                Log::writeln(
+                                    MessageKind::Error,
+                                    format!("Unable to create static shadow map. Reason: {:?}", e),
+                                );
+                                static_shadows = false;
+                            }
+                        }
+                    }
 
                     pass_stats += self.spot_shadow_map_renderer.render(
                         state,
@@ -529,10 +1035,65 @@ impl DeferredLightRenderer {
                         normal_dummy.clone(),
                         white_dummy.clone(),
                         black_dummy.clone(),
+                        camera.culling_mask(),
+                        if static_shadows {
+                            ShadowCasters::Dynamic
+                        } else {
+                            ShadowCasters::All
+                        },
                     );
 
                     light_stats.spot_shadow_maps_rendered += 1;
+                    if static_shadows {
+                        light_stats.dynamic_shadow_maps_rendered += 1;
+                    }
                 } else if light.cast::<PointLight>().is_some() {
+                    if static_shadows {
+                        let key = static_shadow_casters_key(
+                            batch_storage,
+                            camera.culling_mask(),
+                            &[
+                                light_position.x,
+                                light_position.y,
+                                light_position.z,
+                                light_radius,
+                            ],
+                            |aabb| aabb.is_intersects_sphere(light_position, light_radius),
+                        );
+                        match self.point_shadow_map_renderer.render_static(
+                            light_handle,
+                            key,
+                            PointShadowMapRenderContext {
+                                state,
+                                light_pos: light_position,
+                                light_radius,
+                                geom_cache: geometry_cache,
+                                cascade: 0,
+                                batch_storage,
+                                shader_cache,
+                                texture_cache: textures,
+                                normal_dummy: normal_dummy.clone(),
+                                white_dummy: white_dummy.clone(),
+                                black_dummy: black_dummy.clone(),
+                                culling_mask: camera.culling_mask(),
+                                casters: ShadowCasters::Static,
+                            },
+                        ) {
+                            Ok(Some(stats)) => {
+                                pass_stats += stats;
+                                light_stats.static_shadow_maps_rendered += 1;
+                            }
+                            Ok(None) => (),
+                            Err(e) => {
+                                Log::writeln(
+                                    MessageKind::Error,
+                                    format!("Unable to create static shadow map. Reason: {:?}", e),
+                                );
+                                static_shadows = false;
+                            }
+                        }
+                    }
+
                     pass_stats +=
                         self.point_shadow_map_renderer
                             .render(PointShadowMapRenderContext {
@@ -547,11 +1108,22 @@ impl DeferredLightRenderer {
                                 normal_dummy: normal_dummy.clone(),
                                 white_dummy: white_dummy.clone(),
                                 black_dummy: black_dummy.clone(),
+                                culling_mask: camera.culling_mask(),
+                                casters: if static_shadows {
+                                    ShadowCasters::Dynamic
+                                } else {
+                                    ShadowCasters::All
+                                },
                             });
 
                     light_stats.point_shadow_maps_rendered += 1;
-                } else if let Some(directional) = light.cast::<DirectionalLight>() {
-                    pass_stats += self.csm_renderer.render(CsmRenderContext {
+                    if static_shadows {
+                        light_stats.dynamic_shadow_maps_rendered += 1;
+                    }
+                } else if let (Some(directional), Some(csm_renderer)) =
+                    (light.cast::<DirectionalLight>(), self.csm_renderer.as_mut())
+                {
+                    pass_stats += csm_renderer.render(CsmRenderContext {
                         frame_size: Vector2::new(gbuffer.width as f32, gbuffer.height as f32),
                         state,
                         graph: &scene.graph,
@@ -593,6 +1165,8 @@ impl DeferredLightRenderer {
                     },
                     depth_test: true,
                     blend: None,
+                    alpha_to_coverage: false,
+                    depth_func: Default::default(),
                 },
                 |mut program_binding| {
                     program_binding.set_matrix4(
@@ -623,6 +1197,8 @@ impl DeferredLightRenderer {
                     },
                     depth_test: true,
                     blend: None,
+                    alpha_to_coverage: false,
+                    depth_func: Default::default(),
                 },
                 |mut program_binding| {
                     program_binding.set_matrix4(
@@ -651,13 +1227,13 @@ impl DeferredLightRenderer {
                     sfactor: BlendFactor::One,
                     dfactor: BlendFactor::One,
                 }),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
             };
 
             let quad = &self.quad;
 
             pass_stats += if let Some(spot_light) = light.cast::<SpotLight>() {
-                let shader = &self.spot_light_shader;
-
                 let (cookie_enabled, cookie_texture) =
                     if let Some(texture) = spot_light.cookie_texture_ref() {
                         if let Some(cookie) = textures.get(state, texture) {
@@ -671,62 +1247,144 @@ impl DeferredLightRenderer {
 
                 light_stats.spot_lights_rendered += 1;
 
-                frame_buffer.draw(
-                    quad,
-                    state,
-                    viewport,
-                    &shader.program,
-                    &draw_params,
-                    |mut program_binding| {
-                        program_binding
-                            .set_bool(&shader.shadows_enabled, shadows_enabled)
-                            .set_matrix4(&shader.light_view_proj_matrix, &light_view_projection)
-                            .set_bool(&shader.soft_shadows, settings.spot_soft_shadows)
-                            .set_vector3(&shader.light_position, &light_position)
-                            .set_vector3(&shader.light_direction, &emit_direction)
-                            .set_f32(&shader.light_radius, light_radius)
-                            .set_matrix4(&shader.inv_view_proj_matrix, &inv_view_projection)
-                            .set_linear_color(
-                                &shader.light_color,
-                                &spot_light.base_light_ref().color(),
-                            )
-                            .set_f32(
-                                &shader.half_hotspot_cone_angle_cos,
-                                (spot_light.hotspot_cone_angle() * 0.5).cos(),
-                            )
-                            .set_f32(
-                                &shader.half_cone_angle_cos,
-                                (spot_light.full_cone_angle() * 0.5).cos(),
-                            )
-                            .set_matrix4(&shader.wvp_matrix, &frame_matrix)
-                            .set_f32(
-                                &shader.shadow_map_inv_size,
-                                1.0 / (self.spot_shadow_map_renderer.cascade_size(cascade_index)
-                                    as f32),
-                            )
-                            .set_vector3(&shader.camera_position, &camera_global_position)
-                            .set_texture(&shader.depth_sampler, &gbuffer_depth_map)
-                            .set_texture(&shader.color_sampler, &gbuffer_diffuse_map)
-                            .set_texture(&shader.normal_sampler, &gbuffer_normal_map)
-                            .set_texture(&shader.material_sampler, &gbuffer_material_map)
-                            .set_texture(
-                                &shader.spot_shadow_texture,
-                                &self.spot_shadow_map_renderer.cascade_texture(cascade_index),
-                            )
-                            .set_texture(&shader.cookie_texture, &cookie_texture)
-                            .set_bool(&shader.cookie_enabled, cookie_enabled)
-                            .set_f32(&shader.shadow_bias, spot_light.shadow_bias())
-                            .set_f32(
-                                &shader.light_intensity,
-                                spot_light.base_light_ref().intensity(),
-                            );
-                    },
-                )
+                // Unused sampler still must be bound to a texture of a proper type.
+                let static_shadow_texture = self
+                    .spot_shadow_map_renderer
+                    .static_texture(light_handle)
+                    .filter(|_| static_shadows)
+                    .unwrap_or_else(|| {
+                        self.spot_shadow_map_renderer.cascade_texture(cascade_index)
+                    });
+                let static_shadow_map_inv_size =
+                    1.0 / (self.spot_shadow_map_renderer.static_size() as f32);
+
+                if spot_light.projection() == SpotLightProjection::Orthographic {
+                    let shader = &self.ortho_spot_light_shader;
+
+                    frame_buffer.draw(
+                        quad,
+                        state,
+                        viewport,
+                        &shader.program,
+                        &draw_params,
+                        |mut program_binding| {
+                            program_binding
+                                .set_bool(&shader.shadows_enabled, shadows_enabled)
+                                .set_matrix4(&shader.light_view_proj_matrix, &light_view_projection)
+                                .set_bool(&shader.soft_shadows, settings.spot_soft_shadows)
+                                .set_vector3(&shader.light_direction, &emit_direction)
+                                .set_f32(&shader.edge_falloff, spot_light.edge_falloff())
+                                .set_matrix4(&shader.inv_view_proj_matrix, &inv_view_projection)
+                                .set_linear_color(
+                                    &shader.light_color,
+                                    &spot_light.base_light_ref().color(),
+                                )
+                                .set_matrix4(&shader.wvp_matrix, &frame_matrix)
+                                .set_f32(
+                                    &shader.shadow_map_inv_size,
+                                    1.0 / (self.spot_shadow_map_renderer.cascade_size(cascade_index)
+                                        as f32),
+                                )
+                                .set_vector3(&shader.camera_position, &camera_global_position)
+                                .set_texture(&shader.depth_sampler, &gbuffer_depth_map)
+                                .set_texture(&shader.color_sampler, &gbuffer_diffuse_map)
+                                .set_texture(&shader.normal_sampler, &gbuffer_normal_map)
+                                .set_texture(&shader.material_sampler, &gbuffer_material_map)
+                                .set_texture(
+                                    &shader.spot_shadow_texture,
+                                    &self.spot_shadow_map_renderer.cascade_texture(cascade_index),
+                                )
+                                .set_bool(&shader.static_shadows_enabled, static_shadows)
+                                .set_f32(
+                                    &shader.static_shadow_map_inv_size,
+                                    static_shadow_map_inv_size,
+                                )
+                                .set_texture(&shader.static_shadow_texture, &static_shadow_texture)
+                                .set_texture(&shader.cookie_texture, &cookie_texture)
+                                .set_bool(&shader.cookie_enabled, cookie_enabled)
+                                .set_f32(&shader.shadow_bias, spot_light.shadow_bias())
+                                .set_f32(
+                                    &shader.light_intensity,
+                                    spot_light.base_light_ref().intensity(),
+                                );
+                        },
+                    )
+                } else {
+                    let shader = &self.spot_light_shader;
+
+                    frame_buffer.draw(
+                        quad,
+                        state,
+                        viewport,
+                        &shader.program,
+                        &draw_params,
+                        |mut program_binding| {
+                            program_binding
+                                .set_bool(&shader.shadows_enabled, shadows_enabled)
+                                .set_matrix4(&shader.light_view_proj_matrix, &light_view_projection)
+                                .set_bool(&shader.soft_shadows, settings.spot_soft_shadows)
+                                .set_vector3(&shader.light_position, &light_position)
+                                .set_vector3(&shader.light_direction, &emit_direction)
+                                .set_f32(&shader.light_radius, light_radius)
+                                .set_matrix4(&shader.inv_view_proj_matrix, &inv_view_projection)
+                                .set_linear_color(
+                                    &shader.light_color,
+                                    &spot_light.base_light_ref().color(),
+                                )
+                                .set_f32(
+                                    &shader.half_hotspot_cone_angle_cos,
+                                    (spot_light.hotspot_cone_angle() * 0.5).cos(),
+                                )
+                                .set_f32(
+                                    &shader.half_cone_angle_cos,
+                                    (spot_light.full_cone_angle() * 0.5).cos(),
+                                )
+                                .set_matrix4(&shader.wvp_matrix, &frame_matrix)
+                                .set_f32(
+                                    &shader.shadow_map_inv_size,
+                                    1.0 / (self.spot_shadow_map_renderer.cascade_size(cascade_index)
+                                        as f32),
+                                )
+                                .set_vector3(&shader.camera_position, &camera_global_position)
+                                .set_texture(&shader.depth_sampler, &gbuffer_depth_map)
+                                .set_texture(&shader.color_sampler, &gbuffer_diffuse_map)
+                                .set_texture(&shader.normal_sampler, &gbuffer_normal_map)
+                                .set_texture(&shader.material_sampler, &gbuffer_material_map)
+                                .set_texture(
+                                    &shader.spot_shadow_texture,
+                                    &self.spot_shadow_map_renderer.cascade_texture(cascade_index),
+                                )
+                                .set_bool(&shader.static_shadows_enabled, static_shadows)
+                                .set_f32(
+                                    &shader.static_shadow_map_inv_size,
+                                    static_shadow_map_inv_size,
+                                )
+                                .set_texture(&shader.static_shadow_texture, &static_shadow_texture)
+                                .set_texture(&shader.cookie_texture, &cookie_texture)
+                                .set_bool(&shader.cookie_enabled, cookie_enabled)
+                                .set_f32(&shader.shadow_bias, spot_light.shadow_bias())
+                                .set_f32(
+                                    &shader.light_intensity,
+                                    spot_light.base_light_ref().intensity(),
+                                );
+                        },
+                    )
+                }
             } else if let Some(point_light) = light.cast::<PointLight>() {
                 let shader = &self.point_light_shader;
 
                 light_stats.point_lights_rendered += 1;
 
+                // Unused sampler still must be bound to a texture of a proper type.
+                let static_shadow_texture = self
+                    .point_shadow_map_renderer
+                    .static_texture(light_handle)
+                    .filter(|_| static_shadows)
+                    .unwrap_or_else(|| {
+                        self.point_shadow_map_renderer
+                            .cascade_texture(cascade_index)
+                    });
+
                 frame_buffer.draw(
                     quad,
                     state,
@@ -760,7 +1418,9 @@ impl DeferredLightRenderer {
                                 &self
                                     .point_shadow_map_renderer
                                     .cascade_texture(cascade_index),
-                            );
+                            )
+                            .set_bool(&shader.static_shadows_enabled, static_shadows)
+                            .set_texture(&shader.static_shadow_texture, &static_shadow_texture);
                     },
                 )
             } else if let Some(directional) = light.cast::<DirectionalLight>() {
@@ -784,18 +1444,39 @@ impl DeferredLightRenderer {
                             dfactor: BlendFactor::One,
                         }),
                         stencil_op: Default::default(),
+                        alpha_to_coverage: false,
+                        depth_func: Default::default(),
                     },
                     |mut program_binding| {
-                        let distances = [
-                            self.csm_renderer.cascades()[0].z_far,
-                            self.csm_renderer.cascades()[1].z_far,
-                            self.csm_renderer.cascades()[2].z_far,
-                        ];
-                        let matrices = [
-                            self.csm_renderer.cascades()[0].view_proj_matrix,
-                            self.csm_renderer.cascades()[1].view_proj_matrix,
-                            self.csm_renderer.cascades()[2].view_proj_matrix,
-                        ];
+                        // Cascades are not used if shadows are disabled, so any textures will do.
+                        let (distances, matrices, cascade_textures) =
+                            match self.csm_renderer.as_ref() {
+                                Some(csm_renderer) => {
+                                    let cascades = csm_renderer.cascades();
+                                    (
+                                        [cascades[0].z_far, cascades[1].z_far, cascades[2].z_far],
+                                        [
+                                            cascades[0].view_proj_matrix,
+                                            cascades[1].view_proj_matrix,
+                                            cascades[2].view_proj_matrix,
+                                        ],
+                                        [
+                                            cascades[0].texture(),
+                                            cascades[1].texture(),
+                                            cascades[2].texture(),
+                                        ],
+                                    )
+                                }
+                                None => (
+                                    [0.0; 3],
+                                    [Matrix4::identity(); 3],
+                                    [
+                                        white_dummy.clone(),
+                                        white_dummy.clone(),
+                                        white_dummy.clone(),
+                                    ],
+                                ),
+                            };
 
                         program_binding
                             .set_vector3(&shader.light_direction, &emit_direction)
@@ -815,18 +1496,9 @@ impl DeferredLightRenderer {
                             .set_texture(&shader.normal_sampler, &gbuffer_normal_map)
                             .set_texture(&shader.material_sampler, &gbuffer_material_map)
                             .set_matrix4_array(&shader.light_view_proj_matrices, &matrices)
-                            .set_texture(
-                                &shader.shadow_cascade0,
-                                &self.csm_renderer.cascades()[0].texture(),
-                            )
-                            .set_texture(
-                                &shader.shadow_cascade1,
-                                &self.csm_renderer.cascades()[1].texture(),
-                            )
-                            .set_texture(
-                                &shader.shadow_cascade2,
-                                &self.csm_renderer.cascades()[2].texture(),
-                            )
+                            .set_texture(&shader.shadow_cascade0, &cascade_textures[0])
+                            .set_texture(&shader.shadow_cascade1, &cascade_textures[1])
+                            .set_texture(&shader.shadow_cascade2, &cascade_textures[2])
                             .set_f32_slice(&shader.cascade_distances, &distances)
                             .set_matrix4(&shader.view_matrix, &camera.view_matrix())
                             .set_f32(&shader.shadow_bias, directional.csm_options.shadow_bias())
@@ -836,22 +1508,19 @@ impl DeferredLightRenderer {
             } else {
                 unreachable!()
             };
+        }
 
-            if settings.light_scatter_enabled {
-                pass_stats += self.light_volume.render_volume(
-                    state,
-                    light,
-                    light_handle,
-                    gbuffer,
-                    &self.quad,
-                    camera.view_matrix(),
-                    inv_projection,
-                    view_projection,
-                    viewport,
-                    &scene.graph,
-                    frame_buffer,
-                );
-            }
+        if !self.clustered_lights.is_empty() {
+            pass_stats += self.render_clustered_lights(
+                state,
+                camera,
+                gbuffer,
+                frame_buffer,
+                viewport,
+                frame_matrix,
+                inv_view_projection,
+            );
+            light_stats.clusters = self.light_clusters.statistics();
         }
 
         (pass_stats, light_stats)
@@ -13,6 +13,8 @@ pub struct PointLightShader {
     pub normal_sampler: UniformLocation,
     pub material_sampler: UniformLocation,
     pub point_shadow_texture: UniformLocation,
+    pub static_shadow_texture: UniformLocation,
+    pub static_shadows_enabled: UniformLocation,
     pub shadows_enabled: UniformLocation,
     pub soft_shadows: UniformLocation,
     pub light_position: UniformLocation,
@@ -43,6 +45,10 @@ impl PointLightShader {
                 .uniform_location(state, &ImmutableString::new("materialTexture"))?,
             point_shadow_texture: program
                 .uniform_location(state, &ImmutableString::new("pointShadowTexture"))?,
+            static_shadow_texture: program
+                .uniform_location(state, &ImmutableString::new("staticShadowTexture"))?,
+            static_shadows_enabled: program
+                .uniform_location(state, &ImmutableString::new("staticShadowsEnabled"))?,
             shadows_enabled: program
                 .uniform_location(state, &ImmutableString::new("shadowsEnabled"))?,
             soft_shadows: program.uniform_location(state, &ImmutableString::new("softShadows"))?,
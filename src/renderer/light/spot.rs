@@ -13,6 +13,9 @@ pub struct SpotLightShader {
     pub normal_sampler: UniformLocation,
     pub material_sampler: UniformLocation,
     pub spot_shadow_texture: UniformLocation,
+    pub static_shadow_texture: UniformLocation,
+    pub static_shadows_enabled: UniformLocation,
+    pub static_shadow_map_inv_size: UniformLocation,
     pub cookie_enabled: UniformLocation,
     pub cookie_texture: UniformLocation,
     pub light_view_proj_matrix: UniformLocation,
@@ -50,6 +53,12 @@ impl SpotLightShader {
                 .uniform_location(state, &ImmutableString::new("materialTexture"))?,
             spot_shadow_texture: program
                 .uniform_location(state, &ImmutableString::new("spotShadowTexture"))?,
+            static_shadow_texture: program
+                .uniform_location(state, &ImmutableString::new("staticShadowTexture"))?,
+            static_shadows_enabled: program
+                .uniform_location(state, &ImmutableString::new("staticShadowsEnabled"))?,
+            static_shadow_map_inv_size: program
+                .uniform_location(state, &ImmutableString::new("staticShadowMapInvSize"))?,
             cookie_enabled: program
                 .uniform_location(state, &ImmutableString::new("cookieEnabled"))?,
             cookie_texture: program
@@ -81,3 +90,85 @@ impl SpotLightShader {
         })
     }
 }
+
+pub struct OrthoSpotLightShader {
+    pub program: GpuProgram,
+    pub wvp_matrix: UniformLocation,
+    pub depth_sampler: UniformLocation,
+    pub color_sampler: UniformLocation,
+    pub normal_sampler: UniformLocation,
+    pub material_sampler: UniformLocation,
+    pub spot_shadow_texture: UniformLocation,
+    pub static_shadow_texture: UniformLocation,
+    pub static_shadows_enabled: UniformLocation,
+    pub static_shadow_map_inv_size: UniformLocation,
+    pub cookie_enabled: UniformLocation,
+    pub cookie_texture: UniformLocation,
+    pub light_view_proj_matrix: UniformLocation,
+    pub shadows_enabled: UniformLocation,
+    pub soft_shadows: UniformLocation,
+    pub shadow_map_inv_size: UniformLocation,
+    pub light_color: UniformLocation,
+    pub light_direction: UniformLocation,
+    pub edge_falloff: UniformLocation,
+    pub inv_view_proj_matrix: UniformLocation,
+    pub camera_position: UniformLocation,
+    pub shadow_bias: UniformLocation,
+    pub light_intensity: UniformLocation,
+}
+
+impl OrthoSpotLightShader {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("../shaders/deferred_ortho_spot_light_fs.glsl");
+        let vertex_source = include_str!("../shaders/deferred_light_vs.glsl");
+        let program = GpuProgram::from_source(
+            state,
+            "OrthoSpotLightShader",
+            vertex_source,
+            fragment_source,
+        )?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            depth_sampler: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            color_sampler: program
+                .uniform_location(state, &ImmutableString::new("colorTexture"))?,
+            normal_sampler: program
+                .uniform_location(state, &ImmutableString::new("normalTexture"))?,
+            material_sampler: program
+                .uniform_location(state, &ImmutableString::new("materialTexture"))?,
+            spot_shadow_texture: program
+                .uniform_location(state, &ImmutableString::new("spotShadowTexture"))?,
+            static_shadow_texture: program
+                .uniform_location(state, &ImmutableString::new("staticShadowTexture"))?,
+            static_shadows_enabled: program
+                .uniform_location(state, &ImmutableString::new("staticShadowsEnabled"))?,
+            static_shadow_map_inv_size: program
+                .uniform_location(state, &ImmutableString::new("staticShadowMapInvSize"))?,
+            cookie_enabled: program
+                .uniform_location(state, &ImmutableString::new("cookieEnabled"))?,
+            cookie_texture: program
+                .uniform_location(state, &ImmutableString::new("cookieTexture"))?,
+            light_view_proj_matrix: program
+                .uniform_location(state, &ImmutableString::new("lightViewProjMatrix"))?,
+            shadows_enabled: program
+                .uniform_location(state, &ImmutableString::new("shadowsEnabled"))?,
+            soft_shadows: program.uniform_location(state, &ImmutableString::new("softShadows"))?,
+            shadow_map_inv_size: program
+                .uniform_location(state, &ImmutableString::new("shadowMapInvSize"))?,
+            light_color: program.uniform_location(state, &ImmutableString::new("lightColor"))?,
+            light_direction: program
+                .uniform_location(state, &ImmutableString::new("lightDirection"))?,
+            edge_falloff: program.uniform_location(state, &ImmutableString::new("edgeFalloff"))?,
+            inv_view_proj_matrix: program
+                .uniform_location(state, &ImmutableString::new("invViewProj"))?,
+            camera_position: program
+                .uniform_location(state, &ImmutableString::new("cameraPosition"))?,
+            shadow_bias: program.uniform_location(state, &ImmutableString::new("shadowBias"))?,
+            light_intensity: program
+                .uniform_location(state, &ImmutableString::new("lightIntensity"))?,
+            program,
+        })
+    }
+}
@@ -1,5 +1,5 @@
 use crate::scene::light::point::PointLight;
-use crate::scene::light::spot::SpotLight;
+use crate::scene::light::spot::{SpotLight, SpotLightProjection};
 use crate::{
     core::{
         algebra::{Isometry3, Matrix4, Point3, Translation, Vector3},
@@ -35,6 +35,7 @@ struct SpotLightShader {
     cone_angle_cos: UniformLocation,
     light_color: UniformLocation,
     scatter_factor: UniformLocation,
+    scatter_anisotropy: UniformLocation,
     inv_proj: UniformLocation,
 }
 
@@ -58,6 +59,8 @@ impl SpotLightShader {
             light_color: program.uniform_location(state, &ImmutableString::new("lightColor"))?,
             scatter_factor: program
                 .uniform_location(state, &ImmutableString::new("scatterFactor"))?,
+            scatter_anisotropy: program
+                .uniform_location(state, &ImmutableString::new("scatterAnisotropy"))?,
             inv_proj: program.uniform_location(state, &ImmutableString::new("invProj"))?,
             program,
         })
@@ -72,6 +75,7 @@ struct PointLightShader {
     light_radius: UniformLocation,
     light_color: UniformLocation,
     scatter_factor: UniformLocation,
+    scatter_anisotropy: UniformLocation,
     inv_proj: UniformLocation,
 }
 
@@ -97,6 +101,8 @@ impl PointLightShader {
             light_color: program.uniform_location(state, &ImmutableString::new("lightColor"))?,
             scatter_factor: program
                 .uniform_location(state, &ImmutableString::new("scatterFactor"))?,
+            scatter_anisotropy: program
+                .uniform_location(state, &ImmutableString::new("scatterAnisotropy"))?,
             program,
         })
     }
@@ -171,7 +177,10 @@ impl LightVolumeRenderer {
             .coords;
 
         if let Some(spot) = light.cast::<SpotLight>() {
-            if !spot.base_light_ref().is_scatter_enabled() {
+            // Light scattering is not supported for projector lights.
+            if !spot.base_light_ref().is_scatter_enabled()
+                || spot.projection() == SpotLightProjection::Orthographic
+            {
                 return stats;
             }
 
@@ -222,6 +231,8 @@ impl LightVolumeRenderer {
                         zpass: StencilAction::Replace,
                         write_mask: 0xFFFF_FFFF,
                     },
+                    alpha_to_coverage: false,
+                    depth_func: Default::default(),
                 },
                 |mut program_binding| {
                     program_binding.set_matrix4(&self.flat_shader.wvp_matrix, &mvp);
@@ -257,6 +268,8 @@ impl LightVolumeRenderer {
                         zpass: StencilAction::Zero,
                         ..Default::default()
                     },
+                    alpha_to_coverage: false,
+                    depth_func: Default::default(),
                 },
                 |mut program_binding| {
                     program_binding
@@ -270,7 +283,14 @@ impl LightVolumeRenderer {
                             &shader.light_color,
                             &spot.base_light_ref().color().srgb_to_linear_f32().xyz(),
                         )
-                        .set_vector3(&shader.scatter_factor, &spot.base_light_ref().scatter());
+                        .set_vector3(
+                            &shader.scatter_factor,
+                            &spot.base_light_ref().effective_scatter(),
+                        )
+                        .set_f32(
+                            &shader.scatter_anisotropy,
+                            spot.base_light_ref().scatter_anisotropy(),
+                        );
                 },
             )
         } else if let Some(point) = light.cast::<PointLight>() {
@@ -310,6 +330,8 @@ impl LightVolumeRenderer {
                         zpass: StencilAction::Replace,
                         write_mask: 0xFFFF_FFFF,
                     },
+                    alpha_to_coverage: false,
+                    depth_func: Default::default(),
                 },
                 |mut program_binding| {
                     program_binding.set_matrix4(&self.flat_shader.wvp_matrix, &mvp);
@@ -345,6 +367,8 @@ impl LightVolumeRenderer {
                         zpass: StencilAction::Zero,
                         ..Default::default()
                     },
+                    alpha_to_coverage: false,
+                    depth_func: Default::default(),
                 },
                 |mut program_binding| {
                     program_binding
@@ -357,7 +381,14 @@ impl LightVolumeRenderer {
                             &shader.light_color,
                             &point.base_light_ref().color().srgb_to_linear_f32().xyz(),
                         )
-                        .set_vector3(&shader.scatter_factor, &point.base_light_ref().scatter());
+                        .set_vector3(
+                            &shader.scatter_factor,
+                            &point.base_light_ref().effective_scatter(),
+                        )
+                        .set_f32(
+                            &shader.scatter_anisotropy,
+                            point.base_light_ref().scatter_anisotropy(),
+                        );
                 },
             )
         }
@@ -0,0 +1,201 @@
+//! Material override replaces materials of every mesh rendered by a camera, see [`MaterialOverride`]
+//! docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2},
+        color::Color,
+        math::Rect,
+        parking_lot::Mutex,
+        scope_profile,
+        sstorage::ImmutableString,
+    },
+    material::Material,
+    renderer::{
+        apply_material,
+        batch::BatchStorage,
+        cache::{shader::ShaderCache, texture::TextureCache},
+        framework::{framebuffer::FrameBuffer, gpu_texture::GpuTexture, state::PipelineState},
+        GeometryCache, MaterialContext, RenderPassStatistics,
+    },
+    scene::camera::Camera,
+};
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+/// Material override replaces materials of every mesh (both static and skinned) rendered by a
+/// camera, it is used for debug visualizations such as UV checker, normals, overdraw, etc. Scene
+/// materials are not modified, so removing the override restores the usual rendering.
+///
+/// Lighting and every post effect (bloom, tone mapping, anti-aliasing) are skipped for the
+/// camera, the override material is rendered directly into the final (low dynamic range) frame.
+/// Particle systems, sprites and 2D nodes are not rendered as well.
+///
+/// # Shader
+///
+/// The shader of the material must have `Forward` render pass, draw parameters of the pass are
+/// used as is, so the pass could use additive blending without depth test to visualize overdraw
+/// for example. Built-in variables (including bone matrices) are available in the same way as for
+/// any other material.
+#[derive(Clone, Debug)]
+pub struct MaterialOverride {
+    /// A material that is used instead of the materials of meshes.
+    pub material: Arc<Mutex<Material>>,
+    /// Names of properties that are copied from the original material of a mesh, if the override
+    /// material has a property with the same name and kind. For example, `lightmapTexture` could
+    /// be used to visualize density of lightmaps.
+    pub inherited_properties: Vec<ImmutableString>,
+    /// A color that is used to clear the frame before rendering.
+    pub clear_color: Color,
+}
+
+impl MaterialOverride {
+    /// Creates new material override without inherited properties.
+    pub fn new(material: Material) -> Self {
+        Self {
+            material: Arc::new(Mutex::new(material)),
+            inherited_properties: Default::default(),
+            clear_color: Color::opaque(40, 40, 40),
+        }
+    }
+
+    /// Sets names of properties that are copied from the original materials, see
+    /// [`Self::inherited_properties`] docs.
+    pub fn with_inherited_properties(mut self, properties: Vec<ImmutableString>) -> Self {
+        self.inherited_properties = properties;
+        self
+    }
+
+    /// Sets a color that is used to clear the frame before rendering.
+    pub fn with_clear_color(mut self, color: Color) -> Self {
+        self.clear_color = color;
+        self
+    }
+}
+
+pub(crate) struct MaterialOverrideRenderer {
+    render_pass_name: ImmutableString,
+}
+
+pub(crate) struct MaterialOverrideRenderContext<'a, 'b> {
+    pub state: &'a mut PipelineState,
+    pub camera: &'b Camera,
+    pub geom_cache: &'a mut GeometryCache,
+    pub texture_cache: &'a mut TextureCache,
+    pub shader_cache: &'a mut ShaderCache,
+    pub batch_storage: &'a BatchStorage,
+    pub framebuffer: &'a mut FrameBuffer,
+    pub viewport: Rect<i32>,
+    pub material_override: &'b MaterialOverride,
+    pub white_dummy: Rc<RefCell<GpuTexture>>,
+    pub normal_dummy: Rc<RefCell<GpuTexture>>,
+    pub black_dummy: Rc<RefCell<GpuTexture>>,
+}
+
+impl MaterialOverrideRenderer {
+    pub(crate) fn new() -> Self {
+        Self {
+            render_pass_name: ImmutableString::new("Forward"),
+        }
+    }
+
+    pub(crate) fn render(&self, args: MaterialOverrideRenderContext) -> RenderPassStatistics {
+        scope_profile!();
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let MaterialOverrideRenderContext {
+            state,
+            camera,
+            geom_cache,
+            texture_cache,
+            shader_cache,
+            batch_storage,
+            framebuffer,
+            viewport,
+            material_override,
+            white_dummy,
+            normal_dummy,
+            black_dummy,
+        } = args;
+
+        framebuffer.clear(
+            state,
+            viewport,
+            Some(material_override.clear_color),
+            Some(1.0),
+            Some(0),
+        );
+
+        let override_material = material_override.material.lock();
+
+        let render_pass = match shader_cache
+            .get(state, override_material.shader())
+            .and_then(|shader_set| shader_set.render_passes.get(&self.render_pass_name))
+        {
+            Some(render_pass) => render_pass,
+            None => return statistics,
+        };
+
+        let view_projection = camera.view_projection_matrix();
+
+        for batch in batch_storage.batches.iter() {
+            let geometry = geom_cache.get(state, &batch.data);
+
+            // Every batch gets its own copy of the material only if some properties must be taken
+            // from the original material.
+            let inherited_material = if material_override.inherited_properties.is_empty() {
+                None
+            } else {
+                let original_material = batch.material.lock();
+                let mut material = override_material.clone();
+                for name in material_override.inherited_properties.iter() {
+                    if let Some(value) = original_material.property_ref(name) {
+                        // Mismatching properties are just ignored.
+                        let _ = material.set_property(name, value.clone());
+                    }
+                }
+                Some(material)
+            };
+            let material = inherited_material.as_ref().unwrap_or(&*override_material);
+
+            for instance in batch.instances.iter() {
+                if camera.visibility_cache.is_visible(instance.owner) {
+                    let wvp_matrix: Matrix4<f32> = view_projection * instance.world_transform;
+
+                    statistics += framebuffer.draw(
+                        geometry,
+                        state,
+                        viewport,
+                        &render_pass.program,
+                        &render_pass.draw_params,
+                        |mut program_binding| {
+                            apply_material(MaterialContext {
+                                material,
+                                program_binding: &mut program_binding,
+                                texture_cache,
+                                world_matrix: &instance.world_transform,
+                                wvp_matrix: &wvp_matrix,
+                                bone_matrices: batch_storage.instance_bone_matrices(instance),
+                                bone_matrices_offset: instance.bone_matrices.start,
+                                bone_matrices_storage: batch_storage.bone_matrices_texture(),
+                                use_skeletal_animation: batch.is_skinned,
+                                camera_position: &camera.global_position(),
+                                use_pom: false,
+                                light_position: &Default::default(),
+                                prev_wvp_matrix: &wvp_matrix,
+                                prev_bone_matrices_storage: None,
+                                jitter: &Vector2::default(),
+                                wind: &batch_storage.wind,
+                                normal_dummy: normal_dummy.clone(),
+                                white_dummy: white_dummy.clone(),
+                                black_dummy: black_dummy.clone(),
+                            });
+                        },
+                    );
+                }
+            }
+        }
+
+        statistics
+    }
+}
@@ -15,12 +15,17 @@ pub mod framework;
 
 pub mod batch;
 pub mod cache;
+pub mod capabilities;
+pub mod color_management;
 pub mod debug_renderer;
+pub mod material_override;
+pub mod outline;
 pub mod renderer2d;
 pub mod ui_renderer;
 
 mod bloom;
 mod flat_shader;
+mod fog_volume;
 mod forward_renderer;
 mod fxaa;
 mod gbuffer;
@@ -32,14 +37,16 @@ mod shadow;
 mod skybox_shader;
 mod sprite_renderer;
 mod ssao;
+mod storage;
+mod taa;
 
 use crate::{
     core::{
-        algebra::{Matrix4, Vector2, Vector3},
+        algebra::{Matrix4, Vector2, Vector3, Vector4},
         color::Color,
         inspect::{Inspect, PropertyInfo},
         instant,
-        math::Rect,
+        math::{frustum::Frustum, Rect},
         pool::Handle,
         scope_profile,
     },
@@ -50,11 +57,17 @@ use crate::{
         Material, PropertyValue,
     },
     renderer::{
-        batch::BatchStorage,
+        batch::{BatchStorage, BONE_MATRICES_COUNT},
         bloom::BloomRenderer,
         cache::{geometry::GeometryCache, shader::ShaderCache, texture::TextureCache, CacheEntry},
-        debug_renderer::DebugRenderer,
+        capabilities::{RendererCapabilities, RendererFeature},
+        color_management::{
+            composition_target, ColorManagementSettings, OutputCapabilities, OutputRenderer,
+            OutputTarget,
+        },
+        debug_renderer::{DebugRenderer, DebugTextRenderContext},
         flat_shader::FlatShader,
+        fog_volume::{FogVolumeRenderContext, FogVolumeRenderer, FogVolumeStatistics},
         forward_renderer::{ForwardRenderContext, ForwardRenderer},
         framework::{
             error::FrameworkError,
@@ -68,16 +81,32 @@ use crate::{
             state::{PipelineState, PipelineStatistics},
         },
         fxaa::FxaaRenderer,
-        gbuffer::{GBuffer, GBufferRenderContext},
+        gbuffer::{GBuffer, GBufferRenderContext, GBufferStatistics},
         hdr::HighDynamicRangeRenderer,
         light::{DeferredLightRenderer, DeferredRendererContext, LightingStatistics},
+        material_override::{
+            MaterialOverride, MaterialOverrideRenderContext, MaterialOverrideRenderer,
+        },
+        outline::{OutlineRenderContext, OutlineRenderer, OutlineSettings},
         particle_system_renderer::{ParticleSystemRenderContext, ParticleSystemRenderer},
         renderer2d::Renderer2d,
         sprite_renderer::{SpriteRenderContext, SpriteRenderer},
+        ssao::SsaoHistory,
+        taa::{TaaHistory, TaaRenderer, TaaResolveContext},
         ui_renderer::{UiRenderContext, UiRenderer},
     },
-    resource::texture::{Texture, TextureKind},
-    scene::{camera::Camera, mesh::surface::SurfaceData, Scene, SceneContainer},
+    resource::texture::{
+        Texture, TextureKind, TextureMinificationFilter, TexturePixelKind, TextureWrapMode,
+    },
+    scene::{
+        base::BaseBuilder,
+        camera::{Camera, CameraBuilder},
+        mesh::surface::SurfaceData,
+        node::Node,
+        reflection_probe::ReflectionProbe,
+        wind::Wind,
+        Scene, SceneContainer,
+    },
     utils::log::{Log, MessageKind},
 };
 use fxhash::FxHashMap;
@@ -86,6 +115,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     cell::RefCell,
     collections::hash_map::Entry,
+    f32::consts::FRAC_PI_2,
     fmt::{Display, Formatter},
     rc::Rc,
     sync::mpsc::Receiver,
@@ -102,6 +132,10 @@ pub struct Statistics {
     pub lighting: LightingStatistics,
     /// Shows how many draw calls was made and how many triangles were rendered.
     pub geometry: RenderPassStatistics,
+    /// Shows how many fog volumes were rendered and how expensive they were.
+    pub fog: FogVolumeStatistics,
+    /// Shows whether depth pre-pass was used and how much time GPU spent on the G-Buffer.
+    pub gbuffer: GBufferStatistics,
     /// Real time consumed to render frame. Time given in **seconds**.
     pub pure_frame_time: f32,
     /// Total time renderer took to process single frame, usually includes
@@ -124,12 +158,16 @@ impl Display for Statistics {
             Capped Frame Time: {:.2} ms\n\
             {}\n\
             {}\n\
+            {}\n\
+            {}\n\
             {}\n",
             self.frames_per_second,
             self.pure_frame_time * 1000.0,
             self.capped_frame_time * 1000.0,
             self.geometry,
+            self.gbuffer,
             self.lighting,
+            self.fog,
             self.pipeline
         )
     }
@@ -175,6 +213,13 @@ impl std::ops::AddAssign<RenderPassStatistics> for Statistics {
     }
 }
 
+impl std::ops::AddAssign<GBufferStatistics> for Statistics {
+    fn add_assign(&mut self, rhs: GBufferStatistics) {
+        self.geometry += rhs.pass;
+        self.gbuffer += rhs;
+    }
+}
+
 /// Shadow map precision allows you to select compromise between quality and performance.
 #[derive(
     Copy,
@@ -229,6 +274,183 @@ impl Default for CsmSettings {
     }
 }
 
+/// Quality tier of screen-space ambient occlusion, it is a preset of [`SsaoSettings`].
+#[derive(
+    Copy,
+    Clone,
+    Hash,
+    PartialOrd,
+    PartialEq,
+    Eq,
+    Ord,
+    Debug,
+    Serialize,
+    Deserialize,
+    Inspect,
+    AsRefStr,
+    EnumString,
+    EnumVariantNames,
+)]
+pub enum SsaoQuality {
+    /// Few samples, no temporal accumulation. Noticeable noise, but very cheap.
+    Low,
+    /// Moderate amount of samples, no temporal accumulation.
+    Medium,
+    /// Many samples at increased resolution, temporal accumulation and bent normals.
+    High,
+    /// Maximum amount of samples at full resolution, temporal accumulation and bent normals.
+    Ultra,
+}
+
+/// Maximum amount of samples per pixel of screen-space ambient occlusion.
+pub const MAX_SSAO_SAMPLES: usize = 64;
+
+/// Maximum amount of blur passes of screen-space ambient occlusion.
+pub const MAX_SSAO_BLUR_PASSES: usize = 4;
+
+/// Screen-space ambient occlusion settings. Use [`SsaoSettings::from_quality`] to get a preset
+/// for a quality tier.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Inspect)]
+pub struct SsaoSettings {
+    /// A tier the settings were created from. Changing the tier in the editor resets every other
+    /// setting to the preset of the tier.
+    pub quality: SsaoQuality,
+
+    /// Amount of samples per pixel, up to [`MAX_SSAO_SAMPLES`]. The more samples, the less noise
+    /// there is, but the slower rendering is.
+    #[inspect(min_value = 1.0, max_value = 64.0, step = 1.0)]
+    pub sample_count: usize,
+
+    /// Resolution of ambient occlusion map relative to the frame size. The map is upscaled when
+    /// lighting is applied, so lower resolution gives blurrier but faster result.
+    #[inspect(min_value = 0.25, max_value = 1.0, step = 0.05)]
+    pub resolution_scale: f32,
+
+    /// Amount of depth-aware blur passes, up to [`MAX_SSAO_BLUR_PASSES`]. Every pass consists of
+    /// horizontal and vertical blur. Zero disables the blur.
+    #[inspect(min_value = 0.0, max_value = 4.0, step = 1.0)]
+    pub blur_passes: usize,
+
+    /// Whether to accumulate ambient occlusion over multiple frames or not. Every frame uses a
+    /// different sampling pattern, so accumulation greatly reduces noise, but it may produce
+    /// slight ghosting on fast moving objects.
+    pub temporal_accumulation: bool,
+
+    /// Whether to calculate bent normals (average unoccluded directions) or not. They are used by
+    /// the lighting pass to occlude reflections of the environment more accurately.
+    pub bent_normals: bool,
+}
+
+impl Default for SsaoSettings {
+    fn default() -> Self {
+        Self::from_quality(SsaoQuality::High)
+    }
+}
+
+impl SsaoSettings {
+    /// Returns a preset of the given quality tier.
+    pub fn from_quality(quality: SsaoQuality) -> Self {
+        match quality {
+            SsaoQuality::Low => Self {
+                quality,
+                sample_count: 8,
+                resolution_scale: 0.5,
+                blur_passes: 1,
+                temporal_accumulation: false,
+                bent_normals: false,
+            },
+            SsaoQuality::Medium => Self {
+                quality,
+                sample_count: 16,
+                resolution_scale: 0.5,
+                blur_passes: 1,
+                temporal_accumulation: false,
+                bent_normals: false,
+            },
+            SsaoQuality::High => Self {
+                quality,
+                sample_count: 32,
+                resolution_scale: 0.75,
+                blur_passes: 2,
+                temporal_accumulation: true,
+                bent_normals: true,
+            },
+            SsaoQuality::Ultra => Self {
+                quality,
+                sample_count: 64,
+                resolution_scale: 1.0,
+                blur_passes: 2,
+                temporal_accumulation: true,
+                bent_normals: true,
+            },
+        }
+    }
+
+    /// Returns the settings with every value clamped to its valid range.
+    pub fn clamped(&self) -> Self {
+        Self {
+            sample_count: self.sample_count.clamp(1, MAX_SSAO_SAMPLES),
+            resolution_scale: self.resolution_scale.clamp(0.25, 1.0),
+            blur_passes: self.blur_passes.min(MAX_SSAO_BLUR_PASSES),
+            ..*self
+        }
+    }
+}
+
+/// Maximum amount of screen-space tiles along each axis of the light cluster grid.
+pub const MAX_LIGHT_CLUSTER_TILES: usize = 64;
+
+/// Maximum amount of depth slices of the light cluster grid.
+pub const MAX_LIGHT_CLUSTER_DEPTH_SLICES: usize = 64;
+
+/// Light clustering settings. Clustering splits the view frustum into a grid of clusters (screen
+/// tiles that are split into depth slices) and bins visible lights into the clusters they touch.
+/// Point and spot lights that do not cast shadows are then shaded in a single full-screen pass,
+/// where every pixel iterates over the lights of its cluster only. Lights that cast shadows are
+/// still drawn one by one.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Inspect)]
+pub struct LightClusterSettings {
+    /// Whether to use light clustering or not. When disabled, every light is drawn separately,
+    /// which is fine for scenes with a few lights.
+    pub enabled: bool,
+
+    /// Amount of screen-space tiles along the horizontal axis of the screen.
+    #[inspect(min_value = 1.0, max_value = 64.0, step = 1.0)]
+    pub tiles_x: usize,
+
+    /// Amount of screen-space tiles along the vertical axis of the screen.
+    #[inspect(min_value = 1.0, max_value = 64.0, step = 1.0)]
+    pub tiles_y: usize,
+
+    /// Amount of depth slices, they are distributed exponentially between the near and far
+    /// clipping planes of a camera, so the slices close to the camera are thinner.
+    #[inspect(min_value = 1.0, max_value = 64.0, step = 1.0)]
+    pub depth_slices: usize,
+}
+
+impl Default for LightClusterSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            tiles_x: 16,
+            tiles_y: 9,
+            depth_slices: 24,
+        }
+    }
+}
+
+impl LightClusterSettings {
+    /// Returns the settings with every value clamped to its valid range.
+    pub fn clamped(&self) -> Self {
+        Self {
+            tiles_x: self.tiles_x.clamp(1, MAX_LIGHT_CLUSTER_TILES),
+            tiles_y: self.tiles_y.clamp(1, MAX_LIGHT_CLUSTER_TILES),
+            depth_slices: self.depth_slices.clamp(1, MAX_LIGHT_CLUSTER_DEPTH_SLICES),
+            ..*self
+        }
+    }
+}
+
 /// Quality settings allows you to find optimal balance between performance and
 /// graphics quality.
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Inspect)]
@@ -259,6 +481,13 @@ pub struct QualitySettings {
     /// quality and performance.
     pub spot_shadow_map_precision: ShadowMapPrecision,
 
+    /// Multiplier for maximum shadows distances of every point and spot light (including the
+    /// `point_shadows_distance` and `spot_shadows_distance`). It allows lower presets to shrink
+    /// every distance uniformly, without changing each light.
+    #[serde(default = "default_shadows_distance_multiplier")]
+    #[inspect(min_value = 0.0, step = 0.05)]
+    pub shadows_distance_multiplier: f32,
+
     /// Cascaded-shadow maps settings.
     pub csm_settings: CsmSettings,
 
@@ -267,19 +496,52 @@ pub struct QualitySettings {
     /// Radius of sampling hemisphere used in SSAO, it defines much ambient
     /// occlusion will be in your scene.
     pub ssao_radius: f32,
+    /// Quality of screen space ambient occlusion, see [`SsaoSettings`] docs for more info.
+    #[serde(default)]
+    pub ssao_settings: SsaoSettings,
+
+    /// Light clustering settings, see [`LightClusterSettings`] docs for more info.
+    #[serde(default)]
+    pub light_cluster_settings: LightClusterSettings,
 
     /// Global switch to enable or disable light scattering. Each light can have
     /// its own scatter switch, but this one is able to globally disable scatter.
     pub light_scatter_enabled: bool,
 
-    /// Whether to use Fast Approximate AntiAliasing or not.
+    /// Amount of ray-marching steps per pixel for fog volumes. The more steps, the more precise
+    /// the scattering of light in the fog is, but the slower rendering is. Zero means that fog
+    /// volumes will be rendered as a cheap analytic exponential fog without light scattering.
+    #[serde(default)]
+    pub fog_volume_samples: u32,
+
+    /// Whether to use Fast Approximate AntiAliasing or not. It is ignored if temporal
+    /// anti-aliasing is enabled.
     pub fxaa: bool,
 
+    /// Whether to use Temporal AntiAliasing or not. It gives much better quality than FXAA on
+    /// thin geometry and alpha-tested edges, but it may produce slight ghosting on fast moving
+    /// objects.
+    #[serde(default)]
+    pub taa: bool,
+
     /// Whether to use Parallax Mapping or not.
     pub use_parallax_mapping: bool,
 
     /// Whether to use bloom effect.
     pub use_bloom: bool,
+
+    /// Whether to render depth of opaque geometry before the G-Buffer pass or not. When enabled,
+    /// the G-Buffer pass shades every pixel only once, which helps in scenes with lots of
+    /// overdraw and expensive materials (dense foliage, overlapping props, etc.). It doubles the
+    /// amount of vertex work though, so scenes that are limited by vertex processing could become
+    /// slower. Only materials with `DepthPrePass` render pass (such as the standard material)
+    /// are rendered in the pre-pass, see renderer statistics to compare timings of the passes.
+    #[serde(default)]
+    pub use_depth_prepass: bool,
+}
+
+fn default_shadows_distance_multiplier() -> f32 {
+    1.0
 }
 
 impl Default for QualitySettings {
@@ -302,18 +564,26 @@ impl QualitySettings {
             spot_shadows_enabled: true,
             spot_soft_shadows: true,
 
+            shadows_distance_multiplier: 1.0,
+
             use_ssao: true,
             ssao_radius: 0.5,
+            ssao_settings: SsaoSettings::from_quality(SsaoQuality::Ultra),
+            light_cluster_settings: LightClusterSettings::default(),
 
             light_scatter_enabled: true,
+            fog_volume_samples: 64,
 
             point_shadow_map_precision: ShadowMapPrecision::Full,
             spot_shadow_map_precision: ShadowMapPrecision::Full,
 
             fxaa: true,
+            taa: false,
 
             use_bloom: true,
 
+            use_depth_prepass: false,
+
             use_parallax_mapping: false, // TODO: Enable when it is fixed!
 
             csm_settings: Default::default(),
@@ -333,18 +603,26 @@ impl QualitySettings {
             spot_shadows_enabled: true,
             spot_soft_shadows: true,
 
+            shadows_distance_multiplier: 1.0,
+
             use_ssao: true,
             ssao_radius: 0.5,
+            ssao_settings: SsaoSettings::from_quality(SsaoQuality::High),
+            light_cluster_settings: LightClusterSettings::default(),
 
             light_scatter_enabled: true,
+            fog_volume_samples: 32,
 
             point_shadow_map_precision: ShadowMapPrecision::Full,
             spot_shadow_map_precision: ShadowMapPrecision::Full,
 
             fxaa: true,
+            taa: false,
 
             use_bloom: true,
 
+            use_depth_prepass: false,
+
             use_parallax_mapping: false, // TODO: Enable when it is fixed!
 
             csm_settings: CsmSettings {
@@ -369,18 +647,31 @@ impl QualitySettings {
             spot_shadows_enabled: true,
             spot_soft_shadows: false,
 
+            shadows_distance_multiplier: 0.75,
+
             use_ssao: true,
             ssao_radius: 0.5,
+            ssao_settings: SsaoSettings::from_quality(SsaoQuality::Medium),
+            light_cluster_settings: LightClusterSettings {
+                enabled: true,
+                tiles_x: 16,
+                tiles_y: 9,
+                depth_slices: 16,
+            },
 
             light_scatter_enabled: false,
+            fog_volume_samples: 8,
 
             point_shadow_map_precision: ShadowMapPrecision::Half,
             spot_shadow_map_precision: ShadowMapPrecision::Half,
 
             fxaa: true,
+            taa: false,
 
             use_bloom: true,
 
+            use_depth_prepass: false,
+
             use_parallax_mapping: false,
 
             csm_settings: CsmSettings {
@@ -405,18 +696,31 @@ impl QualitySettings {
             spot_shadows_enabled: false,
             spot_soft_shadows: false,
 
+            shadows_distance_multiplier: 0.5,
+
             use_ssao: false,
             ssao_radius: 0.5,
+            ssao_settings: SsaoSettings::from_quality(SsaoQuality::Low),
+            light_cluster_settings: LightClusterSettings {
+                enabled: true,
+                tiles_x: 8,
+                tiles_y: 6,
+                depth_slices: 16,
+            },
 
             light_scatter_enabled: false,
+            fog_volume_samples: 0,
 
             point_shadow_map_precision: ShadowMapPrecision::Half,
             spot_shadow_map_precision: ShadowMapPrecision::Half,
 
             fxaa: false,
+            taa: false,
 
             use_bloom: false,
 
+            use_depth_prepass: false,
+
             use_parallax_mapping: false,
 
             csm_settings: CsmSettings {
@@ -435,6 +739,8 @@ impl Statistics {
         self.frame_start_time = instant::Instant::now();
         self.geometry = Default::default();
         self.lighting = Default::default();
+        self.fog = Default::default();
+        self.gbuffer = Default::default();
     }
 
     /// Must be called before SwapBuffers but after all rendering is done.
@@ -471,6 +777,8 @@ impl Default for Statistics {
             pipeline: Default::default(),
             lighting: Default::default(),
             geometry: Default::default(),
+            fog: Default::default(),
+            gbuffer: Default::default(),
             pure_frame_time: 0.0,
             capped_frame_time: 0.0,
             frames_per_second: 0,
@@ -502,6 +810,21 @@ pub struct AssociatedSceneData {
     /// Bloom contains only overly bright pixels that creates light
     /// bleeding effect (glow effect).
     pub bloom_renderer: BloomRenderer,
+
+    // Accumulated frames of each camera of the scene, used by temporal anti-aliasing.
+    taa_history: FxHashMap<Handle<Node>, TaaHistory>,
+
+    // Accumulated ambient occlusion of each camera of the scene, used by temporal accumulation
+    // of SSAO.
+    ssao_history: FxHashMap<Handle<Node>, SsaoHistory>,
+
+    // Global transforms of the scene nodes from the previous frame, they're used to calculate
+    // motion vectors.
+    prev_transforms: FxHashMap<Handle<Node>, Matrix4<f32>>,
+
+    // Depth of highlighted nodes, it is created on first use, because most of the scenes never
+    // highlight anything.
+    outline_mask: Option<FrameBuffer>,
 }
 
 impl AssociatedSceneData {
@@ -510,6 +833,17 @@ impl AssociatedSceneData {
         state: &mut PipelineState,
         width: usize,
         height: usize,
+    ) -> Result<Self, FrameworkError> {
+        Self::with_ldr_pixel_kind(state, width, height, PixelKind::RGBA8)
+    }
+
+    /// Creates new scene data with the given pixel format of the final frame. HDR output needs a
+    /// floating point format, because the final frame keeps values above 1.0.
+    pub(in crate) fn with_ldr_pixel_kind(
+        state: &mut PipelineState,
+        width: usize,
+        height: usize,
+        ldr_pixel_kind: PixelKind,
     ) -> Result<Self, FrameworkError> {
         let mut depth_stencil_texture = GpuTexture::new(
             state,
@@ -554,7 +888,7 @@ impl AssociatedSceneData {
             state,
             GpuTextureKind::Rectangle { width, height },
             // Final scene frame is in standard sRGB space.
-            PixelKind::RGBA8,
+            ldr_pixel_kind,
             MinificationFilter::Linear,
             MagnificationFilter::Linear,
             1,
@@ -577,7 +911,7 @@ impl AssociatedSceneData {
             state,
             GpuTextureKind::Rectangle { width, height },
             // Final scene frame is in standard sRGB space.
-            PixelKind::RGBA8,
+            ldr_pixel_kind,
             MinificationFilter::Linear,
             MagnificationFilter::Linear,
             1,
@@ -603,6 +937,10 @@ impl AssociatedSceneData {
             hdr_scene_framebuffer,
             ldr_scene_framebuffer,
             ldr_temp_framebuffer,
+            taa_history: Default::default(),
+            ssao_history: Default::default(),
+            prev_transforms: Default::default(),
+            outline_mask: None,
         })
     }
 
@@ -638,6 +976,11 @@ impl AssociatedSceneData {
             .clone()
     }
 
+    /// Returns pixel format of the final frame.
+    pub fn ldr_pixel_kind(&self) -> PixelKind {
+        self.ldr_scene_frame_texture().borrow().pixel_kind()
+    }
+
     /// Returns low-dynamic range frame buffer texture (accumulation frame).
     pub fn ldr_temp_frame_texture(&self) -> Rc<RefCell<GpuTexture>> {
         self.ldr_temp_framebuffer.color_attachments()[0]
@@ -665,7 +1008,10 @@ pub(in crate) fn make_viewport_matrix(viewport: Rect<i32>) -> Matrix4<f32> {
 pub struct Renderer {
     backbuffer: FrameBuffer,
     scene_render_passes: Vec<Rc<RefCell<dyn SceneRenderPass>>>,
-    deferred_light_renderer: DeferredLightRenderer,
+    // `None` if deferred lighting is not supported, unlit rendering is used in this case.
+    deferred_light_renderer: Option<DeferredLightRenderer>,
+    capabilities: RendererCapabilities,
+    fog_volume_renderer: FogVolumeRenderer,
     flat_shader: FlatShader,
     sprite_renderer: SpriteRenderer,
     particle_system_renderer: ParticleSystemRenderer,
@@ -690,6 +1036,10 @@ pub struct Renderer {
     pub debug_renderer: DebugRenderer,
     /// A set of associated data for each scene that was rendered.
     pub scene_data_map: FxHashMap<Handle<Scene>, AssociatedSceneData>,
+    // Keys of render targets of scenes that are registered in the texture cache. Render targets
+    // never expire in the cache, so stale entries must be removed explicitly when a scene changes
+    // its render target or the scene is destroyed.
+    render_target_keys: FxHashMap<Handle<Scene>, usize>,
     backbuffer_clear_color: Color,
     /// Texture cache with GPU textures.
     pub texture_cache: TextureCache,
@@ -697,7 +1047,21 @@ pub struct Renderer {
     geometry_cache: GeometryCache,
     batch_storage: BatchStorage,
     forward_renderer: ForwardRenderer,
-    fxaa_renderer: FxaaRenderer,
+    fxaa_renderer: Option<FxaaRenderer>,
+    taa_renderer: TaaRenderer,
+    show_motion_vectors: bool,
+    outline_renderer: OutlineRenderer,
+    outline_settings: OutlineSettings,
+    highlighted_nodes: FxHashMap<Handle<Scene>, Vec<Handle<Node>>>,
+    material_override_renderer: MaterialOverrideRenderer,
+    material_overrides: FxHashMap<Handle<Scene>, FxHashMap<Handle<Node>, MaterialOverride>>,
+    color_management_settings: ColorManagementSettings,
+    output_capabilities: OutputCapabilities,
+    // Output target that is actually used, it differs from the target in the settings if the
+    // latter is not supported.
+    active_output_target: OutputTarget,
+    // `None` for sRGB output, everything goes directly to the back buffer in this case.
+    output_renderer: Option<OutputRenderer>,
     renderer2d: Renderer2d,
     texture_event_receiver: Receiver<ResourceEvent<Texture>>,
     shader_event_receiver: Receiver<ResourceEvent<Shader>>,
@@ -877,6 +1241,8 @@ fn blit_pixels(
             depth_test: false,
             blend: None,
             stencil_op: Default::default(),
+            alpha_to_coverage: false,
+            depth_func: Default::default(),
         },
         |mut program_binding| {
             program_binding
@@ -899,6 +1265,44 @@ fn blit_pixels(
     )
 }
 
+/// Fallback for the case when deferred lighting is not supported, it copies colors of materials
+/// from G-Buffer to the frame as is. Depth of the frame is left untouched, so forward passes still
+/// work correctly.
+fn render_unlit(
+    state: &mut PipelineState,
+    gbuffer: &GBuffer,
+    framebuffer: &mut FrameBuffer,
+    shader: &FlatShader,
+    quad: &GeometryBuffer,
+) -> RenderPassStatistics {
+    // Same viewport as in deferred lighting.
+    let viewport = Rect::new(0, 0, gbuffer.width, gbuffer.height);
+    let mut statistics = RenderPassStatistics::default();
+    statistics += framebuffer.draw(
+        quad,
+        state,
+        viewport,
+        &shader.program,
+        &DrawParameters {
+            cull_face: None,
+            color_write: Default::default(),
+            depth_write: false,
+            stencil_test: None,
+            depth_test: false,
+            blend: None,
+            stencil_op: Default::default(),
+            alpha_to_coverage: false,
+            depth_func: Default::default(),
+        },
+        |mut program_binding| {
+            program_binding
+                .set_matrix4(&shader.wvp_matrix, &make_viewport_matrix(viewport))
+                .set_texture(&shader.diffuse_texture, &gbuffer.diffuse_texture());
+        },
+    );
+    statistics
+}
+
 pub(in crate) struct MaterialContext<'a, 'b, 'c> {
     pub material: &'a Material,
     pub program_binding: &'a mut GpuProgramBinding<'b, 'c>,
@@ -908,10 +1312,16 @@ pub(in crate) struct MaterialContext<'a, 'b, 'c> {
     pub world_matrix: &'a Matrix4<f32>,
     pub wvp_matrix: &'a Matrix4<f32>,
     pub bone_matrices: &'a [Matrix4<f32>],
+    pub bone_matrices_offset: usize,
+    pub bone_matrices_storage: Option<&'a Rc<RefCell<GpuTexture>>>,
     pub use_skeletal_animation: bool,
     pub camera_position: &'a Vector3<f32>,
     pub use_pom: bool,
     pub light_position: &'a Vector3<f32>,
+    pub prev_wvp_matrix: &'a Matrix4<f32>,
+    pub prev_bone_matrices_storage: Option<&'a Rc<RefCell<GpuTexture>>>,
+    pub jitter: &'a Vector2<f32>,
+    pub wind: &'a Wind,
 
     // Fallback samplers.
     pub normal_dummy: Rc<RefCell<GpuTexture>>,
@@ -930,8 +1340,17 @@ pub(in crate) fn apply_material(ctx: MaterialContext) {
         ctx.program_binding.set_matrix4(location, ctx.wvp_matrix);
     }
     if let Some(location) = &built_in_uniforms[BuiltInUniform::BoneMatrices as usize] {
+        let count = ctx.bone_matrices.len().min(BONE_MATRICES_COUNT);
+        ctx.program_binding
+            .set_matrix4_array(location, &ctx.bone_matrices[..count]);
+    }
+    if let Some(location) = &built_in_uniforms[BuiltInUniform::BoneMatricesStorage as usize] {
+        let storage = ctx.bone_matrices_storage.unwrap_or(&ctx.black_dummy);
+        ctx.program_binding.set_texture(location, storage);
+    }
+    if let Some(location) = &built_in_uniforms[BuiltInUniform::BoneMatricesOffset as usize] {
         ctx.program_binding
-            .set_matrix4_array(location, ctx.bone_matrices);
+            .set_i32(location, ctx.bone_matrices_offset as i32);
     }
     if let Some(location) = &built_in_uniforms[BuiltInUniform::UseSkeletalAnimation as usize] {
         ctx.program_binding
@@ -948,6 +1367,33 @@ pub(in crate) fn apply_material(ctx: MaterialContext) {
         ctx.program_binding
             .set_vector3(location, ctx.light_position);
     }
+    if let Some(location) =
+        &built_in_uniforms[BuiltInUniform::PrevWorldViewProjectionMatrix as usize]
+    {
+        ctx.program_binding
+            .set_matrix4(location, ctx.prev_wvp_matrix);
+    }
+    if let Some(location) = &built_in_uniforms[BuiltInUniform::PrevBoneMatricesStorage as usize] {
+        // Previous pose is unknown, use current one - this gives zero motion of bones.
+        let storage = ctx
+            .prev_bone_matrices_storage
+            .or(ctx.bone_matrices_storage)
+            .unwrap_or(&ctx.black_dummy);
+        ctx.program_binding.set_texture(location, storage);
+    }
+    if let Some(location) = &built_in_uniforms[BuiltInUniform::Jitter as usize] {
+        ctx.program_binding.set_vector2(location, ctx.jitter);
+    }
+    let (wind, wind_noise) = ctx.wind.uniforms();
+    if let Some(location) = &built_in_uniforms[BuiltInUniform::Wind as usize] {
+        ctx.program_binding.set_vector4(location, &wind);
+    }
+    if let Some(location) = &built_in_uniforms[BuiltInUniform::WindNoise as usize] {
+        ctx.program_binding.set_vector4(location, &wind_noise);
+    }
+    if let Some(location) = &built_in_uniforms[BuiltInUniform::WindTime as usize] {
+        ctx.program_binding.set_f32(location, ctx.wind.time());
+    }
 
     // Apply material properties.
     for (name, value) in ctx.material.properties() {
@@ -1034,9 +1480,10 @@ impl Renderer {
     pub(in crate) fn new(
         context: glow::Context,
         frame_size: (u32, u32),
+        output_capabilities: OutputCapabilities,
         resource_manager: &ResourceManager,
     ) -> Result<Self, FrameworkError> {
-        let settings = QualitySettings::default();
+        let mut settings = QualitySettings::default();
 
         let (texture_event_sender, texture_event_receiver) = std::sync::mpsc::channel();
 
@@ -1066,10 +1513,41 @@ impl Renderer {
             state.gl.supported_extensions()
         ));
 
+        // Optional features are disabled if their shaders fail to compile, this way the renderer
+        // still works on old hardware, but in degraded mode.
+        let mut capabilities = RendererCapabilities::default();
+        let deferred_light_renderer =
+            DeferredLightRenderer::new(&mut state, frame_size, &settings, &mut capabilities);
+        let deferred_light_renderer =
+            capabilities.try_init(RendererFeature::Lighting, deferred_light_renderer);
+        if deferred_light_renderer.is_none() {
+            // Every other part of lighting is useless without lighting itself.
+            for feature in [
+                RendererFeature::Ssao,
+                RendererFeature::LightScattering,
+                RendererFeature::Csm,
+            ] {
+                if capabilities.is_active(feature) {
+                    capabilities.disable(feature, "Lighting is not supported.".to_owned());
+                }
+            }
+        }
+        let fxaa_renderer =
+            capabilities.try_init(RendererFeature::Fxaa, FxaaRenderer::new(&mut state));
+        capabilities.restrict(&mut settings);
+        if capabilities.is_degraded() {
+            Log::warn(format!(
+                "Renderer works in degraded mode, some features are not supported:\n{}",
+                capabilities
+            ));
+        }
+
         Ok(Self {
             backbuffer: FrameBuffer::backbuffer(&mut state),
             frame_size,
-            deferred_light_renderer: DeferredLightRenderer::new(&mut state, frame_size, &settings)?,
+            deferred_light_renderer,
+            capabilities,
+            fog_volume_renderer: FogVolumeRenderer::new(&mut state)?,
             flat_shader: FlatShader::new(&mut state)?,
             sprite_renderer: SpriteRenderer::new(&mut state)?,
             white_dummy: Rc::new(RefCell::new(GpuTexture::new(
@@ -1149,13 +1627,25 @@ impl Renderer {
             quality_settings: settings,
             debug_renderer: DebugRenderer::new(&mut state)?,
             scene_data_map: Default::default(),
+            render_target_keys: Default::default(),
             backbuffer_clear_color: Color::BLACK,
             texture_cache: Default::default(),
             geometry_cache: Default::default(),
             batch_storage: Default::default(),
             forward_renderer: ForwardRenderer::new(),
             ui_frame_buffers: Default::default(),
-            fxaa_renderer: FxaaRenderer::new(&mut state)?,
+            fxaa_renderer,
+            taa_renderer: TaaRenderer::new(&mut state)?,
+            show_motion_vectors: false,
+            outline_renderer: OutlineRenderer::new(&mut state)?,
+            outline_settings: Default::default(),
+            highlighted_nodes: Default::default(),
+            material_override_renderer: MaterialOverrideRenderer::new(),
+            material_overrides: Default::default(),
+            color_management_settings: Default::default(),
+            output_capabilities,
+            active_output_target: OutputTarget::Srgb,
+            output_renderer: None,
             statistics: Statistics::default(),
             renderer2d: Renderer2d::new(&mut state)?,
             shader_event_receiver,
@@ -1181,6 +1671,11 @@ impl Renderer {
         self.texture_cache.unload(texture)
     }
 
+    /// Unloads GPU texture of a texture resource that was unloaded by the resource manager.
+    pub(crate) fn unload_texture_by_key(&mut self, key: usize) {
+        self.texture_cache.unload_by_key(key)
+    }
+
     /// Sets color which will be used to fill screen when there is nothing to render.
     pub fn set_backbuffer_clear_color(&mut self, color: Color) {
         self.backbuffer_clear_color = color;
@@ -1203,8 +1698,13 @@ impl Renderer {
         self.frame_size.0 = new_size.0.max(1);
         self.frame_size.1 = new_size.1.max(1);
 
-        self.deferred_light_renderer
-            .set_frame_size(&mut self.state, new_size)?;
+        if let Some(deferred_light_renderer) = self.deferred_light_renderer.as_mut() {
+            deferred_light_renderer.set_frame_size(&mut self.state, new_size)?;
+        }
+
+        if let Some(output_renderer) = self.output_renderer.as_mut() {
+            output_renderer.set_frame_size(&mut self.state, self.frame_size)?;
+        }
 
         Ok(())
     }
@@ -1222,13 +1722,29 @@ impl Renderer {
     /// Sets new quality settings for renderer. Never call this method in a loop, otherwise
     /// you may get **significant** lags. Always check if current quality setting differs
     /// from new!
+    ///
+    /// Features that are not supported by hardware (see [`Self::capabilities`]) stay disabled,
+    /// regardless of the settings.
     pub fn set_quality_settings(
         &mut self,
         settings: &QualitySettings,
     ) -> Result<(), FrameworkError> {
         self.quality_settings = *settings;
-        self.deferred_light_renderer
-            .set_quality_settings(&mut self.state, settings)
+        self.capabilities.restrict(&mut self.quality_settings);
+        match self.deferred_light_renderer.as_mut() {
+            Some(deferred_light_renderer) => deferred_light_renderer.set_quality_settings(
+                &mut self.state,
+                self.frame_size,
+                &self.quality_settings,
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns a report of optional features of the renderer, that tells which features are not
+    /// supported by hardware and why.
+    pub fn capabilities(&self) -> &RendererCapabilities {
+        &self.capabilities
     }
 
     /// Returns current quality settings.
@@ -1236,6 +1752,146 @@ impl Renderer {
         self.quality_settings
     }
 
+    /// Enables or disables visualization of motion vectors, that are used by temporal
+    /// anti-aliasing. When enabled, the final frame of every scene is replaced with motion
+    /// vectors: red and green channels show horizontal and vertical motion respectively. Useful
+    /// for debugging ghosting issues.
+    pub fn set_show_motion_vectors(&mut self, show: bool) {
+        self.show_motion_vectors = show;
+    }
+
+    /// Returns `true` if motion vectors visualization is enabled.
+    pub fn is_showing_motion_vectors(&self) -> bool {
+        self.show_motion_vectors
+    }
+
+    /// Sets nodes of the scene that will be outlined, descendants of the nodes are outlined as
+    /// well. Empty list disables the outline for the scene, the outline has no cost in this case.
+    /// See [`OutlineSettings`] to change the look of the outline.
+    pub fn set_highlighted_nodes(&mut self, scene: Handle<Scene>, nodes: Vec<Handle<Node>>) {
+        if nodes.is_empty() {
+            self.highlighted_nodes.remove(&scene);
+        } else {
+            self.highlighted_nodes.insert(scene, nodes);
+        }
+    }
+
+    /// Returns nodes of the scene that are outlined.
+    pub fn highlighted_nodes(&self, scene: Handle<Scene>) -> &[Handle<Node>] {
+        self.highlighted_nodes
+            .get(&scene)
+            .map_or(&[], |nodes| nodes.as_slice())
+    }
+
+    /// Sets material override for the camera of the scene, `None` removes the override. See
+    /// [`MaterialOverride`] docs for more info.
+    pub fn set_material_override(
+        &mut self,
+        scene: Handle<Scene>,
+        camera: Handle<Node>,
+        material_override: Option<MaterialOverride>,
+    ) {
+        match material_override {
+            Some(material_override) => {
+                self.material_overrides
+                    .entry(scene)
+                    .or_default()
+                    .insert(camera, material_override);
+            }
+            None => {
+                if let Some(overrides) = self.material_overrides.get_mut(&scene) {
+                    overrides.remove(&camera);
+                    if overrides.is_empty() {
+                        self.material_overrides.remove(&scene);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns material override of the camera of the scene.
+    pub fn material_override(
+        &self,
+        scene: Handle<Scene>,
+        camera: Handle<Node>,
+    ) -> Option<&MaterialOverride> {
+        self.material_overrides
+            .get(&scene)
+            .and_then(|overrides| overrides.get(&camera))
+    }
+
+    /// Sets new output color management settings. If the output target of the settings is not
+    /// supported by the back buffer (see [`Self::output_capabilities`]), the renderer falls back
+    /// to [`OutputTarget::Srgb`], [`Self::active_output_target`] could be used to check which
+    /// target is actually used. The default settings (sRGB) do not add any extra work.
+    pub fn set_color_management_settings(
+        &mut self,
+        settings: ColorManagementSettings,
+    ) -> Result<(), FrameworkError> {
+        let mut target = settings.output_target;
+        if !self.output_capabilities.supports(target) {
+            Log::warn(format!(
+                "{} output is not supported by the back buffer ({:?}), falling back to sRGB.",
+                target, self.output_capabilities
+            ));
+            target = OutputTarget::Srgb;
+        }
+
+        if target == OutputTarget::Srgb {
+            self.output_renderer = None;
+        } else if self.output_renderer.is_none() {
+            self.output_renderer = Some(OutputRenderer::new(&mut self.state, self.frame_size)?);
+        }
+
+        self.color_management_settings = settings;
+        self.active_output_target = target;
+
+        Ok(())
+    }
+
+    /// Returns current output color management settings.
+    pub fn color_management_settings(&self) -> ColorManagementSettings {
+        self.color_management_settings
+    }
+
+    /// Returns the output target that is actually used, see
+    /// [`Self::set_color_management_settings`] for more info.
+    pub fn active_output_target(&self) -> OutputTarget {
+        self.active_output_target
+    }
+
+    /// Returns the format of the back buffer and output targets that could be used with it.
+    pub fn output_capabilities(&self) -> &OutputCapabilities {
+        &self.output_capabilities
+    }
+
+    /// Reads pixels of the last rendered frame of the scene (before it was copied to the back
+    /// buffer), returns width and height of the frame and RGB triples of colors, rows go from
+    /// bottom to top. `None` is returned if the scene wasn't rendered yet. It stalls the pipeline,
+    /// so it should be used only for screenshots and tests.
+    pub fn read_scene_frame(&mut self, scene: Handle<Scene>) -> Option<(usize, usize, Vec<f32>)> {
+        let scene_data = self.scene_data_map.get(&scene)?;
+        let (width, height) = (scene_data.gbuffer.width, scene_data.gbuffer.height);
+        let bytes = scene_data
+            .ldr_scene_framebuffer
+            .read_pixels_rgb_f32(&mut self.state, Rect::new(0, 0, width, height));
+        let pixels = bytes
+            .chunks_exact(4)
+            .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        Some((width as usize, height as usize, pixels))
+    }
+
+    /// Sets new settings for the outline of highlighted nodes.
+    pub fn set_outline_settings(&mut self, settings: OutlineSettings) {
+        self.outline_settings = settings;
+    }
+
+    /// Returns current settings of the outline of highlighted nodes.
+    pub fn outline_settings(&self) -> &OutlineSettings {
+        &self.outline_settings
+    }
+
     /// Removes all cached GPU data, forces renderer to re-upload data to GPU.
     /// Do not call this method until you absolutely need! It may cause **significant**
     /// performance lag!
@@ -1245,6 +1901,221 @@ impl Renderer {
         self.renderer2d.flush();
     }
 
+    /// Captures environment map of the given reflection probe by rendering the scene from the
+    /// position of the probe in six directions. Skybox and clipping planes are taken from the first
+    /// enabled camera of the scene. Returned texture is a cube map in high dynamic range, which is
+    /// embedded in the scene when saved, it could be assigned to the probe using
+    /// [`ReflectionProbe::set_environment`].
+    ///
+    /// # Performance
+    ///
+    /// The method renders the scene six times and reads the results back from GPU memory, it is
+    /// **very** slow and should not be called every frame.
+    pub fn capture_reflection_probe(
+        &mut self,
+        scene: &Scene,
+        probe: Handle<Node>,
+    ) -> Result<Texture, FrameworkError> {
+        scope_profile!();
+
+        let probe = scene
+            .graph
+            .try_get_of_type::<ReflectionProbe>(probe)
+            .map_err(|e| FrameworkError::Custom(e.to_string()))?;
+        let resolution = probe.resolution();
+        let position = probe.global_position();
+
+        let mut camera_builder = CameraBuilder::new(BaseBuilder::new()).with_fov(FRAC_PI_2);
+        if let Some(template) = scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<Camera>())
+            .find(|camera| camera.is_enabled())
+        {
+            camera_builder = camera_builder
+                .with_z_near(template.projection().z_near())
+                .with_z_far(template.projection().z_far())
+                .with_culling_mask(template.culling_mask());
+            if let Some(skybox) = template.skybox_ref() {
+                camera_builder = camera_builder.with_skybox(skybox.clone());
+            }
+        }
+        let mut camera = camera_builder.build_camera();
+
+        self.state.invalidate_resource_bindings_cache();
+        let state = &mut self.state;
+
+        self.batch_storage.generate_batches(&scene.graph, None);
+        self.batch_storage.upload_bone_matrices(state)?;
+
+        let mut scene_data =
+            AssociatedSceneData::new(state, resolution as usize, resolution as usize)?;
+        let frame_size = Vector2::new(resolution as f32, resolution as f32);
+        let viewport = Rect::new(0, 0, resolution as i32, resolution as i32);
+
+        // SSAO is screen-space effect, it makes no sense for a cube map.
+        let settings = QualitySettings {
+            use_ssao: false,
+            ..self.quality_settings
+        };
+
+        // Faces in the order of OpenGL cube map faces: +X, -X, +Y, -Y, +Z, -Z.
+        let faces = [
+            (Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, -1.0, 0.0)),
+            (Vector3::new(-1.0, 0.0, 0.0), Vector3::new(0.0, -1.0, 0.0)),
+            (Vector3::new(0.0, 1.0, 0.0), Vector3::new(0.0, 0.0, 1.0)),
+            (Vector3::new(0.0, -1.0, 0.0), Vector3::new(0.0, 0.0, -1.0)),
+            (Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.0, -1.0, 0.0)),
+            (Vector3::new(0.0, 0.0, -1.0), Vector3::new(0.0, -1.0, 0.0)),
+        ];
+
+        let mut bytes = Vec::new();
+        for (look, up) in faces {
+            let side = up.cross(&look);
+            camera.global_transform.set(Matrix4::from_columns(&[
+                side.to_homogeneous(),
+                up.to_homogeneous(),
+                look.to_homogeneous(),
+                Vector4::new(position.x, position.y, position.z, 1.0),
+            ]));
+            camera.calculate_matrices(frame_size);
+
+            let frustum = Frustum::from(camera.view_projection_matrix()).unwrap_or_default();
+            let (z_near, z_far) = (camera.projection().z_near(), camera.projection().z_far());
+            camera.visibility_cache.clear();
+            camera.visibility_cache.update(
+                scene.graph.node_pool(),
+                position,
+                z_near,
+                z_far,
+                Some(&[&frustum]),
+                camera.culling_mask(),
+            );
+
+            self.statistics += scene_data.gbuffer.fill(GBufferRenderContext {
+                state,
+                camera: &camera,
+                geom_cache: &mut self.geometry_cache,
+                batch_storage: &self.batch_storage,
+                texture_cache: &mut self.texture_cache,
+                shader_cache: &mut self.shader_cache,
+                environment_dummy: self.environment_dummy.clone(),
+                use_parallax_mapping: settings.use_parallax_mapping,
+                use_depth_prepass: settings.use_depth_prepass,
+                normal_dummy: self.normal_dummy.clone(),
+                white_dummy: self.white_dummy.clone(),
+                black_dummy: self.black_dummy.clone(),
+                graph: &scene.graph,
+                jitter: Default::default(),
+                prev_view_projection: camera.view_projection_matrix(),
+            });
+
+            scene_data.copy_depth_stencil_to_scene_framebuffer(state);
+
+            scene_data.hdr_scene_framebuffer.clear(
+                state,
+                viewport,
+                Some(Color::from_rgba(0, 0, 0, 255)),
+                None,
+                Some(0),
+            );
+
+            let (pass_stats, light_stats) = match self.deferred_light_renderer.as_mut() {
+                Some(deferred_light_renderer) => {
+                    deferred_light_renderer.render(DeferredRendererContext {
+                        state,
+                        scene,
+                        camera: &camera,
+                        gbuffer: &mut scene_data.gbuffer,
+                        white_dummy: self.white_dummy.clone(),
+                        ambient_color: scene.ambient_lighting_color,
+                        settings: &settings,
+                        textures: &mut self.texture_cache,
+                        geometry_cache: &mut self.geometry_cache,
+                        batch_storage: &self.batch_storage,
+                        frame_buffer: &mut scene_data.hdr_scene_framebuffer,
+                        shader_cache: &mut self.shader_cache,
+                        normal_dummy: self.normal_dummy.clone(),
+                        black_dummy: self.black_dummy.clone(),
+                        environment_dummy: self.environment_dummy.clone(),
+                        ssao_history: None,
+                    })
+                }
+                None => (
+                    render_unlit(
+                        state,
+                        &scene_data.gbuffer,
+                        &mut scene_data.hdr_scene_framebuffer,
+                        &self.flat_shader,
+                        &self.quad,
+                    ),
+                    Default::default(),
+                ),
+            };
+
+            self.statistics.lighting += light_stats;
+            self.statistics.geometry += pass_stats;
+
+            let (pass_stats, fog_stats) = self.fog_volume_renderer.render(FogVolumeRenderContext {
+                state,
+                graph: &scene.graph,
+                camera: &camera,
+                depth: scene_data.gbuffer.depth(),
+                frame_buffer: &mut scene_data.hdr_scene_framebuffer,
+                viewport,
+                ambient_color: scene.ambient_lighting_color,
+                settings: &settings,
+            });
+
+            self.statistics.fog += fog_stats;
+            self.statistics.geometry += pass_stats;
+
+            self.statistics += self.forward_renderer.render(ForwardRenderContext {
+                state,
+                camera: &camera,
+                geom_cache: &mut self.geometry_cache,
+                texture_cache: &mut self.texture_cache,
+                shader_cache: &mut self.shader_cache,
+                batch_storage: &self.batch_storage,
+                framebuffer: &mut scene_data.hdr_scene_framebuffer,
+                viewport,
+                quality_settings: &settings,
+                white_dummy: self.white_dummy.clone(),
+                normal_dummy: self.normal_dummy.clone(),
+                black_dummy: self.black_dummy.clone(),
+                jitter: Default::default(),
+            });
+
+            bytes.extend(
+                scene_data
+                    .hdr_scene_framebuffer
+                    .read_pixels_rgb_f32(state, viewport),
+            );
+        }
+
+        let texture = Texture::from_bytes(
+            TextureKind::Cube {
+                width: resolution,
+                height: resolution,
+            },
+            TexturePixelKind::RGB32F,
+            bytes,
+            true,
+        )
+        .ok_or_else(|| {
+            FrameworkError::Custom("Unable to create environment map texture!".to_owned())
+        })?;
+
+        {
+            let mut data = texture.data_ref();
+            data.set_minification_filter(TextureMinificationFilter::Linear);
+            data.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+            data.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+        }
+
+        Ok(texture)
+    }
+
     /// Renders given UI into specified render target. This method is especially useful if you need
     /// to have off-screen UIs (like interactive touch-screen in Doom 3, Dead Space, etc).
     pub fn render_ui_to_texture(
@@ -1377,6 +2248,26 @@ impl Renderer {
         // Make sure to drop associated data for destroyed scenes.
         self.scene_data_map
             .retain(|h, _| scenes.is_valid_handle(*h));
+        self.highlighted_nodes
+            .retain(|h, _| scenes.is_valid_handle(*h));
+        self.material_overrides
+            .retain(|h, _| scenes.is_valid_handle(*h));
+
+        // Free frame textures of render targets that are not used anymore, otherwise every change
+        // of a render target (resizing for example) would leak a frame buffer.
+        let texture_cache = &mut self.texture_cache;
+        self.render_target_keys.retain(|scene_handle, key| {
+            let current_key = scenes
+                .try_get(*scene_handle)
+                .and_then(|scene| scene.render_target.as_ref())
+                .map(|render_target| render_target.key());
+            if current_key == Some(*key) {
+                true
+            } else {
+                texture_cache.unload_by_key(*key);
+                false
+            }
+        });
 
         // We have to invalidate resource bindings cache because some textures or programs,
         // or other GL resources can be destroyed and then on their "names" some new resource
@@ -1387,7 +2278,7 @@ impl Renderer {
         self.statistics.begin_frame();
 
         let window_viewport = Rect::new(0, 0, self.frame_size.0 as i32, self.frame_size.1 as i32);
-        self.backbuffer.clear(
+        composition_target(&mut self.backbuffer, &mut self.output_renderer).clear(
             &mut self.state,
             window_viewport,
             Some(self.backbuffer_clear_color),
@@ -1421,7 +2312,39 @@ impl Renderer {
 
             let state = &mut self.state;
 
-            self.batch_storage.generate_batches(graph);
+            let use_taa = self.quality_settings.taa;
+            // Size of SSAO map, it is known only if temporal accumulation of SSAO is used.
+            let ssao_history_size = self
+                .deferred_light_renderer
+                .as_ref()
+                .and_then(|renderer| renderer.ssao_renderer.as_ref())
+                .filter(|ssao_renderer| {
+                    self.quality_settings.use_ssao && ssao_renderer.settings().temporal_accumulation
+                })
+                .map(|ssao_renderer| ssao_renderer.map_size());
+            let use_ssao_history = ssao_history_size.is_some();
+
+            // Previous transforms are needed only for motion vectors.
+            let prev_transforms = if use_taa {
+                self.scene_data_map
+                    .get(&scene_handle)
+                    .map(|data| &data.prev_transforms)
+            } else {
+                None
+            };
+
+            self.batch_storage.generate_batches(graph, prev_transforms);
+            self.batch_storage.upload_bone_matrices(state)?;
+
+            // Values above SDR white are kept only for scenes that go to the back buffer, render
+            // targets are used as usual textures.
+            let peak_white = Some(self.color_management_settings.peak_white())
+                .filter(|_| self.active_output_target.is_hdr() && scene.render_target.is_none());
+            let ldr_pixel_kind = if peak_white.is_some() {
+                PixelKind::RGBA16F
+            } else {
+                PixelKind::RGBA8
+            };
 
             let scene_associated_data = self
                 .scene_data_map
@@ -1439,7 +2362,26 @@ impl Renderer {
                             data.gbuffer.width,data.gbuffer.height,width,height
                         ));
 
-                        *data = AssociatedSceneData::new(state, width, height).unwrap();
+                        *data = AssociatedSceneData::with_ldr_pixel_kind(
+                            state,
+                            width,
+                            height,
+                            ldr_pixel_kind,
+                        )
+                        .unwrap();
+                    } else if data.ldr_pixel_kind() != ldr_pixel_kind {
+                        Log::info(format!(
+                            "Associated scene rendering data was re-created for scene {}, because output target was changed!",
+                            scene_handle
+                        ));
+
+                        *data = AssociatedSceneData::with_ldr_pixel_kind(
+                            state,
+                            data.gbuffer.width as usize,
+                            data.gbuffer.height as usize,
+                            ldr_pixel_kind,
+                        )
+                        .unwrap();
                     }
                 })
                 .or_insert_with(|| {
@@ -1451,7 +2393,8 @@ impl Renderer {
                         scene_handle
                     ));
 
-                    AssociatedSceneData::new(state, width, height).unwrap()
+                    AssociatedSceneData::with_ldr_pixel_kind(state, width, height, ldr_pixel_kind)
+                        .unwrap()
                 });
 
             // If we specified a texture to draw to, we have to register it in texture cache
@@ -1460,6 +2403,7 @@ impl Renderer {
             // TODO: However it can be dangerous to use frame texture as it may be bound to
             //  pipeline.
             if let Some(rt) = scene.render_target.clone() {
+                self.render_target_keys.insert(scene_handle, rt.key());
                 self.texture_cache.map.insert(
                     rt.key(),
                     CacheEntry {
@@ -1470,10 +2414,10 @@ impl Renderer {
                 );
             }
 
-            for camera in graph.linear_iter().filter_map(|node| {
+            for (camera_handle, camera) in graph.pair_iter().filter_map(|(handle, node)| {
                 if let Some(camera) = node.cast::<Camera>() {
                     if camera.is_enabled() {
-                        Some(camera)
+                        Some((handle, camera))
                     } else {
                         None
                     }
@@ -1483,6 +2427,39 @@ impl Renderer {
             }) {
                 let viewport = camera.viewport_pixels(frame_size);
 
+                let view_projection = camera.view_projection_matrix();
+                let (jitter, prev_view_projection) = if use_taa {
+                    if !scene_associated_data
+                        .taa_history
+                        .contains_key(&camera_handle)
+                    {
+                        scene_associated_data.taa_history.insert(
+                            camera_handle,
+                            TaaHistory::new(state, frame_size.x as usize, frame_size.y as usize)?,
+                        );
+                    }
+                    let history = &scene_associated_data.taa_history[&camera_handle];
+                    (
+                        history.jitter(viewport),
+                        history.prev_view_projection(view_projection),
+                    )
+                } else {
+                    (Vector2::default(), view_projection)
+                };
+
+                if let Some(ssao_map_size) = ssao_history_size {
+                    if scene_associated_data
+                        .ssao_history
+                        .get(&camera_handle)
+                        .map_or(true, |history| history.size() != ssao_map_size)
+                    {
+                        scene_associated_data.ssao_history.insert(
+                            camera_handle,
+                            SsaoHistory::new(state, ssao_map_size.0, ssao_map_size.1)?,
+                        );
+                    }
+                }
+
                 self.statistics += scene_associated_data.gbuffer.fill(GBufferRenderContext {
                     state,
                     camera,
@@ -1492,166 +2469,298 @@ impl Renderer {
                     shader_cache: &mut self.shader_cache,
                     environment_dummy: self.environment_dummy.clone(),
                     use_parallax_mapping: self.quality_settings.use_parallax_mapping,
+                    use_depth_prepass: self.quality_settings.use_depth_prepass,
                     normal_dummy: self.normal_dummy.clone(),
                     white_dummy: self.white_dummy.clone(),
                     black_dummy: self.black_dummy.clone(),
                     graph,
+                    jitter,
+                    prev_view_projection,
                 });
 
                 scene_associated_data.copy_depth_stencil_to_scene_framebuffer(state);
 
-                scene_associated_data.hdr_scene_framebuffer.clear(
-                    state,
-                    viewport,
-                    Some(Color::from_rgba(0, 0, 0, 255)),
-                    None, // Keep depth, we've just copied valid data in it.
-                    Some(0),
-                );
-
-                let (pass_stats, light_stats) =
-                    self.deferred_light_renderer
-                        .render(DeferredRendererContext {
-                            state,
-                            scene,
-                            camera,
-                            gbuffer: &mut scene_associated_data.gbuffer,
-                            white_dummy: self.white_dummy.clone(),
-                            ambient_color: scene.ambient_lighting_color,
-                            settings: &self.quality_settings,
-                            textures: &mut self.texture_cache,
-                            geometry_cache: &mut self.geometry_cache,
-                            batch_storage: &self.batch_storage,
-                            frame_buffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                            shader_cache: &mut self.shader_cache,
-                            normal_dummy: self.normal_dummy.clone(),
-                            black_dummy: self.black_dummy.clone(),
-                        });
+                let material_override = self
+                    .material_overrides
+                    .get(&scene_handle)
+                    .and_then(|overrides| overrides.get(&camera_handle));
 
-                self.statistics.lighting += light_stats;
-                self.statistics.geometry += pass_stats;
+                if let Some(material_override) = material_override {
+                    // Debug visualization: lighting and post effects are skipped, the override is
+                    // rendered directly into the final frame.
+                    self.statistics +=
+                        self.material_override_renderer
+                            .render(MaterialOverrideRenderContext {
+                                state,
+                                camera,
+                                geom_cache: &mut self.geometry_cache,
+                                texture_cache: &mut self.texture_cache,
+                                shader_cache: &mut self.shader_cache,
+                                batch_storage: &self.batch_storage,
+                                framebuffer: &mut scene_associated_data.ldr_scene_framebuffer,
+                                viewport,
+                                material_override,
+                                white_dummy: self.white_dummy.clone(),
+                                normal_dummy: self.normal_dummy.clone(),
+                                black_dummy: self.black_dummy.clone(),
+                            });
 
-                let depth = scene_associated_data.gbuffer.depth();
+                    // Accumulated history is not valid anymore, it will be re-created when the
+                    // override is removed.
+                    scene_associated_data.taa_history.remove(&camera_handle);
+                } else {
+                    scene_associated_data.hdr_scene_framebuffer.clear(
+                        state,
+                        viewport,
+                        Some(Color::from_rgba(0, 0, 0, 255)),
+                        None, // Keep depth, we've just copied valid data in it.
+                        Some(0),
+                    );
 
-                self.statistics +=
-                    self.particle_system_renderer
-                        .render(ParticleSystemRenderContext {
+                    let (pass_stats, light_stats) = match self.deferred_light_renderer.as_mut() {
+                        Some(deferred_light_renderer) => {
+                            deferred_light_renderer.render(DeferredRendererContext {
+                                state,
+                                scene,
+                                camera,
+                                gbuffer: &mut scene_associated_data.gbuffer,
+                                white_dummy: self.white_dummy.clone(),
+                                ambient_color: scene.ambient_lighting_color,
+                                settings: &self.quality_settings,
+                                textures: &mut self.texture_cache,
+                                geometry_cache: &mut self.geometry_cache,
+                                batch_storage: &self.batch_storage,
+                                frame_buffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                                shader_cache: &mut self.shader_cache,
+                                normal_dummy: self.normal_dummy.clone(),
+                                black_dummy: self.black_dummy.clone(),
+                                environment_dummy: self.environment_dummy.clone(),
+                                ssao_history: scene_associated_data
+                                    .ssao_history
+                                    .get_mut(&camera_handle),
+                            })
+                        }
+                        None => (
+                            render_unlit(
+                                state,
+                                &scene_associated_data.gbuffer,
+                                &mut scene_associated_data.hdr_scene_framebuffer,
+                                &self.flat_shader,
+                                &self.quad,
+                            ),
+                            Default::default(),
+                        ),
+                    };
+
+                    self.statistics.lighting += light_stats;
+                    self.statistics.geometry += pass_stats;
+
+                    // Fog must be composited over lit opaque geometry, but before transparent objects.
+                    let (pass_stats, fog_stats) =
+                        self.fog_volume_renderer.render(FogVolumeRenderContext {
                             state,
-                            framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
                             graph,
                             camera,
-                            white_dummy: self.white_dummy.clone(),
-                            depth,
-                            frame_width: frame_size.x,
-                            frame_height: frame_size.y,
+                            depth: scene_associated_data.gbuffer.depth(),
+                            frame_buffer: &mut scene_associated_data.hdr_scene_framebuffer,
                             viewport,
-                            texture_cache: &mut self.texture_cache,
+                            ambient_color: scene.ambient_lighting_color,
+                            settings: &self.quality_settings,
                         });
 
-                self.statistics += self.sprite_renderer.render(SpriteRenderContext {
-                    state,
-                    framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                    graph,
-                    camera,
-                    white_dummy: self.white_dummy.clone(),
-                    viewport,
-                    textures: &mut self.texture_cache,
-                });
+                    self.statistics.fog += fog_stats;
+                    self.statistics.geometry += pass_stats;
 
-                self.statistics += self.renderer2d.render(
-                    state,
-                    camera,
-                    &mut scene_associated_data.hdr_scene_framebuffer,
-                    viewport,
-                    graph,
-                    &mut self.texture_cache,
-                    self.white_dummy.clone(),
-                    scene.ambient_lighting_color,
-                )?;
-
-                self.statistics += self.forward_renderer.render(ForwardRenderContext {
-                    state,
-                    camera,
-                    geom_cache: &mut self.geometry_cache,
-                    texture_cache: &mut self.texture_cache,
-                    shader_cache: &mut self.shader_cache,
-                    batch_storage: &self.batch_storage,
-                    framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                    viewport,
-                    quality_settings: &self.quality_settings,
-                    white_dummy: self.white_dummy.clone(),
-                    normal_dummy: self.normal_dummy.clone(),
-                    black_dummy: self.black_dummy.clone(),
-                });
+                    let depth = scene_associated_data.gbuffer.depth();
 
-                for render_pass in self.scene_render_passes.iter() {
                     self.statistics +=
-                        render_pass
-                            .borrow_mut()
-                            .on_hdr_render(SceneRenderPassContext {
-                                pipeline_state: state,
-                                texture_cache: &mut self.texture_cache,
-                                geometry_cache: &mut self.geometry_cache,
-                                quality_settings: &self.quality_settings,
-                                batch_storage: &self.batch_storage,
-                                viewport,
-                                scene,
+                        self.particle_system_renderer
+                            .render(ParticleSystemRenderContext {
+                                state,
+                                framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                                graph,
                                 camera,
-                                scene_handle,
                                 white_dummy: self.white_dummy.clone(),
-                                normal_dummy: self.normal_dummy.clone(),
-                                metallic_dummy: self.metallic_dummy.clone(),
-                                environment_dummy: self.environment_dummy.clone(),
-                                black_dummy: self.black_dummy.clone(),
-                                depth_texture: scene_associated_data.gbuffer.depth(),
-                                normal_texture: scene_associated_data.gbuffer.normal_texture(),
-                                ambient_texture: scene_associated_data.gbuffer.ambient_texture(),
-                                framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
-                                ui_renderer: &mut self.ui_renderer,
-                            })?;
-                }
-
-                let quad = &self.quad;
+                                depth,
+                                frame_width: frame_size.x,
+                                frame_height: frame_size.y,
+                                viewport,
+                                texture_cache: &mut self.texture_cache,
+                            });
 
-                // Prepare glow map.
-                self.statistics.geometry += scene_associated_data.bloom_renderer.render(
-                    state,
-                    quad,
-                    scene_associated_data.hdr_scene_frame_texture(),
-                );
+                    self.statistics += self.sprite_renderer.render(SpriteRenderContext {
+                        state,
+                        framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                        graph,
+                        camera,
+                        white_dummy: self.white_dummy.clone(),
+                        viewport,
+                        textures: &mut self.texture_cache,
+                    });
 
-                // Convert high dynamic range frame to low dynamic range (sRGB) with tone mapping and gamma correction.
-                self.statistics.geometry += scene_associated_data.hdr_renderer.render(
-                    state,
-                    scene_associated_data.hdr_scene_frame_texture(),
-                    scene_associated_data.bloom_renderer.result(),
-                    &mut scene_associated_data.ldr_scene_framebuffer,
-                    viewport,
-                    quad,
-                    dt,
-                    camera.exposure(),
-                    camera.color_grading_lut_ref(),
-                    camera.color_grading_enabled(),
-                    &mut self.texture_cache,
-                );
+                    self.statistics += self.renderer2d.render(
+                        state,
+                        camera,
+                        &mut scene_associated_data.hdr_scene_framebuffer,
+                        viewport,
+                        graph,
+                        &mut self.texture_cache,
+                        self.white_dummy.clone(),
+                        scene.ambient_lighting_color,
+                    )?;
 
-                // Apply FXAA if needed.
-                if self.quality_settings.fxaa {
-                    self.statistics.geometry += self.fxaa_renderer.render(
+                    self.statistics += self.forward_renderer.render(ForwardRenderContext {
                         state,
+                        camera,
+                        geom_cache: &mut self.geometry_cache,
+                        texture_cache: &mut self.texture_cache,
+                        shader_cache: &mut self.shader_cache,
+                        batch_storage: &self.batch_storage,
+                        framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
                         viewport,
-                        scene_associated_data.ldr_scene_frame_texture(),
-                        &mut scene_associated_data.ldr_temp_framebuffer,
-                    );
+                        quality_settings: &self.quality_settings,
+                        white_dummy: self.white_dummy.clone(),
+                        normal_dummy: self.normal_dummy.clone(),
+                        black_dummy: self.black_dummy.clone(),
+                        jitter,
+                    });
+
+                    for render_pass in self.scene_render_passes.iter() {
+                        self.statistics +=
+                            render_pass
+                                .borrow_mut()
+                                .on_hdr_render(SceneRenderPassContext {
+                                    pipeline_state: state,
+                                    texture_cache: &mut self.texture_cache,
+                                    geometry_cache: &mut self.geometry_cache,
+                                    quality_settings: &self.quality_settings,
+                                    batch_storage: &self.batch_storage,
+                                    viewport,
+                                    scene,
+                                    camera,
+                                    scene_handle,
+                                    white_dummy: self.white_dummy.clone(),
+                                    normal_dummy: self.normal_dummy.clone(),
+                                    metallic_dummy: self.metallic_dummy.clone(),
+                                    environment_dummy: self.environment_dummy.clone(),
+                                    black_dummy: self.black_dummy.clone(),
+                                    depth_texture: scene_associated_data.gbuffer.depth(),
+                                    normal_texture: scene_associated_data.gbuffer.normal_texture(),
+                                    ambient_texture: scene_associated_data
+                                        .gbuffer
+                                        .ambient_texture(),
+                                    framebuffer: &mut scene_associated_data.hdr_scene_framebuffer,
+                                    ui_renderer: &mut self.ui_renderer,
+                                })?;
+                    }
+
+                    // Temporal anti-aliasing must be done after everything was rendered in the HDR
+                    // frame, but before any post effects.
+                    let hdr_frame_texture = scene_associated_data.hdr_scene_frame_texture();
+                    let hdr_frame_texture = if use_taa {
+                        let motion_vectors_texture =
+                            scene_associated_data.gbuffer.motion_vectors_texture();
+                        let depth_texture = scene_associated_data.gbuffer.depth();
+                        match scene_associated_data.taa_history.get_mut(&camera_handle) {
+                            Some(history) => {
+                                self.statistics.geometry +=
+                                    self.taa_renderer.resolve(TaaResolveContext {
+                                        state,
+                                        viewport,
+                                        history,
+                                        frame_texture: hdr_frame_texture,
+                                        motion_vectors_texture,
+                                        depth_texture,
+                                        view_projection,
+                                    });
+                                history.result()
+                            }
+                            None => hdr_frame_texture,
+                        }
+                    } else {
+                        hdr_frame_texture
+                    };
 
                     let quad = &self.quad;
-                    let temp_frame_texture = scene_associated_data.ldr_temp_frame_texture();
-                    self.statistics.geometry += blit_pixels(
+
+                    // Prepare glow map.
+                    self.statistics.geometry += scene_associated_data.bloom_renderer.render(
+                        state,
+                        quad,
+                        hdr_frame_texture.clone(),
+                    );
+
+                    // Convert high dynamic range frame to low dynamic range (sRGB) with tone mapping and gamma correction.
+                    self.statistics.geometry += scene_associated_data.hdr_renderer.render(
                         state,
+                        hdr_frame_texture,
+                        scene_associated_data.bloom_renderer.result(),
                         &mut scene_associated_data.ldr_scene_framebuffer,
-                        temp_frame_texture,
-                        &self.flat_shader,
                         viewport,
                         quad,
+                        dt,
+                        camera.exposure(),
+                        camera.color_grading_lut_ref(),
+                        camera.color_grading_enabled(),
+                        peak_white,
+                        &mut self.texture_cache,
+                    );
+
+                    // Apply FXAA if needed, it makes no sense if TAA is used.
+                    if let Some(fxaa_renderer) = self
+                        .fxaa_renderer
+                        .as_ref()
+                        .filter(|_| self.quality_settings.fxaa && !use_taa)
+                    {
+                        self.statistics.geometry += fxaa_renderer.render(
+                            state,
+                            viewport,
+                            scene_associated_data.ldr_scene_frame_texture(),
+                            &mut scene_associated_data.ldr_temp_framebuffer,
+                        );
+
+                        let quad = &self.quad;
+                        let temp_frame_texture = scene_associated_data.ldr_temp_frame_texture();
+                        self.statistics.geometry += blit_pixels(
+                            state,
+                            &mut scene_associated_data.ldr_scene_framebuffer,
+                            temp_frame_texture,
+                            &self.flat_shader,
+                            viewport,
+                            quad,
+                        );
+                    }
+                }
+
+                if let Some(highlighted_nodes) = self.highlighted_nodes.get(&scene_handle) {
+                    self.statistics.geometry +=
+                        self.outline_renderer.render(OutlineRenderContext {
+                            state,
+                            viewport,
+                            frame_size,
+                            graph,
+                            camera,
+                            highlighted_nodes,
+                            settings: &self.outline_settings,
+                            batch_storage: &self.batch_storage,
+                            geometry_cache: &mut self.geometry_cache,
+                            shader_cache: &mut self.shader_cache,
+                            texture_cache: &mut self.texture_cache,
+                            normal_dummy: self.normal_dummy.clone(),
+                            white_dummy: self.white_dummy.clone(),
+                            black_dummy: self.black_dummy.clone(),
+                            depth_texture: scene_associated_data.gbuffer.depth(),
+                            mask: &mut scene_associated_data.outline_mask,
+                            framebuffer: &mut scene_associated_data.ldr_scene_framebuffer,
+                        })?;
+                }
+
+                if self.show_motion_vectors {
+                    self.statistics.geometry += self.taa_renderer.visualize_motion_vectors(
+                        state,
+                        viewport,
+                        scene_associated_data.gbuffer.motion_vectors_texture(),
+                        &mut scene_associated_data.ldr_scene_framebuffer,
                     );
                 }
 
@@ -1660,10 +2769,25 @@ impl Renderer {
                     state,
                     viewport,
                     &mut scene_associated_data.ldr_scene_framebuffer,
-                    &scene.drawing_context,
+                    scene
+                        .drawing_context
+                        .lines
+                        .iter()
+                        .chain(scene.debug_draw.lines()),
                     camera,
                 );
 
+                self.statistics += self.debug_renderer.render_texts(DebugTextRenderContext {
+                    state,
+                    framebuffer: &mut scene_associated_data.ldr_scene_framebuffer,
+                    frame_size,
+                    texts: scene.debug_draw.texts(),
+                    camera,
+                    ui_renderer: &mut self.ui_renderer,
+                    white_dummy: self.white_dummy.clone(),
+                    texture_cache: &mut self.texture_cache,
+                })?;
+
                 for render_pass in self.scene_render_passes.iter() {
                     self.statistics +=
                         render_pass
@@ -1692,12 +2816,43 @@ impl Renderer {
                 }
             }
 
+            if use_taa {
+                scene_associated_data.prev_transforms.clear();
+                scene_associated_data.prev_transforms.extend(
+                    graph
+                        .pair_iter()
+                        .map(|(handle, node)| (handle, node.global_transform())),
+                );
+
+                // Remove history of deleted and disabled cameras.
+                scene_associated_data.taa_history.retain(|handle, _| {
+                    graph
+                        .try_get(*handle)
+                        .and_then(|node| node.cast::<Camera>())
+                        .map_or(false, |camera| camera.is_enabled())
+                });
+            } else {
+                scene_associated_data.prev_transforms.clear();
+                scene_associated_data.taa_history.clear();
+            }
+
+            if use_ssao_history {
+                scene_associated_data.ssao_history.retain(|handle, _| {
+                    graph
+                        .try_get(*handle)
+                        .and_then(|node| node.cast::<Camera>())
+                        .map_or(false, |camera| camera.is_enabled())
+                });
+            } else {
+                scene_associated_data.ssao_history.clear();
+            }
+
             // Optionally render everything into back buffer.
             if scene.render_target.is_none() {
                 let quad = &self.quad;
                 self.statistics.geometry += blit_pixels(
                     state,
-                    &mut self.backbuffer,
+                    composition_target(&mut self.backbuffer, &mut self.output_renderer),
                     scene_associated_data.ldr_scene_frame_texture(),
                     &self.flat_shader,
                     window_viewport,
@@ -1706,11 +2861,15 @@ impl Renderer {
             }
         }
 
+        if let Some(deferred_light_renderer) = self.deferred_light_renderer.as_mut() {
+            deferred_light_renderer.collect_static_shadow_maps();
+        }
+
         // Render UI on top of everything without gamma correction.
         self.statistics += self.ui_renderer.render(UiRenderContext {
             state: &mut self.state,
             viewport: window_viewport,
-            frame_buffer: &mut self.backbuffer,
+            frame_buffer: composition_target(&mut self.backbuffer, &mut self.output_renderer),
             frame_width: backbuffer_width,
             frame_height: backbuffer_height,
             drawing_context,
@@ -1718,6 +2877,17 @@ impl Renderer {
             texture_cache: &mut self.texture_cache,
         })?;
 
+        // Convert the composed frame to the color space of the output target.
+        if let Some(output_renderer) = self.output_renderer.as_mut() {
+            self.statistics += output_renderer.render(
+                &mut self.state,
+                &mut self.backbuffer,
+                window_viewport,
+                &self.quad,
+                &self.color_management_settings,
+            );
+        }
+
         Ok(())
     }
 
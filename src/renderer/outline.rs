@@ -0,0 +1,362 @@
+//! Outline (selection highlight) render pass. Highlighted nodes are drawn into a depth-only mask,
+//! then the mask is dilated in screen space and the outline is composited on top of the final
+//! frame. See [`OutlineSettings`] for available options and [`crate::renderer::Renderer::set_highlighted_nodes`]
+//! to specify nodes that should be highlighted.
+
+use crate::{
+    core::{
+        algebra::Vector2, color::Color, math::Rect, pool::Handle, scope_profile,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        apply_material,
+        batch::BatchStorage,
+        cache::{shader::ShaderCache, texture::TextureCache},
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, CullFace, DrawParameters, FrameBuffer},
+            geometry_buffer::{GeometryBuffer, GeometryBufferKind},
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{
+                Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
+                PixelKind, WrapMode,
+            },
+            state::{BlendFactor, BlendFunc, ColorMask, PipelineState},
+        },
+        make_viewport_matrix, GeometryCache, MaterialContext, RenderPassStatistics,
+    },
+    scene::{camera::Camera, graph::Graph, mesh::surface::SurfaceData, node::Node},
+};
+use fxhash::FxHashSet;
+use std::{cell::RefCell, rc::Rc};
+
+/// Max width of an outline in pixels. Wider outlines are clamped, because the cost of the outline
+/// grows quadratically with its width.
+pub const MAX_OUTLINE_WIDTH: u32 = 8;
+
+/// Defines how parts of an outline, that are hidden behind other objects, are drawn.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OccludedOutlineStyle {
+    /// Occluded parts of an outline are not drawn.
+    Hidden = 0,
+    /// Occluded parts of an outline are drawn with [`OutlineSettings::occluded_opacity`].
+    Dimmed = 1,
+    /// Occluded parts of an outline are drawn with dashes.
+    Dashed = 2,
+}
+
+impl Default for OccludedOutlineStyle {
+    fn default() -> Self {
+        Self::Dimmed
+    }
+}
+
+/// A set of options for outline of highlighted nodes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutlineSettings {
+    /// Color of the outline.
+    pub color: Color,
+    /// Width of the outline in pixels, clamped to [`MAX_OUTLINE_WIDTH`].
+    pub width: u32,
+    /// Defines how occluded parts of the outline are drawn.
+    pub occluded_style: OccludedOutlineStyle,
+    /// Opacity of occluded parts of the outline when [`OccludedOutlineStyle::Dimmed`] is used.
+    pub occluded_opacity: f32,
+}
+
+impl Default for OutlineSettings {
+    fn default() -> Self {
+        Self {
+            color: Color::opaque(255, 160, 0),
+            width: 2,
+            occluded_style: Default::default(),
+            occluded_opacity: 0.35,
+        }
+    }
+}
+
+struct OutlineShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    mask_texture: UniformLocation,
+    depth_texture: UniformLocation,
+    inverse_screen_size: UniformLocation,
+    width: UniformLocation,
+    color: UniformLocation,
+    occluded_style: UniformLocation,
+    occluded_opacity: UniformLocation,
+}
+
+impl OutlineShader {
+    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/outline_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "OutlineShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            mask_texture: program.uniform_location(state, &ImmutableString::new("maskTexture"))?,
+            depth_texture: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            inverse_screen_size: program
+                .uniform_location(state, &ImmutableString::new("inverseScreenSize"))?,
+            width: program.uniform_location(state, &ImmutableString::new("width"))?,
+            color: program.uniform_location(state, &ImmutableString::new("color"))?,
+            occluded_style: program
+                .uniform_location(state, &ImmutableString::new("occludedStyle"))?,
+            occluded_opacity: program
+                .uniform_location(state, &ImmutableString::new("occludedOpacity"))?,
+            program,
+        })
+    }
+}
+
+/// Creates a depth-only frame buffer for the outline mask.
+fn make_outline_mask(
+    state: &mut PipelineState,
+    width: usize,
+    height: usize,
+) -> Result<FrameBuffer, FrameworkError> {
+    let mut depth = GpuTexture::new(
+        state,
+        GpuTextureKind::Rectangle { width, height },
+        PixelKind::D32F,
+        MinificationFilter::Nearest,
+        MagnificationFilter::Nearest,
+        1,
+        None,
+    )?;
+    depth
+        .bind_mut(state, 0)
+        .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+        .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+
+    FrameBuffer::new(
+        state,
+        Some(Attachment {
+            kind: AttachmentKind::Depth,
+            texture: Rc::new(RefCell::new(depth)),
+        }),
+        vec![],
+    )
+}
+
+pub(in crate) struct OutlineRenderContext<'a, 'b> {
+    pub state: &'a mut PipelineState,
+    pub viewport: Rect<i32>,
+    pub frame_size: Vector2<f32>,
+    pub graph: &'b Graph,
+    pub camera: &'b Camera,
+    pub highlighted_nodes: &'b [Handle<Node>],
+    pub settings: &'b OutlineSettings,
+    pub batch_storage: &'a BatchStorage,
+    pub geometry_cache: &'a mut GeometryCache,
+    pub shader_cache: &'a mut ShaderCache,
+    pub texture_cache: &'a mut TextureCache,
+    pub normal_dummy: Rc<RefCell<GpuTexture>>,
+    pub white_dummy: Rc<RefCell<GpuTexture>>,
+    pub black_dummy: Rc<RefCell<GpuTexture>>,
+    pub depth_texture: Rc<RefCell<GpuTexture>>,
+    pub mask: &'a mut Option<FrameBuffer>,
+    pub framebuffer: &'a mut FrameBuffer,
+}
+
+pub(in crate) struct OutlineRenderer {
+    shader: OutlineShader,
+    quad: GeometryBuffer,
+    render_pass_name: ImmutableString,
+}
+
+impl OutlineRenderer {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: OutlineShader::new(state)?,
+            quad: GeometryBuffer::from_surface_data(
+                &SurfaceData::make_unit_xy_quad(),
+                GeometryBufferKind::StaticDraw,
+                state,
+            ),
+            // Depth-only pass of a material, it handles skinning as well as any other vertex
+            // transformation of the material.
+            render_pass_name: ImmutableString::new("SpotShadow"),
+        })
+    }
+
+    pub fn render(
+        &self,
+        args: OutlineRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        scope_profile!();
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let OutlineRenderContext {
+            state,
+            viewport,
+            frame_size,
+            graph,
+            camera,
+            highlighted_nodes,
+            settings,
+            batch_storage,
+            geometry_cache,
+            shader_cache,
+            texture_cache,
+            normal_dummy,
+            white_dummy,
+            black_dummy,
+            depth_texture,
+            mask,
+            framebuffer,
+        } = args;
+
+        if highlighted_nodes.is_empty() {
+            return Ok(statistics);
+        }
+
+        // Descendants of highlighted nodes are highlighted too, this way a whole model is outlined
+        // when its root is highlighted.
+        let mut nodes = FxHashSet::default();
+        let mut stack = highlighted_nodes
+            .iter()
+            .filter(|&&handle| graph.is_valid_handle(handle))
+            .cloned()
+            .collect::<Vec<_>>();
+        while let Some(handle) = stack.pop() {
+            if nodes.insert(handle) {
+                stack.extend_from_slice(graph[handle].children());
+            }
+        }
+
+        let is_outlined = |owner: Handle<Node>| {
+            nodes.contains(&owner) && camera.visibility_cache.is_visible(owner)
+        };
+
+        // Do not touch anything if there is nothing to outline.
+        if !batch_storage
+            .batches
+            .iter()
+            .any(|batch| batch.instances.iter().any(|i| is_outlined(i.owner)))
+        {
+            return Ok(statistics);
+        }
+
+        // The mask is created on first use, so scenes without highlighted nodes do not waste memory.
+        if mask.is_none() {
+            *mask = Some(make_outline_mask(
+                state,
+                frame_size.x as usize,
+                frame_size.y as usize,
+            )?);
+        }
+        let mask = mask.as_mut().unwrap();
+
+        mask.clear(state, viewport, None, Some(1.0), None);
+
+        let view_projection = camera.view_projection_matrix();
+        let camera_position = camera.global_position();
+
+        for batch in batch_storage.batches.iter() {
+            let material = batch.material.lock();
+            let geometry = geometry_cache.get(state, &batch.data);
+
+            if let Some(render_pass) = shader_cache
+                .get(state, material.shader())
+                .and_then(|shader_set| shader_set.render_passes.get(&self.render_pass_name))
+            {
+                for instance in batch.instances.iter() {
+                    if !is_outlined(instance.owner) {
+                        continue;
+                    }
+
+                    statistics += mask.draw(
+                        geometry,
+                        state,
+                        viewport,
+                        &render_pass.program,
+                        &DrawParameters {
+                            cull_face: Some(CullFace::Back),
+                            color_write: ColorMask::all(false),
+                            depth_write: true,
+                            stencil_test: None,
+                            depth_test: true,
+                            blend: None,
+                            stencil_op: Default::default(),
+                            alpha_to_coverage: false,
+                            depth_func: Default::default(),
+                        },
+                        |mut program_binding| {
+                            let wvp_matrix = view_projection * instance.world_transform;
+                            apply_material(MaterialContext {
+                                material: &*material,
+                                program_binding: &mut program_binding,
+                                texture_cache,
+                                world_matrix: &instance.world_transform,
+                                wvp_matrix: &wvp_matrix,
+                                bone_matrices: batch_storage.instance_bone_matrices(instance),
+                                bone_matrices_offset: instance.bone_matrices.start,
+                                bone_matrices_storage: batch_storage.bone_matrices_texture(),
+                                use_skeletal_animation: batch.is_skinned,
+                                camera_position: &camera_position,
+                                use_pom: false,
+                                light_position: &Default::default(),
+                                prev_wvp_matrix: &wvp_matrix,
+                                prev_bone_matrices_storage: None,
+                                jitter: &Default::default(),
+                                wind: &batch_storage.wind,
+                                normal_dummy: normal_dummy.clone(),
+                                white_dummy: white_dummy.clone(),
+                                black_dummy: black_dummy.clone(),
+                            });
+                        },
+                    );
+                }
+            }
+        }
+
+        let mask_texture = mask.depth_attachment().unwrap().texture.clone();
+        let frame_matrix = make_viewport_matrix(viewport);
+
+        statistics += framebuffer.draw(
+            &self.quad,
+            state,
+            viewport,
+            &self.shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: Some(BlendFunc {
+                    sfactor: BlendFactor::SrcAlpha,
+                    dfactor: BlendFactor::OneMinusSrcAlpha,
+                }),
+                stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
+            },
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(&self.shader.wvp_matrix, &frame_matrix)
+                    .set_vector2(
+                        &self.shader.inverse_screen_size,
+                        &Vector2::new(1.0 / frame_size.x, 1.0 / frame_size.y),
+                    )
+                    .set_i32(
+                        &self.shader.width,
+                        settings.width.min(MAX_OUTLINE_WIDTH) as i32,
+                    )
+                    .set_srgb_color(&self.shader.color, &settings.color)
+                    .set_i32(&self.shader.occluded_style, settings.occluded_style as i32)
+                    .set_f32(&self.shader.occluded_opacity, settings.occluded_opacity)
+                    .set_texture(&self.shader.mask_texture, &mask_texture)
+                    .set_texture(&self.shader.depth_texture, &depth_texture);
+            },
+        );
+
+        Ok(statistics)
+    }
+}
@@ -164,6 +164,7 @@ impl ParticleSystemRenderer {
         for particle_system in graph
             .linear_iter()
             .filter_map(|n| n.cast::<ParticleSystem>())
+            .filter(|p| camera.can_see_layers(p.render_mask()))
         {
             particle_system.generate_draw_data(
                 &mut self.sorted_particles,
@@ -177,7 +178,8 @@ impl ParticleSystemRenderer {
                 .bind(state)
                 .set_triangles(self.draw_data.triangles());
 
-            let global_transform = particle_system.global_transform();
+            // World-space particles must not be transformed by the particle system's transform.
+            let global_transform = particle_system.particles_world_transform();
 
             let draw_params = DrawParameters {
                 cull_face: None,
@@ -190,6 +192,8 @@ impl ParticleSystemRenderer {
                     dfactor: BlendFactor::OneMinusSrcAlpha,
                 }),
                 stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
             };
 
             let diffuse_texture = particle_system
@@ -1,6 +1,6 @@
 use crate::{
     core::{
-        algebra::{Matrix4, Vector2},
+        algebra::{Matrix4, Vector4},
         color::Color,
         math::TriangleDefinition,
         scope_profile,
@@ -15,12 +15,15 @@ use crate::{
     },
 };
 use fxhash::FxHashMap;
-use fyrox_core::algebra::Vector3;
 
+/// A vertex of the patch grid, it does not store a position and texture coordinates, instead it
+/// selects a column and a row of the grid of an instance, the rest is done in the vertex shader.
 #[repr(C)]
 pub struct Vertex {
-    position: Vector3<f32>,
-    tex_coord: Vector2<f32>,
+    // One-hot vector of the column of the vertex.
+    column: Vector4<f32>,
+    // One-hot vector of the row of the vertex.
+    row: Vector4<f32>,
 }
 
 pub struct Mesh {
@@ -29,27 +32,36 @@ pub struct Mesh {
 }
 
 impl Mesh {
-    pub fn new_unit_quad() -> Self {
-        let vertices = vec![
-            Vertex {
-                position: Vector3::new(-0.5, 0.5, 0.0),
-                tex_coord: Vector2::new(1.0, 0.0),
-            },
-            Vertex {
-                position: Vector3::new(0.5, 0.5, 0.0),
-                tex_coord: Vector2::new(0.0, 0.0),
-            },
-            Vertex {
-                position: Vector3::new(0.5, -0.5, 0.0),
-                tex_coord: Vector2::new(0.0, 1.0),
-            },
-            Vertex {
-                position: Vector3::new(-0.5, -0.5, 0.0),
-                tex_coord: Vector2::new(1.0, 1.0),
-            },
-        ];
-
-        let triangles = vec![TriangleDefinition([0, 1, 2]), TriangleDefinition([0, 2, 3])];
+    /// Creates a grid of 4x4 vertices that forms 9 patches, see
+    /// [`crate::scene::dim2::rectangle::PatchGrid`] docs for more info.
+    pub fn new_patch_grid() -> Self {
+        let one_hot = |i: usize| {
+            let mut v = Vector4::default();
+            v[i] = 1.0;
+            v
+        };
+
+        let mut vertices = Vec::with_capacity(16);
+        for row in 0..4 {
+            for column in 0..4 {
+                vertices.push(Vertex {
+                    column: one_hot(column),
+                    row: one_hot(row),
+                });
+            }
+        }
+
+        let mut triangles = Vec::with_capacity(18);
+        for row in 0..3u32 {
+            for column in 0..3u32 {
+                let a = row * 4 + column;
+                let b = a + 1;
+                let c = a + 5;
+                let d = a + 4;
+                triangles.push(TriangleDefinition([a, b, c]));
+                triangles.push(TriangleDefinition([a, c, d]));
+            }
+        }
 
         Self {
             vertices,
@@ -68,6 +80,10 @@ pub(in crate) struct GeometryCache {
 pub(in crate) struct InstanceData {
     pub color: Color,
     pub world_matrix: Matrix4<f32>,
+    pub columns: Vector4<f32>,
+    pub rows: Vector4<f32>,
+    pub tex_coord_columns: Vector4<f32>,
+    pub tex_coord_rows: Vector4<f32>,
 }
 
 impl GeometryCache {
@@ -86,13 +102,13 @@ impl GeometryCache {
                     .with_attribute(AttributeDefinition {
                         location: 0,
                         divisor: 0,
-                        kind: AttributeKind::Float3,
+                        kind: AttributeKind::Float4,
                         normalized: false,
                     })
                     .with_attribute(AttributeDefinition {
                         location: 1,
                         divisor: 0,
-                        kind: AttributeKind::Float2,
+                        kind: AttributeKind::Float4,
                         normalized: false,
                     }),
                 )
@@ -130,6 +146,31 @@ impl GeometryCache {
                             kind: AttributeKind::Float4,
                             normalized: false,
                             divisor: 1,
+                        })
+                        // Patch grid
+                        .with_attribute(AttributeDefinition {
+                            location: 7,
+                            kind: AttributeKind::Float4,
+                            normalized: false,
+                            divisor: 1,
+                        })
+                        .with_attribute(AttributeDefinition {
+                            location: 8,
+                            kind: AttributeKind::Float4,
+                            normalized: false,
+                            divisor: 1,
+                        })
+                        .with_attribute(AttributeDefinition {
+                            location: 9,
+                            kind: AttributeKind::Float4,
+                            normalized: false,
+                            divisor: 1,
+                        })
+                        .with_attribute(AttributeDefinition {
+                            location: 10,
+                            kind: AttributeKind::Float4,
+                            normalized: false,
+                            divisor: 1,
                         }),
                 )
                 .build(state)
@@ -22,7 +22,7 @@ use crate::{
         renderer2d::cache::{GeometryCache, InstanceData, Mesh},
         RenderPassStatistics, TextureCache,
     },
-    scene::{camera::Camera, graph::Graph},
+    scene::{base::LayerMask, camera::Camera, graph::Graph},
     utils::value_as_u8_slice,
 };
 use fxhash::{FxHashMap, FxHasher};
@@ -71,7 +71,7 @@ impl SpriteShader {
 
 pub(in crate) struct Renderer2d {
     sprite_shader: SpriteShader,
-    quad: Mesh,
+    patch_grid: Mesh,
     geometry_cache: GeometryCache,
     batch_storage: SpriteBatchStorage,
     instance_data_set: Vec<InstanceData>,
@@ -90,6 +90,7 @@ impl SpriteBatchStorage {
         graph: &Graph,
         texture_cache: &mut TextureCache,
         white_dummy: Rc<RefCell<GpuTexture>>,
+        culling_mask: LayerMask,
     ) {
         self.index_map.clear();
         for batch in self.batches.iter_mut() {
@@ -99,7 +100,9 @@ impl SpriteBatchStorage {
         let mut batch_index = 0;
         for node in graph.linear_iter() {
             if let Some(rectangle) = node.cast::<Rectangle>() {
-                if !rectangle.global_visibility() {
+                if !rectangle.global_visibility()
+                    || !rectangle.render_mask().intersects(culling_mask)
+                {
                     continue;
                 }
 
@@ -141,10 +144,16 @@ impl SpriteBatchStorage {
                     self.batches.last_mut().unwrap()
                 };
 
+                let grid = rectangle.patch_grid();
+
                 batch.instances.push(Instance {
                     gpu_data: InstanceData {
                         color: rectangle.color().srgb_to_linear(),
                         world_matrix: rectangle.global_transform(),
+                        columns: grid.columns,
+                        rows: grid.rows,
+                        tex_coord_columns: grid.tex_coord_columns,
+                        tex_coord_rows: grid.tex_coord_rows,
                     },
                     aabb: rectangle.world_bounding_box(),
                 });
@@ -179,7 +188,7 @@ impl Renderer2d {
     pub(in crate) fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
         Ok(Self {
             sprite_shader: SpriteShader::new(state)?,
-            quad: Mesh::new_unit_quad(),
+            patch_grid: Mesh::new_patch_grid(),
             geometry_cache: Default::default(),
             batch_storage: Default::default(),
             instance_data_set: Default::default(),
@@ -202,10 +211,15 @@ impl Renderer2d {
         ambient_color: Color,
     ) -> Result<RenderPassStatistics, FrameworkError> {
         let mut stats = RenderPassStatistics::default();
-        let quad = self.geometry_cache.get(state, &self.quad);
+        let patch_grid = self.geometry_cache.get(state, &self.patch_grid);
 
-        self.batch_storage
-            .generate_batches(state, graph, texture_cache, white_dummy);
+        self.batch_storage.generate_batches(
+            state,
+            graph,
+            texture_cache,
+            white_dummy,
+            camera.culling_mask(),
+        );
 
         let view_projection = camera.view_projection_matrix();
 
@@ -219,7 +233,10 @@ impl Renderer2d {
         let mut light_parameters = [Vector2::default(); MAX_LIGHTS];
 
         for light in graph.linear_iter() {
-            if !light.global_visibility() || light_count == MAX_LIGHTS {
+            if !light.global_visibility()
+                || !camera.can_see_layers(light.render_mask())
+                || light_count == MAX_LIGHTS
+            {
                 continue;
             }
 
@@ -270,13 +287,13 @@ impl Renderer2d {
                 }
             }
 
-            quad.set_buffer_data(state, 1, &self.instance_data_set);
+            patch_grid.set_buffer_data(state, 1, &self.instance_data_set);
 
             if !self.instance_data_set.is_empty() {
                 let shader = &self.sprite_shader;
                 stats += frame_buffer.draw_instances(
                     self.instance_data_set.len(),
-                    quad,
+                    patch_grid,
                     state,
                     viewport,
                     &shader.program,
@@ -291,6 +308,8 @@ impl Renderer2d {
                             dfactor: BlendFactor::OneMinusSrcAlpha,
                         }),
                         stencil_op: Default::default(),
+                        alpha_to_coverage: false,
+                        depth_func: Default::default(),
                     },
                     |mut program_binding| {
                         program_binding
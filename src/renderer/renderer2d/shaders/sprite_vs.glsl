@@ -1,7 +1,13 @@
-layout(location = 0) in vec3 vertexPosition;
-layout(location = 1) in vec2 vertexTexCoord;
+// One-hot vectors that select a column and a row of the patch grid.
+layout(location = 0) in vec4 vertexColumn;
+layout(location = 1) in vec4 vertexRow;
 layout(location = 2) in vec4 vertexColor;
 layout(location = 3) in mat4 worldMatrix;
+// Patch grid of the instance, see PatchGrid docs for more info.
+layout(location = 7) in vec4 columns;
+layout(location = 8) in vec4 rows;
+layout(location = 9) in vec4 texCoordColumns;
+layout(location = 10) in vec4 texCoordRows;
 
 uniform mat4 viewProjection;
 
@@ -11,7 +17,8 @@ out vec3 fragmentPosition;
 
 void main()
 {
-    texCoord = vertexTexCoord;
+    vec3 vertexPosition = vec3(dot(vertexColumn, columns), dot(vertexRow, rows), 0.0);
+    texCoord = vec2(dot(vertexColumn, texCoordColumns), dot(vertexRow, texCoordRows));
     vec4 worldPosition = worldMatrix * vec4(vertexPosition, 1.0);
     fragmentPosition = worldPosition.xyz;
     gl_Position = viewProjection * worldPosition;
@@ -1,18 +1,122 @@
+// Max amount of reflection probes that could affect a pixel, must be in sync with the renderer.
+#define MAX_PROBES 4
+
 uniform sampler2D diffuseTexture;
+// RGB - world space bent normal packed into [0; 1] range, A - ambient occlusion.
 uniform sampler2D aoSampler;
+uniform bool useBentNormals;
 uniform sampler2D ambientTexture;
+uniform sampler2D depthTexture;
+uniform sampler2D normalTexture;
+uniform sampler2D materialTexture;
+uniform samplerCube environmentTexture;
+uniform samplerCube probeTexture0;
+uniform samplerCube probeTexture1;
+uniform samplerCube probeTexture2;
+uniform samplerCube probeTexture3;
 uniform vec4 ambientColor;
+uniform mat4 invViewProj;
+uniform vec3 cameraPosition;
+uniform int probeCount;
+uniform vec3 probeBoxMin[MAX_PROBES];
+uniform vec3 probeBoxMax[MAX_PROBES];
+uniform vec3 probePosition[MAX_PROBES];
+uniform float probeBlendDistance[MAX_PROBES];
 
 out vec4 FragColor;
 in vec2 texCoord;
 
+// Intersects the reflection ray with the box of a probe and returns a direction from the center
+// of the probe to the intersection point. The direction is used to fetch a pixel from the cube map.
+vec3 BoxProject(vec3 position, vec3 direction, int index)
+{
+    vec3 first = (probeBoxMax[index] - position) / direction;
+    vec3 second = (probeBoxMin[index] - position) / direction;
+    vec3 furthest = max(first, second);
+    float distance = min(min(furthest.x, furthest.y), furthest.z);
+    vec3 intersection = position + direction * distance;
+    return intersection - probePosition[index];
+}
+
+// Must be in sync with ReflectionProbe::weight.
+float ProbeWeight(vec3 position, int index)
+{
+    vec3 toEdges = min(position - probeBoxMin[index], probeBoxMax[index] - position);
+    float distanceToEdge = min(min(toEdges.x, toEdges.y), toEdges.z);
+    if (distanceToEdge < 0.0) {
+        return 0.0;
+    }
+    if (probeBlendDistance[index] <= 0.0) {
+        return 1.0;
+    }
+    return min(distanceToEdge / probeBlendDistance[index], 1.0);
+}
+
+vec3 SampleProbe(vec3 direction, int index)
+{
+    if (index == 0) {
+        return texture(probeTexture0, direction).rgb;
+    } else if (index == 1) {
+        return texture(probeTexture1, direction).rgb;
+    } else if (index == 2) {
+        return texture(probeTexture2, direction).rgb;
+    } else {
+        return texture(probeTexture3, direction).rgb;
+    }
+}
+
 void main()
 {
-    float ambientOcclusion = texture(aoSampler, texCoord).r;
+    vec4 aoPixel = texture(aoSampler, texCoord);
+    float ambientOcclusion = aoPixel.a;
     vec4 ambientPixel = texture(ambientTexture, texCoord);
-    FragColor = (ambientColor + ambientPixel) * texture(diffuseTexture, texCoord);
+    vec3 albedo = texture(diffuseTexture, texCoord).rgb;
+    FragColor = (ambientColor + ambientPixel) * vec4(albedo, 1.0);
     FragColor.rgb *= ambientOcclusion;
     FragColor.a = ambientPixel.a;
 
-    // TODO: Implement IBL.
-}
\ No newline at end of file
+    // Image-based lighting: reflections of the environment using reflection probes with the
+    // environment (skybox) as a fallback.
+    vec3 material = texture(materialTexture, texCoord).rgb;
+    float metallic = material.x;
+    float roughness = material.y;
+
+    vec3 fragmentPosition = S_UnProject(vec3(texCoord, texture(depthTexture, texCoord).r), invViewProj);
+    vec3 normal = normalize(texture(normalTexture, texCoord).xyz * 2.0 - 1.0);
+    vec3 viewVector = normalize(cameraPosition - fragmentPosition);
+    vec3 reflection = reflect(-viewVector, normal);
+
+    float weights[MAX_PROBES];
+    float totalWeight = 0.0;
+    for (int i = 0; i < probeCount; ++i) {
+        weights[i] = ProbeWeight(fragmentPosition, i);
+        totalWeight += weights[i];
+    }
+
+    float normalization = totalWeight > 1.0 ? 1.0 / totalWeight : 1.0;
+
+    vec3 environment = vec3(0.0);
+    for (int i = 0; i < probeCount; ++i) {
+        if (weights[i] > 0.0) {
+            vec3 direction = BoxProject(fragmentPosition, reflection, i);
+            environment += SampleProbe(direction, i) * weights[i] * normalization;
+        }
+    }
+    environment += texture(environmentTexture, reflection).rgb * max(1.0 - totalWeight, 0.0);
+
+    float NdotV = max(dot(normal, viewVector), 0.0);
+    vec3 F0 = mix(vec3(0.04), albedo, metallic);
+    vec3 fresnel = F0 + (max(vec3(1.0 - roughness), F0) - F0) * pow(1.0 - NdotV, 5.0);
+
+    // Specular occlusion. Unoccluded directions form a cone around the bent normal, the more
+    // occlusion there is, the narrower the cone is. Reflections outside of the cone are occluded,
+    // rough surfaces have wider reflection lobes, so the transition is softer for them.
+    float specularOcclusion = ambientOcclusion;
+    if (useBentNormals) {
+        vec3 bentNormal = normalize(aoPixel.rgb * 2.0 - 1.0);
+        float coneCos = sqrt(clamp(1.0 - ambientOcclusion, 0.0, 1.0));
+        specularOcclusion = smoothstep(coneCos - 0.1 - roughness, coneCos, dot(bentNormal, reflection));
+    }
+
+    FragColor.rgb += environment * fresnel * (1.0 - roughness) * specularOcclusion;
+}
@@ -1,22 +1,41 @@
-// Simple 4x4 box blur.
+// Separable depth-aware gaussian blur. Samples that are too far (in depth) from the center
+// sample are rejected, this keeps edges of objects sharp.
+
+#define BLUR_RADIUS 4
 
 uniform sampler2D inputTexture;
+uniform sampler2D depthTexture;
+uniform mat4 inverseProjectionMatrix;
+// Offset (in texture coordinates) between two adjacent samples.
+uniform vec2 direction;
 
-out float FragColor;
+out vec4 FragColor;
 
 in vec2 texCoord;
 
+const float weights[BLUR_RADIUS + 1] = float[](0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+
+float GetViewSpaceDepth(vec2 screenCoord) {
+    return S_UnProject(vec3(screenCoord, texture(depthTexture, screenCoord).r), inverseProjectionMatrix).z;
+}
+
 void main()
 {
-    vec2 texelSize = 1.0 / vec2(textureSize(inputTexture, 0));
-    float result = 0.0;
-    for (int y = -2; y < 2; ++y)
-    {
-        for (int x = -2; x < 2; ++x)
-        {
-            vec2 offset = vec2(float(x), float(y)) * texelSize;
-            result += texture(inputTexture, texCoord + offset).r;
+    float centerDepth = GetViewSpaceDepth(texCoord);
+    // Depth difference tolerance is relative, because depth precision drops with the distance.
+    float tolerance = max(abs(centerDepth) * 0.05, 0.001);
+
+    vec4 result = texture(inputTexture, texCoord) * weights[0];
+    float totalWeight = weights[0];
+    for (int i = 1; i <= BLUR_RADIUS; ++i) {
+        for (int side = -1; side <= 1; side += 2) {
+            vec2 sampleCoord = texCoord + direction * float(i * side);
+            float depthDifference = abs(GetViewSpaceDepth(sampleCoord) - centerDepth);
+            float weight = weights[i] * max(0.0, 1.0 - depthDifference / tolerance);
+            result += texture(inputTexture, sampleCoord) * weight;
+            totalWeight += weight;
         }
     }
-    FragColor = result / 16.0;
-}
\ No newline at end of file
+
+    FragColor = result / totalWeight;
+}
@@ -54,7 +54,7 @@ void main()
     tangentToWorld[1] = normalize(fragmentBinormal); // Binormal
     tangentToWorld[2] = normalize(fragmentNormal); // Normal
 
-    vec3 rawNormal = (texture(normalTexture, decalTexCoord) * 2.0 - 1.0).xyz;
+    vec3 rawNormal = S_UnpackNormal(texture(normalTexture, decalTexCoord));
     vec3 worldSpaceNormal = tangentToWorld * rawNormal;
     outNormalMap = vec4(worldSpaceNormal * 0.5 + 0.5, outDiffuseMap.a);
 }
\ No newline at end of file
@@ -0,0 +1,95 @@
+uniform sampler2D depthTexture;
+uniform sampler2D colorTexture;
+uniform sampler2D normalTexture;
+uniform sampler2D materialTexture;
+// Layout of the storage: 3 texels per light, then 1 texel per cluster (offset and count of its
+// lights in the list of indices), then the list of indices of lights packed in 4 per texel.
+uniform highp sampler2D lightStorage;
+
+uniform mat4 invViewProj;
+uniform mat4 invProj;
+uniform vec3 cameraPosition;
+uniform int clusterOffset;
+uniform int indexOffset;
+uniform int tilesX;
+uniform int tilesY;
+uniform int depthSlices;
+uniform float zNear;
+uniform float depthSliceScale;
+
+in vec2 texCoord;
+out vec4 FragColor;
+
+vec4 FetchTexel(int index)
+{
+    int width = textureSize(lightStorage, 0).x;
+    return texelFetch(lightStorage, ivec2(index % width, index / width), 0);
+}
+
+void main()
+{
+    float depth = texture(depthTexture, texCoord).r;
+
+    // There is nothing to lit on the far plane.
+    if (depth >= 1.0) {
+        discard;
+    }
+
+    vec3 screenPosition = vec3(texCoord, depth);
+    vec3 fragmentPosition = S_UnProject(screenPosition, invViewProj);
+    float viewDepth = -S_UnProject(screenPosition, invProj).z;
+
+    // Must match the way lights are binned into clusters on CPU side.
+    int tileX = clamp(int(texCoord.x * float(tilesX)), 0, tilesX - 1);
+    int tileY = clamp(int(texCoord.y * float(tilesY)), 0, tilesY - 1);
+    int slice = clamp(int(floor(log(max(viewDepth, zNear) / zNear) * depthSliceScale)), 0, depthSlices - 1);
+
+    vec4 cluster = FetchTexel(clusterOffset + tileX + tilesX * (tileY + tilesY * slice));
+    int firstLight = int(cluster.x);
+    int lightCount = int(cluster.y);
+
+    if (lightCount == 0) {
+        discard;
+    }
+
+    vec3 material = texture(materialTexture, texCoord).rgb;
+
+    TPBRContext ctx;
+    ctx.albedo = texture(colorTexture, texCoord).rgb;
+    ctx.fragmentNormal = normalize(texture(normalTexture, texCoord).xyz * 2.0 - 1.0);
+    ctx.metallic = material.x;
+    ctx.roughness = material.y;
+    ctx.viewVector = normalize(cameraPosition - fragmentPosition);
+
+    vec3 lighting = vec3(0.0);
+
+    for (int i = 0; i < lightCount; ++i) {
+        int index = firstLight + i;
+        int lightIndex = int(FetchTexel(indexOffset + index / 4)[index % 4]);
+
+        vec4 positionRadius = FetchTexel(3 * lightIndex);
+        vec4 colorConeCos = FetchTexel(3 * lightIndex + 1);
+        vec4 directionHotspotCos = FetchTexel(3 * lightIndex + 2);
+
+        vec3 fragmentToLight = positionRadius.xyz - fragmentPosition;
+        float distance = length(fragmentToLight);
+
+        if (distance >= positionRadius.w) {
+            continue;
+        }
+
+        ctx.fragmentToLight = fragmentToLight / distance;
+        // Color is premultiplied by the intensity.
+        ctx.lightColor = colorConeCos.rgb;
+
+        float distanceAttenuation = S_LightDistanceAttenuation(distance, positionRadius.w);
+
+        // Point lights have a cone that covers the whole sphere, so this factor is always 1.0 for them.
+        float spotAngleCos = dot(directionHotspotCos.xyz, ctx.fragmentToLight);
+        float coneFactor = smoothstep(colorConeCos.w, directionHotspotCos.w, spotAngleCos);
+
+        lighting += distanceAttenuation * coneFactor * S_PBR_CalculateLight(ctx);
+    }
+
+    FragColor = vec4(lighting, 1.0);
+}
@@ -0,0 +1,77 @@
+uniform sampler2D depthTexture;
+uniform sampler2D colorTexture;
+uniform sampler2D normalTexture;
+uniform sampler2D materialTexture;
+uniform sampler2D spotShadowTexture;
+uniform sampler2D staticShadowTexture;
+uniform sampler2D cookieTexture;
+
+uniform mat4 lightViewProjMatrix;
+uniform vec4 lightColor;
+uniform vec3 lightDirection;
+uniform float edgeFalloff;
+uniform mat4 invViewProj;
+uniform vec3 cameraPosition;
+uniform bool shadowsEnabled;
+uniform bool softShadows;
+uniform float shadowMapInvSize;
+uniform bool staticShadowsEnabled;
+uniform float staticShadowMapInvSize;
+uniform float shadowBias;
+uniform bool cookieEnabled;
+uniform float lightIntensity;
+
+in vec2 texCoord;
+out vec4 FragColor;
+
+void main()
+{
+    vec3 material = texture(materialTexture, texCoord).rgb;
+
+    vec3 fragmentPosition = S_UnProject(vec3(texCoord, texture(depthTexture, texCoord).r), invViewProj);
+
+    // Position of the fragment in light box, each component is in [0; 1] range if the fragment is
+    // inside the box.
+    vec3 lightSpacePosition = S_Project(fragmentPosition, lightViewProjMatrix);
+
+    vec3 inside = step(vec3(0.0), lightSpacePosition) * step(lightSpacePosition, vec3(1.0));
+    float boxFactor = inside.x * inside.y * inside.z;
+
+    if (edgeFalloff > 0.0) {
+        vec3 edgeDistance = min(lightSpacePosition, vec3(1.0) - lightSpacePosition);
+        boxFactor *= smoothstep(0.0, edgeFalloff, edgeDistance.x)
+            * smoothstep(0.0, edgeFalloff, edgeDistance.y)
+            * smoothstep(0.0, edgeFalloff, 1.0 - lightSpacePosition.z);
+    }
+
+    // All rays of the light are parallel.
+    TPBRContext ctx;
+    ctx.albedo = texture(colorTexture, texCoord).rgb;
+    ctx.fragmentToLight = lightDirection;
+    ctx.fragmentNormal = normalize(texture(normalTexture, texCoord).xyz * 2.0 - 1.0);
+    ctx.lightColor = lightColor.rgb;
+    ctx.metallic = material.x;
+    ctx.roughness = material.y;
+    ctx.viewVector = normalize(cameraPosition - fragmentPosition);
+
+    vec3 lighting = S_PBR_CalculateLight(ctx);
+
+    float shadow = S_SpotShadowFactor(
+        shadowsEnabled, softShadows, shadowBias, fragmentPosition,
+            lightViewProjMatrix, shadowMapInvSize, spotShadowTexture);
+
+    // Shadows of static geometry are cached in a separate map, the regular map contains only
+    // dynamic shadow casters in this case.
+    if (staticShadowsEnabled) {
+        shadow = min(shadow, S_SpotShadowFactor(
+            shadowsEnabled, softShadows, shadowBias, fragmentPosition,
+                lightViewProjMatrix, staticShadowMapInvSize, staticShadowTexture));
+    }
+
+    vec4 cookieAttenuation = vec4(1.0);
+    if (cookieEnabled) {
+        cookieAttenuation = texture(cookieTexture, lightSpacePosition.xy);
+    }
+
+    FragColor = cookieAttenuation * vec4(boxFactor * lightIntensity * shadow * lighting, 1.0);
+}
@@ -3,6 +3,7 @@ uniform sampler2D colorTexture;
 uniform sampler2D normalTexture;
 uniform sampler2D materialTexture;
 uniform samplerCube pointShadowTexture;
+uniform samplerCube staticShadowTexture;
 
 uniform vec3 lightPos;
 uniform float lightRadius;
@@ -11,6 +12,7 @@ uniform mat4 invViewProj;
 uniform vec3 cameraPosition;
 uniform bool softShadows;
 uniform bool shadowsEnabled;
+uniform bool staticShadowsEnabled;
 uniform float shadowBias;
 uniform float lightIntensity;
 
@@ -41,5 +43,12 @@ void main()
     float shadow = S_PointShadow(
         shadowsEnabled, softShadows, distance, shadowBias, ctx.fragmentToLight, pointShadowTexture);
 
+    // Shadows of static geometry are cached in a separate map, the regular map contains only
+    // dynamic shadow casters in this case.
+    if (staticShadowsEnabled) {
+        shadow = min(shadow, S_PointShadow(
+            shadowsEnabled, softShadows, distance, shadowBias, ctx.fragmentToLight, staticShadowTexture));
+    }
+
     FragColor = vec4(lightIntensity * distanceAttenuation * shadow * lighting, 1.0);
 }
@@ -3,6 +3,7 @@ uniform sampler2D colorTexture;
 uniform sampler2D normalTexture;
 uniform sampler2D materialTexture;
 uniform sampler2D spotShadowTexture;
+uniform sampler2D staticShadowTexture;
 uniform sampler2D cookieTexture;
 
 uniform mat4 lightViewProjMatrix;
@@ -17,6 +18,8 @@ uniform vec3 cameraPosition;
 uniform bool shadowsEnabled;
 uniform bool softShadows;
 uniform float shadowMapInvSize;
+uniform bool staticShadowsEnabled;
+uniform float staticShadowMapInvSize;
 uniform float shadowBias;
 uniform bool cookieEnabled;
 uniform float lightIntensity;
@@ -52,6 +55,14 @@ void main()
         shadowsEnabled, softShadows, shadowBias, fragmentPosition,
             lightViewProjMatrix, shadowMapInvSize, spotShadowTexture);
 
+    // Shadows of static geometry are cached in a separate map, the regular map contains only
+    // dynamic shadow casters in this case.
+    if (staticShadowsEnabled) {
+        shadow = min(shadow, S_SpotShadowFactor(
+            shadowsEnabled, softShadows, shadowBias, fragmentPosition,
+                lightViewProjMatrix, staticShadowMapInvSize, staticShadowTexture));
+    }
+
     vec4 cookieAttenuation = vec4(1.0);
     if (cookieEnabled) {
         vec2 texCoords = S_Project(fragmentPosition, lightViewProjMatrix).xy;
@@ -0,0 +1,155 @@
+// Max amount of lights that could scatter in a fog volume, must be in sync with the renderer.
+#define MAX_LIGHTS 16
+
+#define LIGHT_KIND_POINT 0
+#define LIGHT_KIND_SPOT 1
+#define LIGHT_KIND_DIRECTIONAL 2
+
+#define SHAPE_BOX 0
+#define SHAPE_SPHERE 1
+
+uniform sampler2D depthTexture;
+uniform mat4 invViewProj;
+uniform mat4 invWorldMatrix;
+uniform vec3 cameraPosition;
+uniform int shape;
+uniform vec3 extents;
+uniform float radius;
+uniform float density;
+uniform vec3 fogColor;
+uniform vec3 ambientColor;
+// Zero means analytic fog without light scattering.
+uniform int sampleCount;
+
+uniform int lightCount;
+uniform int lightKind[MAX_LIGHTS];
+uniform vec3 lightPosition[MAX_LIGHTS];
+uniform vec3 lightDirection[MAX_LIGHTS];
+uniform vec3 lightColor[MAX_LIGHTS];
+uniform float lightRadius[MAX_LIGHTS];
+uniform float halfHotspotConeAngleCos[MAX_LIGHTS];
+uniform float halfConeAngleCos[MAX_LIGHTS];
+uniform float lightAnisotropy[MAX_LIGHTS];
+
+in vec2 texCoord;
+
+out vec4 FragColor;
+
+// Returns range of ray parameter in which the ray is inside of the volume. Ray is given in local
+// coordinates of the volume.
+bool IntersectVolume(vec3 origin, vec3 dir, out float minT, out float maxT)
+{
+    if (shape == SHAPE_SPHERE)
+    {
+        return S_RaySphereIntersection(origin, dir, vec3(0.0), radius, minT, maxT);
+    }
+
+    // Slab test, direction components must not be zero.
+    vec3 safeDir = mix(dir, vec3(1.0e-6), lessThan(abs(dir), vec3(1.0e-6)));
+    vec3 invDir = 1.0 / safeDir;
+    vec3 t1 = (-extents - origin) * invDir;
+    vec3 t2 = (extents - origin) * invDir;
+    vec3 tMin = min(t1, t2);
+    vec3 tMax = max(t1, t2);
+    minT = max(max(tMin.x, tMin.y), tMin.z);
+    maxT = min(min(tMax.x, tMax.y), tMax.z);
+    return maxT > minT;
+}
+
+// View direction goes from the camera to the point.
+vec3 InScatteredLight(vec3 point, vec3 viewDirection)
+{
+    vec3 light = vec3(0.0);
+
+    for (int i = 0; i < lightCount; ++i)
+    {
+        if (lightKind[i] == LIGHT_KIND_DIRECTIONAL)
+        {
+            float phase = S_HenyeyGreenstein(dot(lightDirection[i], viewDirection), lightAnisotropy[i]);
+            light += lightColor[i] * phase;
+        }
+        else
+        {
+            vec3 toLight = lightPosition[i] - point;
+            float distance = length(toLight);
+            vec3 toLightDirection = toLight / max(distance, 1.0e-6);
+            float attenuation = S_LightDistanceAttenuation(distance, lightRadius[i]);
+
+            if (lightKind[i] == LIGHT_KIND_SPOT)
+            {
+                float spotAngleCos = dot(lightDirection[i], toLightDirection);
+                attenuation *= smoothstep(halfConeAngleCos[i], halfHotspotConeAngleCos[i], spotAngleCos);
+            }
+
+            // Light travels from the light source and is scattered towards the camera.
+            float phase = S_HenyeyGreenstein(dot(toLightDirection, viewDirection), lightAnisotropy[i]);
+
+            light += lightColor[i] * attenuation * phase;
+        }
+    }
+
+    return light;
+}
+
+// Interleaved gradient noise, it is used to jitter ray-marching start to hide banding.
+float Noise(vec2 pixel)
+{
+    return fract(52.9829189 * fract(dot(pixel, vec2(0.06711056, 0.00583715))));
+}
+
+void main()
+{
+    vec3 fragmentPosition = S_UnProject(vec3(texCoord, texture(depthTexture, texCoord).r), invViewProj);
+
+    vec3 localOrigin = (invWorldMatrix * vec4(cameraPosition, 1.0)).xyz;
+    vec3 localEnd = (invWorldMatrix * vec4(fragmentPosition, 1.0)).xyz;
+
+    // Ray parameter is in [0; 1] range between the camera and the fragment, it is the same in
+    // local and world spaces.
+    float minT, maxT;
+    if (!IntersectVolume(localOrigin, localEnd - localOrigin, minT, maxT))
+    {
+        discard;
+    }
+
+    minT = max(minT, 0.0);
+    maxT = min(maxT, 1.0);
+    if (maxT <= minT)
+    {
+        discard;
+    }
+
+    float rayLength = length(fragmentPosition - cameraPosition);
+    float distance = (maxT - minT) * rayLength;
+    float transmittance = exp(-density * distance);
+    vec3 ambient = fogColor * ambientColor;
+
+    if (sampleCount <= 0)
+    {
+        // Classic exponential fog.
+        FragColor = vec4(ambient * (1.0 - transmittance), 1.0 - transmittance);
+        return;
+    }
+
+    float stepT = (maxT - minT) / float(sampleCount);
+    float stepTransmittance = exp(-density * stepT * rayLength);
+    float t = minT + stepT * Noise(gl_FragCoord.xy);
+
+    vec3 viewDirection = normalize(fragmentPosition - cameraPosition);
+    vec3 scattered = vec3(0.0);
+    float accumulatedTransmittance = 1.0;
+    for (int i = 0; i < sampleCount; ++i)
+    {
+        vec3 point = mix(cameraPosition, fragmentPosition, t);
+
+        vec3 light = ambient + fogColor * InScatteredLight(point, viewDirection);
+
+        // Energy-conserving integration of the light scattered in the step.
+        scattered += accumulatedTransmittance * (1.0 - stepTransmittance) * light;
+        accumulatedTransmittance *= stepTransmittance;
+
+        t += stepT;
+    }
+
+    FragColor = vec4(scattered, 1.0 - accumulatedTransmittance);
+}
@@ -8,6 +8,8 @@ uniform float minLuminance;
 uniform float maxLuminance;
 uniform bool autoExposure;
 uniform float fixedExposure;
+uniform bool hdrOutput;
+uniform float peakWhite;
 
 in vec2 texCoord;
 
@@ -36,11 +38,26 @@ void main() {
         exposure = fixedExposure;
     }
 
-    vec4 ldrColor = vec4(1.0) - exp(-hdrColor * exposure);
-
-    if (useColorGrading) {
-        outLdrColor = vec4(ColorGrading(S_LinearToSRGB(ldrColor).rgb), 1.0);
+    if (hdrOutput) {
+        // The same curve, but it goes up to the peak white of a display instead of SDR white, the
+        // output is not tone mapped again.
+        vec4 mappedColor = peakWhite * (vec4(1.0) - exp(-hdrColor * exposure / peakWhite));
+        vec4 encodedColor = S_LinearToSRGB(mappedColor);
+
+        if (useColorGrading) {
+            // Color grading table covers only SDR range, the rest is kept as is.
+            vec3 sdrColor = min(encodedColor.rgb, vec3(1.0));
+            outLdrColor = vec4(ColorGrading(sdrColor) + (encodedColor.rgb - sdrColor), 1.0);
+        } else {
+            outLdrColor = encodedColor;
+        }
     } else {
-        outLdrColor = S_LinearToSRGB(ldrColor);
+        vec4 ldrColor = vec4(1.0) - exp(-hdrColor * exposure);
+
+        if (useColorGrading) {
+            outLdrColor = vec4(ColorGrading(S_LinearToSRGB(ldrColor).rgb), 1.0);
+        } else {
+            outLdrColor = S_LinearToSRGB(ldrColor);
+        }
     }
 }
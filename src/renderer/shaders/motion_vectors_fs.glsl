@@ -0,0 +1,15 @@
+// Debug visualization of motion vectors: red and green channels show horizontal and vertical
+// motion respectively, full intensity corresponds to the motion of `scale` pixels per frame.
+
+uniform sampler2D motionVectorsTexture;
+uniform vec2 screenSize;
+uniform float scale;
+
+in vec2 texCoord;
+out vec4 FragColor;
+
+void main()
+{
+    vec2 motion = texture(motionVectorsTexture, texCoord).xy * screenSize;
+    FragColor = vec4(abs(motion) / scale, 0.0, 1.0);
+}
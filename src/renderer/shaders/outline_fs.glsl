@@ -0,0 +1,63 @@
+// Occluded outline styles, must be in sync with OccludedOutlineStyle.
+#define OCCLUDED_HIDDEN 0
+#define OCCLUDED_DIMMED 1
+#define OCCLUDED_DASHED 2
+
+#define DASH_LENGTH 6.0
+
+uniform sampler2D maskTexture;
+uniform sampler2D depthTexture;
+uniform vec2 inverseScreenSize;
+uniform int width;
+uniform vec4 color;
+uniform int occludedStyle;
+uniform float occludedOpacity;
+
+in vec2 texCoord;
+
+out vec4 FragColor;
+
+void main()
+{
+    // Mask contains depth of highlighted objects, pixels covered by them are not outlined.
+    if (texture(maskTexture, texCoord).r < 1.0) {
+        discard;
+    }
+
+    // Find nearest covered pixel within the width of the outline.
+    int maxDistance = width * width;
+    int nearestDistance = maxDistance + 1;
+    vec2 nearestCoord = texCoord;
+    for (int y = -width; y <= width; ++y) {
+        for (int x = -width; x <= width; ++x) {
+            int distance = x * x + y * y;
+            if (distance < nearestDistance) {
+                vec2 coord = texCoord + vec2(float(x), float(y)) * inverseScreenSize;
+                if (texture(maskTexture, coord).r < 1.0) {
+                    nearestDistance = distance;
+                    nearestCoord = coord;
+                }
+            }
+        }
+    }
+
+    if (nearestDistance > maxDistance) {
+        discard;
+    }
+
+    // The silhouette is occluded if there is something in front of it in the scene.
+    float maskDepth = texture(maskTexture, nearestCoord).r;
+    float sceneDepth = texture(depthTexture, nearestCoord).r;
+    float alpha = color.a;
+    if (maskDepth > sceneDepth + 0.0001) {
+        if (occludedStyle == OCCLUDED_HIDDEN) {
+            discard;
+        } else if (occludedStyle == OCCLUDED_DIMMED) {
+            alpha *= occludedOpacity;
+        } else if (mod(floor((gl_FragCoord.x + gl_FragCoord.y) / DASH_LENGTH), 2.0) > 0.5) {
+            discard;
+        }
+    }
+
+    FragColor = vec4(color.rgb, alpha);
+}
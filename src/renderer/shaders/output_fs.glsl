@@ -0,0 +1,51 @@
+// Output targets, must match `OutputTarget` enum.
+#define OUTPUT_TARGET_SRGB 0
+#define OUTPUT_TARGET_REC709 1
+#define OUTPUT_TARGET_HDR10 2
+
+uniform sampler2D frameTexture;
+uniform int outputTarget;
+uniform float paperWhite;
+uniform float maxLuminance;
+
+in vec2 texCoord;
+
+out vec4 FragColor;
+
+// Rec.709 (sRGB) to Rec.2020 primaries, both with D65 white point. Columns go first.
+const mat3 Rec709ToRec2020 = mat3(
+    0.6274040, 0.0690970, 0.0163916,
+    0.3292820, 0.9195400, 0.0880132,
+    0.0433136, 0.0113612, 0.8955950
+);
+
+// SMPTE ST 2084 inverse EOTF, input is luminance normalized to 10000 nits.
+vec3 LinearToPQ(vec3 y) {
+    const float m1 = 2610.0 / 16384.0;
+    const float m2 = 2523.0 / 4096.0 * 128.0;
+    const float c1 = 3424.0 / 4096.0;
+    const float c2 = 2413.0 / 4096.0 * 32.0;
+    const float c3 = 2392.0 / 4096.0 * 32.0;
+    vec3 ym1 = pow(clamp(y, 0.0, 1.0), vec3(m1));
+    return pow((c1 + c2 * ym1) / (1.0 + c3 * ym1), vec3(m2));
+}
+
+void main() {
+    vec4 frameColor = texture(frameTexture, texCoord);
+
+    // The frame is sRGB-encoded, values above 1.0 are brighter than SDR white.
+    vec3 linear = S_SRGBToLinear(max(frameColor, vec4(0.0))).rgb;
+
+    vec3 color;
+    if (outputTarget == OUTPUT_TARGET_REC709) {
+        // BT.1886 with zero black level is a pure 2.4 gamma.
+        color = pow(clamp(linear, 0.0, 1.0), vec3(1.0 / 2.4));
+    } else if (outputTarget == OUTPUT_TARGET_HDR10) {
+        vec3 nits = clamp(Rec709ToRec2020 * linear * paperWhite, 0.0, maxLuminance);
+        color = LinearToPQ(nits / 10000.0);
+    } else {
+        color = S_LinearToSRGB(vec4(linear, 1.0)).rgb;
+    }
+
+    FragColor = vec4(color, frameColor.a);
+}
@@ -5,6 +5,7 @@ uniform mat4 invProj;
 uniform float lightRadius;
 uniform vec3 lightColor;
 uniform vec3 scatterFactor;
+uniform float scatterAnisotropy;
 
 out vec4 FragColor;
 
@@ -29,7 +30,7 @@ void main()
 
             vec3 closestPoint = viewDirection * minDepth;
 
-            scatter = scatterFactor * S_InScatter(closestPoint, viewDirection, lightPosition, maxDepth - minDepth);
+            scatter = scatterFactor * S_InScatterAnisotropic(closestPoint, viewDirection, lightPosition, maxDepth - minDepth, scatterAnisotropy);
         }
     }
 
@@ -6,6 +6,7 @@ uniform float coneAngleCos;
 uniform mat4 invProj;
 uniform vec3 lightColor;
 uniform vec3 scatterFactor;
+uniform float scatterAnisotropy;
 
 out vec4 FragColor;
 
@@ -53,7 +54,7 @@ void main()
             minDepth = max(minDepth, 0.0);
             maxDepth = clamp(maxDepth, 0.0, fragmentDepth);
 
-            scatter = scatterFactor * S_InScatter(viewDirection * minDepth, viewDirection, lightPosition, maxDepth - minDepth);
+            scatter = scatterFactor * S_InScatterAnisotropic(viewDirection * minDepth, viewDirection, lightPosition, maxDepth - minDepth, scatterAnisotropy);
         }
     }
 
@@ -1,4 +1,5 @@
-#define KERNEL_SIZE 32
+// Keep in sync with MAX_SSAO_SAMPLES.
+#define MAX_KERNEL_SIZE 64
 
 uniform sampler2D depthSampler;
 uniform sampler2D normalSampler;
@@ -7,11 +8,14 @@ uniform sampler2D noiseSampler;
 uniform float radius;
 uniform mat4 inverseProjectionMatrix;
 uniform mat4 projectionMatrix;
-uniform vec3 kernel[KERNEL_SIZE];
+uniform int sampleCount;
+uniform vec3 kernel[MAX_KERNEL_SIZE];
 uniform vec2 noiseScale;
+uniform vec2 noiseOffset;
 uniform mat3 viewMatrix;
 
-out float finalOcclusion;
+// RGB - world space bent normal packed into [0; 1] range, A - ambient occlusion.
+out vec4 result;
 
 in vec2 texCoord;
 
@@ -23,15 +27,21 @@ void main() {
     vec3 fragPos = GetViewSpacePosition(texCoord);
     vec3 worldSpaceNormal = texture(normalSampler, texCoord).xyz * 2.0 - 1.0;
     vec3 viewSpaceNormal = normalize(viewMatrix * worldSpaceNormal);
-    vec3 randomVec = normalize(texture(noiseSampler, texCoord * noiseScale).xyz * 2.0 - 1.0);
+    vec3 randomVec = normalize(texture(noiseSampler, texCoord * noiseScale + noiseOffset).xyz * 2.0 - 1.0);
 
     vec3 tangent = normalize(randomVec - viewSpaceNormal * dot(randomVec, viewSpaceNormal));
     vec3 bitangent = normalize(cross(viewSpaceNormal, tangent));
     mat3 TBN = mat3(tangent, bitangent, viewSpaceNormal);
 
+    // Bias grows with the distance, this prevents self-occlusion of flat surfaces at grazing
+    // angles, where depth precision is the worst.
+    float bias = 0.02 * radius + 0.002 * abs(fragPos.z);
+
     float occlusion = 0.0;
-    for (int i = 0; i < KERNEL_SIZE; ++i) {
-        vec3 samplePoint = fragPos.xyz + TBN * kernel[i] * radius;
+    vec3 bentNormal = vec3(0.0);
+    for (int i = 0; i < sampleCount; ++i) {
+        vec3 sampleDirection = TBN * kernel[i];
+        vec3 samplePoint = fragPos.xyz + sampleDirection * radius;
 
         vec4 offset = projectionMatrix * vec4(samplePoint, 1.0);
         offset.xy /= offset.w;
@@ -39,9 +49,18 @@ void main() {
 
         vec3 position = GetViewSpacePosition(offset.xy);
 
-        float rangeCheck = smoothstep(0.0, 1.0, radius / abs(fragPos.z - position.z));
-        occlusion += rangeCheck * ((position.z > samplePoint.z + 0.04) ? 1.0 : 0.0);
+        float rangeCheck = smoothstep(0.0, 1.0, radius / max(abs(fragPos.z - position.z), 0.0001));
+        float sampleOcclusion = rangeCheck * ((position.z > samplePoint.z + bias) ? 1.0 : 0.0);
+        occlusion += sampleOcclusion;
+        bentNormal += normalize(sampleDirection) * (1.0 - sampleOcclusion);
+    }
+
+    if (dot(bentNormal, bentNormal) < 0.0001) {
+        bentNormal = viewSpaceNormal;
     }
 
-    finalOcclusion = 1.0 - occlusion / float(KERNEL_SIZE);
-}
\ No newline at end of file
+    // View matrix is orthonormal, so its inverse is its transpose.
+    vec3 worldSpaceBentNormal = normalize(transpose(viewMatrix) * normalize(bentNormal));
+
+    result = vec4(worldSpaceBentNormal * 0.5 + 0.5, 1.0 - occlusion / float(sampleCount));
+}
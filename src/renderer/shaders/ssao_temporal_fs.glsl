@@ -0,0 +1,41 @@
+// Temporal accumulation of ambient occlusion. Blends current (noisy) occlusion with reprojected
+// history, history is clamped to the range of the 3x3 neighborhood of a pixel to reduce ghosting.
+
+uniform sampler2D currentTexture;
+uniform sampler2D historyTexture;
+uniform sampler2D depthTexture;
+uniform mat4 invViewProjection;
+uniform mat4 prevViewProjection;
+uniform bool historyValid;
+uniform float blendFactor;
+
+in vec2 texCoord;
+out vec4 FragColor;
+
+void main()
+{
+    vec4 current = texture(currentTexture, texCoord);
+
+    vec3 worldPosition = S_UnProject(vec3(texCoord, texture(depthTexture, texCoord).r), invViewProjection);
+    vec2 historyTexCoord = S_Project(worldPosition, prevViewProjection).xy;
+
+    if (!historyValid || any(lessThan(historyTexCoord, vec2(0.0))) || any(greaterThan(historyTexCoord, vec2(1.0)))) {
+        FragColor = current;
+        return;
+    }
+
+    vec2 texelSize = 1.0 / vec2(textureSize(currentTexture, 0));
+    vec4 minValue = current;
+    vec4 maxValue = current;
+    for (int y = -1; y <= 1; ++y) {
+        for (int x = -1; x <= 1; ++x) {
+            vec4 neighbour = texture(currentTexture, texCoord + vec2(float(x), float(y)) * texelSize);
+            minValue = min(minValue, neighbour);
+            maxValue = max(maxValue, neighbour);
+        }
+    }
+
+    vec4 history = clamp(texture(historyTexture, historyTexCoord), minValue, maxValue);
+
+    FragColor = mix(history, current, blendFactor);
+}
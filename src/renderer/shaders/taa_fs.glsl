@@ -0,0 +1,103 @@
+// Temporal anti-aliasing resolve pass. Blends current (jittered) frame with reprojected history,
+// history is clamped to the color range of the 3x3 neighborhood of a pixel to reduce ghosting.
+//
+// Based on "High Quality Temporal Supersampling" by Brian Karis.
+
+uniform sampler2D currentTexture;
+uniform sampler2D historyTexture;
+uniform sampler2D motionVectorsTexture;
+uniform sampler2D depthTexture;
+uniform mat4 invViewProjection;
+uniform mat4 prevViewProjection;
+uniform vec2 inverseScreenSize;
+uniform bool historyValid;
+uniform float blendFactor;
+
+in vec2 texCoord;
+out vec4 FragColor;
+
+vec3 RGBToYCoCg(vec3 c)
+{
+    return vec3(
+        0.25 * c.r + 0.5 * c.g + 0.25 * c.b,
+        0.5 * c.r - 0.5 * c.b,
+        -0.25 * c.r + 0.5 * c.g - 0.25 * c.b
+    );
+}
+
+vec3 YCoCgToRGB(vec3 c)
+{
+    return vec3(
+        c.x + c.y - c.z,
+        c.x + c.z,
+        c.x - c.y - c.z
+    );
+}
+
+// Frame is in HDR, very bright pixels would dominate blending, so samples are tone mapped before
+// blending and restored afterwards.
+vec3 Tonemap(vec3 c)
+{
+    return c / (1.0 + max(c.r, max(c.g, c.b)));
+}
+
+vec3 InverseTonemap(vec3 c)
+{
+    return c / max(1.0 - max(c.r, max(c.g, c.b)), 0.0001);
+}
+
+vec3 FetchCurrent(vec2 uv)
+{
+    return RGBToYCoCg(Tonemap(texture(currentTexture, uv).rgb));
+}
+
+void main()
+{
+    vec3 current = FetchCurrent(texCoord);
+
+    vec3 minColor = current;
+    vec3 maxColor = current;
+    float closestDepth = texture(depthTexture, texCoord).r;
+    vec2 closestOffset = vec2(0.0);
+    for (int y = -1; y <= 1; ++y) {
+        for (int x = -1; x <= 1; ++x) {
+            vec2 offset = vec2(float(x), float(y)) * inverseScreenSize;
+
+            vec3 color = FetchCurrent(texCoord + offset);
+            minColor = min(minColor, color);
+            maxColor = max(maxColor, color);
+
+            // Use motion of the closest fragment in the neighborhood, this keeps edges of moving
+            // objects sharp.
+            float depth = texture(depthTexture, texCoord + offset).r;
+            if (depth < closestDepth) {
+                closestDepth = depth;
+                closestOffset = offset;
+            }
+        }
+    }
+
+    vec2 motion;
+    if (closestDepth < 1.0) {
+        motion = texture(motionVectorsTexture, texCoord + closestOffset).xy;
+    } else {
+        // There is no geometry (sky), reproject the pixel using camera motion only.
+        vec3 worldPosition = S_UnProject(vec3(texCoord, 1.0), invViewProjection);
+        vec3 prevScreenPosition = S_Project(worldPosition, prevViewProjection);
+        motion = texCoord - prevScreenPosition.xy;
+    }
+
+    vec2 historyTexCoord = texCoord - motion;
+
+    if (!historyValid || any(lessThan(historyTexCoord, vec2(0.0))) || any(greaterThan(historyTexCoord, vec2(1.0)))) {
+        FragColor = vec4(texture(currentTexture, texCoord).rgb, 1.0);
+        return;
+    }
+
+    vec3 history = RGBToYCoCg(Tonemap(texture(historyTexture, historyTexCoord).rgb));
+    history = clamp(history, minColor, maxColor);
+
+    vec3 result = mix(history, current, blendFactor);
+
+    FragColor = vec4(InverseTonemap(YCoCgToRGB(result)), 1.0);
+}
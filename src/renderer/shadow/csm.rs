@@ -95,7 +95,7 @@ pub struct CsmRenderer {
     render_pass_name: ImmutableString,
 }
 
-pub(in crate) struct CsmRenderContext<'a, 'c> {
+pub(crate) struct CsmRenderContext<'a, 'c> {
     pub frame_size: Vector2<f32>,
     pub state: &'a mut PipelineState,
     pub graph: &'c Graph,
@@ -140,7 +140,7 @@ impl CsmRenderer {
         &self.cascades
     }
 
-    pub(in crate) fn render(&mut self, ctx: CsmRenderContext) -> RenderPassStatistics {
+    pub(crate) fn render(&mut self, ctx: CsmRenderContext) -> RenderPassStatistics {
         let mut stats = RenderPassStatistics::default();
 
         let CsmRenderContext {
@@ -251,7 +251,7 @@ impl CsmRenderer {
                             false
                         };
 
-                        if !visible {
+                        if !visible || !camera.can_see_layers(instance.render_mask) {
                             continue;
                         }
 
@@ -268,19 +268,28 @@ impl CsmRenderer {
                                 depth_test: true,
                                 blend: None,
                                 stencil_op: Default::default(),
+                                alpha_to_coverage: false,
+                                depth_func: Default::default(),
                             },
                             |mut program_binding| {
+                                let wvp_matrix = light_view_projection * instance.world_transform;
                                 apply_material(MaterialContext {
                                     material: &*material,
                                     program_binding: &mut program_binding,
                                     texture_cache,
                                     world_matrix: &instance.world_transform,
-                                    wvp_matrix: &(light_view_projection * instance.world_transform),
-                                    bone_matrices: &instance.bone_matrices,
+                                    wvp_matrix: &wvp_matrix,
+                                    bone_matrices: batch_storage.instance_bone_matrices(instance),
+                                    bone_matrices_offset: instance.bone_matrices.start,
+                                    bone_matrices_storage: batch_storage.bone_matrices_texture(),
                                     use_skeletal_animation: batch.is_skinned,
                                     camera_position: &camera.global_position(),
                                     use_pom: false,
                                     light_position: &Default::default(),
+                                    prev_wvp_matrix: &wvp_matrix,
+                                    prev_bone_matrices_storage: None,
+                                    jitter: &Default::default(),
+                                    wind: &batch_storage.wind,
                                     normal_dummy: normal_dummy.clone(),
                                     white_dummy: white_dummy.clone(),
                                     black_dummy: black_dummy.clone(),
@@ -1,7 +1,18 @@
 #![warn(clippy::too_many_arguments)]
 
-use crate::core::math::frustum::Frustum;
-use crate::renderer::batch::{SurfaceInstance, SurfaceInstanceFlags};
+use crate::{
+    core::{
+        math::{aabb::AxisAlignedBoundingBox, frustum::Frustum},
+        pool::Handle,
+    },
+    renderer::{
+        batch::{BatchStorage, SurfaceInstance, SurfaceInstanceFlags},
+        framework::{error::FrameworkError, framebuffer::FrameBuffer},
+    },
+    scene::{base::LayerMask, node::Node},
+};
+use fxhash::{FxHashMap, FxHasher};
+use std::{hash::Hasher, sync::Arc};
 
 pub mod csm;
 pub mod point;
@@ -16,15 +27,280 @@ pub fn cascade_size(base_size: usize, cascade: usize) -> usize {
     }
 }
 
-fn should_cast_shadows(surface_instance: &SurfaceInstance, light_frustum: &Frustum) -> bool {
+/// Defines which shadow casters are drawn into a shadow map.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShadowCasters {
+    /// Every shadow caster.
+    All,
+    /// Only instances of nodes with static or stationary mobility, they're drawn into cached
+    /// static shadow maps.
+    Static,
+    /// Only instances of nodes with dynamic mobility, they're drawn every frame in addition to a
+    /// cached static shadow map.
+    Dynamic,
+}
+
+impl ShadowCasters {
+    fn accepts(self, surface_instance: &SurfaceInstance) -> bool {
+        let is_static = surface_instance
+            .flags
+            .contains(SurfaceInstanceFlags::IS_STATIC);
+        match self {
+            ShadowCasters::All => true,
+            ShadowCasters::Static => is_static,
+            ShadowCasters::Dynamic => !is_static,
+        }
+    }
+}
+
+fn is_shadow_caster(surface_instance: &SurfaceInstance, culling_mask: LayerMask) -> bool {
     surface_instance
         .flags
         .contains(SurfaceInstanceFlags::IS_VISIBLE)
+        && surface_instance.render_mask.intersects(culling_mask)
         && surface_instance
             .flags
             .contains(SurfaceInstanceFlags::CAST_SHADOWS)
+}
+
+// Lights have no masks of their own, so shadows are cast only by instances that can be seen by
+// the camera the shadow maps are rendered for.
+fn should_cast_shadows(
+    surface_instance: &SurfaceInstance,
+    light_frustum: &Frustum,
+    culling_mask: LayerMask,
+    casters: ShadowCasters,
+) -> bool {
+    is_shadow_caster(surface_instance, culling_mask)
+        && casters.accepts(surface_instance)
         && (!surface_instance
             .flags
             .contains(SurfaceInstanceFlags::FRUSTUM_CULLING)
             || light_frustum.is_intersects_aabb(&surface_instance.world_aabb))
 }
+
+/// Calculates a key of the static shadow casters in the area of effect of a light. The key
+/// changes when any static caster in the area is moved, added or removed, or when a parameter of
+/// the light (`light_params`) is changed, so a cached static shadow map is valid while its key
+/// stays the same.
+pub(crate) fn static_shadow_casters_key<F>(
+    batches: &BatchStorage,
+    culling_mask: LayerMask,
+    light_params: &[f32],
+    mut is_in_light_area: F,
+) -> u64
+where
+    F: FnMut(&AxisAlignedBoundingBox) -> bool,
+{
+    let mut hasher = FxHasher::default();
+
+    hasher.write_u32(culling_mask.0);
+    for param in light_params {
+        hasher.write_u32(param.to_bits());
+    }
+
+    for batch in batches.batches.iter() {
+        for instance in batch.instances.iter() {
+            if is_shadow_caster(instance, culling_mask)
+                && ShadowCasters::Static.accepts(instance)
+                && is_in_light_area(&instance.world_aabb)
+            {
+                hasher.write_usize(Arc::as_ptr(&batch.data) as usize);
+                hasher.write_u32(instance.owner.index());
+                hasher.write_u32(instance.owner.generation());
+                // World transform of skinned instances is always identity, their bounds are
+                // defined by bones, so the bounds must be hashed as well.
+                for value in instance
+                    .world_transform
+                    .iter()
+                    .chain(instance.world_aabb.min.iter())
+                    .chain(instance.world_aabb.max.iter())
+                {
+                    hasher.write_u32(value.to_bits());
+                }
+            }
+        }
+    }
+
+    hasher.finish()
+}
+
+struct StaticShadowMap {
+    framebuffer: FrameBuffer,
+    key: u64,
+    used: bool,
+}
+
+/// A set of cached shadow maps of static geometry, one per light with static shadows.
+#[derive(Default)]
+pub(crate) struct StaticShadowMapCache {
+    maps: FxHashMap<Handle<Node>, StaticShadowMap>,
+}
+
+impl StaticShadowMapCache {
+    /// Returns a cached shadow map of the light and a flag that tells whether the map must be
+    /// (re)rendered, because it was just created or its key has changed.
+    pub fn get_or_create<F>(
+        &mut self,
+        light: Handle<Node>,
+        key: u64,
+        make_framebuffer: F,
+    ) -> Result<(&mut FrameBuffer, bool), FrameworkError>
+    where
+        F: FnOnce() -> Result<FrameBuffer, FrameworkError>,
+    {
+        let (map, outdated) = match self.maps.entry(light) {
+            std::collections::hash_map::Entry::Occupied(entry) => {
+                let map = entry.into_mut();
+                let outdated = map.key != key;
+                (map, outdated)
+            }
+            std::collections::hash_map::Entry::Vacant(entry) => (
+                entry.insert(StaticShadowMap {
+                    framebuffer: make_framebuffer()?,
+                    key,
+                    used: false,
+                }),
+                true,
+            ),
+        };
+        map.key = key;
+        map.used = true;
+        Ok((&mut map.framebuffer, outdated))
+    }
+
+    pub fn get(&self, light: Handle<Node>) -> Option<&FrameBuffer> {
+        self.maps.get(&light).map(|map| &map.framebuffer)
+    }
+
+    /// Removes shadow maps of lights that weren't rendered since the previous call, it frees
+    /// memory occupied by maps of deleted lights and lights without static shadows.
+    pub fn collect_garbage(&mut self) {
+        self.maps.retain(|_, map| std::mem::take(&mut map.used));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector2, Vector3},
+            parking_lot::Mutex,
+            pool::Handle,
+        },
+        renderer::{
+            batch::BatchStorage,
+            shadow::{static_shadow_casters_key, ShadowCasters},
+        },
+        scene::{
+            base::{BaseBuilder, LayerMask, Mobility},
+            graph::Graph,
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData},
+                MeshBuilder,
+            },
+            node::Node,
+            transform::TransformBuilder,
+        },
+    };
+    use std::sync::Arc;
+
+    const LIGHT_POSITION: Vector3<f32> = Vector3::new(0.0, 2.0, 0.0);
+    const LIGHT_RADIUS: f32 = 10.0;
+
+    fn make_cube(graph: &mut Graph, position: Vector3<f32>, mobility: Mobility) -> Handle<Node> {
+        MeshBuilder::new(
+            BaseBuilder::new()
+                .with_mobility(mobility)
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+            SurfaceData::make_cube(Matrix4::identity()),
+        )))
+        .build()])
+        .build(graph)
+    }
+
+    fn key(graph: &mut Graph, light_radius: f32) -> u64 {
+        // Full update is needed to refresh world bounds of the meshes.
+        graph.update(Vector2::new(100.0, 100.0), 0.0);
+        let mut batch_storage = BatchStorage::default();
+        batch_storage.generate_batches(graph, None);
+        static_shadow_casters_key(
+            &batch_storage,
+            LayerMask::ALL,
+            &[
+                LIGHT_POSITION.x,
+                LIGHT_POSITION.y,
+                LIGHT_POSITION.z,
+                light_radius,
+            ],
+            |aabb| aabb.is_intersects_sphere(LIGHT_POSITION, light_radius),
+        )
+    }
+
+    #[test]
+    fn test_static_shadow_casters_key() {
+        let mut graph = Graph::new();
+        let wall = make_cube(&mut graph, Vector3::new(2.0, 0.0, 0.0), Mobility::Static);
+        let far_wall = make_cube(&mut graph, Vector3::new(100.0, 0.0, 0.0), Mobility::Static);
+        let dynamic = make_cube(&mut graph, Vector3::new(-2.0, 0.0, 0.0), Mobility::Dynamic);
+
+        let initial = key(&mut graph, LIGHT_RADIUS);
+        assert_eq!(key(&mut graph, LIGHT_RADIUS), initial);
+
+        // Dynamic casters are not cached, so they must not invalidate the cache.
+        graph[dynamic]
+            .local_transform_mut()
+            .set_position(Vector3::new(-3.0, 0.0, 0.0));
+        assert_eq!(key(&mut graph, LIGHT_RADIUS), initial);
+
+        // Static casters outside of the light area do not affect its shadows.
+        graph[far_wall]
+            .local_transform_mut()
+            .set_position(Vector3::new(101.0, 0.0, 0.0));
+        assert_eq!(key(&mut graph, LIGHT_RADIUS), initial);
+
+        // Changes of the light must invalidate the cache.
+        assert_ne!(key(&mut graph, LIGHT_RADIUS * 2.0), initial);
+
+        // As well as movement of static casters in the light area.
+        graph[wall]
+            .local_transform_mut()
+            .set_position(Vector3::new(3.0, 0.0, 0.0));
+        let moved = key(&mut graph, LIGHT_RADIUS);
+        assert_ne!(moved, initial);
+
+        // Or removal of them.
+        graph.remove_node(wall);
+        assert_ne!(key(&mut graph, LIGHT_RADIUS), moved);
+    }
+
+    #[test]
+    fn test_shadow_casters_filter() {
+        let mut graph = Graph::new();
+        let static_cube = make_cube(&mut graph, Vector3::default(), Mobility::Static);
+        let stationary_cube = make_cube(&mut graph, Vector3::default(), Mobility::Stationary);
+        let dynamic_cube = make_cube(&mut graph, Vector3::default(), Mobility::Dynamic);
+        graph.update_hierarchical_data();
+
+        let mut batch_storage = BatchStorage::default();
+        batch_storage.generate_batches(&graph, None);
+
+        for instance in batch_storage
+            .batches
+            .iter()
+            .flat_map(|b| b.instances.iter())
+        {
+            let is_static = instance.owner == static_cube || instance.owner == stationary_cube;
+            assert_eq!(instance.owner == dynamic_cube, !is_static);
+            assert!(ShadowCasters::All.accepts(instance));
+            assert_eq!(ShadowCasters::Static.accepts(instance), is_static);
+            assert_eq!(ShadowCasters::Dynamic.accepts(instance), !is_static);
+        }
+    }
+}
@@ -3,6 +3,7 @@ use crate::{
         algebra::{Matrix4, Point3, Vector3},
         color::Color,
         math::{frustum::Frustum, Rect},
+        pool::Handle,
         scope_profile,
         sstorage::ImmutableString,
     },
@@ -19,9 +20,10 @@ use crate::{
             },
             state::PipelineState,
         },
-        shadow::{cascade_size, should_cast_shadows},
+        shadow::{cascade_size, should_cast_shadows, ShadowCasters, StaticShadowMapCache},
         GeometryCache, MaterialContext, RenderPassStatistics, ShadowMapPrecision,
     },
+    scene::{base::LayerMask, node::Node},
 };
 use std::{cell::RefCell, rc::Rc};
 
@@ -31,6 +33,8 @@ pub struct PointShadowMapRenderer {
     size: usize,
     faces: [PointShadowCubeMapFace; 6],
     render_pass_name: ImmutableString,
+    // Shadow maps of static geometry, they have the size of the first cascade.
+    static_maps: StaticShadowMapCache,
 }
 
 struct PointShadowCubeMapFace {
@@ -39,7 +43,7 @@ struct PointShadowCubeMapFace {
     up: Vector3<f32>,
 }
 
-pub(in crate) struct PointShadowMapRenderContext<'a> {
+pub(crate) struct PointShadowMapRenderContext<'a> {
     pub state: &'a mut PipelineState,
     pub light_pos: Vector3<f32>,
     pub light_radius: f32,
@@ -51,6 +55,176 @@ pub(in crate) struct PointShadowMapRenderContext<'a> {
     pub normal_dummy: Rc<RefCell<GpuTexture>>,
     pub white_dummy: Rc<RefCell<GpuTexture>>,
     pub black_dummy: Rc<RefCell<GpuTexture>>,
+    pub culling_mask: LayerMask,
+    pub casters: ShadowCasters,
+}
+
+fn make_shadow_map(
+    state: &mut PipelineState,
+    size: usize,
+    precision: ShadowMapPrecision,
+) -> Result<FrameBuffer, FrameworkError> {
+    let depth = {
+        let kind = GpuTextureKind::Rectangle {
+            width: size,
+            height: size,
+        };
+        let mut texture = GpuTexture::new(
+            state,
+            kind,
+            match precision {
+                ShadowMapPrecision::Full => PixelKind::D32F,
+                ShadowMapPrecision::Half => PixelKind::D16,
+            },
+            MinificationFilter::Nearest,
+            MagnificationFilter::Nearest,
+            1,
+            None,
+        )?;
+        texture
+            .bind_mut(state, 0)
+            .set_minification_filter(MinificationFilter::Nearest)
+            .set_magnification_filter(MagnificationFilter::Nearest)
+            .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+        texture
+    };
+
+    let cube_map = {
+        let kind = GpuTextureKind::Cube {
+            width: size,
+            height: size,
+        };
+        let mut texture = GpuTexture::new(
+            state,
+            kind,
+            PixelKind::F16,
+            MinificationFilter::Linear,
+            MagnificationFilter::Linear,
+            1,
+            None,
+        )?;
+        texture
+            .bind_mut(state, 0)
+            .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::T, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::R, WrapMode::ClampToEdge);
+        texture
+    };
+
+    FrameBuffer::new(
+        state,
+        Some(Attachment {
+            kind: AttachmentKind::Depth,
+            texture: Rc::new(RefCell::new(depth)),
+        }),
+        vec![Attachment {
+            kind: AttachmentKind::Color,
+            texture: Rc::new(RefCell::new(cube_map)),
+        }],
+    )
+}
+
+// Renders shadow casters into every face of the cube map of the given frame buffer, the cascade
+// of the context is ignored.
+fn render_casters(
+    framebuffer: &mut FrameBuffer,
+    size: usize,
+    faces: &[PointShadowCubeMapFace],
+    render_pass_name: &ImmutableString,
+    args: PointShadowMapRenderContext,
+) -> RenderPassStatistics {
+    let mut statistics = RenderPassStatistics::default();
+
+    let PointShadowMapRenderContext {
+        state,
+        light_pos,
+        light_radius,
+        geom_cache,
+        cascade: _,
+        batch_storage,
+        shader_cache,
+        texture_cache,
+        normal_dummy,
+        white_dummy,
+        black_dummy,
+        culling_mask,
+        casters,
+    } = args;
+
+    let viewport = Rect::new(0, 0, size as i32, size as i32);
+
+    let light_projection_matrix =
+        Matrix4::new_perspective(1.0, std::f32::consts::FRAC_PI_2, 0.01, light_radius);
+
+    for face in faces.iter() {
+        framebuffer.set_cubemap_face(state, 0, face.face).clear(
+            state,
+            viewport,
+            Some(Color::WHITE),
+            Some(1.0),
+            None,
+        );
+
+        let light_look_at = light_pos + face.look;
+        let light_view_matrix = Matrix4::look_at_rh(
+            &Point3::from(light_pos),
+            &Point3::from(light_look_at),
+            &face.up,
+        );
+        let light_view_projection_matrix = light_projection_matrix * light_view_matrix;
+
+        let frustum = Frustum::from(light_view_projection_matrix).unwrap_or_default();
+
+        for batch in batch_storage.batches.iter() {
+            let material = batch.material.lock();
+            let geometry = geom_cache.get(state, &batch.data);
+
+            if let Some(render_pass) = shader_cache
+                .get(state, material.shader())
+                .and_then(|shader_set| shader_set.render_passes.get(render_pass_name))
+            {
+                for instance in batch.instances.iter() {
+                    if should_cast_shadows(instance, &frustum, culling_mask, casters) {
+                        statistics += framebuffer.draw(
+                            geometry,
+                            state,
+                            viewport,
+                            &render_pass.program,
+                            &render_pass.draw_params,
+                            |mut program_binding| {
+                                let wvp_matrix =
+                                    light_view_projection_matrix * instance.world_transform;
+                                apply_material(MaterialContext {
+                                    material: &*material,
+                                    program_binding: &mut program_binding,
+                                    texture_cache,
+                                    world_matrix: &instance.world_transform,
+                                    wvp_matrix: &wvp_matrix,
+                                    bone_matrices: batch_storage.instance_bone_matrices(instance),
+                                    bone_matrices_offset: instance.bone_matrices.start,
+                                    bone_matrices_storage: batch_storage.bone_matrices_texture(),
+                                    use_skeletal_animation: batch.is_skinned,
+                                    camera_position: &Default::default(),
+                                    use_pom: false,
+                                    light_position: &light_pos,
+                                    prev_wvp_matrix: &wvp_matrix,
+                                    prev_bone_matrices_storage: None,
+                                    jitter: &Default::default(),
+                                    wind: &batch_storage.wind,
+                                    normal_dummy: normal_dummy.clone(),
+                                    white_dummy: white_dummy.clone(),
+                                    black_dummy: black_dummy.clone(),
+                                });
+                            },
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    statistics
 }
 
 impl PointShadowMapRenderer {
@@ -59,78 +233,12 @@ impl PointShadowMapRenderer {
         size: usize,
         precision: ShadowMapPrecision,
     ) -> Result<Self, FrameworkError> {
-        fn make_cascade(
-            state: &mut PipelineState,
-            size: usize,
-            precision: ShadowMapPrecision,
-        ) -> Result<FrameBuffer, FrameworkError> {
-            let depth = {
-                let kind = GpuTextureKind::Rectangle {
-                    width: size,
-                    height: size,
-                };
-                let mut texture = GpuTexture::new(
-                    state,
-                    kind,
-                    match precision {
-                        ShadowMapPrecision::Full => PixelKind::D32F,
-                        ShadowMapPrecision::Half => PixelKind::D16,
-                    },
-                    MinificationFilter::Nearest,
-                    MagnificationFilter::Nearest,
-                    1,
-                    None,
-                )?;
-                texture
-                    .bind_mut(state, 0)
-                    .set_minification_filter(MinificationFilter::Nearest)
-                    .set_magnification_filter(MagnificationFilter::Nearest)
-                    .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
-                    .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
-                texture
-            };
-
-            let cube_map = {
-                let kind = GpuTextureKind::Cube {
-                    width: size,
-                    height: size,
-                };
-                let mut texture = GpuTexture::new(
-                    state,
-                    kind,
-                    PixelKind::F16,
-                    MinificationFilter::Linear,
-                    MagnificationFilter::Linear,
-                    1,
-                    None,
-                )?;
-                texture
-                    .bind_mut(state, 0)
-                    .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
-                    .set_wrap(Coordinate::T, WrapMode::ClampToEdge)
-                    .set_wrap(Coordinate::R, WrapMode::ClampToEdge);
-                texture
-            };
-
-            FrameBuffer::new(
-                state,
-                Some(Attachment {
-                    kind: AttachmentKind::Depth,
-                    texture: Rc::new(RefCell::new(depth)),
-                }),
-                vec![Attachment {
-                    kind: AttachmentKind::Color,
-                    texture: Rc::new(RefCell::new(cube_map)),
-                }],
-            )
-        }
-
         Ok(Self {
             precision,
             cascades: [
-                make_cascade(state, cascade_size(size, 0), precision)?,
-                make_cascade(state, cascade_size(size, 1), precision)?,
-                make_cascade(state, cascade_size(size, 2), precision)?,
+                make_shadow_map(state, cascade_size(size, 0), precision)?,
+                make_shadow_map(state, cascade_size(size, 1), precision)?,
+                make_shadow_map(state, cascade_size(size, 2), precision)?,
             ],
             size,
             faces: [
@@ -166,6 +274,7 @@ impl PointShadowMapRenderer {
                 },
             ],
             render_pass_name: ImmutableString::new("PointShadow"),
+            static_maps: Default::default(),
         })
     }
 
@@ -183,93 +292,63 @@ impl PointShadowMapRenderer {
             .clone()
     }
 
-    pub(in crate) fn render(&mut self, args: PointShadowMapRenderContext) -> RenderPassStatistics {
-        scope_profile!();
-
-        let mut statistics = RenderPassStatistics::default();
-
-        let PointShadowMapRenderContext {
-            state,
-            light_pos,
-            light_radius,
-            geom_cache,
-            cascade,
-            batch_storage,
-            shader_cache,
-            texture_cache,
-            normal_dummy,
-            white_dummy,
-            black_dummy,
-        } = args;
-
-        let framebuffer = &mut self.cascades[cascade];
-        let cascade_size = cascade_size(self.size, cascade);
-
-        let viewport = Rect::new(0, 0, cascade_size as i32, cascade_size as i32);
+    /// Returns a cached shadow map of static geometry of the light, if any.
+    pub fn static_texture(&self, light: Handle<Node>) -> Option<Rc<RefCell<GpuTexture>>> {
+        self.static_maps
+            .get(light)
+            .map(|framebuffer| framebuffer.color_attachments()[0].texture.clone())
+    }
 
-        let light_projection_matrix =
-            Matrix4::new_perspective(1.0, std::f32::consts::FRAC_PI_2, 0.01, light_radius);
+    /// Removes static shadow maps of lights that weren't rendered since the previous call.
+    pub(crate) fn collect_static_garbage(&mut self) {
+        self.static_maps.collect_garbage();
+    }
 
-        for face in self.faces.iter() {
-            framebuffer.set_cubemap_face(state, 0, face.face).clear(
-                state,
-                viewport,
-                Some(Color::WHITE),
-                Some(1.0),
-                None,
-            );
+    pub(crate) fn render(&mut self, args: PointShadowMapRenderContext) -> RenderPassStatistics {
+        scope_profile!();
 
-            let light_look_at = light_pos + face.look;
-            let light_view_matrix = Matrix4::look_at_rh(
-                &Point3::from(light_pos),
-                &Point3::from(light_look_at),
-                &face.up,
-            );
-            let light_view_projection_matrix = light_projection_matrix * light_view_matrix;
+        let cascade = args.cascade;
+        render_casters(
+            &mut self.cascades[cascade],
+            cascade_size(self.size, cascade),
+            &self.faces,
+            &self.render_pass_name,
+            args,
+        )
+    }
 
-            let frustum = Frustum::from(light_view_projection_matrix).unwrap_or_default();
+    /// Renders static shadow casters into the cached shadow map of the light, if the cached map
+    /// is missing or its key is different from the given one. Returns `None` if the cached map
+    /// is up-to-date and nothing was rendered. Shadow casters and cascade of the context are
+    /// ignored.
+    pub(crate) fn render_static(
+        &mut self,
+        light: Handle<Node>,
+        key: u64,
+        args: PointShadowMapRenderContext,
+    ) -> Result<Option<RenderPassStatistics>, FrameworkError> {
+        scope_profile!();
 
-            for batch in batch_storage.batches.iter() {
-                let material = batch.material.lock();
-                let geometry = geom_cache.get(state, &batch.data);
+        let size = self.size;
+        let precision = self.precision;
+        let state = &mut *args.state;
+        let (framebuffer, outdated) = self
+            .static_maps
+            .get_or_create(light, key, || make_shadow_map(state, size, precision))?;
 
-                if let Some(render_pass) = shader_cache
-                    .get(state, material.shader())
-                    .and_then(|shader_set| shader_set.render_passes.get(&self.render_pass_name))
-                {
-                    for instance in batch.instances.iter() {
-                        if should_cast_shadows(instance, &frustum) {
-                            statistics += framebuffer.draw(
-                                geometry,
-                                state,
-                                viewport,
-                                &render_pass.program,
-                                &render_pass.draw_params,
-                                |mut program_binding| {
-                                    apply_material(MaterialContext {
-                                        material: &*material,
-                                        program_binding: &mut program_binding,
-                                        texture_cache,
-                                        world_matrix: &instance.world_transform,
-                                        wvp_matrix: &(light_view_projection_matrix
-                                            * instance.world_transform),
-                                        bone_matrices: &instance.bone_matrices,
-                                        use_skeletal_animation: batch.is_skinned,
-                                        camera_position: &Default::default(),
-                                        use_pom: false,
-                                        light_position: &light_pos,
-                                        normal_dummy: normal_dummy.clone(),
-                                        white_dummy: white_dummy.clone(),
-                                        black_dummy: black_dummy.clone(),
-                                    });
-                                },
-                            );
-                        }
-                    }
-                }
-            }
+        if !outdated {
+            return Ok(None);
         }
 
-        statistics
+        Ok(Some(render_casters(
+            framebuffer,
+            size,
+            &self.faces,
+            &self.render_pass_name,
+            PointShadowMapRenderContext {
+                casters: ShadowCasters::Static,
+                ..args
+            },
+        )))
     }
 }
@@ -3,6 +3,7 @@ use crate::{
         algebra::Matrix4,
         color::Color,
         math::{frustum::Frustum, Rect},
+        pool::Handle,
         scope_profile,
         sstorage::ImmutableString,
     },
@@ -19,9 +20,10 @@ use crate::{
             },
             state::{ColorMask, PipelineState},
         },
-        shadow::{cascade_size, should_cast_shadows},
+        shadow::{cascade_size, should_cast_shadows, ShadowCasters, StaticShadowMapCache},
         GeometryCache, MaterialContext, RenderPassStatistics, ShadowMapPrecision,
     },
+    scene::{base::LayerMask, node::Node},
 };
 use std::{cell::RefCell, rc::Rc};
 
@@ -34,6 +36,131 @@ pub struct SpotShadowMapRenderer {
     cascades: [FrameBuffer; 3],
     size: usize,
     render_pass_name: ImmutableString,
+    // Shadow maps of static geometry, they have the size of the first cascade.
+    static_maps: StaticShadowMapCache,
+}
+
+fn make_shadow_map(
+    state: &mut PipelineState,
+    size: usize,
+    precision: ShadowMapPrecision,
+) -> Result<FrameBuffer, FrameworkError> {
+    let depth = {
+        let kind = GpuTextureKind::Rectangle {
+            width: size,
+            height: size,
+        };
+        let mut texture = GpuTexture::new(
+            state,
+            kind,
+            match precision {
+                ShadowMapPrecision::Full => PixelKind::D32F,
+                ShadowMapPrecision::Half => PixelKind::D16,
+            },
+            MinificationFilter::Linear,
+            MagnificationFilter::Linear,
+            1,
+            None,
+        )?;
+        texture
+            .bind_mut(state, 0)
+            .set_wrap(Coordinate::T, WrapMode::ClampToEdge)
+            .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+            .set_border_color(Color::WHITE);
+        texture
+    };
+
+    FrameBuffer::new(
+        state,
+        Some(Attachment {
+            kind: AttachmentKind::Depth,
+            texture: Rc::new(RefCell::new(depth)),
+        }),
+        vec![],
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render_casters(
+    framebuffer: &mut FrameBuffer,
+    size: usize,
+    render_pass_name: &ImmutableString,
+    state: &mut PipelineState,
+    light_view_projection: &Matrix4<f32>,
+    batches: &BatchStorage,
+    geom_cache: &mut GeometryCache,
+    shader_cache: &mut ShaderCache,
+    texture_cache: &mut TextureCache,
+    normal_dummy: Rc<RefCell<GpuTexture>>,
+    white_dummy: Rc<RefCell<GpuTexture>>,
+    black_dummy: Rc<RefCell<GpuTexture>>,
+    culling_mask: LayerMask,
+    casters: ShadowCasters,
+) -> RenderPassStatistics {
+    let mut statistics = RenderPassStatistics::default();
+
+    let viewport = Rect::new(0, 0, size as i32, size as i32);
+
+    framebuffer.clear(state, viewport, None, Some(1.0), None);
+    let frustum = Frustum::from(*light_view_projection).unwrap_or_default();
+
+    for batch in batches.batches.iter() {
+        let material = batch.material.lock();
+        let geometry = geom_cache.get(state, &batch.data);
+
+        if let Some(render_pass) = shader_cache
+            .get(state, material.shader())
+            .and_then(|shader_set| shader_set.render_passes.get(render_pass_name))
+        {
+            for instance in batch.instances.iter() {
+                if should_cast_shadows(instance, &frustum, culling_mask, casters) {
+                    statistics += framebuffer.draw(
+                        geometry,
+                        state,
+                        viewport,
+                        &render_pass.program,
+                        &DrawParameters {
+                            cull_face: Some(CullFace::Back),
+                            color_write: ColorMask::all(false),
+                            depth_write: true,
+                            stencil_test: None,
+                            depth_test: true,
+                            blend: None,
+                            stencil_op: Default::default(),
+                            alpha_to_coverage: false,
+                            depth_func: Default::default(),
+                        },
+                        |mut program_binding| {
+                            let wvp_matrix = light_view_projection * instance.world_transform;
+                            apply_material(MaterialContext {
+                                material: &*material,
+                                program_binding: &mut program_binding,
+                                texture_cache,
+                                world_matrix: &instance.world_transform,
+                                wvp_matrix: &wvp_matrix,
+                                bone_matrices: batches.instance_bone_matrices(instance),
+                                bone_matrices_offset: instance.bone_matrices.start,
+                                bone_matrices_storage: batches.bone_matrices_texture(),
+                                use_skeletal_animation: batch.is_skinned,
+                                camera_position: &Default::default(),
+                                use_pom: false,
+                                light_position: &Default::default(),
+                                prev_wvp_matrix: &wvp_matrix,
+                                prev_bone_matrices_storage: None,
+                                jitter: &Default::default(),
+                                wind: &batches.wind,
+                                normal_dummy: normal_dummy.clone(),
+                                white_dummy: white_dummy.clone(),
+                                black_dummy: black_dummy.clone(),
+                            });
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    statistics
 }
 
 impl SpotShadowMapRenderer {
@@ -42,55 +169,16 @@ impl SpotShadowMapRenderer {
         size: usize,
         precision: ShadowMapPrecision,
     ) -> Result<Self, FrameworkError> {
-        fn make_cascade(
-            state: &mut PipelineState,
-            size: usize,
-            precision: ShadowMapPrecision,
-        ) -> Result<FrameBuffer, FrameworkError> {
-            let depth = {
-                let kind = GpuTextureKind::Rectangle {
-                    width: size,
-                    height: size,
-                };
-                let mut texture = GpuTexture::new(
-                    state,
-                    kind,
-                    match precision {
-                        ShadowMapPrecision::Full => PixelKind::D32F,
-                        ShadowMapPrecision::Half => PixelKind::D16,
-                    },
-                    MinificationFilter::Linear,
-                    MagnificationFilter::Linear,
-                    1,
-                    None,
-                )?;
-                texture
-                    .bind_mut(state, 0)
-                    .set_wrap(Coordinate::T, WrapMode::ClampToEdge)
-                    .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
-                    .set_border_color(Color::WHITE);
-                texture
-            };
-
-            FrameBuffer::new(
-                state,
-                Some(Attachment {
-                    kind: AttachmentKind::Depth,
-                    texture: Rc::new(RefCell::new(depth)),
-                }),
-                vec![],
-            )
-        }
-
         Ok(Self {
             precision,
             size,
             cascades: [
-                make_cascade(state, cascade_size(size, 0), precision)?,
-                make_cascade(state, cascade_size(size, 1), precision)?,
-                make_cascade(state, cascade_size(size, 2), precision)?,
+                make_shadow_map(state, cascade_size(size, 0), precision)?,
+                make_shadow_map(state, cascade_size(size, 1), precision)?,
+                make_shadow_map(state, cascade_size(size, 2), precision)?,
             ],
             render_pass_name: ImmutableString::new("SpotShadow"),
+            static_maps: Default::default(),
         })
     }
 
@@ -114,8 +202,25 @@ impl SpotShadowMapRenderer {
         cascade_size(self.size, cascade)
     }
 
+    /// Returns a cached shadow map of static geometry of the light, if any.
+    pub fn static_texture(&self, light: Handle<Node>) -> Option<Rc<RefCell<GpuTexture>>> {
+        self.static_maps
+            .get(light)
+            .map(|framebuffer| framebuffer.depth_attachment().unwrap().texture.clone())
+    }
+
+    /// Returns size of static shadow maps, it is always the size of the first cascade.
+    pub fn static_size(&self) -> usize {
+        self.size
+    }
+
+    /// Removes static shadow maps of lights that weren't rendered since the previous call.
+    pub(crate) fn collect_static_garbage(&mut self) {
+        self.static_maps.collect_garbage();
+    }
+
     #[allow(clippy::too_many_arguments)]
-    pub(in crate) fn render(
+    pub(crate) fn render(
         &mut self,
         state: &mut PipelineState,
         light_view_projection: &Matrix4<f32>,
@@ -127,66 +232,75 @@ impl SpotShadowMapRenderer {
         normal_dummy: Rc<RefCell<GpuTexture>>,
         white_dummy: Rc<RefCell<GpuTexture>>,
         black_dummy: Rc<RefCell<GpuTexture>>,
+        culling_mask: LayerMask,
+        casters: ShadowCasters,
     ) -> RenderPassStatistics {
         scope_profile!();
 
-        let mut statistics = RenderPassStatistics::default();
-
-        let framebuffer = &mut self.cascades[cascade];
-        let cascade_size = cascade_size(self.size, cascade);
-
-        let viewport = Rect::new(0, 0, cascade_size as i32, cascade_size as i32);
-
-        framebuffer.clear(state, viewport, None, Some(1.0), None);
-        let frustum = Frustum::from(*light_view_projection).unwrap_or_default();
-
-        for batch in batches.batches.iter() {
-            let material = batch.material.lock();
-            let geometry = geom_cache.get(state, &batch.data);
-
-            if let Some(render_pass) = shader_cache
-                .get(state, material.shader())
-                .and_then(|shader_set| shader_set.render_passes.get(&self.render_pass_name))
-            {
-                for instance in batch.instances.iter() {
-                    if should_cast_shadows(instance, &frustum) {
-                        statistics += framebuffer.draw(
-                            geometry,
-                            state,
-                            viewport,
-                            &render_pass.program,
-                            &DrawParameters {
-                                cull_face: Some(CullFace::Back),
-                                color_write: ColorMask::all(false),
-                                depth_write: true,
-                                stencil_test: None,
-                                depth_test: true,
-                                blend: None,
-                                stencil_op: Default::default(),
-                            },
-                            |mut program_binding| {
-                                apply_material(MaterialContext {
-                                    material: &*material,
-                                    program_binding: &mut program_binding,
-                                    texture_cache,
-                                    world_matrix: &instance.world_transform,
-                                    wvp_matrix: &(light_view_projection * instance.world_transform),
-                                    bone_matrices: &instance.bone_matrices,
-                                    use_skeletal_animation: batch.is_skinned,
-                                    camera_position: &Default::default(),
-                                    use_pom: false,
-                                    light_position: &Default::default(),
-                                    normal_dummy: normal_dummy.clone(),
-                                    white_dummy: white_dummy.clone(),
-                                    black_dummy: black_dummy.clone(),
-                                });
-                            },
-                        );
-                    }
-                }
-            }
+        render_casters(
+            &mut self.cascades[cascade],
+            cascade_size(self.size, cascade),
+            &self.render_pass_name,
+            state,
+            light_view_projection,
+            batches,
+            geom_cache,
+            shader_cache,
+            texture_cache,
+            normal_dummy,
+            white_dummy,
+            black_dummy,
+            culling_mask,
+            casters,
+        )
+    }
+
+    /// Renders static shadow casters into the cached shadow map of the light, if the cached map
+    /// is missing or its key is different from the given one. Returns `None` if the cached map
+    /// is up-to-date and nothing was rendered.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn render_static(
+        &mut self,
+        state: &mut PipelineState,
+        light: Handle<Node>,
+        key: u64,
+        light_view_projection: &Matrix4<f32>,
+        batches: &BatchStorage,
+        geom_cache: &mut GeometryCache,
+        shader_cache: &mut ShaderCache,
+        texture_cache: &mut TextureCache,
+        normal_dummy: Rc<RefCell<GpuTexture>>,
+        white_dummy: Rc<RefCell<GpuTexture>>,
+        black_dummy: Rc<RefCell<GpuTexture>>,
+        culling_mask: LayerMask,
+    ) -> Result<Option<RenderPassStatistics>, FrameworkError> {
+        scope_profile!();
+
+        let size = self.size;
+        let precision = self.precision;
+        let (framebuffer, outdated) = self
+            .static_maps
+            .get_or_create(light, key, || make_shadow_map(state, size, precision))?;
+
+        if !outdated {
+            return Ok(None);
         }
 
-        statistics
+        Ok(Some(render_casters(
+            framebuffer,
+            size,
+            &self.render_pass_name,
+            state,
+            light_view_projection,
+            batches,
+            geom_cache,
+            shader_cache,
+            texture_cache,
+            normal_dummy,
+            white_dummy,
+            black_dummy,
+            culling_mask,
+            ShadowCasters::Static,
+        )))
     }
 }
@@ -109,7 +109,7 @@ impl SpriteRenderer {
         let camera_side = inv_view.side();
 
         for sprite in graph.linear_iter().filter_map(|node| {
-            if !node.global_visibility() {
+            if !node.global_visibility() || !camera.can_see_layers(node.render_mask()) {
                 return None;
             }
 
@@ -149,6 +149,8 @@ impl SpriteRenderer {
                         dfactor: BlendFactor::OneMinusSrcAlpha,
                     }),
                     stencil_op: Default::default(),
+                    alpha_to_coverage: false,
+                    depth_func: Default::default(),
                 },
                 |mut program_binding| {
                     program_binding
@@ -1,5 +1,13 @@
+//! Separable depth-aware blur of ambient occlusion map. Every pass consists of horizontal and
+//! vertical blur, passes are ping-ponged between two framebuffers.
+
 use crate::{
-    core::{math::Rect, scope_profile, sstorage::ImmutableString},
+    core::{
+        algebra::{Matrix4, Vector2},
+        math::Rect,
+        scope_profile,
+        sstorage::ImmutableString,
+    },
     renderer::{
         framework::{
             error::FrameworkError,
@@ -12,7 +20,7 @@ use crate::{
             },
             state::PipelineState,
         },
-        make_viewport_matrix,
+        make_viewport_matrix, RenderPassStatistics,
     },
     scene::mesh::surface::SurfaceData,
 };
@@ -22,6 +30,9 @@ struct Shader {
     program: GpuProgram,
     world_view_projection_matrix: UniformLocation,
     input_texture: UniformLocation,
+    depth_texture: UniformLocation,
+    inv_proj_matrix: UniformLocation,
+    direction: UniformLocation,
 }
 
 impl Shader {
@@ -35,6 +46,11 @@ impl Shader {
                 .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
             input_texture: program
                 .uniform_location(state, &ImmutableString::new("inputTexture"))?,
+            depth_texture: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            inv_proj_matrix: program
+                .uniform_location(state, &ImmutableString::new("inverseProjectionMatrix"))?,
+            direction: program.uniform_location(state, &ImmutableString::new("direction"))?,
             program,
         })
     }
@@ -42,10 +58,13 @@ impl Shader {
 
 pub struct Blur {
     shader: Shader,
-    framebuffer: FrameBuffer,
+    framebuffers: [FrameBuffer; 2],
     quad: GeometryBuffer,
     width: usize,
     height: usize,
+    passes: usize,
+    // Index of the framebuffer with the latest result, `None` if there was no passes.
+    result: Option<usize>,
 }
 
 impl Blur {
@@ -53,15 +72,15 @@ impl Blur {
         state: &mut PipelineState,
         width: usize,
         height: usize,
+        passes: usize,
     ) -> Result<Self, FrameworkError> {
-        let frame = {
-            let kind = GpuTextureKind::Rectangle { width, height };
+        let mut make_framebuffer = || -> Result<FrameBuffer, FrameworkError> {
             let mut texture = GpuTexture::new(
                 state,
-                kind,
-                PixelKind::F32,
-                MinificationFilter::Nearest,
-                MagnificationFilter::Nearest,
+                GpuTextureKind::Rectangle { width, height },
+                PixelKind::RGBA16F,
+                MinificationFilter::Linear,
+                MagnificationFilter::Linear,
                 1,
                 None,
             )?;
@@ -69,19 +88,22 @@ impl Blur {
                 .bind_mut(state, 0)
                 .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
                 .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
-            texture
-        };
 
-        Ok(Self {
-            shader: Shader::new(state)?,
-            framebuffer: FrameBuffer::new(
+            FrameBuffer::new(
                 state,
                 None,
                 vec![Attachment {
                     kind: AttachmentKind::Color,
-                    texture: Rc::new(RefCell::new(frame)),
+                    texture: Rc::new(RefCell::new(texture)),
                 }],
-            )?,
+            )
+        };
+
+        let framebuffers = [make_framebuffer()?, make_framebuffer()?];
+
+        Ok(Self {
+            shader: Shader::new(state)?,
+            framebuffers,
             quad: GeometryBuffer::from_surface_data(
                 &SurfaceData::make_unit_xy_quad(),
                 GeometryBufferKind::StaticDraw,
@@ -89,41 +111,80 @@ impl Blur {
             ),
             width,
             height,
+            passes,
+            result: None,
         })
     }
 
-    pub fn result(&self) -> Rc<RefCell<GpuTexture>> {
-        self.framebuffer.color_attachments()[0].texture.clone()
+    /// Returns blurred texture, or `None` if blur is disabled (zero passes).
+    pub fn result(&self) -> Option<Rc<RefCell<GpuTexture>>> {
+        self.result.map(|index| {
+            self.framebuffers[index].color_attachments()[0]
+                .texture
+                .clone()
+        })
     }
 
-    pub(in crate) fn render(&mut self, state: &mut PipelineState, input: Rc<RefCell<GpuTexture>>) {
+    pub(in crate) fn render(
+        &mut self,
+        state: &mut PipelineState,
+        input: Rc<RefCell<GpuTexture>>,
+        depth: Rc<RefCell<GpuTexture>>,
+        projection_matrix: &Matrix4<f32>,
+    ) -> RenderPassStatistics {
         scope_profile!();
 
+        let mut stats = RenderPassStatistics::default();
+
         let viewport = Rect::new(0, 0, self.width as i32, self.height as i32);
+        let frame_matrix = make_viewport_matrix(viewport);
+        let inv_projection = projection_matrix.try_inverse().unwrap_or_default();
+        let texel_size = Vector2::new(1.0 / self.width as f32, 1.0 / self.height as f32);
 
-        let shader = &self.shader;
-        self.framebuffer.draw(
-            &self.quad,
-            state,
-            viewport,
-            &shader.program,
-            &DrawParameters {
-                cull_face: None,
-                color_write: Default::default(),
-                depth_write: false,
-                stencil_test: None,
-                depth_test: false,
-                blend: None,
-                stencil_op: Default::default(),
-            },
-            |mut program_binding| {
-                program_binding
-                    .set_matrix4(
-                        &shader.world_view_projection_matrix,
-                        &(make_viewport_matrix(viewport)),
-                    )
-                    .set_texture(&shader.input_texture, &input);
-            },
-        );
+        self.result = None;
+
+        let mut source = input;
+        for i in 0..self.passes * 2 {
+            let target = i % 2;
+            let direction = if target == 0 {
+                Vector2::new(texel_size.x, 0.0)
+            } else {
+                Vector2::new(0.0, texel_size.y)
+            };
+
+            let shader = &self.shader;
+            stats += self.framebuffers[target].draw(
+                &self.quad,
+                state,
+                viewport,
+                &shader.program,
+                &DrawParameters {
+                    cull_face: None,
+                    color_write: Default::default(),
+                    depth_write: false,
+                    stencil_test: None,
+                    depth_test: false,
+                    blend: None,
+                    stencil_op: Default::default(),
+                    alpha_to_coverage: false,
+                    depth_func: Default::default(),
+                },
+                |mut program_binding| {
+                    program_binding
+                        .set_matrix4(&shader.world_view_projection_matrix, &frame_matrix)
+                        .set_texture(&shader.input_texture, &source)
+                        .set_texture(&shader.depth_texture, &depth)
+                        .set_matrix4(&shader.inv_proj_matrix, &inv_projection)
+                        .set_vector2(&shader.direction, &direction);
+                },
+            );
+
+            source = self.framebuffers[target].color_attachments()[0]
+                .texture
+                .clone();
+            self.result = Some(target);
+        }
+
+        stats
     }
 }
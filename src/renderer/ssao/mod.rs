@@ -17,20 +17,22 @@ use crate::{
                 Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
                 PixelKind, WrapMode,
             },
+            query::GpuTimer,
             state::PipelineState,
         },
         gbuffer::GBuffer,
-        ssao::blur::Blur,
-        RenderPassStatistics,
+        light::SsaoStatistics,
+        ssao::{blur::Blur, temporal::TemporalShader},
+        RenderPassStatistics, SsaoSettings,
     },
     scene::mesh::surface::SurfaceData,
 };
 use std::{cell::RefCell, rc::Rc};
 
-mod blur;
+pub use temporal::SsaoHistory;
 
-// Keep in sync with shader define.
-const KERNEL_SIZE: usize = 32;
+mod blur;
+mod temporal;
 
 // Size of noise texture.
 const NOISE_SIZE: usize = 4;
@@ -42,8 +44,10 @@ struct Shader {
     noise_sampler: UniformLocation,
     radius: UniformLocation,
     kernel: UniformLocation,
+    sample_count: UniformLocation,
     projection_matrix: UniformLocation,
     noise_scale: UniformLocation,
+    noise_offset: UniformLocation,
     inv_proj_matrix: UniformLocation,
     world_view_proj_matrix: UniformLocation,
     view_matrix: UniformLocation,
@@ -62,12 +66,14 @@ impl Shader {
             noise_sampler: program
                 .uniform_location(state, &ImmutableString::new("noiseSampler"))?,
             kernel: program.uniform_location(state, &ImmutableString::new("kernel"))?,
+            sample_count: program.uniform_location(state, &ImmutableString::new("sampleCount"))?,
             radius: program.uniform_location(state, &ImmutableString::new("radius"))?,
             projection_matrix: program
                 .uniform_location(state, &ImmutableString::new("projectionMatrix"))?,
             inv_proj_matrix: program
                 .uniform_location(state, &ImmutableString::new("inverseProjectionMatrix"))?,
             noise_scale: program.uniform_location(state, &ImmutableString::new("noiseScale"))?,
+            noise_offset: program.uniform_location(state, &ImmutableString::new("noiseOffset"))?,
             world_view_proj_matrix: program
                 .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
             view_matrix: program.uniform_location(state, &ImmutableString::new("viewMatrix"))?,
@@ -76,16 +82,56 @@ impl Shader {
     }
 }
 
+/// Generates sampling kernel - a set of points inside a hemisphere, that are denser near its
+/// center.
+fn make_kernel(sample_count: usize) -> Vec<Vector3<f32>> {
+    let mut rng = crate::rand::thread_rng();
+    (0..sample_count)
+        .map(|i| {
+            let k = i as f32 / sample_count as f32;
+            let scale = lerpf(0.1, 1.0, k * k);
+            Vector3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(0.0..1.0),
+            )
+            // Make sphere
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(|| Vector3::z())
+            // Use non-uniform distribution to shuffle points inside hemisphere.
+            .scale(scale)
+        })
+        .collect()
+}
+
+/// Returns an offset of noise texture for the given frame. Every frame uses a different
+/// rotation of sampling kernel, it lets temporal accumulation to gather more unique samples.
+fn noise_offset(frame_index: usize) -> Vector2<f32> {
+    // Visit every texel of noise texture in a scattered order.
+    const ORDER: [usize; NOISE_SIZE * NOISE_SIZE] =
+        [0, 10, 5, 15, 2, 8, 7, 13, 1, 11, 4, 14, 3, 9, 6, 12];
+    let texel = ORDER[frame_index % ORDER.len()];
+    Vector2::new(
+        (texel % NOISE_SIZE) as f32 / NOISE_SIZE as f32,
+        (texel / NOISE_SIZE) as f32 / NOISE_SIZE as f32,
+    )
+}
+
 pub struct ScreenSpaceAmbientOcclusionRenderer {
     blur: Blur,
     shader: Shader,
+    temporal_shader: TemporalShader,
     framebuffer: FrameBuffer,
     quad: GeometryBuffer,
     width: i32,
     height: i32,
     noise: Rc<RefCell<GpuTexture>>,
-    kernel: [Vector3<f32>; KERNEL_SIZE],
+    kernel: Vec<Vector3<f32>>,
     radius: f32,
+    settings: SsaoSettings,
+    timer: GpuTimer,
+    frame_index: usize,
+    result: Rc<RefCell<GpuTexture>>,
 }
 
 impl ScreenSpaceAmbientOcclusionRenderer {
@@ -93,41 +139,45 @@ impl ScreenSpaceAmbientOcclusionRenderer {
         state: &mut PipelineState,
         frame_width: usize,
         frame_height: usize,
+        settings: &SsaoSettings,
     ) -> Result<Self, FrameworkError> {
-        // It is good balance between quality and performance, no need to do SSAO in full resolution.
-        // This SSAO map size reduction was taken from DOOM (2016).
-        let width = (frame_width / 2).max(1);
-        let height = (frame_height / 2).max(1);
+        let settings = settings.clamped();
+
+        // There is no need to do SSAO in full resolution in most cases, the map is upscaled when
+        // lighting is applied. This SSAO map size reduction was taken from DOOM (2016).
+        let width = ((frame_width as f32 * settings.resolution_scale) as usize).max(1);
+        let height = ((frame_height as f32 * settings.resolution_scale) as usize).max(1);
 
         let occlusion = {
             let kind = GpuTextureKind::Rectangle { width, height };
             let mut texture = GpuTexture::new(
                 state,
                 kind,
-                PixelKind::F32,
-                MinificationFilter::Nearest,
-                MagnificationFilter::Nearest,
+                PixelKind::RGBA16F,
+                MinificationFilter::Linear,
+                MagnificationFilter::Linear,
                 1,
                 None,
             )?;
             texture
                 .bind_mut(state, 0)
-                .set_minification_filter(MinificationFilter::Nearest)
-                .set_magnification_filter(MagnificationFilter::Nearest);
-            texture
+                .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+                .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+            Rc::new(RefCell::new(texture))
         };
 
         let mut rng = crate::rand::thread_rng();
 
         Ok(Self {
-            blur: Blur::new(state, width, height)?,
+            blur: Blur::new(state, width, height, settings.blur_passes)?,
             shader: Shader::new(state)?,
+            temporal_shader: TemporalShader::new(state)?,
             framebuffer: FrameBuffer::new(
                 state,
                 None,
                 vec![Attachment {
                     kind: AttachmentKind::Color,
-                    texture: Rc::new(RefCell::new(occlusion)),
+                    texture: occlusion.clone(),
                 }],
             )?,
             quad: GeometryBuffer::from_surface_data(
@@ -137,24 +187,7 @@ impl ScreenSpaceAmbientOcclusionRenderer {
             ),
             width: width as i32,
             height: height as i32,
-            kernel: {
-                let mut kernel = [Default::default(); KERNEL_SIZE];
-                for (i, v) in kernel.iter_mut().enumerate() {
-                    let k = i as f32 / KERNEL_SIZE as f32;
-                    let scale = lerpf(0.1, 1.0, k * k);
-                    *v = Vector3::new(
-                        rng.gen_range(-1.0..1.0),
-                        rng.gen_range(-1.0..1.0),
-                        rng.gen_range(0.0..1.0),
-                    )
-                    // Make sphere
-                    .try_normalize(f32::EPSILON)
-                    .unwrap()
-                    // Use non-uniform distribution to shuffle points inside hemisphere.
-                    .scale(scale);
-                }
-                kernel
-            },
+            kernel: make_kernel(settings.sample_count),
             noise: Rc::new(RefCell::new({
                 const RGB_PIXEL_SIZE: usize = 3;
                 let mut pixels = [0u8; RGB_PIXEL_SIZE * NOISE_SIZE * NOISE_SIZE];
@@ -183,6 +216,10 @@ impl ScreenSpaceAmbientOcclusionRenderer {
                 texture
             })),
             radius: 0.5,
+            settings,
+            timer: GpuTimer::new(state),
+            frame_index: 0,
+            result: occlusion,
         })
     }
 
@@ -190,23 +227,53 @@ impl ScreenSpaceAmbientOcclusionRenderer {
         self.radius = radius.abs();
     }
 
-    fn raw_ao_map(&self) -> Rc<RefCell<GpuTexture>> {
-        self.framebuffer.color_attachments()[0].texture.clone()
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Returns current settings, they may differ from the settings passed to [`Self::new`],
+    /// because every value is clamped to its valid range.
+    pub fn settings(&self) -> &SsaoSettings {
+        &self.settings
+    }
+
+    /// Returns size of ambient occlusion map in pixels.
+    pub fn map_size(&self) -> (usize, usize) {
+        (self.width as usize, self.height as usize)
     }
 
+    /// Returns ambient occlusion map: RGB - world space bent normal packed into [0; 1] range,
+    /// A - ambient occlusion.
     pub fn ao_map(&self) -> Rc<RefCell<GpuTexture>> {
-        self.blur.result()
+        self.result.clone()
     }
 
+    pub fn statistics(&self) -> SsaoStatistics {
+        SsaoStatistics {
+            sample_count: self.settings.sample_count,
+            width: self.width as usize,
+            height: self.height as usize,
+            blur_passes: self.settings.blur_passes,
+            temporal_accumulation: self.settings.temporal_accumulation,
+            gpu_time: self.timer.time(),
+        }
+    }
+
+    /// Renders ambient occlusion map. `history` is used only if temporal accumulation is
+    /// enabled, its size must match the size of the map (see [`Self::map_size`]).
     pub(in crate) fn render(
         &mut self,
         state: &mut PipelineState,
         gbuffer: &GBuffer,
         projection_matrix: Matrix4<f32>,
         view_matrix: Matrix3<f32>,
+        view_projection: Matrix4<f32>,
+        history: Option<&mut SsaoHistory>,
     ) -> RenderPassStatistics {
         scope_profile!();
 
+        self.timer.begin(state);
+
         let mut stats = RenderPassStatistics::default();
 
         let viewport = Rect::new(0, 0, self.width, self.height);
@@ -232,6 +299,10 @@ impl ScreenSpaceAmbientOcclusionRenderer {
             None,
         );
 
+        let history = history.filter(|history| {
+            self.settings.temporal_accumulation && history.size() == self.map_size()
+        });
+
         let shader = &self.shader;
         let noise = &self.noise;
         let kernel = &self.kernel;
@@ -239,6 +310,12 @@ impl ScreenSpaceAmbientOcclusionRenderer {
             self.width as f32 / NOISE_SIZE as f32,
             self.height as f32 / NOISE_SIZE as f32,
         );
+        // Stable pattern gives less flickering when there is no accumulation.
+        let noise_offset = if history.is_some() {
+            noise_offset(self.frame_index)
+        } else {
+            Vector2::default()
+        };
         let radius = self.radius;
         stats += self.framebuffer.draw(
             &self.quad,
@@ -253,6 +330,8 @@ impl ScreenSpaceAmbientOcclusionRenderer {
                 depth_test: false,
                 blend: None,
                 stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
             },
             |mut program_binding| {
                 program_binding
@@ -260,7 +339,9 @@ impl ScreenSpaceAmbientOcclusionRenderer {
                     .set_texture(&shader.normal_sampler, &gbuffer.normal_texture())
                     .set_texture(&shader.noise_sampler, noise)
                     .set_vector3_slice(&shader.kernel, kernel)
+                    .set_i32(&shader.sample_count, kernel.len() as i32)
                     .set_vector2(&shader.noise_scale, &noise_scale)
+                    .set_vector2(&shader.noise_offset, &noise_offset)
                     .set_f32(&shader.radius, radius)
                     .set_matrix4(&shader.world_view_proj_matrix, &frame_matrix)
                     .set_matrix4(&shader.projection_matrix, &projection_matrix)
@@ -272,7 +353,30 @@ impl ScreenSpaceAmbientOcclusionRenderer {
             },
         );
 
-        self.blur.render(state, self.raw_ao_map());
+        let mut result = self.framebuffer.color_attachments()[0].texture.clone();
+
+        // Accumulation is done before the blur, so the history contains as much unique samples
+        // as possible.
+        if let Some(history) = history {
+            stats += history.accumulate(
+                state,
+                &self.temporal_shader,
+                &self.quad,
+                result,
+                gbuffer.depth(),
+                view_projection,
+            );
+            result = history.result();
+        }
+
+        stats += self
+            .blur
+            .render(state, result.clone(), gbuffer.depth(), &projection_matrix);
+
+        self.result = self.blur.result().unwrap_or(result);
+        self.frame_index = self.frame_index.wrapping_add(1);
+
+        self.timer.end(state);
 
         stats
     }
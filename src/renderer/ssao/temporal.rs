@@ -0,0 +1,195 @@
+//! Temporal accumulation of ambient occlusion. Every frame the SSAO pass uses a different
+//! sampling pattern, and the accumulation pass blends the current (noisy) result with the
+//! reprojected result of the previous frames.
+
+use crate::{
+    core::{algebra::Matrix4, math::Rect, scope_profile, sstorage::ImmutableString},
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, DrawParameters, FrameBuffer},
+            geometry_buffer::GeometryBuffer,
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{
+                Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
+                PixelKind, WrapMode,
+            },
+            state::PipelineState,
+        },
+        make_viewport_matrix, RenderPassStatistics,
+    },
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// How much of the current frame goes into the result, the rest is taken from the history.
+const BLEND_FACTOR: f32 = 0.1;
+
+pub(super) struct TemporalShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    current_texture: UniformLocation,
+    history_texture: UniformLocation,
+    depth_texture: UniformLocation,
+    inv_view_projection: UniformLocation,
+    prev_view_projection: UniformLocation,
+    history_valid: UniformLocation,
+    blend_factor: UniformLocation,
+}
+
+impl TemporalShader {
+    pub(super) fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("../shaders/ssao_temporal_fs.glsl");
+        let vertex_source = include_str!("../shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "SsaoTemporalShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            current_texture: program
+                .uniform_location(state, &ImmutableString::new("currentTexture"))?,
+            history_texture: program
+                .uniform_location(state, &ImmutableString::new("historyTexture"))?,
+            depth_texture: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            inv_view_projection: program
+                .uniform_location(state, &ImmutableString::new("invViewProjection"))?,
+            prev_view_projection: program
+                .uniform_location(state, &ImmutableString::new("prevViewProjection"))?,
+            history_valid: program
+                .uniform_location(state, &ImmutableString::new("historyValid"))?,
+            blend_factor: program.uniform_location(state, &ImmutableString::new("blendFactor"))?,
+            program,
+        })
+    }
+}
+
+/// Accumulated ambient occlusion of a camera. History is per camera, because every camera sees
+/// its own image.
+pub struct SsaoHistory {
+    framebuffers: [FrameBuffer; 2],
+    // Index of the framebuffer with the latest result.
+    current: usize,
+    prev_view_projection: Matrix4<f32>,
+    valid: bool,
+    width: usize,
+    height: usize,
+}
+
+impl SsaoHistory {
+    /// Creates new empty history, its size must match the size of ambient occlusion map.
+    pub fn new(
+        state: &mut PipelineState,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, FrameworkError> {
+        let mut make_framebuffer = || -> Result<FrameBuffer, FrameworkError> {
+            let mut texture = GpuTexture::new(
+                state,
+                GpuTextureKind::Rectangle { width, height },
+                PixelKind::RGBA16F,
+                // History is sampled at arbitrary positions, so it must be filtered.
+                MinificationFilter::Linear,
+                MagnificationFilter::Linear,
+                1,
+                None,
+            )?;
+            texture
+                .bind_mut(state, 0)
+                .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+                .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+
+            FrameBuffer::new(
+                state,
+                None,
+                vec![Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: Rc::new(RefCell::new(texture)),
+                }],
+            )
+        };
+
+        Ok(Self {
+            framebuffers: [make_framebuffer()?, make_framebuffer()?],
+            current: 0,
+            prev_view_projection: Matrix4::identity(),
+            valid: false,
+            width,
+            height,
+        })
+    }
+
+    /// Returns size of the history in pixels.
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Returns a texture with the latest accumulated ambient occlusion.
+    pub fn result(&self) -> Rc<RefCell<GpuTexture>> {
+        self.framebuffers[self.current].color_attachments()[0]
+            .texture
+            .clone()
+    }
+
+    /// Blends the current ambient occlusion with the history and advances the history to the
+    /// next frame. The result is available via [`Self::result`].
+    pub(super) fn accumulate(
+        &mut self,
+        state: &mut PipelineState,
+        shader: &TemporalShader,
+        quad: &GeometryBuffer,
+        current_texture: Rc<RefCell<GpuTexture>>,
+        depth_texture: Rc<RefCell<GpuTexture>>,
+        view_projection: Matrix4<f32>,
+    ) -> RenderPassStatistics {
+        scope_profile!();
+
+        let viewport = Rect::new(0, 0, self.width as i32, self.height as i32);
+        let frame_matrix = make_viewport_matrix(viewport);
+        let history_texture = self.result();
+        let inv_view_projection = view_projection.try_inverse().unwrap_or_default();
+        let prev_view_projection = if self.valid {
+            self.prev_view_projection
+        } else {
+            view_projection
+        };
+        let history_valid = self.valid;
+
+        let target = 1 - self.current;
+        let mut statistics = RenderPassStatistics::default();
+        statistics += self.framebuffers[target].draw(
+            quad,
+            state,
+            viewport,
+            &shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: None,
+                stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
+            },
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(&shader.wvp_matrix, &frame_matrix)
+                    .set_texture(&shader.current_texture, &current_texture)
+                    .set_texture(&shader.history_texture, &history_texture)
+                    .set_texture(&shader.depth_texture, &depth_texture)
+                    .set_matrix4(&shader.inv_view_projection, &inv_view_projection)
+                    .set_matrix4(&shader.prev_view_projection, &prev_view_projection)
+                    .set_bool(&shader.history_valid, history_valid)
+                    .set_f32(&shader.blend_factor, BLEND_FACTOR);
+            },
+        );
+
+        self.current = target;
+        self.prev_view_projection = view_projection;
+        self.valid = true;
+
+        statistics
+    }
+}
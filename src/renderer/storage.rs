@@ -0,0 +1,137 @@
+//! GPU storage for large sets of matrices or vectors, that cannot fit into uniforms.
+
+use crate::{
+    core::algebra::{Matrix4, Vector4},
+    renderer::framework::{
+        error::FrameworkError,
+        gpu_texture::{
+            GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter, PixelKind,
+        },
+        state::PipelineState,
+    },
+    utils::array_as_u8_slice,
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// Width of the storage texture in texels. Every matrix takes 4 texels (one per column), so every
+/// row of the texture holds 256 matrices.
+pub(in crate::renderer) const TEXTURE_WIDTH: usize = 1024;
+const MATRICES_PER_ROW: usize = TEXTURE_WIDTH / 4;
+
+/// A texture that holds an arbitrary amount of matrices. The texture is persistent - it is
+/// re-allocated only when it is not big enough to hold new set of matrices, otherwise its
+/// content is just replaced.
+pub struct MatrixStorage {
+    texture: Rc<RefCell<GpuTexture>>,
+    rows: usize,
+}
+
+fn create_texture(
+    state: &mut PipelineState,
+    rows: usize,
+) -> Result<Rc<RefCell<GpuTexture>>, FrameworkError> {
+    // Float textures can't be filtered on some platforms, mips are useless too.
+    Ok(Rc::new(RefCell::new(GpuTexture::new(
+        state,
+        GpuTextureKind::Rectangle {
+            width: TEXTURE_WIDTH,
+            height: rows,
+        },
+        PixelKind::RGBA32F,
+        MinificationFilter::Nearest,
+        MagnificationFilter::Nearest,
+        1,
+        None,
+    )?)))
+}
+
+impl MatrixStorage {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            texture: create_texture(state, 1)?,
+            rows: 1,
+        })
+    }
+
+    /// Returns a texture with the matrices.
+    pub fn texture(&self) -> &Rc<RefCell<GpuTexture>> {
+        &self.texture
+    }
+
+    /// Uploads the matrices to the GPU. The texture grows if needed.
+    pub fn upload(
+        &mut self,
+        state: &mut PipelineState,
+        matrices: &[Matrix4<f32>],
+    ) -> Result<(), FrameworkError> {
+        let required_rows = (matrices.len() + MATRICES_PER_ROW - 1) / MATRICES_PER_ROW;
+        if required_rows > self.rows {
+            self.rows = required_rows.next_power_of_two();
+            self.texture = create_texture(state, self.rows)?;
+        }
+
+        let full_rows = matrices.len() / MATRICES_PER_ROW;
+        let (full, rest) = matrices.split_at(full_rows * MATRICES_PER_ROW);
+
+        let mut texture = self.texture.borrow_mut();
+        let mut binding = texture.bind_mut(state, 0);
+        if !full.is_empty() {
+            binding =
+                binding.set_sub_data(0, 0, TEXTURE_WIDTH, full_rows, array_as_u8_slice(full))?;
+        }
+        if !rest.is_empty() {
+            binding.set_sub_data(0, full_rows, rest.len() * 4, 1, array_as_u8_slice(rest))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A texture that holds an arbitrary amount of 4-component vectors, one vector per texel. The
+/// texture is persistent, just like [`MatrixStorage`].
+pub struct VectorStorage {
+    texture: Rc<RefCell<GpuTexture>>,
+    rows: usize,
+}
+
+impl VectorStorage {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            texture: create_texture(state, 1)?,
+            rows: 1,
+        })
+    }
+
+    /// Returns a texture with the vectors.
+    pub fn texture(&self) -> &Rc<RefCell<GpuTexture>> {
+        &self.texture
+    }
+
+    /// Uploads the vectors to the GPU. The texture grows if needed.
+    pub fn upload(
+        &mut self,
+        state: &mut PipelineState,
+        vectors: &[Vector4<f32>],
+    ) -> Result<(), FrameworkError> {
+        let required_rows = (vectors.len() + TEXTURE_WIDTH - 1) / TEXTURE_WIDTH;
+        if required_rows > self.rows {
+            self.rows = required_rows.next_power_of_two();
+            self.texture = create_texture(state, self.rows)?;
+        }
+
+        let full_rows = vectors.len() / TEXTURE_WIDTH;
+        let (full, rest) = vectors.split_at(full_rows * TEXTURE_WIDTH);
+
+        let mut texture = self.texture.borrow_mut();
+        let mut binding = texture.bind_mut(state, 0);
+        if !full.is_empty() {
+            binding =
+                binding.set_sub_data(0, 0, TEXTURE_WIDTH, full_rows, array_as_u8_slice(full))?;
+        }
+        if !rest.is_empty() {
+            binding.set_sub_data(0, full_rows, rest.len(), 1, array_as_u8_slice(rest))?;
+        }
+
+        Ok(())
+    }
+}
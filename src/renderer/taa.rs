@@ -0,0 +1,357 @@
+//! Temporal anti-aliasing (TAA). Every frame the projection matrix of a camera is shifted by a
+//! sub-pixel offset (jitter), so every frame samples slightly different positions inside pixels.
+//! Then the resolve pass accumulates the samples over time by blending the current frame with
+//! the previous result, that was reprojected using motion vectors from the G-Buffer.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        math::Rect,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, DrawParameters, FrameBuffer},
+            geometry_buffer::{GeometryBuffer, GeometryBufferKind},
+            gpu_program::{GpuProgram, UniformLocation},
+            gpu_texture::{
+                Coordinate, GpuTexture, GpuTextureKind, MagnificationFilter, MinificationFilter,
+                PixelKind, WrapMode,
+            },
+            state::PipelineState,
+        },
+        RenderPassStatistics,
+    },
+    scene::mesh::surface::SurfaceData,
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// Amount of different jitter offsets, the sequence repeats after this amount of frames.
+const JITTER_SEQUENCE_LENGTH: usize = 8;
+
+/// How much of the current frame goes into the result, the rest is taken from the history.
+const BLEND_FACTOR: f32 = 0.1;
+
+/// Motion (in pixels per frame) that is shown with full intensity by motion vectors visualization.
+const MOTION_VECTORS_VISUALIZATION_SCALE: f32 = 16.0;
+
+struct TaaShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    current_texture: UniformLocation,
+    history_texture: UniformLocation,
+    motion_vectors_texture: UniformLocation,
+    depth_texture: UniformLocation,
+    inv_view_projection: UniformLocation,
+    prev_view_projection: UniformLocation,
+    inverse_screen_size: UniformLocation,
+    history_valid: UniformLocation,
+    blend_factor: UniformLocation,
+}
+
+impl TaaShader {
+    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/taa_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program = GpuProgram::from_source(state, "TAAShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            current_texture: program
+                .uniform_location(state, &ImmutableString::new("currentTexture"))?,
+            history_texture: program
+                .uniform_location(state, &ImmutableString::new("historyTexture"))?,
+            motion_vectors_texture: program
+                .uniform_location(state, &ImmutableString::new("motionVectorsTexture"))?,
+            depth_texture: program
+                .uniform_location(state, &ImmutableString::new("depthTexture"))?,
+            inv_view_projection: program
+                .uniform_location(state, &ImmutableString::new("invViewProjection"))?,
+            prev_view_projection: program
+                .uniform_location(state, &ImmutableString::new("prevViewProjection"))?,
+            inverse_screen_size: program
+                .uniform_location(state, &ImmutableString::new("inverseScreenSize"))?,
+            history_valid: program
+                .uniform_location(state, &ImmutableString::new("historyValid"))?,
+            blend_factor: program.uniform_location(state, &ImmutableString::new("blendFactor"))?,
+            program,
+        })
+    }
+}
+
+struct MotionVectorsShader {
+    program: GpuProgram,
+    wvp_matrix: UniformLocation,
+    motion_vectors_texture: UniformLocation,
+    screen_size: UniformLocation,
+    scale: UniformLocation,
+}
+
+impl MotionVectorsShader {
+    fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        let fragment_source = include_str!("shaders/motion_vectors_fs.glsl");
+        let vertex_source = include_str!("shaders/flat_vs.glsl");
+
+        let program =
+            GpuProgram::from_source(state, "MotionVectorsShader", vertex_source, fragment_source)?;
+        Ok(Self {
+            wvp_matrix: program
+                .uniform_location(state, &ImmutableString::new("worldViewProjection"))?,
+            motion_vectors_texture: program
+                .uniform_location(state, &ImmutableString::new("motionVectorsTexture"))?,
+            screen_size: program.uniform_location(state, &ImmutableString::new("screenSize"))?,
+            scale: program.uniform_location(state, &ImmutableString::new("scale"))?,
+            program,
+        })
+    }
+}
+
+/// Returns `index`-th element of Halton sequence with the given base.
+fn halton(mut index: usize, base: usize) -> f32 {
+    let mut fraction = 1.0;
+    let mut result = 0.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+/// Accumulated frames of a camera. History is per camera, because every camera sees its own
+/// image.
+pub struct TaaHistory {
+    framebuffers: [FrameBuffer; 2],
+    // Index of the framebuffer with the latest result.
+    current: usize,
+    frame_index: usize,
+    prev_view_projection: Matrix4<f32>,
+    valid: bool,
+}
+
+impl TaaHistory {
+    /// Creates new empty history.
+    pub fn new(
+        state: &mut PipelineState,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, FrameworkError> {
+        let mut make_framebuffer = || -> Result<FrameBuffer, FrameworkError> {
+            let mut texture = GpuTexture::new(
+                state,
+                GpuTextureKind::Rectangle { width, height },
+                PixelKind::RGBA16F,
+                // History is sampled at arbitrary positions, so it must be filtered.
+                MinificationFilter::Linear,
+                MagnificationFilter::Linear,
+                1,
+                None,
+            )?;
+            texture
+                .bind_mut(state, 0)
+                .set_wrap(Coordinate::S, WrapMode::ClampToEdge)
+                .set_wrap(Coordinate::T, WrapMode::ClampToEdge);
+
+            FrameBuffer::new(
+                state,
+                None,
+                vec![Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: Rc::new(RefCell::new(texture)),
+                }],
+            )
+        };
+
+        Ok(Self {
+            framebuffers: [make_framebuffer()?, make_framebuffer()?],
+            current: 0,
+            frame_index: 0,
+            prev_view_projection: Matrix4::identity(),
+            valid: false,
+        })
+    }
+
+    /// Returns sub-pixel offset (in NDC) of the projection matrix for the current frame.
+    pub fn jitter(&self, viewport: Rect<i32>) -> Vector2<f32> {
+        // Halton sequence starts from 0, skip it to not have a bias to the corner of a pixel.
+        let index = self.frame_index % JITTER_SEQUENCE_LENGTH + 1;
+        Vector2::new(
+            (halton(index, 2) - 0.5) * 2.0 / viewport.w().max(1) as f32,
+            (halton(index, 3) - 0.5) * 2.0 / viewport.h().max(1) as f32,
+        )
+    }
+
+    /// Returns unjittered view-projection matrix of the previous frame. It is the same as the
+    /// given matrix if there is no previous frame yet.
+    pub fn prev_view_projection(&self, view_projection: Matrix4<f32>) -> Matrix4<f32> {
+        if self.valid {
+            self.prev_view_projection
+        } else {
+            view_projection
+        }
+    }
+
+    /// Returns a texture with the latest anti-aliased frame.
+    pub fn result(&self) -> Rc<RefCell<GpuTexture>> {
+        self.framebuffers[self.current].color_attachments()[0]
+            .texture
+            .clone()
+    }
+}
+
+/// Input data of the resolve pass.
+pub(in crate) struct TaaResolveContext<'a> {
+    pub state: &'a mut PipelineState,
+    pub viewport: Rect<i32>,
+    pub history: &'a mut TaaHistory,
+    pub frame_texture: Rc<RefCell<GpuTexture>>,
+    pub motion_vectors_texture: Rc<RefCell<GpuTexture>>,
+    pub depth_texture: Rc<RefCell<GpuTexture>>,
+    /// Unjittered view-projection matrix of the current frame.
+    pub view_projection: Matrix4<f32>,
+}
+
+pub struct TaaRenderer {
+    shader: TaaShader,
+    motion_vectors_shader: MotionVectorsShader,
+    quad: GeometryBuffer,
+}
+
+impl TaaRenderer {
+    pub fn new(state: &mut PipelineState) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: TaaShader::new(state)?,
+            motion_vectors_shader: MotionVectorsShader::new(state)?,
+            quad: GeometryBuffer::from_surface_data(
+                &SurfaceData::make_unit_xy_quad(),
+                GeometryBufferKind::StaticDraw,
+                state,
+            ),
+        })
+    }
+
+    fn frame_matrix(viewport: Rect<i32>) -> Matrix4<f32> {
+        Matrix4::new_orthographic(
+            0.0,
+            viewport.w() as f32,
+            viewport.h() as f32,
+            0.0,
+            -1.0,
+            1.0,
+        ) * Matrix4::new_nonuniform_scaling(&Vector3::new(
+            viewport.w() as f32,
+            viewport.h() as f32,
+            0.0,
+        ))
+    }
+
+    /// Blends the current frame with the history and advances the history to the next frame. The
+    /// result is available via [`TaaHistory::result`].
+    pub(in crate) fn resolve(&self, args: TaaResolveContext) -> RenderPassStatistics {
+        let TaaResolveContext {
+            state,
+            viewport,
+            history,
+            frame_texture,
+            motion_vectors_texture,
+            depth_texture,
+            view_projection,
+        } = args;
+
+        let history_texture = history.result();
+        let inv_view_projection = view_projection.try_inverse().unwrap_or_default();
+        let prev_view_projection = history.prev_view_projection(view_projection);
+        let history_valid = history.valid;
+        let frame_matrix = Self::frame_matrix(viewport);
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let target = 1 - history.current;
+        statistics += history.framebuffers[target].draw(
+            &self.quad,
+            state,
+            viewport,
+            &self.shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: None,
+                stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
+            },
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(&self.shader.wvp_matrix, &frame_matrix)
+                    .set_texture(&self.shader.current_texture, &frame_texture)
+                    .set_texture(&self.shader.history_texture, &history_texture)
+                    .set_texture(&self.shader.motion_vectors_texture, &motion_vectors_texture)
+                    .set_texture(&self.shader.depth_texture, &depth_texture)
+                    .set_matrix4(&self.shader.inv_view_projection, &inv_view_projection)
+                    .set_matrix4(&self.shader.prev_view_projection, &prev_view_projection)
+                    .set_vector2(
+                        &self.shader.inverse_screen_size,
+                        &Vector2::new(1.0 / viewport.w() as f32, 1.0 / viewport.h() as f32),
+                    )
+                    .set_bool(&self.shader.history_valid, history_valid)
+                    .set_f32(&self.shader.blend_factor, BLEND_FACTOR);
+            },
+        );
+
+        history.current = target;
+        history.frame_index = history.frame_index.wrapping_add(1);
+        history.prev_view_projection = view_projection;
+        history.valid = true;
+
+        statistics
+    }
+
+    /// Draws motion vectors on top of the given frame buffer, it is useful for debugging.
+    pub(in crate) fn visualize_motion_vectors(
+        &self,
+        state: &mut PipelineState,
+        viewport: Rect<i32>,
+        motion_vectors_texture: Rc<RefCell<GpuTexture>>,
+        frame_buffer: &mut FrameBuffer,
+    ) -> RenderPassStatistics {
+        let frame_matrix = Self::frame_matrix(viewport);
+        let shader = &self.motion_vectors_shader;
+
+        let mut statistics = RenderPassStatistics::default();
+
+        statistics += frame_buffer.draw(
+            &self.quad,
+            state,
+            viewport,
+            &shader.program,
+            &DrawParameters {
+                cull_face: None,
+                color_write: Default::default(),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: false,
+                blend: None,
+                stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
+            },
+            |mut program_binding| {
+                program_binding
+                    .set_matrix4(&shader.wvp_matrix, &frame_matrix)
+                    .set_texture(&shader.motion_vectors_texture, &motion_vectors_texture)
+                    .set_vector2(
+                        &shader.screen_size,
+                        &Vector2::new(viewport.w() as f32, viewport.h() as f32),
+                    )
+                    .set_f32(&shader.scale, MOTION_VECTORS_VISUALIZATION_SCALE);
+            },
+        );
+
+        statistics
+    }
+}
@@ -245,6 +245,8 @@ impl UiRenderer {
                             zpass: StencilAction::Incr,
                             ..Default::default()
                         },
+                        alpha_to_coverage: false,
+                        depth_func: Default::default(),
                     },
                     |mut program_binding| {
                         program_binding.set_matrix4(&self.shader.wvp_matrix, &ortho);
@@ -322,6 +324,8 @@ impl UiRenderer {
                     dfactor: BlendFactor::OneMinusSrcAlpha,
                 }),
                 stencil_op: Default::default(),
+                alpha_to_coverage: false,
+                depth_func: Default::default(),
             };
 
             let shader = &self.shader;
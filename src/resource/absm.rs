@@ -1,7 +1,10 @@
 //! Animation blending state machine resource.
 
 use crate::{
-    animation::machine::{Machine, MachineDefinition, MachineInstantiationError},
+    animation::{
+        machine::{Machine, MachineDefinition, MachineInstantiationError},
+        retarget::BoneMapping,
+    },
     asset::{define_new_resource, Resource, ResourceData},
     core::{pool::Handle, visitor::prelude::*},
     engine::resource_manager::{options::ImportOptions, ResourceManager},
@@ -10,6 +13,7 @@ use crate::{
 use serde::{Deserialize, Serialize};
 use std::{
     borrow::Cow,
+    error::Error,
     path::{Path, PathBuf},
 };
 
@@ -30,6 +34,13 @@ impl ResourceData for AbsmResourceState {
     fn set_path(&mut self, path: PathBuf) {
         self.path = path;
     }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut visitor = Visitor::new();
+        self.absm_definition.visit("Machine", &mut visitor)?;
+        visitor.save_binary(path)?;
+        Ok(())
+    }
 }
 
 impl AbsmResourceState {
@@ -62,6 +73,14 @@ impl AbsmResource {
     ///   suitable for the hierarchy.
     /// - ABSM instantiation - it uses ABSM definition to create a new instance of the ABSM.
     ///
+    /// # Retargeting
+    ///
+    /// By default animations are retargeted by names of nodes, it works only if the hierarchy has
+    /// exactly the same skeleton as animations. If the skeleton has different bone names or
+    /// proportions, pass a bone mapping (see [`BoneMapping`]) - animations will be converted using
+    /// bind poses of the skeletons (see [`crate::animation::retarget::retarget_animation`]). The
+    /// hierarchy must be in its bind pose at the moment of instantiation.
+    ///
     /// # Important notes
     ///
     /// The method loads multiple animation resources at once and it will fail even if one of them
@@ -76,12 +95,13 @@ impl AbsmResource {
         root: Handle<Node>,
         scene: &mut Scene,
         resource_manager: ResourceManager,
+        bone_mapping: Option<&BoneMapping>,
     ) -> Result<Handle<Machine>, MachineInstantiationError> {
         let data = self.data_ref();
         let definition = &data.absm_definition;
 
         let machine = definition
-            .instantiate(root, scene, resource_manager)
+            .instantiate(root, scene, resource_manager, bone_mapping)
             .await?;
 
         scene.animation_machines[machine].resource = Some(self.clone());
@@ -94,4 +114,6 @@ impl AbsmResource {
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct AbsmImportOptions {}
 
-impl ImportOptions for AbsmImportOptions {}
+impl ImportOptions for AbsmImportOptions {
+    const FOLDER_OPTIONS_FILE_NAME: &'static str = "folder.absm.options";
+}
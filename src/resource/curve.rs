@@ -8,6 +8,7 @@ use crate::{
 use serde::{Deserialize, Serialize};
 use std::{
     borrow::Cow,
+    error::Error,
     path::{Path, PathBuf},
 };
 
@@ -51,6 +52,13 @@ impl ResourceData for CurveResourceState {
     fn set_path(&mut self, path: PathBuf) {
         self.path = path;
     }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut visitor = Visitor::new();
+        self.curve.visit("Curve", &mut visitor)?;
+        visitor.save_binary(path)?;
+        Ok(())
+    }
 }
 
 impl CurveResourceState {
@@ -75,4 +83,6 @@ define_new_resource!(
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct CurveImportOptions {}
 
-impl ImportOptions for CurveImportOptions {}
+impl ImportOptions for CurveImportOptions {
+    const FOLDER_OPTIONS_FILE_NAME: &'static str = "folder.curve.options";
+}
@@ -700,6 +700,32 @@ async fn convert(
         }
     }
 
+    // Precalculate radii of bones of skinned surfaces, they're used to calculate bounds of
+    // skinned meshes from actual positions of bones.
+    for &handle in fbx_model_to_node_map.values() {
+        let inv_bind_poses = match scene.graph[handle].cast::<Mesh>() {
+            Some(mesh) => mesh
+                .surfaces()
+                .iter()
+                .map(|surface| {
+                    surface
+                        .bones()
+                        .iter()
+                        .map(|&bone| scene.graph[bone].inv_bind_pose_transform())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>(),
+            None => continue,
+        };
+
+        let mesh = scene.graph[handle].as_mesh_mut();
+        for (surface, inv_bind_poses) in mesh.surfaces_mut().iter_mut().zip(inv_bind_poses) {
+            if !surface.bones().is_empty() {
+                surface.calculate_bone_radii(&inv_bind_poses);
+            }
+        }
+    }
+
     Ok(())
 }
 
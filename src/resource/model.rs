@@ -19,11 +19,15 @@
 //! and RGS (native Fyroxed format) formats are supported.
 use crate::animation::AnimationContainer;
 use crate::{
-    animation::Animation,
+    animation::{
+        retarget::{retarget_animation, BindPose, BoneMapping},
+        Animation,
+    },
     asset::{define_new_resource, Resource, ResourceData},
     core::{
         inspect::{Inspect, PropertyInfo},
         pool::Handle,
+        uuid::Uuid,
         visitor::{Visit, VisitError, VisitResult, Visitor},
     },
     engine::{
@@ -85,6 +89,17 @@ impl Model {
 
             node.resource = Some(model.clone());
 
+            // Every instance is a separate set of nodes.
+            node.instance_id = Uuid::new_v4();
+
+            // Nested instances (instances of other resources inside the model) become plain
+            // nodes of the model instance. Their inheritance was already resolved when the model
+            // was loaded, so the instance inherits properties from the model only, which in its
+            // turn inherits them from the nested resource.
+            node.is_resource_instance_root = false;
+            node.deleted_resource_nodes.clear();
+            node.added_nodes.clear();
+
             // Reset inheritable properties, so property inheritance system will take properties
             // from parent objects on resolve stage.
             node.reset_inheritable_properties();
@@ -209,6 +224,47 @@ impl Model {
     ) -> Vec<Handle<Animation>> {
         self.retarget_animations_internal(root, &mut dest_scene.graph, &mut dest_scene.animations)
     }
+
+    pub(crate) fn retarget_animations_with_mapping_internal(
+        &self,
+        mapping: &BoneMapping,
+        target_bind_pose: &BindPose,
+        animations: &mut AnimationContainer,
+    ) -> Vec<Handle<Animation>> {
+        let data = self.data_ref();
+        let graph = &data.scene.graph;
+        let source_bind_pose = BindPose::from_hierarchy(graph, graph.get_root());
+
+        data.scene
+            .animations
+            .iter()
+            .map(|ref_anim| {
+                let mut animation =
+                    retarget_animation(ref_anim, mapping, &source_bind_pose, target_bind_pose);
+                animation.resource = Some(self.clone());
+                animations.add(animation)
+            })
+            .collect()
+    }
+
+    /// Same as [`Self::retarget_animations`], but for skeletons with different bone names,
+    /// proportions or orientations of bones. Bind pose of the model is taken from the resource
+    /// and bind pose of the hierarchy starting from `root` is taken from its current state, so
+    /// the hierarchy must not be animated at the moment. See
+    /// [`crate::animation::retarget::retarget_animation`] docs for more info.
+    pub fn retarget_animations_with_mapping(
+        &self,
+        root: Handle<Node>,
+        dest_scene: &mut Scene,
+        mapping: &BoneMapping,
+    ) -> Vec<Handle<Animation>> {
+        let target_bind_pose = BindPose::from_hierarchy(&dest_scene.graph, root);
+        self.retarget_animations_with_mapping_internal(
+            mapping,
+            &target_bind_pose,
+            &mut dest_scene.animations,
+        )
+    }
 }
 
 impl ResourceData for ModelData {
@@ -219,6 +275,11 @@ impl ResourceData for ModelData {
     fn set_path(&mut self, path: PathBuf) {
         self.path = path;
     }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        // Models are always saved in the native format, even if they were loaded from FBX.
+        Ok(self.scene.save_to_file(path)?)
+    }
 }
 
 impl Default for ModelData {
@@ -325,7 +386,9 @@ pub struct ModelImportOptions {
     pub material_search_options: MaterialSearchOptions,
 }
 
-impl ImportOptions for ModelImportOptions {}
+impl ImportOptions for ModelImportOptions {
+    const FOLDER_OPTIONS_FILE_NAME: &'static str = "folder.model.options";
+}
 
 /// Model instance is a combination of handle to root node of instance in a scene,
 /// and list of all animations from model which were instantiated on a scene.
@@ -440,3 +503,327 @@ impl ModelData {
         &mut self.scene
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::{Resource, ResourceState},
+        core::{algebra::Vector3, pool::Handle, visitor::prelude::*},
+        engine::SerializationContext,
+        resource::model::{Model, ModelData, NodeMapping},
+        scene::{
+            base::{BaseBuilder, Property, PropertyValue},
+            graph::Graph,
+            node::Node,
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+            Scene,
+        },
+    };
+    use std::sync::Arc;
+
+    fn make_model(path: &str, graph: Graph) -> Model {
+        let mut scene = Scene::new();
+        scene.graph = graph;
+        Model::from(Resource::new(ResourceState::Ok(ModelData {
+            path: path.into(),
+            mapping: NodeMapping::UseHandles,
+            scene,
+        })))
+    }
+
+    fn make_pivot(graph: &mut Graph, name: &str, position: Vector3<f32>) -> Handle<Node> {
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name(name)
+                .with_tag("inner".to_owned())
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                ),
+        )
+        .build(graph)
+    }
+
+    // Handles of nodes must be the same across the versions of the inner prefab, just like they
+    // would be if the prefab was edited and saved.
+    fn make_inner_prefab(
+        body_position: Vector3<f32>,
+        hat_position: Vector3<f32>,
+        body_tag: &str,
+        with_shield: bool,
+    ) -> Model {
+        let mut graph = Graph::new();
+        let body = make_pivot(&mut graph, "Body", body_position);
+        graph[body].set_tag(body_tag.to_owned());
+        let hat = make_pivot(&mut graph, "Hat", hat_position);
+        graph.link_nodes(hat, body);
+        let visor = make_pivot(&mut graph, "Visor", Vector3::default());
+        graph.link_nodes(visor, hat);
+        make_pivot(&mut graph, "Gun", Vector3::default());
+        graph[body].properties.set(vec![Property {
+            name: "Target".to_owned(),
+            value: PropertyValue::NodeHandle(hat),
+        }]);
+        if with_shield {
+            make_pivot(&mut graph, "Shield", Vector3::default());
+        }
+        make_model("inner.rgs", graph)
+    }
+
+    fn instantiate(model: &Model, graph: &mut Graph) -> Handle<Node> {
+        let data = model.data_ref();
+        let root =
+            Model::instantiate_from(model.clone(), &data, data.scene.graph.get_root(), graph).0;
+        graph[root].is_resource_instance_root = true;
+        root
+    }
+
+    fn save(graph: &mut Graph) -> Vec<u8> {
+        let mut visitor = Visitor::new();
+        graph.visit("Graph", &mut visitor).unwrap();
+        visitor.save_binary_to_vec().unwrap()
+    }
+
+    // Mimics scene loader - loads a graph, replaces shallow resources with real ones and resolves
+    // the graph.
+    fn load(data: Vec<u8>, models: &[&Model]) -> Graph {
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        visitor.environment = Some(Arc::new(SerializationContext::new()));
+        let mut graph = Graph::default();
+        graph.visit("Graph", &mut visitor).unwrap();
+
+        for node in graph.linear_iter_mut() {
+            if let Some(shallow) = node.resource.clone() {
+                let path = shallow.state().path().to_path_buf();
+                node.resource = models
+                    .iter()
+                    .find(|m| m.state().path() == path)
+                    .map(|&m| m.clone());
+                assert!(node.resource.is_some());
+            }
+        }
+
+        graph.resolve();
+        graph
+    }
+
+    fn find(graph: &Graph, name: &str) -> Handle<Node> {
+        let count = graph.linear_iter().filter(|n| n.name() == name).count();
+        assert!(count <= 1, "{} has {} instances", name, count);
+        graph.find_by_name_from_root(name)
+    }
+
+    fn target(graph: &Graph, handle: Handle<Node>) -> Handle<Node> {
+        match graph[handle].properties[0].value {
+            PropertyValue::NodeHandle(target) => target,
+            _ => unreachable!(),
+        }
+    }
+
+    // Outer prefab contains an instance of the inner one and overrides its tag and deletes Gun,
+    // the scene contains an instance of the outer prefab, overrides Hat position and its properties
+    // and deletes Visor.
+    fn make_outer_prefab_and_scene(inner: &Model) -> (Vec<u8>, Vec<u8>) {
+        let mut outer_graph = Graph::new();
+        instantiate(inner, &mut outer_graph);
+        let body = find(&outer_graph, "Body");
+        outer_graph[body].set_tag("outer".to_owned());
+        let gun = find(&outer_graph, "Gun");
+        outer_graph.remove_node(gun);
+        let outer_data = save(&mut outer_graph);
+        let outer = make_model("outer.rgs", load(outer_data.clone(), &[inner]));
+
+        let mut scene_graph = Graph::new();
+        // Add some nodes to make handles of the scene graph different from handles of prefabs.
+        let extra = (0..5)
+            .map(|i| make_pivot(&mut scene_graph, &format!("Extra{}", i), Vector3::default()))
+            .collect::<Vec<_>>();
+        instantiate(&outer, &mut scene_graph);
+        let hat = find(&scene_graph, "Hat");
+        scene_graph[hat]
+            .local_transform_mut()
+            .set_position(Vector3::new(0.0, 0.0, 5.0));
+        scene_graph[hat].properties.set(vec![Property {
+            name: "Target".to_owned(),
+            value: PropertyValue::NodeHandle(extra[2]),
+        }]);
+        let visor = find(&scene_graph, "Visor");
+        scene_graph.remove_node(visor);
+        let scene_data = save(&mut scene_graph);
+
+        (outer_data, scene_data)
+    }
+
+    fn check_scene(graph: &Graph, body_position: Vector3<f32>) {
+        let body = find(graph, "Body");
+        let hat = find(graph, "Hat");
+        assert_eq!(**graph[body].local_transform().position(), body_position);
+        assert_eq!(graph[body].tag(), "outer");
+        assert_eq!(target(graph, body), hat);
+        assert_eq!(
+            **graph[hat].local_transform().position(),
+            Vector3::new(0.0, 0.0, 5.0)
+        );
+        assert_eq!(target(graph, hat), find(graph, "Extra2"));
+        assert_eq!(find(graph, "Gun"), Handle::NONE);
+        assert_eq!(find(graph, "Visor"), Handle::NONE);
+    }
+
+    #[test]
+    fn test_nested_prefab_round_trip() {
+        let inner = make_inner_prefab(
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            "inner",
+            false,
+        );
+        let (outer_data, scene_data) = make_outer_prefab_and_scene(&inner);
+        let outer = make_model("outer.rgs", load(outer_data, &[&inner]));
+
+        let scene = load(scene_data.clone(), &[&outer]);
+        check_scene(&scene, Vector3::new(1.0, 0.0, 0.0));
+
+        // Saving and loading of a resolved graph must not change anything.
+        let mut scene = scene;
+        let scene = load(save(&mut scene), &[&outer]);
+        check_scene(&scene, Vector3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_nested_prefab_inner_source_changed() {
+        let inner = make_inner_prefab(
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            "inner",
+            false,
+        );
+        let (outer_data, scene_data) = make_outer_prefab_and_scene(&inner);
+
+        // Inner prefab was changed: Body and Hat were moved, Body's tag was changed and a new node
+        // was added.
+        let changed_inner = make_inner_prefab(
+            Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(0.0, 2.0, 0.0),
+            "changed",
+            true,
+        );
+        let outer = make_model("outer.rgs", load(outer_data, &[&changed_inner]));
+        {
+            let outer_graph = &outer.data_ref().scene.graph;
+            let body = find(outer_graph, "Body");
+            assert_eq!(
+                **outer_graph[body].local_transform().position(),
+                Vector3::new(2.0, 0.0, 0.0)
+            );
+            assert_eq!(outer_graph[body].tag(), "outer");
+            assert_eq!(find(outer_graph, "Gun"), Handle::NONE);
+            assert_ne!(find(outer_graph, "Shield"), Handle::NONE);
+        }
+
+        let scene = load(scene_data, &[&outer]);
+        check_scene(&scene, Vector3::new(2.0, 0.0, 0.0));
+        assert_ne!(find(&scene, "Shield"), Handle::NONE);
+    }
+
+    fn parent_name(graph: &Graph, handle: Handle<Node>) -> &str {
+        graph[graph[handle].parent()].name()
+    }
+
+    // The scene contains an instance of the inner prefab with Gun deleted, Lamp added under Hat and
+    // a copy of Visor added under Body.
+    fn make_customized_scene(inner: &Model) -> Vec<u8> {
+        let mut graph = Graph::new();
+        let instance_root = instantiate(inner, &mut graph);
+
+        let gun = find(&graph, "Gun");
+        graph.remove_node(gun);
+
+        let lamp = make_pivot(&mut graph, "Lamp", Vector3::default());
+        graph.link_nodes(lamp, find(&graph, "Hat"));
+
+        let mut visor_copy = graph.copy_single_node(find(&graph, "Visor"));
+        visor_copy.set_name("VisorCopy");
+        visor_copy
+            .local_transform_mut()
+            .set_position(Vector3::new(0.0, 0.0, 3.0));
+        let visor_copy = graph.add_node(visor_copy);
+        graph.link_nodes(visor_copy, find(&graph, "Body"));
+
+        assert_eq!(graph[instance_root].suppressed_resource_nodes().len(), 1);
+        let added = graph.find_added_nodes(instance_root);
+        assert_eq!(added.len(), 2);
+        assert!(added.contains(&lamp) && added.contains(&visor_copy));
+
+        save(&mut graph)
+    }
+
+    fn check_customized_scene(graph: &Graph) {
+        assert_eq!(find(graph, "Gun"), Handle::NONE);
+        assert_eq!(parent_name(graph, find(graph, "Lamp")), "Hat");
+
+        let visor_copy = find(graph, "VisorCopy");
+        assert_eq!(parent_name(graph, visor_copy), "Body");
+        assert!(graph[visor_copy].resource().is_none());
+        assert_eq!(
+            **graph[visor_copy].local_transform().position(),
+            Vector3::new(0.0, 0.0, 3.0)
+        );
+        assert_eq!(parent_name(graph, find(graph, "Visor")), "Hat");
+    }
+
+    #[test]
+    fn test_structural_overrides_source_changed() {
+        let inner = make_inner_prefab(
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            "inner",
+            false,
+        );
+        let scene_data = make_customized_scene(&inner);
+
+        // The prefab was changed after the instance was customized: Hat was moved and a new node
+        // was added.
+        let changed_inner = make_inner_prefab(
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 2.0, 0.0),
+            "inner",
+            true,
+        );
+
+        let scene = load(scene_data, &[&changed_inner]);
+        check_customized_scene(&scene);
+        assert_ne!(find(&scene, "Shield"), Handle::NONE);
+        assert_eq!(
+            **scene[find(&scene, "Hat")].local_transform().position(),
+            Vector3::new(0.0, 2.0, 0.0)
+        );
+
+        // Saving and loading of a resolved graph must not change anything.
+        let mut scene = scene;
+        let scene = load(save(&mut scene), &[&changed_inner]);
+        check_customized_scene(&scene);
+        assert_ne!(find(&scene, "Shield"), Handle::NONE);
+    }
+
+    #[test]
+    fn test_restore_suppressed_node() {
+        let inner = make_inner_prefab(
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            "inner",
+            false,
+        );
+        let mut scene = load(make_customized_scene(&inner), &[&inner]);
+
+        let instance_root = scene.find_instance_root(find(&scene, "Body"));
+        let original = scene[instance_root].suppressed_resource_nodes()[0];
+        let gun = scene.restore_suppressed_node(instance_root, original);
+        assert_eq!(find(&scene, "Gun"), gun);
+        assert!(scene[instance_root].suppressed_resource_nodes().is_empty());
+
+        let scene = load(save(&mut scene), &[&inner]);
+        assert_ne!(find(&scene, "Gun"), Handle::NONE);
+    }
+}
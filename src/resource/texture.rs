@@ -2,7 +2,7 @@
 //!
 //! In most cases textures are just 2D images, however there are some exclusions to that -
 //! for example cube maps, that may be used for environment mapping. Fyrox supports 1D, 2D,
-//! 3D and Cube textures.
+//! 3D, Cube and 2D array textures.
 //!
 //! ## Supported formats
 //!
@@ -13,6 +13,14 @@
 //!
 //! Fyrox supports most commonly used formats of compressed textures: DXT1, DXT3, DXT5.
 //!
+//! Textures in other formats are compressed at import time, the target format is defined by
+//! [`TextureCompressionFormat`] in import options of a texture. Compression (and mip-map
+//! generation) is expensive, so its result is stored in the [`TEXTURE_CACHE_FOLDER`] under a key
+//! that depends on the source data and import options, and next loads of the texture use the
+//! cached data. Use [`prewarm_texture_cache`] to fill the cache for every texture in a folder.
+//! If a GPU does not support a compressed format, textures of this format are decompressed
+//! before uploading to the GPU.
+//!
 //! ## Render target
 //!
 //! Texture can be used as render target to render scene in it. To do this you should use
@@ -23,12 +31,17 @@
 use crate::{
     asset::{define_new_resource, Resource, ResourceData, ResourceState},
     core::{
+        algebra::Vector3,
+        color::Color,
         futures::io::Error,
         inspect::{Inspect, PropertyInfo},
         io::{self, FileLoadError},
+        parking_lot::Mutex,
         visitor::{PodVecView, Visit, VisitError, VisitResult, Visitor},
     },
-    engine::resource_manager::options::ImportOptions,
+    engine::resource_manager::options::{resolve_import_options, ImportOptions},
+    lazy_static::lazy_static,
+    utils::log::Log,
 };
 use ddsfile::{Caps2, D3DFormat};
 use fxhash::FxHasher;
@@ -75,6 +88,16 @@ pub enum TextureKind {
         /// Depth of the volume.
         depth: u32,
     },
+    /// Array of 2D textures of the same size and pixel format. Shaders access such textures using
+    /// `sampler2DArray` samplers, where third texture coordinate is an index of a layer.
+    Array {
+        /// Width of each layer.
+        width: u32,
+        /// Height of each layer.
+        height: u32,
+        /// Amount of layers.
+        layers: u32,
+    },
 }
 
 impl Default for TextureKind {
@@ -95,6 +118,7 @@ impl Visit for TextureKind {
             TextureKind::Rectangle { .. } => 1,
             TextureKind::Cube { .. } => 2,
             TextureKind::Volume { .. } => 3,
+            TextureKind::Array { .. } => 4,
         };
         id.visit("Id", &mut region)?;
         if region.is_reading() {
@@ -113,6 +137,11 @@ impl Visit for TextureKind {
                     height: 0,
                     depth: 0,
                 },
+                4 => TextureKind::Array {
+                    width: 0,
+                    height: 0,
+                    layers: 0,
+                },
                 _ => {
                     return VisitResult::Err(VisitError::User(format!(
                         "Invalid texture kind {}!",
@@ -142,6 +171,15 @@ impl Visit for TextureKind {
                 height.visit("Height", &mut region)?;
                 depth.visit("Depth", &mut region)?;
             }
+            TextureKind::Array {
+                width,
+                height,
+                layers,
+            } => {
+                width.visit("Width", &mut region)?;
+                height.visit("Height", &mut region)?;
+                layers.visit("Layers", &mut region)?;
+            }
         }
 
         Ok(())
@@ -209,6 +247,14 @@ impl ResourceData for TextureData {
     fn set_path(&mut self, path: PathBuf) {
         self.path = path;
     }
+
+    fn save(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(self.save_to_file(path)?)
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.bytes.len()
+    }
 }
 
 impl Visit for TextureData {
@@ -285,7 +331,10 @@ impl Default for TextureData {
 ///     s_wrap_mode: Repeat,
 ///     t_wrap_mode: ClampToEdge,
 ///     anisotropy: 8.0,
-///     compression: NoCompression,    
+///     compression: NoCompression,
+///     format: Auto,
+///     normal_map: false,
+///     srgb: true,
 /// )
 /// ```
 #[derive(Clone, Deserialize, Serialize, Inspect)]
@@ -302,6 +351,12 @@ pub struct TextureImportOptions {
     pub(crate) anisotropy: f32,
     #[serde(default)]
     pub(crate) compression: CompressionOptions,
+    #[serde(default)]
+    pub(crate) format: TextureCompressionFormat,
+    #[serde(default)]
+    pub(crate) normal_map: bool,
+    #[serde(default)]
+    pub(crate) srgb: bool,
 }
 
 impl Default for TextureImportOptions {
@@ -313,11 +368,16 @@ impl Default for TextureImportOptions {
             t_wrap_mode: TextureWrapMode::Repeat,
             anisotropy: 16.0,
             compression: CompressionOptions::Quality,
+            format: TextureCompressionFormat::Auto,
+            normal_map: false,
+            srgb: false,
         }
     }
 }
 
-impl ImportOptions for TextureImportOptions {}
+impl ImportOptions for TextureImportOptions {
+    const FOLDER_OPTIONS_FILE_NAME: &'static str = "folder.texture.options";
+}
 
 impl TextureImportOptions {
     /// Sets new minification filter which will be applied to every imported texture as
@@ -401,6 +461,43 @@ impl TextureImportOptions {
     pub fn set_compression(&mut self, compression: CompressionOptions) {
         self.compression = compression;
     }
+
+    /// Sets desired format of compressed texture, see [`TextureCompressionFormat`] docs for more info.
+    pub fn with_format(mut self, format: TextureCompressionFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets desired format of compressed texture, see [`TextureCompressionFormat`] docs for more info.
+    pub fn set_format(&mut self, format: TextureCompressionFormat) {
+        self.format = format;
+    }
+
+    /// Marks the texture as a normal map. Normal maps are compressed into two-channel format (only X
+    /// and Y components are stored, Z is restored in shaders) and their mip-maps are re-normalized.
+    pub fn with_normal_map(mut self, normal_map: bool) -> Self {
+        self.normal_map = normal_map;
+        self
+    }
+
+    /// Marks the texture as a normal map. Normal maps are compressed into two-channel format (only X
+    /// and Y components are stored, Z is restored in shaders) and their mip-maps are re-normalized.
+    pub fn set_normal_map(&mut self, normal_map: bool) {
+        self.normal_map = normal_map;
+    }
+
+    /// Tells that color components of the texture are in sRGB color space, mip-maps of such textures
+    /// are generated in linear color space. Ignored for normal maps.
+    pub fn with_srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    /// Tells that color components of the texture are in sRGB color space, mip-maps of such textures
+    /// are generated in linear color space. Ignored for normal maps.
+    pub fn set_srgb(&mut self, srgb: bool) {
+        self.srgb = srgb;
+    }
 }
 
 define_new_resource!(
@@ -473,6 +570,14 @@ impl Texture {
             TextureData::from_bytes(kind, pixel_kind, bytes, serialize_content)?,
         ))))
     }
+
+    /// Creates new texture array from a set of 2D textures. See [`TextureData::new_array`] for
+    /// more info.
+    pub fn new_array(layers: &[Option<Texture>], fallback: Color) -> Self {
+        Self(Resource::new(TextureState::Ok(TextureData::new_array(
+            layers, fallback,
+        ))))
+    }
 }
 
 /// The texture magnification function is used when the pixel being textured maps to an area
@@ -712,6 +817,19 @@ impl TexturePixelKind {
     fn id(self) -> u32 {
         self as u32
     }
+
+    /// Returns `true` if the pixel kind is a block-compressed format.
+    pub fn is_compressed(self) -> bool {
+        matches!(
+            self,
+            Self::DXT1RGB
+                | Self::DXT1RGBA
+                | Self::DXT3RGBA
+                | Self::DXT5RGBA
+                | Self::R8RGTC
+                | Self::RG8RGTC
+        )
+    }
 }
 
 /// An error that may occur during texture operations.
@@ -729,6 +847,9 @@ pub enum TextureError {
     /// An error occurred during file loading.
     #[error("A file load error has occurred {0:?}")]
     FileLoadError(FileLoadError),
+    /// Texture content exists only on GPU (for example, it is a render target).
+    #[error("Texture content is not available on CPU side!")]
+    NoCpuData,
 }
 
 impl From<FileLoadError> for TextureError {
@@ -757,9 +878,10 @@ fn ceil_div_4(x: u32) -> u32 {
 ///
 /// # Notes
 ///
-/// Try to avoid using compression for normal maps, normals maps usually has smooth
-/// gradients, but compression algorithms used by Fyrox cannot preserve good quality
-/// of such gradients.
+/// Try to avoid using these options for normal maps, normals maps usually has smooth
+/// gradients, but DXT compression cannot preserve good quality of such gradients. Mark
+/// normal maps using [`TextureImportOptions::set_normal_map`] instead, they will be
+/// compressed to two-channel BC5 format that preserves gradients much better.
 #[derive(
     Copy,
     Clone,
@@ -799,6 +921,55 @@ impl Default for CompressionOptions {
     }
 }
 
+/// Target format of texture compression at import time.
+///
+/// # Notes
+///
+/// BC7, ETC2 and ASTC formats are not supported yet, because there is no encoder for them. On
+/// platforms that do not support BC formats (mobile GPUs, WebGL) compressed textures are
+/// decompressed on load, use `Uncompressed` for such platforms to skip decompression.
+#[derive(
+    Copy,
+    Clone,
+    Deserialize,
+    Serialize,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+    Inspect,
+    EnumVariantNames,
+    EnumString,
+    AsRefStr,
+)]
+#[repr(u32)]
+pub enum TextureCompressionFormat {
+    /// The format is chosen automatically: normal maps are compressed to BC5, other textures are
+    /// compressed according to [`CompressionOptions`].
+    Auto = 0,
+
+    /// A texture is stored without compression, [`CompressionOptions`] are ignored.
+    Uncompressed = 1,
+
+    /// DXT1 (BC1) - 4 bits per pixel, RGB with 1-bit alpha. Good for opaque color textures.
+    Bc1 = 2,
+
+    /// DXT5 (BC3) - 8 bits per pixel, RGB with smooth alpha.
+    Bc3 = 3,
+
+    /// BC4 - 4 bits per pixel, single channel (red). Good for masks, roughness, metallic, etc.
+    Bc4 = 4,
+
+    /// BC5 - 8 bits per pixel, two channels (red and green). Good for normal maps.
+    Bc5 = 5,
+}
+
+impl Default for TextureCompressionFormat {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
 fn transmute_slice<T>(bytes: &[u8]) -> &'_ [T] {
     // This is absolutely safe because `image` crate's Rgb8/Rgba8/etc. and `tbc`s Rgb8/Rgba8/etc.
     // have exactly the same memory layout.
@@ -874,6 +1045,383 @@ fn try_compress(
     }
 }
 
+fn encode(
+    image: &DynamicImage,
+    w: usize,
+    h: usize,
+    options: &TextureImportOptions,
+) -> Option<(Vec<u8>, TexturePixelKind)> {
+    let format = match options.format {
+        TextureCompressionFormat::Auto => {
+            if options.compression == CompressionOptions::NoCompression {
+                return None;
+            } else if !options.normal_map {
+                return try_compress(image, w, h, options.compression);
+            }
+            TextureCompressionFormat::Bc5
+        }
+        format => format,
+    };
+
+    match format {
+        TextureCompressionFormat::Auto | TextureCompressionFormat::Uncompressed => None,
+        TextureCompressionFormat::Bc1 => Some(if image.color().has_alpha() {
+            (
+                compress_bc1::<tbc::color::Rgba8>(image.to_rgba8().as_raw(), w, h),
+                TexturePixelKind::DXT1RGBA,
+            )
+        } else {
+            (
+                compress_bc1::<tbc::color::Rgb8>(image.to_rgb8().as_raw(), w, h),
+                TexturePixelKind::DXT1RGB,
+            )
+        }),
+        TextureCompressionFormat::Bc3 => Some((
+            compress_bc3::<tbc::color::Rgba8>(image.to_rgba8().as_raw(), w, h),
+            TexturePixelKind::DXT5RGBA,
+        )),
+        TextureCompressionFormat::Bc4 => {
+            let red = image.to_rgb8().pixels().map(|p| p[0]).collect::<Vec<_>>();
+            Some((
+                compress_r8_bc4::<tbc::color::Red8>(&red, w, h),
+                TexturePixelKind::R8RGTC,
+            ))
+        }
+        TextureCompressionFormat::Bc5 => {
+            let red_green = match image {
+                DynamicImage::ImageLumaA8(luma_alpha) => luma_alpha.as_raw().clone(),
+                _ => image
+                    .to_rgb8()
+                    .pixels()
+                    .flat_map(|p| [p[0], p[1]])
+                    .collect::<Vec<_>>(),
+            };
+            Some((
+                compress_rg8_bc4::<tbc::color::RedGreen8>(&red_green, w, h),
+                TexturePixelKind::RG8RGTC,
+            ))
+        }
+    }
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    c.max(0.0).powf(2.2)
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    c.max(0.0).powf(1.0 / 2.2)
+}
+
+/// Creates next mip level of an image. Color of sRGB images is filtered in linear space, normals
+/// of normal maps are re-normalized after filtering.
+fn make_mip_level(
+    image: &DynamicImage,
+    width: u32,
+    height: u32,
+    options: &TextureImportOptions,
+) -> DynamicImage {
+    let is_8bit_color = matches!(
+        image,
+        DynamicImage::ImageRgb8(_) | DynamicImage::ImageRgba8(_)
+    );
+
+    if !is_8bit_color || !(options.srgb || options.normal_map) {
+        return image.resize_exact(width, height, FilterType::Lanczos3);
+    }
+
+    let mut linear = image.to_rgba32f();
+    if !options.normal_map {
+        for pixel in linear.pixels_mut() {
+            for c in pixel.0.iter_mut().take(3) {
+                *c = srgb_to_linear(*c);
+            }
+        }
+    }
+
+    let mut resized = image::imageops::resize(&linear, width, height, FilterType::Lanczos3);
+    for pixel in resized.pixels_mut() {
+        let [r, g, b, _] = &mut pixel.0;
+        if options.normal_map {
+            let n = Vector3::new(*r * 2.0 - 1.0, *g * 2.0 - 1.0, *b * 2.0 - 1.0)
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::z);
+            *r = n.x * 0.5 + 0.5;
+            *g = n.y * 0.5 + 0.5;
+            *b = n.z * 0.5 + 0.5;
+        } else {
+            *r = linear_to_srgb(*r);
+            *g = linear_to_srgb(*g);
+            *b = linear_to_srgb(*b);
+        }
+    }
+
+    let resized = DynamicImage::ImageRgba32F(resized);
+    match image {
+        DynamicImage::ImageRgb8(_) => DynamicImage::ImageRgb8(resized.to_rgb8()),
+        _ => DynamicImage::ImageRgba8(resized.to_rgba8()),
+    }
+}
+
+fn expand_565(color: u16) -> [u8; 4] {
+    let r = ((color >> 11) & 0x1F) as u32;
+    let g = ((color >> 5) & 0x3F) as u32;
+    let b = (color & 0x1F) as u32;
+    [
+        ((r * 255 + 15) / 31) as u8,
+        ((g * 255 + 31) / 63) as u8,
+        ((b * 255 + 15) / 31) as u8,
+        255,
+    ]
+}
+
+/// Decodes color part of BC1, BC2 or BC3 block. `bc1_black_alpha` must be `Some` only for BC1 blocks,
+/// it defines alpha of the black color of three-color mode (zero for DXT1 with alpha).
+fn decode_color_block(block: &[u8], bc1_black_alpha: Option<u8>, out: &mut [[u8; 4]; 16]) {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let (a, b) = (expand_565(c0), expand_565(c1));
+    let mix = |wa: u32, wb: u32| {
+        let c = |i: usize| ((a[i] as u32 * wa + b[i] as u32 * wb) / (wa + wb)) as u8;
+        [c(0), c(1), c(2), 255]
+    };
+    let palette = match bc1_black_alpha {
+        Some(black_alpha) if c0 <= c1 => [a, b, mix(1, 1), [0, 0, 0, black_alpha]],
+        _ => [a, b, mix(2, 1), mix(1, 2)],
+    };
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    for (i, texel) in out.iter_mut().enumerate() {
+        *texel = palette[((indices >> (2 * i)) & 3) as usize];
+    }
+}
+
+/// Decodes single channel BC4 block (alpha part of BC3 block is the same) into a channel of texels.
+fn decode_channel_block(block: &[u8], channel: usize, out: &mut [[u8; 4]; 16]) {
+    let (e0, e1) = (block[0] as u32, block[1] as u32);
+    let mut palette = [e0, e1, 0, 0, 0, 0, 0, 255];
+    if e0 > e1 {
+        for i in 1..7 {
+            palette[i + 1] = ((7 - i as u32) * e0 + i as u32 * e1) / 7;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i + 1] = ((5 - i as u32) * e0 + i as u32 * e1) / 5;
+        }
+        palette[6] = 0;
+    }
+    let indices = block[2..8]
+        .iter()
+        .enumerate()
+        .fold(0u64, |bits, (i, byte)| bits | ((*byte as u64) << (8 * i)));
+    for (i, texel) in out.iter_mut().enumerate() {
+        texel[channel] = palette[((indices >> (3 * i)) & 7) as usize] as u8;
+    }
+}
+
+/// Decodes explicit 4-bit alpha of BC2 block.
+fn decode_explicit_alpha_block(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    for (i, texel) in out.iter_mut().enumerate() {
+        texel[3] = ((block[i / 2] >> (4 * (i % 2))) & 0xF) * 17;
+    }
+}
+
+/// Decompresses a single image, returns pixels in the respective uncompressed pixel kind (see
+/// [`TextureData::decompress`]).
+fn decompress_image(
+    pixel_kind: TexturePixelKind,
+    bytes: &[u8],
+    width: usize,
+    height: usize,
+) -> Vec<u8> {
+    let (block_size, channels) = match pixel_kind {
+        TexturePixelKind::DXT1RGB | TexturePixelKind::DXT1RGBA => (8, 4),
+        TexturePixelKind::DXT3RGBA | TexturePixelKind::DXT5RGBA => (16, 4),
+        TexturePixelKind::R8RGTC => (8, 1),
+        TexturePixelKind::RG8RGTC => (16, 2),
+        _ => unreachable!(),
+    };
+
+    let blocks_x = (width + 3) / 4;
+    let mut pixels = vec![0; width * height * channels];
+    let mut texels = [[0u8; 4]; 16];
+    for (block_index, block) in bytes.chunks_exact(block_size).enumerate() {
+        match pixel_kind {
+            TexturePixelKind::DXT1RGB => decode_color_block(block, Some(255), &mut texels),
+            TexturePixelKind::DXT1RGBA => decode_color_block(block, Some(0), &mut texels),
+            TexturePixelKind::DXT3RGBA => {
+                decode_explicit_alpha_block(&block[0..8], &mut texels);
+                // Color part must not overwrite alpha.
+                let alpha = texels.map(|t| t[3]);
+                decode_color_block(&block[8..16], None, &mut texels);
+                for (texel, alpha) in texels.iter_mut().zip(alpha) {
+                    texel[3] = alpha;
+                }
+            }
+            TexturePixelKind::DXT5RGBA => {
+                decode_color_block(&block[8..16], None, &mut texels);
+                decode_channel_block(&block[0..8], 3, &mut texels);
+            }
+            TexturePixelKind::R8RGTC => decode_channel_block(block, 0, &mut texels),
+            TexturePixelKind::RG8RGTC => {
+                decode_channel_block(&block[0..8], 0, &mut texels);
+                decode_channel_block(&block[8..16], 1, &mut texels);
+            }
+            _ => unreachable!(),
+        }
+
+        let (bx, by) = (4 * (block_index % blocks_x), 4 * (block_index / blocks_x));
+        for (i, texel) in texels.iter().enumerate() {
+            let (x, y) = (bx + i % 4, by + i / 4);
+            if x < width && y < height {
+                let offset = (y * width + x) * channels;
+                pixels[offset..offset + channels].copy_from_slice(&texel[0..channels]);
+            }
+        }
+    }
+    pixels
+}
+
+/// Increment this if the way textures are imported has changed, so old cached data won't be used.
+const TEXTURE_CACHE_VERSION: u32 = 1;
+
+/// A folder (relative to the data root, see [`set_texture_cache_root`]) where compressed
+/// representation of imported textures is stored. It is safe to delete the folder, it will be
+/// filled again on next loads.
+pub const TEXTURE_CACHE_FOLDER: &str = ".derived/textures";
+
+lazy_static! {
+    static ref TEXTURE_CACHE_ROOT: Mutex<PathBuf> = Mutex::new(default_texture_cache_root());
+}
+
+fn default_texture_cache_root() -> PathBuf {
+    // Tests must not litter the working directory.
+    if cfg!(test) {
+        std::env::temp_dir().join("fyrox_test_data")
+    } else {
+        PathBuf::from(".")
+    }
+}
+
+/// Sets the data root of the project, the texture cache is stored in the [`TEXTURE_CACHE_FOLDER`]
+/// inside it. The game executor and the editor set it from the project manifest (see
+/// [`crate::engine::project::ProjectManifest::data_root`]), the working directory is used by
+/// default.
+pub fn set_texture_cache_root<P: AsRef<Path>>(data_root: P) {
+    *TEXTURE_CACHE_ROOT.lock() = data_root.as_ref().to_path_buf();
+}
+
+/// Returns the folder where compressed representation of imported textures is stored.
+pub fn texture_cache_folder() -> PathBuf {
+    TEXTURE_CACHE_ROOT.lock().join(TEXTURE_CACHE_FOLDER)
+}
+
+fn is_dds(data: &[u8]) -> bool {
+    data.starts_with(b"DDS ")
+}
+
+/// Returns path of cached data of a texture with the given source data and import options.
+fn cached_texture_path(data: &[u8], options: &TextureImportOptions, gen_mip_maps: bool) -> PathBuf {
+    let mut hasher = FxHasher::default();
+    TEXTURE_CACHE_VERSION.hash(&mut hasher);
+    data.hash(&mut hasher);
+    (options.compression as u32).hash(&mut hasher);
+    options.format.hash(&mut hasher);
+    options.normal_map.hash(&mut hasher);
+    options.srgb.hash(&mut hasher);
+    gen_mip_maps.hash(&mut hasher);
+    texture_cache_folder().join(format!("{:016x}.texture", hasher.finish()))
+}
+
+/// Imports every texture in the given folder (including sub-folders) whose compressed
+/// representation is not in the [`TEXTURE_CACHE_FOLDER`] yet, so next loads of the textures won't
+/// spend time on compression. Import options of a texture are resolved by
+/// [`resolve_import_options`], the given default options are used if neither the texture nor its
+/// folders have options. Returns the amount of textures that were imported.
+pub async fn prewarm_texture_cache<P: AsRef<Path>>(
+    folder: P,
+    default_import_options: &TextureImportOptions,
+) -> usize {
+    let mut count = 0;
+    for entry in walkdir::WalkDir::new(folder).into_iter().flatten() {
+        let path = entry.path();
+        let is_texture = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map_or(false, |ext| {
+                matches!(
+                    ext.to_lowercase().as_str(),
+                    "png" | "jpg" | "jpeg" | "tga" | "bmp" | "gif" | "tif" | "tiff"
+                )
+            });
+        if !is_texture {
+            continue;
+        }
+
+        let (options, _) = resolve_import_options(path, default_import_options.clone()).await;
+
+        match TextureData::update_cache(path, &options).await {
+            Ok(true) => {
+                Log::info(format!("Texture {} is added to the cache.", path.display()));
+                count += 1;
+            }
+            Ok(false) => (),
+            Err(e) => Log::err(format!(
+                "Unable to add texture {} to the cache. Reason: {:?}",
+                path.display(),
+                e
+            )),
+        }
+    }
+    count
+}
+
+/// Returns bytes of a single pixel (or a single block for compressed formats) filled with
+/// the given color.
+fn constant_pixel_block(pixel_kind: TexturePixelKind, color: Color) -> Vec<u8> {
+    let wide = |c: u8| (c as u16 * 257).to_le_bytes();
+    let float = |c: u8| (c as f32 / 255.0).to_le_bytes();
+    let color_block = || {
+        let rgb565 =
+            ((color.r as u16 >> 3) << 11) | ((color.g as u16 >> 2) << 5) | (color.b as u16 >> 3);
+        // Both end points are the same and all indices are zero.
+        let mut block = Vec::with_capacity(8);
+        block.extend_from_slice(&rgb565.to_le_bytes());
+        block.extend_from_slice(&rgb565.to_le_bytes());
+        block.extend_from_slice(&[0; 4]);
+        block
+    };
+    let channel_block = |c: u8| [c, c, 0, 0, 0, 0, 0, 0];
+    match pixel_kind {
+        TexturePixelKind::R8 => vec![color.r],
+        TexturePixelKind::RG8 => vec![color.r, color.g],
+        TexturePixelKind::RGB8 => vec![color.r, color.g, color.b],
+        TexturePixelKind::RGBA8 => vec![color.r, color.g, color.b, color.a],
+        TexturePixelKind::BGR8 => vec![color.b, color.g, color.r],
+        TexturePixelKind::BGRA8 => vec![color.b, color.g, color.r, color.a],
+        TexturePixelKind::R16 => wide(color.r).to_vec(),
+        TexturePixelKind::RG16 => [wide(color.r), wide(color.g)].concat(),
+        TexturePixelKind::RGB16 => [wide(color.r), wide(color.g), wide(color.b)].concat(),
+        TexturePixelKind::RGBA16 => {
+            [wide(color.r), wide(color.g), wide(color.b), wide(color.a)].concat()
+        }
+        TexturePixelKind::RGB32F => [float(color.r), float(color.g), float(color.b)].concat(),
+        TexturePixelKind::RGBA32F => [
+            float(color.r),
+            float(color.g),
+            float(color.b),
+            float(color.a),
+        ]
+        .concat(),
+        TexturePixelKind::DXT1RGB | TexturePixelKind::DXT1RGBA => color_block(),
+        TexturePixelKind::DXT3RGBA => {
+            let alpha = (color.a >> 4) | (color.a & 0xF0);
+            [vec![alpha; 8], color_block()].concat()
+        }
+        TexturePixelKind::DXT5RGBA => [channel_block(color.a).to_vec(), color_block()].concat(),
+        TexturePixelKind::R8RGTC => channel_block(color.r).to_vec(),
+        TexturePixelKind::RG8RGTC => [channel_block(color.r), channel_block(color.g)].concat(),
+    }
+}
+
 fn bytes_in_first_mip(kind: TextureKind, pixel_kind: TexturePixelKind) -> u32 {
     let pixel_count = match kind {
         TextureKind::Line { length } => length,
@@ -884,6 +1432,11 @@ fn bytes_in_first_mip(kind: TextureKind, pixel_kind: TexturePixelKind) -> u32 {
             height,
             depth,
         } => width * height * depth,
+        TextureKind::Array {
+            width,
+            height,
+            layers,
+        } => width * height * layers,
     };
     match pixel_kind {
         // Uncompressed formats.
@@ -927,6 +1480,11 @@ fn bytes_in_first_mip(kind: TextureKind, pixel_kind: TexturePixelKind) -> u32 {
                     height,
                     depth,
                 } => ceil_div_4(width) * ceil_div_4(height) * ceil_div_4(depth) * block_size,
+                TextureKind::Array {
+                    width,
+                    height,
+                    layers,
+                } => ceil_div_4(width) * ceil_div_4(height) * layers * block_size,
             }
         }
     }
@@ -958,6 +1516,18 @@ impl TextureData {
         data: &[u8],
         compression: CompressionOptions,
         gen_mip_maps: bool,
+    ) -> Result<Self, TextureError> {
+        Self::import(
+            data,
+            &TextureImportOptions::default().with_compression(compression),
+            gen_mip_maps,
+        )
+    }
+
+    fn import(
+        data: &[u8],
+        options: &TextureImportOptions,
+        gen_mip_maps: bool,
     ) -> Result<Self, TextureError> {
         // DDS is special. It can contain various kinds of textures as well as textures with
         // various pixel formats.
@@ -1078,22 +1648,17 @@ impl TextureData {
 
                 while level_width != 0 && level_height != 0 {
                     if mip_count != 0 {
-                        current_level = current_level.resize_exact(
-                            level_width,
-                            level_height,
-                            FilterType::Lanczos3,
-                        );
+                        current_level =
+                            make_mip_level(&current_level, level_width, level_height, options);
                     }
 
                     mip_count += 1;
 
-                    if compression == CompressionOptions::NoCompression {
-                        bytes.extend_from_slice(current_level.as_bytes())
-                    } else if let Some((compressed_data, new_pixel_kind)) = try_compress(
+                    if let Some((compressed_data, new_pixel_kind)) = encode(
                         &current_level,
                         level_width as usize,
                         level_height as usize,
-                        compression,
+                        options,
                     ) {
                         pixel_kind = new_pixel_kind;
                         bytes.extend_from_slice(&compressed_data);
@@ -1107,10 +1672,8 @@ impl TextureData {
             } else {
                 mip_count = 1;
 
-                if compression == CompressionOptions::NoCompression {
-                    bytes.extend_from_slice(dyn_img.as_bytes());
-                } else if let Some((compressed_data, new_pixel_kind)) =
-                    try_compress(&dyn_img, width as usize, height as usize, compression)
+                if let Some((compressed_data, new_pixel_kind)) =
+                    encode(&dyn_img, width as usize, height as usize, options)
                 {
                     pixel_kind = new_pixel_kind;
                     bytes.extend_from_slice(&compressed_data);
@@ -1136,17 +1699,124 @@ impl TextureData {
     ///
     /// It is **not** public because you must use resource manager to load textures from external
     /// resources.
-    pub(in crate) async fn load_from_file<P: AsRef<Path>>(
+    ///
+    /// Compressed representation of the texture is taken from the [`TEXTURE_CACHE_FOLDER`] if it is
+    /// there, otherwise the texture is imported and the result is put in the cache.
+    pub(crate) async fn load_from_file<P: AsRef<Path>>(
         path: P,
-        compression: CompressionOptions,
-        gen_mip_maps: bool,
+        import_options: &TextureImportOptions,
     ) -> Result<Self, TextureError> {
         let data = io::load_file(path.as_ref()).await?;
-        let mut texture = Self::load_from_memory(&data, compression, gen_mip_maps)?;
+        let gen_mip_maps = import_options.minification_filter.is_using_mip_mapping();
+
+        let mut texture = if is_dds(&data) {
+            // DDS textures are loaded as is, there is nothing to cache.
+            Self::import(&data, import_options, gen_mip_maps)?
+        } else {
+            let cache_path = cached_texture_path(&data, import_options, gen_mip_maps);
+            match Self::load_cached(&cache_path).await {
+                Some(texture) => texture,
+                None => {
+                    let mut texture = Self::import(&data, import_options, gen_mip_maps)?;
+                    texture.save_cached(&cache_path);
+                    texture
+                }
+            }
+        };
+
         texture.path = path.as_ref().to_path_buf();
         Ok(texture)
     }
 
+    /// Imports a texture from the given file and puts its compressed representation in the
+    /// [`TEXTURE_CACHE_FOLDER`] if it is not there yet. Returns `true` if the texture was imported.
+    pub async fn update_cache<P: AsRef<Path>>(
+        path: P,
+        import_options: &TextureImportOptions,
+    ) -> Result<bool, TextureError> {
+        let data = io::load_file(path.as_ref()).await?;
+        if is_dds(&data) {
+            return Ok(false);
+        }
+
+        let gen_mip_maps = import_options.minification_filter.is_using_mip_mapping();
+        let cache_path = cached_texture_path(&data, import_options, gen_mip_maps);
+        if cache_path.exists() {
+            return Ok(false);
+        }
+
+        Self::import(&data, import_options, gen_mip_maps)?.save_cached(&cache_path);
+        Ok(true)
+    }
+
+    /// Returns path of compressed representation of a texture in the [`TEXTURE_CACHE_FOLDER`], the
+    /// texture is imported if it is not in the cache yet. Returns `None` for DDS textures, they're
+    /// loaded as is. It is useful to ship compressed textures with a game, so the game won't
+    /// spend time on compression.
+    pub async fn cached_data_path<P: AsRef<Path>>(
+        path: P,
+        import_options: &TextureImportOptions,
+    ) -> Result<Option<PathBuf>, TextureError> {
+        let data = io::load_file(path.as_ref()).await?;
+        if is_dds(&data) {
+            return Ok(None);
+        }
+
+        let gen_mip_maps = import_options.minification_filter.is_using_mip_mapping();
+        let cache_path = cached_texture_path(&data, import_options, gen_mip_maps);
+        if !cache_path.exists() {
+            Self::import(&data, import_options, gen_mip_maps)?.save_cached(&cache_path);
+        }
+
+        Ok(Some(cache_path))
+    }
+
+    async fn load_cached(cache_path: &Path) -> Option<Self> {
+        let mut visitor = Visitor::load_binary(cache_path).await.ok()?;
+        let mut texture = Self::default();
+        if let Err(e) = texture.visit("Texture", &mut visitor) {
+            Log::warn(format!(
+                "Cached texture data {} is corrupted and will be ignored. Reason: {:?}",
+                cache_path.display(),
+                e
+            ));
+            return None;
+        }
+        texture.serialize_content = false;
+        texture.data_hash = data_hash(&texture.bytes);
+        Some(texture)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_cached(&mut self, cache_path: &Path) {
+        // Content must be serialized to be in the cache, but it must not be serialized in scenes.
+        self.serialize_content = true;
+        let mut visitor = Visitor::new();
+        let result = self
+            .visit("Texture", &mut visitor)
+            .and_then(|_| {
+                if let Some(folder) = cache_path.parent() {
+                    std::fs::create_dir_all(folder)?;
+                }
+                Ok(())
+            })
+            .and_then(|_| visitor.save_binary(cache_path));
+        self.serialize_content = false;
+
+        if let Err(e) = result {
+            Log::warn(format!(
+                "Unable to save texture data to the cache {}. Reason: {:?}",
+                cache_path.display(),
+                e
+            ));
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn save_cached(&mut self, _cache_path: &Path) {
+        // There is no file system to write the cache to.
+    }
+
     /// Creates new texture instance from given parameters.
     ///
     /// # Limitations
@@ -1173,6 +1843,115 @@ impl TextureData {
         }
     }
 
+    /// Creates new texture array (see [`TextureKind::Array`]) from a set of 2D textures. Every
+    /// layer of the array will contain respective texture from the given slice. First loaded
+    /// texture defines size, pixel kind, mip count and sampler settings of the array, every layer
+    /// that is missing, not loaded or does not match with the first one is filled with the
+    /// fallback color (a warning is written to the log in case of mismatch).
+    ///
+    /// # Important notes
+    ///
+    /// All textures in the slice must be loaded, otherwise they will be replaced with the
+    /// fallback color. Resulting data is procedural, it means that it will be serialized with
+    /// its content.
+    pub fn new_array(layers: &[Option<Texture>], fallback: Color) -> Self {
+        let layer_count = layers.len().max(1) as u32;
+        let mut array = Self {
+            kind: TextureKind::Array {
+                width: 1,
+                height: 1,
+                layers: layer_count,
+            },
+            minification_filter: TextureMinificationFilter::Linear,
+            serialize_content: true,
+            ..Default::default()
+        };
+        let (mut width, mut height) = (1, 1);
+
+        // First loaded texture defines parameters of the whole array.
+        for texture in layers.iter().flatten() {
+            if let TextureState::Ok(data) = &*texture.state() {
+                if let TextureKind::Rectangle {
+                    width: layer_width,
+                    height: layer_height,
+                } = data.kind
+                {
+                    width = layer_width;
+                    height = layer_height;
+                    array.kind = TextureKind::Array {
+                        width,
+                        height,
+                        layers: layer_count,
+                    };
+                    array.pixel_kind = data.pixel_kind;
+                    array.mip_count = data.mip_count.max(1);
+                    array.minification_filter = data.minification_filter;
+                    array.magnification_filter = data.magnification_filter;
+                    array.s_wrap_mode = data.s_wrap_mode;
+                    array.t_wrap_mode = data.t_wrap_mode;
+                    array.anisotropy = data.anisotropy;
+                    break;
+                }
+            }
+        }
+
+        let (pixel_kind, mip_count) = (array.pixel_kind, array.mip_count);
+        let mip_size = |mip: u32| {
+            bytes_in_first_mip(
+                TextureKind::Rectangle {
+                    width: width.checked_shr(mip).unwrap_or_default(),
+                    height: height.checked_shr(mip).unwrap_or_default(),
+                },
+                pixel_kind,
+            ) as usize
+        };
+        let layer_size = (0..mip_count).map(mip_size).sum::<usize>();
+
+        // Collect bytes of every layer first, mismatched layers are replaced with `None`.
+        let sources = (0..layer_count as usize)
+            .map(|i| {
+                let texture = layers.get(i)?.as_ref()?;
+                let state = texture.state();
+                if let TextureState::Ok(data) = &*state {
+                    let matches = matches!(data.kind, TextureKind::Rectangle { width: w, height: h } if w == width && h == height)
+                        && data.pixel_kind == pixel_kind
+                        && data.mip_count.max(1) == mip_count
+                        && data.bytes.len() >= layer_size;
+                    if matches {
+                        return Some(data.bytes[..layer_size].to_vec());
+                    }
+                    Log::warn(format!(
+                        "Layer {} ({}) of texture array does not match with the first layer \
+                         (size, pixel format or mip count differs), it will be replaced \
+                         with a fallback color.",
+                        i,
+                        data.path.display()
+                    ));
+                }
+                None
+            })
+            .collect::<Vec<_>>();
+
+        let fill = constant_pixel_block(pixel_kind, fallback);
+        let mut bytes = Vec::with_capacity(layer_size * layer_count as usize);
+        // Every mip level contains all layers one after another.
+        let mut mip_offset = 0;
+        for mip in 0..mip_count {
+            let size = mip_size(mip);
+            for source in sources.iter() {
+                match source {
+                    Some(source) => bytes.extend_from_slice(&source[mip_offset..mip_offset + size]),
+                    None => bytes.extend(fill.iter().cycle().take(size)),
+                }
+            }
+            mip_offset += size;
+        }
+
+        array.data_hash = data_hash(&bytes);
+        array.bytes = bytes.into();
+        array
+    }
+
     /// Sets new minification filter. It is used when texture becomes smaller.
     pub fn set_minification_filter(&mut self, filter: TextureMinificationFilter) {
         self.minification_filter = filter;
@@ -1243,6 +2022,56 @@ impl TextureData {
         &self.bytes[0..bytes_in_first_mip(self.kind, self.pixel_kind) as usize]
     }
 
+    /// Decompresses content of a compressed texture. DXT textures are decompressed to RGBA8, R8RGTC - to
+    /// R8 and RG8RGTC - to RG8. It is used by the renderer if a GPU does not support compressed formats.
+    /// Returns `None` if the texture is not compressed or it is a 1D or 3D texture.
+    pub fn decompress(&self) -> Option<(TexturePixelKind, Vec<u8>)> {
+        if !self.pixel_kind.is_compressed() {
+            return None;
+        }
+
+        let (width, height, images) = match self.kind {
+            TextureKind::Rectangle { width, height } => (width, height, 1),
+            TextureKind::Cube { width, height } => (width, height, 6),
+            TextureKind::Array {
+                width,
+                height,
+                layers,
+            } => (width, height, layers),
+            TextureKind::Line { .. } | TextureKind::Volume { .. } => return None,
+        };
+
+        let block_size = match self.pixel_kind {
+            TexturePixelKind::DXT1RGB | TexturePixelKind::DXT1RGBA | TexturePixelKind::R8RGTC => 8,
+            _ => 16,
+        };
+
+        let mut pixels = Vec::new();
+        let mut offset = 0;
+        for mip in 0..self.mip_count.max(1) {
+            let (w, h) = (width >> mip, height >> mip);
+            let size = (ceil_div_4(w) * ceil_div_4(h) * block_size) as usize;
+            for _ in 0..images {
+                let bytes = self.bytes.get(offset..offset + size)?;
+                pixels.extend(decompress_image(
+                    self.pixel_kind,
+                    bytes,
+                    w as usize,
+                    h as usize,
+                ));
+                offset += size;
+            }
+        }
+
+        let pixel_kind = match self.pixel_kind {
+            TexturePixelKind::R8RGTC => TexturePixelKind::R8,
+            TexturePixelKind::RG8RGTC => TexturePixelKind::RG8,
+            _ => TexturePixelKind::RGBA8,
+        };
+
+        Some((pixel_kind, pixels))
+    }
+
     /// Returns true if the texture is procedural, false - otherwise.
     ///
     /// # Notes
@@ -1279,6 +2108,17 @@ impl TextureData {
 
     /// Tries to save internal buffer into source file.
     pub fn save(&self) -> Result<(), TextureError> {
+        self.save_to_file(&self.path)
+    }
+
+    /// Tries to save internal buffer into the given file, image format is defined by the extension
+    /// of the file. Only rectangle textures with uncompressed pixels could be saved. Render targets
+    /// cannot be saved, because their content exists only on GPU.
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), TextureError> {
+        if self.is_render_target {
+            return Err(TextureError::NoCpuData);
+        }
+
         let color_type = match self.pixel_kind {
             TexturePixelKind::R8 => ColorType::L8,
             TexturePixelKind::RGB8 => ColorType::Rgb8,
@@ -1301,7 +2141,7 @@ impl TextureData {
         };
         if let TextureKind::Rectangle { width, height } = self.kind {
             Ok(image::save_buffer(
-                &self.path,
+                path.as_ref(),
                 self.bytes.as_ref(),
                 width,
                 height,
@@ -1354,7 +2194,14 @@ impl<'a> TextureDataRefMut<'a> {
 
 #[cfg(test)]
 pub mod test {
-    use crate::resource::texture::{Texture, TextureKind, TexturePixelKind};
+    use crate::{
+        core::color::Color,
+        resource::texture::{
+            cached_texture_path, encode, Texture, TextureCompressionFormat, TextureData,
+            TextureImportOptions, TextureKind, TexturePixelKind,
+        },
+    };
+    use image::{DynamicImage, Rgba, RgbaImage};
 
     pub fn create_test_texture() -> Texture {
         Texture::from_bytes(
@@ -1368,4 +2215,108 @@ pub mod test {
         )
         .unwrap()
     }
+
+    #[test]
+    fn test_texture_array() {
+        let layer = |value: u8, width: u32| {
+            Texture::from_bytes(
+                TextureKind::Rectangle { width, height: 2 },
+                TexturePixelKind::R8,
+                vec![value; (width * 2) as usize],
+                false,
+            )
+        };
+
+        let layers = (0..12)
+            .map(|i| match i {
+                // Missing layer.
+                3 => None,
+                // Size mismatch.
+                5 => layer(i, 4),
+                _ => layer(i, 2),
+            })
+            .collect::<Vec<_>>();
+
+        let array = TextureData::new_array(&layers, Color::opaque(200, 0, 0));
+
+        assert!(matches!(
+            array.kind(),
+            TextureKind::Array {
+                width: 2,
+                height: 2,
+                layers: 12
+            }
+        ));
+        assert_eq!(array.data().len(), 12 * 4);
+        for (i, layer) in array.data().chunks(4).enumerate() {
+            let expected = if i == 3 || i == 5 { 200 } else { i as u8 };
+            assert!(layer.iter().all(|v| *v == expected));
+        }
+    }
+
+    #[test]
+    fn test_compressed_texture_decompression() {
+        // Every 4x4 block has its own solid color, so the only loss is quantization of end points.
+        let block_color = |x: u32, y: u32| [(x / 4 * 90) as u8, (y / 4 * 120) as u8, 200, 255];
+        let image = RgbaImage::from_fn(8, 8, |x, y| Rgba(block_color(x, y)));
+
+        for (format, expected_pixel_kind, channels) in [
+            (TextureCompressionFormat::Bc1, TexturePixelKind::DXT1RGBA, 4),
+            (TextureCompressionFormat::Bc3, TexturePixelKind::DXT5RGBA, 4),
+            (TextureCompressionFormat::Bc4, TexturePixelKind::R8RGTC, 1),
+            (TextureCompressionFormat::Bc5, TexturePixelKind::RG8RGTC, 2),
+        ] {
+            let options = TextureImportOptions::default().with_format(format);
+            let (bytes, pixel_kind) =
+                encode(&DynamicImage::ImageRgba8(image.clone()), 8, 8, &options).unwrap();
+            assert_eq!(pixel_kind, expected_pixel_kind);
+
+            let texture = TextureData {
+                kind: TextureKind::Rectangle {
+                    width: 8,
+                    height: 8,
+                },
+                pixel_kind,
+                bytes: bytes.into(),
+                ..Default::default()
+            };
+
+            let (_, pixels) = texture.decompress().unwrap();
+            assert_eq!(pixels.len(), 8 * 8 * channels);
+            for (i, pixel) in pixels.chunks(channels).enumerate() {
+                let expected = block_color(i as u32 % 8, i as u32 / 8);
+                for (actual, expected) in pixel.iter().zip(expected.iter()) {
+                    assert!((*actual as i32 - *expected as i32).abs() <= 8);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_texture_cache_key() {
+        let data = [1, 2, 3, 4];
+        let options = TextureImportOptions::default();
+
+        assert_eq!(
+            cached_texture_path(&data, &options, true),
+            cached_texture_path(&data, &options, true)
+        );
+        assert_ne!(
+            cached_texture_path(&data, &options, true),
+            cached_texture_path(&data, &options, false)
+        );
+        assert_ne!(
+            cached_texture_path(&data, &options, true),
+            cached_texture_path(&[4, 3, 2, 1], &options, true)
+        );
+        assert_ne!(
+            cached_texture_path(&data, &options, true),
+            cached_texture_path(&data, &options.clone().with_normal_map(true), true)
+        );
+        // Sampling options do not affect the content of the texture.
+        assert_eq!(
+            cached_texture_path(&data, &options, true),
+            cached_texture_path(&data, &options.clone().with_anisotropy(2.0), true)
+        );
+    }
 }
@@ -17,13 +17,15 @@ use crate::{
     impl_directly_inheritable_entity_trait,
     resource::model::Model,
     scene::{node::Node, transform::Transform, DirectlyInheritableEntity},
-    script::Script,
+    script::{start::ScriptStartState, Script},
     utils::log::Log,
 };
 use fxhash::FxHashMap;
 use std::{
     cell::Cell,
+    cmp::Ordering,
     ops::{Deref, DerefMut},
+    sync::Arc,
 };
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
@@ -126,6 +128,32 @@ pub struct LodGroup {
     pub levels: Vec<LevelOfDetail>,
 }
 
+impl LodGroup {
+    /// Adds a new level, that starts at the given normalized distance and lasts up to the farthest
+    /// distance. Existing levels are clamped so they end at the beginning of the new level, levels
+    /// that were completely beyond it become empty, so their objects are never shown. If all
+    /// existing levels end before the new one, the farthest of them is extended to avoid a gap.
+    /// It is used to add an impostor (see [`crate::scene::impostor::Impostor`]) that replaces all
+    /// other levels at large distances.
+    pub fn add_last_level(&mut self, begin: f32, objects: Vec<LodControlledObject>) {
+        let begin = begin.clamp(0.0, 1.0);
+        for level in self.levels.iter_mut() {
+            level.set_begin(level.begin().min(begin));
+            level.set_end(level.end().min(begin));
+        }
+        if let Some(farthest) = self
+            .levels
+            .iter_mut()
+            .max_by(|a, b| a.end().partial_cmp(&b.end()).unwrap_or(Ordering::Equal))
+        {
+            if farthest.end() < begin {
+                farthest.set_end(begin);
+            }
+        }
+        self.levels.push(LevelOfDetail::new(begin, 1.0, objects));
+    }
+}
+
 /// Mobility defines a group for scene node which has direct impact on performance
 /// and capabilities of nodes.
 #[derive(
@@ -202,6 +230,75 @@ impl Visit for Mobility {
     }
 }
 
+/// A set of 32 render layers. Every scene node has a render mask that defines layers the node
+/// belongs to and every camera has a culling mask that defines layers the camera can see. A node
+/// is rendered by a camera only if both masks have at least one common layer. By default, masks
+/// have all layers enabled, which means that every camera can see every node.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct LayerMask(pub u32);
+
+impl Default for LayerMask {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl Visit for LayerMask {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        self.0.visit(name, visitor)
+    }
+}
+
+impl LayerMask {
+    /// Amount of layers in a mask.
+    pub const LAYER_COUNT: usize = 32;
+
+    /// A mask with every layer enabled.
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// A mask without any layers.
+    pub const NONE: Self = Self(0);
+
+    /// Creates new mask from raw bits, each bit represents a layer.
+    pub const fn new(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// Creates new mask that contains only the given layer.
+    pub fn from_layer(layer: usize) -> Self {
+        let mut mask = Self::NONE;
+        mask.set_layer(layer, true);
+        mask
+    }
+
+    /// Returns raw bits of the mask.
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns `true` if the mask contains the given layer. Layers outside of `0..32` range are
+    /// never contained in a mask.
+    pub fn contains_layer(self, layer: usize) -> bool {
+        layer < Self::LAYER_COUNT && self.0 & (1 << layer) != 0
+    }
+
+    /// Enables or disables the given layer. Layers outside of `0..32` range are ignored.
+    pub fn set_layer(&mut self, layer: usize, enabled: bool) {
+        if layer < Self::LAYER_COUNT {
+            if enabled {
+                self.0 |= 1 << layer;
+            } else {
+                self.0 &= !(1 << layer);
+            }
+        }
+    }
+
+    /// Returns `true` if the masks have at least one common layer.
+    pub fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
 /// A property value.
 #[derive(Debug, Visit, Inspect, PartialEq, Clone, AsRefStr, EnumString, EnumVariantNames)]
 pub enum PropertyValue {
@@ -288,10 +385,13 @@ pub struct Base {
     #[inspect(getter = "Deref::deref")]
     visibility: TemplateVariable<bool>,
 
+    #[inspect(getter = "Deref::deref")]
+    enabled: TemplateVariable<bool>,
+
     // Maximum amount of Some(time) that node will "live" or None
     // if node has undefined lifetime.
     #[inspect(skip)] // TEMPORARILY HIDDEN. It causes crashes when set from the editor.
-    pub(in crate) lifetime: TemplateVariable<Option<f32>>,
+    pub(crate) lifetime: TemplateVariable<Option<f32>>,
 
     #[inspect(min_value = 0.0, max_value = 1.0, step = 0.1, getter = "Deref::deref")]
     depth_offset: TemplateVariable<f32>,
@@ -316,40 +416,81 @@ pub struct Base {
     #[inspect(getter = "Deref::deref")]
     frustum_culling: TemplateVariable<bool>,
 
+    #[inspect(getter = "Deref::deref")]
+    render_mask: TemplateVariable<LayerMask>,
+
+    // Whether the state of the node should be saved by [`crate::scene::Scene::capture_state`].
+    #[inspect(getter = "Deref::deref")]
+    persistent: TemplateVariable<bool>,
+
+    // Unique id of the node, it is kept when a scene is saved and loaded, so it can be used
+    // to find the same node in different instances of the scene.
+    #[inspect(skip)]
+    pub(crate) instance_id: Uuid,
+
     #[inspect(skip)]
-    pub(in crate) transform_modified: Cell<bool>,
+    pub(crate) transform_modified: Cell<bool>,
 
     // When `true` it means that this node is instance of `resource`.
     // More precisely - this node is root of whole descendant nodes
     // hierarchy which was instantiated from resource.
     #[inspect(read_only)]
-    pub(in crate) is_resource_instance_root: bool,
+    pub(crate) is_resource_instance_root: bool,
 
     #[inspect(skip)]
-    pub(in crate) global_visibility: Cell<bool>,
+    pub(crate) global_visibility: Cell<bool>,
 
+    // Global visibility without the visibility filter of the graph, it is used to calculate
+    // global visibility of descendants. Non-serializable.
     #[inspect(skip)]
-    pub(in crate) parent: Handle<Node>,
+    pub(crate) unfiltered_global_visibility: Cell<bool>,
 
     #[inspect(skip)]
-    pub(in crate) children: Vec<Handle<Node>>,
+    pub(crate) global_enabled: Cell<bool>,
 
+    // Enabled state of the node as it was seen by its script last time. It is used to detect
+    // transitions and call respective script methods. Non-serializable.
     #[inspect(skip)]
-    pub(in crate) global_transform: Cell<Matrix4<f32>>,
+    pub(crate) script_enabled: Option<bool>,
+
+    // Whether the script of the node was started (see [`crate::script::ScriptTrait::on_start`])
+    // or queued to start. Non-serializable.
+    #[inspect(skip)]
+    pub(crate) script_start: ScriptStartState,
+
+    #[inspect(skip)]
+    pub(crate) parent: Handle<Node>,
+
+    #[inspect(skip)]
+    pub(crate) children: Vec<Handle<Node>>,
+
+    #[inspect(skip)]
+    pub(crate) global_transform: Cell<Matrix4<f32>>,
 
     // Bone-specific matrix. Non-serializable.
     #[inspect(skip)]
-    pub(in crate) inv_bind_pose_transform: Matrix4<f32>,
+    pub(crate) inv_bind_pose_transform: Matrix4<f32>,
 
     // A resource from which this node was instantiated from, can work in pair
     // with `original` handle to get corresponding node from resource.
     #[inspect(read_only)]
-    pub(in crate) resource: Option<Model>,
+    pub(crate) resource: Option<Model>,
 
     // Handle to node in scene of model resource from which this node
     // was instantiated from.
     #[inspect(read_only)]
-    pub(in crate) original_handle_in_resource: Handle<Node>,
+    pub(crate) original_handle_in_resource: Handle<Node>,
+
+    // Handles of nodes in `resource` that were deleted from the instance (suppressed). Only
+    // instance roots have it, it prevents deleted nodes from being restored on resolve.
+    #[inspect(skip)]
+    pub(crate) deleted_resource_nodes: Vec<Handle<Node>>,
+
+    // Handles of instance-local nodes that were added to the instance (roots of added sub-graphs).
+    // Only instance roots have it, it is filled when a graph is saved and prevents added nodes
+    // from being treated as nodes of the resource on resolve.
+    #[inspect(skip)]
+    pub(crate) added_nodes: Vec<Handle<Node>>,
 
     /// Current script of the scene node.
     pub script: Option<Script>,
@@ -358,13 +499,16 @@ pub struct Base {
 impl_directly_inheritable_entity_trait!(Base;
     name,
     visibility,
+    enabled,
     lifetime,
     depth_offset,
     lod_group,
     mobility,
     tag,
     properties,
-    frustum_culling
+    frustum_culling,
+    render_mask,
+    persistent
 );
 
 impl Clone for Base {
@@ -375,10 +519,15 @@ impl Clone for Base {
             global_transform: self.global_transform.clone(),
             visibility: self.visibility.clone(),
             global_visibility: self.global_visibility.clone(),
+            unfiltered_global_visibility: self.unfiltered_global_visibility.clone(),
+            enabled: self.enabled.clone(),
+            global_enabled: self.global_enabled.clone(),
             inv_bind_pose_transform: self.inv_bind_pose_transform,
             resource: self.resource.clone(),
             original_handle_in_resource: self.original_handle_in_resource,
             is_resource_instance_root: self.is_resource_instance_root,
+            deleted_resource_nodes: self.deleted_resource_nodes.clone(),
+            added_nodes: self.added_nodes.clone(),
             lifetime: self.lifetime.clone(),
             mobility: self.mobility.clone(),
             tag: self.tag.clone(),
@@ -387,12 +536,17 @@ impl Clone for Base {
             frustum_culling: self.frustum_culling.clone(),
             depth_offset: self.depth_offset.clone(),
             cast_shadows: self.cast_shadows.clone(),
+            render_mask: self.render_mask.clone(),
+            persistent: self.persistent.clone(),
+            instance_id: self.instance_id,
             script: self.script.clone(),
 
             // Rest of data is *not* copied!
             parent: Default::default(),
             children: Default::default(),
             transform_modified: Cell::new(false),
+            script_enabled: None,
+            script_start: Default::default(),
         }
     }
 }
@@ -510,6 +664,27 @@ impl Base {
         *self.visibility
     }
 
+    /// Enables or disables the node. Disabled node (and all its descendants) is excluded from
+    /// rendering regardless of its visibility, its scripts, sounds, particle systems and physical
+    /// entities are not updated. Unlike visibility, this flag stops any activity of the node,
+    /// while keeping its state intact so it can be resumed later on.
+    pub fn set_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.enabled.set(enabled);
+        self
+    }
+
+    /// Returns local enabled state of the node.
+    pub fn is_enabled(&self) -> bool {
+        *self.enabled
+    }
+
+    /// Returns combined enabled state of the node. The node is globally enabled only if it and all
+    /// its ancestors are enabled. The value is calculated once per frame when hierarchical data of
+    /// the graph is updated.
+    pub fn is_globally_enabled(&self) -> bool {
+        self.global_enabled.get()
+    }
+
     /// Returns current **local-space** bounding box. Keep in mind that this value is just
     /// a placeholder, because there is not information to calculate actual bounding box.
     #[inline]
@@ -523,10 +698,8 @@ impl Base {
             .transform(&self.global_transform())
     }
 
-    /// Set new mobility for the node.
-    ///
-    /// TODO. Mobility still has no effect, it was designed to be used in combined
-    /// rendering (dynamic + static lights (lightmaps))
+    /// Set new mobility for the node. Mobility defines whether the node participates in
+    /// lightmap generation, see [`crate::utils::lightmap`] for more info.
     pub fn set_mobility(&mut self, mobility: Mobility) -> &mut Self {
         self.mobility.set(mobility);
         self
@@ -539,7 +712,8 @@ impl Base {
 
     /// Returns combined visibility of an node. This is the final visibility of a node. Global visibility calculated
     /// using visibility of all parent nodes until root one, so if some parent node upper on tree is invisible then
-    /// all its children will be invisible. It defines if object will be rendered. It is *not* the same as real
+    /// all its children will be invisible. Disabled nodes are invisible as well, see [`Self::is_globally_enabled`].
+    /// It defines if object will be rendered. It is *not* the same as real
     /// visibility from point of view of a camera. To check if object is visible from some camera, use
     /// [VisibilityCache](super::visibility::VisibilityCache). However this still can't tell you if object is behind obstacle or not.
     pub fn global_visibility(&self) -> bool {
@@ -556,6 +730,21 @@ impl Base {
         self.original_handle_in_resource
     }
 
+    /// Returns handles of nodes in the resource, that were deleted from this instance of the
+    /// resource (suppressed). Suppressed nodes are not restored on resolve, even if the resource
+    /// was changed. Only instance roots (see [`Self::is_resource_instance_root`]) have suppressed
+    /// nodes, see [`super::graph::Graph::suppress_resource_node`] for more info.
+    pub fn suppressed_resource_nodes(&self) -> &[Handle<Node>] {
+        &self.deleted_resource_nodes
+    }
+
+    /// Returns handles of instance-local nodes, that were added to this instance of the resource,
+    /// as they were recorded when the graph was saved last time. Use
+    /// [`super::graph::Graph::find_added_nodes`] to get actual added nodes.
+    pub fn added_nodes(&self) -> &[Handle<Node>] {
+        &self.added_nodes
+    }
+
     /// Returns position of the node in absolute coordinates.
     pub fn global_position(&self) -> Vector3<f32> {
         self.global_transform.get().position()
@@ -650,9 +839,45 @@ impl Base {
         self.cast_shadows.set(cast_shadows);
     }
 
+    /// Returns unique id of the node. The id is kept when a scene is saved and loaded, but copies
+    /// of the node (for example instances of a model) get new ids.
+    #[inline]
+    pub fn instance_id(&self) -> Uuid {
+        self.instance_id
+    }
+
+    /// Returns `true` if the state of the node is saved by [`crate::scene::Scene::capture_state`].
+    #[inline]
+    pub fn is_persistent(&self) -> bool {
+        *self.persistent
+    }
+
+    /// Sets whether the state of the node (local transform, visibility, enabled state) should be
+    /// saved by [`crate::scene::Scene::capture_state`]. Persistent fields of the script of the
+    /// node are saved regardless of the flag, see [`crate::script::ScriptTrait::persistent_fields`].
+    #[inline]
+    pub fn set_persistent(&mut self, persistent: bool) {
+        self.persistent.set(persistent);
+    }
+
+    /// Sets new render mask of the node. The node will be rendered only by cameras whose culling
+    /// mask has at least one common layer with the render mask. See [`LayerMask`] for more info.
+    #[inline]
+    pub fn set_render_mask(&mut self, render_mask: LayerMask) {
+        self.render_mask.set(render_mask);
+    }
+
+    /// Returns current render mask of the node.
+    #[inline]
+    pub fn render_mask(&self) -> LayerMask {
+        *self.render_mask
+    }
+
     /// Sets new script for the scene node.
     pub fn set_script(&mut self, script: Option<Script>) {
         self.script = script;
+        // New script must be started as well.
+        self.script_start = Default::default();
     }
 
     /// Returns a copy of the current script.
@@ -725,6 +950,16 @@ impl Base {
                 });
             }
         }
+
+        // Added nodes may be left out of a copy, such nodes are just discarded.
+        self.added_nodes.retain_mut_ext(|node| {
+            if let Some(new_node) = old_new_mapping.get(node) {
+                *node = *new_node;
+                true
+            } else {
+                false
+            }
+        });
     }
 }
 
@@ -753,9 +988,11 @@ pub fn serialize_script(script: &Script) -> Result<Vec<u8>, VisitError> {
 /// Deserializes script from the data blob.
 pub fn deserialize_script(
     data: Vec<u8>,
-    serialization_context: &SerializationContext,
+    serialization_context: &Arc<SerializationContext>,
 ) -> Result<Script, VisitError> {
     let mut visitor = Visitor::load_from_memory(data)?;
+    // Script components are created using the context.
+    visitor.environment = Some(serialization_context.clone());
 
     let mut script_type_uuid = Uuid::default();
     script_type_uuid.visit("TypeUuid", &mut visitor)?;
@@ -777,6 +1014,8 @@ pub fn deserialize_script(
 
         script.visit("ScriptData", &mut visitor)?;
 
+        script.clamp_to_property_ranges();
+
         Ok(script)
     }
 }
@@ -818,6 +1057,11 @@ pub fn visit_opt_script(
 
     if let Some(script) = script {
         script.visit("ScriptData", &mut region)?;
+
+        // Ranges of properties might be changed after the data was saved.
+        if region.is_reading() {
+            script.clamp_to_property_ranges();
+        }
     }
 
     Ok(())
@@ -841,10 +1085,18 @@ impl Visit for Base {
         self.mobility.visit("Mobility", &mut region)?;
         self.original_handle_in_resource
             .visit("Original", &mut region)?;
+        let _ = self
+            .deleted_resource_nodes
+            .visit("DeletedResourceNodes", &mut region);
+        let _ = self.added_nodes.visit("AddedNodes", &mut region);
         self.tag.visit("Tag", &mut region)?;
         let _ = self.properties.visit("Properties", &mut region);
         let _ = self.frustum_culling.visit("FrustumCulling", &mut region);
         let _ = self.cast_shadows.visit("CastShadows", &mut region);
+        let _ = self.render_mask.visit("RenderMask", &mut region);
+        let _ = self.enabled.visit("Enabled", &mut region);
+        let _ = self.persistent.visit("Persistent", &mut region);
+        let _ = self.instance_id.visit("InstanceId", &mut region);
 
         // Script visiting may fail for various reasons:
         //
@@ -870,6 +1122,7 @@ impl Visit for Base {
 pub struct BaseBuilder {
     name: String,
     visibility: bool,
+    enabled: bool,
     local_transform: Transform,
     children: Vec<Handle<Node>>,
     lifetime: Option<f32>,
@@ -880,6 +1133,8 @@ pub struct BaseBuilder {
     tag: String,
     frustum_culling: bool,
     cast_shadows: bool,
+    render_mask: LayerMask,
+    persistent: bool,
     script: Option<Script>,
 }
 
@@ -895,6 +1150,7 @@ impl BaseBuilder {
         Self {
             name: Default::default(),
             visibility: true,
+            enabled: true,
             local_transform: Default::default(),
             children: Default::default(),
             lifetime: None,
@@ -905,6 +1161,8 @@ impl BaseBuilder {
             tag: Default::default(),
             frustum_culling: true,
             cast_shadows: true,
+            render_mask: LayerMask::ALL,
+            persistent: false,
             script: None,
         }
     }
@@ -927,6 +1185,12 @@ impl BaseBuilder {
         self
     }
 
+    /// Sets whether the node should be enabled or not.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
     /// Sets desired local transform.
     pub fn with_local_transform(mut self, transform: Transform) -> Self {
         self.local_transform = transform;
@@ -988,6 +1252,19 @@ impl BaseBuilder {
         self
     }
 
+    /// Sets desired render mask.
+    pub fn with_render_mask(mut self, render_mask: LayerMask) -> Self {
+        self.render_mask = render_mask;
+        self
+    }
+
+    /// Sets whether the state of the node should be saved by
+    /// [`crate::scene::Scene::capture_state`].
+    pub fn with_persistent(mut self, persistent: bool) -> Self {
+        self.persistent = persistent;
+        self
+    }
+
     /// Sets desired script of the node.
     pub fn with_script(mut self, script: Script) -> Self {
         self.script = Some(script);
@@ -1003,12 +1280,19 @@ impl BaseBuilder {
             lifetime: self.lifetime.into(),
             visibility: self.visibility.into(),
             global_visibility: Cell::new(true),
+            unfiltered_global_visibility: Cell::new(true),
+            enabled: self.enabled.into(),
+            global_enabled: Cell::new(true),
+            script_enabled: None,
+            script_start: Default::default(),
             parent: Handle::NONE,
             global_transform: Cell::new(Matrix4::identity()),
             inv_bind_pose_transform: self.inv_bind_pose_transform,
             resource: None,
             original_handle_in_resource: Handle::NONE,
             is_resource_instance_root: false,
+            deleted_resource_nodes: Default::default(),
+            added_nodes: Default::default(),
             depth_offset: self.depth_offset.into(),
             lod_group: self.lod_group.into(),
             mobility: self.mobility.into(),
@@ -1017,6 +1301,9 @@ impl BaseBuilder {
             transform_modified: Cell::new(false),
             frustum_culling: self.frustum_culling.into(),
             cast_shadows: self.cast_shadows.into(),
+            render_mask: self.render_mask.into(),
+            persistent: self.persistent.into(),
+            instance_id: Uuid::new_v4(),
             script: self.script,
         }
     }
@@ -1025,9 +1312,10 @@ impl BaseBuilder {
 #[cfg(test)]
 pub mod test {
     use crate::scene::{
-        base::{BaseBuilder, LevelOfDetail, LodGroup, Mobility},
+        base::{BaseBuilder, LayerMask, LevelOfDetail, LodControlledObject, LodGroup, Mobility},
         DirectlyInheritableEntity,
     };
+    use fyrox_core::pool::Handle;
 
     pub fn check_inheritable_properties_equality<T: DirectlyInheritableEntity>(
         entity_a: &T,
@@ -1048,12 +1336,14 @@ pub mod test {
     fn test_base_inheritance() {
         let parent = BaseBuilder::new()
             .with_visibility(false)
+            .with_enabled(false)
             .with_depth_offset(1.0)
             .with_tag("Tag".to_string())
             .with_name("Name")
             .with_lifetime(1.0)
             .with_frustum_culling(false)
             .with_mobility(Mobility::Static)
+            .with_render_mask(LayerMask::from_layer(3))
             .with_lod_group(LodGroup {
                 levels: vec![LevelOfDetail {
                     begin: 0.0,
@@ -1070,4 +1360,32 @@ pub mod test {
         check_inheritable_properties_equality(&child.local_transform, &parent.local_transform);
         check_inheritable_properties_equality(&child, &parent)
     }
+
+    #[test]
+    fn test_lod_group_last_level() {
+        let object = |index| LodControlledObject(Handle::new(index, 1));
+        let mut lod_group = LodGroup {
+            levels: vec![
+                LevelOfDetail::new(0.0, 0.2, vec![object(1)]),
+                LevelOfDetail::new(0.2, 0.5, vec![object(2)]),
+                LevelOfDetail::new(0.5, 1.0, vec![object(3)]),
+            ],
+        };
+
+        lod_group.add_last_level(0.4, vec![object(4)]);
+
+        let ranges = lod_group
+            .levels
+            .iter()
+            .map(|level| (level.begin(), level.end()))
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, vec![(0.0, 0.2), (0.2, 0.4), (0.4, 0.4), (0.4, 1.0)]);
+        assert_eq!(lod_group.levels[3].objects, vec![object(4)]);
+
+        // Replace the last level with a farther one.
+        lod_group.levels.pop();
+        lod_group.add_last_level(0.6, vec![object(5)]);
+        assert_eq!(lod_group.levels[2].end(), 0.6);
+        assert_eq!(lod_group.levels[3].begin(), 0.6);
+    }
 }
@@ -28,7 +28,7 @@ use crate::{
     impl_directly_inheritable_entity_trait,
     resource::texture::{Texture, TextureError, TextureKind, TexturePixelKind, TextureWrapMode},
     scene::{
-        base::{Base, BaseBuilder},
+        base::{Base, BaseBuilder, LayerMask},
         graph::Graph,
         node::{Node, NodeTrait, TypeUuidProvider, UpdateContext},
         visibility::VisibilityCache,
@@ -279,6 +279,10 @@ pub struct Camera {
     #[inspect(getter = "Deref::deref")]
     color_grading_enabled: TemplateVariable<bool>,
 
+    #[inspect(getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    culling_mask: TemplateVariable<LayerMask>,
+
     #[visit(skip)]
     #[inspect(skip)]
     view_matrix: Matrix4<f32>,
@@ -301,7 +305,8 @@ impl_directly_inheritable_entity_trait!(Camera;
     environment,
     exposure,
     color_grading_lut,
-    color_grading_enabled
+    color_grading_enabled,
+    culling_mask
 );
 
 impl Deref for Camera {
@@ -488,7 +493,8 @@ impl Camera {
         (*self.environment).clone()
     }
 
-    /// Creates picking ray from given screen coordinates.
+    /// Creates picking ray from given screen coordinates. Coordinates must be relative to the
+    /// top-left corner of the viewport of the camera, `screen_size` is the size of the whole frame.
     pub fn make_ray(&self, screen_coord: Vector2<f32>, screen_size: Vector2<f32>) -> Ray {
         let viewport = self.viewport_pixels(screen_size);
         let nx = screen_coord.x / (viewport.w() as f32) * 2.0 - 1.0;
@@ -506,7 +512,8 @@ impl Camera {
         Ray::from_two_points(begin, end)
     }
 
-    /// Projects given world space point on screen plane.
+    /// Projects given world space point on screen plane. Returned coordinates are relative to the
+    /// top-left corner of the whole frame of the given size, not to the viewport of the camera.
     pub fn project(
         &self,
         world_pos: Vector3<f32>,
@@ -519,8 +526,7 @@ impl Camera {
             let k = (1.0 / proj.w) * 0.5;
             Some(Vector2::new(
                 viewport.x() as f32 + viewport.w() as f32 * (proj.x * k + 0.5),
-                viewport.h() as f32
-                    - (viewport.y() as f32 + viewport.h() as f32 * (proj.y * k + 0.5)),
+                screen_size.y - (viewport.y() as f32 + viewport.h() as f32 * (proj.y * k + 0.5)),
             ))
         } else {
             None
@@ -561,6 +567,24 @@ impl Camera {
     pub fn exposure(&self) -> Exposure {
         *self.exposure
     }
+
+    /// Sets new culling mask. The camera renders only nodes whose render mask has at least one
+    /// common layer with the culling mask. See [`LayerMask`] for more info.
+    pub fn set_culling_mask(&mut self, culling_mask: LayerMask) {
+        self.culling_mask.set(culling_mask);
+    }
+
+    /// Returns current culling mask.
+    pub fn culling_mask(&self) -> LayerMask {
+        *self.culling_mask
+    }
+
+    /// Returns `true` if a node with the given render mask can be seen by the camera. It does not
+    /// check if the node is inside of the frustum of the camera, use [`Camera::visibility_cache`]
+    /// for that.
+    pub fn can_see_layers(&self, render_mask: LayerMask) -> bool {
+        self.culling_mask.intersects(render_mask)
+    }
 }
 
 impl NodeTrait for Camera {
@@ -627,6 +651,7 @@ impl NodeTrait for Camera {
             self.projection().z_near(),
             self.projection().z_far(),
             Some(&[&Frustum::from(self.view_projection_matrix()).unwrap_or_default()]),
+            *self.culling_mask,
         );
 
         self.base.update_lifetime(context.dt)
@@ -814,6 +839,7 @@ pub struct CameraBuilder {
     color_grading_lut: Option<ColorGradingLut>,
     color_grading_enabled: bool,
     projection: Projection,
+    culling_mask: LayerMask,
 }
 
 impl CameraBuilder {
@@ -832,6 +858,7 @@ impl CameraBuilder {
             color_grading_lut: None,
             color_grading_enabled: false,
             projection: Projection::default(),
+            culling_mask: LayerMask::ALL,
         }
     }
 
@@ -901,6 +928,12 @@ impl CameraBuilder {
         self
     }
 
+    /// Sets desired culling mask.
+    pub fn with_culling_mask(mut self, culling_mask: LayerMask) -> Self {
+        self.culling_mask = culling_mask;
+        self
+    }
+
     /// Creates new instance of camera.
     pub fn build_camera(self) -> Camera {
         Camera {
@@ -918,6 +951,7 @@ impl CameraBuilder {
             exposure: self.exposure.into(),
             color_grading_lut: self.color_grading_lut.into(),
             color_grading_enabled: self.color_grading_enabled.into(),
+            culling_mask: self.culling_mask.into(),
         }
     }
 
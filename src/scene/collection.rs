@@ -0,0 +1,249 @@
+//! Named collections of nodes, see [`NodeCollection`] docs for more info.
+
+use crate::{
+    core::{pool::Handle, visitor::prelude::*},
+    scene::{graph::Graph, node::Node},
+};
+use fxhash::FxHashMap;
+
+/// Named set of nodes, that is independent from the hierarchy of a graph. Collections allow
+/// grouping of nodes scattered across the graph (for example every gameplay trigger of a level)
+/// without re-parenting them under a common node. The engine itself does not use collections,
+/// they're used by the editor to select, hide or lock multiple nodes at once.
+///
+/// Collections store handles of nodes, so a collection may contain handles of deleted nodes.
+/// Use [`NodeCollection::valid_nodes`] to iterate over existing members only.
+#[derive(Default, Clone, Debug, PartialEq, Visit)]
+pub struct NodeCollection {
+    /// Name of the collection, it is unique within a [`NodeCollectionContainer`].
+    pub name: String,
+    /// Members of the collection.
+    pub nodes: Vec<Handle<Node>>,
+    /// Members of locked collections cannot be picked in the editor's scene viewer.
+    pub locked: bool,
+}
+
+impl NodeCollection {
+    /// Creates new unlocked collection with the given members.
+    pub fn new<S: AsRef<str>>(name: S, nodes: Vec<Handle<Node>>) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            nodes,
+            locked: false,
+        }
+    }
+
+    /// Checks whether the given node is a member of the collection or not.
+    pub fn contains(&self, node: Handle<Node>) -> bool {
+        self.nodes.contains(&node)
+    }
+
+    /// Returns an iterator over members of the collection that exist in the given graph.
+    pub fn valid_nodes<'a>(&'a self, graph: &'a Graph) -> impl Iterator<Item = Handle<Node>> + 'a {
+        self.nodes
+            .iter()
+            .cloned()
+            .filter(move |&node| graph.is_valid_handle(node))
+    }
+}
+
+/// A container for node collections of a scene.
+#[derive(Default, Clone, Debug, PartialEq, Visit)]
+pub struct NodeCollectionContainer {
+    collections: Vec<NodeCollection>,
+}
+
+impl NodeCollectionContainer {
+    /// Adds new collection to the end of the container and returns its index. The name of the
+    /// collection is made unique (see [`Self::unique_name`]).
+    pub fn add(&mut self, mut collection: NodeCollection) -> usize {
+        collection.name = self.unique_name(&collection.name);
+        self.collections.push(collection);
+        self.collections.len() - 1
+    }
+
+    /// Inserts a collection at the given position, it is used to restore removed collections at
+    /// their previous position. The name of the collection is made unique.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, mut collection: NodeCollection) {
+        collection.name = self.unique_name(&collection.name);
+        self.collections.insert(index, collection);
+    }
+
+    /// Removes a collection at the given position and returns it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn remove(&mut self, index: usize) -> NodeCollection {
+        self.collections.remove(index)
+    }
+
+    /// Tries to borrow a collection by its index.
+    pub fn get(&self, index: usize) -> Option<&NodeCollection> {
+        self.collections.get(index)
+    }
+
+    /// Tries to borrow a collection by its index.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut NodeCollection> {
+        self.collections.get_mut(index)
+    }
+
+    /// Searches for a collection with the given name and returns its index.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.collections.iter().position(|c| c.name == name)
+    }
+
+    /// Creates new immutable iterator.
+    pub fn iter(&self) -> impl Iterator<Item = &NodeCollection> {
+        self.collections.iter()
+    }
+
+    /// Returns amount of collections in the container.
+    pub fn len(&self) -> usize {
+        self.collections.len()
+    }
+
+    /// Checks whether the container is empty or not.
+    pub fn is_empty(&self) -> bool {
+        self.collections.is_empty()
+    }
+
+    /// Checks whether the given node is a member of any locked collection.
+    pub fn is_locked(&self, node: Handle<Node>) -> bool {
+        self.collections
+            .iter()
+            .any(|c| c.locked && c.contains(node))
+    }
+
+    /// Returns a name, that is not used by any collection of the container. The given name is
+    /// returned as is if it is not used, otherwise a numeric suffix is added to it.
+    pub fn unique_name(&self, name: &str) -> String {
+        if self.find(name).is_none() {
+            return name.to_owned();
+        }
+
+        let mut index = 1;
+        loop {
+            let candidate = format!("{} {}", name, index);
+            if self.find(&candidate).is_none() {
+                return candidate;
+            }
+            index += 1;
+        }
+    }
+
+    /// Removes handles of nodes that do not exist in the given graph from every collection.
+    /// Returns amount of removed handles.
+    pub fn prune(&mut self, graph: &Graph) -> usize {
+        let mut count = 0;
+        for collection in self.collections.iter_mut() {
+            let len = collection.nodes.len();
+            collection.nodes.retain(|&node| graph.is_valid_handle(node));
+            count += len - collection.nodes.len();
+        }
+        count
+    }
+
+    /// Replaces every handle using the given old-to-new map, handles that are not in the map are
+    /// removed. It is used when a scene is copied.
+    pub fn remap(&mut self, old_new_map: &FxHashMap<Handle<Node>, Handle<Node>>) {
+        for collection in self.collections.iter_mut() {
+            collection.nodes = collection
+                .nodes
+                .iter()
+                .filter_map(|node| old_new_map.get(node).cloned())
+                .collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{pool::Handle, visitor::prelude::*},
+        scene::{
+            base::BaseBuilder,
+            collection::{NodeCollection, NodeCollectionContainer},
+            graph::Graph,
+            pivot::PivotBuilder,
+        },
+    };
+    use fxhash::FxHashMap;
+
+    #[test]
+    fn test_unique_names() {
+        let mut container = NodeCollectionContainer::default();
+        assert_eq!(container.add(NodeCollection::new("Triggers", vec![])), 0);
+        assert_eq!(container.add(NodeCollection::new("Triggers", vec![])), 1);
+        assert_eq!(container.get(1).unwrap().name, "Triggers 1");
+        assert_eq!(container.find("Triggers 1"), Some(1));
+    }
+
+    #[test]
+    fn test_prune_and_remap() {
+        let mut graph = Graph::new();
+        let a = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let b = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let c = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        let mut container = NodeCollectionContainer::default();
+        container.add(NodeCollection::new("Nodes", vec![a, b, c]));
+
+        graph.remove_node(b);
+        assert_eq!(
+            container
+                .get(0)
+                .unwrap()
+                .valid_nodes(&graph)
+                .collect::<Vec<_>>(),
+            vec![a, c]
+        );
+        assert_eq!(container.prune(&graph), 1);
+        assert_eq!(container.get(0).unwrap().nodes, vec![a, c]);
+
+        let new_a = Handle::new(10, 1);
+        let mut map = FxHashMap::default();
+        map.insert(a, new_a);
+        container.remap(&map);
+        assert_eq!(container.get(0).unwrap().nodes, vec![new_a]);
+    }
+
+    #[test]
+    fn test_locked() {
+        let a = Handle::new(1, 1);
+        let b = Handle::new(2, 1);
+
+        let mut container = NodeCollectionContainer::default();
+        container.add(NodeCollection {
+            locked: true,
+            ..NodeCollection::new("Locked", vec![a])
+        });
+        container.add(NodeCollection::new("Unlocked", vec![b]));
+
+        assert!(container.is_locked(a));
+        assert!(!container.is_locked(b));
+    }
+
+    #[test]
+    fn test_serialization() {
+        let mut container = NodeCollectionContainer::default();
+        container.add(NodeCollection {
+            locked: true,
+            ..NodeCollection::new("Triggers", vec![Handle::new(1, 1), Handle::new(3, 2)])
+        });
+
+        let mut visitor = Visitor::new();
+        container.visit("Collections", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        let mut loaded = NodeCollectionContainer::default();
+        loaded.visit("Collections", &mut visitor).unwrap();
+
+        assert_eq!(container, loaded);
+    }
+}
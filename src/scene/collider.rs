@@ -328,6 +328,26 @@ impl ColliderShape {
         Self::Capsule(CapsuleShape { begin, end, radius })
     }
 
+    /// Initializes a capsule shape that encloses the given bounding box. The capsule is oriented
+    /// along the longest side of the box, its radius is the largest of two other half-extents.
+    pub fn capsule_from_bounds(bounds: &AxisAlignedBoundingBox) -> Self {
+        // Capsules must not be too thin, otherwise the physics engine will have troubles with them.
+        const MIN_HALF_EXTENT: f32 = 0.001;
+
+        let center = bounds.center();
+        let half_extents = bounds.half_extents().map(|v| v.max(MIN_HALF_EXTENT));
+
+        let axis = half_extents.imax();
+        let radius = (0..3)
+            .filter(|&i| i != axis)
+            .map(|i| half_extents[i])
+            .fold(MIN_HALF_EXTENT, f32::max);
+        let mut half_height = Vector3::default();
+        half_height[axis] = (half_extents[axis] - radius).max(0.0);
+
+        Self::capsule(center - half_height, center + half_height, radius)
+    }
+
     /// Initializes a new collider builder with a capsule shape aligned with the `x` axis.
     pub fn capsule_x(half_height: f32, radius: f32) -> Self {
         let p = Vector3::x() * half_height;
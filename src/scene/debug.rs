@@ -17,7 +17,7 @@ pub struct Line {
     pub begin: Vector3<f32>,
     /// End of the line.
     pub end: Vector3<f32>,
-    /// Color of the line.
+    /// Color of the line, lines with transparent colors are blended with the frame.
     pub color: Color,
 }
 
@@ -985,3 +985,266 @@ impl SceneDrawingContext {
         self.lines.clear()
     }
 }
+
+/// Text in world space that is drawn on top of a scene, see [`DebugDrawBuffer::draw_text_3d`].
+#[derive(Clone, Debug)]
+pub struct DebugText {
+    /// World-space position of the text.
+    pub position: Vector3<f32>,
+    /// The text itself.
+    pub text: String,
+    /// Color of the text.
+    pub color: Color,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Lifetime {
+    time_left: f32,
+    // Items that were added since last update must be drawn at least once.
+    fresh: bool,
+}
+
+impl Lifetime {
+    #[cfg_attr(not(feature = "debug_draw"), allow(dead_code))]
+    fn new(duration: f32) -> Self {
+        Self {
+            time_left: duration,
+            fresh: true,
+        }
+    }
+
+    // Returns `false` if an item has expired.
+    fn advance(&mut self, dt: f32) -> bool {
+        if !self.fresh && self.time_left <= 0.0 {
+            return false;
+        }
+        self.fresh = false;
+        self.time_left -= dt;
+        true
+    }
+}
+
+/// Debug drawing buffer with automatic expiration of drawn items. Unlike [`SceneDrawingContext`]
+/// it does not need to be cleared manually, every item has a duration (in seconds) after which it
+/// is removed from the buffer. Zero duration means that an item will be drawn only once, so it
+/// must be drawn again on next update (this is the most common case for scripts that draw some
+/// info every frame).
+///
+/// Every scene has its own buffer (see [`crate::scene::Scene::debug_draw`]), which is also
+/// accessible from scripts via [`crate::script::ScriptContext::debug_draw`]. Lines are drawn
+/// after opaque geometry of the scene, text is drawn on top of everything in the scene.
+///
+/// # Shipping builds
+///
+/// Every drawing method is no-op if `debug_draw` feature of the engine (enabled by default) is
+/// disabled, so there is no need to remove debug drawing code from your game for release builds.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::{algebra::Vector3, color::Color},
+///     scene::debug::DebugDrawBuffer,
+/// };
+///
+/// fn draw_sight(buffer: &mut DebugDrawBuffer, position: Vector3<f32>, target: Vector3<f32>) {
+///     // Both items will be visible in this frame only.
+///     buffer.draw_sphere(position, 10.0, Color::GREEN, 0.0);
+///     buffer.draw_arrow(position, target, Color::RED, 0.0);
+///     // The text will be visible for two seconds.
+///     buffer.draw_text_3d(target, "Target spotted!", Color::WHITE, 2.0);
+/// }
+/// ```
+#[derive(Default, Clone, Debug)]
+pub struct DebugDrawBuffer {
+    lines: Vec<Line>,
+    line_lifetimes: Vec<Lifetime>,
+    texts: Vec<DebugText>,
+    text_lifetimes: Vec<Lifetime>,
+}
+
+#[cfg_attr(not(feature = "debug_draw"), allow(unused_variables))]
+impl DebugDrawBuffer {
+    fn add_lines<I: IntoIterator<Item = Line>>(&mut self, lines: I, duration: f32) {
+        #[cfg(feature = "debug_draw")]
+        for line in lines {
+            self.lines.push(line);
+            self.line_lifetimes.push(Lifetime::new(duration));
+        }
+    }
+
+    /// Draws a line between two points.
+    pub fn draw_line(
+        &mut self,
+        begin: Vector3<f32>,
+        end: Vector3<f32>,
+        color: Color,
+        duration: f32,
+    ) {
+        self.add_lines([Line { begin, end, color }], duration);
+    }
+
+    /// Draws a wireframe sphere.
+    pub fn draw_sphere(&mut self, center: Vector3<f32>, radius: f32, color: Color, duration: f32) {
+        let mut ctx = SceneDrawingContext::default();
+        ctx.draw_sphere(center, 10, 10, radius, color);
+        self.add_lines(ctx.lines, duration);
+    }
+
+    /// Draws a wireframe axis-aligned box.
+    pub fn draw_wire_box(&mut self, aabb: &AxisAlignedBoundingBox, color: Color, duration: f32) {
+        let mut ctx = SceneDrawingContext::default();
+        ctx.draw_aabb(aabb, color);
+        self.add_lines(ctx.lines, duration);
+    }
+
+    /// Draws an arrow that points from `begin` to `end`.
+    pub fn draw_arrow(
+        &mut self,
+        begin: Vector3<f32>,
+        end: Vector3<f32>,
+        color: Color,
+        duration: f32,
+    ) {
+        let dir = end - begin;
+        let length = dir.norm();
+        if length <= f32::EPSILON {
+            return;
+        }
+        let dir = dir.scale(1.0 / length);
+
+        // Find two vectors that are perpendicular to the direction of the arrow.
+        let up = if dir.y.abs() < 0.99 {
+            Vector3::y()
+        } else {
+            Vector3::x()
+        };
+        let side = dir.cross(&up).normalize();
+        let up = side.cross(&dir);
+
+        let head_length = length * 0.2;
+        let head_base = end - dir.scale(head_length);
+        let head_width = head_length * 0.5;
+
+        self.add_lines(
+            [
+                Line { begin, end, color },
+                Line {
+                    begin: end,
+                    end: head_base + side.scale(head_width),
+                    color,
+                },
+                Line {
+                    begin: end,
+                    end: head_base - side.scale(head_width),
+                    color,
+                },
+                Line {
+                    begin: end,
+                    end: head_base + up.scale(head_width),
+                    color,
+                },
+                Line {
+                    begin: end,
+                    end: head_base - up.scale(head_width),
+                    color,
+                },
+            ],
+            duration,
+        );
+    }
+
+    /// Draws a text at the given world-space position. The text always faces the camera and has
+    /// the same size regardless of the distance to the camera.
+    pub fn draw_text_3d<S: Into<String>>(
+        &mut self,
+        position: Vector3<f32>,
+        text: S,
+        color: Color,
+        duration: f32,
+    ) {
+        #[cfg(feature = "debug_draw")]
+        {
+            self.texts.push(DebugText {
+                position,
+                text: text.into(),
+                color,
+            });
+            self.text_lifetimes.push(Lifetime::new(duration));
+        }
+    }
+
+    /// Returns a slice with every line in the buffer.
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// Returns a slice with every text in the buffer.
+    pub fn texts(&self) -> &[DebugText] {
+        &self.texts
+    }
+
+    /// Removes everything from the buffer immediately.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.line_lifetimes.clear();
+        self.texts.clear();
+        self.text_lifetimes.clear();
+    }
+
+    /// Removes expired items from the buffer. Does nothing if `dt` is zero (when the engine is
+    /// paused), so every item will stay on screen until the engine is resumed.
+    pub(in crate) fn update(&mut self, dt: f32) {
+        if dt <= 0.0 {
+            return;
+        }
+
+        retain_alive(&mut self.lines, &mut self.line_lifetimes, dt);
+        retain_alive(&mut self.texts, &mut self.text_lifetimes, dt);
+    }
+}
+
+fn retain_alive<T>(items: &mut Vec<T>, lifetimes: &mut Vec<Lifetime>, dt: f32) {
+    let mut i = 0;
+    while i < items.len() {
+        if lifetimes[i].advance(dt) {
+            i += 1;
+        } else {
+            items.swap_remove(i);
+            lifetimes.swap_remove(i);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "debug_draw"))]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, color::Color},
+        scene::debug::DebugDrawBuffer,
+    };
+
+    #[test]
+    fn test_debug_draw_buffer_expiration() {
+        let mut buffer = DebugDrawBuffer::default();
+
+        buffer.draw_line(Vector3::default(), Vector3::x(), Color::WHITE, 0.0);
+        buffer.draw_text_3d(Vector3::default(), "Text", Color::WHITE, 1.0);
+
+        // Items must survive the update in which they were drawn.
+        buffer.update(0.5);
+        assert_eq!(buffer.lines().len(), 1);
+        assert_eq!(buffer.texts().len(), 1);
+
+        // Single-frame line expires on next update.
+        buffer.update(0.5);
+        assert!(buffer.lines().is_empty());
+        assert_eq!(buffer.texts().len(), 1);
+
+        // Pause must not affect anything.
+        buffer.update(0.0);
+        assert_eq!(buffer.texts().len(), 1);
+
+        buffer.update(0.5);
+        assert!(buffer.texts().is_empty());
+    }
+}
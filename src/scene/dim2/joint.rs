@@ -157,6 +157,12 @@ pub struct Joint {
     #[visit(skip)]
     #[inspect(skip)]
     pub(crate) native: Cell<ImpulseJointHandle>,
+
+    // Whether the joint was deactivated because its bodies became invalid, it is used to report
+    // re-activation of the joint.
+    #[visit(skip)]
+    #[inspect(skip)]
+    pub(crate) deactivated: Cell<bool>,
 }
 
 impl_directly_inheritable_entity_trait!(Joint;
@@ -173,6 +179,7 @@ impl Default for Joint {
             body1: Default::default(),
             body2: Default::default(),
             native: Cell::new(ImpulseJointHandle::invalid()),
+            deactivated: Cell::new(false),
         }
     }
 }
@@ -199,6 +206,7 @@ impl Clone for Joint {
             body1: self.body1.clone(),
             body2: self.body2.clone(),
             native: Cell::new(ImpulseJointHandle::invalid()),
+            deactivated: Cell::new(false),
         }
     }
 }
@@ -242,6 +250,19 @@ impl Joint {
     pub fn body2(&self) -> Handle<Node> {
         *self.body2
     }
+
+    /// Returns `true` if the joint participates in simulation. A joint is inactive if it is
+    /// disabled, or if any of its bodies is missing or disabled (for example, when a body was
+    /// moved to a disabled node), inactive joints are re-activated automatically when their
+    /// bodies become valid again. The state is updated on every graph update.
+    pub fn is_active(&self) -> bool {
+        self.native.get() != ImpulseJointHandle::invalid()
+    }
+
+    /// Returns `true` if the joint is connected to the given body.
+    pub fn is_connected_to(&self, body: Handle<Node>) -> bool {
+        body.is_some() && (self.body1() == body || self.body2() == body)
+    }
 }
 
 impl NodeTrait for Joint {
@@ -364,6 +385,7 @@ impl JointBuilder {
             body1: self.body1.into(),
             body2: self.body2.into(),
             native: Cell::new(ImpulseJointHandle::invalid()),
+            deactivated: Cell::new(false),
         }
     }
 
@@ -21,7 +21,9 @@ use crate::{
         debug::{Line, SceneDrawingContext},
         dim2::{self, collider::ColliderShape, rigidbody::ApplyAction},
         graph::{
-            physics::{FeatureId, IntegrationParameters, PhysicsPerformanceStatistics},
+            physics::{
+                FeatureId, IntegrationParameters, PhysicsPerformanceStatistics, PhysicsStepMode,
+            },
             NodePool,
         },
         node::Node,
@@ -198,6 +200,32 @@ where
     map: BiDirHashMap<A, Handle<Node>>,
 }
 
+/// Returns native handles of the bodies of the joint, or the reason why the joint cannot connect
+/// them.
+fn joint_native_bodies(
+    nodes: &NodePool,
+    joint: &scene::dim2::joint::Joint,
+) -> Result<(RigidBodyHandle, RigidBodyHandle), &'static str> {
+    let native_body = |handle: Handle<Node>| {
+        match nodes
+            .try_borrow(handle)
+            .and_then(|n| n.cast::<dim2::rigidbody::RigidBody>())
+        {
+            // Native bodies are missing if they're disabled.
+            Some(body) if body.native.get() != RigidBodyHandle::invalid() => Ok(body.native.get()),
+            Some(_) => Err("one of the bodies is disabled"),
+            None => Err("one of the bodies is missing or it is not a rigid body"),
+        }
+    };
+
+    let bodies = (native_body(joint.body1())?, native_body(joint.body2())?);
+    if bodies.0 == bodies.1 {
+        Err("a joint cannot connect a body with itself")
+    } else {
+        Ok(bodies)
+    }
+}
+
 fn convert_joint_params(params: scene::dim2::joint::JointParams) -> GenericJoint {
     match params {
         scene::dim2::joint::JointParams::BallJoint(v) => RevoluteJointBuilder::new()
@@ -274,71 +302,185 @@ fn isometry2_to_mat4(isometry: &Isometry2<f32>) -> Matrix4<f32> {
     .to_homogeneous()
 }
 
+/// Settings of a 2D physics world, they're the same as settings of 3D physics world (see
+/// [`crate::scene::graph::physics::PhysicsSettings`]), except the gravity.
+#[derive(Copy, Clone, Debug, PartialEq, Inspect)]
+pub struct PhysicsSettings {
+    /// Gravity vector of the world (default: `(0.0, -9.81)`).
+    #[inspect(description = "Gravity vector of the world (default: `(0.0, -9.81)`).")]
+    pub gravity: Vector2<f32>,
+
+    /// Parameters of the solver, including amount of solver iterations.
+    pub integration_parameters: IntegrationParameters,
+
+    /// Defines length of a simulation step, see [`PhysicsStepMode`].
+    pub step_mode: PhysicsStepMode,
+
+    /// Enables continuous collision detection for every rigid body of the world, regardless of
+    /// CCD flag of a body (default: `false`).
+    #[inspect(
+        description = "Enables continuous collision detection for every rigid body of \
+    the world, regardless of CCD flag of a body (default: `false`)."
+    )]
+    pub ccd_enabled_by_default: bool,
+
+    /// Rigid bodies with linear velocity below this value for some time fall asleep.
+    #[inspect(
+        min_value = 0.0,
+        description = "Rigid bodies with linear velocity below this value for some time fall asleep."
+    )]
+    pub linear_sleep_threshold: f32,
+
+    /// Rigid bodies with angular velocity below this value for some time fall asleep.
+    #[inspect(
+        min_value = 0.0,
+        description = "Rigid bodies with angular velocity below this value for some time fall asleep."
+    )]
+    pub angular_sleep_threshold: f32,
+}
+
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        Self {
+            gravity: Vector2::new(0.0, -9.81),
+            integration_parameters: Default::default(),
+            step_mode: Default::default(),
+            ccd_enabled_by_default: false,
+            linear_sleep_threshold: RigidBodyActivation::default_linear_threshold(),
+            angular_sleep_threshold: RigidBodyActivation::default_angular_threshold(),
+        }
+    }
+}
+
+impl PhysicsSettings {
+    // Returns rapier's integration parameters for a step.
+    fn step_parameters(
+        &self,
+        dt: f32,
+        time_scale: f32,
+    ) -> rapier2d::dynamics::IntegrationParameters {
+        let parameters = &self.integration_parameters;
+        rapier2d::dynamics::IntegrationParameters {
+            dt: match self.step_mode {
+                PhysicsStepMode::Fixed => parameters.dt * time_scale,
+                PhysicsStepMode::Variable => dt,
+            },
+            min_ccd_dt: parameters.min_ccd_dt * time_scale,
+            erp: parameters.erp,
+            damping_ratio: parameters.damping_ratio,
+            joint_erp: parameters.joint_erp,
+            joint_damping_ratio: parameters.joint_damping_ratio,
+            allowed_linear_error: parameters.allowed_linear_error,
+            max_penetration_correction: parameters.max_penetration_correction,
+            prediction_distance: parameters.prediction_distance,
+            max_velocity_iterations: parameters.max_velocity_iterations as usize,
+            max_velocity_friction_iterations: parameters.max_velocity_friction_iterations as usize,
+            max_stabilization_iterations: parameters.max_stabilization_iterations as usize,
+            interleave_restitution_and_friction_resolution: parameters
+                .interleave_restitution_and_friction_resolution,
+            min_island_size: parameters.min_island_size as usize,
+            max_ccd_substeps: parameters.max_ccd_substeps as usize,
+        }
+    }
+
+    // Settings of native rigid bodies are changed.
+    fn affects_bodies(&self, other: &Self) -> bool {
+        self.ccd_enabled_by_default != other.ccd_enabled_by_default
+            || self.linear_sleep_threshold != other.linear_sleep_threshold
+            || self.angular_sleep_threshold != other.angular_sleep_threshold
+    }
+
+    // Applies sleep thresholds to a native rigid body that is allowed to sleep.
+    fn apply_sleep_thresholds(&self, activation: &mut RigidBodyActivation) {
+        activation.linear_threshold = self.linear_sleep_threshold;
+        activation.angular_threshold = self.angular_sleep_threshold;
+    }
+}
+
 /// Physics world is responsible for physics simulation in the engine. There is a very few public
 /// methods, mostly for ray casting. You should add physical entities using scene graph nodes, such
 /// as RigidBody, Collider, Joint.
-#[derive(Visit, Inspect)]
+#[derive(Inspect)]
 pub struct PhysicsWorld {
     /// A flag that defines whether physics simulation is enabled or not.
     pub enabled: bool,
 
-    /// A set of parameters that define behavior of every rigid body.
-    pub integration_parameters: IntegrationParameters,
+    /// Settings of the world, see [`PhysicsSettings`] for more info.
+    pub settings: PhysicsSettings,
 
-    /// Current gravity vector. Default is (0.0, -9.81)
-    pub gravity: Vector2<f32>,
+    // Settings that were applied to native rigid bodies.
+    #[inspect(skip)]
+    applied_settings: PhysicsSettings,
 
     /// Performance statistics of a single simulation step.
-    #[visit(skip)]
     #[inspect(skip)]
     pub performance_statistics: PhysicsPerformanceStatistics,
 
     // Current physics pipeline.
-    #[visit(skip)]
     #[inspect(skip)]
     pipeline: PhysicsPipeline,
     // Broad phase performs rough intersection checks.
-    #[visit(skip)]
     #[inspect(skip)]
     broad_phase: BroadPhase,
     // Narrow phase is responsible for precise contact generation.
-    #[visit(skip)]
     #[inspect(skip)]
     narrow_phase: NarrowPhase,
     // A continuous collision detection solver.
-    #[visit(skip)]
     #[inspect(skip)]
     ccd_solver: CCDSolver,
     // Structure responsible for maintaining the set of active rigid-bodies, and putting non-moving
     // rigid-bodies to sleep to save computation times.
-    #[visit(skip)]
     #[inspect(skip)]
     islands: IslandManager,
     // A container of rigid bodies.
-    #[visit(skip)]
     #[inspect(skip)]
     bodies: Container<RigidBodySet, RigidBodyHandle>,
     // A container of colliders.
-    #[visit(skip)]
     #[inspect(skip)]
     colliders: Container<ColliderSet, ColliderHandle>,
     // A container of impulse joints.
-    #[visit(skip)]
     #[inspect(skip)]
     joints: Container<ImpulseJointSet, ImpulseJointHandle>,
     // A container of multibody joints.
-    #[visit(skip)]
     #[inspect(skip)]
     multibody_joints: Container<MultibodyJointSet, MultibodyJointHandle>,
     // Event handler collects info about contacts and proximity events.
-    #[visit(skip)]
     #[inspect(skip)]
     event_handler: Box<dyn EventHandler>,
-    #[visit(skip)]
     #[inspect(skip)]
     query: RefCell<QueryPipeline>,
 }
 
+impl Visit for PhysicsWorld {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.enabled.visit("Enabled", &mut region)?;
+        // Settings are stored directly in the region of the world to be able to load scenes that
+        // were saved before the settings were introduced.
+        self.settings
+            .integration_parameters
+            .visit("IntegrationParameters", &mut region)?;
+        self.settings.gravity.visit("Gravity", &mut region)?;
+        // Backward compatibility.
+        let _ = self.settings.step_mode.visit("StepMode", &mut region);
+        let _ = self
+            .settings
+            .ccd_enabled_by_default
+            .visit("CcdEnabledByDefault", &mut region);
+        let _ = self
+            .settings
+            .linear_sleep_threshold
+            .visit("LinearSleepThreshold", &mut region);
+        let _ = self
+            .settings
+            .angular_sleep_threshold
+            .visit("AngularSleepThreshold", &mut region);
+
+        Ok(())
+    }
+}
+
 fn isometry_from_global_transform(transform: &Matrix4<f32>) -> Isometry2<f32> {
     Isometry2 {
         translation: Translation2::new(transform[12], transform[13]),
@@ -354,8 +496,8 @@ impl PhysicsWorld {
         Self {
             enabled: true,
             pipeline: PhysicsPipeline::new(),
-            gravity: Vector2::new(0.0, -9.81),
-            integration_parameters: IntegrationParameters::default(),
+            settings: Default::default(),
+            applied_settings: Default::default(),
             broad_phase: BroadPhase::new(),
             narrow_phase: NarrowPhase::new(),
             ccd_solver: CCDSolver::new(),
@@ -382,39 +524,17 @@ impl PhysicsWorld {
         }
     }
 
-    pub(crate) fn update(&mut self) {
+    /// Performs a single simulation step. Length of the step is defined by the step mode of the
+    /// settings, `dt` is the scaled time that have passed since the previous update. Zero time
+    /// scale means that there is nothing to simulate and the step is skipped.
+    pub(crate) fn update(&mut self, dt: f32, time_scale: f32) {
         let time = instant::Instant::now();
 
-        if self.enabled {
-            let integration_parameters = rapier2d::dynamics::IntegrationParameters {
-                dt: self.integration_parameters.dt,
-                min_ccd_dt: self.integration_parameters.min_ccd_dt,
-                erp: self.integration_parameters.erp,
-                damping_ratio: self.integration_parameters.damping_ratio,
-                joint_erp: self.integration_parameters.joint_erp,
-                joint_damping_ratio: self.integration_parameters.joint_damping_ratio,
-                allowed_linear_error: self.integration_parameters.allowed_linear_error,
-                max_penetration_correction: self.integration_parameters.max_penetration_correction,
-                prediction_distance: self.integration_parameters.prediction_distance,
-                max_velocity_iterations: self.integration_parameters.max_velocity_iterations
-                    as usize,
-                max_velocity_friction_iterations: self
-                    .integration_parameters
-                    .max_velocity_friction_iterations
-                    as usize,
-                max_stabilization_iterations: self
-                    .integration_parameters
-                    .max_stabilization_iterations
-                    as usize,
-                interleave_restitution_and_friction_resolution: self
-                    .integration_parameters
-                    .interleave_restitution_and_friction_resolution,
-                min_island_size: self.integration_parameters.min_island_size as usize,
-                max_ccd_substeps: self.integration_parameters.max_ccd_substeps as usize,
-            };
+        if self.enabled && time_scale > 0.0 {
+            let integration_parameters = self.settings.step_parameters(dt, time_scale);
 
             self.pipeline.step(
-                &self.gravity,
+                &self.settings.gravity,
                 &integration_parameters,
                 &mut self.islands,
                 &mut self.broad_phase,
@@ -432,6 +552,32 @@ impl PhysicsWorld {
         self.performance_statistics.step_time += instant::Instant::now() - time;
     }
 
+    /// Applies settings, that affect rigid bodies (CCD and sleep thresholds), to every native
+    /// rigid body. It does nothing if the settings weren't changed since the last call.
+    pub(crate) fn sync_settings(&mut self, nodes: &NodePool) {
+        if !self.settings.affects_bodies(&self.applied_settings) {
+            return;
+        }
+
+        for (&native, &owner) in self.bodies.map.forward_map() {
+            if let (Some(body), Some(rigid_body_node)) = (
+                self.bodies.set.get_mut(native),
+                nodes
+                    .try_borrow(owner)
+                    .and_then(|n| n.cast::<dim2::rigidbody::RigidBody>()),
+            ) {
+                body.enable_ccd(
+                    rigid_body_node.is_ccd_enabled() || self.settings.ccd_enabled_by_default,
+                );
+                if rigid_body_node.is_can_sleep() {
+                    self.settings.apply_sleep_thresholds(body.activation_mut());
+                }
+            }
+        }
+
+        self.applied_settings = self.settings;
+    }
+
     pub(crate) fn add_body(&mut self, owner: Handle<Node>, body: RigidBody) -> RigidBodyHandle {
         let handle = self.bodies.set.insert(body);
         self.bodies.map.insert(handle, owner);
@@ -439,7 +585,27 @@ impl PhysicsWorld {
     }
 
     pub(crate) fn remove_body(&mut self, handle: RigidBodyHandle) {
-        assert!(self.bodies.map.remove_by_key(&handle).is_some());
+        if self.bodies.map.remove_by_key(&handle).is_none() {
+            return;
+        }
+
+        // Attached colliders and joints are removed together with the body, forget them too.
+        if let Some(body) = self.bodies.set.get(handle) {
+            for collider in body.colliders() {
+                self.colliders.map.remove_by_key(collider);
+            }
+        }
+        let attached_joints = self
+            .joints
+            .set
+            .iter()
+            .filter(|(_, joint)| joint.body1 == handle || joint.body2 == handle)
+            .map(|(joint_handle, _)| joint_handle)
+            .collect::<Vec<_>>();
+        for joint in attached_joints {
+            self.joints.map.remove_by_key(&joint);
+        }
+
         self.bodies.set.remove(
             handle,
             &mut self.islands,
@@ -491,10 +657,11 @@ impl PhysicsWorld {
     }
 
     pub(crate) fn remove_joint(&mut self, handle: ImpulseJointHandle) {
-        assert!(self.joints.map.remove_by_key(&handle).is_some());
-        self.joints
-            .set
-            .remove(handle, &mut self.islands, &mut self.bodies.set, false);
+        if self.joints.map.remove_by_key(&handle).is_some() {
+            self.joints
+                .set
+                .remove(handle, &mut self.islands, &mut self.bodies.set, false);
+        }
     }
 
     /// Draws physics world. Very useful for debugging, it allows you to see where are
@@ -681,6 +848,16 @@ impl PhysicsWorld {
         handle: Handle<Node>,
         rigid_body_node: &scene::dim2::rigidbody::RigidBody,
     ) {
+        // Disabled rigid bodies do not participate in simulation, the native body is removed
+        // and it will be re-created from the actual state of the node when it is enabled again.
+        if !rigid_body_node.is_globally_enabled() {
+            if rigid_body_node.native.get() != RigidBodyHandle::invalid() {
+                self.remove_body(rigid_body_node.native.get());
+                rigid_body_node.native.set(RigidBodyHandle::invalid());
+            }
+            return;
+        }
+
         // Important notes!
         // 1) `get_mut` is **very** expensive because it forces physics engine to recalculate contacts
         //    and a lot of other stuff, this is why we need `anything_changed` flag.
@@ -710,16 +887,14 @@ impl PhysicsWorld {
                     rigid_body_node
                         .ang_damping
                         .try_sync_model(|v| native.set_angular_damping(v));
-                    rigid_body_node
-                        .ccd_enabled
-                        .try_sync_model(|v| native.enable_ccd(v));
+                    let settings = &self.settings;
+                    rigid_body_node.ccd_enabled.try_sync_model(|v| {
+                        native.enable_ccd(v || settings.ccd_enabled_by_default)
+                    });
                     rigid_body_node.can_sleep.try_sync_model(|v| {
                         let mut activation = native.activation_mut();
                         if v {
-                            activation.linear_threshold =
-                                RigidBodyActivation::default_linear_threshold();
-                            activation.angular_threshold =
-                                RigidBodyActivation::default_angular_threshold();
+                            settings.apply_sleep_thresholds(activation);
                         } else {
                             activation.sleeping = false;
                             activation.linear_threshold = -1.0;
@@ -763,7 +938,9 @@ impl PhysicsWorld {
                 .position(isometry_from_global_transform(
                     &rigid_body_node.global_transform(),
                 ))
-                .ccd_enabled(rigid_body_node.is_ccd_enabled())
+                .ccd_enabled(
+                    rigid_body_node.is_ccd_enabled() || self.settings.ccd_enabled_by_default,
+                )
                 .additional_mass(rigid_body_node.mass())
                 .angvel(*rigid_body_node.ang_vel)
                 .linvel(*rigid_body_node.lin_vel)
@@ -787,6 +964,10 @@ impl PhysicsWorld {
                 false,
             );
 
+            if rigid_body_node.is_can_sleep() {
+                self.settings.apply_sleep_thresholds(body.activation_mut());
+            }
+
             rigid_body_node.native.set(self.add_body(handle, body));
 
             Log::writeln(
@@ -805,6 +986,19 @@ impl PhysicsWorld {
         handle: Handle<Node>,
         collider_node: &scene::dim2::collider::Collider,
     ) {
+        // Native collider is removed if the collider is disabled or if it was removed together
+        // with its rigid body, it will be re-created when both are enabled again.
+        let native = collider_node.native.get();
+        if native != ColliderHandle::invalid()
+            && (!collider_node.is_globally_enabled() || self.colliders.set.get(native).is_none())
+        {
+            self.remove_collider(native);
+            collider_node.native.set(ColliderHandle::invalid());
+        }
+        if !collider_node.is_globally_enabled() {
+            return;
+        }
+
         let anything_changed =
             collider_node.transform_modified.get() || collider_node.needs_sync_model();
 
@@ -912,56 +1106,88 @@ impl PhysicsWorld {
         handle: Handle<Node>,
         joint: &scene::dim2::joint::Joint,
     ) {
-        if let Some(native) = self.joints.set.get_mut(joint.native.get()) {
-            joint
-                .params
-                .try_sync_model(|v| native.data = convert_joint_params(v));
-            joint.body1.try_sync_model(|v| {
-                if let Some(rigid_body_node) = nodes
-                    .try_borrow(v)
-                    .and_then(|n| n.cast::<dim2::rigidbody::RigidBody>())
-                {
-                    native.body1 = rigid_body_node.native.get();
-                }
-            });
-            joint.body2.try_sync_model(|v| {
-                if let Some(rigid_body_node) = nodes
-                    .try_borrow(v)
-                    .and_then(|n| n.cast::<dim2::rigidbody::RigidBody>())
-                {
-                    native.body2 = rigid_body_node.native.get();
+        let was_active = joint.native.get() != ImpulseJointHandle::invalid();
+
+        // Native joint is removed if the joint is disabled or if it was removed together with
+        // one of its rigid bodies, it will be re-created when everything is enabled again.
+        let native = joint.native.get();
+        if native != ImpulseJointHandle::invalid()
+            && (!joint.is_globally_enabled() || self.joints.set.get(native).is_none())
+        {
+            self.remove_joint(native);
+            joint.native.set(ImpulseJointHandle::invalid());
+        }
+        if !joint.is_globally_enabled() {
+            return;
+        }
+
+        // Changes of the bodies are detected by comparing native handles below.
+        joint.body1.try_sync_model(|_| ());
+        joint.body2.try_sync_model(|_| ());
+
+        match joint_native_bodies(nodes, joint) {
+            Ok((native_body1, native_body2)) => {
+                if let Some(native) = self.joints.set.get_mut(joint.native.get()) {
+                    if native.body1 == native_body1 && native.body2 == native_body2 {
+                        joint
+                            .params
+                            .try_sync_model(|v| native.data = convert_joint_params(v));
+                    } else {
+                        // One of the bodies was replaced or its native body was re-created, the
+                        // joint must be re-created to be attached to the actual bodies.
+                        self.remove_joint(joint.native.get());
+                        joint.native.set(ImpulseJointHandle::invalid());
+                    }
                 }
-            });
-        } else {
-            let body1_handle = joint.body1();
-            let body2_handle = joint.body2();
-            let params = joint.params().clone();
 
-            // A native joint can be created iff both rigid bodies are correctly assigned.
-            if let (Some(body1), Some(body2)) = (
-                nodes
-                    .try_borrow(body1_handle)
-                    .and_then(|n| n.cast::<dim2::rigidbody::RigidBody>()),
-                nodes
-                    .try_borrow(body2_handle)
-                    .and_then(|n| n.cast::<dim2::rigidbody::RigidBody>()),
-            ) {
-                let native_body1 = body1.native.get();
-                let native_body2 = body2.native.get();
-
-                let native = self.add_joint(
-                    handle,
-                    native_body1,
-                    native_body2,
-                    convert_joint_params(params),
-                );
+                if joint.native.get() == ImpulseJointHandle::invalid() {
+                    let native = self.add_joint(
+                        handle,
+                        native_body1,
+                        native_body2,
+                        convert_joint_params(joint.params().clone()),
+                    );
 
-                joint.native.set(native);
+                    joint.native.set(native);
+
+                    if joint.deactivated.replace(false) {
+                        Log::info(format!(
+                            "Joint {} was re-activated, its bodies are valid again.",
+                            joint.name()
+                        ));
+                    } else {
+                        Log::writeln(
+                            MessageKind::Information,
+                            format!("Native joint was created for node {}", joint.name()),
+                        );
+                    }
+                }
+            }
+            Err(reason) => {
+                if joint.native.get() != ImpulseJointHandle::invalid() {
+                    self.remove_joint(joint.native.get());
+                    joint.native.set(ImpulseJointHandle::invalid());
+                }
 
-                Log::writeln(
-                    MessageKind::Information,
-                    format!("Native joint was created for node {}", joint.name()),
-                );
+                // Report only when an active joint breaks, a joint which was never bound (for
+                // example, its bodies are not assigned yet) is not an error.
+                if was_active {
+                    joint.deactivated.set(true);
+
+                    let name = |handle: Handle<Node>| {
+                        nodes
+                            .try_borrow(handle)
+                            .map_or_else(|| handle.to_string(), |n| n.name().to_owned())
+                    };
+                    Log::warn(format!(
+                        "Joint {} was deactivated, it cannot connect {} and {}: {}. The joint \
+                        will be re-activated when its bodies are valid again.",
+                        joint.name(),
+                        name(joint.body1()),
+                        name(joint.body2()),
+                        reason
+                    ));
+                }
             }
         }
     }
@@ -6,9 +6,10 @@
 use crate::{
     core::variable::{InheritError, TemplateVariable},
     core::{
+        algebra::{Vector2, Vector4},
         color::Color,
         inspect::{Inspect, PropertyInfo},
-        math::aabb::AxisAlignedBoundingBox,
+        math::{aabb::AxisAlignedBoundingBox, Rect},
         pool::Handle,
         uuid::{uuid, Uuid},
         visitor::prelude::*,
@@ -92,7 +93,15 @@ use std::ops::{Deref, DerefMut};
 /// scene lights, but it will be a very simple diffuse lighting without any "physically correct"
 /// lighting. This is perfectly ok for 95% of 2D games, if you want to add custom lighting then
 /// you should use custom shader.
-#[derive(Visit, Inspect, Debug, Clone, Default)]
+///
+/// ## Atlases and nine-slice
+///
+/// A rectangle can show only a part of its texture (see [`Rectangle::set_uv_rect`]), it allows you
+/// to use sprite atlases. In nine-slice mode (see [`Rectangle::set_nine_slice`]) the rectangle is
+/// split into 9 patches, corner patches keep their size when the rectangle is scaled, side patches
+/// are stretched along one axis and the center patch is stretched along both axes. It is useful for
+/// scalable UI-like sprites, such as panels and frames.
+#[derive(Visit, Inspect, Debug, Clone)]
 pub struct Rectangle {
     base: Base,
 
@@ -101,13 +110,152 @@ pub struct Rectangle {
 
     #[inspect(getter = "Deref::deref")]
     color: TemplateVariable<Color>,
+
+    #[inspect(getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    uv_rect: TemplateVariable<Rect<f32>>,
+
+    #[inspect(getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    nine_slice: TemplateVariable<Option<NineSlice>>,
 }
 
 impl_directly_inheritable_entity_trait!(Rectangle;
     texture,
-    color
+    color,
+    uv_rect,
+    nine_slice
 );
 
+impl Default for Rectangle {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            texture: Default::default(),
+            color: Default::default(),
+            uv_rect: TemplateVariable::new(Rect::new(0.0, 0.0, 1.0, 1.0)),
+            nine_slice: Default::default(),
+        }
+    }
+}
+
+/// Margins of nine-slice mode of a rectangle. Margins are defined in texture space and normalized
+/// to the size of UV rectangle of the rectangle, for example `left = 0.25` means that the left
+/// column of patches takes a quarter of the width of the UV rectangle.
+///
+/// Corner patches are not scaled - their size is the same as if the rectangle had unit scale. If
+/// a rectangle is too small to fit its corners, the corners are shrunk proportionally and the
+/// center patch disappears.
+#[derive(Visit, Inspect, Debug, Clone, Copy, PartialEq)]
+pub struct NineSlice {
+    /// Width of the left column of patches.
+    #[inspect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub left: f32,
+    /// Height of the top row of patches.
+    #[inspect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub top: f32,
+    /// Width of the right column of patches.
+    #[inspect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub right: f32,
+    /// Height of the bottom row of patches.
+    #[inspect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub bottom: f32,
+}
+
+impl Default for NineSlice {
+    fn default() -> Self {
+        Self::uniform(0.25)
+    }
+}
+
+impl NineSlice {
+    /// Creates nine-slice margins with the same value for every side.
+    pub fn uniform(margin: f32) -> Self {
+        Self {
+            left: margin,
+            top: margin,
+            right: margin,
+            bottom: margin,
+        }
+    }
+}
+
+/// A grid of 4x4 vertices of a rectangle, that splits it into 9 patches. Columns are ordered
+/// along the U axis of the texture and rows are ordered along the V axis. Positions are defined
+/// in local space of the rectangle, which is a unit quad centered at the origin.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PatchGrid {
+    /// Local X coordinates of the columns of the grid.
+    pub columns: Vector4<f32>,
+    /// Local Y coordinates of the rows of the grid.
+    pub rows: Vector4<f32>,
+    /// U texture coordinates of the columns of the grid.
+    pub tex_coord_columns: Vector4<f32>,
+    /// V texture coordinates of the rows of the grid.
+    pub tex_coord_rows: Vector4<f32>,
+}
+
+// Shrinks a pair of margins proportionally, so their sum does not exceed the limit.
+fn fit_margins(a: f32, b: f32, limit: f32) -> (f32, f32) {
+    let a = a.max(0.0);
+    let b = b.max(0.0);
+    let sum = a + b;
+    if sum > limit && sum > 0.0 {
+        let k = limit.max(0.0) / sum;
+        (a * k, b * k)
+    } else {
+        (a, b)
+    }
+}
+
+fn to_local(margin: f32, size: f32) -> f32 {
+    if size > f32::EPSILON {
+        margin / size
+    } else {
+        0.0
+    }
+}
+
+impl PatchGrid {
+    /// Calculates the grid of a rectangle with given UV rectangle, nine-slice margins and world
+    /// size (scale of the rectangle). Without nine-slice margins, the grid has zero-sized border
+    /// patches, so only the center patch is visible and it covers the entire rectangle.
+    pub fn new(uv_rect: Rect<f32>, nine_slice: Option<NineSlice>, size: Vector2<f32>) -> Self {
+        let margins = nine_slice.unwrap_or_else(|| NineSlice::uniform(0.0));
+
+        let (left, right) = fit_margins(margins.left, margins.right, 1.0);
+        let (top, bottom) = fit_margins(margins.top, margins.bottom, 1.0);
+
+        let u = uv_rect.position.x;
+        let v = uv_rect.position.y;
+        let w = uv_rect.size.x;
+        let h = uv_rect.size.y;
+
+        // Corners have the same size as if the rectangle had unit scale.
+        let (local_left, local_right) = fit_margins(left, right, size.x);
+        let (local_top, local_bottom) = fit_margins(top, bottom, size.y);
+
+        // Left side of the texture is mapped on positive X axis of the rectangle, top side - on
+        // positive Y axis.
+        Self {
+            columns: Vector4::new(
+                0.5,
+                0.5 - to_local(local_left, size.x),
+                -0.5 + to_local(local_right, size.x),
+                -0.5,
+            ),
+            rows: Vector4::new(
+                0.5,
+                0.5 - to_local(local_top, size.y),
+                -0.5 + to_local(local_bottom, size.y),
+                -0.5,
+            ),
+            tex_coord_columns: Vector4::new(u, u + left * w, u + (1.0 - right) * w, u + w),
+            tex_coord_rows: Vector4::new(v, v + top * h, v + (1.0 - bottom) * h, v + h),
+        }
+    }
+}
+
 impl Deref for Rectangle {
     type Target = Base;
 
@@ -153,6 +301,39 @@ impl Rectangle {
     pub fn set_color(&mut self, color: Color) {
         self.color.set(color);
     }
+
+    /// Returns a normalized sub-rectangle of the texture that is shown by the rectangle.
+    pub fn uv_rect(&self) -> Rect<f32> {
+        *self.uv_rect
+    }
+
+    /// Sets a normalized sub-rectangle of the texture that will be shown by the rectangle. It
+    /// could be used to show a sprite from an atlas. Default is `(0, 0, 1, 1)` - the entire
+    /// texture.
+    pub fn set_uv_rect(&mut self, uv_rect: Rect<f32>) -> Rect<f32> {
+        self.uv_rect.set(uv_rect)
+    }
+
+    /// Returns nine-slice margins of the rectangle, `None` means that nine-slice mode is off.
+    pub fn nine_slice(&self) -> Option<NineSlice> {
+        *self.nine_slice
+    }
+
+    /// Sets nine-slice margins of the rectangle, `None` turns nine-slice mode off. See
+    /// [`NineSlice`] docs for more info.
+    pub fn set_nine_slice(&mut self, nine_slice: Option<NineSlice>) -> Option<NineSlice> {
+        self.nine_slice.set(nine_slice)
+    }
+
+    /// Returns the grid of patches of the rectangle with its current global scale. See
+    /// [`PatchGrid`] docs for more info.
+    pub fn patch_grid(&self) -> PatchGrid {
+        PatchGrid::new(
+            self.uv_rect(),
+            self.nine_slice(),
+            Vector2::new(self.side_vector().norm(), self.up_vector().norm()),
+        )
+    }
 }
 
 impl NodeTrait for Rectangle {
@@ -205,6 +386,8 @@ pub struct RectangleBuilder {
     base_builder: BaseBuilder,
     texture: Option<Texture>,
     color: Color,
+    uv_rect: Rect<f32>,
+    nine_slice: Option<NineSlice>,
 }
 
 impl RectangleBuilder {
@@ -214,6 +397,8 @@ impl RectangleBuilder {
             base_builder,
             texture: None,
             color: Color::WHITE,
+            uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            nine_slice: None,
         }
     }
 
@@ -229,12 +414,26 @@ impl RectangleBuilder {
         self
     }
 
+    /// Sets desired normalized sub-rectangle of the texture to show.
+    pub fn with_uv_rect(mut self, uv_rect: Rect<f32>) -> Self {
+        self.uv_rect = uv_rect;
+        self
+    }
+
+    /// Sets desired nine-slice margins of the rectangle.
+    pub fn with_nine_slice(mut self, nine_slice: NineSlice) -> Self {
+        self.nine_slice = Some(nine_slice);
+        self
+    }
+
     /// Creates new [`Rectangle`] instance.
     pub fn build_rectangle(self) -> Rectangle {
         Rectangle {
             base: self.base_builder.build_base(),
             texture: self.texture.into(),
             color: self.color.into(),
+            uv_rect: self.uv_rect.into(),
+            nine_slice: self.nine_slice.into(),
         }
     }
 
@@ -252,20 +451,39 @@ impl RectangleBuilder {
 #[cfg(test)]
 mod test {
     use crate::{
-        core::color::Color,
+        core::{
+            algebra::{Vector2, Vector4},
+            color::Color,
+            math::Rect,
+        },
         resource::texture::test::create_test_texture,
         scene::{
             base::{test::check_inheritable_properties_equality, BaseBuilder},
-            dim2::rectangle::{Rectangle, RectangleBuilder},
+            dim2::rectangle::{NineSlice, PatchGrid, Rectangle, RectangleBuilder},
             node::NodeTrait,
         },
     };
 
+    fn patch_tex_coords(grid: &PatchGrid, column: usize, row: usize) -> Rect<f32> {
+        Rect::new(
+            grid.tex_coord_columns[column],
+            grid.tex_coord_rows[row],
+            grid.tex_coord_columns[column + 1] - grid.tex_coord_columns[column],
+            grid.tex_coord_rows[row + 1] - grid.tex_coord_rows[row],
+        )
+    }
+
+    fn assert_vec_eq(a: Vector4<f32>, b: Vector4<f32>) {
+        assert!((a - b).norm() < 1.0e-5, "{:?} != {:?}", a, b);
+    }
+
     #[test]
     fn test_rectangle_inheritance() {
         let parent = RectangleBuilder::new(BaseBuilder::new())
             .with_color(Color::opaque(1, 2, 3))
             .with_texture(create_test_texture())
+            .with_uv_rect(Rect::new(0.25, 0.5, 0.25, 0.5))
+            .with_nine_slice(NineSlice::uniform(0.1))
             .build_node();
 
         let mut child = RectangleBuilder::new(BaseBuilder::new()).build_rectangle();
@@ -277,4 +495,98 @@ mod test {
         check_inheritable_properties_equality(&child.base, &parent.base);
         check_inheritable_properties_equality(&child, parent);
     }
+
+    #[test]
+    fn test_patch_grid_without_nine_slice() {
+        let grid = PatchGrid::new(
+            Rect::new(0.5, 0.25, 0.5, 0.25),
+            None,
+            Vector2::new(3.0, 2.0),
+        );
+
+        // Only the center patch is visible and it covers the entire rectangle and the UV rect.
+        assert_vec_eq(grid.columns, Vector4::new(0.5, 0.5, -0.5, -0.5));
+        assert_vec_eq(grid.rows, Vector4::new(0.5, 0.5, -0.5, -0.5));
+        assert_eq!(
+            patch_tex_coords(&grid, 1, 1),
+            Rect::new(0.5, 0.25, 0.5, 0.25)
+        );
+        for i in [0, 2] {
+            assert_eq!(patch_tex_coords(&grid, i, 1).size.x, 0.0);
+            assert_eq!(patch_tex_coords(&grid, 1, i).size.y, 0.0);
+        }
+    }
+
+    #[test]
+    fn test_patch_grid_tex_coords() {
+        let grid = PatchGrid::new(
+            Rect::new(0.0, 0.5, 0.5, 0.5),
+            Some(NineSlice {
+                left: 0.25,
+                top: 0.5,
+                right: 0.25,
+                bottom: 0.25,
+            }),
+            Vector2::new(4.0, 4.0),
+        );
+
+        let columns = [(0.0, 0.125), (0.125, 0.25), (0.375, 0.125)];
+        let rows = [(0.5, 0.25), (0.75, 0.125), (0.875, 0.125)];
+        for (row, (v, height)) in rows.iter().enumerate() {
+            for (column, (u, width)) in columns.iter().enumerate() {
+                assert_eq!(
+                    patch_tex_coords(&grid, column, row),
+                    Rect::new(*u, *v, *width, *height),
+                    "patch {} {}",
+                    column,
+                    row
+                );
+            }
+        }
+
+        // Corners keep the size they have with unit scale.
+        assert_vec_eq(
+            grid.columns,
+            Vector4::new(0.5, 0.5 - 0.25 / 4.0, -0.5 + 0.25 / 4.0, -0.5),
+        );
+        assert_vec_eq(
+            grid.rows,
+            Vector4::new(0.5, 0.5 - 0.5 / 4.0, -0.5 + 0.25 / 4.0, -0.5),
+        );
+    }
+
+    #[test]
+    fn test_patch_grid_degenerate_margins() {
+        // Margins are larger than the UV rect, they're shrunk proportionally.
+        let grid = PatchGrid::new(
+            Rect::new(0.0, 0.0, 1.0, 1.0),
+            Some(NineSlice {
+                left: 1.5,
+                top: 0.25,
+                right: 0.5,
+                bottom: -1.0,
+            }),
+            Vector2::new(1.0, 1.0),
+        );
+        assert_vec_eq(grid.tex_coord_columns, Vector4::new(0.0, 0.75, 0.75, 1.0));
+        assert_vec_eq(grid.tex_coord_rows, Vector4::new(0.0, 0.25, 1.0, 1.0));
+        assert_vec_eq(grid.columns, Vector4::new(0.5, -0.25, -0.25, -0.5));
+        assert_vec_eq(grid.rows, Vector4::new(0.5, 0.25, -0.5, -0.5));
+
+        // The rectangle is smaller than its corners, so the center disappears and the corners
+        // are shrunk.
+        let grid = PatchGrid::new(
+            Rect::new(0.0, 0.0, 1.0, 1.0),
+            Some(NineSlice::uniform(0.25)),
+            Vector2::new(0.25, 0.0),
+        );
+        assert_vec_eq(grid.tex_coord_columns, Vector4::new(0.0, 0.25, 0.75, 1.0));
+        assert_vec_eq(grid.columns, Vector4::new(0.5, 0.0, 0.0, -0.5));
+        assert_vec_eq(grid.rows, Vector4::new(0.5, 0.5, -0.5, -0.5));
+        assert!(grid
+            .columns
+            .iter()
+            .chain(grid.rows.iter())
+            .all(|v| v.is_finite()));
+    }
 }
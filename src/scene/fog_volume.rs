@@ -0,0 +1,389 @@
+//! Fog volume is a box or a sphere filled with fog that scatters light of light sources.
+//!
+//! For more info see [`FogVolume`]
+
+use crate::{
+    core::variable::{InheritError, TemplateVariable},
+    core::{
+        algebra::Vector3,
+        color::Color,
+        inspect::{Inspect, PropertyInfo},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    impl_directly_inheritable_entity_trait,
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait, TypeUuidProvider},
+        DirectlyInheritableEntity,
+    },
+};
+use fxhash::FxHashMap;
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Shape of the space filled with fog.
+#[derive(
+    Copy, Clone, PartialEq, Eq, Hash, Debug, Visit, Inspect, AsRefStr, EnumString, EnumVariantNames,
+)]
+#[repr(u32)]
+pub enum FogVolumeShape {
+    /// A box defined by half-extents of the volume.
+    Box = 0,
+
+    /// A sphere defined by radius of the volume.
+    Sphere = 1,
+}
+
+impl Default for FogVolumeShape {
+    fn default() -> Self {
+        Self::Box
+    }
+}
+
+/// Fog volume is a box or a sphere filled with fog of uniform density. Unlike global fog, it
+/// allows you to put a fog only in a specific place of a level - a mist above a swamp, a dust in
+/// a beam of a window light, a smoke in a room, etc.
+///
+/// # Shape
+///
+/// The volume is either a box defined by half-extents or a sphere defined by radius (see
+/// [`FogVolumeShape`]). Both shapes are defined in local coordinates of the node, so they could be
+/// rotated and scaled as any other node - a scaled sphere becomes an ellipsoid.
+///
+/// # Lighting
+///
+/// Fog is lit by ambient lighting of the scene, its color is multiplied by the ambient color.
+/// When [`FogVolume::is_scatter_lights`] is set, light sources that intersect the volume also
+/// scatter in the fog. Only lights with enabled scattering participate, and their scatter factor
+/// tints the in-scattered light (see [`crate::scene::light::BaseLight::set_scatter`]). The
+/// factor is relative to the default one, so lights with default settings contribute their full
+/// color. Shadows are not taken into account.
+///
+/// # Performance
+///
+/// Fog volumes are ray-marched per pixel, the amount of steps is defined by
+/// [`crate::renderer::QualitySettings::fog_volume_samples`]. When it is set to zero, volumes are
+/// rendered as a cheap analytic exponential fog without light scattering. Amount of rendered
+/// volumes is available in [`crate::renderer::Statistics::fog`].
+#[derive(Debug, Visit, Clone, Inspect)]
+pub struct FogVolume {
+    base: Base,
+
+    #[inspect(getter = "Deref::deref")]
+    shape: TemplateVariable<FogVolumeShape>,
+
+    #[inspect(getter = "Deref::deref")]
+    extents: TemplateVariable<Vector3<f32>>,
+
+    #[inspect(min_value = 0.0, step = 0.1, getter = "Deref::deref")]
+    radius: TemplateVariable<f32>,
+
+    #[inspect(min_value = 0.0, step = 0.01, getter = "Deref::deref")]
+    density: TemplateVariable<f32>,
+
+    #[inspect(getter = "Deref::deref")]
+    color: TemplateVariable<Color>,
+
+    #[inspect(getter = "Deref::deref")]
+    scatter_lights: TemplateVariable<bool>,
+}
+
+impl_directly_inheritable_entity_trait!(FogVolume;
+    shape,
+    extents,
+    radius,
+    density,
+    color,
+    scatter_lights
+);
+
+impl Default for FogVolume {
+    fn default() -> Self {
+        FogVolumeBuilder::new(BaseBuilder::new()).build_fog_volume()
+    }
+}
+
+impl Deref for FogVolume {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for FogVolume {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for FogVolume {
+    fn type_uuid() -> Uuid {
+        uuid!("6f2ab2a2-96a3-4a4c-9d2b-2d5b4f3f7a81")
+    }
+}
+
+impl FogVolume {
+    /// Sets new shape of the volume.
+    pub fn set_shape(&mut self, shape: FogVolumeShape) {
+        self.shape.set(shape);
+    }
+
+    /// Returns current shape of the volume.
+    pub fn shape(&self) -> FogVolumeShape {
+        *self.shape
+    }
+
+    /// Sets new half-extents of the box, they're used only by [`FogVolumeShape::Box`].
+    pub fn set_extents(&mut self, extents: Vector3<f32>) {
+        self.extents.set(extents.map(|v| v.abs()));
+    }
+
+    /// Returns current half-extents of the box.
+    pub fn extents(&self) -> Vector3<f32> {
+        *self.extents
+    }
+
+    /// Sets new radius of the sphere, it is used only by [`FogVolumeShape::Sphere`].
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius.set(radius.max(0.0));
+    }
+
+    /// Returns current radius of the sphere.
+    pub fn radius(&self) -> f32 {
+        *self.radius
+    }
+
+    /// Sets new density of the fog - the fraction of light that is absorbed or scattered per
+    /// unit of distance. Reasonable values are in `0.0..1.0` range.
+    pub fn set_density(&mut self, density: f32) {
+        self.density.set(density.max(0.0));
+    }
+
+    /// Returns current density of the fog.
+    pub fn density(&self) -> f32 {
+        *self.density
+    }
+
+    /// Sets new color (albedo) of the fog.
+    pub fn set_color(&mut self, color: Color) {
+        self.color.set(color);
+    }
+
+    /// Returns current color of the fog.
+    pub fn color(&self) -> Color {
+        *self.color
+    }
+
+    /// Enables or disables scattering of light of light sources in the fog.
+    pub fn set_scatter_lights(&mut self, scatter_lights: bool) {
+        self.scatter_lights.set(scatter_lights);
+    }
+
+    /// Returns `true` if light sources scatter in the fog.
+    pub fn is_scatter_lights(&self) -> bool {
+        *self.scatter_lights
+    }
+
+    /// Returns a fraction of light that passes through the given distance in the fog.
+    pub fn transmittance(&self, distance: f32) -> f32 {
+        (-*self.density * distance.max(0.0)).exp()
+    }
+}
+
+impl NodeTrait for FogVolume {
+    crate::impl_query_component!();
+
+    /// Returns current **local-space** bounding box.
+    #[inline]
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        let half_size = match *self.shape {
+            FogVolumeShape::Box => *self.extents,
+            FogVolumeShape::Sphere => Vector3::repeat(*self.radius),
+        };
+        AxisAlignedBoundingBox::from_min_max(-half_size, half_size)
+    }
+
+    /// Returns current **world-space** bounding box.
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    // Prefab inheritance resolving.
+    fn inherit(&mut self, parent: &Node) -> Result<(), InheritError> {
+        self.base.inherit_properties(parent)?;
+        if let Some(parent) = parent.cast::<Self>() {
+            self.try_inherit_self_properties(parent)?;
+        }
+        Ok(())
+    }
+
+    fn reset_inheritable_properties(&mut self) {
+        self.base.reset_inheritable_properties();
+        self.reset_self_inheritable_properties();
+    }
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        self.base.restore_resources(resource_manager);
+    }
+
+    fn remap_handles(&mut self, old_new_mapping: &FxHashMap<Handle<Node>, Handle<Node>>) {
+        self.base.remap_handles(old_new_mapping);
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create a fog volume in a declarative manner.
+pub struct FogVolumeBuilder {
+    base_builder: BaseBuilder,
+    shape: FogVolumeShape,
+    extents: Vector3<f32>,
+    radius: f32,
+    density: f32,
+    color: Color,
+    scatter_lights: bool,
+}
+
+impl FogVolumeBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            shape: FogVolumeShape::Box,
+            extents: Vector3::new(2.0, 2.0, 2.0),
+            radius: 2.0,
+            density: 0.2,
+            color: Color::WHITE,
+            scatter_lights: true,
+        }
+    }
+
+    /// Sets desired shape of the volume.
+    pub fn with_shape(mut self, shape: FogVolumeShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets desired half-extents of the box.
+    pub fn with_extents(mut self, extents: Vector3<f32>) -> Self {
+        self.extents = extents;
+        self
+    }
+
+    /// Sets desired radius of the sphere.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets desired density of the fog.
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Sets desired color of the fog.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets whether light sources should scatter in the fog or not.
+    pub fn with_scatter_lights(mut self, scatter_lights: bool) -> Self {
+        self.scatter_lights = scatter_lights;
+        self
+    }
+
+    /// Creates new fog volume.
+    pub fn build_fog_volume(self) -> FogVolume {
+        FogVolume {
+            base: self.base_builder.build_base(),
+            shape: self.shape.into(),
+            extents: self.extents.map(|v| v.abs()).into(),
+            radius: self.radius.max(0.0).into(),
+            density: self.density.max(0.0).into(),
+            color: self.color.into(),
+            scatter_lights: self.scatter_lights.into(),
+        }
+    }
+
+    /// Creates new fog volume node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_fog_volume())
+    }
+
+    /// Creates new instance of fog volume node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, color::Color},
+        scene::{
+            base::{test::check_inheritable_properties_equality, BaseBuilder},
+            fog_volume::{FogVolume, FogVolumeBuilder, FogVolumeShape},
+            node::NodeTrait,
+        },
+    };
+
+    #[test]
+    fn test_fog_volume_inheritance() {
+        let parent = FogVolumeBuilder::new(BaseBuilder::new())
+            .with_shape(FogVolumeShape::Sphere)
+            .with_extents(Vector3::new(1.0, 2.0, 3.0))
+            .with_radius(4.0)
+            .with_density(0.5)
+            .with_color(Color::opaque(10, 20, 30))
+            .with_scatter_lights(false)
+            .build_node();
+
+        let mut child = FogVolumeBuilder::new(BaseBuilder::new()).build_fog_volume();
+
+        child.inherit(&parent).unwrap();
+
+        let parent = parent.cast::<FogVolume>().unwrap();
+
+        check_inheritable_properties_equality(&child.base, &parent.base);
+        check_inheritable_properties_equality(&child, parent);
+    }
+
+    #[test]
+    fn test_fog_volume_bounds() {
+        let mut volume = FogVolumeBuilder::new(BaseBuilder::new())
+            .with_extents(Vector3::new(1.0, -2.0, 3.0))
+            .with_radius(5.0)
+            .build_fog_volume();
+
+        let bounds = volume.local_bounding_box();
+        assert_eq!(bounds.min, Vector3::new(-1.0, -2.0, -3.0));
+        assert_eq!(bounds.max, Vector3::new(1.0, 2.0, 3.0));
+
+        volume.set_shape(FogVolumeShape::Sphere);
+        let bounds = volume.local_bounding_box();
+        assert_eq!(bounds.min, Vector3::new(-5.0, -5.0, -5.0));
+        assert_eq!(bounds.max, Vector3::new(5.0, 5.0, 5.0));
+    }
+
+    #[test]
+    fn test_fog_volume_transmittance() {
+        let volume = FogVolumeBuilder::new(BaseBuilder::new())
+            .with_density(0.5)
+            .build_fog_volume();
+
+        assert_eq!(volume.transmittance(0.0), 1.0);
+        assert!((volume.transmittance(2.0) - (-1.0f32).exp()).abs() < 1e-6);
+        assert_eq!(volume.transmittance(-1.0), 1.0);
+    }
+}
@@ -0,0 +1,101 @@
+//! Contains an error that may occur when accessing nodes of a graph using typed accessors, such as
+//! [`super::Graph::try_get_of_type`].
+
+use crate::{core::pool::Handle, scene::node::Node};
+use std::fmt::{Display, Formatter};
+
+/// An error that may occur when accessing a node of a graph (or its script) by a handle with a
+/// particular type in mind.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphError {
+    /// A handle does not point to a living node. It happens when the node was deleted (the handle
+    /// is stale) or the handle was never valid for the graph.
+    InvalidHandle {
+        /// The handle that was used.
+        handle: Handle<Node>,
+        /// A handle of the node that currently occupies the same slot of the graph (if any). Its
+        /// generation differs from the generation of the used handle.
+        current: Handle<Node>,
+    },
+    /// A node has a type different from expected.
+    UnexpectedType {
+        /// A handle of the node.
+        handle: Handle<Node>,
+        /// Name of the node.
+        name: String,
+        /// Name of the expected type.
+        expected: &'static str,
+        /// Name of the actual type of the node.
+        actual: &'static str,
+    },
+    /// A node has no script.
+    NoScript {
+        /// A handle of the node.
+        handle: Handle<Node>,
+        /// Name of the node.
+        name: String,
+    },
+    /// A script of a node has a type different from expected.
+    UnexpectedScriptType {
+        /// A handle of the node.
+        handle: Handle<Node>,
+        /// Name of the node.
+        name: String,
+        /// Name of the expected type.
+        expected: &'static str,
+        /// Name of the actual type of the script.
+        actual: &'static str,
+    },
+}
+
+impl Display for GraphError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphError::InvalidHandle { handle, current } => {
+                if current.is_some() {
+                    write!(
+                        f,
+                        "Handle {} is invalid: the node was deleted and its slot is now \
+                        occupied by a node with handle {} (generation {} instead of {})!",
+                        handle,
+                        current,
+                        current.generation(),
+                        handle.generation()
+                    )
+                } else {
+                    write!(
+                        f,
+                        "Handle {} is invalid: there is no node at index {}!",
+                        handle,
+                        handle.index()
+                    )
+                }
+            }
+            GraphError::UnexpectedType {
+                handle,
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Node {} ({}) has unexpected type! Expected {}, but it is {}.",
+                name, handle, expected, actual
+            ),
+            GraphError::NoScript { handle, name } => {
+                write!(f, "Node {} ({}) has no script!", name, handle)
+            }
+            GraphError::UnexpectedScriptType {
+                handle,
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Script of node {} ({}) has unexpected type! Expected {}, but it is {}.",
+                name, handle, expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
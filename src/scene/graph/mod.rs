@@ -22,6 +22,8 @@
 //! just by linking nodes to each other. Good example of this is skeleton which
 //! is used in skinning (animating 3d model by set of bones).
 
+#[cfg(feature = "pool_diagnostics")]
+use crate::core::pool::diagnostics::{HandleDiagnostic, LeakReport};
 use crate::{
     asset::ResourceState,
     core::{
@@ -30,23 +32,34 @@ use crate::{
         instant,
         math::Matrix4Ext,
         pool::{Handle, Pool, Ticket},
+        uuid::Uuid,
         visitor::{Visit, VisitResult, Visitor},
     },
-    resource::model::{Model, NodeMapping},
+    resource::model::{Model, ModelData, NodeMapping},
     scene::{
         self,
         camera::Camera,
         dim2::{self},
-        graph::physics::{PhysicsPerformanceStatistics, PhysicsWorld},
-        mesh::Mesh,
-        node::{container::NodeContainer, Node, SyncContext, UpdateContext},
+        graph::{
+            error::GraphError,
+            physics::{isometry_from_global_transform, PhysicsPerformanceStatistics, PhysicsWorld},
+        },
+        ik::{solve_two_bone_ik, TwoBoneIk},
+        mesh::{
+            socket::{Socket, SocketError},
+            Mesh,
+        },
+        node::{container::NodeContainer, Node, NodeTrait, SyncContext, UpdateContext},
         pivot::Pivot,
+        rigidbody::RigidBody,
         sound::context::SoundContext,
         transform::TransformBuilder,
+        wind::Wind,
     },
+    script::ScriptTrait,
     utils::log::{Log, MessageKind},
 };
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use rapier3d::geometry::ColliderHandle;
 use std::{
     fmt::Debug,
@@ -54,6 +67,8 @@ use std::{
     time::Duration,
 };
 
+pub mod error;
+pub mod path;
 pub mod physics;
 
 /// Graph performance statistics. Allows you to find out "hot" parts of the scene graph, which
@@ -113,9 +128,21 @@ pub struct Graph {
     /// Backing sound context. It is responsible for sound rendering.
     pub sound_context: SoundContext,
 
+    /// Wind of the scene. It sways vegetation and pushes particles, see [`Wind`] docs for more info.
+    pub wind: Wind,
+
     /// Performance statistics of a last [`Graph::update`] call.
     #[inspect(skip)]
     pub performance_statistics: GraphPerformanceStatistics,
+
+    // Runtime-only, see `Graph::set_visibility_filter`.
+    #[inspect(skip)]
+    visibility_filter: Option<FxHashSet<Handle<Node>>>,
+
+    // Actual global transforms of nodes, that were replaced by interpolated ones for rendering.
+    // See `Graph::begin_physics_interpolation`.
+    #[inspect(skip)]
+    interpolated_transforms: Vec<(Handle<Node>, Matrix4<f32>)>,
 }
 
 impl Default for Graph {
@@ -127,7 +154,10 @@ impl Default for Graph {
             pool: Pool::new(),
             stack: Vec::new(),
             sound_context: Default::default(),
+            wind: Default::default(),
             performance_statistics: Default::default(),
+            visibility_filter: None,
+            interpolated_transforms: Default::default(),
         }
     }
 }
@@ -188,6 +218,8 @@ impl Graph {
         let mut root = Node::new(Pivot::default());
         root.set_name("__ROOT__");
         let root = pool.spawn(root);
+        #[cfg(feature = "pool_diagnostics")]
+        pool.set_debug_label(root, "__ROOT__");
         Self {
             physics: Default::default(),
             stack: Vec::new(),
@@ -195,7 +227,10 @@ impl Graph {
             pool,
             physics2d: Default::default(),
             sound_context: SoundContext::new(),
+            wind: Default::default(),
             performance_statistics: Default::default(),
+            visibility_filter: None,
+            interpolated_transforms: Default::default(),
         }
     }
 
@@ -203,10 +238,15 @@ impl Graph {
     /// storage and you'll get a handle to the node. Node will be automatically attached
     /// to root node of graph, it is required because graph can contain only one root.
     #[inline]
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     pub fn add_node(&mut self, mut node: Node) -> Handle<Node> {
         let children = node.children.clone();
         node.children.clear();
+        #[cfg(feature = "pool_diagnostics")]
+        let label = format!("{} ({})", node.name(), node.type_name());
         let handle = self.pool.spawn(node);
+        #[cfg(feature = "pool_diagnostics")]
+        self.pool.set_debug_label(handle, label);
         if self.root.is_some() {
             self.link_nodes(handle, self.root);
         }
@@ -256,6 +296,95 @@ impl Graph {
         self.pool.try_borrow_mut(handle)
     }
 
+    /// Tries to borrow a node and cast it to the given type. Unlike `graph[handle].cast::<T>()`,
+    /// it never panics and returns descriptive error if the handle is invalid or the node has
+    /// different type.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fyrox::{
+    ///     core::pool::Handle,
+    ///     scene::{graph::Graph, light::point::PointLight, node::Node},
+    /// };
+    ///
+    /// fn set_light_radius(graph: &mut Graph, light: Handle<Node>) {
+    ///     match graph.try_get_mut_of_type::<PointLight>(light) {
+    ///         Ok(light) => {
+    ///             light.set_radius(10.0);
+    ///         }
+    ///         Err(error) => println!("{}", error),
+    ///     }
+    /// }
+    /// ```
+    pub fn try_get_of_type<T: NodeTrait>(&self, handle: Handle<Node>) -> Result<&T, GraphError> {
+        let node = self.try_get_checked(handle)?;
+        node.cast::<T>().ok_or_else(|| GraphError::UnexpectedType {
+            handle,
+            name: node.name_owned(),
+            expected: std::any::type_name::<T>(),
+            actual: node.type_name(),
+        })
+    }
+
+    /// Tries to mutably borrow a node and cast it to the given type. See
+    /// [`Self::try_get_of_type`] for more info.
+    pub fn try_get_mut_of_type<T: NodeTrait>(
+        &mut self,
+        handle: Handle<Node>,
+    ) -> Result<&mut T, GraphError> {
+        // Check first to be able to build an error using shared borrow.
+        self.try_get_of_type::<T>(handle)?;
+        Ok(self.pool[handle].cast_mut::<T>().unwrap())
+    }
+
+    /// Tries to borrow a script of a node and cast it to the given type. Returns descriptive error
+    /// if the handle is invalid, the node has no script or the script has different type.
+    pub fn get_script_of<S: ScriptTrait>(&self, handle: Handle<Node>) -> Result<&S, GraphError> {
+        let node = self.try_get_checked(handle)?;
+        let script = node.script.as_ref().ok_or_else(|| GraphError::NoScript {
+            handle,
+            name: node.name_owned(),
+        })?;
+        script
+            .cast::<S>()
+            .ok_or_else(|| GraphError::UnexpectedScriptType {
+                handle,
+                name: node.name_owned(),
+                expected: std::any::type_name::<S>(),
+                actual: script.type_name(),
+            })
+    }
+
+    /// Tries to mutably borrow a script of a node and cast it to the given type. See
+    /// [`Self::get_script_of`] for more info.
+    pub fn get_script_of_mut<S: ScriptTrait>(
+        &mut self,
+        handle: Handle<Node>,
+    ) -> Result<&mut S, GraphError> {
+        // Check first to be able to build an error using shared borrow.
+        self.get_script_of::<S>(handle)?;
+        Ok(self.pool[handle]
+            .script
+            .as_mut()
+            .and_then(|script| script.cast_mut::<S>())
+            .unwrap())
+    }
+
+    fn try_get_checked(&self, handle: Handle<Node>) -> Result<&Node, GraphError> {
+        self.pool.try_borrow(handle).ok_or_else(|| {
+            let current = self.pool.handle_from_index(handle.index());
+            GraphError::InvalidHandle {
+                handle,
+                current: if self.pool.is_valid_handle(current) {
+                    current
+                } else {
+                    Handle::NONE
+                },
+            }
+        })
+    }
+
     /// Destroys node and its children recursively.
     ///
     /// # Notes
@@ -264,7 +393,11 @@ impl Graph {
     /// physics, etc. You should prefer to use [Scene::remove_node](crate::scene::Scene::remove_node) -
     /// it automatically breaks all associations between nodes.
     #[inline]
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     pub fn remove_node(&mut self, node_handle: Handle<Node>) {
+        // Removed node of a resource must not be restored on resolve.
+        self.suppress_resource_node(node_handle);
+
         self.unlink_internal(node_handle);
 
         self.stack.clear();
@@ -350,6 +483,76 @@ impl Graph {
             .set_position(Vector3::default());
     }
 
+    /// Returns an iterator over handles of joints (both 3D and 2D) connected to the given rigid
+    /// body. A joint stays connected to its bodies when they're moved in the hierarchy, but it
+    /// is deactivated while any of its bodies is invalid (for example, when a body was moved to a
+    /// disabled node), use `is_active` method of a joint to check it.
+    pub fn connected_joints(&self, body: Handle<Node>) -> impl Iterator<Item = Handle<Node>> + '_ {
+        self.pool.pair_iter().filter_map(move |(handle, node)| {
+            let connected = if let Some(joint) = node.cast::<scene::joint::Joint>() {
+                joint.is_connected_to(body)
+            } else if let Some(joint) = node.cast::<dim2::joint::Joint>() {
+                joint.is_connected_to(body)
+            } else {
+                false
+            };
+            if connected {
+                Some(handle)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Searches a socket of a mesh by its name and checks that the socket is bound to a valid bone.
+    fn find_socket(&self, mesh: Handle<Node>, socket_name: &str) -> Result<&Socket, SocketError> {
+        let socket = self
+            .try_get(mesh)
+            .and_then(|n| n.cast::<Mesh>())
+            .ok_or(SocketError::NotAMesh(mesh))?
+            .find_socket(socket_name)
+            .ok_or_else(|| SocketError::NoSuchSocket(socket_name.to_owned()))?;
+        if self.is_valid_handle(socket.bone) {
+            Ok(socket)
+        } else {
+            Err(SocketError::InvalidBone {
+                socket: socket.name.clone(),
+                bone: socket.bone,
+            })
+        }
+    }
+
+    /// Returns world transform of a socket of the given mesh without attaching anything to it.
+    /// The transform is as actual as global transform of the socket's bone, which means that it
+    /// will be valid only after first `update` call of the graph.
+    pub fn socket_global_transform(
+        &self,
+        mesh: Handle<Node>,
+        socket_name: &str,
+    ) -> Result<Matrix4<f32>, SocketError> {
+        let socket = self.find_socket(mesh, socket_name)?;
+        Ok(self.pool[socket.bone].global_transform() * socket.local_matrix())
+    }
+
+    /// Attaches a node to a socket of the given mesh. The node will be linked with the bone of the
+    /// socket and its local position and rotation will be set to the offset of the socket, so the
+    /// node will follow the bone without any lag.
+    pub fn attach_to_socket(
+        &mut self,
+        mesh: Handle<Node>,
+        socket_name: &str,
+        node: Handle<Node>,
+    ) -> Result<(), SocketError> {
+        let socket = self.find_socket(mesh, socket_name)?;
+        let (bone, position, rotation) = (socket.bone, socket.position, socket.rotation);
+        self.link_nodes(node, bone);
+        self.pool[node]
+            .local_transform_mut()
+            .set_position(position)
+            .set_rotation(rotation);
+        Ok(())
+    }
+
     /// Tries to find a copy of `node_handle` in hierarchy tree starting from `root_handle`.
     pub fn find_copy_of(
         &self,
@@ -414,6 +617,71 @@ impl Graph {
         self.find(self.root, cmp)
     }
 
+    /// Returns a path to the given node from the root of the graph, for example
+    /// `Level/Enemies/Guard`. Path of the root node is an empty string. See
+    /// [`path::NodePath`] for more info about the format of paths.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle is invalid.
+    pub fn node_path(&self, node: Handle<Node>) -> String {
+        self.relative_node_path(self.root, node)
+            .expect("Node must be a descendant of the root of the graph!")
+    }
+
+    /// Returns a path to the given node relative to the `root` node. Returns `None` if the node
+    /// is not a descendant of `root` (or the node itself) or if any of the handles is invalid.
+    pub fn relative_node_path(&self, root: Handle<Node>, node: Handle<Node>) -> Option<String> {
+        let mut segments = Vec::new();
+        let mut current = node;
+        while current != root {
+            let current_ref = self.pool.try_borrow(current)?;
+            let parent = self.pool.try_borrow(current_ref.parent())?;
+            let name = current_ref.name();
+            // Amount of siblings with the same name before the node.
+            let index = parent
+                .children()
+                .iter()
+                .take_while(|c| **c != current)
+                .filter(|c| self.pool[**c].name() == name)
+                .count();
+            segments.push((name, index));
+            current = current_ref.parent();
+        }
+
+        let mut path = String::new();
+        for (i, (name, index)) in segments.into_iter().rev().enumerate() {
+            if i > 0 {
+                path.push('/');
+            }
+            path::write_segment(&mut path, name, index);
+        }
+        Some(path)
+    }
+
+    /// Tries to find a node by the path from the root of the graph (see [`Self::node_path`]).
+    /// Returns `None` if there is no such node or the path is malformed.
+    pub fn find_by_path(&self, path: &str) -> Option<Handle<Node>> {
+        self.find_by_relative_path(self.root, path)
+    }
+
+    /// Tries to find a node by the path relative to the `root` node (see
+    /// [`Self::relative_node_path`]). Returns `None` if there is no such node or the path is
+    /// malformed.
+    pub fn find_by_relative_path(&self, root: Handle<Node>, path: &str) -> Option<Handle<Node>> {
+        let mut current = root;
+        self.pool.try_borrow(current)?;
+        for segment in path::parse_path(path)? {
+            current = self.pool[current]
+                .children()
+                .iter()
+                .filter(|c| self.pool[**c].name() == segment.name)
+                .nth(segment.index)
+                .cloned()?;
+        }
+        Some(current)
+    }
+
     /// Creates deep copy of node with all children. This is relatively heavy operation!
     /// In case if any error happened it returns `Handle::NONE`. This method can be used
     /// to create exact copy of given node hierarchy. For example you can prepare rocket
@@ -486,39 +754,48 @@ impl Graph {
     {
         let mut old_new_mapping = FxHashMap::default();
 
-        let to_copy = self
-            .traverse_handle_iter(node_handle)
-            .map(|node| (node, self.pool[node].children.clone()))
-            .collect::<Vec<_>>();
-
-        let mut root_handle = Handle::NONE;
-
-        for (parent, children) in to_copy.iter() {
-            // Copy parent first.
-            let parent_copy = self.pool[*parent].clone_box();
-            let parent_copy_handle = self.add_node(parent_copy);
-            old_new_mapping.insert(*parent, parent_copy_handle);
+        let root_copy = self.pool[node_handle].clone_box();
+        let root_handle = self.add_node(root_copy);
+        old_new_mapping.insert(node_handle, root_handle);
 
-            if root_handle.is_none() {
-                root_handle = parent_copy_handle;
-            }
-
-            // Copy children and link to new parent.
-            for &child in children {
+        // Every node is copied exactly once, children are copied only after their parent, so
+        // the copy of the parent is always known.
+        let mut stack = vec![node_handle];
+        while let Some(parent) = stack.pop() {
+            let parent_copy_handle = old_new_mapping[&parent];
+            for child in self.pool[parent].children.clone() {
                 if filter(child, &self.pool[child]) {
                     let child_copy = self.pool[child].clone_box();
                     let child_copy_handle = self.add_node(child_copy);
                     old_new_mapping.insert(child, child_copy_handle);
                     self.link_nodes(child_copy_handle, parent_copy_handle);
+                    stack.push(child);
                 }
             }
         }
 
         remap_handles(&old_new_mapping, self);
 
+        // Copies must be distinguishable from the originals.
+        self.regenerate_instance_ids(root_handle);
+
         (root_handle, old_new_mapping)
     }
 
+    /// Assigns new unique instance ids (see [`crate::scene::base::Base::instance_id`]) to the
+    /// node and all its descendants. [`Self::copy_node`] keeps the ids of the copied nodes as is,
+    /// it allows to make exact copies of graphs. Call this method if the copy is added to the
+    /// same graph as the originals (or a graph that has them) and the copies must have their own
+    /// ids.
+    pub fn regenerate_instance_ids(&mut self, root: Handle<Node>) {
+        let mut stack = vec![root];
+        while let Some(handle) = stack.pop() {
+            let node = &mut self.pool[handle];
+            node.instance_id = Uuid::new_v4();
+            stack.extend_from_slice(node.children());
+        }
+    }
+
     /// Creates copy of a node and breaks all connections with other nodes. Keep in mind that
     /// this method may give unexpected results when the node has connections with other nodes.
     /// For example if you'll try to copy a skinned mesh, its copy won't be skinned anymore -
@@ -529,6 +806,7 @@ impl Graph {
         let mut clone = node.clone_box();
         clone.parent = Handle::NONE;
         clone.children.clear();
+        clone.instance_id = Uuid::new_v4();
         if let Some(ref mut mesh) = clone.cast_mut::<Mesh>() {
             for surface in mesh.surfaces_mut() {
                 surface.bones.clear();
@@ -565,8 +843,8 @@ impl Graph {
 
     fn restore_original_handles(&mut self) {
         // Iterate over each node in the graph and resolve original handles. Original handle is a handle
-        // to a node in resource from which a node was instantiated from. Also sync templated properties
-        // if needed and copy surfaces from originals.
+        // to a node in resource from which a node was instantiated from. Properties are inherited later,
+        // when integrity of every instance is restored.
         for node in self.pool.iter_mut() {
             if let Some(model) = node.resource() {
                 let model = model.state();
@@ -604,8 +882,6 @@ impl Graph {
                         if let Some((resource_node, original)) = resource_node {
                             node.original_handle_in_resource = original;
                             node.inv_bind_pose_transform = resource_node.inv_bind_pose_transform();
-
-                            Log::verify(node.inherit(resource_node));
                         } else {
                             Log::warn(format!(
                                 "Unable to find original handle for node {}",
@@ -627,41 +903,350 @@ impl Graph {
         );
     }
 
-    fn remap_handles(&mut self, instances: &[(Handle<Node>, Model)]) {
+    // Builds original -> instance handle mapping for an instance of a resource.
+    fn instance_mapping(
+        &self,
+        instance_root: Handle<Node>,
+        resource: &Model,
+    ) -> FxHashMap<Handle<Node>, Handle<Node>> {
+        let mut old_new_mapping = FxHashMap::default();
+        let mut traverse_stack = vec![instance_root];
+        while let Some(node_handle) = traverse_stack.pop() {
+            let node = &self.pool[node_handle];
+            // We're interested only in instance nodes.
+            if node.resource.as_ref() == Some(resource) {
+                let previous_mapping =
+                    old_new_mapping.insert(node.original_handle_in_resource, node_handle);
+                // There should be no such node.
+                if previous_mapping.is_some() {
+                    Log::warn(format!(
+                        "There are multiple original nodes for {:?}! Previous was {:?}. \
+                        This can happen if a respective node was deleted.",
+                        node_handle, node.original_handle_in_resource
+                    ))
+                }
+            }
+
+            traverse_stack.extend_from_slice(node.children());
+        }
+        old_new_mapping
+    }
+
+    // Inherits properties of instance nodes from their originals in resources. Handles in the
+    // originals are remapped to the instance *before* inheritance, so modified properties (that
+    // already hold handles of this graph) are never touched by the remapping. Resources are resolved
+    // when they're loaded, so for nested instances (an instance of a prefab inside another prefab)
+    // this applies the overrides layer by layer and composes handle mappings across the levels.
+    fn inherit_properties(&mut self, instances: &[(Handle<Node>, Model)]) {
+        let mut inherited = FxHashSet::default();
+
         for (instance_root, resource) in instances {
-            // Prepare old -> new handle mapping first by walking over the graph
-            // starting from instance root.
-            let mut old_new_mapping = FxHashMap::default();
-            let mut traverse_stack = vec![*instance_root];
-            while let Some(node_handle) = traverse_stack.pop() {
-                let node = &self.pool[node_handle];
-                if let Some(node_resource) = node.resource().as_ref() {
-                    // We're interested only in instance nodes.
-                    if node_resource == resource {
-                        let previous_mapping =
-                            old_new_mapping.insert(node.original_handle_in_resource, node_handle);
-                        // There should be no such node.
-                        if previous_mapping.is_some() {
-                            Log::warn(format!(
-                                "There are multiple original nodes for {:?}! Previous was {:?}. \
-                                This can happen if a respective node was deleted.",
-                                node_handle, node.original_handle_in_resource
-                            ))
-                        }
+            let old_new_mapping = self.instance_mapping(*instance_root, resource);
+
+            let model = resource.state();
+            if let ResourceState::Ok(ref data) = *model {
+                let resource_graph = &data.get_scene().graph;
+
+                for (&original, &node_handle) in old_new_mapping.iter() {
+                    if let Some(resource_node) = resource_graph.pool.try_borrow(original) {
+                        let mut parent = resource_node.clone_box();
+                        parent.remap_handles(&old_new_mapping);
+                        Log::verify(self.pool[node_handle].inherit(&parent));
+                        inherited.insert(node_handle);
                     }
                 }
+            }
+        }
 
-                traverse_stack.extend_from_slice(node.children());
+        // Instance nodes could be moved out of their instances, such nodes cannot be remapped, but
+        // they still should inherit properties from the resource.
+        for (handle, node) in self.pool.pair_iter_mut() {
+            if inherited.contains(&handle) {
+                continue;
             }
 
-            // Lastly, remap handles. We can't do this in single pass because there could
-            // be cross references.
-            for (_, handle) in old_new_mapping.iter() {
-                self.pool[*handle].remap_handles(&old_new_mapping);
+            if let Some(model) = node.resource.clone() {
+                let model = model.state();
+                if let ResourceState::Ok(ref data) = *model {
+                    if let Some(resource_node) = data
+                        .get_scene()
+                        .graph
+                        .pool
+                        .try_borrow(node.original_handle_in_resource)
+                    {
+                        Log::verify(node.inherit(resource_node));
+                    }
+                }
             }
         }
     }
 
+    // Splits descendants of an instance root in two groups: nodes of the resource (their originals
+    // are returned) and instance-local nodes that were added to the instance (roots of added
+    // sub-graphs are returned). A node that has the same original as a node visited before (a copy
+    // of a node of the resource) or an original that is suppressed is an added node too.
+    fn split_instance_nodes(
+        &self,
+        instance_root: Handle<Node>,
+    ) -> (FxHashSet<Handle<Node>>, Vec<Handle<Node>>) {
+        let root = &self.pool[instance_root];
+
+        let mut originals = FxHashSet::default();
+        let mut added = Vec::new();
+
+        let resource = match root.resource.as_ref() {
+            Some(resource) if root.is_resource_instance_root => resource,
+            _ => return (originals, added),
+        };
+
+        originals.insert(root.original_handle_in_resource);
+
+        let mut stack = root.children().iter().rev().cloned().collect::<Vec<_>>();
+        while let Some(handle) = stack.pop() {
+            let node = &self.pool[handle];
+            if node.resource.as_ref() == Some(resource)
+                && !node.is_resource_instance_root
+                && node.original_handle_in_resource.is_some()
+                && !root
+                    .deleted_resource_nodes
+                    .contains(&node.original_handle_in_resource)
+                && originals.insert(node.original_handle_in_resource)
+            {
+                // Push children in reverse order to visit them in the order of the list, so
+                // an original node is always visited before its copies that were added after it.
+                stack.extend(node.children().iter().rev());
+            } else {
+                added.push(handle);
+            }
+        }
+
+        (originals, added)
+    }
+
+    /// Returns handles of instance-local nodes, that were added to the instance of a resource with
+    /// the given root. Only roots of added sub-graphs are returned. Nested instances of other
+    /// resources and copies of nodes of the resource are added nodes too.
+    pub fn find_added_nodes(&self, instance_root: Handle<Node>) -> Vec<Handle<Node>> {
+        self.split_instance_nodes(instance_root).1
+    }
+
+    /// Returns a handle of the root of the instance of a resource, that contains the given node.
+    /// The node itself is returned if it is an instance root. [`Handle::NONE`] is returned if the
+    /// node is not a part of an instance.
+    pub fn find_instance_root(&self, node: Handle<Node>) -> Handle<Node> {
+        let mut handle = node;
+        while let Some(node) = self.pool.try_borrow(handle) {
+            if node.is_resource_instance_root {
+                return handle;
+            }
+            handle = node.parent;
+        }
+        Handle::NONE
+    }
+
+    /// Records that the given node of a resource was deleted from its instance (the node becomes
+    /// suppressed), this way it won't be restored on resolve even if the resource was changed. The
+    /// node itself is not removed, use [`Self::remove_node`] to remove it (it suppresses the node
+    /// automatically). Returns the handle of the instance root, or [`Handle::NONE`] if the node is
+    /// not a node of a resource in an instance.
+    pub fn suppress_resource_node(&mut self, node: Handle<Node>) -> Handle<Node> {
+        let node_ref = &self.pool[node];
+        if node_ref.is_resource_instance_root || node_ref.original_handle_in_resource.is_none() {
+            return Handle::NONE;
+        }
+
+        let instance_root = self.find_instance_root(node);
+        if instance_root.is_none() || self.pool[instance_root].resource != node_ref.resource {
+            return Handle::NONE;
+        }
+
+        let original = node_ref.original_handle_in_resource;
+        let suppressed = &mut self.pool[instance_root].deleted_resource_nodes;
+        if !suppressed.contains(&original) {
+            suppressed.push(original);
+        }
+
+        instance_root
+    }
+
+    /// Removes the suppression of a node of a resource (see [`Self::suppress_resource_node`]), the
+    /// node will be restored on next resolve. Returns `false` if the node wasn't suppressed. Use
+    /// [`Self::restore_suppressed_node`] to restore the node immediately.
+    pub fn unsuppress_resource_node(
+        &mut self,
+        instance_root: Handle<Node>,
+        original: Handle<Node>,
+    ) -> bool {
+        let suppressed = &mut self.pool[instance_root].deleted_resource_nodes;
+        if let Some(position) = suppressed.iter().position(|&h| h == original) {
+            suppressed.remove(position);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Removes the suppression of a node of a resource (see [`Self::suppress_resource_node`]) and
+    /// instantiates the node with its descendants from the resource. Returns the handle of the
+    /// restored node, or [`Handle::NONE`] if the node wasn't suppressed or the resource is not
+    /// loaded.
+    pub fn restore_suppressed_node(
+        &mut self,
+        instance_root: Handle<Node>,
+        original: Handle<Node>,
+    ) -> Handle<Node> {
+        let root = &self.pool[instance_root];
+        if !root.deleted_resource_nodes.contains(&original) {
+            return Handle::NONE;
+        }
+
+        let resource = match root.resource.clone() {
+            Some(resource) => resource,
+            None => return Handle::NONE,
+        };
+
+        let state = resource.state();
+        if let ResourceState::Ok(ref data) = *state {
+            let resource_graph = &data.get_scene().graph;
+            if resource_graph.is_valid_handle(original) {
+                // Suppressed descendants are restored as well, because the whole sub-graph is
+                // instantiated.
+                let descendants = resource_graph
+                    .traverse_handle_iter(original)
+                    .collect::<FxHashSet<_>>();
+                self.pool[instance_root]
+                    .deleted_resource_nodes
+                    .retain(|h| !descendants.contains(h));
+
+                return self
+                    .instantiate_missing_resource_node(instance_root, &resource, data, original)
+                    .0;
+            }
+        }
+
+        Handle::NONE
+    }
+
+    // Remembers which nodes were added to every instance, so they won't be mistaken for nodes of
+    // resources on resolve. Suppressions of nodes that are present in an instance are discarded.
+    // Must be called before saving.
+    fn record_structural_overrides(&mut self) {
+        let instance_roots = self
+            .pool
+            .pair_iter()
+            .filter_map(|(h, n)| {
+                if n.is_resource_instance_root && n.resource.is_some() {
+                    Some(h)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for instance_root in instance_roots {
+            let (originals, added) = self.split_instance_nodes(instance_root);
+
+            let root = &mut self.pool[instance_root];
+            root.deleted_resource_nodes
+                .retain(|original| !originals.contains(original));
+            root.added_nodes = added;
+        }
+    }
+
+    // Added nodes could be copies of nodes of the resource, they're detached from the resource so
+    // they won't be confused with their originals. Must be called on resolve, before restoring
+    // integrity.
+    fn detach_added_nodes(&mut self) {
+        let instance_roots = self
+            .pool
+            .pair_iter()
+            .filter_map(|(h, n)| {
+                if n.is_resource_instance_root && !n.added_nodes.is_empty() {
+                    n.resource.clone().map(|r| (h, r))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for (instance_root, resource) in instance_roots {
+            let mut added = self.pool[instance_root].added_nodes.clone();
+            added.retain(|&h| self.is_valid_handle(h));
+
+            // Originals of nodes of the instance, copies of them are detached.
+            let mut originals = FxHashSet::default();
+            let mut stack = vec![instance_root];
+            while let Some(handle) = stack.pop() {
+                if added.contains(&handle) {
+                    continue;
+                }
+                let node = &self.pool[handle];
+                if node.resource.as_ref() == Some(&resource) {
+                    originals.insert(node.original_handle_in_resource);
+                }
+                stack.extend_from_slice(node.children());
+            }
+            originals.extend(
+                self.pool[instance_root]
+                    .deleted_resource_nodes
+                    .iter()
+                    .cloned(),
+            );
+
+            for &added_root in added.iter() {
+                let mut stack = vec![added_root];
+                while let Some(handle) = stack.pop() {
+                    let node = &mut self.pool[handle];
+                    if node.resource.as_ref() == Some(&resource)
+                        && !node.is_resource_instance_root
+                        && (handle == added_root
+                            || originals.contains(&node.original_handle_in_resource))
+                    {
+                        node.resource = None;
+                        node.original_handle_in_resource = Handle::NONE;
+                    }
+                    stack.extend_from_slice(node.children());
+                }
+            }
+
+            self.pool[instance_root].added_nodes = added;
+        }
+    }
+
+    // Instantiates a node of the resource with its descendants and links the copy with the instance
+    // of its parent. Returns the handle of the copy and the amount of instantiated nodes.
+    fn instantiate_missing_resource_node(
+        &mut self,
+        instance_root: Handle<Node>,
+        resource: &Model,
+        data: &ModelData,
+        resource_node_handle: Handle<Node>,
+    ) -> (Handle<Node>, usize) {
+        let resource_parent = data.get_scene().graph[resource_node_handle].parent();
+
+        let (copy, old_to_new_mapping) =
+            Model::instantiate_from(resource.clone(), data, resource_node_handle, self);
+
+        // Link it with existing node.
+        let parent = if resource_parent.is_some() {
+            self.find(instance_root, &mut |n| {
+                n.original_handle_in_resource == resource_parent
+                    && n.resource.as_ref() == Some(resource)
+            })
+        } else {
+            Handle::NONE
+        };
+
+        if parent.is_some() {
+            self.link_nodes(copy, parent);
+        } else {
+            // Fail-safe route - link with root of instance.
+            self.link_nodes(copy, instance_root);
+        }
+
+        (copy, old_to_new_mapping.len())
+    }
+
     fn restore_integrity(&mut self) -> Vec<(Handle<Node>, Model)> {
         Log::writeln(MessageKind::Information, "Checking integrity...".to_owned());
 
@@ -708,12 +1293,23 @@ impl Graph {
 
                 let mut traverse_stack = vec![resource_instance_root];
                 while let Some(resource_node_handle) = traverse_stack.pop() {
+                    // Deleted nodes are structural overrides of the instance, they must not be
+                    // restored together with their descendants.
+                    if self.pool[instance_root]
+                        .deleted_resource_nodes
+                        .contains(&resource_node_handle)
+                    {
+                        continue;
+                    }
+
                     let resource_node = &resource_graph[resource_node_handle];
 
                     // Root of the resource is not belongs to resource, it is just a convenient way of
                     // consolidation all descendants under a single node.
-                    let mut compare =
-                        |n: &Node| n.original_handle_in_resource == resource_node_handle;
+                    let mut compare = |n: &Node| {
+                        n.original_handle_in_resource == resource_node_handle
+                            && n.resource.as_ref() == Some(&resource)
+                    };
 
                     if resource_node_handle != resource_graph.root
                         && self.find(instance_root, &mut compare).is_none()
@@ -726,32 +1322,14 @@ impl Graph {
                             ),
                         );
 
-                        // Instantiate missing node.
-                        let (copy, old_to_new_mapping) = Model::instantiate_from(
-                            resource.clone(),
-                            data,
-                            resource_node_handle,
-                            self,
-                        );
-
-                        restored_count += old_to_new_mapping.len();
-
-                        // Link it with existing node.
-                        if resource_node.parent().is_some() {
-                            let parent = self.find(instance_root, &mut |n| {
-                                n.original_handle_in_resource == resource_node.parent()
-                            });
-
-                            if parent.is_some() {
-                                self.link_nodes(copy, parent);
-                            } else {
-                                // Fail-safe route - link with root of instance.
-                                self.link_nodes(copy, instance_root);
-                            }
-                        } else {
-                            // Fail-safe route - link with root of instance.
-                            self.link_nodes(copy, instance_root);
-                        }
+                        restored_count += self
+                            .instantiate_missing_resource_node(
+                                instance_root,
+                                &resource,
+                                data,
+                                resource_node_handle,
+                            )
+                            .1;
                     }
 
                     traverse_stack.extend_from_slice(resource_node.children());
@@ -770,13 +1348,14 @@ impl Graph {
         instances
     }
 
-    pub(in crate) fn resolve(&mut self) {
+    pub(crate) fn resolve(&mut self) {
         Log::writeln(MessageKind::Information, "Resolving graph...".to_owned());
 
         self.update_hierarchical_data();
         self.restore_original_handles();
+        self.detach_added_nodes();
         let instances = self.restore_integrity();
-        self.remap_handles(&instances);
+        self.inherit_properties(&instances);
 
         // Update cube maps for sky boxes.
         for node in self.linear_iter_mut() {
@@ -799,59 +1378,85 @@ impl Graph {
     /// need to know global transform of nodes before entering update loop, then you can call
     /// this method.
     pub fn update_hierarchical_data(&mut self) {
-        fn update_recursively(
-            nodes: &NodePool,
-            sound_context: &mut SoundContext,
-            physics: &mut PhysicsWorld,
-            physics2d: &mut dim2::physics::PhysicsWorld,
-            node_handle: Handle<Node>,
-        ) {
-            let node = &nodes[node_handle];
-
-            let (parent_global_transform, parent_visibility) =
-                if let Some(parent) = nodes.try_borrow(node.parent()) {
-                    (parent.global_transform(), parent.global_visibility())
-                } else {
-                    (Matrix4::identity(), true)
-                };
-
-            let new_global_transform = parent_global_transform * node.local_transform().matrix();
-
-            // TODO: Detect changes from user code here.
-            node.sync_transform(
-                &new_global_transform,
-                &mut SyncContext {
-                    nodes,
-                    physics,
-                    physics2d,
-                    sound_context,
-                },
-            );
-
-            node.global_transform.set(new_global_transform);
-            node.global_visibility
-                .set(parent_visibility && node.visibility());
-
-            for &child in node.children() {
-                update_recursively(nodes, sound_context, physics, physics2d, child);
-            }
-        }
+        self.update_hierarchical_data_for_descendants(self.root);
+    }
 
-        update_recursively(
+    /// Calculates local and global transform, global visibility for the given node and its
+    /// descendants. It is useful when you change local transform of a node and need to know global
+    /// transforms of its descendants right away.
+    pub fn update_hierarchical_data_for_descendants(&mut self, node: Handle<Node>) {
+        let parent = self.pool[node].parent();
+        let parent_passes_filter = self.passes_visibility_filter(parent);
+        update_hierarchical_data_recursively(
             &self.pool,
             &mut self.sound_context,
             &mut self.physics,
             &mut self.physics2d,
-            self.root,
+            self.visibility_filter.as_ref(),
+            node,
+            parent_passes_filter,
         );
     }
 
+    /// Sets a set of nodes that are allowed to be visible, every other node is hidden unless it
+    /// is a descendant of a node from the set. The filter does not change visibility flags of
+    /// nodes, it affects only their global visibility (see [`Base::global_visibility`]) and it is
+    /// not serialized or copied, so it is suitable for temporary hiding of nodes (for example
+    /// in an editor). `None` disables the filter. Changes will be applied on next update of
+    /// hierarchical data of the graph.
+    ///
+    /// [`Base::global_visibility`]: crate::scene::base::Base::global_visibility
+    pub fn set_visibility_filter(&mut self, filter: Option<FxHashSet<Handle<Node>>>) {
+        self.visibility_filter = filter;
+    }
+
+    /// Returns current visibility filter, see [`Self::set_visibility_filter`].
+    pub fn visibility_filter(&self) -> Option<&FxHashSet<Handle<Node>>> {
+        self.visibility_filter.as_ref()
+    }
+
+    /// Returns `true` if there is no visibility filter or the node or one of its ancestors is in
+    /// the filter, see [`Self::set_visibility_filter`].
+    pub fn passes_visibility_filter(&self, node: Handle<Node>) -> bool {
+        let filter = match self.visibility_filter {
+            Some(ref filter) => filter,
+            None => return true,
+        };
+
+        let mut handle = node;
+        while let Some(node) = self.pool.try_borrow(handle) {
+            if filter.contains(&handle) {
+                return true;
+            }
+            handle = node.parent();
+        }
+        false
+    }
+
     /// Checks whether given node handle is valid or not.
     pub fn is_valid_handle(&self, node_handle: Handle<Node>) -> bool {
         self.pool.is_valid_handle(node_handle)
     }
 
+    // IK corrects the final pose of skeletons, so it must be solved when global transforms of
+    // every node are known.
+    fn solve_inverse_kinematics(&mut self) {
+        for i in 0..self.pool.get_capacity() {
+            let handle = self.pool.handle_from_index(i);
+            if self
+                .pool
+                .try_borrow(handle)
+                .map_or(false, |node| node.cast::<TwoBoneIk>().is_some())
+            {
+                solve_two_bone_ik(self, handle);
+            }
+        }
+    }
+
     fn sync_native(&mut self) {
+        self.physics.sync_settings(&self.pool);
+        self.physics2d.sync_settings(&self.pool);
+
         let mut sync_context = SyncContext {
             nodes: &self.pool,
             physics: &mut self.physics,
@@ -866,8 +1471,20 @@ impl Graph {
 
     /// Updates nodes in graph using given delta time. There is no need to call it manually.
     pub fn update(&mut self, frame_size: Vector2<f32>, dt: f32) {
+        self.update_with_time_scale(frame_size, dt, 1.0)
+    }
+
+    /// Updates nodes in graph using given (unscaled) delta time and time scale. Nodes receive
+    /// scaled delta time, physics is stepped with scaled time step (see
+    /// [`crate::scene::Scene::set_time_scale`] for more info). Zero delta time means that no time
+    /// has passed (for example, when the engine is paused), in this case nothing is simulated.
+    pub fn update_with_time_scale(&mut self, frame_size: Vector2<f32>, dt: f32, time_scale: f32) {
+        let time_scale = if dt > 0.0 { time_scale.max(0.0) } else { 0.0 };
+        let dt = dt * time_scale;
+
         let last_time = instant::Instant::now();
         self.update_hierarchical_data();
+        self.solve_inverse_kinematics();
         self.performance_statistics.hierarchical_properties_time =
             instant::Instant::now() - last_time;
 
@@ -876,20 +1493,25 @@ impl Graph {
         self.performance_statistics.sync_time = instant::Instant::now() - last_time;
 
         self.physics.performance_statistics.reset();
-        self.physics.update();
+        self.physics.update(dt, time_scale);
         self.performance_statistics.physics = self.physics.performance_statistics.clone();
 
         self.physics2d.performance_statistics.reset();
-        self.physics2d.update();
+        self.physics2d.update(dt, time_scale);
         self.performance_statistics.physics2d = self.physics2d.performance_statistics.clone();
 
-        self.sound_context.update(&self.pool);
+        self.sound_context.update(&self.pool, time_scale);
+        self.sound_context
+            .update_occlusion(&self.pool, &self.physics, dt);
         self.performance_statistics.sound_update_time = self.sound_context.full_render_duration();
 
+        self.wind.update(dt);
+
         for i in 0..self.pool.get_capacity() {
             let mut update_context = UpdateContext {
                 frame_size,
                 dt,
+                time_scale,
                 // SAFETY: There multiple reasons why this is safe to get immutable reference to nodes
                 // along with mutable reference:
                 //
@@ -905,12 +1527,18 @@ impl Graph {
                 physics: &mut self.physics,
                 physics2d: &mut self.physics2d,
                 sound_context: &mut self.sound_context,
+                wind: &self.wind,
             };
 
             let handle = self.pool.handle_from_index(i);
             if let Some(node) = self.pool.at_mut(i) {
                 node.transform_modified.set(false);
 
+                // Disabled nodes keep their state as is until they're enabled again.
+                if !node.is_globally_enabled() {
+                    continue;
+                }
+
                 let is_alive = node.update(&mut update_context);
 
                 if !is_alive {
@@ -920,6 +1548,72 @@ impl Graph {
         }
     }
 
+    /// Replaces global transforms of dynamic rigid bodies and their descendants with transforms
+    /// interpolated between two last physics steps (see [`PhysicsSettings::interpolation`]). It
+    /// must be called right before rendering and followed by [`Self::end_physics_interpolation`]
+    /// right after, so game code always sees actual transforms. The engine does this automatically
+    /// in [`crate::engine::Engine::render`].
+    ///
+    /// [`PhysicsSettings::interpolation`]: crate::scene::graph::physics::PhysicsSettings::interpolation
+    pub fn begin_physics_interpolation(&mut self) {
+        self.end_physics_interpolation();
+
+        if !self.physics.settings.interpolation {
+            return;
+        }
+
+        for (handle, node) in self.pool.pair_iter() {
+            let interpolated_pose = match node.cast::<RigidBody>() {
+                Some(rigid_body) if rigid_body.is_globally_enabled() => {
+                    match self.physics.interpolated_pose(rigid_body) {
+                        Some(pose) => pose,
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+
+            // Global transforms are calculated before the simulation, so the correction is
+            // calculated relative to the pose, that is stored in the global transform of the body.
+            let current_pose = isometry_from_global_transform(&node.global_transform());
+            let correction = (interpolated_pose * current_pose.inverse()).to_homogeneous();
+
+            self.stack.clear();
+            self.stack.push(handle);
+            while let Some(descendant_handle) = self.stack.pop() {
+                let descendant = &self.pool[descendant_handle];
+
+                // Nested bodies are moved by their own interpolated poses.
+                if descendant_handle != handle
+                    && descendant.cast::<RigidBody>().map_or(false, |rigid_body| {
+                        self.physics.interpolated_pose(rigid_body).is_some()
+                    })
+                {
+                    continue;
+                }
+
+                let global_transform = descendant.global_transform();
+                self.interpolated_transforms
+                    .push((descendant_handle, global_transform));
+                descendant
+                    .global_transform
+                    .set(correction * global_transform);
+
+                self.stack.extend_from_slice(descendant.children());
+            }
+        }
+    }
+
+    /// Restores actual global transforms of nodes, that were replaced by
+    /// [`Self::begin_physics_interpolation`].
+    pub fn end_physics_interpolation(&mut self) {
+        for (handle, global_transform) in self.interpolated_transforms.drain(..) {
+            if let Some(node) = self.pool.try_borrow(handle) {
+                node.global_transform.set(global_transform);
+            }
+        }
+    }
+
     /// Returns capacity of internal pool. Can be used to iterate over all **potentially**
     /// available indices and try to convert them to handles.
     ///
@@ -964,6 +1658,11 @@ impl Graph {
         self.pool.handle_from_index(index)
     }
 
+    /// Returns a reference to the inner pool of nodes.
+    pub(crate) fn node_pool(&self) -> &NodePool {
+        &self.pool
+    }
+
     /// Creates an iterator that has linear iteration order over internal collection
     /// of nodes. It does *not* perform any tree traversal!
     pub fn linear_iter(&self) -> impl Iterator<Item = &Node> {
@@ -1068,6 +1767,20 @@ impl Graph {
         self.pool.alive_count()
     }
 
+    /// Returns a list of nodes that are still alive in the graph, grouped by their names and types
+    /// at the moment of creation. Call it right before destruction of a scene to find nodes that
+    /// should have been removed. The root node is always in the list.
+    #[cfg(feature = "pool_diagnostics")]
+    pub fn leak_report(&self) -> LeakReport {
+        self.pool.leak_report()
+    }
+
+    /// Explains why the given handle is invalid, see [`HandleDiagnostic`] docs for more info.
+    #[cfg(feature = "pool_diagnostics")]
+    pub fn diagnose(&self, handle: Handle<Node>) -> HandleDiagnostic {
+        self.pool.diagnose(handle)
+    }
+
     /// Create a graph depth traversal iterator.
     ///
     /// # Notes
@@ -1103,6 +1816,9 @@ impl Graph {
         let mut copy = Self::default();
         let (root, old_new_map) = self.copy_node(self.root, &mut copy, filter);
         copy.root = root;
+        copy.wind = self.wind.clone();
+        copy.physics.settings = self.physics.settings;
+        copy.physics2d.settings = self.physics2d.settings;
         (copy, old_new_map)
     }
 
@@ -1186,15 +1902,79 @@ impl Graph {
     }
 }
 
+fn update_hierarchical_data_recursively(
+    nodes: &NodePool,
+    sound_context: &mut SoundContext,
+    physics: &mut PhysicsWorld,
+    physics2d: &mut dim2::physics::PhysicsWorld,
+    visibility_filter: Option<&FxHashSet<Handle<Node>>>,
+    node_handle: Handle<Node>,
+    parent_passes_filter: bool,
+) {
+    let node = &nodes[node_handle];
+
+    // Visibility of parent is taken without the filter, otherwise a node from the filter would
+    // be hidden by its filtered out ancestors.
+    let (parent_global_transform, parent_visibility, parent_enabled) =
+        if let Some(parent) = nodes.try_borrow(node.parent()) {
+            (
+                parent.global_transform(),
+                parent.unfiltered_global_visibility.get(),
+                parent.is_globally_enabled(),
+            )
+        } else {
+            (Matrix4::identity(), true, true)
+        };
+
+    let new_global_transform = parent_global_transform * node.local_transform().matrix();
+
+    // TODO: Detect changes from user code here.
+    node.sync_transform(
+        &new_global_transform,
+        &mut SyncContext {
+            nodes,
+            physics,
+            physics2d,
+            sound_context,
+        },
+    );
+
+    let global_enabled = parent_enabled && node.is_enabled();
+
+    let passes_filter =
+        parent_passes_filter || visibility_filter.map_or(true, |f| f.contains(&node_handle));
+    let global_visibility = parent_visibility && node.visibility() && global_enabled;
+
+    node.global_transform.set(new_global_transform);
+    node.global_enabled.set(global_enabled);
+    node.unfiltered_global_visibility.set(global_visibility);
+    node.global_visibility
+        .set(global_visibility && passes_filter);
+
+    for &child in node.children() {
+        update_hierarchical_data_recursively(
+            nodes,
+            sound_context,
+            physics,
+            physics2d,
+            visibility_filter,
+            child,
+            passes_filter,
+        );
+    }
+}
+
 impl Index<Handle<Node>> for Graph {
     type Output = Node;
 
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     fn index(&self, index: Handle<Node>) -> &Self::Output {
         &self.pool[index]
     }
 }
 
 impl IndexMut<Handle<Node>> for Graph {
+    #[cfg_attr(feature = "pool_diagnostics", track_caller)]
     fn index_mut(&mut self, index: Handle<Node>) -> &mut Self::Output {
         &mut self.pool[index]
     }
@@ -1252,6 +2032,10 @@ impl Visit for Graph {
             panic!("Graph pool must be empty on load!")
         }
 
+        if !visitor.is_reading() {
+            self.record_structural_overrides();
+        }
+
         let mut region = visitor.enter_region(name)?;
 
         self.root.visit("Root", &mut region)?;
@@ -1259,6 +2043,7 @@ impl Visit for Graph {
         self.sound_context.visit("SoundContext", &mut region)?;
         self.physics.visit("PhysicsWorld", &mut region)?;
         self.physics2d.visit("PhysicsWorld2D", &mut region)?;
+        let _ = self.wind.visit("Wind", &mut region); // Backward compatibility.
 
         Ok(())
     }
@@ -1267,8 +2052,26 @@ impl Visit for Graph {
 #[cfg(test)]
 mod test {
     use crate::{
-        core::pool::Handle,
-        scene::{graph::Graph, node::Node, pivot::Pivot},
+        core::{
+            algebra::{Matrix4, Vector2, Vector3},
+            inspect::{Inspect, PropertyInfo},
+            pool::Handle,
+            uuid::Uuid,
+            visitor::prelude::*,
+        },
+        scene::{
+            base::BaseBuilder,
+            graph::{error::GraphError, physics::PhysicsStepMode, Graph},
+            mesh::{
+                socket::{Socket, SocketError},
+                Mesh, MeshBuilder,
+            },
+            node::Node,
+            pivot::{Pivot, PivotBuilder},
+            rigidbody::RigidBodyBuilder,
+            transform::TransformBuilder,
+        },
+        script::{Script, ScriptTrait},
     };
 
     #[test]
@@ -1286,4 +2089,364 @@ mod test {
         graph.add_node(Node::new(Pivot::default()));
         assert_eq!(graph.pool.alive_count(), 4);
     }
+
+    fn make_skinned_mesh(graph: &mut Graph) -> (Handle<Node>, Handle<Node>) {
+        let bone = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(1.0, 2.0, 3.0))
+                    .build(),
+            ),
+        )
+        .build(graph);
+        let mesh = MeshBuilder::new(BaseBuilder::new().with_children(&[bone]))
+            .with_sockets(vec![
+                Socket::new("Hand", bone).with_position(Vector3::new(0.0, 1.0, 0.0))
+            ])
+            .build(graph);
+        (mesh, bone)
+    }
+
+    #[test]
+    fn test_socket_attach() {
+        let mut graph = Graph::new();
+        let (mesh, bone) = make_skinned_mesh(&mut graph);
+        let prop = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        assert_eq!(
+            graph.attach_to_socket(mesh, "Foot", prop),
+            Err(SocketError::NoSuchSocket("Foot".to_owned()))
+        );
+        assert_eq!(
+            graph.attach_to_socket(prop, "Hand", prop),
+            Err(SocketError::NotAMesh(prop))
+        );
+
+        graph.attach_to_socket(mesh, "Hand", prop).unwrap();
+        assert_eq!(graph[prop].parent(), bone);
+
+        graph.update_hierarchical_data();
+
+        let expected = Matrix4::new_translation(&Vector3::new(1.0, 3.0, 3.0));
+        assert_eq!(graph.socket_global_transform(mesh, "Hand"), Ok(expected));
+        assert_eq!(graph[prop].global_transform(), expected);
+    }
+
+    #[test]
+    fn test_socket_bone_remapping() {
+        let mut graph = Graph::new();
+        let (mesh, bone) = make_skinned_mesh(&mut graph);
+
+        let mut other = Graph::new();
+        let (mesh_copy, old_new_map) = graph.copy_node(mesh, &mut other, &mut |_, _| true);
+
+        let bone_copy = old_new_map[&bone];
+        assert_eq!(
+            other[mesh_copy].as_mesh().find_socket("Hand").unwrap().bone,
+            bone_copy
+        );
+    }
+
+    #[test]
+    fn test_copy_node_inplace() {
+        let mut graph = Graph::new();
+        let (mesh, bone) = make_skinned_mesh(&mut graph);
+        let tip = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        graph.link_nodes(tip, bone);
+        let count = graph.pool.alive_count();
+
+        let (mesh_copy, old_new_map) = graph.copy_node_inplace(mesh, &mut |_, _| true);
+
+        // Each node of the sub-graph is copied exactly once.
+        assert_eq!(graph.pool.alive_count(), count + 3);
+        assert_eq!(old_new_map.len(), 3);
+
+        let bone_copy = old_new_map[&bone];
+        let tip_copy = old_new_map[&tip];
+        assert_eq!(graph[mesh_copy].children(), &[bone_copy]);
+        assert_eq!(graph[bone_copy].children(), &[tip_copy]);
+        assert_eq!(graph[bone].children(), &[tip]);
+        assert_eq!(
+            graph[mesh_copy].as_mesh().find_socket("Hand").unwrap().bone,
+            bone_copy
+        );
+        assert_ne!(graph[tip_copy].instance_id(), graph[tip].instance_id());
+    }
+
+    #[derive(Debug, Clone, Default, Visit, Inspect)]
+    struct FooScript {
+        value: u32,
+    }
+
+    impl ScriptTrait for FooScript {
+        fn id(&self) -> Uuid {
+            Uuid::default()
+        }
+
+        fn plugin_uuid(&self) -> Uuid {
+            Uuid::default()
+        }
+    }
+
+    #[derive(Debug, Clone, Default, Visit, Inspect)]
+    struct BarScript {
+        flag: bool,
+    }
+
+    impl ScriptTrait for BarScript {
+        fn id(&self) -> Uuid {
+            Uuid::default()
+        }
+
+        fn plugin_uuid(&self) -> Uuid {
+            Uuid::default()
+        }
+    }
+
+    #[test]
+    fn test_typed_access() {
+        let mut graph = Graph::new();
+        let pivot = PivotBuilder::new(BaseBuilder::new().with_name("Foo")).build(&mut graph);
+
+        assert!(graph.try_get_of_type::<Pivot>(pivot).is_ok());
+        assert!(graph.try_get_mut_of_type::<Pivot>(pivot).is_ok());
+
+        let error = graph.try_get_of_type::<Mesh>(pivot).unwrap_err();
+        assert_eq!(
+            error,
+            GraphError::UnexpectedType {
+                handle: pivot,
+                name: "Foo".to_owned(),
+                expected: std::any::type_name::<Mesh>(),
+                actual: std::any::type_name::<Pivot>(),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Node Foo ({}) has unexpected type! Expected {}, but it is {}.",
+                pivot,
+                std::any::type_name::<Mesh>(),
+                std::any::type_name::<Pivot>()
+            )
+        );
+        assert_eq!(graph.try_get_mut_of_type::<Mesh>(pivot).unwrap_err(), error);
+    }
+
+    #[test]
+    fn test_typed_access_invalid_handle() {
+        let mut graph = Graph::new();
+        let pivot = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        graph.remove_node(pivot);
+
+        // Stale handle, the slot is free.
+        let error = graph.try_get_of_type::<Pivot>(pivot).unwrap_err();
+        assert_eq!(
+            error,
+            GraphError::InvalidHandle {
+                handle: pivot,
+                current: Handle::NONE
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Handle {} is invalid: there is no node at index {}!",
+                pivot,
+                pivot.index()
+            )
+        );
+
+        // Stale handle, the slot is reused by another node.
+        let new_pivot = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        assert_eq!(new_pivot.index(), pivot.index());
+        let error = graph.try_get_mut_of_type::<Pivot>(pivot).unwrap_err();
+        assert_eq!(
+            error,
+            GraphError::InvalidHandle {
+                handle: pivot,
+                current: new_pivot
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Handle {} is invalid: the node was deleted and its slot is now occupied by a \
+                node with handle {} (generation {} instead of {})!",
+                pivot,
+                new_pivot,
+                new_pivot.generation(),
+                pivot.generation()
+            )
+        );
+    }
+
+    #[test]
+    fn test_script_access() {
+        let mut graph = Graph::new();
+        let pivot = PivotBuilder::new(BaseBuilder::new().with_name("Foo")).build(&mut graph);
+
+        let error = graph.get_script_of::<FooScript>(pivot).unwrap_err();
+        assert_eq!(
+            error,
+            GraphError::NoScript {
+                handle: pivot,
+                name: "Foo".to_owned()
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            format!("Node Foo ({}) has no script!", pivot)
+        );
+
+        graph[pivot].set_script(Some(Script::new(FooScript { value: 123 })));
+        let other = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        graph[other].set_script(Some(Script::new(BarScript { flag: true })));
+
+        assert!(graph.get_script_of::<BarScript>(other).unwrap().flag);
+
+        assert_eq!(graph.get_script_of::<FooScript>(pivot).unwrap().value, 123);
+        graph.get_script_of_mut::<FooScript>(pivot).unwrap().value = 321;
+        assert_eq!(graph.get_script_of::<FooScript>(pivot).unwrap().value, 321);
+
+        let error = graph.get_script_of_mut::<BarScript>(pivot).unwrap_err();
+        assert_eq!(
+            error,
+            GraphError::UnexpectedScriptType {
+                handle: pivot,
+                name: "Foo".to_owned(),
+                expected: std::any::type_name::<BarScript>(),
+                actual: std::any::type_name::<FooScript>(),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Script of node Foo ({}) has unexpected type! Expected {}, but it is {}.",
+                pivot,
+                std::any::type_name::<BarScript>(),
+                std::any::type_name::<FooScript>()
+            )
+        );
+    }
+
+    #[test]
+    fn test_physics_settings_serialization() {
+        let mut graph = Graph::new();
+        graph.physics.settings.gravity = Vector3::new(0.0, 0.0, -2.0);
+        graph.physics.settings.step_mode = PhysicsStepMode::Variable;
+        graph.physics.settings.ccd_enabled_by_default = true;
+        graph.physics.settings.integration_parameters.max_velocity_iterations = 8;
+
+        let mut visitor = Visitor::new();
+        graph.physics.visit("Physics", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut loaded = Graph::new();
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        loaded.physics.visit("Physics", &mut visitor).unwrap();
+        assert_eq!(loaded.physics.settings, graph.physics.settings);
+
+        // Loaded gravity is used in simulation.
+        let body = RigidBodyBuilder::new(BaseBuilder::new())
+            .with_mass(1.0)
+            .with_can_sleep(false)
+            .build(&mut loaded);
+        for _ in 0..10 {
+            loaded.update(Vector2::new(1.0, 1.0), 0.1);
+        }
+        let velocity = loaded[body].as_rigid_body().lin_vel();
+        assert!(velocity.x.abs() < 1.0e-4);
+        assert!(velocity.y.abs() < 1.0e-4);
+        assert!((velocity.z + 2.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_physics_interpolation() {
+        let mut graph = Graph::new();
+        graph.physics.settings.gravity = Vector3::default();
+        graph.physics.settings.interpolation = true;
+        let step = graph.physics.settings.integration_parameters.dt;
+
+        let body = RigidBodyBuilder::new(BaseBuilder::new())
+            .with_mass(1.0)
+            .with_can_sleep(false)
+            .with_lin_vel(Vector3::new(1.0, 0.0, 0.0))
+            .build(&mut graph);
+
+        // Frames of mixed length, both shorter and longer than the simulation step.
+        let frame_times = [
+            1.0 / 144.0,
+            1.0 / 30.0,
+            1.0 / 144.0,
+            1.0 / 60.0,
+            1.0 / 90.0,
+            0.002,
+        ];
+
+        let mut last_rendered: Option<f32> = None;
+        for &dt in frame_times.iter().cycle().take(60) {
+            graph.update(Vector2::new(1.0, 1.0), dt);
+
+            let actual = graph[body].global_transform();
+            graph.begin_physics_interpolation();
+            let rendered = graph[body].global_position().x;
+            graph.end_physics_interpolation();
+
+            // Game code sees actual transforms.
+            assert_eq!(graph[body].global_transform(), actual);
+
+            if let Some(last_rendered) = last_rendered {
+                // The body moves at constant velocity, so rendered position must change exactly
+                // by the time of a frame, not by the whole simulation step at once.
+                assert!(rendered >= last_rendered);
+                assert!((rendered - last_rendered - dt).abs() < 1.0e-4);
+            }
+            if rendered > 0.0 {
+                last_rendered = Some(rendered);
+            }
+        }
+
+        // The simulation keeps up with time.
+        let time = frame_times.iter().sum::<f32>() * 10.0;
+        let simulated = graph.physics.rigid_body_position(graph[body].as_rigid_body());
+        assert!((simulated.unwrap().x - time).abs() <= step + 1.0e-3);
+    }
+
+    #[test]
+    fn test_visibility_filter() {
+        let mut graph = Graph::new();
+        let child = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let isolated =
+            PivotBuilder::new(BaseBuilder::new().with_children(&[child])).build(&mut graph);
+        let parent =
+            PivotBuilder::new(BaseBuilder::new().with_children(&[isolated])).build(&mut graph);
+        let other = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        graph.set_visibility_filter(Some([isolated].iter().cloned().collect()));
+        graph.update_hierarchical_data();
+
+        // Filtered out parent does not hide the isolated node and its descendants.
+        assert!(!graph[parent].global_visibility());
+        assert!(!graph[other].global_visibility());
+        assert!(graph[isolated].global_visibility());
+        assert!(graph[child].global_visibility());
+        assert!(graph.passes_visibility_filter(child));
+        assert!(!graph.passes_visibility_filter(other));
+
+        // Own visibility flags are still respected.
+        graph[child].set_visibility(false);
+        graph.update_hierarchical_data_for_descendants(isolated);
+        assert!(!graph[child].global_visibility());
+
+        // The filter is temporary, it is not copied.
+        let (copy, _) = graph.clone(&mut |_, _| true);
+        assert!(copy.visibility_filter().is_none());
+
+        graph.set_visibility_filter(None);
+        graph.update_hierarchical_data();
+        assert!(graph[parent].global_visibility());
+        assert!(graph[other].global_visibility());
+        assert!(graph[parent].visibility());
+    }
 }
@@ -0,0 +1,352 @@
+//! Node paths allow you to reference scene nodes by names instead of handles.
+//!
+//! For more info see [`NodePath`]
+
+use crate::{
+    core::{
+        pool::Handle,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    scene::{graph::Graph, node::Node},
+    utils::log::Log,
+};
+use std::fmt::{Display, Formatter};
+
+/// A slash-separated path of node names, for example `Level/Enemies/Guard`. Paths are much more
+/// stable than handles, they survive re-creation of a scene, they could be written by hand in
+/// config files, etc.
+///
+/// # Format
+///
+/// Every segment of a path is a name of a node. If a node has siblings with the same name, the
+/// segment of every sibling except the first one has an index suffix - `Guard`, `Guard[1]`,
+/// `Guard[2]` and so on. The index is the number of siblings with the same name that go before
+/// the node. Nodes with empty names always have an index suffix (`[0]`, `[1]`, ...), because an
+/// empty path is reserved for the root node. Characters `\`, `/` and `[` in names are escaped
+/// with a backslash, for example a node with `A/B` name will have `A\/B` segment.
+///
+/// Paths are resolved from the root of a graph (see [`Graph::find_by_path`]) or from an
+/// arbitrary node (see [`Graph::find_by_relative_path`]).
+///
+/// # Scripts
+///
+/// The type could be used as a field of a script, the editor allows you to pick a node for such
+/// field. Resolve the path when your script is initialized:
+///
+/// ```rust
+/// use fyrox::{
+///     core::pool::Handle,
+///     scene::{graph::path::NodePath, node::Node},
+///     script::ScriptContext,
+/// };
+///
+/// struct MyScript {
+///     target_path: NodePath,
+///     target: Handle<Node>,
+/// }
+///
+/// impl MyScript {
+///     fn on_init(&mut self, context: ScriptContext) {
+///         // Prints a warning to the log if there is no such node.
+///         self.target = self.target_path.resolve(&context.scene.graph);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct NodePath(String);
+
+impl Visit for NodePath {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        self.0.visit(name, visitor)
+    }
+}
+
+impl Display for NodePath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<String> for NodePath {
+    fn from(path: String) -> Self {
+        Self(path)
+    }
+}
+
+impl From<&str> for NodePath {
+    fn from(path: &str) -> Self {
+        Self(path.to_owned())
+    }
+}
+
+impl NodePath {
+    /// Creates new path from a string. The string is not checked, malformed paths will simply
+    /// fail to resolve.
+    pub fn new<S: Into<String>>(path: S) -> Self {
+        Self(path.into())
+    }
+
+    /// Creates a path to the given node from the root of the graph.
+    pub fn of(graph: &Graph, node: Handle<Node>) -> Self {
+        Self(graph.node_path(node))
+    }
+
+    /// Returns the path as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns `true` if the path is empty. Empty path points to the root of a graph.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Tries to find a node by the path starting from the root of the graph. Prints a warning to
+    /// the log and returns [`Handle::NONE`] if there is no such node.
+    pub fn resolve(&self, graph: &Graph) -> Handle<Node> {
+        self.resolve_relative(graph, graph.get_root())
+    }
+
+    /// Tries to find a node by the path starting from the given node. Prints a warning to the log
+    /// and returns [`Handle::NONE`] if there is no such node.
+    pub fn resolve_relative(&self, graph: &Graph, root: Handle<Node>) -> Handle<Node> {
+        match graph.find_by_relative_path(root, &self.0) {
+            Some(handle) => handle,
+            None => {
+                Log::warn(format!(
+                    "Unable to resolve node path \"{}\": there is no such node!",
+                    self.0
+                ));
+                Handle::NONE
+            }
+        }
+    }
+}
+
+/// A single parsed segment of a path.
+#[derive(Debug, PartialEq)]
+pub(in crate) struct PathSegment {
+    pub name: String,
+    pub index: usize,
+}
+
+/// Writes escaped segment of a path to the given string.
+pub(in crate) fn write_segment(out: &mut String, name: &str, index: usize) {
+    for c in name.chars() {
+        if matches!(c, '\\' | '/' | '[') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    if index > 0 || name.is_empty() {
+        out.push_str(&format!("[{}]", index));
+    }
+}
+
+/// Splits a path into segments. Returns `None` if the path is malformed.
+pub(in crate) fn parse_path(path: &str) -> Option<Vec<PathSegment>> {
+    let mut segments = Vec::new();
+
+    if path.is_empty() {
+        return Some(segments);
+    }
+
+    let mut chars = path.chars();
+    let mut name = String::new();
+    let mut index = None;
+    loop {
+        let c = chars.next();
+        match c {
+            None | Some('/') => {
+                // Nodes with empty names must have an explicit index, this also rejects empty
+                // segments like in `A//B` or `A/`.
+                if name.is_empty() && index.is_none() {
+                    return None;
+                }
+                segments.push(PathSegment {
+                    name: std::mem::take(&mut name),
+                    index: index.take().unwrap_or_default(),
+                });
+                if c.is_none() {
+                    return Some(segments);
+                }
+            }
+            // Index suffix must be the last part of a segment.
+            Some(_) if index.is_some() => return None,
+            Some('\\') => name.push(chars.next()?),
+            Some('[') => {
+                let mut digits = String::new();
+                loop {
+                    match chars.next()? {
+                        ']' => break,
+                        c => digits.push(c),
+                    }
+                }
+                index = Some(digits.parse::<usize>().ok()?);
+            }
+            Some(c) => name.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::core::pool::Handle;
+    use crate::scene::{
+        base::BaseBuilder,
+        graph::{
+            path::{parse_path, NodePath, PathSegment},
+            Graph,
+        },
+        node::Node,
+        pivot::PivotBuilder,
+    };
+
+    fn make_node(graph: &mut Graph, name: &str, parent: Handle<Node>) -> Handle<Node> {
+        let node = PivotBuilder::new(BaseBuilder::new().with_name(name)).build(graph);
+        graph.link_nodes(node, parent);
+        node
+    }
+
+    #[test]
+    fn test_node_path_round_trip() {
+        let mut graph = Graph::new();
+        let root = graph.get_root();
+        let level = make_node(&mut graph, "Level", root);
+        let enemies = make_node(&mut graph, "Enemies", level);
+        let guard = make_node(&mut graph, "Guard_03", enemies);
+
+        assert_eq!(graph.node_path(root), "");
+        assert_eq!(graph.node_path(guard), "Level/Enemies/Guard_03");
+        assert_eq!(graph.find_by_path("Level/Enemies/Guard_03"), Some(guard));
+        assert_eq!(graph.find_by_path("Level/Enemies"), Some(enemies));
+        assert_eq!(graph.find_by_path(""), Some(root));
+        assert_eq!(graph.find_by_path("Level/Allies"), None);
+    }
+
+    #[test]
+    fn test_node_path_duplicates() {
+        let mut graph = Graph::new();
+        let root = graph.get_root();
+        let a = make_node(&mut graph, "Guard", root);
+        let other = make_node(&mut graph, "Other", root);
+        let b = make_node(&mut graph, "Guard", root);
+        let c = make_node(&mut graph, "Guard", root);
+
+        assert_eq!(graph.node_path(a), "Guard");
+        assert_eq!(graph.node_path(other), "Other");
+        assert_eq!(graph.node_path(b), "Guard[1]");
+        assert_eq!(graph.node_path(c), "Guard[2]");
+
+        for node in [a, b, c, other] {
+            assert_eq!(graph.find_by_path(&graph.node_path(node)), Some(node));
+        }
+        assert_eq!(graph.find_by_path("Guard[0]"), Some(a));
+        assert_eq!(graph.find_by_path("Guard[3]"), None);
+    }
+
+    #[test]
+    fn test_node_path_renames() {
+        let mut graph = Graph::new();
+        let root = graph.get_root();
+        let a = make_node(&mut graph, "Guard", root);
+        let b = make_node(&mut graph, "Guard", root);
+
+        assert_eq!(graph.node_path(b), "Guard[1]");
+
+        graph[a].set_name("Captain");
+
+        assert_eq!(graph.node_path(b), "Guard");
+        assert_eq!(graph.find_by_path("Guard"), Some(b));
+        assert_eq!(graph.find_by_path("Guard[1]"), None);
+        assert_eq!(graph.find_by_path("Captain"), Some(a));
+    }
+
+    #[test]
+    fn test_node_path_special_characters() {
+        let mut graph = Graph::new();
+        let root = graph.get_root();
+        let names = ["A/B", "C\\D", "E[1]", "F]", "", "", "Ünïcödé name"];
+        let nodes = names
+            .iter()
+            .map(|name| make_node(&mut graph, name, root))
+            .collect::<Vec<_>>();
+
+        assert_eq!(graph.node_path(nodes[0]), "A\\/B");
+        assert_eq!(graph.node_path(nodes[1]), "C\\\\D");
+        assert_eq!(graph.node_path(nodes[2]), "E\\[1]");
+        assert_eq!(graph.node_path(nodes[3]), "F]");
+        assert_eq!(graph.node_path(nodes[4]), "[0]");
+        assert_eq!(graph.node_path(nodes[5]), "[1]");
+
+        for node in nodes {
+            assert_eq!(graph.find_by_path(&graph.node_path(node)), Some(node));
+        }
+
+        let parent = graph.find_by_path("A\\/B").unwrap();
+        let child = make_node(&mut graph, "x/y", parent);
+        assert_eq!(graph.node_path(child), "A\\/B/x\\/y");
+        assert_eq!(graph.find_by_path("A\\/B/x\\/y"), Some(child));
+    }
+
+    #[test]
+    fn test_node_path_relative() {
+        let mut graph = Graph::new();
+        let root = graph.get_root();
+        let level = make_node(&mut graph, "Level", root);
+        let enemies = make_node(&mut graph, "Enemies", level);
+        let guard = make_node(&mut graph, "Guard", enemies);
+        let other = make_node(&mut graph, "Other", root);
+
+        assert_eq!(
+            graph.relative_node_path(level, guard),
+            Some("Enemies/Guard".to_owned())
+        );
+        assert_eq!(graph.relative_node_path(level, level), Some(String::new()));
+        assert_eq!(graph.relative_node_path(level, other), None);
+        assert_eq!(
+            graph.find_by_relative_path(level, "Enemies/Guard"),
+            Some(guard)
+        );
+        assert_eq!(graph.find_by_relative_path(level, ""), Some(level));
+        assert_eq!(graph.find_by_relative_path(enemies, "Enemies/Guard"), None);
+    }
+
+    #[test]
+    fn test_node_path_resolve() {
+        let mut graph = Graph::new();
+        let root = graph.get_root();
+        let level = make_node(&mut graph, "Level", root);
+        let guard = make_node(&mut graph, "Guard", level);
+
+        assert_eq!(NodePath::of(&graph, guard).resolve(&graph), guard);
+        assert_eq!(
+            NodePath::new("Guard").resolve_relative(&graph, level),
+            guard
+        );
+        assert_eq!(NodePath::new("Level/Nobody").resolve(&graph), Handle::NONE);
+    }
+
+    #[test]
+    fn test_parse_malformed_paths() {
+        assert_eq!(parse_path("A/"), None);
+        assert_eq!(parse_path("A//B"), None);
+        assert_eq!(parse_path("A[1]B"), None);
+        assert_eq!(parse_path("A[x]"), None);
+        assert_eq!(parse_path("A[1"), None);
+        assert_eq!(parse_path("A\\"), None);
+        assert_eq!(
+            parse_path("A[2]/\\[0]"),
+            Some(vec![
+                PathSegment {
+                    name: "A".to_owned(),
+                    index: 2
+                },
+                PathSegment {
+                    name: "[0]".to_owned(),
+                    index: 0
+                }
+            ])
+        );
+    }
+}
@@ -35,6 +35,7 @@ use crate::{
         raw_mesh::{RawMeshBuilder, RawVertex},
     },
 };
+use fxhash::FxHashMap;
 use rapier3d::{
     dynamics::{
         CCDSolver, GenericJoint, GenericJointBuilder, ImpulseJointHandle, ImpulseJointSet,
@@ -331,6 +332,32 @@ where
     map: BiDirHashMap<A, Handle<Node>>,
 }
 
+/// Returns native handles of the bodies of the joint, or the reason why the joint cannot connect
+/// them.
+fn joint_native_bodies(
+    nodes: &NodePool,
+    joint: &scene::joint::Joint,
+) -> Result<(RigidBodyHandle, RigidBodyHandle), &'static str> {
+    let native_body = |handle: Handle<Node>| {
+        match nodes
+            .try_borrow(handle)
+            .and_then(|n| n.cast::<scene::rigidbody::RigidBody>())
+        {
+            // Native bodies are missing if they're disabled.
+            Some(body) if body.native.get() != RigidBodyHandle::invalid() => Ok(body.native.get()),
+            Some(_) => Err("one of the bodies is disabled"),
+            None => Err("one of the bodies is missing or it is not a rigid body"),
+        }
+    };
+
+    let bodies = (native_body(joint.body1())?, native_body(joint.body2())?);
+    if bodies.0 == bodies.1 {
+        Err("a joint cannot connect a body with itself")
+    } else {
+        Ok(bodies)
+    }
+}
+
 fn convert_joint_params(params: scene::joint::JointParams) -> GenericJoint {
     match params {
         scene::joint::JointParams::BallJoint(v) => SphericalJointBuilder::new()
@@ -658,7 +685,7 @@ fn collider_shape_into_native_shape(
 ///
 /// This is almost one-to-one copy of Rapier's integration parameters with custom attributes for
 /// each parameter.
-#[derive(Copy, Clone, Visit, Inspect, Debug)]
+#[derive(Copy, Clone, Visit, Inspect, Debug, PartialEq)]
 pub struct IntegrationParameters {
     /// The timestep length (default: `1.0 / 60.0`)
     #[inspect(
@@ -812,69 +839,236 @@ impl Default for IntegrationParameters {
     }
 }
 
+/// Defines length of a simulation step of a physics world.
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Visit, Inspect, EnumVariantNames, EnumString, AsRefStr,
+)]
+#[repr(u32)]
+pub enum PhysicsStepMode {
+    /// The step is `dt` of integration parameters multiplied by time scale of the scene. It is
+    /// the most stable mode, but the speed of the simulation depends on frame rate, if the frame
+    /// rate is not fixed.
+    Fixed = 0,
+    /// The step is the (scaled) time that have passed since the previous update of the scene. The
+    /// simulation runs in real time at any frame rate, but it is less stable, especially with long
+    /// frames.
+    Variable,
+}
+
+impl Default for PhysicsStepMode {
+    fn default() -> Self {
+        Self::Fixed
+    }
+}
+
+/// Settings of a physics world. They're saved together with the scene and applied to the world
+/// every time they're changed, so they could be freely modified at runtime (for example, gravity
+/// could be changed from a script via `scene.graph.physics.settings.gravity`).
+#[derive(Copy, Clone, Debug, PartialEq, Inspect)]
+pub struct PhysicsSettings {
+    /// Gravity vector of the world (default: `(0.0, -9.81, 0.0)`).
+    #[inspect(description = "Gravity vector of the world (default: `(0.0, -9.81, 0.0)`).")]
+    pub gravity: Vector3<f32>,
+
+    /// Parameters of the solver, including amount of solver iterations.
+    pub integration_parameters: IntegrationParameters,
+
+    /// Defines length of a simulation step, see [`PhysicsStepMode`].
+    pub step_mode: PhysicsStepMode,
+
+    /// Enables continuous collision detection for every rigid body of the world, regardless of
+    /// CCD flag of a body (default: `false`).
+    #[inspect(description = "Enables continuous collision detection for every rigid body of \
+    the world, regardless of CCD flag of a body (default: `false`).")]
+    pub ccd_enabled_by_default: bool,
+
+    /// Rigid bodies with linear velocity below this value for some time fall asleep.
+    #[inspect(
+        min_value = 0.0,
+        description = "Rigid bodies with linear velocity below this value for some time fall asleep."
+    )]
+    pub linear_sleep_threshold: f32,
+
+    /// Rigid bodies with angular velocity below this value for some time fall asleep.
+    #[inspect(
+        min_value = 0.0,
+        description = "Rigid bodies with angular velocity below this value for some time fall asleep."
+    )]
+    pub angular_sleep_threshold: f32,
+
+    /// Smooths rendered movement of dynamic rigid bodies (default: `false`). It works only with
+    /// [`PhysicsStepMode::Fixed`]: the world is stepped as many times as fit in the time of a
+    /// frame, and rendered transforms of bodies (and their descendants) are interpolated between
+    /// poses of two last steps. Transforms of nodes, that are seen by game code, are not affected.
+    #[inspect(description = "Smooths rendered movement of dynamic rigid bodies. It works only \
+    with fixed step mode: the world is stepped as many times as fit in the time of a frame, and \
+    rendered transforms of bodies are interpolated between poses of two last steps.")]
+    pub interpolation: bool,
+}
+
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        Self {
+            gravity: Vector3::new(0.0, -9.81, 0.0),
+            integration_parameters: Default::default(),
+            step_mode: Default::default(),
+            ccd_enabled_by_default: false,
+            linear_sleep_threshold: RigidBodyActivation::default_linear_threshold(),
+            angular_sleep_threshold: RigidBodyActivation::default_angular_threshold(),
+            interpolation: false,
+        }
+    }
+}
+
+impl PhysicsSettings {
+    // Returns rapier's integration parameters for a step.
+    fn step_parameters(
+        &self,
+        dt: f32,
+        time_scale: f32,
+    ) -> rapier3d::dynamics::IntegrationParameters {
+        let parameters = &self.integration_parameters;
+        rapier3d::dynamics::IntegrationParameters {
+            dt: match self.step_mode {
+                PhysicsStepMode::Fixed => parameters.dt * time_scale,
+                PhysicsStepMode::Variable => dt,
+            },
+            min_ccd_dt: parameters.min_ccd_dt * time_scale,
+            erp: parameters.erp,
+            damping_ratio: parameters.damping_ratio,
+            joint_erp: parameters.joint_erp,
+            joint_damping_ratio: parameters.joint_damping_ratio,
+            allowed_linear_error: parameters.allowed_linear_error,
+            max_penetration_correction: parameters.max_penetration_correction,
+            prediction_distance: parameters.prediction_distance,
+            max_velocity_iterations: parameters.max_velocity_iterations as usize,
+            max_velocity_friction_iterations: parameters.max_velocity_friction_iterations
+                as usize,
+            max_stabilization_iterations: parameters.max_stabilization_iterations as usize,
+            interleave_restitution_and_friction_resolution: parameters
+                .interleave_restitution_and_friction_resolution,
+            min_island_size: parameters.min_island_size as usize,
+            max_ccd_substeps: parameters.max_ccd_substeps as usize,
+        }
+    }
+
+    // Settings of native rigid bodies are changed.
+    fn affects_bodies(&self, other: &Self) -> bool {
+        self.ccd_enabled_by_default != other.ccd_enabled_by_default
+            || self.linear_sleep_threshold != other.linear_sleep_threshold
+            || self.angular_sleep_threshold != other.angular_sleep_threshold
+    }
+
+    fn is_interpolated(&self) -> bool {
+        self.interpolation && self.step_mode == PhysicsStepMode::Fixed
+    }
+
+    // Applies sleep thresholds to a native rigid body that is allowed to sleep.
+    fn apply_sleep_thresholds(&self, activation: &mut RigidBodyActivation) {
+        activation.linear_threshold = self.linear_sleep_threshold;
+        activation.angular_threshold = self.angular_sleep_threshold;
+    }
+}
+
 /// Physics world is responsible for physics simulation in the engine. There is a very few public
 /// methods, mostly for ray casting. You should add physical entities using scene graph nodes, such
 /// as RigidBody, Collider, Joint.
-#[derive(Visit, Inspect)]
+#[derive(Inspect)]
 pub struct PhysicsWorld {
     /// A flag that defines whether physics simulation is enabled or not.
     pub enabled: bool,
 
-    /// A set of parameters that define behavior of every rigid body.
-    pub integration_parameters: IntegrationParameters,
+    /// Settings of the world, see [`PhysicsSettings`] for more info.
+    pub settings: PhysicsSettings,
 
-    /// Current gravity vector. Default is (0.0, -9.81, 0.0)
-    pub gravity: Vector3<f32>,
+    // Settings that were applied to native rigid bodies.
+    #[inspect(skip)]
+    applied_settings: PhysicsSettings,
 
     /// Performance statistics of a single simulation step.
-    #[visit(skip)]
     #[inspect(skip)]
     pub performance_statistics: PhysicsPerformanceStatistics,
 
     // Current physics pipeline.
-    #[visit(skip)]
     #[inspect(skip)]
     pipeline: PhysicsPipeline,
     // Broad phase performs rough intersection checks.
-    #[visit(skip)]
     #[inspect(skip)]
     broad_phase: BroadPhase,
     // Narrow phase is responsible for precise contact generation.
-    #[visit(skip)]
     #[inspect(skip)]
     narrow_phase: NarrowPhase,
     // A continuous collision detection solver.
-    #[visit(skip)]
     #[inspect(skip)]
     ccd_solver: CCDSolver,
     // Structure responsible for maintaining the set of active rigid-bodies, and putting non-moving
     // rigid-bodies to sleep to save computation times.
-    #[visit(skip)]
     #[inspect(skip)]
     islands: IslandManager,
     // A container of rigid bodies.
-    #[visit(skip)]
     #[inspect(skip)]
     bodies: Container<RigidBodySet, RigidBodyHandle>,
     // A container of colliders.
-    #[visit(skip)]
     #[inspect(skip)]
     colliders: Container<ColliderSet, ColliderHandle>,
     // A container of impulse joints.
-    #[visit(skip)]
     #[inspect(skip)]
     joints: Container<ImpulseJointSet, ImpulseJointHandle>,
     // A container of multibody joints.
-    #[visit(skip)]
     #[inspect(skip)]
     multibody_joints: Container<MultibodyJointSet, MultibodyJointHandle>,
     // Event handler collects info about contacts and proximity events.
-    #[visit(skip)]
     #[inspect(skip)]
     event_handler: Box<dyn EventHandler>,
-    #[visit(skip)]
     #[inspect(skip)]
     query: RefCell<QueryPipeline>,
+    // Scaled time that is not simulated yet, it is used only with interpolation.
+    #[inspect(skip)]
+    accumulator: f32,
+    // Position between poses of two last steps that is used to interpolate rendered transforms.
+    #[inspect(skip)]
+    interpolation_alpha: f32,
+    // Poses of dynamic bodies before the last step, see `PhysicsSettings::interpolation`.
+    #[inspect(skip)]
+    previous_poses: FxHashMap<RigidBodyHandle, Isometry3<f32>>,
+}
+
+/// Max amount of simulation steps per update with interpolation, the rest of the time is dropped
+/// to not stall the game when the simulation cannot keep up with the frame rate.
+const MAX_INTERPOLATED_STEPS: usize = 8;
+
+impl Visit for PhysicsWorld {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.enabled.visit("Enabled", &mut region)?;
+        // Settings are stored directly in the region of the world to be able to load scenes that
+        // were saved before the settings were introduced.
+        self.settings
+            .integration_parameters
+            .visit("IntegrationParameters", &mut region)?;
+        self.settings.gravity.visit("Gravity", &mut region)?;
+        // Backward compatibility.
+        let _ = self.settings.step_mode.visit("StepMode", &mut region);
+        let _ = self
+            .settings
+            .ccd_enabled_by_default
+            .visit("CcdEnabledByDefault", &mut region);
+        let _ = self
+            .settings
+            .linear_sleep_threshold
+            .visit("LinearSleepThreshold", &mut region);
+        let _ = self
+            .settings
+            .angular_sleep_threshold
+            .visit("AngularSleepThreshold", &mut region);
+        let _ = self
+            .settings
+            .interpolation
+            .visit("Interpolation", &mut region);
+
+        Ok(())
+    }
 }
 
 fn draw_shape(shape: &dyn Shape, transform: Matrix4<f32>, context: &mut SceneDrawingContext) {
@@ -974,7 +1168,7 @@ fn draw_shape(shape: &dyn Shape, transform: Matrix4<f32>, context: &mut SceneDra
     }
 }
 
-fn isometry_from_global_transform(transform: &Matrix4<f32>) -> Isometry3<f32> {
+pub(super) fn isometry_from_global_transform(transform: &Matrix4<f32>) -> Isometry3<f32> {
     Isometry3 {
         translation: Translation3::new(transform[12], transform[13], transform[14]),
         rotation: UnitQuaternion::from_matrix(&transform.basis()),
@@ -987,8 +1181,8 @@ impl PhysicsWorld {
         Self {
             enabled: true,
             pipeline: PhysicsPipeline::new(),
-            gravity: Vector3::new(0.0, -9.81, 0.0),
-            integration_parameters: IntegrationParameters::default(),
+            settings: Default::default(),
+            applied_settings: Default::default(),
             broad_phase: BroadPhase::new(),
             narrow_phase: NarrowPhase::new(),
             ccd_solver: CCDSolver::new(),
@@ -1012,59 +1206,121 @@ impl PhysicsWorld {
             event_handler: Box::new(()),
             query: RefCell::new(Default::default()),
             performance_statistics: Default::default(),
+            accumulator: 0.0,
+            interpolation_alpha: 1.0,
+            previous_poses: Default::default(),
         }
     }
 
-    pub(super) fn update(&mut self) {
+    /// Performs a single simulation step. Length of the step is defined by the step mode of the
+    /// settings, `dt` is the scaled time that have passed since the previous update. Zero time
+    /// scale means that there is nothing to simulate and the step is skipped. With interpolation
+    /// (see [`PhysicsSettings::interpolation`]) the world is stepped as many times as fit in the
+    /// accumulated time instead.
+    pub(super) fn update(&mut self, dt: f32, time_scale: f32) {
         let time = instant::Instant::now();
 
-        if self.enabled {
-            let integration_parameters = rapier3d::dynamics::IntegrationParameters {
-                dt: self.integration_parameters.dt,
-                min_ccd_dt: self.integration_parameters.min_ccd_dt,
-                erp: self.integration_parameters.erp,
-                damping_ratio: self.integration_parameters.damping_ratio,
-                joint_erp: self.integration_parameters.joint_erp,
-                joint_damping_ratio: self.integration_parameters.joint_damping_ratio,
-                allowed_linear_error: self.integration_parameters.allowed_linear_error,
-                max_penetration_correction: self.integration_parameters.max_penetration_correction,
-                prediction_distance: self.integration_parameters.prediction_distance,
-                max_velocity_iterations: self.integration_parameters.max_velocity_iterations
-                    as usize,
-                max_velocity_friction_iterations: self
-                    .integration_parameters
-                    .max_velocity_friction_iterations
-                    as usize,
-                max_stabilization_iterations: self
-                    .integration_parameters
-                    .max_stabilization_iterations
-                    as usize,
-                interleave_restitution_and_friction_resolution: self
-                    .integration_parameters
-                    .interleave_restitution_and_friction_resolution,
-                min_island_size: self.integration_parameters.min_island_size as usize,
-                max_ccd_substeps: self.integration_parameters.max_ccd_substeps as usize,
-            };
-
-            self.pipeline.step(
-                &self.gravity,
-                &integration_parameters,
-                &mut self.islands,
-                &mut self.broad_phase,
-                &mut self.narrow_phase,
-                &mut self.bodies.set,
-                &mut self.colliders.set,
-                &mut self.joints.set,
-                &mut self.multibody_joints.set,
-                &mut self.ccd_solver,
-                &(),
-                &*self.event_handler,
-            );
+        if !self.settings.is_interpolated() {
+            self.accumulator = 0.0;
+            self.interpolation_alpha = 1.0;
+            self.previous_poses.clear();
+        }
+
+        if self.enabled && time_scale > 0.0 {
+            let integration_parameters = self.settings.step_parameters(dt, time_scale);
+
+            if self.settings.is_interpolated() && integration_parameters.dt > 0.0 {
+                self.accumulator += dt;
+
+                let mut steps = 0;
+                while self.accumulator >= integration_parameters.dt
+                    && steps < MAX_INTERPOLATED_STEPS
+                {
+                    self.accumulator -= integration_parameters.dt;
+                    self.save_previous_poses();
+                    self.step(&integration_parameters);
+                    steps += 1;
+                }
+
+                self.accumulator = self.accumulator.min(integration_parameters.dt);
+                self.interpolation_alpha = self.accumulator / integration_parameters.dt;
+            } else {
+                self.step(&integration_parameters);
+            }
         }
 
         self.performance_statistics.step_time += instant::Instant::now() - time;
     }
 
+    fn step(&mut self, integration_parameters: &rapier3d::dynamics::IntegrationParameters) {
+        self.pipeline.step(
+            &self.settings.gravity,
+            integration_parameters,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies.set,
+            &mut self.colliders.set,
+            &mut self.joints.set,
+            &mut self.multibody_joints.set,
+            &mut self.ccd_solver,
+            &(),
+            &*self.event_handler,
+        );
+    }
+
+    fn save_previous_poses(&mut self) {
+        self.previous_poses.clear();
+        for (handle, body) in self.bodies.set.iter() {
+            // Kinematic bodies are moved by game code (or animations), they're not interpolated.
+            if body.body_type() == RigidBodyType::Dynamic {
+                self.previous_poses.insert(handle, *body.position());
+            }
+        }
+    }
+
+    /// Returns a pose of the given rigid body, that is interpolated between poses of two last
+    /// simulation steps. `None` is returned if the body is not interpolated.
+    pub(crate) fn interpolated_pose(
+        &self,
+        rigid_body: &scene::rigidbody::RigidBody,
+    ) -> Option<Isometry3<f32>> {
+        if !self.enabled || !self.settings.is_interpolated() {
+            return None;
+        }
+
+        let handle = rigid_body.native.get();
+        let previous = self.previous_poses.get(&handle)?;
+        let current = self.bodies.set.get(handle)?.position();
+        Some(previous.lerp_slerp(current, self.interpolation_alpha))
+    }
+
+    /// Applies settings, that affect rigid bodies (CCD and sleep thresholds), to every native
+    /// rigid body. It does nothing if the settings weren't changed since the last call.
+    pub(crate) fn sync_settings(&mut self, nodes: &NodePool) {
+        if !self.settings.affects_bodies(&self.applied_settings) {
+            return;
+        }
+
+        for (&native, &owner) in self.bodies.map.forward_map() {
+            if let (Some(body), Some(rigid_body_node)) = (
+                self.bodies.set.get_mut(native),
+                nodes
+                    .try_borrow(owner)
+                    .and_then(|n| n.cast::<scene::rigidbody::RigidBody>()),
+            ) {
+                body.enable_ccd(
+                    rigid_body_node.is_ccd_enabled() || self.settings.ccd_enabled_by_default,
+                );
+                if rigid_body_node.is_can_sleep() {
+                    self.settings.apply_sleep_thresholds(body.activation_mut());
+                }
+            }
+        }
+
+        self.applied_settings = self.settings;
+    }
+
     pub(super) fn add_body(&mut self, owner: Handle<Node>, body: RigidBody) -> RigidBodyHandle {
         let handle = self.bodies.set.insert(body);
         self.bodies.map.insert(handle, owner);
@@ -1072,7 +1328,27 @@ impl PhysicsWorld {
     }
 
     pub(crate) fn remove_body(&mut self, handle: RigidBodyHandle) {
-        assert!(self.bodies.map.remove_by_key(&handle).is_some());
+        if self.bodies.map.remove_by_key(&handle).is_none() {
+            return;
+        }
+
+        // Attached colliders and joints are removed together with the body, forget them too.
+        if let Some(body) = self.bodies.set.get(handle) {
+            for collider in body.colliders() {
+                self.colliders.map.remove_by_key(collider);
+            }
+        }
+        let attached_joints = self
+            .joints
+            .set
+            .iter()
+            .filter(|(_, joint)| joint.body1 == handle || joint.body2 == handle)
+            .map(|(joint_handle, _)| joint_handle)
+            .collect::<Vec<_>>();
+        for joint in attached_joints {
+            self.joints.map.remove_by_key(&joint);
+        }
+
         self.bodies.set.remove(
             handle,
             &mut self.islands,
@@ -1124,10 +1400,11 @@ impl PhysicsWorld {
     }
 
     pub(crate) fn remove_joint(&mut self, handle: ImpulseJointHandle) {
-        assert!(self.joints.map.remove_by_key(&handle).is_some());
-        self.joints
-            .set
-            .remove(handle, &mut self.islands, &mut self.bodies.set, false);
+        if self.joints.map.remove_by_key(&handle).is_some() {
+            self.joints
+                .set
+                .remove(handle, &mut self.islands, &mut self.bodies.set, false);
+        }
     }
 
     /// Draws physics world. Very useful for debugging, it allows you to see where are
@@ -1206,8 +1483,23 @@ impl PhysicsWorld {
         new_global_transform: &Matrix4<f32>,
     ) {
         if let Some(native) = self.bodies.set.get_mut(rigid_body.native.get()) {
+            let new_position = isometry_from_global_transform(new_global_transform);
+
+            // Global transform of the node is calculated before the simulation, so it differs
+            // from the transform that was synced with the body after the last step. This is not
+            // a teleport and the pose of the body must be kept as is.
+            let position = native.position();
+            if (position.translation.vector - new_position.translation.vector).norm() <= 1.0e-5
+                && position.rotation.angle_to(&new_position.rotation) <= 1.0e-5
+            {
+                return;
+            }
+
+            // The body is teleported, there is nothing to interpolate until the next step.
+            self.previous_poses.remove(&rigid_body.native.get());
+
             native.set_position(
-                isometry_from_global_transform(new_global_transform),
+                new_position,
                 // Do not wake up body, it is too expensive and must be done **only** by explicit
                 // `wake_up` call!
                 false,
@@ -1215,6 +1507,18 @@ impl PhysicsWorld {
         }
     }
 
+    /// Returns current position of the native rigid body of the given node. `None` is returned if
+    /// the node has no native body (for example if it is disabled).
+    pub(crate) fn rigid_body_position(
+        &self,
+        rigid_body: &scene::rigidbody::RigidBody,
+    ) -> Option<Vector3<f32>> {
+        self.bodies
+            .set
+            .get(rigid_body.native.get())
+            .map(|native| *native.translation())
+    }
+
     pub(crate) fn sync_rigid_body_node(
         &mut self,
         rigid_body: &mut scene::rigidbody::RigidBody,
@@ -1256,6 +1560,16 @@ impl PhysicsWorld {
         handle: Handle<Node>,
         rigid_body_node: &scene::rigidbody::RigidBody,
     ) {
+        // Disabled rigid bodies do not participate in simulation, the native body is removed
+        // and it will be re-created from the actual state of the node when it is enabled again.
+        if !rigid_body_node.is_globally_enabled() {
+            if rigid_body_node.native.get() != RigidBodyHandle::invalid() {
+                self.remove_body(rigid_body_node.native.get());
+                rigid_body_node.native.set(RigidBodyHandle::invalid());
+            }
+            return;
+        }
+
         // Important notes!
         // 1) `get_mut` is **very** expensive because it forces physics engine to recalculate contacts
         //    and a lot of other stuff, this is why we need `anything_changed` flag.
@@ -1285,16 +1599,14 @@ impl PhysicsWorld {
                     rigid_body_node
                         .ang_damping
                         .try_sync_model(|v| native.set_angular_damping(v));
-                    rigid_body_node
-                        .ccd_enabled
-                        .try_sync_model(|v| native.enable_ccd(v));
+                    let settings = &self.settings;
+                    rigid_body_node.ccd_enabled.try_sync_model(|v| {
+                        native.enable_ccd(v || settings.ccd_enabled_by_default)
+                    });
                     rigid_body_node.can_sleep.try_sync_model(|v| {
                         let mut activation = native.activation_mut();
                         if v {
-                            activation.linear_threshold =
-                                RigidBodyActivation::default_linear_threshold();
-                            activation.angular_threshold =
-                                RigidBodyActivation::default_angular_threshold();
+                            settings.apply_sleep_thresholds(activation);
                         } else {
                             activation.sleeping = false;
                             activation.linear_threshold = -1.0;
@@ -1359,7 +1671,9 @@ impl PhysicsWorld {
                 .position(isometry_from_global_transform(
                     &rigid_body_node.global_transform(),
                 ))
-                .ccd_enabled(rigid_body_node.is_ccd_enabled())
+                .ccd_enabled(
+                    rigid_body_node.is_ccd_enabled() || self.settings.ccd_enabled_by_default,
+                )
                 .additional_mass(rigid_body_node.mass())
                 .angvel(*rigid_body_node.ang_vel)
                 .linvel(*rigid_body_node.lin_vel)
@@ -1379,9 +1693,12 @@ impl PhysicsWorld {
                 builder = builder.lock_translations();
             }
 
-            rigid_body_node
-                .native
-                .set(self.add_body(handle, builder.build()));
+            let mut body = builder.build();
+            if rigid_body_node.is_can_sleep() {
+                self.settings.apply_sleep_thresholds(body.activation_mut());
+            }
+
+            rigid_body_node.native.set(self.add_body(handle, body));
 
             Log::writeln(
                 MessageKind::Information,
@@ -1399,6 +1716,19 @@ impl PhysicsWorld {
         handle: Handle<Node>,
         collider_node: &scene::collider::Collider,
     ) {
+        // Native collider is removed if the collider is disabled or if it was removed together
+        // with its rigid body, it will be re-created when both are enabled again.
+        let native = collider_node.native.get();
+        if native != ColliderHandle::invalid()
+            && (!collider_node.is_globally_enabled() || self.colliders.set.get(native).is_none())
+        {
+            self.remove_collider(native);
+            collider_node.native.set(ColliderHandle::invalid());
+        }
+        if !collider_node.is_globally_enabled() {
+            return;
+        }
+
         let anything_changed =
             collider_node.transform_modified.get() || collider_node.needs_sync_model();
 
@@ -1518,56 +1848,88 @@ impl PhysicsWorld {
         handle: Handle<Node>,
         joint: &scene::joint::Joint,
     ) {
-        if let Some(native) = self.joints.set.get_mut(joint.native.get()) {
-            joint
-                .params
-                .try_sync_model(|v| native.data = convert_joint_params(v));
-            joint.body1.try_sync_model(|v| {
-                if let Some(rigid_body_node) = nodes
-                    .try_borrow(v)
-                    .and_then(|n| n.cast::<scene::rigidbody::RigidBody>())
-                {
-                    native.body1 = rigid_body_node.native.get();
-                }
-            });
-            joint.body2.try_sync_model(|v| {
-                if let Some(rigid_body_node) = nodes
-                    .try_borrow(v)
-                    .and_then(|n| n.cast::<scene::rigidbody::RigidBody>())
-                {
-                    native.body2 = rigid_body_node.native.get();
-                }
-            });
-        } else {
-            let body1_handle = joint.body1();
-            let body2_handle = joint.body2();
-            let params = joint.params().clone();
+        let was_active = joint.native.get() != ImpulseJointHandle::invalid();
 
-            // A native joint can be created iff both rigid bodies are correctly assigned.
-            if let (Some(body1), Some(body2)) = (
-                nodes
-                    .try_borrow(body1_handle)
-                    .and_then(|n| n.cast::<scene::rigidbody::RigidBody>()),
-                nodes
-                    .try_borrow(body2_handle)
-                    .and_then(|n| n.cast::<scene::rigidbody::RigidBody>()),
-            ) {
-                let native_body1 = body1.native.get();
-                let native_body2 = body2.native.get();
+        // Native joint is removed if the joint is disabled or if it was removed together with
+        // one of its rigid bodies, it will be re-created when everything is enabled again.
+        let native = joint.native.get();
+        if native != ImpulseJointHandle::invalid()
+            && (!joint.is_globally_enabled() || self.joints.set.get(native).is_none())
+        {
+            self.remove_joint(native);
+            joint.native.set(ImpulseJointHandle::invalid());
+        }
+        if !joint.is_globally_enabled() {
+            return;
+        }
 
-                let native = self.add_joint(
-                    handle,
-                    native_body1,
-                    native_body2,
-                    convert_joint_params(params),
-                );
+        // Changes of the bodies are detected by comparing native handles below.
+        joint.body1.try_sync_model(|_| ());
+        joint.body2.try_sync_model(|_| ());
+
+        match joint_native_bodies(nodes, joint) {
+            Ok((native_body1, native_body2)) => {
+                if let Some(native) = self.joints.set.get_mut(joint.native.get()) {
+                    if native.body1 == native_body1 && native.body2 == native_body2 {
+                        joint
+                            .params
+                            .try_sync_model(|v| native.data = convert_joint_params(v));
+                    } else {
+                        // One of the bodies was replaced or its native body was re-created, the
+                        // joint must be re-created to be attached to the actual bodies.
+                        self.remove_joint(joint.native.get());
+                        joint.native.set(ImpulseJointHandle::invalid());
+                    }
+                }
 
-                joint.native.set(native);
+                if joint.native.get() == ImpulseJointHandle::invalid() {
+                    let native = self.add_joint(
+                        handle,
+                        native_body1,
+                        native_body2,
+                        convert_joint_params(joint.params().clone()),
+                    );
 
-                Log::writeln(
-                    MessageKind::Information,
-                    format!("Native joint was created for node {}", joint.name()),
-                );
+                    joint.native.set(native);
+
+                    if joint.deactivated.replace(false) {
+                        Log::info(format!(
+                            "Joint {} was re-activated, its bodies are valid again.",
+                            joint.name()
+                        ));
+                    } else {
+                        Log::writeln(
+                            MessageKind::Information,
+                            format!("Native joint was created for node {}", joint.name()),
+                        );
+                    }
+                }
+            }
+            Err(reason) => {
+                if joint.native.get() != ImpulseJointHandle::invalid() {
+                    self.remove_joint(joint.native.get());
+                    joint.native.set(ImpulseJointHandle::invalid());
+                }
+
+                // Report only when an active joint breaks, a joint which was never bound (for
+                // example, its bodies are not assigned yet) is not an error.
+                if was_active {
+                    joint.deactivated.set(true);
+
+                    let name = |handle: Handle<Node>| {
+                        nodes
+                            .try_borrow(handle)
+                            .map_or_else(|| handle.to_string(), |n| n.name().to_owned())
+                    };
+                    Log::warn(format!(
+                        "Joint {} was deactivated, it cannot connect {} and {}: {}. The joint \
+                        will be re-activated when its bodies are valid again.",
+                        joint.name(),
+                        name(joint.body1()),
+                        name(joint.body2()),
+                        reason
+                    ));
+                }
             }
         }
     }
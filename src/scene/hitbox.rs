@@ -0,0 +1,354 @@
+//! Hitboxes are colliders attached to bones of a skeleton, they're used for per-limb hit detection
+//! (for example, to apply more damage on a headshot). A hitbox is a kinematic rigid body linked to
+//! a bone, with a single collider that is a member of [`HITBOX_COLLISION_GROUP`]. Hitboxes follow
+//! animated bones, but they're never simulated and do not push other bodies - their colliders do
+//! not participate in collision response, they're only visible to ray casts and intersection
+//! queries.
+//!
+//! Use [`generate_hitboxes`] to create hitboxes for bones of a skinned mesh and
+//! [`cast_ray_hitboxes`] to find out which bone was hit.
+//!
+//! # Collision groups
+//!
+//! By default every collider is a member of every collision group, so ray casts with default
+//! options will hit hitboxes too. Exclude [`HITBOX_COLLISION_GROUP`] from memberships of your
+//! colliders (or from the filter of your ray casts) to make hitboxes invisible for regular
+//! queries.
+
+use crate::{
+    core::{
+        algebra::{Point3, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+    },
+    scene::{
+        base::BaseBuilder,
+        collider::{Collider, ColliderBuilder, ColliderShape, InteractionGroups},
+        graph::{
+            physics::{Intersection, RayCastOptions},
+            Graph,
+        },
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            Mesh,
+        },
+        node::Node,
+        rigidbody::{RigidBodyBuilder, RigidBodyType},
+    },
+};
+
+/// A collision group that contains colliders of hitboxes only.
+pub const HITBOX_COLLISION_GROUP: u32 = 1 << 31;
+
+/// Returns collision groups of hitbox colliders - they're the members of
+/// [`HITBOX_COLLISION_GROUP`] and can be found by any query.
+pub fn hitbox_collision_groups() -> InteractionGroups {
+    InteractionGroups::new(HITBOX_COLLISION_GROUP, u32::MAX)
+}
+
+/// Returns `true` if the node is a collider of a hitbox.
+pub fn is_hitbox(node: &Node) -> bool {
+    node.cast::<Collider>()
+        .map_or(false, |c| c.collision_groups() == hitbox_collision_groups())
+}
+
+/// Returns `true` if the node is a rigid body of a hitbox, see [`make_hitbox`].
+pub fn is_hitbox_body(graph: &Graph, handle: Handle<Node>) -> bool {
+    graph.try_get(handle).map_or(false, |node| {
+        node.is_rigid_body()
+            && node
+                .children()
+                .iter()
+                .any(|&child| is_hitbox(&graph[child]))
+    })
+}
+
+/// Fits a capsule to vertices of the mesh that are mostly affected by the bone. The capsule is
+/// defined in the space of the bone. Returns `None` if there is no such vertices.
+pub fn fit_bone_capsule(
+    graph: &Graph,
+    mesh: Handle<Node>,
+    bone: Handle<Node>,
+) -> Option<ColliderShape> {
+    let mesh = graph.try_get(mesh)?.cast::<Mesh>()?;
+    let inv_bind_pose = graph.try_get(bone)?.inv_bind_pose_transform();
+
+    let mut bounds = AxisAlignedBoundingBox::default();
+    let mut is_empty = true;
+
+    for surface in mesh.surfaces() {
+        let index = match surface.bones().iter().position(|&b| b == bone) {
+            Some(index) => index,
+            None => continue,
+        };
+
+        let data = surface.data();
+        let data = data.lock();
+        for view in data.vertex_buffer.iter() {
+            if let (Ok(position), Ok(indices), Ok(weights)) = (
+                view.read_3_f32(VertexAttributeUsage::Position),
+                view.read_4_u8(VertexAttributeUsage::BoneIndices),
+                view.read_4_f32(VertexAttributeUsage::BoneWeight),
+            ) {
+                // A vertex belongs to the bone with the largest weight.
+                let (weight, dominant) = weights.iter().zip(indices.iter()).fold(
+                    (0.0, 0),
+                    |(max_weight, max_index), (&weight, &index)| {
+                        if weight > max_weight {
+                            (weight, index)
+                        } else {
+                            (max_weight, max_index)
+                        }
+                    },
+                );
+
+                if weight > 0.0 && dominant as usize == index {
+                    bounds.add_point(
+                        inv_bind_pose
+                            .transform_point(&Point3::from(position))
+                            .coords,
+                    );
+                    is_empty = false;
+                }
+            }
+        }
+    }
+
+    if is_empty {
+        None
+    } else {
+        Some(ColliderShape::capsule_from_bounds(&bounds))
+    }
+}
+
+/// Creates a hitbox - kinematic rigid body with a collider of the given shape. The body is not
+/// linked to anything, link it to a bone and the hitbox will follow the bone.
+pub fn make_hitbox(graph: &mut Graph, name: &str, shape: ColliderShape) -> Handle<Node> {
+    let collider = ColliderBuilder::new(BaseBuilder::new().with_name(format!("{}Collider", name)))
+        .with_shape(shape)
+        .with_collision_groups(hitbox_collision_groups())
+        // Hitboxes must not push anything.
+        .with_solver_groups(InteractionGroups::new(0, 0))
+        .build(graph);
+
+    RigidBodyBuilder::new(
+        BaseBuilder::new()
+            .with_name(name)
+            .with_children(&[collider]),
+    )
+    .with_body_type(RigidBodyType::KinematicPositionBased)
+    .build(graph)
+}
+
+/// Creates a hitbox with auto-fitted capsule (see [`fit_bone_capsule`]) for each bone of the
+/// skinned mesh and links them to the bones. Bones without vertices are skipped. Returns handles
+/// of rigid bodies of the hitboxes.
+pub fn generate_hitboxes(
+    graph: &mut Graph,
+    mesh: Handle<Node>,
+    bones: &[Handle<Node>],
+) -> Vec<Handle<Node>> {
+    let mut hitboxes = Vec::new();
+    for &bone in bones {
+        if let Some(shape) = fit_bone_capsule(graph, mesh, bone) {
+            let name = format!("{}Hitbox", graph[bone].name());
+            let hitbox = make_hitbox(graph, &name, shape);
+            graph.link_nodes(hitbox, bone);
+            hitboxes.push(hitbox);
+        }
+    }
+    hitboxes
+}
+
+/// Result of [`cast_ray_hitboxes`].
+#[derive(Clone, Debug)]
+pub struct HitboxHit {
+    /// A bone the hitbox is attached to.
+    pub bone: Handle<Node>,
+    /// Name of the bone.
+    pub bone_name: String,
+    /// A handle of the collider of the hitbox.
+    pub collider: Handle<Node>,
+    /// A position of the hit in world coordinates.
+    pub position: Vector3<f32>,
+    /// A normal at the hit position.
+    pub normal: Vector3<f32>,
+    /// Distance from the ray origin.
+    pub toi: f32,
+}
+
+/// Casts a ray against hitboxes only and returns the closest hit.
+pub fn cast_ray_hitboxes(
+    graph: &Graph,
+    origin: Vector3<f32>,
+    direction: Vector3<f32>,
+    max_len: f32,
+) -> Option<HitboxHit> {
+    let mut intersections: Vec<Intersection> = Vec::new();
+    graph.physics.cast_ray(
+        RayCastOptions {
+            ray_origin: Point3::from(origin),
+            ray_direction: direction,
+            max_len,
+            groups: InteractionGroups::new(HITBOX_COLLISION_GROUP, HITBOX_COLLISION_GROUP),
+            sort_results: true,
+        },
+        &mut intersections,
+    );
+
+    intersections.into_iter().find_map(|intersection| {
+        let collider = graph.try_get(intersection.collider)?;
+        if !is_hitbox(collider) {
+            return None;
+        }
+        let body = graph.try_get(collider.parent())?;
+        let bone = body.parent();
+        graph.try_get(bone).map(|bone_node| HitboxHit {
+            bone,
+            bone_name: bone_node.name_owned(),
+            collider: intersection.collider,
+            position: intersection.position.coords,
+            normal: intersection.normal,
+            toi: intersection.toi,
+        })
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{Animation, KeyFrame, Track},
+        core::{
+            algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+            math::TriangleDefinition,
+            parking_lot::Mutex,
+            pool::Handle,
+        },
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            hitbox::{cast_ray_hitboxes, generate_hitboxes, is_hitbox_body},
+            mesh::{
+                buffer::{TriangleBuffer, VertexBuffer},
+                surface::{SurfaceBuilder, SurfaceData},
+                vertex::AnimatedVertex,
+                MeshBuilder,
+            },
+            node::Node,
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+            Scene,
+        },
+    };
+    use std::sync::Arc;
+
+    fn make_bone(graph: &mut Graph, name: &str, position: Vector3<f32>) -> Handle<Node> {
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name(name)
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                )
+                .with_inv_bind_pose_transform(Matrix4::new_translation(&-position)),
+        )
+        .build(graph)
+    }
+
+    fn limb_vertices(bottom: f32, bone: u8) -> Vec<AnimatedVertex> {
+        [
+            Vector3::new(0.1, bottom + 0.1, 0.0),
+            Vector3::new(-0.1, bottom + 0.9, 0.0),
+            Vector3::new(0.0, bottom + 0.5, 0.1),
+            Vector3::new(0.0, bottom + 0.5, -0.1),
+        ]
+        .iter()
+        .map(|&position| AnimatedVertex {
+            position,
+            bone_weights: [1.0, 0.0, 0.0, 0.0],
+            bone_indices: [bone, 0, 0, 0],
+            ..Default::default()
+        })
+        .collect()
+    }
+
+    // An arm that points up in bind pose: upper arm bone at the origin and forearm bone one
+    // unit above it.
+    fn make_arm(graph: &mut Graph) -> (Handle<Node>, Handle<Node>, Handle<Node>) {
+        let upper_arm = make_bone(graph, "UpperArm", Vector3::default());
+        let forearm = make_bone(graph, "Forearm", Vector3::new(0.0, 1.0, 0.0));
+        graph.link_nodes(forearm, upper_arm);
+
+        let mut vertices = limb_vertices(0.0, 0);
+        vertices.extend(limb_vertices(1.0, 1));
+        let data = SurfaceData::new(
+            VertexBuffer::new(vertices.len(), AnimatedVertex::layout(), vertices).unwrap(),
+            TriangleBuffer::new(vec![
+                TriangleDefinition([0, 1, 2]),
+                TriangleDefinition([4, 5, 6]),
+            ]),
+            true,
+        );
+        let mesh = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(data)))
+                .with_bones(vec![upper_arm, forearm])
+                .build()])
+            .build(graph);
+
+        (mesh, upper_arm, forearm)
+    }
+
+    #[test]
+    fn test_ray_hits_animated_limb() {
+        let mut scene = Scene::new();
+        let (mesh, upper_arm, forearm) = make_arm(&mut scene.graph);
+
+        let hitboxes = generate_hitboxes(&mut scene.graph, mesh, &[upper_arm, forearm]);
+        assert_eq!(hitboxes.len(), 2);
+        assert!(hitboxes.iter().all(|&h| is_hitbox_body(&scene.graph, h)));
+
+        // Rotate the arm so it points along -X axis, the forearm is in [-2; -1] range then.
+        let mut track = Track::new();
+        track.set_node(upper_arm);
+        track.add_key_frame(KeyFrame::new(
+            0.0,
+            Vector3::default(),
+            Vector3::new(1.0, 1.0, 1.0),
+            UnitQuaternion::from_axis_angle(&Vector3::z_axis(), std::f32::consts::FRAC_PI_2),
+        ));
+        let mut animation = Animation::default();
+        animation.add_track(track);
+        let animation = scene.animations.add(animation);
+
+        for _ in 0..3 {
+            scene.update(Vector2::new(100.0, 100.0), 1.0 / 60.0);
+            // Only blended animations are applied automatically.
+            scene.animations[animation]
+                .get_pose()
+                .apply(&mut scene.graph);
+        }
+
+        let down = Vector3::new(0.0, -1.0, 0.0);
+
+        let hit = cast_ray_hitboxes(&scene.graph, Vector3::new(-1.5, 5.0, 0.0), down, 100.0)
+            .expect("Forearm must be hit");
+        assert_eq!(hit.bone, forearm);
+        assert_eq!(hit.bone_name, "Forearm");
+        assert!((hit.position.y - 0.1).abs() < 0.05);
+
+        let hit = cast_ray_hitboxes(&scene.graph, Vector3::new(-0.5, 5.0, 0.0), down, 100.0)
+            .expect("Upper arm must be hit");
+        assert_eq!(hit.bone, upper_arm);
+        assert_eq!(hit.bone_name, "UpperArm");
+
+        // The forearm was here in bind pose.
+        assert!(cast_ray_hitboxes(
+            &scene.graph,
+            Vector3::new(5.0, 1.5, 0.0),
+            Vector3::new(-1.0, 0.0, 0.0),
+            4.5
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,305 @@
+//! Hierarchical level of detail (HLOD) replaces a cluster of distant static meshes with a single
+//! merged and simplified proxy mesh.
+//!
+//! For more info see [`Hlod`]
+
+use crate::{
+    core::variable::{InheritError, TemplateVariable},
+    core::{
+        algebra::Vector3,
+        inspect::{Inspect, PropertyInfo},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    impl_directly_inheritable_entity_trait,
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait, TypeUuidProvider},
+        DirectlyInheritableEntity,
+    },
+};
+use fxhash::FxHashMap;
+use std::ops::{Deref, DerefMut};
+
+/// Hierarchical level of detail (HLOD) node switches between a set of original meshes and a single
+/// proxy mesh, that is a merged and simplified version of the originals. The proxy is shown when
+/// an observer is farther than [`Hlod::switch_distance`] from the center of the cluster, the
+/// originals are hidden at the same time. This turns hundreds of distant draw calls into one.
+///
+/// # Hysteresis
+///
+/// To prevent flickering when an observer stays near the switch distance, the switch happens with
+/// some hysteresis - the proxy is shown when the distance exceeds `switch_distance + hysteresis / 2`
+/// and it is hidden when the distance becomes less than `switch_distance - hysteresis / 2`.
+///
+/// # Baking
+///
+/// HLOD nodes are not supposed to be created manually, they're produced by the offline bake (see
+/// [`crate::utils::hlod`]), which also creates proxy meshes. The proxy is a child mesh of the HLOD
+/// node, the originals could be anywhere in the graph.
+///
+/// # Performance
+///
+/// Runtime cost of the node is just a distance check per observer, it is done together with the
+/// checks of LOD groups (see [`crate::scene::visibility::VisibilityCache`]).
+#[derive(Debug, Visit, Clone, Inspect)]
+pub struct Hlod {
+    base: Base,
+
+    #[inspect(min_value = 0.0, step = 1.0, getter = "Deref::deref")]
+    switch_distance: TemplateVariable<f32>,
+
+    #[inspect(min_value = 0.0, step = 0.5, getter = "Deref::deref")]
+    hysteresis: TemplateVariable<f32>,
+
+    #[inspect(skip)]
+    proxy: Handle<Node>,
+
+    #[inspect(skip)]
+    originals: Vec<Handle<Node>>,
+
+    // Bounds of the cluster in local coordinates of the node.
+    #[inspect(skip)]
+    bounds: AxisAlignedBoundingBox,
+
+    // Hash of the content the proxy was baked from, it is used to re-bake only changed clusters.
+    #[inspect(skip)]
+    content_hash: u64,
+}
+
+impl_directly_inheritable_entity_trait!(Hlod;
+    switch_distance,
+    hysteresis
+);
+
+impl Default for Hlod {
+    fn default() -> Self {
+        HlodBuilder::new(BaseBuilder::new()).build_hlod()
+    }
+}
+
+impl Deref for Hlod {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Hlod {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Hlod {
+    fn type_uuid() -> Uuid {
+        uuid!("0c2f6b7e-5d1a-4b8e-9f3c-7a4e2d6b1c95")
+    }
+}
+
+impl Hlod {
+    /// Sets new distance at which the proxy replaces the originals.
+    pub fn set_switch_distance(&mut self, distance: f32) {
+        self.switch_distance.set(distance.max(0.0));
+    }
+
+    /// Returns current distance at which the proxy replaces the originals.
+    pub fn switch_distance(&self) -> f32 {
+        *self.switch_distance
+    }
+
+    /// Sets new width of the distance band around the switch distance in which the current state
+    /// is kept, see [`Hlod`] docs for more info.
+    pub fn set_hysteresis(&mut self, hysteresis: f32) {
+        self.hysteresis.set(hysteresis.max(0.0));
+    }
+
+    /// Returns current width of the hysteresis band.
+    pub fn hysteresis(&self) -> f32 {
+        *self.hysteresis
+    }
+
+    /// Returns a handle of the proxy mesh.
+    pub fn proxy(&self) -> Handle<Node> {
+        self.proxy
+    }
+
+    /// Sets new proxy mesh.
+    pub fn set_proxy(&mut self, proxy: Handle<Node>) {
+        self.proxy = proxy;
+    }
+
+    /// Returns a list of original meshes, that are replaced by the proxy.
+    pub fn originals(&self) -> &[Handle<Node>] {
+        &self.originals
+    }
+
+    /// Sets new list of original meshes.
+    pub fn set_originals(&mut self, originals: Vec<Handle<Node>>) {
+        self.originals = originals;
+    }
+
+    /// Returns hash of the content the proxy was baked from.
+    pub fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    /// Returns `true` if the proxy should be shown for an observer at the given distance from the
+    /// center of the cluster. `proxy_shown` is the previous state for the observer, it is used to
+    /// apply hysteresis.
+    pub fn should_show_proxy(&self, distance: f32, proxy_shown: bool) -> bool {
+        let half_band = *self.hysteresis * 0.5;
+        if proxy_shown {
+            distance > *self.switch_distance - half_band
+        } else {
+            distance > *self.switch_distance + half_band
+        }
+    }
+
+    /// Returns distance from the given point to the center of the cluster.
+    pub fn distance_to(&self, point: Vector3<f32>) -> f32 {
+        self.world_bounding_box().center().metric_distance(&point)
+    }
+}
+
+impl NodeTrait for Hlod {
+    crate::impl_query_component!();
+
+    /// Returns current **local-space** bounding box of the cluster.
+    #[inline]
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.bounds
+    }
+
+    /// Returns current **world-space** bounding box of the cluster.
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    // Prefab inheritance resolving.
+    fn inherit(&mut self, parent: &Node) -> Result<(), InheritError> {
+        self.base.inherit_properties(parent)?;
+        if let Some(parent) = parent.cast::<Self>() {
+            self.try_inherit_self_properties(parent)?;
+        }
+        Ok(())
+    }
+
+    fn reset_inheritable_properties(&mut self) {
+        self.base.reset_inheritable_properties();
+        self.reset_self_inheritable_properties();
+    }
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        self.base.restore_resources(resource_manager);
+    }
+
+    fn remap_handles(&mut self, old_new_mapping: &FxHashMap<Handle<Node>, Handle<Node>>) {
+        self.base.remap_handles(old_new_mapping);
+
+        // Originals are usually not a part of copied hierarchy, they're kept as is then.
+        if let Some(proxy) = old_new_mapping.get(&self.proxy) {
+            self.proxy = *proxy;
+        }
+        for original in self.originals.iter_mut() {
+            if let Some(new_original) = old_new_mapping.get(original) {
+                *original = *new_original;
+            }
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create a HLOD node in a declarative manner.
+pub struct HlodBuilder {
+    base_builder: BaseBuilder,
+    switch_distance: f32,
+    hysteresis: f32,
+    proxy: Handle<Node>,
+    originals: Vec<Handle<Node>>,
+    bounds: AxisAlignedBoundingBox,
+    content_hash: u64,
+}
+
+impl HlodBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            switch_distance: 100.0,
+            hysteresis: 10.0,
+            proxy: Handle::NONE,
+            originals: Default::default(),
+            bounds: AxisAlignedBoundingBox::from_radius(0.0),
+            content_hash: 0,
+        }
+    }
+
+    /// Sets desired switch distance.
+    pub fn with_switch_distance(mut self, distance: f32) -> Self {
+        self.switch_distance = distance;
+        self
+    }
+
+    /// Sets desired width of the hysteresis band.
+    pub fn with_hysteresis(mut self, hysteresis: f32) -> Self {
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    /// Sets desired proxy mesh.
+    pub fn with_proxy(mut self, proxy: Handle<Node>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Sets desired list of original meshes.
+    pub fn with_originals(mut self, originals: Vec<Handle<Node>>) -> Self {
+        self.originals = originals;
+        self
+    }
+
+    /// Sets desired bounds of the cluster in local coordinates of the node.
+    pub fn with_bounds(mut self, bounds: AxisAlignedBoundingBox) -> Self {
+        self.bounds = bounds;
+        self
+    }
+
+    /// Sets hash of the content the proxy was baked from.
+    pub fn with_content_hash(mut self, content_hash: u64) -> Self {
+        self.content_hash = content_hash;
+        self
+    }
+
+    /// Creates new HLOD.
+    pub fn build_hlod(self) -> Hlod {
+        Hlod {
+            base: self.base_builder.build_base(),
+            switch_distance: self.switch_distance.max(0.0).into(),
+            hysteresis: self.hysteresis.max(0.0).into(),
+            proxy: self.proxy,
+            originals: self.originals,
+            bounds: self.bounds,
+            content_hash: self.content_hash,
+        }
+    }
+
+    /// Creates new HLOD node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_hlod())
+    }
+
+    /// Creates new instance of HLOD node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
@@ -0,0 +1,738 @@
+//! Inverse kinematics (IK) allows you to orient bones of a skeleton so the end of a chain of bones
+//! reaches a specific point.
+//!
+//! For more info see [`TwoBoneIk`]
+
+use crate::{
+    core::variable::{InheritError, TemplateVariable},
+    core::{
+        algebra::{Unit, UnitQuaternion, Vector3},
+        inspect::{Inspect, PropertyInfo},
+        math::{aabb::AxisAlignedBoundingBox, Matrix4Ext},
+        pool::Handle,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    impl_directly_inheritable_entity_trait,
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait, TypeUuidProvider},
+        DirectlyInheritableEntity,
+    },
+    utils::log::Log,
+};
+use fxhash::FxHashMap;
+use std::ops::{Deref, DerefMut};
+
+/// Local rotations of the bones before and after the last solution.
+#[derive(Debug, Clone, Copy)]
+struct AppliedRotation {
+    bone: Handle<Node>,
+    original: UnitQuaternion<f32>,
+    solved: UnitQuaternion<f32>,
+}
+
+/// Two-bone IK is a constraint that rotates two bones of a chain (for example thigh and calf, or
+/// upper arm and forearm) so the end of the chain (foot or hand) reaches a target. It is used for
+/// foot placement on slopes and stairs, to put hands on a weapon or a door handle, etc.
+///
+/// # Bones
+///
+/// The chain is defined by three nodes - [`TwoBoneIk::root`] (a hip), [`TwoBoneIk::middle`] (a
+/// knee) and [`TwoBoneIk::tip`] (an ankle). Only the root and the middle bones are rotated, the
+/// tip is the point that should reach the target. The middle bone must be a descendant of the
+/// root bone, and the tip must be a descendant of the middle bone. Lengths of the bones are
+/// taken from the current pose.
+///
+/// # Target
+///
+/// The target is either a node ([`TwoBoneIk::set_target`]) or a position in world coordinates
+/// ([`TwoBoneIk::set_target_position`]), the position takes priority. If the target is out of
+/// reach, the chain is fully stretched towards it. The optional pole node
+/// ([`TwoBoneIk::set_pole`]) defines the direction in which the middle joint bends - it is the
+/// point where the knee "looks at".
+///
+/// # Evaluation
+///
+/// The constraint is solved once per frame after the animations, so it corrects the animated
+/// pose. [`TwoBoneIk::weight`] defines how much of the solution is applied - `0.0` leaves the
+/// pose as is, `1.0` applies the full solution. The weight could be animated to smoothly enable
+/// or disable the constraint, for example when a character jumps.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::{algebra::Vector3, pool::Handle},
+///     scene::{base::BaseBuilder, graph::Graph, ik::TwoBoneIkBuilder, node::Node},
+/// };
+///
+/// fn create_leg_ik(
+///     graph: &mut Graph,
+///     hip: Handle<Node>,
+///     knee: Handle<Node>,
+///     ankle: Handle<Node>,
+///     knee_pole: Handle<Node>,
+/// ) -> Handle<Node> {
+///     TwoBoneIkBuilder::new(BaseBuilder::new().with_name("LeftLegIK"))
+///         .with_bones(hip, knee, ankle)
+///         .with_pole(knee_pole)
+///         .with_target_position(Vector3::new(0.2, 0.1, 0.0))
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Inspect)]
+pub struct TwoBoneIk {
+    base: Base,
+
+    #[inspect(getter = "Deref::deref")]
+    root: TemplateVariable<Handle<Node>>,
+
+    #[inspect(getter = "Deref::deref")]
+    middle: TemplateVariable<Handle<Node>>,
+
+    #[inspect(getter = "Deref::deref")]
+    tip: TemplateVariable<Handle<Node>>,
+
+    #[inspect(getter = "Deref::deref")]
+    target: TemplateVariable<Handle<Node>>,
+
+    #[inspect(getter = "Deref::deref")]
+    pole: TemplateVariable<Handle<Node>>,
+
+    #[inspect(min_value = 0.0, max_value = 1.0, step = 0.01, getter = "Deref::deref")]
+    weight: TemplateVariable<f32>,
+
+    #[visit(skip)]
+    #[inspect(skip)]
+    target_position: Option<Vector3<f32>>,
+
+    #[visit(skip)]
+    #[inspect(skip)]
+    applied: Vec<AppliedRotation>,
+}
+
+impl_directly_inheritable_entity_trait!(TwoBoneIk;
+    root,
+    middle,
+    tip,
+    target,
+    pole,
+    weight
+);
+
+impl Default for TwoBoneIk {
+    fn default() -> Self {
+        TwoBoneIkBuilder::new(BaseBuilder::new()).build_two_bone_ik()
+    }
+}
+
+impl Clone for TwoBoneIk {
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+            root: self.root.clone(),
+            middle: self.middle.clone(),
+            tip: self.tip.clone(),
+            target: self.target.clone(),
+            pole: self.pole.clone(),
+            weight: self.weight.clone(),
+            target_position: self.target_position,
+            // Handles of the bones are not valid for the copy.
+            applied: Default::default(),
+        }
+    }
+}
+
+impl Deref for TwoBoneIk {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for TwoBoneIk {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for TwoBoneIk {
+    fn type_uuid() -> Uuid {
+        uuid!("09bd5031-c6ea-4cf6-832b-d31e65bdea9d")
+    }
+}
+
+impl TwoBoneIk {
+    /// Sets the first bone of the chain (for example a hip).
+    pub fn set_root(&mut self, root: Handle<Node>) {
+        self.root.set(root);
+    }
+
+    /// Returns the first bone of the chain.
+    pub fn root(&self) -> Handle<Node> {
+        *self.root
+    }
+
+    /// Sets the second bone of the chain (for example a knee).
+    pub fn set_middle(&mut self, middle: Handle<Node>) {
+        self.middle.set(middle);
+    }
+
+    /// Returns the second bone of the chain.
+    pub fn middle(&self) -> Handle<Node> {
+        *self.middle
+    }
+
+    /// Sets the end of the chain (for example an ankle), it is the point that should reach the
+    /// target.
+    pub fn set_tip(&mut self, tip: Handle<Node>) {
+        self.tip.set(tip);
+    }
+
+    /// Returns the end of the chain.
+    pub fn tip(&self) -> Handle<Node> {
+        *self.tip
+    }
+
+    /// Sets a node, whose position will be used as the target. It has no effect if the target
+    /// position is set (see [`Self::set_target_position`]).
+    pub fn set_target(&mut self, target: Handle<Node>) {
+        self.target.set(target);
+    }
+
+    /// Returns current target node.
+    pub fn target(&self) -> Handle<Node> {
+        *self.target
+    }
+
+    /// Sets a node, that defines the direction in which the middle joint bends. Could be
+    /// [`Handle::NONE`], in this case the bending plane of the current pose is kept.
+    pub fn set_pole(&mut self, pole: Handle<Node>) {
+        self.pole.set(pole);
+    }
+
+    /// Returns current pole node.
+    pub fn pole(&self) -> Handle<Node> {
+        *self.pole
+    }
+
+    /// Sets how much of the solution is applied to the pose, the value is clamped to `0.0..1.0`
+    /// range.
+    pub fn set_weight(&mut self, weight: f32) {
+        self.weight.set(weight.clamp(0.0, 1.0));
+    }
+
+    /// Returns current weight of the solution.
+    pub fn weight(&self) -> f32 {
+        *self.weight
+    }
+
+    /// Sets the target position in world coordinates, it overrides the target node. `None` means
+    /// that the target node will be used. The position is not serialized, it is meant to be set
+    /// from scripts, for example to a point on the ground under a foot.
+    pub fn set_target_position(&mut self, position: Option<Vector3<f32>>) {
+        self.target_position = position;
+    }
+
+    /// Returns current target position.
+    pub fn target_position(&self) -> Option<Vector3<f32>> {
+        self.target_position
+    }
+
+    /// Sets all bones of the chain at once.
+    pub fn set_bones(&mut self, root: Handle<Node>, middle: Handle<Node>, tip: Handle<Node>) {
+        self.set_root(root);
+        self.set_middle(middle);
+        self.set_tip(tip);
+    }
+}
+
+impl NodeTrait for TwoBoneIk {
+    crate::impl_query_component!();
+
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    // Prefab inheritance resolving.
+    fn inherit(&mut self, parent: &Node) -> Result<(), InheritError> {
+        self.base.inherit_properties(parent)?;
+        if let Some(parent) = parent.cast::<Self>() {
+            self.try_inherit_self_properties(parent)?;
+        }
+        Ok(())
+    }
+
+    fn reset_inheritable_properties(&mut self) {
+        self.base.reset_inheritable_properties();
+        self.reset_self_inheritable_properties();
+    }
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        self.base.restore_resources(resource_manager);
+    }
+
+    fn remap_handles(&mut self, old_new_mapping: &FxHashMap<Handle<Node>, Handle<Node>>) {
+        self.base.remap_handles(old_new_mapping);
+
+        let name = self.name_owned();
+        for (kind, handle) in [
+            ("root bone", &mut self.root),
+            ("middle bone", &mut self.middle),
+            ("tip", &mut self.tip),
+            ("target", &mut self.target),
+            ("pole", &mut self.pole),
+        ] {
+            if handle.is_none() {
+                continue;
+            }
+
+            if let Some(entry) = old_new_mapping.get(&**handle) {
+                handle.set_silent(*entry);
+            } else {
+                Log::warn(format!(
+                    "Unable to remap {} of a two-bone IK {}. Handle is {}!",
+                    kind, name, **handle
+                ))
+            }
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create two-bone IK in a declarative manner.
+pub struct TwoBoneIkBuilder {
+    base_builder: BaseBuilder,
+    root: Handle<Node>,
+    middle: Handle<Node>,
+    tip: Handle<Node>,
+    target: Handle<Node>,
+    pole: Handle<Node>,
+    weight: f32,
+    target_position: Option<Vector3<f32>>,
+}
+
+impl TwoBoneIkBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            root: Default::default(),
+            middle: Default::default(),
+            tip: Default::default(),
+            target: Default::default(),
+            pole: Default::default(),
+            weight: 1.0,
+            target_position: None,
+        }
+    }
+
+    /// Sets desired bones of the chain.
+    pub fn with_bones(
+        mut self,
+        root: Handle<Node>,
+        middle: Handle<Node>,
+        tip: Handle<Node>,
+    ) -> Self {
+        self.root = root;
+        self.middle = middle;
+        self.tip = tip;
+        self
+    }
+
+    /// Sets desired target node.
+    pub fn with_target(mut self, target: Handle<Node>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Sets desired pole node.
+    pub fn with_pole(mut self, pole: Handle<Node>) -> Self {
+        self.pole = pole;
+        self
+    }
+
+    /// Sets desired weight of the solution.
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Sets desired target position in world coordinates.
+    pub fn with_target_position(mut self, position: Vector3<f32>) -> Self {
+        self.target_position = Some(position);
+        self
+    }
+
+    /// Creates new two-bone IK.
+    pub fn build_two_bone_ik(self) -> TwoBoneIk {
+        TwoBoneIk {
+            base: self.base_builder.build_base(),
+            root: self.root.into(),
+            middle: self.middle.into(),
+            tip: self.tip.into(),
+            target: self.target.into(),
+            pole: self.pole.into(),
+            weight: self.weight.clamp(0.0, 1.0).into(),
+            target_position: self.target_position,
+            applied: Default::default(),
+        }
+    }
+
+    /// Creates new two-bone IK node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_two_bone_ik())
+    }
+
+    /// Creates new instance of two-bone IK node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+// Same as `UnitQuaternion::rotation_between`, but handles opposite and zero vectors.
+fn rotation_between(from: &Vector3<f32>, to: &Vector3<f32>) -> UnitQuaternion<f32> {
+    if let Some(rotation) = UnitQuaternion::rotation_between(from, to) {
+        rotation
+    } else if from.dot(to) < 0.0 {
+        UnitQuaternion::from_axis_angle(&any_orthogonal(from), std::f32::consts::PI)
+    } else {
+        UnitQuaternion::identity()
+    }
+}
+
+fn any_orthogonal(v: &Vector3<f32>) -> Unit<Vector3<f32>> {
+    let other = if v.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    Unit::try_new(v.cross(&other), f32::EPSILON).unwrap_or_else(Vector3::z_axis)
+}
+
+/// Analytic solution for a chain of two bones. Takes world-space positions of the joints and
+/// returns world-space rotations that must be applied to the root and the middle bones. Rotation
+/// of the middle bone is relative to the original (not rotated) chain. If the target is out of
+/// reach, the chain is stretched towards it.
+fn solve_two_bone_chain(
+    root: Vector3<f32>,
+    middle: Vector3<f32>,
+    tip: Vector3<f32>,
+    target: Vector3<f32>,
+    pole: Option<Vector3<f32>>,
+) -> (UnitQuaternion<f32>, UnitQuaternion<f32>) {
+    let upper_length = (middle - root).norm();
+    let lower_length = (tip - middle).norm();
+    if upper_length <= f32::EPSILON || lower_length <= f32::EPSILON {
+        return (UnitQuaternion::identity(), UnitQuaternion::identity());
+    }
+
+    // Clamp the distance to make sure the triangle formed by the bones exists.
+    let target_distance = (target - root)
+        .norm()
+        .min(upper_length + lower_length)
+        .max((upper_length - lower_length).abs());
+
+    // Law of cosines gives the angle between the bones at the middle joint.
+    let cos_middle_angle = ((upper_length * upper_length + lower_length * lower_length
+        - target_distance * target_distance)
+        / (2.0 * upper_length * lower_length))
+        .clamp(-1.0, 1.0);
+    let middle_angle = cos_middle_angle.acos();
+
+    // Bend the chain in the plane formed by the bones, or by the pole if the chain is straight.
+    let to_root = root - middle;
+    let bend_axis = Unit::try_new(to_root.cross(&(tip - middle)), 1.0e-6)
+        .or_else(|| pole.and_then(|pole| Unit::try_new(to_root.cross(&(pole - middle)), 1.0e-6)))
+        .unwrap_or_else(|| any_orthogonal(&to_root));
+    let new_lower_direction =
+        UnitQuaternion::from_axis_angle(&bend_axis, middle_angle) * to_root.normalize();
+    let middle_rotation = rotation_between(&(tip - middle), &new_lower_direction);
+    let bent_tip = middle + middle_rotation * (tip - middle);
+
+    // Aim the bent chain at the target.
+    let target_direction = target - root;
+    let mut root_rotation = rotation_between(&(bent_tip - root), &target_direction);
+
+    // Twist the chain around the root-target axis to make the middle joint face the pole.
+    if let (Some(pole), Some(axis)) = (pole, Unit::try_new(target_direction, 1.0e-6)) {
+        let project = |v: Vector3<f32>| v - axis.scale(axis.dot(&v));
+        let middle_offset = project(root_rotation * (middle - root));
+        let pole_offset = project(pole - root);
+        if middle_offset.norm() > 1.0e-6 && pole_offset.norm() > 1.0e-6 {
+            // The twist must happen around the root-target axis, otherwise opposite offsets
+            // would flip the whole chain around an arbitrary axis.
+            let angle = axis
+                .dot(&middle_offset.cross(&pole_offset))
+                .atan2(middle_offset.dot(&pole_offset));
+            root_rotation = UnitQuaternion::from_axis_angle(&axis, angle) * root_rotation;
+        }
+    }
+
+    (root_rotation, middle_rotation)
+}
+
+fn global_rotation(graph: &Graph, node: Handle<Node>) -> UnitQuaternion<f32> {
+    graph
+        .try_get(node)
+        .map(|node| {
+            let transform = node.global_transform();
+            UnitQuaternion::face_towards(&transform.look(), &transform.up())
+        })
+        .unwrap_or_default()
+}
+
+// Converts world-space rotation of a bone to the new local rotation of the bone.
+fn rotate_bone(
+    graph: &Graph,
+    bone: Handle<Node>,
+    rotation: UnitQuaternion<f32>,
+) -> UnitQuaternion<f32> {
+    let node = &graph[bone];
+    let transform = node.local_transform();
+    // Local rotation is applied in the space of the parent after pre-rotation.
+    let space = global_rotation(graph, node.parent()) * **transform.pre_rotation();
+    space.inverse() * rotation * space * **transform.rotation()
+}
+
+/// Solves two-bone IK with the given handle and applies the solution to the bones. Global
+/// transforms of the bones must be up-to-date.
+pub(crate) fn solve_two_bone_ik(graph: &mut Graph, handle: Handle<Node>) {
+    let ik = match graph
+        .try_get_mut(handle)
+        .and_then(|n| n.cast_mut::<TwoBoneIk>())
+    {
+        Some(ik) => ik,
+        None => return,
+    };
+
+    let applied = std::mem::take(&mut ik.applied);
+    let (root, middle, tip) = (ik.root(), ik.middle(), ik.tip());
+    let (target, target_position, pole, weight) =
+        (ik.target(), ik.target_position, ik.pole(), ik.weight());
+
+    // Bones that are not animated keep the previous solution, it must be reverted to get the
+    // same result for partial weights every frame.
+    let mut reverted = false;
+    for applied in applied {
+        if let Some(bone) = graph.try_get_mut(applied.bone) {
+            if **bone.local_transform().rotation() == applied.solved {
+                bone.local_transform_mut().set_rotation(applied.original);
+                reverted = true;
+            }
+        }
+    }
+    if reverted && graph.is_valid_handle(root) {
+        graph.update_hierarchical_data_for_descendants(root);
+    }
+
+    if weight <= 0.0
+        || !graph.is_valid_handle(root)
+        || !graph.is_valid_handle(middle)
+        || !graph.is_valid_handle(tip)
+    {
+        return;
+    }
+
+    let target =
+        match target_position.or_else(|| graph.try_get(target).map(|t| t.global_position())) {
+            Some(target) => target,
+            None => return,
+        };
+    let pole = graph.try_get(pole).map(|p| p.global_position());
+
+    let (root_rotation, middle_rotation) = solve_two_bone_chain(
+        graph[root].global_position(),
+        graph[middle].global_position(),
+        graph[tip].global_position(),
+        target,
+        pole,
+    );
+
+    // Both rotations must be calculated using the original pose.
+    let new_root_rotation = rotate_bone(graph, root, root_rotation.powf(weight));
+    let new_middle_rotation = rotate_bone(graph, middle, middle_rotation.powf(weight));
+
+    let mut applied = Vec::with_capacity(2);
+    for (bone, solved) in [(root, new_root_rotation), (middle, new_middle_rotation)] {
+        let transform = graph[bone].local_transform_mut();
+        applied.push(AppliedRotation {
+            bone,
+            original: **transform.rotation(),
+            solved,
+        });
+        transform.set_rotation(solved);
+    }
+
+    graph.update_hierarchical_data_for_descendants(root);
+
+    if let Some(ik) = graph[handle].cast_mut::<TwoBoneIk>() {
+        ik.applied = applied;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            pool::Handle,
+        },
+        scene::{
+            base::{test::check_inheritable_properties_equality, BaseBuilder},
+            graph::Graph,
+            ik::{TwoBoneIk, TwoBoneIkBuilder},
+            node::{Node, NodeTrait},
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+
+    #[test]
+    fn test_two_bone_ik_inheritance() {
+        let parent = TwoBoneIkBuilder::new(BaseBuilder::new())
+            .with_bones(Handle::new(1, 1), Handle::new(2, 1), Handle::new(3, 1))
+            .with_target(Handle::new(4, 1))
+            .with_pole(Handle::new(5, 1))
+            .with_weight(0.5)
+            .build_node();
+
+        let mut child = TwoBoneIkBuilder::new(BaseBuilder::new()).build_two_bone_ik();
+
+        child.inherit(&parent).unwrap();
+
+        let parent = parent.cast::<TwoBoneIk>().unwrap();
+
+        check_inheritable_properties_equality(&child.base, &parent.base);
+        check_inheritable_properties_equality(&child, parent);
+    }
+
+    const UPPER_LENGTH: f32 = 1.2;
+    const LOWER_LENGTH: f32 = 0.8;
+
+    fn make_bone(graph: &mut Graph, position: Vector3<f32>, parent: Handle<Node>) -> Handle<Node> {
+        let bone = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            ),
+        )
+        .build(graph);
+        graph.link_nodes(bone, parent);
+        bone
+    }
+
+    // Hip at (0, 2, 0), knee and ankle are right below it.
+    fn make_leg(graph: &mut Graph) -> [Handle<Node>; 3] {
+        let root = graph.get_root();
+        let hip = make_bone(graph, Vector3::new(0.0, 2.0, 0.0), root);
+        let knee = make_bone(graph, Vector3::new(0.0, -UPPER_LENGTH, 0.0), hip);
+        let ankle = make_bone(graph, Vector3::new(0.0, -LOWER_LENGTH, 0.0), knee);
+        [hip, knee, ankle]
+    }
+
+    fn knee_angle(graph: &Graph, [hip, knee, ankle]: [Handle<Node>; 3]) -> f32 {
+        let knee_position = graph[knee].global_position();
+        (graph[hip].global_position() - knee_position)
+            .angle(&(graph[ankle].global_position() - knee_position))
+    }
+
+    fn expected_knee_angle(distance: f32) -> f32 {
+        ((UPPER_LENGTH * UPPER_LENGTH + LOWER_LENGTH * LOWER_LENGTH - distance * distance)
+            / (2.0 * UPPER_LENGTH * LOWER_LENGTH))
+            .clamp(-1.0, 1.0)
+            .acos()
+    }
+
+    #[test]
+    fn test_two_bone_ik_reachable_targets() {
+        let mut graph = Graph::new();
+        let leg = make_leg(&mut graph);
+        let root = graph.get_root();
+        let pole = make_bone(&mut graph, Vector3::new(0.0, 1.0, 5.0), root);
+        let ik = TwoBoneIkBuilder::new(BaseBuilder::new())
+            .with_bones(leg[0], leg[1], leg[2])
+            .with_pole(pole)
+            .build(&mut graph);
+
+        let hip = Vector3::new(0.0, 2.0, 0.0);
+        for target in [
+            Vector3::new(0.0, 0.5, 0.0),
+            Vector3::new(0.3, 0.4, 0.2),
+            Vector3::new(1.0, 1.5, -0.5),
+            Vector3::new(0.0, 1.0, 0.3),
+        ] {
+            graph[ik]
+                .cast_mut::<TwoBoneIk>()
+                .unwrap()
+                .set_target_position(Some(target));
+            graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0);
+
+            let ankle = graph[leg[2]].global_position();
+            assert!((ankle - target).norm() < 1.0e-3, "{:?} {:?}", ankle, target);
+            assert!(
+                (knee_angle(&graph, leg) - expected_knee_angle((target - hip).norm())).abs()
+                    < 1.0e-3
+            );
+            // Lengths of the bones must be preserved.
+            assert!(((graph[leg[1]].global_position() - hip).norm() - UPPER_LENGTH).abs() < 1.0e-3);
+            // Knee must bend towards the pole.
+            assert!(graph[leg[1]].global_position().z > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_two_bone_ik_unreachable_target() {
+        let mut graph = Graph::new();
+        let leg = make_leg(&mut graph);
+        let root = graph.get_root();
+        let target = make_bone(&mut graph, Vector3::new(5.0, 2.0, 0.0), root);
+        TwoBoneIkBuilder::new(BaseBuilder::new())
+            .with_bones(leg[0], leg[1], leg[2])
+            .with_target(target)
+            .build(&mut graph);
+
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0);
+
+        // The leg must be fully stretched towards the target.
+        assert!((knee_angle(&graph, leg) - std::f32::consts::PI).abs() < 1.0e-3);
+        let ankle = graph[leg[2]].global_position();
+        let expected = Vector3::new(UPPER_LENGTH + LOWER_LENGTH, 2.0, 0.0);
+        assert!((ankle - expected).norm() < 1.0e-3, "{:?}", ankle);
+    }
+
+    #[test]
+    fn test_two_bone_ik_weight() {
+        let mut graph = Graph::new();
+        let leg = make_leg(&mut graph);
+        let ik = TwoBoneIkBuilder::new(BaseBuilder::new())
+            .with_bones(leg[0], leg[1], leg[2])
+            .with_target_position(Vector3::new(0.0, 1.0, 1.0))
+            .with_weight(0.5)
+            .build(&mut graph);
+
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0);
+        let first = graph[leg[2]].global_position();
+        assert!((first - Vector3::new(0.0, 1.0, 1.0)).norm() > 1.0e-2);
+
+        // Partial solution must not accumulate when bones are not animated.
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0);
+        assert!((graph[leg[2]].global_position() - first).norm() < 1.0e-4);
+
+        graph[ik].cast_mut::<TwoBoneIk>().unwrap().set_weight(0.0);
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0);
+        assert!((graph[leg[2]].global_position() - Vector3::new(0.0, 0.0, 0.0)).norm() < 1.0e-4);
+    }
+}
@@ -0,0 +1,487 @@
+//! Impostor is a camera-facing billboard, that shows a pre-rendered image of a distant object.
+//!
+//! For more info see [`Impostor`]
+
+use crate::{
+    core::variable::{InheritError, TemplateVariable},
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        inspect::{Inspect, PropertyInfo},
+        math::aabb::AxisAlignedBoundingBox,
+        parking_lot::Mutex,
+        pool::Handle,
+        sstorage::ImmutableString,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    impl_directly_inheritable_entity_trait,
+    material::{shader::SamplerFallback, Material, PropertyValue},
+    resource::texture::Texture,
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        mesh::surface::SurfaceData,
+        node::{Node, NodeTrait, TypeUuidProvider},
+        DirectlyInheritableEntity,
+    },
+    utils::log::Log,
+};
+use fxhash::{FxHashMap, FxHasher};
+use lazy_static::lazy_static;
+use std::{
+    f32::consts::TAU,
+    hash::Hasher,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+lazy_static! {
+    // Every impostor is rendered using the same quad, the shader turns it to the camera.
+    static ref IMPOSTOR_QUAD: Arc<Mutex<SurfaceData>> =
+        Arc::new(Mutex::new(SurfaceData::make_quad(&Matrix4::identity())));
+}
+
+/// Impostor is a camera-facing billboard, that shows a pre-rendered image of an object (a tree,
+/// a rock, etc.). Impostors are used to replace distant objects, a few thousands of impostors are
+/// much cheaper to render than the same amount of full-detail models.
+///
+/// # Atlases
+///
+/// An object is captured from `frame_count` directions evenly spread around vertical (Y) axis of
+/// the impostor. Frame `i` is captured from direction `(sin(a), 0, cos(a))` where
+/// `a = 2 * PI * i / frame_count`, so the first frame shows the object from its `+Z` side. Frames
+/// are stored in an atlas in a grid with `ceil(sqrt(frame_count))` columns, row by row. There are
+/// two atlases: the first one stores unlit color and coverage (alpha), the second one stores
+/// normals in local coordinates of the impostor (packed into `[0; 1]` range), they're used for
+/// lighting, so impostors are lit just like any other object in deferred pipeline.
+///
+/// Atlases are not supposed to be made manually, they're produced by the impostor bake (see
+/// [`crate::utils::impostor`]), which also sets size and center of the billboard.
+///
+/// # Frame selection
+///
+/// At runtime, the frame that was captured from the closest direction to the direction to a camera
+/// is shown. Optionally, two closest frames could be blended, which makes transition between
+/// frames smooth, see [`Impostor::set_blend_frames`]. Selection is done on GPU, but
+/// [`Impostor::frames_for_direction`] does exactly the same on CPU.
+///
+/// # Level of detail
+///
+/// Impostors are usually the last level of detail of a model, see
+/// [`crate::scene::base::LodGroup::add_last_level`].
+#[derive(Debug, Visit, Clone, Inspect)]
+pub struct Impostor {
+    base: Base,
+
+    #[inspect(getter = "Deref::deref")]
+    atlas: TemplateVariable<Option<Texture>>,
+
+    #[inspect(getter = "Deref::deref")]
+    normal_atlas: TemplateVariable<Option<Texture>>,
+
+    #[inspect(min_value = 1.0, getter = "Deref::deref")]
+    frame_count: TemplateVariable<u32>,
+
+    #[inspect(getter = "Deref::deref")]
+    size: TemplateVariable<Vector2<f32>>,
+
+    #[inspect(getter = "Deref::deref")]
+    center: TemplateVariable<Vector3<f32>>,
+
+    #[inspect(getter = "Deref::deref")]
+    blend_frames: TemplateVariable<bool>,
+}
+
+impl_directly_inheritable_entity_trait!(Impostor;
+    atlas,
+    normal_atlas,
+    frame_count,
+    size,
+    center,
+    blend_frames
+);
+
+impl Default for Impostor {
+    fn default() -> Self {
+        ImpostorBuilder::new(BaseBuilder::new()).build_impostor()
+    }
+}
+
+impl Deref for Impostor {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Impostor {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Impostor {
+    fn type_uuid() -> Uuid {
+        uuid!("5b9e3c1d-8a4f-4e2b-b6d7-2f1c9a8e4d63")
+    }
+}
+
+impl Impostor {
+    /// Sets new atlas with color and coverage of frames.
+    pub fn set_atlas(&mut self, atlas: Option<Texture>) -> Option<Texture> {
+        std::mem::replace(self.atlas.get_mut(), atlas)
+    }
+
+    /// Returns current atlas with color and coverage of frames.
+    pub fn atlas(&self) -> Option<&Texture> {
+        self.atlas.as_ref()
+    }
+
+    /// Returns current atlas with color and coverage of frames.
+    pub fn atlas_value(&self) -> Option<Texture> {
+        (*self.atlas).clone()
+    }
+
+    /// Sets new atlas with normals of frames.
+    pub fn set_normal_atlas(&mut self, normal_atlas: Option<Texture>) -> Option<Texture> {
+        std::mem::replace(self.normal_atlas.get_mut(), normal_atlas)
+    }
+
+    /// Returns current atlas with normals of frames.
+    pub fn normal_atlas(&self) -> Option<&Texture> {
+        self.normal_atlas.as_ref()
+    }
+
+    /// Returns current atlas with normals of frames.
+    pub fn normal_atlas_value(&self) -> Option<Texture> {
+        (*self.normal_atlas).clone()
+    }
+
+    /// Sets amount of frames in the atlases. It must match the amount of frames the atlases
+    /// were baked with.
+    pub fn set_frame_count(&mut self, frame_count: u32) {
+        self.frame_count.set(frame_count.max(1));
+    }
+
+    /// Returns amount of frames in the atlases.
+    pub fn frame_count(&self) -> u32 {
+        *self.frame_count
+    }
+
+    /// Returns amount of columns and rows of frames in the atlases.
+    pub fn atlas_grid(&self) -> (u32, u32) {
+        atlas_grid(*self.frame_count)
+    }
+
+    /// Sets new size (width and height) of the billboard in local coordinates.
+    pub fn set_size(&mut self, size: Vector2<f32>) {
+        self.size.set(size);
+    }
+
+    /// Returns size of the billboard in local coordinates.
+    pub fn size(&self) -> Vector2<f32> {
+        *self.size
+    }
+
+    /// Sets new center of the billboard in local coordinates. The billboard rotates around
+    /// vertical axis that passes through the center.
+    pub fn set_center(&mut self, center: Vector3<f32>) {
+        self.center.set(center);
+    }
+
+    /// Returns center of the billboard in local coordinates.
+    pub fn center(&self) -> Vector3<f32> {
+        *self.center
+    }
+
+    /// Defines whether two closest frames should be blended or not. Blending makes transitions
+    /// between frames smooth, but it requires twice as much texture fetches.
+    pub fn set_blend_frames(&mut self, blend_frames: bool) {
+        self.blend_frames.set(blend_frames);
+    }
+
+    /// Returns `true` if two closest frames are blended.
+    pub fn is_blend_frames(&self) -> bool {
+        *self.blend_frames
+    }
+
+    /// Returns indices of two frames, that should be shown for an observer in the given direction
+    /// (in local coordinates, from the center of the impostor to the observer), and the weight of
+    /// the second frame. If blending is disabled, both indices are the same and the weight is zero.
+    pub fn frames_for_direction(&self, direction: Vector3<f32>) -> (u32, u32, f32) {
+        let frame_count = *self.frame_count;
+        let angle = direction.x.atan2(direction.z).rem_euclid(TAU);
+        let frame = angle / TAU * frame_count as f32;
+        if *self.blend_frames {
+            let first = frame.floor();
+            (
+                first as u32 % frame_count,
+                (first as u32 + 1) % frame_count,
+                frame - first,
+            )
+        } else {
+            let closest = frame.round() as u32 % frame_count;
+            (closest, closest, 0.0)
+        }
+    }
+
+    pub(crate) fn quad() -> Arc<Mutex<SurfaceData>> {
+        IMPOSTOR_QUAD.clone()
+    }
+
+    // Impostors with the same atlases and parameters share the same material and batch.
+    pub(crate) fn batch_key(&self) -> u64 {
+        let mut hasher = FxHasher::default();
+        for texture in [self.atlas(), self.normal_atlas()] {
+            hasher.write_usize(texture.map_or(0, |texture| texture.key()));
+        }
+        hasher.write_u32(*self.frame_count);
+        for value in [
+            self.size.x,
+            self.size.y,
+            self.center.x,
+            self.center.y,
+            self.center.z,
+        ] {
+            hasher.write_u32(value.to_bits());
+        }
+        hasher.write_u8(*self.blend_frames as u8);
+        hasher.finish()
+    }
+
+    pub(crate) fn create_material(&self) -> Material {
+        let (columns, rows) = self.atlas_grid();
+        let mut material = Material::standard_impostor();
+        for (name, value) in [
+            (
+                "impostorAtlas",
+                PropertyValue::Sampler {
+                    value: (*self.atlas).clone(),
+                    fallback: SamplerFallback::White,
+                },
+            ),
+            (
+                "impostorNormalAtlas",
+                PropertyValue::Sampler {
+                    value: (*self.normal_atlas).clone(),
+                    fallback: SamplerFallback::Normal,
+                },
+            ),
+            ("impostorSize", PropertyValue::Vector2(*self.size)),
+            ("impostorCenter", PropertyValue::Vector3(*self.center)),
+            ("frameCount", PropertyValue::UInt(*self.frame_count)),
+            (
+                "atlasGrid",
+                PropertyValue::Vector2(Vector2::new(columns as f32, rows as f32)),
+            ),
+            ("blendFrames", PropertyValue::Bool(*self.blend_frames)),
+        ] {
+            Log::verify(material.set_property(&ImmutableString::new(name), value));
+        }
+        material
+    }
+}
+
+/// Returns amount of columns and rows of an atlas with the given amount of frames.
+pub fn atlas_grid(frame_count: u32) -> (u32, u32) {
+    let frame_count = frame_count.max(1);
+    let columns = (frame_count as f32).sqrt().ceil() as u32;
+    (columns, (frame_count + columns - 1) / columns)
+}
+
+impl NodeTrait for Impostor {
+    crate::impl_query_component!();
+
+    /// Returns current **local-space** bounding box of the billboard in any orientation.
+    #[inline]
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        let half_size = self.size.scale(0.5);
+        let extent = Vector3::new(half_size.x, half_size.y, half_size.x);
+        AxisAlignedBoundingBox::from_min_max(*self.center - extent, *self.center + extent)
+    }
+
+    /// Returns current **world-space** bounding box of the billboard.
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    // Prefab inheritance resolving.
+    fn inherit(&mut self, parent: &Node) -> Result<(), InheritError> {
+        self.base.inherit_properties(parent)?;
+        if let Some(parent) = parent.cast::<Self>() {
+            self.try_inherit_self_properties(parent)?;
+        }
+        Ok(())
+    }
+
+    fn reset_inheritable_properties(&mut self) {
+        self.base.reset_inheritable_properties();
+        self.reset_self_inheritable_properties();
+    }
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        self.base.restore_resources(resource_manager.clone());
+
+        let mut state = resource_manager.state();
+        let texture_container = &mut state.containers_mut().textures;
+        texture_container.try_restore_template_resource(&mut self.atlas);
+        texture_container.try_restore_template_resource(&mut self.normal_atlas);
+    }
+
+    fn remap_handles(&mut self, old_new_mapping: &FxHashMap<Handle<Node>, Handle<Node>>) {
+        self.base.remap_handles(old_new_mapping);
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create an impostor in a declarative manner.
+pub struct ImpostorBuilder {
+    base_builder: BaseBuilder,
+    atlas: Option<Texture>,
+    normal_atlas: Option<Texture>,
+    frame_count: u32,
+    size: Vector2<f32>,
+    center: Vector3<f32>,
+    blend_frames: bool,
+}
+
+impl ImpostorBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            atlas: None,
+            normal_atlas: None,
+            frame_count: 8,
+            size: Vector2::new(1.0, 1.0),
+            center: Default::default(),
+            blend_frames: true,
+        }
+    }
+
+    /// Sets desired atlas with color and coverage of frames.
+    pub fn with_atlas(mut self, atlas: Texture) -> Self {
+        self.atlas = Some(atlas);
+        self
+    }
+
+    /// Sets desired atlas with normals of frames.
+    pub fn with_normal_atlas(mut self, normal_atlas: Texture) -> Self {
+        self.normal_atlas = Some(normal_atlas);
+        self
+    }
+
+    /// Sets desired amount of frames in the atlases.
+    pub fn with_frame_count(mut self, frame_count: u32) -> Self {
+        self.frame_count = frame_count;
+        self
+    }
+
+    /// Sets desired size of the billboard.
+    pub fn with_size(mut self, size: Vector2<f32>) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets desired center of the billboard.
+    pub fn with_center(mut self, center: Vector3<f32>) -> Self {
+        self.center = center;
+        self
+    }
+
+    /// Sets whether two closest frames should be blended or not.
+    pub fn with_blend_frames(mut self, blend_frames: bool) -> Self {
+        self.blend_frames = blend_frames;
+        self
+    }
+
+    /// Creates new impostor.
+    pub fn build_impostor(self) -> Impostor {
+        Impostor {
+            base: self.base_builder.build_base(),
+            atlas: self.atlas.into(),
+            normal_atlas: self.normal_atlas.into(),
+            frame_count: self.frame_count.max(1).into(),
+            size: self.size.into(),
+            center: self.center.into(),
+            blend_frames: self.blend_frames.into(),
+        }
+    }
+
+    /// Creates new impostor node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_impostor())
+    }
+
+    /// Creates new instance of impostor node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::{
+            base::BaseBuilder,
+            impostor::{atlas_grid, ImpostorBuilder},
+        },
+    };
+    use std::f32::consts::TAU;
+
+    #[test]
+    fn test_atlas_grid() {
+        assert_eq!(atlas_grid(1), (1, 1));
+        assert_eq!(atlas_grid(8), (3, 3));
+        assert_eq!(atlas_grid(12), (4, 3));
+        assert_eq!(atlas_grid(16), (4, 4));
+    }
+
+    #[test]
+    fn test_closest_frame() {
+        let impostor = ImpostorBuilder::new(BaseBuilder::new())
+            .with_frame_count(8)
+            .with_blend_frames(false)
+            .build_impostor();
+
+        let direction = |angle: f32| Vector3::new(angle.sin(), 0.3, angle.cos());
+
+        assert_eq!(impostor.frames_for_direction(Vector3::z()), (0, 0, 0.0));
+        assert_eq!(impostor.frames_for_direction(Vector3::x()), (2, 2, 0.0));
+        assert_eq!(impostor.frames_for_direction(-Vector3::x()), (6, 6, 0.0));
+        // Slightly before the first frame - wraps around.
+        assert_eq!(
+            impostor.frames_for_direction(direction(TAU - 0.1)),
+            (0, 0, 0.0)
+        );
+        assert_eq!(
+            impostor.frames_for_direction(direction(TAU / 8.0 * 2.6)),
+            (3, 3, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_blended_frames() {
+        let impostor = ImpostorBuilder::new(BaseBuilder::new())
+            .with_frame_count(4)
+            .with_blend_frames(true)
+            .build_impostor();
+
+        let (first, second, weight) = impostor.frames_for_direction(Vector3::new(1.0, 0.0, 1.0));
+        assert_eq!((first, second), (0, 1));
+        assert!((weight - 0.5).abs() < 1.0e-5);
+
+        // The last frame is blended with the first one.
+        let (first, second, weight) =
+            impostor.frames_for_direction(Vector3::new(-1.0, 0.0, 3.0f32.sqrt()));
+        assert_eq!((first, second), (3, 0));
+        assert!((weight - 2.0 / 3.0).abs() < 1.0e-4);
+    }
+}
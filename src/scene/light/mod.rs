@@ -15,6 +15,13 @@
 //! Most of light sources supports shadows (via shadows maps) and light scattering,
 //! these are common effects for modern games but still can significantly impact
 //! performance.
+//!
+//! # Light scattering
+//!
+//! Scattering of a light is defined by three parameters: scatter factor per color channel,
+//! density of the medium and anisotropy of scattering. Instead of tuning them by hand, it is
+//! possible to pick one of [`ScatterPreset`]s that sets all of them at once, the values can still
+//! be tweaked individually afterwards.
 
 use crate::{
     core::variable::{InheritError, TemplateVariable},
@@ -34,7 +41,11 @@ use crate::{
     },
 };
 use fxhash::FxHashMap;
-use std::ops::{Deref, DerefMut};
+use std::{
+    cell::Cell,
+    ops::{Deref, DerefMut},
+};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
 pub mod directional;
 pub mod point;
@@ -52,6 +63,84 @@ pub const DEFAULT_SCATTER_G: f32 = 0.03;
 /// significant value and you'll clearly see light volume with such settings.
 pub const DEFAULT_SCATTER_B: f32 = 0.03;
 
+/// Default density of a medium in which light scatters, it keeps the amount of scattered light
+/// defined only by the scatter factor.
+pub const DEFAULT_SCATTER_DENSITY: f32 = 1.0;
+
+/// Default anisotropy of light scattering, light scatters equally in every direction.
+pub const DEFAULT_SCATTER_ANISOTROPY: f32 = 0.0;
+
+/// Max absolute value of anisotropy of light scattering. Values closer to one concentrate
+/// scattered light in a very narrow cone, which cannot be rendered without artifacts.
+pub const MAX_SCATTER_ANISOTROPY: f32 = 0.95;
+
+/// Parameters of light scattering that are set by a [`ScatterPreset`]. See
+/// [`BaseLight::set_scatter`], [`BaseLight::set_scatter_density`] and
+/// [`BaseLight::set_scatter_anisotropy`] for the meaning of each parameter.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ScatterParameters {
+    /// Scatter factor per color channel.
+    pub factor: Vector3<f32>,
+    /// Density of a medium in which light scatters.
+    pub density: f32,
+    /// Anisotropy of light scattering.
+    pub anisotropy: f32,
+}
+
+/// Named set of light scattering parameters, that resembles a typical medium. Run the
+/// `light_scattering` example and press `P` to render every preset side by side.
+#[derive(
+    Copy, Clone, PartialEq, Eq, Hash, Debug, Visit, Inspect, AsRefStr, EnumString, EnumVariantNames,
+)]
+#[repr(u32)]
+pub enum ScatterPreset {
+    /// Parameters are set manually. This is the default preset, it does not change parameters.
+    Custom = 0,
+
+    /// Clean air, barely visible scattering with a slight blue tint.
+    Clear = 1,
+
+    /// Light haze, scattering is slightly stronger in the direction of light.
+    Haze = 2,
+
+    /// Dense fog, most of light scatters forward, so light sources have bright halos when looking
+    /// towards them.
+    Fog = 3,
+
+    /// Dusty air, warm tint and moderate forward scattering.
+    Dust = 4,
+}
+
+impl Default for ScatterPreset {
+    fn default() -> Self {
+        Self::Custom
+    }
+}
+
+impl ScatterPreset {
+    /// Returns parameters of light scattering of the preset, `None` for [`ScatterPreset::Custom`].
+    pub fn parameters(self) -> Option<ScatterParameters> {
+        let (factor, density, anisotropy) = match self {
+            ScatterPreset::Custom => return None,
+            ScatterPreset::Clear => (Vector3::new(0.02, 0.025, 0.03), 0.3, 0.0),
+            ScatterPreset::Haze => (Vector3::new(0.03, 0.03, 0.03), 1.0, 0.4),
+            ScatterPreset::Fog => (Vector3::new(0.03, 0.03, 0.03), 3.0, 0.75),
+            ScatterPreset::Dust => (Vector3::new(0.035, 0.03, 0.022), 2.0, 0.5),
+        };
+        Some(ScatterParameters {
+            factor,
+            density,
+            anisotropy,
+        })
+    }
+}
+
+/// Relative width of a band around maximum shadows distance in which shadows keep their state
+/// from the previous frame. It prevents flickering of shadows when a camera moves back and forth
+/// near the boundary: shadows are switched off only at `1.0 + SHADOWS_DISTANCE_HYSTERESIS` of
+/// the distance and switched on again only at `1.0 - SHADOWS_DISTANCE_HYSTERESIS` of it.
+pub const SHADOWS_DISTANCE_HYSTERESIS: f32 = 0.05;
+
 /// Light scene node. It contains common properties of light such as color,
 /// scattering factor (per color channel) and other useful properties. Exact
 /// behavior defined by specific light kind.
@@ -65,23 +154,52 @@ pub struct BaseLight {
     #[inspect(getter = "Deref::deref")]
     cast_shadows: TemplateVariable<bool>,
 
+    #[inspect(getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    scatter_preset: TemplateVariable<ScatterPreset>,
+
     #[inspect(getter = "Deref::deref")]
     #[visit(rename = "ScatterFactor")]
     scatter: TemplateVariable<Vector3<f32>>,
 
+    #[inspect(min_value = 0.0, step = 0.1, getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    scatter_density: TemplateVariable<f32>,
+
+    #[inspect(min_value = -0.95, max_value = 0.95, step = 0.05, getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    scatter_anisotropy: TemplateVariable<f32>,
+
     #[inspect(getter = "Deref::deref")]
     scatter_enabled: TemplateVariable<bool>,
 
     #[inspect(min_value = 0.0, step = 0.1, getter = "Deref::deref")]
     intensity: TemplateVariable<f32>,
+
+    #[inspect(min_value = 0.0, step = 0.5, getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    shadows_max_distance: TemplateVariable<f32>,
+
+    #[inspect(getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    static_shadows: TemplateVariable<bool>,
+
+    #[inspect(skip)]
+    #[visit(skip)]
+    shadows_culled: Cell<bool>,
 }
 
 impl_directly_inheritable_entity_trait!(BaseLight;
     color,
     cast_shadows,
+    scatter_preset,
     scatter,
+    scatter_density,
+    scatter_anisotropy,
     scatter_enabled,
-    intensity
+    intensity,
+    shadows_max_distance,
+    static_shadows
 );
 
 impl Deref for BaseLight {
@@ -104,13 +222,19 @@ impl Default for BaseLight {
             base: Default::default(),
             color: TemplateVariable::new(Color::WHITE),
             cast_shadows: TemplateVariable::new(true),
+            scatter_preset: TemplateVariable::new(Default::default()),
             scatter: TemplateVariable::new(Vector3::new(
                 DEFAULT_SCATTER_R,
                 DEFAULT_SCATTER_G,
                 DEFAULT_SCATTER_B,
             )),
+            scatter_density: TemplateVariable::new(DEFAULT_SCATTER_DENSITY),
+            scatter_anisotropy: TemplateVariable::new(DEFAULT_SCATTER_ANISOTROPY),
             scatter_enabled: TemplateVariable::new(true),
             intensity: TemplateVariable::new(1.0),
+            shadows_max_distance: TemplateVariable::new(f32::MAX),
+            static_shadows: TemplateVariable::new(false),
+            shadows_culled: Cell::new(false),
         }
     }
 }
@@ -140,6 +264,59 @@ impl BaseLight {
         *self.cast_shadows
     }
 
+    /// Sets maximum distance from a camera at which the light still renders shadows, beyond the
+    /// distance the light is rendered without shadows. Default is `f32::MAX`, which means that
+    /// the distance is defined only by the quality settings of the renderer (see
+    /// `point_shadows_distance` and `spot_shadows_distance` of
+    /// [`crate::renderer::QualitySettings`]). The effective distance is the smallest one of both
+    /// and it is scaled by `shadows_distance_multiplier` of the quality settings. The distance is
+    /// ignored for directional lights.
+    #[inline]
+    pub fn set_shadows_max_distance(&mut self, distance: f32) {
+        self.shadows_max_distance.set(distance.max(0.0));
+    }
+
+    /// Returns maximum distance from a camera at which the light still renders shadows.
+    #[inline]
+    pub fn shadows_max_distance(&self) -> f32 {
+        *self.shadows_max_distance
+    }
+
+    /// Enables or disables static shadows. Shadows of static geometry (nodes with
+    /// [`crate::scene::base::Mobility::Static`] or [`crate::scene::base::Mobility::Stationary`])
+    /// of such light are rendered once and cached by the renderer, only dynamic shadow casters are
+    /// rendered every frame. The cache is refreshed automatically when static geometry in the
+    /// light's area of effect or the light itself is changed. It is a cheap way of having shadows
+    /// in mostly static scenes. Static shadows are supported only by point and spot lights, the
+    /// flag is ignored for directional lights.
+    #[inline]
+    pub fn set_static_shadows(&mut self, value: bool) {
+        self.static_shadows.set(value);
+    }
+
+    /// Returns true if shadows of static geometry are cached for the light, false - otherwise.
+    #[inline]
+    pub fn is_static_shadows(&self) -> bool {
+        *self.static_shadows
+    }
+
+    /// Decides whether the light should render shadows at the given distance from a camera and
+    /// remembers the decision for the next frame. Distances inside the hysteresis band (see
+    /// [`SHADOWS_DISTANCE_HYSTERESIS`]) keep the decision made in the previous frame.
+    pub(crate) fn update_shadows_culling(
+        &self,
+        distance_to_camera: f32,
+        max_distance: f32,
+    ) -> bool {
+        let culled = if self.shadows_culled.get() {
+            distance_to_camera > max_distance * (1.0 - SHADOWS_DISTANCE_HYSTERESIS)
+        } else {
+            distance_to_camera > max_distance * (1.0 + SHADOWS_DISTANCE_HYSTERESIS)
+        };
+        self.shadows_culled.set(culled);
+        !culled
+    }
+
     /// Sets scatter factor per color channel (red, green, blue) in (0..1) range.
     /// This parameter defines how "thick" environment is and how much light will
     /// be scattered in light volume. Ability to change this parameter per channel
@@ -159,6 +336,64 @@ impl BaseLight {
         *self.scatter
     }
 
+    /// Sets density of a medium in which light scatters. Amount of scattered light grows linearly
+    /// with density, like it does with concentration of particles in real air: `0.3` is clean
+    /// air, `1.0` (default) is light haze, `3.0` is dense fog. Default value keeps the amount of
+    /// scattered light defined only by the scatter factor.
+    #[inline]
+    pub fn set_scatter_density(&mut self, density: f32) {
+        self.scatter_density.set(density.max(0.0));
+    }
+
+    /// Returns current density of a medium in which light scatters.
+    #[inline]
+    pub fn scatter_density(&self) -> f32 {
+        *self.scatter_density
+    }
+
+    /// Sets anisotropy of light scattering, it is the `g` parameter of the Henyey-Greenstein phase
+    /// function. Positive values scatter light forward (in the direction of light), so the light
+    /// source looks brighter when looking towards it through the light volume, negative values
+    /// scatter light backward. Zero (default) scatters light equally in every direction. The value
+    /// is clamped to `[-MAX_SCATTER_ANISOTROPY; MAX_SCATTER_ANISOTROPY]` range.
+    #[inline]
+    pub fn set_scatter_anisotropy(&mut self, anisotropy: f32) {
+        self.scatter_anisotropy
+            .set(anisotropy.clamp(-MAX_SCATTER_ANISOTROPY, MAX_SCATTER_ANISOTROPY));
+    }
+
+    /// Returns current anisotropy of light scattering.
+    #[inline]
+    pub fn scatter_anisotropy(&self) -> f32 {
+        *self.scatter_anisotropy
+    }
+
+    /// Applies scattering parameters of the preset to the light. [`ScatterPreset::Custom`] keeps
+    /// current parameters. The parameters can be changed individually afterwards, the light
+    /// remembers the last applied preset only for information.
+    #[inline]
+    pub fn set_scatter_preset(&mut self, preset: ScatterPreset) {
+        if let Some(parameters) = preset.parameters() {
+            self.scatter.set(parameters.factor);
+            self.set_scatter_density(parameters.density);
+            self.set_scatter_anisotropy(parameters.anisotropy);
+        }
+        self.scatter_preset.set(preset);
+    }
+
+    /// Returns the last applied scatter preset.
+    #[inline]
+    pub fn scatter_preset(&self) -> ScatterPreset {
+        *self.scatter_preset
+    }
+
+    /// Returns scatter factor multiplied by density of the medium, it defines the actual amount
+    /// of scattered light per color channel.
+    #[inline]
+    pub fn effective_scatter(&self) -> Vector3<f32> {
+        self.scatter.scale(*self.scatter_density)
+    }
+
     /// Sets new light intensity. Default is 1.0.
     ///
     /// Intensity is used for very bright light sources in HDR. For examples, sun
@@ -221,9 +456,14 @@ pub struct BaseLightBuilder {
     base_builder: BaseBuilder,
     color: Color,
     cast_shadows: bool,
+    scatter_preset: ScatterPreset,
     scatter_factor: Vector3<f32>,
+    scatter_density: f32,
+    scatter_anisotropy: f32,
     scatter_enabled: bool,
     intensity: f32,
+    shadows_max_distance: f32,
+    static_shadows: bool,
 }
 
 impl BaseLightBuilder {
@@ -236,9 +476,14 @@ impl BaseLightBuilder {
             base_builder,
             color: Color::WHITE,
             cast_shadows: true,
+            scatter_preset: Default::default(),
             scatter_factor: Vector3::new(DEFAULT_SCATTER_R, DEFAULT_SCATTER_G, DEFAULT_SCATTER_B),
+            scatter_density: DEFAULT_SCATTER_DENSITY,
+            scatter_anisotropy: DEFAULT_SCATTER_ANISOTROPY,
             scatter_enabled: true,
             intensity: 1.0,
+            shadows_max_distance: f32::MAX,
+            static_shadows: false,
         }
     }
 
@@ -260,6 +505,32 @@ impl BaseLightBuilder {
         self
     }
 
+    /// Sets density of a medium in which light scatters. See [`BaseLight::set_scatter_density`]
+    /// for more info.
+    pub fn with_scatter_density(mut self, density: f32) -> Self {
+        self.scatter_density = density.max(0.0);
+        self
+    }
+
+    /// Sets anisotropy of light scattering. See [`BaseLight::set_scatter_anisotropy`] for more
+    /// info.
+    pub fn with_scatter_anisotropy(mut self, anisotropy: f32) -> Self {
+        self.scatter_anisotropy = anisotropy.clamp(-MAX_SCATTER_ANISOTROPY, MAX_SCATTER_ANISOTROPY);
+        self
+    }
+
+    /// Applies scattering parameters of the preset, parameters set after the preset override the
+    /// values of the preset. See [`BaseLight::set_scatter_preset`] for more info.
+    pub fn with_scatter_preset(mut self, preset: ScatterPreset) -> Self {
+        if let Some(parameters) = preset.parameters() {
+            self.scatter_factor = parameters.factor;
+            self.scatter_density = parameters.density;
+            self.scatter_anisotropy = parameters.anisotropy;
+        }
+        self.scatter_preset = preset;
+        self
+    }
+
     /// Whether light scatter enabled or not.
     pub fn with_scatter_enabled(mut self, state: bool) -> Self {
         self.scatter_enabled = state;
@@ -272,15 +543,117 @@ impl BaseLightBuilder {
         self
     }
 
+    /// Sets maximum distance from a camera at which the light still renders shadows. See
+    /// [`BaseLight::set_shadows_max_distance`] for more info.
+    pub fn with_shadows_max_distance(mut self, distance: f32) -> Self {
+        self.shadows_max_distance = distance.max(0.0);
+        self
+    }
+
+    /// Sets whether shadows of static geometry should be cached or not. See
+    /// [`BaseLight::set_static_shadows`] for more info.
+    pub fn with_static_shadows(mut self, static_shadows: bool) -> Self {
+        self.static_shadows = static_shadows;
+        self
+    }
+
     /// Creates new instance of base light.
     pub fn build(self) -> BaseLight {
         BaseLight {
             base: self.base_builder.build_base(),
             color: self.color.into(),
             cast_shadows: self.cast_shadows.into(),
+            scatter_preset: self.scatter_preset.into(),
             scatter: self.scatter_factor.into(),
+            scatter_density: self.scatter_density.into(),
+            scatter_anisotropy: self.scatter_anisotropy.into(),
             scatter_enabled: self.scatter_enabled.into(),
             intensity: self.intensity.into(),
+            shadows_max_distance: self.shadows_max_distance.into(),
+            static_shadows: self.static_shadows.into(),
+            shadows_culled: Cell::new(false),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::{
+            base::{test::check_inheritable_properties_equality, BaseBuilder},
+            light::{
+                BaseLight, BaseLightBuilder, ScatterPreset, DEFAULT_SCATTER_B, DEFAULT_SCATTER_G,
+                DEFAULT_SCATTER_R, MAX_SCATTER_ANISOTROPY,
+            },
+        },
+    };
+
+    #[test]
+    fn test_base_light_inheritance() {
+        let parent = BaseLightBuilder::new(BaseBuilder::new())
+            .with_intensity(2.0)
+            .with_shadows_max_distance(25.0)
+            .with_static_shadows(true)
+            .with_scatter_preset(ScatterPreset::Fog)
+            .with_scatter_density(2.5)
+            .build();
+
+        let mut child = BaseLightBuilder::new(BaseBuilder::new()).build();
+
+        child.inherit(&parent).unwrap();
+
+        check_inheritable_properties_equality(&child.base, &parent.base);
+        check_inheritable_properties_equality(&child, &parent);
+    }
+
+    #[test]
+    fn test_default_scatter_matches_scatter_factor() {
+        // Old scenes do not have density and anisotropy, defaults must not change their look.
+        let light = BaseLight::default();
+
+        assert_eq!(light.scatter_preset(), ScatterPreset::Custom);
+        assert_eq!(light.scatter_anisotropy(), 0.0);
+        assert_eq!(
+            light.effective_scatter(),
+            Vector3::new(DEFAULT_SCATTER_R, DEFAULT_SCATTER_G, DEFAULT_SCATTER_B)
+        );
+    }
+
+    #[test]
+    fn test_scatter_preset() {
+        let mut light = BaseLight::default();
+
+        light.set_scatter_preset(ScatterPreset::Fog);
+        let fog = ScatterPreset::Fog.parameters().unwrap();
+        assert_eq!(light.scatter(), fog.factor);
+        assert_eq!(light.scatter_density(), fog.density);
+        assert_eq!(light.scatter_anisotropy(), fog.anisotropy);
+
+        // Parameters are still tweakable after a preset was applied.
+        light.set_scatter_density(0.5);
+        light.set_scatter_anisotropy(2.0);
+        assert_eq!(light.scatter_preset(), ScatterPreset::Fog);
+        assert_eq!(light.effective_scatter(), fog.factor.scale(0.5));
+        assert_eq!(light.scatter_anisotropy(), MAX_SCATTER_ANISOTROPY);
+
+        // Custom preset keeps current parameters.
+        light.set_scatter_preset(ScatterPreset::Custom);
+        assert_eq!(light.scatter_preset(), ScatterPreset::Custom);
+        assert_eq!(light.scatter_density(), 0.5);
+    }
+
+    #[test]
+    fn test_shadows_culling_hysteresis() {
+        let light = BaseLight::default();
+
+        assert!(light.update_shadows_culling(10.0, 10.0));
+        // Inside the band, shadows keep rendering.
+        assert!(light.update_shadows_culling(10.4, 10.0));
+        assert!(!light.update_shadows_culling(10.6, 10.0));
+        // Inside the band, shadows stay culled.
+        assert!(!light.update_shadows_culling(9.6, 10.0));
+        assert!(!light.update_shadows_culling(10.0, 10.0));
+        assert!(light.update_shadows_culling(9.4, 10.0));
+    }
+}
@@ -17,6 +17,14 @@
 //! should be used carefully with sane values of light scattering, otherwise you'll
 //! get bright glowing cone instead of slightly visible light volume.
 //!
+//! # Orthographic projection
+//!
+//! Spot light can also work as a projector - in this mode it emits parallel rays
+//! in a box defined by width, height and distance instead of a cone. This could be
+//! useful to fake sunlight coming through a window without full CSM. Shadows of
+//! such light are rendered with orthographic projection. See [`SpotLightProjection`]
+//! for more info.
+//!
 //! # Performance notes
 //!
 //! Light scattering feature may significantly impact performance on low-end
@@ -25,6 +33,7 @@
 use crate::{
     core::variable::{InheritError, TemplateVariable},
     core::{
+        algebra::Matrix4,
         inspect::{Inspect, PropertyInfo},
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
@@ -44,6 +53,29 @@ use crate::{
 };
 use fxhash::FxHashMap;
 use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Defines the shape of light volume of a spot light.
+#[derive(
+    Copy, Clone, PartialEq, Eq, Hash, Debug, Visit, Inspect, AsRefStr, EnumString, EnumVariantNames,
+)]
+#[repr(u32)]
+pub enum SpotLightProjection {
+    /// Light is emitted from a single point in a cone defined by hotspot and falloff angles.
+    /// This is the default mode.
+    Perspective = 0,
+
+    /// Light is emitted with parallel rays in a box defined by width, height and distance of
+    /// the light. Hotspot and falloff angles are ignored in this mode. Light scattering is not
+    /// supported in this mode.
+    Orthographic = 1,
+}
+
+impl Default for SpotLightProjection {
+    fn default() -> Self {
+        Self::Perspective
+    }
+}
 
 /// See module docs.
 #[derive(Debug, Inspect, Clone, Visit)]
@@ -69,6 +101,22 @@ pub struct SpotLight {
 
     #[inspect(getter = "Deref::deref")]
     cookie_texture: TemplateVariable<Option<Texture>>,
+
+    #[inspect(getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    projection: TemplateVariable<SpotLightProjection>,
+
+    #[inspect(min_value = 0.0, step = 0.1, getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    ortho_width: TemplateVariable<f32>,
+
+    #[inspect(min_value = 0.0, step = 0.1, getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    ortho_height: TemplateVariable<f32>,
+
+    #[inspect(min_value = 0.0, max_value = 0.5, step = 0.01, getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    edge_falloff: TemplateVariable<f32>,
 }
 
 impl_directly_inheritable_entity_trait!(SpotLight;
@@ -76,7 +124,11 @@ impl_directly_inheritable_entity_trait!(SpotLight;
     falloff_angle_delta,
     shadow_bias,
     distance,
-    cookie_texture
+    cookie_texture,
+    projection,
+    ortho_width,
+    ortho_height,
+    edge_falloff
 );
 
 impl Deref for SpotLight {
@@ -102,6 +154,10 @@ impl Default for SpotLight {
             shadow_bias: TemplateVariable::new(0.00005),
             distance: TemplateVariable::new(10.0),
             cookie_texture: TemplateVariable::new(None),
+            projection: TemplateVariable::new(SpotLightProjection::Perspective),
+            ortho_width: TemplateVariable::new(5.0),
+            ortho_height: TemplateVariable::new(5.0),
+            edge_falloff: TemplateVariable::new(0.1),
         }
     }
 }
@@ -201,6 +257,82 @@ impl SpotLight {
     pub fn cookie_texture_ref(&self) -> Option<&Texture> {
         self.cookie_texture.as_ref()
     }
+
+    /// Sets new projection mode of the light. See [`SpotLightProjection`] docs for more info.
+    #[inline]
+    pub fn set_projection(&mut self, projection: SpotLightProjection) -> &mut Self {
+        self.projection.set(projection);
+        self
+    }
+
+    /// Returns current projection mode of the light.
+    #[inline]
+    pub fn projection(&self) -> SpotLightProjection {
+        *self.projection
+    }
+
+    /// Sets width of the light box. Used only in orthographic projection mode.
+    #[inline]
+    pub fn set_ortho_width(&mut self, width: f32) -> &mut Self {
+        self.ortho_width.set(width.abs());
+        self
+    }
+
+    /// Returns width of the light box.
+    #[inline]
+    pub fn ortho_width(&self) -> f32 {
+        *self.ortho_width
+    }
+
+    /// Sets height of the light box. Used only in orthographic projection mode.
+    #[inline]
+    pub fn set_ortho_height(&mut self, height: f32) -> &mut Self {
+        self.ortho_height.set(height.abs());
+        self
+    }
+
+    /// Returns height of the light box.
+    #[inline]
+    pub fn ortho_height(&self) -> f32 {
+        *self.ortho_height
+    }
+
+    /// Sets the size of the zone near the edges of the light box where intensity of light
+    /// smoothly falls to zero. The value is a fraction of the box size in (0; 0.5) range, zero
+    /// means hard edges. Used only in orthographic projection mode.
+    #[inline]
+    pub fn set_edge_falloff(&mut self, edge_falloff: f32) -> &mut Self {
+        self.edge_falloff.set(edge_falloff.max(0.0).min(0.5));
+        self
+    }
+
+    /// Returns current edge falloff of the light box.
+    #[inline]
+    pub fn edge_falloff(&self) -> f32 {
+        *self.edge_falloff
+    }
+
+    /// Returns projection matrix of the light for the given far clipping plane. The matrix is
+    /// used to render shadow maps and to project cookie texture.
+    pub fn projection_matrix(&self, z_far: f32) -> Matrix4<f32> {
+        match *self.projection {
+            SpotLightProjection::Perspective => {
+                Matrix4::new_perspective(1.0, self.full_cone_angle(), 0.01, z_far)
+            }
+            SpotLightProjection::Orthographic => {
+                let half_width = *self.ortho_width * 0.5;
+                let half_height = *self.ortho_height * 0.5;
+                Matrix4::new_orthographic(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    0.01,
+                    z_far,
+                )
+            }
+        }
+    }
 }
 
 impl NodeTrait for SpotLight {
@@ -254,6 +386,10 @@ pub struct SpotLightBuilder {
     shadow_bias: f32,
     distance: f32,
     cookie_texture: Option<Texture>,
+    projection: SpotLightProjection,
+    ortho_width: f32,
+    ortho_height: f32,
+    edge_falloff: f32,
 }
 
 impl SpotLightBuilder {
@@ -266,6 +402,10 @@ impl SpotLightBuilder {
             shadow_bias: 0.00005,
             distance: 10.0,
             cookie_texture: None,
+            projection: SpotLightProjection::Perspective,
+            ortho_width: 5.0,
+            ortho_height: 5.0,
+            edge_falloff: 0.1,
         }
     }
 
@@ -299,6 +439,25 @@ impl SpotLightBuilder {
         self
     }
 
+    /// Sets desired projection mode.
+    pub fn with_projection(mut self, projection: SpotLightProjection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Sets desired size of the light box. Used only in orthographic projection mode.
+    pub fn with_ortho_size(mut self, width: f32, height: f32) -> Self {
+        self.ortho_width = width;
+        self.ortho_height = height;
+        self
+    }
+
+    /// Sets desired edge falloff of the light box. Used only in orthographic projection mode.
+    pub fn with_edge_falloff(mut self, edge_falloff: f32) -> Self {
+        self.edge_falloff = edge_falloff;
+        self
+    }
+
     /// Creates new spot light.
     pub fn build_spot_light(self) -> SpotLight {
         SpotLight {
@@ -308,6 +467,10 @@ impl SpotLightBuilder {
             shadow_bias: self.shadow_bias.into(),
             distance: self.distance.into(),
             cookie_texture: self.cookie_texture.into(),
+            projection: self.projection.into(),
+            ortho_width: self.ortho_width.into(),
+            ortho_height: self.ortho_height.into(),
+            edge_falloff: self.edge_falloff.into(),
         }
     }
 
@@ -325,11 +488,12 @@ impl SpotLightBuilder {
 #[cfg(test)]
 mod test {
     use crate::{
+        core::algebra::{Point3, Vector3},
         resource::texture::test::create_test_texture,
         scene::{
             base::{test::check_inheritable_properties_equality, BaseBuilder},
             light::{
-                spot::{SpotLight, SpotLightBuilder},
+                spot::{SpotLight, SpotLightBuilder, SpotLightProjection},
                 BaseLightBuilder,
             },
             node::NodeTrait,
@@ -344,6 +508,9 @@ mod test {
             .with_falloff_angle_delta(0.1)
             .with_shadow_bias(1.0)
             .with_hotspot_cone_angle(0.1)
+            .with_projection(SpotLightProjection::Orthographic)
+            .with_ortho_size(2.0, 3.0)
+            .with_edge_falloff(0.2)
             .build_node();
 
         let mut child =
@@ -357,4 +524,19 @@ mod test {
         check_inheritable_properties_equality(&child.base_light, &parent.base_light);
         check_inheritable_properties_equality(&child, parent);
     }
+
+    #[test]
+    fn test_orthographic_spot_light_projection() {
+        let light = SpotLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new()))
+            .with_projection(SpotLightProjection::Orthographic)
+            .with_ortho_size(4.0, 2.0)
+            .build_spot_light();
+
+        // Far corner of the light box must be projected to the corner of the clip space.
+        let corner = light
+            .projection_matrix(10.0)
+            .transform_point(&Point3::new(2.0, 1.0, -10.0));
+
+        assert!((corner.coords - Vector3::new(1.0, 1.0, 1.0)).norm() < 1.0e-5);
+    }
 }
@@ -57,7 +57,8 @@ impl VertexAttributeDataType {
 }
 
 /// An usage for vertex attribute. It is a fixed set, but there are plenty
-/// room for any custom data - it may be fit into `TexCoordN` attributes.
+/// room for any custom data - it may be fit into `TexCoordN` attributes or
+/// into named custom attributes (see [`VertexBufferRefMut::add_custom_attribute`]).
 #[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Ord, Hash, Visit, Debug)]
 #[repr(u32)]
 pub enum VertexAttributeUsage {
@@ -88,6 +89,18 @@ pub enum VertexAttributeUsage {
     BoneWeight = 11,
     /// Bone indices. Usually Vector4<u8>.
     BoneIndices = 12,
+    /// First named custom attribute.
+    Custom0 = 13,
+    /// Second named custom attribute.
+    Custom1 = 14,
+    /// Third named custom attribute.
+    Custom2 = 15,
+    /// Fourth named custom attribute.
+    Custom3 = 16,
+    /// Vertex color. Usually Vector4<f32>, it must be bound to [`COLOR_SHADER_LOCATION`]. The
+    /// standard shader uses red channel of the color as flexibility of vegetation, see
+    /// [`crate::scene::wind::Wind`] for more info.
+    Color = 17,
     /// Maximum amount of attribute kinds.
     Count,
 }
@@ -98,6 +111,41 @@ impl Default for VertexAttributeUsage {
     }
 }
 
+/// Maximum amount of named custom attributes in a vertex buffer.
+pub const MAX_CUSTOM_ATTRIBUTES: usize = 4;
+
+/// Shader location of the first named custom attribute, every next custom attribute is bound to
+/// the next location. Locations below are occupied by the attributes of the standard vertices.
+pub const CUSTOM_ATTRIBUTES_SHADER_LOCATION: u8 = 7;
+
+/// Shader location of vertex color (see [`VertexAttributeUsage::Color`]), it goes right after the
+/// named custom attributes.
+pub const COLOR_SHADER_LOCATION: u8 =
+    CUSTOM_ATTRIBUTES_SHADER_LOCATION + MAX_CUSTOM_ATTRIBUTES as u8;
+
+impl VertexAttributeUsage {
+    /// Returns usage of n-th named custom attribute.
+    pub fn custom(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(Self::Custom0),
+            1 => Some(Self::Custom1),
+            2 => Some(Self::Custom2),
+            3 => Some(Self::Custom3),
+            _ => None,
+        }
+    }
+
+    /// Returns index of a named custom attribute, or `None` if the usage is a standard one.
+    pub fn custom_index(self) -> Option<usize> {
+        match self {
+            Self::Custom0 | Self::Custom1 | Self::Custom2 | Self::Custom3 => {
+                Some(self as usize - Self::Custom0 as usize)
+            }
+            _ => None,
+        }
+    }
+}
+
 /// Input vertex attribute descriptor used to construct layouts and feed vertex buffer.
 #[derive(Debug)]
 pub struct VertexAttributeDescriptor {
@@ -117,6 +165,19 @@ pub struct VertexAttributeDescriptor {
     pub shader_location: u8,
 }
 
+/// Input descriptor of a named custom vertex attribute, see
+/// [`VertexBufferRefMut::add_custom_attribute`].
+#[derive(Debug, Clone)]
+pub struct CustomVertexAttributeDescriptor {
+    /// A name of the attribute. Shaders bind the attribute by this name, see
+    /// [`crate::material::shader::Shader`] docs.
+    pub name: String,
+    /// Data type of every component of the attribute.
+    pub data_type: VertexAttributeDataType,
+    /// Size of attribute expressed in components, must be either 1, 2, 3 or 4.
+    pub size: u8,
+}
+
 /// Vertex attribute is a simple "bridge" between raw data and its interpretation. In
 /// other words it defines how to treat raw data in vertex shader.
 #[derive(Visit, Copy, Clone, Default, Debug)]
@@ -143,11 +204,14 @@ pub struct VertexAttribute {
 #[derive(Clone, Visit, Default, Debug)]
 pub struct VertexBuffer {
     dense_layout: Vec<VertexAttribute>,
-    sparse_layout: [Option<VertexAttribute>; 13],
+    sparse_layout: [Option<VertexAttribute>; VertexAttributeUsage::Count as usize],
     vertex_size: u8,
     vertex_count: u32,
     data: Vec<u8>,
     data_hash: u64,
+    // Names of custom attributes, n-th name belongs to `VertexAttributeUsage::custom(n)`.
+    #[visit(optional)] // Backward compatibility
+    custom_attribute_names: Vec<String>,
 }
 
 fn calculate_data_hash(data: &[u8]) -> u64 {
@@ -357,6 +421,86 @@ impl<'a> VertexBufferRefMut<'a> {
         }
     }
 
+    /// Adds new named custom attribute at the end of layout, see [`Self::add_attribute`] for
+    /// details. The attribute gets next free custom usage (`Custom0`, `Custom1`, ...) and shader
+    /// location (starting from [`CUSTOM_ATTRIBUTES_SHADER_LOCATION`]), the usage is returned, so
+    /// the data of the attribute could be filled using vertex accessors:
+    ///
+    /// ```no_run
+    /// use fyrox::scene::mesh::buffer::{
+    ///     CustomVertexAttributeDescriptor, VertexAttributeDataType, VertexBuffer,
+    ///     VertexWriteTrait,
+    /// };
+    ///
+    /// fn add_wetness(buffer: &mut VertexBuffer, wetness: &[f32]) {
+    ///     let mut buffer = buffer.modify();
+    ///     let usage = buffer
+    ///         .add_custom_attribute(
+    ///             CustomVertexAttributeDescriptor {
+    ///                 name: "wetness".to_owned(),
+    ///                 data_type: VertexAttributeDataType::F32,
+    ///                 size: 1,
+    ///             },
+    ///             0.0f32,
+    ///         )
+    ///         .unwrap();
+    ///     for (mut vertex, &value) in buffer.iter_mut().zip(wetness) {
+    ///         vertex.write_1_f32(usage, value).unwrap();
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// Custom attributes must be added before a surface with the buffer is rendered for the first
+    /// time, the renderer does not track changes of the layout.
+    pub fn add_custom_attribute<T: Copy>(
+        &mut self,
+        descriptor: CustomVertexAttributeDescriptor,
+        fill_value: T,
+    ) -> Result<VertexAttributeUsage, ValidationError> {
+        if descriptor.size < 1 || descriptor.size > 4 {
+            return Err(ValidationError::InvalidAttributeSize(
+                descriptor.size as usize,
+            ));
+        }
+
+        let expected = descriptor.size as usize * descriptor.data_type.size() as usize;
+        if std::mem::size_of::<T>() != expected {
+            return Err(ValidationError::InvalidDataSize {
+                expected,
+                actual: std::mem::size_of::<T>(),
+            });
+        }
+
+        if self
+            .vertex_buffer
+            .custom_attribute(&descriptor.name)
+            .is_some()
+        {
+            return Err(ValidationError::DuplicatedAttributeDescriptor);
+        }
+
+        let index = self.vertex_buffer.custom_attribute_names.len();
+        let usage =
+            VertexAttributeUsage::custom(index).ok_or(ValidationError::TooManyCustomAttributes)?;
+
+        self.add_attribute(
+            VertexAttributeDescriptor {
+                usage,
+                data_type: descriptor.data_type,
+                size: descriptor.size,
+                divisor: 0,
+                shader_location: CUSTOM_ATTRIBUTES_SHADER_LOCATION + index as u8,
+            },
+            fill_value,
+        )?;
+
+        self.vertex_buffer
+            .custom_attribute_names
+            .push(descriptor.name);
+
+        Ok(usage)
+    }
+
     /// Clears the buffer making it empty.
     pub fn clear(&mut self) {
         self.data.clear();
@@ -396,6 +540,10 @@ pub enum ValidationError {
     /// Duplicate shader locations were found.
     #[error("Duplicate shader locations were found {0}.")]
     ConflictingShaderLocations(usize),
+
+    /// There is no free slot for a named custom attribute.
+    #[error("Too many custom attributes, maximum is {}.", MAX_CUSTOM_ATTRIBUTES)]
+    TooManyCustomAttributes,
 }
 
 impl VertexBuffer {
@@ -472,6 +620,7 @@ impl VertexBuffer {
             data: bytes,
             sparse_layout,
             dense_layout,
+            custom_attribute_names: Default::default(),
         })
     }
 
@@ -551,6 +700,32 @@ impl VertexBuffer {
         &self.dense_layout
     }
 
+    /// Returns a named custom attribute, see [`VertexBufferRefMut::add_custom_attribute`].
+    pub fn custom_attribute(&self, name: &str) -> Option<VertexAttribute> {
+        self.custom_attribute_names
+            .iter()
+            .position(|n| n == name)
+            .and_then(VertexAttributeUsage::custom)
+            .and_then(|usage| self.sparse_layout[usage as usize])
+    }
+
+    /// Returns an iterator over names and descriptions of named custom attributes.
+    pub fn custom_attributes(&self) -> impl Iterator<Item = (&str, VertexAttribute)> + '_ {
+        self.custom_attribute_names
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, name)| {
+                VertexAttributeUsage::custom(index)
+                    .and_then(|usage| self.sparse_layout[usage as usize])
+                    .map(|attribute| (name.as_str(), attribute))
+            })
+    }
+
+    /// Returns true if the buffer has at least one named custom attribute.
+    pub fn has_custom_attributes(&self) -> bool {
+        !self.custom_attribute_names.is_empty()
+    }
+
     /// Tries to cast internal data buffer to a slice of given type. It may fail if
     /// size of type is not equal with claimed size (which is set by the layout).
     pub fn cast_data_ref<T: Copy>(&self) -> Result<&[T], ValidationError> {
@@ -729,6 +904,17 @@ pub trait VertexReadTrait {
     #[doc(hidden)]
     fn data_layout_ref(&self) -> (&[u8], &[Option<VertexAttribute>]);
 
+    /// Tries to read an attribute with given usage as a single f32.
+    #[inline(always)]
+    fn read_1_f32(&self, usage: VertexAttributeUsage) -> Result<f32, VertexFetchError> {
+        let (data, layout) = self.data_layout_ref();
+        if let Some(attribute) = layout.get(usage as usize).unwrap() {
+            Ok((&data[(attribute.offset as usize)..]).read_f32::<LittleEndian>()?)
+        } else {
+            Err(VertexFetchError::NoSuchAttribute(usage))
+        }
+    }
+
     /// Tries to read an attribute with given usage as a pair of two f32.
     #[inline(always)]
     fn read_2_f32(&self, usage: VertexAttributeUsage) -> Result<Vector2<f32>, VertexFetchError> {
@@ -799,6 +985,13 @@ pub trait VertexWriteTrait: VertexReadTrait {
     #[doc(hidden)]
     fn data_layout_mut(&mut self) -> (&mut [u8], &[Option<VertexAttribute>]);
 
+    /// Tries to write an attribute with given usage as a single f32.
+    fn write_1_f32(
+        &mut self,
+        usage: VertexAttributeUsage,
+        value: f32,
+    ) -> Result<(), VertexFetchError>;
+
     /// Tries to write an attribute with given usage as a pair of two f32.
     fn write_2_f32(
         &mut self,
@@ -839,6 +1032,20 @@ impl<'a> VertexWriteTrait for VertexViewMut<'a> {
         (self.vertex_data, self.sparse_layout)
     }
 
+    fn write_1_f32(
+        &mut self,
+        usage: VertexAttributeUsage,
+        value: f32,
+    ) -> Result<(), VertexFetchError> {
+        let (data, layout) = self.data_layout_mut();
+        if let Some(attribute) = layout.get(usage as usize).unwrap() {
+            (&mut data[(attribute.offset as usize)..]).write_f32::<LittleEndian>(value)?;
+            Ok(())
+        } else {
+            Err(VertexFetchError::NoSuchAttribute(usage))
+        }
+    }
+
     fn write_2_f32(
         &mut self,
         usage: VertexAttributeUsage,
@@ -1036,10 +1243,14 @@ impl<'a> IndexMut<usize> for TriangleBufferRefMut<'a> {
 #[cfg(test)]
 mod test {
     use crate::{
-        core::algebra::{Vector2, Vector3, Vector4},
+        core::{
+            algebra::{Vector2, Vector3, Vector4},
+            visitor::{Visit, Visitor},
+        },
         scene::mesh::buffer::{
-            VertexAttributeDataType, VertexAttributeDescriptor, VertexAttributeUsage, VertexBuffer,
-            VertexReadTrait,
+            CustomVertexAttributeDescriptor, ValidationError, VertexAttributeDataType,
+            VertexAttributeDescriptor, VertexAttributeUsage, VertexBuffer, VertexReadTrait,
+            VertexWriteTrait, CUSTOM_ATTRIBUTES_SHADER_LOCATION, MAX_CUSTOM_ATTRIBUTES,
         },
     };
 
@@ -1311,4 +1522,127 @@ mod test {
             new_1.bone_indices
         );
     }
+
+    fn add_wetness(buffer: &mut VertexBuffer) -> VertexAttributeUsage {
+        let mut buffer = buffer.modify();
+        let usage = buffer
+            .add_custom_attribute(
+                CustomVertexAttributeDescriptor {
+                    name: "wetness".to_owned(),
+                    data_type: VertexAttributeDataType::F32,
+                    size: 1,
+                },
+                0.0f32,
+            )
+            .unwrap();
+        for (i, mut vertex) in buffer.iter_mut().enumerate() {
+            vertex.write_1_f32(usage, i as f32 * 0.5).unwrap();
+        }
+        usage
+    }
+
+    #[test]
+    fn test_add_custom_attribute() {
+        let mut buffer = create_test_buffer();
+        assert!(!buffer.has_custom_attributes());
+
+        let usage = add_wetness(&mut buffer);
+        assert_eq!(usage, VertexAttributeUsage::Custom0);
+
+        let attribute = buffer.custom_attribute("wetness").unwrap();
+        assert_eq!(attribute.usage, VertexAttributeUsage::Custom0);
+        assert_eq!(attribute.shader_location, CUSTOM_ATTRIBUTES_SHADER_LOCATION);
+        assert_eq!(attribute.size, 1);
+        assert!(buffer.custom_attribute("dryness").is_none());
+        assert_eq!(
+            buffer
+                .custom_attributes()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+            vec!["wetness"]
+        );
+
+        // Standard attributes must stay intact.
+        for (i, (view, original)) in buffer.iter().zip(VERTICES.iter()).enumerate() {
+            assert_eq!(view.read_1_f32(usage).unwrap(), i as f32 * 0.5);
+            test_view_original_equal(view, original);
+        }
+
+        // Names must be unique.
+        assert!(matches!(
+            buffer.modify().add_custom_attribute(
+                CustomVertexAttributeDescriptor {
+                    name: "wetness".to_owned(),
+                    data_type: VertexAttributeDataType::F32,
+                    size: 1,
+                },
+                0.0f32,
+            ),
+            Err(ValidationError::DuplicatedAttributeDescriptor)
+        ));
+
+        // Fill value must match the size of the attribute.
+        assert!(matches!(
+            buffer.modify().add_custom_attribute(
+                CustomVertexAttributeDescriptor {
+                    name: "secondColor".to_owned(),
+                    data_type: VertexAttributeDataType::U8,
+                    size: 4,
+                },
+                0.0f32,
+            ),
+            Err(ValidationError::InvalidDataSize { .. })
+        ));
+    }
+
+    #[test]
+    fn test_too_many_custom_attributes() {
+        let mut buffer = create_test_buffer();
+
+        let descriptor = |index: usize| CustomVertexAttributeDescriptor {
+            name: format!("attribute{}", index),
+            data_type: VertexAttributeDataType::U8,
+            size: 4,
+        };
+
+        for index in 0..MAX_CUSTOM_ATTRIBUTES {
+            let usage = buffer
+                .modify()
+                .add_custom_attribute(descriptor(index), [index as u8; 4])
+                .unwrap();
+            assert_eq!(usage.custom_index(), Some(index));
+        }
+
+        assert!(matches!(
+            buffer
+                .modify()
+                .add_custom_attribute(descriptor(MAX_CUSTOM_ATTRIBUTES), [0u8; 4]),
+            Err(ValidationError::TooManyCustomAttributes)
+        ));
+    }
+
+    #[test]
+    fn test_custom_attributes_serialization() {
+        let mut buffer = create_test_buffer();
+        add_wetness(&mut buffer);
+
+        let mut visitor = Visitor::new();
+        buffer.visit("Buffer", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        let mut loaded = VertexBuffer::default();
+        loaded.visit("Buffer", &mut visitor).unwrap();
+
+        assert_eq!(loaded.raw_data(), buffer.raw_data());
+        assert_eq!(loaded.data_hash(), buffer.data_hash());
+        let attribute = loaded.custom_attribute("wetness").unwrap();
+        assert_eq!(attribute.usage, VertexAttributeUsage::Custom0);
+        for (i, view) in loaded.iter().enumerate() {
+            assert_eq!(
+                view.read_1_f32(VertexAttributeUsage::Custom0).unwrap(),
+                i as f32 * 0.5
+            );
+        }
+    }
 }
@@ -9,11 +9,12 @@
 //! 3d model can contain multiple nodes, 3d model loading discussed in model resource section.
 
 use crate::{
-    core::variable::{InheritError, TemplateVariable, VariableFlags},
+    core::variable::{InheritError, InheritableVariable, TemplateVariable, VariableFlags},
     core::{
         algebra::{Matrix4, Point3, Vector3},
         inspect::{Inspect, PropertyInfo},
-        math::aabb::AxisAlignedBoundingBox,
+        math::{aabb::AxisAlignedBoundingBox, Matrix4Ext},
+        parking_lot::Mutex,
         pool::Handle,
         uuid::{uuid, Uuid},
         visitor::{Visit, VisitResult, Visitor},
@@ -25,20 +26,24 @@ use crate::{
         graph::Graph,
         mesh::{
             buffer::{VertexAttributeUsage, VertexReadTrait},
-            surface::Surface,
+            socket::Socket,
+            surface::{Surface, SurfaceData},
         },
         node::{Node, NodeTrait, TypeUuidProvider, UpdateContext},
         DirectlyInheritableEntity,
     },
+    utils::log::Log,
 };
 use fxhash::FxHashMap;
 use std::{
     cell::Cell,
     ops::{Deref, DerefMut},
+    sync::Arc,
 };
 use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
 pub mod buffer;
+pub mod socket;
 pub mod surface;
 pub mod vertex;
 
@@ -101,6 +106,14 @@ pub struct Mesh {
     #[inspect(getter = "Deref::deref")]
     decal_layer_index: TemplateVariable<u8>,
 
+    #[inspect(getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    sockets: TemplateVariable<Vec<Socket>>,
+
+    #[inspect(getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    bounds_padding: TemplateVariable<f32>,
+
     #[inspect(skip)]
     #[visit(skip)]
     local_bounding_box: Cell<AxisAlignedBoundingBox>,
@@ -117,7 +130,9 @@ pub struct Mesh {
 impl_directly_inheritable_entity_trait!(Mesh;
     surfaces,
     render_path,
-    decal_layer_index
+    decal_layer_index,
+    sockets,
+    bounds_padding
 );
 
 impl Default for Mesh {
@@ -133,6 +148,8 @@ impl Default for Mesh {
             local_bounding_box_dirty: Cell::new(true),
             render_path: TemplateVariable::new(RenderPath::Deferred),
             decal_layer_index: TemplateVariable::new(0),
+            sockets: Default::default(),
+            bounds_padding: TemplateVariable::new(0.0),
         }
     }
 }
@@ -170,6 +187,33 @@ impl Mesh {
         self.surfaces.get_mut_silent()
     }
 
+    /// Replaces data of a surface with the given index and returns previous data, `None` if there
+    /// is no such surface. Unlike changes made through [`Self::surfaces_mut`], this marks the
+    /// surfaces of the mesh as modified, so they're saved with a scene and are not replaced with
+    /// the surfaces of a parent model resource when the scene is loaded. Only procedural data is
+    /// saved (see [`SurfaceData::procedural_copy`]).
+    pub fn set_surface_data(
+        &mut self,
+        index: usize,
+        data: Arc<Mutex<SurfaceData>>,
+    ) -> Option<Arc<Mutex<SurfaceData>>> {
+        let old_data = self.surfaces.get_mut().get_mut(index)?.set_data(data);
+        self.local_bounding_box_dirty.set(true);
+        Some(old_data)
+    }
+
+    /// Returns `true` if surfaces of the mesh were modified and won't be inherited from a parent
+    /// model resource, see [`Self::set_surface_data`].
+    pub fn is_surfaces_modified(&self) -> bool {
+        self.surfaces.is_modified()
+    }
+
+    /// Makes surfaces of the mesh inherited from a parent model resource again, they will be
+    /// replaced with the surfaces of the resource when a scene is loaded.
+    pub fn reset_surfaces_modified_flag(&mut self) {
+        self.surfaces.reset_modified_flag();
+    }
+
     /// Removes all surfaces from mesh.
     #[inline]
     pub fn clear_surfaces(&mut self) {
@@ -263,6 +307,45 @@ impl Mesh {
     pub fn decal_layer_index(&self) -> u8 {
         *self.decal_layer_index
     }
+
+    /// Returns shared reference to array of attachment sockets of the mesh.
+    pub fn sockets(&self) -> &[Socket] {
+        &self.sockets
+    }
+
+    /// Returns mutable reference to array of attachment sockets of the mesh.
+    pub fn sockets_mut(&mut self) -> &mut Vec<Socket> {
+        self.sockets.get_mut()
+    }
+
+    /// Sets new attachment sockets of the mesh.
+    pub fn set_sockets(&mut self, sockets: Vec<Socket>) -> Vec<Socket> {
+        std::mem::replace(self.sockets.get_mut(), sockets)
+    }
+
+    /// Adds new attachment socket to the mesh.
+    pub fn add_socket(&mut self, socket: Socket) {
+        self.sockets.get_mut().push(socket);
+    }
+
+    /// Sets additional padding for bounds of skinned surfaces of the mesh. Bounds of skinned
+    /// surfaces are calculated from actual positions of bones, which is precise enough for
+    /// most of rigs. However vertices of rigs with stretchy (scaled) bones may go beyond
+    /// the bounds, the padding could be used to prevent such meshes from being culled when
+    /// they're still visible. Default value is zero.
+    pub fn set_bounds_padding(&mut self, padding: f32) {
+        self.bounds_padding.set(padding.max(0.0));
+    }
+
+    /// Returns current padding of bounds of skinned surfaces of the mesh.
+    pub fn bounds_padding(&self) -> f32 {
+        *self.bounds_padding
+    }
+
+    /// Tries to find an attachment socket by its name.
+    pub fn find_socket(&self, name: &str) -> Option<&Socket> {
+        self.sockets.iter().find(|s| s.name == name)
+    }
 }
 
 impl NodeTrait for Mesh {
@@ -274,7 +357,8 @@ impl NodeTrait for Mesh {
         self.local_bounding_box.get()
     }
 
-    /// Returns current **world-space** bounding box.
+    /// Returns current **world-space** bounding box. Bounds of skinned surfaces are defined
+    /// by actual positions of bones, see [`Surface::calculate_bone_radii`] for more info.
     fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
         self.world_bounding_box.get()
     }
@@ -311,6 +395,18 @@ impl NodeTrait for Mesh {
                 }
             }
         }
+
+        let name = self.name_owned();
+        for socket in self.sockets.get_mut_silent() {
+            if let Some(entry) = old_new_mapping.get(&socket.bone) {
+                socket.bone = *entry;
+            } else if socket.bone.is_some() {
+                Log::warn(format!(
+                    "Unable to remap bone handle of socket {} of a mesh {}. Handle is {}!",
+                    socket.name, name, socket.bone
+                ))
+            }
+        }
     }
 
     fn id(&self) -> Uuid {
@@ -333,17 +429,48 @@ impl NodeTrait for Mesh {
         }
 
         if self.surfaces.iter().any(|s| !s.bones.is_empty()) {
-            let mut world_aabb = self
-                .local_bounding_box()
-                .transform(&self.global_transform());
-
-            // Special case for skinned meshes.
+            // Special case for skinned meshes - bounds of skinned surfaces are defined by actual
+            // positions of their bones, bind pose geometry has nothing in common with actual
+            // positions of vertices.
+            let mut world_aabb = AxisAlignedBoundingBox::default();
+            let mut has_static_surfaces = false;
             for surface in self.surfaces.iter() {
-                for &bone in surface.bones() {
-                    world_aabb.add_point(context.nodes[bone].global_position())
+                if surface.bones.is_empty() {
+                    has_static_surfaces = true;
+                } else if surface.bone_radii().len() == surface.bones.len() {
+                    // Each vertex is a weighted sum of its positions relative to each bone it
+                    // is affected by, so the union of spheres around bones contains the vertex.
+                    for (&bone, &radius) in surface.bones.iter().zip(surface.bone_radii()) {
+                        let bone = &context.nodes[bone];
+                        let transform = bone.global_transform();
+                        let scale = transform
+                            .side()
+                            .norm()
+                            .max(transform.up().norm())
+                            .max(transform.look().norm());
+                        let position = bone.global_position();
+                        let radius = Vector3::repeat(radius * scale);
+                        world_aabb.add_point(position - radius);
+                        world_aabb.add_point(position + radius);
+                    }
+                } else {
+                    // Radii of bones are unknown, use bind pose bounds and positions of bones.
+                    has_static_surfaces = true;
+                    for &bone in surface.bones() {
+                        world_aabb.add_point(context.nodes[bone].global_position())
+                    }
                 }
             }
 
+            world_aabb.inflate(Vector3::repeat(2.0 * *self.bounds_padding));
+
+            if has_static_surfaces {
+                world_aabb.add_box(
+                    self.local_bounding_box()
+                        .transform(&self.global_transform()),
+                );
+            }
+
             self.world_bounding_box.set(world_aabb)
         } else {
             self.world_bounding_box.set(
@@ -362,6 +489,8 @@ pub struct MeshBuilder {
     surfaces: Vec<Surface>,
     render_path: RenderPath,
     decal_layer_index: u8,
+    sockets: Vec<Socket>,
+    bounds_padding: f32,
 }
 
 impl MeshBuilder {
@@ -372,6 +501,8 @@ impl MeshBuilder {
             surfaces: Default::default(),
             render_path: RenderPath::Deferred,
             decal_layer_index: 0,
+            sockets: Default::default(),
+            bounds_padding: 0.0,
         }
     }
 
@@ -394,6 +525,19 @@ impl MeshBuilder {
         self
     }
 
+    /// Sets desired attachment sockets.
+    pub fn with_sockets(mut self, sockets: Vec<Socket>) -> Self {
+        self.sockets = sockets;
+        self
+    }
+
+    /// Sets desired padding for bounds of skinned surfaces. See [`Mesh::set_bounds_padding`]
+    /// for more info.
+    pub fn with_bounds_padding(mut self, padding: f32) -> Self {
+        self.bounds_padding = padding.max(0.0);
+        self
+    }
+
     /// Creates new mesh.
     pub fn build_node(self) -> Node {
         Node::new(Mesh {
@@ -403,6 +547,8 @@ impl MeshBuilder {
             local_bounding_box_dirty: Cell::new(true),
             render_path: self.render_path.into(),
             decal_layer_index: self.decal_layer_index.into(),
+            sockets: self.sockets.into(),
+            bounds_padding: self.bounds_padding.into(),
             world_bounding_box: Default::default(),
         })
     }
@@ -412,3 +558,186 @@ impl MeshBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Point3, Vector2, Vector3},
+            math::TriangleDefinition,
+            parking_lot::Mutex,
+            pool::Handle,
+            visitor::{Visit, Visitor},
+        },
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            mesh::{
+                buffer::{TriangleBuffer, VertexBuffer},
+                surface::{SurfaceBuilder, SurfaceData},
+                vertex::AnimatedVertex,
+                Mesh, MeshBuilder,
+            },
+            node::Node,
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+    use std::sync::Arc;
+
+    fn make_bone(
+        graph: &mut Graph,
+        position: Vector3<f32>,
+        bind_position: Vector3<f32>,
+    ) -> Handle<Node> {
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                )
+                .with_inv_bind_pose_transform(Matrix4::new_translation(&-bind_position)),
+        )
+        .build(graph)
+    }
+
+    // Two bones: root bone at the origin and its child one unit above it.
+    fn make_skinned_mesh(graph: &mut Graph, padding: f32) -> (Handle<Node>, Handle<Node>) {
+        let root = make_bone(graph, Vector3::default(), Vector3::default());
+        let child = make_bone(
+            graph,
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+        );
+        graph.link_nodes(child, root);
+
+        let vertices = vec![
+            AnimatedVertex {
+                position: Vector3::new(0.5, 0.0, 0.0),
+                bone_weights: [1.0, 0.0, 0.0, 0.0],
+                bone_indices: [0, 0, 0, 0],
+                ..Default::default()
+            },
+            AnimatedVertex {
+                position: Vector3::new(0.0, 2.0, 0.0),
+                bone_weights: [1.0, 0.0, 0.0, 0.0],
+                bone_indices: [1, 0, 0, 0],
+                ..Default::default()
+            },
+            AnimatedVertex {
+                position: Vector3::new(0.0, 1.0, 0.0),
+                bone_weights: [0.5, 0.5, 0.0, 0.0],
+                bone_indices: [0, 1, 0, 0],
+                ..Default::default()
+            },
+        ];
+        let data = SurfaceData::new(
+            VertexBuffer::new(vertices.len(), AnimatedVertex::layout(), vertices).unwrap(),
+            TriangleBuffer::new(vec![TriangleDefinition([0, 1, 2])]),
+            true,
+        );
+        let mut surface = SurfaceBuilder::new(Arc::new(Mutex::new(data)))
+            .with_bones(vec![root, child])
+            .build();
+        surface.calculate_bone_radii(&[
+            graph[root].inv_bind_pose_transform(),
+            graph[child].inv_bind_pose_transform(),
+        ]);
+
+        let mesh = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![surface])
+            .with_bounds_padding(padding)
+            .build(graph);
+
+        (mesh, child)
+    }
+
+    #[test]
+    fn test_bone_radii() {
+        let mut graph = Graph::new();
+        let (mesh, _) = make_skinned_mesh(&mut graph, 0.0);
+
+        let radii = graph[mesh].as_mesh().surfaces()[0].bone_radii().to_vec();
+        assert_eq!(radii.len(), 2);
+        assert!((radii[0] - 1.0).abs() < 1.0e-5);
+        assert!((radii[1] - 1.0).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_skinned_mesh_bounds_follow_bones() {
+        let mut graph = Graph::new();
+        let (mesh, bone) = make_skinned_mesh(&mut graph, 0.0);
+
+        // Mesh itself stays at the origin while the bone moves far away.
+        graph[bone]
+            .local_transform_mut()
+            .set_position(Vector3::new(100.0, 1.0, 0.0));
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0);
+
+        let bone_position = graph[bone].global_position();
+        assert_eq!(bone_position, Vector3::new(100.0, 1.0, 0.0));
+
+        // Skinned position of the vertex affected by the bone only.
+        let vertex = (graph[bone].global_transform() * graph[bone].inv_bind_pose_transform())
+            .transform_point(&Point3::new(0.0, 2.0, 0.0))
+            .coords;
+
+        let bounds = graph[mesh].world_bounding_box();
+        assert!(bounds.is_contains_point(bone_position));
+        assert!(bounds.is_contains_point(vertex));
+        assert!(bounds.is_contains_point(Vector3::new(0.5, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_skinned_mesh_bounds_padding() {
+        let mut graph = Graph::new();
+        let (mesh, _) = make_skinned_mesh(&mut graph, 2.0);
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0);
+
+        // Spheres around bones give [-1; 2] range along Y axis, padding extends it on both sides.
+        let bounds = graph[mesh].world_bounding_box();
+        assert!(bounds.is_contains_point(Vector3::new(0.0, 3.9, 0.0)));
+        assert!(bounds.is_contains_point(Vector3::new(-2.9, 0.0, 0.0)));
+        assert!(!bounds.is_contains_point(Vector3::new(0.0, 4.1, 0.0)));
+    }
+
+    #[test]
+    fn test_procedural_copy_of_surface_data_is_saved() {
+        // Data of model resources is not saved with a scene.
+        let quad = SurfaceData::make_quad(&Matrix4::identity());
+        let resource_data = Arc::new(Mutex::new(SurfaceData::new(
+            quad.vertex_buffer.clone(),
+            quad.geometry_buffer.clone(),
+            false,
+        )));
+
+        let mut node = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(resource_data.clone()).build()])
+            .build_node();
+        let mesh = node.as_mesh_mut();
+        assert!(!mesh.is_surfaces_modified());
+
+        let copy = Arc::new(Mutex::new(resource_data.lock().procedural_copy()));
+        let old_data = mesh.set_surface_data(0, copy).unwrap();
+        assert!(Arc::ptr_eq(&old_data, &resource_data));
+        assert!(mesh.is_surfaces_modified());
+
+        let mut visitor = Visitor::new();
+        mesh.visit("Mesh", &mut visitor).unwrap();
+        let mut visitor = Visitor::load_from_memory(visitor.save_binary_to_vec().unwrap()).unwrap();
+        let mut loaded = Mesh::default();
+        loaded.visit("Mesh", &mut visitor).unwrap();
+
+        // Modified surfaces are not inherited from the resource when a scene is loaded.
+        assert!(loaded.is_surfaces_modified());
+        let loaded_data = loaded.surfaces()[0].data();
+        let loaded_data = loaded_data.lock();
+        assert!(loaded_data.is_procedural());
+        assert_eq!(loaded_data.vertex_buffer.vertex_count(), 4);
+        assert_eq!(
+            loaded_data.geometry_buffer.triangles_ref(),
+            quad.geometry_buffer.triangles_ref()
+        );
+    }
+}
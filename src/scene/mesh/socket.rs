@@ -0,0 +1,116 @@
+//! Attachment sockets allows you to attach arbitrary nodes to bones of a skinned mesh using
+//! stable names. See [`Socket`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        inspect::{Inspect, PropertyInfo},
+        pool::Handle,
+        visitor::prelude::*,
+    },
+    scene::node::Node,
+};
+use std::fmt::{Display, Formatter};
+
+/// Socket is a named attachment point on a skinned mesh. It binds a name to a bone of the mesh
+/// and an offset relative to the bone. Sockets allows you to attach things (weapons, props, etc.)
+/// to a character without searching bones by their names in game code, which is fragile because
+/// bone names may change when a model is re-exported or retargeted.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::{algebra::Vector3, pool::Handle},
+///     scene::{graph::Graph, mesh::socket::Socket, node::Node},
+/// };
+///
+/// fn attach_sword(graph: &mut Graph, character_mesh: Handle<Node>, sword: Handle<Node>) {
+///     graph
+///         .attach_to_socket(character_mesh, "RightHand", sword)
+///         .expect("Character must have RightHand socket!");
+/// }
+///
+/// fn make_socket(hand_bone: Handle<Node>) -> Socket {
+///     Socket::new("RightHand", hand_bone).with_position(Vector3::new(0.0, 0.1, 0.0))
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Visit, Inspect)]
+pub struct Socket {
+    /// Name of the socket, it should be unique within a mesh.
+    pub name: String,
+    /// A handle of a bone to which the socket is bound. The editor shows a drop-down list with the
+    /// bones of the mesh to pick the bone by its name.
+    pub bone: Handle<Node>,
+    /// Local offset of the socket relative to the bone.
+    pub position: Vector3<f32>,
+    /// Local rotation of the socket relative to the bone.
+    pub rotation: UnitQuaternion<f32>,
+}
+
+impl Default for Socket {
+    fn default() -> Self {
+        Self {
+            name: "Socket".to_owned(),
+            bone: Default::default(),
+            position: Default::default(),
+            rotation: UnitQuaternion::identity(),
+        }
+    }
+}
+
+impl Socket {
+    /// Creates new socket bound to the given bone with zero offset.
+    pub fn new<N: AsRef<str>>(name: N, bone: Handle<Node>) -> Self {
+        Self {
+            name: name.as_ref().to_owned(),
+            bone,
+            ..Default::default()
+        }
+    }
+
+    /// Sets desired local offset of the socket.
+    pub fn with_position(mut self, position: Vector3<f32>) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Sets desired local rotation of the socket.
+    pub fn with_rotation(mut self, rotation: UnitQuaternion<f32>) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Returns local transformation matrix of the socket (relative to the bone).
+    pub fn local_matrix(&self) -> Matrix4<f32> {
+        Matrix4::new_translation(&self.position) * self.rotation.to_homogeneous()
+    }
+}
+
+/// An error that may occur when working with sockets.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SocketError {
+    /// A node is not a mesh.
+    NotAMesh(Handle<Node>),
+    /// A mesh has no socket with the given name.
+    NoSuchSocket(String),
+    /// A socket is bound to a bone that does not exist in the graph.
+    InvalidBone {
+        /// Name of the socket.
+        socket: String,
+        /// Handle of the bone.
+        bone: Handle<Node>,
+    },
+}
+
+impl Display for SocketError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SocketError::NotAMesh(handle) => write!(f, "Node {} is not a mesh!", handle),
+            SocketError::NoSuchSocket(name) => write!(f, "There is no socket named {}!", name),
+            SocketError::InvalidBone { socket, bone } => {
+                write!(f, "Socket {} is bound to invalid bone {}!", socket, bone)
+            }
+        }
+    }
+}
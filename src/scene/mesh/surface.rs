@@ -838,6 +838,26 @@ impl SurfaceData {
         )
     }
 
+    /// Returns `true` if the data was generated procedurally. Procedural data is saved together
+    /// with a scene, other data (for example, surfaces of model resources) is taken from its
+    /// resource when a scene is loaded.
+    pub fn is_procedural(&self) -> bool {
+        self.is_procedural
+    }
+
+    /// Creates a procedural copy of the data, that could be modified without affecting the
+    /// source (and everything that uses the source) and is saved together with a scene. Unlike
+    /// `clone`, the copy does not share GPU buffers with the source, so its vertex layout could
+    /// be changed before it is rendered for the first time.
+    pub fn procedural_copy(&self) -> Self {
+        Self {
+            vertex_buffer: self.vertex_buffer.clone(),
+            geometry_buffer: self.geometry_buffer.clone(),
+            is_procedural: true,
+            cache_entry: AtomicIndex::unassigned(),
+        }
+    }
+
     /// Clears both vertex and index buffers.
     pub fn clear(&mut self) {
         self.geometry_buffer.modify().clear();
@@ -956,6 +976,11 @@ pub struct Surface {
     pub vertex_weights: Vec<VertexWeightSet>,
     /// Array of handle to scene nodes which are used as bones.
     pub bones: Vec<Handle<Node>>,
+    /// Radius of each bone from `bones` array - maximum distance from a bone to vertices it
+    /// affects, measured in bind pose. Used to calculate bounds of skinned meshes.
+    #[inspect(skip)]
+    #[visit(optional)] // Backward compatibility
+    bone_radii: Vec<f32>,
 }
 
 impl PartialEq for Surface {
@@ -969,6 +994,7 @@ impl PartialEq for Surface {
         let material_equal = Arc::ptr_eq(&self.material, &other.material);
 
         self.bones == other.bones
+            && self.bone_radii == other.bone_radii
             && self.vertex_weights == other.vertex_weights
             && data_equal
             && material_equal
@@ -982,6 +1008,7 @@ impl Default for Surface {
             material: Arc::new(Mutex::new(Material::standard())),
             vertex_weights: Default::default(),
             bones: Default::default(),
+            bone_radii: Default::default(),
         }
     }
 }
@@ -1015,6 +1042,12 @@ impl Surface {
         self.data.as_ref().unwrap().clone()
     }
 
+    /// Sets new data of the surface and returns previous data.
+    #[inline]
+    pub fn set_data(&mut self, data: Arc<Mutex<SurfaceData>>) -> Arc<Mutex<SurfaceData>> {
+        self.data.replace(data).unwrap()
+    }
+
     /// Returns current material of the surface.
     pub fn material(&self) -> &Arc<Mutex<Material>> {
         &self.material
@@ -1030,6 +1063,55 @@ impl Surface {
     pub fn bones(&self) -> &[Handle<Node>] {
         &self.bones
     }
+
+    /// Returns radii of bones of the surface (in the same order as bones), it could be empty
+    /// if the radii were never calculated. See [`Self::calculate_bone_radii`] for more info.
+    #[inline]
+    pub fn bone_radii(&self) -> &[f32] {
+        &self.bone_radii
+    }
+
+    /// Calculates radius of each bone of the surface - maximum distance from a bone to
+    /// vertices it affects, measured in bind pose. The radii are used to calculate bounds of
+    /// skinned meshes from actual positions of bones. `inv_bind_poses` must contain inverse
+    /// bind pose transforms of the bones in the same order as the bones of the surface.
+    ///
+    /// This method is called automatically when a model is imported, it must be called
+    /// manually for procedural skinned surfaces, otherwise bounds of meshes will be less
+    /// precise.
+    pub fn calculate_bone_radii(&mut self, inv_bind_poses: &[Matrix4<f32>]) {
+        let mut radii = vec![0.0f32; self.bones.len()];
+
+        let data = self.data();
+        let data = data.lock();
+        for view in data.vertex_buffer.iter() {
+            if let (Ok(position), Ok(indices), Ok(weights)) = (
+                view.read_3_f32(VertexAttributeUsage::Position),
+                view.read_4_u8(VertexAttributeUsage::BoneIndices),
+                view.read_4_f32(VertexAttributeUsage::BoneWeight),
+            ) {
+                for (&index, &weight) in indices.iter().zip(weights.iter()) {
+                    if weight <= 0.0 {
+                        continue;
+                    }
+
+                    if let (Some(radius), Some(inv_bind_pose)) = (
+                        radii.get_mut(index as usize),
+                        inv_bind_poses.get(index as usize),
+                    ) {
+                        // Position of the vertex relative to the bone in bind pose.
+                        let distance = inv_bind_pose
+                            .transform_point(&Point3::from(position))
+                            .coords
+                            .norm();
+                        *radius = radius.max(distance);
+                    }
+                }
+            }
+        }
+
+        self.bone_radii = radii;
+    }
 }
 
 /// Surface builder allows you to create surfaces in declarative manner.
@@ -1070,6 +1152,7 @@ impl SurfaceBuilder {
                 .unwrap_or_else(|| Arc::new(Mutex::new(Material::standard()))),
             vertex_weights: Default::default(),
             bones: self.bones,
+            bone_radii: Default::default(),
         }
     }
 }
@@ -7,23 +7,34 @@
 pub mod accel;
 pub mod base;
 pub mod camera;
+pub mod collection;
 pub mod collider;
 pub mod debug;
 pub mod decal;
 pub mod dim2;
+pub mod fog_volume;
 pub mod graph;
+pub mod hitbox;
+pub mod hlod;
+pub mod ik;
+pub mod impostor;
 pub mod joint;
 pub mod light;
 pub mod mesh;
+pub mod navmesh_obstacle;
 pub mod node;
 pub mod particle_system;
 pub mod pivot;
+pub mod reflection_probe;
 pub mod rigidbody;
 pub mod sound;
 pub mod sprite;
+pub mod state;
 pub mod terrain;
 pub mod transform;
+pub mod trigger_volume;
 pub mod visibility;
+pub mod wind;
 
 use crate::{
     animation::{machine::container::AnimationMachineContainer, AnimationContainer},
@@ -38,21 +49,25 @@ use crate::{
         sstorage::ImmutableString,
         visitor::{Visit, VisitError, VisitResult, Visitor},
     },
-    engine::{resource_manager::ResourceManager, SerializationContext},
+    engine::{resource_manager::ResourceManager, task::TaskContainer, SerializationContext},
     material::{shader::SamplerFallback, PropertyValue},
-    resource::texture::Texture,
+    resource::texture::{Texture, TextureKind},
     scene::{
         camera::Camera,
-        debug::SceneDrawingContext,
+        collection::NodeCollectionContainer,
+        debug::{DebugDrawBuffer, SceneDrawingContext},
         graph::{Graph, GraphPerformanceStatistics},
         mesh::buffer::{
             VertexAttributeDataType, VertexAttributeDescriptor, VertexAttributeUsage,
             VertexWriteTrait,
         },
         mesh::Mesh,
+        navmesh_obstacle::NavmeshObstacle,
         node::Node,
+        particle_system::ParticleSystem,
         sound::SoundEngine,
     },
+    script::start::ScriptStartQueue,
     utils::{lightmap::Lightmap, log::Log, log::MessageKind, navmesh::Navmesh},
 };
 use fxhash::FxHashMap;
@@ -201,6 +216,27 @@ impl NavMeshContainer {
     pub fn try_get_mut(&mut self, handle: Handle<Navmesh>) -> Option<&mut Navmesh> {
         self.pool.try_borrow_mut(handle)
     }
+
+    /// Carves every navmesh by every navmesh obstacle of the graph, only changed obstacles are
+    /// applied. It is called automatically once per frame by [`Scene::update`].
+    pub fn update_obstacles(&mut self, graph: &Graph) {
+        if self.pool.alive_count() == 0 {
+            return;
+        }
+
+        let obstacles = graph
+            .pair_iter()
+            .filter_map(|(handle, node)| {
+                node.cast::<NavmeshObstacle>()
+                    .and_then(|obstacle| obstacle.footprint())
+                    .map(|footprint| (handle, footprint))
+            })
+            .collect::<FxHashMap<_, _>>();
+
+        for navmesh in self.pool.iter_mut() {
+            navmesh.sync_obstacles(&obstacles);
+        }
+    }
 }
 
 impl Index<Handle<Navmesh>> for NavMeshContainer {
@@ -244,6 +280,11 @@ pub struct Scene {
     #[inspect(skip)]
     pub drawing_context: SceneDrawingContext,
 
+    /// Debug drawing buffer with automatic expiration of drawn items, see [`DebugDrawBuffer`]
+    /// docs for more info.
+    #[inspect(skip)]
+    pub debug_draw: DebugDrawBuffer,
+
     /// A container for navigational meshes.
     #[inspect(skip)]
     pub navmeshes: NavMeshContainer,
@@ -271,6 +312,23 @@ pub struct Scene {
     /// A container for animation blending state machines.
     #[inspect(skip)]
     pub animation_machines: AnimationMachineContainer,
+
+    #[inspect(min_value = 0.0, step = 0.05)]
+    time_scale: f32,
+
+    /// Named collections of nodes, see [`NodeCollection`](collection::NodeCollection) docs for
+    /// more info.
+    #[inspect(skip)]
+    pub node_collections: NodeCollectionContainer,
+
+    /// Tasks spawned by scripts of the scene, every task is owned by a node. Tasks of deleted nodes
+    /// are cancelled automatically. See [`TaskContainer`] docs for more info.
+    #[inspect(skip)]
+    pub tasks: TaskContainer<Handle<Node>>,
+
+    /// Scripts of the scene waiting for [`crate::script::ScriptTrait::on_start`].
+    #[inspect(skip)]
+    pub(crate) script_starts: ScriptStartQueue,
 }
 
 impl Default for Scene {
@@ -281,11 +339,16 @@ impl Default for Scene {
             render_target: None,
             lightmap: None,
             drawing_context: Default::default(),
+            debug_draw: Default::default(),
             navmeshes: Default::default(),
             performance_statistics: Default::default(),
             ambient_lighting_color: Color::opaque(100, 100, 100),
             enabled: true,
             animation_machines: Default::default(),
+            time_scale: 1.0,
+            node_collections: Default::default(),
+            tasks: Default::default(),
+            script_starts: Default::default(),
         }
     }
 }
@@ -333,6 +396,7 @@ impl Display for PerformanceStatistics {
 /// Scene loader.
 pub struct SceneLoader {
     scene: Scene,
+    errors: Vec<VisitError>,
 }
 
 impl SceneLoader {
@@ -346,6 +410,25 @@ impl SceneLoader {
         Self::load("Scene", serialization_context, &mut visitor)
     }
 
+    /// Same as [`Self::from_file`], but the scene is read in lenient mode (see
+    /// [`Visitor::set_lenient`]), which allows loading damaged scenes as much as possible. Errors
+    /// encountered during loading could be fetched using [`Self::errors`].
+    pub async fn from_file_lenient<P: AsRef<Path>>(
+        path: P,
+        serialization_context: Arc<SerializationContext>,
+    ) -> Result<Self, VisitError> {
+        let mut visitor = Visitor::load_binary(path).await?;
+        visitor.set_lenient(true);
+        let mut loader = Self::load("Scene", serialization_context, &mut visitor)?;
+        loader.errors = visitor.take_errors();
+        Ok(loader)
+    }
+
+    /// Returns a list of errors that were encountered while loading the scene in lenient mode.
+    pub fn errors(&self) -> &[VisitError] {
+        &self.errors
+    }
+
     /// Tries to load a scene using specified visitor and region name.
     pub fn load(
         region_name: &str,
@@ -363,7 +446,10 @@ impl SceneLoader {
         let mut scene = Scene::default();
         scene.visit(region_name, visitor)?;
 
-        Ok(Self { scene })
+        Ok(Self {
+            scene,
+            errors: Default::default(),
+        })
     }
 
     /// Finishes scene loading.
@@ -442,6 +528,104 @@ impl SceneLoader {
     }
 }
 
+/// Patches surface data of lightmapped meshes with secondary texture coordinates and new
+/// topology from the lightmap. Surface data that is already patched is left as is.
+fn apply_lightmap_patches(graph: &Graph, lightmap: &Lightmap) {
+    // Gather all unique surface data instances first, then look if there is a patch for them.
+    let mut unique_data_set = FxHashMap::default();
+    for &handle in lightmap.map.keys() {
+        if let Some(mesh) = graph.try_get(handle).and_then(|node| node.cast::<Mesh>()) {
+            for surface in mesh.surfaces() {
+                let data = surface.data();
+                let key = &*data as *const _ as u64;
+                unique_data_set.entry(key).or_insert(data);
+            }
+        }
+    }
+
+    for (_, data) in unique_data_set.into_iter() {
+        let mut data = data.lock();
+
+        if let Some(patch) = lightmap.patches.get(&data.content_hash()) {
+            if !data
+                .vertex_buffer
+                .has_attribute(VertexAttributeUsage::TexCoord1)
+            {
+                data.vertex_buffer
+                    .modify()
+                    .add_attribute(
+                        VertexAttributeDescriptor {
+                            usage: VertexAttributeUsage::TexCoord1,
+                            data_type: VertexAttributeDataType::F32,
+                            size: 2,
+                            divisor: 0,
+                            shader_location: 6, // HACK: GBuffer renderer expects it to be at 6
+                        },
+                        Vector2::<f32>::default(),
+                    )
+                    .unwrap();
+            }
+
+            data.geometry_buffer.set_triangles(patch.triangles.clone());
+
+            let mut vertex_buffer_mut = data.vertex_buffer.modify();
+            for &v in patch.additional_vertices.iter() {
+                vertex_buffer_mut.duplicate(v as usize);
+            }
+
+            assert_eq!(
+                vertex_buffer_mut.vertex_count() as usize,
+                patch.second_tex_coords.len()
+            );
+            for (mut view, &tex_coord) in vertex_buffer_mut
+                .iter_mut()
+                .zip(patch.second_tex_coords.iter())
+            {
+                view.write_2_f32(VertexAttributeUsage::TexCoord1, tex_coord)
+                    .unwrap();
+            }
+        } else if !data
+            .vertex_buffer
+            .has_attribute(VertexAttributeUsage::TexCoord1)
+        {
+            Log::writeln(
+                MessageKind::Warning,
+                "Failed to get surface data patch while resolving lightmap!\
+                    This means that surface has changed and lightmap must be regenerated!"
+                    .to_owned(),
+            );
+        }
+    }
+}
+
+/// Assigns (or removes if `clear` is set) lightmap textures to materials of lightmapped meshes.
+fn set_lightmap_textures(graph: &mut Graph, lightmap: &Lightmap, clear: bool) {
+    for (&handle, entries) in lightmap.map.iter() {
+        if let Some(mesh) = graph
+            .try_get_mut(handle)
+            .and_then(|node| node.cast_mut::<Mesh>())
+        {
+            for (entry, surface) in entries.iter().zip(mesh.surfaces_mut()) {
+                if let Err(e) = surface.material().lock().set_property(
+                    &ImmutableString::new("lightmapTexture"),
+                    PropertyValue::Sampler {
+                        value: if clear { None } else { entry.texture.clone() },
+                        fallback: SamplerFallback::Black,
+                    },
+                ) {
+                    Log::writeln(
+                        MessageKind::Error,
+                        format!(
+                            "Failed to apply light map texture to material. Reason {:?}",
+                            e
+                        ),
+                    )
+                }
+            }
+        }
+    }
+}
+
 impl Scene {
     /// Creates new scene with single root node.
     ///
@@ -458,11 +642,16 @@ impl Scene {
             render_target: None,
             lightmap: None,
             drawing_context: Default::default(),
+            debug_draw: Default::default(),
             navmeshes: Default::default(),
             performance_statistics: Default::default(),
             ambient_lighting_color: Color::opaque(100, 100, 100),
             enabled: true,
             animation_machines: Default::default(),
+            time_scale: 1.0,
+            node_collections: Default::default(),
+            tasks: Default::default(),
+            script_starts: Default::default(),
         }
     }
 
@@ -502,148 +691,143 @@ impl Scene {
         // Re-apply lightmap if any. This has to be done after resolve because we must patch surface
         // data at this stage, but if we'd do this before we wouldn't be able to do this because
         // meshes contains invalid surface data.
-        if let Some(lightmap) = self.lightmap.as_mut() {
-            // Patch surface data first. To do this we gather all surface data instances and
-            // look in patch data if we have patch for data.
-            let mut unique_data_set = FxHashMap::default();
-            for &handle in lightmap.map.keys() {
-                if let Some(mesh) = self.graph[handle].cast_mut::<Mesh>() {
-                    for surface in mesh.surfaces() {
-                        let data = surface.data();
-                        let key = &*data as *const _ as u64;
-                        unique_data_set.entry(key).or_insert(data);
-                    }
-                }
-            }
-
-            for (_, data) in unique_data_set.into_iter() {
-                let mut data = data.lock();
-
-                if let Some(patch) = lightmap.patches.get(&data.content_hash()) {
-                    if !data
-                        .vertex_buffer
-                        .has_attribute(VertexAttributeUsage::TexCoord1)
-                    {
-                        data.vertex_buffer
-                            .modify()
-                            .add_attribute(
-                                VertexAttributeDescriptor {
-                                    usage: VertexAttributeUsage::TexCoord1,
-                                    data_type: VertexAttributeDataType::F32,
-                                    size: 2,
-                                    divisor: 0,
-                                    shader_location: 6, // HACK: GBuffer renderer expects it to be at 6
-                                },
-                                Vector2::<f32>::default(),
-                            )
-                            .unwrap();
-                    }
-
-                    data.geometry_buffer.set_triangles(patch.triangles.clone());
-
-                    let mut vertex_buffer_mut = data.vertex_buffer.modify();
-                    for &v in patch.additional_vertices.iter() {
-                        vertex_buffer_mut.duplicate(v as usize);
-                    }
-
-                    assert_eq!(
-                        vertex_buffer_mut.vertex_count() as usize,
-                        patch.second_tex_coords.len()
-                    );
-                    for (mut view, &tex_coord) in vertex_buffer_mut
-                        .iter_mut()
-                        .zip(patch.second_tex_coords.iter())
-                    {
-                        view.write_2_f32(VertexAttributeUsage::TexCoord1, tex_coord)
-                            .unwrap();
-                    }
-                } else {
-                    Log::writeln(
-                        MessageKind::Warning,
-                        "Failed to get surface data patch while resolving lightmap!\
-                    This means that surface has changed and lightmap must be regenerated!"
-                            .to_owned(),
-                    );
-                }
-            }
-
-            // Apply textures.
-            for (&handle, entries) in lightmap.map.iter_mut() {
-                if let Some(mesh) = self.graph[handle].cast_mut::<Mesh>() {
-                    for (entry, surface) in entries.iter_mut().zip(mesh.surfaces_mut()) {
-                        if let Err(e) = surface.material().lock().set_property(
-                            &ImmutableString::new("lightmapTexture"),
-                            PropertyValue::Sampler {
-                                value: entry.texture.clone(),
-                                fallback: SamplerFallback::Black,
-                            },
-                        ) {
-                            Log::writeln(
-                                MessageKind::Error,
-                                format!(
-                                    "Failed to apply light map texture to material. Reason {:?}",
-                                    e
-                                ),
-                            )
-                        }
-                    }
-                }
-            }
+        if let Some(lightmap) = self.lightmap.as_ref() {
+            apply_lightmap_patches(&self.graph, lightmap);
+            set_lightmap_textures(&mut self.graph, lightmap, false);
         }
 
         Log::writeln(MessageKind::Information, "Resolve succeeded!".to_owned());
     }
 
-    /// Tries to set new lightmap to scene.
+    /// Tries to set new lightmap to scene. Surface data of the meshes is patched with secondary
+    /// texture coordinates (if needed) and materials of the surfaces will use lightmap textures.
+    /// Returns previous lightmap of the scene.
     pub fn set_lightmap(&mut self, lightmap: Lightmap) -> Result<Option<Lightmap>, &'static str> {
-        // Assign textures to surfaces.
         for (handle, lightmaps) in lightmap.map.iter() {
-            if let Some(mesh) = self.graph[*handle].cast_mut::<Mesh>() {
+            if let Some(mesh) = self
+                .graph
+                .try_get(*handle)
+                .and_then(|node| node.cast::<Mesh>())
+            {
                 if mesh.surfaces().len() != lightmaps.len() {
                     return Err("failed to set lightmap, surface count mismatch");
                 }
+            }
+        }
 
-                for (surface, entry) in mesh.surfaces_mut().iter_mut().zip(lightmaps) {
-                    // This unwrap() call must never panic in normal conditions, because texture wrapped in Option
-                    // only to implement Default trait to be serializable.
-                    let texture = entry.texture.clone().unwrap();
-                    if let Err(e) = surface.material().lock().set_property(
-                        &ImmutableString::new("lightmapTexture"),
-                        PropertyValue::Sampler {
-                            value: Some(texture),
-                            fallback: SamplerFallback::Black,
-                        },
-                    ) {
-                        Log::writeln(
-                            MessageKind::Error,
-                            format!(
-                                "Failed to apply light map texture to material. Reason {:?}",
-                                e
-                            ),
-                        )
-                    }
+        // Meshes that aren't in the new lightmap must not use old textures.
+        let previous = self.clear_lightmap();
+
+        apply_lightmap_patches(&self.graph, &lightmap);
+        set_lightmap_textures(&mut self.graph, &lightmap, false);
+        self.lightmap = Some(lightmap);
+
+        Ok(previous)
+    }
+
+    /// Removes lightmap from the scene, materials of the surfaces will no longer use lightmap
+    /// textures and baked lights will be applied dynamically again. Returns removed lightmap.
+    /// Secondary texture coordinates of the meshes are left as is, so the lightmap could be set
+    /// back using [`Self::set_lightmap`].
+    pub fn clear_lightmap(&mut self) -> Option<Lightmap> {
+        let lightmap = self.lightmap.take();
+        if let Some(lightmap) = lightmap.as_ref() {
+            set_lightmap_textures(&mut self.graph, lightmap, true);
+        }
+        lightmap
+    }
+
+    /// Returns current lightmap of the scene (if any).
+    pub fn lightmap(&self) -> Option<&Lightmap> {
+        self.lightmap.as_ref()
+    }
+
+    /// Sets new time scale of the scene. Time scale defines how fast the time flows in the scene,
+    /// for example 0.5 means that everything will be two times slower and 2.0 - two times faster.
+    /// Zero time scale "freezes" the scene. Negative and non-finite values are treated as zero.
+    ///
+    /// Time scale affects animations, animation blending state machines, particle systems,
+    /// lifetime of nodes and delta time that is passed to scripts (scripts also receive unscaled
+    /// delta time, see [`crate::script::ScriptContext::unscaled_dt`]). Sounds are affected only if
+    /// [`crate::scene::sound::Sound::set_time_scale_affects_pitch`] is set.
+    ///
+    /// # Physics
+    ///
+    /// Physics uses fixed time step, it is scaled by the time scale, so each update simulates
+    /// `dt * time_scale` seconds of the physical world. It means that very large time scale could
+    /// make simulation less stable, because of the large time step, and physics simulation is
+    /// skipped completely when time scale is zero.
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = if time_scale.is_finite() {
+            time_scale.max(0.0)
+        } else {
+            0.0
+        };
+    }
+
+    /// Returns current time scale of the scene. See [`Self::set_time_scale`] for more info.
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Makes sure that the scene is rendered into an off-screen texture of the given size (in
+    /// pixels), independently of the size of the window. A new render target is created only if
+    /// there is no render target or its size differs from the requested one, the renderer frees
+    /// GPU resources of the previous render target automatically. Zero sizes are clamped to one
+    /// pixel.
+    ///
+    /// Returns `true` if a new render target was created, in this case every user of the old
+    /// texture (an image widget for example) must be switched to the new one.
+    pub fn set_render_target_size(&mut self, width: u32, height: u32) -> bool {
+        let width = width.max(1);
+        let height = height.max(1);
+
+        if let Some(render_target) = self.render_target.as_ref() {
+            if let TextureKind::Rectangle {
+                width: current_width,
+                height: current_height,
+            } = render_target.data_ref().kind()
+            {
+                if current_width == width && current_height == height {
+                    return false;
                 }
             }
         }
-        Ok(std::mem::replace(&mut self.lightmap, Some(lightmap)))
+
+        self.render_target = Some(Texture::new_render_target(width, height));
+
+        true
     }
 
     /// Performs single update tick with given delta time from last frame. Internally
     /// it updates physics, animations, and each graph node. In most cases there is
     /// no need to call it directly, engine automatically updates all available scenes.
+    ///
+    /// Given delta time is scaled by the time scale of the scene (see [`Self::set_time_scale`]).
+    /// Zero delta time means that no time has passed, nothing will be simulated in this case.
     pub fn update(&mut self, frame_size: Vector2<f32>, dt: f32) {
-        let last = instant::Instant::now();
-        self.animations.update_animations(dt);
-        self.performance_statistics.animations_update_time = instant::Instant::now() - last;
-
-        self.graph.update(frame_size, dt);
-        self.performance_statistics.graph = self.graph.performance_statistics.clone();
+        let scaled_dt = dt.max(0.0) * self.time_scale;
 
+        let last = instant::Instant::now();
+        self.animations
+            .update_animations_in_graph(&self.graph, scaled_dt);
+        self.animations.blended_pose().apply(&mut self.graph);
+        // Poses of machines must be applied before the graph is updated, otherwise kinematic
+        // bodies attached to bones (hitboxes, for example) will lag one frame behind animation.
         for machine in self.animation_machines.iter_mut() {
             machine
-                .evaluate_pose(&self.animations, dt)
+                .evaluate_pose(&self.animations, scaled_dt)
                 .apply(&mut self.graph);
         }
+        self.performance_statistics.animations_update_time = instant::Instant::now() - last;
+
+        self.graph
+            .update_with_time_scale(frame_size, dt, self.time_scale);
+
+        self.navmeshes.update_obstacles(&self.graph);
+
+        self.debug_draw.update(dt);
+        self.performance_statistics.graph = self.graph.performance_statistics.clone();
     }
 
     /// Creates deep copy of a scene, filter predicate allows you to filter out nodes
@@ -668,6 +852,10 @@ impl Scene {
             machine.root = old_new_map.get(&machine.root).cloned().unwrap_or_default();
         }
 
+        // Members that were filtered out are removed from collections.
+        let mut node_collections = self.node_collections.clone();
+        node_collections.remap(&old_new_map);
+
         (
             Self {
                 graph,
@@ -678,10 +866,17 @@ impl Scene {
                 render_target: Default::default(),
                 lightmap: self.lightmap.clone(),
                 drawing_context: self.drawing_context.clone(),
+                debug_draw: Default::default(),
                 navmeshes: self.navmeshes.clone(),
                 performance_statistics: Default::default(),
                 ambient_lighting_color: self.ambient_lighting_color,
                 enabled: self.enabled,
+                time_scale: self.time_scale,
+                node_collections,
+                // Tasks belong to the original scene, there is no way to copy them.
+                tasks: Default::default(),
+                // Scripts of the copy are started independently.
+                script_starts: Default::default(),
             },
             old_new_map,
         )
@@ -700,6 +895,8 @@ impl Scene {
         let _ = self
             .animation_machines
             .visit("AnimationMachines", &mut region);
+        let _ = self.time_scale.visit("TimeScale", &mut region);
+        let _ = self.node_collections.visit("NodeCollections", &mut region);
 
         Ok(())
     }
@@ -714,6 +911,50 @@ impl Scene {
 
         self.visit(region_name, visitor)
     }
+
+    /// Creates a copy of the scene that is ready to be saved. Nodes rejected by the `filter` are
+    /// not copied (together with their descendants). Runtime-only state is not copied as well -
+    /// particle systems lose generated particles, render target and debug drawings are dropped.
+    pub fn clone_for_save<F>(&self, filter: &mut F) -> Self
+    where
+        F: FnMut(Handle<Node>, &Node) -> bool,
+    {
+        self.clone_for_save_with_map(filter).0
+    }
+
+    /// Same as [`Self::clone_for_save`], but also returns a map that maps handles of the nodes of
+    /// the scene to handles of their copies. It could be used to save external data associated
+    /// with the nodes (for example editor-only data), because handles of the copies may differ.
+    pub fn clone_for_save_with_map<F>(
+        &self,
+        filter: &mut F,
+    ) -> (Self, FxHashMap<Handle<Node>, Handle<Node>>)
+    where
+        F: FnMut(Handle<Node>, &Node) -> bool,
+    {
+        let (mut scene, old_new_map) = self.clone(filter);
+
+        for node in scene.graph.linear_iter_mut() {
+            if let Some(particle_system) = node.cast_mut::<ParticleSystem>() {
+                // Particle system must not save generated vertices.
+                particle_system.clear_particles();
+            }
+        }
+
+        scene.drawing_context.clear_lines();
+
+        (scene, old_new_map)
+    }
+
+    /// Saves the scene to a file in the native format, the file could be opened in the editor or
+    /// loaded using [`SceneLoader`]. The scene itself stays intact, the method saves a copy made by
+    /// [`Self::clone_for_save`], so it is safe to save a running scene.
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> VisitResult {
+        let mut scene = self.clone_for_save(&mut |_, _| true);
+        let mut visitor = Visitor::new();
+        scene.save("Scene", &mut visitor)?;
+        visitor.save_binary(path)
+    }
 }
 
 /// Container for scenes in the engine.
@@ -827,3 +1068,174 @@ impl IndexMut<Handle<Scene>> for SceneContainer {
         &mut self.pool[index]
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        animation::{Animation, KeyFrame, Track},
+        core::{
+            algebra::{UnitQuaternion, Vector2, Vector3},
+            pool::Handle,
+            visitor::Visitor,
+        },
+        engine::SerializationContext,
+        scene::{base::BaseBuilder, pivot::PivotBuilder, transform::TransformBuilder, Scene},
+    };
+    use std::sync::Arc;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    fn add_animation(scene: &mut Scene, length: f32) -> Handle<Animation> {
+        let mut track = Track::new();
+        for time in [0.0, length] {
+            track.add_key_frame(KeyFrame::new(
+                time,
+                Vector3::default(),
+                Vector3::new(1.0, 1.0, 1.0),
+                UnitQuaternion::default(),
+            ));
+        }
+
+        let mut animation = Animation::default();
+        animation.add_track(track);
+        animation.set_loop(false);
+        scene.animations.add(animation)
+    }
+
+    fn update(scene: &mut Scene, dt: f32) {
+        scene.update(Vector2::new(100.0, 100.0), dt);
+    }
+
+    #[test]
+    fn test_animation_respects_time_scale() {
+        let mut scene = Scene::new();
+        scene.set_time_scale(0.5);
+        let animation = add_animation(&mut scene, 1.0);
+
+        let mut steps = 0u32;
+        while !scene.animations[animation].has_ended() {
+            update(&mut scene, DT);
+            steps += 1;
+            assert!(steps < 1000);
+        }
+
+        // 1 second long animation with 0.5 time scale must end in 2 seconds of wall-clock time,
+        // accumulated rounding error of the time position could take one more frame.
+        assert!(steps.abs_diff(120) <= 1, "{}", steps);
+    }
+
+    #[test]
+    fn test_pause_does_not_double_step() {
+        let mut scene = Scene::new();
+        let animation = add_animation(&mut scene, 10.0);
+
+        for _ in 0..10 {
+            update(&mut scene, DT);
+        }
+
+        // Paused engine updates scenes with zero delta time.
+        let paused_time = scene.animations[animation].get_time_position();
+        for _ in 0..10 {
+            update(&mut scene, 0.0);
+        }
+        assert_eq!(scene.animations[animation].get_time_position(), paused_time);
+
+        update(&mut scene, DT);
+        let time = scene.animations[animation].get_time_position();
+        assert!((time - 11.0 * DT).abs() <= 1.0e-5);
+    }
+
+    #[test]
+    fn test_zero_time_scale() {
+        let mut scene = Scene::new();
+        scene.set_time_scale(0.0);
+        let animation = add_animation(&mut scene, 1.0);
+
+        for _ in 0..10 {
+            update(&mut scene, DT);
+        }
+
+        let time = scene.animations[animation].get_time_position();
+        assert!(time.is_finite());
+        assert_eq!(time, 0.0);
+
+        scene.set_time_scale(f32::NAN);
+        assert_eq!(scene.time_scale(), 0.0);
+    }
+
+    #[test]
+    fn test_save_to_file_round_trip() {
+        let mut scene = Scene::new();
+        let child = PivotBuilder::new(
+            BaseBuilder::new().with_name("Child").with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(1.0, 2.0, 3.0))
+                    .build(),
+            ),
+        )
+        .build(&mut scene.graph);
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Parent")
+                .with_tag("Tag".to_owned())
+                .with_children(&[child]),
+        )
+        .build(&mut scene.graph);
+
+        let path = std::env::temp_dir().join("fyrox_test_save_to_file_round_trip.rgs");
+        scene.save_to_file(&path).unwrap();
+
+        let mut visitor = Visitor::load_from_memory(std::fs::read(&path).unwrap()).unwrap();
+        visitor.environment = Some(Arc::new(SerializationContext::new()));
+        let mut loaded = Scene::default();
+        loaded.visit("Scene", &mut visitor).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.graph.node_count(), scene.graph.node_count());
+        // Root is not compared, it has no parent.
+        for node in scene.graph.linear_iter().filter(|n| n.parent().is_some()) {
+            let loaded_node = &loaded.graph[loaded.graph.find_by_name_from_root(node.name())];
+            assert_eq!(loaded_node.tag(), node.tag());
+            assert_eq!(
+                **loaded_node.local_transform().position(),
+                **node.local_transform().position()
+            );
+            assert_eq!(
+                loaded.graph[loaded_node.parent()].name(),
+                scene.graph[node.parent()].name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_disabled_parent_pauses_child_animation() {
+        let mut scene = Scene::new();
+        let child = PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+        let parent =
+            PivotBuilder::new(BaseBuilder::new().with_children(&[child])).build(&mut scene.graph);
+        let animation = add_animation(&mut scene, 10.0);
+        scene.animations[animation].get_tracks_mut()[0].set_node(child);
+
+        for _ in 0..10 {
+            update(&mut scene, DT);
+        }
+
+        scene.graph[parent].set_enabled(false);
+        scene.graph.update_hierarchical_data();
+        assert!(!scene.graph[child].is_globally_enabled());
+
+        let time = scene.animations[animation].get_time_position();
+        for _ in 0..10 {
+            update(&mut scene, DT);
+        }
+        assert_eq!(scene.animations[animation].get_time_position(), time);
+
+        scene.graph[parent].set_enabled(true);
+        scene.graph.update_hierarchical_data();
+
+        update(&mut scene, DT);
+        assert!(
+            (scene.animations[animation].get_time_position() - (time + DT)).abs() <= f32::EPSILON
+        );
+    }
+}
@@ -0,0 +1,378 @@
+//! Navmesh obstacle is a box or a cylinder that carves a hole in every navigational mesh of a
+//! scene.
+//!
+//! For more info see [`NavmeshObstacle`]
+
+use crate::{
+    core::variable::{InheritError, TemplateVariable},
+    core::{
+        algebra::{Point3, Vector3},
+        inspect::{Inspect, PropertyInfo},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    impl_directly_inheritable_entity_trait,
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait, TypeUuidProvider},
+        DirectlyInheritableEntity,
+    },
+    utils::navmesh::ObstacleFootprint,
+};
+use fxhash::FxHashMap;
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Amount of points on a circle of a cylinder that are used to build its footprint.
+const CYLINDER_SEGMENTS: usize = 16;
+
+/// Shape of a navmesh obstacle.
+#[derive(
+    Copy, Clone, PartialEq, Eq, Hash, Debug, Visit, Inspect, AsRefStr, EnumString, EnumVariantNames,
+)]
+#[repr(u32)]
+pub enum NavmeshObstacleShape {
+    /// A box defined by half-extents of the obstacle.
+    Box = 0,
+
+    /// A cylinder along local Y axis defined by radius and half-height of the obstacle.
+    Cylinder = 1,
+}
+
+impl Default for NavmeshObstacleShape {
+    fn default() -> Self {
+        Self::Box
+    }
+}
+
+/// Navmesh obstacle is a box or a cylinder that removes parts of navigational meshes of a scene
+/// that are under (or inside) it, so agents will go around the obstacle. It is useful for doors,
+/// movable crates, barricades and other dynamic objects that block paths.
+///
+/// # Carving
+///
+/// The obstacle is projected on XZ plane and its convex hull is cut out from every navmesh of
+/// the scene, only triangles that are touched by the obstacle are re-built (see
+/// [`crate::utils::navmesh::Navmesh::set_obstacle`]). Navmeshes are updated once per frame when
+/// the obstacle is moved, changed, disabled or removed, disabled obstacles don't carve anything.
+/// Agents that use [`crate::utils::navmesh::NavmeshAgent`] re-calculate their paths
+/// automatically if a change is close to their paths.
+#[derive(Debug, Visit, Clone, Inspect)]
+pub struct NavmeshObstacle {
+    base: Base,
+
+    #[inspect(getter = "Deref::deref")]
+    shape: TemplateVariable<NavmeshObstacleShape>,
+
+    #[inspect(getter = "Deref::deref")]
+    extents: TemplateVariable<Vector3<f32>>,
+
+    #[inspect(min_value = 0.0, step = 0.1, getter = "Deref::deref")]
+    radius: TemplateVariable<f32>,
+
+    #[inspect(min_value = 0.0, step = 0.1, getter = "Deref::deref")]
+    height: TemplateVariable<f32>,
+}
+
+impl_directly_inheritable_entity_trait!(NavmeshObstacle;
+    shape,
+    extents,
+    radius,
+    height
+);
+
+impl Default for NavmeshObstacle {
+    fn default() -> Self {
+        NavmeshObstacleBuilder::new(BaseBuilder::new()).build_navmesh_obstacle()
+    }
+}
+
+impl Deref for NavmeshObstacle {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for NavmeshObstacle {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for NavmeshObstacle {
+    fn type_uuid() -> Uuid {
+        uuid!("c2a4d6e1-3b5f-4f0a-8e7d-1a9b3c5d7e2f")
+    }
+}
+
+impl NavmeshObstacle {
+    /// Sets new shape of the obstacle.
+    pub fn set_shape(&mut self, shape: NavmeshObstacleShape) {
+        self.shape.set(shape);
+    }
+
+    /// Returns current shape of the obstacle.
+    pub fn shape(&self) -> NavmeshObstacleShape {
+        *self.shape
+    }
+
+    /// Sets new half-extents of the box, they're used only by [`NavmeshObstacleShape::Box`].
+    pub fn set_extents(&mut self, extents: Vector3<f32>) {
+        self.extents.set(extents.map(|v| v.abs()));
+    }
+
+    /// Returns current half-extents of the box.
+    pub fn extents(&self) -> Vector3<f32> {
+        *self.extents
+    }
+
+    /// Sets new radius of the cylinder, it is used only by [`NavmeshObstacleShape::Cylinder`].
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius.set(radius.max(0.0));
+    }
+
+    /// Returns current radius of the cylinder.
+    pub fn radius(&self) -> f32 {
+        *self.radius
+    }
+
+    /// Sets new half-height of the cylinder, it is used only by
+    /// [`NavmeshObstacleShape::Cylinder`].
+    pub fn set_height(&mut self, height: f32) {
+        self.height.set(height.max(0.0));
+    }
+
+    /// Returns current half-height of the cylinder.
+    pub fn height(&self) -> f32 {
+        *self.height
+    }
+
+    /// Returns world space footprint of the obstacle, or `None` if the obstacle is disabled.
+    /// Global transform of the obstacle must be up-to-date.
+    pub fn footprint(&self) -> Option<ObstacleFootprint> {
+        if !self.is_globally_enabled() {
+            return None;
+        }
+
+        let local_points = match *self.shape {
+            NavmeshObstacleShape::Box => {
+                AxisAlignedBoundingBox::from_min_max(-*self.extents, *self.extents)
+                    .corners()
+                    .to_vec()
+            }
+            NavmeshObstacleShape::Cylinder => {
+                let mut points = Vec::with_capacity(CYLINDER_SEGMENTS * 2);
+                for i in 0..CYLINDER_SEGMENTS {
+                    let angle = i as f32 / CYLINDER_SEGMENTS as f32 * std::f32::consts::TAU;
+                    let (z, x) = angle.sin_cos();
+                    for y in [-*self.height, *self.height] {
+                        points.push(Vector3::new(x * *self.radius, y, z * *self.radius));
+                    }
+                }
+                points
+            }
+        };
+
+        let transform = self.global_transform();
+        let points = local_points
+            .into_iter()
+            .map(|p| transform.transform_point(&Point3::from(p)).coords)
+            .collect::<Vec<_>>();
+
+        Some(ObstacleFootprint::from_points(&points))
+    }
+}
+
+impl NodeTrait for NavmeshObstacle {
+    crate::impl_query_component!();
+
+    /// Returns current **local-space** bounding box.
+    #[inline]
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        let half_size = match *self.shape {
+            NavmeshObstacleShape::Box => *self.extents,
+            NavmeshObstacleShape::Cylinder => {
+                Vector3::new(*self.radius, *self.height, *self.radius)
+            }
+        };
+        AxisAlignedBoundingBox::from_min_max(-half_size, half_size)
+    }
+
+    /// Returns current **world-space** bounding box.
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    // Prefab inheritance resolving.
+    fn inherit(&mut self, parent: &Node) -> Result<(), InheritError> {
+        self.base.inherit_properties(parent)?;
+        if let Some(parent) = parent.cast::<Self>() {
+            self.try_inherit_self_properties(parent)?;
+        }
+        Ok(())
+    }
+
+    fn reset_inheritable_properties(&mut self) {
+        self.base.reset_inheritable_properties();
+        self.reset_self_inheritable_properties();
+    }
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        self.base.restore_resources(resource_manager);
+    }
+
+    fn remap_handles(&mut self, old_new_mapping: &FxHashMap<Handle<Node>, Handle<Node>>) {
+        self.base.remap_handles(old_new_mapping);
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create a navmesh obstacle in a declarative manner.
+pub struct NavmeshObstacleBuilder {
+    base_builder: BaseBuilder,
+    shape: NavmeshObstacleShape,
+    extents: Vector3<f32>,
+    radius: f32,
+    height: f32,
+}
+
+impl NavmeshObstacleBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            shape: NavmeshObstacleShape::Box,
+            extents: Vector3::new(0.5, 0.5, 0.5),
+            radius: 0.5,
+            height: 0.5,
+        }
+    }
+
+    /// Sets desired shape of the obstacle.
+    pub fn with_shape(mut self, shape: NavmeshObstacleShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets desired half-extents of the box.
+    pub fn with_extents(mut self, extents: Vector3<f32>) -> Self {
+        self.extents = extents;
+        self
+    }
+
+    /// Sets desired radius of the cylinder.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets desired half-height of the cylinder.
+    pub fn with_height(mut self, height: f32) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Creates new navmesh obstacle.
+    pub fn build_navmesh_obstacle(self) -> NavmeshObstacle {
+        NavmeshObstacle {
+            base: self.base_builder.build_base(),
+            shape: self.shape.into(),
+            extents: self.extents.map(|v| v.abs()).into(),
+            radius: self.radius.max(0.0).into(),
+            height: self.height.max(0.0).into(),
+        }
+    }
+
+    /// Creates new navmesh obstacle node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_navmesh_obstacle())
+    }
+
+    /// Creates new instance of navmesh obstacle node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        scene::{
+            base::{test::check_inheritable_properties_equality, BaseBuilder},
+            graph::Graph,
+            navmesh_obstacle::{NavmeshObstacle, NavmeshObstacleBuilder, NavmeshObstacleShape},
+            node::NodeTrait,
+            transform::TransformBuilder,
+        },
+    };
+
+    #[test]
+    fn test_navmesh_obstacle_inheritance() {
+        let parent = NavmeshObstacleBuilder::new(BaseBuilder::new())
+            .with_shape(NavmeshObstacleShape::Cylinder)
+            .with_extents(Vector3::new(1.0, 2.0, 3.0))
+            .with_radius(4.0)
+            .with_height(5.0)
+            .build_node();
+
+        let mut child = NavmeshObstacleBuilder::new(BaseBuilder::new()).build_navmesh_obstacle();
+
+        child.inherit(&parent).unwrap();
+
+        let parent = parent.cast::<NavmeshObstacle>().unwrap();
+
+        check_inheritable_properties_equality(&child.base, &parent.base);
+        check_inheritable_properties_equality(&child, parent);
+    }
+
+    #[test]
+    fn test_navmesh_obstacle_footprint() {
+        let mut graph = Graph::new();
+        let handle = NavmeshObstacleBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(2.0, 0.0, 3.0))
+                    .build(),
+            ),
+        )
+        .with_extents(Vector3::new(1.0, 0.5, 2.0))
+        .with_radius(1.0)
+        .with_height(0.5)
+        .build(&mut graph);
+        graph.update_hierarchical_data();
+
+        let obstacle = graph[handle].cast_mut::<NavmeshObstacle>().unwrap();
+        let footprint = obstacle.footprint().unwrap();
+        assert_eq!(footprint.points().len(), 4);
+        assert!(footprint.points().contains(&Vector2::new(1.0, 1.0)));
+        assert!(footprint.points().contains(&Vector2::new(3.0, 5.0)));
+        assert_eq!(footprint.min_y(), -0.5);
+        assert_eq!(footprint.max_y(), 0.5);
+        assert!(footprint.contains(Vector3::new(2.0, 0.0, 4.5)));
+        assert!(!footprint.contains(Vector3::new(3.5, 0.0, 3.0)));
+
+        obstacle.set_shape(NavmeshObstacleShape::Cylinder);
+        let footprint = obstacle.footprint().unwrap();
+        assert!(footprint.contains(Vector3::new(2.9, 0.0, 3.0)));
+        assert!(!footprint.contains(Vector3::new(2.0, 0.0, 4.5)));
+
+        obstacle.set_enabled(false);
+        graph.update_hierarchical_data();
+        assert!(graph[handle]
+            .cast::<NavmeshObstacle>()
+            .unwrap()
+            .footprint()
+            .is_none());
+    }
+}
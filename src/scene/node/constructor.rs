@@ -1,26 +1,163 @@
 //! A special container that is able to create nodes by their type UUID.
 
 use crate::{
-    core::{parking_lot::Mutex, uuid::Uuid},
+    core::{
+        algebra::{Matrix4, Vector2},
+        parking_lot::{Mutex, MutexGuard},
+        sstorage::ImmutableString,
+        uuid::Uuid,
+    },
+    material::{Material, PropertyValue},
     scene::{
         self,
-        camera::Camera,
+        base::BaseBuilder,
+        camera::{Camera, CameraBuilder},
+        collider::{ColliderBuilder, ColliderShape},
         decal::Decal,
         dim2::{self, rectangle::Rectangle},
-        light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
-        mesh::Mesh,
+        fog_volume::FogVolume,
+        hlod::Hlod,
+        ik::TwoBoneIk,
+        impostor::Impostor,
+        joint::{JointBuilder, JointParams},
+        light::{
+            directional::DirectionalLight,
+            point::PointLight,
+            spot::{SpotLight, SpotLightBuilder},
+            BaseLightBuilder,
+        },
+        mesh::{
+            surface::{Surface, SurfaceData},
+            Mesh, MeshBuilder,
+        },
+        navmesh_obstacle::NavmeshObstacle,
         node::{Node, NodeTrait, TypeUuidProvider},
-        particle_system::ParticleSystem,
+        particle_system::{
+            emitter::{base::BaseEmitterBuilder, sphere::SphereEmitterBuilder},
+            ParticleSystem, ParticleSystemBuilder,
+        },
         pivot::Pivot,
+        reflection_probe::ReflectionProbe,
         sound::{listener::Listener, Sound},
         sprite::Sprite,
-        terrain::Terrain,
+        terrain::{LayerDefinition, Terrain, TerrainBuilder},
+        trigger_volume::TriggerVolume,
     },
 };
 use fxhash::FxHashMap;
+use std::{path::PathBuf, sync::Arc};
+
+/// A named preset of a node type, for example a cube is a preset of a mesh. Presets are shown by
+/// the editor in its creation menu instead of the default constructor of a type.
+pub struct NodeVariant {
+    /// Name of the preset.
+    pub name: String,
+
+    /// A closure that creates the preset.
+    pub constructor: Box<dyn FnMut() -> Node + Send>,
+}
+
+/// Node constructor creates nodes of a particular type, it also holds the metadata that is used
+/// by the editor to show the type in its creation menu.
+pub struct NodeConstructor {
+    /// A closure that creates default instance of a node. It is used by the serializer.
+    pub constructor: Box<dyn FnMut() -> Node + Send>,
+
+    /// Display name of the node type.
+    pub name: String,
+
+    /// Category of the node type, the editor groups node types with the same category in a
+    /// sub-menu. Empty category means that the type will be shown in the root of the menu.
+    pub category: String,
+
+    /// Optional path to an icon of the node type.
+    pub icon: Option<PathBuf>,
+
+    /// A set of presets of the node type.
+    pub variants: Vec<NodeVariant>,
+}
+
+impl NodeConstructor {
+    /// Creates new constructor for a given type. Display name of the constructor is the name of
+    /// the type.
+    pub fn new<T>() -> Self
+    where
+        T: NodeTrait + Default,
+    {
+        Self {
+            constructor: Box::new(|| Node::new(T::default())),
+            name: std::any::type_name::<T>()
+                .rsplit("::")
+                .next()
+                .unwrap_or_default()
+                .to_owned(),
+            category: Default::default(),
+            icon: None,
+            variants: Default::default(),
+        }
+    }
+
+    /// Sets desired display name.
+    pub fn with_name<N: AsRef<str>>(mut self, name: N) -> Self {
+        self.name = name.as_ref().to_owned();
+        self
+    }
+
+    /// Sets desired category.
+    pub fn with_category<C: AsRef<str>>(mut self, category: C) -> Self {
+        self.category = category.as_ref().to_owned();
+        self
+    }
 
-/// A simple type alias for boxed node constructor.
-pub type NodeConstructor = Box<dyn FnMut() -> Node + Send>;
+    /// Sets desired icon path.
+    pub fn with_icon<P: Into<PathBuf>>(mut self, icon: P) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    /// Adds new preset of the node type.
+    pub fn with_variant<N, F>(mut self, name: N, constructor: F) -> Self
+    where
+        N: AsRef<str>,
+        F: FnMut() -> Node + Send + 'static,
+    {
+        self.variants.push(NodeVariant {
+            name: name.as_ref().to_owned(),
+            constructor: Box::new(constructor),
+        });
+        self
+    }
+
+    /// Creates default instance of the node type.
+    pub fn create(&mut self) -> Node {
+        (self.constructor)()
+    }
+
+    /// Creates an instance of a preset with the given index. Returns `None` if there is no such
+    /// preset.
+    pub fn create_variant(&mut self, index: usize) -> Option<Node> {
+        self.variants
+            .get_mut(index)
+            .map(|variant| (variant.constructor)())
+    }
+}
+
+fn make_mesh(surface_data: SurfaceData) -> Node {
+    MeshBuilder::new(BaseBuilder::new())
+        .with_surfaces(vec![Surface::new(Arc::new(Mutex::new(surface_data)))])
+        .build_node()
+}
+
+fn make_terrain_layer_material() -> Arc<Mutex<Material>> {
+    let mut material = Material::standard_terrain();
+    material
+        .set_property(
+            &ImmutableString::new("texCoordScale"),
+            PropertyValue::Vector2(Vector2::new(10.0, 10.0)),
+        )
+        .unwrap();
+    Arc::new(Mutex::new(material))
+}
 
 /// A special container that is able to create nodes by their type UUID.
 #[derive(Default)]
@@ -33,31 +170,239 @@ impl NodeConstructorContainer {
     pub fn new() -> Self {
         let container = NodeConstructorContainer::default();
 
-        container.add::<dim2::collider::Collider>();
-        container.add::<dim2::joint::Joint>();
-        container.add::<Rectangle>();
-        container.add::<dim2::rigidbody::RigidBody>();
-        container.add::<DirectionalLight>();
-        container.add::<PointLight>();
-        container.add::<SpotLight>();
-        container.add::<Mesh>();
-        container.add::<ParticleSystem>();
-        container.add::<Sound>();
-        container.add::<Listener>();
-        container.add::<Camera>();
-        container.add::<scene::collider::Collider>();
+        container.add_custom(
+            dim2::collider::Collider::type_uuid(),
+            NodeConstructor::new::<dim2::collider::Collider>()
+                .with_name("Collider 2D")
+                .with_category("Physics 2D")
+                .with_variant("Collider 2D", || {
+                    dim2::collider::ColliderBuilder::new(BaseBuilder::new())
+                        .with_shape(dim2::collider::ColliderShape::Cuboid(Default::default()))
+                        .build_node()
+                }),
+        );
+        container.add_custom(
+            dim2::joint::Joint::type_uuid(),
+            NodeConstructor::new::<dim2::joint::Joint>()
+                .with_name("Joint 2D")
+                .with_category("Physics 2D")
+                .with_variant("Ball Joint 2D", || {
+                    dim2::joint::JointBuilder::new(BaseBuilder::new())
+                        .with_params(dim2::joint::JointParams::BallJoint(Default::default()))
+                        .build_node()
+                })
+                .with_variant("Prismatic Joint 2D", || {
+                    dim2::joint::JointBuilder::new(BaseBuilder::new())
+                        .with_params(dim2::joint::JointParams::PrismaticJoint(Default::default()))
+                        .build_node()
+                })
+                .with_variant("Fixed Joint 2D", || {
+                    dim2::joint::JointBuilder::new(BaseBuilder::new())
+                        .with_params(dim2::joint::JointParams::FixedJoint(Default::default()))
+                        .build_node()
+                }),
+        );
+        container.add_custom(
+            Rectangle::type_uuid(),
+            NodeConstructor::new::<Rectangle>().with_category("2D"),
+        );
+        container.add_custom(
+            dim2::rigidbody::RigidBody::type_uuid(),
+            NodeConstructor::new::<dim2::rigidbody::RigidBody>()
+                .with_name("Rigid Body 2D")
+                .with_category("Physics 2D"),
+        );
+        container.add_custom(
+            DirectionalLight::type_uuid(),
+            NodeConstructor::new::<DirectionalLight>()
+                .with_name("Directional Light")
+                .with_category("Light"),
+        );
+        container.add_custom(
+            PointLight::type_uuid(),
+            NodeConstructor::new::<PointLight>()
+                .with_name("Point Light")
+                .with_category("Light"),
+        );
+        container.add_custom(
+            SpotLight::type_uuid(),
+            NodeConstructor::new::<SpotLight>()
+                .with_name("Spot Light")
+                .with_category("Light")
+                .with_variant("Spot Light", || {
+                    SpotLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new()))
+                        .with_distance(10.0)
+                        .with_hotspot_cone_angle(45.0f32.to_radians())
+                        .with_falloff_angle_delta(2.0f32.to_radians())
+                        .build_node()
+                }),
+        );
+        container.add_custom(
+            Mesh::type_uuid(),
+            NodeConstructor::new::<Mesh>()
+                .with_category("Mesh")
+                .with_variant("Cube", || {
+                    make_mesh(SurfaceData::make_cube(Matrix4::identity()))
+                })
+                .with_variant("Sphere", || {
+                    make_mesh(SurfaceData::make_sphere(16, 16, 0.5, &Matrix4::identity()))
+                })
+                .with_variant("Cylinder", || {
+                    make_mesh(SurfaceData::make_cylinder(
+                        16,
+                        0.5,
+                        1.0,
+                        true,
+                        &Matrix4::identity(),
+                    ))
+                })
+                .with_variant("Cone", || {
+                    make_mesh(SurfaceData::make_cone(16, 0.5, 1.0, &Matrix4::identity()))
+                })
+                .with_variant("Quad", || {
+                    make_mesh(SurfaceData::make_quad(&Matrix4::identity()))
+                }),
+        );
+        container.add_custom(
+            ParticleSystem::type_uuid(),
+            NodeConstructor::new::<ParticleSystem>()
+                .with_name("Particle System")
+                .with_variant("Particle System", || {
+                    ParticleSystemBuilder::new(BaseBuilder::new())
+                        .with_emitters(vec![SphereEmitterBuilder::new(
+                            BaseEmitterBuilder::new()
+                                .with_max_particles(100)
+                                .resurrect_particles(true),
+                        )
+                        .with_radius(1.0)
+                        .build()])
+                        .build_node()
+                }),
+        );
+        container.add_custom(
+            Sound::type_uuid(),
+            NodeConstructor::new::<Sound>()
+                .with_name("Source")
+                .with_category("Sound"),
+        );
+        container.add_custom(
+            Listener::type_uuid(),
+            NodeConstructor::new::<Listener>().with_category("Sound"),
+        );
+        container.add_custom(
+            Camera::type_uuid(),
+            NodeConstructor::new::<Camera>().with_variant("Camera", || {
+                CameraBuilder::new(BaseBuilder::new())
+                    .enabled(false)
+                    .build_node()
+            }),
+        );
+        container.add_custom(
+            scene::collider::Collider::type_uuid(),
+            NodeConstructor::new::<scene::collider::Collider>()
+                .with_category("Physics")
+                .with_variant("Collider", || {
+                    ColliderBuilder::new(BaseBuilder::new())
+                        .with_shape(ColliderShape::Cuboid(Default::default()))
+                        .build_node()
+                }),
+        );
         container.add::<Decal>();
-        container.add::<scene::joint::Joint>();
+        container.add_custom(
+            FogVolume::type_uuid(),
+            NodeConstructor::new::<FogVolume>()
+                .with_name("Fog Volume")
+                .with_category("Light"),
+        );
+        container.add_custom(
+            TriggerVolume::type_uuid(),
+            NodeConstructor::new::<TriggerVolume>()
+                .with_name("Trigger Volume")
+                .with_category("Physics"),
+        );
+        container.add_custom(
+            NavmeshObstacle::type_uuid(),
+            NodeConstructor::new::<NavmeshObstacle>()
+                .with_name("Navmesh Obstacle")
+                .with_category("Navigation"),
+        );
+        container.add_custom(
+            Hlod::type_uuid(),
+            NodeConstructor::new::<Hlod>()
+                .with_name("HLOD")
+                .with_category("Mesh"),
+        );
+        container.add_custom(
+            Impostor::type_uuid(),
+            NodeConstructor::new::<Impostor>().with_category("Mesh"),
+        );
+        container.add_custom(
+            scene::joint::Joint::type_uuid(),
+            NodeConstructor::new::<scene::joint::Joint>()
+                .with_category("Physics")
+                .with_variant("Revolute Joint", || {
+                    JointBuilder::new(BaseBuilder::new())
+                        .with_params(JointParams::RevoluteJoint(Default::default()))
+                        .build_node()
+                })
+                .with_variant("Ball Joint", || {
+                    JointBuilder::new(BaseBuilder::new())
+                        .with_params(JointParams::BallJoint(Default::default()))
+                        .build_node()
+                })
+                .with_variant("Prismatic Joint", || {
+                    JointBuilder::new(BaseBuilder::new())
+                        .with_params(JointParams::PrismaticJoint(Default::default()))
+                        .build_node()
+                })
+                .with_variant("Fixed Joint", || {
+                    JointBuilder::new(BaseBuilder::new())
+                        .with_params(JointParams::FixedJoint(Default::default()))
+                        .build_node()
+                }),
+        );
         container.add::<Pivot>();
-        container.add::<scene::rigidbody::RigidBody>();
+        container.add_custom(
+            TwoBoneIk::type_uuid(),
+            NodeConstructor::new::<TwoBoneIk>()
+                .with_name("Two-Bone IK")
+                .with_category("Animation"),
+        );
+        container.add_custom(
+            ReflectionProbe::type_uuid(),
+            NodeConstructor::new::<ReflectionProbe>()
+                .with_name("Reflection Probe")
+                .with_category("Light"),
+        );
+        container.add_custom(
+            scene::rigidbody::RigidBody::type_uuid(),
+            NodeConstructor::new::<scene::rigidbody::RigidBody>()
+                .with_name("Rigid Body")
+                .with_category("Physics"),
+        );
         container.add::<Sprite>();
-        container.add::<Terrain>();
+        container.add_custom(
+            Terrain::type_uuid(),
+            NodeConstructor::new::<Terrain>().with_variant("Terrain", || {
+                TerrainBuilder::new(BaseBuilder::new())
+                    .with_layers(vec![LayerDefinition {
+                        material: make_terrain_layer_material(),
+                        mask_property_name: "maskTexture".to_owned(),
+                    }])
+                    .with_height_map_resolution(4.0)
+                    .build_node()
+            }),
+        );
 
         container
     }
 
-    /// Adds new type constructor for a given type and return previous constructor for the type
-    /// (if any).
+    /// Adds new type constructor for a given type. Display name of the type will be the name of
+    /// the type, use [`Self::add_custom`] to specify other metadata.
+    ///
+    /// # Panic
+    ///
+    /// The method will panic if there is already a constructor for given type uuid.
     pub fn add<T>(&self)
     where
         T: TypeUuidProvider + NodeTrait + Default,
@@ -65,12 +410,13 @@ impl NodeConstructorContainer {
         let previous = self
             .map
             .lock()
-            .insert(T::type_uuid(), Box::new(|| Node::new(T::default())));
+            .insert(T::type_uuid(), NodeConstructor::new::<T>());
 
         assert!(previous.is_none());
     }
 
-    /// Adds custom type constructor.
+    /// Adds custom type constructor. The metadata of the constructor (name, category, icon and
+    /// variants) is used by the editor to show the type in its creation menu.
     pub fn add_custom(&self, type_uuid: Uuid, constructor: NodeConstructor) {
         self.map.lock().insert(type_uuid, constructor);
     }
@@ -83,7 +429,12 @@ impl NodeConstructorContainer {
     /// Makes an attempt to create a node using provided type UUID. It may fail if there is no
     /// node constructor for specified type UUID.
     pub fn try_create(&self, type_uuid: &Uuid) -> Option<Node> {
-        self.map.lock().get_mut(type_uuid).map(|c| (c)())
+        self.map.lock().get_mut(type_uuid).map(|c| c.create())
+    }
+
+    /// Returns inner map of node constructors.
+    pub fn map(&self) -> MutexGuard<FxHashMap<Uuid, NodeConstructor>> {
+        self.map.lock()
     }
 
     /// Returns total amount of constructors.
@@ -21,13 +21,21 @@ use crate::{
         camera::Camera,
         decal::Decal,
         dim2::{self, rectangle::Rectangle},
+        fog_volume::FogVolume,
         graph::{self, Graph, NodePool},
-        light::{point::PointLight, spot::SpotLight},
+        hlod::Hlod,
+        ik::TwoBoneIk,
+        impostor::Impostor,
+        light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
         mesh::Mesh,
+        navmesh_obstacle::NavmeshObstacle,
         particle_system::ParticleSystem,
+        reflection_probe::ReflectionProbe,
         sound::{context::SoundContext, listener::Listener, Sound},
         sprite::Sprite,
         terrain::Terrain,
+        trigger_volume::TriggerVolume,
+        wind::Wind,
     },
 };
 use fxhash::FxHashMap;
@@ -58,6 +66,9 @@ pub trait BaseNodeTrait: Any + Debug + Deref<Target = Base> + DerefMut + Send {
 
     /// Returns self as mutable reference to [`Any`].
     fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// Returns name of the actual type of the node.
+    fn type_name(&self) -> &'static str;
 }
 
 impl<T> BaseNodeTrait for T
@@ -75,6 +86,10 @@ where
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
 }
 
 /// A data for synchronization. See [`NodeTrait::sync_native`] for more info.
@@ -93,8 +108,11 @@ pub struct SyncContext<'a> {
 pub struct UpdateContext<'a> {
     /// Size of client area of the window.
     pub frame_size: Vector2<f32>,
-    /// A time that have passed since last update call.
+    /// A time that have passed since last update call, scaled by the time scale of the scene.
     pub dt: f32,
+    /// Current time scale of the scene, see [`crate::scene::Scene::set_time_scale`]. It is zero
+    /// when no time has passed, so it could be used to scale per-frame quantities.
+    pub time_scale: f32,
     /// A reference to a pool with nodes from a scene graph.
     pub nodes: &'a NodePool,
     /// A mutable reference to 3D physics world.
@@ -103,6 +121,8 @@ pub struct UpdateContext<'a> {
     pub physics2d: &'a mut dim2::physics::PhysicsWorld,
     /// A mutable reference to sound context.
     pub sound_context: &'a mut SoundContext,
+    /// A reference to wind of the scene.
+    pub wind: &'a Wind,
 }
 
 /// Implements [`NodeTrait::query_component_ref`] and [`NodeTrait::query_component_mut`] in a much
@@ -394,11 +414,18 @@ impl Node {
     define_is_as!(Camera  => fn is_camera, fn as_camera, fn as_camera_mut);
     define_is_as!(SpotLight  => fn is_spot_light, fn as_spot_light, fn as_spot_light_mut);
     define_is_as!(PointLight  => fn is_point_light, fn as_point_light, fn as_point_light_mut);
-    define_is_as!(PointLight  => fn is_directional_light, fn as_directional_light, fn as_directional_light_mut);
+    define_is_as!(DirectionalLight  => fn is_directional_light, fn as_directional_light, fn as_directional_light_mut);
     define_is_as!(ParticleSystem => fn is_particle_system, fn as_particle_system, fn as_particle_system_mut);
     define_is_as!(Sprite  => fn is_sprite, fn as_sprite, fn as_sprite_mut);
     define_is_as!(Terrain  => fn is_terrain, fn as_terrain, fn as_terrain_mut);
     define_is_as!(Decal => fn is_decal, fn as_decal, fn as_decal_mut);
+    define_is_as!(ReflectionProbe => fn is_reflection_probe, fn as_reflection_probe, fn as_reflection_probe_mut);
+    define_is_as!(FogVolume => fn is_fog_volume, fn as_fog_volume, fn as_fog_volume_mut);
+    define_is_as!(TriggerVolume => fn is_trigger_volume, fn as_trigger_volume, fn as_trigger_volume_mut);
+    define_is_as!(NavmeshObstacle => fn is_navmesh_obstacle, fn as_navmesh_obstacle, fn as_navmesh_obstacle_mut);
+    define_is_as!(Hlod => fn is_hlod, fn as_hlod, fn as_hlod_mut);
+    define_is_as!(Impostor => fn is_impostor, fn as_impostor, fn as_impostor_mut);
+    define_is_as!(TwoBoneIk => fn is_two_bone_ik, fn as_two_bone_ik, fn as_two_bone_ik_mut);
     define_is_as!(Rectangle => fn is_rectangle, fn as_rectangle, fn as_rectangle_mut);
     define_is_as!(scene::rigidbody::RigidBody  => fn is_rigid_body, fn as_rigid_body, fn as_rigid_body_mut);
     define_is_as!(scene::collider::Collider => fn is_collider, fn as_collider, fn as_collider_mut);
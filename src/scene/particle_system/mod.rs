@@ -7,6 +7,11 @@
 //! to control separate particles, all particles controlled by parameters of particle
 //! emitters.
 //!
+//! # Simulation space
+//!
+//! Particles can be simulated either in local coordinates of the particle system or in world
+//! coordinates, see [`SimulationSpace`] for more info.
+//!
 //! # Emitters
 //!
 //! Particle system can contain multiple particle emitters, each emitter has its own
@@ -73,7 +78,7 @@
 use crate::{
     core::variable::{InheritError, TemplateVariable},
     core::{
-        algebra::{Vector2, Vector3},
+        algebra::{Matrix4, Point3, Vector2, Vector3},
         color::Color,
         color_gradient::ColorGradient,
         inspect::{Inspect, PropertyInfo},
@@ -103,6 +108,7 @@ use std::{
     fmt::Debug,
     ops::{Deref, DerefMut},
 };
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
 
 pub(crate) mod draw;
 pub mod emitter;
@@ -142,6 +148,29 @@ impl Visit for ParticleLimit {
     }
 }
 
+/// Defines a coordinate system in which particles are simulated.
+#[derive(
+    Copy, Clone, PartialEq, Eq, Hash, Debug, Visit, Inspect, AsRefStr, EnumString, EnumVariantNames,
+)]
+#[repr(u32)]
+pub enum SimulationSpace {
+    /// Particles are simulated in local coordinates of the particle system, which means that
+    /// every particle moves together with the particle system. This is the default mode.
+    Local = 0,
+
+    /// Particles are emitted at current world position of the particle system, but simulated
+    /// in world coordinates. It means that particles will leave a trail when the particle system
+    /// moves, this is useful for effects like torches, smoke from exhaust pipes and so on.
+    /// Acceleration of the particle system is treated as world-space acceleration in this mode.
+    World = 1,
+}
+
+impl Default for SimulationSpace {
+    fn default() -> Self {
+        Self::Local
+    }
+}
+
 /// See module docs.
 #[derive(Debug, Visit, Clone, Inspect)]
 pub struct ParticleSystem {
@@ -167,6 +196,14 @@ pub struct ParticleSystem {
     #[inspect(getter = "Deref::deref")]
     enabled: TemplateVariable<bool>,
 
+    #[inspect(getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    simulation_space: TemplateVariable<SimulationSpace>,
+
+    #[inspect(getter = "Deref::deref", min_value = 0.0, step = 0.1)]
+    #[visit(optional)] // Backward compatibility
+    wind_influence: TemplateVariable<f32>,
+
     #[inspect(skip)]
     particles: Vec<Particle>,
     #[inspect(skip)]
@@ -179,7 +216,9 @@ impl_directly_inheritable_entity_trait!(ParticleSystem;
     acceleration,
     color_over_lifetime,
     soft_boundary_sharpness_factor,
-    enabled
+    enabled,
+    simulation_space,
+    wind_influence
 );
 
 impl Deref for ParticleSystem {
@@ -243,6 +282,74 @@ impl ParticleSystem {
         self.soft_boundary_sharpness_factor.set(factor);
     }
 
+    /// Sets new simulation space of the particle system. Positions and velocities of alive
+    /// particles are converted to the new space, so the switch does not cause any visible jumps.
+    pub fn set_simulation_space(&mut self, simulation_space: SimulationSpace) {
+        let transform = match (*self.simulation_space, simulation_space) {
+            (SimulationSpace::Local, SimulationSpace::World) => self.global_transform(),
+            (SimulationSpace::World, SimulationSpace::Local) => self
+                .global_transform()
+                .try_inverse()
+                .unwrap_or_else(Matrix4::identity),
+            _ => Matrix4::identity(),
+        };
+
+        for particle in self.particles.iter_mut() {
+            particle.position = transform
+                .transform_point(&Point3::from(particle.position))
+                .coords;
+            particle.velocity = transform.transform_vector(&particle.velocity);
+        }
+
+        self.simulation_space.set(simulation_space);
+    }
+
+    /// Returns current simulation space of the particle system.
+    pub fn simulation_space(&self) -> SimulationSpace {
+        *self.simulation_space
+    }
+
+    /// Sets how strongly particles are dragged by the wind of the scene (see
+    /// [`crate::scene::wind::Wind`]). The influence is a rate (per second) at which velocity of
+    /// particles approaches velocity of the wind: `0.0` - particles are not affected by the wind
+    /// at all, `1.0` - light particles like smoke, large values make particles follow the wind
+    /// almost immediately. Negative values are clamped to zero.
+    pub fn set_wind_influence(&mut self, influence: f32) {
+        self.wind_influence.set(influence.max(0.0));
+    }
+
+    /// Returns current wind influence. See [`Self::set_wind_influence`] for more info.
+    pub fn wind_influence(&self) -> f32 {
+        *self.wind_influence
+    }
+
+    /// Returns a transformation matrix that should be used to transform particles from
+    /// the simulation space to world space.
+    pub fn particles_world_transform(&self) -> Matrix4<f32> {
+        match *self.simulation_space {
+            SimulationSpace::Local => self.global_transform(),
+            SimulationSpace::World => Matrix4::identity(),
+        }
+    }
+
+    /// Returns a bounding box of all alive particles in the simulation space of the particle
+    /// system. Returns `None` if there are no alive particles.
+    fn particles_bounding_box(&self) -> Option<AxisAlignedBoundingBox> {
+        let mut bounding_box = AxisAlignedBoundingBox::default();
+        let mut any = false;
+        for particle in self.particles.iter().filter(|p| p.alive) {
+            let half_size = Vector3::repeat(particle.size * 0.5);
+            bounding_box.add_point(particle.position - half_size);
+            bounding_box.add_point(particle.position + half_size);
+            any = true;
+        }
+        if any {
+            Some(bounding_box)
+        } else {
+            None
+        }
+    }
+
     /// Removes all generated particles.
     pub fn clear_particles(&mut self) {
         self.particles.clear();
@@ -261,9 +368,13 @@ impl ParticleSystem {
         camera_pos: &Vector3<f32>,
     ) {
         sorted_particles.clear();
+        let offset = match *self.simulation_space {
+            SimulationSpace::Local => self.base.global_position(),
+            SimulationSpace::World => Vector3::default(),
+        };
         for (i, particle) in self.particles.iter().enumerate() {
             if particle.alive {
-                let actual_position = particle.position + self.base.global_position();
+                let actual_position = particle.position + offset;
                 particle
                     .sqr_distance_to_camera
                     .set((camera_pos - actual_position).norm_squared());
@@ -367,12 +478,29 @@ impl NodeTrait for ParticleSystem {
     crate::impl_query_component!();
 
     fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
-        AxisAlignedBoundingBox::unit()
+        match *self.simulation_space {
+            SimulationSpace::Local => self
+                .particles_bounding_box()
+                .unwrap_or_else(AxisAlignedBoundingBox::unit),
+            SimulationSpace::World => self.world_bounding_box().transform(
+                &self
+                    .global_transform()
+                    .try_inverse()
+                    .unwrap_or_else(Matrix4::identity),
+            ),
+        }
     }
 
     fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
-        self.local_bounding_box()
-            .transform(&self.global_transform())
+        match *self.simulation_space {
+            SimulationSpace::Local => self
+                .local_bounding_box()
+                .transform(&self.global_transform()),
+            // Particles are already in world space.
+            SimulationSpace::World => self.particles_bounding_box().unwrap_or_else(|| {
+                AxisAlignedBoundingBox::unit().transform(&self.global_transform())
+            }),
+        }
     }
 
     // Prefab inheritance resolving.
@@ -407,12 +535,20 @@ impl NodeTrait for ParticleSystem {
 
     fn update(&mut self, context: &mut UpdateContext) -> bool {
         let dt = context.dt;
+        let time_scale = context.time_scale;
 
         if *self.enabled {
             for emitter in self.emitters.get_mut_silent().iter_mut() {
                 emitter.tick(dt);
             }
 
+            // In world space mode particles are emitted at current world position of the
+            // particle system and then simulated without any relation to the particle system.
+            let emission_transform = match *self.simulation_space {
+                SimulationSpace::Local => None,
+                SimulationSpace::World => Some(self.global_transform()),
+            };
+
             for (i, emitter) in self.emitters.get_mut_silent().iter_mut().enumerate() {
                 for _ in 0..emitter.particles_to_spawn {
                     let mut particle = Particle {
@@ -421,6 +557,12 @@ impl NodeTrait for ParticleSystem {
                     };
                     emitter.alive_particles += 1;
                     emitter.emit(&mut particle);
+                    if let Some(transform) = emission_transform.as_ref() {
+                        particle.position = transform
+                            .transform_point(&Point3::from(particle.position))
+                            .coords;
+                        particle.velocity = transform.transform_vector(&particle.velocity);
+                    }
                     if let Some(free_index) = self.free_particles.pop() {
                         self.particles[free_index as usize] = particle;
                     } else {
@@ -429,7 +571,30 @@ impl NodeTrait for ParticleSystem {
                 }
             }
 
-            let acceleration_offset = self.acceleration.scale(dt * dt);
+            // Velocity of particles is stored as a distance per (unscaled) frame, so time scale
+            // must be applied to the velocity as well. `dt * dt / time_scale` is the same as
+            // `scaled_dt * unscaled_dt`.
+            let acceleration_offset = if time_scale > 0.0 {
+                self.acceleration.scale(dt * dt / time_scale)
+            } else {
+                Vector3::default()
+            };
+
+            // Wind drags particles towards its velocity, the wind is sampled in world space and
+            // then converted to the simulation space, so particles drift along with vegetation.
+            let wind_drag = if time_scale > 0.0 && *self.wind_influence > 0.0 {
+                let world_transform = self.particles_world_transform();
+                Some((
+                    1.0 - (-*self.wind_influence * dt).exp(),
+                    dt / time_scale,
+                    world_transform,
+                    world_transform
+                        .try_inverse()
+                        .unwrap_or_else(Matrix4::identity),
+                ))
+            } else {
+                None
+            };
 
             for (i, particle) in self.particles.iter_mut().enumerate() {
                 if particle.alive {
@@ -447,7 +612,18 @@ impl NodeTrait for ParticleSystem {
                         particle.lifetime = particle.initial_lifetime;
                     } else {
                         particle.velocity += acceleration_offset;
-                        particle.position += particle.velocity;
+                        if let Some((drag, unscaled_dt, world_transform, inv_world_transform)) =
+                            wind_drag.as_ref()
+                        {
+                            let world_position = world_transform
+                                .transform_point(&Point3::from(particle.position))
+                                .coords;
+                            let wind = inv_world_transform
+                                .transform_vector(&context.wind.velocity_at(world_position))
+                                .scale(*unscaled_dt);
+                            particle.velocity += (wind - particle.velocity).scale(*drag);
+                        }
+                        particle.position += particle.velocity.scale(time_scale);
                         particle.size += particle.size_modifier * dt;
                         if particle.size < 0.0 {
                             particle.size = 0.0;
@@ -479,6 +655,8 @@ pub struct ParticleSystemBuilder {
     color_over_lifetime: Option<ColorGradient>,
     soft_boundary_sharpness_factor: f32,
     enabled: bool,
+    simulation_space: SimulationSpace,
+    wind_influence: f32,
 }
 
 impl ParticleSystemBuilder {
@@ -493,6 +671,8 @@ impl ParticleSystemBuilder {
             color_over_lifetime: None,
             soft_boundary_sharpness_factor: 2.5,
             enabled: true,
+            simulation_space: Default::default(),
+            wind_influence: 0.0,
         }
     }
 
@@ -545,6 +725,19 @@ impl ParticleSystemBuilder {
         self
     }
 
+    /// Sets desired simulation space of the particle system.
+    pub fn with_simulation_space(mut self, simulation_space: SimulationSpace) -> Self {
+        self.simulation_space = simulation_space;
+        self
+    }
+
+    /// Sets desired wind influence of the particle system, see
+    /// [`ParticleSystem::set_wind_influence`] for more info.
+    pub fn with_wind_influence(mut self, influence: f32) -> Self {
+        self.wind_influence = influence.max(0.0);
+        self
+    }
+
     fn build_particle_system(self) -> ParticleSystem {
         ParticleSystem {
             base: self.base_builder.build_base(),
@@ -556,6 +749,8 @@ impl ParticleSystemBuilder {
             color_over_lifetime: self.color_over_lifetime.into(),
             soft_boundary_sharpness_factor: self.soft_boundary_sharpness_factor.into(),
             enabled: self.enabled.into(),
+            simulation_space: self.simulation_space.into(),
+            wind_influence: self.wind_influence.into(),
         }
     }
 
@@ -573,12 +768,19 @@ impl ParticleSystemBuilder {
 #[cfg(test)]
 mod test {
     use crate::{
-        core::algebra::Vector3,
+        core::{
+            algebra::{Vector2, Vector3},
+            pool::Handle,
+        },
         resource::texture::test::create_test_texture,
         scene::{
             base::{test::check_inheritable_properties_equality, BaseBuilder},
-            node::NodeTrait,
-            particle_system::{ParticleSystem, ParticleSystemBuilder},
+            graph::Graph,
+            node::{Node, NodeTrait},
+            particle_system::{
+                emitter::{base::BaseEmitterBuilder, sphere::SphereEmitterBuilder},
+                ParticleSystem, ParticleSystemBuilder, SimulationSpace,
+            },
         },
     };
 
@@ -588,6 +790,8 @@ mod test {
             .with_texture(create_test_texture())
             .with_acceleration(Vector3::new(1.0, 0.0, 0.0))
             .with_enabled(false)
+            .with_simulation_space(SimulationSpace::World)
+            .with_wind_influence(2.0)
             .build_node();
 
         let mut child = ParticleSystemBuilder::new(BaseBuilder::new()).build_particle_system();
@@ -599,4 +803,95 @@ mod test {
         check_inheritable_properties_equality(&child.base, &parent.base);
         check_inheritable_properties_equality(&child, parent);
     }
+
+    #[test]
+    fn test_world_space_particles_do_not_follow_emitter() {
+        let mut graph = Graph::new();
+        let particle_system = ParticleSystemBuilder::new(BaseBuilder::new())
+            .with_acceleration(Vector3::default())
+            .with_simulation_space(SimulationSpace::World)
+            .with_emitters(vec![SphereEmitterBuilder::new(
+                BaseEmitterBuilder::new().with_spawn_rate(120),
+            )
+            .with_radius(0.01)
+            .build()])
+            .build(&mut graph);
+
+        let update = |graph: &mut Graph| graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0);
+
+        for _ in 0..10 {
+            update(&mut graph);
+        }
+
+        let old_positions = graph[particle_system]
+            .cast::<ParticleSystem>()
+            .unwrap()
+            .particles
+            .iter()
+            .map(|p| p.position)
+            .collect::<Vec<_>>();
+        assert!(!old_positions.is_empty());
+
+        // Move the emitter abruptly.
+        let new_position = Vector3::new(100.0, 0.0, 0.0);
+        graph[particle_system]
+            .local_transform_mut()
+            .set_position(new_position);
+
+        update(&mut graph);
+
+        let particle_system = graph[particle_system].cast::<ParticleSystem>().unwrap();
+        let (old, new) = particle_system.particles.split_at(old_positions.len());
+
+        // Existing particles must stay where they were.
+        for (particle, old_position) in old.iter().zip(old_positions.iter()) {
+            assert!(particle.alive);
+            assert!((particle.position - old_position).norm() < 0.01);
+        }
+
+        // New particles must be emitted at the new position.
+        assert!(!new.is_empty());
+        for particle in new {
+            assert!((particle.position - new_position).norm() < 0.1);
+        }
+
+        // Bounding box must include both old and new particles.
+        let bounding_box = particle_system.world_bounding_box();
+        assert!(bounding_box.is_contains_point(Vector3::default()));
+        assert!(bounding_box.is_contains_point(new_position));
+    }
+
+    #[test]
+    fn test_wind_drags_particles() {
+        let mut graph = Graph::new();
+        graph.wind.set_direction(Vector3::x());
+        graph.wind.set_strength(5.0);
+        graph.wind.set_gustiness(0.0);
+
+        let mut make_particle_system = |wind_influence| {
+            ParticleSystemBuilder::new(BaseBuilder::new())
+                .with_acceleration(Vector3::default())
+                .with_wind_influence(wind_influence)
+                .with_emitters(vec![SphereEmitterBuilder::new(
+                    BaseEmitterBuilder::new().with_spawn_rate(60),
+                )
+                .with_radius(0.01)
+                .build()])
+                .build(&mut graph)
+        };
+        let calm = make_particle_system(0.0);
+        let windy = make_particle_system(2.0);
+
+        for _ in 0..60 {
+            graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0);
+        }
+
+        // The first particle lived for a second, the wind must carry it a few meters downwind.
+        let first_particle_position = |handle: Handle<Node>| {
+            graph[handle].cast::<ParticleSystem>().unwrap().particles[0].position
+        };
+        assert!(first_particle_position(calm).x.abs() < 0.1);
+        assert!(first_particle_position(windy).x > 1.0);
+        assert!(first_particle_position(windy).y.abs() < 0.1);
+    }
 }
@@ -0,0 +1,342 @@
+//! Reflection probe is a box with a cube map of its surroundings that is used to calculate
+//! reflections of objects inside the box.
+//!
+//! For more info see [`ReflectionProbe`]
+
+use crate::{
+    asset::ResourceState,
+    core::variable::{InheritError, TemplateVariable},
+    core::{
+        algebra::Vector3,
+        inspect::{Inspect, PropertyInfo},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    impl_directly_inheritable_entity_trait,
+    resource::texture::Texture,
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{Node, NodeTrait, TypeUuidProvider},
+        DirectlyInheritableEntity,
+    },
+};
+use fxhash::FxHashMap;
+use std::ops::{Deref, DerefMut};
+
+/// Reflection probe is a box with a cube map of its surroundings (environment map). Objects inside
+/// the box use the environment map to calculate reflections, which gives much more plausible results
+/// indoors than reflections of a skybox.
+///
+/// # Capturing
+///
+/// The environment map is captured by rendering the scene from the position of the probe in six
+/// directions, see [`crate::renderer::Renderer::capture_reflection_probe`]. Usually it is done in
+/// the editor, but it could be done at runtime as well (keep in mind that it is expensive). Size
+/// of each face of the cube map is defined by [`ReflectionProbe::resolution`].
+///
+/// # Box projection
+///
+/// Environment map is sampled using box projection - a reflection ray is intersected with the box
+/// of the probe and the intersection point is used to fetch a pixel from the map. This makes
+/// reflections of objects near the walls of a room match their real positions. The box is always
+/// axis-aligned in world space, rotation and scale of the probe are ignored.
+///
+/// # Blending
+///
+/// An object could be inside of multiple probes at once, in this case reflections will be blended
+/// using weights that depend on the distance to the edges of each box (see
+/// [`ReflectionProbe::weight`]). Objects outside of every probe reflect the skybox.
+///
+/// # Serialization
+///
+/// Captured environment maps are embedded into the scene. It is also possible to assign an
+/// environment map loaded from a file, in this case only the path to the file will be saved and
+/// the map will be loaded again when the scene is loaded.
+#[derive(Debug, Visit, Clone, Inspect)]
+pub struct ReflectionProbe {
+    base: Base,
+
+    #[inspect(getter = "Deref::deref")]
+    extents: TemplateVariable<Vector3<f32>>,
+
+    #[inspect(min_value = 0.0, getter = "Deref::deref")]
+    blend_distance: TemplateVariable<f32>,
+
+    #[inspect(min_value = 1.0, max_value = 2048.0, getter = "Deref::deref")]
+    resolution: TemplateVariable<u32>,
+
+    #[inspect(getter = "Deref::deref")]
+    environment: TemplateVariable<Option<Texture>>,
+}
+
+impl_directly_inheritable_entity_trait!(ReflectionProbe;
+    extents,
+    blend_distance,
+    resolution,
+    environment
+);
+
+impl Default for ReflectionProbe {
+    fn default() -> Self {
+        ReflectionProbeBuilder::new(BaseBuilder::new()).build_reflection_probe()
+    }
+}
+
+impl Deref for ReflectionProbe {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for ReflectionProbe {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for ReflectionProbe {
+    fn type_uuid() -> Uuid {
+        uuid!("0e1ae1c8-1f8d-4b59-9c0b-63a3ad7b1d44")
+    }
+}
+
+impl ReflectionProbe {
+    /// Sets new half-extents of the box of the probe.
+    pub fn set_extents(&mut self, extents: Vector3<f32>) {
+        self.extents.set(extents.map(|v| v.abs()));
+    }
+
+    /// Returns current half-extents of the box of the probe.
+    pub fn extents(&self) -> Vector3<f32> {
+        *self.extents
+    }
+
+    /// Sets new distance from the edges of the box in which reflections of the probe will fade
+    /// out. It allows to have smooth transitions between probes.
+    pub fn set_blend_distance(&mut self, blend_distance: f32) {
+        self.blend_distance.set(blend_distance.max(0.0));
+    }
+
+    /// Returns current blend distance.
+    pub fn blend_distance(&self) -> f32 {
+        *self.blend_distance
+    }
+
+    /// Sets new size of a face of the environment map. It will be used on next capture.
+    pub fn set_resolution(&mut self, resolution: u32) {
+        self.resolution.set(resolution.max(1));
+    }
+
+    /// Returns current size of a face of the environment map.
+    pub fn resolution(&self) -> u32 {
+        *self.resolution
+    }
+
+    /// Sets new environment map, it must be a cube map.
+    pub fn set_environment(&mut self, environment: Option<Texture>) -> Option<Texture> {
+        std::mem::replace(self.environment.get_mut(), environment)
+    }
+
+    /// Returns current environment map.
+    pub fn environment(&self) -> Option<&Texture> {
+        self.environment.as_ref()
+    }
+
+    /// Returns current environment map.
+    pub fn environment_value(&self) -> Option<Texture> {
+        (*self.environment).clone()
+    }
+
+    /// Returns world-space box of the probe.
+    pub fn world_box(&self) -> AxisAlignedBoundingBox {
+        let position = self.global_position();
+        AxisAlignedBoundingBox::from_min_max(position - *self.extents, position + *self.extents)
+    }
+
+    /// Returns a weight of the probe for the given world-space point. The weight is zero outside
+    /// of the box, it is one inside of the box further than blend distance from its edges and it
+    /// fades linearly in between.
+    pub fn weight(&self, point: Vector3<f32>) -> f32 {
+        let world_box = self.world_box();
+        let to_edges = (point - world_box.min).inf(&(world_box.max - point));
+        let distance_to_edge = to_edges.min();
+        if distance_to_edge < 0.0 {
+            0.0
+        } else if *self.blend_distance <= f32::EPSILON {
+            1.0
+        } else {
+            (distance_to_edge / *self.blend_distance).min(1.0)
+        }
+    }
+}
+
+impl NodeTrait for ReflectionProbe {
+    crate::impl_query_component!();
+
+    /// Returns current **local-space** bounding box.
+    #[inline]
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::from_min_max(-*self.extents, *self.extents)
+    }
+
+    /// Returns current **world-space** bounding box.
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.world_box()
+    }
+
+    // Prefab inheritance resolving.
+    fn inherit(&mut self, parent: &Node) -> Result<(), InheritError> {
+        self.base.inherit_properties(parent)?;
+        if let Some(parent) = parent.cast::<Self>() {
+            self.try_inherit_self_properties(parent)?;
+        }
+        Ok(())
+    }
+
+    fn reset_inheritable_properties(&mut self) {
+        self.base.reset_inheritable_properties();
+        self.reset_self_inheritable_properties();
+    }
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        self.base.restore_resources(resource_manager.clone());
+
+        // Captured maps are embedded in the scene, only maps loaded from files must be restored.
+        if let Some(environment) = self.environment.get_mut_silent().as_mut() {
+            let state = environment.state();
+            let path = state.path().to_path_buf();
+            let is_embedded = matches!(&*state, ResourceState::Ok(data) if data.is_procedural());
+            drop(state);
+
+            if !is_embedded {
+                *environment = resource_manager.request_texture(path);
+            }
+        }
+    }
+
+    fn remap_handles(&mut self, old_new_mapping: &FxHashMap<Handle<Node>, Handle<Node>>) {
+        self.base.remap_handles(old_new_mapping);
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+/// Allows you to create a reflection probe in a declarative manner.
+pub struct ReflectionProbeBuilder {
+    base_builder: BaseBuilder,
+    extents: Vector3<f32>,
+    blend_distance: f32,
+    resolution: u32,
+    environment: Option<Texture>,
+}
+
+impl ReflectionProbeBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            extents: Vector3::new(5.0, 5.0, 5.0),
+            blend_distance: 1.0,
+            resolution: 128,
+            environment: None,
+        }
+    }
+
+    /// Sets desired half-extents of the box.
+    pub fn with_extents(mut self, extents: Vector3<f32>) -> Self {
+        self.extents = extents;
+        self
+    }
+
+    /// Sets desired blend distance.
+    pub fn with_blend_distance(mut self, blend_distance: f32) -> Self {
+        self.blend_distance = blend_distance;
+        self
+    }
+
+    /// Sets desired size of a face of the environment map.
+    pub fn with_resolution(mut self, resolution: u32) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    /// Sets desired environment map.
+    pub fn with_environment(mut self, environment: Texture) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// Creates new reflection probe.
+    pub fn build_reflection_probe(self) -> ReflectionProbe {
+        ReflectionProbe {
+            base: self.base_builder.build_base(),
+            extents: self.extents.map(|v| v.abs()).into(),
+            blend_distance: self.blend_distance.max(0.0).into(),
+            resolution: self.resolution.max(1).into(),
+            environment: self.environment.into(),
+        }
+    }
+
+    /// Creates new reflection probe node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_reflection_probe())
+    }
+
+    /// Creates new instance of reflection probe node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        resource::texture::test::create_test_texture,
+        scene::{
+            base::{test::check_inheritable_properties_equality, BaseBuilder},
+            node::NodeTrait,
+            reflection_probe::{ReflectionProbe, ReflectionProbeBuilder},
+        },
+    };
+
+    #[test]
+    fn test_reflection_probe_inheritance() {
+        let parent = ReflectionProbeBuilder::new(BaseBuilder::new())
+            .with_extents(Vector3::new(1.0, 2.0, 3.0))
+            .with_blend_distance(0.5)
+            .with_resolution(64)
+            .with_environment(create_test_texture())
+            .build_node();
+
+        let mut child = ReflectionProbeBuilder::new(BaseBuilder::new()).build_reflection_probe();
+
+        child.inherit(&parent).unwrap();
+
+        let parent = parent.cast::<ReflectionProbe>().unwrap();
+
+        check_inheritable_properties_equality(&child.base, &parent.base);
+        check_inheritable_properties_equality(&child, parent);
+    }
+
+    #[test]
+    fn test_reflection_probe_weight() {
+        let probe = ReflectionProbeBuilder::new(BaseBuilder::new())
+            .with_extents(Vector3::new(2.0, 2.0, 2.0))
+            .with_blend_distance(1.0)
+            .build_reflection_probe();
+
+        assert_eq!(probe.weight(Vector3::new(0.0, 0.0, 0.0)), 1.0);
+        assert_eq!(probe.weight(Vector3::new(1.5, 0.0, 0.0)), 0.5);
+        assert_eq!(probe.weight(Vector3::new(0.0, -2.0, 0.0)), 0.0);
+        assert_eq!(probe.weight(Vector3::new(0.0, 0.0, 3.0)), 0.0);
+    }
+}
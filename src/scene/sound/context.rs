@@ -3,12 +3,15 @@
 use crate::scene::graph::NodePool;
 use crate::{
     core::{
+        algebra::{Point3, Vector3},
+        arrayvec::ArrayVec,
         inspect::{Inspect, PropertyInfo},
         pool::{Handle, Pool},
         visitor::prelude::*,
     },
     resource::model::Model,
     scene::{
+        graph::physics::{Intersection, PhysicsWorld, RayCastOptions},
         node::Node,
         sound::{self, effect::Effect, Sound},
     },
@@ -17,13 +20,110 @@ use crate::{
 use fxhash::FxHashMap;
 use fyrox_core::pool::Ticket;
 use fyrox_sound::{
-    context::DistanceModel,
+    context::{AirAbsorption, DistanceModel},
     effects::{reverb::Reverb, BaseEffect, EffectInput, InputFilter},
     renderer::Renderer,
     source::{SoundSource, SoundSourceBuilder, Status},
 };
 use std::time::Duration;
 
+/// Global settings of sound occlusion. Occlusion is calculated by casting rays from the active
+/// listener to every sound source that has occlusion enabled (see [`Sound::set_occlusion_enabled`]).
+/// A fraction of rays blocked by colliders defines target occlusion factor of a source, the actual
+/// factor smoothly moves towards the target and drives volume attenuation and low-pass filtering
+/// of the source.
+#[derive(Debug, Clone, PartialEq, Visit, Inspect)]
+pub struct OcclusionSettings {
+    /// Amount of rays per sound source. The first ray always goes directly to the source, the rest
+    /// are distributed uniformly on a circle around the source (see [`Self::spread`]). More rays
+    /// gives smoother transitions when a source moves behind an obstacle, but costs more.
+    #[inspect(min_value = 1.0, step = 1.0)]
+    pub ray_count: u32,
+    /// Radius of a circle around a sound source at which additional rays are aimed.
+    #[inspect(min_value = 0.0, step = 0.05)]
+    pub spread: f32,
+    /// Strength of low-pass filtering of fully occluded sound in 0..1 range.
+    #[inspect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub low_pass: f32,
+    /// Volume attenuation of fully occluded sound in 0..1 range. 0 - no attenuation, 1 - fully
+    /// occluded sound will be silent.
+    #[inspect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub attenuation: f32,
+    /// Maximum change of occlusion factor per second. Lower values gives smoother transitions.
+    #[inspect(min_value = 0.0, step = 0.1)]
+    pub speed: f32,
+    /// Maximum amount of sound sources that will be checked for occlusion per frame. Ray casting
+    /// is amortized across multiple frames, so if there are more sources than this value, then
+    /// each source will be updated once per few frames.
+    #[inspect(min_value = 1.0, step = 1.0)]
+    pub sources_per_frame: u32,
+}
+
+impl Default for OcclusionSettings {
+    fn default() -> Self {
+        Self {
+            ray_count: 5,
+            spread: 0.5,
+            low_pass: 0.8,
+            attenuation: 0.5,
+            speed: 4.0,
+            sources_per_frame: 8,
+        }
+    }
+}
+
+impl OcclusionSettings {
+    fn calculate_occlusion(
+        &self,
+        physics: &PhysicsWorld,
+        listener_position: Vector3<f32>,
+        sound: &Sound,
+    ) -> f32 {
+        let source_position = sound.global_position();
+        let direction = source_position - listener_position;
+        if direction.norm() <= f32::EPSILON {
+            return 0.0;
+        }
+
+        let side = direction
+            .cross(&Vector3::y())
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::x);
+        let up = side.cross(&direction).normalize();
+
+        let ray_count = self.ray_count.max(1);
+        let mut blocked = 0;
+        let mut query_buffer = ArrayVec::<Intersection, 1>::new();
+        for i in 0..ray_count {
+            let target = if i == 0 {
+                source_position
+            } else {
+                let angle = std::f32::consts::TAU * (i - 1) as f32 / (ray_count - 1) as f32;
+                source_position + (side.scale(angle.cos()) + up.scale(angle.sin())) * self.spread
+            };
+
+            let ray_direction = target - listener_position;
+
+            physics.cast_ray(
+                RayCastOptions {
+                    ray_origin: Point3::from(listener_position),
+                    ray_direction,
+                    max_len: ray_direction.norm(),
+                    groups: sound.occluder_groups(),
+                    sort_results: false,
+                },
+                &mut query_buffer,
+            );
+
+            if !query_buffer.is_empty() {
+                blocked += 1;
+            }
+        }
+
+        blocked as f32 / ray_count as f32
+    }
+}
+
 /// Sound context.
 #[derive(Debug, Visit, Inspect)]
 pub struct SoundContext {
@@ -31,6 +131,13 @@ pub struct SoundContext {
     renderer: Renderer,
     distance_model: DistanceModel,
     paused: bool,
+    #[visit(optional)] // Backward compatibility
+    occlusion: OcclusionSettings,
+    #[visit(optional)] // Backward compatibility
+    air_absorption: AirAbsorption,
+    #[visit(skip)]
+    #[inspect(skip)]
+    occlusion_cursor: u32,
     #[inspect(skip)]
     pub(crate) effects: Pool<Effect>,
     #[inspect(read_only)]
@@ -48,6 +155,9 @@ impl Default for SoundContext {
             renderer: Default::default(),
             distance_model: Default::default(),
             paused: false,
+            occlusion: Default::default(),
+            air_absorption: Default::default(),
+            occlusion_cursor: 0,
             effects: Default::default(),
             resource: None,
             native: fyrox_sound::context::SoundContext::new(),
@@ -170,8 +280,95 @@ impl SoundContext {
         self.master_gain
     }
 
-    pub(crate) fn update(&mut self, nodes: &NodePool) {
+    /// Sets new occlusion settings. See [`OcclusionSettings`] docs for more info.
+    pub fn set_occlusion_settings(&mut self, settings: OcclusionSettings) {
+        self.occlusion = settings;
+    }
+
+    /// Returns current occlusion settings.
+    pub fn occlusion_settings(&self) -> OcclusionSettings {
+        self.occlusion.clone()
+    }
+
+    /// Sets new air absorption settings. See [`AirAbsorption`] docs for more info.
+    pub fn set_air_absorption(&mut self, air_absorption: AirAbsorption) {
+        self.air_absorption = air_absorption;
+        self.native
+            .state()
+            .set_air_absorption(self.air_absorption.clone());
+    }
+
+    /// Returns current air absorption settings.
+    pub fn air_absorption(&self) -> AirAbsorption {
+        self.air_absorption.clone()
+    }
+
+    pub(crate) fn update_occlusion(&mut self, nodes: &NodePool, physics: &PhysicsWorld, dt: f32) {
         let mut state = self.native.state();
+        let listener_position = state.listener().position();
+
+        // Ray casting is amortized across frames - only a limited amount of sounds gets new target
+        // occlusion factor per frame, while interpolation is done for every sound on every frame.
+        let capacity = nodes.get_capacity();
+        let mut budget = self.occlusion.sources_per_frame;
+        let mut visited = 0;
+        while budget > 0 && visited < capacity {
+            let index = self.occlusion_cursor % capacity;
+            self.occlusion_cursor = index + 1;
+            visited += 1;
+
+            if let Some(sound) = nodes.at(index).and_then(|n| n.cast::<Sound>()) {
+                if sound.is_occlusion_enabled() {
+                    sound
+                        .occlusion_target
+                        .set(
+                            self.occlusion
+                                .calculate_occlusion(physics, listener_position, sound),
+                        );
+                    budget -= 1;
+                }
+            }
+        }
+
+        let max_step = self.occlusion.speed * dt;
+        for sound in nodes.iter().filter_map(|n| n.cast::<Sound>()) {
+            if let Some(source) = state.try_get_source_mut(sound.native.get()) {
+                if sound.is_occlusion_enabled() {
+                    let factor = sound.occlusion_factor.get();
+                    let new_factor = (factor
+                        + (sound.occlusion_target.get() - factor).clamp(-max_step, max_step))
+                    .clamp(0.0, 1.0);
+                    sound.occlusion_factor.set(new_factor);
+                    source.set_occlusion(
+                        1.0 - self.occlusion.attenuation * new_factor,
+                        self.occlusion.low_pass * new_factor,
+                    );
+                } else if sound.occlusion_factor.get() != 0.0 {
+                    sound.occlusion_factor.set(0.0);
+                    sound.occlusion_target.set(0.0);
+                    source.set_occlusion(1.0, 0.0);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn update(&mut self, nodes: &NodePool, time_scale: f32) {
+        let mut state = self.native.state();
+
+        // Settings could be loaded from a scene, make sure the native context uses them.
+        if state.air_absorption() != &self.air_absorption {
+            state.set_air_absorption(self.air_absorption.clone());
+        }
+
+        // Pitch of sounds that follow time scale of the scene. Zero pitch is fine, it just stops
+        // the playback of a sound.
+        for sound in nodes.iter().filter_map(|n| n.cast::<Sound>()) {
+            if sound.is_time_scale_affects_pitch() && sound.native.get().is_some() {
+                if let Some(source) = state.try_get_source_mut(sound.native.get()) {
+                    source.set_pitch(sound.pitch() * time_scale as f64);
+                }
+            }
+        }
 
         fn sync_effect_inputs(
             native_effect: &mut fyrox_sound::effects::BaseEffect,
@@ -269,12 +466,27 @@ impl SoundContext {
             sound.radius.try_sync_model(|v| {
                 source.set_radius(v);
             });
+            sound.spread.try_sync_model(|v| {
+                source.set_spread(v);
+            });
+            sound.min_distance.try_sync_model(|v| {
+                source.set_min_distance(v);
+            });
+            sound.air_absorption.try_sync_model(|v| {
+                source.set_air_absorption(v);
+            });
             sound.playback_time.try_sync_model(|v| {
                 source.set_playback_time(v);
             });
             sound.pitch.try_sync_model(|v| {
                 source.set_pitch(v);
             });
+            sound.time_scale_affects_pitch.try_sync_model(|v| {
+                if !v {
+                    // Restore original pitch, it will be scaled on update otherwise.
+                    source.set_pitch(sound.pitch());
+                }
+            });
             sound.looping.try_sync_model(|v| {
                 source.set_looping(v);
             });
@@ -301,6 +513,16 @@ impl SoundContext {
                     source.pause();
                 }
             });
+
+            // Sounds of disabled nodes are paused, status of the node is left untouched, so the
+            // sound will be resumed when the node is enabled again.
+            if !sound.is_globally_enabled() {
+                if source.status() == Status::Playing {
+                    source.pause();
+                }
+            } else if sound.status() == Status::Playing && source.status() == Status::Paused {
+                source.play();
+            }
         } else {
             match SoundSourceBuilder::new()
                 .with_gain(sound.gain())
@@ -314,6 +536,9 @@ impl SoundContext {
                 .with_radius(sound.radius())
                 .with_max_distance(sound.max_distance())
                 .with_rolloff_factor(sound.rolloff_factor())
+                .with_spread(sound.spread())
+                .with_min_distance(sound.min_distance())
+                .with_air_absorption(sound.air_absorption())
                 .build()
             {
                 Ok(source) => {
@@ -351,3 +576,113 @@ impl SoundContext {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            pool::Handle,
+        },
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            node::Node,
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            sound::{listener::ListenerBuilder, AirAbsorption, Sound, SoundBuilder},
+            transform::TransformBuilder,
+        },
+    };
+
+    fn make_sound(graph: &mut Graph, position: Vector3<f32>) -> Handle<Node> {
+        SoundBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            ),
+        )
+        .with_occlusion_enabled(true)
+        .build(graph)
+    }
+
+    #[test]
+    fn test_sound_occlusion_converges() {
+        let mut graph = Graph::new();
+
+        ListenerBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        // A wall between the listener and the first sound.
+        let wall = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(5.0, 5.0, 0.1))
+            .build(&mut graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 0.0, 5.0))
+                        .build(),
+                )
+                .with_children(&[wall]),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(&mut graph);
+
+        let occluded = make_sound(&mut graph, Vector3::new(0.0, 0.0, 10.0));
+        let free = make_sound(&mut graph, Vector3::new(0.0, 0.0, -10.0));
+
+        for _ in 0..60 {
+            graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0);
+        }
+
+        let occluded = graph.try_get_of_type::<Sound>(occluded).unwrap();
+        let free = graph.try_get_of_type::<Sound>(free).unwrap();
+
+        assert!((occluded.occlusion_factor() - 1.0).abs() < 0.001);
+        assert!(free.occlusion_factor().abs() < 0.001);
+
+        let mut state = graph.sound_context.native.state();
+        let native = state.source_mut(occluded.native.get());
+        assert!((native.occlusion_gain() - 0.5).abs() < 0.001);
+        assert!((native.occlusion_low_pass() - 0.8).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_sound_spatial_parameters_are_synced() {
+        let mut graph = Graph::new();
+
+        ListenerBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        graph.sound_context.set_air_absorption(AirAbsorption {
+            enabled: true,
+            ..Default::default()
+        });
+
+        let sound = SoundBuilder::new(BaseBuilder::new())
+            .with_spread(90.0)
+            .with_min_distance(2.0)
+            .with_air_absorption(0.5)
+            .build(&mut graph);
+
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0);
+
+        {
+            let sound = graph.try_get_of_type::<Sound>(sound).unwrap();
+            let mut state = graph.sound_context.native.state();
+            assert!(state.air_absorption().enabled);
+            let native = state.source_mut(sound.native.get());
+            assert_eq!(native.spread(), 90.0);
+            assert_eq!(native.min_distance(), 2.0);
+            assert_eq!(native.air_absorption(), 0.5);
+        }
+
+        // Changes must be propagated too.
+        graph[sound].as_sound_mut().set_spread(180.0);
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0);
+
+        let sound = graph.try_get_of_type::<Sound>(sound).unwrap();
+        let mut state = graph.sound_context.native.state();
+        assert_eq!(state.source_mut(sound.native.get()).spread(), 180.0);
+    }
+}
@@ -1,6 +1,7 @@
 //! Everything related to sound in the engine.
 
 use crate::{
+    asset::ResourceState,
     core::variable::{InheritError, TemplateVariable},
     core::{
         algebra::Matrix4,
@@ -15,6 +16,7 @@ use crate::{
     impl_directly_inheritable_entity_trait,
     scene::{
         base::{Base, BaseBuilder},
+        collider::InteractionGroups,
         graph::Graph,
         node::{Node, NodeTrait, SyncContext, TypeUuidProvider, UpdateContext},
         DirectlyInheritableEntity,
@@ -24,8 +26,11 @@ use crate::{
 
 // Re-export some the fyrox_sound entities.
 pub use fyrox_sound::{
-    buffer::{DataSource, SoundBufferResource, SoundBufferResourceLoadError, SoundBufferState},
-    context::{DistanceModel, SAMPLE_RATE},
+    buffer::{
+        procedural::{ProceduralBuffer, ProceduralSource, SampleQueue},
+        DataSource, SoundBufferResource, SoundBufferResourceLoadError, SoundBufferState,
+    },
+    context::{AirAbsorption, DistanceModel, SAMPLE_RATE},
     dsp::{filters::*, DelayLine},
     engine::SoundEngine,
     error::SoundError,
@@ -70,13 +75,42 @@ pub struct Sound {
     max_distance: TemplateVariable<f32>,
     #[inspect(min_value = 0.0, step = 0.05, getter = "Deref::deref")]
     rolloff_factor: TemplateVariable<f32>,
+    #[inspect(
+        min_value = 0.0,
+        max_value = 360.0,
+        step = 1.0,
+        getter = "Deref::deref"
+    )]
+    #[visit(optional)] // Backward compatibility
+    spread: TemplateVariable<f32>,
+    #[inspect(min_value = 0.0, step = 0.05, getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    min_distance: TemplateVariable<f32>,
+    #[inspect(min_value = 0.0, step = 0.05, getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    air_absorption: TemplateVariable<f32>,
     #[inspect(getter = "Deref::deref")]
     playback_time: TemplateVariable<Duration>,
     #[inspect(getter = "Deref::deref")]
     spatial_blend: TemplateVariable<f32>,
+    #[inspect(getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    occlusion_enabled: TemplateVariable<bool>,
+    #[inspect(getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    occluder_groups: TemplateVariable<InteractionGroups>,
+    #[inspect(getter = "Deref::deref")]
+    #[visit(optional)] // Backward compatibility
+    time_scale_affects_pitch: TemplateVariable<bool>,
     #[inspect(skip)]
     #[visit(skip)]
     pub(crate) native: Cell<Handle<SoundSource>>,
+    #[inspect(skip)]
+    #[visit(skip)]
+    pub(crate) occlusion_factor: Cell<f32>,
+    #[inspect(skip)]
+    #[visit(skip)]
+    pub(crate) occlusion_target: Cell<f32>,
 }
 
 impl_directly_inheritable_entity_trait!(Sound;
@@ -90,7 +124,13 @@ impl_directly_inheritable_entity_trait!(Sound;
     radius,
     max_distance,
     rolloff_factor,
-    playback_time
+    spread,
+    min_distance,
+    air_absorption,
+    playback_time,
+    occlusion_enabled,
+    occluder_groups,
+    time_scale_affects_pitch
 );
 
 impl Deref for Sound {
@@ -121,9 +161,17 @@ impl Default for Sound {
             radius: TemplateVariable::new(10.0),
             max_distance: TemplateVariable::new(f32::MAX),
             rolloff_factor: TemplateVariable::new(1.0),
+            spread: TemplateVariable::new(0.0),
+            min_distance: TemplateVariable::new(0.0),
+            air_absorption: TemplateVariable::new(1.0),
             playback_time: Default::default(),
             spatial_blend: TemplateVariable::new(1.0),
+            occlusion_enabled: TemplateVariable::new(false),
+            occluder_groups: Default::default(),
+            time_scale_affects_pitch: TemplateVariable::new(false),
             native: Default::default(),
+            occlusion_factor: Cell::new(0.0),
+            occlusion_target: Cell::new(0.0),
         }
     }
 }
@@ -142,10 +190,18 @@ impl Clone for Sound {
             radius: self.radius.clone(),
             max_distance: self.max_distance.clone(),
             rolloff_factor: self.rolloff_factor.clone(),
+            spread: self.spread.clone(),
+            min_distance: self.min_distance.clone(),
+            air_absorption: self.air_absorption.clone(),
             playback_time: self.playback_time.clone(),
             spatial_blend: self.spatial_blend.clone(),
+            occlusion_enabled: self.occlusion_enabled.clone(),
+            occluder_groups: self.occluder_groups.clone(),
+            time_scale_affects_pitch: self.time_scale_affects_pitch.clone(),
             // Do not copy.
             native: Default::default(),
+            occlusion_factor: Cell::new(0.0),
+            occlusion_target: Cell::new(0.0),
         }
     }
 }
@@ -317,6 +373,84 @@ impl Sound {
     pub fn max_distance(&self) -> f32 {
         *self.max_distance
     }
+
+    /// Sets spread angle (in degrees, 0..360 range) of the sound. The spread reduces panning of the
+    /// sound, so it does not snap between left and right channels when the listener passes near
+    /// it. 0 degrees means a point source, 360 degrees - no panning at all. See
+    /// [`fyrox_sound::source::SoundSource::set_spread`] for more info.
+    pub fn set_spread(&mut self, spread: f32) {
+        self.spread.set(spread.clamp(0.0, 360.0));
+    }
+
+    /// Returns spread angle of the sound in degrees.
+    pub fn spread(&self) -> f32 {
+        *self.spread
+    }
+
+    /// Sets radius of a sphere around the sound, in which the sound is heard at full volume. The
+    /// distance attenuation starts at the edge of the sphere. See
+    /// [`fyrox_sound::source::SoundSource::set_min_distance`] for more info.
+    pub fn set_min_distance(&mut self, min_distance: f32) {
+        self.min_distance.set(min_distance.max(0.0));
+    }
+
+    /// Returns radius of the full-volume sphere.
+    pub fn min_distance(&self) -> f32 {
+        *self.min_distance
+    }
+
+    /// Sets air absorption multiplier of the sound. Air absorption is configured globally by
+    /// [`AirAbsorption`] settings of the sound context, the multiplier defines how much the sound is
+    /// affected by it: 0 - not affected at all, 1 - default absorption.
+    pub fn set_air_absorption(&mut self, multiplier: f32) {
+        self.air_absorption.set(multiplier.max(0.0));
+    }
+
+    /// Returns air absorption multiplier of the sound.
+    pub fn air_absorption(&self) -> f32 {
+        *self.air_absorption
+    }
+
+    /// Enables or disables occlusion of the sound. Occluded sound will be muffled and attenuated
+    /// if there is some geometry (colliders) between the sound and the active listener. Occlusion
+    /// parameters are defined by [`context::OcclusionSettings`] of the sound context.
+    pub fn set_occlusion_enabled(&mut self, enabled: bool) {
+        self.occlusion_enabled.set(enabled);
+    }
+
+    /// Returns true if the sound can be occluded, false - otherwise.
+    pub fn is_occlusion_enabled(&self) -> bool {
+        *self.occlusion_enabled
+    }
+
+    /// Sets collision groups of colliders that will be treated as occluders. Colliders that do not
+    /// match the groups will be ignored.
+    pub fn set_occluder_groups(&mut self, groups: InteractionGroups) {
+        self.occluder_groups.set(groups);
+    }
+
+    /// Returns collision groups of colliders that will be treated as occluders.
+    pub fn occluder_groups(&self) -> InteractionGroups {
+        *self.occluder_groups
+    }
+
+    /// Sets whether the time scale of the scene should affect the pitch of the sound or not. If
+    /// enabled, actual pitch of the sound will be `pitch * time_scale`, so the sound will slow down
+    /// together with the scene (see [`crate::scene::Scene::set_time_scale`]). Disabled by default.
+    pub fn set_time_scale_affects_pitch(&mut self, enabled: bool) {
+        self.time_scale_affects_pitch.set(enabled);
+    }
+
+    /// Returns `true` if the time scale of the scene affects the pitch of the sound.
+    pub fn is_time_scale_affects_pitch(&self) -> bool {
+        *self.time_scale_affects_pitch
+    }
+
+    /// Returns current occlusion factor of the sound in 0..1 range, where 0 means that the sound
+    /// is fully audible and 1 - fully occluded. The factor is changed smoothly over time.
+    pub fn occlusion_factor(&self) -> f32 {
+        self.occlusion_factor.get()
+    }
 }
 
 impl NodeTrait for Sound {
@@ -350,7 +484,11 @@ impl NodeTrait for Sound {
 
         if let Some(buffer) = self.buffer() {
             let state = buffer.state();
-            self.set_buffer(Some(resource_manager.request_sound_buffer(state.path())));
+            // Procedural buffers have no source file, loaded placeholder is kept as is so
+            // the game can re-bind its source of samples.
+            if !matches!(*state, ResourceState::Ok(SoundBufferState::Procedural(_))) {
+                self.set_buffer(Some(resource_manager.request_sound_buffer(state.path())));
+            }
         }
     }
 
@@ -402,8 +540,14 @@ pub struct SoundBuilder {
     radius: f32,
     max_distance: f32,
     rolloff_factor: f32,
+    spread: f32,
+    min_distance: f32,
+    air_absorption: f32,
     playback_time: Duration,
     spatial_blend: f32,
+    occlusion_enabled: bool,
+    occluder_groups: InteractionGroups,
+    time_scale_affects_pitch: bool,
 }
 
 impl SoundBuilder {
@@ -421,8 +565,14 @@ impl SoundBuilder {
             radius: 10.0,
             max_distance: f32::MAX,
             rolloff_factor: 1.0,
+            spread: 0.0,
+            min_distance: 0.0,
+            air_absorption: 1.0,
             spatial_blend: 1.0,
             playback_time: Default::default(),
+            occlusion_enabled: false,
+            occluder_groups: Default::default(),
+            time_scale_affects_pitch: false,
         }
     }
 
@@ -476,6 +626,21 @@ impl SoundBuilder {
         fn with_rolloff_factor(rolloff_factor: f32)
     );
 
+    define_with!(
+        /// Sets desired spread angle. See [`Sound::set_spread`] for more info.
+        fn with_spread(spread: f32)
+    );
+
+    define_with!(
+        /// Sets desired radius of the full-volume sphere. See [`Sound::set_min_distance`] for more info.
+        fn with_min_distance(min_distance: f32)
+    );
+
+    define_with!(
+        /// Sets desired air absorption multiplier. See [`Sound::set_air_absorption`] for more info.
+        fn with_air_absorption(air_absorption: f32)
+    );
+
     define_with!(
         /// Sets desired spatial blend factor. See [`Sound::set_spatial_blend`] for more info.
         fn with_spatial_blend_factor(spatial_blend: f32)
@@ -486,6 +651,22 @@ impl SoundBuilder {
         fn with_playback_time(playback_time: Duration)
     );
 
+    define_with!(
+        /// Sets whether the sound can be occluded or not. See [`Sound::set_occlusion_enabled`] for more info.
+        fn with_occlusion_enabled(occlusion_enabled: bool)
+    );
+
+    define_with!(
+        /// Sets desired occluder groups. See [`Sound::set_occluder_groups`] for more info.
+        fn with_occluder_groups(occluder_groups: InteractionGroups)
+    );
+
+    define_with!(
+        /// Sets whether the time scale of the scene affects the pitch of the sound. See
+        /// [`Sound::set_time_scale_affects_pitch`] for more info.
+        fn with_time_scale_affects_pitch(time_scale_affects_pitch: bool)
+    );
+
     /// Creates a new [`Sound`] node.
     #[must_use]
     pub fn build_sound(self) -> Sound {
@@ -501,9 +682,17 @@ impl SoundBuilder {
             radius: self.radius.into(),
             max_distance: self.max_distance.into(),
             rolloff_factor: self.rolloff_factor.into(),
+            spread: self.spread.clamp(0.0, 360.0).into(),
+            min_distance: self.min_distance.max(0.0).into(),
+            air_absorption: self.air_absorption.max(0.0).into(),
             playback_time: self.playback_time.into(),
             spatial_blend: self.spatial_blend.into(),
+            occlusion_enabled: self.occlusion_enabled.into(),
+            occluder_groups: self.occluder_groups.into(),
+            time_scale_affects_pitch: self.time_scale_affects_pitch.into(),
             native: Default::default(),
+            occlusion_factor: Cell::new(0.0),
+            occlusion_target: Cell::new(0.0),
         }
     }
 
@@ -523,6 +712,7 @@ impl SoundBuilder {
 mod test {
     use crate::scene::{
         base::{test::check_inheritable_properties_equality, BaseBuilder},
+        collider::InteractionGroups,
         node::NodeTrait,
         sound::{Sound, SoundBuilder},
     };
@@ -540,6 +730,12 @@ mod test {
             .with_looping(true)
             .with_play_once(true)
             .with_panning(0.1)
+            .with_occlusion_enabled(true)
+            .with_occluder_groups(InteractionGroups::new(1, 2))
+            .with_time_scale_affects_pitch(true)
+            .with_spread(90.0)
+            .with_min_distance(2.0)
+            .with_air_absorption(0.5)
             .build_node();
 
         let mut child = SoundBuilder::new(BaseBuilder::new()).build_sound();
@@ -0,0 +1,536 @@
+//! Save-game oriented snapshots of runtime state of a scene. See [`Scene::capture_state`] for
+//! more info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+        uuid::Uuid,
+        visitor::{PodVecView, Visit, VisitError, VisitResult, Visitor},
+    },
+    engine::SerializationContext,
+    scene::{node::Node, Scene},
+    script::Script,
+    utils::log::Log,
+};
+use fxhash::FxHashMap;
+use std::sync::Arc;
+
+/// Current version of the format of scene state. The version is increased when the format changes
+/// in a way that older versions of the engine cannot read.
+pub const SCENE_STATE_VERSION: u32 = 1;
+
+/// Persistent state of the base part of a node.
+#[derive(Default, Clone, Debug, Visit)]
+struct BaseState {
+    local_position: Vector3<f32>,
+    local_rotation: UnitQuaternion<f32>,
+    local_scale: Vector3<f32>,
+    visibility: bool,
+    enabled: bool,
+}
+
+impl BaseState {
+    fn from_node(node: &Node) -> Self {
+        let transform = node.local_transform();
+        Self {
+            local_position: **transform.position(),
+            local_rotation: **transform.rotation(),
+            local_scale: **transform.scale(),
+            visibility: node.visibility(),
+            enabled: node.is_enabled(),
+        }
+    }
+
+    fn apply(&self, node: &mut Node) {
+        node.local_transform_mut()
+            .set_position(self.local_position)
+            .set_rotation(self.local_rotation)
+            .set_scale(self.local_scale);
+        node.set_visibility(self.visibility);
+        node.set_enabled(self.enabled);
+    }
+}
+
+#[derive(Default, Debug)]
+struct NodeState {
+    instance_id: Uuid,
+    base: Option<BaseState>,
+    script_type: Uuid,
+    script_data: Vec<u8>,
+}
+
+impl Visit for NodeState {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        self.instance_id.visit("InstanceId", &mut region)?;
+        let _ = self.base.visit("Base", &mut region);
+        let _ = self.script_type.visit("ScriptType", &mut region);
+        let _ = PodVecView::from_pod_vec(&mut self.script_data).visit("ScriptData", &mut region);
+
+        Ok(())
+    }
+}
+
+/// Result of [`Scene::restore_state`].
+#[derive(Default, Debug)]
+pub struct RestoreStateReport {
+    /// Amount of nodes which state was restored.
+    pub restored: usize,
+    /// Instance ids of nodes that have saved state, but do not exist in the scene.
+    pub missing: Vec<Uuid>,
+}
+
+// Field names are compared regardless of case and underscores, so names of fields in the code
+// (`is_open`) match the names in serialized data (`IsOpen`).
+fn normalize_field_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '_')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn is_persistent_field(fields: &[&str], name: &str) -> bool {
+    let name = normalize_field_name(name);
+    fields.iter().any(|f| normalize_field_name(f) == name)
+}
+
+fn capture_script(script: &Script) -> Result<Vec<u8>, VisitError> {
+    let fields = script.persistent_fields();
+    let mut script = script.clone();
+    let mut visitor = Visitor::new();
+    script.visit("ScriptData", &mut visitor)?;
+    visitor.filter_region("ScriptData", |name| is_persistent_field(fields, name))?;
+    visitor.save_binary_to_vec()
+}
+
+fn restore_script(
+    script: &mut Script,
+    data: Vec<u8>,
+    serialization_context: &Arc<SerializationContext>,
+) -> VisitResult {
+    let saved = Visitor::load_from_memory(data)?;
+
+    // Apply saved fields over the current state of the script and read it back, so the rest of
+    // the fields are left intact. Saved fields that are not persistent anymore are ignored.
+    let fields = script.persistent_fields();
+    let mut visitor = Visitor::new();
+    script.visit("ScriptData", &mut visitor)?;
+    visitor.merge_region("ScriptData", &saved, "ScriptData", |name| {
+        is_persistent_field(fields, name)
+    })?;
+
+    let mut visitor = Visitor::load_from_memory(visitor.save_binary_to_vec()?)?;
+    visitor.environment = Some(serialization_context.clone());
+    // Fields could change their types since the state was saved, such fields keep their values.
+    visitor.set_lenient(true);
+    script.visit("ScriptData", &mut visitor)?;
+    for error in visitor.take_errors() {
+        Log::warn(format!("Unable to restore script field. Reason: {}", error));
+    }
+
+    script.clamp_to_property_ranges();
+
+    Ok(())
+}
+
+fn write_state(version: u32, nodes: &mut Vec<NodeState>) -> Result<Vec<u8>, VisitError> {
+    let mut visitor = Visitor::new();
+    let mut region = visitor.enter_region("SceneState")?;
+    let mut version = version;
+    version.visit("Version", &mut region)?;
+    nodes.visit("Nodes", &mut region)?;
+    drop(region);
+    visitor.save_binary_to_vec()
+}
+
+impl Scene {
+    /// Captures persistent state of the scene into a compact data blob, that could be applied to
+    /// a freshly loaded scene using [`Self::restore_state`]. Unlike saving the whole scene, the
+    /// blob contains only the data that changes during the game:
+    ///
+    /// - Local transform, visibility and enabled state of persistent nodes (see
+    /// [`crate::scene::base::Base::set_persistent`]).
+    /// - Persistent fields of scripts (see [`crate::script::ScriptTrait::persistent_fields`]).
+    ///
+    /// The state of every node is keyed by its instance id (see
+    /// [`crate::scene::base::Base::instance_id`]), which is kept when a scene is saved and
+    /// loaded. The blob is versioned, see [`SCENE_STATE_VERSION`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use fyrox::{engine::SerializationContext, scene::Scene};
+    /// use std::sync::Arc;
+    ///
+    /// fn save_and_load(
+    ///     scene: &Scene,
+    ///     fresh_level: &mut Scene,
+    ///     serialization_context: Arc<SerializationContext>,
+    /// ) {
+    ///     let save_game = scene.capture_state().unwrap();
+    ///     fresh_level
+    ///         .restore_state(save_game, serialization_context)
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub fn capture_state(&self) -> Result<Vec<u8>, VisitError> {
+        let mut nodes = Vec::new();
+
+        for node in self.graph.linear_iter() {
+            let base = if node.is_persistent() {
+                Some(BaseState::from_node(node))
+            } else {
+                None
+            };
+
+            let (script_type, script_data) = match node.script {
+                Some(ref script) if !script.persistent_fields().is_empty() => {
+                    (script.id(), capture_script(script)?)
+                }
+                _ => (Uuid::nil(), Vec::new()),
+            };
+
+            if base.is_some() || !script_type.is_nil() {
+                nodes.push(NodeState {
+                    instance_id: node.instance_id(),
+                    base,
+                    script_type,
+                    script_data,
+                });
+            }
+        }
+
+        write_state(SCENE_STATE_VERSION, &mut nodes)
+    }
+
+    /// Applies the state captured by [`Self::capture_state`] to the scene. Nodes are matched by
+    /// their instance ids, nodes that were not found are logged and listed in the report. The
+    /// state of a script is applied only if the node has a script of the same type, only fields
+    /// that are persistent in the current version of the script are restored. Unknown data is
+    /// skipped, so the state saved by a newer version of a game can still be loaded. The
+    /// serialization context is used to restore parts of scripts, that need constructors (for
+    /// example [`crate::script::component::ScriptComponent`]).
+    pub fn restore_state(
+        &mut self,
+        data: Vec<u8>,
+        serialization_context: Arc<SerializationContext>,
+    ) -> Result<RestoreStateReport, VisitError> {
+        let mut visitor = Visitor::load_from_memory(data)?;
+        let mut region = visitor.enter_region("SceneState")?;
+
+        let mut version = 0u32;
+        version.visit("Version", &mut region)?;
+        if version == 0 {
+            return Err(VisitError::User(
+                "Invalid version of scene state!".to_string(),
+            ));
+        } else if version > SCENE_STATE_VERSION {
+            Log::warn(format!(
+                "Scene state has version {}, but the latest supported version is {}. \
+                Unknown data will be ignored.",
+                version, SCENE_STATE_VERSION
+            ));
+        }
+
+        let mut nodes = Vec::<NodeState>::new();
+        nodes.visit("Nodes", &mut region)?;
+        drop(region);
+
+        let instances = self
+            .graph
+            .pair_iter()
+            .map(|(handle, node)| (node.instance_id(), handle))
+            .collect::<FxHashMap<Uuid, Handle<Node>>>();
+
+        let mut report = RestoreStateReport::default();
+        for state in nodes {
+            let node = match instances.get(&state.instance_id) {
+                Some(handle) => &mut self.graph[*handle],
+                None => {
+                    Log::warn(format!(
+                        "Unable to restore state of node {}, there is no such node in the scene!",
+                        state.instance_id
+                    ));
+                    report.missing.push(state.instance_id);
+                    continue;
+                }
+            };
+
+            if let Some(ref base) = state.base {
+                base.apply(node);
+            }
+
+            if !state.script_type.is_nil() {
+                match node.script {
+                    Some(ref mut script) if script.id() == state.script_type => {
+                        if let Err(e) =
+                            restore_script(script, state.script_data, &serialization_context)
+                        {
+                            Log::err(format!(
+                                "Unable to restore script of node {}. Reason: {:?}",
+                                state.instance_id, e
+                            ));
+                        }
+                    }
+                    _ => Log::warn(format!(
+                        "Unable to restore script of node {}, the node has no script of {} type!",
+                        state.instance_id, state.script_type
+                    )),
+                }
+            }
+
+            report.restored += 1;
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::Vector3,
+            inspect::{Inspect, PropertyInfo},
+            pool::Handle,
+            uuid::{uuid, Uuid},
+            visitor::prelude::*,
+        },
+        engine::SerializationContext,
+        scene::{
+            base::BaseBuilder,
+            node::{Node, TypeUuidProvider},
+            pivot::PivotBuilder,
+            state::{write_state, BaseState, NodeState, SCENE_STATE_VERSION},
+            Scene,
+        },
+        script::{Script, ScriptTrait},
+    };
+    use std::sync::Arc;
+
+    #[derive(Visit, Inspect, Debug, Clone, Default)]
+    struct Door {
+        is_open: bool,
+        open_speed: f32,
+    }
+
+    impl TypeUuidProvider for Door {
+        fn type_uuid() -> Uuid {
+            uuid!("5a0d3e1c-8b7f-4c62-9e14-2f6a8d9b0c31")
+        }
+    }
+
+    impl ScriptTrait for Door {
+        fn persistent_fields(&self) -> &'static [&'static str] {
+            &[Self::IS_OPEN]
+        }
+
+        fn id(&self) -> Uuid {
+            Self::type_uuid()
+        }
+
+        fn plugin_uuid(&self) -> Uuid {
+            Self::type_uuid()
+        }
+    }
+
+    // Previous version of the door script, it had a field that does not exist anymore.
+    #[derive(Visit, Inspect, Debug, Clone, Default)]
+    struct OldDoor {
+        is_open: bool,
+        lock_code: u32,
+    }
+
+    impl ScriptTrait for OldDoor {
+        fn persistent_fields(&self) -> &'static [&'static str] {
+            &[Self::IS_OPEN, Self::LOCK_CODE]
+        }
+
+        fn id(&self) -> Uuid {
+            Door::type_uuid()
+        }
+
+        fn plugin_uuid(&self) -> Uuid {
+            Door::type_uuid()
+        }
+    }
+
+    fn make_context() -> Arc<SerializationContext> {
+        let serialization_context = SerializationContext::new();
+        serialization_context
+            .script_constructors
+            .add::<Door, Door, _>("Door");
+        Arc::new(serialization_context)
+    }
+
+    struct Level {
+        scene: Scene,
+        player: Handle<Node>,
+        door: Handle<Node>,
+    }
+
+    fn make_level() -> Level {
+        let mut scene = Scene::new();
+        let player =
+            PivotBuilder::new(BaseBuilder::new().with_name("Player").with_persistent(true))
+                .build(&mut scene.graph);
+        let door = PivotBuilder::new(BaseBuilder::new().with_name("Door").with_script(
+            Script::new(Door {
+                is_open: false,
+                open_speed: 1.0,
+            }),
+        ))
+        .build(&mut scene.graph);
+        Level {
+            scene,
+            player,
+            door,
+        }
+    }
+
+    // Emulates loading of the level from a file.
+    fn reload(scene: &Scene, serialization_context: Arc<SerializationContext>) -> Scene {
+        let mut visitor = Visitor::new();
+        scene
+            .clone_for_save(&mut |_, _| true)
+            .save("Scene", &mut visitor)
+            .unwrap();
+        let mut visitor = Visitor::load_from_memory(visitor.save_binary_to_vec().unwrap()).unwrap();
+        visitor.environment = Some(serialization_context);
+        let mut loaded = Scene::default();
+        loaded.visit("Scene", &mut visitor).unwrap();
+        loaded
+    }
+
+    fn door(scene: &Scene) -> &Door {
+        let door = scene.graph.find_by_name_from_root("Door");
+        scene.graph[door]
+            .script
+            .as_ref()
+            .and_then(|s| s.cast::<Door>())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_scene_state_round_trip() {
+        let serialization_context = make_context();
+        let mut level = make_level();
+        let mut fresh = reload(&level.scene, serialization_context.clone());
+
+        let graph = &mut level.scene.graph;
+        graph[level.player]
+            .local_transform_mut()
+            .set_position(Vector3::new(1.0, 2.0, 3.0));
+        graph[level.player].set_visibility(false);
+        let script = graph[level.door].script.as_mut().unwrap();
+        let door_script = script.cast_mut::<Door>().unwrap();
+        door_script.is_open = true;
+        // Not persistent, must not be restored.
+        door_script.open_speed = 5.0;
+
+        let data = level.scene.capture_state().unwrap();
+        let report = fresh.restore_state(data, serialization_context).unwrap();
+        assert_eq!(report.restored, 2);
+        assert!(report.missing.is_empty());
+
+        let player = &fresh.graph[fresh.graph.find_by_name_from_root("Player")];
+        assert_eq!(
+            **player.local_transform().position(),
+            Vector3::new(1.0, 2.0, 3.0)
+        );
+        assert!(!player.visibility());
+        assert!(door(&fresh).is_open);
+        assert_eq!(door(&fresh).open_speed, 1.0);
+    }
+
+    #[test]
+    fn test_scene_state_missing_node() {
+        let serialization_context = make_context();
+        let mut level = make_level();
+        let mut fresh = reload(&level.scene, serialization_context.clone());
+
+        // A newer version of the level does not have the door.
+        let door_id = level.scene.graph[level.door].instance_id();
+        fresh
+            .graph
+            .remove_node(fresh.graph.find_by_name_from_root("Door"));
+
+        level.scene.graph[level.player].set_enabled(false);
+        let data = level.scene.capture_state().unwrap();
+        let report = fresh.restore_state(data, serialization_context).unwrap();
+        assert_eq!(report.restored, 1);
+        assert_eq!(report.missing, vec![door_id]);
+        assert!(!fresh.graph[fresh.graph.find_by_name_from_root("Player")].is_enabled());
+    }
+
+    #[test]
+    fn test_scene_state_unknown_script_fields_are_skipped() {
+        let serialization_context = make_context();
+        let mut level = make_level();
+        let mut fresh = reload(&level.scene, serialization_context.clone());
+
+        // The state was saved by a previous version of the game.
+        level.scene.graph[level.door].script = Some(Script::new(OldDoor {
+            is_open: true,
+            lock_code: 1234,
+        }));
+
+        let data = level.scene.capture_state().unwrap();
+        let report = fresh.restore_state(data, serialization_context).unwrap();
+        assert_eq!(report.restored, 2);
+        assert!(door(&fresh).is_open);
+    }
+
+    #[test]
+    fn test_scene_state_newer_version() {
+        let serialization_context = make_context();
+        let level = make_level();
+        let mut fresh = reload(&level.scene, serialization_context.clone());
+
+        let mut nodes = vec![NodeState {
+            instance_id: level.scene.graph[level.player].instance_id(),
+            base: Some(BaseState {
+                local_position: Vector3::new(4.0, 5.0, 6.0),
+                local_scale: Vector3::new(1.0, 1.0, 1.0),
+                visibility: true,
+                enabled: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }];
+        let data = write_state(SCENE_STATE_VERSION + 1, &mut nodes).unwrap();
+
+        let report = fresh.restore_state(data, serialization_context).unwrap();
+        assert_eq!(report.restored, 1);
+        assert_eq!(
+            **fresh.graph[fresh.graph.find_by_name_from_root("Player")]
+                .local_transform()
+                .position(),
+            Vector3::new(4.0, 5.0, 6.0)
+        );
+    }
+
+    #[test]
+    fn test_scene_state_invalid_data() {
+        let serialization_context = make_context();
+        let mut level = make_level();
+
+        // Not a scene state at all.
+        let mut visitor = Visitor::new();
+        let mut value = 1u32;
+        value.visit("Value", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+        assert!(level
+            .scene
+            .restore_state(data, serialization_context.clone())
+            .is_err());
+
+        let data = write_state(0, &mut Vec::new()).unwrap();
+        assert!(level
+            .scene
+            .restore_state(data, serialization_context)
+            .is_err());
+    }
+}
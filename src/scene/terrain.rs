@@ -5,19 +5,23 @@ use crate::{
     core::{
         algebra::{Matrix4, Point3, Vector2, Vector3},
         arrayvec::ArrayVec,
+        color::Color,
         inspect::{Inspect, PropertyInfo},
         math::{
             aabb::AxisAlignedBoundingBox, ray::Ray, ray_rect_intersection, Rect, TriangleDefinition,
         },
         parking_lot::Mutex,
         pool::Handle,
+        sstorage::ImmutableString,
         uuid::{uuid, Uuid},
         visitor::{prelude::*, PodVecView},
     },
     engine::resource_manager::ResourceManager,
     impl_directly_inheritable_entity_trait,
-    material::Material,
-    resource::texture::{Texture, TextureKind, TexturePixelKind, TextureWrapMode},
+    material::{shader::Shader, Material, PropertyValue},
+    resource::texture::{
+        Texture, TextureData, TextureKind, TexturePixelKind, TextureState, TextureWrapMode,
+    },
     scene::{
         base::{Base, BaseBuilder},
         graph::Graph,
@@ -29,19 +33,36 @@ use crate::{
         node::{Node, NodeTrait, TypeUuidProvider, UpdateContext},
         DirectlyInheritableEntity,
     },
+    utils::log::Log,
 };
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHasher};
 use std::{
     cell::Cell,
     cmp::Ordering,
+    hash::{Hash, Hasher},
     ops::{Deref, DerefMut},
+    path::Path,
     sync::Arc,
 };
 
+/// Maximum amount of layers that can be rendered in a single pass. Terrains with more layers are
+/// rendered using one pass per layer.
+pub const MAX_SINGLE_PASS_LAYERS: usize = 32;
+
+// Samplers of layer materials that are packed into texture arrays for single-pass rendering,
+// with colors for layers without a texture.
+const ARRAY_SAMPLERS: [(&str, Color); 6] = [
+    ("diffuseTexture", Color::WHITE),
+    ("normalTexture", Color::opaque(128, 128, 255)),
+    ("metallicTexture", Color::BLACK),
+    ("roughnessTexture", Color::WHITE),
+    ("emissionTexture", Color::BLACK),
+    ("aoTexture", Color::WHITE),
+];
+
 /// Layers is a set of textures for rendering + mask texture to exclude some pixels from
-/// rendering. Terrain can have as many layers as you want, but each layer slightly decreases
-/// performance, so keep amount of layers on reasonable level (1 - 5 should be enough for most
-/// cases).
+/// rendering. Terrain can have as many layers as you want, see [`Terrain`] docs to learn how
+/// the amount of layers affects performance.
 #[derive(Default, Debug, Clone, Visit, Inspect)]
 pub struct Layer {
     /// Material of the layer.
@@ -246,6 +267,16 @@ pub struct TerrainRayCastResult {
 /// Y coordinate. It can be used to create landscapes. It supports multiple layers, where each
 /// layer has its own material and mask.
 ///
+/// # Performance
+///
+/// Terrains with up to [`MAX_SINGLE_PASS_LAYERS`] layers, where every layer uses the standard
+/// terrain shader ([`Shader::standard_terrain`]) without a height texture, are rendered in a
+/// single pass per chunk: textures of the layers are packed into texture arrays automatically,
+/// so the amount of layers has little impact on the number of draw calls. Textures of the same
+/// kind (diffuse, normal, etc.) must have the same size, pixel format and mip count in every
+/// layer, layers without a texture are filled with a neutral color. If any of these conditions
+/// is not met, each layer of the terrain is rendered in a separate pass, which is much slower.
+///
 /// # Prefab inheritance notes
 ///
 /// There is very limited inheritance possible, only layers, decal layer index and cast shadows flag
@@ -280,6 +311,64 @@ pub struct Terrain {
     bounding_box_dirty: Cell<bool>,
     #[inspect(skip)]
     bounding_box: Cell<AxisAlignedBoundingBox>,
+    #[inspect(skip)]
+    #[visit(skip)]
+    single_pass: SinglePassData,
+}
+
+#[derive(Default, Debug)]
+struct ChunkMaskArray {
+    key: u64,
+    texture: Texture,
+}
+
+/// Texture arrays and materials that are used to render every chunk of a terrain in a single pass.
+#[derive(Default, Debug)]
+struct SinglePassData {
+    textures_key: u64,
+    is_compatible: bool,
+    // One array per each entry of `ARRAY_SAMPLERS`.
+    textures: Vec<Texture>,
+    masks: Vec<ChunkMaskArray>,
+    materials: Vec<Arc<Mutex<Material>>>,
+}
+
+impl Clone for SinglePassData {
+    fn clone(&self) -> Self {
+        // Every copy of a terrain must have its own arrays, they will be re-created on next update.
+        Self::default()
+    }
+}
+
+fn is_single_pass_compatible(material: &Material) -> bool {
+    material.shader().state().path() == Path::new("StandardTerrain")
+        && material
+            .property_ref(&ImmutableString::new("heightTexture"))
+            .and_then(|p| p.as_sampler())
+            .is_none()
+}
+
+// Textures of the same kind must match each other to be packed in an array.
+fn are_layer_textures_compatible(textures: &[Option<Texture>]) -> bool {
+    let mut reference = None;
+    for texture in textures.iter().flatten() {
+        if let TextureState::Ok(data) = &*texture.state() {
+            let kind = data.kind();
+            let parameters = (
+                match kind {
+                    TextureKind::Rectangle { width, height } => (width, height),
+                    _ => return false,
+                },
+                data.pixel_kind(),
+                data.mip_count(),
+            );
+            match reference {
+                Some(reference) if reference != parameters => return false,
+                _ => reference = Some(parameters),
+            }
+        }
+    }
+    true
 }
 
 impl_directly_inheritable_entity_trait!(Terrain;
@@ -582,6 +671,190 @@ impl Terrain {
         self.layers.get_mut().insert(index, layer)
     }
 
+    /// Returns a slice with materials that are used to render every chunk of the terrain in a
+    /// single pass. The slice is empty if the terrain must be rendered using one pass per layer,
+    /// see [`Terrain`] docs for more info.
+    pub fn single_pass_materials(&self) -> &[Arc<Mutex<Material>>] {
+        &self.single_pass.materials
+    }
+
+    fn update_single_pass_data(&mut self) {
+        let layers = &**self.layers;
+        let data = &mut self.single_pass;
+
+        if layers.len() < 2 || layers.len() > MAX_SINGLE_PASS_LAYERS {
+            data.textures_key = 0;
+            data.materials.clear();
+            return;
+        }
+
+        let mut slots = vec![Vec::with_capacity(layers.len()); ARRAY_SAMPLERS.len()];
+        let mut hasher = FxHasher::default();
+        for layer in layers {
+            let material = layer.material.lock();
+            if !is_single_pass_compatible(&material) {
+                data.textures_key = 0;
+                data.materials.clear();
+                return;
+            }
+            for ((name, _), slot) in ARRAY_SAMPLERS.iter().zip(slots.iter_mut()) {
+                let texture = material
+                    .property_ref(&ImmutableString::new(name))
+                    .and_then(|p| p.as_sampler());
+                if let Some(texture) = texture.as_ref() {
+                    match &*texture.state() {
+                        // Keep current state until every texture is loaded.
+                        TextureState::Pending { .. } => return,
+                        TextureState::Ok(texture_data) => {
+                            texture_data.data_hash().hash(&mut hasher)
+                        }
+                        TextureState::LoadError { .. } => (),
+                    }
+                    texture.key().hash(&mut hasher);
+                }
+                slot.push(texture);
+            }
+        }
+
+        let textures_key = hasher.finish();
+        if textures_key != data.textures_key {
+            data.textures_key = textures_key;
+            data.is_compatible = slots.iter().all(|s| are_layer_textures_compatible(s));
+            if data.is_compatible {
+                data.textures
+                    .resize_with(ARRAY_SAMPLERS.len(), Default::default);
+                for ((slot, (_, fallback)), texture) in slots
+                    .iter()
+                    .zip(ARRAY_SAMPLERS.iter())
+                    .zip(data.textures.iter())
+                {
+                    *texture.state() = TextureState::Ok(TextureData::new_array(slot, *fallback));
+                }
+            } else {
+                Log::warn(format!(
+                    "Layers of terrain {} cannot be rendered in a single pass, because textures \
+                    of the layers have different sizes, pixel formats or mip counts.",
+                    self.base.name()
+                ));
+            }
+        }
+
+        if !data.is_compatible {
+            data.materials.clear();
+            return;
+        }
+
+        data.masks.resize_with(self.chunks.len(), Default::default);
+        for (chunk_index, mask) in data.masks.iter_mut().enumerate() {
+            let chunk_masks = layers
+                .iter()
+                .map(|l| l.chunk_masks.get(chunk_index).cloned())
+                .collect::<Vec<_>>();
+
+            let mut hasher = FxHasher::default();
+            for chunk_mask in chunk_masks.iter().flatten() {
+                chunk_mask.key().hash(&mut hasher);
+                if let TextureState::Ok(mask_data) = &*chunk_mask.state() {
+                    mask_data.data_hash().hash(&mut hasher);
+                }
+            }
+            let key = hasher.finish();
+
+            if mask.key != key {
+                mask.key = key;
+                // Replace content of the array in-place, so it keeps its GPU counterpart.
+                *mask.texture.state() =
+                    TextureState::Ok(TextureData::new_array(&chunk_masks, Color::BLACK));
+            }
+        }
+
+        // Properties are cheap to copy, so they're updated every frame to reflect changes in
+        // layer materials.
+        let first = layers[0].material.lock();
+        let lightmap = first
+            .property_ref(&ImmutableString::new("lightmapTexture"))
+            .and_then(|p| p.as_sampler());
+        let layer_index = first
+            .property_ref(&ImmutableString::new("layerIndex"))
+            .and_then(|p| p.as_uint())
+            .unwrap_or_default();
+        drop(first);
+
+        let mut tex_coord_scales = Vec::with_capacity(layers.len());
+        let mut diffuse_colors = Vec::with_capacity(layers.len());
+        let mut emission_strengths = Vec::with_capacity(layers.len());
+        for layer in layers {
+            let material = layer.material.lock();
+            let property = |name: &str| material.property_ref(&ImmutableString::new(name));
+            tex_coord_scales.push(
+                property("texCoordScale")
+                    .and_then(|p| p.as_vector2())
+                    .unwrap_or_else(|| Vector2::new(1.0, 1.0)),
+            );
+            diffuse_colors.push(
+                property("diffuseColor")
+                    .and_then(|p| p.as_color())
+                    .unwrap_or(Color::WHITE)
+                    .as_frgba(),
+            );
+            emission_strengths.push(
+                property("emissionStrength")
+                    .and_then(|p| p.as_vector3())
+                    .unwrap_or_else(|| Vector3::new(2.0, 2.0, 2.0)),
+            );
+        }
+
+        data.materials.resize_with(self.chunks.len(), || {
+            Arc::new(Mutex::new(Material::from_shader(
+                Shader::standard_terrain_array(),
+                None,
+            )))
+        });
+        for (material, mask) in data.materials.iter().zip(data.masks.iter()) {
+            let mut material = material.lock();
+            let mut set = |name: &str, value: PropertyValue| {
+                Log::verify(material.set_property(&ImmutableString::new(name), value));
+            };
+            for ((name, _), texture) in ARRAY_SAMPLERS.iter().zip(data.textures.iter()) {
+                set(
+                    *name,
+                    PropertyValue::Sampler {
+                        value: Some(texture.clone()),
+                        fallback: Default::default(),
+                    },
+                );
+            }
+            set(
+                "maskTexture",
+                PropertyValue::Sampler {
+                    value: Some(mask.texture.clone()),
+                    fallback: Default::default(),
+                },
+            );
+            set(
+                "lightmapTexture",
+                PropertyValue::Sampler {
+                    value: lightmap.clone(),
+                    fallback: Default::default(),
+                },
+            );
+            set(
+                "texCoordScale",
+                PropertyValue::Vector2Array(tex_coord_scales.clone()),
+            );
+            set(
+                "diffuseColor",
+                PropertyValue::Vector4Array(diffuse_colors.clone()),
+            );
+            set(
+                "emissionStrength",
+                PropertyValue::Vector3Array(emission_strengths.clone()),
+            );
+            set("layerCount", PropertyValue::UInt(layers.len() as u32));
+            set("layerIndex", PropertyValue::UInt(layer_index));
+        }
+    }
+
     /// Creates new layer with given parameters, but does **not** add it to the terrain.
     pub fn create_layer(
         &self,
@@ -677,6 +950,8 @@ impl NodeTrait for Terrain {
             chunk.update();
         }
 
+        self.update_single_pass_data();
+
         self.base.update_lifetime(context.dt)
     }
 }
@@ -930,6 +1205,7 @@ impl TerrainBuilder {
             chunks,
             bounding_box_dirty: Cell::new(true),
             bounding_box: Default::default(),
+            single_pass: Default::default(),
             mask_resolution: self.mask_resolution,
             height_map_resolution: self.height_map_resolution,
             width_chunks: self.width_chunks as u32,
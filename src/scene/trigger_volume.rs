@@ -0,0 +1,869 @@
+//! Trigger volume is a box, a sphere or a convex polyhedron that tracks rigid bodies inside it and
+//! notifies scripts when they enter or exit the volume.
+//!
+//! For more info see [`TriggerVolume`]
+
+use crate::{
+    core::variable::{InheritError, TemplateVariable},
+    core::{
+        algebra::{Point3, UnitQuaternion, Vector3},
+        inspect::{Inspect, PropertyInfo},
+        math::{aabb::AxisAlignedBoundingBox, ray::Ray, Matrix4Ext},
+        pool::Handle,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    engine::resource_manager::ResourceManager,
+    impl_directly_inheritable_entity_trait,
+    scene::{
+        base::{Base, BaseBuilder},
+        collider::{Collider, GeometrySource, InteractionGroups},
+        graph::{Graph, NodePool},
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            Mesh,
+        },
+        node::{Node, NodeTrait, TypeUuidProvider, UpdateContext},
+        rigidbody::RigidBody,
+        DirectlyInheritableEntity,
+    },
+    utils::log::Log,
+};
+use fxhash::FxHashMap;
+use rapier3d::{geometry::SharedShape, parry::query::Ray as NativeRay};
+use std::{
+    fmt::{Debug, Formatter},
+    ops::{Deref, DerefMut},
+};
+use strum_macros::{AsRefStr, EnumString, EnumVariantNames};
+
+/// Shape of a trigger volume.
+#[derive(
+    Copy, Clone, PartialEq, Eq, Hash, Debug, Visit, Inspect, AsRefStr, EnumString, EnumVariantNames,
+)]
+#[repr(u32)]
+pub enum TriggerVolumeShape {
+    /// A box defined by half-extents of the volume.
+    Box = 0,
+
+    /// A sphere defined by radius of the volume.
+    Sphere = 1,
+
+    /// A convex hull of vertices of a mesh node (see [`TriggerVolume::set_geometry_source`]).
+    Convex = 2,
+}
+
+impl Default for TriggerVolumeShape {
+    fn default() -> Self {
+        Self::Box
+    }
+}
+
+/// An event of a trigger volume, it holds a handle of a rigid body that entered or exited the
+/// volume.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum TriggerEvent {
+    /// A rigid body has entered the volume.
+    Enter(Handle<Node>),
+
+    /// A rigid body has exited the volume.
+    Exit(Handle<Node>),
+}
+
+#[derive(Copy, Clone, Debug)]
+struct TrackedBody {
+    position: Vector3<f32>,
+    inside: bool,
+}
+
+#[derive(Clone)]
+struct ConvexHull {
+    shape: SharedShape,
+    bounds: AxisAlignedBoundingBox,
+}
+
+impl Debug for ConvexHull {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConvexHull")
+            .field("bounds", &self.bounds)
+            .finish()
+    }
+}
+
+/// Trigger volume is a space that tracks which rigid bodies are inside of it and generates
+/// enter and exit events for them. It replaces the usual combination of a sensor collider and
+/// manual intersection polling for things like level exits, traps, checkpoints and so on.
+///
+/// # Shape
+///
+/// The volume is either a box defined by half-extents, a sphere defined by radius or a convex
+/// hull of vertices of a mesh node (see [`TriggerVolumeShape`]). The shapes are defined in local
+/// coordinates of the node, but, just like collider shapes, they ignore scaling of the node.
+/// The convex hull is built when the volume is updated for the first time or when its geometry
+/// source is changed, so the mesh must not move relative to the volume.
+///
+/// # Tracking
+///
+/// Every update (right after a physics step) the volume checks the position of every enabled rigid
+/// body of the graph. A body is inside the volume if its origin is inside the shape. A body that
+/// moved through the volume in a single step (for example it was teleported or it is just very
+/// fast) gets both [`TriggerEvent::Enter`] and [`TriggerEvent::Exit`] events in this order, so
+/// nothing is lost. Events of a step are ordered by handles of the bodies, exits of bodies that
+/// were deleted or disabled go last. Rigid bodies that are ancestors of the volume are ignored,
+/// so a volume could be attached to a moving body.
+///
+/// Bodies could be filtered by collision groups (see [`TriggerVolume::set_collision_groups`]),
+/// a body passes the filter if at least one of its colliders interacts with the groups of the
+/// volume. A body without colliders is treated as a member of every group.
+///
+/// # Events
+///
+/// Events of the last step are available via [`TriggerVolume::events`], they're also delivered
+/// to the script of the volume (see [`crate::script::ScriptTrait::on_trigger_enter`] and
+/// [`crate::script::ScriptTrait::on_trigger_exit`]), and optionally to the scripts of the bodies
+/// (see [`TriggerVolume::set_notify_entering`]).
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::algebra::Vector3,
+///     scene::{base::BaseBuilder, graph::Graph, node::Node, trigger_volume::TriggerVolumeBuilder},
+///     core::pool::Handle,
+/// };
+///
+/// fn create_exit_zone(graph: &mut Graph) -> Handle<Node> {
+///     TriggerVolumeBuilder::new(BaseBuilder::new().with_name("LevelExit"))
+///         .with_extents(Vector3::new(2.0, 3.0, 0.5))
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Inspect)]
+pub struct TriggerVolume {
+    base: Base,
+
+    #[inspect(getter = "Deref::deref")]
+    shape: TemplateVariable<TriggerVolumeShape>,
+
+    #[inspect(getter = "Deref::deref")]
+    extents: TemplateVariable<Vector3<f32>>,
+
+    #[inspect(min_value = 0.0, step = 0.1, getter = "Deref::deref")]
+    radius: TemplateVariable<f32>,
+
+    #[inspect(getter = "Deref::deref")]
+    geometry_source: TemplateVariable<GeometrySource>,
+
+    #[inspect(getter = "Deref::deref")]
+    collision_groups: TemplateVariable<InteractionGroups>,
+
+    #[inspect(getter = "Deref::deref")]
+    notify_entering: TemplateVariable<bool>,
+
+    #[visit(skip)]
+    #[inspect(skip)]
+    tracked: FxHashMap<Handle<Node>, TrackedBody>,
+
+    #[visit(skip)]
+    #[inspect(skip)]
+    events: Vec<TriggerEvent>,
+
+    #[visit(skip)]
+    #[inspect(skip)]
+    hull: Option<ConvexHull>,
+
+    // A geometry source the hull was built from, the hull is rebuilt when the source changes.
+    #[visit(skip)]
+    #[inspect(skip)]
+    hull_source: Option<Handle<Node>>,
+}
+
+impl_directly_inheritable_entity_trait!(TriggerVolume;
+    shape,
+    extents,
+    radius,
+    geometry_source,
+    collision_groups,
+    notify_entering
+);
+
+impl Clone for TriggerVolume {
+    fn clone(&self) -> Self {
+        // Tracked bodies and events belong to the original volume, a copy starts from scratch.
+        Self {
+            base: self.base.clone(),
+            shape: self.shape.clone(),
+            extents: self.extents.clone(),
+            radius: self.radius.clone(),
+            geometry_source: self.geometry_source.clone(),
+            collision_groups: self.collision_groups.clone(),
+            notify_entering: self.notify_entering.clone(),
+            tracked: Default::default(),
+            events: Default::default(),
+            hull: None,
+            hull_source: None,
+        }
+    }
+}
+
+impl Default for TriggerVolume {
+    fn default() -> Self {
+        TriggerVolumeBuilder::new(BaseBuilder::new()).build_trigger_volume()
+    }
+}
+
+impl Deref for TriggerVolume {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for TriggerVolume {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for TriggerVolume {
+    fn type_uuid() -> Uuid {
+        uuid!("b3d7e4a1-58c2-4f0e-9a36-7c1d2e9f4b85")
+    }
+}
+
+fn interacts(a: InteractionGroups, b: InteractionGroups) -> bool {
+    (a.memberships & b.filter) != 0 && (b.memberships & a.filter) != 0
+}
+
+fn build_convex_hull(
+    nodes: &NodePool,
+    source: Handle<Node>,
+    to_local: &dyn Fn(Vector3<f32>) -> Vector3<f32>,
+) -> Option<ConvexHull> {
+    let mesh = nodes.try_borrow(source)?.cast::<Mesh>()?;
+
+    let mut points = Vec::new();
+    for surface in mesh.surfaces() {
+        let data = surface.data();
+        let data = data.lock();
+        for vertex in data.vertex_buffer.iter() {
+            if let Ok(position) = vertex.read_3_f32(VertexAttributeUsage::Position) {
+                let world = mesh
+                    .global_transform()
+                    .transform_point(&Point3::from(position))
+                    .coords;
+                points.push(to_local(world));
+            }
+        }
+    }
+
+    let shape =
+        SharedShape::convex_hull(&points.iter().map(|p| Point3::from(*p)).collect::<Vec<_>>())?;
+
+    Some(ConvexHull {
+        shape,
+        bounds: AxisAlignedBoundingBox::from_points(&points),
+    })
+}
+
+impl TriggerVolume {
+    /// Sets new shape of the volume.
+    pub fn set_shape(&mut self, shape: TriggerVolumeShape) {
+        self.shape.set(shape);
+    }
+
+    /// Returns current shape of the volume.
+    pub fn shape(&self) -> TriggerVolumeShape {
+        *self.shape
+    }
+
+    /// Sets new half-extents of the box, they're used only by [`TriggerVolumeShape::Box`].
+    pub fn set_extents(&mut self, extents: Vector3<f32>) {
+        self.extents.set(extents.map(|v| v.abs()));
+    }
+
+    /// Returns current half-extents of the box.
+    pub fn extents(&self) -> Vector3<f32> {
+        *self.extents
+    }
+
+    /// Sets new radius of the sphere, it is used only by [`TriggerVolumeShape::Sphere`].
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius.set(radius.max(0.0));
+    }
+
+    /// Returns current radius of the sphere.
+    pub fn radius(&self) -> f32 {
+        *self.radius
+    }
+
+    /// Sets a mesh node, whose vertices define the convex hull of the volume. It is used only by
+    /// [`TriggerVolumeShape::Convex`].
+    pub fn set_geometry_source(&mut self, source: GeometrySource) {
+        self.geometry_source.set(source);
+    }
+
+    /// Returns current geometry source of the convex hull.
+    pub fn geometry_source(&self) -> GeometrySource {
+        *self.geometry_source
+    }
+
+    /// Sets collision groups of the volume, only bodies with colliders that interact with the
+    /// groups are tracked. See [`InteractionGroups`] docs for more info.
+    pub fn set_collision_groups(&mut self, groups: InteractionGroups) {
+        self.collision_groups.set(groups);
+    }
+
+    /// Returns current collision groups of the volume.
+    pub fn collision_groups(&self) -> InteractionGroups {
+        *self.collision_groups
+    }
+
+    /// Defines whether events should be delivered to the scripts of entering and exiting bodies
+    /// as well. Events are always delivered to the script of the volume itself.
+    pub fn set_notify_entering(&mut self, notify: bool) {
+        self.notify_entering.set(notify);
+    }
+
+    /// Returns `true` if events are delivered to the scripts of entering and exiting bodies.
+    pub fn is_notify_entering(&self) -> bool {
+        *self.notify_entering
+    }
+
+    /// Returns events of the last update in the order they happened.
+    pub fn events(&self) -> &[TriggerEvent] {
+        &self.events
+    }
+
+    /// Returns `true` if the given rigid body was inside the volume at the last update.
+    pub fn is_inside(&self, body: Handle<Node>) -> bool {
+        self.tracked.get(&body).map_or(false, |body| body.inside)
+    }
+
+    /// Returns an iterator over rigid bodies that were inside the volume at the last update.
+    pub fn bodies_inside(&self) -> impl Iterator<Item = Handle<Node>> + '_ {
+        self.tracked
+            .iter()
+            .filter_map(|(handle, body)| if body.inside { Some(*handle) } else { None })
+    }
+
+    /// Returns edges of the convex hull in local coordinates of the volume. The hull is available
+    /// only after the first update of the volume with [`TriggerVolumeShape::Convex`] shape.
+    pub fn convex_hull_edges(&self) -> Vec<(Vector3<f32>, Vector3<f32>)> {
+        self.hull
+            .as_ref()
+            .and_then(|hull| hull.shape.as_convex_polyhedron())
+            .map(|polyhedron| {
+                let points = polyhedron.points();
+                polyhedron
+                    .edges()
+                    .iter()
+                    .map(|edge| {
+                        (
+                            points[edge.vertices.x as usize].coords,
+                            points[edge.vertices.y as usize].coords,
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn contains(&self, point: Vector3<f32>) -> bool {
+        match *self.shape {
+            TriggerVolumeShape::Box => {
+                let extents = *self.extents;
+                point.x.abs() <= extents.x
+                    && point.y.abs() <= extents.y
+                    && point.z.abs() <= extents.z
+            }
+            TriggerVolumeShape::Sphere => point.norm() <= *self.radius,
+            TriggerVolumeShape::Convex => self.hull.as_ref().map_or(false, |hull| {
+                hull.shape.contains_local_point(&Point3::from(point))
+            }),
+        }
+    }
+
+    // Checks whether a segment between two points (both are outside) crosses the volume.
+    fn is_crossed(&self, begin: Vector3<f32>, end: Vector3<f32>) -> bool {
+        if begin == end {
+            return false;
+        }
+
+        let ray = Ray::from_two_points(begin, end);
+        match *self.shape {
+            TriggerVolumeShape::Box => ray
+                .box_intersection(&-*self.extents, &*self.extents)
+                .is_some(),
+            TriggerVolumeShape::Sphere => ray
+                .sphere_intersection(&Vector3::default(), *self.radius)
+                .map_or(false, |result| result.min <= 1.0 && result.max >= 0.0),
+            TriggerVolumeShape::Convex => self.hull.as_ref().map_or(false, |hull| {
+                hull.shape
+                    .intersects_local_ray(&NativeRay::new(Point3::from(begin), end - begin), 1.0)
+            }),
+        }
+    }
+
+    fn update_hull(&mut self, nodes: &NodePool, to_local: &dyn Fn(Vector3<f32>) -> Vector3<f32>) {
+        let source = self.geometry_source.0;
+        if self.hull_source != Some(source) {
+            self.hull_source = Some(source);
+            self.hull = build_convex_hull(nodes, source, to_local);
+            if self.hull.is_none() && source.is_some() {
+                Log::warn(format!(
+                    "Unable to build convex hull for trigger volume {}, the geometry source \
+                    must be a mesh with at least four non-coplanar vertices.",
+                    self.name()
+                ));
+            }
+        }
+    }
+
+    fn track_bodies(&mut self, context: &UpdateContext) {
+        let global_transform = self.global_transform();
+        let origin = global_transform.position();
+        let inv_rotation = UnitQuaternion::from_matrix(&global_transform.basis()).inverse();
+        let to_local = move |point: Vector3<f32>| inv_rotation * (point - origin);
+
+        if *self.shape == TriggerVolumeShape::Convex {
+            self.update_hull(context.nodes, &to_local);
+        }
+
+        // Ignore the bodies the volume is attached to.
+        let mut ancestors = Vec::new();
+        let mut parent = self.parent();
+        while let Some(node) = context.nodes.try_borrow(parent) {
+            ancestors.push(parent);
+            parent = node.parent();
+        }
+
+        let groups = *self.collision_groups;
+        let mut tracked = FxHashMap::default();
+        for (handle, node) in context.nodes.pair_iter() {
+            let body = match node.cast::<RigidBody>() {
+                Some(body) if body.is_globally_enabled() && !ancestors.contains(&handle) => body,
+                _ => continue,
+            };
+
+            let mut colliders = body
+                .children()
+                .iter()
+                .filter_map(|c| {
+                    context
+                        .nodes
+                        .try_borrow(*c)
+                        .and_then(|n| n.cast::<Collider>())
+                })
+                .peekable();
+            let passes = if colliders.peek().is_some() {
+                colliders.any(|collider| interacts(collider.collision_groups(), groups))
+            } else {
+                interacts(InteractionGroups::default(), groups)
+            };
+            if !passes {
+                continue;
+            }
+
+            let position = context
+                .physics
+                .rigid_body_position(body)
+                .unwrap_or_else(|| body.global_position());
+            let inside = self.contains(to_local(position));
+
+            match self.tracked.get(&handle) {
+                Some(previous) => {
+                    if previous.inside && !inside {
+                        self.events.push(TriggerEvent::Exit(handle));
+                    } else if !previous.inside && inside {
+                        self.events.push(TriggerEvent::Enter(handle));
+                    } else if !previous.inside
+                        && self.is_crossed(to_local(previous.position), to_local(position))
+                    {
+                        self.events.push(TriggerEvent::Enter(handle));
+                        self.events.push(TriggerEvent::Exit(handle));
+                    }
+                }
+                None => {
+                    if inside {
+                        self.events.push(TriggerEvent::Enter(handle));
+                    }
+                }
+            }
+
+            tracked.insert(handle, TrackedBody { position, inside });
+        }
+
+        // Bodies that were deleted, disabled or filtered out leave the volume.
+        let mut gone = self
+            .tracked
+            .iter()
+            .filter(|(handle, body)| body.inside && !tracked.contains_key(handle))
+            .map(|(handle, _)| *handle)
+            .collect::<Vec<_>>();
+        gone.sort_unstable_by_key(|handle| handle.index());
+        self.events.extend(gone.into_iter().map(TriggerEvent::Exit));
+
+        self.tracked = tracked;
+    }
+}
+
+impl NodeTrait for TriggerVolume {
+    crate::impl_query_component!();
+
+    /// Returns current **local-space** bounding box.
+    #[inline]
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        match *self.shape {
+            TriggerVolumeShape::Box => {
+                AxisAlignedBoundingBox::from_min_max(-*self.extents, *self.extents)
+            }
+            TriggerVolumeShape::Sphere => AxisAlignedBoundingBox::from_radius(*self.radius),
+            TriggerVolumeShape::Convex => self
+                .hull
+                .as_ref()
+                .map_or_else(AxisAlignedBoundingBox::unit, |hull| hull.bounds),
+        }
+    }
+
+    /// Returns current **world-space** bounding box.
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    // Prefab inheritance resolving.
+    fn inherit(&mut self, parent: &Node) -> Result<(), InheritError> {
+        self.base.inherit_properties(parent)?;
+        if let Some(parent) = parent.cast::<Self>() {
+            self.try_inherit_self_properties(parent)?;
+        }
+        Ok(())
+    }
+
+    fn reset_inheritable_properties(&mut self) {
+        self.base.reset_inheritable_properties();
+        self.reset_self_inheritable_properties();
+    }
+
+    fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        self.base.restore_resources(resource_manager);
+    }
+
+    fn remap_handles(&mut self, old_new_mapping: &FxHashMap<Handle<Node>, Handle<Node>>) {
+        self.base.remap_handles(old_new_mapping);
+
+        if let Some(entry) = old_new_mapping.get(&self.geometry_source.0) {
+            self.geometry_source.get_mut_silent().0 = *entry;
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) -> bool {
+        // Events that weren't handled during the previous step are dropped.
+        self.events.clear();
+        self.track_bodies(context);
+
+        self.base.update_lifetime(context.dt)
+    }
+}
+
+/// Allows you to create a trigger volume in a declarative manner.
+pub struct TriggerVolumeBuilder {
+    base_builder: BaseBuilder,
+    shape: TriggerVolumeShape,
+    extents: Vector3<f32>,
+    radius: f32,
+    geometry_source: GeometrySource,
+    collision_groups: InteractionGroups,
+    notify_entering: bool,
+}
+
+impl TriggerVolumeBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            shape: TriggerVolumeShape::Box,
+            extents: Vector3::new(1.0, 1.0, 1.0),
+            radius: 1.0,
+            geometry_source: Default::default(),
+            collision_groups: Default::default(),
+            notify_entering: false,
+        }
+    }
+
+    /// Sets desired shape of the volume.
+    pub fn with_shape(mut self, shape: TriggerVolumeShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets desired half-extents of the box.
+    pub fn with_extents(mut self, extents: Vector3<f32>) -> Self {
+        self.extents = extents;
+        self
+    }
+
+    /// Sets desired radius of the sphere.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets desired mesh node for the convex hull.
+    pub fn with_geometry_source(mut self, source: GeometrySource) -> Self {
+        self.geometry_source = source;
+        self
+    }
+
+    /// Sets desired collision groups of the volume.
+    pub fn with_collision_groups(mut self, groups: InteractionGroups) -> Self {
+        self.collision_groups = groups;
+        self
+    }
+
+    /// Sets whether events should be delivered to the scripts of entering bodies or not.
+    pub fn with_notify_entering(mut self, notify: bool) -> Self {
+        self.notify_entering = notify;
+        self
+    }
+
+    /// Creates new trigger volume.
+    pub fn build_trigger_volume(self) -> TriggerVolume {
+        TriggerVolume {
+            base: self.base_builder.build_base(),
+            shape: self.shape.into(),
+            extents: self.extents.map(|v| v.abs()).into(),
+            radius: self.radius.max(0.0).into(),
+            geometry_source: self.geometry_source.into(),
+            collision_groups: self.collision_groups.into(),
+            notify_entering: self.notify_entering.into(),
+            tracked: Default::default(),
+            events: Default::default(),
+            hull: None,
+            hull_source: None,
+        }
+    }
+
+    /// Creates new trigger volume node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_trigger_volume())
+    }
+
+    /// Creates new instance of trigger volume node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            pool::Handle,
+        },
+        scene::{
+            base::{test::check_inheritable_properties_equality, BaseBuilder},
+            collider::{ColliderBuilder, ColliderShape, GeometrySource, InteractionGroups},
+            graph::Graph,
+            node::{Node, NodeTrait},
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+            trigger_volume::{
+                TriggerEvent, TriggerVolume, TriggerVolumeBuilder, TriggerVolumeShape,
+            },
+        },
+    };
+
+    fn make_body(
+        graph: &mut Graph,
+        position: Vector3<f32>,
+        groups: InteractionGroups,
+    ) -> Handle<Node> {
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::ball(0.1))
+            .with_collision_groups(groups)
+            .build(graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                )
+                .with_children(&[collider]),
+        )
+        .with_body_type(RigidBodyType::KinematicPositionBased)
+        .build(graph)
+    }
+
+    fn teleport(graph: &mut Graph, body: Handle<Node>, position: Vector3<f32>) {
+        graph[body].local_transform_mut().set_position(position);
+    }
+
+    fn step(graph: &mut Graph, trigger: Handle<Node>) -> Vec<TriggerEvent> {
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0);
+        graph
+            .try_get_of_type::<TriggerVolume>(trigger)
+            .unwrap()
+            .events()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_trigger_volume_inheritance() {
+        let parent = TriggerVolumeBuilder::new(BaseBuilder::new())
+            .with_shape(TriggerVolumeShape::Sphere)
+            .with_extents(Vector3::new(1.0, 2.0, 3.0))
+            .with_radius(4.0)
+            .with_geometry_source(GeometrySource(Handle::new(1, 1)))
+            .with_collision_groups(InteractionGroups::new(1, 2))
+            .with_notify_entering(true)
+            .build_node();
+
+        let mut child = TriggerVolumeBuilder::new(BaseBuilder::new()).build_trigger_volume();
+
+        child.inherit(&parent).unwrap();
+
+        let parent = parent.cast::<TriggerVolume>().unwrap();
+
+        check_inheritable_properties_equality(&child.base, &parent.base);
+        check_inheritable_properties_equality(&child, parent);
+    }
+
+    #[test]
+    fn test_trigger_volume_enter_exit() {
+        let mut graph = Graph::new();
+
+        let trigger = TriggerVolumeBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let body = make_body(&mut graph, Vector3::new(-5.0, 0.0, 0.0), Default::default());
+
+        assert!(step(&mut graph, trigger).is_empty());
+
+        teleport(&mut graph, body, Vector3::new(0.5, 0.0, 0.0));
+        assert_eq!(step(&mut graph, trigger), vec![TriggerEvent::Enter(body)]);
+        assert!(graph
+            .try_get_of_type::<TriggerVolume>(trigger)
+            .unwrap()
+            .is_inside(body));
+
+        // Staying inside generates nothing.
+        teleport(&mut graph, body, Vector3::new(-0.5, 0.0, 0.0));
+        assert!(step(&mut graph, trigger).is_empty());
+
+        teleport(&mut graph, body, Vector3::new(0.0, 5.0, 0.0));
+        assert_eq!(step(&mut graph, trigger), vec![TriggerEvent::Exit(body)]);
+        assert!(!graph
+            .try_get_of_type::<TriggerVolume>(trigger)
+            .unwrap()
+            .is_inside(body));
+    }
+
+    #[test]
+    fn test_trigger_volume_teleport_across() {
+        let mut graph = Graph::new();
+
+        let trigger = TriggerVolumeBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let sphere = TriggerVolumeBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, 10.0))
+                    .build(),
+            ),
+        )
+        .with_shape(TriggerVolumeShape::Sphere)
+        .build(&mut graph);
+        let body = make_body(&mut graph, Vector3::new(-5.0, 0.0, 0.0), Default::default());
+
+        assert!(step(&mut graph, trigger).is_empty());
+
+        // The body jumps over the box in a single step, it must enter and then exit the volume.
+        teleport(&mut graph, body, Vector3::new(5.0, 0.0, 0.0));
+        assert_eq!(
+            step(&mut graph, trigger),
+            vec![TriggerEvent::Enter(body), TriggerEvent::Exit(body)]
+        );
+
+        // Same for the sphere.
+        teleport(&mut graph, body, Vector3::new(0.0, 0.5, 15.0));
+        step(&mut graph, trigger);
+        teleport(&mut graph, body, Vector3::new(0.0, 0.5, 5.0));
+        assert_eq!(
+            step(&mut graph, sphere),
+            vec![TriggerEvent::Enter(body), TriggerEvent::Exit(body)]
+        );
+
+        // A jump that misses the volume generates nothing.
+        teleport(&mut graph, body, Vector3::new(5.0, 5.0, 0.0));
+        step(&mut graph, trigger);
+        teleport(&mut graph, body, Vector3::new(-5.0, 5.0, 0.0));
+        assert!(step(&mut graph, trigger).is_empty());
+    }
+
+    #[test]
+    fn test_trigger_volume_event_order() {
+        let mut graph = Graph::new();
+
+        let trigger = TriggerVolumeBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let a = make_body(&mut graph, Vector3::new(0.0, 0.0, 0.0), Default::default());
+        let b = make_body(&mut graph, Vector3::new(-5.0, 0.0, 0.0), Default::default());
+        let c = make_body(&mut graph, Vector3::new(0.5, 0.0, 0.0), Default::default());
+
+        assert_eq!(
+            step(&mut graph, trigger),
+            vec![TriggerEvent::Enter(a), TriggerEvent::Enter(c)]
+        );
+
+        // `a` leaves, `b` crosses the volume and `c` is deleted - exits of deleted bodies go last.
+        teleport(&mut graph, a, Vector3::new(0.0, 0.0, 5.0));
+        teleport(&mut graph, b, Vector3::new(5.0, 0.0, 0.0));
+        graph.remove_node(c);
+        assert_eq!(
+            step(&mut graph, trigger),
+            vec![
+                TriggerEvent::Exit(a),
+                TriggerEvent::Enter(b),
+                TriggerEvent::Exit(b),
+                TriggerEvent::Exit(c)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trigger_volume_collision_groups() {
+        let mut graph = Graph::new();
+
+        let trigger = TriggerVolumeBuilder::new(BaseBuilder::new())
+            .with_collision_groups(InteractionGroups::new(0b01, 0b01))
+            .build(&mut graph);
+        let player = make_body(
+            &mut graph,
+            Vector3::new(0.0, 0.0, 0.0),
+            InteractionGroups::new(0b01, 0b11),
+        );
+        make_body(
+            &mut graph,
+            Vector3::new(0.0, 0.0, 0.0),
+            InteractionGroups::new(0b10, 0b11),
+        );
+
+        assert_eq!(step(&mut graph, trigger), vec![TriggerEvent::Enter(player)]);
+    }
+
+    #[test]
+    fn test_trigger_volume_ignores_ancestors() {
+        let mut graph = Graph::new();
+
+        let trigger = TriggerVolumeBuilder::new(BaseBuilder::new()).build(&mut graph);
+        let body = make_body(&mut graph, Vector3::default(), Default::default());
+        graph.link_nodes(trigger, body);
+
+        assert!(step(&mut graph, trigger).is_empty());
+    }
+}
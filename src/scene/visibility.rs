@@ -5,7 +5,7 @@
 use crate::scene::graph::NodePool;
 use crate::{
     core::{algebra::Vector3, math::frustum::Frustum, pool::Handle},
-    scene::node::Node,
+    scene::{base::LayerMask, hlod::Hlod, node::Node},
 };
 use fxhash::FxHashMap;
 
@@ -20,17 +20,28 @@ use fxhash::FxHashMap;
 /// LODs have priority over other visibility options, if a level is not active, then its every object will be hidden,
 /// not matter if the actual visibility state is `visible`.
 ///
+/// HLOD nodes are checked right after LODs, a far HLOD hides its original meshes and a near one hides its proxy.
+/// The state of every HLOD is kept between updates to apply hysteresis, see [`Hlod`] for more info.
+///
+/// Nodes whose render mask does not intersect with the culling mask of an observer are always hidden, see
+/// [`LayerMask`] for more info.
+///
 /// # Performance
 ///
 /// The cache is based on hash map, so it is very fast and has O(1) complexity for fetching.
 #[derive(Default, Debug, Clone)]
 pub struct VisibilityCache {
     map: FxHashMap<Handle<Node>, bool>,
+    // Whether the proxy of an HLOD was shown on previous update or not.
+    hlod_states: FxHashMap<Handle<Node>, bool>,
 }
 
 impl From<FxHashMap<Handle<Node>, bool>> for VisibilityCache {
     fn from(map: FxHashMap<Handle<Node>, bool>) -> Self {
-        Self { map }
+        Self {
+            map,
+            hlod_states: Default::default(),
+        }
     }
 }
 
@@ -47,7 +58,8 @@ impl VisibilityCache {
     }
 
     /// Updates visibility cache - checks visibility for each node in given graph, also performs
-    /// frustum culling if frustum set is specified.
+    /// frustum culling if frustum set is specified. Nodes that are not on any layer of the given
+    /// culling mask are marked as invisible.
     pub fn update(
         &mut self,
         nodes: &NodePool,
@@ -55,6 +67,7 @@ impl VisibilityCache {
         z_near: f32,
         z_far: f32,
         frustums: Option<&[&Frustum]>,
+        culling_mask: LayerMask,
     ) {
         self.map.clear();
 
@@ -69,7 +82,8 @@ impl VisibilityCache {
                             let z_range = z_far - z_near;
                             let normalized_distance = (distance - z_near) / z_range;
                             let visible = normalized_distance >= level.begin()
-                                && normalized_distance <= level.end();
+                                && normalized_distance <= level.end()
+                                && object_ref.render_mask().intersects(culling_mask);
                             self.map.insert(*object, visible);
                         }
                     }
@@ -77,12 +91,39 @@ impl VisibilityCache {
             }
         }
 
+        // Then check HLODs, they're switching between the originals and the proxy.
+        for (handle, node) in nodes.pair_iter() {
+            if let Some(hlod) = node.cast::<Hlod>() {
+                // Invisible HLOD hides its proxy (as a descendant) and must not hide the originals.
+                if !hlod.global_visibility() {
+                    self.hlod_states.remove(&handle);
+                    continue;
+                }
+
+                let was_proxy_shown = self.hlod_states.get(&handle).cloned().unwrap_or(false);
+                let proxy_shown =
+                    hlod.should_show_proxy(hlod.distance_to(observer_position), was_proxy_shown);
+                self.hlod_states.insert(handle, proxy_shown);
+
+                if proxy_shown {
+                    for &original in hlod.originals() {
+                        self.map.insert(original, false);
+                    }
+                } else {
+                    self.map.insert(hlod.proxy(), false);
+                }
+            }
+        }
+        self.hlod_states
+            .retain(|handle, _| nodes.is_valid_handle(*handle));
+
         // Fill rest of data from global visibility flag of nodes and check frustums (if any).
         for (handle, node) in nodes.pair_iter() {
             // We need to fill only unfilled entries, none of visibility flags of a node can
             // make it visible again if lod group hid it.
             self.map.entry(handle).or_insert_with(|| {
-                let mut visibility = node.global_visibility();
+                let mut visibility =
+                    node.global_visibility() && node.render_mask().intersects(culling_mask);
                 if visibility && node.frustum_culling() {
                     // If a node globally visible, check it with each frustum (if any).
                     if let Some(frustums) = frustums {
@@ -110,3 +151,111 @@ impl VisibilityCache {
         self.map.get(&node).cloned().unwrap_or(false)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            math::aabb::AxisAlignedBoundingBox,
+            pool::Handle,
+        },
+        scene::{
+            base::{BaseBuilder, LayerMask},
+            camera::{Camera, CameraBuilder},
+            graph::Graph,
+            hlod::HlodBuilder,
+            node::Node,
+            pivot::PivotBuilder,
+        },
+    };
+
+    fn make_node(graph: &mut Graph, render_mask: LayerMask) -> Handle<Node> {
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_frustum_culling(false)
+                .with_render_mask(render_mask),
+        )
+        .build(graph)
+    }
+
+    fn make_camera(graph: &mut Graph, culling_mask: LayerMask) -> Handle<Node> {
+        CameraBuilder::new(BaseBuilder::new())
+            .with_culling_mask(culling_mask)
+            .build(graph)
+    }
+
+    #[test]
+    fn test_cameras_with_disjoint_masks_see_disjoint_sets() {
+        let mut graph = Graph::new();
+
+        let first_layer = LayerMask::from_layer(0);
+        let second_layer = LayerMask::from_layer(1);
+
+        let nodes = [
+            make_node(&mut graph, first_layer),
+            make_node(&mut graph, first_layer),
+            make_node(&mut graph, second_layer),
+            make_node(&mut graph, LayerMask::NONE),
+        ];
+        let first_camera = make_camera(&mut graph, first_layer);
+        let second_camera = make_camera(&mut graph, second_layer);
+
+        graph.update(Vector2::new(100.0, 100.0), 0.0);
+
+        let visible_set = |camera: Handle<Node>| {
+            let camera = graph[camera].cast::<Camera>().unwrap();
+            nodes
+                .iter()
+                .filter(|node| camera.visibility_cache.is_visible(**node))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        let first_set = visible_set(first_camera);
+        let second_set = visible_set(second_camera);
+
+        assert_eq!(first_set, vec![nodes[0], nodes[1]]);
+        assert_eq!(second_set, vec![nodes[2]]);
+        assert!(first_set.iter().all(|node| !second_set.contains(node)));
+    }
+
+    #[test]
+    fn test_hlod_switches_with_hysteresis() {
+        let mut graph = Graph::new();
+
+        let original = make_node(&mut graph, LayerMask::ALL);
+        let proxy = make_node(&mut graph, LayerMask::ALL);
+        let hlod = HlodBuilder::new(BaseBuilder::new().with_children(&[proxy]))
+            .with_switch_distance(100.0)
+            .with_hysteresis(10.0)
+            .with_proxy(proxy)
+            .with_originals(vec![original])
+            .with_bounds(AxisAlignedBoundingBox::from_radius(1.0))
+            .build(&mut graph);
+        let camera = make_camera(&mut graph, LayerMask::ALL);
+
+        let mut check = |distance: f32| {
+            graph[hlod]
+                .local_transform_mut()
+                .set_position(Vector3::new(0.0, 0.0, distance));
+            graph.update(Vector2::new(100.0, 100.0), 0.0);
+            let camera = graph[camera].cast::<Camera>().unwrap();
+            let proxy_visible = camera.visibility_cache.is_visible(proxy);
+            let original_visible = camera.visibility_cache.is_visible(original);
+            assert_ne!(proxy_visible, original_visible);
+            proxy_visible
+        };
+
+        // Near - originals are shown.
+        assert!(!check(50.0));
+        // Inside hysteresis band, state is kept.
+        assert!(!check(103.0));
+        // Far - proxy is shown.
+        assert!(check(106.0));
+        // Back inside the band - proxy is still shown.
+        assert!(check(97.0));
+        // Near again.
+        assert!(!check(94.0));
+    }
+}
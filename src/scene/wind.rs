@@ -0,0 +1,247 @@
+//! Wind is a scene-wide field of air velocity, that sways vegetation and pushes particles.
+//!
+//! For more info see [`Wind`]
+
+use crate::core::{
+    algebra::{Vector3, Vector4},
+    inspect::{Inspect, PropertyInfo},
+    visitor::prelude::*,
+};
+use std::f32::consts::PI;
+
+/// Relative amount of small-scale turbulence in the speed of the wind.
+pub const TURBULENCE_AMOUNT: f32 = 0.25;
+
+/// Displacement (in meters) of a fully flexible vegetation vertex per 1 m/s of wind speed.
+pub const SWAY_SCALE: f32 = 0.05;
+
+/// Wind of a scene. It is defined by a main direction and strength (speed in m/s), the speed
+/// varies in time and space by two kinds of noise:
+///
+/// - Gusts - large waves of stronger and weaker wind, that travel along the wind direction. Their
+/// amplitude is defined by gustiness (`0.0` - no gusts, `1.0` - the speed varies from zero to
+/// doubled strength), frequency defines how often a gust passes a point, and size defines the
+/// distance between gusts.
+/// - Turbulence - fast small oscillations of the speed, which makes leaves and grass flutter.
+///
+/// The wind is stored in a [`crate::scene::graph::Graph`] and serialized with the scene, it could
+/// be changed at any time (for example, from a script via `scene.graph.wind`).
+///
+/// # Rendering
+///
+/// Parameters of the wind are available in every shader via built-in uniforms `fyrox_wind`
+/// (normalized direction and strength), `fyrox_windNoise` (gustiness, gust frequency, gust size
+/// and turbulence frequency) and `fyrox_windTime`. Shaders should use `S_WindVelocity` and
+/// `S_WindSway` functions, which exactly match [`Wind::velocity_at`] and [`Wind::sway_at`]. The
+/// standard shader sways vertices when `vegetation` property of a material is set to `true`, red
+/// channel of vertex color (see [`crate::scene::mesh::buffer::VertexAttributeUsage::Color`])
+/// defines flexibility of a vertex, meshes without vertex colors are not swayed at all.
+///
+/// # Particle systems
+///
+/// Particle systems are affected by the wind according to their wind influence (see
+/// [`crate::scene::particle_system::ParticleSystem::set_wind_influence`]), the same velocity
+/// field is used, so particles drift in sync with vegetation.
+#[derive(Clone, Debug, PartialEq, Visit, Inspect)]
+pub struct Wind {
+    direction: Vector3<f32>,
+    #[inspect(min_value = 0.0, step = 0.1)]
+    strength: f32,
+    #[inspect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    gustiness: f32,
+    #[inspect(min_value = 0.0, step = 0.05)]
+    gust_frequency: f32,
+    #[inspect(min_value = 0.1, step = 0.5)]
+    gust_size: f32,
+    #[inspect(min_value = 0.0, step = 0.1)]
+    turbulence_frequency: f32,
+    #[visit(skip)]
+    #[inspect(skip)]
+    time: f32,
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Self {
+            direction: Vector3::x(),
+            strength: 0.0,
+            gustiness: 0.3,
+            gust_frequency: 0.2,
+            gust_size: 20.0,
+            turbulence_frequency: 1.5,
+            time: 0.0,
+        }
+    }
+}
+
+impl Wind {
+    /// Sets new direction of the wind, the direction is normalized. Zero vectors are ignored.
+    pub fn set_direction(&mut self, direction: Vector3<f32>) {
+        if let Some(direction) = direction.try_normalize(f32::EPSILON) {
+            self.direction = direction;
+        }
+    }
+
+    /// Returns normalized direction of the wind.
+    pub fn direction(&self) -> Vector3<f32> {
+        self.direction
+    }
+
+    /// Sets average speed of the wind in m/s.
+    pub fn set_strength(&mut self, strength: f32) {
+        self.strength = strength.max(0.0);
+    }
+
+    /// Returns average speed of the wind in m/s.
+    pub fn strength(&self) -> f32 {
+        self.strength
+    }
+
+    /// Sets amplitude of gusts relative to the strength, the value is clamped to `[0; 1]` range.
+    pub fn set_gustiness(&mut self, gustiness: f32) {
+        self.gustiness = gustiness.clamp(0.0, 1.0);
+    }
+
+    /// Returns amplitude of gusts relative to the strength.
+    pub fn gustiness(&self) -> f32 {
+        self.gustiness
+    }
+
+    /// Sets how many gusts pass a point per second.
+    pub fn set_gust_frequency(&mut self, frequency: f32) {
+        self.gust_frequency = frequency.max(0.0);
+    }
+
+    /// Returns how many gusts pass a point per second.
+    pub fn gust_frequency(&self) -> f32 {
+        self.gust_frequency
+    }
+
+    /// Sets distance between gusts in meters.
+    pub fn set_gust_size(&mut self, size: f32) {
+        self.gust_size = size.max(0.1);
+    }
+
+    /// Returns distance between gusts in meters.
+    pub fn gust_size(&self) -> f32 {
+        self.gust_size
+    }
+
+    /// Sets frequency (in Hz) of small-scale turbulence.
+    pub fn set_turbulence_frequency(&mut self, frequency: f32) {
+        self.turbulence_frequency = frequency.max(0.0);
+    }
+
+    /// Returns frequency (in Hz) of small-scale turbulence.
+    pub fn turbulence_frequency(&self) -> f32 {
+        self.turbulence_frequency
+    }
+
+    /// Returns time of the wind, it is advanced by scaled delta time of the scene.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub(crate) fn update(&mut self, dt: f32) {
+        self.time += dt;
+    }
+
+    /// Returns velocity of the wind (in m/s) at the given world position at current time.
+    pub fn velocity_at(&self, position: Vector3<f32>) -> Vector3<f32> {
+        let gust_phase = 2.0
+            * PI
+            * (position.dot(&self.direction) / self.gust_size - self.time * self.gust_frequency);
+        // Integer harmonics keep gusts periodic with the gust frequency.
+        let noise = 0.6 * gust_phase.sin()
+            + 0.3 * (2.0 * gust_phase + 1.7).sin()
+            + 0.1 * (5.0 * gust_phase + 4.1).sin();
+        let turbulence = (2.0 * PI * self.time * self.turbulence_frequency
+            + position.dot(&Vector3::new(1.3, 0.7, 1.1)))
+        .sin();
+        self.direction
+            .scale(self.strength * (1.0 + self.gustiness * noise + TURBULENCE_AMOUNT * turbulence))
+    }
+
+    /// Returns world-space offset of a vegetation vertex at the given world position. Weight
+    /// defines flexibility of the vertex - `0.0` is rigid (a root of a grass blade), `1.0` is
+    /// fully flexible (a tip of a blade).
+    pub fn sway_at(&self, position: Vector3<f32>, weight: f32) -> Vector3<f32> {
+        self.velocity_at(position).scale(weight * SWAY_SCALE)
+    }
+
+    /// Returns values of `fyrox_wind` and `fyrox_windNoise` built-in uniforms.
+    pub(crate) fn uniforms(&self) -> (Vector4<f32>, Vector4<f32>) {
+        (
+            Vector4::new(
+                self.direction.x,
+                self.direction.y,
+                self.direction.z,
+                self.strength,
+            ),
+            Vector4::new(
+                self.gustiness,
+                self.gust_frequency,
+                self.gust_size,
+                self.turbulence_frequency,
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{core::algebra::Vector3, scene::wind::Wind};
+
+    #[test]
+    fn test_calm_wind() {
+        let mut wind = Wind::default();
+        wind.update(1.3);
+        assert_eq!(
+            wind.velocity_at(Vector3::new(1.0, 2.0, 3.0)),
+            Vector3::default()
+        );
+    }
+
+    #[test]
+    fn test_wind_is_bounded_and_aligned() {
+        let mut wind = Wind::default();
+        wind.set_direction(Vector3::new(0.0, 0.0, 2.0));
+        wind.set_strength(4.0);
+        wind.set_gustiness(1.0);
+        assert_eq!(wind.direction(), Vector3::z());
+
+        for i in 0..100 {
+            wind.update(0.1);
+            let velocity = wind.velocity_at(Vector3::new(i as f32, 0.0, i as f32 * 0.5));
+            assert_eq!(velocity.x, 0.0);
+            assert_eq!(velocity.y, 0.0);
+            // Gusts and turbulence could not make the wind faster than 2.25x of its strength.
+            assert!(velocity.z.abs() <= 4.0 * 2.25 + f32::EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_gusts_are_periodic() {
+        let mut wind = Wind::default();
+        wind.set_strength(1.0);
+        wind.set_gustiness(1.0);
+        wind.set_turbulence_frequency(0.0);
+
+        let position = Vector3::new(3.0, 1.0, 2.0);
+        let before = wind.velocity_at(position);
+        wind.update(1.0 / wind.gust_frequency());
+        let after = wind.velocity_at(position);
+        assert!((before - after).norm() < 1.0e-3);
+    }
+
+    #[test]
+    fn test_rigid_vertex_does_not_sway() {
+        let mut wind = Wind::default();
+        wind.set_strength(10.0);
+        assert_eq!(
+            wind.sway_at(Vector3::new(1.0, 0.0, 0.0), 0.0),
+            Vector3::default()
+        );
+        assert!(wind.sway_at(Vector3::new(1.0, 0.0, 0.0), 1.0).norm() > 0.0);
+    }
+}
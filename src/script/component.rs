@@ -0,0 +1,356 @@
+//! Script components are polymorphic parts of scripts. See [`ScriptComponent`] docs for more info.
+
+use crate::{
+    core::{
+        inspect::{Inspect, PropertyInfo},
+        uuid::Uuid,
+        visitor::{Visit, VisitError, VisitResult, Visitor},
+    },
+    engine::{resource_manager::ResourceManager, SerializationContext},
+    gui::inspector::{FieldKind, PropertyChanged},
+};
+use std::{any::Any, fmt::Debug};
+
+pub trait BaseScriptComponent: Visit + Inspect + Send + Debug + 'static {
+    fn clone_box(&self) -> Box<dyn ScriptComponentTrait>;
+
+    /// Returns self as shared reference to [`Any`].
+    fn as_any(&self) -> &dyn Any;
+
+    /// Returns self as mutable reference to [`Any`].
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// Returns name of the actual type of the component.
+    fn type_name(&self) -> &'static str;
+}
+
+impl<T> BaseScriptComponent for T
+where
+    T: Clone + ScriptComponentTrait,
+{
+    fn clone_box(&self) -> Box<dyn ScriptComponentTrait> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+}
+
+/// A part of a script, that could be chosen from a set of registered types. Every type must be
+/// registered in [`crate::script::constructor::ScriptComponentConstructorContainer`] of the
+/// serialization context, otherwise it cannot be loaded or chosen in the editor.
+pub trait ScriptComponentTrait: BaseScriptComponent {
+    /// Mutates the state of the component according to the [`PropertyChanged`] info. It works
+    /// the same as [`crate::script::ScriptTrait::on_property_changed`].
+    fn on_property_changed(&mut self, #[allow(unused_variables)] args: &PropertyChanged) -> bool {
+        false
+    }
+
+    /// Allows you to restore resources after deserialization. See
+    /// [`crate::script::ScriptTrait::restore_resources`] for more info.
+    fn restore_resources(&mut self, #[allow(unused_variables)] resource_manager: ResourceManager) {}
+
+    /// Component instance type UUID. The value will be used for serialization, to write type
+    /// identifier to a data source so the engine can restore the component from data source.
+    /// All you need to do in the method is to return `Self::type_uuid`.
+    fn id(&self) -> Uuid;
+}
+
+/// An optional polymorphic field of a script. It allows scripts to be composed of behaviors, for
+/// example a character script could hold one of a few movement strategies, each with its own
+/// parameters. The editor shows a list of registered component types for such fields, and the
+/// fields of the chosen component.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox::{
+///     core::{inspect::{Inspect, PropertyInfo}, uuid::{uuid, Uuid}, visitor::prelude::*},
+///     gui::inspector::PropertyChanged,
+///     scene::node::TypeUuidProvider,
+///     script::component::{ScriptComponent, ScriptComponentTrait},
+/// };
+///
+/// #[derive(Inspect, Visit, Debug, Clone, Default)]
+/// struct Patrol {
+///     speed: f32,
+/// }
+///
+/// impl TypeUuidProvider for Patrol {
+///     fn type_uuid() -> Uuid {
+///         uuid!("b1f6a2c4-3d7e-4f80-9a15-6c2e8d4b7f01")
+///     }
+/// }
+///
+/// impl ScriptComponentTrait for Patrol {
+///     fn id(&self) -> Uuid {
+///         Self::type_uuid()
+///     }
+/// }
+///
+/// #[derive(Inspect, Visit, Debug, Clone, Default)]
+/// struct Guard {
+///     strategy: ScriptComponent,
+/// }
+///
+/// impl Guard {
+///     // Call this from `ScriptTrait::on_property_changed` of the script.
+///     fn on_property_changed(&mut self, args: &PropertyChanged) -> bool {
+///         match args.name.as_ref() {
+///             Self::STRATEGY => self.strategy.on_property_changed(args),
+///             _ => false,
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct ScriptComponent(Option<Box<dyn ScriptComponentTrait>>);
+
+impl Clone for ScriptComponent {
+    fn clone(&self) -> Self {
+        Self(self.0.as_ref().map(|c| c.clone_box()))
+    }
+}
+
+impl ScriptComponent {
+    /// Creates new component field with the given value.
+    pub fn new<T: ScriptComponentTrait>(component: T) -> Self {
+        Self(Some(Box::new(component)))
+    }
+
+    /// Returns type UUID of the current component, if any.
+    pub fn id(&self) -> Option<Uuid> {
+        self.0.as_ref().map(|c| c.id())
+    }
+
+    /// Returns `true` if there is no component.
+    pub fn is_none(&self) -> bool {
+        self.0.is_none()
+    }
+
+    /// Returns a reference to the current component, if any.
+    pub fn get(&self) -> Option<&dyn ScriptComponentTrait> {
+        self.0.as_deref()
+    }
+
+    /// Returns a reference to the current component, if any.
+    pub fn get_mut(&mut self) -> Option<&mut dyn ScriptComponentTrait> {
+        // Explicit match, because the lifetime of trait object cannot be shortened in `Option`.
+        match self.0 {
+            Some(ref mut component) => Some(&mut **component),
+            None => None,
+        }
+    }
+
+    /// Performs downcasting to a particular type.
+    pub fn cast<T: ScriptComponentTrait>(&self) -> Option<&T> {
+        self.0.as_ref().and_then(|c| c.as_any().downcast_ref::<T>())
+    }
+
+    /// Performs downcasting to a particular type.
+    pub fn cast_mut<T: ScriptComponentTrait>(&mut self) -> Option<&mut T> {
+        self.0
+            .as_mut()
+            .and_then(|c| c.as_any_mut().downcast_mut::<T>())
+    }
+
+    /// Applies a change made in the editor. A new component is set as is when the type was
+    /// changed, changes of properties are passed to [`ScriptComponentTrait::on_property_changed`]
+    /// of the current component.
+    pub fn on_property_changed(&mut self, args: &PropertyChanged) -> bool {
+        match args.value {
+            FieldKind::Object(ref value) => value.try_override(self),
+            FieldKind::Inspectable(ref property_changed) => self
+                .0
+                .as_mut()
+                .map_or(false, |c| c.on_property_changed(property_changed)),
+            FieldKind::Collection(_) => false,
+        }
+    }
+
+    /// Restores resources of the current component, if any.
+    pub fn restore_resources(&mut self, resource_manager: ResourceManager) {
+        if let Some(component) = self.0.as_mut() {
+            component.restore_resources(resource_manager);
+        }
+    }
+}
+
+impl Inspect for ScriptComponent {
+    fn properties(&self) -> Vec<PropertyInfo<'_>> {
+        self.0
+            .as_ref()
+            .map(|c| c.properties())
+            .unwrap_or_default()
+    }
+}
+
+impl Visit for ScriptComponent {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        let mut type_uuid = self.id().unwrap_or_default();
+        type_uuid.visit("TypeUuid", &mut region)?;
+
+        if region.is_reading() {
+            self.0 = if type_uuid.is_nil() {
+                None
+            } else {
+                let serialization_context = region
+                    .environment
+                    .as_ref()
+                    .and_then(|e| e.downcast_ref::<SerializationContext>())
+                    .ok_or_else(|| {
+                        VisitError::User(
+                            "Visitor environment must contain serialization context!".to_string(),
+                        )
+                    })?;
+
+                serialization_context
+                    .component_constructors
+                    .try_create(&type_uuid)
+                    .ok_or_else(|| {
+                        VisitError::User(format!(
+                            "There is no corresponding script component constructor for {} type!",
+                            type_uuid
+                        ))
+                    })?
+                    .0
+            };
+        }
+
+        if let Some(component) = self.0.as_mut() {
+            component.visit("ComponentData", &mut region)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            inspect::{Inspect, PropertyInfo},
+            uuid::{uuid, Uuid},
+            visitor::prelude::*,
+        },
+        engine::SerializationContext,
+        gui::inspector::{FieldKind, PropertyChanged},
+        handle_object_property_changed,
+        scene::node::TypeUuidProvider,
+        script::component::{ScriptComponent, ScriptComponentTrait},
+    };
+    use std::{any::TypeId, sync::Arc};
+
+    #[derive(Inspect, Visit, Debug, Clone, Default)]
+    struct Patrol {
+        speed: f32,
+    }
+
+    impl TypeUuidProvider for Patrol {
+        fn type_uuid() -> Uuid {
+            uuid!("7c0e2f4a-9b3d-4e61-8a5f-1d2c3b4a5e69")
+        }
+    }
+
+    impl ScriptComponentTrait for Patrol {
+        fn on_property_changed(&mut self, args: &PropertyChanged) -> bool {
+            handle_object_property_changed!(self, args, Self::SPEED => speed)
+        }
+
+        fn id(&self) -> Uuid {
+            Self::type_uuid()
+        }
+    }
+
+    #[derive(Inspect, Visit, Debug, Clone, Default)]
+    struct Chase {
+        target_distance: f32,
+    }
+
+    impl TypeUuidProvider for Chase {
+        fn type_uuid() -> Uuid {
+            uuid!("e3a1b5c7-2d4f-4a86-b9c0-5f7e9d1a3b24")
+        }
+    }
+
+    impl ScriptComponentTrait for Chase {
+        fn id(&self) -> Uuid {
+            Self::type_uuid()
+        }
+    }
+
+    fn make_context() -> Arc<SerializationContext> {
+        let serialization_context = SerializationContext::new();
+        serialization_context
+            .component_constructors
+            .add::<Patrol, Patrol, _>("Patrol");
+        serialization_context
+            .component_constructors
+            .add::<Chase, Chase, _>("Chase");
+        Arc::new(serialization_context)
+    }
+
+    fn round_trip(
+        component: &mut ScriptComponent,
+        serialization_context: Arc<SerializationContext>,
+    ) -> ScriptComponent {
+        let mut visitor = Visitor::new();
+        component.visit("Component", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        visitor.environment = Some(serialization_context);
+        let mut loaded = ScriptComponent::new(Chase::default());
+        loaded.visit("Component", &mut visitor).unwrap();
+        loaded
+    }
+
+    #[test]
+    fn test_component_serialization() {
+        let serialization_context = make_context();
+
+        let mut component = ScriptComponent::new(Patrol { speed: 3.0 });
+        let loaded = round_trip(&mut component, serialization_context.clone());
+        assert_eq!(loaded.cast::<Patrol>().unwrap().speed, 3.0);
+
+        let loaded = round_trip(&mut ScriptComponent::default(), serialization_context);
+        assert!(loaded.is_none());
+    }
+
+    #[test]
+    fn test_component_property_changed() {
+        let mut component = ScriptComponent::default();
+
+        // Type switch replaces the whole component.
+        assert!(component.on_property_changed(&PropertyChanged {
+            name: "Strategy".to_string(),
+            owner_type_id: TypeId::of::<()>(),
+            value: FieldKind::object(ScriptComponent::new(Patrol { speed: 1.0 })),
+        }));
+        assert_eq!(component.id(), Some(Patrol::type_uuid()));
+
+        // Property changes are passed to the component.
+        assert!(component.on_property_changed(&PropertyChanged {
+            name: "Strategy".to_string(),
+            owner_type_id: TypeId::of::<()>(),
+            value: FieldKind::Inspectable(Box::new(PropertyChanged {
+                name: Patrol::SPEED.to_string(),
+                owner_type_id: TypeId::of::<Patrol>(),
+                value: FieldKind::object(5.0f32),
+            })),
+        }));
+        assert_eq!(component.cast::<Patrol>().unwrap().speed, 5.0);
+        assert_eq!(component.properties().len(), 1);
+    }
+}
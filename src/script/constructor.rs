@@ -1,4 +1,4 @@
-//! A special container that is able to create nodes by their type UUID.
+//! Special containers that are able to create scripts and script components by their type UUID.
 
 use crate::{
     core::{
@@ -6,7 +6,10 @@ use crate::{
         uuid::Uuid,
     },
     scene::node::TypeUuidProvider,
-    script::{Script, ScriptTrait},
+    script::{
+        component::{ScriptComponent, ScriptComponentTrait},
+        Script, ScriptTrait,
+    },
 };
 use std::collections::BTreeMap;
 
@@ -81,3 +84,77 @@ impl ScriptConstructorContainer {
         self.map.lock()
     }
 }
+
+pub struct ScriptComponentConstructor {
+    /// Parent plugin UUID.
+    pub plugin_uuid: Uuid,
+
+    /// A simple type alias for boxed component constructor.
+    pub constructor: Box<dyn FnMut() -> ScriptComponent + Send>,
+
+    /// Component name.
+    pub name: String,
+}
+
+/// A special container that is able to create script components (see [`ScriptComponent`]) by
+/// their type UUID. Every registered component is an allowed choice for polymorphic fields of
+/// scripts in the editor.
+#[derive(Default)]
+pub struct ScriptComponentConstructorContainer {
+    // BTreeMap allows to have sorted list of constructors.
+    map: Mutex<BTreeMap<Uuid, ScriptComponentConstructor>>,
+}
+
+impl ScriptComponentConstructorContainer {
+    /// Creates empty component constructor container.
+    pub fn new() -> Self {
+        ScriptComponentConstructorContainer::default()
+    }
+
+    /// Adds new type constructor for a given type.
+    ///
+    /// # Panic
+    ///
+    /// The method will panic if there is already a constructor for given type uuid.
+    pub fn add<P, T, N>(&self, name: N)
+    where
+        P: TypeUuidProvider,
+        T: TypeUuidProvider + ScriptComponentTrait + Default,
+        N: AsRef<str>,
+    {
+        let old = self.map.lock().insert(
+            T::type_uuid(),
+            ScriptComponentConstructor {
+                plugin_uuid: P::type_uuid(),
+                constructor: Box::new(|| ScriptComponent::new(T::default())),
+                name: name.as_ref().to_string(),
+            },
+        );
+
+        assert!(old.is_none());
+    }
+
+    /// Adds custom type constructor.
+    pub fn add_custom(&self, type_uuid: Uuid, constructor: ScriptComponentConstructor) {
+        self.map.lock().insert(type_uuid, constructor);
+    }
+
+    /// Unregisters type constructor.
+    pub fn remove(&self, type_uuid: Uuid) {
+        self.map.lock().remove(&type_uuid);
+    }
+
+    /// Makes an attempt to create a component using provided type UUID. It may fail if there is
+    /// no component constructor for specified type UUID.
+    pub fn try_create(&self, type_uuid: &Uuid) -> Option<ScriptComponent> {
+        self.map
+            .lock()
+            .get_mut(type_uuid)
+            .map(|c| (c.constructor)())
+    }
+
+    /// Returns inner map of component constructors.
+    pub fn map(&self) -> MutexGuard<BTreeMap<Uuid, ScriptComponentConstructor>> {
+        self.map.lock()
+    }
+}
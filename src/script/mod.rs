@@ -1,5 +1,10 @@
-use crate::engine::resource_manager::ResourceManager;
+use crate::engine::{
+    frame_pacing::FramePacing,
+    resource_manager::ResourceManager,
+    task::{TaskHandle, TaskResult},
+};
 use crate::{
+    animation::machine::StateAction,
     core::{
         inspect::{Inspect, PropertyInfo},
         pool::Handle,
@@ -7,20 +12,33 @@ use crate::{
         visitor::{Visit, VisitResult, Visitor},
     },
     event::Event,
-    gui::inspector::PropertyChanged,
+    gui::inspector::{FieldKind, PropertyChanged},
     plugin::Plugin,
-    scene::{node::Node, Scene},
+    scene::{debug::DebugDrawBuffer, node::Node, Scene},
+    utils::log::Log,
 };
 use fxhash::FxHashMap;
 use std::{
+    any::Any,
     fmt::Debug,
     ops::{Deref, DerefMut},
 };
 
+pub mod component;
 pub mod constructor;
+pub mod start;
 
 pub trait BaseScript: Visit + Inspect + Send + Debug + 'static {
     fn clone_box(&self) -> Box<dyn ScriptTrait>;
+
+    /// Returns self as shared reference to [`Any`].
+    fn as_any(&self) -> &dyn Any;
+
+    /// Returns self as mutable reference to [`Any`].
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// Returns name of the actual type of the script.
+    fn type_name(&self) -> &'static str;
 }
 
 impl<T> BaseScript for T
@@ -30,15 +48,71 @@ where
     fn clone_box(&self) -> Box<dyn ScriptTrait> {
         Box::new(self.clone())
     }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
 }
 
 pub struct ScriptContext<'a, 'b, 'c> {
+    /// Time that has passed since last update, scaled by the time scale of the scene (see
+    /// [`Scene::set_time_scale`]).
     pub dt: f32,
+    /// Time that has passed since last update, not affected by the time scale of the scene. It
+    /// could be used for things that must work even if the scene is slowed down or frozen.
+    pub unscaled_dt: f32,
     pub plugin: &'a mut dyn Plugin,
     pub node: &'b mut Node,
     pub handle: Handle<Node>,
     pub scene: &'c mut Scene,
     pub resource_manager: &'a ResourceManager,
+    /// Frame rate limit, vertical synchronization mode, etc. See [`FramePacing`] docs for more
+    /// info.
+    pub frame_pacing: &'a mut FramePacing,
+}
+
+impl<'a, 'b, 'c> ScriptContext<'a, 'b, 'c> {
+    /// Returns debug drawing buffer of the scene, it could be used to draw lines, shapes and text
+    /// for debugging purposes. See [`DebugDrawBuffer`] docs for more info.
+    pub fn debug_draw(&mut self) -> &mut DebugDrawBuffer {
+        &mut self.scene.debug_draw
+    }
+
+    /// Spawns a task, owned by the node of the script, that runs given closure on a worker thread.
+    /// The result of the closure is passed to [`ScriptTrait::on_task_complete`] on the main thread,
+    /// where it is safe to modify the scene. The closure must own every piece of data it needs, so
+    /// copy required data of the scene into it.
+    ///
+    /// The task is cancelled if the node is deleted. Amount of tasks that run at the same time is
+    /// limited per scene, the rest waits in a queue. See [`crate::engine::task::TaskContainer`]
+    /// docs for more info.
+    pub fn spawn_task<F, T>(&mut self, func: F) -> TaskHandle
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Any + Send,
+    {
+        self.scene.tasks.spawn(self.handle, func)
+    }
+
+    /// Cancels a task spawned by [`Self::spawn_task`]. Returns `false` if the task is already
+    /// finished.
+    pub fn cancel_task(&mut self, task: TaskHandle) -> bool {
+        self.scene.tasks.cancel(task)
+    }
+
+    /// Returns `true` if some scripts of the scene wait for resources to be loaded, in other words
+    /// if [`ScriptTrait::on_start`] was not called for them yet.
+    pub fn is_scene_loading(&self) -> bool {
+        self.scene.script_starts.is_loading()
+    }
 }
 
 pub trait ScriptTrait: BaseScript {
@@ -118,6 +192,24 @@ pub trait ScriptTrait: BaseScript {
     /// In the editor, the method will be called on entering the play mode.
     fn on_init(&mut self, #[allow(unused_variables)] context: ScriptContext) {}
 
+    /// Called once, when resources that were pending at the moment the engine saw the script for
+    /// the first time are loaded (or failed to load). For scripts of a loaded scene these are the
+    /// resources of the scene and resources requested in [`Self::on_init`], for scripts of spawned
+    /// nodes (for example instances of a prefab) - resources of the prefab. So it is safe to read
+    /// mesh bounds, texture sizes, etc. here. See [`start::ScriptStartQueue`] docs for more info.
+    ///
+    /// # Ordering
+    ///
+    /// The method is called right before [`Self::on_update`] of the same frame. Parents are always
+    /// started before their children. [`Self::on_update`] is called while the script waits for
+    /// resources, use [`ScriptContext::is_scene_loading`] to check whether the loading is finished.
+    /// The method is called even if the parent node is disabled.
+    ///
+    /// # Editor-specific information
+    ///
+    /// Does not work in editor mode, works only in play mode.
+    fn on_start(&mut self, #[allow(unused_variables)] context: ScriptContext) {}
+
     /// Called when there is an event from the OS. The method allows you to "listen" for events
     /// coming from the main window of your game (or the editor if the game running inside the
     /// editor.
@@ -143,6 +235,82 @@ pub trait ScriptTrait: BaseScript {
     /// Does not work in editor mode, works only in play mode.
     fn on_update(&mut self, #[allow(unused_variables)] context: ScriptContext) {}
 
+    /// Called when the parent node becomes enabled (see [`crate::scene::base::Base::set_enabled`]),
+    /// either because it was enabled directly or because one of its ancestors was enabled. The
+    /// method is called right before [`Self::on_update`].
+    ///
+    /// # Editor-specific information
+    ///
+    /// Does not work in editor mode, works only in play mode.
+    fn on_enable(&mut self, #[allow(unused_variables)] context: ScriptContext) {}
+
+    /// Called when the parent node becomes disabled, either because it was disabled directly or
+    /// because one of its ancestors was disabled. The script of a disabled node does not receive
+    /// any updates, but its state is kept intact.
+    ///
+    /// # Editor-specific information
+    ///
+    /// Does not work in editor mode, works only in play mode.
+    fn on_disable(&mut self, #[allow(unused_variables)] context: ScriptContext) {}
+
+    /// Called when an animation blending state machine, that animates the parent node, fires a
+    /// state action (see [`crate::animation::machine::state::StateDefinition::on_enter`]). Actions
+    /// are delivered right after [`Self::on_update`] in the order they were fired.
+    ///
+    /// # Editor-specific information
+    ///
+    /// Does not work in editor mode, works only in play mode.
+    fn on_state_action(
+        &mut self,
+        #[allow(unused_variables)] action: &StateAction,
+        #[allow(unused_variables)] context: ScriptContext,
+    ) {
+    }
+
+    /// Called when a rigid body enters a trigger volume (see
+    /// [`crate::scene::trigger_volume::TriggerVolume`]). The method is called for the script of
+    /// the volume, `other` is the handle of the body then. If the volume is set to notify entering
+    /// bodies, it is also called for the script of the body, `other` is the handle of the volume
+    /// then. Events are delivered after [`Self::on_state_action`] in the order they happened.
+    ///
+    /// # Editor-specific information
+    ///
+    /// Does not work in editor mode, works only in play mode.
+    fn on_trigger_enter(
+        &mut self,
+        #[allow(unused_variables)] other: Handle<Node>,
+        #[allow(unused_variables)] context: ScriptContext,
+    ) {
+    }
+
+    /// Called when a rigid body exits a trigger volume. See [`Self::on_trigger_enter`] for more
+    /// info.
+    ///
+    /// # Editor-specific information
+    ///
+    /// Does not work in editor mode, works only in play mode.
+    fn on_trigger_exit(
+        &mut self,
+        #[allow(unused_variables)] other: Handle<Node>,
+        #[allow(unused_variables)] context: ScriptContext,
+    ) {
+    }
+
+    /// Called when a task spawned by the script (see [`ScriptContext::spawn_task`]) is finished.
+    /// Results are delivered after trigger events (see [`Self::on_trigger_enter`]) in the order of
+    /// completion, even if the parent node is disabled. Use [`Box::downcast`] to get the actual value of the result.
+    ///
+    /// # Editor-specific information
+    ///
+    /// Does not work in editor mode, works only in play mode.
+    fn on_task_complete(
+        &mut self,
+        #[allow(unused_variables)] task: TaskHandle,
+        #[allow(unused_variables)] result: TaskResult,
+        #[allow(unused_variables)] context: ScriptContext,
+    ) {
+    }
+
     /// Called right after the parent node was copied, giving you the ability to remap handles to
     /// nodes stored inside of your script.
     ///
@@ -167,6 +335,21 @@ pub trait ScriptTrait: BaseScript {
     /// manager to restore handles.
     fn restore_resources(&mut self, #[allow(unused_variables)] resource_manager: ResourceManager) {}
 
+    /// Returns names of the fields of the script that should be saved by
+    /// [`crate::scene::Scene::capture_state`] and restored by [`crate::scene::Scene::restore_state`].
+    /// Names of fields are the same as in the code (for example `is_open`), so you can use the
+    /// constants generated by `Inspect` derive (for example `Self::IS_OPEN`). By default the script
+    /// has no persistent fields and it is not saved at all.
+    ///
+    /// # Motivation
+    ///
+    /// A save game usually needs just a small part of the state of a level - health of a player,
+    /// state of doors, picked items and so on. The rest of the state (meshes, materials, etc.)
+    /// is loaded from the level itself.
+    fn persistent_fields(&self) -> &'static [&'static str] {
+        &[]
+    }
+
     /// Script instance type UUID. The value will be used for serialization, to write type
     /// identifier to a data source so the engine can restore the script from data source.
     ///
@@ -260,6 +443,84 @@ impl Script {
     pub fn new<T: ScriptTrait>(script_object: T) -> Self {
         Self(Box::new(script_object))
     }
+
+    /// Performs downcasting to a particular type.
+    pub fn cast<T: ScriptTrait>(&self) -> Option<&T> {
+        self.0.as_any().downcast_ref::<T>()
+    }
+
+    /// Performs downcasting to a particular type.
+    pub fn cast_mut<T: ScriptTrait>(&mut self) -> Option<&mut T> {
+        self.0.as_any_mut().downcast_mut::<T>()
+    }
+
+    /// Clamps numeric properties of the script to the ranges set by `#[inspect(min_value = ..)]` and
+    /// `#[inspect(max_value = ..)]` attributes. It is used to fix data that was saved before the range
+    /// of a property was changed. New values are applied via [`ScriptTrait::on_property_changed`], so
+    /// the script must handle respective properties, otherwise a value is left as is. Every fixed
+    /// value is reported as a warning. Returns amount of clamped properties.
+    pub fn clamp_to_property_ranges(&mut self) -> usize {
+        let changes = self
+            .properties()
+            .iter()
+            .filter_map(|property| {
+                clamp_numeric_property(property).map(|value| PropertyChanged {
+                    name: property.name.to_string(),
+                    owner_type_id: property.owner_type_id,
+                    value,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut count = 0;
+        for change in changes {
+            if self.on_property_changed(&change) {
+                Log::warn(format!(
+                    "Value of {} property of {} script is out of range and was clamped!",
+                    change.name,
+                    self.type_name()
+                ));
+                count += 1;
+            } else {
+                Log::warn(format!(
+                    "Value of {} property of {} script is out of range, but it cannot be clamped, \
+                    because the script does not handle changes of the property!",
+                    change.name,
+                    self.type_name()
+                ));
+            }
+        }
+        count
+    }
+}
+
+// Returns clamped value of a numeric property, if the value is out of its range.
+fn clamp_numeric_property(property: &PropertyInfo) -> Option<FieldKind> {
+    macro_rules! clamp_numeric {
+        ($($ty:ty),*) => {
+            $(
+                if let Ok(value) = property.cast_value::<$ty>() {
+                    let original = *value as f64;
+                    let mut clamped = original;
+                    if let Some(min_value) = property.min_value {
+                        clamped = clamped.max(min_value);
+                    }
+                    if let Some(max_value) = property.max_value {
+                        clamped = clamped.min(max_value);
+                    }
+                    return if clamped != original {
+                        Some(FieldKind::object(clamped as $ty))
+                    } else {
+                        None
+                    };
+                }
+            )*
+        };
+    }
+
+    clamp_numeric!(f32, f64, i8, u8, i16, u16, i32, u32, i64, u64, isize, usize);
+
+    None
 }
 
 /// A helper macro that allows you to handle object's property changed message. Such messages may come
@@ -375,3 +636,110 @@ macro_rules! handle_collection_property_changed {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            inspect::{Inspect, PropertyInfo},
+            uuid::{uuid, Uuid},
+            visitor::prelude::*,
+        },
+        engine::SerializationContext,
+        gui::inspector::{FieldKind, PropertyChanged},
+        scene::{
+            base::{deserialize_script, serialize_script},
+            node::TypeUuidProvider,
+        },
+        script::{Script, ScriptTrait},
+    };
+    use std::sync::Arc;
+
+    #[derive(Visit, Inspect, Debug, Clone)]
+    struct MyScript {
+        #[inspect(
+            min_value = 0.0,
+            max_value = 10.0,
+            step = 0.5,
+            precision = 2,
+            display_name = "Movement Speed",
+            description = "Speed in meters per second."
+        )]
+        speed: f32,
+
+        #[inspect(read_only)]
+        id: u32,
+    }
+
+    impl Default for MyScript {
+        fn default() -> Self {
+            Self { speed: 1.0, id: 0 }
+        }
+    }
+
+    impl TypeUuidProvider for MyScript {
+        fn type_uuid() -> Uuid {
+            uuid!("d4f5a3b8-7c2e-4a19-9b6e-3f8d1c0e5a27")
+        }
+    }
+
+    impl ScriptTrait for MyScript {
+        fn on_property_changed(&mut self, args: &PropertyChanged) -> bool {
+            handle_object_property_changed!(self, args, Self::SPEED => speed)
+        }
+
+        fn id(&self) -> Uuid {
+            Self::type_uuid()
+        }
+
+        fn plugin_uuid(&self) -> Uuid {
+            Self::type_uuid()
+        }
+    }
+
+    #[test]
+    fn test_script_property_info_survives_data_blob() {
+        let serialization_context = Arc::new(SerializationContext::new());
+        serialization_context
+            .script_constructors
+            .add::<MyScript, MyScript, _>("MyScript");
+
+        let script = Script::new(MyScript { speed: 2.5, id: 7 });
+        let data = serialize_script(&script).unwrap();
+        let script = deserialize_script(data, &serialization_context).unwrap();
+
+        let properties = script.properties();
+
+        let speed = properties
+            .iter()
+            .find(|p| p.name == MyScript::SPEED)
+            .unwrap();
+        assert_eq!(*speed.cast_value::<f32>().unwrap(), 2.5);
+        assert_eq!(speed.min_value, Some(0.0));
+        assert_eq!(speed.max_value, Some(10.0));
+        assert_eq!(speed.step, Some(0.5));
+        assert_eq!(speed.precision, Some(2));
+        assert_eq!(speed.display_name, "Movement Speed");
+        assert_eq!(speed.description, "Speed in meters per second.");
+        assert!(!speed.read_only);
+
+        let id = properties.iter().find(|p| p.name == MyScript::ID).unwrap();
+        assert_eq!(*id.cast_value::<u32>().unwrap(), 7);
+        assert!(id.read_only);
+    }
+
+    #[test]
+    fn test_script_out_of_range_values_are_clamped_on_load() {
+        let serialization_context = Arc::new(SerializationContext::new());
+        serialization_context
+            .script_constructors
+            .add::<MyScript, MyScript, _>("MyScript");
+
+        // Simulate data saved before the range was introduced.
+        let script = Script::new(MyScript { speed: 25.0, id: 0 });
+        let data = serialize_script(&script).unwrap();
+        let script = deserialize_script(data, &serialization_context).unwrap();
+
+        assert_eq!(script.cast::<MyScript>().unwrap().speed, 10.0);
+    }
+}
@@ -0,0 +1,253 @@
+//! Scheduling of [`ScriptTrait::on_start`](super::ScriptTrait::on_start) calls. See
+//! [`ScriptStartQueue`] docs for more info.
+
+use crate::{
+    core::pool::Handle,
+    scene::{graph::Graph, node::Node},
+};
+use fxhash::FxHashSet;
+
+/// Start state of the script of a node.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum ScriptStartState {
+    /// The script was not seen by the engine yet.
+    Unseen,
+    /// The script waits for resources to be loaded.
+    Queued,
+    /// [`super::ScriptTrait::on_start`] was called for the script.
+    Started,
+}
+
+impl Default for ScriptStartState {
+    fn default() -> Self {
+        Self::Unseen
+    }
+}
+
+#[derive(Debug)]
+struct StartBatch {
+    // Nodes in hierarchical order - parents before children.
+    nodes: Vec<Handle<Node>>,
+    // Keys of resources that were pending when the batch was created and still aren't loaded.
+    resources: FxHashSet<usize>,
+}
+
+/// A queue of scripts waiting for [`super::ScriptTrait::on_start`].
+///
+/// Every script, that is seen by the engine for the first time, is put in a batch together with
+/// other new scripts. The batch remembers every resource that is pending at the moment and waits
+/// until all of them reach a terminal state (loaded or failed to load), resources requested later
+/// do not delay the batch. This way scripts of a scene wait for resources requested while loading
+/// the scene (and in [`super::ScriptTrait::on_init`]), and scripts of spawned nodes (for example
+/// instances of a prefab) wait for resources of the prefab.
+///
+/// # Ordering
+///
+/// Batches are started in the order of creation, a batch never starts before previous ones. Within
+/// a batch, scripts are started in hierarchical order - parents before children, siblings in the
+/// order of the children list. So a parent is always started before its children, even if the
+/// children were spawned later.
+#[derive(Default, Debug)]
+pub struct ScriptStartQueue {
+    batches: Vec<StartBatch>,
+}
+
+impl ScriptStartQueue {
+    /// Puts every new script of the graph in a new batch and returns nodes whose scripts are ready
+    /// to start, in the order they must be started. `pending_resources` must return keys of pending
+    /// resources (see [`crate::engine::resource_manager::ResourceManagerState::pending_resource_keys`]),
+    /// it is called only if there is something to wait for.
+    ///
+    /// Returned nodes are marked as started, so every script is returned exactly once.
+    pub(crate) fn update<F>(
+        &mut self,
+        graph: &mut Graph,
+        mut pending_resources: F,
+    ) -> Vec<Handle<Node>>
+    where
+        F: FnMut() -> FxHashSet<usize>,
+    {
+        let mut pending = None;
+
+        let mut nodes = Vec::new();
+        let mut stack = vec![graph.get_root()];
+        while let Some(handle) = stack.pop() {
+            let node = &mut graph[handle];
+            if node.script.is_some() && node.script_start == ScriptStartState::Unseen {
+                node.script_start = ScriptStartState::Queued;
+                nodes.push(handle);
+            }
+            // Push children in reverse order to visit them in the order of the list.
+            stack.extend(node.children().iter().rev());
+        }
+
+        if !nodes.is_empty() {
+            self.batches.push(StartBatch {
+                nodes,
+                resources: pending.get_or_insert_with(&mut pending_resources).clone(),
+            });
+        }
+
+        let mut ready = Vec::new();
+        while let Some(batch) = self.batches.first_mut() {
+            if !batch.resources.is_empty() {
+                let pending = pending.get_or_insert_with(&mut pending_resources);
+                batch.resources.retain(|key| pending.contains(key));
+                if !batch.resources.is_empty() {
+                    break;
+                }
+            }
+
+            ready.extend(self.batches.remove(0).nodes);
+        }
+
+        // Scripts of deleted nodes are skipped, as well as nodes that changed their scripts and
+        // will be queued again.
+        ready.retain(|&handle| {
+            graph.try_get_mut(handle).map_or(false, |node| {
+                if node.script_start == ScriptStartState::Queued {
+                    node.script_start = ScriptStartState::Started;
+                    true
+                } else {
+                    false
+                }
+            })
+        });
+
+        ready
+    }
+
+    /// Returns `true` if there are scripts that wait for resources to be loaded.
+    pub fn is_loading(&self) -> bool {
+        !self.batches.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::{Resource, ResourceState},
+        core::{
+            inspect::{Inspect, PropertyInfo},
+            pool::Handle,
+            uuid::{uuid, Uuid},
+            visitor::prelude::*,
+        },
+        engine::{resource_manager::ResourceManager, SerializationContext},
+        resource::texture::{Texture, TextureData, TextureKind, TexturePixelKind},
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            node::{Node, TypeUuidProvider},
+            pivot::PivotBuilder,
+        },
+        script::{start::ScriptStartQueue, Script, ScriptTrait},
+    };
+    use std::{path::PathBuf, sync::Arc};
+
+    #[derive(Visit, Inspect, Debug, Clone, Default)]
+    struct MyScript {}
+
+    impl TypeUuidProvider for MyScript {
+        fn type_uuid() -> Uuid {
+            uuid!("7a1e9c42-5b3d-4f86-a0d2-6c8e1b94f357")
+        }
+    }
+
+    impl ScriptTrait for MyScript {
+        fn id(&self) -> Uuid {
+            Self::type_uuid()
+        }
+
+        fn plugin_uuid(&self) -> Uuid {
+            Self::type_uuid()
+        }
+    }
+
+    fn make_scripted(graph: &mut Graph, parent: Handle<Node>) -> Handle<Node> {
+        let node =
+            PivotBuilder::new(BaseBuilder::new().with_script(Script::new(MyScript::default())))
+                .build(graph);
+        graph.link_nodes(node, parent);
+        node
+    }
+
+    #[test]
+    fn test_parents_are_started_before_children() {
+        let mut graph = Graph::new();
+        let root = graph.get_root();
+        // Spawn children first, so the order of the pool differs from the hierarchical order.
+        let child =
+            PivotBuilder::new(BaseBuilder::new().with_script(Script::new(MyScript::default())))
+                .build(&mut graph);
+        let grandchild = make_scripted(&mut graph, child);
+        let parent = make_scripted(&mut graph, root);
+        graph.link_nodes(child, parent);
+        let sibling = make_scripted(&mut graph, parent);
+        // Nodes without scripts are not started.
+        let _ = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        let mut queue = ScriptStartQueue::default();
+        let ready = queue.update(&mut graph, Default::default);
+        assert_eq!(ready, vec![parent, child, grandchild, sibling]);
+        assert!(!queue.is_loading());
+
+        // Every script is started exactly once.
+        assert!(queue.update(&mut graph, Default::default).is_empty());
+    }
+
+    #[test]
+    fn test_start_waits_for_slow_resource() {
+        let resource_manager = ResourceManager::new(Arc::new(SerializationContext::new()));
+
+        // Mock of a texture that takes a while to load.
+        let texture = Texture::from(Resource::new(ResourceState::new_pending(PathBuf::from(
+            "slow.png",
+        ))));
+        resource_manager
+            .state()
+            .containers_mut()
+            .textures
+            .push(texture.clone());
+
+        let mut graph = Graph::new();
+        let root = graph.get_root();
+        let parent = make_scripted(&mut graph, root);
+
+        let mut queue = ScriptStartQueue::default();
+        let mut pending_resources = || resource_manager.state().pending_resource_keys();
+
+        assert!(queue.update(&mut graph, &mut pending_resources).is_empty());
+        assert!(queue.is_loading());
+
+        // A node spawned during loading must not start before its parent.
+        let child = make_scripted(&mut graph, parent);
+        assert!(queue.update(&mut graph, &mut pending_resources).is_empty());
+
+        texture.state().commit_ok(
+            TextureData::from_bytes(
+                TextureKind::Rectangle {
+                    width: 2,
+                    height: 2,
+                },
+                TexturePixelKind::RGBA8,
+                vec![255; 16],
+                false,
+            )
+            .unwrap(),
+        );
+
+        let ready = queue.update(&mut graph, &mut pending_resources);
+        assert_eq!(ready, vec![parent, child]);
+        assert!(!queue.is_loading());
+
+        // This is what a script sees in its on_start.
+        assert!(matches!(
+            texture.data_ref().kind(),
+            TextureKind::Rectangle {
+                width: 2,
+                height: 2
+            }
+        ));
+    }
+}
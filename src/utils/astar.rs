@@ -168,11 +168,35 @@ impl PathFinder {
         }
     }
 
+    /// Removes bidirectional link between two vertices, see [`Self::link_bidirect`].
+    pub fn unlink_bidirect(&mut self, a: usize, b: usize) {
+        self.unlink_unidirect(a, b);
+        self.unlink_unidirect(b, a);
+    }
+
+    /// Removes unidirectional link from vertex `a` to vertex `b`, see [`Self::link_unidirect`].
+    pub fn unlink_unidirect(&mut self, a: usize, b: usize) {
+        if let Some(vertex_a) = self.vertices.get_mut(a) {
+            vertex_a.neighbours.retain(|n| *n != b as u32);
+        }
+    }
+
+    /// Adds new vertex without any links and returns its index.
+    pub fn add_vertex(&mut self, vertex: PathVertex) -> usize {
+        self.vertices.push(vertex);
+        self.vertices.len() - 1
+    }
+
     /// Returns shared reference to path vertex at given index.
     pub fn get_vertex(&self, index: usize) -> Option<&PathVertex> {
         self.vertices.get(index)
     }
 
+    /// Returns mutable reference to path vertex at given index.
+    pub fn get_vertex_mut(&mut self, index: usize) -> Option<&mut PathVertex> {
+        self.vertices.get_mut(index)
+    }
+
     /// Returns reference to array of vertices.
     pub fn vertices(&self) -> &[PathVertex] {
         &self.vertices
@@ -0,0 +1,845 @@
+//! Offline baking of hierarchical level of detail (HLOD) proxies. For runtime part see
+//! [`crate::scene::hlod::Hlod`].
+//!
+//! Baking is split in two steps, just like lightmap generation: [`HlodInputData::from_scene`]
+//! takes a snapshot of the scene and [`bake`] does the heavy work (simplification and atlas
+//! packing), so it could be done in a background thread while the scene is still used.
+//!
+//! # Incremental baking
+//!
+//! Every cluster has a hash of its content (transforms, geometry, materials and bake settings).
+//! Clusters that already have an HLOD node with the same hash are not baked again, HLOD nodes that
+//! does not match any cluster are considered stale and should be removed (see
+//! [`HlodBakeResult::apply`]).
+
+use crate::{
+    core::{
+        algebra::{Matrix3, Matrix4, Point3, Vector2, Vector3},
+        color::Color,
+        hash_combine,
+        math::{aabb::AxisAlignedBoundingBox, Matrix4Ext, TriangleDefinition},
+        parking_lot::Mutex,
+        pool::Handle,
+        sstorage::ImmutableString,
+    },
+    engine::resource_manager::{ResourceManager, TextureRegistrationError},
+    material::{shader::SamplerFallback, Material, PropertyValue},
+    resource::texture::{Texture, TextureError, TextureKind, TexturePixelKind, TextureState},
+    scene::{
+        base::{BaseBuilder, Mobility},
+        graph::Graph,
+        hlod::{Hlod, HlodBuilder},
+        mesh::{
+            buffer::{
+                TriangleBuffer, VertexAttributeUsage, VertexBuffer, VertexFetchError,
+                VertexReadTrait,
+            },
+            surface::{SurfaceBuilder, SurfaceData},
+            vertex::StaticVertex,
+            Mesh, MeshBuilder,
+        },
+        node::Node,
+        Scene,
+    },
+    utils::{lightmap::CancellationToken, simplify::simplify},
+};
+use fxhash::{FxHashMap, FxHasher};
+use std::{
+    hash::Hasher,
+    ops::Deref,
+    path::Path,
+    sync::{
+        atomic::{self, AtomicU32},
+        Arc,
+    },
+};
+
+/// A set of parameters of HLOD baking.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HlodSettings {
+    /// Size of a grid cell in local coordinates of the root node. Meshes are grouped into
+    /// clusters by the cell in which the center of their bounds is. Zero means that every mesh
+    /// under the root node will be in a single cluster.
+    pub cell_size: f32,
+    /// Distance at which proxies replace original meshes, see [`Hlod::set_switch_distance`].
+    pub switch_distance: f32,
+    /// Width of the hysteresis band, see [`Hlod::set_hysteresis`].
+    pub hysteresis: f32,
+    /// Fraction of triangles of original meshes that should be kept in a proxy, in [0; 1] range.
+    pub triangle_ratio: f32,
+    /// Size of a tile in the texture atlas of a proxy, each unique material of a cluster gets its
+    /// own tile.
+    pub atlas_tile_size: u32,
+}
+
+impl Default for HlodSettings {
+    fn default() -> Self {
+        Self {
+            cell_size: 50.0,
+            switch_distance: 150.0,
+            hysteresis: 10.0,
+            triangle_ratio: 0.25,
+            atlas_tile_size: 64,
+        }
+    }
+}
+
+impl HlodSettings {
+    fn hash(&self) -> u64 {
+        hash_floats(&[
+            self.cell_size,
+            self.switch_distance,
+            self.hysteresis,
+            self.triangle_ratio,
+            self.atlas_tile_size as f32,
+        ])
+    }
+}
+
+/// Progress internals.
+#[derive(Default)]
+pub struct ProgressData {
+    // Range is [0; max_iterations]
+    progress: AtomicU32,
+    max_iterations: AtomicU32,
+}
+
+impl ProgressData {
+    /// Returns progress percentage in [0; 100] range.
+    pub fn progress_percent(&self) -> u32 {
+        let iterations = self.max_iterations.load(atomic::Ordering::SeqCst);
+        if iterations > 0 {
+            self.progress.load(atomic::Ordering::SeqCst) * 100 / iterations
+        } else {
+            0
+        }
+    }
+
+    pub(crate) fn reset(&self, max_iterations: u32) {
+        self.max_iterations
+            .store(max_iterations, atomic::Ordering::SeqCst);
+        self.progress.store(0, atomic::Ordering::SeqCst);
+    }
+
+    pub(crate) fn advance_progress(&self) {
+        self.progress.fetch_add(1, atomic::Ordering::SeqCst);
+    }
+}
+
+/// Small helper that allows you to track progress of HLOD or impostor baking.
+#[derive(Clone, Default)]
+pub struct ProgressIndicator(pub Arc<ProgressData>);
+
+impl ProgressIndicator {
+    /// Creates new progress indicator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Deref for ProgressIndicator {
+    type Target = ProgressData;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+/// An error that may occur during HLOD baking.
+#[derive(Debug, thiserror::Error)]
+pub enum HlodBakeError {
+    /// Baking was cancelled by user.
+    #[error("HLOD baking was cancelled by the user.")]
+    Cancelled,
+    /// Vertex buffer of a mesh lacks required data.
+    #[error("Vertex buffer of a mesh lacks required data {0}.")]
+    InvalidData(VertexFetchError),
+}
+
+impl From<VertexFetchError> for HlodBakeError {
+    fn from(e: VertexFetchError) -> Self {
+        Self::InvalidData(e)
+    }
+}
+
+struct SourceSurface {
+    vertices: Vec<StaticVertex>,
+    triangles: Vec<TriangleDefinition>,
+    tile: usize,
+}
+
+struct ClusterInput {
+    cell: [i32; 3],
+    originals: Vec<Handle<Node>>,
+    surfaces: Vec<SourceSurface>,
+    // RGBA8 pixels of every unique material of the cluster.
+    tiles: Vec<Vec<u8>>,
+    tile_map: FxHashMap<(u64, [u8; 4]), usize>,
+    bounds: AxisAlignedBoundingBox,
+    hash: u64,
+}
+
+/// A snapshot of a part of a scene with everything that is needed to bake HLOD proxies. It does
+/// not borrow the scene, so the baking could be done in a background thread.
+///
+/// Only visible meshes with [`Mobility::Static`] are taken into account, meshes that are already
+/// a part of some HLOD (proxies) are ignored.
+pub struct HlodInputData {
+    settings: HlodSettings,
+    clusters: Vec<ClusterInput>,
+    // HLOD nodes under the root and their content hashes.
+    existing: Vec<(Handle<Node>, u64)>,
+}
+
+impl HlodInputData {
+    /// Gathers static meshes under the given root node and groups them into clusters. `filter`
+    /// allows you to exclude some nodes, it must return `true` for nodes that should be taken
+    /// into account.
+    pub fn from_scene<F>(
+        scene: &mut Scene,
+        root: Handle<Node>,
+        settings: HlodSettings,
+        mut filter: F,
+        cancellation_token: CancellationToken,
+    ) -> Result<Self, HlodBakeError>
+    where
+        F: FnMut(Handle<Node>, &Node) -> bool,
+    {
+        scene.graph.update_hierarchical_data();
+
+        let graph = &scene.graph;
+        let settings_hash = settings.hash();
+        // Proxies are placed in the local coordinates of the root.
+        let inv_root_transform = graph[root]
+            .global_transform()
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity);
+
+        let mut clusters = FxHashMap::<[i32; 3], ClusterInput>::default();
+        let mut existing = Vec::new();
+
+        let mut stack = graph[root].children().to_vec();
+        while let Some(handle) = stack.pop() {
+            if cancellation_token.is_cancelled() {
+                return Err(HlodBakeError::Cancelled);
+            }
+
+            let node = &graph[handle];
+
+            if let Some(hlod) = node.cast::<Hlod>() {
+                existing.push((handle, hlod.content_hash()));
+                // Proxies must not be baked into other proxies.
+                continue;
+            }
+
+            stack.extend_from_slice(node.children());
+
+            let mesh = match node.cast::<Mesh>() {
+                Some(mesh) => mesh,
+                None => continue,
+            };
+
+            if !mesh.global_visibility()
+                || mesh.mobility() != Mobility::Static
+                || !filter(handle, node)
+            {
+                continue;
+            }
+
+            let transform = inv_root_transform * mesh.global_transform();
+            let basis = transform.basis();
+            let normal_matrix = basis
+                .try_inverse()
+                .map(|m| m.transpose())
+                .unwrap_or_else(Matrix3::identity);
+
+            // Bounds are calculated from the surfaces, because cached bounds of the mesh could be
+            // outdated if the graph wasn't updated yet.
+            let mut mesh_bounds = AxisAlignedBoundingBox::default();
+            for surface in mesh.surfaces() {
+                for view in surface.data().lock().vertex_buffer.iter() {
+                    let position = view.read_3_f32(VertexAttributeUsage::Position)?;
+                    mesh_bounds
+                        .add_point(transform.transform_point(&Point3::from(position)).coords);
+                }
+            }
+
+            let center = mesh_bounds.center();
+            let cell = if settings.cell_size > 0.0 {
+                let cell = center / settings.cell_size;
+                [
+                    cell.x.floor() as i32,
+                    cell.y.floor() as i32,
+                    cell.z.floor() as i32,
+                ]
+            } else {
+                [0, 0, 0]
+            };
+
+            let cluster = clusters.entry(cell).or_insert_with(|| ClusterInput {
+                cell,
+                originals: Default::default(),
+                surfaces: Default::default(),
+                tiles: Default::default(),
+                tile_map: Default::default(),
+                bounds: AxisAlignedBoundingBox::default(),
+                hash: settings_hash,
+            });
+
+            cluster.originals.push(handle);
+            cluster.hash = hash_combine(cluster.hash, hash_floats(transform.as_slice()));
+
+            for surface in mesh.surfaces() {
+                let data = surface.data();
+                let data = data.lock();
+
+                let material = surface.material().lock();
+                let texture = material
+                    .property_ref(&ImmutableString::new("diffuseTexture"))
+                    .and_then(|value| value.as_sampler());
+                let color = match material.property_ref(&ImmutableString::new("diffuseColor")) {
+                    Some(PropertyValue::Color(color)) => *color,
+                    _ => Color::WHITE,
+                };
+
+                let texture_hash = texture.as_ref().map_or(0, hash_texture);
+                let color_bytes = [color.r, color.g, color.b, color.a];
+
+                cluster.hash = hash_combine(cluster.hash, data.content_hash());
+                cluster.hash = hash_combine(cluster.hash, texture_hash);
+                cluster.hash = hash_combine(cluster.hash, u32::from_le_bytes(color_bytes) as u64);
+
+                let tiles = &mut cluster.tiles;
+                let tile = *cluster
+                    .tile_map
+                    .entry((texture_hash, color_bytes))
+                    .or_insert_with(|| {
+                        tiles.push(make_tile(
+                            texture.as_ref(),
+                            color,
+                            settings.atlas_tile_size.max(1),
+                        ));
+                        tiles.len() - 1
+                    });
+
+                let mut vertices = Vec::with_capacity(data.vertex_buffer.vertex_count() as usize);
+                for view in data.vertex_buffer.iter() {
+                    let position = transform
+                        .transform_point(&Point3::from(
+                            view.read_3_f32(VertexAttributeUsage::Position)?,
+                        ))
+                        .coords;
+                    let normal = view
+                        .read_3_f32(VertexAttributeUsage::Normal)
+                        .unwrap_or_else(|_| Vector3::y());
+                    let tex_coord = view
+                        .read_2_f32(VertexAttributeUsage::TexCoord0)
+                        .unwrap_or_default();
+
+                    cluster.bounds.add_point(position);
+
+                    vertices.push(StaticVertex::from_pos_uv_normal(
+                        position,
+                        tex_coord,
+                        (normal_matrix * normal)
+                            .try_normalize(f32::EPSILON)
+                            .unwrap_or_else(Vector3::y),
+                    ));
+                }
+
+                cluster.surfaces.push(SourceSurface {
+                    vertices,
+                    triangles: data.geometry_buffer.triangles_ref().to_vec(),
+                    tile,
+                });
+            }
+        }
+
+        let mut clusters = clusters.into_values().collect::<Vec<_>>();
+        clusters.sort_by_key(|c| c.cell);
+
+        Ok(Self {
+            settings,
+            clusters,
+            existing,
+        })
+    }
+
+    /// Returns amount of clusters in the snapshot.
+    pub fn cluster_count(&self) -> usize {
+        self.clusters.len()
+    }
+}
+
+/// Baked proxy of a single cluster. Use [`HlodProxyData::instantiate`] to add it to a graph.
+pub struct HlodProxyData {
+    hash: u64,
+    bounds: AxisAlignedBoundingBox,
+    originals: Vec<Handle<Node>>,
+    switch_distance: f32,
+    hysteresis: f32,
+    data: SurfaceData,
+    atlas: Texture,
+}
+
+impl HlodProxyData {
+    /// Returns the texture atlas of the proxy.
+    pub fn atlas(&self) -> &Texture {
+        &self.atlas
+    }
+
+    /// Returns amount of triangles in the proxy.
+    pub fn triangle_count(&self) -> usize {
+        self.data.geometry_buffer.len()
+    }
+
+    /// Creates HLOD node with the proxy mesh as a child and attaches it to the given parent. The
+    /// parent must be the root node the proxy was baked for, because the proxy is in its local
+    /// coordinates.
+    pub fn instantiate(&self, graph: &mut Graph, parent: Handle<Node>) -> Handle<Node> {
+        let mut material = Material::standard();
+        material
+            .set_property(
+                &ImmutableString::new("diffuseTexture"),
+                PropertyValue::Sampler {
+                    value: Some(self.atlas.clone()),
+                    fallback: SamplerFallback::White,
+                },
+            )
+            .unwrap();
+
+        let proxy = MeshBuilder::new(
+            BaseBuilder::new()
+                .with_name("HlodProxy")
+                .with_mobility(Mobility::Static),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+            self.data.clone(),
+        )))
+        .with_material(Arc::new(Mutex::new(material)))
+        .build()])
+        .build(graph);
+
+        let hlod = HlodBuilder::new(
+            BaseBuilder::new()
+                .with_name("HLOD")
+                .with_mobility(Mobility::Static)
+                .with_children(&[proxy]),
+        )
+        .with_switch_distance(self.switch_distance)
+        .with_hysteresis(self.hysteresis)
+        .with_proxy(proxy)
+        .with_originals(self.originals.clone())
+        .with_bounds(self.bounds)
+        .with_content_hash(self.hash)
+        .build(graph);
+
+        graph.link_nodes(hlod, parent);
+
+        hlod
+    }
+}
+
+/// Result of HLOD baking.
+pub struct HlodBakeResult {
+    /// Proxies of new or changed clusters.
+    pub proxies: Vec<HlodProxyData>,
+    /// HLOD nodes that does not match any cluster anymore, they should be removed.
+    pub stale: Vec<Handle<Node>>,
+    /// HLOD nodes that are still valid.
+    pub unchanged: Vec<Handle<Node>>,
+}
+
+impl HlodBakeResult {
+    /// Saves texture atlases of proxies into specified folder.
+    pub fn save<P: AsRef<Path>>(
+        &self,
+        base_path: P,
+        resource_manager: ResourceManager,
+    ) -> Result<(), TextureRegistrationError> {
+        if !base_path.as_ref().exists() {
+            std::fs::create_dir_all(base_path.as_ref()).map_err(TextureError::Io)?;
+        }
+
+        for proxy in self.proxies.iter() {
+            // Names are based on hashes, so they do not clash with the atlases of previous bakes.
+            let mut suffix = 0;
+            loop {
+                let file_name = if suffix == 0 {
+                    format!("{:x}_hlod.png", proxy.hash)
+                } else {
+                    format!("{:x}_hlod_{}.png", proxy.hash, suffix)
+                };
+                match resource_manager
+                    .register_texture(proxy.atlas.clone(), base_path.as_ref().join(file_name))
+                {
+                    Err(TextureRegistrationError::AlreadyRegistered) => suffix += 1,
+                    result => {
+                        result?;
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes stale HLOD nodes and instantiates new proxies under the given root. Returns
+    /// handles of new HLOD nodes.
+    pub fn apply(self, graph: &mut Graph, root: Handle<Node>) -> Vec<Handle<Node>> {
+        for &stale in self.stale.iter() {
+            if graph.is_valid_handle(stale) {
+                graph.remove_node(stale);
+            }
+        }
+
+        self.proxies
+            .iter()
+            .map(|proxy| proxy.instantiate(graph, root))
+            .collect()
+    }
+}
+
+/// Bakes proxies for every changed cluster of the given snapshot. This method is blocking, use a
+/// background thread to not block the main thread.
+pub fn bake(
+    input: HlodInputData,
+    cancellation_token: CancellationToken,
+    progress_indicator: ProgressIndicator,
+) -> Result<HlodBakeResult, HlodBakeError> {
+    let HlodInputData {
+        settings,
+        clusters,
+        existing,
+    } = input;
+
+    let mut unchanged = Vec::new();
+    let mut changed = Vec::new();
+    for cluster in clusters {
+        if let Some((handle, _)) = existing
+            .iter()
+            .find(|(handle, hash)| *hash == cluster.hash && !unchanged.contains(handle))
+        {
+            unchanged.push(*handle);
+        } else {
+            changed.push(cluster);
+        }
+    }
+
+    let stale = existing
+        .iter()
+        .map(|(handle, _)| *handle)
+        .filter(|handle| !unchanged.contains(handle))
+        .collect();
+
+    progress_indicator.reset(changed.len() as u32);
+
+    let mut proxies = Vec::with_capacity(changed.len());
+    for cluster in changed {
+        if cancellation_token.is_cancelled() {
+            return Err(HlodBakeError::Cancelled);
+        }
+
+        proxies.push(bake_cluster(cluster, &settings)?);
+
+        progress_indicator.advance_progress();
+    }
+
+    Ok(HlodBakeResult {
+        proxies,
+        stale,
+        unchanged,
+    })
+}
+
+fn bake_cluster(
+    cluster: ClusterInput,
+    settings: &HlodSettings,
+) -> Result<HlodProxyData, HlodBakeError> {
+    let tile_size = settings.atlas_tile_size.max(1);
+    let tiles_per_row = (cluster.tiles.len() as f32).sqrt().ceil().max(1.0) as u32;
+    let atlas_size = tiles_per_row * tile_size;
+
+    let mut atlas = vec![0u8; (atlas_size * atlas_size * 4) as usize];
+    for (i, tile) in cluster.tiles.iter().enumerate() {
+        let (tx, ty) = (i as u32 % tiles_per_row, i as u32 / tiles_per_row);
+        for y in 0..tile_size {
+            let src = (y * tile_size * 4) as usize;
+            let dst = (((ty * tile_size + y) * atlas_size + tx * tile_size) * 4) as usize;
+            let len = (tile_size * 4) as usize;
+            atlas[dst..dst + len].copy_from_slice(&tile[src..src + len]);
+        }
+    }
+
+    let mut vertices = Vec::new();
+    let mut triangles = Vec::new();
+    for surface in cluster.surfaces {
+        let target = ((surface.triangles.len() as f32 * settings.triangle_ratio.clamp(0.0, 1.0))
+            .ceil() as usize)
+            .max(1);
+        let (surface_vertices, surface_triangles) =
+            simplify(&surface.vertices, &surface.triangles, target);
+
+        // Texture coordinates are clamped to a tile, repeating textures can't be represented
+        // in the atlas. Half-texel inset prevents bleeding of adjacent tiles.
+        let tile = surface.tile as u32;
+        let origin = Vector2::new(
+            (tile % tiles_per_row * tile_size) as f32,
+            (tile / tiles_per_row * tile_size) as f32,
+        );
+        let offset = vertices.len() as u32;
+        vertices.extend(surface_vertices.into_iter().map(|mut v| {
+            let uv = Vector2::new(v.tex_coord.x.clamp(0.0, 1.0), v.tex_coord.y.clamp(0.0, 1.0));
+            v.tex_coord = (origin + uv.scale((tile_size - 1) as f32) + Vector2::new(0.5, 0.5))
+                .scale(1.0 / atlas_size as f32);
+            v
+        }));
+        triangles.extend(
+            surface_triangles
+                .into_iter()
+                .map(|t| TriangleDefinition([t[0] + offset, t[1] + offset, t[2] + offset])),
+        );
+    }
+
+    let mut data = SurfaceData::new(
+        // Layout of the static vertex is always valid.
+        VertexBuffer::new(vertices.len(), StaticVertex::layout(), vertices).unwrap(),
+        TriangleBuffer::new(triangles),
+        true,
+    );
+    data.calculate_tangents()?;
+
+    Ok(HlodProxyData {
+        hash: cluster.hash,
+        bounds: cluster.bounds,
+        originals: cluster.originals,
+        switch_distance: settings.switch_distance,
+        hysteresis: settings.hysteresis,
+        data,
+        atlas: Texture::from_bytes(
+            TextureKind::Rectangle {
+                width: atlas_size,
+                height: atlas_size,
+            },
+            TexturePixelKind::RGBA8,
+            atlas,
+            false,
+        )
+        .unwrap(),
+    })
+}
+
+/// Creates a tile of the atlas, the tile is a downsampled texture multiplied by the color.
+fn make_tile(texture: Option<&Texture>, color: Color, size: u32) -> Vec<u8> {
+    let mut tile = vec![255u8; (size * size * 4) as usize];
+
+    if let Some((width, height, pixels)) = texture.and_then(rgba8_pixels) {
+        for y in 0..size {
+            for x in 0..size {
+                // Box filter over the footprint of the tile pixel.
+                let (x0, x1) = (
+                    x * width / size,
+                    ((x + 1) * width / size).max(x * width / size + 1),
+                );
+                let (y0, y1) = (
+                    y * height / size,
+                    ((y + 1) * height / size).max(y * height / size + 1),
+                );
+                let mut sum = [0u32; 4];
+                let mut count = 0;
+                for sy in y0..y1.min(height) {
+                    for sx in x0..x1.min(width) {
+                        let i = ((sy * width + sx) * 4) as usize;
+                        for (s, p) in sum.iter_mut().zip(&pixels[i..i + 4]) {
+                            *s += *p as u32;
+                        }
+                        count += 1;
+                    }
+                }
+                let i = ((y * size + x) * 4) as usize;
+                for (t, s) in tile[i..i + 4].iter_mut().zip(sum.iter()) {
+                    *t = (*s / count.max(1)) as u8;
+                }
+            }
+        }
+    }
+
+    for pixel in tile.chunks_exact_mut(4) {
+        for (p, c) in pixel.iter_mut().zip([color.r, color.g, color.b, color.a]) {
+            *p = (*p as u32 * c as u32 / 255) as u8;
+        }
+    }
+
+    tile
+}
+
+/// Returns first mip level of a rectangle texture as RGBA8 pixels.
+pub(crate) fn rgba8_pixels(texture: &Texture) -> Option<(u32, u32, Vec<u8>)> {
+    let state = texture.state();
+    let data = match &*state {
+        TextureState::Ok(data) => data,
+        _ => return None,
+    };
+
+    let (width, height) = match data.kind() {
+        TextureKind::Rectangle { width, height } => (width, height),
+        _ => return None,
+    };
+    let pixel_count = (width * height) as usize;
+
+    let (pixel_kind, bytes) = match data.decompress() {
+        Some(decompressed) => decompressed,
+        None => (data.pixel_kind(), data.first_mip_level_data().to_vec()),
+    };
+
+    let convert = |bpp: usize, f: &dyn Fn(&[u8]) -> [u8; 4]| {
+        bytes
+            .get(0..pixel_count * bpp)
+            .map(|bytes| bytes.chunks_exact(bpp).flat_map(f).collect::<Vec<_>>())
+    };
+
+    let pixels = match pixel_kind {
+        TexturePixelKind::RGBA8 => convert(4, &|p| [p[0], p[1], p[2], p[3]]),
+        TexturePixelKind::BGRA8 => convert(4, &|p| [p[2], p[1], p[0], p[3]]),
+        TexturePixelKind::RGB8 => convert(3, &|p| [p[0], p[1], p[2], 255]),
+        TexturePixelKind::BGR8 => convert(3, &|p| [p[2], p[1], p[0], 255]),
+        TexturePixelKind::RG8 => convert(2, &|p| [p[0], p[1], 0, 255]),
+        TexturePixelKind::R8 => convert(1, &|p| [p[0], p[0], p[0], 255]),
+        _ => None,
+    }?;
+
+    Some((width, height, pixels))
+}
+
+pub(crate) fn hash_texture(texture: &Texture) -> u64 {
+    let mut hasher = FxHasher::default();
+    let state = texture.state();
+    let path = state.path();
+    if path.as_os_str().is_empty() {
+        // Procedural textures have no path, so their content is used instead.
+        if let TextureState::Ok(data) = &*state {
+            hasher.write(data.data());
+        }
+    } else {
+        hasher.write(path.to_string_lossy().as_bytes());
+    }
+    hasher.finish()
+}
+
+pub(crate) fn hash_floats(values: &[f32]) -> u64 {
+    let mut hasher = FxHasher::default();
+    for value in values {
+        hasher.write_u32(value.to_bits());
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector3},
+            parking_lot::Mutex,
+            pool::Handle,
+        },
+        scene::{
+            base::{BaseBuilder, Mobility},
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData},
+                MeshBuilder,
+            },
+            node::Node,
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+            Scene,
+        },
+        utils::{
+            hlod::{bake, HlodInputData, HlodSettings, ProgressIndicator},
+            lightmap::CancellationToken,
+        },
+    };
+    use std::sync::Arc;
+
+    fn make_cube(scene: &mut Scene, parent: Handle<Node>, position: Vector3<f32>) -> Handle<Node> {
+        let cube = MeshBuilder::new(
+            BaseBuilder::new()
+                .with_mobility(Mobility::Static)
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(
+            SurfaceData::make_sphere(8, 8, 1.0, &Matrix4::identity()),
+        )))
+        .build()])
+        .build(&mut scene.graph);
+        scene.graph.link_nodes(cube, parent);
+        cube
+    }
+
+    fn snapshot(scene: &mut Scene, root: Handle<Node>) -> HlodInputData {
+        HlodInputData::from_scene(
+            scene,
+            root,
+            HlodSettings {
+                cell_size: 10.0,
+                atlas_tile_size: 4,
+                ..Default::default()
+            },
+            |_, _| true,
+            CancellationToken::new(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_hlod_rebakes_only_changed_clusters() {
+        let mut scene = Scene::new();
+        let root = PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+
+        make_cube(&mut scene, root, Vector3::new(1.0, 1.0, 1.0));
+        make_cube(&mut scene, root, Vector3::new(3.0, 1.0, 1.0));
+        make_cube(&mut scene, root, Vector3::new(21.0, 1.0, 1.0));
+        let moved = make_cube(&mut scene, root, Vector3::new(23.0, 1.0, 1.0));
+
+        let input = snapshot(&mut scene, root);
+        assert_eq!(input.cluster_count(), 2);
+
+        let result = bake(input, CancellationToken::new(), ProgressIndicator::new()).unwrap();
+        assert_eq!(result.proxies.len(), 2);
+        assert!(result.stale.is_empty());
+        for proxy in result.proxies.iter() {
+            // Two spheres of 8x8 are 224 triangles.
+            assert!(proxy.triangle_count() > 0 && proxy.triangle_count() < 224);
+        }
+        let hlods = result.apply(&mut scene.graph, root);
+        assert_eq!(hlods.len(), 2);
+
+        // Nothing is changed - nothing to bake.
+        let result = bake(
+            snapshot(&mut scene, root),
+            CancellationToken::new(),
+            ProgressIndicator::new(),
+        )
+        .unwrap();
+        assert!(result.proxies.is_empty());
+        assert!(result.stale.is_empty());
+        assert_eq!(result.unchanged.len(), 2);
+
+        // Move a mesh inside its cell, only its cluster must be baked again.
+        scene.graph[moved]
+            .local_transform_mut()
+            .set_position(Vector3::new(24.0, 1.0, 1.0));
+        let result = bake(
+            snapshot(&mut scene, root),
+            CancellationToken::new(),
+            ProgressIndicator::new(),
+        )
+        .unwrap();
+        assert_eq!(result.proxies.len(), 1);
+        assert_eq!(result.stale, vec![hlods[1]]);
+        assert_eq!(result.unchanged, vec![hlods[0]]);
+    }
+}
@@ -0,0 +1,978 @@
+//! Offline baking of impostors - pre-rendered images of an object from a set of directions around
+//! it. For runtime part see [`crate::scene::impostor::Impostor`].
+//!
+//! Baking is split in two steps, just like HLOD baking: [`ImpostorInputData::from_graph`] takes a
+//! snapshot of meshes of an object and [`bake`] renders frames of the atlases on CPU, so it could
+//! be done in a background thread while the scene is still used.
+//!
+//! # Lighting
+//!
+//! Frames are captured with neutral lighting - color atlas contains unlit albedo (diffuse texture
+//! multiplied by diffuse color), normal atlas contains normals in local coordinates of the object.
+//! Impostors are lit at runtime just like any other object in the deferred pipeline, so they match
+//! the lighting of the scene.
+//!
+//! # Transparency
+//!
+//! Meshes with [`RenderPath::Deferred`] are alpha-tested the same way as the standard shader does
+//! it. Meshes with [`RenderPath::Forward`] (or with translucent diffuse color) are considered
+//! transparent, they're blended on top of opaque surfaces from back to front, resulting coverage
+//! is stored in alpha channel of the color atlas.
+
+use crate::{
+    core::{
+        algebra::{Matrix3, Matrix4, Point3, Vector2, Vector3},
+        color::Color,
+        hash_combine,
+        math::{aabb::AxisAlignedBoundingBox, Matrix4Ext},
+        pool::Handle,
+        sstorage::ImmutableString,
+    },
+    engine::resource_manager::{ResourceManager, TextureRegistrationError},
+    material::PropertyValue,
+    resource::texture::{Texture, TextureError, TextureKind, TexturePixelKind},
+    scene::{
+        base::{BaseBuilder, LevelOfDetail, LodControlledObject, LodGroup},
+        graph::Graph,
+        impostor::{atlas_grid, Impostor, ImpostorBuilder},
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexFetchError, VertexReadTrait},
+            Mesh, RenderPath,
+        },
+        node::Node,
+    },
+    utils::{
+        hlod::{hash_floats, hash_texture, rgba8_pixels, ProgressIndicator},
+        lightmap::CancellationToken,
+    },
+};
+use std::{cmp::Ordering, f32::consts::TAU, path::Path};
+
+/// A set of parameters of impostor baking.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImpostorSettings {
+    /// Amount of directions around vertical axis from which the object is captured.
+    pub frame_count: u32,
+    /// Size (in pixels) of a single frame in the atlases.
+    pub frame_size: u32,
+    /// Normalized distance (see [`LevelOfDetail`]) at which the impostor replaces the object.
+    pub lod_distance: f32,
+    /// Whether the impostor should blend two closest frames or not.
+    pub blend_frames: bool,
+}
+
+impl Default for ImpostorSettings {
+    fn default() -> Self {
+        Self {
+            frame_count: 8,
+            frame_size: 128,
+            lod_distance: 0.3,
+            blend_frames: true,
+        }
+    }
+}
+
+impl ImpostorSettings {
+    fn hash(&self) -> u64 {
+        hash_floats(&[
+            self.frame_count as f32,
+            self.frame_size as f32,
+            self.lod_distance,
+            self.blend_frames as u32 as f32,
+        ])
+    }
+}
+
+/// An error that may occur during impostor baking.
+#[derive(Debug, thiserror::Error)]
+pub enum ImpostorBakeError {
+    /// Baking was cancelled by user.
+    #[error("Impostor baking was cancelled by the user.")]
+    Cancelled,
+    /// There are no visible meshes under the root node.
+    #[error("There is nothing to bake, the object has no visible meshes.")]
+    NoGeometry,
+    /// Vertex buffer of a mesh lacks required data.
+    #[error("Vertex buffer of a mesh lacks required data {0}.")]
+    InvalidData(VertexFetchError),
+}
+
+impl From<VertexFetchError> for ImpostorBakeError {
+    fn from(e: VertexFetchError) -> Self {
+        Self::InvalidData(e)
+    }
+}
+
+struct SourceMaterial {
+    pixels: Option<(u32, u32, Vec<u8>)>,
+    color: Color,
+    transparent: bool,
+}
+
+impl SourceMaterial {
+    fn sample(&self, tex_coord: Vector2<f32>) -> [f32; 4] {
+        let mut color = [
+            self.color.r as f32 / 255.0,
+            self.color.g as f32 / 255.0,
+            self.color.b as f32 / 255.0,
+            self.color.a as f32 / 255.0,
+        ];
+        if let Some((width, height, pixels)) = self.pixels.as_ref() {
+            // Nearest filtering with repeat wrapping.
+            let x = ((tex_coord.x.rem_euclid(1.0) * *width as f32) as u32).min(width - 1);
+            let y = ((tex_coord.y.rem_euclid(1.0) * *height as f32) as u32).min(height - 1);
+            let i = ((y * width + x) * 4) as usize;
+            for (c, p) in color.iter_mut().zip(&pixels[i..i + 4]) {
+                *c *= *p as f32 / 255.0;
+            }
+        }
+        color
+    }
+}
+
+struct SourceVertex {
+    position: Vector3<f32>,
+    normal: Vector3<f32>,
+    tex_coord: Vector2<f32>,
+}
+
+struct SourceTriangle {
+    vertices: [SourceVertex; 3],
+    material: usize,
+}
+
+/// A snapshot of meshes of an object with everything that is needed to bake an impostor. It does
+/// not borrow the graph, so the baking could be done in a background thread.
+pub struct ImpostorInputData {
+    settings: ImpostorSettings,
+    triangles: Vec<SourceTriangle>,
+    materials: Vec<SourceMaterial>,
+    meshes: Vec<Handle<Node>>,
+    bounds: AxisAlignedBoundingBox,
+    hash: u64,
+}
+
+impl ImpostorInputData {
+    /// Gathers visible meshes of the given root node and its descendants (usually it is the root
+    /// of a prefab instance) in local coordinates of the root. If the root has a LOD group, only
+    /// the most detailed level is taken into account. `filter` allows you to exclude some nodes,
+    /// it must return `true` for nodes that should be taken into account.
+    pub fn from_graph<F>(
+        graph: &mut Graph,
+        root: Handle<Node>,
+        settings: ImpostorSettings,
+        mut filter: F,
+        cancellation_token: CancellationToken,
+    ) -> Result<Self, ImpostorBakeError>
+    where
+        F: FnMut(Handle<Node>, &Node) -> bool,
+    {
+        graph.update_hierarchical_data();
+
+        let graph = &*graph;
+        let inv_root_transform = graph[root]
+            .global_transform()
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity);
+
+        let mut triangles = Vec::new();
+        let mut materials = Vec::new();
+        let mut meshes = Vec::new();
+        let mut bounds = AxisAlignedBoundingBox::default();
+        let mut hash = settings.hash();
+
+        // Less detailed levels would overlap with the most detailed one.
+        let mut coarse_levels = Vec::new();
+        if let Some(lod_group) = graph[root].lod_group() {
+            let mut levels = lod_group.levels.iter().collect::<Vec<_>>();
+            levels.sort_by(|a, b| a.begin().partial_cmp(&b.begin()).unwrap_or(Ordering::Equal));
+            for level in levels.into_iter().skip(1) {
+                coarse_levels.extend(level.objects.iter().map(|object| **object));
+            }
+        }
+
+        let mut stack = vec![root];
+        while let Some(handle) = stack.pop() {
+            if cancellation_token.is_cancelled() {
+                return Err(ImpostorBakeError::Cancelled);
+            }
+
+            let node = &graph[handle];
+            stack.extend_from_slice(node.children());
+
+            let mesh = match node.cast::<Mesh>() {
+                Some(mesh) => mesh,
+                None => continue,
+            };
+
+            if !mesh.global_visibility() || coarse_levels.contains(&handle) || !filter(handle, node)
+            {
+                continue;
+            }
+
+            meshes.push(handle);
+
+            let transform = inv_root_transform * mesh.global_transform();
+            let normal_matrix = transform
+                .basis()
+                .try_inverse()
+                .map(|m| m.transpose())
+                .unwrap_or_else(Matrix3::identity);
+
+            hash = hash_combine(hash, hash_floats(transform.as_slice()));
+
+            for surface in mesh.surfaces() {
+                let data = surface.data();
+                let data = data.lock();
+
+                let material = surface.material().lock();
+                let texture = material
+                    .property_ref(&ImmutableString::new("diffuseTexture"))
+                    .and_then(|value| value.as_sampler());
+                let color = match material.property_ref(&ImmutableString::new("diffuseColor")) {
+                    Some(PropertyValue::Color(color)) => *color,
+                    _ => Color::WHITE,
+                };
+
+                hash = hash_combine(hash, data.content_hash());
+                hash = hash_combine(hash, texture.as_ref().map_or(0, hash_texture));
+                hash = hash_combine(
+                    hash,
+                    u32::from_le_bytes([color.r, color.g, color.b, color.a]) as u64,
+                );
+
+                let material_index = materials.len();
+                materials.push(SourceMaterial {
+                    pixels: texture.as_ref().and_then(rgba8_pixels),
+                    color,
+                    transparent: mesh.render_path() == RenderPath::Forward || color.a < 255,
+                });
+
+                let mut vertices = Vec::with_capacity(data.vertex_buffer.vertex_count() as usize);
+                for view in data.vertex_buffer.iter() {
+                    let position = transform
+                        .transform_point(&Point3::from(
+                            view.read_3_f32(VertexAttributeUsage::Position)?,
+                        ))
+                        .coords;
+                    let normal = view
+                        .read_3_f32(VertexAttributeUsage::Normal)
+                        .unwrap_or_else(|_| Vector3::y());
+
+                    bounds.add_point(position);
+
+                    vertices.push(SourceVertex {
+                        position,
+                        normal: (normal_matrix * normal)
+                            .try_normalize(f32::EPSILON)
+                            .unwrap_or_else(Vector3::y),
+                        tex_coord: view
+                            .read_2_f32(VertexAttributeUsage::TexCoord0)
+                            .unwrap_or_default(),
+                    });
+                }
+
+                for triangle in data.geometry_buffer.iter() {
+                    let vertex = |i: usize| {
+                        let v = &vertices[triangle[i] as usize];
+                        SourceVertex {
+                            position: v.position,
+                            normal: v.normal,
+                            tex_coord: v.tex_coord,
+                        }
+                    };
+                    triangles.push(SourceTriangle {
+                        vertices: [vertex(0), vertex(1), vertex(2)],
+                        material: material_index,
+                    });
+                }
+            }
+        }
+
+        if triangles.is_empty() {
+            return Err(ImpostorBakeError::NoGeometry);
+        }
+
+        Ok(Self {
+            settings,
+            triangles,
+            materials,
+            meshes,
+            bounds,
+            hash,
+        })
+    }
+
+    /// Returns amount of triangles of the object.
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+}
+
+/// Result of impostor baking. Use [`ImpostorBakeResult::apply`] to add the impostor to a graph.
+pub struct ImpostorBakeResult {
+    /// Atlas with color and coverage of every frame.
+    pub atlas: Texture,
+    /// Atlas with normals of every frame in local coordinates of the object.
+    pub normal_atlas: Texture,
+    /// Amount of frames in the atlases.
+    pub frame_count: u32,
+    /// Size of the billboard.
+    pub size: Vector2<f32>,
+    /// Center of the billboard in local coordinates of the object.
+    pub center: Vector3<f32>,
+    /// Hash of the content of the object and bake settings.
+    pub hash: u64,
+    /// Meshes of the object, they're replaced with the impostor at large distances.
+    pub meshes: Vec<Handle<Node>>,
+    lod_distance: f32,
+    blend_frames: bool,
+}
+
+impl ImpostorBakeResult {
+    /// Saves the atlases into specified folder.
+    pub fn save<P: AsRef<Path>>(
+        &self,
+        base_path: P,
+        resource_manager: ResourceManager,
+    ) -> Result<(), TextureRegistrationError> {
+        if !base_path.as_ref().exists() {
+            std::fs::create_dir_all(base_path.as_ref()).map_err(TextureError::Io)?;
+        }
+
+        for (texture, kind) in [(&self.atlas, "color"), (&self.normal_atlas, "normal")] {
+            // Names are based on hashes, so they do not clash with the atlases of previous bakes.
+            let mut suffix = 0;
+            loop {
+                let file_name = if suffix == 0 {
+                    format!("{:x}_impostor_{}.png", self.hash, kind)
+                } else {
+                    format!("{:x}_impostor_{}_{}.png", self.hash, kind, suffix)
+                };
+                match resource_manager
+                    .register_texture(texture.clone(), base_path.as_ref().join(file_name))
+                {
+                    Err(TextureRegistrationError::AlreadyRegistered) => suffix += 1,
+                    result => {
+                        result?;
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates impostor node and attaches it to the given parent. The parent must be the root
+    /// node the impostor was baked for, because the impostor is in its local coordinates.
+    pub fn instantiate(&self, graph: &mut Graph, parent: Handle<Node>) -> Handle<Node> {
+        let impostor = ImpostorBuilder::new(BaseBuilder::new().with_name("Impostor"))
+            .with_atlas(self.atlas.clone())
+            .with_normal_atlas(self.normal_atlas.clone())
+            .with_frame_count(self.frame_count)
+            .with_size(self.size)
+            .with_center(self.center)
+            .with_blend_frames(self.blend_frames)
+            .build(graph);
+
+        graph.link_nodes(impostor, parent);
+
+        impostor
+    }
+
+    /// Returns LOD group of the root with the given impostor as the last level of detail. If the
+    /// root has no LOD group, a new one is created with all the meshes of the object in the first
+    /// level. Levels of other impostors are removed.
+    pub fn make_lod_group(
+        &self,
+        graph: &Graph,
+        root: Handle<Node>,
+        impostor: Handle<Node>,
+    ) -> LodGroup {
+        let mut lod_group = graph[root]
+            .lod_group()
+            .cloned()
+            .unwrap_or_else(|| LodGroup {
+                levels: vec![LevelOfDetail::new(
+                    0.0,
+                    self.lod_distance,
+                    self.meshes
+                        .iter()
+                        .map(|&mesh| LodControlledObject(mesh))
+                        .collect(),
+                )],
+            });
+        lod_group.levels.retain(|level| {
+            level.objects.iter().all(|object| {
+                graph
+                    .try_get(**object)
+                    .map_or(false, |node| node.cast::<Impostor>().is_none())
+            })
+        });
+        lod_group.add_last_level(self.lod_distance, vec![LodControlledObject(impostor)]);
+        lod_group
+    }
+
+    /// Creates impostor node under the given root and makes it the last level of detail of the
+    /// root (see [`Self::make_lod_group`]). Impostors that were previously baked for the root are
+    /// replaced. Returns handle of the impostor.
+    pub fn apply(&self, graph: &mut Graph, root: Handle<Node>) -> Handle<Node> {
+        let impostor = self.instantiate(graph, root);
+
+        let lod_group = self.make_lod_group(graph, root, impostor);
+        graph[root].set_lod_group(Some(lod_group));
+
+        for child in graph[root].children().to_vec() {
+            if child != impostor && graph[child].cast::<Impostor>().is_some() {
+                graph.remove_node(child);
+            }
+        }
+
+        impostor
+    }
+}
+
+// Orthographic projection of a frame.
+struct FrameView {
+    direction: Vector3<f32>,
+    right: Vector3<f32>,
+    center: Vector3<f32>,
+    size: Vector2<f32>,
+    frame_size: u32,
+}
+
+impl FrameView {
+    // Projects a point to pixel coordinates of the frame, z is the distance towards the viewer.
+    fn project(&self, point: Vector3<f32>) -> Vector3<f32> {
+        let local = point - self.center;
+        let frame_size = self.frame_size as f32;
+        Vector3::new(
+            (local.dot(&self.right) / self.size.x + 0.5) * frame_size,
+            (0.5 - local.y / self.size.y) * frame_size,
+            local.dot(&self.direction),
+        )
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Fragment {
+    depth: f32,
+    color: [f32; 4],
+    normal: Vector3<f32>,
+}
+
+fn edge(a: &Vector3<f32>, b: &Vector3<f32>, x: f32, y: f32) -> f32 {
+    (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+// Rasterizes a triangle, both sides of a triangle are rasterized.
+fn rasterize<F>(triangle: &SourceTriangle, view: &FrameView, mut fragment: F)
+where
+    F: FnMut(usize, f32, Vector3<f32>, Vector2<f32>),
+{
+    let [a, b, c] = [0, 1, 2].map(|i| view.project(triangle.vertices[i].position));
+    let area = edge(&a, &b, c.x, c.y);
+    if area.abs() <= f32::EPSILON {
+        return;
+    }
+
+    let frame_size = view.frame_size as f32;
+    let min_x = a.x.min(b.x).min(c.x).floor().clamp(0.0, frame_size) as usize;
+    let max_x = a.x.max(b.x).max(c.x).ceil().clamp(0.0, frame_size) as usize;
+    let min_y = a.y.min(b.y).min(c.y).floor().clamp(0.0, frame_size) as usize;
+    let max_y = a.y.max(b.y).max(c.y).ceil().clamp(0.0, frame_size) as usize;
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let w0 = edge(&b, &c, px, py) / area;
+            let w1 = edge(&c, &a, px, py) / area;
+            let w2 = edge(&a, &b, px, py) / area;
+            if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                continue;
+            }
+
+            let [v0, v1, v2] = &triangle.vertices;
+            let mut normal = (v0.normal.scale(w0) + v1.normal.scale(w1) + v2.normal.scale(w2))
+                .try_normalize(f32::EPSILON)
+                .unwrap_or(view.direction);
+            // Back faces are shown with flipped normals.
+            if normal.dot(&view.direction) < 0.0 {
+                normal = -normal;
+            }
+
+            fragment(
+                y * view.frame_size as usize + x,
+                w0 * a.z + w1 * b.z + w2 * c.z,
+                normal,
+                v0.tex_coord.scale(w0) + v1.tex_coord.scale(w1) + v2.tex_coord.scale(w2),
+            );
+        }
+    }
+}
+
+// Renders a single frame, returns colors with coverage and normals of every pixel.
+fn bake_frame(input: &ImpostorInputData, view: &FrameView) -> Vec<Fragment> {
+    let pixel_count = (view.frame_size * view.frame_size) as usize;
+    let empty = Fragment {
+        depth: f32::MIN,
+        color: [0.0; 4],
+        normal: view.direction,
+    };
+
+    // Opaque surfaces first, they're alpha-tested just like the standard shader does it.
+    let mut opaque = vec![empty; pixel_count];
+    for triangle in input.triangles.iter() {
+        let material = &input.materials[triangle.material];
+        if material.transparent {
+            continue;
+        }
+        rasterize(triangle, view, |index, depth, normal, tex_coord| {
+            let color = material.sample(tex_coord);
+            let pixel = &mut opaque[index];
+            if color[3] >= 0.5 && depth > pixel.depth {
+                *pixel = Fragment {
+                    depth,
+                    color: [color[0], color[1], color[2], 1.0],
+                    normal,
+                };
+            }
+        });
+    }
+
+    // Then transparent surfaces in front of opaque ones.
+    let mut transparent = vec![Vec::new(); pixel_count];
+    for triangle in input.triangles.iter() {
+        let material = &input.materials[triangle.material];
+        if !material.transparent {
+            continue;
+        }
+        rasterize(triangle, view, |index, depth, normal, tex_coord| {
+            let color = material.sample(tex_coord);
+            if color[3] > 0.0 && depth > opaque[index].depth {
+                transparent[index].push(Fragment {
+                    depth,
+                    color,
+                    normal,
+                });
+            }
+        });
+    }
+
+    // Blend transparent fragments from back to front.
+    for (pixel, mut fragments) in opaque.iter_mut().zip(transparent) {
+        fragments.sort_by(|a, b| a.depth.partial_cmp(&b.depth).unwrap_or(Ordering::Equal));
+        for fragment in fragments {
+            let src_alpha = fragment.color[3];
+            let dst_alpha = pixel.color[3] * (1.0 - src_alpha);
+            let alpha = src_alpha + dst_alpha;
+            for i in 0..3 {
+                pixel.color[i] =
+                    (fragment.color[i] * src_alpha + pixel.color[i] * dst_alpha) / alpha;
+            }
+            pixel.color[3] = alpha;
+            // Normals of opaque surfaces are more important than normals of thin transparent ones.
+            if dst_alpha < 0.5 {
+                pixel.normal = fragment.normal;
+            }
+            pixel.depth = fragment.depth;
+        }
+    }
+
+    dilate(&mut opaque, view.frame_size as usize);
+
+    opaque
+}
+
+// Spreads color and normals of covered pixels to empty neighbours, otherwise filtering would blend
+// covered pixels with black color at silhouettes. Coverage is not changed.
+fn dilate(pixels: &mut [Fragment], frame_size: usize) {
+    const ITERATIONS: usize = 4;
+
+    let mut filled = pixels
+        .iter()
+        .map(|pixel| pixel.color[3] > 0.0)
+        .collect::<Vec<_>>();
+
+    for _ in 0..ITERATIONS {
+        let mut new_filled = filled.clone();
+        for y in 0..frame_size {
+            for x in 0..frame_size {
+                let index = y * frame_size + x;
+                if filled[index] {
+                    continue;
+                }
+
+                let mut color = [0.0; 3];
+                let mut normal = Vector3::default();
+                let mut count = 0;
+                for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    let (nx, ny) = (x as isize + dx, y as isize + dy);
+                    if nx < 0 || ny < 0 || nx >= frame_size as isize || ny >= frame_size as isize {
+                        continue;
+                    }
+                    let neighbour = ny as usize * frame_size + nx as usize;
+                    if filled[neighbour] {
+                        for (c, n) in color.iter_mut().zip(pixels[neighbour].color) {
+                            *c += n;
+                        }
+                        normal += pixels[neighbour].normal;
+                        count += 1;
+                    }
+                }
+
+                if count > 0 {
+                    let pixel = &mut pixels[index];
+                    for (p, c) in pixel.color.iter_mut().zip(color) {
+                        *p = c / count as f32;
+                    }
+                    pixel.normal = normal.try_normalize(f32::EPSILON).unwrap_or(pixel.normal);
+                    new_filled[index] = true;
+                }
+            }
+        }
+        filled = new_filled;
+    }
+}
+
+fn to_byte(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Bakes color and normal atlases of the given snapshot. This method is blocking, use a
+/// background thread to not block the main thread.
+pub fn bake(
+    input: ImpostorInputData,
+    cancellation_token: CancellationToken,
+    progress_indicator: ProgressIndicator,
+) -> Result<ImpostorBakeResult, ImpostorBakeError> {
+    let settings = &input.settings;
+    let frame_count = settings.frame_count.max(1);
+    let frame_size = settings.frame_size.max(1);
+
+    // The billboard rotates around vertical axis that passes through the center of the bounds, so
+    // its width must fit the object from any direction.
+    let center = input.bounds.center();
+    let radius = input
+        .triangles
+        .iter()
+        .flat_map(|triangle| triangle.vertices.iter())
+        .map(|vertex| {
+            let offset = vertex.position - center;
+            Vector2::new(offset.x, offset.z).norm()
+        })
+        .fold(0.0f32, f32::max);
+    let size = Vector2::new(
+        (2.0 * radius).max(f32::EPSILON),
+        (input.bounds.max.y - input.bounds.min.y).max(f32::EPSILON),
+    );
+
+    let (columns, rows) = atlas_grid(frame_count);
+    let (width, height) = (columns * frame_size, rows * frame_size);
+    let mut atlas = vec![0u8; (width * height * 4) as usize];
+    let mut normal_atlas = vec![0u8; (width * height * 4) as usize];
+
+    progress_indicator.reset(frame_count);
+
+    for frame in 0..frame_count {
+        if cancellation_token.is_cancelled() {
+            return Err(ImpostorBakeError::Cancelled);
+        }
+
+        // Must match Impostor::frames_for_direction.
+        let angle = TAU * frame as f32 / frame_count as f32;
+        let direction = Vector3::new(angle.sin(), 0.0, angle.cos());
+        let view = FrameView {
+            direction,
+            right: Vector3::y().cross(&direction),
+            center,
+            size,
+            frame_size,
+        };
+
+        let pixels = bake_frame(&input, &view);
+
+        let (column, row) = (frame % columns, frame / columns);
+        for y in 0..frame_size {
+            for x in 0..frame_size {
+                let pixel = &pixels[(y * frame_size + x) as usize];
+                let i = (((row * frame_size + y) * width + column * frame_size + x) * 4) as usize;
+                atlas[i..i + 4].copy_from_slice(&pixel.color.map(to_byte));
+                normal_atlas[i..i + 3].copy_from_slice(
+                    &[pixel.normal.x, pixel.normal.y, pixel.normal.z]
+                        .map(|n| to_byte(n * 0.5 + 0.5)),
+                );
+                normal_atlas[i + 3] = 255;
+            }
+        }
+
+        progress_indicator.advance_progress();
+    }
+
+    let make_texture = |bytes| {
+        Texture::from_bytes(
+            TextureKind::Rectangle { width, height },
+            TexturePixelKind::RGBA8,
+            bytes,
+            false,
+        )
+        .unwrap()
+    };
+
+    Ok(ImpostorBakeResult {
+        atlas: make_texture(atlas),
+        normal_atlas: make_texture(normal_atlas),
+        frame_count,
+        size,
+        center,
+        hash: input.hash,
+        meshes: input.meshes,
+        lod_distance: settings.lod_distance,
+        blend_frames: settings.blend_frames,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector3},
+            color::Color,
+            parking_lot::Mutex,
+            pool::Handle,
+            sstorage::ImmutableString,
+        },
+        material::{Material, PropertyValue},
+        resource::texture::TextureState,
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            impostor::Impostor,
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData},
+                MeshBuilder, RenderPath,
+            },
+            node::Node,
+            pivot::PivotBuilder,
+        },
+        utils::{
+            hlod::ProgressIndicator,
+            impostor::{bake, ImpostorBakeResult, ImpostorInputData, ImpostorSettings},
+            lightmap::CancellationToken,
+        },
+    };
+    use std::sync::Arc;
+
+    const FRAME_SIZE: u32 = 16;
+
+    fn make_mesh(
+        graph: &mut Graph,
+        data: SurfaceData,
+        color: Color,
+        render_path: RenderPath,
+    ) -> Handle<Node> {
+        let mut material = Material::standard();
+        material
+            .set_property(
+                &ImmutableString::new("diffuseColor"),
+                PropertyValue::Color(color),
+            )
+            .unwrap();
+        MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(data)))
+                .with_material(Arc::new(Mutex::new(material)))
+                .build()])
+            .with_render_path(render_path)
+            .build(graph)
+    }
+
+    fn bake_object(graph: &mut Graph, root: Handle<Node>) -> ImpostorBakeResult {
+        let settings = ImpostorSettings {
+            frame_count: 8,
+            frame_size: FRAME_SIZE,
+            ..Default::default()
+        };
+        let input = ImpostorInputData::from_graph(
+            graph,
+            root,
+            settings,
+            |_, _| true,
+            CancellationToken::new(),
+        )
+        .unwrap();
+        bake(input, CancellationToken::new(), ProgressIndicator::new()).unwrap()
+    }
+
+    // Returns RGBA8 pixel of the given frame of the given atlas.
+    fn pixel(result: &ImpostorBakeResult, normals: bool, frame: u32, x: u32, y: u32) -> [u8; 4] {
+        let texture = if normals {
+            &result.normal_atlas
+        } else {
+            &result.atlas
+        };
+        let state = texture.state();
+        if let TextureState::Ok(data) = &*state {
+            let width = 3 * FRAME_SIZE;
+            let (column, row) = (frame % 3, frame / 3);
+            let i = (((row * FRAME_SIZE + y) * width + column * FRAME_SIZE + x) * 4) as usize;
+            let bytes = data.data();
+            [bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]
+        } else {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn test_bake_cube() {
+        let mut graph = Graph::new();
+        let cube = make_mesh(
+            &mut graph,
+            SurfaceData::make_cube(Matrix4::new_nonuniform_scaling(&Vector3::new(
+                1.0, 2.0, 1.0,
+            ))),
+            Color::opaque(255, 0, 0),
+            RenderPath::Deferred,
+        );
+        let root = PivotBuilder::new(BaseBuilder::new().with_children(&[cube])).build(&mut graph);
+
+        let result = bake_object(&mut graph, root);
+
+        // 8 frames are packed in 3x3 grid.
+        let state = result.atlas.state();
+        if let TextureState::Ok(data) = &*state {
+            assert_eq!(
+                data.data().len(),
+                (9 * FRAME_SIZE * FRAME_SIZE * 4) as usize
+            );
+        }
+        drop(state);
+
+        assert!((result.size.x - 2.0f32.sqrt()).abs() < 1.0e-4);
+        assert!((result.size.y - 2.0).abs() < 1.0e-4);
+        assert_eq!(result.meshes, vec![cube]);
+
+        let middle = FRAME_SIZE / 2;
+        for frame in 0..8 {
+            assert_eq!(
+                pixel(&result, false, frame, middle, middle),
+                [255, 0, 0, 255]
+            );
+        }
+
+        // The first frame shows the front face of the cube, its normal is +Z.
+        let normal = pixel(&result, true, 0, middle, middle);
+        assert!((normal[0] as i32 - 128).abs() <= 1 && (normal[1] as i32 - 128).abs() <= 1);
+        assert_eq!(normal[2], 255);
+
+        // The front face covers only the middle of the frame, the diagonal view (frame 1) covers
+        // the whole width.
+        assert_eq!(pixel(&result, false, 0, 0, middle)[3], 0);
+        assert_eq!(pixel(&result, false, 1, 0, middle)[3], 255);
+    }
+
+    #[test]
+    fn test_bake_transparent() {
+        let mut graph = Graph::new();
+        // Opaque red cube behind translucent blue quad.
+        let cube = make_mesh(
+            &mut graph,
+            SurfaceData::make_cube(Matrix4::identity()),
+            Color::opaque(255, 0, 0),
+            RenderPath::Deferred,
+        );
+        let glass = make_mesh(
+            &mut graph,
+            SurfaceData::make_quad(&Matrix4::new_translation(&Vector3::new(0.0, 0.0, 0.6))),
+            Color::from_rgba(0, 0, 255, 128),
+            RenderPath::Forward,
+        );
+        // Translucent quad that does not cover anything.
+        let veil = make_mesh(
+            &mut graph,
+            SurfaceData::make_quad(&Matrix4::new_translation(&Vector3::new(0.0, 2.0, 0.0))),
+            Color::from_rgba(0, 255, 0, 64),
+            RenderPath::Forward,
+        );
+        let root = PivotBuilder::new(BaseBuilder::new().with_children(&[cube, glass, veil]))
+            .build(&mut graph);
+
+        let result = bake_object(&mut graph, root);
+
+        // Bounds are [-0.5; 2.5] on Y, so the cube is in the bottom third of the frame and the
+        // veil is in the top third.
+        let cube_pixel = pixel(&result, false, 0, FRAME_SIZE / 2, FRAME_SIZE * 5 / 6);
+        assert_eq!(cube_pixel[3], 255);
+        assert!((cube_pixel[0] as i32 - 127).abs() <= 1);
+        assert!((cube_pixel[2] as i32 - 128).abs() <= 1);
+
+        let veil_pixel = pixel(&result, false, 0, FRAME_SIZE / 2, FRAME_SIZE / 6);
+        assert_eq!(veil_pixel[3], 64);
+        assert_eq!(veil_pixel[1], 255);
+    }
+
+    #[test]
+    fn test_apply_adds_last_lod_level() {
+        let mut graph = Graph::new();
+        let cube = make_mesh(
+            &mut graph,
+            SurfaceData::make_cube(Matrix4::identity()),
+            Color::WHITE,
+            RenderPath::Deferred,
+        );
+        let root = PivotBuilder::new(BaseBuilder::new().with_children(&[cube])).build(&mut graph);
+
+        let result = bake_object(&mut graph, root);
+        let impostor = result.apply(&mut graph, root);
+
+        assert!(graph[impostor].cast::<Impostor>().is_some());
+        assert_eq!(graph[impostor].parent(), root);
+
+        let levels = &graph[root].lod_group().unwrap().levels;
+        assert_eq!(levels.len(), 2);
+        assert_eq!(*levels[0].objects[0], cube);
+        assert_eq!(levels[0].end(), levels[1].begin());
+        assert_eq!(*levels[1].objects[0], impostor);
+        assert_eq!(levels[1].end(), 1.0);
+
+        // Baking again replaces the impostor.
+        let new_impostor = result.apply(&mut graph, root);
+        assert!(!graph.is_valid_handle(impostor));
+        let levels = &graph[root].lod_group().unwrap().levels;
+        assert_eq!(levels.len(), 2);
+        assert_eq!(*levels[1].objects[0], new_impostor);
+    }
+
+    #[test]
+    fn test_bake_cancellation() {
+        let mut graph = Graph::new();
+        let cube = make_mesh(
+            &mut graph,
+            SurfaceData::make_cube(Matrix4::identity()),
+            Color::WHITE,
+            RenderPath::Deferred,
+        );
+
+        let input = ImpostorInputData::from_graph(
+            &mut graph,
+            cube,
+            Default::default(),
+            |_, _| true,
+            CancellationToken::new(),
+        )
+        .unwrap();
+        let token = CancellationToken::new();
+        token.0.store(true, std::sync::atomic::Ordering::SeqCst);
+        assert!(bake(input, token, ProgressIndicator::new()).is_err());
+
+        // Nothing to bake.
+        let pivot = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        assert!(ImpostorInputData::from_graph(
+            &mut graph,
+            pivot,
+            Default::default(),
+            |_, _| true,
+            CancellationToken::new(),
+        )
+        .is_err());
+    }
+}
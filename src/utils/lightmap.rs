@@ -2,10 +2,9 @@
 //!
 //! # Performance
 //!
-//! This is CPU lightmapper, its performance is linear with core count of your CPU.
-//!
-//! WARNING: There is still work-in-progress, so it is not advised to use lightmapper
-//! now!
+//! This is CPU lightmapper, its performance is linear with core count of your CPU. Generation
+//! is incremental - lightmaps of the meshes that weren't affected by changes in the scene are
+//! reused (see [`Lightmap::from_input`]).
 
 #![forbid(unsafe_code)]
 
@@ -18,15 +17,22 @@ use crate::{
     core::{
         algebra::{Matrix3, Matrix4, Point3, Vector2, Vector3, Vector4},
         arrayvec::ArrayVec,
-        math::{self, ray::Ray, Matrix4Ext, Rect, TriangleDefinition, Vector2Ext},
+        hash_combine,
+        math::{
+            self, aabb::AxisAlignedBoundingBox, ray::Ray, Matrix4Ext, Rect, TriangleDefinition,
+            Vector2Ext,
+        },
         octree::{Octree, OctreeNode},
         parking_lot::Mutex,
         pool::Handle,
         visitor::{Visit, VisitResult, Visitor},
     },
     engine::resource_manager::{ResourceManager, TextureRegistrationError},
-    resource::texture::{Texture, TextureData, TextureKind, TexturePixelKind, TextureState},
+    resource::texture::{
+        Texture, TextureData, TextureError, TextureKind, TexturePixelKind, TextureState,
+    },
     scene::{
+        base::Mobility,
         mesh::{
             buffer::{VertexAttributeUsage, VertexFetchError, VertexReadTrait},
             surface::SurfaceData,
@@ -36,9 +42,11 @@ use crate::{
     },
     utils::{uvgen, uvgen::SurfaceDataPatch},
 };
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHasher};
 use rayon::prelude::*;
 use std::{
+    collections::hash_map::Entry,
+    hash::Hasher,
     ops::Deref,
     path::Path,
     sync::{
@@ -60,6 +68,10 @@ pub struct LightmapEntry {
     /// masking when applying dynamic lights for surfaces with light, it prevents double
     /// lighting.
     pub lights: Vec<Handle<Node>>,
+    /// A hash of everything that had an effect on the lightmap at the moment of generation. It
+    /// is used to decide whether the lightmap must be regenerated.
+    #[visit(optional)] // Backward compatibility
+    pub hash: u64,
 }
 
 /// Lightmap is a texture with precomputed lighting.
@@ -72,6 +84,11 @@ pub struct Lightmap {
     /// List of surface data patches. Each patch will be applied to corresponding
     /// surface data on resolve stage.
     pub patches: FxHashMap<u64, SurfaceDataPatch>,
+
+    /// List of lights that were baked into the lightmap. The renderer does not apply these
+    /// lights dynamically.
+    #[visit(optional)] // Backward compatibility
+    pub baked_lights: Vec<Handle<Node>>,
 }
 
 struct WorldVertex {
@@ -216,49 +233,68 @@ impl From<VertexFetchError> for LightmapGenerationError {
     }
 }
 
-impl Lightmap {
-    /// Generates lightmap for given scene. This method **automatically** generates secondary
-    /// texture coordinates! This method is blocking, however internally it uses massive parallelism
-    /// to use all available CPU power efficiently.
-    ///
-    /// `texels_per_unit` defines resolution of lightmap, the higher value is, the more quality
-    /// lightmap will be generated, but also it will be slow to generate.
-    /// `progress_indicator` allows you to get info about current progress.
-    /// `cancellation_token` allows you to stop generation in any time.
-    pub fn new(
+/// A snapshot of a scene with everything that is needed to generate a lightmap. It does not
+/// borrow the scene, so the generation could be done in a background thread while the scene
+/// is still used (see [`Lightmap::from_input`]).
+///
+/// Only lights with [`Mobility::Static`] are baked, and only meshes with [`Mobility::Static`]
+/// or [`Mobility::Stationary`] receive baked light and cast shadows. Invisible nodes are
+/// ignored.
+pub struct LightmapInputData {
+    lights: Vec<LightDefinition>,
+    instances: Vec<Instance>,
+    // Hashes of everything that has an effect on the lightmap of each mesh.
+    hashes: FxHashMap<Handle<Node>, u64>,
+    // Lightmap of the scene at the moment of the snapshot, it is used for incremental generation.
+    previous: Option<Lightmap>,
+}
+
+struct MeshInfo {
+    handle: Handle<Node>,
+    bounds: AxisAlignedBoundingBox,
+    hash: u64,
+}
+
+impl LightmapInputData {
+    /// Gathers lights and meshes from the given scene. Surface data of meshes is copied, so the
+    /// scene could be freely modified while the lightmap is generating. `filter` allows you to
+    /// exclude some nodes from the lightmap, it must return `true` for nodes that should be
+    /// taken into account.
+    pub fn from_scene<F>(
         scene: &mut Scene,
-        texels_per_unit: u32,
+        mut filter: F,
         cancellation_token: CancellationToken,
         progress_indicator: ProgressIndicator,
-    ) -> Result<Self, LightmapGenerationError> {
+    ) -> Result<Self, LightmapGenerationError>
+    where
+        F: FnMut(Handle<Node>, &Node) -> bool,
+    {
         scene.graph.update_hierarchical_data();
 
-        // Extract info about lights first. We need it to be in separate array because
-        // it won't be possible to store immutable references to light sources and at the
-        // same time modify meshes. Also it precomputes a lot of things for faster calculations.
-        let mut light_count = 0;
-        for node in scene.graph.linear_iter() {
-            if node.cast::<PointLight>().is_some()
-                || node.cast::<SpotLight>().is_some()
-                || node.cast::<DirectionalLight>().is_some()
-            {
-                light_count += 1;
-            }
-        }
+        // Extract info about lights first. It also precomputes a lot of things for faster
+        // calculations.
+        let light_handles = scene
+            .graph
+            .pair_iter()
+            .filter(|(handle, node)| is_baked_light(node) && filter(*handle, node))
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
 
-        progress_indicator.set_stage(ProgressStage::LightsCaching, light_count);
+        progress_indicator.set_stage(ProgressStage::LightsCaching, light_handles.len() as u32);
 
-        let mut lights = Vec::with_capacity(light_count as usize);
+        let mut lights = Vec::with_capacity(light_handles.len());
 
-        for (handle, light) in scene.graph.pair_iter() {
+        for handle in light_handles {
             if cancellation_token.is_cancelled() {
                 return Err(LightmapGenerationError::Cancelled);
             }
 
+            let light = &scene.graph[handle];
+
             if let Some(point) = light.cast::<PointLight>() {
                 lights.push(LightDefinition::Point(PointLightDefinition {
                     handle,
-                    intensity: 1.0,
+                    intensity: point.base_light_ref().intensity(),
                     position: light.global_position(),
                     color: point.base_light_ref().color().srgb_to_linear().as_frgb(),
                     radius: point.radius(),
@@ -267,7 +303,7 @@ impl Lightmap {
             } else if let Some(spot) = light.cast::<SpotLight>() {
                 lights.push(LightDefinition::Spot(SpotLightDefinition {
                     handle,
-                    intensity: 1.0,
+                    intensity: spot.base_light_ref().intensity(),
                     edge0: ((spot.hotspot_cone_angle() + spot.falloff_angle_delta()) * 0.5).cos(),
                     edge1: (spot.hotspot_cone_angle() * 0.5).cos(),
                     color: spot.base_light_ref().color().srgb_to_linear().as_frgb(),
@@ -282,7 +318,7 @@ impl Lightmap {
             } else if let Some(directional) = light.cast::<DirectionalLight>() {
                 lights.push(LightDefinition::Directional(DirectionalLightDefinition {
                     handle,
-                    intensity: 1.0,
+                    intensity: directional.base_light_ref().intensity(),
                     direction: light
                         .up_vector()
                         .try_normalize(std::f32::EPSILON)
@@ -293,57 +329,227 @@ impl Lightmap {
                         .srgb_to_linear()
                         .as_frgb(),
                 }))
-            } else {
-                continue;
-            };
+            }
 
             progress_indicator.advance_progress()
         }
 
         let mut instances = Vec::new();
+        let mut meshes = Vec::new();
+        // Surface data could be shared across multiple meshes, so we copy every unique data only
+        // once.
         let mut data_set = FxHashMap::default();
 
         for (handle, node) in scene.graph.pair_iter() {
             if let Some(mesh) = node.cast::<Mesh>() {
-                if !mesh.global_visibility() {
+                if !mesh.global_visibility()
+                    || mesh.mobility() == Mobility::Dynamic
+                    || !filter(handle, node)
+                {
                     continue;
                 }
+
                 let global_transform = mesh.global_transform();
+                let mut info = MeshInfo {
+                    handle,
+                    bounds: AxisAlignedBoundingBox::default(),
+                    hash: hash_floats(global_transform.as_slice()),
+                };
+
                 for surface in mesh.surfaces() {
-                    // Gather unique "list" of surface data to generate UVs for.
                     let data = surface.data();
-                    let key = &*data.lock() as *const _ as u64;
-                    data_set.entry(key).or_insert_with(|| surface.data());
+                    let key = &*data as *const _ as u64;
+                    let (copy, geometry_hash, local_bounds) = match data_set.entry(key) {
+                        Entry::Occupied(entry) => entry.into_mut(),
+                        Entry::Vacant(entry) => {
+                            let data = data.lock();
+                            let (geometry_hash, local_bounds) = hash_geometry(&data)?;
+                            entry.insert((
+                                Arc::new(Mutex::new(data.clone())),
+                                geometry_hash,
+                                local_bounds,
+                            ))
+                        }
+                    };
+
+                    info.hash = hash_combine(info.hash, *geometry_hash);
+                    info.bounds
+                        .add_box(local_bounds.transform(&global_transform));
 
                     instances.push(Instance {
                         owner: handle,
-                        source_data: data.clone(),
+                        source_data: copy.clone(),
                         transform: global_transform,
-                        // Calculated down below.
+                        // Calculated in `Lightmap::from_input`.
                         data: None,
                     });
                 }
+
+                meshes.push(info);
             }
         }
 
+        let hashes = meshes
+            .iter()
+            .map(|mesh| {
+                let mut hash = mesh.hash;
+                // Shadows could be cast by the meshes between the mesh and the lights.
+                let mut shadow_bounds = mesh.bounds;
+                let mut has_directional = false;
+                for light in lights.iter().filter(|light| light.affects(&mesh.bounds)) {
+                    hash = hash_combine(hash, light.hash());
+                    match light {
+                        LightDefinition::Directional(_) => has_directional = true,
+                        LightDefinition::Spot(spot) => shadow_bounds.add_point(spot.position),
+                        LightDefinition::Point(point) => shadow_bounds.add_point(point.position),
+                    }
+                }
+                for other in meshes.iter() {
+                    if other.handle != mesh.handle
+                        && (has_directional || other.bounds.intersect_aabb(&shadow_bounds))
+                    {
+                        hash = hash_combine(hash, other.hash);
+                    }
+                }
+                (mesh.handle, hash)
+            })
+            .collect();
+
+        Ok(Self {
+            lights,
+            instances,
+            hashes,
+            previous: scene.lightmap().cloned(),
+        })
+    }
+}
+
+impl Lightmap {
+    /// Generates lightmap for given scene. This method **automatically** generates secondary
+    /// texture coordinates for meshes that does not have them! This method is blocking, however
+    /// internally it uses massive parallelism to use all available CPU power efficiently. Use
+    /// [`LightmapInputData::from_scene`] and [`Lightmap::from_input`] if you need to generate a
+    /// lightmap in background.
+    ///
+    /// `texels_per_unit` defines resolution of lightmap, the higher value is, the more quality
+    /// lightmap will be generated, but also it will be slow to generate.
+    /// `progress_indicator` allows you to get info about current progress.
+    /// `cancellation_token` allows you to stop generation in any time.
+    ///
+    /// Generated lightmap must be applied to the scene using [`Scene::set_lightmap`].
+    pub fn new(
+        scene: &mut Scene,
+        texels_per_unit: u32,
+        cancellation_token: CancellationToken,
+        progress_indicator: ProgressIndicator,
+    ) -> Result<Self, LightmapGenerationError> {
+        let input = LightmapInputData::from_scene(
+            scene,
+            |_, _| true,
+            cancellation_token.clone(),
+            progress_indicator.clone(),
+        )?;
+        Self::from_input(
+            input,
+            texels_per_unit,
+            0.005,
+            cancellation_token,
+            progress_indicator,
+        )
+    }
+
+    /// Generates lightmap using the data gathered from a scene. The generation is incremental:
+    /// if the scene had a lightmap at the moment of the snapshot, lightmaps of the meshes whose
+    /// geometry, affecting lights, potential shadow casters and generation settings are the same
+    /// will be reused.
+    ///
+    /// `spacing` defines a gap between the charts of generated secondary texture coordinates, it
+    /// prevents light bleeding between the charts. See [`Lightmap::new`] for other arguments.
+    pub fn from_input(
+        input: LightmapInputData,
+        texels_per_unit: u32,
+        spacing: f32,
+        cancellation_token: CancellationToken,
+        progress_indicator: ProgressIndicator,
+    ) -> Result<Self, LightmapGenerationError> {
+        let LightmapInputData {
+            lights,
+            mut instances,
+            hashes,
+            previous,
+        } = input;
+
+        let baked_lights = lights
+            .iter()
+            .map(|light| light.handle())
+            .collect::<Vec<_>>();
+        let settings_hash = hash_combine(texels_per_unit as u64, spacing.to_bits() as u64);
+        let entry_hash = |owner: &Handle<Node>| hash_combine(hashes[owner], settings_hash);
+
+        let mut map: FxHashMap<Handle<Node>, Vec<LightmapEntry>> = FxHashMap::default();
+        let mut patches = FxHashMap::default();
+
+        if let Some(previous) = previous {
+            // Keep old patches, they're still needed for the meshes that weren't changed.
+            patches = previous.patches;
+
+            for (owner, mut entries) in previous.map {
+                if !hashes.contains_key(&owner) {
+                    continue;
+                }
+                let mut surface_count = 0;
+                let mut has_second_tex_coords = true;
+                for instance in instances.iter().filter(|i| i.owner == owner) {
+                    surface_count += 1;
+                    has_second_tex_coords &=
+                        has_second_tex_coords_set(&instance.source_data.lock());
+                }
+                let hash = entry_hash(&owner);
+                if has_second_tex_coords
+                    && entries.len() == surface_count
+                    && entries
+                        .iter()
+                        .all(|entry| entry.hash == hash && entry.texture.is_some())
+                {
+                    for entry in entries.iter_mut() {
+                        entry.lights = baked_lights.clone();
+                    }
+                    map.insert(owner, entries);
+                }
+            }
+        }
+
+        // Gather unique "list" of surface data to generate UVs for.
+        let mut data_set = FxHashMap::default();
+        for instance in instances.iter() {
+            let key = &*instance.source_data as *const _ as u64;
+            data_set
+                .entry(key)
+                .or_insert_with(|| instance.source_data.clone());
+        }
+        data_set.retain(|_, data| !has_second_tex_coords_set(&data.lock()));
+
         progress_indicator.set_stage(ProgressStage::UvGeneration, data_set.len() as u32);
 
-        let patches = data_set
+        let new_patches = data_set
             .into_par_iter()
             .map(|(_, data)| {
                 if cancellation_token.is_cancelled() {
                     Err(LightmapGenerationError::Cancelled)
                 } else {
                     let mut data = data.lock();
-                    let patch = uvgen::generate_uvs(&mut data, 0.005)?;
+                    let patch = uvgen::generate_uvs(&mut data, spacing)?;
                     progress_indicator.advance_progress();
                     Ok((patch.data_id, patch))
                 }
             })
-            .collect::<Result<FxHashMap<_, _>, LightmapGenerationError>>()?;
+            .collect::<Result<Vec<_>, LightmapGenerationError>>()?;
+        patches.extend(new_patches);
 
         progress_indicator.set_stage(ProgressStage::GeometryCaching, instances.len() as u32);
 
+        // Every mesh could cast shadows, so the geometry of all of them is needed even if their
+        // own lightmaps are reused.
         instances
             .par_iter_mut()
             .map(|instance: &mut Instance| {
@@ -408,48 +614,170 @@ impl Lightmap {
             })
             .collect::<Result<(), LightmapGenerationError>>()?;
 
-        progress_indicator.set_stage(ProgressStage::CalculatingLight, instances.len() as u32);
+        let dirty_instances = instances
+            .iter()
+            .filter(|instance| !map.contains_key(&instance.owner))
+            .collect::<Vec<_>>();
 
-        let mut map: FxHashMap<Handle<Node>, Vec<LightmapEntry>> = FxHashMap::default();
-        for instance in instances.iter() {
-            if cancellation_token.is_cancelled() {
-                return Err(LightmapGenerationError::Cancelled);
-            }
+        progress_indicator.set_stage(
+            ProgressStage::CalculatingLight,
+            dirty_instances.len() as u32,
+        );
 
-            let lightmap = generate_lightmap(instance, &instances, &lights, texels_per_unit);
-            map.entry(instance.owner).or_default().push(LightmapEntry {
-                texture: Some(Texture(Resource::new(TextureState::Ok(lightmap)))),
-                lights: lights.iter().map(|light| light.handle()).collect(),
-            });
+        // Order of the results is preserved, so the entries will be in the same order as the
+        // surfaces of the meshes.
+        let new_entries = dirty_instances
+            .into_par_iter()
+            .map(|instance| -> Result<_, LightmapGenerationError> {
+                let lightmap = generate_lightmap(
+                    instance,
+                    &instances,
+                    &lights,
+                    texels_per_unit,
+                    &cancellation_token,
+                )
+                .ok_or(LightmapGenerationError::Cancelled)?;
+
+                progress_indicator.advance_progress();
+
+                Ok((
+                    instance.owner,
+                    LightmapEntry {
+                        texture: Some(Texture(Resource::new(TextureState::Ok(lightmap)))),
+                        lights: baked_lights.clone(),
+                        hash: entry_hash(&instance.owner),
+                    },
+                ))
+            })
+            .collect::<Result<Vec<_>, LightmapGenerationError>>()?;
 
-            progress_indicator.advance_progress();
+        for (owner, entry) in new_entries {
+            map.entry(owner).or_default().push(entry);
         }
 
-        Ok(Self { map, patches })
+        Ok(Self {
+            map,
+            patches,
+            baked_lights,
+        })
+    }
+
+    /// Returns `true` if the given light is baked into the lightmap. Such lights are not applied
+    /// dynamically by the renderer.
+    pub fn is_baked_light(&self, light: Handle<Node>) -> bool {
+        self.baked_lights.contains(&light)
     }
 
-    /// Saves lightmap textures into specified folder.
+    /// Saves lightmap textures into specified folder. Textures that are already saved (for
+    /// example the ones reused by incremental generation) are left as is.
     pub fn save<P: AsRef<Path>>(
         &self,
         base_path: P,
         resource_manager: ResourceManager,
     ) -> Result<(), TextureRegistrationError> {
         if !base_path.as_ref().exists() {
-            std::fs::create_dir(base_path.as_ref()).unwrap();
+            std::fs::create_dir_all(base_path.as_ref()).map_err(TextureError::Io)?;
         }
 
-        for (handle, entries) in self.map.iter() {
-            let handle_path = handle.index().to_string();
+        for entries in self.map.values() {
             for (i, entry) in entries.iter().enumerate() {
-                let file_path = handle_path.clone() + "_" + i.to_string().as_str() + ".png";
                 let texture = entry.texture.clone().unwrap();
-                resource_manager.register_texture(texture, base_path.as_ref().join(file_path))?;
+                if !texture.state().path().as_os_str().is_empty() {
+                    continue;
+                }
+
+                // Names are based on hashes, so they do not clash with the textures of the
+                // previous lightmaps.
+                let mut suffix = 0;
+                loop {
+                    let file_name = if suffix == 0 {
+                        format!("{:x}_{}.png", entry.hash, i)
+                    } else {
+                        format!("{:x}_{}_{}.png", entry.hash, i, suffix)
+                    };
+                    match resource_manager
+                        .register_texture(texture.clone(), base_path.as_ref().join(file_name))
+                    {
+                        Err(TextureRegistrationError::AlreadyRegistered) => suffix += 1,
+                        result => {
+                            result?;
+                            break;
+                        }
+                    }
+                }
             }
         }
         Ok(())
     }
 }
 
+fn is_baked_light(node: &Node) -> bool {
+    node.mobility() == Mobility::Static
+        && node.global_visibility()
+        && (node.cast::<PointLight>().is_some()
+            || node.cast::<SpotLight>().is_some()
+            || node.cast::<DirectionalLight>().is_some())
+}
+
+/// Checks whether surface data has usable secondary texture coordinates. Some vertex formats
+/// have the attribute, but it is filled with zeros.
+fn has_second_tex_coords_set(data: &SurfaceData) -> bool {
+    if !data
+        .vertex_buffer
+        .has_attribute(VertexAttributeUsage::TexCoord1)
+    {
+        return false;
+    }
+
+    let fetch = |index: u32| {
+        data.vertex_buffer
+            .get(index as usize)
+            .and_then(|view| view.read_2_f32(VertexAttributeUsage::TexCoord1).ok())
+    };
+
+    data.geometry_buffer.iter().any(|triangle| {
+        match (fetch(triangle[0]), fetch(triangle[1]), fetch(triangle[2])) {
+            (Some(a), Some(b), Some(c)) => {
+                let ab = b - a;
+                let ac = c - a;
+                (ab.x * ac.y - ab.y * ac.x).abs() > f32::EPSILON
+            }
+            _ => false,
+        }
+    })
+}
+
+fn hash_floats(values: &[f32]) -> u64 {
+    let mut hasher = FxHasher::default();
+    for value in values {
+        hasher.write_u32(value.to_bits());
+    }
+    hasher.finish()
+}
+
+/// Calculates a hash of triangles of surface data and its bounds. The hash does not depend on
+/// the layout of the vertex buffer, so it stays the same after the surface data is patched with
+/// the second texture coordinates.
+fn hash_geometry(data: &SurfaceData) -> Result<(u64, AxisAlignedBoundingBox), VertexFetchError> {
+    let mut hasher = FxHasher::default();
+    let mut bounds = AxisAlignedBoundingBox::default();
+    for triangle in data.geometry_buffer.iter() {
+        for &index in triangle.indices() {
+            let view = match data.vertex_buffer.get(index as usize) {
+                Some(view) => view,
+                None => continue,
+            };
+            let position = view.read_3_f32(VertexAttributeUsage::Position)?;
+            let normal = view.read_3_f32(VertexAttributeUsage::Normal)?;
+            for value in position.iter().chain(normal.iter()) {
+                hasher.write_u32(value.to_bits());
+            }
+            bounds.add_point(position);
+        }
+    }
+    Ok((hasher.finish(), bounds))
+}
+
 /// Directional light is a light source with parallel rays. Example: Sun.
 pub struct DirectionalLightDefinition {
     /// A handle of light in the scene.
@@ -518,8 +846,72 @@ impl LightDefinition {
             LightDefinition::Point(v) => v.handle,
         }
     }
+
+    /// Checks whether the light can lit anything inside the given bounds.
+    fn affects(&self, bounds: &AxisAlignedBoundingBox) -> bool {
+        match self {
+            LightDefinition::Directional(_) => true,
+            LightDefinition::Spot(v) => bounds.is_intersects_sphere(v.position, v.distance),
+            LightDefinition::Point(v) => bounds.is_intersects_sphere(v.position, v.radius),
+        }
+    }
+
+    fn hash(&self) -> u64 {
+        let (handle, hash) = match self {
+            LightDefinition::Directional(v) => (
+                v.handle,
+                hash_floats(&[
+                    v.intensity,
+                    v.direction.x,
+                    v.direction.y,
+                    v.direction.z,
+                    v.color.x,
+                    v.color.y,
+                    v.color.z,
+                ]),
+            ),
+            LightDefinition::Spot(v) => (
+                v.handle,
+                hash_floats(&[
+                    v.intensity,
+                    v.color.x,
+                    v.color.y,
+                    v.color.z,
+                    v.direction.x,
+                    v.direction.y,
+                    v.direction.z,
+                    v.position.x,
+                    v.position.y,
+                    v.position.z,
+                    v.distance,
+                    v.edge0,
+                    v.edge1,
+                ]),
+            ),
+            LightDefinition::Point(v) => (
+                v.handle,
+                hash_floats(&[
+                    v.intensity,
+                    v.position.x,
+                    v.position.y,
+                    v.position.z,
+                    v.color.x,
+                    v.color.y,
+                    v.color.z,
+                    v.radius,
+                ]),
+            ),
+        };
+        hash_combine(
+            ((handle.index() as u64) << 32) | handle.generation() as u64,
+            hash,
+        )
+    }
 }
 
+/// Distance at which directional light shadow rays start.
+const DIRECTIONAL_SHADOW_DISTANCE: f32 = 1000.0;
+
 /// Computes total area of triangles in surface data and returns size of square
 /// in which triangles can fit.
 fn estimate_size(data: &InstanceData, texels_per_unit: u32) -> u32 {
@@ -530,7 +922,7 @@ fn estimate_size(data: &InstanceData, texels_per_unit: u32) -> u32 {
         let c = data.vertices[triangle[2] as usize].world_position;
         area += math::triangle_area(a, b, c);
     }
-    area.sqrt().ceil() as u32 * texels_per_unit
+    (area.sqrt().ceil() as u32 * texels_per_unit).max(4)
 }
 
 /// Calculates distance attenuation for a point using given distance to the point and
@@ -663,12 +1055,16 @@ fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
 /// This method is has linear complexity - the more complex mesh you pass, the more
 /// time it will take. Required time increases drastically if you enable shadows and
 /// global illumination (TODO), because in this case your data will be raytraced.
+///
+/// Rows of the lightmap are processed in parallel. Returns `None` if the generation was
+/// cancelled.
 fn generate_lightmap(
     instance: &Instance,
     other_instances: &[Instance],
     lights: &[LightDefinition],
     texels_per_unit: u32,
-) -> TextureData {
+    cancellation_token: &CancellationToken,
+) -> Option<TextureData> {
     // We have to re-generate new set of world-space vertices because UV generator
     // may add new vertices on seams.
     let atlas_size = estimate_size(instance.data(), texels_per_unit);
@@ -680,97 +1076,113 @@ fn generate_lightmap(
 
     let half_pixel = scale * 0.5;
     pixels
-        .par_iter_mut()
+        .par_chunks_mut(atlas_size as usize)
         .enumerate()
-        .for_each(|(i, pixel): (usize, &mut Vector4<u8>)| {
-            let x = i as u32 % atlas_size;
-            let y = i as u32 / atlas_size;
-
-            let uv = Vector2::new(x as f32 * scale + half_pixel, y as f32 * scale + half_pixel);
-
-            if let Some((world_position, world_normal)) = pick(uv, &grid, instance.data(), scale) {
-                let mut pixel_color = Vector3::default();
-                for light in lights {
-                    let (light_color, mut attenuation, light_position) = match light {
-                        LightDefinition::Directional(directional) => {
-                            let attenuation = directional.intensity
-                                * lambertian(directional.direction, world_normal);
-                            (directional.color, attenuation, Vector3::default())
-                        }
-                        LightDefinition::Spot(spot) => {
-                            let d = spot.position - world_position;
-                            let distance = d.norm();
-                            let light_vec = d.scale(1.0 / distance);
-                            let spot_angle_cos = light_vec.dot(&spot.direction);
-                            let cone_factor = smoothstep(spot.edge0, spot.edge1, spot_angle_cos);
-                            let attenuation = cone_factor
-                                * spot.intensity
-                                * lambertian(light_vec, world_normal)
-                                * distance_attenuation(distance, spot.sqr_distance);
-                            (spot.color, attenuation, spot.position)
-                        }
-                        LightDefinition::Point(point) => {
-                            let d = point.position - world_position;
-                            let distance = d.norm();
-                            let light_vec = d.scale(1.0 / distance);
-                            let attenuation = point.intensity
-                                * lambertian(light_vec, world_normal)
-                                * distance_attenuation(distance, point.sqr_radius);
-                            (point.color, attenuation, point.position)
-                        }
-                    };
-                    // Shadows
-                    if attenuation >= 0.01 {
-                        let mut query_buffer = ArrayVec::<Handle<OctreeNode>, 64>::new();
-                        let shadow_bias = 0.01;
-                        let ray = Ray::from_two_points(light_position, world_position);
-                        'outer_loop: for other_instance in other_instances {
-                            other_instance
-                                .data()
-                                .octree
-                                .ray_query_static(&ray, &mut query_buffer);
-                            for &node in query_buffer.iter() {
-                                match other_instance.data().octree.node(node) {
-                                    OctreeNode::Leaf { indices, .. } => {
-                                        let other_data = other_instance.data();
-                                        for &triangle_index in indices {
-                                            let triangle =
-                                                &other_data.triangles[triangle_index as usize];
-                                            let va = other_data.vertices[triangle[0] as usize]
-                                                .world_position;
-                                            let vb = other_data.vertices[triangle[1] as usize]
-                                                .world_position;
-                                            let vc = other_data.vertices[triangle[2] as usize]
-                                                .world_position;
-                                            if let Some(pt) =
-                                                ray.triangle_intersection_point(&[va, vb, vc])
-                                            {
-                                                if ray.origin.metric_distance(&pt) + shadow_bias
-                                                    < ray.dir.norm()
+        .for_each(|(y, row): (usize, &mut [Vector4<u8>])| {
+            if cancellation_token.is_cancelled() {
+                return;
+            }
+
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let (x, y) = (x as u32, y as u32);
+
+                let uv = Vector2::new(x as f32 * scale + half_pixel, y as f32 * scale + half_pixel);
+
+                if let Some((world_position, world_normal)) =
+                    pick(uv, &grid, instance.data(), scale)
+                {
+                    let mut pixel_color = Vector3::default();
+                    for light in lights {
+                        let (light_color, mut attenuation, light_position) = match light {
+                            LightDefinition::Directional(directional) => {
+                                let attenuation = directional.intensity
+                                    * lambertian(directional.direction, world_normal);
+                                // Rays of directional light are parallel, so shadows are
+                                // checked using a far point in the direction of the light.
+                                let light_position = world_position
+                                    + directional.direction.scale(DIRECTIONAL_SHADOW_DISTANCE);
+                                (directional.color, attenuation, light_position)
+                            }
+                            LightDefinition::Spot(spot) => {
+                                let d = spot.position - world_position;
+                                let distance = d.norm();
+                                let light_vec = d.scale(1.0 / distance);
+                                let spot_angle_cos = light_vec.dot(&spot.direction);
+                                let cone_factor =
+                                    smoothstep(spot.edge0, spot.edge1, spot_angle_cos);
+                                let attenuation = cone_factor
+                                    * spot.intensity
+                                    * lambertian(light_vec, world_normal)
+                                    * distance_attenuation(distance, spot.sqr_distance);
+                                (spot.color, attenuation, spot.position)
+                            }
+                            LightDefinition::Point(point) => {
+                                let d = point.position - world_position;
+                                let distance = d.norm();
+                                let light_vec = d.scale(1.0 / distance);
+                                let attenuation = point.intensity
+                                    * lambertian(light_vec, world_normal)
+                                    * distance_attenuation(distance, point.sqr_radius);
+                                (point.color, attenuation, point.position)
+                            }
+                        };
+                        // Shadows
+                        if attenuation >= 0.01 {
+                            let mut query_buffer = ArrayVec::<Handle<OctreeNode>, 64>::new();
+                            let shadow_bias = 0.01;
+                            let ray = Ray::from_two_points(light_position, world_position);
+                            'outer_loop: for other_instance in other_instances {
+                                other_instance
+                                    .data()
+                                    .octree
+                                    .ray_query_static(&ray, &mut query_buffer);
+                                for &node in query_buffer.iter() {
+                                    match other_instance.data().octree.node(node) {
+                                        OctreeNode::Leaf { indices, .. } => {
+                                            let other_data = other_instance.data();
+                                            for &triangle_index in indices {
+                                                let triangle =
+                                                    &other_data.triangles[triangle_index as usize];
+                                                let va = other_data.vertices[triangle[0] as usize]
+                                                    .world_position;
+                                                let vb = other_data.vertices[triangle[1] as usize]
+                                                    .world_position;
+                                                let vc = other_data.vertices[triangle[2] as usize]
+                                                    .world_position;
+                                                if let Some(pt) =
+                                                    ray.triangle_intersection_point(&[va, vb, vc])
                                                 {
-                                                    attenuation = 0.0;
-                                                    break 'outer_loop;
+                                                    if ray.origin.metric_distance(&pt) + shadow_bias
+                                                        < ray.dir.norm()
+                                                    {
+                                                        attenuation = 0.0;
+                                                        break 'outer_loop;
+                                                    }
                                                 }
                                             }
                                         }
+                                        OctreeNode::Branch { .. } => unreachable!(),
                                     }
-                                    OctreeNode::Branch { .. } => unreachable!(),
                                 }
                             }
                         }
+                        pixel_color += light_color.scale(attenuation);
                     }
-                    pixel_color += light_color.scale(attenuation);
-                }
 
-                *pixel = Vector4::new(
-                    (pixel_color.x.max(0.0).min(1.0) * 255.0) as u8,
-                    (pixel_color.y.max(0.0).min(1.0) * 255.0) as u8,
-                    (pixel_color.z.max(0.0).min(1.0) * 255.0) as u8,
-                    255, // Indicates that this pixel was "filled"
-                );
+                    *pixel = Vector4::new(
+                        (pixel_color.x.max(0.0).min(1.0) * 255.0) as u8,
+                        (pixel_color.y.max(0.0).min(1.0) * 255.0) as u8,
+                        (pixel_color.z.max(0.0).min(1.0) * 255.0) as u8,
+                        255, // Indicates that this pixel was "filled"
+                    );
+                }
             }
         });
 
+    if cancellation_token.is_cancelled() {
+        return None;
+    }
+
     // Prepare light map for bilinear filtration. This step is mandatory to prevent bleeding.
     let mut rgb_pixels: Vec<Vector3<u8>> = Vec::with_capacity((atlas_size * atlas_size) as usize);
     for y in 0..(atlas_size as i32) {
@@ -856,18 +1268,20 @@ fn generate_lightmap(
         }
     }
 
-    TextureData::from_bytes(
-        TextureKind::Rectangle {
-            width: atlas_size,
-            height: atlas_size,
-        },
-        TexturePixelKind::RGB8,
-        bytes,
-        // Do not serialize content because lightmap is saved as a series of images in
-        // a common format.
-        false,
+    Some(
+        TextureData::from_bytes(
+            TextureKind::Rectangle {
+                width: atlas_size,
+                height: atlas_size,
+            },
+            TexturePixelKind::RGB8,
+            bytes,
+            // Do not serialize content because lightmap is saved as a series of images in
+            // a common format.
+            false,
+        )
+        .unwrap(),
     )
-    .unwrap()
 }
 
 #[cfg(test)]
@@ -876,14 +1290,16 @@ mod test {
         core::{
             algebra::{Matrix4, Vector3},
             parking_lot::Mutex,
+            pool::Handle,
         },
         scene::{
-            base::BaseBuilder,
+            base::{BaseBuilder, Mobility},
             light::{point::PointLightBuilder, BaseLightBuilder},
             mesh::{
                 surface::{SurfaceBuilder, SurfaceData},
                 MeshBuilder,
             },
+            node::Node,
             transform::TransformBuilder,
             Scene,
         },
@@ -891,10 +1307,7 @@ mod test {
     };
     use std::sync::Arc;
 
-    #[test]
-    fn test_generate_lightmap() {
-        let mut scene = Scene::new();
-
+    fn make_cone(scene: &mut Scene, position: Vector3<f32>) -> Handle<Node> {
         let data = SurfaceData::make_cone(
             16,
             1.0,
@@ -902,31 +1315,100 @@ mod test {
             &Matrix4::new_nonuniform_scaling(&Vector3::new(1.0, 1.1, 1.0)),
         );
 
-        MeshBuilder::new(BaseBuilder::new())
-            .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(data))).build()])
-            .build(&mut scene.graph);
+        MeshBuilder::new(
+            BaseBuilder::new()
+                .with_mobility(Mobility::Static)
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(Arc::new(Mutex::new(data))).build()])
+        .build(&mut scene.graph)
+    }
 
+    fn make_light(scene: &mut Scene, mobility: Mobility) -> Handle<Node> {
         PointLightBuilder::new(BaseLightBuilder::new(
-            BaseBuilder::new().with_local_transform(
-                TransformBuilder::new()
-                    .with_local_position(Vector3::new(0.0, 2.0, 0.0))
-                    .build(),
-            ),
+            BaseBuilder::new()
+                .with_mobility(mobility)
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 2.0, 0.0))
+                        .build(),
+                ),
         ))
         .with_radius(4.0)
-        .build(&mut scene.graph);
+        .build(&mut scene.graph)
+    }
+
+    #[test]
+    fn test_generate_lightmap() {
+        let mut scene = Scene::new();
+
+        make_cone(&mut scene, Default::default());
+        let light = make_light(&mut scene, Mobility::Static);
+        let dynamic_light = make_light(&mut scene, Mobility::Dynamic);
 
         let lightmap =
             Lightmap::new(&mut scene, 64, Default::default(), Default::default()).unwrap();
 
+        assert!(lightmap.is_baked_light(light));
+        assert!(!lightmap.is_baked_light(dynamic_light));
+
         let mut counter = 0;
         for entry_set in lightmap.map.values() {
             for entry in entry_set {
                 let mut data = entry.texture.as_ref().unwrap().data_ref();
-                data.set_path(format!("{}.png", counter));
+                data.set_path(std::env::temp_dir().join(format!("fyrox_lightmap_{}.png", counter)));
                 data.save().unwrap();
                 counter += 1;
             }
         }
     }
+
+    #[test]
+    fn test_incremental_lightmap() {
+        let mut scene = Scene::new();
+
+        let near = make_cone(&mut scene, Default::default());
+        let far = make_cone(&mut scene, Vector3::new(100.0, 0.0, 0.0));
+        let light = make_light(&mut scene, Mobility::Static);
+
+        let texture = |scene: &Scene, mesh: Handle<Node>| {
+            scene.lightmap().unwrap().map[&mesh][0]
+                .texture
+                .clone()
+                .unwrap()
+        };
+
+        let lightmap =
+            Lightmap::new(&mut scene, 16, Default::default(), Default::default()).unwrap();
+        scene.set_lightmap(lightmap).unwrap();
+        let near_texture = texture(&scene, near);
+        let far_texture = texture(&scene, far);
+
+        // Nothing has changed, everything must be reused.
+        let lightmap =
+            Lightmap::new(&mut scene, 16, Default::default(), Default::default()).unwrap();
+        scene.set_lightmap(lightmap).unwrap();
+        assert!(texture(&scene, near) == near_texture);
+        assert!(texture(&scene, far) == far_texture);
+
+        // The light does not reach the far mesh.
+        scene.graph[light]
+            .local_transform_mut()
+            .set_position(Vector3::new(0.0, 2.5, 0.0));
+        let lightmap =
+            Lightmap::new(&mut scene, 16, Default::default(), Default::default()).unwrap();
+        scene.set_lightmap(lightmap).unwrap();
+        assert!(texture(&scene, near) != near_texture);
+        assert!(texture(&scene, far) == far_texture);
+
+        // Density change invalidates everything.
+        let lightmap =
+            Lightmap::new(&mut scene, 20, Default::default(), Default::default()).unwrap();
+        scene.set_lightmap(lightmap).unwrap();
+        assert!(texture(&scene, far) != far_texture);
+    }
 }
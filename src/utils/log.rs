@@ -2,7 +2,7 @@
 
 use crate::core::parking_lot::Mutex;
 use crate::lazy_static::lazy_static;
-use std::fmt::Debug;
+use std::{collections::VecDeque, fmt::Debug};
 
 use fyrox_core::instant::Instant;
 #[cfg(not(target_arch = "wasm32"))]
@@ -22,6 +22,9 @@ extern "C" {
     fn log(s: &str);
 }
 
+/// Default amount of last messages kept in the history of the log, see [`Log::history`].
+pub const DEFAULT_HISTORY_CAPACITY: usize = 128;
+
 /// A message that could be sent by the logger to all listeners.
 pub struct LogMessage {
     /// Kind of the message: information, warning or error.
@@ -38,8 +41,11 @@ lazy_static! {
         #[cfg(not(target_arch = "wasm32"))]
         file: std::fs::File::create("fyrox.log").unwrap(),
         verbosity: MessageKind::Information,
+        console_output: true,
         listeners: Default::default(),
-        time_origin: Instant::now()
+        time_origin: Instant::now(),
+        history: Default::default(),
+        history_capacity: DEFAULT_HISTORY_CAPACITY,
     });
 }
 
@@ -70,31 +76,47 @@ pub struct Log {
     #[cfg(not(target_arch = "wasm32"))]
     file: std::fs::File,
     verbosity: MessageKind,
+    console_output: bool,
     listeners: Vec<Sender<LogMessage>>,
     time_origin: Instant,
+    history: VecDeque<String>,
+    history_capacity: usize,
 }
 
 impl Log {
     fn write_internal(&mut self, kind: MessageKind, mut msg: String) {
         if kind as u32 >= self.verbosity as u32 {
-            for listener in self.listeners.iter() {
-                let _ = listener.send(LogMessage {
-                    kind,
-                    content: msg.clone(),
-                    time: Instant::now() - self.time_origin,
-                });
-            }
+            let time = Instant::now() - self.time_origin;
+            // Listeners with dropped receivers are removed.
+            self.listeners.retain(|listener| {
+                listener
+                    .send(LogMessage {
+                        kind,
+                        content: msg.clone(),
+                        time,
+                    })
+                    .is_ok()
+            });
 
             msg.insert_str(0, kind.as_str());
 
+            if self.history_capacity > 0 {
+                while self.history.len() >= self.history_capacity {
+                    self.history.pop_front();
+                }
+                self.history.push_back(msg.trim_end().to_owned());
+            }
+
             #[cfg(target_arch = "wasm32")]
-            {
+            if self.console_output {
                 log(&msg);
             }
 
             #[cfg(not(target_arch = "wasm32"))]
             {
-                let _ = io::stdout().write_all(msg.as_bytes());
+                if self.console_output {
+                    let _ = io::stdout().write_all(msg.as_bytes());
+                }
                 let _ = self.file.write_all(msg.as_bytes());
             }
         }
@@ -135,11 +157,38 @@ impl Log {
         LOG.lock().verbosity = kind;
     }
 
-    /// Adds a listener that will receive a copy of every message passed into the log.
+    /// Enables or disables printing of messages into the standard output. Messages are still
+    /// written into the file and sent to listeners. Useful for command-line tools that use the
+    /// standard output for their own needs.
+    pub fn set_console_output(enabled: bool) {
+        LOG.lock().console_output = enabled;
+    }
+
+    /// Adds a listener that will receive a copy of every message passed into the log. The listener
+    /// is removed automatically when its receiver is dropped.
     pub fn add_listener(listener: Sender<LogMessage>) {
         LOG.lock().listeners.push(listener)
     }
 
+    /// Sets amount of last messages kept in the history of the log. Zero disables the history.
+    pub fn set_history_capacity(capacity: usize) {
+        let mut log = LOG.lock();
+        log.history_capacity = capacity;
+        while log.history.len() > capacity {
+            log.history.pop_front();
+        }
+    }
+
+    /// Returns last messages (with their kind prefixes) passed into the log, from the oldest to
+    /// the newest. The method does not block forever if the log is locked (for example, when it
+    /// is called from a panic hook while the panicking thread holds the log), an empty history
+    /// is returned in this case.
+    pub fn history() -> Vec<String> {
+        LOG.try_lock_for(Duration::from_millis(100))
+            .map(|log| log.history.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
     /// Allows you to verify that the result of operation is Ok, or print the error in the log.
     ///
     /// # Use cases
@@ -4,10 +4,14 @@
 
 pub mod astar;
 pub mod behavior;
+pub mod hlod;
+pub mod impostor;
 pub mod lightmap;
 pub mod log;
 pub mod navmesh;
 pub mod raw_mesh;
+pub mod replication;
+pub mod simplify;
 pub mod uvgen;
 pub mod watcher;
 
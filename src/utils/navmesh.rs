@@ -2,29 +2,46 @@
 //!
 //! Navigation mesh is a set of convex polygons which is used for path finding in complex
 //! environment.
+//!
+//! # Obstacles
+//!
+//! Navigation mesh could be changed at runtime by obstacles, every obstacle carves a hole in the
+//! navmesh using its footprint (see [`ObstacleFootprint`]). Only triangles that are touched by
+//! an obstacle are re-built, so moving obstacles are relatively cheap. Every change increments
+//! the revision of the navmesh and is recorded in a short history, agents use it to check if
+//! their paths must be re-calculated (see [`Navmesh::is_path_affected`]). Removal of an obstacle
+//! restores the triangles that were carved by it. Navmeshes of a scene are carved automatically
+//! by [`crate::scene::navmesh_obstacle::NavmeshObstacle`] nodes.
 
 #![warn(missing_docs)]
 
 use crate::{
     core::{
-        algebra::{Point3, Vector3},
+        algebra::{Point3, Vector2, Vector3},
         arrayvec::ArrayVec,
-        math::{self, ray::Ray, TriangleDefinition},
+        math::{self, aabb::AxisAlignedBoundingBox, ray::Ray, TriangleDefinition},
         octree::{Octree, OctreeNode},
         pool::Handle,
         visitor::{Visit, VisitResult, Visitor},
     },
-    scene::mesh::{
-        buffer::{VertexAttributeUsage, VertexReadTrait},
-        Mesh,
+    scene::{
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            Mesh,
+        },
+        node::Node,
     },
     utils::{
         astar::{PathError, PathFinder, PathKind, PathVertex},
         raw_mesh::{RawMeshBuilder, RawVertex},
     },
 };
-use fxhash::FxHashSet;
-use std::hash::{Hash, Hasher};
+use fxhash::{FxHashMap, FxHashSet};
+use std::{
+    cmp::Ordering,
+    collections::VecDeque,
+    hash::{Hash, Hasher},
+};
 
 /// See module docs.
 #[derive(Clone, Debug, Default)]
@@ -33,19 +50,41 @@ pub struct Navmesh {
     triangles: Vec<TriangleDefinition>,
     pathfinder: PathFinder,
     query_buffer: Vec<u32>,
+    carving: Option<Carving>,
+    revision: u64,
+    changes: VecDeque<NavmeshChange>,
 }
 
 impl Visit for Navmesh {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
         let mut region = visitor.enter_region(name)?;
 
-        self.pathfinder.visit("PathFinder", &mut region)?;
-        self.triangles.visit("Triangles", &mut region)?;
+        match self.carving.as_ref().filter(|_| !region.is_reading()) {
+            // Holes are not saved, obstacles will carve them again after loading.
+            Some(carving) => {
+                let vertices = self.pathfinder.vertices()[..carving.base_vertex_count]
+                    .iter()
+                    .map(|v| v.position)
+                    .collect::<Vec<_>>();
+                make_pathfinder(&carving.base_triangles, &vertices)
+                    .visit("PathFinder", &mut region)?;
+                carving
+                    .base_triangles
+                    .clone()
+                    .visit("Triangles", &mut region)?;
+            }
+            None => {
+                self.pathfinder.visit("PathFinder", &mut region)?;
+                self.triangles.visit("Triangles", &mut region)?;
+            }
+        }
 
         drop(region);
 
         // No need to save octree, we can restore it on load.
         if visitor.is_reading() {
+            self.carving = None;
+
             let vertices = self.pathfinder.vertices();
             let raw_triangles = self
                 .triangles
@@ -66,7 +105,7 @@ impl Visit for Navmesh {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 struct Edge {
     a: u32,
     b: u32,
@@ -88,6 +127,490 @@ impl Hash for Edge {
     }
 }
 
+fn make_pathfinder(triangles: &[TriangleDefinition], vertices: &[Vector3<f32>]) -> PathFinder {
+    let mut pathfinder = PathFinder::new();
+    pathfinder.set_vertices(vertices.iter().map(|v| PathVertex::new(*v)).collect());
+
+    let mut edges = FxHashSet::default();
+    for triangle in triangles {
+        edges.insert(Edge {
+            a: triangle[0],
+            b: triangle[1],
+        });
+        edges.insert(Edge {
+            a: triangle[1],
+            b: triangle[2],
+        });
+        edges.insert(Edge {
+            a: triangle[2],
+            b: triangle[0],
+        });
+    }
+
+    for edge in edges {
+        pathfinder.link_bidirect(edge.a as usize, edge.b as usize);
+    }
+
+    pathfinder
+}
+
+/// Distances (in meters) that are smaller than this value are treated as zero by carving.
+const CARVE_EPSILON: f32 = 1.0e-5;
+
+/// Size of a cell that is used to weld vertices that were added by carving.
+const WELD_STEP: f32 = 1.0e-4;
+
+/// Pieces of triangles with smaller area are discarded by carving.
+const MIN_PIECE_AREA: f32 = 1.0e-8;
+
+/// Amount of changes that are stored in the history of a navmesh.
+const MAX_CHANGES: usize = 64;
+
+fn cross_2d(a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn xz(v: Vector3<f32>) -> Vector2<f32> {
+    Vector2::new(v.x, v.z)
+}
+
+/// Footprint of an obstacle on a navmesh - a convex polygon on XZ plane with vertical extent.
+/// Every part of a navmesh that is inside the polygon and the vertical extent is removed from
+/// the navmesh, see [`Navmesh::set_obstacle`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObstacleFootprint {
+    points: Vec<Vector2<f32>>,
+    min_y: f32,
+    max_y: f32,
+}
+
+impl ObstacleFootprint {
+    /// Creates new footprint that covers every given point. Points are projected on XZ plane
+    /// and their convex hull is used as the polygon of the footprint.
+    pub fn from_points(points: &[Vector3<f32>]) -> Self {
+        let mut projected = points.iter().map(|p| xz(*p)).collect::<Vec<_>>();
+        projected.sort_by(|a, b| {
+            a.x.partial_cmp(&b.x)
+                .unwrap_or(Ordering::Equal)
+                .then(a.y.partial_cmp(&b.y).unwrap_or(Ordering::Equal))
+        });
+        projected.dedup_by(|a, b| a.metric_distance(b) <= CARVE_EPSILON);
+
+        // Monotone chain, gives counter-clockwise hull without collinear points.
+        let mut hull = Vec::<Vector2<f32>>::with_capacity(projected.len() * 2);
+        if projected.len() >= 3 {
+            for pass in 0..2 {
+                let start = hull.len();
+                let mut add = |p: Vector2<f32>| {
+                    while hull.len() >= start + 2
+                        && cross_2d(
+                            hull[hull.len() - 1] - hull[hull.len() - 2],
+                            p - hull[hull.len() - 2],
+                        ) <= 0.0
+                    {
+                        hull.pop();
+                    }
+                    hull.push(p);
+                };
+                if pass == 0 {
+                    projected.iter().for_each(|p| add(*p));
+                } else {
+                    projected.iter().rev().for_each(|p| add(*p));
+                }
+                hull.pop();
+            }
+        }
+
+        Self {
+            points: hull,
+            min_y: points.iter().map(|p| p.y).fold(f32::MAX, f32::min),
+            max_y: points.iter().map(|p| p.y).fold(-f32::MAX, f32::max),
+        }
+    }
+
+    /// Returns counter-clockwise convex polygon of the footprint, `x` and `y` of every point are
+    /// world `x` and `z` coordinates. The polygon is empty if the footprint has no area.
+    pub fn points(&self) -> &[Vector2<f32>] {
+        &self.points
+    }
+
+    /// Returns lowest world `y` coordinate of the footprint.
+    pub fn min_y(&self) -> f32 {
+        self.min_y
+    }
+
+    /// Returns highest world `y` coordinate of the footprint.
+    pub fn max_y(&self) -> f32 {
+        self.max_y
+    }
+
+    /// Returns world space bounding box of the footprint.
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        let mut aabb = AxisAlignedBoundingBox::default();
+        for point in self.points.iter() {
+            aabb.add_point(Vector3::new(point.x, self.min_y, point.y));
+            aabb.add_point(Vector3::new(point.x, self.max_y, point.y));
+        }
+        aabb
+    }
+
+    fn lines(&self) -> impl Iterator<Item = CarveLine> + '_ {
+        self.points
+            .iter()
+            .zip(self.points.iter().cycle().skip(1))
+            .map(|(a, b)| CarveLine::new(*a, *b))
+    }
+
+    fn overlaps_vertically(&self, min_y: f32, max_y: f32) -> bool {
+        self.points.len() >= 3 && min_y <= self.max_y && max_y >= self.min_y
+    }
+
+    /// Checks if given point is strictly inside of the footprint, points on the boundary of the
+    /// footprint are treated as outside.
+    pub fn contains(&self, point: Vector3<f32>) -> bool {
+        self.overlaps_vertically(point.y, point.y)
+            && self
+                .lines()
+                .all(|line| line.distance(point) > CARVE_EPSILON)
+    }
+
+    /// Checks if given segment passes through the interior of the footprint, segments that
+    /// touch or go along the boundary of the footprint are treated as outside.
+    pub fn intersects_segment(&self, begin: Vector3<f32>, end: Vector3<f32>) -> bool {
+        if !self.overlaps_vertically(begin.y.min(end.y), begin.y.max(end.y)) {
+            return false;
+        }
+
+        // Cyrus-Beck clipping of the segment by every edge of the polygon.
+        let (mut t_min, mut t_max) = (0.0f32, 1.0f32);
+        for line in self.lines() {
+            let distance = line.distance(begin) - CARVE_EPSILON;
+            let rate = line.normal.dot(&(xz(end) - xz(begin)));
+            if rate.abs() <= f32::EPSILON {
+                if distance <= 0.0 {
+                    return false;
+                }
+            } else {
+                let t = -distance / rate;
+                if rate > 0.0 {
+                    t_min = t_min.max(t);
+                } else {
+                    t_max = t_max.min(t);
+                }
+                if t_min >= t_max {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// A change of a navmesh, see [`Navmesh::changes_since`].
+#[derive(Clone, Debug)]
+struct NavmeshChange {
+    revision: u64,
+    region: AxisAlignedBoundingBox,
+}
+
+/// A line on XZ plane, the normal points to the interior of a footprint.
+struct CarveLine {
+    origin: Vector2<f32>,
+    normal: Vector2<f32>,
+}
+
+impl CarveLine {
+    fn new(a: Vector2<f32>, b: Vector2<f32>) -> Self {
+        let dir = (b - a).try_normalize(f32::EPSILON).unwrap_or_default();
+        Self {
+            origin: a,
+            normal: Vector2::new(-dir.y, dir.x),
+        }
+    }
+
+    fn distance(&self, point: Vector3<f32>) -> f32 {
+        (xz(point) - self.origin).dot(&self.normal)
+    }
+
+    fn side(&self, point: Vector3<f32>) -> f32 {
+        let distance = self.distance(point);
+        if distance.abs() <= CARVE_EPSILON {
+            0.0
+        } else {
+            distance
+        }
+    }
+}
+
+/// A vertex of a polygon that is being carved, `index` is `None` for vertices that were added
+/// by carving and don't have an index yet.
+#[derive(Copy, Clone, Debug)]
+struct CarveVertex {
+    position: Vector3<f32>,
+    index: Option<u32>,
+}
+
+fn clip_polygon(polygon: &[CarveVertex], line: &CarveLine, keep_inside: bool) -> Vec<CarveVertex> {
+    let sign = if keep_inside { 1.0 } else { -1.0 };
+    let mut result = Vec::with_capacity(polygon.len() + 1);
+    for (i, current) in polygon.iter().enumerate() {
+        let next = &polygon[(i + 1) % polygon.len()];
+        let current_side = sign * line.side(current.position);
+        let next_side = sign * line.side(next.position);
+
+        if current_side >= 0.0 {
+            result.push(*current);
+        }
+
+        if current_side * next_side < 0.0 {
+            // Edges shared by adjacent polygons must give exactly the same point, so the order
+            // of the end points must not depend on the polygon.
+            let (a, b) = match current.position.x.partial_cmp(&next.position.x) {
+                Some(Ordering::Less) => (current, next),
+                Some(Ordering::Greater) => (next, current),
+                _ if current.position.z <= next.position.z => (current, next),
+                _ => (next, current),
+            };
+            let distance_a = line.distance(a.position);
+            let distance_b = line.distance(b.position);
+            let t = distance_a / (distance_a - distance_b);
+            result.push(CarveVertex {
+                position: a.position.lerp(&b.position, t),
+                index: None,
+            });
+        }
+    }
+    result
+}
+
+fn polygon_area(polygon: &[CarveVertex]) -> f32 {
+    let mut area = 0.0;
+    for (i, current) in polygon.iter().enumerate() {
+        let next = &polygon[(i + 1) % polygon.len()];
+        area += cross_2d(xz(current.position), xz(next.position));
+    }
+    (area * 0.5).abs()
+}
+
+/// Splits the part of the polygon that is outside of the footprint into convex pieces. Returns
+/// `None` if the polygon does not intersect the footprint.
+fn subtract_footprint(
+    polygon: Vec<CarveVertex>,
+    footprint: &ObstacleFootprint,
+) -> Option<Vec<Vec<CarveVertex>>> {
+    let mut pieces = Vec::new();
+    let mut remaining = polygon;
+    for line in footprint.lines() {
+        let inside = clip_polygon(&remaining, &line, true);
+        if polygon_area(&inside) <= MIN_PIECE_AREA {
+            return None;
+        }
+        let outside = clip_polygon(&remaining, &line, false);
+        if polygon_area(&outside) > MIN_PIECE_AREA {
+            pieces.push(outside);
+        }
+        remaining = inside;
+    }
+    Some(pieces)
+}
+
+fn weld_key(position: Vector3<f32>) -> [i32; 3] {
+    [
+        (position.x / WELD_STEP).round() as i32,
+        (position.y / WELD_STEP).round() as i32,
+        (position.z / WELD_STEP).round() as i32,
+    ]
+}
+
+/// State of a navmesh that is carved by obstacles. Octree of the navmesh is built for the base
+/// (non-carved) triangles, every base triangle is either used as is, or replaced by its pieces.
+#[derive(Clone, Debug, Default)]
+struct Carving {
+    base_triangles: Vec<TriangleDefinition>,
+    base_vertex_count: usize,
+    obstacles: FxHashMap<Handle<Node>, ObstacleFootprint>,
+    pieces: FxHashMap<u32, Vec<TriangleDefinition>>,
+    /// Index of the first triangle of every base triangle in the list of current triangles.
+    offsets: Vec<u32>,
+    /// Amount of triangles that share an edge, vertices of edges with zero count are unlinked.
+    edges: FxHashMap<Edge, u32>,
+    welded: FxHashMap<[i32; 3], u32>,
+    /// Amount of triangles that use every added vertex, indexed from `base_vertex_count`.
+    vertex_refs: Vec<u32>,
+    free_vertices: Vec<u32>,
+    unused_candidates: Vec<u32>,
+}
+
+impl Carving {
+    fn new(triangles: &[TriangleDefinition], vertex_count: usize) -> Self {
+        let mut carving = Self {
+            base_triangles: triangles.to_vec(),
+            base_vertex_count: vertex_count,
+            offsets: (0..=triangles.len() as u32).collect(),
+            ..Default::default()
+        };
+        for triangle in triangles {
+            for edge in Self::triangle_edges(triangle) {
+                *carving.edges.entry(edge).or_default() += 1;
+            }
+        }
+        carving
+    }
+
+    fn triangle_edges(triangle: &TriangleDefinition) -> [Edge; 3] {
+        [
+            Edge {
+                a: triangle[0],
+                b: triangle[1],
+            },
+            Edge {
+                a: triangle[1],
+                b: triangle[2],
+            },
+            Edge {
+                a: triangle[2],
+                b: triangle[0],
+            },
+        ]
+    }
+
+    fn vertex_index(&mut self, pathfinder: &mut PathFinder, vertex: CarveVertex) -> u32 {
+        if let Some(index) = vertex.index {
+            return index;
+        }
+
+        let key = weld_key(vertex.position);
+        if let Some(index) = self.welded.get(&key) {
+            return *index;
+        }
+
+        let index = match self.free_vertices.pop() {
+            Some(index) => {
+                pathfinder.get_vertex_mut(index as usize).unwrap().position = vertex.position;
+                index
+            }
+            None => {
+                self.vertex_refs.push(0);
+                pathfinder.add_vertex(PathVertex::new(vertex.position)) as u32
+            }
+        };
+        self.welded.insert(key, index);
+        // Vertex could be left unused if every triangle with it is degenerated.
+        self.unused_candidates.push(index);
+        index
+    }
+
+    fn add_triangle(&mut self, pathfinder: &mut PathFinder, triangle: &TriangleDefinition) {
+        for edge in Self::triangle_edges(triangle) {
+            let count = self.edges.entry(edge).or_default();
+            if *count == 0 {
+                pathfinder.link_bidirect(edge.a as usize, edge.b as usize);
+            }
+            *count += 1;
+        }
+        for &vertex in triangle.0.iter() {
+            if let Some(refs) = self.vertex_refs_mut(vertex) {
+                *refs += 1;
+            }
+        }
+    }
+
+    fn remove_triangle(&mut self, pathfinder: &mut PathFinder, triangle: &TriangleDefinition) {
+        for edge in Self::triangle_edges(triangle) {
+            if let Some(count) = self.edges.get_mut(&edge) {
+                *count -= 1;
+                if *count == 0 {
+                    self.edges.remove(&edge);
+                    pathfinder.unlink_bidirect(edge.a as usize, edge.b as usize);
+                }
+            }
+        }
+        for &vertex in triangle.0.iter() {
+            if let Some(refs) = self.vertex_refs_mut(vertex) {
+                *refs -= 1;
+                if *refs == 0 {
+                    self.unused_candidates.push(vertex);
+                }
+            }
+        }
+    }
+
+    fn vertex_refs_mut(&mut self, vertex: u32) -> Option<&mut u32> {
+        (vertex as usize)
+            .checked_sub(self.base_vertex_count)
+            .and_then(|i| self.vertex_refs.get_mut(i))
+    }
+
+    fn release_unused_vertices(&mut self, pathfinder: &PathFinder) {
+        let mut candidates = std::mem::take(&mut self.unused_candidates);
+        candidates.sort_unstable();
+        candidates.dedup();
+        for vertex in candidates {
+            if self
+                .vertex_refs_mut(vertex)
+                .map_or(false, |refs| *refs == 0)
+            {
+                let key = weld_key(pathfinder.vertices()[vertex as usize].position);
+                if self.welded.get(&key) == Some(&vertex) {
+                    self.welded.remove(&key);
+                    self.free_vertices.push(vertex);
+                }
+            }
+        }
+    }
+
+    /// Carves a base triangle by every obstacle, returns `None` if no obstacle intersects it.
+    fn carve(&self, pathfinder: &PathFinder, index: u32) -> Option<Vec<Vec<CarveVertex>>> {
+        let triangle = &self.base_triangles[index as usize];
+        let vertices = pathfinder.vertices();
+        let polygon = triangle
+            .0
+            .iter()
+            .map(|&i| CarveVertex {
+                position: vertices[i as usize].position,
+                index: Some(i),
+            })
+            .collect::<Vec<_>>();
+
+        let bounds = AxisAlignedBoundingBox::from_points(
+            &polygon.iter().map(|v| v.position).collect::<Vec<_>>(),
+        );
+
+        // Order of obstacles must not depend on the order of the map, otherwise adjacent
+        // triangles could get different points on their shared edges.
+        let mut obstacles = self
+            .obstacles
+            .iter()
+            .filter(|(_, footprint)| {
+                footprint.overlaps_vertically(bounds.min.y, bounds.max.y)
+                    && footprint.aabb().intersect_aabb(&bounds)
+            })
+            .collect::<Vec<_>>();
+        obstacles.sort_by_key(|(handle, _)| (handle.index(), handle.generation()));
+
+        let mut carved = false;
+        let mut pieces = vec![polygon];
+        for (_, footprint) in obstacles {
+            pieces = pieces
+                .into_iter()
+                .flat_map(|piece| match subtract_footprint(piece.clone(), footprint) {
+                    Some(outside) => {
+                        carved = true;
+                        outside
+                    }
+                    None => vec![piece],
+                })
+                .collect();
+        }
+
+        if carved {
+            Some(pieces)
+        } else {
+            None
+        }
+    }
+}
+
 impl Navmesh {
     /// Creates new navigation mesh from given set of triangles and vertices. This is
     /// low level method that allows to specify triangles and vertices directly. In
@@ -105,35 +628,14 @@ impl Navmesh {
             })
             .collect::<Vec<[Vector3<f32>; 3]>>();
 
-        // Fill in pathfinder.
-        let mut pathfinder = PathFinder::new();
-        pathfinder.set_vertices(vertices.iter().map(|v| PathVertex::new(*v)).collect());
-
-        let mut edges = FxHashSet::default();
-        for triangle in triangles {
-            edges.insert(Edge {
-                a: triangle[0],
-                b: triangle[1],
-            });
-            edges.insert(Edge {
-                a: triangle[1],
-                b: triangle[2],
-            });
-            edges.insert(Edge {
-                a: triangle[2],
-                b: triangle[0],
-            });
-        }
-
-        for edge in edges {
-            pathfinder.link_bidirect(edge.a as usize, edge.b as usize);
-        }
-
         Self {
             triangles: triangles.to_vec(),
             octree: Octree::new(&raw_triangles, 32),
-            pathfinder,
+            pathfinder: make_pathfinder(triangles, vertices),
             query_buffer: Default::default(),
+            carving: None,
+            revision: 0,
+            changes: Default::default(),
         }
     }
 
@@ -216,6 +718,14 @@ impl Navmesh {
     /// if navmesh was empty.
     pub fn query_closest(&mut self, point: Vector3<f32>) -> Option<usize> {
         self.octree.point_query(point, &mut self.query_buffer);
+        if let Some(carving) = self.carving.as_ref() {
+            // Octree contains indices of base triangles, replace them with their pieces.
+            let base_indices = std::mem::take(&mut self.query_buffer);
+            for index in base_indices {
+                self.query_buffer
+                    .extend(carving.offsets[index as usize]..carving.offsets[index as usize + 1]);
+            }
+        }
         if self.query_buffer.is_empty() {
             // TODO: This is not optimal. It is better to trace ray down from given point
             //  and pick closest triangle.
@@ -282,7 +792,10 @@ impl Navmesh {
         let mut result = None;
         for node in buffer.into_iter() {
             if let OctreeNode::Leaf { indices, .. } = self.octree.node(node) {
-                for &index in indices {
+                for index in indices.iter().flat_map(|&i| match self.carving.as_ref() {
+                    Some(carving) => carving.offsets[i as usize]..carving.offsets[i as usize + 1],
+                    None => i..i + 1,
+                }) {
                     let triangle = self.triangles[index as usize].clone();
                     let a = self.pathfinder.vertices()[triangle[0] as usize].position;
                     let b = self.pathfinder.vertices()[triangle[1] as usize].position;
@@ -303,6 +816,210 @@ impl Navmesh {
 
         result
     }
+
+    /// Adds, moves or removes (if `footprint` is `None`) an obstacle with given id. Only the
+    /// triangles that were or will be touched by the obstacle are re-built.
+    pub fn set_obstacle(&mut self, id: Handle<Node>, footprint: Option<ObstacleFootprint>) {
+        let mut carving = match self.carving.take() {
+            Some(carving) => carving,
+            None if footprint.is_none() => return,
+            None => Carving::new(&self.triangles, self.pathfinder.vertices().len()),
+        };
+
+        let old = match footprint {
+            Some(footprint) => {
+                if carving.obstacles.get(&id) == Some(&footprint) {
+                    self.carving = Some(carving);
+                    return;
+                }
+                carving.obstacles.insert(id, footprint)
+            }
+            None => carving.obstacles.remove(&id),
+        };
+
+        let mut region = AxisAlignedBoundingBox::default();
+        for footprint in old
+            .iter()
+            .chain(carving.obstacles.get(&id))
+            .filter(|f| f.points.len() >= 3)
+        {
+            region.add_box(footprint.aabb());
+        }
+
+        let mut affected = Vec::new();
+        if region.min.x <= region.max.x && !carving.base_triangles.is_empty() {
+            self.octree.aabb_query(&region, &mut self.query_buffer);
+            affected.extend_from_slice(&self.query_buffer);
+            affected.sort_unstable();
+            affected.dedup();
+        }
+
+        for &index in affected.iter() {
+            let new_pieces = carving.carve(&self.pathfinder, index).map(|pieces| {
+                let mut triangles = Vec::new();
+                for piece in pieces {
+                    let indices = piece
+                        .iter()
+                        .map(|v| carving.vertex_index(&mut self.pathfinder, *v))
+                        .collect::<Vec<_>>();
+                    for i in 1..indices.len().saturating_sub(1) {
+                        let (a, b, c) = (indices[0], indices[i], indices[i + 1]);
+                        if a != b
+                            && b != c
+                            && c != a
+                            && polygon_area(&[piece[0], piece[i], piece[i + 1]]) > MIN_PIECE_AREA
+                        {
+                            triangles.push(TriangleDefinition([a, b, c]));
+                        }
+                    }
+                }
+                triangles
+            });
+
+            let old_pieces = carving
+                .pieces
+                .remove(&index)
+                .unwrap_or_else(|| vec![carving.base_triangles[index as usize].clone()]);
+
+            // New triangles are added first, so edges that are shared by old and new triangles
+            // stay linked.
+            match new_pieces {
+                Some(new_pieces) => {
+                    for triangle in new_pieces.iter() {
+                        carving.add_triangle(&mut self.pathfinder, triangle);
+                    }
+                    carving.pieces.insert(index, new_pieces);
+                }
+                None => {
+                    let triangle = carving.base_triangles[index as usize].clone();
+                    carving.add_triangle(&mut self.pathfinder, &triangle);
+                }
+            }
+            for triangle in old_pieces.iter() {
+                carving.remove_triangle(&mut self.pathfinder, triangle);
+            }
+        }
+
+        carving.release_unused_vertices(&self.pathfinder);
+
+        if carving.obstacles.is_empty() {
+            // Every added vertex is unused now, so the navmesh is exactly the same as before
+            // the first obstacle.
+            let mut vertices = self.pathfinder.vertices().to_vec();
+            vertices.truncate(carving.base_vertex_count);
+            self.pathfinder.set_vertices(vertices);
+            self.triangles = carving.base_triangles;
+        } else {
+            self.triangles.clear();
+            carving.offsets.clear();
+            for (index, triangle) in carving.base_triangles.iter().enumerate() {
+                carving.offsets.push(self.triangles.len() as u32);
+                match carving.pieces.get(&(index as u32)) {
+                    Some(pieces) => self.triangles.extend_from_slice(pieces),
+                    None => self.triangles.push(triangle.clone()),
+                }
+            }
+            carving.offsets.push(self.triangles.len() as u32);
+            self.carving = Some(carving);
+        }
+
+        if !affected.is_empty() {
+            self.revision += 1;
+            self.changes.push_back(NavmeshChange {
+                revision: self.revision,
+                region,
+            });
+            if self.changes.len() > MAX_CHANGES {
+                self.changes.pop_front();
+            }
+        }
+    }
+
+    /// Makes obstacles of the navmesh the same as given, obstacles that are not in the given
+    /// set are removed. Unchanged obstacles are ignored.
+    pub fn sync_obstacles(&mut self, obstacles: &FxHashMap<Handle<Node>, ObstacleFootprint>) {
+        if let Some(carving) = self.carving.as_ref() {
+            let removed = carving
+                .obstacles
+                .keys()
+                .filter(|id| !obstacles.contains_key(id))
+                .cloned()
+                .collect::<Vec<_>>();
+            for id in removed {
+                self.set_obstacle(id, None);
+            }
+        }
+
+        for (id, footprint) in obstacles.iter() {
+            if self
+                .carving
+                .as_ref()
+                .map_or(true, |c| c.obstacles.get(id) != Some(footprint))
+            {
+                self.set_obstacle(*id, Some(footprint.clone()));
+            }
+        }
+    }
+
+    /// Returns footprint of an obstacle with given id.
+    pub fn obstacle(&self, id: Handle<Node>) -> Option<&ObstacleFootprint> {
+        self.carving.as_ref().and_then(|c| c.obstacles.get(&id))
+    }
+
+    /// Returns current revision of the navmesh, it is incremented on every change of triangles
+    /// of the navmesh made by obstacles.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Returns world space bounds of every change that was made after given revision. Returns
+    /// `None` if the history of changes does not go that far, in this case every path should be
+    /// treated as affected.
+    pub fn changes_since(
+        &self,
+        revision: u64,
+    ) -> Option<impl Iterator<Item = &AxisAlignedBoundingBox>> {
+        if revision < self.revision
+            && self
+                .changes
+                .front()
+                .map_or(true, |change| change.revision > revision + 1)
+        {
+            return None;
+        }
+
+        Some(
+            self.changes
+                .iter()
+                .filter(move |change| change.revision > revision)
+                .map(|change| &change.region),
+        )
+    }
+
+    /// Checks if any change made after given revision is close to given path, such paths must
+    /// be re-calculated.
+    pub fn is_path_affected(&self, path: &[Vector3<f32>], revision: u64) -> bool {
+        match self.changes_since(revision) {
+            Some(mut changes) => changes.any(|region| {
+                path.iter()
+                    .zip(path.iter().skip(1).chain(path.last()))
+                    .any(|(a, b)| {
+                        AxisAlignedBoundingBox::from_points(&[*a, *b]).intersect_aabb(region)
+                    })
+            }),
+            None => true,
+        }
+    }
+
+    /// Checks if given segment goes through a hole made by any obstacle.
+    pub fn is_segment_obstructed(&self, begin: Vector3<f32>, end: Vector3<f32>) -> bool {
+        self.carving.as_ref().map_or(false, |carving| {
+            carving
+                .obstacles
+                .values()
+                .any(|footprint| footprint.intersects_segment(begin, end))
+        })
+    }
 }
 
 /// Navmesh agent is a "pathfinding unit" that performs navigation on a mesh. It is designed to
@@ -318,6 +1035,8 @@ pub struct NavmeshAgent {
     recalculation_threshold: f32,
     speed: f32,
     path_dirty: bool,
+    #[visit(skip)]
+    navmesh_revision: u64,
 }
 
 impl Default for NavmeshAgent {
@@ -339,6 +1058,7 @@ impl NavmeshAgent {
             recalculation_threshold: 0.25,
             speed: 1.5,
             path_dirty: true,
+            navmesh_revision: 0,
         }
     }
 
@@ -386,6 +1106,8 @@ impl NavmeshAgent {
 
         self.current = 0;
 
+        self.navmesh_revision = navmesh.revision();
+
         let (n_from, begin, from_triangle) = if let Some((point, index, triangle)) = navmesh
             .ray_cast(Ray::new(
                 from + Vector3::new(0.0, 1.0, 0.0),
@@ -472,6 +1194,12 @@ impl NavmeshAgent {
             // i    C   i+2
             let center = (begin + end).scale(0.5);
 
+            // Straight segment must not cut through holes made by obstacles.
+            if navmesh.is_segment_obstructed(begin, end) {
+                i += 1;
+                continue;
+            }
+
             // And check if center is lying on navmesh or not. If so - replace i+1 vertex
             // with its projection on the triangle it belongs to.
             for triangle in navmesh.triangles.iter() {
@@ -503,8 +1231,16 @@ impl NavmeshAgent {
     }
 
     /// Performs single update tick that moves agent to the target along the path (which is automatically
-    /// recalculated if target's position has changed).
+    /// recalculated if target's position has changed or the navmesh was changed near the path).
     pub fn update(&mut self, dt: f32, navmesh: &mut Navmesh) -> Result<PathKind, PathError> {
+        if self.navmesh_revision != navmesh.revision() {
+            let remaining = &self.path[(self.current as usize).min(self.path.len())..];
+            if navmesh.is_path_affected(remaining, self.navmesh_revision) {
+                self.path_dirty = true;
+            }
+            self.navmesh_revision = navmesh.revision();
+        }
+
         if self.path_dirty {
             self.calculate_path(navmesh, self.position, self.target)?;
             self.path_dirty = false;
@@ -621,3 +1357,217 @@ impl NavmeshAgentBuilder {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::Vector3,
+            math::TriangleDefinition,
+            pool::Handle,
+            visitor::{Visit, Visitor},
+        },
+        utils::navmesh::{Navmesh, NavmeshAgentBuilder, ObstacleFootprint},
+    };
+
+    /// Flat grid of `size x size` square cells on XZ plane, every cell has size of one meter.
+    fn make_grid(size: u32) -> Navmesh {
+        let mut vertices = Vec::new();
+        for z in 0..=size {
+            for x in 0..=size {
+                vertices.push(Vector3::new(x as f32, 0.0, z as f32));
+            }
+        }
+
+        let mut triangles = Vec::new();
+        for z in 0..size {
+            for x in 0..size {
+                let i = z * (size + 1) + x;
+                triangles.push(TriangleDefinition([i, i + size + 1, i + 1]));
+                triangles.push(TriangleDefinition([i + 1, i + size + 1, i + size + 2]));
+            }
+        }
+
+        Navmesh::new(&triangles, &vertices)
+    }
+
+    fn make_box(center: Vector3<f32>, half_extents: Vector3<f32>) -> ObstacleFootprint {
+        let mut points = Vec::new();
+        for x in [-1.0, 1.0] {
+            for y in [-1.0, 1.0] {
+                for z in [-1.0, 1.0] {
+                    points.push(center + half_extents.component_mul(&Vector3::new(x, y, z)));
+                }
+            }
+        }
+        ObstacleFootprint::from_points(&points)
+    }
+
+    fn crosses(path: &[Vector3<f32>], footprint: &ObstacleFootprint) -> bool {
+        path.windows(2)
+            .any(|segment| footprint.intersects_segment(segment[0], segment[1]))
+    }
+
+    #[test]
+    fn test_obstacle_reroutes_path_and_restores_navmesh() {
+        let mut navmesh = make_grid(10);
+        let triangles = navmesh.triangles().to_vec();
+        let vertex_count = navmesh.vertices().len();
+
+        let from = Vector3::new(4.9, 0.0, 0.3);
+        let to = Vector3::new(5.1, 0.0, 9.7);
+        let mut agent = NavmeshAgentBuilder::new()
+            .with_position(from)
+            .with_target(to)
+            .build();
+        agent.calculate_path(&mut navmesh, from, to).unwrap();
+        let original_path = agent.path().to_vec();
+
+        let obstacle = Handle::new(1, 1);
+        let footprint = make_box(Vector3::new(5.0, 0.0, 5.0), Vector3::new(1.5, 1.0, 1.5));
+        assert!(crosses(&original_path, &footprint));
+
+        navmesh.set_obstacle(obstacle, Some(footprint.clone()));
+        assert_eq!(navmesh.revision(), 1);
+        assert!(navmesh.vertices().len() > vertex_count);
+        // Every triangle of the navmesh is outside of the obstacle.
+        for triangle in navmesh.triangles() {
+            let center = triangle
+                .0
+                .iter()
+                .map(|&i| navmesh.vertices()[i as usize].position)
+                .sum::<Vector3<f32>>()
+                .scale(1.0 / 3.0);
+            assert!(!footprint.contains(center));
+        }
+
+        agent.calculate_path(&mut navmesh, from, to).unwrap();
+        let path = agent.path().to_vec();
+        assert!(path.len() >= 2);
+        assert!(path.first().unwrap().metric_distance(&from) < 0.001);
+        assert!(path.last().unwrap().metric_distance(&to) < 0.001);
+        assert!(!crosses(&path, &footprint));
+        for point in path.iter() {
+            assert!(!footprint.contains(*point));
+        }
+
+        navmesh.set_obstacle(obstacle, None);
+        assert_eq!(navmesh.revision(), 2);
+        assert_eq!(navmesh.triangles(), triangles.as_slice());
+        assert_eq!(navmesh.vertices().len(), vertex_count);
+
+        agent.calculate_path(&mut navmesh, from, to).unwrap();
+        assert_eq!(agent.path(), original_path.as_slice());
+    }
+
+    #[test]
+    fn test_carved_navmesh_is_saved_without_holes() {
+        let mut navmesh = make_grid(4);
+        let triangles = navmesh.triangles().to_vec();
+        let vertex_count = navmesh.vertices().len();
+
+        navmesh.set_obstacle(
+            Handle::new(1, 1),
+            Some(make_box(
+                Vector3::new(2.0, 0.0, 2.0),
+                Vector3::new(0.5, 1.0, 0.5),
+            )),
+        );
+        assert_ne!(navmesh.triangles(), triangles.as_slice());
+
+        let mut visitor = Visitor::new();
+        navmesh.visit("Navmesh", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut loaded = Navmesh::default();
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        loaded.visit("Navmesh", &mut visitor).unwrap();
+        assert_eq!(loaded.triangles(), triangles.as_slice());
+        assert_eq!(loaded.vertices().len(), vertex_count);
+        assert!(loaded.obstacle(Handle::new(1, 1)).is_none());
+    }
+
+    #[test]
+    fn test_moving_obstacle_does_not_leak_vertices() {
+        let mut navmesh = make_grid(10);
+        let obstacle = Handle::new(1, 1);
+
+        navmesh.set_obstacle(
+            obstacle,
+            Some(make_box(
+                Vector3::new(2.0, 0.0, 2.0),
+                Vector3::new(0.7, 1.0, 0.7),
+            )),
+        );
+        let triangle_count = navmesh.triangles().len();
+
+        // The obstacle is moved back and forth, every position has the same layout of the
+        // grid under it. Vertices of the previous position are released after the vertices of
+        // the new one are added, so the amount of vertices grows only once.
+        let mut vertex_count = None;
+        for i in 1..=6 {
+            let x = if i % 2 == 0 { 2.0 } else { 6.0 };
+            navmesh.set_obstacle(
+                obstacle,
+                Some(make_box(
+                    Vector3::new(x, 0.0, 2.0),
+                    Vector3::new(0.7, 1.0, 0.7),
+                )),
+            );
+            assert_eq!(
+                *vertex_count.get_or_insert(navmesh.vertices().len()),
+                navmesh.vertices().len()
+            );
+            assert_eq!(navmesh.triangles().len(), triangle_count);
+        }
+    }
+
+    #[test]
+    fn test_agent_recalculates_affected_path() {
+        let mut navmesh = make_grid(10);
+        let from = Vector3::new(4.9, 0.0, 0.3);
+        let to = Vector3::new(5.1, 0.0, 9.7);
+        let mut agent = NavmeshAgentBuilder::new()
+            .with_position(from)
+            .with_target(to)
+            .build();
+        agent.update(0.0, &mut navmesh).unwrap();
+        let path = agent.path().to_vec();
+
+        // An obstacle that is far from the path must not invalidate it.
+        let revision = navmesh.revision();
+        navmesh.set_obstacle(
+            Handle::new(1, 1),
+            Some(make_box(
+                Vector3::new(1.5, 0.0, 1.5),
+                Vector3::new(0.6, 1.0, 0.6),
+            )),
+        );
+        assert_ne!(navmesh.revision(), revision);
+        assert!(!navmesh.is_path_affected(&path, revision));
+        agent.update(0.0, &mut navmesh).unwrap();
+        assert_eq!(agent.path(), path.as_slice());
+
+        // An obstacle on the path must cause re-calculation of the path on next update.
+        let revision = navmesh.revision();
+        let footprint = make_box(Vector3::new(5.0, 0.0, 5.0), Vector3::new(1.5, 1.0, 1.5));
+        navmesh.set_obstacle(Handle::new(2, 1), Some(footprint.clone()));
+        assert!(navmesh.is_path_affected(&path, revision));
+        assert!(crosses(agent.path(), &footprint));
+        agent.update(0.0, &mut navmesh).unwrap();
+        assert!(!crosses(agent.path(), &footprint));
+
+        // Too old revisions are always treated as affected.
+        for i in 0..70 {
+            navmesh.set_obstacle(
+                Handle::new(1, 1),
+                Some(make_box(
+                    Vector3::new(1.5 + (i % 2) as f32, 0.0, 1.5),
+                    Vector3::new(0.6, 1.0, 0.6),
+                )),
+            );
+        }
+        assert!(navmesh.changes_since(revision).is_none());
+        assert!(navmesh.is_path_affected(&path, revision));
+    }
+}
@@ -0,0 +1,765 @@
+//! Helpers for transform replication in networked games.
+//!
+//! The module does not deal with transport at all - it converts transforms of scene nodes
+//! to compact byte representation and back ([`TransformSnapshot`]), and smooths incoming
+//! snapshots on the receiving side ([`InterpolationBuffer`], [`TransformReplicator`]).
+//! Sending and receiving the bytes is up to the game.
+//!
+//! Typical usage on a client looks like this:
+//!
+//! ```no_run
+//! use fyrox::{
+//!     core::pool::Handle,
+//!     scene::{graph::Graph, node::Node},
+//!     utils::replication::{Quantization, TransformReplicator, TransformSnapshot},
+//! };
+//!
+//! fn on_packet(
+//!     replicator: &mut TransformReplicator,
+//!     node: Handle<Node>,
+//!     server_time: f64,
+//!     mut bytes: &[u8],
+//! ) {
+//!     let snapshot = TransformSnapshot::decode(&mut bytes, &Quantization::default()).unwrap();
+//!     replicator.push_snapshot(node, server_time, snapshot);
+//! }
+//!
+//! fn on_update(replicator: &mut TransformReplicator, graph: &mut Graph, server_time: f64) {
+//!     // Moves every replicated node to its state at `server_time - interpolation_delay`.
+//!     replicator.apply(graph, server_time);
+//! }
+//! ```
+
+use crate::{
+    core::{
+        algebra::{Quaternion, UnitQuaternion, Vector3, Vector4},
+        pool::Handle,
+    },
+    scene::{graph::Graph, node::Node, transform::Transform},
+};
+use fxhash::FxHashMap;
+use std::collections::VecDeque;
+
+/// An error that may occur during snapshot decoding.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// Input data ended before the snapshot was fully read.
+    #[error("Unexpected end of snapshot data.")]
+    UnexpectedEnd,
+
+    /// Snapshot was encoded with different quantization settings.
+    #[error("Snapshot quantization settings do not match the decoder settings.")]
+    QuantizationMismatch,
+
+    /// Data is corrupted.
+    #[error("Snapshot data is corrupted.")]
+    Corrupted,
+}
+
+/// Defines how transforms are compressed when they are written to bytes. Both sides must use
+/// the same settings, mismatch is detected during decoding.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Quantization {
+    /// Precision (step) of positions and scales in world units. Coordinates are stored as
+    /// variable-length integers, so smaller values take less space. `None` means that
+    /// coordinates are stored as raw `f32` values. Maximum quantization error is a half of
+    /// the step.
+    pub position_precision: Option<f32>,
+
+    /// Whether to store rotation using "smallest three" compression: index of the largest
+    /// component of the quaternion and three remaining components in 16 bits each (7 bytes in
+    /// total, instead of 16). Maximum error is about `0.0001` radians.
+    pub compress_rotation: bool,
+}
+
+impl Default for Quantization {
+    fn default() -> Self {
+        Self {
+            position_precision: Some(0.001),
+            compress_rotation: true,
+        }
+    }
+}
+
+impl Quantization {
+    /// Quantization settings that store every value as is, without any precision loss.
+    pub fn lossless() -> Self {
+        Self {
+            position_precision: None,
+            compress_rotation: false,
+        }
+    }
+}
+
+const FLAG_HAS_SCALE: u8 = 1;
+const FLAG_QUANTIZED_POSITION: u8 = 1 << 1;
+const FLAG_COMPRESSED_ROTATION: u8 = 1 << 2;
+
+const SMALLEST_THREE_RANGE: f32 = std::f32::consts::FRAC_1_SQRT_2;
+const SMALLEST_THREE_MAX: f32 = i16::MAX as f32;
+
+/// State of a transform at some point in time.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TransformSnapshot {
+    /// Local position.
+    pub position: Vector3<f32>,
+    /// Local rotation.
+    pub rotation: UnitQuaternion<f32>,
+    /// Local scale.
+    pub scale: Vector3<f32>,
+}
+
+impl Default for TransformSnapshot {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            rotation: Default::default(),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl TransformSnapshot {
+    /// Captures local position, rotation and scale of the given transform.
+    pub fn from_transform(transform: &Transform) -> Self {
+        Self {
+            position: **transform.position(),
+            rotation: **transform.rotation(),
+            scale: **transform.scale(),
+        }
+    }
+
+    /// Writes the snapshot to the given transform.
+    pub fn apply(&self, transform: &mut Transform) {
+        transform
+            .set_position(self.position)
+            .set_rotation(self.rotation)
+            .set_scale(self.scale);
+    }
+
+    /// Blends two snapshots, `t` is not clamped so values outside of `[0; 1]` range will
+    /// extrapolate position and scale. Rotation is always clamped.
+    pub fn interpolate(&self, other: &Self, t: f32) -> Self {
+        let rotation_t = t.clamp(0.0, 1.0);
+        Self {
+            position: self.position.lerp(&other.position, t),
+            rotation: self
+                .rotation
+                .try_slerp(&other.rotation, rotation_t, f32::EPSILON)
+                .unwrap_or_else(|| self.rotation.nlerp(&other.rotation, rotation_t)),
+            scale: self.scale.lerp(&other.scale, t),
+        }
+    }
+
+    /// Writes the snapshot to the end of the `output` using the given quantization settings.
+    /// Unit scale is not written at all.
+    pub fn encode(&self, quantization: &Quantization, output: &mut Vec<u8>) {
+        let has_scale = self.scale.iter().any(|s| (s - 1.0).abs() > f32::EPSILON);
+
+        let mut flags = 0;
+        if has_scale {
+            flags |= FLAG_HAS_SCALE;
+        }
+        if quantization.position_precision.is_some() {
+            flags |= FLAG_QUANTIZED_POSITION;
+        }
+        if quantization.compress_rotation {
+            flags |= FLAG_COMPRESSED_ROTATION;
+        }
+        output.push(flags);
+
+        write_vector(output, &self.position, quantization.position_precision);
+
+        if quantization.compress_rotation {
+            write_smallest_three(output, &self.rotation);
+        } else {
+            for component in self.rotation.coords.iter() {
+                output.extend_from_slice(&component.to_le_bytes());
+            }
+        }
+
+        if has_scale {
+            write_vector(output, &self.scale, quantization.position_precision);
+        }
+    }
+
+    /// Reads a snapshot from the beginning of the `input` and advances it past the snapshot
+    /// data, so multiple snapshots could be read one after another.
+    pub fn decode(input: &mut &[u8], quantization: &Quantization) -> Result<Self, SnapshotError> {
+        let flags = read_byte(input)?;
+
+        if (flags & FLAG_QUANTIZED_POSITION != 0) != quantization.position_precision.is_some()
+            || (flags & FLAG_COMPRESSED_ROTATION != 0) != quantization.compress_rotation
+        {
+            return Err(SnapshotError::QuantizationMismatch);
+        }
+
+        let position = read_vector(input, quantization.position_precision)?;
+
+        let rotation = if quantization.compress_rotation {
+            read_smallest_three(input)?
+        } else {
+            let mut coords = Vector4::default();
+            for component in coords.iter_mut() {
+                *component = read_f32(input)?;
+            }
+            UnitQuaternion::new_normalize(Quaternion::from(coords))
+        };
+
+        let scale = if flags & FLAG_HAS_SCALE != 0 {
+            read_vector(input, quantization.position_precision)?
+        } else {
+            Vector3::new(1.0, 1.0, 1.0)
+        };
+
+        Ok(Self {
+            position,
+            rotation,
+            scale,
+        })
+    }
+}
+
+fn write_varint(output: &mut Vec<u8>, value: i64) {
+    // Zig-zag encoding maps small negative values to small positive values.
+    let mut value = ((value << 1) ^ (value >> 63)) as u64;
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            output.push(byte);
+            break;
+        } else {
+            output.push(byte | 0x80);
+        }
+    }
+}
+
+fn read_varint(input: &mut &[u8]) -> Result<i64, SnapshotError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = read_byte(input)?;
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(((value >> 1) as i64) ^ -((value & 1) as i64));
+        }
+    }
+    Err(SnapshotError::Corrupted)
+}
+
+fn read_byte(input: &mut &[u8]) -> Result<u8, SnapshotError> {
+    let (&byte, rest) = input.split_first().ok_or(SnapshotError::UnexpectedEnd)?;
+    *input = rest;
+    Ok(byte)
+}
+
+fn read_array<const N: usize>(input: &mut &[u8]) -> Result<[u8; N], SnapshotError> {
+    if input.len() < N {
+        return Err(SnapshotError::UnexpectedEnd);
+    }
+    let mut bytes = [0; N];
+    bytes.copy_from_slice(&input[..N]);
+    *input = &input[N..];
+    Ok(bytes)
+}
+
+fn read_f32(input: &mut &[u8]) -> Result<f32, SnapshotError> {
+    Ok(f32::from_le_bytes(read_array(input)?))
+}
+
+fn write_vector(output: &mut Vec<u8>, vector: &Vector3<f32>, precision: Option<f32>) {
+    for &component in vector.iter() {
+        if let Some(precision) = precision {
+            write_varint(output, (component / precision).round() as i64);
+        } else {
+            output.extend_from_slice(&component.to_le_bytes());
+        }
+    }
+}
+
+fn read_vector(input: &mut &[u8], precision: Option<f32>) -> Result<Vector3<f32>, SnapshotError> {
+    let mut vector = Vector3::default();
+    for component in vector.iter_mut() {
+        *component = if let Some(precision) = precision {
+            (read_varint(input)? as f64 * precision as f64) as f32
+        } else {
+            read_f32(input)?
+        };
+    }
+    Ok(vector)
+}
+
+fn write_smallest_three(output: &mut Vec<u8>, rotation: &UnitQuaternion<f32>) {
+    let coords = rotation.coords;
+
+    let largest = (1..4).fold(0, |largest, i| {
+        if coords[i].abs() > coords[largest].abs() {
+            i
+        } else {
+            largest
+        }
+    });
+
+    // `q` and `-q` represent the same rotation, flip the sign so the largest component is
+    // positive and it could be restored from the rest.
+    let sign = if coords[largest] < 0.0 { -1.0 } else { 1.0 };
+
+    output.push(largest as u8);
+    for i in (0..4).filter(|i| *i != largest) {
+        let normalized = (coords[i] * sign / SMALLEST_THREE_RANGE).clamp(-1.0, 1.0);
+        let quantized = (normalized * SMALLEST_THREE_MAX).round() as i16;
+        output.extend_from_slice(&quantized.to_le_bytes());
+    }
+}
+
+fn read_smallest_three(input: &mut &[u8]) -> Result<UnitQuaternion<f32>, SnapshotError> {
+    let largest = read_byte(input)? as usize;
+    if largest > 3 {
+        return Err(SnapshotError::Corrupted);
+    }
+
+    let mut coords = Vector4::default();
+    let mut sum = 0.0;
+    for i in (0..4).filter(|i| *i != largest) {
+        let quantized = i16::from_le_bytes(read_array(input)?);
+        let component = quantized as f32 / SMALLEST_THREE_MAX * SMALLEST_THREE_RANGE;
+        coords[i] = component;
+        sum += component * component;
+    }
+    coords[largest] = (1.0 - sum).max(0.0).sqrt();
+
+    Ok(UnitQuaternion::new_normalize(Quaternion::from(coords)))
+}
+
+/// Stores timestamped snapshots of a single transform and produces smooth transform at any
+/// point in time between them.
+///
+/// Snapshots may arrive out of order, they are sorted by their time on insertion. Dropped
+/// snapshots are not a problem either, the buffer just interpolates over a longer span.
+/// Sampling past the newest snapshot extrapolates position and scale using the velocity
+/// between last two snapshots, but no further than [`Self::max_extrapolation`] seconds.
+#[derive(Clone, Debug)]
+pub struct InterpolationBuffer {
+    snapshots: VecDeque<(f64, TransformSnapshot)>,
+    capacity: usize,
+    max_extrapolation: f64,
+    last_sample_time: Option<f64>,
+}
+
+impl Default for InterpolationBuffer {
+    fn default() -> Self {
+        Self::new(32, 0.25)
+    }
+}
+
+impl InterpolationBuffer {
+    /// Creates new buffer that holds up to `capacity` snapshots (at least two) and extrapolates
+    /// no further than `max_extrapolation` seconds past the newest snapshot.
+    pub fn new(capacity: usize, max_extrapolation: f64) -> Self {
+        let capacity = capacity.max(2);
+        Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity,
+            max_extrapolation: max_extrapolation.max(0.0),
+            last_sample_time: None,
+        }
+    }
+
+    /// Returns maximum amount of seconds the buffer can extrapolate past the newest snapshot.
+    pub fn max_extrapolation(&self) -> f64 {
+        self.max_extrapolation
+    }
+
+    /// Returns amount of snapshots in the buffer.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Returns `true` if the buffer has no snapshots.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Returns time of the newest snapshot.
+    pub fn newest_time(&self) -> Option<f64> {
+        self.snapshots.back().map(|(time, _)| *time)
+    }
+
+    /// Adds a new snapshot. A snapshot with the same time as an existing one replaces it.
+    /// Returns `false` if the snapshot was discarded because it is too old to be useful.
+    pub fn push(&mut self, time: f64, snapshot: TransformSnapshot) -> bool {
+        if let (Some(last_sample_time), Some((oldest_time, _))) =
+            (self.last_sample_time, self.snapshots.front())
+        {
+            if time < *oldest_time && *oldest_time <= last_sample_time {
+                return false;
+            }
+        }
+
+        // Most of the time snapshots come in order, so search from the end.
+        let position = self
+            .snapshots
+            .iter()
+            .rposition(|(t, _)| *t <= time)
+            .map_or(0, |i| i + 1);
+
+        if position > 0 && self.snapshots[position - 1].0 == time {
+            self.snapshots[position - 1].1 = snapshot;
+        } else {
+            self.snapshots.insert(position, (time, snapshot));
+        }
+
+        while self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+
+        true
+    }
+
+    /// Returns a transform at the given time. Snapshots that are no longer needed for sampling
+    /// at this or later time are removed. Returns `None` if the buffer is empty.
+    pub fn sample(&mut self, time: f64) -> Option<TransformSnapshot> {
+        // Keep only one snapshot at or before the sample time.
+        while self.snapshots.len() > 2 && self.snapshots[1].0 <= time {
+            self.snapshots.pop_front();
+        }
+        self.last_sample_time = Some(time);
+
+        let (first_time, first) = *self.snapshots.front()?;
+        if time <= first_time || self.snapshots.len() == 1 {
+            return Some(first);
+        }
+
+        let next = self.snapshots.iter().position(|(t, _)| *t > time);
+        let (a, b, t) = match next {
+            Some(index) => {
+                let (a_time, a) = self.snapshots[index - 1];
+                let (b_time, b) = self.snapshots[index];
+                (a, b, (time - a_time) / (b_time - a_time))
+            }
+            None => {
+                let (a_time, a) = self.snapshots[self.snapshots.len() - 2];
+                let (b_time, b) = self.snapshots[self.snapshots.len() - 1];
+                let extrapolated = (time - b_time).min(self.max_extrapolation);
+                (a, b, 1.0 + extrapolated / (b_time - a_time))
+            }
+        };
+
+        Some(a.interpolate(&b, t as f32))
+    }
+
+    /// Removes every snapshot from the buffer.
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.last_sample_time = None;
+    }
+}
+
+/// Applies interpolated snapshots to a set of scene nodes. It is meant to be updated every
+/// frame on a client with current (estimated) server time; every replicated node is moved to
+/// its state at `server_time - interpolation_delay`, so there are snapshots on both sides of
+/// the sampled time in most cases.
+#[derive(Clone, Debug)]
+pub struct TransformReplicator {
+    buffers: FxHashMap<Handle<Node>, InterpolationBuffer>,
+    prototype: InterpolationBuffer,
+    /// Delay (in seconds) between the server time and the time the nodes are shown at.
+    /// It should be at least two intervals between snapshots to tolerate a dropped snapshot.
+    pub interpolation_delay: f64,
+}
+
+impl Default for TransformReplicator {
+    fn default() -> Self {
+        Self::new(0.1, InterpolationBuffer::default())
+    }
+}
+
+impl TransformReplicator {
+    /// Creates new replicator with given interpolation delay, the `prototype` buffer is cloned
+    /// for every new replicated node.
+    pub fn new(interpolation_delay: f64, prototype: InterpolationBuffer) -> Self {
+        Self {
+            buffers: Default::default(),
+            prototype,
+            interpolation_delay,
+        }
+    }
+
+    /// Adds a snapshot of the node's local transform at the given server time. Returns `false`
+    /// if the snapshot was discarded as too old.
+    pub fn push_snapshot(
+        &mut self,
+        node: Handle<Node>,
+        time: f64,
+        snapshot: TransformSnapshot,
+    ) -> bool {
+        let prototype = &self.prototype;
+        self.buffers
+            .entry(node)
+            .or_insert_with(|| {
+                let mut buffer = prototype.clone();
+                buffer.clear();
+                buffer
+            })
+            .push(time, snapshot)
+    }
+
+    /// Returns interpolation buffer of the node, if any.
+    pub fn buffer(&self, node: Handle<Node>) -> Option<&InterpolationBuffer> {
+        self.buffers.get(&node)
+    }
+
+    /// Stops replication of the node.
+    pub fn remove_node(&mut self, node: Handle<Node>) -> Option<InterpolationBuffer> {
+        self.buffers.remove(&node)
+    }
+
+    /// Sets local transforms of replicated nodes to their state at
+    /// `server_time - interpolation_delay`. Buffers of deleted nodes are removed.
+    pub fn apply(&mut self, graph: &mut Graph, server_time: f64) {
+        let time = server_time - self.interpolation_delay;
+        self.buffers.retain(|&node, buffer| {
+            if let Some(node) = graph.try_get_mut(node) {
+                if let Some(snapshot) = buffer.sample(time) {
+                    snapshot.apply(node.local_transform_mut());
+                }
+                true
+            } else {
+                false
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{UnitQuaternion, Vector3},
+        scene::{base::BaseBuilder, graph::Graph, pivot::PivotBuilder},
+        utils::replication::{
+            InterpolationBuffer, Quantization, SnapshotError, TransformReplicator,
+            TransformSnapshot,
+        },
+    };
+
+    fn encode(snapshot: &TransformSnapshot, quantization: &Quantization) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        snapshot.encode(quantization, &mut bytes);
+        bytes
+    }
+
+    // `angle_to` is imprecise for close rotations because of `acos`, so compare quaternions
+    // directly, taking into account that `q` and `-q` are the same rotation.
+    fn rotation_error(a: &UnitQuaternion<f32>, b: &UnitQuaternion<f32>) -> f32 {
+        2.0 * (a.coords - b.coords)
+            .norm()
+            .min((a.coords + b.coords).norm())
+    }
+
+    fn decode(bytes: &[u8], quantization: &Quantization) -> TransformSnapshot {
+        let mut input = bytes;
+        let snapshot = TransformSnapshot::decode(&mut input, quantization).unwrap();
+        assert!(input.is_empty());
+        snapshot
+    }
+
+    // Deterministic set of transforms that covers all signs and largest quaternion components.
+    fn test_snapshots() -> Vec<TransformSnapshot> {
+        let mut snapshots = Vec::new();
+        for i in 0..200 {
+            let f = i as f32;
+            snapshots.push(TransformSnapshot {
+                position: Vector3::new(
+                    (f * 0.37).sin() * 500.0,
+                    (f * 1.13).cos() * 20.0 - 3.0,
+                    f * -7.77,
+                ),
+                rotation: UnitQuaternion::from_euler_angles(
+                    f * 0.731 - 3.0,
+                    f * 0.117 - 1.5,
+                    (f * 2.31).sin() * 3.0,
+                ),
+                scale: if i % 2 == 0 {
+                    Vector3::new(1.0, 1.0, 1.0)
+                } else {
+                    Vector3::new(0.5 + f * 0.01, 1.0, 2.0)
+                },
+            });
+        }
+        snapshots
+    }
+
+    #[test]
+    fn test_quantization_error_bounds() {
+        let quantization = Quantization {
+            position_precision: Some(0.01),
+            compress_rotation: true,
+        };
+
+        for snapshot in test_snapshots() {
+            let decoded = decode(&encode(&snapshot, &quantization), &quantization);
+
+            for i in 0..3 {
+                assert!((decoded.position[i] - snapshot.position[i]).abs() <= 0.005 + 1.0e-4);
+                assert!((decoded.scale[i] - snapshot.scale[i]).abs() <= 0.005 + 1.0e-4);
+            }
+
+            assert!(rotation_error(&decoded.rotation, &snapshot.rotation) < 2.0e-4);
+        }
+    }
+
+    #[test]
+    fn test_lossless_round_trip() {
+        let quantization = Quantization::lossless();
+        for snapshot in test_snapshots() {
+            let decoded = decode(&encode(&snapshot, &quantization), &quantization);
+            assert_eq!(decoded.position, snapshot.position);
+            assert_eq!(decoded.scale, snapshot.scale);
+            assert!(rotation_error(&decoded.rotation, &snapshot.rotation) < 1.0e-6);
+        }
+    }
+
+    #[test]
+    fn test_compact_encoding() {
+        let snapshot = TransformSnapshot {
+            position: Vector3::new(1.0, 0.0, -2.5),
+            rotation: UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        };
+
+        // Flags (1) + three small varints (2 + 1 + 2) + smallest three (7).
+        let bytes = encode(&snapshot, &Quantization::default());
+        assert_eq!(bytes.len(), 13);
+
+        // Flags (1) + position (12) + rotation (16), unit scale is omitted.
+        assert_eq!(encode(&snapshot, &Quantization::lossless()).len(), 29);
+    }
+
+    #[test]
+    fn test_decoding_errors() {
+        let snapshot = TransformSnapshot::default();
+        let bytes = encode(&snapshot, &Quantization::default());
+
+        let mut truncated = &bytes[..bytes.len() - 1];
+        assert_eq!(
+            TransformSnapshot::decode(&mut truncated, &Quantization::default()),
+            Err(SnapshotError::UnexpectedEnd)
+        );
+
+        let mut input = &bytes[..];
+        assert_eq!(
+            TransformSnapshot::decode(&mut input, &Quantization::lossless()),
+            Err(SnapshotError::QuantizationMismatch)
+        );
+    }
+
+    #[test]
+    fn test_multiple_snapshots_in_stream() {
+        let quantization = Quantization::default();
+        let snapshots = test_snapshots();
+
+        let mut bytes = Vec::new();
+        for snapshot in snapshots.iter() {
+            snapshot.encode(&quantization, &mut bytes);
+        }
+
+        let mut input = &bytes[..];
+        for snapshot in snapshots.iter() {
+            let decoded = TransformSnapshot::decode(&mut input, &quantization).unwrap();
+            assert!((decoded.position - snapshot.position).norm() < 0.001);
+        }
+        assert!(input.is_empty());
+    }
+
+    fn at(x: f32) -> TransformSnapshot {
+        TransformSnapshot {
+            position: Vector3::new(x, 0.0, 0.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let mut buffer = InterpolationBuffer::new(8, 0.5);
+        buffer.push(1.0, at(0.0));
+        buffer.push(2.0, at(10.0));
+
+        assert_eq!(buffer.sample(1.5).unwrap().position.x, 5.0);
+        assert_eq!(buffer.sample(1.75).unwrap().position.x, 7.5);
+    }
+
+    #[test]
+    fn test_interpolation_at_buffer_edges() {
+        let mut buffer = InterpolationBuffer::new(8, 0.5);
+        assert!(buffer.sample(0.0).is_none());
+
+        buffer.push(1.0, at(0.0));
+        // Single snapshot is returned as is.
+        assert_eq!(buffer.sample(5.0).unwrap().position.x, 0.0);
+
+        buffer.push(2.0, at(10.0));
+
+        // Before the oldest snapshot - clamped to it.
+        let mut early = buffer.clone();
+        assert_eq!(early.sample(0.0).unwrap().position.x, 0.0);
+
+        // Exactly at the newest snapshot.
+        assert_eq!(buffer.clone().sample(2.0).unwrap().position.x, 10.0);
+
+        // Past the newest snapshot - extrapolated using the last velocity.
+        assert_eq!(buffer.clone().sample(2.25).unwrap().position.x, 12.5);
+
+        // Far past the newest snapshot - extrapolation is clamped.
+        assert_eq!(buffer.clone().sample(100.0).unwrap().position.x, 15.0);
+    }
+
+    #[test]
+    fn test_out_of_order_and_dropped_snapshots() {
+        let mut buffer = InterpolationBuffer::new(8, 0.0);
+
+        // Snapshot at 2.0 is dropped, 4.0 comes before 3.0.
+        buffer.push(1.0, at(1.0));
+        buffer.push(4.0, at(4.0));
+        buffer.push(3.0, at(3.0));
+        assert_eq!(buffer.len(), 3);
+
+        assert_eq!(buffer.sample(2.0).unwrap().position.x, 2.0);
+        assert_eq!(buffer.sample(3.5).unwrap().position.x, 3.5);
+
+        // Snapshot that is older than anything that could be sampled is discarded.
+        assert!(!buffer.push(1.5, at(100.0)));
+        assert_eq!(buffer.sample(3.5).unwrap().position.x, 3.5);
+
+        // Duplicates replace existing snapshots.
+        assert!(buffer.push(4.0, at(5.0)));
+        assert_eq!(buffer.sample(4.0).unwrap().position.x, 5.0);
+    }
+
+    #[test]
+    fn test_buffer_capacity() {
+        let mut buffer = InterpolationBuffer::new(4, 0.0);
+        for i in 0..10 {
+            buffer.push(i as f64, at(i as f32));
+        }
+        assert_eq!(buffer.len(), 4);
+        assert_eq!(buffer.newest_time(), Some(9.0));
+    }
+
+    #[test]
+    fn test_replicator_applies_transforms() {
+        let mut graph = Graph::new();
+        let node = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        let mut replicator = TransformReplicator::new(0.1, InterpolationBuffer::new(8, 0.0));
+        replicator.push_snapshot(node, 1.0, at(0.0));
+        replicator.push_snapshot(node, 2.0, at(10.0));
+
+        replicator.apply(&mut graph, 1.6);
+        assert!((graph[node].local_transform().position().x - 5.0).abs() < 1.0e-5);
+
+        // Buffers of deleted nodes are removed.
+        graph.remove_node(node);
+        replicator.apply(&mut graph, 1.7);
+        assert!(replicator.buffer(node).is_none());
+    }
+}
@@ -0,0 +1,494 @@
+//! Mesh simplification. Used to produce coarse versions of meshes for level-of-detail systems.
+//!
+//! Current implementation uses quadric error metrics with half-edge collapses.
+
+use crate::{
+    core::{algebra::Vector3, math::TriangleDefinition},
+    scene::mesh::vertex::StaticVertex,
+};
+use fxhash::FxHashMap;
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+// Symmetric 4x4 matrix of a quadric, only upper triangle is stored.
+#[derive(Copy, Clone, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    fn from_plane(normal: Vector3<f64>, d: f64, weight: f64) -> Self {
+        let (a, b, c) = (normal.x, normal.y, normal.z);
+        let mut q = [
+            a * a,
+            a * b,
+            a * c,
+            a * d,
+            b * b,
+            b * c,
+            b * d,
+            c * c,
+            c * d,
+            d * d,
+        ];
+        for v in q.iter_mut() {
+            *v *= weight;
+        }
+        Self(q)
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a += *b;
+        }
+    }
+
+    fn error(&self, p: Vector3<f64>) -> f64 {
+        let q = &self.0;
+        let (x, y, z) = (p.x, p.y, p.z);
+        q[0] * x * x
+            + 2.0 * q[1] * x * y
+            + 2.0 * q[2] * x * z
+            + 2.0 * q[3] * x
+            + q[4] * y * y
+            + 2.0 * q[5] * y * z
+            + 2.0 * q[6] * y
+            + q[7] * z * z
+            + 2.0 * q[8] * z
+            + q[9]
+    }
+}
+
+// Collapse of `from` position into `to` position.
+struct Collapse {
+    cost: f64,
+    from: usize,
+    to: usize,
+    from_version: u32,
+    to_version: u32,
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, because binary heap is a max-heap and we need cheapest collapses first.
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+struct Simplifier<'a> {
+    vertices: &'a [StaticVertex],
+    triangles: Vec<[u32; 3]>,
+    alive: Vec<bool>,
+    alive_count: usize,
+    // Welded position index of each vertex.
+    position_of: Vec<usize>,
+    positions: Vec<Vector3<f64>>,
+    quadrics: Vec<Quadric>,
+    locked: Vec<bool>,
+    removed: Vec<bool>,
+    versions: Vec<u32>,
+    // Triangles that use a position, may contain dead triangles and duplicates.
+    adjacency: Vec<Vec<usize>>,
+    heap: BinaryHeap<Collapse>,
+}
+
+impl<'a> Simplifier<'a> {
+    fn new(vertices: &'a [StaticVertex], triangles: &[TriangleDefinition]) -> Self {
+        // Weld vertices by position, so vertices on attribute seams (for example with different
+        // texture coordinates) will be treated as a single point of a surface.
+        let mut position_map = FxHashMap::default();
+        let mut positions = Vec::new();
+        let position_of = vertices
+            .iter()
+            .map(|v| {
+                let key = [
+                    v.position.x.to_bits(),
+                    v.position.y.to_bits(),
+                    v.position.z.to_bits(),
+                ];
+                *position_map.entry(key).or_insert_with(|| {
+                    positions.push(v.position.cast::<f64>());
+                    positions.len() - 1
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let triangles = triangles
+            .iter()
+            .filter(|t| t.0.iter().all(|&i| (i as usize) < vertices.len()))
+            .map(|t| t.0)
+            .collect::<Vec<_>>();
+
+        let mut simplifier = Self {
+            vertices,
+            alive: vec![true; triangles.len()],
+            alive_count: triangles.len(),
+            quadrics: vec![Default::default(); positions.len()],
+            locked: vec![false; positions.len()],
+            removed: vec![false; positions.len()],
+            versions: vec![0; positions.len()],
+            adjacency: vec![Default::default(); positions.len()],
+            heap: Default::default(),
+            triangles,
+            position_of,
+            positions,
+        };
+
+        simplifier.initialize();
+
+        simplifier
+    }
+
+    fn triangle_positions(&self, triangle: usize) -> [usize; 3] {
+        let t = &self.triangles[triangle];
+        [
+            self.position_of[t[0] as usize],
+            self.position_of[t[1] as usize],
+            self.position_of[t[2] as usize],
+        ]
+    }
+
+    fn initialize(&mut self) {
+        let mut edge_use_count = FxHashMap::default();
+
+        for triangle in 0..self.triangles.len() {
+            let [a, b, c] = self.triangle_positions(triangle);
+
+            if a == b || b == c || c == a {
+                // Degenerated triangles are not needed at all.
+                self.alive[triangle] = false;
+                self.alive_count -= 1;
+                continue;
+            }
+
+            let (pa, pb, pc) = (self.positions[a], self.positions[b], self.positions[c]);
+            let cross = (pb - pa).cross(&(pc - pa));
+            let double_area = cross.norm();
+            if double_area > f64::EPSILON {
+                let normal = cross.scale(1.0 / double_area);
+                let quadric = Quadric::from_plane(normal, -normal.dot(&pa), double_area * 0.5);
+                for &p in [a, b, c].iter() {
+                    self.quadrics[p].add(&quadric);
+                }
+            }
+
+            for &p in [a, b, c].iter() {
+                self.adjacency[p].push(triangle);
+            }
+
+            for &(u, v) in [(a, b), (b, c), (c, a)].iter() {
+                *edge_use_count.entry((u.min(v), u.max(v))).or_insert(0u32) += 1;
+            }
+        }
+
+        // Vertices on open edges are locked to keep outline of a mesh (and to prevent cracks
+        // between adjacent meshes).
+        for (&(u, v), &count) in edge_use_count.iter() {
+            if count == 1 {
+                self.locked[u] = true;
+                self.locked[v] = true;
+            }
+        }
+
+        for &(u, v) in edge_use_count.keys() {
+            self.push_collapse(u, v);
+        }
+    }
+
+    fn push_collapse(&mut self, u: usize, v: usize) {
+        let mut quadric = self.quadrics[u];
+        quadric.add(&self.quadrics[v]);
+
+        let mut best: Option<(f64, usize, usize)> = None;
+        for &(from, to) in [(u, v), (v, u)].iter() {
+            if self.locked[from] {
+                continue;
+            }
+            let cost = quadric.error(self.positions[to]);
+            if best.map_or(true, |(best_cost, _, _)| cost < best_cost) {
+                best = Some((cost, from, to));
+            }
+        }
+
+        if let Some((cost, from, to)) = best {
+            self.heap.push(Collapse {
+                cost,
+                from,
+                to,
+                from_version: self.versions[from],
+                to_version: self.versions[to],
+            });
+        }
+    }
+
+    fn alive_triangles_of(&self, position: usize) -> Vec<usize> {
+        let mut triangles = self.adjacency[position]
+            .iter()
+            .cloned()
+            .filter(|&t| self.alive[t] && self.triangle_positions(t).contains(&position))
+            .collect::<Vec<_>>();
+        triangles.sort_unstable();
+        triangles.dedup();
+        triangles
+    }
+
+    // Finds a replacement for every vertex at `from` position - a vertex at `to` position that
+    // shares a triangle with it. Collapse is not possible if there's no such vertex, because it
+    // will break attribute seams.
+    fn find_vertex_mapping(
+        &self,
+        triangles: &[usize],
+        from: usize,
+        to: usize,
+    ) -> Option<FxHashMap<u32, u32>> {
+        let mut mapping = FxHashMap::default();
+        for &triangle in triangles {
+            let t = self.triangles[triangle];
+            for &vertex in t.iter() {
+                if self.position_of[vertex as usize] == from {
+                    mapping.entry(vertex).or_insert(None);
+                    if let Some(partner) = t
+                        .iter()
+                        .find(|&&other| self.position_of[other as usize] == to)
+                    {
+                        mapping.insert(vertex, Some(*partner));
+                    }
+                }
+            }
+        }
+        mapping
+            .into_iter()
+            .map(|(vertex, partner)| partner.map(|partner| (vertex, partner)))
+            .collect()
+    }
+
+    fn is_flipping(&self, triangles: &[usize], from: usize, to: usize) -> bool {
+        for &triangle in triangles {
+            let positions = self.triangle_positions(triangle);
+            if positions.contains(&to) {
+                // Will be removed.
+                continue;
+            }
+            let [a, b, c] = positions.map(|p| self.positions[p]);
+            let moved = positions.map(|p| {
+                if p == from {
+                    self.positions[to]
+                } else {
+                    self.positions[p]
+                }
+            });
+            let before = (b - a).cross(&(c - a));
+            let after = (moved[1] - moved[0]).cross(&(moved[2] - moved[0]));
+            if before.dot(&after) <= 0.0 {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn run(&mut self, target_triangle_count: usize) {
+        while self.alive_count > target_triangle_count {
+            let collapse = match self.heap.pop() {
+                Some(collapse) => collapse,
+                None => break,
+            };
+
+            let (from, to) = (collapse.from, collapse.to);
+            if self.removed[from]
+                || self.removed[to]
+                || self.versions[from] != collapse.from_version
+                || self.versions[to] != collapse.to_version
+            {
+                continue;
+            }
+
+            let triangles = self.alive_triangles_of(from);
+            if !triangles
+                .iter()
+                .any(|&t| self.triangle_positions(t).contains(&to))
+            {
+                // Positions are not connected anymore.
+                continue;
+            }
+
+            let mapping = match self.find_vertex_mapping(&triangles, from, to) {
+                Some(mapping) => mapping,
+                None => continue,
+            };
+
+            if self.is_flipping(&triangles, from, to) {
+                continue;
+            }
+
+            for &triangle in triangles.iter() {
+                if self.triangle_positions(triangle).contains(&to) {
+                    self.alive[triangle] = false;
+                    self.alive_count -= 1;
+                } else {
+                    for vertex in self.triangles[triangle].iter_mut() {
+                        if let Some(partner) = mapping.get(vertex) {
+                            *vertex = *partner;
+                        }
+                    }
+                    self.adjacency[to].push(triangle);
+                }
+            }
+
+            let from_quadric = self.quadrics[from];
+            self.quadrics[to].add(&from_quadric);
+            self.removed[from] = true;
+            self.adjacency[from].clear();
+            // Only the costs of the edges around the target position are changed, other
+            // collapses in the heap are still valid.
+            self.versions[to] += 1;
+
+            let mut neighbours = self
+                .alive_triangles_of(to)
+                .into_iter()
+                .flat_map(|t| self.triangle_positions(t))
+                .filter(|&p| p != to)
+                .collect::<Vec<_>>();
+            neighbours.sort_unstable();
+            neighbours.dedup();
+            for neighbour in neighbours {
+                self.push_collapse(to, neighbour);
+            }
+        }
+    }
+
+    fn finish(self) -> (Vec<StaticVertex>, Vec<TriangleDefinition>) {
+        let mut index_map = FxHashMap::default();
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::with_capacity(self.alive_count);
+        for (triangle, alive) in self.triangles.iter().zip(self.alive.iter()) {
+            if !*alive {
+                continue;
+            }
+            let mut definition = [0; 3];
+            for (new_index, &old_index) in definition.iter_mut().zip(triangle.iter()) {
+                *new_index = *index_map.entry(old_index).or_insert_with(|| {
+                    vertices.push(self.vertices[old_index as usize]);
+                    (vertices.len() - 1) as u32
+                });
+            }
+            triangles.push(TriangleDefinition(definition));
+        }
+        (vertices, triangles)
+    }
+}
+
+/// Reduces amount of triangles of a mesh, so it will have at most `target_triangle_count` triangles
+/// (if possible). Returns new set of vertices and triangles.
+///
+/// # Algorithm
+///
+/// Simplification is done by collapsing edges one by one, the cheapest collapses are performed
+/// first. The cost of a collapse is measured by quadric error metrics, which is a sum of squared
+/// distances to the planes of original triangles around a vertex. Every collapse moves one vertex
+/// into another, so the result uses a subset of original vertices.
+///
+/// Vertices on open edges of a mesh are never moved, it keeps outline of the mesh intact. Collapses
+/// that break texture seams or flip triangles are rejected, this means that the actual amount of
+/// triangles could be higher than requested.
+pub fn simplify(
+    vertices: &[StaticVertex],
+    triangles: &[TriangleDefinition],
+    target_triangle_count: usize,
+) -> (Vec<StaticVertex>, Vec<TriangleDefinition>) {
+    let mut simplifier = Simplifier::new(vertices, triangles);
+    simplifier.run(target_triangle_count);
+    simplifier.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            math::TriangleDefinition,
+        },
+        scene::mesh::vertex::StaticVertex,
+        utils::simplify::simplify,
+    };
+
+    // Flat grid of `n` x `n` quads with unit size.
+    fn make_grid(n: u32) -> (Vec<StaticVertex>, Vec<TriangleDefinition>) {
+        let mut vertices = Vec::new();
+        for y in 0..=n {
+            for x in 0..=n {
+                let k = Vector2::new(x as f32, y as f32) / n as f32;
+                vertices.push(StaticVertex::from_pos_uv_normal(
+                    Vector3::new(k.x, 0.0, k.y),
+                    k,
+                    Vector3::y(),
+                ));
+            }
+        }
+        let mut triangles = Vec::new();
+        for y in 0..n {
+            for x in 0..n {
+                let i = y * (n + 1) + x;
+                triangles.push(TriangleDefinition([i, i + n + 1, i + 1]));
+                triangles.push(TriangleDefinition([i + 1, i + n + 1, i + n + 2]));
+            }
+        }
+        (vertices, triangles)
+    }
+
+    fn area(vertices: &[StaticVertex], triangles: &[TriangleDefinition]) -> f32 {
+        triangles
+            .iter()
+            .map(|t| {
+                let a = vertices[t[0] as usize].position;
+                let b = vertices[t[1] as usize].position;
+                let c = vertices[t[2] as usize].position;
+                (b - a).cross(&(c - a)).norm() * 0.5
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_simplify_flat_grid() {
+        let (vertices, triangles) = make_grid(8);
+
+        let (new_vertices, new_triangles) = simplify(&vertices, &triangles, 0);
+
+        // Interior of a flat grid has zero error, so it must be removed almost entirely.
+        assert!(new_triangles.len() < triangles.len() / 2);
+        // Corners are on open edges, they must be kept.
+        for corner in [
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(1.0, 0.0, 1.0),
+        ] {
+            assert!(new_vertices.iter().any(|v| v.position == corner));
+        }
+        // No triangles are flipped or overlapped - area must be the same.
+        assert!((area(&new_vertices, &new_triangles) - 1.0).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_simplify_keeps_mesh_when_target_is_reached() {
+        let (vertices, triangles) = make_grid(4);
+
+        let (new_vertices, new_triangles) = simplify(&vertices, &triangles, triangles.len());
+
+        assert_eq!(new_vertices.len(), vertices.len());
+        assert_eq!(new_triangles.len(), triangles.len());
+    }
+}
@@ -195,10 +195,16 @@ fyroxed_base = "0.13"
         format!(
             r#"//! Editor with your game connected to it as a plugin.
 use fyrox::event_loop::EventLoop;
-use fyroxed_base::{{Editor, StartupData}};
+use fyroxed_base::{{cli, Editor, StartupData}};
 use {}::Game;
 
 fn main() {{
+    // Process scenes without opening the editor if a command is specified, for example
+    // `cargo run --package editor -- validate data/scene.rgs`.
+    if let Some(exit_code) = cli::run_from_args(|tool| tool.add_plugin(Game::new())) {{
+        std::process::exit(exit_code);
+    }}
+
     let event_loop = EventLoop::new();
     let mut editor = Editor::new(
         &event_loop,
@@ -240,6 +246,21 @@ members = ["editor", "executor", "game"]"#,
     );
 }
 
+fn init_manifest(base_path: &Path, args: &Args) {
+    // Write fyrox-project.toml
+    write_file(
+        base_path.join("fyrox-project.toml"),
+        format!(
+            r#"name = "{}"
+data_root = "."
+required_features = []
+excluded_features = []
+"#,
+            args.name
+        ),
+    );
+}
+
 fn main() {
     let args = Args::parse();
 
@@ -253,6 +274,7 @@ fn main() {
     init_game(&base_path, &args);
     init_editor(&base_path, &args);
     init_executor(&base_path, &args);
+    init_manifest(&base_path, &args);
 
     println!("Project {} was generated successfully!", args.name);
     println!(